@@ -5,7 +5,7 @@ use crate::{
     be_cpu::device::CpuDevice,
     error::HoduResult,
     into::flatten::IntoFlattened,
-    types::{DType, Device},
+    types::{DType, Device, MemoryStats},
 };
 
 pub trait BackendDeviceT: Sized {
@@ -29,6 +29,9 @@ pub enum BackendDevice {
     #[cfg(feature = "metal")]
     #[allow(dead_code)]
     Metal(crate::be_metal::device::MetalDevice),
+    #[cfg(feature = "wgpu")]
+    #[allow(dead_code)]
+    WebGPU(crate::be_wgpu::device::WgpuDevice),
 }
 
 impl BackendDevice {
@@ -49,6 +52,13 @@ impl BackendDevice {
                     crate::be_metal::storage::MetalStorage::from_cpu_storage(&cpu_storage)?,
                 ))
             },
+            #[cfg(feature = "wgpu")]
+            Device::WebGPU => {
+                let cpu_storage = data.to_cpu_storage();
+                Ok(BackendStorage::WebGPU(
+                    crate::be_wgpu::storage::WgpuStorage::from_cpu_storage(&cpu_storage)?,
+                ))
+            },
             #[allow(unreachable_patterns)]
             _ => panic!("Unsupported device: {:?}", device),
         }
@@ -65,6 +75,10 @@ impl BackendDevice {
             Device::Metal => Ok(BackendStorage::Metal(crate::be_metal::device::MetalDevice::allocate(
                 size, dtype,
             )?)),
+            #[cfg(feature = "wgpu")]
+            Device::WebGPU => Ok(BackendStorage::WebGPU(crate::be_wgpu::device::WgpuDevice::allocate(
+                size, dtype,
+            )?)),
             #[allow(unreachable_patterns)]
             _ => panic!("Unsupported device: {:?}", device),
         }
@@ -87,6 +101,13 @@ impl BackendDevice {
                     crate::be_metal::storage::MetalStorage::from_cpu_storage(&cpu_storage)?,
                 ))
             },
+            #[cfg(feature = "wgpu")]
+            Device::WebGPU => {
+                let cpu_storage = CpuDevice::zeros(size, dtype)?;
+                Ok(BackendStorage::WebGPU(
+                    crate::be_wgpu::storage::WgpuStorage::from_cpu_storage(&cpu_storage)?,
+                ))
+            },
             #[allow(unreachable_patterns)]
             _ => panic!("Unsupported device: {:?}", device),
         }
@@ -109,6 +130,13 @@ impl BackendDevice {
                     crate::be_metal::storage::MetalStorage::from_cpu_storage(&cpu_storage)?,
                 ))
             },
+            #[cfg(feature = "wgpu")]
+            Device::WebGPU => {
+                let cpu_storage = CpuDevice::randn(size, dtype, mean, std)?;
+                Ok(BackendStorage::WebGPU(
+                    crate::be_wgpu::storage::WgpuStorage::from_cpu_storage(&cpu_storage)?,
+                ))
+            },
             #[allow(unreachable_patterns)]
             _ => panic!("Unsupported device: {:?}", device),
         }
@@ -137,8 +165,35 @@ impl BackendDevice {
                     crate::be_metal::storage::MetalStorage::from_cpu_storage(&cpu_storage)?,
                 ))
             },
+            #[cfg(feature = "wgpu")]
+            Device::WebGPU => {
+                let cpu_storage = CpuDevice::rand_uniform(size, dtype, low, high)?;
+                Ok(BackendStorage::WebGPU(
+                    crate::be_wgpu::storage::WgpuStorage::from_cpu_storage(&cpu_storage)?,
+                ))
+            },
             #[allow(unreachable_patterns)]
             _ => panic!("Unsupported device: {:?}", device),
         }
     }
+
+    pub(crate) fn empty_cache(device: Device) -> HoduResult<()> {
+        match device {
+            #[cfg(feature = "cuda")]
+            Device::CUDA(device_id) => crate::be_cuda::device::CudaDevice::get(device_id)?.empty_cache(),
+            #[cfg(feature = "metal")]
+            Device::Metal => crate::be_metal::device::MetalDevice::global().empty_cache(),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn memory_stats(device: Device) -> HoduResult<MemoryStats> {
+        match device {
+            #[cfg(feature = "cuda")]
+            Device::CUDA(device_id) => crate::be_cuda::device::CudaDevice::get(device_id)?.memory_stats(),
+            #[cfg(feature = "metal")]
+            Device::Metal => crate::be_metal::device::MetalDevice::global().memory_stats(),
+            _ => Ok(MemoryStats::default()),
+        }
+    }
 }
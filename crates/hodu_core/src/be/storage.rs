@@ -7,7 +7,7 @@ use crate::{
     error::{HoduError, HoduResult},
     ops::Op,
     scalar::Scalar,
-    types::{DType, Device, Layout, Shape},
+    types::{BlockFormat, DType, Device, Layout, Shape},
 };
 
 pub trait BackendStorageT: Sized {
@@ -54,6 +54,18 @@ pub trait BackendStorageT: Sized {
 
     fn call_ops_trace(&self, _: &Layout) -> HoduResult<Self>;
 
+    fn call_ops_quantize(&self, _: &Layout, _: f32, _: i32) -> HoduResult<Self>;
+
+    fn call_ops_dequantize(&self, _: &Layout, _: f32, _: i32, _: DType) -> HoduResult<Self>;
+
+    fn call_ops_matmul_int8(&self, _: &Self, _: &Layout, _: &Layout) -> HoduResult<Self>;
+
+    fn call_ops_block_quantize(&self, _: &Layout, _: BlockFormat) -> HoduResult<Self>;
+
+    fn call_ops_block_dequantize(&self, _: &Layout, _: BlockFormat, _: DType) -> HoduResult<Self>;
+
+    fn call_ops_block_matmul(&self, _: &Self, _: &Layout, _: &Layout, _: BlockFormat) -> HoduResult<Self>;
+
     fn call_ops_reduce(&self, _: &Layout, _: &[usize], _: bool, _: Op) -> HoduResult<Self>;
 
     fn call_ops_concat(&self, _: &[&Self], _: &[&Layout], _: usize, _: Op) -> HoduResult<Self>;
@@ -182,6 +194,8 @@ pub enum BackendStorage {
     CUDA(crate::be_cuda::storage::CudaStorage),
     #[cfg(feature = "metal")]
     Metal(crate::be_metal::storage::MetalStorage),
+    #[cfg(feature = "wgpu")]
+    WebGPU(crate::be_wgpu::storage::WgpuStorage),
 }
 
 impl BackendStorage {
@@ -192,6 +206,8 @@ impl BackendStorage {
             Self::CUDA(storage) => storage.dtype(),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => storage.dtype(),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => storage.dtype(),
         }
     }
 
@@ -202,6 +218,8 @@ impl BackendStorage {
             Self::CUDA(storage) => storage.device(),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => storage.device(),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => storage.device(),
         }
     }
 
@@ -213,6 +231,8 @@ impl BackendStorage {
             Self::CUDA(storage) => BackendDevice::CUDA(storage.backend_device().clone()),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => BackendDevice::Metal(storage.backend_device().clone()),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => BackendDevice::WebGPU(storage.backend_device().clone()),
         }
     }
 
@@ -224,6 +244,8 @@ impl BackendStorage {
             Self::CUDA(storage) => storage.to_cpu_storage(),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => storage.to_cpu_storage(),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => storage.to_cpu_storage(),
         }
     }
 
@@ -234,6 +256,8 @@ impl BackendStorage {
             Self::CUDA(storage) => storage.const_set(scalar, layout),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => storage.const_set(scalar, layout),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => storage.const_set(scalar, layout),
         }
     }
 
@@ -274,7 +298,14 @@ impl BackendStorage {
                 rhs_layout,
                 op,
             )?)),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(lhs_storage), Self::WebGPU(rhs_storage)) => Ok(Self::WebGPU(lhs_storage.call_ops_binary(
+                rhs_storage,
+                lhs_layout,
+                rhs_layout,
+                op,
+            )?)),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: lhs_device,
                 got: rhs_device,
@@ -316,7 +347,11 @@ impl BackendStorage {
             (Self::Metal(lhs_storage), Self::Metal(rhs_storage)) => Ok(Self::Metal(
                 lhs_storage.call_ops_binary_logical(rhs_storage, lhs_layout, rhs_layout, op)?,
             )),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(lhs_storage), Self::WebGPU(rhs_storage)) => Ok(Self::WebGPU(
+                lhs_storage.call_ops_binary_logical(rhs_storage, lhs_layout, rhs_layout, op)?,
+            )),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: lhs_device,
                 got: rhs_device,
@@ -358,7 +393,11 @@ impl BackendStorage {
             (Self::Metal(lhs_storage), Self::Metal(rhs_storage)) => Ok(Self::Metal(
                 lhs_storage.call_ops_bitwise_binary(rhs_storage, lhs_layout, rhs_layout, op)?,
             )),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(lhs_storage), Self::WebGPU(rhs_storage)) => Ok(Self::WebGPU(
+                lhs_storage.call_ops_bitwise_binary(rhs_storage, lhs_layout, rhs_layout, op)?,
+            )),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: lhs_device,
                 got: rhs_device,
@@ -373,6 +412,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_bitwise_unary(layout, op)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_bitwise_unary(layout, op)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_bitwise_unary(layout, op)?)),
         }
     }
 
@@ -383,6 +424,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_bitwise_unary_scalar(layout, shift, op)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_bitwise_unary_scalar(layout, shift, op)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_bitwise_unary_scalar(layout, shift, op)?)),
         }
     }
 
@@ -423,7 +466,14 @@ impl BackendStorage {
                 rhs_layout,
                 op,
             )?)),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(lhs_storage), Self::WebGPU(rhs_storage)) => Ok(Self::WebGPU(lhs_storage.call_ops_cmp(
+                rhs_storage,
+                lhs_layout,
+                rhs_layout,
+                op,
+            )?)),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: lhs_device,
                 got: rhs_device,
@@ -438,6 +488,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_cmp_scalar(layout, scalar, op)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_cmp_scalar(layout, scalar, op)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_cmp_scalar(layout, scalar, op)?)),
         }
     }
 
@@ -448,6 +500,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_unary(layout, op)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_unary(layout, op)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_unary(layout, op)?)),
         }
     }
 
@@ -458,6 +512,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_unary_logical(layout, op)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_unary_logical(layout, op)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_unary_logical(layout, op)?)),
         }
     }
 
@@ -468,6 +524,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_unary_scalar(layout, scalar, op)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_unary_scalar(layout, scalar, op)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_unary_scalar(layout, scalar, op)?)),
         }
     }
 
@@ -508,7 +566,14 @@ impl BackendStorage {
                 rhs_layout,
                 op,
             )?)),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(lhs_storage), Self::WebGPU(rhs_storage)) => Ok(Self::WebGPU(lhs_storage.call_ops_matmul(
+                rhs_storage,
+                lhs_layout,
+                rhs_layout,
+                op,
+            )?)),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: lhs_device,
                 got: rhs_device,
@@ -553,7 +618,14 @@ impl BackendStorage {
                 rhs_layout,
                 op,
             )?)),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(lhs_storage), Self::WebGPU(rhs_storage)) => Ok(Self::WebGPU(lhs_storage.call_ops_dot(
+                rhs_storage,
+                lhs_layout,
+                rhs_layout,
+                op,
+            )?)),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: lhs_device,
                 got: rhs_device,
@@ -568,6 +640,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_det(layout)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_det(layout)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_det(layout)?)),
         }
     }
 
@@ -578,6 +652,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_inv(layout)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_inv(layout)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_inv(layout)?)),
         }
     }
 
@@ -588,6 +664,163 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_trace(layout)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_trace(layout)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_trace(layout)?)),
+        }
+    }
+
+    pub(crate) fn call_ops_quantize(&self, layout: &Layout, scale: f32, zero_point: i32) -> HoduResult<Self> {
+        match self {
+            Self::CPU(storage) => Ok(Self::CPU(storage.call_ops_quantize(layout, scale, zero_point)?)),
+            #[cfg(feature = "cuda")]
+            Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_quantize(layout, scale, zero_point)?)),
+            #[cfg(feature = "metal")]
+            Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_quantize(layout, scale, zero_point)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_quantize(layout, scale, zero_point)?)),
+        }
+    }
+
+    pub(crate) fn call_ops_dequantize(
+        &self,
+        layout: &Layout,
+        scale: f32,
+        zero_point: i32,
+        dtype: DType,
+    ) -> HoduResult<Self> {
+        match self {
+            Self::CPU(storage) => Ok(Self::CPU(
+                storage.call_ops_dequantize(layout, scale, zero_point, dtype)?,
+            )),
+            #[cfg(feature = "cuda")]
+            Self::CUDA(storage) => Ok(Self::CUDA(
+                storage.call_ops_dequantize(layout, scale, zero_point, dtype)?,
+            )),
+            #[cfg(feature = "metal")]
+            Self::Metal(storage) => Ok(Self::Metal(
+                storage.call_ops_dequantize(layout, scale, zero_point, dtype)?,
+            )),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(
+                storage.call_ops_dequantize(layout, scale, zero_point, dtype)?,
+            )),
+        }
+    }
+
+    pub(crate) fn call_ops_matmul_int8(
+        &self,
+        rhs_storage: &Self,
+        lhs_layout: &Layout,
+        rhs_layout: &Layout,
+    ) -> HoduResult<Self> {
+        let lhs_device = self.device();
+        let rhs_device = rhs_storage.device();
+        if lhs_device != rhs_device {
+            return Err(HoduError::DeviceMismatch {
+                expected: lhs_device,
+                got: rhs_device,
+            });
+        }
+
+        match (self, rhs_storage) {
+            (Self::CPU(lhs_storage), Self::CPU(rhs_storage)) => Ok(Self::CPU(lhs_storage.call_ops_matmul_int8(
+                rhs_storage,
+                lhs_layout,
+                rhs_layout,
+            )?)),
+            #[cfg(feature = "cuda")]
+            (Self::CUDA(lhs_storage), Self::CUDA(rhs_storage)) => Ok(Self::CUDA(lhs_storage.call_ops_matmul_int8(
+                rhs_storage,
+                lhs_layout,
+                rhs_layout,
+            )?)),
+            #[cfg(feature = "metal")]
+            (Self::Metal(lhs_storage), Self::Metal(rhs_storage)) => Ok(Self::Metal(lhs_storage.call_ops_matmul_int8(
+                rhs_storage,
+                lhs_layout,
+                rhs_layout,
+            )?)),
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(lhs_storage), Self::WebGPU(rhs_storage)) => Ok(Self::WebGPU(
+                lhs_storage.call_ops_matmul_int8(rhs_storage, lhs_layout, rhs_layout)?,
+            )),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
+            _ => Err(HoduError::DeviceMismatch {
+                expected: lhs_device,
+                got: rhs_device,
+            }),
+        }
+    }
+
+    pub(crate) fn call_ops_block_quantize(&self, layout: &Layout, format: BlockFormat) -> HoduResult<Self> {
+        match self {
+            Self::CPU(storage) => Ok(Self::CPU(storage.call_ops_block_quantize(layout, format)?)),
+            #[cfg(feature = "cuda")]
+            Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_block_quantize(layout, format)?)),
+            #[cfg(feature = "metal")]
+            Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_block_quantize(layout, format)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_block_quantize(layout, format)?)),
+        }
+    }
+
+    pub(crate) fn call_ops_block_dequantize(
+        &self,
+        layout: &Layout,
+        format: BlockFormat,
+        dtype: DType,
+    ) -> HoduResult<Self> {
+        match self {
+            Self::CPU(storage) => Ok(Self::CPU(storage.call_ops_block_dequantize(layout, format, dtype)?)),
+            #[cfg(feature = "cuda")]
+            Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_block_dequantize(layout, format, dtype)?)),
+            #[cfg(feature = "metal")]
+            Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_block_dequantize(layout, format, dtype)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_block_dequantize(layout, format, dtype)?)),
+        }
+    }
+
+    pub(crate) fn call_ops_block_matmul(
+        &self,
+        weight_storage: &Self,
+        lhs_layout: &Layout,
+        weight_layout: &Layout,
+        format: BlockFormat,
+    ) -> HoduResult<Self> {
+        let lhs_device = self.device();
+        let weight_device = weight_storage.device();
+        if lhs_device != weight_device {
+            return Err(HoduError::DeviceMismatch {
+                expected: lhs_device,
+                got: weight_device,
+            });
+        }
+
+        match (self, weight_storage) {
+            (Self::CPU(lhs_storage), Self::CPU(weight_storage)) => Ok(Self::CPU(lhs_storage.call_ops_block_matmul(
+                weight_storage,
+                lhs_layout,
+                weight_layout,
+                format,
+            )?)),
+            #[cfg(feature = "cuda")]
+            (Self::CUDA(lhs_storage), Self::CUDA(weight_storage)) => Ok(Self::CUDA(
+                lhs_storage.call_ops_block_matmul(weight_storage, lhs_layout, weight_layout, format)?,
+            )),
+            #[cfg(feature = "metal")]
+            (Self::Metal(lhs_storage), Self::Metal(weight_storage)) => Ok(Self::Metal(
+                lhs_storage.call_ops_block_matmul(weight_storage, lhs_layout, weight_layout, format)?,
+            )),
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(lhs_storage), Self::WebGPU(weight_storage)) => Ok(Self::WebGPU(
+                lhs_storage.call_ops_block_matmul(weight_storage, lhs_layout, weight_layout, format)?,
+            )),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
+            _ => Err(HoduError::DeviceMismatch {
+                expected: lhs_device,
+                got: weight_device,
+            }),
         }
     }
 
@@ -598,6 +831,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_reduce(layout, dims, keep_dim, op)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_reduce(layout, dims, keep_dim, op)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_reduce(layout, dims, keep_dim, op)?)),
         }
     }
 
@@ -626,7 +861,7 @@ impl BackendStorage {
                     .iter()
                     .map(|s| match s {
                         Self::CPU(cpu) => cpu,
-                        #[cfg(any(feature = "cuda", feature = "metal"))]
+                        #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
                         _ => unreachable!("Device mismatch already checked"),
                     })
                     .collect();
@@ -654,6 +889,17 @@ impl BackendStorage {
                     .collect();
                 Ok(Self::Metal(storage.call_ops_concat(&others_metal, layouts, dim, op)?))
             },
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => {
+                let others_wgpu: Vec<&crate::be_wgpu::storage::WgpuStorage> = others
+                    .iter()
+                    .map(|s| match s {
+                        Self::WebGPU(wgpu) => wgpu,
+                        _ => unreachable!("Device mismatch already checked"),
+                    })
+                    .collect();
+                Ok(Self::WebGPU(storage.call_ops_concat(&others_wgpu, layouts, dim, op)?))
+            },
         }
     }
 
@@ -671,6 +917,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_split(layout, dim, start, size, op)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_split(layout, dim, start, size, op)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_split(layout, dim, start, size, op)?)),
         }
     }
 
@@ -716,7 +964,15 @@ impl BackendStorage {
                 dim,
                 op,
             )?)),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(storage), Self::WebGPU(indices)) => Ok(Self::WebGPU(storage.call_ops_index_select(
+                layout,
+                indices,
+                indices_layout,
+                dim,
+                op,
+            )?)),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: device,
                 got: indices_device,
@@ -770,7 +1026,11 @@ impl BackendStorage {
             (Self::Metal(storage), Self::Metal(indices), Self::Metal(values)) => Ok(Self::Metal(
                 storage.call_ops_index_put(layout, indices, indices_layout, values, values_layout, dim, op)?,
             )),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(storage), Self::WebGPU(indices), Self::WebGPU(values)) => Ok(Self::WebGPU(
+                storage.call_ops_index_put(layout, indices, indices_layout, values, values_layout, dim, op)?,
+            )),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => {
                 if device != indices_device {
                     Err(HoduError::DeviceMismatch {
@@ -829,7 +1089,15 @@ impl BackendStorage {
                 dim,
                 op,
             )?)),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(storage), Self::WebGPU(indices)) => Ok(Self::WebGPU(storage.call_ops_gather(
+                layout,
+                indices,
+                indices_layout,
+                dim,
+                op,
+            )?)),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: device,
                 got: indices_device,
@@ -889,7 +1157,11 @@ impl BackendStorage {
             (Self::Metal(storage), Self::Metal(indices), Self::Metal(src)) => Ok(Self::Metal(
                 storage.call_ops_scatter(layout, indices, indices_layout, src, src_layout, dim, op)?,
             )),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(storage), Self::WebGPU(indices), Self::WebGPU(src)) => Ok(Self::WebGPU(
+                storage.call_ops_scatter(layout, indices, indices_layout, src, src_layout, dim, op)?,
+            )),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => {
                 if device != indices_device {
                     Err(HoduError::DeviceMismatch {
@@ -938,6 +1210,14 @@ impl BackendStorage {
                 output_dtype,
                 op,
             )?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_onehot(
+                layout,
+                num_classes,
+                axis,
+                output_dtype,
+                op,
+            )?)),
         }
     }
 
@@ -957,6 +1237,11 @@ impl BackendStorage {
                 let (indices, count) = storage.call_nonzero(layout)?;
                 Ok((Self::Metal(indices), count))
             },
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => {
+                let (indices, count) = storage.call_nonzero(layout)?;
+                Ok((Self::WebGPU(indices), count))
+            },
         }
     }
 
@@ -986,6 +1271,16 @@ impl BackendStorage {
                     unique_count,
                 ))
             },
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => {
+                let (values, inverse, counts, unique_count) = storage.call_unique(layout)?;
+                Ok((
+                    Self::WebGPU(values),
+                    Self::WebGPU(inverse),
+                    Self::WebGPU(counts),
+                    unique_count,
+                ))
+            },
         }
     }
 
@@ -1021,7 +1316,12 @@ impl BackendStorage {
                 let (result, count) = storage.call_compress(layout, condition, condition_layout, axis)?;
                 Ok((Self::Metal(result), count))
             },
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(storage), Self::WebGPU(condition)) => {
+                let (result, count) = storage.call_compress(layout, condition, condition_layout, axis)?;
+                Ok((Self::WebGPU(result), count))
+            },
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: device,
                 got: condition_device,
@@ -1079,7 +1379,17 @@ impl BackendStorage {
                 dilation,
                 op,
             )?)),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(storage), Self::WebGPU(weight)) => Ok(Self::WebGPU(storage.call_ops_conv(
+                layout,
+                weight,
+                weight_layout,
+                stride,
+                padding,
+                dilation,
+                op,
+            )?)),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: device,
                 got: weight_device,
@@ -1141,7 +1451,18 @@ impl BackendStorage {
                 dilation,
                 op,
             )?)),
-            #[cfg(any(feature = "cuda", feature = "metal"))]
+            #[cfg(feature = "wgpu")]
+            (Self::WebGPU(storage), Self::WebGPU(grad_output)) => Ok(Self::WebGPU(storage.call_ops_conv_grad_weight(
+                layout,
+                grad_output,
+                grad_output_layout,
+                weight_shape,
+                stride,
+                padding,
+                dilation,
+                op,
+            )?)),
+            #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
             _ => Err(HoduError::DeviceMismatch {
                 expected: device,
                 got: grad_output_device,
@@ -1181,6 +1502,14 @@ impl BackendStorage {
                 padding,
                 op,
             )?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_reduce_window(
+                layout,
+                window_shape,
+                strides,
+                padding,
+                op,
+            )?)),
         }
     }
 
@@ -1204,6 +1533,10 @@ impl BackendStorage {
             Self::Metal(storage) => Ok(Self::Metal(
                 storage.call_ops_pad(layout, pad_before, pad_after, pad_value, op)?,
             )),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(
+                storage.call_ops_pad(layout, pad_before, pad_after, pad_value, op)?,
+            )),
         }
     }
 
@@ -1239,6 +1572,14 @@ impl BackendStorage {
                 coord_transform,
                 nearest_mode,
             )?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_resize(
+                layout,
+                output_shape,
+                mode,
+                coord_transform,
+                nearest_mode,
+            )?)),
         }
     }
 
@@ -1249,6 +1590,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_cumsum(layout, dim)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_cumsum(layout, dim)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_cumsum(layout, dim)?)),
         }
     }
 
@@ -1259,6 +1602,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_cumprod(layout, dim)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_cumprod(layout, dim)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_cumprod(layout, dim)?)),
         }
     }
 
@@ -1274,7 +1619,7 @@ impl BackendStorage {
                     .iter()
                     .map(|s| match s {
                         Self::CPU(cpu_storage) => Ok(cpu_storage),
-                        #[cfg(any(feature = "cuda", feature = "metal"))]
+                        #[cfg(any(feature = "cuda", feature = "metal", feature = "wgpu"))]
                         _ => Err(HoduError::DeviceMismatch {
                             expected: Device::CPU,
                             got: s.device(),
@@ -1323,6 +1668,24 @@ impl BackendStorage {
                     parsed,
                 )?))
             },
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => {
+                let wgpu_inputs: Vec<&crate::be_wgpu::storage::WgpuStorage> = inputs
+                    .iter()
+                    .map(|s| match s {
+                        Self::WebGPU(wgpu_storage) => Ok(wgpu_storage),
+                        _ => Err(HoduError::DeviceMismatch {
+                            expected: Device::WebGPU,
+                            got: s.device(),
+                        }),
+                    })
+                    .collect::<HoduResult<Vec<_>>>()?;
+                Ok(Self::WebGPU(storage.call_ops_einsum(
+                    &wgpu_inputs,
+                    input_layouts,
+                    parsed,
+                )?))
+            },
         }
     }
 
@@ -1333,6 +1696,8 @@ impl BackendStorage {
             Self::CUDA(storage) => Ok(Self::CUDA(storage.call_ops_flip(layout, dims)?)),
             #[cfg(feature = "metal")]
             Self::Metal(storage) => Ok(Self::Metal(storage.call_ops_flip(layout, dims)?)),
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => Ok(Self::WebGPU(storage.call_ops_flip(layout, dims)?)),
         }
     }
 
@@ -1360,6 +1725,11 @@ impl BackendStorage {
                 let (values, indices) = storage.call_topk(layout, k, last_dim_size, outer_size, largest, sorted)?;
                 Ok((Self::Metal(values), Self::Metal(indices)))
             },
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => {
+                let (values, indices) = storage.call_topk(layout, k, last_dim_size, outer_size, largest, sorted)?;
+                Ok((Self::WebGPU(values), Self::WebGPU(indices)))
+            },
         }
     }
 
@@ -1379,6 +1749,11 @@ impl BackendStorage {
                 let converted_storage = storage.to_dtype(layout, target_dtype)?;
                 Ok(Self::Metal(converted_storage))
             },
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => {
+                let converted_storage = storage.to_dtype(layout, target_dtype)?;
+                Ok(Self::WebGPU(converted_storage))
+            },
         }
     }
 
@@ -1403,6 +1778,13 @@ impl BackendStorage {
                     let converted_storage = MetalStorage::from_cpu_storage(&contiguous_storage)?;
                     Ok(Self::Metal(converted_storage))
                 },
+                #[cfg(feature = "wgpu")]
+                Device::WebGPU => {
+                    use crate::be_wgpu::storage::WgpuStorage;
+                    let contiguous_storage = storage.contiguous(layout)?;
+                    let converted_storage = WgpuStorage::from_cpu_storage(&contiguous_storage)?;
+                    Ok(Self::WebGPU(converted_storage))
+                },
                 #[cfg(all(feature = "metal-device", not(feature = "metal")))]
                 Device::Metal => Err(HoduError::UnsupportedDevice(Device::Metal)),
             },
@@ -1420,12 +1802,9 @@ impl BackendStorage {
                         let contiguous_storage = storage.contiguous(layout)?;
                         Ok(Self::CUDA(contiguous_storage))
                     } else {
-                        // Different device - transfer via CPU
+                        // Different device - direct device-to-device copy
                         let contiguous_storage = storage.contiguous(layout)?;
-                        let cpu_storage = contiguous_storage.to_cpu_storage()?;
-                        let converted_storage =
-                            crate::be_cuda::storage::CudaStorage::from_cpu_storage(&cpu_storage, device_id)?;
-                        Ok(Self::CUDA(converted_storage))
+                        Ok(Self::CUDA(contiguous_storage.to_cuda_device(device_id)?))
                     }
                 },
                 #[cfg(feature = "metal")]
@@ -1436,6 +1815,14 @@ impl BackendStorage {
                     let converted_storage = MetalStorage::from_cpu_storage(&cpu_storage)?;
                     Ok(Self::Metal(converted_storage))
                 },
+                #[cfg(feature = "wgpu")]
+                Device::WebGPU => {
+                    use crate::be_wgpu::storage::WgpuStorage;
+                    let contiguous_storage = storage.contiguous(layout)?;
+                    let cpu_storage = contiguous_storage.to_cpu_storage()?;
+                    let converted_storage = WgpuStorage::from_cpu_storage(&cpu_storage)?;
+                    Ok(Self::WebGPU(converted_storage))
+                },
             },
             #[cfg(feature = "metal")]
             Self::Metal(storage) => match target_device {
@@ -1457,6 +1844,45 @@ impl BackendStorage {
                     let contiguous_storage = storage.contiguous(layout)?;
                     Ok(Self::Metal(contiguous_storage))
                 },
+                #[cfg(feature = "wgpu")]
+                Device::WebGPU => {
+                    use crate::be_wgpu::storage::WgpuStorage;
+                    let contiguous_storage = storage.contiguous(layout)?;
+                    let cpu_storage = contiguous_storage.to_cpu_storage()?;
+                    let converted_storage = WgpuStorage::from_cpu_storage(&cpu_storage)?;
+                    Ok(Self::WebGPU(converted_storage))
+                },
+            },
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => match target_device {
+                Device::CPU => {
+                    let contiguoused_storage = storage.contiguous(layout)?;
+                    let converted_storage = contiguoused_storage.to_cpu_storage()?;
+                    Ok(Self::CPU(converted_storage))
+                },
+                #[cfg(feature = "cuda")]
+                Device::CUDA(device_id) => {
+                    use crate::be_cuda::storage::CudaStorage;
+                    let contiguous_storage = storage.contiguous(layout)?;
+                    let cpu_storage = contiguous_storage.to_cpu_storage()?;
+                    let converted_storage = CudaStorage::from_cpu_storage(&cpu_storage, device_id)?;
+                    Ok(Self::CUDA(converted_storage))
+                },
+                #[cfg(feature = "metal")]
+                Device::Metal => {
+                    use crate::be_metal::storage::MetalStorage;
+                    let contiguous_storage = storage.contiguous(layout)?;
+                    let cpu_storage = contiguous_storage.to_cpu_storage()?;
+                    let converted_storage = MetalStorage::from_cpu_storage(&cpu_storage)?;
+                    Ok(Self::Metal(converted_storage))
+                },
+                #[cfg(all(feature = "metal-device", not(feature = "metal")))]
+                Device::Metal => Err(HoduError::UnsupportedDevice(Device::Metal)),
+                #[cfg(feature = "wgpu")]
+                Device::WebGPU => {
+                    let contiguous_storage = storage.contiguous(layout)?;
+                    Ok(Self::WebGPU(contiguous_storage))
+                },
             },
         }
     }
@@ -1477,6 +1903,11 @@ impl BackendStorage {
                 let contiguous_storage = storage.contiguous(layout)?;
                 Ok(Self::Metal(contiguous_storage))
             },
+            #[cfg(feature = "wgpu")]
+            Self::WebGPU(storage) => {
+                let contiguous_storage = storage.contiguous(layout)?;
+                Ok(Self::WebGPU(contiguous_storage))
+            },
         }
     }
 }
@@ -1,2 +1,3 @@
 pub mod device;
+pub(crate) mod placement;
 pub mod storage;
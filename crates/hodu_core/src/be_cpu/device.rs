@@ -1,8 +1,8 @@
 use crate::{
     be::device::BackendDeviceT,
-    be_cpu::storage::CpuStorage,
+    be_cpu::{placement, storage::CpuStorage},
     error::{HoduError, HoduResult},
-    types::DType,
+    types::{get_cpu_alloc_config, DType, Device},
 };
 use float8::F8E4M3;
 #[cfg(feature = "f8e5m2")]
@@ -17,7 +17,8 @@ impl BackendDeviceT for CpuDevice {
 
     #[allow(clippy::uninit_vec)]
     fn allocate(size: usize, dtype: DType) -> HoduResult<Self::BackendStorage> {
-        let storage = match dtype {
+        crate::profiler::record_alloc(Device::CPU, size * dtype.size_in_bytes(), false);
+        let mut storage = match dtype {
             DType::BOOL => {
                 let mut v = Vec::with_capacity(size);
                 unsafe {
@@ -130,6 +131,12 @@ impl BackendDeviceT for CpuDevice {
                 CpuStorage::I64(v)
             },
         };
+        // The buffer is still untouched, so placement hints decide where its pages land
+        placement::apply(
+            storage.as_mut_ptr(),
+            size * dtype.size_in_bytes(),
+            &get_cpu_alloc_config(),
+        );
         Ok(storage)
     }
 
@@ -0,0 +1,71 @@
+//! NUMA binding and huge-page hints for freshly allocated CPU buffers
+
+use crate::types::CpuAllocConfig;
+
+/// Apply `config` to the untouched buffer at `ptr` of `len` bytes
+///
+/// Only whole pages inside the buffer are affected. Failures are ignored: the hints change
+/// where pages land, never whether the allocation is usable.
+#[cfg(target_os = "linux")]
+pub(crate) fn apply(ptr: *mut u8, len: usize, config: &CpuAllocConfig) {
+    const MPOL_BIND: libc::c_long = 2;
+
+    if config.numa_node.is_none() && !config.huge_pages {
+        return;
+    }
+
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = (ptr as usize).next_multiple_of(page);
+    let end = (ptr as usize + len) / page * page;
+    if end <= start {
+        return;
+    }
+    let addr = start as *mut libc::c_void;
+
+    if config.huge_pages && len >= config.huge_page_threshold {
+        unsafe { libc::madvise(addr, end - start, libc::MADV_HUGEPAGE) };
+    }
+
+    if let Some(node) = config.numa_node {
+        let bits = u64::BITS as usize;
+        let mut mask = vec![0u64; node / bits + 1];
+        mask[node / bits] |= 1 << (node % bits);
+        unsafe {
+            libc::syscall(
+                libc::SYS_mbind,
+                addr,
+                end - start,
+                MPOL_BIND,
+                mask.as_ptr(),
+                // The kernel reads one bit less than `maxnode`
+                mask.len() * bits + 1,
+                0u32,
+            )
+        };
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn apply(_ptr: *mut u8, _len: usize, _config: &CpuAllocConfig) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_keeps_buffer_usable() {
+        let config = CpuAllocConfig::DEFAULT
+            .with_numa_node(Some(0))
+            .with_huge_pages(true)
+            .with_huge_page_threshold(0);
+        let len = 8 << 20;
+        let mut buffer: Vec<u8> = Vec::with_capacity(len);
+        apply(buffer.as_mut_ptr(), len, &config);
+        buffer.resize(len, 7);
+        assert!(buffer.iter().all(|&b| b == 7));
+
+        // Buffers smaller than a page are left alone
+        let mut small = [0u8; 16];
+        apply(small.as_mut_ptr(), small.len(), &config);
+    }
+}
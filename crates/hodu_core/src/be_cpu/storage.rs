@@ -9,6 +9,7 @@ mod ops_indexing;
 mod ops_linalg;
 mod ops_matrix;
 mod ops_padding;
+mod ops_quant;
 mod ops_reduce;
 mod ops_resize;
 mod ops_scan;
@@ -24,7 +25,7 @@ use crate::{
     op_metadatas,
     ops::Op,
     scalar::Scalar,
-    types::{DType, Device, Layout},
+    types::{BlockFormat, DType, Device, Layout},
 };
 use core::ffi::c_void;
 use float8::F8E4M3;
@@ -475,6 +476,36 @@ impl BackendStorageT for CpuStorage {
         ops_linalg::call_ops_trace(self, layout)
     }
 
+    fn call_ops_quantize(&self, layout: &Layout, scale: f32, zero_point: i32) -> HoduResult<Self> {
+        ops_quant::call_ops_quantize(self, layout, scale, zero_point)
+    }
+
+    fn call_ops_dequantize(&self, layout: &Layout, scale: f32, zero_point: i32, dtype: DType) -> HoduResult<Self> {
+        ops_quant::call_ops_dequantize(self, layout, scale, zero_point, dtype)
+    }
+
+    fn call_ops_matmul_int8(&self, rhs_storage: &Self, lhs_layout: &Layout, rhs_layout: &Layout) -> HoduResult<Self> {
+        ops_quant::call_ops_matmul_int8(self, rhs_storage, lhs_layout, rhs_layout)
+    }
+
+    fn call_ops_block_quantize(&self, layout: &Layout, format: BlockFormat) -> HoduResult<Self> {
+        ops_quant::call_ops_block_quantize(self, layout, format)
+    }
+
+    fn call_ops_block_dequantize(&self, layout: &Layout, format: BlockFormat, dtype: DType) -> HoduResult<Self> {
+        ops_quant::call_ops_block_dequantize(self, layout, format, dtype)
+    }
+
+    fn call_ops_block_matmul(
+        &self,
+        weight_storage: &Self,
+        lhs_layout: &Layout,
+        weight_layout: &Layout,
+        format: BlockFormat,
+    ) -> HoduResult<Self> {
+        ops_quant::call_ops_block_matmul(self, weight_storage, lhs_layout, weight_layout, format)
+    }
+
     fn call_ops_reduce(&self, layout: &Layout, dims: &[usize], keep_dim: bool, op: Op) -> HoduResult<Self> {
         ops_reduce::call_ops_reduce(self, layout, dims, keep_dim, op)
     }
@@ -0,0 +1,287 @@
+use crate::{
+    be::{device::BackendDeviceT, storage::BackendStorageT},
+    be_cpu::{device::CpuDevice, storage::CpuStorage},
+    error::{HoduError, HoduResult},
+    ops::{Op, QuantOp},
+    types::{BlockFormat, DType, Layout},
+};
+use core::ffi::c_void;
+
+/// Execute int8 quantization
+///
+/// Computes `q = clamp(round(x / scale) + zero_point, -128, 127)` for a floating-point tensor.
+///
+/// # Arguments
+/// * `storage` - Floating-point input storage (BF16, F16, F32 or F64)
+/// * `layout` - Layout of input tensor
+/// * `scale` - Quantization step
+/// * `zero_point` - Integer value that represents 0.0
+///
+/// # Returns
+/// Contiguous I8 output storage
+pub fn call_ops_quantize(storage: &CpuStorage, layout: &Layout, scale: f32, zero_point: i32) -> HoduResult<CpuStorage> {
+    let dtype = storage.dtype();
+    match dtype {
+        DType::BF16 | DType::F16 | DType::F32 => (),
+        #[cfg(feature = "f64")]
+        DType::F64 => (),
+        _ => {
+            return Err(HoduError::UnsupportedDTypeForOp {
+                dtype,
+                op: Op::Quant(QuantOp::Quantize),
+            })
+        },
+    }
+
+    let metadata = crate::op_metadatas::cast_metadata(layout);
+    let mut output = CpuDevice::allocate(layout.size(), DType::I8)?;
+
+    let kernel_name = format!("hodu_cpu_quantize_{}", dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = hodu_cpu_kernels::macros::Kernel(kernel_name_static);
+
+    let in_ptr = storage.as_ptr() as *const c_void;
+    let out_ptr = output.as_mut_ptr() as *mut c_void;
+
+    hodu_cpu_kernels::call_ops_quantize(kernel, in_ptr, out_ptr, &metadata, scale, zero_point)?;
+
+    Ok(output)
+}
+
+/// Execute int8 dequantization
+///
+/// Computes `x = (q - zero_point) * scale` for an I8 tensor.
+///
+/// # Arguments
+/// * `storage` - I8 input storage
+/// * `layout` - Layout of input tensor
+/// * `scale` - Quantization step
+/// * `zero_point` - Integer value that represents 0.0
+/// * `dtype` - Floating-point output dtype (BF16, F16, F32 or F64)
+///
+/// # Returns
+/// Contiguous output storage of `dtype`
+pub fn call_ops_dequantize(
+    storage: &CpuStorage,
+    layout: &Layout,
+    scale: f32,
+    zero_point: i32,
+    dtype: DType,
+) -> HoduResult<CpuStorage> {
+    let op = Op::Quant(QuantOp::Dequantize);
+    if storage.dtype() != DType::I8 {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype: storage.dtype(),
+            op,
+        });
+    }
+    match dtype {
+        DType::BF16 | DType::F16 | DType::F32 => (),
+        #[cfg(feature = "f64")]
+        DType::F64 => (),
+        _ => return Err(HoduError::UnsupportedDTypeForOp { dtype, op }),
+    }
+
+    let metadata = crate::op_metadatas::cast_metadata(layout);
+    let mut output = CpuDevice::allocate(layout.size(), dtype)?;
+
+    let kernel_name = format!("hodu_cpu_dequantize_{}", dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = hodu_cpu_kernels::macros::Kernel(kernel_name_static);
+
+    let in_ptr = storage.as_ptr() as *const c_void;
+    let out_ptr = output.as_mut_ptr() as *mut c_void;
+
+    hodu_cpu_kernels::call_ops_dequantize(kernel, in_ptr, out_ptr, &metadata, scale, zero_point)?;
+
+    Ok(output)
+}
+
+/// Execute int8 × int8 → int32 batched matrix multiplication
+///
+/// Batch dimensions broadcast like `matmul`. Both operands must be I8; the output is I32.
+///
+/// # Arguments
+/// * `lhs_storage` - Left-hand side I8 storage
+/// * `rhs_storage` - Right-hand side I8 storage
+/// * `lhs_layout` - Layout of left-hand side tensor
+/// * `rhs_layout` - Layout of right-hand side tensor
+///
+/// # Returns
+/// Contiguous I32 output storage
+pub fn call_ops_matmul_int8(
+    lhs_storage: &CpuStorage,
+    rhs_storage: &CpuStorage,
+    lhs_layout: &Layout,
+    rhs_layout: &Layout,
+) -> HoduResult<CpuStorage> {
+    let (CpuStorage::I8(lhs), CpuStorage::I8(rhs)) = (lhs_storage, rhs_storage) else {
+        let dtype = if lhs_storage.dtype() != DType::I8 {
+            lhs_storage.dtype()
+        } else {
+            rhs_storage.dtype()
+        };
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype,
+            op: Op::Quant(QuantOp::MatmulInt8),
+        });
+    };
+
+    let output_shape = crate::op_metadatas::matmul_output_shape(lhs_layout, rhs_layout)?;
+    let output_layout = Layout::from_shape(&output_shape);
+    let metadata = crate::op_metadatas::matmul_metadata(lhs_layout, rhs_layout, &output_layout)?;
+
+    let mut output = CpuDevice::allocate(output_shape.size(), DType::I32)?;
+
+    let lhs_ptr = lhs.as_ptr() as *const c_void;
+    let rhs_ptr = rhs.as_ptr() as *const c_void;
+    let out_ptr = output.as_mut_ptr() as *mut c_void;
+
+    hodu_cpu_kernels::call_ops_matmul_int8(hodu_cpu_kernels::matmul_int8::I8, lhs_ptr, rhs_ptr, out_ptr, &metadata)?;
+
+    Ok(output)
+}
+
+/// Execute block quantization
+///
+/// Packs a floating-point tensor into `format` blocks along its last dimension.
+///
+/// # Arguments
+/// * `storage` - Floating-point input storage (BF16, F16, F32 or F64)
+/// * `layout` - Layout of input tensor; the last dimension must be a multiple of the block size
+/// * `format` - Block format to pack into
+///
+/// # Returns
+/// Contiguous U8 storage with `size / block_size * block_bytes` bytes
+pub fn call_ops_block_quantize(storage: &CpuStorage, layout: &Layout, format: BlockFormat) -> HoduResult<CpuStorage> {
+    let dtype = storage.dtype();
+    match dtype {
+        DType::BF16 | DType::F16 | DType::F32 => (),
+        #[cfg(feature = "f64")]
+        DType::F64 => (),
+        _ => {
+            return Err(HoduError::UnsupportedDTypeForOp {
+                dtype,
+                op: Op::Quant(QuantOp::BlockQuantize),
+            })
+        },
+    }
+    let last_dim = layout.shape().dims().last().copied().unwrap_or(0);
+    format.packed_dim(last_dim)?;
+
+    let metadata = crate::op_metadatas::cast_metadata(layout);
+    let mut output = CpuDevice::allocate(layout.size() / format.block_size() * format.block_bytes(), DType::U8)?;
+
+    let kernel_name = format!("hodu_cpu_quantize_{}_{}", format, dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = hodu_cpu_kernels::macros::Kernel(kernel_name_static);
+
+    let in_ptr = storage.as_ptr() as *const c_void;
+    let out_ptr = output.as_mut_ptr() as *mut c_void;
+
+    match format {
+        BlockFormat::Q4_0 => hodu_cpu_kernels::call_ops_quantize_q4_0(kernel, in_ptr, out_ptr, &metadata)?,
+    }
+
+    Ok(output)
+}
+
+/// Execute block dequantization
+///
+/// # Arguments
+/// * `storage` - Contiguous U8 storage holding packed `format` blocks
+/// * `layout` - Layout of packed tensor
+/// * `format` - Block format of the packed data
+/// * `dtype` - Floating-point output dtype (BF16, F16, F32 or F64)
+///
+/// # Returns
+/// Contiguous output storage of `dtype`
+pub fn call_ops_block_dequantize(
+    storage: &CpuStorage,
+    layout: &Layout,
+    format: BlockFormat,
+    dtype: DType,
+) -> HoduResult<CpuStorage> {
+    let op = Op::Quant(QuantOp::BlockDequantize);
+    if storage.dtype() != DType::U8 {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype: storage.dtype(),
+            op,
+        });
+    }
+    match dtype {
+        DType::BF16 | DType::F16 | DType::F32 => (),
+        #[cfg(feature = "f64")]
+        DType::F64 => (),
+        _ => return Err(HoduError::UnsupportedDTypeForOp { dtype, op }),
+    }
+
+    let metadata = crate::op_metadatas::block_dequantize_metadata(layout, format)?;
+    let mut output = CpuDevice::allocate(metadata[0] * format.block_size(), dtype)?;
+
+    let kernel_name = format!("hodu_cpu_dequantize_{}_{}", format, dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = hodu_cpu_kernels::macros::Kernel(kernel_name_static);
+
+    let in_ptr = storage.as_ptr() as *const c_void;
+    let out_ptr = output.as_mut_ptr() as *mut c_void;
+
+    match format {
+        BlockFormat::Q4_0 => hodu_cpu_kernels::call_ops_dequantize_q4_0(kernel, in_ptr, out_ptr, &metadata)?,
+    }
+
+    Ok(output)
+}
+
+/// Execute a fused block-dequant matmul
+///
+/// Computes `lhs[..., K] @ dequant(weight[N, K])^T` without materializing the weight.
+///
+/// # Arguments
+/// * `lhs_storage` - Contiguous floating-point storage (BF16, F16, F32 or F64)
+/// * `weight_storage` - Contiguous U8 storage holding packed `[N, K]` blocks
+/// * `lhs_layout` - Layout of lhs tensor
+/// * `weight_layout` - Layout of packed weight tensor
+/// * `format` - Block format of the weight
+///
+/// # Returns
+/// Contiguous output storage of the lhs dtype with shape `[..., N]`
+pub fn call_ops_block_matmul(
+    lhs_storage: &CpuStorage,
+    weight_storage: &CpuStorage,
+    lhs_layout: &Layout,
+    weight_layout: &Layout,
+    format: BlockFormat,
+) -> HoduResult<CpuStorage> {
+    let op = Op::Quant(QuantOp::BlockMatmul);
+    let dtype = lhs_storage.dtype();
+    match dtype {
+        DType::BF16 | DType::F16 | DType::F32 => (),
+        #[cfg(feature = "f64")]
+        DType::F64 => (),
+        _ => return Err(HoduError::UnsupportedDTypeForOp { dtype, op }),
+    }
+    if weight_storage.dtype() != DType::U8 {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype: weight_storage.dtype(),
+            op,
+        });
+    }
+
+    let metadata = crate::op_metadatas::block_matmul_metadata(lhs_layout, weight_layout, format)?;
+    let mut output = CpuDevice::allocate(metadata[0] * metadata[2], dtype)?;
+
+    let kernel_name = format!("hodu_cpu_matmul_{}_{}", format, dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = hodu_cpu_kernels::macros::Kernel(kernel_name_static);
+
+    let lhs_ptr = lhs_storage.as_ptr() as *const c_void;
+    let weight_ptr = weight_storage.as_ptr() as *const c_void;
+    let out_ptr = output.as_mut_ptr() as *mut c_void;
+
+    match format {
+        BlockFormat::Q4_0 => hodu_cpu_kernels::call_ops_matmul_q4_0(kernel, lhs_ptr, weight_ptr, out_ptr, &metadata)?,
+    }
+
+    Ok(output)
+}
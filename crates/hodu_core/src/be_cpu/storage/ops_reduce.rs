@@ -66,7 +66,7 @@ pub fn call_ops_reduce(
     let output_shape = Shape::new(&output_shape_vec);
 
     // Generate metadata using centralized function
-    let metadata = crate::op_metadatas::reduce_metadata(layout, dims, keep_dim);
+    let metadata = crate::op_metadatas::reduce_metadata(layout, dims);
 
     // Generate kernel name
     let kernel_name = format!("hodu_cpu_{}_{}", reduce_op, storage.dtype());
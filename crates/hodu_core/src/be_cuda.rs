@@ -1,2 +1,3 @@
+pub mod allocator;
 pub mod device;
 pub mod storage;
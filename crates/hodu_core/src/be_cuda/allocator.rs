@@ -0,0 +1,193 @@
+use crate::{
+    error::{HoduError, HoduResult},
+    types::{Device, MemoryStats},
+};
+use hodu_cuda_kernels::cuda::{CudaEvent, CudaSlice, CudaStream, DevicePtr, DeviceRepr};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Smallest block handed out by the allocator
+const MIN_BLOCK_SIZE: usize = 512;
+/// Requests up to this size are rounded to a power of two
+const SMALL_BLOCK_LIMIT: usize = 1 << 20;
+/// Larger requests are rounded to a multiple of this
+const LARGE_BLOCK_ROUND: usize = 2 << 20;
+
+/// Size class (in bytes) a request of `size` bytes is served from
+fn size_class(size: usize) -> usize {
+    let size = size.max(MIN_BLOCK_SIZE);
+    if size <= SMALL_BLOCK_LIMIT {
+        size.next_power_of_two()
+    } else {
+        size.div_ceil(LARGE_BLOCK_ROUND) * LARGE_BLOCK_ROUND
+    }
+}
+
+/// Cached block that is no longer referenced by any storage
+struct FreeBlock {
+    ptr: u64,
+    /// Stream the block was last used on
+    stream: Arc<CudaStream>,
+    /// Recorded on `stream` once all work touching the block was queued
+    ready: CudaEvent,
+}
+
+#[derive(Default)]
+struct AllocatorState {
+    /// Size class -> cached blocks
+    free: HashMap<usize, Vec<FreeBlock>>,
+    /// Device pointer -> size class of blocks currently owned by a storage
+    live: HashMap<u64, usize>,
+    stats: MemoryStats,
+}
+
+/// Caching allocator for one CUDA device
+///
+/// Blocks are binned by size class and returned to their bin when the owning storage is
+/// dropped instead of being freed. A block is reused immediately on the stream it was
+/// released from; other streams first wait on the event recorded at release.
+#[derive(Default)]
+pub struct CudaAllocator {
+    state: Mutex<AllocatorState>,
+}
+
+impl CudaAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate an uninitialized buffer of `len` elements on `stream`
+    pub fn alloc<T: DeviceRepr>(&self, stream: &Arc<CudaStream>, len: usize) -> HoduResult<CudaSlice<T>> {
+        let class = size_class(len * std::mem::size_of::<T>());
+        let mut state = self.state.lock()?;
+
+        let cached = Self::take_cached(&mut state, stream, class)?;
+        crate::profiler::record_alloc(Device::CUDA(stream.context().ordinal()), class, cached.is_some());
+        let ptr = match cached {
+            Some(ptr) => {
+                state.stats.num_cache_hits += 1;
+                state.stats.cached_bytes -= class;
+                ptr
+            },
+            None => {
+                let ptr = match Self::alloc_block(stream, class) {
+                    Ok(ptr) => ptr,
+                    Err(_) => {
+                        // Out of memory: give cached blocks back to the driver and retry once
+                        Self::release_cached(&mut state);
+                        Self::alloc_block(stream, class)?
+                    },
+                };
+                state.stats.num_allocs += 1;
+                ptr
+            },
+        };
+
+        state.live.insert(ptr, class);
+        state.stats.allocated_bytes += class;
+        state.stats.peak_allocated_bytes = state.stats.peak_allocated_bytes.max(state.stats.allocated_bytes);
+
+        Ok(unsafe { stream.upgrade_device_ptr(ptr, len) })
+    }
+
+    /// Return `slice` to the cache
+    ///
+    /// Slices that were not allocated by this allocator are dropped normally.
+    pub fn release<T>(&self, slice: CudaSlice<T>) -> HoduResult<()> {
+        let stream = slice.stream().clone();
+        let ptr = {
+            let (ptr, _record) = slice.device_ptr(&stream);
+            ptr
+        };
+
+        let mut state = self.state.lock()?;
+        let Some(class) = state.live.remove(&ptr) else {
+            return Ok(());
+        };
+
+        // `leak` makes the slice's stream wait for every pending read and write of the block
+        let ptr = slice.leak();
+        let ready = match stream.record_event(None) {
+            Ok(ready) => ready,
+            Err(e) => {
+                state.stats.allocated_bytes -= class;
+                drop(unsafe { stream.upgrade_device_ptr::<u8>(ptr, class) });
+                return Err(HoduError::BackendError(format!("CUDA record_event failed: {:?}", e)));
+            },
+        };
+
+        state.stats.allocated_bytes -= class;
+        state.stats.cached_bytes += class;
+        state
+            .free
+            .entry(class)
+            .or_default()
+            .push(FreeBlock { ptr, stream, ready });
+        Ok(())
+    }
+
+    /// Free every cached block back to the driver
+    pub fn empty_cache(&self) -> HoduResult<()> {
+        let mut state = self.state.lock()?;
+        Self::release_cached(&mut state);
+        Ok(())
+    }
+
+    pub fn stats(&self) -> HoduResult<MemoryStats> {
+        Ok(self.state.lock()?.stats)
+    }
+
+    fn take_cached(state: &mut AllocatorState, stream: &Arc<CudaStream>, class: usize) -> HoduResult<Option<u64>> {
+        let Some(blocks) = state.free.get_mut(&class) else {
+            return Ok(None);
+        };
+
+        // Prefer a block from the same stream, which is already ordered after its last use
+        let index = match blocks.iter().rposition(|b| b.stream.cu_stream() == stream.cu_stream()) {
+            Some(index) => index,
+            None if !blocks.is_empty() => blocks.len() - 1,
+            None => return Ok(None),
+        };
+
+        let block = &blocks[index];
+        if block.stream.cu_stream() != stream.cu_stream() {
+            stream
+                .wait(&block.ready)
+                .map_err(|e| HoduError::BackendError(format!("CUDA stream wait failed: {:?}", e)))?;
+        }
+        Ok(Some(blocks.swap_remove(index).ptr))
+    }
+
+    fn alloc_block(stream: &Arc<CudaStream>, class: usize) -> HoduResult<u64> {
+        let slice = unsafe { stream.alloc::<u8>(class) }
+            .map_err(|e| HoduError::BackendError(format!("CUDA alloc failed: {:?}", e)))?;
+        Ok(slice.leak())
+    }
+
+    fn release_cached(state: &mut AllocatorState) {
+        for (class, blocks) in state.free.drain() {
+            for block in blocks {
+                // Dropping the re-wrapped slice frees the block on the stream it was last used on
+                drop(unsafe { block.stream.upgrade_device_ptr::<u8>(block.ptr, class) });
+            }
+        }
+        state.stats.cached_bytes = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_class() {
+        assert_eq!(size_class(0), MIN_BLOCK_SIZE);
+        assert_eq!(size_class(1), MIN_BLOCK_SIZE);
+        assert_eq!(size_class(513), 1024);
+        assert_eq!(size_class(SMALL_BLOCK_LIMIT), SMALL_BLOCK_LIMIT);
+        assert_eq!(size_class(SMALL_BLOCK_LIMIT + 1), LARGE_BLOCK_ROUND);
+        assert_eq!(size_class(LARGE_BLOCK_ROUND + 1), 2 * LARGE_BLOCK_ROUND);
+    }
+}
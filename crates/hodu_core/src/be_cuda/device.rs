@@ -1,19 +1,28 @@
 use crate::{
     be::device::BackendDeviceT,
-    be_cuda::storage::CudaStorage,
+    be_cuda::{allocator::CudaAllocator, storage::CudaStorage},
     error::{HoduError, HoduResult},
-    types::DType,
+    types::{get_precision, DType, MemoryStats},
 };
 use hodu_cuda_kernels::{
-    cuda::{CudaContext, CudaSlice},
+    cuda::{CUevent_flags, CudaContext, CudaEvent, CudaSlice, CudaStream},
     kernel::Kernels,
+    precision::{Precision, PrecisionGuard},
+    stream::{current_stream, StreamGuard, StreamPool},
 };
 
+/// Number of compute streams in each device's pool
+const NUM_COMPUTE_STREAMS: usize = 4;
+
 #[derive(Clone)]
 pub struct CudaDevice {
     pub(crate) cuda_device_id: usize,
     pub(crate) context: Arc<CudaContext>,
     pub(crate) kernels: Arc<Kernels>,
+    pub(crate) streams: Arc<StreamPool>,
+    pub(crate) allocator: Arc<CudaAllocator>,
+    // Peer device ID -> whether direct access to it is enabled
+    pub(crate) peer_access: Arc<RwLock<HashMap<usize, bool>>>,
 }
 
 // Global device pool: maps CUDA device ID -> CudaDevice
@@ -53,11 +62,20 @@ impl CudaDevice {
             ))
         })?;
         let kernels = Kernels::new();
+        let streams = StreamPool::new(&context, NUM_COMPUTE_STREAMS).map_err(|e| {
+            HoduError::BackendError(format!(
+                "Failed to create CUDA streams for device {}: {}",
+                cuda_device_id, e
+            ))
+        })?;
 
         let device = Arc::new(CudaDevice {
             cuda_device_id,
             context,
             kernels: Arc::new(kernels),
+            streams: Arc::new(streams),
+            allocator: Arc::new(CudaAllocator::new()),
+            peer_access: Arc::new(RwLock::new(HashMap::new())),
         });
 
         devices.insert(cuda_device_id, device.clone());
@@ -88,26 +106,94 @@ impl CudaDevice {
         &self.context
     }
 
+    pub fn streams(&self) -> &StreamPool {
+        &self.streams
+    }
+
+    /// Stream that work for this device is enqueued on from the calling thread
+    pub fn stream(&self) -> Arc<CudaStream> {
+        current_stream(&self.context)
+    }
+
+    /// Run subsequent work for this device on the next pooled stream until the guard is dropped
+    ///
+    /// Each storage op enters its own stream, so independent ops overlap; ops that share
+    /// buffers are ordered by the events recorded on those buffers. While a profile is recording
+    /// the current stream is kept instead, so the profiler's events bracket each op's work.
+    pub fn enter_stream(&self) -> StreamGuard {
+        if crate::profiler::is_recording() {
+            return StreamGuard::new(self.stream());
+        }
+        self.streams.enter()
+    }
+
+    /// Record an event with timing enabled on the current stream
+    pub fn record_timing_event(&self) -> HoduResult<CudaEvent> {
+        self.stream()
+            .record_event(Some(CUevent_flags::CU_EVENT_DEFAULT))
+            .map_err(|e| HoduError::BackendError(format!("Failed to record CUDA event: {:?}", e)))
+    }
+
+    /// Make the calling thread's [`Precision`](crate::types::Precision) visible to
+    /// matmul and convolution kernels until the guard is dropped
+    pub fn enter_precision(&self) -> PrecisionGuard {
+        let precision = get_precision();
+        Precision {
+            allow_tf32: precision.allow_tf32,
+            f16_accumulate_f32: precision.f16_accumulate_f32,
+        }
+        .enter()
+    }
+
+    /// Enable direct access from this device to `peer`'s memory, once per pair
+    ///
+    /// Returns `false` when the devices have no peer path, in which case copies between them
+    /// are staged through host memory by the driver.
+    pub fn enable_peer_access(&self, peer: &CudaDevice) -> HoduResult<bool> {
+        if peer.cuda_device_id == self.cuda_device_id {
+            return Ok(true);
+        }
+
+        if let Some(&enabled) = self.peer_access.read()?.get(&peer.cuda_device_id) {
+            return Ok(enabled);
+        }
+
+        let enabled = hodu_cuda_kernels::peer::enable_peer_access(&self.context, &peer.context)?;
+        self.peer_access.write()?.insert(peer.cuda_device_id, enabled);
+        Ok(enabled)
+    }
+
+    pub fn allocator(&self) -> &CudaAllocator {
+        &self.allocator
+    }
+
+    /// Free all cached device memory that is not used by a live tensor
+    pub fn empty_cache(&self) -> HoduResult<()> {
+        self.allocator.empty_cache()
+    }
+
+    pub fn memory_stats(&self) -> HoduResult<MemoryStats> {
+        self.allocator.stats()
+    }
+
     pub fn new_buffer<T>(&self, element_count: usize) -> HoduResult<CudaSlice<T>>
     where
         T: hodu_cuda_kernels::cuda::DeviceRepr,
     {
-        let stream = self.context.default_stream();
-        unsafe {
-            stream
-                .alloc(element_count)
-                .map_err(|e| HoduError::BackendError(format!("CUDA alloc failed: {:?}", e)))
-        }
+        self.allocator.alloc(&self.stream(), element_count)
     }
 
     pub fn new_buffer_with_data<T>(&self, data: &[T]) -> HoduResult<CudaSlice<T>>
     where
         T: hodu_cuda_kernels::cuda::DeviceRepr + Clone,
     {
-        let stream = self.context.default_stream();
+        // Uploads go through the transfer stream so they overlap with compute
+        let stream = self.streams.transfer_stream();
+        let mut buffer = self.allocator.alloc(stream, data.len())?;
         stream
-            .memcpy_stod(data)
-            .map_err(|e| HoduError::BackendError(format!("CUDA memcpy_stod failed: {:?}", e)))
+            .memcpy_htod(data, &mut buffer)
+            .map_err(|e| HoduError::BackendError(format!("CUDA memcpy_htod failed: {:?}", e)))?;
+        Ok(buffer)
     }
 
     /// Allocate storage on a specific CUDA device
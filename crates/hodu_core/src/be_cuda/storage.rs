@@ -9,6 +9,7 @@ mod ops_indexing;
 mod ops_linalg;
 mod ops_matrix;
 mod ops_padding;
+mod ops_quant;
 mod ops_reduce;
 mod ops_resize;
 mod ops_scan;
@@ -25,13 +26,14 @@ use crate::{
     op_metadatas,
     ops::Op,
     scalar::Scalar,
-    types::{DType, Device, Layout, Shape},
+    types::{BlockFormat, DType, Device, Layout, Shape},
 };
 use float8::F8E4M3;
 #[cfg(feature = "f8e5m2")]
 use float8::F8E5M2;
 use half::{bf16, f16};
 use hodu_cuda_kernels::cuda::{CudaSlice, DevicePtr, DeviceRepr};
+use std::mem::ManuallyDrop;
 
 #[derive(Debug, Clone)]
 pub(crate) enum CudaStorageData {
@@ -62,7 +64,8 @@ pub(crate) enum CudaStorageData {
 pub struct CudaStorage {
     device_id: usize,
     device: Arc<CudaDevice>,
-    data: CudaStorageData,
+    // Taken on drop to hand the buffer back to the device allocator
+    data: ManuallyDrop<CudaStorageData>,
 }
 
 impl CudaStorage {
@@ -70,7 +73,7 @@ impl CudaStorage {
         Self {
             device_id,
             device,
-            data,
+            data: ManuallyDrop::new(data),
         }
     }
 
@@ -84,7 +87,7 @@ impl CudaStorage {
 
     /// Get device pointer to the underlying CUDA data
     pub fn as_ptr(&self) -> *const u8 {
-        match &self.data {
+        match &*self.data {
             CudaStorageData::BOOL(s) => s.device_ptr().as_ptr() as *const u8,
             CudaStorageData::F8E4M3(s) => s.device_ptr().as_ptr() as *const u8,
             #[cfg(feature = "f8e5m2")]
@@ -111,7 +114,7 @@ impl CudaStorage {
 
     /// Get mutable device pointer to the underlying CUDA data
     pub fn as_mut_ptr(&mut self) -> *mut u8 {
-        match &mut self.data {
+        match &mut *self.data {
             CudaStorageData::BOOL(s) => s.device_ptr().as_ptr() as *mut u8,
             CudaStorageData::F8E4M3(s) => s.device_ptr().as_ptr() as *mut u8,
             #[cfg(feature = "f8e5m2")]
@@ -137,7 +140,7 @@ impl CudaStorage {
     }
 
     pub fn len(&self) -> usize {
-        match &self.data {
+        match &*self.data {
             CudaStorageData::BOOL(s) => s.len(),
             CudaStorageData::F8E4M3(s) => s.len(),
             #[cfg(feature = "f8e5m2")]
@@ -164,7 +167,7 @@ impl CudaStorage {
 
     pub fn to_cpu<T: DeviceRepr + Clone>(&self, slice: &CudaSlice<T>) -> HoduResult<Vec<T>> {
         let device = self.get_device();
-        let stream = device.context().default_stream();
+        let stream = device.streams().transfer_stream();
         let mut result = vec![unsafe { core::mem::zeroed() }; slice.len()];
         stream
             .memcpy_dtoh(slice, &mut result)
@@ -172,6 +175,50 @@ impl CudaStorage {
         Ok(result)
     }
 
+    /// Copy this storage to another CUDA device without going through host memory
+    pub fn to_cuda_device(&self, device_id: usize) -> HoduResult<Self> {
+        let target = CudaDevice::get(device_id)?;
+        self.device.enable_peer_access(&target)?;
+        target.enable_peer_access(&self.device)?;
+
+        // Allocate on the target's stream; the copy itself is ordered through events
+        let _stream = target.enter_stream();
+
+        macro_rules! copy_peer {
+            ($src:expr) => {{
+                let mut dst = target.new_buffer($src.len())?;
+                hodu_cuda_kernels::peer::copy_peer($src, &mut dst)?;
+                dst
+            }};
+        }
+
+        let data = match &*self.data {
+            CudaStorageData::BOOL(s) => CudaStorageData::BOOL(copy_peer!(s)),
+            CudaStorageData::F8E4M3(s) => CudaStorageData::F8E4M3(copy_peer!(s)),
+            #[cfg(feature = "f8e5m2")]
+            CudaStorageData::F8E5M2(s) => CudaStorageData::F8E5M2(copy_peer!(s)),
+            CudaStorageData::BF16(s) => CudaStorageData::BF16(copy_peer!(s)),
+            CudaStorageData::F16(s) => CudaStorageData::F16(copy_peer!(s)),
+            CudaStorageData::F32(s) => CudaStorageData::F32(copy_peer!(s)),
+            #[cfg(feature = "f64")]
+            CudaStorageData::F64(s) => CudaStorageData::F64(copy_peer!(s)),
+            CudaStorageData::U8(s) => CudaStorageData::U8(copy_peer!(s)),
+            #[cfg(feature = "u16")]
+            CudaStorageData::U16(s) => CudaStorageData::U16(copy_peer!(s)),
+            CudaStorageData::U32(s) => CudaStorageData::U32(copy_peer!(s)),
+            #[cfg(feature = "u64")]
+            CudaStorageData::U64(s) => CudaStorageData::U64(copy_peer!(s)),
+            CudaStorageData::I8(s) => CudaStorageData::I8(copy_peer!(s)),
+            #[cfg(feature = "i16")]
+            CudaStorageData::I16(s) => CudaStorageData::I16(copy_peer!(s)),
+            CudaStorageData::I32(s) => CudaStorageData::I32(copy_peer!(s)),
+            #[cfg(feature = "i64")]
+            CudaStorageData::I64(s) => CudaStorageData::I64(copy_peer!(s)),
+        };
+
+        Ok(Self::new(device_id, target, data))
+    }
+
     pub fn from_cpu_storage(cpu_storage: &CpuStorage, device_id: usize) -> HoduResult<Self> {
         let device = CudaDevice::get(device_id)?;
         let data = Self::cpu_to_cuda_storage_data(&device, cpu_storage)?;
@@ -205,11 +252,44 @@ impl CudaStorage {
     }
 }
 
+impl Drop for CudaStorage {
+    fn drop(&mut self) {
+        // SAFETY: `data` is not accessed again after being taken here
+        let data = unsafe { ManuallyDrop::take(&mut self.data) };
+        let allocator = self.device.allocator();
+
+        // Errors cannot be propagated from drop; the buffer is freed instead of cached
+        let _ = match data {
+            CudaStorageData::BOOL(s) => allocator.release(s),
+            CudaStorageData::F8E4M3(s) => allocator.release(s),
+            #[cfg(feature = "f8e5m2")]
+            CudaStorageData::F8E5M2(s) => allocator.release(s),
+            CudaStorageData::BF16(s) => allocator.release(s),
+            CudaStorageData::F16(s) => allocator.release(s),
+            CudaStorageData::F32(s) => allocator.release(s),
+            #[cfg(feature = "f64")]
+            CudaStorageData::F64(s) => allocator.release(s),
+            CudaStorageData::U8(s) => allocator.release(s),
+            #[cfg(feature = "u16")]
+            CudaStorageData::U16(s) => allocator.release(s),
+            CudaStorageData::U32(s) => allocator.release(s),
+            #[cfg(feature = "u64")]
+            CudaStorageData::U64(s) => allocator.release(s),
+            CudaStorageData::I8(s) => allocator.release(s),
+            #[cfg(feature = "i16")]
+            CudaStorageData::I16(s) => allocator.release(s),
+            CudaStorageData::I32(s) => allocator.release(s),
+            #[cfg(feature = "i64")]
+            CudaStorageData::I64(s) => allocator.release(s),
+        };
+    }
+}
+
 impl BackendStorageT for CudaStorage {
     type BackendDevice = CudaDevice;
 
     fn dtype(&self) -> DType {
-        match &self.data {
+        match &*self.data {
             CudaStorageData::BOOL(_) => DType::BOOL,
             CudaStorageData::F8E4M3(_) => DType::F8E4M3,
             #[cfg(feature = "f8e5m2")]
@@ -243,7 +323,7 @@ impl BackendStorageT for CudaStorage {
     }
 
     fn to_cpu_storage(&self) -> HoduResult<CpuStorage> {
-        match &self.data {
+        match &*self.data {
             CudaStorageData::BOOL(s) => Ok(CpuStorage::BOOL(self.to_cpu(s)?)),
             CudaStorageData::F8E4M3(s) => Ok(CpuStorage::F8E4M3(self.to_cpu(s)?)),
             #[cfg(feature = "f8e5m2")]
@@ -269,6 +349,7 @@ impl BackendStorageT for CudaStorage {
     }
 
     fn const_set(&mut self, scalar: Scalar, layout: &Layout) -> HoduResult<()> {
+        let _stream = self.device.enter_stream();
         let shape = layout.shape();
         let strides = layout.strides();
         let offset = layout.offset();
@@ -301,7 +382,7 @@ impl BackendStorageT for CudaStorage {
             }};
         }
 
-        match (&mut self.data, scalar) {
+        match (&mut *self.data, scalar) {
             (CudaStorageData::BOOL(slice), Scalar::BOOL(v)) => call_const_set!(slice, v, bool),
             (CudaStorageData::F8E4M3(slice), Scalar::F8E4M3(v)) => call_const_set!(slice, v, float8::F8E4M3),
             #[cfg(feature = "f8e5m2")]
@@ -335,6 +416,7 @@ impl BackendStorageT for CudaStorage {
     }
 
     fn call_ops_binary(&self, rhs: &Self, lhs_layout: &Layout, rhs_layout: &Layout, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_binary::call_ops_binary(self, rhs, lhs_layout, rhs_layout, op)
     }
 
@@ -345,6 +427,7 @@ impl BackendStorageT for CudaStorage {
         rhs_layout: &Layout,
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_binary::call_ops_binary_logical(self, rhs, lhs_layout, rhs_layout, op)
     }
 
@@ -355,66 +438,120 @@ impl BackendStorageT for CudaStorage {
         rhs_layout: &Layout,
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_bitwise::call_ops_bitwise_binary(self, rhs, lhs_layout, rhs_layout, op)
     }
 
     fn call_ops_bitwise_unary(&self, layout: &Layout, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_bitwise::call_ops_bitwise_unary(self, layout, op)
     }
 
     fn call_ops_bitwise_unary_scalar(&self, layout: &Layout, shift: u32, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_bitwise::call_ops_bitwise_unary_scalar(self, layout, shift, op)
     }
 
     fn call_ops_cmp(&self, rhs: &Self, lhs_layout: &Layout, rhs_layout: &Layout, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_binary::call_ops_cmp(self, rhs, lhs_layout, rhs_layout, op)
     }
 
     fn call_ops_cmp_scalar(&self, layout: &Layout, scalar: Scalar, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_unary::call_ops_cmp_scalar(self, layout, scalar, op)
     }
 
     fn call_ops_unary(&self, layout: &Layout, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_unary::call_ops_unary(self, layout, op)
     }
 
     fn call_ops_unary_logical(&self, layout: &Layout, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_unary::call_ops_unary_logical(self, layout, op)
     }
 
     fn call_ops_unary_scalar(&self, layout: &Layout, scalar: Scalar, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_unary::call_ops_unary_scalar(self, layout, scalar, op)
     }
 
     fn call_ops_matmul(&self, rhs: &Self, lhs_layout: &Layout, rhs_layout: &Layout, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
+        let _precision = self.device.enter_precision();
         ops_matrix::call_ops_matmul(self, rhs, lhs_layout, rhs_layout, op)
     }
 
     fn call_ops_dot(&self, rhs: &Self, lhs_layout: &Layout, rhs_layout: &Layout, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
+        let _precision = self.device.enter_precision();
         ops_matrix::call_ops_dot(self, rhs, lhs_layout, rhs_layout, op)
     }
 
     fn call_ops_det(&self, layout: &Layout) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_linalg::call_ops_det(self, layout)
     }
 
     fn call_ops_inv(&self, layout: &Layout) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_linalg::call_ops_inv(self, layout)
     }
 
     fn call_ops_trace(&self, layout: &Layout) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_linalg::call_ops_trace(self, layout)
     }
 
+    fn call_ops_quantize(&self, layout: &Layout, scale: f32, zero_point: i32) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
+        ops_quant::call_ops_quantize(self, layout, scale, zero_point)
+    }
+
+    fn call_ops_dequantize(&self, layout: &Layout, scale: f32, zero_point: i32, dtype: DType) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
+        ops_quant::call_ops_dequantize(self, layout, scale, zero_point, dtype)
+    }
+
+    fn call_ops_matmul_int8(&self, rhs_storage: &Self, lhs_layout: &Layout, rhs_layout: &Layout) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
+        ops_quant::call_ops_matmul_int8(self, rhs_storage, lhs_layout, rhs_layout)
+    }
+
+    fn call_ops_block_quantize(&self, layout: &Layout, format: BlockFormat) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
+        ops_quant::call_ops_block_quantize(self, layout, format)
+    }
+
+    fn call_ops_block_dequantize(&self, layout: &Layout, format: BlockFormat, dtype: DType) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
+        ops_quant::call_ops_block_dequantize(self, layout, format, dtype)
+    }
+
+    fn call_ops_block_matmul(
+        &self,
+        weight_storage: &Self,
+        lhs_layout: &Layout,
+        weight_layout: &Layout,
+        format: BlockFormat,
+    ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
+        ops_quant::call_ops_block_matmul(self, weight_storage, lhs_layout, weight_layout, format)
+    }
+
     fn call_ops_reduce(&self, layout: &Layout, dims: &[usize], keep_dim: bool, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_reduce::call_ops_reduce(self, layout, dims, keep_dim, op)
     }
 
     fn call_ops_concat(&self, others: &[&Self], layouts: &[&Layout], dim: usize, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_concat_split::call_ops_concat(self, others, layouts, dim, op)
     }
 
     fn call_ops_split(&self, layout: &Layout, dim: usize, start: usize, size: usize, op: Op) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_concat_split::call_ops_split(self, layout, dim, start, size, op)
     }
 
@@ -426,6 +563,7 @@ impl BackendStorageT for CudaStorage {
         dim: usize,
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_indexing::call_ops_index_select(self, layout, indices, indices_layout, dim, op)
     }
 
@@ -439,6 +577,7 @@ impl BackendStorageT for CudaStorage {
         dim: usize,
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_indexing::call_ops_index_put(self, layout, indices, indices_layout, values, values_layout, dim, op)
     }
 
@@ -450,6 +589,7 @@ impl BackendStorageT for CudaStorage {
         dim: usize,
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_indexing::call_ops_gather(self, layout, indices, indices_layout, dim, op)
     }
 
@@ -463,6 +603,7 @@ impl BackendStorageT for CudaStorage {
         dim: usize,
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_indexing::call_ops_scatter(self, layout, indices, indices_layout, src, src_layout, dim, op)
     }
 
@@ -474,6 +615,7 @@ impl BackendStorageT for CudaStorage {
         output_dtype: DType,
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_indexing::call_ops_onehot(self, layout, num_classes, axis, output_dtype, op)
     }
 
@@ -487,6 +629,8 @@ impl BackendStorageT for CudaStorage {
         dilation: &[usize],
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
+        let _precision = self.device.enter_precision();
         ops_conv::call_ops_conv(self, layout, weight, weight_layout, stride, padding, dilation, op)
     }
 
@@ -501,6 +645,7 @@ impl BackendStorageT for CudaStorage {
         dilation: &[usize],
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_conv::call_ops_conv_grad_weight(
             self,
             layout,
@@ -522,6 +667,7 @@ impl BackendStorageT for CudaStorage {
         padding: &[usize],
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_windowing::call_ops_reduce_window(self, layout, window_shape, strides, padding, op)
     }
 
@@ -533,6 +679,7 @@ impl BackendStorageT for CudaStorage {
         pad_value: Scalar,
         op: Op,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_padding::call_ops_pad(self, layout, pad_before, pad_after, pad_value, op)
     }
 
@@ -544,14 +691,17 @@ impl BackendStorageT for CudaStorage {
         coord_transform: crate::op_params::ResizeCoordTransform,
         nearest_mode: crate::op_params::ResizeNearestMode,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_resize::call_ops_resize(self, layout, output_shape, mode, coord_transform, nearest_mode)
     }
 
     fn call_ops_cumsum(&self, layout: &Layout, dim: usize) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_scan::call_ops_cumsum(self, layout, dim)
     }
 
     fn call_ops_cumprod(&self, layout: &Layout, dim: usize) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_scan::call_ops_cumprod(self, layout, dim)
     }
 
@@ -561,10 +711,12 @@ impl BackendStorageT for CudaStorage {
         input_layouts: &[&Layout],
         parsed: &crate::einsum::ParsedEinsum,
     ) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_einsum::call_ops_einsum(self, inputs, input_layouts, parsed)
     }
 
     fn call_ops_flip(&self, layout: &Layout, dims: &[usize]) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         ops_shape_memory::call_ops_flip(self, layout, dims)
     }
 
@@ -577,14 +729,17 @@ impl BackendStorageT for CudaStorage {
         largest: bool,
         sorted: bool,
     ) -> HoduResult<(Self, Self)> {
+        let _stream = self.device.enter_stream();
         ops_sort::call_topk(self, layout, k, last_dim_size, outer_size, largest, sorted)
     }
 
     fn call_nonzero(&self, layout: &Layout) -> HoduResult<(Self, usize)> {
+        let _stream = self.device.enter_stream();
         ops_indexing::call_nonzero(self, layout)
     }
 
     fn call_unique(&self, layout: &Layout) -> HoduResult<(Self, Self, Self, usize)> {
+        let _stream = self.device.enter_stream();
         ops_indexing::call_unique(self, layout)
     }
 
@@ -595,10 +750,12 @@ impl BackendStorageT for CudaStorage {
         condition_layout: &Layout,
         axis: Option<usize>,
     ) -> HoduResult<(Self, usize)> {
+        let _stream = self.device.enter_stream();
         ops_indexing::call_compress(self, layout, condition, condition_layout, axis)
     }
 
     fn to_dtype(&self, layout: &Layout, target_dtype: DType) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         if self.dtype() == target_dtype {
             return self.contiguous(layout);
         }
@@ -628,7 +785,7 @@ impl BackendStorageT for CudaStorage {
             }};
         }
 
-        match (&self.data, target_dtype) {
+        match (&*self.data, target_dtype) {
             (CudaStorageData::F32(input), DType::F16) => Ok(CudaStorage::new(
                 self.device_id(),
                 self.device.clone(),
@@ -679,6 +836,7 @@ impl BackendStorageT for CudaStorage {
     }
 
     fn contiguous(&self, layout: &Layout) -> HoduResult<Self> {
+        let _stream = self.device.enter_stream();
         if layout.is_contiguous() {
             return Ok(self.clone());
         }
@@ -708,7 +866,7 @@ impl BackendStorageT for CudaStorage {
             }};
         }
 
-        match &self.data {
+        match &*self.data {
             CudaStorageData::BOOL(input) => Ok(CudaStorage::new(
                 self.device_id(),
                 self.device.clone(),
@@ -49,7 +49,7 @@ pub fn call_ops_binary(
         }};
     }
 
-    match (&lhs_storage.data, &rhs_storage.data) {
+    match (&*lhs_storage.data, &*rhs_storage.data) {
         (CudaStorageData::BOOL(lhs), CudaStorageData::BOOL(rhs)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
@@ -185,7 +185,7 @@ pub fn call_ops_binary_logical(
     let device_id = lhs_storage.device_id();
     let device_arc = Arc::clone(&lhs_storage.device);
 
-    let output = match (&lhs_storage.data, &rhs_storage.data) {
+    let output = match (&*lhs_storage.data, &*rhs_storage.data) {
         (CudaStorageData::BOOL(lhs), CudaStorageData::BOOL(rhs)) => call_binary_logical!(lhs, rhs, bool),
         (CudaStorageData::F8E4M3(lhs), CudaStorageData::F8E4M3(rhs)) => call_binary_logical!(lhs, rhs, float8::F8E4M3),
         #[cfg(feature = "f8e5m2")]
@@ -261,7 +261,7 @@ pub fn call_ops_cmp(
     let device_id = lhs_storage.device_id();
     let device_arc = Arc::clone(&lhs_storage.device);
 
-    let output = match (&lhs_storage.data, &rhs_storage.data) {
+    let output = match (&*lhs_storage.data, &*rhs_storage.data) {
         (CudaStorageData::BOOL(lhs), CudaStorageData::BOOL(rhs)) => call_cmp!(lhs, rhs, bool),
         (CudaStorageData::F8E4M3(lhs), CudaStorageData::F8E4M3(rhs)) => call_cmp!(lhs, rhs, float8::F8E4M3),
         #[cfg(feature = "f8e5m2")]
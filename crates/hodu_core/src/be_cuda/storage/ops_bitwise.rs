@@ -55,7 +55,7 @@ pub fn call_ops_bitwise_binary(
     }
 
     // Bitwise operations only support integer types
-    match (&lhs_storage.data, &rhs_storage.data) {
+    match (&*lhs_storage.data, &*rhs_storage.data) {
         (CudaStorageData::U8(lhs), CudaStorageData::U8(rhs)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
@@ -147,7 +147,7 @@ pub fn call_ops_bitwise_unary(storage: &CudaStorage, layout: &Layout, op: Op) ->
     }
 
     // Bitwise operations only support integer types
-    match &storage.data {
+    match &*storage.data {
         CudaStorageData::U8(input) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
@@ -245,7 +245,7 @@ pub fn call_ops_bitwise_unary_scalar(
     }
 
     // Bitwise operations only support integer types
-    match &storage.data {
+    match &*storage.data {
         CudaStorageData::U8(input) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
@@ -102,8 +102,8 @@ pub fn call_ops_concat(
             // Download all storages to CPU and pack into single vector
             let mut packed_data = Vec::new();
             for storage in storages.iter() {
-                if let CudaStorageData::$variant(slice) = &storage.data {
-                    let stream = device.context().default_stream();
+                if let CudaStorageData::$variant(slice) = &*storage.data {
+                    let stream = device.stream();
                     let mut temp = vec![unsafe { core::mem::zeroed() }; slice.len()];
                     stream
                         .memcpy_dtoh(slice, &mut temp)
@@ -219,7 +219,7 @@ pub fn call_ops_split(
         }};
     }
 
-    match &storage.data {
+    match &*storage.data {
         CudaStorageData::F32(input) => impl_split!(input, f32, F32),
         CudaStorageData::I32(input) => impl_split!(input, i32, I32),
         CudaStorageData::U32(input) => impl_split!(input, u32, U32),
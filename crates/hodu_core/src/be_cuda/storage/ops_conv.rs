@@ -105,7 +105,32 @@ pub fn call_ops_conv(
         }};
     }
 
-    match (&input_storage.data, &weight_storage.data) {
+    // With `cudnn`, float conv2d goes through cuDNN (tiny convolutions still use the PTX kernels)
+    macro_rules! call_conv_float {
+        ($input:expr, $weight:expr, $ty:ty, $variant:ident, $cudnn:ident) => {{
+            #[cfg(feature = "cudnn")]
+            if conv_op == crate::ops::ConvOp::Conv2d {
+                let mut output: CudaSlice<$ty> = device.new_buffer(output_size)?;
+                kernels::$cudnn(
+                    kernel,
+                    device.kernels(),
+                    device.context(),
+                    $input,
+                    $weight,
+                    &mut output,
+                    &metadata,
+                )?;
+                return Ok(CudaStorage::new(
+                    device_id,
+                    Arc::clone(&device_arc),
+                    CudaStorageData::$variant(output),
+                ));
+            }
+            call_conv!($input, $weight, $ty, $variant)
+        }};
+    }
+
+    match (&*input_storage.data, &*weight_storage.data) {
         (CudaStorageData::BOOL(input), CudaStorageData::BOOL(weight)) => call_conv!(input, weight, bool, BOOL),
         (CudaStorageData::F8E4M3(input), CudaStorageData::F8E4M3(weight)) => {
             call_conv!(input, weight, float8::F8E4M3, F8E4M3)
@@ -114,11 +139,19 @@ pub fn call_ops_conv(
         (CudaStorageData::F8E5M2(input), CudaStorageData::F8E5M2(weight)) => {
             call_conv!(input, weight, float8::F8E5M2, F8E5M2)
         },
-        (CudaStorageData::BF16(input), CudaStorageData::BF16(weight)) => call_conv!(input, weight, half::bf16, BF16),
-        (CudaStorageData::F16(input), CudaStorageData::F16(weight)) => call_conv!(input, weight, half::f16, F16),
-        (CudaStorageData::F32(input), CudaStorageData::F32(weight)) => call_conv!(input, weight, f32, F32),
+        (CudaStorageData::BF16(input), CudaStorageData::BF16(weight)) => {
+            call_conv_float!(input, weight, half::bf16, BF16, call_ops_conv2d_cudnn_bf16)
+        },
+        (CudaStorageData::F16(input), CudaStorageData::F16(weight)) => {
+            call_conv_float!(input, weight, half::f16, F16, call_ops_conv2d_cudnn_f16)
+        },
+        (CudaStorageData::F32(input), CudaStorageData::F32(weight)) => {
+            call_conv_float!(input, weight, f32, F32, call_ops_conv2d_cudnn_f32)
+        },
         #[cfg(feature = "f64")]
-        (CudaStorageData::F64(input), CudaStorageData::F64(weight)) => call_conv!(input, weight, f64, F64),
+        (CudaStorageData::F64(input), CudaStorageData::F64(weight)) => {
+            call_conv_float!(input, weight, f64, F64, call_ops_conv2d_cudnn_f64)
+        },
         (CudaStorageData::U8(input), CudaStorageData::U8(weight)) => call_conv!(input, weight, u8, U8),
         #[cfg(feature = "u16")]
         (CudaStorageData::U16(input), CudaStorageData::U16(weight)) => call_conv!(input, weight, u16, U16),
@@ -200,7 +233,7 @@ pub fn call_ops_conv_grad_weight(
         }};
     }
 
-    match (&input_storage.data, &grad_output_storage.data) {
+    match (&*input_storage.data, &*grad_output_storage.data) {
         (CudaStorageData::BOOL(input), CudaStorageData::BOOL(grad_output)) => {
             call_conv_grad_weight!(input, grad_output, bool, BOOL)
         },
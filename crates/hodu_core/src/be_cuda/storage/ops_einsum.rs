@@ -35,7 +35,7 @@ pub fn call_ops_einsum(
         ($variant:ident, $ty:ty) => {{
             let input_slices: Vec<&CudaSlice<$ty>> = std::iter::once(storage)
                 .chain(inputs.iter().cloned())
-                .map(|s| match &s.data {
+                .map(|s| match &*s.data {
                     CudaStorageData::$variant(data) => data,
                     _ => unreachable!(),
                 })
@@ -54,7 +54,7 @@ pub fn call_ops_einsum(
         }};
     }
 
-    match &storage.data {
+    match &*storage.data {
         CudaStorageData::F8E4M3(_) => Ok(CudaStorage::new(
             device_id,
             device_arc,
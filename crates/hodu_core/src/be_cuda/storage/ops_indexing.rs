@@ -66,7 +66,7 @@ pub fn call_ops_index_select(
     let kernel = kernels::Kernel(kernel_name_static);
 
     // Extract indices
-    let indices = match &indices_storage.data {
+    let indices = match &*indices_storage.data {
         CudaStorageData::I32(data) => data,
         _ => unreachable!(),
     };
@@ -91,7 +91,7 @@ pub fn call_ops_index_select(
         }};
     }
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::BOOL(input) => call_kernel!(input, bool, BOOL),
         CudaStorageData::F8E4M3(input) => call_kernel!(input, float8::F8E4M3, F8E4M3),
         #[cfg(feature = "f8e5m2")]
@@ -171,7 +171,7 @@ pub fn call_ops_index_put(
     let kernel = kernels::Kernel(kernel_name_static);
 
     // Extract indices
-    let indices = match &indices_storage.data {
+    let indices = match &*indices_storage.data {
         CudaStorageData::I32(data) => data,
         _ => unreachable!(),
     };
@@ -179,7 +179,7 @@ pub fn call_ops_index_put(
     macro_rules! call_kernel {
         ($input:expr, $values:expr, $ty:ty, $variant:ident) => {{
             // Copy input to output first
-            let stream = device.context().default_stream();
+            let stream = device.stream();
             let mut temp = vec![unsafe { core::mem::zeroed() }; $input.len()];
             stream
                 .memcpy_dtoh($input, &mut temp)
@@ -205,7 +205,7 @@ pub fn call_ops_index_put(
         }};
     }
 
-    match (&input_storage.data, &values_storage.data) {
+    match (&*input_storage.data, &*values_storage.data) {
         (CudaStorageData::BOOL(input), CudaStorageData::BOOL(values)) => call_kernel!(input, values, bool, BOOL),
         (CudaStorageData::F8E4M3(input), CudaStorageData::F8E4M3(values)) => {
             call_kernel!(input, values, float8::F8E4M3, F8E4M3)
@@ -276,7 +276,7 @@ pub fn call_ops_gather(
     let kernel = kernels::Kernel(kernel_name_static);
 
     // Extract indices
-    let indices = match &indices_storage.data {
+    let indices = match &*indices_storage.data {
         CudaStorageData::I32(data) => data,
         _ => unreachable!(),
     };
@@ -301,7 +301,7 @@ pub fn call_ops_gather(
         }};
     }
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::BOOL(input) => call_kernel!(input, bool, BOOL),
         CudaStorageData::F8E4M3(input) => call_kernel!(input, float8::F8E4M3, F8E4M3),
         #[cfg(feature = "f8e5m2")]
@@ -381,7 +381,7 @@ pub fn call_ops_scatter(
     let kernel = kernels::Kernel(kernel_name_static);
 
     // Extract indices
-    let indices = match &indices_storage.data {
+    let indices = match &*indices_storage.data {
         CudaStorageData::I32(data) => data,
         _ => unreachable!(),
     };
@@ -389,7 +389,7 @@ pub fn call_ops_scatter(
     macro_rules! call_kernel {
         ($input:expr, $src:expr, $ty:ty, $variant:ident) => {{
             // Copy input to output first
-            let stream = device.context().default_stream();
+            let stream = device.stream();
             let mut temp = vec![unsafe { core::mem::zeroed() }; $input.len()];
             stream
                 .memcpy_dtoh($input, &mut temp)
@@ -415,7 +415,7 @@ pub fn call_ops_scatter(
         }};
     }
 
-    match (&input_storage.data, &src_storage.data) {
+    match (&*input_storage.data, &*src_storage.data) {
         (CudaStorageData::BOOL(input), CudaStorageData::BOOL(src)) => call_kernel!(input, src, bool, BOOL),
         (CudaStorageData::F8E4M3(input), CudaStorageData::F8E4M3(src)) => {
             call_kernel!(input, src, float8::F8E4M3, F8E4M3)
@@ -484,7 +484,7 @@ pub fn call_ops_onehot(
     let kernel = kernels::Kernel(kernel_name_static);
 
     // Extract indices
-    let indices = match &indices_storage.data {
+    let indices = match &*indices_storage.data {
         CudaStorageData::I32(data) => data,
         _ => unreachable!(),
     };
@@ -550,15 +550,9 @@ pub fn call_nonzero(input_storage: &CudaStorage, input_layout: &Layout) -> HoduR
     // Create count buffer (single u32, initialized to 0)
     let mut count_buffer = device.new_buffer::<u32>(1)?;
     device
-        .context()
-        .default_stream()
+        .stream()
         .memcpy_stod(&[0u32])
-        .and_then(|zeroed| {
-            device
-                .context()
-                .default_stream()
-                .memcpy_dtod(&zeroed, &mut count_buffer)
-        })
+        .and_then(|zeroed| device.stream().memcpy_dtod(&zeroed, &mut count_buffer))
         .map_err(|e| HoduError::BackendError(format!("Failed to initialize count buffer: {:?}", e)))?;
 
     // First pass: count non-zero elements
@@ -579,7 +573,7 @@ pub fn call_nonzero(input_storage: &CudaStorage, input_layout: &Layout) -> HoduR
         }};
     }
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::BOOL(input) => call_count_kernel!(input),
         CudaStorageData::F8E4M3(input) => call_count_kernel!(input),
         #[cfg(feature = "f8e5m2")]
@@ -605,15 +599,13 @@ pub fn call_nonzero(input_storage: &CudaStorage, input_layout: &Layout) -> HoduR
 
     // Synchronize and read count
     device
-        .context()
-        .default_stream()
+        .stream()
         .synchronize()
         .map_err(|e| HoduError::BackendError(format!("CUDA synchronize failed: {:?}", e)))?;
 
     let mut count_host = [0u32];
     device
-        .context()
-        .default_stream()
+        .stream()
         .memcpy_dtoh(&count_buffer, &mut count_host)
         .map_err(|e| HoduError::BackendError(format!("Failed to read count: {:?}", e)))?;
     let count = count_host[0] as usize;
@@ -631,15 +623,9 @@ pub fn call_nonzero(input_storage: &CudaStorage, input_layout: &Layout) -> HoduR
     // Create counter buffer for fill (initialized to 0)
     let mut counter_buffer = device.new_buffer::<u32>(1)?;
     device
-        .context()
-        .default_stream()
+        .stream()
         .memcpy_stod(&[0u32])
-        .and_then(|zeroed| {
-            device
-                .context()
-                .default_stream()
-                .memcpy_dtod(&zeroed, &mut counter_buffer)
-        })
+        .and_then(|zeroed| device.stream().memcpy_dtod(&zeroed, &mut counter_buffer))
         .map_err(|e| HoduError::BackendError(format!("Failed to initialize counter buffer: {:?}", e)))?;
 
     // Second pass: fill indices
@@ -661,7 +647,7 @@ pub fn call_nonzero(input_storage: &CudaStorage, input_layout: &Layout) -> HoduR
         }};
     }
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::BOOL(input) => call_fill_kernel!(input),
         CudaStorageData::F8E4M3(input) => call_fill_kernel!(input),
         #[cfg(feature = "f8e5m2")]
@@ -705,7 +691,7 @@ pub fn call_unique(
     let kernels = input_storage.kernels();
 
     if num_els == 0 {
-        let stream = context.default_stream();
+        let stream = hodu_cuda_kernels::stream::current_stream(context);
         let empty_values: CudaSlice<u8> = stream.alloc_zeros(0).map_err(cuda_err)?;
         let empty_inverse: CudaSlice<i32> = stream.alloc_zeros(0).map_err(cuda_err)?;
         let empty_counts: CudaSlice<i32> = stream.alloc_zeros(0).map_err(cuda_err)?;
@@ -721,7 +707,7 @@ pub fn call_unique(
         ));
     }
 
-    let stream = context.default_stream();
+    let stream = hodu_cuda_kernels::stream::current_stream(context);
 
     // Generate metadata using common function
     let metadata = crate::op_metadatas::unique_sort_metadata(input_layout);
@@ -836,7 +822,7 @@ pub fn call_unique(
         }};
     }
 
-    let result = match &input_storage.data {
+    let result = match &*input_storage.data {
         CudaStorageData::I8(input) => call_unique_kernel!(
             input,
             I8,
@@ -985,22 +971,20 @@ pub fn call_compress(
     let condition_size = condition_layout.size();
 
     // Read condition from device to count true values
-    let condition_buf = match &condition_storage.data {
+    let condition_buf = match &*condition_storage.data {
         CudaStorageData::BOOL(data) => data,
         _ => unreachable!(),
     };
 
     // Synchronize and read condition to host
     device
-        .context()
-        .default_stream()
+        .stream()
         .synchronize()
         .map_err(|e| HoduError::BackendError(format!("CUDA synchronize failed: {:?}", e)))?;
 
     let mut condition_host = vec![false; condition_size];
     device
-        .context()
-        .default_stream()
+        .stream()
         .memcpy_dtoh(condition_buf, &mut condition_host)
         .map_err(|e| HoduError::BackendError(format!("Failed to read condition: {:?}", e)))?;
 
@@ -1043,15 +1027,9 @@ pub fn call_compress(
     // Create counter buffer (single u32, initialized to 0)
     let mut counter_buffer = device.new_buffer::<u32>(1)?;
     device
-        .context()
-        .default_stream()
+        .stream()
         .memcpy_stod(&[0u32])
-        .and_then(|zeroed| {
-            device
-                .context()
-                .default_stream()
-                .memcpy_dtod(&zeroed, &mut counter_buffer)
-        })
+        .and_then(|zeroed| device.stream().memcpy_dtod(&zeroed, &mut counter_buffer))
         .map_err(|e| HoduError::BackendError(format!("Failed to initialize counter buffer: {:?}", e)))?;
 
     macro_rules! call_compress_kernel {
@@ -1074,7 +1052,7 @@ pub fn call_compress(
         }};
     }
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::BOOL(input) => call_compress_kernel!(input, bool, BOOL),
         CudaStorageData::F8E4M3(input) => call_compress_kernel!(input, float8::F8E4M3, F8E4M3),
         #[cfg(feature = "f8e5m2")]
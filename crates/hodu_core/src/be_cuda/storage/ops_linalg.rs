@@ -61,7 +61,7 @@ pub fn call_ops_det(storage: &CudaStorage, layout: &Layout) -> HoduResult<CudaSt
     let device_id = storage.device_id();
     let device_arc = Arc::clone(&storage.device);
 
-    match &storage.data {
+    match &*storage.data {
         CudaStorageData::F32(input) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
@@ -143,7 +143,7 @@ pub fn call_ops_inv(storage: &CudaStorage, layout: &Layout) -> HoduResult<CudaSt
     let device_id = storage.device_id();
     let device_arc = Arc::clone(&storage.device);
 
-    match &storage.data {
+    match &*storage.data {
         CudaStorageData::F32(input) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
@@ -226,7 +226,7 @@ pub fn call_ops_trace(storage: &CudaStorage, layout: &Layout) -> HoduResult<Cuda
     let device_id = storage.device_id();
     let device_arc = Arc::clone(&storage.device);
 
-    match &storage.data {
+    match &*storage.data {
         CudaStorageData::F32(input) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
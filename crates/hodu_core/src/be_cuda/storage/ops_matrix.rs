@@ -44,9 +44,21 @@ pub fn call_ops_matmul(
     let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
     let kernel = kernels::Kernel(kernel_name_static);
 
+    // With `cublas`, float types go through cuBLAS (tiny shapes still use the PTX kernels)
     macro_rules! call_matmul {
-        ($lhs:expr, $rhs:expr, $ty:ty) => {{
+        ($lhs:expr, $rhs:expr, $ty:ty, $cublas:ident) => {{
             let mut output: CudaSlice<$ty> = device.new_buffer(output_size as usize)?;
+            #[cfg(feature = "cublas")]
+            kernels::$cublas(
+                kernel,
+                device.kernels(),
+                device.context(),
+                $lhs,
+                $rhs,
+                &mut output,
+                &metadata,
+            )?;
+            #[cfg(not(feature = "cublas"))]
             kernels::call_ops_matmul(
                 kernel,
                 device.kernels(),
@@ -63,27 +75,27 @@ pub fn call_ops_matmul(
     let device_id = lhs_storage.device_id();
     let device_arc = Arc::clone(&lhs_storage.device);
 
-    match (&lhs_storage.data, &rhs_storage.data) {
+    match (&*lhs_storage.data, &*rhs_storage.data) {
         (CudaStorageData::F32(lhs), CudaStorageData::F32(rhs)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
-            CudaStorageData::F32(call_matmul!(lhs, rhs, f32)),
+            CudaStorageData::F32(call_matmul!(lhs, rhs, f32, call_ops_matmul_cublas_f32)),
         )),
         #[cfg(feature = "f64")]
         (CudaStorageData::F64(lhs), CudaStorageData::F64(rhs)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
-            CudaStorageData::F64(call_matmul!(lhs, rhs, f64)),
+            CudaStorageData::F64(call_matmul!(lhs, rhs, f64, call_ops_matmul_cublas_f64)),
         )),
         (CudaStorageData::F16(lhs), CudaStorageData::F16(rhs)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
-            CudaStorageData::F16(call_matmul!(lhs, rhs, half::f16)),
+            CudaStorageData::F16(call_matmul!(lhs, rhs, half::f16, call_ops_matmul_cublas_f16)),
         )),
         (CudaStorageData::BF16(lhs), CudaStorageData::BF16(rhs)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
-            CudaStorageData::BF16(call_matmul!(lhs, rhs, half::bf16)),
+            CudaStorageData::BF16(call_matmul!(lhs, rhs, half::bf16, call_ops_matmul_cublas_bf16)),
         )),
         _ => Err(HoduError::DTypeMismatch {
             expected: lhs_storage.dtype(),
@@ -120,9 +132,21 @@ pub fn call_ops_dot(
     let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
     let kernel = kernels::Kernel(kernel_name_static);
 
+    // With `cublas`, float types go through cuBLAS (tiny shapes still use the PTX kernels)
     macro_rules! call_dot {
-        ($lhs:expr, $rhs:expr, $ty:ty) => {{
+        ($lhs:expr, $rhs:expr, $ty:ty, $cublas:ident) => {{
             let mut output: CudaSlice<$ty> = device.new_buffer(output_size)?;
+            #[cfg(feature = "cublas")]
+            kernels::$cublas(
+                kernel,
+                device.kernels(),
+                device.context(),
+                $lhs,
+                $rhs,
+                &mut output,
+                &metadata,
+            )?;
+            #[cfg(not(feature = "cublas"))]
             kernels::call_ops_dot(
                 kernel,
                 device.kernels(),
@@ -139,27 +163,27 @@ pub fn call_ops_dot(
     let device_id = lhs_storage.device_id();
     let device_arc = Arc::clone(&lhs_storage.device);
 
-    match (&lhs_storage.data, &rhs_storage.data) {
+    match (&*lhs_storage.data, &*rhs_storage.data) {
         (CudaStorageData::F32(lhs), CudaStorageData::F32(rhs)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
-            CudaStorageData::F32(call_dot!(lhs, rhs, f32)),
+            CudaStorageData::F32(call_dot!(lhs, rhs, f32, call_ops_dot_cublas_f32)),
         )),
         #[cfg(feature = "f64")]
         (CudaStorageData::F64(lhs), CudaStorageData::F64(rhs)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
-            CudaStorageData::F64(call_dot!(lhs, rhs, f64)),
+            CudaStorageData::F64(call_dot!(lhs, rhs, f64, call_ops_dot_cublas_f64)),
         )),
         (CudaStorageData::F16(lhs), CudaStorageData::F16(rhs)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
-            CudaStorageData::F16(call_dot!(lhs, rhs, half::f16)),
+            CudaStorageData::F16(call_dot!(lhs, rhs, half::f16, call_ops_dot_cublas_f16)),
         )),
         (CudaStorageData::BF16(lhs), CudaStorageData::BF16(rhs)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
-            CudaStorageData::BF16(call_dot!(lhs, rhs, half::bf16)),
+            CudaStorageData::BF16(call_dot!(lhs, rhs, half::bf16, call_ops_dot_cublas_bf16)),
         )),
         _ => Err(HoduError::DTypeMismatch {
             expected: lhs_storage.dtype(),
@@ -66,7 +66,10 @@ pub fn call_ops_pad(
                 ($input:expr, $ty:ty, $pv:expr) => {{
                     let mut output: CudaSlice<$ty> = device.new_buffer(output_size)?;
                     let mut pv_buf: CudaSlice<$ty> = device.new_buffer(1)?;
-                    device.context().stream().memcpy_stod(&[$pv], &mut pv_buf)?;
+                    device
+                        .stream()
+                        .memcpy_htod(&[$pv], &mut pv_buf)
+                        .map_err(|e| HoduError::BackendError(format!("CUDA memcpy_htod failed: {:?}", e)))?;
                     kernels::call_ops_pad_constant(
                         kernel,
                         device.kernels(),
@@ -80,7 +83,7 @@ pub fn call_ops_pad(
                 }};
             }
 
-            match &input_storage.data {
+            match &*input_storage.data {
                 CudaStorageData::BOOL(input) => {
                     let pv = pad_value.to_bool();
                     Ok(CudaStorage::new(
@@ -227,7 +230,7 @@ pub fn call_ops_pad(
 
             macro_rules! dispatch_pad_other {
                 ($call_fn:ident) => {
-                    match &input_storage.data {
+                    match &*input_storage.data {
                         CudaStorageData::BOOL(input) => Ok(CudaStorage::new(
                             device_id,
                             device_arc,
@@ -0,0 +1,321 @@
+use crate::{
+    be::storage::BackendStorageT,
+    be_cuda::storage::{CudaStorage, CudaStorageData},
+    error::{HoduError, HoduResult},
+    ops::{Op, QuantOp},
+    types::{BlockFormat, DType, Layout},
+};
+use hodu_cuda_kernels::{cuda::CudaSlice, kernels};
+use std::sync::Arc;
+
+pub fn call_ops_quantize(
+    storage: &CudaStorage,
+    layout: &Layout,
+    scale: f32,
+    zero_point: i32,
+) -> HoduResult<CudaStorage> {
+    let metadata = crate::op_metadatas::cast_metadata(layout);
+    let num_els = layout.size();
+
+    let dtype = storage.dtype();
+    let device = storage.get_device();
+
+    let kernel_name = format!("hodu_cuda_quantize_{}", dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = kernels::Kernel(kernel_name_static);
+
+    macro_rules! call_quantize {
+        ($input:expr) => {{
+            let mut output: CudaSlice<i8> = device.new_buffer(num_els)?;
+            kernels::call_ops_quantize(
+                kernel,
+                device.kernels(),
+                device.context(),
+                $input,
+                &mut output,
+                &metadata,
+                scale,
+                zero_point,
+            )?;
+            output
+        }};
+    }
+
+    let output = match &*storage.data {
+        CudaStorageData::BF16(input) => call_quantize!(input),
+        CudaStorageData::F16(input) => call_quantize!(input),
+        CudaStorageData::F32(input) => call_quantize!(input),
+        #[cfg(feature = "f64")]
+        CudaStorageData::F64(input) => call_quantize!(input),
+        _ => {
+            return Err(HoduError::UnsupportedDTypeForOp {
+                dtype,
+                op: Op::Quant(QuantOp::Quantize),
+            })
+        },
+    };
+
+    Ok(CudaStorage::new(
+        storage.device_id(),
+        Arc::clone(&storage.device),
+        CudaStorageData::I8(output),
+    ))
+}
+
+pub fn call_ops_dequantize(
+    storage: &CudaStorage,
+    layout: &Layout,
+    scale: f32,
+    zero_point: i32,
+    dtype: DType,
+) -> HoduResult<CudaStorage> {
+    let op = Op::Quant(QuantOp::Dequantize);
+    let CudaStorageData::I8(input) = &*storage.data else {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype: storage.dtype(),
+            op,
+        });
+    };
+
+    let metadata = crate::op_metadatas::cast_metadata(layout);
+    let num_els = layout.size();
+
+    let device = storage.get_device();
+
+    let kernel_name = format!("hodu_cuda_dequantize_{}", dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = kernels::Kernel(kernel_name_static);
+
+    macro_rules! call_dequantize {
+        ($ty:ty) => {{
+            let mut output: CudaSlice<$ty> = device.new_buffer(num_els)?;
+            kernels::call_ops_dequantize(
+                kernel,
+                device.kernels(),
+                device.context(),
+                input,
+                &mut output,
+                &metadata,
+                scale,
+                zero_point,
+            )?;
+            output
+        }};
+    }
+
+    let data = match dtype {
+        DType::BF16 => CudaStorageData::BF16(call_dequantize!(half::bf16)),
+        DType::F16 => CudaStorageData::F16(call_dequantize!(half::f16)),
+        DType::F32 => CudaStorageData::F32(call_dequantize!(f32)),
+        #[cfg(feature = "f64")]
+        DType::F64 => CudaStorageData::F64(call_dequantize!(f64)),
+        _ => return Err(HoduError::UnsupportedDTypeForOp { dtype, op }),
+    };
+
+    Ok(CudaStorage::new(storage.device_id(), Arc::clone(&storage.device), data))
+}
+
+pub fn call_ops_matmul_int8(
+    lhs_storage: &CudaStorage,
+    rhs_storage: &CudaStorage,
+    lhs_layout: &Layout,
+    rhs_layout: &Layout,
+) -> HoduResult<CudaStorage> {
+    let (CudaStorageData::I8(lhs), CudaStorageData::I8(rhs)) = (&*lhs_storage.data, &*rhs_storage.data) else {
+        let dtype = if lhs_storage.dtype() != DType::I8 {
+            lhs_storage.dtype()
+        } else {
+            rhs_storage.dtype()
+        };
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype,
+            op: Op::Quant(QuantOp::MatmulInt8),
+        });
+    };
+
+    let output_shape = crate::op_metadatas::matmul_output_shape(lhs_layout, rhs_layout)?;
+    let output_layout = Layout::from_shape(&output_shape);
+    let metadata = crate::op_metadatas::matmul_metadata(lhs_layout, rhs_layout, &output_layout)?;
+
+    let device = lhs_storage.get_device();
+
+    let mut output: CudaSlice<i32> = device.new_buffer(output_shape.size())?;
+    kernels::call_ops_matmul_int8(
+        kernels::matmul_int8::I8,
+        device.kernels(),
+        device.context(),
+        lhs,
+        rhs,
+        &mut output,
+        &metadata,
+    )?;
+
+    Ok(CudaStorage::new(
+        lhs_storage.device_id(),
+        Arc::clone(&lhs_storage.device),
+        CudaStorageData::I32(output),
+    ))
+}
+
+pub fn call_ops_block_quantize(storage: &CudaStorage, layout: &Layout, format: BlockFormat) -> HoduResult<CudaStorage> {
+    let dtype = storage.dtype();
+    let last_dim = layout.shape().dims().last().copied().unwrap_or(0);
+    format.packed_dim(last_dim)?;
+
+    let metadata = crate::op_metadatas::cast_metadata(layout);
+    let num_bytes = layout.size() / format.block_size() * format.block_bytes();
+
+    let device = storage.get_device();
+
+    let kernel_name = format!("hodu_cuda_quantize_{}_{}", format, dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = kernels::Kernel(kernel_name_static);
+
+    macro_rules! call_block_quantize {
+        ($input:expr) => {{
+            let mut output: CudaSlice<u8> = device.new_buffer(num_bytes)?;
+            match format {
+                BlockFormat::Q4_0 => kernels::call_ops_quantize_q4_0(
+                    kernel,
+                    device.kernels(),
+                    device.context(),
+                    $input,
+                    &mut output,
+                    &metadata,
+                )?,
+            }
+            output
+        }};
+    }
+
+    let output = match &*storage.data {
+        CudaStorageData::BF16(input) => call_block_quantize!(input),
+        CudaStorageData::F16(input) => call_block_quantize!(input),
+        CudaStorageData::F32(input) => call_block_quantize!(input),
+        #[cfg(feature = "f64")]
+        CudaStorageData::F64(input) => call_block_quantize!(input),
+        _ => {
+            return Err(HoduError::UnsupportedDTypeForOp {
+                dtype,
+                op: Op::Quant(QuantOp::BlockQuantize),
+            })
+        },
+    };
+
+    Ok(CudaStorage::new(
+        storage.device_id(),
+        Arc::clone(&storage.device),
+        CudaStorageData::U8(output),
+    ))
+}
+
+pub fn call_ops_block_dequantize(
+    storage: &CudaStorage,
+    layout: &Layout,
+    format: BlockFormat,
+    dtype: DType,
+) -> HoduResult<CudaStorage> {
+    let op = Op::Quant(QuantOp::BlockDequantize);
+    let CudaStorageData::U8(input) = &*storage.data else {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype: storage.dtype(),
+            op,
+        });
+    };
+
+    let metadata = crate::op_metadatas::block_dequantize_metadata(layout, format)?;
+    let num_els = metadata[0] * format.block_size();
+
+    let device = storage.get_device();
+
+    let kernel_name = format!("hodu_cuda_dequantize_{}_{}", format, dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = kernels::Kernel(kernel_name_static);
+
+    macro_rules! call_block_dequantize {
+        ($ty:ty) => {{
+            let mut output: CudaSlice<$ty> = device.new_buffer(num_els)?;
+            match format {
+                BlockFormat::Q4_0 => kernels::call_ops_dequantize_q4_0(
+                    kernel,
+                    device.kernels(),
+                    device.context(),
+                    input,
+                    &mut output,
+                    &metadata,
+                )?,
+            }
+            output
+        }};
+    }
+
+    let data = match dtype {
+        DType::BF16 => CudaStorageData::BF16(call_block_dequantize!(half::bf16)),
+        DType::F16 => CudaStorageData::F16(call_block_dequantize!(half::f16)),
+        DType::F32 => CudaStorageData::F32(call_block_dequantize!(f32)),
+        #[cfg(feature = "f64")]
+        DType::F64 => CudaStorageData::F64(call_block_dequantize!(f64)),
+        _ => return Err(HoduError::UnsupportedDTypeForOp { dtype, op }),
+    };
+
+    Ok(CudaStorage::new(storage.device_id(), Arc::clone(&storage.device), data))
+}
+
+pub fn call_ops_block_matmul(
+    lhs_storage: &CudaStorage,
+    weight_storage: &CudaStorage,
+    lhs_layout: &Layout,
+    weight_layout: &Layout,
+    format: BlockFormat,
+) -> HoduResult<CudaStorage> {
+    let op = Op::Quant(QuantOp::BlockMatmul);
+    let CudaStorageData::U8(weight) = &*weight_storage.data else {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype: weight_storage.dtype(),
+            op,
+        });
+    };
+
+    let metadata = crate::op_metadatas::block_matmul_metadata(lhs_layout, weight_layout, format)?;
+    let num_els = metadata[0] * metadata[2];
+
+    let dtype = lhs_storage.dtype();
+    let device = lhs_storage.get_device();
+
+    let kernel_name = format!("hodu_cuda_matmul_{}_{}", format, dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = kernels::Kernel(kernel_name_static);
+
+    macro_rules! call_block_matmul {
+        ($lhs:expr, $ty:ty) => {{
+            let mut output: CudaSlice<$ty> = device.new_buffer(num_els)?;
+            match format {
+                BlockFormat::Q4_0 => kernels::call_ops_matmul_q4_0(
+                    kernel,
+                    device.kernels(),
+                    device.context(),
+                    $lhs,
+                    weight,
+                    &mut output,
+                    &metadata,
+                )?,
+            }
+            output
+        }};
+    }
+
+    let data = match &*lhs_storage.data {
+        CudaStorageData::BF16(lhs) => CudaStorageData::BF16(call_block_matmul!(lhs, half::bf16)),
+        CudaStorageData::F16(lhs) => CudaStorageData::F16(call_block_matmul!(lhs, half::f16)),
+        CudaStorageData::F32(lhs) => CudaStorageData::F32(call_block_matmul!(lhs, f32)),
+        #[cfg(feature = "f64")]
+        CudaStorageData::F64(lhs) => CudaStorageData::F64(call_block_matmul!(lhs, f64)),
+        _ => return Err(HoduError::UnsupportedDTypeForOp { dtype, op }),
+    };
+
+    Ok(CudaStorage::new(
+        lhs_storage.device_id(),
+        Arc::clone(&lhs_storage.device),
+        data,
+    ))
+}
@@ -51,7 +51,7 @@ pub fn call_ops_reduce(
 
     let output_size: u32 = output_shape_vec.iter().map(|&x| x as u32).product();
 
-    let metadata = crate::op_metadatas::reduce_metadata(layout, dims, keep_dim);
+    let metadata = crate::op_metadatas::reduce_metadata(layout, dims);
 
     let dtype = input_storage.dtype();
     let device = input_storage.get_device();
@@ -77,7 +77,7 @@ pub fn call_ops_reduce(
     let device_id = input_storage.device_id;
     let device_arc = Arc::clone(&input_storage.device);
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::F32(input) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
@@ -69,7 +69,7 @@ pub fn call_ops_resize(
         }};
     }
 
-    let output_data = match &input_storage.data {
+    let output_data = match &*input_storage.data {
         CudaStorageData::F8E4M3(input) => CudaStorageData::F8E4M3(call_resize!(input, float8::F8E4M3)),
         #[cfg(feature = "f8e5m2")]
         CudaStorageData::F8E5M2(input) => CudaStorageData::F8E5M2(call_resize!(input, float8::F8E5M2)),
@@ -85,9 +85,5 @@ pub fn call_ops_resize(
         },
     };
 
-    Ok(CudaStorage {
-        data: output_data,
-        device: device_arc,
-        device_id,
-    })
+    Ok(CudaStorage::new(device_id, device_arc, output_data))
 }
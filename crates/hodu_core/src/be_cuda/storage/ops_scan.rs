@@ -37,7 +37,7 @@ pub fn call_ops_cumsum(input_storage: &CudaStorage, input_layout: &Layout, dim:
         }};
     }
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::F8E4M3(input) => Ok(CudaStorage::new(
             device_id,
             device_arc,
@@ -148,7 +148,7 @@ pub fn call_ops_cumprod(input_storage: &CudaStorage, input_layout: &Layout, dim:
         }};
     }
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::F8E4M3(input) => Ok(CudaStorage::new(
             device_id,
             device_arc,
@@ -37,7 +37,7 @@ pub fn call_ops_flip(input_storage: &CudaStorage, input_layout: &Layout, dims: &
         }};
     }
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::BOOL(input) => Ok(CudaStorage::new(
             device_id,
             device_arc,
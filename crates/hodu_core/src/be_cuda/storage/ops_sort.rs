@@ -54,7 +54,7 @@ pub fn call_topk(
         }};
     }
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::F8E4M3(input) => Ok(call_topk!(input, float8::F8E4M3, F8E4M3)),
         #[cfg(feature = "f8e5m2")]
         CudaStorageData::F8E5M2(input) => Ok(call_topk!(input, float8::F8E5M2, F8E5M2)),
@@ -43,7 +43,7 @@ pub fn call_ops_unary(input_storage: &CudaStorage, layout: &Layout, op: Op) -> H
         }};
     }
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::BOOL(input) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
@@ -168,7 +168,7 @@ pub fn call_ops_unary_logical(input_storage: &CudaStorage, layout: &Layout, op:
     let device_id = input_storage.device_id();
     let device_arc = Arc::clone(&input_storage.device);
 
-    let output = match &input_storage.data {
+    let output = match &*input_storage.data {
         CudaStorageData::BOOL(input) => call_unary_logical!(input, bool),
         CudaStorageData::F32(input) => call_unary_logical!(input, f32),
         CudaStorageData::I32(input) => call_unary_logical!(input, i32),
@@ -229,7 +229,7 @@ pub fn call_ops_unary_scalar(
         }};
     }
 
-    match (&input_storage.data, scalar) {
+    match (&*input_storage.data, scalar) {
         (CudaStorageData::F32(input), Scalar::F32(v)) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
@@ -299,7 +299,7 @@ pub fn call_ops_cmp_scalar(
     let device_id = input_storage.device_id();
     let device_arc = Arc::clone(&input_storage.device);
 
-    let output = match (&input_storage.data, scalar) {
+    let output = match (&*input_storage.data, scalar) {
         (CudaStorageData::BOOL(input), Scalar::BOOL(v)) => call_cmp_scalar!(input, v, bool),
         (CudaStorageData::F8E4M3(input), Scalar::F8E4M3(v)) => call_cmp_scalar!(input, v, float8::F8E4M3),
         #[cfg(feature = "f8e5m2")]
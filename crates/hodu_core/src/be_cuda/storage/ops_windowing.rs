@@ -104,7 +104,7 @@ pub fn call_ops_reduce_window(
     let device_id = input_storage.device_id;
     let device_arc = Arc::clone(&input_storage.device);
 
-    match &input_storage.data {
+    match &*input_storage.data {
         CudaStorageData::F32(input) => Ok(CudaStorage::new(
             device_id,
             Arc::clone(&device_arc),
@@ -3,11 +3,12 @@ use crate::{
     be_cpu::storage::CpuStorage,
     be_metal::storage::MetalStorage,
     error::{HoduError, HoduResult},
-    types::DType,
+    types::{get_precision, DType, MemoryStats},
 };
 use hodu_metal_kernels::{
     kernel::Kernels,
     metal::{Buffer, BufferMap, CommandBuffer, Commands, Device},
+    precision::{Precision, PrecisionGuard},
     RESOURCE_OPTIONS,
 };
 use std::sync::{Arc, LazyLock, RwLock};
@@ -30,6 +31,7 @@ pub struct MetalDevice {
     pub(crate) commands: Arc<RwLock<Commands>>,
     pub(crate) buffers: Arc<RwLock<BufferMap>>,
     pub(crate) kernels: Arc<Kernels>,
+    pub(crate) stats: Arc<RwLock<MemoryStats>>,
 }
 
 // Global singleton Metal device
@@ -45,6 +47,7 @@ static METAL_DEVICE: LazyLock<MetalDevice> = LazyLock::new(|| {
         commands: Arc::new(RwLock::new(commands)),
         buffers: Arc::new(RwLock::new(BufferMap::default())),
         kernels: Arc::new(kernels),
+        stats: Arc::new(RwLock::new(MemoryStats::default())),
     }
 });
 
@@ -78,6 +81,45 @@ impl MetalDevice {
                 .collect();
             *subbuffers = newbuffers;
         }
+        buffers.retain(|_, subbuffers| !subbuffers.is_empty());
+        Ok(())
+    }
+
+    /// Make the calling thread's [`Precision`](crate::types::Precision) visible to
+    /// matmul and convolution kernels until the guard is dropped
+    pub fn enter_precision(&self) -> PrecisionGuard {
+        let precision = get_precision();
+        Precision {
+            allow_tf32: precision.allow_tf32,
+            f16_accumulate_f32: precision.f16_accumulate_f32,
+        }
+        .enter()
+    }
+
+    /// Free all cached buffers that are not used by a live tensor
+    pub fn empty_cache(&self) -> HoduResult<()> {
+        self.drop_unused_buffers()
+    }
+
+    pub fn memory_stats(&self) -> HoduResult<MemoryStats> {
+        let buffers = self.buffers.read()?;
+        let (allocated_bytes, cached_bytes) = buffer_usage(&buffers);
+        Ok(MemoryStats {
+            allocated_bytes,
+            cached_bytes,
+            ..*self.stats.read()?
+        })
+    }
+
+    fn record_allocation(&self, buffers: &BufferMap, cache_hit: bool) -> HoduResult<()> {
+        let mut stats = self.stats.write()?;
+        if cache_hit {
+            stats.num_cache_hits += 1;
+        } else {
+            stats.num_allocs += 1;
+        }
+        let (allocated_bytes, _) = buffer_usage(buffers);
+        stats.peak_allocated_bytes = stats.peak_allocated_bytes.max(allocated_bytes);
         Ok(())
     }
 
@@ -96,6 +138,15 @@ impl MetalDevice {
         Ok(())
     }
 
+    /// Like [`wait_until_completed`](Self::wait_until_completed), also returning the GPU time in
+    /// seconds of the command buffer that was waited on
+    pub fn wait_until_completed_timed(&self) -> HoduResult<Option<f64>> {
+        let mut commands = self.commands.write()?;
+        Ok(commands
+            .wait_until_completed()?
+            .map(|command_buffer| command_buffer.gpu_duration()))
+    }
+
     pub fn kernels(&self) -> &Kernels {
         &self.kernels
     }
@@ -120,6 +171,8 @@ impl MetalDevice {
 
         let new_buffer = Arc::new(new_buffer);
         subbuffers.push(new_buffer.clone());
+        self.record_allocation(&buffers, false)?;
+        crate::profiler::record_alloc(crate::types::Device::Metal, size, false);
         Ok(new_buffer)
     }
 
@@ -152,6 +205,8 @@ impl MetalDevice {
         let mut buffers = self.buffers.write()?;
         if let Some(b) = find_available_buffer(size, &buffers) {
             // Cloning also ensures we increment the strong count
+            self.record_allocation(&buffers, true)?;
+            crate::profiler::record_alloc(crate::types::Device::Metal, b.length(), true);
             return Ok(b.clone());
         }
         let size = buf_size(size);
@@ -160,10 +215,28 @@ impl MetalDevice {
         let new_buffer = self.device.new_buffer(size, RESOURCE_OPTIONS)?;
         let new_buffer = Arc::new(new_buffer);
         subbuffers.push(new_buffer.clone());
+        self.record_allocation(&buffers, false)?;
+        crate::profiler::record_alloc(crate::types::Device::Metal, size, false);
         Ok(new_buffer)
     }
 }
 
+/// Bytes of cached buffers that are in use and free, respectively
+fn buffer_usage(buffers: &BufferMap) -> (usize, usize) {
+    let mut in_use = 0;
+    let mut free = 0;
+    for (size, subbuffers) in buffers.iter() {
+        for sub in subbuffers {
+            if Arc::strong_count(sub) > 1 {
+                in_use += size;
+            } else {
+                free += size;
+            }
+        }
+    }
+    (in_use, free)
+}
+
 fn buf_size(size: usize) -> usize {
     size.saturating_sub(1).next_power_of_two()
 }
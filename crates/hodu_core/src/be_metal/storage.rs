@@ -7,6 +7,7 @@ mod ops_indexing;
 mod ops_linalg;
 mod ops_matrix;
 mod ops_padding;
+mod ops_quant;
 mod ops_reduce;
 mod ops_resize;
 mod ops_scan;
@@ -23,7 +24,7 @@ use crate::{
     op_metadatas,
     ops::Op,
     scalar::Scalar,
-    types::{DType, Device, Layout, Shape},
+    types::{BlockFormat, DType, Device, Layout, Shape},
 };
 use hodu_metal_kernels::{
     kernels::{call_const_set, call_ops_cast, call_ops_contiguous, const_set, contiguous, Kernel},
@@ -291,10 +292,12 @@ impl BackendStorageT for MetalStorage {
         rhs_layout: &Layout,
         op: Op,
     ) -> HoduResult<Self> {
+        let _precision = self.device.enter_precision();
         ops_matrix::call_ops_matmul(self, rhs_storage, lhs_layout, rhs_layout, op)
     }
 
     fn call_ops_dot(&self, rhs_storage: &Self, lhs_layout: &Layout, rhs_layout: &Layout, op: Op) -> HoduResult<Self> {
+        let _precision = self.device.enter_precision();
         ops_matrix::call_ops_dot(self, rhs_storage, lhs_layout, rhs_layout, op)
     }
 
@@ -310,8 +313,39 @@ impl BackendStorageT for MetalStorage {
         ops_linalg::call_ops_trace(self, layout)
     }
 
-    fn call_ops_reduce(&self, layout: &Layout, dims: &[usize], keep_dim: bool, op: Op) -> HoduResult<Self> {
-        ops_reduce::call_ops_reduce(self, layout, dims, keep_dim, op)
+    fn call_ops_quantize(&self, layout: &Layout, scale: f32, zero_point: i32) -> HoduResult<Self> {
+        ops_quant::call_ops_quantize(self, layout, scale, zero_point)
+    }
+
+    fn call_ops_dequantize(&self, layout: &Layout, scale: f32, zero_point: i32, dtype: DType) -> HoduResult<Self> {
+        ops_quant::call_ops_dequantize(self, layout, scale, zero_point, dtype)
+    }
+
+    fn call_ops_matmul_int8(&self, rhs_storage: &Self, lhs_layout: &Layout, rhs_layout: &Layout) -> HoduResult<Self> {
+        ops_quant::call_ops_matmul_int8(self, rhs_storage, lhs_layout, rhs_layout)
+    }
+
+    fn call_ops_block_quantize(&self, layout: &Layout, format: BlockFormat) -> HoduResult<Self> {
+        ops_quant::call_ops_block_quantize(self, layout, format)
+    }
+
+    fn call_ops_block_dequantize(&self, layout: &Layout, format: BlockFormat, dtype: DType) -> HoduResult<Self> {
+        ops_quant::call_ops_block_dequantize(self, layout, format, dtype)
+    }
+
+    fn call_ops_block_matmul(
+        &self,
+        weight_storage: &Self,
+        lhs_layout: &Layout,
+        weight_layout: &Layout,
+        format: BlockFormat,
+    ) -> HoduResult<Self> {
+        ops_quant::call_ops_block_matmul(self, weight_storage, lhs_layout, weight_layout, format)
+    }
+
+    fn call_ops_reduce(&self, layout: &Layout, dims: &[usize], _keep_dim: bool, op: Op) -> HoduResult<Self> {
+        // Reduction metadata describes the output in flat order, which keep_dim does not change
+        ops_reduce::call_ops_reduce(self, layout, dims, op)
     }
 
     fn call_ops_concat(&self, others: &[&Self], layouts: &[&Layout], dim: usize, op: Op) -> HoduResult<Self> {
@@ -409,6 +443,7 @@ impl BackendStorageT for MetalStorage {
         dilation: &[usize],
         op: Op,
     ) -> HoduResult<Self> {
+        let _precision = self.device.enter_precision();
         ops_conv::call_ops_conv(
             self,
             layout,
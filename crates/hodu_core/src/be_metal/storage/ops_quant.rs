@@ -0,0 +1,266 @@
+use crate::{
+    be::storage::BackendStorageT,
+    be_metal::storage::MetalStorage,
+    error::{HoduError, HoduResult},
+    ops::{Op, QuantOp},
+    types::{BlockFormat, DType, Layout},
+};
+use hodu_metal_kernels::{kernels, utils::BufferOffset};
+
+pub fn call_ops_quantize(
+    storage: &MetalStorage,
+    layout: &Layout,
+    scale: f32,
+    zero_point: i32,
+) -> HoduResult<MetalStorage> {
+    let dtype = storage.dtype();
+    if !matches!(dtype, DType::BF16 | DType::F16 | DType::F32) {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype,
+            op: Op::Quant(QuantOp::Quantize),
+        });
+    }
+
+    let kernel_name = format!("hodu_metal_quantize_{}", dtype);
+    call_ops_affine(storage, layout, scale, zero_point, DType::I8, kernel_name)
+}
+
+pub fn call_ops_dequantize(
+    storage: &MetalStorage,
+    layout: &Layout,
+    scale: f32,
+    zero_point: i32,
+    dtype: DType,
+) -> HoduResult<MetalStorage> {
+    let op = Op::Quant(QuantOp::Dequantize);
+    if storage.dtype() != DType::I8 {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype: storage.dtype(),
+            op,
+        });
+    }
+    if !matches!(dtype, DType::BF16 | DType::F16 | DType::F32) {
+        return Err(HoduError::UnsupportedDTypeForOp { dtype, op });
+    }
+
+    let kernel_name = format!("hodu_metal_dequantize_{}", dtype);
+    call_ops_affine(storage, layout, scale, zero_point, dtype, kernel_name)
+}
+
+fn call_ops_affine(
+    storage: &MetalStorage,
+    layout: &Layout,
+    scale: f32,
+    zero_point: i32,
+    output_dtype: DType,
+    kernel_name: String,
+) -> HoduResult<MetalStorage> {
+    let metadata = crate::op_metadatas::cast_metadata(layout);
+    let num_els = layout.size();
+
+    let device = storage.backend_device();
+
+    let output_buffer = device.new_buffer(num_els, output_dtype, "quant_output")?;
+
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = kernels::Kernel(kernel_name_static);
+
+    let input_offset = BufferOffset::zero_offset(storage.buffer());
+
+    let command_buffer = device.command_buffer()?;
+    kernels::call_ops_quantize(
+        kernel,
+        device.kernels(),
+        device.device(),
+        &command_buffer,
+        input_offset,
+        &output_buffer,
+        &metadata,
+        scale,
+        zero_point,
+    )?;
+
+    Ok(MetalStorage::new(output_buffer, device.clone(), num_els, output_dtype))
+}
+
+pub fn call_ops_matmul_int8(
+    lhs_storage: &MetalStorage,
+    rhs_storage: &MetalStorage,
+    lhs_layout: &Layout,
+    rhs_layout: &Layout,
+) -> HoduResult<MetalStorage> {
+    for dtype in [lhs_storage.dtype(), rhs_storage.dtype()] {
+        if dtype != DType::I8 {
+            return Err(HoduError::UnsupportedDTypeForOp {
+                dtype,
+                op: Op::Quant(QuantOp::MatmulInt8),
+            });
+        }
+    }
+
+    let output_shape = crate::op_metadatas::matmul_output_shape(lhs_layout, rhs_layout)?;
+    let output_layout = Layout::from_shape(&output_shape);
+    let metadata = crate::op_metadatas::matmul_metadata(lhs_layout, rhs_layout, &output_layout)?;
+    let num_els = output_shape.size();
+
+    let device = lhs_storage.backend_device();
+
+    let output_buffer = device.new_buffer(num_els, DType::I32, "matmul_int8_output")?;
+
+    let lhs_offset = BufferOffset::zero_offset(lhs_storage.buffer());
+    let rhs_offset = BufferOffset::zero_offset(rhs_storage.buffer());
+
+    let command_buffer = device.command_buffer()?;
+    kernels::call_ops_matmul_int8(
+        kernels::matmul_int8::I8,
+        device.kernels(),
+        device.device(),
+        &command_buffer,
+        lhs_offset,
+        rhs_offset,
+        &output_buffer,
+        &metadata,
+    )?;
+
+    Ok(MetalStorage::new(output_buffer, device.clone(), num_els, DType::I32))
+}
+
+pub fn call_ops_block_quantize(
+    storage: &MetalStorage,
+    layout: &Layout,
+    format: BlockFormat,
+) -> HoduResult<MetalStorage> {
+    let dtype = storage.dtype();
+    if !matches!(dtype, DType::BF16 | DType::F16 | DType::F32) {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype,
+            op: Op::Quant(QuantOp::BlockQuantize),
+        });
+    }
+    let last_dim = layout.shape().dims().last().copied().unwrap_or(0);
+    format.packed_dim(last_dim)?;
+
+    let metadata = crate::op_metadatas::cast_metadata(layout);
+    let num_bytes = layout.size() / format.block_size() * format.block_bytes();
+
+    let device = storage.backend_device();
+
+    let output_buffer = device.new_buffer(num_bytes, DType::U8, "block_quantize_output")?;
+
+    let kernel_name = format!("hodu_metal_quantize_{}_{}", format, dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = kernels::Kernel(kernel_name_static);
+
+    let input_offset = BufferOffset::zero_offset(storage.buffer());
+
+    let command_buffer = device.command_buffer()?;
+    match format {
+        BlockFormat::Q4_0 => kernels::call_ops_quantize_q4_0(
+            kernel,
+            device.kernels(),
+            device.device(),
+            &command_buffer,
+            input_offset,
+            &output_buffer,
+            &metadata,
+        )?,
+    }
+
+    Ok(MetalStorage::new(output_buffer, device.clone(), num_bytes, DType::U8))
+}
+
+pub fn call_ops_block_dequantize(
+    storage: &MetalStorage,
+    layout: &Layout,
+    format: BlockFormat,
+    dtype: DType,
+) -> HoduResult<MetalStorage> {
+    let op = Op::Quant(QuantOp::BlockDequantize);
+    if storage.dtype() != DType::U8 {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype: storage.dtype(),
+            op,
+        });
+    }
+    if !matches!(dtype, DType::BF16 | DType::F16 | DType::F32) {
+        return Err(HoduError::UnsupportedDTypeForOp { dtype, op });
+    }
+
+    let metadata = crate::op_metadatas::block_dequantize_metadata(layout, format)?;
+    let num_els = metadata[0] * format.block_size();
+
+    let device = storage.backend_device();
+
+    let output_buffer = device.new_buffer(num_els, dtype, "block_dequantize_output")?;
+
+    let kernel_name = format!("hodu_metal_dequantize_{}_{}", format, dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = kernels::Kernel(kernel_name_static);
+
+    let input_offset = BufferOffset::zero_offset(storage.buffer());
+
+    let command_buffer = device.command_buffer()?;
+    match format {
+        BlockFormat::Q4_0 => kernels::call_ops_dequantize_q4_0(
+            kernel,
+            device.kernels(),
+            device.device(),
+            &command_buffer,
+            input_offset,
+            &output_buffer,
+            &metadata,
+        )?,
+    }
+
+    Ok(MetalStorage::new(output_buffer, device.clone(), num_els, dtype))
+}
+
+pub fn call_ops_block_matmul(
+    lhs_storage: &MetalStorage,
+    weight_storage: &MetalStorage,
+    lhs_layout: &Layout,
+    weight_layout: &Layout,
+    format: BlockFormat,
+) -> HoduResult<MetalStorage> {
+    let op = Op::Quant(QuantOp::BlockMatmul);
+    let dtype = lhs_storage.dtype();
+    if !matches!(dtype, DType::BF16 | DType::F16 | DType::F32) {
+        return Err(HoduError::UnsupportedDTypeForOp { dtype, op });
+    }
+    if weight_storage.dtype() != DType::U8 {
+        return Err(HoduError::UnsupportedDTypeForOp {
+            dtype: weight_storage.dtype(),
+            op,
+        });
+    }
+
+    let metadata = crate::op_metadatas::block_matmul_metadata(lhs_layout, weight_layout, format)?;
+    let num_els = metadata[0] * metadata[2];
+
+    let device = lhs_storage.backend_device();
+
+    let output_buffer = device.new_buffer(num_els, dtype, "block_matmul_output")?;
+
+    let kernel_name = format!("hodu_metal_matmul_{}_{}", format, dtype);
+    let kernel_name_static = crate::cache::kernel::get_kernel_name(kernel_name);
+    let kernel = kernels::Kernel(kernel_name_static);
+
+    let lhs_offset = BufferOffset::zero_offset(lhs_storage.buffer());
+    let weight_offset = BufferOffset::zero_offset(weight_storage.buffer());
+
+    let command_buffer = device.command_buffer()?;
+    match format {
+        BlockFormat::Q4_0 => kernels::call_ops_matmul_q4_0(
+            kernel,
+            device.kernels(),
+            device.device(),
+            &command_buffer,
+            lhs_offset,
+            weight_offset,
+            &output_buffer,
+            &metadata,
+        )?,
+    }
+
+    Ok(MetalStorage::new(output_buffer, device.clone(), num_els, dtype))
+}
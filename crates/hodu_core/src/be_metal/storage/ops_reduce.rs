@@ -7,13 +7,7 @@ use crate::{
 };
 use hodu_metal_kernels::{kernels, utils::BufferOffset};
 
-pub fn call_ops_reduce(
-    storage: &MetalStorage,
-    layout: &Layout,
-    dims: &[usize],
-    keep_dim: bool,
-    op: Op,
-) -> HoduResult<MetalStorage> {
+pub fn call_ops_reduce(storage: &MetalStorage, layout: &Layout, dims: &[usize], op: Op) -> HoduResult<MetalStorage> {
     // Extract reduce op
     let reduce_op = match op {
         Op::Reduce(reduce_op) => reduce_op,
@@ -34,10 +28,10 @@ pub fn call_ops_reduce(
         }
     }
 
-    let metadata = crate::op_metadatas::reduce_metadata(layout, dims, keep_dim);
+    let metadata = crate::op_metadatas::reduce_metadata(layout, dims);
 
     // Compute output size from metadata
-    let output_shape_len_idx = 1 + layout.shape().ndim() * 2 + 1;
+    let output_shape_len_idx = 2 + metadata[0] * 2;
     let output_shape_len = metadata[output_shape_len_idx];
     let output_shape_start = output_shape_len_idx + 1;
     let output_size: usize = metadata[output_shape_start..output_shape_start + output_shape_len]
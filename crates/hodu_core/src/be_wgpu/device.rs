@@ -0,0 +1,119 @@
+use crate::{
+    be::{device::BackendDeviceT, storage::BackendStorageT},
+    be_cpu::storage::CpuStorage,
+    be_wgpu::storage::WgpuStorage,
+    error::{HoduError, HoduResult},
+    types::{DType, Device as HoduDevice},
+};
+use hodu_wgpu_kernels::{device::Device, kernel::Kernels, wgpu::Buffer};
+use std::sync::{Arc, LazyLock};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DeviceId(usize);
+
+impl DeviceId {
+    pub(crate) fn new() -> Self {
+        use std::sync::atomic;
+        static COUNTER: atomic::AtomicUsize = atomic::AtomicUsize::new(1);
+        Self(COUNTER.fetch_add(1, atomic::Ordering::Relaxed))
+    }
+}
+
+#[derive(Clone)]
+pub struct WgpuDevice {
+    pub(crate) id: DeviceId,
+    pub(crate) device: Arc<Device>,
+    pub(crate) kernels: Arc<Kernels>,
+}
+
+// Global singleton WebGPU device, the adapter lookup result is kept so a missing adapter surfaces as an error
+static WGPU_DEVICE: LazyLock<Result<WgpuDevice, String>> = LazyLock::new(|| {
+    let device = Device::system_default().map_err(|e| format!("{:?}", e))?;
+
+    Ok(WgpuDevice {
+        id: DeviceId::new(),
+        device: Arc::new(device),
+        kernels: Arc::new(Kernels::new()),
+    })
+});
+
+impl std::fmt::Debug for WgpuDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WgpuDevice({:?})", self.id)
+    }
+}
+
+impl WgpuDevice {
+    /// Returns a reference to the global WebGPU device singleton
+    pub fn global() -> HoduResult<&'static WgpuDevice> {
+        WGPU_DEVICE
+            .as_ref()
+            .map_err(|msg| HoduError::WgpuKernelError(msg.clone()))
+    }
+
+    pub fn kernels(&self) -> &Kernels {
+        &self.kernels
+    }
+
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Every supported dtype occupies one 32-bit word (`bool` is stored as `u32`)
+    pub fn new_buffer(&self, element_count: usize, dtype: DType, name: &str) -> HoduResult<Arc<Buffer>> {
+        check_dtype(dtype)?;
+        Ok(Arc::new(self.device.new_buffer(element_count * 4, name)))
+    }
+
+    pub fn new_buffer_with_cpu_storage(&self, cpu_storage: &CpuStorage) -> HoduResult<Arc<Buffer>> {
+        let buffer = match cpu_storage {
+            CpuStorage::BOOL(data) => {
+                let words: Vec<u32> = data.iter().map(|&v| v as u32).collect();
+                self.device.new_buffer_with_data(&words, "from_cpu")
+            },
+            CpuStorage::F32(data) => self.device.new_buffer_with_data(data, "from_cpu"),
+            CpuStorage::U32(data) => self.device.new_buffer_with_data(data, "from_cpu"),
+            CpuStorage::I32(data) => self.device.new_buffer_with_data(data, "from_cpu"),
+            _ => {
+                return Err(HoduError::UnsupportedDTypeForDevice {
+                    dtype: cpu_storage.dtype(),
+                    device: HoduDevice::WebGPU,
+                })
+            },
+        };
+        Ok(Arc::new(buffer))
+    }
+}
+
+pub(crate) fn check_dtype(dtype: DType) -> HoduResult<()> {
+    match dtype {
+        DType::BOOL | DType::F32 | DType::I32 | DType::U32 => Ok(()),
+        _ => Err(HoduError::UnsupportedDTypeForDevice {
+            dtype,
+            device: HoduDevice::WebGPU,
+        }),
+    }
+}
+
+impl BackendDeviceT for WgpuDevice {
+    type BackendStorage = WgpuStorage;
+
+    fn allocate(size: usize, dtype: DType) -> HoduResult<Self::BackendStorage> {
+        let device = WgpuDevice::global()?.clone();
+        let buffer = device.new_buffer(size, dtype, "allocate")?;
+        Ok(WgpuStorage::new(buffer, device, size, dtype))
+    }
+
+    fn zeros(size: usize, dtype: DType) -> HoduResult<WgpuStorage> {
+        // wgpu zero-initializes new buffers
+        Self::allocate(size, dtype)
+    }
+
+    fn randn(_: usize, _: DType, _: f32, _: f32) -> HoduResult<Self::BackendStorage> {
+        Err(HoduError::NotImplemented("randn on WebGPU device".to_string()))
+    }
+
+    fn rand_uniform(_: usize, _: DType, _: f32, _: f32) -> HoduResult<Self::BackendStorage> {
+        Err(HoduError::NotImplemented("rand_uniform on WebGPU device".to_string()))
+    }
+}
@@ -0,0 +1,622 @@
+mod ops_binary;
+mod ops_host;
+mod ops_matrix;
+mod ops_reduce;
+mod ops_unary;
+
+use crate::{
+    be::storage::BackendStorageT,
+    be_cpu::storage::CpuStorage,
+    be_wgpu::device::WgpuDevice,
+    error::{HoduError, HoduResult},
+    op_metadatas,
+    ops::Op,
+    scalar::Scalar,
+    types::{BlockFormat, DType, Device, Layout, Shape},
+};
+use hodu_wgpu_kernels::{
+    kernels::{call_const_set, call_ops_cast, call_ops_contiguous, Kernel},
+    wgpu::Buffer,
+};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct WgpuStorage {
+    buffer: Arc<Buffer>,
+    device: WgpuDevice,
+    count: usize,
+    dtype: DType,
+}
+
+/// Raw 32-bit pattern of `scalar` converted to `dtype`, as the WGSL kernels expect it
+pub(crate) fn scalar_bits(scalar: Scalar, dtype: DType) -> HoduResult<u32> {
+    match dtype {
+        DType::BOOL => Ok(scalar.to_bool() as u32),
+        DType::F32 => Ok(scalar.to_f32().to_bits()),
+        DType::I32 => Ok(scalar.to_i32() as u32),
+        DType::U32 => Ok(scalar.to_u32()),
+        _ => Err(HoduError::UnsupportedDTypeForDevice {
+            dtype,
+            device: Device::WebGPU,
+        }),
+    }
+}
+
+/// Kernel for `op` on `dtype`, e.g. `hodu_wgpu_add_f32`
+pub(crate) fn kernel(op: impl std::fmt::Display, dtype: DType) -> Kernel {
+    let kernel_name = format!("hodu_wgpu_{}_{}", op, dtype);
+    Kernel(crate::cache::kernel::get_kernel_name(kernel_name))
+}
+
+impl WgpuStorage {
+    pub fn new(buffer: Arc<Buffer>, device: WgpuDevice, count: usize, dtype: DType) -> Self {
+        Self {
+            buffer,
+            device,
+            count,
+            dtype,
+        }
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    pub fn from_cpu_storage(cpu_storage: &CpuStorage) -> HoduResult<Self> {
+        let device = WgpuDevice::global()?.clone();
+        let dtype = cpu_storage.dtype();
+        let count = match cpu_storage {
+            CpuStorage::BOOL(v) => v.len(),
+            CpuStorage::F32(v) => v.len(),
+            CpuStorage::U32(v) => v.len(),
+            CpuStorage::I32(v) => v.len(),
+            _ => {
+                return Err(HoduError::UnsupportedDTypeForDevice {
+                    dtype,
+                    device: Device::WebGPU,
+                })
+            },
+        };
+        let buffer = device.new_buffer_with_cpu_storage(cpu_storage)?;
+        Ok(Self::new(buffer, device, count, dtype))
+    }
+}
+
+impl BackendStorageT for WgpuStorage {
+    type BackendDevice = WgpuDevice;
+
+    fn dtype(&self) -> DType {
+        self.dtype
+    }
+
+    fn device(&self) -> Device {
+        Device::WebGPU
+    }
+
+    fn backend_device(&self) -> &WgpuDevice {
+        &self.device
+    }
+
+    fn to_cpu_storage(&self) -> HoduResult<CpuStorage> {
+        let device = self.device.device();
+        match self.dtype {
+            DType::BOOL => {
+                let words: Vec<u32> = device.read_buffer(&self.buffer, self.count)?;
+                Ok(CpuStorage::BOOL(words.into_iter().map(|w| w != 0).collect()))
+            },
+            DType::F32 => Ok(CpuStorage::F32(device.read_buffer(&self.buffer, self.count)?)),
+            DType::I32 => Ok(CpuStorage::I32(device.read_buffer(&self.buffer, self.count)?)),
+            DType::U32 => Ok(CpuStorage::U32(device.read_buffer(&self.buffer, self.count)?)),
+            dtype => Err(HoduError::UnsupportedDTypeForDevice {
+                dtype,
+                device: Device::WebGPU,
+            }),
+        }
+    }
+
+    fn const_set(&mut self, scalar: Scalar, layout: &Layout) -> HoduResult<()> {
+        if self.dtype != scalar.dtype() {
+            return Err(HoduError::DTypeMismatch {
+                expected: self.dtype,
+                got: scalar.dtype(),
+            });
+        }
+
+        let shape = layout.shape();
+        let strides = layout.strides();
+
+        // Build metadata: [num_els, num_dims, shape..., strides..., offset]
+        let mut metadata = Vec::with_capacity(2 + shape.ndim() * 2 + 1);
+        metadata.push(layout.size());
+        metadata.push(shape.ndim());
+        metadata.extend(shape.dims());
+        metadata.extend(strides.iter().take(shape.ndim()));
+        metadata.push(layout.offset());
+
+        call_const_set(
+            kernel("const_set", self.dtype),
+            self.device.kernels(),
+            self.device.device(),
+            &self.buffer,
+            &metadata,
+            scalar_bits(scalar, self.dtype)?,
+        )?;
+
+        Ok(())
+    }
+
+    fn call_ops_binary(
+        &self,
+        rhs_storage: &Self,
+        lhs_layout: &Layout,
+        rhs_layout: &Layout,
+        op: Op,
+    ) -> HoduResult<Self> {
+        ops_binary::call_ops_binary(self, rhs_storage, lhs_layout, rhs_layout, op)
+    }
+
+    fn call_ops_binary_logical(
+        &self,
+        rhs_storage: &Self,
+        lhs_layout: &Layout,
+        rhs_layout: &Layout,
+        op: Op,
+    ) -> HoduResult<Self> {
+        ops_binary::call_ops_binary_logical(self, rhs_storage, lhs_layout, rhs_layout, op)
+    }
+
+    fn call_ops_bitwise_binary(
+        &self,
+        rhs_storage: &Self,
+        lhs_layout: &Layout,
+        rhs_layout: &Layout,
+        op: Op,
+    ) -> HoduResult<Self> {
+        let rhs = rhs_storage.to_cpu_storage()?;
+        ops_host::on_host(self, |lhs| {
+            lhs.call_ops_bitwise_binary(&rhs, lhs_layout, rhs_layout, op)
+        })
+    }
+
+    fn call_ops_bitwise_unary(&self, layout: &Layout, op: Op) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_bitwise_unary(layout, op))
+    }
+
+    fn call_ops_bitwise_unary_scalar(&self, layout: &Layout, shift: u32, op: Op) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_bitwise_unary_scalar(layout, shift, op))
+    }
+
+    fn call_ops_cmp(&self, rhs_storage: &Self, lhs_layout: &Layout, rhs_layout: &Layout, op: Op) -> HoduResult<Self> {
+        ops_binary::call_ops_cmp(self, rhs_storage, lhs_layout, rhs_layout, op)
+    }
+
+    fn call_ops_cmp_scalar(&self, layout: &Layout, scalar: Scalar, op: Op) -> HoduResult<Self> {
+        ops_unary::call_ops_cmp_scalar(self, layout, scalar, op)
+    }
+
+    fn call_ops_unary(&self, layout: &Layout, op: Op) -> HoduResult<Self> {
+        ops_unary::call_ops_unary(self, layout, op)
+    }
+
+    fn call_ops_unary_logical(&self, layout: &Layout, op: Op) -> HoduResult<Self> {
+        ops_unary::call_ops_unary_logical(self, layout, op)
+    }
+
+    fn call_ops_unary_scalar(&self, layout: &Layout, scalar: Scalar, op: Op) -> HoduResult<Self> {
+        ops_unary::call_ops_unary_scalar(self, layout, scalar, op)
+    }
+
+    fn call_ops_matmul(
+        &self,
+        rhs_storage: &Self,
+        lhs_layout: &Layout,
+        rhs_layout: &Layout,
+        op: Op,
+    ) -> HoduResult<Self> {
+        ops_matrix::call_ops_matmul(self, rhs_storage, lhs_layout, rhs_layout, op)
+    }
+
+    fn call_ops_dot(&self, rhs_storage: &Self, lhs_layout: &Layout, rhs_layout: &Layout, op: Op) -> HoduResult<Self> {
+        ops_matrix::call_ops_dot(self, rhs_storage, lhs_layout, rhs_layout, op)
+    }
+
+    fn call_ops_det(&self, layout: &Layout) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_det(layout))
+    }
+
+    fn call_ops_inv(&self, layout: &Layout) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_inv(layout))
+    }
+
+    fn call_ops_trace(&self, layout: &Layout) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_trace(layout))
+    }
+
+    fn call_ops_quantize(&self, layout: &Layout, scale: f32, zero_point: i32) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_quantize(layout, scale, zero_point))
+    }
+
+    fn call_ops_dequantize(&self, layout: &Layout, scale: f32, zero_point: i32, dtype: DType) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_dequantize(layout, scale, zero_point, dtype)
+        })
+    }
+
+    fn call_ops_matmul_int8(&self, rhs_storage: &Self, lhs_layout: &Layout, rhs_layout: &Layout) -> HoduResult<Self> {
+        let rhs = rhs_storage.to_cpu_storage()?;
+        ops_host::on_host(self, |lhs| lhs.call_ops_matmul_int8(&rhs, lhs_layout, rhs_layout))
+    }
+
+    fn call_ops_block_quantize(&self, layout: &Layout, format: BlockFormat) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_block_quantize(layout, format))
+    }
+
+    fn call_ops_block_dequantize(&self, layout: &Layout, format: BlockFormat, dtype: DType) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_block_dequantize(layout, format, dtype))
+    }
+
+    fn call_ops_block_matmul(
+        &self,
+        weight_storage: &Self,
+        lhs_layout: &Layout,
+        weight_layout: &Layout,
+        format: BlockFormat,
+    ) -> HoduResult<Self> {
+        let weight = weight_storage.to_cpu_storage()?;
+        ops_host::on_host(self, |lhs| {
+            lhs.call_ops_block_matmul(&weight, lhs_layout, weight_layout, format)
+        })
+    }
+
+    fn call_ops_reduce(&self, layout: &Layout, dims: &[usize], keep_dim: bool, op: Op) -> HoduResult<Self> {
+        ops_reduce::call_ops_reduce(self, layout, dims, keep_dim, op)
+    }
+
+    fn call_ops_concat(&self, others: &[&Self], layouts: &[&Layout], dim: usize, op: Op) -> HoduResult<Self> {
+        let others = ops_host::download_all(others)?;
+        let others: Vec<&CpuStorage> = others.iter().collect();
+        ops_host::on_host(self, |storage| storage.call_ops_concat(&others, layouts, dim, op))
+    }
+
+    fn call_ops_split(&self, layout: &Layout, dim: usize, start: usize, size: usize, op: Op) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_split(layout, dim, start, size, op))
+    }
+
+    fn call_ops_index_select(
+        &self,
+        layout: &Layout,
+        indices_storage: &Self,
+        indices_layout: &Layout,
+        dim: usize,
+        op: Op,
+    ) -> HoduResult<Self> {
+        let indices = indices_storage.to_cpu_storage()?;
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_index_select(layout, &indices, indices_layout, dim, op)
+        })
+    }
+
+    fn call_ops_index_put(
+        &self,
+        layout: &Layout,
+        indices_storage: &Self,
+        indices_layout: &Layout,
+        values_storage: &Self,
+        values_layout: &Layout,
+        dim: usize,
+        op: Op,
+    ) -> HoduResult<Self> {
+        let indices = indices_storage.to_cpu_storage()?;
+        let values = values_storage.to_cpu_storage()?;
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_index_put(layout, &indices, indices_layout, &values, values_layout, dim, op)
+        })
+    }
+
+    fn call_ops_gather(
+        &self,
+        layout: &Layout,
+        indices_storage: &Self,
+        indices_layout: &Layout,
+        dim: usize,
+        op: Op,
+    ) -> HoduResult<Self> {
+        let indices = indices_storage.to_cpu_storage()?;
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_gather(layout, &indices, indices_layout, dim, op)
+        })
+    }
+
+    fn call_ops_scatter(
+        &self,
+        layout: &Layout,
+        indices_storage: &Self,
+        indices_layout: &Layout,
+        src_storage: &Self,
+        src_layout: &Layout,
+        dim: usize,
+        op: Op,
+    ) -> HoduResult<Self> {
+        let indices = indices_storage.to_cpu_storage()?;
+        let src = src_storage.to_cpu_storage()?;
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_scatter(layout, &indices, indices_layout, &src, src_layout, dim, op)
+        })
+    }
+
+    fn call_ops_onehot(
+        &self,
+        layout: &Layout,
+        num_classes: usize,
+        axis: usize,
+        output_dtype: DType,
+        op: Op,
+    ) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_onehot(layout, num_classes, axis, output_dtype, op)
+        })
+    }
+
+    fn call_ops_conv(
+        &self,
+        layout: &Layout,
+        weight_storage: &Self,
+        weight_layout: &Layout,
+        stride: &[usize],
+        padding: &[usize],
+        dilation: &[usize],
+        op: Op,
+    ) -> HoduResult<Self> {
+        let weight = weight_storage.to_cpu_storage()?;
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_conv(layout, &weight, weight_layout, stride, padding, dilation, op)
+        })
+    }
+
+    fn call_ops_conv_grad_weight(
+        &self,
+        layout: &Layout,
+        grad_output_storage: &Self,
+        grad_output_layout: &Layout,
+        weight_shape: &Shape,
+        stride: &[usize],
+        padding: &[usize],
+        dilation: &[usize],
+        op: Op,
+    ) -> HoduResult<Self> {
+        let grad_output = grad_output_storage.to_cpu_storage()?;
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_conv_grad_weight(
+                layout,
+                &grad_output,
+                grad_output_layout,
+                weight_shape,
+                stride,
+                padding,
+                dilation,
+                op,
+            )
+        })
+    }
+
+    fn call_ops_reduce_window(
+        &self,
+        layout: &Layout,
+        window_shape: &[usize],
+        strides: &[usize],
+        padding: &[usize],
+        op: Op,
+    ) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_reduce_window(layout, window_shape, strides, padding, op)
+        })
+    }
+
+    fn call_ops_pad(
+        &self,
+        layout: &Layout,
+        pad_before: &[usize],
+        pad_after: &[usize],
+        pad_value: Scalar,
+        op: Op,
+    ) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_pad(layout, pad_before, pad_after, pad_value, op)
+        })
+    }
+
+    fn call_ops_resize(
+        &self,
+        layout: &Layout,
+        output_shape: &[usize],
+        mode: crate::op_params::ResizeMode,
+        coord_transform: crate::op_params::ResizeCoordTransform,
+        nearest_mode: crate::op_params::ResizeNearestMode,
+    ) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| {
+            storage.call_ops_resize(layout, output_shape, mode, coord_transform, nearest_mode)
+        })
+    }
+
+    fn call_ops_cumsum(&self, layout: &Layout, dim: usize) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_cumsum(layout, dim))
+    }
+
+    fn call_ops_cumprod(&self, layout: &Layout, dim: usize) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_cumprod(layout, dim))
+    }
+
+    fn call_ops_einsum(
+        &self,
+        inputs: &[&Self],
+        input_layouts: &[&Layout],
+        parsed: &crate::einsum::ParsedEinsum,
+    ) -> HoduResult<Self> {
+        let inputs = ops_host::download_all(inputs)?;
+        let inputs: Vec<&CpuStorage> = inputs.iter().collect();
+        ops_host::on_host(self, |storage| storage.call_ops_einsum(&inputs, input_layouts, parsed))
+    }
+
+    fn call_ops_flip(&self, layout: &Layout, dims: &[usize]) -> HoduResult<Self> {
+        ops_host::on_host(self, |storage| storage.call_ops_flip(layout, dims))
+    }
+
+    fn call_topk(
+        &self,
+        layout: &Layout,
+        k: usize,
+        last_dim_size: usize,
+        outer_size: usize,
+        largest: bool,
+        sorted: bool,
+    ) -> HoduResult<(Self, Self)> {
+        let (values, indices) =
+            self.to_cpu_storage()?
+                .call_topk(layout, k, last_dim_size, outer_size, largest, sorted)?;
+        Ok((Self::from_cpu_storage(&values)?, Self::from_cpu_storage(&indices)?))
+    }
+
+    fn call_nonzero(&self, layout: &Layout) -> HoduResult<(Self, usize)> {
+        let (indices, count) = self.to_cpu_storage()?.call_nonzero(layout)?;
+        Ok((Self::from_cpu_storage(&indices)?, count))
+    }
+
+    fn call_unique(&self, layout: &Layout) -> HoduResult<(Self, Self, Self, usize)> {
+        let (values, inverse, counts, unique_count) = self.to_cpu_storage()?.call_unique(layout)?;
+        Ok((
+            Self::from_cpu_storage(&values)?,
+            Self::from_cpu_storage(&inverse)?,
+            Self::from_cpu_storage(&counts)?,
+            unique_count,
+        ))
+    }
+
+    fn call_compress(
+        &self,
+        layout: &Layout,
+        condition: &Self,
+        condition_layout: &Layout,
+        axis: Option<usize>,
+    ) -> HoduResult<(Self, usize)> {
+        let condition = condition.to_cpu_storage()?;
+        let (selected, count) = self
+            .to_cpu_storage()?
+            .call_compress(layout, &condition, condition_layout, axis)?;
+        Ok((Self::from_cpu_storage(&selected)?, count))
+    }
+
+    fn to_dtype(&self, layout: &Layout, target_dtype: DType) -> HoduResult<Self> {
+        if self.dtype == target_dtype {
+            // Still need to make it contiguous according to layout
+            return self.contiguous(layout);
+        }
+
+        let metadata = op_metadatas::cast_metadata(layout);
+        let num_els = layout.size();
+
+        // Create output buffer with target dtype
+        let output_buffer = self.device.new_buffer(num_els, target_dtype, "to_dtype")?;
+
+        // Build kernel name: cast_<src>_to_<dst>
+        let kernel_name = format!("hodu_wgpu_cast_{}_to_{}", self.dtype, target_dtype);
+        let kernel = Kernel(crate::cache::kernel::get_kernel_name(kernel_name));
+
+        call_ops_cast(
+            kernel,
+            self.device.kernels(),
+            self.device.device(),
+            &self.buffer,
+            &output_buffer,
+            &metadata,
+        )?;
+
+        Ok(Self::new(output_buffer, self.device.clone(), num_els, target_dtype))
+    }
+
+    fn contiguous(&self, layout: &Layout) -> HoduResult<Self> {
+        // If already contiguous from the start of the buffer, return clone
+        if layout.is_contiguous() && layout.offset() == 0 {
+            return Ok(self.clone());
+        }
+
+        let metadata = op_metadatas::contiguous_metadata(layout);
+        let num_els = layout.size();
+
+        // Create output buffer
+        let output_buffer = self.device.new_buffer(num_els, self.dtype, "contiguous")?;
+
+        call_ops_contiguous(
+            kernel("contiguous", self.dtype),
+            self.device.kernels(),
+            self.device.device(),
+            &self.buffer,
+            &output_buffer,
+            &metadata,
+        )?;
+
+        Ok(Self::new(output_buffer, self.device.clone(), num_els, self.dtype))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        be_wgpu::device::WgpuDevice,
+        tensor::Tensor,
+        types::{DType, Device},
+    };
+
+    // Tests are skipped on machines without a usable adapter
+    fn has_adapter() -> bool {
+        WgpuDevice::global().is_ok()
+    }
+
+    fn assert_close(lhs: &Tensor, rhs: &Tensor) {
+        let lhs = lhs.to_flatten_vec::<f32>().unwrap();
+        let rhs = rhs.to_flatten_vec::<f32>().unwrap();
+        assert_eq!(lhs.len(), rhs.len());
+        for (l, r) in lhs.iter().zip(&rhs) {
+            assert!((l - r).abs() <= 1e-5 * r.abs().max(1.0), "{:?} != {:?}", lhs, rhs);
+        }
+    }
+
+    #[test]
+    fn test_webgpu_matches_cpu() {
+        if !has_adapter() {
+            return;
+        }
+        let cpu = Tensor::from_slice(vec![1.0f32, -2.0, 3.0, 4.0, -5.0, 6.0], [2, 3]).unwrap();
+        let gpu = cpu.to_device(Device::WebGPU).unwrap();
+        assert_eq!(gpu.device(), Device::WebGPU);
+
+        let run = |x: &Tensor| {
+            let y = x.transpose(0, 1).unwrap().exp().unwrap().mul_scalar(0.5f32).unwrap();
+            let z = x.matmul(&y).unwrap();
+            z.add(&z).unwrap().relu().unwrap().mean(&[1], false).unwrap()
+        };
+        assert_close(&run(&gpu), &run(&cpu));
+    }
+
+    #[test]
+    fn test_webgpu_host_fallback() {
+        if !has_adapter() {
+            return;
+        }
+        let cpu = Tensor::from_slice(vec![3i32, 1, 2, 5], [4]).unwrap();
+        let gpu = cpu.to_device(Device::WebGPU).unwrap();
+
+        let summed = gpu.cumsum(0).unwrap();
+        assert_eq!(summed.device(), Device::WebGPU);
+        assert_eq!(summed.to_flatten_vec::<i32>().unwrap(), vec![3, 4, 6, 11]);
+    }
+
+    #[test]
+    fn test_webgpu_rejects_unsupported_dtype() {
+        if !has_adapter() {
+            return;
+        }
+        let cpu = Tensor::from_slice(vec![1u8, 2], [2]).unwrap();
+        assert!(cpu.to_device(Device::WebGPU).is_err());
+        assert!(cpu.to_dtype(DType::F32).unwrap().to_device(Device::WebGPU).is_ok());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{
+    be::storage::BackendStorageT,
+    be_wgpu::storage::{kernel, WgpuStorage},
+    error::{HoduError, HoduResult},
+    ops::Op,
+    types::{DType, Layout},
+};
+use hodu_wgpu_kernels::kernels;
+
+fn dispatch_binary(
+    lhs_storage: &WgpuStorage,
+    rhs_storage: &WgpuStorage,
+    metadata: &[usize],
+    kernel: kernels::Kernel,
+    num_els: usize,
+    output_dtype: DType,
+    name: &str,
+) -> HoduResult<WgpuStorage> {
+    let device = lhs_storage.backend_device();
+    let output_buffer = device.new_buffer(num_els, output_dtype, name)?;
+
+    kernels::call_ops_binary(
+        kernel,
+        device.kernels(),
+        device.device(),
+        lhs_storage.buffer(),
+        rhs_storage.buffer(),
+        &output_buffer,
+        metadata,
+    )?;
+
+    Ok(WgpuStorage::new(output_buffer, device.clone(), num_els, output_dtype))
+}
+
+pub fn call_ops_binary(
+    lhs_storage: &WgpuStorage,
+    rhs_storage: &WgpuStorage,
+    lhs_layout: &Layout,
+    rhs_layout: &Layout,
+    op: Op,
+) -> HoduResult<WgpuStorage> {
+    let binary_op = match op {
+        Op::Binary(binary_op) => binary_op,
+        _ => return Err(HoduError::BackendError("call_ops_binary expects binary op".to_string())),
+    };
+
+    let output_layout = lhs_layout.clone();
+    let metadata = crate::op_metadatas::binary_metadata(lhs_layout, rhs_layout, &output_layout);
+    let dtype = lhs_storage.dtype();
+
+    dispatch_binary(
+        lhs_storage,
+        rhs_storage,
+        &metadata,
+        kernel(binary_op, dtype),
+        lhs_layout.shape().size(),
+        dtype,
+        "binary_output",
+    )
+}
+
+pub fn call_ops_binary_logical(
+    lhs_storage: &WgpuStorage,
+    rhs_storage: &WgpuStorage,
+    lhs_layout: &Layout,
+    rhs_layout: &Layout,
+    op: Op,
+) -> HoduResult<WgpuStorage> {
+    let binary_op = match op {
+        Op::BinaryLogical(binary_op) => binary_op,
+        _ => {
+            return Err(HoduError::BackendError(
+                "call_ops_binary_logical expects binary logical op".to_string(),
+            ))
+        },
+    };
+
+    let output_layout = lhs_layout.clone();
+    let metadata = crate::op_metadatas::binary_logical_metadata(lhs_layout, rhs_layout, &output_layout);
+
+    // Logical ops return BOOL
+    dispatch_binary(
+        lhs_storage,
+        rhs_storage,
+        &metadata,
+        kernel(binary_op, lhs_storage.dtype()),
+        lhs_layout.shape().size(),
+        DType::BOOL,
+        "binary_logical_output",
+    )
+}
+
+pub fn call_ops_cmp(
+    lhs_storage: &WgpuStorage,
+    rhs_storage: &WgpuStorage,
+    lhs_layout: &Layout,
+    rhs_layout: &Layout,
+    op: Op,
+) -> HoduResult<WgpuStorage> {
+    let cmp_op = match op {
+        Op::Cmp(cmp_op) => cmp_op,
+        _ => return Err(HoduError::BackendError("call_ops_cmp expects cmp op".to_string())),
+    };
+
+    let output_layout = lhs_layout.clone();
+    let metadata = crate::op_metadatas::cmp_metadata(lhs_layout, rhs_layout, &output_layout);
+
+    // Cmp ops return BOOL
+    dispatch_binary(
+        lhs_storage,
+        rhs_storage,
+        &metadata,
+        kernel(cmp_op, lhs_storage.dtype()),
+        lhs_layout.shape().size(),
+        DType::BOOL,
+        "cmp_output",
+    )
+}
@@ -0,0 +1,18 @@
+//! Ops without a WGSL kernel yet run on the host: inputs are read back, the CPU backend computes the
+//! result and it is uploaded again. Layouts stay valid because whole buffers are transferred.
+
+use crate::{
+    be::storage::BackendStorageT, be_cpu::storage::CpuStorage, be_wgpu::storage::WgpuStorage, error::HoduResult,
+};
+
+pub fn on_host<F>(storage: &WgpuStorage, f: F) -> HoduResult<WgpuStorage>
+where
+    F: FnOnce(&CpuStorage) -> HoduResult<CpuStorage>,
+{
+    let cpu_storage = storage.to_cpu_storage()?;
+    WgpuStorage::from_cpu_storage(&f(&cpu_storage)?)
+}
+
+pub fn download_all(storages: &[&WgpuStorage]) -> HoduResult<Vec<CpuStorage>> {
+    storages.iter().map(|storage| storage.to_cpu_storage()).collect()
+}
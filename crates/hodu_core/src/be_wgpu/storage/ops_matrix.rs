@@ -0,0 +1,103 @@
+use crate::{
+    be::storage::BackendStorageT,
+    be_wgpu::storage::{kernel, WgpuStorage},
+    error::{HoduError, HoduResult},
+    ops::{MatrixOp, Op},
+    types::{Layout, Shape},
+};
+use hodu_wgpu_kernels::kernels;
+
+pub fn call_ops_matmul(
+    lhs_storage: &WgpuStorage,
+    rhs_storage: &WgpuStorage,
+    lhs_layout: &Layout,
+    rhs_layout: &Layout,
+    op: Op,
+) -> HoduResult<WgpuStorage> {
+    match op {
+        Op::Matrix(MatrixOp::Matmul) => (),
+        _ => return Err(HoduError::BackendError("call_ops_matmul expects matmul op".to_string())),
+    };
+
+    let lhs_dims = lhs_layout.shape().dims();
+    let rhs_dims = rhs_layout.shape().dims();
+    let (lhs_ndim, rhs_ndim) = (lhs_dims.len(), rhs_dims.len());
+
+    if lhs_ndim < 2 || rhs_ndim < 2 {
+        return Err(HoduError::BackendError(
+            "matmul requires at least 2D tensors".to_string(),
+        ));
+    }
+
+    // Batch dims broadcast right-aligned
+    let lhs_batch = &lhs_dims[..lhs_ndim - 2];
+    let rhs_batch = &rhs_dims[..rhs_ndim - 2];
+    let batch_ndim = lhs_batch.len().max(rhs_batch.len());
+    let batch_dim = |batch: &[usize], i: usize| (i + batch.len()).checked_sub(batch_ndim).map_or(1, |idx| batch[idx]);
+
+    let mut output_shape_vec: Vec<usize> = (0..batch_ndim)
+        .map(|i| batch_dim(lhs_batch, i).max(batch_dim(rhs_batch, i)))
+        .collect();
+    output_shape_vec.push(lhs_dims[lhs_ndim - 2]);
+    output_shape_vec.push(rhs_dims[rhs_ndim - 1]);
+    let output_shape = Shape::new(&output_shape_vec);
+    let output_layout = Layout::from_shape(&output_shape);
+
+    let metadata = crate::op_metadatas::matmul_metadata(lhs_layout, rhs_layout, &output_layout)?;
+    let num_els = output_shape.size();
+
+    let dtype = lhs_storage.dtype();
+    let device = lhs_storage.backend_device();
+    let output_buffer = device.new_buffer(num_els, dtype, "matmul_output")?;
+
+    kernels::call_ops_matmul(
+        kernel("matmul", dtype),
+        device.kernels(),
+        device.device(),
+        lhs_storage.buffer(),
+        rhs_storage.buffer(),
+        &output_buffer,
+        &metadata,
+    )?;
+
+    Ok(WgpuStorage::new(output_buffer, device.clone(), num_els, dtype))
+}
+
+pub fn call_ops_dot(
+    lhs_storage: &WgpuStorage,
+    rhs_storage: &WgpuStorage,
+    lhs_layout: &Layout,
+    rhs_layout: &Layout,
+    op: Op,
+) -> HoduResult<WgpuStorage> {
+    match op {
+        Op::Matrix(MatrixOp::Dot) => (),
+        _ => return Err(HoduError::BackendError("call_ops_dot expects dot op".to_string())),
+    };
+
+    let lhs_shape = lhs_layout.shape();
+    let rhs_shape = rhs_layout.shape();
+
+    if lhs_shape.ndim() != 2 || rhs_shape.ndim() != 2 {
+        return Err(HoduError::BackendError("dot requires exactly 2D tensors".to_string()));
+    }
+
+    let metadata = crate::op_metadatas::dot_metadata(lhs_layout, rhs_layout)?;
+    let num_els = lhs_shape.dims()[0] * rhs_shape.dims()[1];
+
+    let dtype = lhs_storage.dtype();
+    let device = lhs_storage.backend_device();
+    let output_buffer = device.new_buffer(num_els, dtype, "dot_output")?;
+
+    kernels::call_ops_dot(
+        kernel("dot", dtype),
+        device.kernels(),
+        device.device(),
+        lhs_storage.buffer(),
+        rhs_storage.buffer(),
+        &output_buffer,
+        &metadata,
+    )?;
+
+    Ok(WgpuStorage::new(output_buffer, device.clone(), num_els, dtype))
+}
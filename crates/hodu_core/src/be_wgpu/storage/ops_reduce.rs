@@ -0,0 +1,66 @@
+use crate::{
+    be::storage::BackendStorageT,
+    be_wgpu::storage::{kernel, ops_host, WgpuStorage},
+    error::{HoduError, HoduResult},
+    ops::{Op, ReduceOp},
+    types::Layout,
+};
+use hodu_wgpu_kernels::kernels;
+
+pub fn call_ops_reduce(
+    storage: &WgpuStorage,
+    layout: &Layout,
+    dims: &[usize],
+    keep_dim: bool,
+    op: Op,
+) -> HoduResult<WgpuStorage> {
+    let reduce_op = match op {
+        Op::Reduce(reduce_op) => reduce_op,
+        _ => return Err(HoduError::BackendError("call_ops_reduce expects reduce op".to_string())),
+    };
+
+    // Index and boolean reductions have no WGSL kernel yet
+    if matches!(
+        reduce_op,
+        ReduceOp::ArgMax | ReduceOp::ArgMin | ReduceOp::Any | ReduceOp::All
+    ) {
+        return ops_host::on_host(storage, |cpu_storage| {
+            cpu_storage.call_ops_reduce(layout, dims, keep_dim, op)
+        });
+    }
+
+    // Validate reduce dimensions
+    for &dim in dims {
+        if dim >= layout.shape().ndim() {
+            return Err(HoduError::InvalidAxis {
+                axis: dim as i32,
+                ndim: layout.shape().ndim(),
+            });
+        }
+    }
+
+    let metadata = crate::op_metadatas::reduce_metadata(layout, dims);
+
+    // Compute output size from metadata
+    let output_shape_len_idx = 2 + metadata[0] * 2;
+    let output_shape_len = metadata[output_shape_len_idx];
+    let output_shape_start = output_shape_len_idx + 1;
+    let output_size: usize = metadata[output_shape_start..output_shape_start + output_shape_len]
+        .iter()
+        .product();
+
+    let dtype = storage.dtype();
+    let device = storage.backend_device();
+    let output_buffer = device.new_buffer(output_size, dtype, "reduce_output")?;
+
+    kernels::call_ops_reduce(
+        kernel(reduce_op, dtype),
+        device.kernels(),
+        device.device(),
+        storage.buffer(),
+        &output_buffer,
+        &metadata,
+    )?;
+
+    Ok(WgpuStorage::new(output_buffer, device.clone(), output_size, dtype))
+}
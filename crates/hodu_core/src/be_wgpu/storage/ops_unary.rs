@@ -0,0 +1,151 @@
+use crate::{
+    be::storage::BackendStorageT,
+    be_wgpu::storage::{kernel, scalar_bits, WgpuStorage},
+    error::{HoduError, HoduResult},
+    ops::Op,
+    scalar::Scalar,
+    types::{DType, Layout},
+};
+use hodu_wgpu_kernels::kernels;
+
+fn dispatch_unary(
+    input_storage: &WgpuStorage,
+    metadata: &[usize],
+    kernel: kernels::Kernel,
+    num_els: usize,
+    output_dtype: DType,
+    scalar: Option<u32>,
+    name: &str,
+) -> HoduResult<WgpuStorage> {
+    let device = input_storage.backend_device();
+    let output_buffer = device.new_buffer(num_els, output_dtype, name)?;
+
+    match scalar {
+        Some(scalar_bits) => kernels::call_ops_unary_scalar(
+            kernel,
+            device.kernels(),
+            device.device(),
+            input_storage.buffer(),
+            &output_buffer,
+            metadata,
+            scalar_bits,
+        )?,
+        None => kernels::call_ops_unary(
+            kernel,
+            device.kernels(),
+            device.device(),
+            input_storage.buffer(),
+            &output_buffer,
+            metadata,
+        )?,
+    }
+
+    Ok(WgpuStorage::new(output_buffer, device.clone(), num_els, output_dtype))
+}
+
+pub fn call_ops_cmp_scalar(
+    input_storage: &WgpuStorage,
+    input_layout: &Layout,
+    scalar: Scalar,
+    op: Op,
+) -> HoduResult<WgpuStorage> {
+    let cmp_op = match op {
+        Op::CmpScalar(cmp_op) => cmp_op,
+        _ => {
+            return Err(HoduError::BackendError(
+                "call_ops_cmp_scalar expects cmp scalar op".to_string(),
+            ))
+        },
+    };
+
+    let output_layout = input_layout.clone();
+    let metadata = crate::op_metadatas::cmp_scalar_metadata(input_layout, &output_layout);
+    let dtype = input_storage.dtype();
+
+    // Cmp ops return BOOL
+    dispatch_unary(
+        input_storage,
+        &metadata,
+        kernel(cmp_op, dtype),
+        input_layout.shape().size(),
+        DType::BOOL,
+        Some(scalar_bits(scalar, dtype)?),
+        "cmp_scalar_output",
+    )
+}
+
+pub fn call_ops_unary(input_storage: &WgpuStorage, input_layout: &Layout, op: Op) -> HoduResult<WgpuStorage> {
+    let unary_op = match op {
+        Op::Unary(unary_op) => unary_op,
+        _ => return Err(HoduError::BackendError("call_ops_unary expects unary op".to_string())),
+    };
+
+    let output_layout = input_layout.clone();
+    let metadata = crate::op_metadatas::unary_metadata(input_layout, &output_layout);
+    let dtype = input_storage.dtype();
+
+    dispatch_unary(
+        input_storage,
+        &metadata,
+        kernel(unary_op, dtype),
+        input_layout.shape().size(),
+        dtype,
+        None,
+        "unary_output",
+    )
+}
+
+pub fn call_ops_unary_logical(input_storage: &WgpuStorage, input_layout: &Layout, op: Op) -> HoduResult<WgpuStorage> {
+    let unary_op = match op {
+        Op::UnaryLogical(unary_op) => unary_op,
+        _ => {
+            return Err(HoduError::BackendError(
+                "call_ops_unary_logical expects unary logical op".to_string(),
+            ))
+        },
+    };
+
+    let output_layout = input_layout.clone();
+    let metadata = crate::op_metadatas::unary_logical_metadata(input_layout, &output_layout);
+
+    // Logical ops return BOOL
+    dispatch_unary(
+        input_storage,
+        &metadata,
+        kernel(unary_op, input_storage.dtype()),
+        input_layout.shape().size(),
+        DType::BOOL,
+        None,
+        "unary_logical_output",
+    )
+}
+
+pub fn call_ops_unary_scalar(
+    input_storage: &WgpuStorage,
+    input_layout: &Layout,
+    scalar: Scalar,
+    op: Op,
+) -> HoduResult<WgpuStorage> {
+    let unary_op = match op {
+        Op::UnaryScalar(unary_op) => unary_op,
+        _ => {
+            return Err(HoduError::BackendError(
+                "call_ops_unary_scalar expects unary scalar op".to_string(),
+            ))
+        },
+    };
+
+    let output_layout = input_layout.clone();
+    let metadata = crate::op_metadatas::unary_scalar_metadata(input_layout, &output_layout);
+    let dtype = input_storage.dtype();
+
+    dispatch_unary(
+        input_storage,
+        &metadata,
+        kernel(unary_op, dtype),
+        input_layout.shape().size(),
+        dtype,
+        Some(scalar_bits(scalar, dtype)?),
+        "unary_scalar_output",
+    )
+}
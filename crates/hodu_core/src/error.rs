@@ -85,6 +85,9 @@ pub enum HoduError {
     /// Metal kernel error
     #[cfg(feature = "metal")]
     MetalKernelError(String),
+    /// WebGPU kernel error
+    #[cfg(feature = "wgpu")]
+    WgpuKernelError(String),
 
     // ===== Gradient Errors =====
     /// VJP (Vector-Jacobian Product) function not found for operation.
@@ -206,6 +209,8 @@ impl fmt::Display for HoduError {
             Self::CudaKernelError(msg) => write!(f, "cuda kernel error: {}", msg),
             #[cfg(feature = "metal")]
             Self::MetalKernelError(msg) => write!(f, "metal kernel error: {}", msg),
+            #[cfg(feature = "wgpu")]
+            Self::WgpuKernelError(msg) => write!(f, "wgpu kernel error: {}", msg),
 
             // Gradient Errors
             Self::VjpFunctionNotFound(msg) => {
@@ -313,6 +318,14 @@ impl From<hodu_metal_kernels::error::MetalKernelError> for HoduError {
     }
 }
 
+// Conversion from hodu_wgpu_kernels error
+#[cfg(feature = "wgpu")]
+impl From<hodu_wgpu_kernels::error::WgpuKernelError> for HoduError {
+    fn from(e: hodu_wgpu_kernels::error::WgpuKernelError) -> Self {
+        HoduError::WgpuKernelError(format!("{:?}", e))
+    }
+}
+
 // Conversion from PoisonError (for RwLock/Mutex)
 impl<T> From<std::sync::PoisonError<T>> for HoduError {
     fn from(e: std::sync::PoisonError<T>) -> Self {
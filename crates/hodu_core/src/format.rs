@@ -2,14 +2,60 @@
 //!
 //! ## Graph formats (model serialization)
 //! - **hdss**: Hodu Snapshot format (native serialized computation graph)
+//! - **onnx**: ONNX models, imported as snapshots (`onnx` feature)
+//!
+//! ## Weight formats
+//! - **gguf**: GGUF weights (llama.cpp/ggml, read-only)
+//!
+//! ## Interop
+//! - **arrow**: Apache Arrow arrays and record batches to and from tensors (`arrow` feature)
+//!
+//! ## Input decoders
+//! - **image**: PNG and JPEG images as HWC/CHW tensors (read-only, `image` feature)
+//! - **wav**: WAV audio as [channels, samples] tensors with the sample rate
 //!
 //! ## Tensor formats (input/output data)
 //! - **hdt**: Hodu Tensor format (native binary tensor)
+//! - **hdta**: Hodu Tensor Archive format (named tensors with an index, partial loading)
 //! - **json**: JSON tensor format (human-readable, debugging)
+//! - **npy**: NumPy array format (single tensor)
+//! - **npz**: NumPy archive format (named tensors, `npz` feature)
+//! - **csv**: CSV tables (numeric columns as a 2D tensor, read-only)
+//! - **parquet**: Apache Parquet tables (numeric columns as a 2D tensor, read-only, `parquet` feature)
+//!
+//! hdt and hdss payloads carry a CRC32C checksum verified on load (see [`set_verify_checksums`])
+//! and can be zstd-compressed on save (`zstd` feature, see [`Compression`]) or AES-GCM encrypted
+//! (`encryption` feature, see [`set_encryption_key`]).
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod csv;
+#[cfg(feature = "serde")]
+pub(crate) mod envelope;
+#[cfg(feature = "std")]
+pub mod gguf;
 #[cfg(feature = "serde")]
 pub mod hdss;
 #[cfg(feature = "serde")]
 pub mod hdt;
 #[cfg(feature = "serde")]
+pub mod hdta;
+#[cfg(feature = "image")]
+pub mod image;
+#[cfg(feature = "serde")]
 pub mod json;
+pub mod npy;
+#[cfg(feature = "npz")]
+pub mod npz;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+mod table;
+pub mod wav;
+
+#[cfg(feature = "serde")]
+pub use envelope::{
+    is_encrypting, is_verifying_checksums, set_encryption_key, set_verify_checksums, Compression, EncryptionKey,
+    KEY_ENV, KEY_FILE_ENV,
+};
@@ -0,0 +1,322 @@
+//! Apache Arrow interop (`arrow` feature)
+//!
+//! Converts numeric Arrow arrays to 1D tensors and record batches to [rows, columns] tensors, and
+//! back. Booleans map to bool tensors and every integer and float width to the dtype of the same
+//! width; widths behind a dtype feature (`i64`, `f64`, ...) need that feature. Nulls are rejected
+//! unless a [`NullPolicy`] fill value is set.
+
+use super::table;
+use crate::error::{HoduError, HoduResult};
+use crate::into::flatten::IntoFlattened;
+use crate::tensor::Tensor;
+use crate::types::DType;
+use arrow_array::cast::AsArray;
+use arrow_array::types::*;
+use arrow_array::{Array, ArrayRef, ArrowPrimitiveType, BooleanArray, PrimitiveArray, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use num_traits::NumCast;
+use std::sync::Arc;
+
+/// How null entries are converted
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NullPolicy {
+    /// Fail on the first array with nulls
+    #[default]
+    Error,
+    /// Replace nulls with a value, e.g. `f64::NAN` for float columns; integer columns need a
+    /// value they can represent and bool columns take any nonzero value as true
+    Fill(f64),
+}
+
+/// Options for converting Arrow data
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrowOptions {
+    /// Record batch columns to convert, by name or zero-based index (`None` converts all of them)
+    pub columns: Option<Vec<String>>,
+    /// Dtype of the converted tensor (`None` keeps the Arrow type, see [`from_record_batch_with`])
+    pub dtype: Option<DType>,
+    pub nulls: NullPolicy,
+}
+
+impl ArrowOptions {
+    pub const DEFAULT: Self = Self {
+        columns: None,
+        dtype: None,
+        nulls: NullPolicy::Error,
+    };
+
+    pub fn with_columns<S: Into<String>>(mut self, columns: impl IntoIterator<Item = S>) -> Self {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_dtype(mut self, dtype: DType) -> Self {
+        self.dtype = Some(dtype);
+        self
+    }
+
+    pub fn with_nulls(mut self, nulls: NullPolicy) -> Self {
+        self.nulls = nulls;
+        self
+    }
+}
+
+impl Default for ArrowOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Convert an Arrow array to a 1D tensor with default options
+pub fn from_array(array: &dyn Array) -> HoduResult<Tensor> {
+    from_array_with(array, &ArrowOptions::DEFAULT)
+}
+
+/// Convert an Arrow array to a 1D tensor
+pub fn from_array_with(array: &dyn Array, options: &ArrowOptions) -> HoduResult<Tensor> {
+    cast(convert(array, options.nulls)?, options.dtype)
+}
+
+/// Convert a record batch to a [rows, columns] tensor with default options
+pub fn from_record_batch(batch: &RecordBatch) -> HoduResult<Tensor> {
+    from_record_batch_with(batch, &ArrowOptions::DEFAULT)
+}
+
+/// Convert a record batch to a [rows, columns] tensor
+///
+/// Without an explicit dtype, columns sharing one type keep it and mixed columns become f32.
+pub fn from_record_batch_with(batch: &RecordBatch, options: &ArrowOptions) -> HoduResult<Tensor> {
+    let schema = batch.schema();
+    let names: Vec<String> = schema.fields().iter().map(|field| field.name().clone()).collect();
+    let columns = table::select_columns(&names, batch.num_columns(), options.columns.as_deref())?;
+    if columns.is_empty() {
+        return Err(HoduError::InvalidArgument("record batch has no columns".to_string()));
+    }
+
+    let tensors = columns
+        .iter()
+        .map(|&column| {
+            convert(batch.column(column).as_ref(), options.nulls)
+                .map_err(|e| HoduError::InvalidArgument(format!("Arrow column '{}': {}", names[column], e)))
+        })
+        .collect::<HoduResult<Vec<_>>>()?;
+    let dtype = options.dtype.unwrap_or_else(|| {
+        let first = tensors[0].dtype();
+        if tensors.iter().all(|tensor| tensor.dtype() == first) {
+            first
+        } else {
+            DType::F32
+        }
+    });
+    let tensors = tensors
+        .into_iter()
+        .map(|tensor| cast(tensor, Some(dtype)))
+        .collect::<HoduResult<Vec<_>>>()?;
+    Tensor::stack(&tensors.iter().collect::<Vec<_>>(), 1)
+}
+
+/// Convert a tensor to a flat Arrow array of the matching type
+///
+/// bf16 and f8 tensors have no Arrow counterpart and must be converted first.
+pub fn to_array(tensor: &Tensor) -> HoduResult<ArrayRef> {
+    Ok(match tensor.dtype() {
+        DType::BOOL => Arc::new(BooleanArray::from(tensor.to_flatten_vec::<bool>()?)),
+        DType::F16 => primitive_array::<Float16Type>(tensor)?,
+        DType::F32 => primitive_array::<Float32Type>(tensor)?,
+        #[cfg(feature = "f64")]
+        DType::F64 => primitive_array::<Float64Type>(tensor)?,
+        DType::U8 => primitive_array::<UInt8Type>(tensor)?,
+        #[cfg(feature = "u16")]
+        DType::U16 => primitive_array::<UInt16Type>(tensor)?,
+        DType::U32 => primitive_array::<UInt32Type>(tensor)?,
+        #[cfg(feature = "u64")]
+        DType::U64 => primitive_array::<UInt64Type>(tensor)?,
+        DType::I8 => primitive_array::<Int8Type>(tensor)?,
+        #[cfg(feature = "i16")]
+        DType::I16 => primitive_array::<Int16Type>(tensor)?,
+        DType::I32 => primitive_array::<Int32Type>(tensor)?,
+        #[cfg(feature = "i64")]
+        DType::I64 => primitive_array::<Int64Type>(tensor)?,
+        dtype => {
+            return Err(HoduError::UnsupportedOperation(format!(
+                "{} tensors have no Arrow type; convert them to f32 first",
+                dtype
+            )))
+        },
+    })
+}
+
+/// Convert a [rows, columns] tensor to a record batch
+///
+/// Columns are named `names`, or by their zero-based index when `names` is `None`.
+pub fn to_record_batch(tensor: &Tensor, names: Option<&[&str]>) -> HoduResult<RecordBatch> {
+    let &[rows, columns] = tensor.shape().dims() else {
+        return Err(HoduError::InvalidArgument(format!(
+            "record batches need a [rows, columns] tensor, got {:?}",
+            tensor.shape().dims()
+        )));
+    };
+    let names: Vec<String> = match names {
+        Some(names) if names.len() != columns => {
+            return Err(HoduError::InvalidArgument(format!(
+                "{} column names for {} columns",
+                names.len(),
+                columns
+            )))
+        },
+        Some(names) => names.iter().map(|name| name.to_string()).collect(),
+        None => (0..columns).map(|column| column.to_string()).collect(),
+    };
+
+    // Column-major values, so each column is a slice of one array
+    let values = to_array(&tensor.transpose(0, 1)?)?;
+    let arrays: Vec<ArrayRef> = (0..columns).map(|column| values.slice(column * rows, rows)).collect();
+    let fields: Vec<Field> = names
+        .into_iter()
+        .map(|name| Field::new(name, values.data_type().clone(), false))
+        .collect();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+        .map_err(|e| HoduError::InvalidArgument(format!("Failed to build record batch: {}", e)))
+}
+
+fn convert(array: &dyn Array, nulls: NullPolicy) -> HoduResult<Tensor> {
+    match array.data_type() {
+        DataType::Boolean => {
+            let array = array.as_boolean();
+            let fill = match fill_value(array, nulls)? {
+                Some(fill) => fill != 0.0,
+                None => false,
+            };
+            let values: Vec<bool> = array.iter().map(|value| value.unwrap_or(fill)).collect();
+            Tensor::from_slice(values, [array.len()])
+        },
+        DataType::Float16 => primitive_tensor::<Float16Type>(array, nulls),
+        DataType::Float32 => primitive_tensor::<Float32Type>(array, nulls),
+        #[cfg(feature = "f64")]
+        DataType::Float64 => primitive_tensor::<Float64Type>(array, nulls),
+        DataType::UInt8 => primitive_tensor::<UInt8Type>(array, nulls),
+        #[cfg(feature = "u16")]
+        DataType::UInt16 => primitive_tensor::<UInt16Type>(array, nulls),
+        DataType::UInt32 => primitive_tensor::<UInt32Type>(array, nulls),
+        #[cfg(feature = "u64")]
+        DataType::UInt64 => primitive_tensor::<UInt64Type>(array, nulls),
+        DataType::Int8 => primitive_tensor::<Int8Type>(array, nulls),
+        #[cfg(feature = "i16")]
+        DataType::Int16 => primitive_tensor::<Int16Type>(array, nulls),
+        DataType::Int32 => primitive_tensor::<Int32Type>(array, nulls),
+        #[cfg(feature = "i64")]
+        DataType::Int64 => primitive_tensor::<Int64Type>(array, nulls),
+        other => Err(HoduError::UnsupportedOperation(format!(
+            "Arrow {} arrays (numeric widths need their dtype feature)",
+            other
+        ))),
+    }
+}
+
+/// The fill value for an array's nulls, or `None` when it has none
+fn fill_value(array: &dyn Array, nulls: NullPolicy) -> HoduResult<Option<f64>> {
+    match (array.null_count(), nulls) {
+        (0, _) => Ok(None),
+        (count, NullPolicy::Error) => Err(HoduError::InvalidArgument(format!(
+            "Arrow array has {} nulls; set a null fill value to convert it",
+            count
+        ))),
+        (_, NullPolicy::Fill(value)) => Ok(Some(value)),
+    }
+}
+
+fn primitive_tensor<T: ArrowPrimitiveType>(array: &dyn Array, nulls: NullPolicy) -> HoduResult<Tensor>
+where
+    T::Native: NumCast,
+    Vec<T::Native>: IntoFlattened,
+{
+    let array = array.as_primitive::<T>();
+    let values = match fill_value(array, nulls)? {
+        None => array.values().to_vec(),
+        Some(value) => {
+            let fill = <T::Native as NumCast>::from(value).ok_or_else(|| {
+                HoduError::InvalidArgument(format!(
+                    "null fill value {} does not fit Arrow {}",
+                    value,
+                    array.data_type()
+                ))
+            })?;
+            array.iter().map(|value| value.unwrap_or(fill)).collect()
+        },
+    };
+    Tensor::from_slice(values, [array.len()])
+}
+
+fn primitive_array<T: ArrowPrimitiveType>(tensor: &Tensor) -> HoduResult<ArrayRef> {
+    Ok(Arc::new(PrimitiveArray::<T>::from_iter_values(
+        tensor.to_flatten_vec::<T::Native>()?,
+    )))
+}
+
+fn cast(tensor: Tensor, dtype: Option<DType>) -> HoduResult<Tensor> {
+    match dtype {
+        Some(dtype) if dtype != tensor.dtype() => tensor.to_dtype(dtype),
+        _ => Ok(tensor),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Float32Array, Int32Array, UInt8Array};
+
+    #[test]
+    fn test_array_roundtrip_and_nulls() {
+        let array = Int32Array::from(vec![Some(1), None, Some(3)]);
+        assert!(from_array(&array).is_err());
+
+        let filled = from_array_with(&array, &ArrowOptions::default().with_nulls(NullPolicy::Fill(-1.0))).unwrap();
+        assert_eq!(filled.dtype(), DType::I32);
+        assert_eq!(filled.to_flatten_vec::<i32>().unwrap(), vec![1, -1, 3]);
+        assert!(from_array_with(&array, &ArrowOptions::default().with_nulls(NullPolicy::Fill(f64::NAN))).is_err());
+
+        let floats = Float32Array::from(vec![Some(0.5), None]);
+        let options = ArrowOptions::default().with_nulls(NullPolicy::Fill(f64::NAN));
+        let tensor = from_array_with(&floats, &options).unwrap();
+        assert!(tensor.to_flatten_vec::<f32>().unwrap()[1].is_nan());
+
+        let back = to_array(&filled).unwrap();
+        assert_eq!(back.as_primitive::<Int32Type>().values().as_ref(), &[1, -1, 3]);
+        assert!(to_array(&filled.to_dtype(DType::BF16).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_record_batch_roundtrip() {
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(UInt8Array::from(vec![1u8, 2, 3])) as ArrayRef),
+            (
+                "score",
+                Arc::new(Float32Array::from(vec![0.5f32, 1.5, 2.5])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        let tensor = from_record_batch(&batch).unwrap();
+        assert_eq!(tensor.dtype(), DType::F32);
+        assert_eq!(tensor.shape().dims(), &[3, 2]);
+        assert_eq!(
+            tensor.to_flatten_vec::<f32>().unwrap(),
+            vec![1.0, 0.5, 2.0, 1.5, 3.0, 2.5]
+        );
+
+        let ids = from_record_batch_with(&batch, &ArrowOptions::default().with_columns(["id"])).unwrap();
+        assert_eq!(ids.dtype(), DType::U8);
+        assert_eq!(ids.shape().dims(), &[3, 1]);
+        assert!(from_record_batch_with(&batch, &ArrowOptions::default().with_columns(["height"])).is_err());
+
+        let back = to_record_batch(&tensor, Some(&["id", "score"])).unwrap();
+        assert_eq!(back.num_rows(), 3);
+        assert_eq!(back.schema().field(0).name(), "id");
+        assert_eq!(
+            back.column(1).as_primitive::<Float32Type>().values().as_ref(),
+            &[0.5, 1.5, 2.5]
+        );
+        assert!(to_record_batch(&tensor, Some(&["id"])).is_err());
+        assert!(to_record_batch(&ids.reshape([3]).unwrap(), None).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! CSV format support
+//!
+//! Loads numeric CSV tables as [rows, columns] tensors. Fields may be quoted (RFC 4180), blank
+//! lines are skipped, and a header row is detected when the first row is not numeric. Empty
+//! cells and `nan` load as NaN.
+//!
+//! The dtype is inferred unless set in [`CsvOptions`]: integer tables that fit in i32 load as
+//! i32, everything else as f32.
+
+use super::table::{self, Cell};
+use crate::error::{HoduError, HoduResult};
+use crate::tensor::Tensor;
+use crate::types::DType;
+
+/// Options for loading CSV tables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    /// Whether the first row holds column names (`None` detects it)
+    pub has_header: Option<bool>,
+    /// Columns to load, by name or zero-based index (`None` loads all of them)
+    pub columns: Option<Vec<String>>,
+    /// Dtype of the loaded tensor (`None` infers it)
+    pub dtype: Option<DType>,
+}
+
+impl CsvOptions {
+    pub const DEFAULT: Self = Self {
+        delimiter: b',',
+        has_header: None,
+        columns: None,
+        dtype: None,
+    };
+
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn with_header(mut self, has_header: bool) -> Self {
+        self.has_header = Some(has_header);
+        self
+    }
+
+    pub fn with_columns<S: Into<String>>(mut self, columns: impl IntoIterator<Item = S>) -> Self {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_dtype(mut self, dtype: DType) -> Self {
+        self.dtype = Some(dtype);
+        self
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Load a table from .csv file with default options
+#[cfg(feature = "std")]
+pub fn load(path: impl AsRef<std::path::Path>) -> HoduResult<Tensor> {
+    load_with(path, &CsvOptions::DEFAULT)
+}
+
+/// Load a table from .csv file
+#[cfg(feature = "std")]
+pub fn load_with(path: impl AsRef<std::path::Path>, options: &CsvOptions) -> HoduResult<Tensor> {
+    let data =
+        std::fs::read(path.as_ref()).map_err(|e| HoduError::IoError(format!("Failed to read csv file: {}", e)))?;
+    deserialize_with(&data, options)
+}
+
+/// Deserialize a table from CSV bytes with default options
+pub fn deserialize(data: &[u8]) -> HoduResult<Tensor> {
+    deserialize_with(data, &CsvOptions::DEFAULT)
+}
+
+/// Deserialize a table from CSV bytes
+pub fn deserialize_with(data: &[u8], options: &CsvOptions) -> HoduResult<Tensor> {
+    let text = std::str::from_utf8(data)
+        .map_err(|e| HoduError::DeserializationFailed(format!("CSV is not valid UTF-8: {}", e)))?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = parse_rows(text, options.delimiter as char)?.into_iter();
+
+    let first = rows
+        .next()
+        .ok_or_else(|| HoduError::DeserializationFailed("CSV has no rows".to_string()))?;
+    let has_header = options
+        .has_header
+        .unwrap_or_else(|| first.iter().any(|field| parse_cell(field).is_none()));
+    let width = first.len();
+    let (names, rows): (Vec<String>, Vec<Vec<String>>) = if has_header {
+        (first, rows.collect())
+    } else {
+        (Vec::new(), std::iter::once(first).chain(rows).collect())
+    };
+
+    let columns = table::select_columns(&names, width, options.columns.as_deref())?;
+    let mut cells = Vec::with_capacity(rows.len() * columns.len());
+    for (i, row) in rows.iter().enumerate() {
+        // Report 1-based line numbers of the data as written, counting the header
+        let line = i + 1 + has_header as usize;
+        if row.len() != width {
+            return Err(HoduError::DeserializationFailed(format!(
+                "CSV row {} has {} fields, expected {}",
+                line,
+                row.len(),
+                width
+            )));
+        }
+        for &column in &columns {
+            let field = &row[column];
+            let cell = parse_cell(field).ok_or_else(|| {
+                HoduError::DeserializationFailed(format!(
+                    "CSV row {} column {} is not a number: '{}'",
+                    line,
+                    column,
+                    field.trim()
+                ))
+            })?;
+            cells.push(cell);
+        }
+    }
+
+    table::to_tensor(cells, rows.len(), columns.len(), options.dtype)
+}
+
+fn parse_cell(field: &str) -> Option<Cell> {
+    let field = field.trim();
+    if field.is_empty() {
+        return Some(Cell::Float(f64::NAN));
+    }
+    if let Ok(v) = field.parse::<i64>() {
+        return Some(Cell::Int(v));
+    }
+    // Rust's float parser also accepts "inf", "infinity" and "nan" in any case
+    field.parse::<f64>().ok().map(Cell::Float)
+}
+
+/// Split CSV text into rows of fields, unquoting quoted fields
+fn parse_rows(text: &str, delimiter: char) -> HoduResult<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                },
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            },
+            '\r' if chars.peek() == Some(&'\n') => {},
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                let row = std::mem::take(&mut row);
+                if !is_blank(&row) {
+                    rows.push(row);
+                }
+            },
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(HoduError::DeserializationFailed(
+            "CSV ends inside a quoted field".to_string(),
+        ));
+    }
+    row.push(field);
+    if !is_blank(&row) {
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn is_blank(row: &[String]) -> bool {
+    matches!(row, [field] if field.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infers_header_and_dtype() {
+        let ints = deserialize(b"a,b,c\n1,2,3\r\n4,5,6\n\n").unwrap();
+        assert_eq!(ints.dtype(), DType::I32);
+        assert_eq!(ints.shape().dims(), &[2, 3]);
+        assert_eq!(ints.to_flatten_vec::<i32>().unwrap(), vec![1, 2, 3, 4, 5, 6]);
+
+        let floats = deserialize(b"1,2.5\n\"3\",-4e1\n").unwrap();
+        assert_eq!(floats.dtype(), DType::F32);
+        assert_eq!(floats.to_flatten_vec::<f32>().unwrap(), vec![1.0, 2.5, 3.0, -40.0]);
+
+        let missing = deserialize(b"x,y\n1,\n").unwrap();
+        assert!(missing.to_flatten_vec::<f32>().unwrap()[1].is_nan());
+    }
+
+    #[test]
+    fn test_selects_columns_and_dtype() {
+        let data = b"\"id\",\"age\",\"income, usd\"\n1,30,1000.5\n2,41,2000\n";
+        let options = CsvOptions::default().with_columns(["income, usd", "1"]);
+        let tensor = deserialize_with(data, &options).unwrap();
+        assert_eq!(tensor.shape().dims(), &[2, 2]);
+        assert_eq!(
+            tensor.to_flatten_vec::<f32>().unwrap(),
+            vec![1000.5, 30.0, 2000.0, 41.0]
+        );
+
+        let tensor = deserialize_with(data, &options.clone().with_columns(["age"]).with_dtype(DType::F32)).unwrap();
+        assert_eq!(tensor.dtype(), DType::F32);
+        assert_eq!(tensor.to_flatten_vec::<f32>().unwrap(), vec![30.0, 41.0]);
+
+        assert!(deserialize_with(data, &options.clone().with_columns(["height"])).is_err());
+        assert!(deserialize_with(data, &options.with_columns(["income, usd"]).with_dtype(DType::I32)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_rows() {
+        let tabs = CsvOptions::default().with_delimiter(b'\t').with_header(false);
+        assert_eq!(deserialize_with(b"1\t2\n3\t4", &tabs).unwrap().shape().dims(), &[2, 2]);
+        assert!(deserialize(b"1,2\n3\n").is_err());
+        assert!(deserialize(b"a,b\n1,x\n").is_err());
+        assert!(deserialize(b"\"1,2\n").is_err());
+        assert!(deserialize(b"").is_err());
+    }
+}
@@ -0,0 +1,358 @@
+//! Envelope around hdt/hdss payloads
+//!
+//! Files start with the magic `HDE\0` and a flags byte, followed by a CRC32C of the stored body
+//! when [`FLAG_CRC32C`] is set, then the postcard body, zstd-compressed when [`FLAG_ZSTD`] is set.
+//! With [`FLAG_AES_GCM`] the (compressed) body is AES-256-GCM encrypted and stored as the nonce
+//! followed by the ciphertext; the checksum then covers the stored ciphertext.
+//! Files without the magic are plain postcard as written before the envelope existed and load
+//! without verification. Such hdt files keep their layout; hdss files also predate the versioned
+//! snapshot layout and are decoded with the original one (see [`crate::snapshot::FORMAT_VERSION`]).
+
+use crate::error::{HoduError, HoduResult};
+use std::borrow::Cow;
+#[cfg(feature = "std")]
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+pub(crate) const MAGIC: &[u8; 4] = b"HDE\0";
+pub(crate) const FLAG_ZSTD: u8 = 0x01;
+pub(crate) const FLAG_CRC32C: u8 = 0x02;
+pub(crate) const FLAG_AES_GCM: u8 = 0x04;
+pub(crate) const KNOWN_FLAGS: u8 = FLAG_ZSTD | FLAG_CRC32C | FLAG_AES_GCM;
+
+/// Environment variable holding the hex-encoded key used to decrypt files
+pub const KEY_ENV: &str = "HODU_ENCRYPTION_KEY";
+/// Environment variable holding the path of a key file used to decrypt files
+pub const KEY_FILE_ENV: &str = "HODU_ENCRYPTION_KEY_FILE";
+
+static VERIFY_CHECKSUMS: AtomicBool = AtomicBool::new(true);
+static ENCRYPTION_KEY: RwLock<Option<EncryptionKey>> = RwLock::new(None);
+
+/// Payload compression applied when saving hdt/hdss files
+///
+/// Loading detects the compression from the file header, so it needs no option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// Store the payload as is
+    #[default]
+    None,
+    /// zstd at the given level (1..=22; 0 selects zstd's default level)
+    Zstd(i32),
+}
+
+impl Compression {
+    /// zstd at its default level, a good balance of speed and size
+    pub const ZSTD: Self = Self::Zstd(3);
+}
+
+/// Whether checksums of hdt/hdss files are verified on load
+pub fn is_verifying_checksums() -> bool {
+    VERIFY_CHECKSUMS.load(Ordering::Relaxed)
+}
+
+/// Turn checksum verification of hdt/hdss files on or off for the whole process
+///
+/// On by default; turning it off skips hashing when loading large trusted files.
+pub fn set_verify_checksums(enabled: bool) {
+    VERIFY_CHECKSUMS.store(enabled, Ordering::Relaxed);
+}
+
+/// A 256-bit AES-GCM key for encrypting hdt/hdss files
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; 32]);
+
+impl EncryptionKey {
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Parse a key from 64 hex digits
+    pub fn from_hex(hex: &str) -> HoduResult<Self> {
+        let hex = hex.trim();
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(HoduError::InvalidArgument(
+                "encryption key must be 64 hex digits (32 bytes)".to_string(),
+            ));
+        }
+        let mut bytes = [0u8; 32];
+        for (byte, pair) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).unwrap_or_default();
+            *byte = u8::from_str_radix(pair, 16)
+                .map_err(|_| HoduError::InvalidArgument("encryption key must be hex-encoded".to_string()))?;
+        }
+        Ok(Self(bytes))
+    }
+
+    /// Read a key file holding either the 32 raw key bytes or the key as 64 hex digits
+    #[cfg(feature = "std")]
+    pub fn from_file(path: impl AsRef<Path>) -> HoduResult<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| HoduError::IoError(format!("Failed to read key file '{}': {}", path.display(), e)))?;
+        match <[u8; 32]>::try_from(data.as_slice()) {
+            Ok(bytes) => Ok(Self(bytes)),
+            Err(_) => Self::from_hex(&String::from_utf8_lossy(&data)),
+        }
+    }
+
+    /// The key from [`KEY_ENV`], or else from the file named by [`KEY_FILE_ENV`]
+    ///
+    /// Without the `std` feature only [`KEY_ENV`] is read.
+    pub fn from_env() -> HoduResult<Option<Self>> {
+        if let Ok(hex) = std::env::var(KEY_ENV) {
+            return Self::from_hex(&hex).map(Some);
+        }
+        #[cfg(feature = "std")]
+        if let Some(path) = std::env::var_os(KEY_FILE_ENV) {
+            return Self::from_file(path).map(Some);
+        }
+        Ok(None)
+    }
+}
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+/// Set the key used to encrypt hdt/hdss files on save and decrypt them on load
+///
+/// While a key is set every hdt/hdss file written by this process is encrypted. Without one,
+/// saving writes plain files and loading an encrypted file takes the key from the environment
+/// (see [`EncryptionKey::from_env`]).
+pub fn set_encryption_key(key: Option<EncryptionKey>) {
+    *ENCRYPTION_KEY.write().unwrap_or_else(|e| e.into_inner()) = key;
+}
+
+/// Whether files written by this process are encrypted
+pub fn is_encrypting() -> bool {
+    ENCRYPTION_KEY.read().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+fn encryption_key() -> Option<EncryptionKey> {
+    ENCRYPTION_KEY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn decryption_key() -> HoduResult<EncryptionKey> {
+    match encryption_key() {
+        Some(key) => Ok(key),
+        None => EncryptionKey::from_env()?.ok_or_else(|| {
+            HoduError::InvalidArgument(format!(
+                "file is encrypted; set {} or {}, or call set_encryption_key",
+                KEY_ENV, KEY_FILE_ENV
+            ))
+        }),
+    }
+}
+
+/// Wrap a serialized payload in the envelope, compressing it according to `compression` and
+/// encrypting it if an encryption key is set
+pub(crate) fn seal(payload: Vec<u8>, compression: Compression) -> HoduResult<Vec<u8>> {
+    let (mut flags, mut body) = match compression {
+        Compression::None => (FLAG_CRC32C, payload),
+        Compression::Zstd(level) => (FLAG_CRC32C | FLAG_ZSTD, zstd_compress(&payload, level)?),
+    };
+    if let Some(key) = encryption_key() {
+        flags |= FLAG_AES_GCM;
+        body = aes_gcm_encrypt(&key, &header(flags), &body)?;
+    }
+
+    let mut data = Vec::with_capacity(MAGIC.len() + 5 + body.len());
+    data.extend_from_slice(MAGIC);
+    data.push(flags);
+    data.extend_from_slice(&crc32c::crc32c(&body).to_le_bytes());
+    data.extend_from_slice(&body);
+    Ok(data)
+}
+
+/// Whether `data` starts with the envelope, as every file written since it was introduced does
+pub(crate) fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Return the serialized payload, verifying, decrypting and decompressing it if the data has an
+/// envelope
+pub(crate) fn open(data: &[u8]) -> HoduResult<Cow<'_, [u8]>> {
+    let Some(rest) = data.strip_prefix(MAGIC.as_slice()) else {
+        return Ok(Cow::Borrowed(data));
+    };
+    let truncated = || HoduError::DeserializationFailed("truncated file header".to_string());
+
+    let (&flags, mut body) = rest.split_first().ok_or_else(truncated)?;
+    if flags & !KNOWN_FLAGS != 0 {
+        return Err(HoduError::DeserializationFailed(format!(
+            "unknown header flags 0x{:02x}",
+            flags
+        )));
+    }
+
+    if flags & FLAG_CRC32C != 0 {
+        let (checksum, rest) = body.split_first_chunk::<4>().ok_or_else(truncated)?;
+        body = rest;
+        if is_verifying_checksums() {
+            let expected = u32::from_le_bytes(*checksum);
+            let actual = crc32c::crc32c(body);
+            if actual != expected {
+                return Err(HoduError::DeserializationFailed(format!(
+                    "checksum mismatch (expected {:08x}, got {:08x}): the file is corrupted or truncated",
+                    expected, actual
+                )));
+            }
+        }
+    }
+
+    let body = if flags & FLAG_AES_GCM != 0 {
+        Cow::Owned(aes_gcm_decrypt(&decryption_key()?, &header(flags), body)?)
+    } else {
+        Cow::Borrowed(body)
+    };
+
+    if flags & FLAG_ZSTD != 0 {
+        Ok(Cow::Owned(zstd_decompress(&body)?))
+    } else {
+        Ok(body)
+    }
+}
+
+/// Magic and flags, authenticated along with the encrypted body so the flags can't be altered
+fn header(flags: u8) -> [u8; 5] {
+    let mut header = [0u8; 5];
+    header[..4].copy_from_slice(MAGIC);
+    header[4] = flags;
+    header
+}
+
+#[cfg(feature = "encryption")]
+fn aes_gcm_encrypt(key: &EncryptionKey, aad: &[u8], body: &[u8]) -> HoduResult<Vec<u8>> {
+    use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+    use aes_gcm::Aes256Gcm;
+
+    let cipher = Aes256Gcm::new(&key.0.into());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: body, aad })
+        .map_err(|_| HoduError::SerializationFailed("Failed to encrypt payload".to_string()))?;
+
+    let mut data = Vec::with_capacity(nonce.len() + ciphertext.len());
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+#[cfg(not(feature = "encryption"))]
+fn aes_gcm_encrypt(_key: &EncryptionKey, _aad: &[u8], _body: &[u8]) -> HoduResult<Vec<u8>> {
+    Err(HoduError::UnsupportedOperation(
+        "encryption requires the `encryption` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "encryption")]
+fn aes_gcm_decrypt(key: &EncryptionKey, aad: &[u8], data: &[u8]) -> HoduResult<Vec<u8>> {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use aes_gcm::{Aes256Gcm, Nonce};
+
+    let (nonce, ciphertext) = data
+        .split_first_chunk::<12>()
+        .ok_or_else(|| HoduError::DeserializationFailed("truncated encrypted payload".to_string()))?;
+    Aes256Gcm::new(&key.0.into())
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map_err(|_| {
+            HoduError::DeserializationFailed(
+                "Failed to decrypt payload: wrong encryption key or tampered file".to_string(),
+            )
+        })
+}
+
+#[cfg(not(feature = "encryption"))]
+fn aes_gcm_decrypt(_key: &EncryptionKey, _aad: &[u8], _data: &[u8]) -> HoduResult<Vec<u8>> {
+    Err(HoduError::UnsupportedOperation(
+        "file is encrypted; loading it requires the `encryption` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_compress(payload: &[u8], level: i32) -> HoduResult<Vec<u8>> {
+    zstd::bulk::compress(payload, level)
+        .map_err(|e| HoduError::SerializationFailed(format!("Failed to compress payload: {}", e)))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_compress(_payload: &[u8], _level: i32) -> HoduResult<Vec<u8>> {
+    Err(HoduError::UnsupportedOperation(
+        "zstd compression requires the `zstd` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decompress(payload: &[u8]) -> HoduResult<Vec<u8>> {
+    zstd::stream::decode_all(payload)
+        .map_err(|e| HoduError::DeserializationFailed(format!("Failed to decompress payload: {}", e)))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decompress(_payload: &[u8]) -> HoduResult<Vec<u8>> {
+    Err(HoduError::UnsupportedOperation(
+        "file is zstd-compressed; loading it requires the `zstd` feature".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_compressed_roundtrip() {
+        let payload: Vec<u8> = (0..4096).map(|i| (i % 7) as u8).collect();
+        let data = seal(payload.clone(), Compression::ZSTD).unwrap();
+        assert!(data.starts_with(MAGIC));
+        assert!(data.len() < payload.len());
+        assert_eq!(open(&data).unwrap().as_ref(), payload.as_slice());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_roundtrip() {
+        let payload: Vec<u8> = (0..256).map(|i| i as u8).collect();
+        let key = EncryptionKey::from_bytes([7; 32]);
+        let data = aes_gcm_encrypt(&key, &header(FLAG_AES_GCM), &payload).unwrap();
+        assert_ne!(&data[12..12 + payload.len()], payload.as_slice());
+        assert_eq!(aes_gcm_decrypt(&key, &header(FLAG_AES_GCM), &data).unwrap(), payload);
+
+        let wrong = EncryptionKey::from_bytes([8; 32]);
+        assert!(aes_gcm_decrypt(&wrong, &header(FLAG_AES_GCM), &data).is_err());
+        assert!(aes_gcm_decrypt(&key, &header(FLAG_AES_GCM | FLAG_ZSTD), &data).is_err());
+    }
+
+    #[test]
+    fn test_key_parsing() {
+        let hex = "00112233445566778899aabbccddeeff00112233445566778899aabbccddeeff";
+        let key = EncryptionKey::from_hex(hex).unwrap();
+        assert_eq!(key.0[1], 0x11);
+        assert_eq!(EncryptionKey::from_hex(&format!("{}\n", hex)).unwrap(), key);
+        assert!(EncryptionKey::from_hex(&hex[2..]).is_err());
+        assert!(EncryptionKey::from_hex(&hex.replace('a', "g")).is_err());
+        assert_eq!(format!("{:?}", key), "EncryptionKey(..)");
+    }
+
+    #[test]
+    fn test_legacy_passthrough() {
+        let payload = vec![1u8, 2, 3];
+        assert!(matches!(open(&payload).unwrap(), Cow::Borrowed(p) if p == payload.as_slice()));
+    }
+
+    #[test]
+    fn test_detects_corruption_and_truncation() {
+        let payload: Vec<u8> = (0..64).collect();
+        let data = seal(payload.clone(), Compression::None).unwrap();
+        assert_eq!(open(&data).unwrap().as_ref(), payload.as_slice());
+
+        let mut corrupted = data.clone();
+        *corrupted.last_mut().unwrap() ^= 0xff;
+        let err = open(&corrupted).unwrap_err().to_string();
+        assert!(err.contains("checksum mismatch"), "{}", err);
+        assert!(open(&data[..data.len() - 8]).is_err());
+    }
+}
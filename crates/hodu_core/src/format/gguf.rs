@@ -0,0 +1,636 @@
+//! GGUF format support
+//!
+//! Reads the weight files used by llama.cpp and the ggml ecosystem (versions 2 and 3): a header
+//! of typed metadata key-values and tensor descriptors, followed by an aligned data section.
+//!
+//! Opening a file parses only the header; tensor data is read on demand, so a multi-gigabyte
+//! model costs nothing until its tensors are requested.
+//!
+//! ggml lists dimensions innermost first; here they are reversed into the usual row-major
+//! shape, so a `[4096, 11008]` ggml weight becomes a `[11008, 4096]` tensor.
+//!
+//! Plain tensors (f32, f16, bf16, f64 and the integer types) load with [`GgufFile::load`].
+//! Block-quantized tensors whose ggml type maps to a [`BlockFormat`] load with
+//! [`GgufFile::load_packed`], repacked into that format's layout; other quantized types are
+//! listed in the header but cannot be loaded.
+
+use crate::error::{HoduError, HoduResult};
+use crate::tensor::Tensor;
+use crate::types::{BlockFormat, DType, Device, Shape};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 4] = b"GGUF";
+
+/// Data section alignment when `general.alignment` is absent
+const DEFAULT_ALIGNMENT: u64 = 32;
+
+/// Upper bound on preallocation for counts read from the file
+const MAX_PREALLOC: usize = 1 << 16;
+
+/// A metadata value
+#[derive(Debug, Clone, PartialEq)]
+pub enum GgufValue {
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    String(String),
+    Array(Vec<GgufValue>),
+}
+
+impl GgufValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The value as an unsigned integer, if it is a non-negative integer of any width
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Self::U8(v) => Some(v as u64),
+            Self::U16(v) => Some(v as u64),
+            Self::U32(v) => Some(v as u64),
+            Self::U64(v) => Some(v),
+            Self::I8(v) => u64::try_from(v).ok(),
+            Self::I16(v) => u64::try_from(v).ok(),
+            Self::I32(v) => u64::try_from(v).ok(),
+            Self::I64(v) => u64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    /// The value as a float, if it is numeric
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::F32(v) => Some(v as f64),
+            Self::F64(v) => Some(v),
+            Self::I8(v) => Some(v as f64),
+            Self::I16(v) => Some(v as f64),
+            Self::I32(v) => Some(v as f64),
+            Self::I64(v) => Some(v as f64),
+            _ => self.as_u64().map(|v| v as f64),
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[GgufValue]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// Element type of a tensor, as a ggml type id
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GgmlType(pub u32);
+
+impl GgmlType {
+    pub const F32: Self = Self(0);
+    pub const F16: Self = Self(1);
+    pub const Q4_0: Self = Self(2);
+    pub const Q4_1: Self = Self(3);
+    pub const Q5_0: Self = Self(6);
+    pub const Q5_1: Self = Self(7);
+    pub const Q8_0: Self = Self(8);
+    pub const Q8_1: Self = Self(9);
+    pub const Q2_K: Self = Self(10);
+    pub const Q3_K: Self = Self(11);
+    pub const Q4_K: Self = Self(12);
+    pub const Q5_K: Self = Self(13);
+    pub const Q6_K: Self = Self(14);
+    pub const Q8_K: Self = Self(15);
+    pub const I8: Self = Self(24);
+    pub const I16: Self = Self(25);
+    pub const I32: Self = Self(26);
+    pub const I64: Self = Self(27);
+    pub const F64: Self = Self(28);
+    pub const BF16: Self = Self(30);
+
+    /// The dtype of a plain (unquantized) tensor of this type
+    pub fn dtype(&self) -> Option<DType> {
+        match *self {
+            Self::F32 => Some(DType::F32),
+            Self::F16 => Some(DType::F16),
+            Self::BF16 => Some(DType::BF16),
+            #[cfg(feature = "f64")]
+            Self::F64 => Some(DType::F64),
+            Self::I8 => Some(DType::I8),
+            #[cfg(feature = "i16")]
+            Self::I16 => Some(DType::I16),
+            Self::I32 => Some(DType::I32),
+            #[cfg(feature = "i64")]
+            Self::I64 => Some(DType::I64),
+            _ => None,
+        }
+    }
+
+    /// The block format a quantized tensor of this type loads as
+    pub fn block_format(&self) -> Option<BlockFormat> {
+        match *self {
+            Self::Q4_0 => Some(BlockFormat::Q4_0),
+            _ => None,
+        }
+    }
+
+    /// Values per block and bytes per block, for every type whose size is known
+    fn block_layout(&self) -> Option<(usize, usize)> {
+        let layout = match *self {
+            Self::F32 | Self::I32 => (1, 4),
+            Self::F16 | Self::BF16 | Self::I16 => (1, 2),
+            Self::F64 | Self::I64 => (1, 8),
+            Self::I8 => (1, 1),
+            Self::Q4_0 => (32, 18),
+            Self::Q4_1 => (32, 20),
+            Self::Q5_0 => (32, 22),
+            Self::Q5_1 => (32, 24),
+            Self::Q8_0 => (32, 34),
+            Self::Q8_1 => (32, 36),
+            Self::Q2_K => (256, 84),
+            Self::Q3_K => (256, 110),
+            Self::Q4_K => (256, 144),
+            Self::Q5_K => (256, 176),
+            Self::Q6_K => (256, 210),
+            Self::Q8_K => (256, 292),
+            _ => return None,
+        };
+        Some(layout)
+    }
+}
+
+impl fmt::Display for GgmlType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            Self::F32 => "f32",
+            Self::F16 => "f16",
+            Self::Q4_0 => "q4_0",
+            Self::Q4_1 => "q4_1",
+            Self::Q5_0 => "q5_0",
+            Self::Q5_1 => "q5_1",
+            Self::Q8_0 => "q8_0",
+            Self::Q8_1 => "q8_1",
+            Self::Q2_K => "q2_k",
+            Self::Q3_K => "q3_k",
+            Self::Q4_K => "q4_k",
+            Self::Q5_K => "q5_k",
+            Self::Q6_K => "q6_k",
+            Self::Q8_K => "q8_k",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::F64 => "f64",
+            Self::BF16 => "bf16",
+            Self(id) => return write!(f, "ggml_type({})", id),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Debug for GgmlType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GgmlType[{}]", self)
+    }
+}
+
+/// A tensor described by the header
+#[derive(Debug, Clone, PartialEq)]
+pub struct GgufTensorInfo {
+    pub name: String,
+    /// Row-major shape (ggml dimensions reversed)
+    pub shape: Vec<usize>,
+    pub ggml_type: GgmlType,
+    /// Offset of the data from the start of the data section
+    pub offset: u64,
+}
+
+impl GgufTensorInfo {
+    /// Number of bytes the tensor occupies in the file, if its type's block size is known
+    pub fn size_in_bytes(&self) -> Option<usize> {
+        let (block_size, block_bytes) = self.ggml_type.block_layout()?;
+        let numel = self.shape.iter().try_fold(1usize, |acc, &d| acc.checked_mul(d))?;
+        numel
+            .is_multiple_of(block_size)
+            .then(|| numel / block_size * block_bytes)
+    }
+}
+
+/// A parsed GGUF header, with tensor data read from the file on demand
+#[derive(Debug, Clone)]
+pub struct GgufFile {
+    path: PathBuf,
+    pub version: u32,
+    /// Metadata key-values, in file order
+    pub metadata: Vec<(String, GgufValue)>,
+    /// Tensor descriptors, in file order
+    pub tensors: Vec<GgufTensorInfo>,
+    /// Absolute offset of the data section
+    data_offset: u64,
+}
+
+impl GgufFile {
+    /// Parse the header of a .gguf file
+    pub fn open(path: impl AsRef<Path>) -> HoduResult<Self> {
+        let path = path.as_ref();
+        let file =
+            std::fs::File::open(path).map_err(|e| HoduError::IoError(format!("Failed to open gguf file: {}", e)))?;
+        let mut reader = HeaderReader::new(BufReader::new(file));
+        let (version, metadata, tensors) = reader.read_header()?;
+
+        let alignment = metadata
+            .iter()
+            .find(|(key, _)| key == "general.alignment")
+            .and_then(|(_, value)| value.as_u64())
+            .unwrap_or(DEFAULT_ALIGNMENT);
+        if alignment == 0 {
+            return Err(HoduError::DeserializationFailed(
+                "gguf general.alignment must not be 0".into(),
+            ));
+        }
+        let data_offset = reader.position.next_multiple_of(alignment);
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            version,
+            metadata,
+            tensors,
+            data_offset,
+        })
+    }
+
+    /// Look up a metadata value by key
+    pub fn get(&self, key: &str) -> Option<&GgufValue> {
+        self.metadata.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Look up a tensor descriptor by name
+    pub fn tensor_info(&self, name: &str) -> Option<&GgufTensorInfo> {
+        self.tensors.iter().find(|t| t.name == name)
+    }
+
+    /// Load a plain (unquantized) tensor
+    pub fn load(&self, name: &str) -> HoduResult<Tensor> {
+        let info = self.find(name)?;
+        let dtype = info.ggml_type.dtype().ok_or_else(|| {
+            let hint = if info.ggml_type.block_format().is_some() {
+                " (quantized; use load_packed)"
+            } else {
+                ""
+            };
+            HoduError::DeserializationFailed(format!(
+                "gguf tensor '{}' has unsupported type {}{}",
+                name, info.ggml_type, hint
+            ))
+        })?;
+
+        let data = self.read_data(info)?;
+        Tensor::from_bytes(&data, Shape::new(&info.shape), dtype, Device::CPU)
+    }
+
+    /// Load a block-quantized tensor
+    ///
+    /// Returns the packed U8 tensor and its block format, as [`Tensor::block_quantize`] would
+    /// have produced them.
+    pub fn load_packed(&self, name: &str) -> HoduResult<(Tensor, BlockFormat)> {
+        let info = self.find(name)?;
+        let format = info.ggml_type.block_format().ok_or_else(|| {
+            HoduError::DeserializationFailed(format!(
+                "gguf tensor '{}' has type {}, which has no block format",
+                name, info.ggml_type
+            ))
+        })?;
+
+        let mut shape = info.shape.clone();
+        if let Some(last) = shape.last_mut() {
+            *last = format
+                .packed_dim(*last)
+                .map_err(|e| HoduError::DeserializationFailed(format!("gguf tensor '{}': {}", name, e)))?;
+        }
+
+        let mut data = self.read_data(info)?;
+        match format {
+            BlockFormat::Q4_0 => repack_q4_0(&mut data),
+        }
+        let tensor = Tensor::from_bytes(&data, Shape::new(&shape), DType::U8, Device::CPU)?;
+        Ok((tensor, format))
+    }
+
+    /// Load every plain tensor, skipping quantized ones
+    pub fn load_many(&self) -> HoduResult<HashMap<String, Tensor>> {
+        self.tensors
+            .iter()
+            .filter(|info| info.ggml_type.dtype().is_some())
+            .map(|info| Ok((info.name.clone(), self.load(&info.name)?)))
+            .collect()
+    }
+
+    fn find(&self, name: &str) -> HoduResult<&GgufTensorInfo> {
+        self.tensor_info(name)
+            .ok_or_else(|| HoduError::InvalidArgument(format!("gguf file has no tensor '{}'", name)))
+    }
+
+    fn read_data(&self, info: &GgufTensorInfo) -> HoduResult<Vec<u8>> {
+        let size = info.size_in_bytes().ok_or_else(|| {
+            HoduError::DeserializationFailed(format!(
+                "gguf tensor '{}' has shape {:?}, which does not fit type {}",
+                info.name, info.shape, info.ggml_type
+            ))
+        })?;
+        let io_err =
+            |e: std::io::Error| HoduError::IoError(format!("Failed to read gguf tensor '{}': {}", info.name, e));
+
+        let mut file = std::fs::File::open(&self.path).map_err(io_err)?;
+        file.seek(SeekFrom::Start(self.data_offset + info.offset))
+            .map_err(io_err)?;
+        let mut data = vec![0u8; size];
+        file.read_exact(&mut data).map_err(io_err)?;
+        Ok(data)
+    }
+}
+
+/// Reorder the nibbles of ggml Q4_0 blocks into [`BlockFormat::Q4_0`] order
+///
+/// Both share the f16 scale and the `(q - 8) * scale` decoding, but ggml keeps value `j` in the
+/// low nibble of byte `j` and value `j + 16` in its high nibble.
+fn repack_q4_0(data: &mut [u8]) {
+    for block in data.chunks_exact_mut(18) {
+        let codes = &mut block[2..];
+        let mut values = [0u8; 32];
+        for (j, &byte) in codes.iter().enumerate() {
+            values[j] = byte & 0x0F;
+            values[j + 16] = byte >> 4;
+        }
+        for (j, byte) in codes.iter_mut().enumerate() {
+            *byte = values[2 * j] | (values[2 * j + 1] << 4);
+        }
+    }
+}
+
+/// Header parser that tracks how many bytes it has consumed
+struct HeaderReader<R> {
+    reader: R,
+    position: u64,
+}
+
+type Header = (u32, Vec<(String, GgufValue)>, Vec<GgufTensorInfo>);
+
+impl<R: Read> HeaderReader<R> {
+    fn new(reader: R) -> Self {
+        Self { reader, position: 0 }
+    }
+
+    fn read_header(&mut self) -> HoduResult<Header> {
+        let magic: [u8; 4] = self.read_array()?;
+        if &magic != MAGIC {
+            return Err(HoduError::DeserializationFailed("Not a gguf file (bad magic)".into()));
+        }
+        let version = self.read_u32()?;
+        if !(2..=3).contains(&version) {
+            return Err(HoduError::DeserializationFailed(format!(
+                "Unsupported gguf version {}",
+                version
+            )));
+        }
+
+        let tensor_count = self.read_u64()? as usize;
+        let kv_count = self.read_u64()? as usize;
+
+        let mut metadata = Vec::with_capacity(kv_count.min(MAX_PREALLOC));
+        for _ in 0..kv_count {
+            let key = self.read_string()?;
+            let value_type = self.read_u32()?;
+            let value = self.read_value(value_type)?;
+            metadata.push((key, value));
+        }
+
+        let mut tensors = Vec::with_capacity(tensor_count.min(MAX_PREALLOC));
+        for _ in 0..tensor_count {
+            let name = self.read_string()?;
+            let n_dims = self.read_u32()?;
+            if n_dims > 8 {
+                return Err(HoduError::DeserializationFailed(format!(
+                    "gguf tensor '{}' has {} dimensions",
+                    name, n_dims
+                )));
+            }
+            let mut shape = (0..n_dims)
+                .map(|_| {
+                    let dim = self.read_u64()?;
+                    usize::try_from(dim).map_err(|_| {
+                        HoduError::DeserializationFailed(format!("gguf tensor '{}' dimension too large", name))
+                    })
+                })
+                .collect::<HoduResult<Vec<_>>>()?;
+            shape.reverse();
+            let ggml_type = GgmlType(self.read_u32()?);
+            let offset = self.read_u64()?;
+            tensors.push(GgufTensorInfo {
+                name,
+                shape,
+                ggml_type,
+                offset,
+            });
+        }
+
+        Ok((version, metadata, tensors))
+    }
+
+    fn read_value(&mut self, value_type: u32) -> HoduResult<GgufValue> {
+        let value = match value_type {
+            0 => GgufValue::U8(self.read_array::<1>()?[0]),
+            1 => GgufValue::I8(self.read_array::<1>()?[0] as i8),
+            2 => GgufValue::U16(u16::from_le_bytes(self.read_array()?)),
+            3 => GgufValue::I16(i16::from_le_bytes(self.read_array()?)),
+            4 => GgufValue::U32(self.read_u32()?),
+            5 => GgufValue::I32(i32::from_le_bytes(self.read_array()?)),
+            6 => GgufValue::F32(f32::from_le_bytes(self.read_array()?)),
+            7 => GgufValue::Bool(self.read_array::<1>()?[0] != 0),
+            8 => GgufValue::String(self.read_string()?),
+            9 => {
+                let element_type = self.read_u32()?;
+                if element_type == 9 {
+                    return Err(HoduError::DeserializationFailed(
+                        "Nested gguf arrays are not supported".into(),
+                    ));
+                }
+                let len = self.read_u64()? as usize;
+                let mut values = Vec::with_capacity(len.min(MAX_PREALLOC));
+                for _ in 0..len {
+                    values.push(self.read_value(element_type)?);
+                }
+                GgufValue::Array(values)
+            },
+            10 => GgufValue::U64(self.read_u64()?),
+            11 => GgufValue::I64(i64::from_le_bytes(self.read_array()?)),
+            12 => GgufValue::F64(f64::from_le_bytes(self.read_array()?)),
+            _ => {
+                return Err(HoduError::DeserializationFailed(format!(
+                    "Unknown gguf value type {}",
+                    value_type
+                )))
+            },
+        };
+        Ok(value)
+    }
+
+    fn read_string(&mut self) -> HoduResult<String> {
+        let len = self.read_u64()?;
+        let mut bytes = Vec::with_capacity((len as usize).min(MAX_PREALLOC));
+        let read = (&mut self.reader)
+            .take(len)
+            .read_to_end(&mut bytes)
+            .map_err(|e| HoduError::IoError(format!("Failed to read gguf header: {}", e)))?;
+        if read as u64 != len {
+            return Err(HoduError::DeserializationFailed("Truncated gguf header".into()));
+        }
+        self.position += len;
+        String::from_utf8(bytes).map_err(|_| HoduError::DeserializationFailed("gguf string is not valid UTF-8".into()))
+    }
+
+    fn read_u32(&mut self) -> HoduResult<u32> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_u64(&mut self) -> HoduResult<u64> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_array<const N: usize>(&mut self) -> HoduResult<[u8; N]> {
+        let mut bytes = [0u8; N];
+        self.reader.read_exact(&mut bytes).map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => HoduError::DeserializationFailed("Truncated gguf header".into()),
+            _ => HoduError::IoError(format!("Failed to read gguf header: {}", e)),
+        })?;
+        self.position += N as u64;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn push_string(buf: &mut Vec<u8>, s: &str) {
+        buf.extend_from_slice(&(s.len() as u64).to_le_bytes());
+        buf.extend_from_slice(s.as_bytes());
+    }
+
+    /// A version 3 file with metadata, an f32 [2, 3] tensor and a q4_0 [1, 32] tensor
+    fn write_test_file() -> tempfile::NamedTempFile {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&3u32.to_le_bytes());
+        buf.extend_from_slice(&2u64.to_le_bytes());
+        buf.extend_from_slice(&3u64.to_le_bytes());
+
+        push_string(&mut buf, "general.architecture");
+        buf.extend_from_slice(&8u32.to_le_bytes());
+        push_string(&mut buf, "llama");
+        push_string(&mut buf, "llama.context_length");
+        buf.extend_from_slice(&4u32.to_le_bytes());
+        buf.extend_from_slice(&4096u32.to_le_bytes());
+        push_string(&mut buf, "tokenizer.ggml.scores");
+        buf.extend_from_slice(&9u32.to_le_bytes());
+        buf.extend_from_slice(&6u32.to_le_bytes());
+        buf.extend_from_slice(&2u64.to_le_bytes());
+        buf.extend_from_slice(&0.5f32.to_le_bytes());
+        buf.extend_from_slice(&(-1.0f32).to_le_bytes());
+
+        // ggml dims are innermost first
+        push_string(&mut buf, "weight");
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        buf.extend_from_slice(&3u64.to_le_bytes());
+        buf.extend_from_slice(&2u64.to_le_bytes());
+        buf.extend_from_slice(&GgmlType::F32.0.to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes());
+
+        push_string(&mut buf, "quantized");
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        buf.extend_from_slice(&32u64.to_le_bytes());
+        buf.extend_from_slice(&1u64.to_le_bytes());
+        buf.extend_from_slice(&GgmlType::Q4_0.0.to_le_bytes());
+        buf.extend_from_slice(&32u64.to_le_bytes());
+
+        buf.resize(buf.len().next_multiple_of(32), 0);
+        for v in [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+        buf.resize(buf.len() + 8, 0);
+
+        // Scale 1.0 and code j % 16 for value j, in ggml nibble order
+        buf.extend_from_slice(&half::f16::from_f32(1.0).to_le_bytes());
+        for j in 0..16u8 {
+            buf.push(j | (j << 4));
+        }
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&buf).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_read_metadata() {
+        let file = write_test_file();
+        let gguf = GgufFile::open(file.path()).unwrap();
+
+        assert_eq!(gguf.version, 3);
+        assert_eq!(gguf.get("general.architecture").and_then(|v| v.as_str()), Some("llama"));
+        assert_eq!(gguf.get("llama.context_length").and_then(|v| v.as_u64()), Some(4096));
+        assert_eq!(
+            gguf.get("tokenizer.ggml.scores").and_then(|v| v.as_array()),
+            Some(&[GgufValue::F32(0.5), GgufValue::F32(-1.0)][..])
+        );
+
+        let info = gguf.tensor_info("weight").unwrap();
+        assert_eq!(info.shape, vec![2, 3]);
+        assert_eq!(info.ggml_type, GgmlType::F32);
+        assert_eq!(gguf.tensor_info("quantized").unwrap().size_in_bytes(), Some(18));
+    }
+
+    #[test]
+    fn test_load_tensors() {
+        let file = write_test_file();
+        let gguf = GgufFile::open(file.path()).unwrap();
+
+        let weight = gguf.load("weight").unwrap();
+        assert_eq!(weight.shape().dims(), &[2, 3]);
+        assert_eq!(
+            weight.to_flatten_vec::<f32>().unwrap(),
+            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+        );
+
+        assert!(gguf.load("quantized").is_err());
+        let (packed, format) = gguf.load_packed("quantized").unwrap();
+        assert_eq!(format, BlockFormat::Q4_0);
+        assert_eq!(packed.shape().dims(), &[1, 18]);
+
+        let values = packed
+            .block_dequantize(format, DType::F32)
+            .unwrap()
+            .to_flatten_vec::<f32>()
+            .unwrap();
+        let expected: Vec<f32> = (0..32).map(|j| (j % 16) as f32 - 8.0).collect();
+        assert_eq!(values, expected);
+
+        assert_eq!(gguf.load_many().unwrap().len(), 1);
+    }
+}
@@ -1,20 +1,392 @@
 //! Hodu Snapshot (.hdss) format support
+//!
+//! Large snapshots can be saved sharded: the graph stays in the .hdss file while constant data
+//! is split across .hdta weight shards, with a JSON manifest mapping each constant to its shard
+//! (like safetensors index files).
 
+use super::Compression;
+#[cfg(feature = "std")]
+use super::{hdta, is_encrypting};
+#[cfg(feature = "std")]
+use crate::error::HoduError;
 use crate::error::HoduResult;
 use crate::snapshot::Snapshot;
+#[cfg(feature = "std")]
+use crate::snapshot::SnapshotConstant;
+#[cfg(feature = "std")]
+use crate::tensor::Tensor;
+#[cfg(feature = "std")]
+use crate::types::Device;
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
 
+/// Snapshot metadata key naming the weight manifest of a sharded snapshot
+pub const WEIGHTS_METADATA_KEY: &str = "hdss.weights";
+
+/// Weight manifest of a sharded snapshot
+#[cfg(feature = "std")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ShardManifest {
+    total_size: u64,
+    shards: Vec<String>,
+    /// Constant id -> shard file
+    weight_map: BTreeMap<usize, String>,
+}
+
+#[cfg(feature = "std")]
 pub fn load(path: impl AsRef<std::path::Path>) -> HoduResult<Snapshot> {
     Snapshot::load(path)
 }
 
+/// Load a snapshot without reading the data of sharded constants
+///
+/// The weights of a sharded snapshot are returned alongside it; a shard is opened only when one
+/// of its constants is read, e.g. through [`Interpreter::constant_loader`].
+///
+/// [`Interpreter::constant_loader`]: crate::snapshot::Interpreter::constant_loader
+#[cfg(feature = "std")]
+pub fn load_lazy(path: impl AsRef<Path>) -> HoduResult<(Snapshot, Option<ShardedWeights>)> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|e| HoduError::IoError(e.to_string()))?;
+    let snapshot = Snapshot::from_bytes(&bytes)?;
+    let weights = match snapshot.metadata.get(WEIGHTS_METADATA_KEY) {
+        Some(manifest) => Some(ShardedWeights::open(
+            path.parent().unwrap_or(Path::new("")).join(manifest),
+        )?),
+        None => None,
+    };
+    Ok((snapshot, weights))
+}
+
+#[cfg(feature = "std")]
 pub fn save(snapshot: &Snapshot, path: impl AsRef<std::path::Path>) -> HoduResult<()> {
     snapshot.save(path)
 }
 
+#[cfg(feature = "std")]
+pub fn save_with(snapshot: &Snapshot, path: impl AsRef<std::path::Path>, compression: Compression) -> HoduResult<()> {
+    snapshot.save_with(path, compression)
+}
+
 pub fn to_bytes(snapshot: &Snapshot) -> HoduResult<Vec<u8>> {
     snapshot.to_bytes()
 }
 
+pub fn to_bytes_with(snapshot: &Snapshot, compression: Compression) -> HoduResult<Vec<u8>> {
+    snapshot.to_bytes_with(compression)
+}
+
 pub fn from_bytes(data: &[u8]) -> HoduResult<Snapshot> {
     Snapshot::from_bytes(data)
 }
+
+/// Save a snapshot with its constant data split into weight shards of at most `max_shard_size`
+/// bytes (a larger constant gets a shard of its own)
+///
+/// For `model.hdss` this writes the graph to `model.hdss`, the shards to
+/// `model-00001-of-0000N.hdta` and the manifest to `model.hdss.index.json`, all side by side.
+/// Shards are not encrypted, so this fails while an encryption key is set.
+#[cfg(feature = "std")]
+pub fn save_sharded(snapshot: &Snapshot, path: impl AsRef<Path>, max_shard_size: usize) -> HoduResult<()> {
+    let path = path.as_ref();
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let (Some(file_name), Some(stem)) = (
+        path.file_name().and_then(|n| n.to_str()),
+        path.file_stem().and_then(|n| n.to_str()),
+    ) else {
+        return Err(HoduError::InvalidArgument(format!(
+            "invalid snapshot path '{}'",
+            path.display()
+        )));
+    };
+
+    if is_encrypting() {
+        return Err(HoduError::UnsupportedOperation(
+            "sharded weights can't be encrypted; save the snapshot unsharded".to_string(),
+        ));
+    }
+
+    let mut groups: Vec<Vec<&SnapshotConstant>> = Vec::new();
+    let mut group_size = 0;
+    for constant in &snapshot.constants {
+        if constant.is_external() {
+            return Err(HoduError::InvalidArgument(format!(
+                "constant {} has no data; load the snapshot's weights before sharding it",
+                constant.id.0
+            )));
+        }
+        let size = constant.data.len();
+        if groups.is_empty() || (group_size > 0 && group_size + size > max_shard_size) {
+            groups.push(Vec::new());
+            group_size = 0;
+        }
+        groups.last_mut().unwrap().push(constant);
+        group_size += size;
+    }
+
+    let mut manifest = ShardManifest {
+        total_size: 0,
+        shards: Vec::with_capacity(groups.len()),
+        weight_map: BTreeMap::new(),
+    };
+    for (index, group) in groups.iter().enumerate() {
+        let shard = format!("{}-{:05}-of-{:05}.hdta", stem, index + 1, groups.len());
+        let tensors = group
+            .iter()
+            .map(|constant| {
+                let tensor = Tensor::from_bytes(&constant.data, constant.shape.clone(), constant.dtype, Device::CPU)?;
+                Ok((constant.id.0.to_string(), tensor))
+            })
+            .collect::<HoduResult<HashMap<_, _>>>()?;
+        hdta::save(&tensors, dir.join(&shard))?;
+
+        for constant in group {
+            manifest.total_size += constant.data.len() as u64;
+            manifest.weight_map.insert(constant.id.0, shard.clone());
+        }
+        manifest.shards.push(shard);
+    }
+
+    let manifest_name = format!("{}.index.json", file_name);
+    let json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| HoduError::SerializationFailed(format!("Failed to serialize weight manifest: {}", e)))?;
+    std::fs::write(dir.join(&manifest_name), json)
+        .map_err(|e| HoduError::IoError(format!("Failed to write weight manifest: {}", e)))?;
+
+    let mut metadata = snapshot.metadata.clone();
+    metadata.insert(WEIGHTS_METADATA_KEY.to_string(), manifest_name);
+    let graph = Snapshot {
+        name: snapshot.name.clone(),
+        inputs: snapshot.inputs.clone(),
+        constants: snapshot
+            .constants
+            .iter()
+            .map(|constant| SnapshotConstant {
+                id: constant.id,
+                name: constant.name.clone(),
+                shape: constant.shape.clone(),
+                dtype: constant.dtype,
+                data: Vec::new(),
+            })
+            .collect(),
+        targets: snapshot.targets.clone(),
+        nodes: snapshot.nodes.clone(),
+        metadata,
+        subgraphs: snapshot.subgraphs.clone(),
+    };
+    graph.save(path)
+}
+
+/// Weight shards of a sharded snapshot, each opened on first use
+#[cfg(feature = "std")]
+pub struct ShardedWeights {
+    dir: PathBuf,
+    shards: Vec<String>,
+    /// Constant id -> index into `shards`
+    weight_map: HashMap<usize, usize>,
+    archives: Mutex<Vec<Option<hdta::Archive>>>,
+}
+
+#[cfg(feature = "std")]
+impl ShardedWeights {
+    /// Open a weight manifest; shard files are resolved relative to it
+    pub fn open(manifest_path: impl AsRef<Path>) -> HoduResult<Self> {
+        let manifest_path = manifest_path.as_ref();
+        let json = std::fs::read(manifest_path).map_err(|e| {
+            HoduError::IoError(format!(
+                "Failed to read weight manifest '{}': {}",
+                manifest_path.display(),
+                e
+            ))
+        })?;
+        let manifest: ShardManifest = serde_json::from_slice(&json)
+            .map_err(|e| HoduError::DeserializationFailed(format!("Invalid weight manifest: {}", e)))?;
+
+        let weight_map = manifest
+            .weight_map
+            .iter()
+            .map(|(&id, shard)| {
+                let index = manifest.shards.iter().position(|s| s == shard).ok_or_else(|| {
+                    HoduError::DeserializationFailed(format!(
+                        "weight manifest maps constant {} to unknown shard '{}'",
+                        id, shard
+                    ))
+                })?;
+                Ok((id, index))
+            })
+            .collect::<HoduResult<HashMap<_, _>>>()?;
+
+        Ok(Self {
+            dir: manifest_path.parent().unwrap_or(Path::new("")).to_path_buf(),
+            archives: Mutex::new((0..manifest.shards.len()).map(|_| None).collect()),
+            shards: manifest.shards,
+            weight_map,
+        })
+    }
+
+    /// Paths of the shard files
+    pub fn shard_paths(&self) -> Vec<PathBuf> {
+        self.shards.iter().map(|shard| self.dir.join(shard)).collect()
+    }
+
+    /// Load the data of a sharded constant as a CPU tensor
+    pub fn get(&self, constant: &SnapshotConstant) -> HoduResult<Tensor> {
+        let &shard = self.weight_map.get(&constant.id.0).ok_or_else(|| {
+            HoduError::InvalidArgument(format!("constant {} is not in any weight shard", constant.id.0))
+        })?;
+
+        let mut archives = self.archives.lock().unwrap();
+        if archives[shard].is_none() {
+            archives[shard] = Some(hdta::Archive::open(self.dir.join(&self.shards[shard]))?);
+        }
+        let tensor = archives[shard].as_ref().unwrap().get(&constant.id.0.to_string())?;
+
+        if tensor.shape() != constant.shape || tensor.dtype() != constant.dtype {
+            return Err(HoduError::DeserializationFailed(format!(
+                "constant {} is {:?} {} in shard '{}' but {:?} {} in the snapshot",
+                constant.id.0,
+                tensor.shape().dims(),
+                tensor.dtype(),
+                self.shards[shard],
+                constant.shape.dims(),
+                constant.dtype
+            )));
+        }
+        Ok(tensor)
+    }
+
+    /// Read the data of every sharded constant into `snapshot`, making it self-contained
+    pub fn load_into(&self, snapshot: &mut Snapshot) -> HoduResult<()> {
+        for constant in snapshot.constants.iter_mut().filter(|constant| constant.is_external()) {
+            constant.data = self.get(constant)?.to_bytes()?;
+        }
+        snapshot.metadata.remove(WEIGHTS_METADATA_KEY);
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::snapshot::{CaptureBoard, Interpreter};
+    use crate::types::DType;
+
+    #[test]
+    fn test_roundtrip_preserves_names_and_metadata() {
+        let board = CaptureBoard::with_name("model");
+        board.open();
+        let x = Tensor::input("x", [2, 3], DType::F32).unwrap();
+        let y = x.relu().unwrap();
+        board.close();
+        board
+            .with_target("y", y.clone())
+            .with_metadata("producer", "hodu-test")
+            .with_node_name(&y, "encoder.act")
+            .with_node_metadata(&y, "source", "model.py:12");
+        let snapshot = board.capture();
+
+        let restored = from_bytes(&to_bytes(&snapshot).unwrap()).unwrap();
+        assert_eq!(restored.metadata.get("producer").map(String::as_str), Some("hodu-test"));
+        let node = restored.find_node("encoder.act").unwrap();
+        assert_eq!(node.metadata.get("source").map(String::as_str), Some("model.py:12"));
+    }
+
+    #[test]
+    fn test_sharded_roundtrip() {
+        let board = CaptureBoard::with_name("sharded");
+        board.open();
+        let x = Tensor::input("x", [1, 4], DType::F32).unwrap();
+        let w1 = Tensor::from_slice((0..16).map(|i| i as f32 * 0.1).collect::<Vec<_>>(), [4, 4]).unwrap();
+        let w2 = Tensor::from_slice((0..16).map(|i| 1.0 - i as f32 * 0.05).collect::<Vec<_>>(), [4, 4]).unwrap();
+        let b = Tensor::from_slice(vec![0.5f32, -0.5, 0.25, -0.25], [4]).unwrap();
+        let y = x.matmul(&w1).unwrap().matmul(&w2).unwrap().add(&b).unwrap();
+        board.close();
+        board.with_target("y", y);
+        let snapshot = board.capture();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.hdss");
+        // Each 4x4 f32 weight fills a 64-byte shard on its own
+        save_sharded(&snapshot, &path, 64).unwrap();
+        assert!(dir.path().join("model.hdss.index.json").exists());
+        assert!(dir.path().join("model-00003-of-00003.hdta").exists());
+
+        let loaded = load(&path).unwrap();
+        assert!(!loaded.metadata.contains_key(WEIGHTS_METADATA_KEY));
+        for (restored, original) in loaded.constants.iter().zip(&snapshot.constants) {
+            assert_eq!(restored.data, original.data);
+        }
+
+        let (lazy, weights) = load_lazy(&path).unwrap();
+        let weights = weights.unwrap();
+        assert_eq!(weights.shard_paths().len(), 3);
+        assert!(lazy.constants.iter().all(SnapshotConstant::is_external));
+
+        let input = Tensor::from_slice(vec![1.0f32, 2.0, 3.0, 4.0], [1, 4]).unwrap();
+        let expected = Interpreter::new(&snapshot).run(&[("x", &input)]).unwrap();
+        let loader = |constant: &SnapshotConstant| weights.get(constant);
+        let outputs = Interpreter::new(&lazy)
+            .constant_loader(&loader)
+            .run(&[("x", &input)])
+            .unwrap();
+        assert_eq!(
+            outputs[0].1.to_flatten_vec::<f32>().unwrap(),
+            expected[0].1.to_flatten_vec::<f32>().unwrap()
+        );
+        assert!(Interpreter::new(&lazy).run(&[("x", &input)]).is_err());
+    }
+
+    /// Written by the last release, before the envelope and the versioned layout: `relu(x * w)`
+    /// for `w = [1, -1, 2, 1]`, followed by its top 2
+    #[test]
+    fn test_load_baseline_snapshot() {
+        let data = include_bytes!("../../testdata/baseline_model.hdss");
+        let snapshot = from_bytes(data).unwrap();
+        assert_eq!(snapshot.name.as_deref(), Some("baseline"));
+        assert!(snapshot.metadata.is_empty() && snapshot.subgraphs.is_empty());
+
+        let resaved = from_bytes(&to_bytes(&snapshot).unwrap()).unwrap();
+        let x = Tensor::from_slice(vec![1.0f32, 2.0, 3.0, 4.0], [1, 4]).unwrap();
+        for snapshot in [&snapshot, &resaved] {
+            let outputs = Interpreter::new(snapshot).run(&[("x", &x)]).unwrap();
+            let names: Vec<&str> = outputs.iter().map(|(name, _)| name.as_str()).collect();
+            assert_eq!(names, ["y", "values", "indices"]);
+            assert_eq!(outputs[0].1.to_flatten_vec::<f32>().unwrap(), vec![1.0, 0.0, 6.0, 4.0]);
+            assert_eq!(outputs[1].1.to_flatten_vec::<f32>().unwrap(), vec![6.0, 4.0]);
+            assert_eq!(outputs[2].1.to_flatten_vec::<i32>().unwrap(), vec![2, 3]);
+        }
+    }
+
+    #[test]
+    fn test_rejects_unknown_format_version() {
+        let payload = postcard::to_allocvec(&(crate::snapshot::FORMAT_VERSION + 1, Snapshot::new())).unwrap();
+        let data = crate::format::envelope::seal(payload, Compression::None).unwrap();
+        let err = from_bytes(&data).unwrap_err().to_string();
+        assert!(err.contains("unsupported snapshot format version"), "{}", err);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_compressed_roundtrip() {
+        let board = CaptureBoard::with_name("compressed");
+        board.open();
+        let x = Tensor::input("x", [1, 256], DType::F32).unwrap();
+        let w = Tensor::full([256, 256], 0.5f32).unwrap();
+        let y = x.matmul(&w).unwrap();
+        board.close();
+        board.with_target("y", y);
+        let snapshot = board.capture();
+
+        let plain = to_bytes(&snapshot).unwrap();
+        let compressed = to_bytes_with(&snapshot, Compression::ZSTD).unwrap();
+        assert!(compressed.len() * 4 < plain.len());
+
+        let restored = from_bytes(&compressed).unwrap();
+        assert_eq!(restored.constants.len(), snapshot.constants.len());
+        assert_eq!(restored.constants[0].data, snapshot.constants[0].data);
+    }
+}
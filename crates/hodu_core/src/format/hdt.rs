@@ -1,13 +1,21 @@
 //! Hodu Tensor (.hdt) format support
 //!
 //! A simple binary format for storing tensors using postcard serialization.
-//! Supports single tensor or named tensor collections.
+//! Supports single tensor or named tensor collections, plus block-quantized weights whose
+//! packed layout is recorded alongside the bytes. Payloads carry a CRC32C checksum verified on
+//! load, and the `_with` variants can zstd-compress them; loading decompresses transparently.
+//! [`Writer`] and [`Reader`] stream a single tensor in chunks for tensors too large to buffer.
 
+mod stream;
+
+use super::envelope::{self, Compression};
 use crate::error::{HoduError, HoduResult};
 use crate::tensor::Tensor;
-use crate::types::{DType, Device, Shape};
+use crate::types::{BlockFormat, DType, Device, Shape};
 use std::collections::HashMap;
 
+pub use stream::{Progress, Reader, Writer, DEFAULT_CHUNK_SIZE};
+
 /// Single tensor data for serialization
 #[derive(serde::Serialize, serde::Deserialize)]
 struct TensorData {
@@ -16,6 +24,16 @@ struct TensorData {
     data: Vec<u8>,
 }
 
+/// Block-quantized tensor data for serialization
+///
+/// `shape` is the logical (unpacked) shape; `data` holds the packed blocks.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PackedTensorData {
+    shape: Vec<usize>,
+    format: BlockFormat,
+    data: Vec<u8>,
+}
+
 /// Multiple named tensors for serialization
 #[derive(serde::Serialize, serde::Deserialize)]
 struct TensorCollection {
@@ -23,6 +41,7 @@ struct TensorCollection {
 }
 
 /// Load a single tensor from .hdt file
+#[cfg(feature = "std")]
 pub fn load(path: impl AsRef<std::path::Path>) -> HoduResult<Tensor> {
     let data =
         std::fs::read(path.as_ref()).map_err(|e| HoduError::IoError(format!("Failed to read hdt file: {}", e)))?;
@@ -30,13 +49,19 @@ pub fn load(path: impl AsRef<std::path::Path>) -> HoduResult<Tensor> {
 }
 
 /// Save a single tensor to .hdt file
+#[cfg(feature = "std")]
 pub fn save(tensor: &Tensor, path: impl AsRef<std::path::Path>) -> HoduResult<()> {
-    let data = serialize(tensor)?;
-    std::fs::write(path.as_ref(), data).map_err(|e| HoduError::IoError(format!("Failed to write hdt file: {}", e)))?;
-    Ok(())
+    save_with(tensor, path, Compression::None)
+}
+
+/// Save a single tensor to .hdt file with the given payload compression
+#[cfg(feature = "std")]
+pub fn save_with(tensor: &Tensor, path: impl AsRef<std::path::Path>, compression: Compression) -> HoduResult<()> {
+    write(path.as_ref(), serialize_with(tensor, compression)?)
 }
 
 /// Load multiple named tensors from .hdt file
+#[cfg(feature = "std")]
 pub fn load_many(path: impl AsRef<std::path::Path>) -> HoduResult<HashMap<String, Tensor>> {
     let data =
         std::fs::read(path.as_ref()).map_err(|e| HoduError::IoError(format!("Failed to read hdt file: {}", e)))?;
@@ -44,34 +69,135 @@ pub fn load_many(path: impl AsRef<std::path::Path>) -> HoduResult<HashMap<String
 }
 
 /// Save multiple named tensors to .hdt file
+#[cfg(feature = "std")]
 pub fn save_many(tensors: &HashMap<String, Tensor>, path: impl AsRef<std::path::Path>) -> HoduResult<()> {
-    let data = serialize_many(tensors)?;
-    std::fs::write(path.as_ref(), data).map_err(|e| HoduError::IoError(format!("Failed to write hdt file: {}", e)))?;
-    Ok(())
+    save_many_with(tensors, path, Compression::None)
+}
+
+/// Save multiple named tensors to .hdt file with the given payload compression
+#[cfg(feature = "std")]
+pub fn save_many_with(
+    tensors: &HashMap<String, Tensor>,
+    path: impl AsRef<std::path::Path>,
+    compression: Compression,
+) -> HoduResult<()> {
+    write(path.as_ref(), serialize_many_with(tensors, compression)?)
+}
+
+/// Load a block-quantized tensor from .hdt file
+///
+/// Returns the packed U8 tensor and its block format.
+#[cfg(feature = "std")]
+pub fn load_packed(path: impl AsRef<std::path::Path>) -> HoduResult<(Tensor, BlockFormat)> {
+    let data =
+        std::fs::read(path.as_ref()).map_err(|e| HoduError::IoError(format!("Failed to read hdt file: {}", e)))?;
+    deserialize_packed(&data)
+}
+
+/// Save a block-quantized tensor to .hdt file
+///
+/// `tensor` is the packed U8 tensor produced by [`Tensor::block_quantize`] with `format`.
+#[cfg(feature = "std")]
+pub fn save_packed(tensor: &Tensor, format: BlockFormat, path: impl AsRef<std::path::Path>) -> HoduResult<()> {
+    save_packed_with(tensor, format, path, Compression::None)
+}
+
+/// Save a block-quantized tensor to .hdt file with the given payload compression
+#[cfg(feature = "std")]
+pub fn save_packed_with(
+    tensor: &Tensor,
+    format: BlockFormat,
+    path: impl AsRef<std::path::Path>,
+    compression: Compression,
+) -> HoduResult<()> {
+    write(path.as_ref(), serialize_packed_with(tensor, format, compression)?)
+}
+
+#[cfg(feature = "std")]
+fn write(path: &std::path::Path, data: Vec<u8>) -> HoduResult<()> {
+    std::fs::write(path, data).map_err(|e| HoduError::IoError(format!("Failed to write hdt file: {}", e)))
 }
 
 /// Serialize a single tensor to bytes
 pub fn serialize(tensor: &Tensor) -> HoduResult<Vec<u8>> {
+    serialize_with(tensor, Compression::None)
+}
+
+/// Serialize a single tensor to bytes with the given payload compression
+pub fn serialize_with(tensor: &Tensor, compression: Compression) -> HoduResult<Vec<u8>> {
     let tensor_data = TensorData {
         shape: tensor.shape().dims().to_vec(),
         dtype: tensor.dtype(),
         data: tensor.to_bytes()?,
     };
-    postcard::to_allocvec(&tensor_data)
-        .map_err(|e| HoduError::SerializationFailed(format!("Failed to serialize tensor: {}", e)))
+    let payload = postcard::to_allocvec(&tensor_data)
+        .map_err(|e| HoduError::SerializationFailed(format!("Failed to serialize tensor: {}", e)))?;
+    envelope::seal(payload, compression)
 }
 
 /// Deserialize a single tensor from bytes
 pub fn deserialize(data: &[u8]) -> HoduResult<Tensor> {
-    let tensor_data: TensorData = postcard::from_bytes(data)
+    let data = envelope::open(data)?;
+    let tensor_data: TensorData = postcard::from_bytes(&data)
         .map_err(|e| HoduError::DeserializationFailed(format!("Failed to deserialize tensor: {}", e)))?;
 
     let shape = Shape::new(&tensor_data.shape);
     Tensor::from_bytes(&tensor_data.data, shape, tensor_data.dtype, Device::CPU)
 }
 
+/// Serialize a block-quantized tensor to bytes
+pub fn serialize_packed(tensor: &Tensor, format: BlockFormat) -> HoduResult<Vec<u8>> {
+    serialize_packed_with(tensor, format, Compression::None)
+}
+
+/// Serialize a block-quantized tensor to bytes with the given payload compression
+pub fn serialize_packed_with(tensor: &Tensor, format: BlockFormat, compression: Compression) -> HoduResult<Vec<u8>> {
+    if tensor.dtype() != DType::U8 {
+        return Err(HoduError::SerializationFailed(format!(
+            "packed {} tensor must be u8, got {}",
+            format,
+            tensor.dtype()
+        )));
+    }
+    let mut shape = tensor.shape().dims().to_vec();
+    if let Some(last) = shape.last_mut() {
+        *last = format.unpacked_dim(*last)?;
+    }
+
+    let tensor_data = PackedTensorData {
+        shape,
+        format,
+        data: tensor.to_bytes()?,
+    };
+    let payload = postcard::to_allocvec(&tensor_data)
+        .map_err(|e| HoduError::SerializationFailed(format!("Failed to serialize packed tensor: {}", e)))?;
+    envelope::seal(payload, compression)
+}
+
+/// Deserialize a block-quantized tensor from bytes
+pub fn deserialize_packed(data: &[u8]) -> HoduResult<(Tensor, BlockFormat)> {
+    let data = envelope::open(data)?;
+    let tensor_data: PackedTensorData = postcard::from_bytes(&data)
+        .map_err(|e| HoduError::DeserializationFailed(format!("Failed to deserialize packed tensor: {}", e)))?;
+
+    let format = tensor_data.format;
+    let mut shape = tensor_data.shape;
+    if let Some(last) = shape.last_mut() {
+        *last = format
+            .packed_dim(*last)
+            .map_err(|e| HoduError::DeserializationFailed(e.to_string()))?;
+    }
+    let tensor = Tensor::from_bytes(&tensor_data.data, Shape::new(&shape), DType::U8, Device::CPU)?;
+    Ok((tensor, format))
+}
+
 /// Serialize multiple named tensors to bytes
 pub fn serialize_many(tensors: &HashMap<String, Tensor>) -> HoduResult<Vec<u8>> {
+    serialize_many_with(tensors, Compression::None)
+}
+
+/// Serialize multiple named tensors to bytes with the given payload compression
+pub fn serialize_many_with(tensors: &HashMap<String, Tensor>, compression: Compression) -> HoduResult<Vec<u8>> {
     let collection = TensorCollection {
         tensors: tensors
             .iter()
@@ -87,13 +213,15 @@ pub fn serialize_many(tensors: &HashMap<String, Tensor>) -> HoduResult<Vec<u8>>
             })
             .collect::<HoduResult<Vec<_>>>()?,
     };
-    postcard::to_allocvec(&collection)
-        .map_err(|e| HoduError::SerializationFailed(format!("Failed to serialize tensors: {}", e)))
+    let payload = postcard::to_allocvec(&collection)
+        .map_err(|e| HoduError::SerializationFailed(format!("Failed to serialize tensors: {}", e)))?;
+    envelope::seal(payload, compression)
 }
 
 /// Deserialize multiple named tensors from bytes
 pub fn deserialize_many(data: &[u8]) -> HoduResult<HashMap<String, Tensor>> {
-    let collection: TensorCollection = postcard::from_bytes(data)
+    let data = envelope::open(data)?;
+    let collection: TensorCollection = postcard::from_bytes(&data)
         .map_err(|e| HoduError::DeserializationFailed(format!("Failed to deserialize tensors: {}", e)))?;
 
     collection
@@ -137,4 +265,54 @@ mod tests {
         assert!(restored.contains_key("a"));
         assert!(restored.contains_key("b"));
     }
+
+    #[test]
+    fn test_serialize_deserialize_packed() {
+        let values: Vec<f32> = (0..128).map(|i| (i as f32 - 64.0) / 16.0).collect();
+        let weight = Tensor::from_slice(values, [2, 64]).unwrap();
+        let packed = weight.block_quantize(BlockFormat::Q4_0).unwrap();
+
+        let data = serialize_packed(&packed, BlockFormat::Q4_0).unwrap();
+        let (restored, format) = deserialize_packed(&data).unwrap();
+
+        assert_eq!(format, BlockFormat::Q4_0);
+        assert_eq!(restored.shape().dims(), &[2, 36]);
+        assert_eq!(restored.to_bytes().unwrap(), packed.to_bytes().unwrap());
+
+        let dequantized = restored.block_dequantize(format, DType::F32).unwrap();
+        assert_eq!(dequantized.shape(), weight.shape());
+    }
+
+    #[test]
+    fn test_corrupted_file_is_rejected() {
+        let tensor = Tensor::from_slice(vec![1.0f32, 2.0, 3.0, 4.0], [2, 2]).unwrap();
+        let mut data = serialize(&tensor).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 0x01;
+
+        let err = deserialize(&data).unwrap_err().to_string();
+        assert!(err.contains("checksum mismatch"), "{}", err);
+    }
+
+    #[cfg(all(feature = "std", feature = "zstd"))]
+    #[test]
+    fn test_save_load_compressed() {
+        let values: Vec<f32> = (0..4096).map(|i| (i % 16) as f32 * 0.25).collect();
+        let tensor = Tensor::from_slice(values.clone(), [64, 64]).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("plain.hdt");
+        let packed = dir.path().join("packed.hdt");
+
+        save(&tensor, &plain).unwrap();
+        save_with(&tensor, &packed, Compression::ZSTD).unwrap();
+        let plain_len = std::fs::metadata(&plain).unwrap().len();
+        let packed_len = std::fs::metadata(&packed).unwrap().len();
+        assert!(packed_len * 4 < plain_len, "{} vs {}", packed_len, plain_len);
+
+        for path in [&plain, &packed] {
+            let restored = load(path).unwrap();
+            assert_eq!(restored.shape().dims(), &[64, 64]);
+            assert_eq!(restored.to_flatten_vec::<f32>().unwrap(), values);
+        }
+    }
 }
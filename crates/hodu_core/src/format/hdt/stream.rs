@@ -0,0 +1,378 @@
+//! Chunked .hdt reading and writing
+//!
+//! [`Writer`] and [`Reader`] move the tensor data of a single-tensor .hdt file through the
+//! caller's buffers a chunk at a time, so tensors far larger than memory can be converted without
+//! materializing them. The files are ordinary uncompressed, unencrypted, checksummed .hdt files.
+
+use super::TensorData;
+use crate::error::{HoduError, HoduResult};
+use crate::format::envelope::{
+    is_encrypting, is_verifying_checksums, FLAG_AES_GCM, FLAG_CRC32C, FLAG_ZSTD, KNOWN_FLAGS, MAGIC,
+};
+use crate::tensor::Tensor;
+use crate::types::{DType, Device, Shape};
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::{BufReader, BufWriter};
+use std::io::{Read, Seek, SeekFrom, Write};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+/// Chunk size used by [`Writer::write_tensor`] and [`Reader::read_tensor`]
+pub const DEFAULT_CHUNK_SIZE: usize = 8 << 20;
+
+/// Called with the bytes of tensor data transferred so far and the total
+pub type Progress = dyn FnMut(u64, u64) + Send;
+
+/// Writes a single tensor to .hdt chunk by chunk
+///
+/// The checksum is patched into the header by [`Writer::finish`], which must be called once all
+/// `shape.size() * dtype.size_in_bytes()` bytes have been written.
+pub struct Writer<W: Write + Seek> {
+    inner: W,
+    dtype: DType,
+    checksum_pos: u64,
+    crc: u32,
+    written: u64,
+    total: u64,
+    progress: Option<Box<Progress>>,
+}
+
+#[cfg(feature = "std")]
+impl Writer<BufWriter<File>> {
+    /// Create a .hdt file for a tensor of the given shape and dtype
+    pub fn create(path: impl AsRef<Path>, shape: impl Into<Shape>, dtype: DType) -> HoduResult<Self> {
+        let file =
+            File::create(path.as_ref()).map_err(|e| HoduError::IoError(format!("Failed to create hdt file: {}", e)))?;
+        Self::new(BufWriter::new(file), shape, dtype)
+    }
+}
+
+impl<W: Write + Seek> Writer<W> {
+    /// Start writing a tensor of the given shape and dtype to `inner`
+    pub fn new(mut inner: W, shape: impl Into<Shape>, dtype: DType) -> HoduResult<Self> {
+        if is_encrypting() {
+            return Err(HoduError::UnsupportedOperation(
+                "streamed hdt files can't be encrypted; use hdt::save".to_string(),
+            ));
+        }
+        let shape = shape.into();
+        let total = (shape.size() * dtype.size_in_bytes()) as u64;
+
+        // The postcard prefix of the body, up to and including the length of the data bytes
+        let mut prefix = postcard::to_allocvec(&TensorData {
+            shape: shape.dims().to_vec(),
+            dtype,
+            data: Vec::new(),
+        })
+        .map_err(|e| HoduError::SerializationFailed(format!("Failed to serialize tensor header: {}", e)))?;
+        prefix.pop();
+        prefix.extend(
+            postcard::to_allocvec(&(total as usize))
+                .map_err(|e| HoduError::SerializationFailed(format!("Failed to serialize tensor header: {}", e)))?,
+        );
+
+        let start = inner.stream_position().map_err(write_err)?;
+        inner.write_all(MAGIC).map_err(write_err)?;
+        inner.write_all(&[FLAG_CRC32C]).map_err(write_err)?;
+        inner.write_all(&[0; 4]).map_err(write_err)?;
+        inner.write_all(&prefix).map_err(write_err)?;
+
+        Ok(Self {
+            inner,
+            dtype,
+            checksum_pos: start + MAGIC.len() as u64 + 1,
+            crc: crc32c::crc32c(&prefix),
+            written: 0,
+            total,
+            progress: None,
+        })
+    }
+
+    /// Call `progress` after every chunk
+    pub fn on_progress(mut self, progress: impl FnMut(u64, u64) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Bytes of tensor data still to be written
+    pub fn bytes_remaining(&self) -> u64 {
+        self.total - self.written
+    }
+
+    /// Append the next chunk of raw little-endian tensor data
+    pub fn write_chunk(&mut self, chunk: &[u8]) -> HoduResult<()> {
+        if chunk.len() as u64 > self.bytes_remaining() {
+            return Err(HoduError::InvalidArgument(format!(
+                "chunk of {} bytes overflows the tensor data ({} bytes remaining)",
+                chunk.len(),
+                self.bytes_remaining()
+            )));
+        }
+        self.inner.write_all(chunk).map_err(write_err)?;
+        self.crc = crc32c::crc32c_append(self.crc, chunk);
+        self.written += chunk.len() as u64;
+        if let Some(progress) = &mut self.progress {
+            progress(self.written, self.total);
+        }
+        Ok(())
+    }
+
+    /// Append the data of `tensor` in [`DEFAULT_CHUNK_SIZE`] chunks
+    ///
+    /// Several tensors can be written one after another, e.g. the row blocks of a larger tensor.
+    pub fn write_tensor(&mut self, tensor: &Tensor) -> HoduResult<()> {
+        if tensor.dtype() != self.dtype {
+            return Err(HoduError::InvalidArgument(format!(
+                "expected a {} tensor, got {}",
+                self.dtype,
+                tensor.dtype()
+            )));
+        }
+        for chunk in tensor.to_bytes()?.chunks(DEFAULT_CHUNK_SIZE) {
+            self.write_chunk(chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Write the checksum and return the underlying writer
+    pub fn finish(mut self) -> HoduResult<W> {
+        if self.written != self.total {
+            return Err(HoduError::InvalidArgument(format!(
+                "tensor data incomplete: wrote {} of {} bytes",
+                self.written, self.total
+            )));
+        }
+        let end = self.inner.stream_position().map_err(write_err)?;
+        self.inner.seek(SeekFrom::Start(self.checksum_pos)).map_err(write_err)?;
+        self.inner.write_all(&self.crc.to_le_bytes()).map_err(write_err)?;
+        self.inner.seek(SeekFrom::Start(end)).map_err(write_err)?;
+        self.inner.flush().map_err(write_err)?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads a single tensor from .hdt chunk by chunk
+///
+/// The checksum is verified once the last chunk has been read.
+pub struct Reader<R: Read> {
+    inner: R,
+    shape: Shape,
+    dtype: DType,
+    checksum: Option<u32>,
+    crc: u32,
+    read: u64,
+    total: u64,
+    progress: Option<Box<Progress>>,
+}
+
+#[cfg(feature = "std")]
+impl Reader<BufReader<File>> {
+    /// Open a .hdt file and read its header
+    pub fn open(path: impl AsRef<Path>) -> HoduResult<Self> {
+        let file =
+            File::open(path.as_ref()).map_err(|e| HoduError::IoError(format!("Failed to open hdt file: {}", e)))?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> Reader<R> {
+    /// Read the header of a .hdt tensor from `inner`
+    pub fn new(inner: R) -> HoduResult<Self> {
+        let mut reader = Self {
+            inner,
+            shape: Shape::new(&[]),
+            dtype: DType::U8,
+            checksum: None,
+            crc: 0,
+            read: 0,
+            total: 0,
+            progress: None,
+        };
+
+        let mut header = [0u8; 5];
+        reader.inner.read_exact(&mut header).map_err(read_err)?;
+        if &header[..4] != MAGIC {
+            return Err(HoduError::DeserializationFailed(
+                "hdt file has no header (written by an older version) and can't be streamed; use hdt::load".to_string(),
+            ));
+        }
+        let flags = header[4];
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(HoduError::DeserializationFailed(format!(
+                "unknown header flags 0x{:02x}",
+                flags
+            )));
+        }
+        if flags & (FLAG_ZSTD | FLAG_AES_GCM) != 0 {
+            return Err(HoduError::UnsupportedOperation(
+                "compressed or encrypted hdt files can't be streamed; use hdt::load".to_string(),
+            ));
+        }
+        if flags & FLAG_CRC32C != 0 {
+            let mut checksum = [0u8; 4];
+            reader.inner.read_exact(&mut checksum).map_err(read_err)?;
+            reader.checksum = Some(u32::from_le_bytes(checksum));
+        }
+
+        let rank = reader.read_varint()? as usize;
+        let dims = (0..rank)
+            .map(|_| reader.read_varint().map(|d| d as usize))
+            .collect::<HoduResult<Vec<_>>>()?;
+        let mut dtype = [0u8; 1];
+        reader.read_body(&mut dtype)?;
+        reader.dtype = postcard::from_bytes(&dtype)
+            .map_err(|e| HoduError::DeserializationFailed(format!("Invalid tensor dtype: {}", e)))?;
+        reader.shape = Shape::new(&dims);
+        reader.total = reader.read_varint()?;
+
+        let expected = (reader.shape.size() * reader.dtype.size_in_bytes()) as u64;
+        if reader.total != expected {
+            return Err(HoduError::DeserializationFailed(format!(
+                "tensor data is {} bytes, expected {} for {:?} {}",
+                reader.total, expected, dims, reader.dtype
+            )));
+        }
+        Ok(reader)
+    }
+
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    pub fn dtype(&self) -> DType {
+        self.dtype
+    }
+
+    /// Bytes of tensor data still to be read
+    pub fn bytes_remaining(&self) -> u64 {
+        self.total - self.read
+    }
+
+    /// Call `progress` after every chunk
+    pub fn on_progress(mut self, progress: impl FnMut(u64, u64) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Fill `buf` with the next chunk of raw little-endian tensor data
+    ///
+    /// Returns the number of bytes read, which is less than `buf.len()` only for the last chunk
+    /// and zero once all data has been read.
+    pub fn read_chunk(&mut self, buf: &mut [u8]) -> HoduResult<usize> {
+        let len = buf.len().min(self.bytes_remaining() as usize);
+        if len == 0 {
+            return Ok(0);
+        }
+        self.read_body(&mut buf[..len])?;
+        self.read += len as u64;
+        if let Some(progress) = &mut self.progress {
+            progress(self.read, self.total);
+        }
+
+        if self.bytes_remaining() == 0 && is_verifying_checksums() {
+            if let Some(expected) = self.checksum.filter(|&expected| expected != self.crc) {
+                return Err(HoduError::DeserializationFailed(format!(
+                    "checksum mismatch (expected {:08x}, got {:08x}): the file is corrupted or truncated",
+                    expected, self.crc
+                )));
+            }
+        }
+        Ok(len)
+    }
+
+    /// Read the remaining data in [`DEFAULT_CHUNK_SIZE`] chunks into a CPU tensor
+    pub fn read_tensor(mut self) -> HoduResult<Tensor> {
+        let mut data = vec![0u8; self.bytes_remaining() as usize];
+        for chunk in data.chunks_mut(DEFAULT_CHUNK_SIZE) {
+            self.read_chunk(chunk)?;
+        }
+        Tensor::from_bytes(&data, self.shape.clone(), self.dtype, Device::CPU)
+    }
+
+    fn read_body(&mut self, buf: &mut [u8]) -> HoduResult<()> {
+        self.inner.read_exact(buf).map_err(read_err)?;
+        self.crc = crc32c::crc32c_append(self.crc, buf);
+        Ok(())
+    }
+
+    /// Read a postcard (LEB128) varint
+    fn read_varint(&mut self) -> HoduResult<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let mut byte = [0u8; 1];
+            self.read_body(&mut byte)?;
+            value |= u64::from(byte[0] & 0x7f) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(HoduError::DeserializationFailed(
+            "invalid varint in hdt header".to_string(),
+        ))
+    }
+}
+
+fn write_err(e: std::io::Error) -> HoduError {
+    HoduError::IoError(format!("Failed to write hdt file: {}", e))
+}
+
+fn read_err(e: std::io::Error) -> HoduError {
+    HoduError::IoError(format!("Failed to read hdt file: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_chunked_write_loads_with_hdt() {
+        let values: Vec<f32> = (0..300).map(|i| i as f32 * 0.5).collect();
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()), [3, 100], DType::F32).unwrap();
+        for chunk in bytes.chunks(128) {
+            writer.write_chunk(chunk).unwrap();
+        }
+        assert!(writer.write_chunk(&[0]).is_err());
+        let data = writer.finish().unwrap().into_inner();
+
+        let tensor = super::super::deserialize(&data).unwrap();
+        assert_eq!(tensor.shape().dims(), &[3, 100]);
+        assert_eq!(tensor.to_flatten_vec::<f32>().unwrap(), values);
+    }
+
+    #[test]
+    fn test_chunked_read_of_saved_tensor() {
+        let values: Vec<i32> = (0..1000).collect();
+        let tensor = Tensor::from_slice(values.clone(), [10, 100]).unwrap();
+        let data = super::super::serialize(&tensor).unwrap();
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let mut reader = Reader::new(Cursor::new(&data))
+            .unwrap()
+            .on_progress(move |done, total| log.lock().unwrap().push((done, total)));
+        assert_eq!(reader.shape().dims(), &[10, 100]);
+        assert_eq!(reader.dtype(), DType::I32);
+
+        let mut restored = Vec::new();
+        let mut buf = [0u8; 1536];
+        loop {
+            let n = reader.read_chunk(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            restored.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(restored, tensor.to_bytes().unwrap());
+        assert_eq!(seen.lock().unwrap().last(), Some(&(4000, 4000)));
+
+        let mut corrupted = data.clone();
+        *corrupted.last_mut().unwrap() ^= 0x01;
+        assert!(Reader::new(Cursor::new(&corrupted)).unwrap().read_tensor().is_err());
+    }
+}
@@ -0,0 +1,363 @@
+//! Hodu Tensor Archive (.hdta) format support
+//!
+//! Many named tensors in one file: the raw bytes of each tensor one after another, followed by a
+//! postcard index of names, shapes, dtypes and byte ranges, and a fixed-size footer locating the
+//! index. [`Archive`] reads the index once and loads entries by name without touching the rest of
+//! the file, and [`append`] adds entries by rewriting only the index. The index and every entry
+//! carry a CRC32C checksum, verified on load unless turned off with
+//! [`set_verify_checksums`](super::set_verify_checksums).
+
+use super::envelope::is_verifying_checksums;
+use crate::error::{HoduError, HoduResult};
+use crate::tensor::Tensor;
+use crate::types::{DType, Device, Shape};
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::fs::{File, OpenOptions};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"HDTA";
+const VERSION: u32 = 2;
+const HEADER_LEN: u64 = 8;
+const FOOTER_MAGIC: &[u8; 8] = b"HDTAINDX";
+const FOOTER_LEN: u64 = 28;
+
+/// Index entry describing one tensor stored in an archive
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub shape: Vec<usize>,
+    pub dtype: DType,
+    offset: u64,
+    len: u64,
+    checksum: u32,
+}
+
+impl ArchiveEntry {
+    /// Size of the stored tensor data in bytes
+    pub fn size_in_bytes(&self) -> usize {
+        self.len as usize
+    }
+}
+
+/// An open .hdta file whose entries load individually by name
+#[cfg(feature = "std")]
+pub struct Archive {
+    file: File,
+    entries: Vec<ArchiveEntry>,
+}
+
+#[cfg(feature = "std")]
+impl Archive {
+    /// Open an archive and read its index
+    pub fn open(path: impl AsRef<Path>) -> HoduResult<Self> {
+        let mut file = File::open(path.as_ref()).map_err(io_err("Failed to open hdta file"))?;
+        let (entries, _) = read_index(&mut file)?;
+        Ok(Self { file, entries })
+    }
+
+    /// Entries in storage order
+    pub fn entries(&self) -> &[ArchiveEntry] {
+        &self.entries
+    }
+
+    /// Look up an entry by name
+    pub fn entry(&self, name: &str) -> Option<&ArchiveEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Whether the archive holds a tensor with this name
+    pub fn contains(&self, name: &str) -> bool {
+        self.entry(name).is_some()
+    }
+
+    /// Load a single tensor by name, reading only its bytes
+    pub fn get(&self, name: &str) -> HoduResult<Tensor> {
+        let entry = self
+            .entry(name)
+            .ok_or_else(|| HoduError::InvalidArgument(format!("hdta archive has no tensor named '{}'", name)))?;
+        read_entry(&mut &self.file, entry)
+    }
+
+    /// Load every tensor in the archive
+    pub fn load_all(&self) -> HoduResult<HashMap<String, Tensor>> {
+        self.entries
+            .iter()
+            .map(|entry| Ok((entry.name.clone(), read_entry(&mut &self.file, entry)?)))
+            .collect()
+    }
+}
+
+/// Load all named tensors from .hdta file
+#[cfg(feature = "std")]
+pub fn load(path: impl AsRef<Path>) -> HoduResult<HashMap<String, Tensor>> {
+    Archive::open(path)?.load_all()
+}
+
+/// Load only the named tensors from .hdta file
+///
+/// Fails if any of `names` is missing from the archive.
+#[cfg(feature = "std")]
+pub fn load_only(path: impl AsRef<Path>, names: &[&str]) -> HoduResult<HashMap<String, Tensor>> {
+    let archive = Archive::open(path)?;
+    names
+        .iter()
+        .map(|&name| Ok((name.to_string(), archive.get(name)?)))
+        .collect()
+}
+
+/// Save named tensors to a new .hdta file, replacing any existing file
+#[cfg(feature = "std")]
+pub fn save(tensors: &HashMap<String, Tensor>, path: impl AsRef<Path>) -> HoduResult<()> {
+    let data = serialize(tensors)?;
+    std::fs::write(path.as_ref(), data).map_err(io_err("Failed to write hdta file"))
+}
+
+/// Append named tensors to a .hdta file, creating it if it doesn't exist
+///
+/// Existing entries with the same name are replaced in the index; their old bytes stay in the
+/// file until it is rewritten with [`save`].
+#[cfg(feature = "std")]
+pub fn append(tensors: &HashMap<String, Tensor>, path: impl AsRef<Path>) -> HoduResult<()> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return save(tensors, path);
+    }
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(io_err("Failed to open hdta file"))?;
+    let (mut entries, index_offset) = read_index(&mut file)?;
+    entries.retain(|entry| !tensors.contains_key(&entry.name));
+
+    file.seek(SeekFrom::Start(index_offset))
+        .map_err(io_err("Failed to seek hdta file"))?;
+    let end = write_archive_tail(&mut file, tensors, entries, index_offset)?;
+    file.set_len(end).map_err(io_err("Failed to write hdta file"))
+}
+
+/// Serialize named tensors to archive bytes
+pub fn serialize(tensors: &HashMap<String, Tensor>) -> HoduResult<Vec<u8>> {
+    let mut data = Vec::new();
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&VERSION.to_le_bytes());
+    write_archive_tail(&mut data, tensors, Vec::new(), HEADER_LEN)?;
+    Ok(data)
+}
+
+/// Deserialize all named tensors from archive bytes
+pub fn deserialize(data: &[u8]) -> HoduResult<HashMap<String, Tensor>> {
+    let mut cursor = Cursor::new(data);
+    let (entries, _) = read_index(&mut cursor)?;
+    entries
+        .iter()
+        .map(|entry| Ok((entry.name.clone(), read_entry(&mut cursor, entry)?)))
+        .collect()
+}
+
+/// Write tensor data starting at `offset`, then the index of `entries` plus the new tensors and
+/// the footer; returns the offset just past the footer
+fn write_archive_tail<W: Write>(
+    writer: &mut W,
+    tensors: &HashMap<String, Tensor>,
+    mut entries: Vec<ArchiveEntry>,
+    mut offset: u64,
+) -> HoduResult<u64> {
+    // Sorted so the same tensors always produce the same archive
+    let mut names: Vec<&String> = tensors.keys().collect();
+    names.sort();
+
+    for name in names {
+        let tensor = &tensors[name];
+        let bytes = tensor.to_bytes()?;
+        writer
+            .write_all(&bytes)
+            .map_err(|e| HoduError::IoError(format!("Failed to write hdta entry '{}': {}", name, e)))?;
+        entries.push(ArchiveEntry {
+            name: name.clone(),
+            shape: tensor.shape().dims().to_vec(),
+            dtype: tensor.dtype(),
+            offset,
+            len: bytes.len() as u64,
+            checksum: crc32c::crc32c(&bytes),
+        });
+        offset += bytes.len() as u64;
+    }
+
+    let index = postcard::to_allocvec(&entries)
+        .map_err(|e| HoduError::SerializationFailed(format!("Failed to serialize hdta index: {}", e)))?;
+    let mut footer = Vec::with_capacity(FOOTER_LEN as usize);
+    footer.extend_from_slice(&offset.to_le_bytes());
+    footer.extend_from_slice(&(index.len() as u64).to_le_bytes());
+    footer.extend_from_slice(&crc32c::crc32c(&index).to_le_bytes());
+    footer.extend_from_slice(FOOTER_MAGIC);
+
+    writer.write_all(&index).map_err(io_err("Failed to write hdta index"))?;
+    writer
+        .write_all(&footer)
+        .map_err(io_err("Failed to write hdta index"))?;
+    Ok(offset + index.len() as u64 + FOOTER_LEN)
+}
+
+/// Read the index, returning its entries and the offset where the index starts
+fn read_index<R: Read + Seek>(reader: &mut R) -> HoduResult<(Vec<ArchiveEntry>, u64)> {
+    let invalid = |msg: &str| HoduError::DeserializationFailed(format!("Invalid hdta file: {}", msg));
+
+    let file_len = reader
+        .seek(SeekFrom::End(0))
+        .map_err(io_err("Failed to seek hdta file"))?;
+    if file_len < HEADER_LEN + FOOTER_LEN {
+        return Err(invalid("file too short"));
+    }
+
+    let mut header = [0u8; HEADER_LEN as usize];
+    reader
+        .seek(SeekFrom::Start(0))
+        .map_err(io_err("Failed to seek hdta file"))?;
+    reader
+        .read_exact(&mut header)
+        .map_err(io_err("Failed to read hdta file"))?;
+    if &header[..4] != MAGIC {
+        return Err(invalid("bad magic"));
+    }
+    let version = u32::from_le_bytes(header[4..].try_into().unwrap());
+    if version != VERSION {
+        return Err(invalid(&format!("unsupported version {}", version)));
+    }
+
+    let mut footer = [0u8; FOOTER_LEN as usize];
+    reader
+        .seek(SeekFrom::Start(file_len - FOOTER_LEN))
+        .map_err(io_err("Failed to seek hdta file"))?;
+    reader
+        .read_exact(&mut footer)
+        .map_err(io_err("Failed to read hdta file"))?;
+    if &footer[20..] != FOOTER_MAGIC {
+        return Err(invalid("missing index footer"));
+    }
+    let index_offset = u64::from_le_bytes(footer[..8].try_into().unwrap());
+    let index_len = u64::from_le_bytes(footer[8..16].try_into().unwrap());
+    let index_checksum = u32::from_le_bytes(footer[16..20].try_into().unwrap());
+    if index_offset < HEADER_LEN || index_offset.checked_add(index_len) != Some(file_len - FOOTER_LEN) {
+        return Err(invalid("index out of range"));
+    }
+
+    let mut index = vec![0u8; index_len as usize];
+    reader
+        .seek(SeekFrom::Start(index_offset))
+        .map_err(io_err("Failed to seek hdta file"))?;
+    reader
+        .read_exact(&mut index)
+        .map_err(io_err("Failed to read hdta index"))?;
+    verify_checksum(&index, index_checksum, "index")?;
+    let entries: Vec<ArchiveEntry> = postcard::from_bytes(&index)
+        .map_err(|e| HoduError::DeserializationFailed(format!("Failed to deserialize hdta index: {}", e)))?;
+
+    for entry in &entries {
+        if entry.offset < HEADER_LEN || entry.offset.checked_add(entry.len).is_none_or(|end| end > index_offset) {
+            return Err(invalid(&format!("entry '{}' out of range", entry.name)));
+        }
+    }
+    Ok((entries, index_offset))
+}
+
+fn read_entry<R: Read + Seek>(reader: &mut R, entry: &ArchiveEntry) -> HoduResult<Tensor> {
+    let mut data = vec![0u8; entry.len as usize];
+    reader
+        .seek(SeekFrom::Start(entry.offset))
+        .map_err(io_err("Failed to seek hdta file"))?;
+    reader
+        .read_exact(&mut data)
+        .map_err(|e| HoduError::IoError(format!("Failed to read hdta entry '{}': {}", entry.name, e)))?;
+    verify_checksum(&data, entry.checksum, &format!("entry '{}'", entry.name))?;
+    Tensor::from_bytes(&data, Shape::new(&entry.shape), entry.dtype, Device::CPU)
+        .map_err(|e| HoduError::DeserializationFailed(format!("Invalid hdta entry '{}': {}", entry.name, e)))
+}
+
+fn verify_checksum(data: &[u8], expected: u32, what: &str) -> HoduResult<()> {
+    if !is_verifying_checksums() {
+        return Ok(());
+    }
+    let actual = crc32c::crc32c(data);
+    if actual != expected {
+        return Err(HoduError::DeserializationFailed(format!(
+            "hdta {} checksum mismatch (expected {:08x}, got {:08x}): the file is corrupted",
+            what, expected, actual
+        )));
+    }
+    Ok(())
+}
+
+fn io_err(context: &'static str) -> impl Fn(std::io::Error) -> HoduError {
+    move |e| HoduError::IoError(format!("{}: {}", context, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> HashMap<String, Tensor> {
+        let mut tensors = HashMap::new();
+        tensors.insert("a".to_string(), Tensor::from_slice(vec![1.0f32, 2.0], [2]).unwrap());
+        tensors.insert(
+            "b".to_string(),
+            Tensor::from_slice(vec![3i32, 4, 5, 6], [2, 2]).unwrap(),
+        );
+        tensors
+    }
+
+    #[test]
+    fn test_serialize_deserialize() {
+        let restored = deserialize(&serialize(&sample()).unwrap()).unwrap();
+
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored["a"].to_flatten_vec::<f32>().unwrap(), vec![1.0, 2.0]);
+        assert_eq!(restored["b"].shape().dims(), &[2, 2]);
+        assert_eq!(restored["b"].to_flatten_vec::<i32>().unwrap(), vec![3, 4, 5, 6]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_append_and_load_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("weights.hdta");
+        save(&sample(), &path).unwrap();
+
+        let mut more = HashMap::new();
+        more.insert(
+            "a".to_string(),
+            Tensor::from_slice(vec![7.0f32, 8.0, 9.0], [3]).unwrap(),
+        );
+        more.insert("c".to_string(), Tensor::from_slice(vec![true, false], [2]).unwrap());
+        append(&more, &path).unwrap();
+
+        let archive = Archive::open(&path).unwrap();
+        let names: Vec<&str> = archive.entries().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "a", "c"]);
+        assert_eq!(archive.entry("b").unwrap().size_in_bytes(), 16);
+        assert_eq!(
+            archive.get("a").unwrap().to_flatten_vec::<f32>().unwrap(),
+            vec![7.0, 8.0, 9.0]
+        );
+        assert!(archive.get("missing").is_err());
+
+        let partial = load_only(&path, &["b", "c"]).unwrap();
+        assert_eq!(partial.len(), 2);
+        assert_eq!(partial["c"].to_flatten_vec::<bool>().unwrap(), vec![true, false]);
+    }
+
+    #[test]
+    fn test_rejects_truncated_or_corrupted_file() {
+        let data = serialize(&sample()).unwrap();
+        assert!(deserialize(&data[..data.len() - 1]).is_err());
+
+        let mut corrupted = data.clone();
+        corrupted[HEADER_LEN as usize] ^= 0x01;
+        let err = deserialize(&corrupted).unwrap_err().to_string();
+        assert!(err.contains("checksum mismatch"), "{}", err);
+    }
+}
@@ -0,0 +1,277 @@
+//! Image decoding (`image` feature)
+//!
+//! Decodes PNG and JPEG images into [height, width, channels] (HWC) or [channels, height, width]
+//! (CHW) tensors. u8 tensors hold the raw pixel values; float tensors are scaled to [0, 1] and
+//! then optionally normalized per channel.
+
+use crate::error::{HoduError, HoduResult};
+use crate::tensor::Tensor;
+use crate::types::DType;
+use ::image::imageops::FilterType;
+use ::image::DynamicImage;
+
+/// Channels to decode an image into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Channels {
+    Gray,
+    #[default]
+    Rgb,
+    Rgba,
+}
+
+impl Channels {
+    pub fn count(&self) -> usize {
+        match self {
+            Self::Gray => 1,
+            Self::Rgb => 3,
+            Self::Rgba => 4,
+        }
+    }
+}
+
+/// Dimension order of a decoded image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageLayout {
+    /// [height, width, channels]
+    #[default]
+    HWC,
+    /// [channels, height, width]
+    CHW,
+}
+
+/// Per-channel `(x - mean) / std` applied to [0, 1]-scaled pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalize {
+    pub mean: Vec<f32>,
+    pub std: Vec<f32>,
+}
+
+impl Normalize {
+    /// The RGB statistics of ImageNet, expected by most pretrained vision models
+    pub fn imagenet() -> Self {
+        Self {
+            mean: vec![0.485, 0.456, 0.406],
+            std: vec![0.229, 0.224, 0.225],
+        }
+    }
+}
+
+/// Options for decoding images
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageOptions {
+    pub channels: Channels,
+    pub layout: ImageLayout,
+    /// u8 keeps pixel values; float dtypes scale them to [0, 1]
+    pub dtype: DType,
+    /// Resize to (width, height) before conversion
+    pub resize: Option<(u32, u32)>,
+    /// Normalization of float tensors
+    pub normalize: Option<Normalize>,
+}
+
+impl ImageOptions {
+    pub const DEFAULT: Self = Self {
+        channels: Channels::Rgb,
+        layout: ImageLayout::HWC,
+        dtype: DType::U8,
+        resize: None,
+        normalize: None,
+    };
+
+    pub fn with_channels(mut self, channels: Channels) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    pub fn with_layout(mut self, layout: ImageLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn with_dtype(mut self, dtype: DType) -> Self {
+        self.dtype = dtype;
+        self
+    }
+
+    pub fn with_resize(mut self, width: u32, height: u32) -> Self {
+        self.resize = Some((width, height));
+        self
+    }
+
+    pub fn with_normalize(mut self, normalize: Normalize) -> Self {
+        self.normalize = Some(normalize);
+        self
+    }
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Load an image as an HWC RGB u8 tensor
+#[cfg(feature = "std")]
+pub fn load(path: impl AsRef<std::path::Path>) -> HoduResult<Tensor> {
+    load_with(path, &ImageOptions::DEFAULT)
+}
+
+/// Load an image from a .png or .jpeg file
+#[cfg(feature = "std")]
+pub fn load_with(path: impl AsRef<std::path::Path>, options: &ImageOptions) -> HoduResult<Tensor> {
+    let data =
+        std::fs::read(path.as_ref()).map_err(|e| HoduError::IoError(format!("Failed to read image file: {}", e)))?;
+    deserialize_with(&data, options)
+}
+
+/// Decode PNG or JPEG bytes as an HWC RGB u8 tensor
+pub fn deserialize(data: &[u8]) -> HoduResult<Tensor> {
+    deserialize_with(data, &ImageOptions::DEFAULT)
+}
+
+/// Decode PNG or JPEG bytes
+pub fn deserialize_with(data: &[u8], options: &ImageOptions) -> HoduResult<Tensor> {
+    if options.dtype != DType::U8 && !options.dtype.is_float() {
+        return Err(HoduError::InvalidArgument(format!(
+            "images decode to u8 or float tensors, not {}",
+            options.dtype
+        )));
+    }
+    let channels = options.channels.count();
+    if let Some(normalize) = &options.normalize {
+        if options.dtype == DType::U8 {
+            return Err(HoduError::InvalidArgument(
+                "normalization needs a float dtype".to_string(),
+            ));
+        }
+        for stats in [&normalize.mean, &normalize.std] {
+            if stats.len() != 1 && stats.len() != channels {
+                return Err(HoduError::InvalidArgument(format!(
+                    "normalization needs 1 or {} values per statistic, got {}",
+                    channels,
+                    stats.len()
+                )));
+            }
+        }
+    }
+
+    let mut image = ::image::load_from_memory(data)
+        .map_err(|e| HoduError::DeserializationFailed(format!("Failed to decode image: {}", e)))?;
+    if let Some((width, height)) = options.resize {
+        if (width, height) != (image.width(), image.height()) {
+            image = image.resize_exact(width, height, FilterType::Triangle);
+        }
+    }
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let pixels = match options.channels {
+        Channels::Gray => DynamicImage::into_luma8(image).into_raw(),
+        Channels::Rgb => DynamicImage::into_rgb8(image).into_raw(),
+        Channels::Rgba => DynamicImage::into_rgba8(image).into_raw(),
+    };
+
+    let shape = match options.layout {
+        ImageLayout::HWC => [height, width, channels],
+        ImageLayout::CHW => [channels, height, width],
+    };
+
+    if options.dtype == DType::U8 {
+        return Tensor::from_slice(arrange(pixels, channels, options.layout), shape);
+    }
+
+    let values: Vec<f32> = pixels
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let value = value as f32 / 255.0;
+            match &options.normalize {
+                Some(normalize) => {
+                    let c = i % channels;
+                    let mean = normalize.mean[c.min(normalize.mean.len() - 1)];
+                    let std = normalize.std[c.min(normalize.std.len() - 1)];
+                    (value - mean) / std
+                },
+                None => value,
+            }
+        })
+        .collect();
+    let values = arrange(values, channels, options.layout);
+    let tensor = Tensor::from_slice(values, shape)?;
+    if options.dtype == DType::F32 {
+        Ok(tensor)
+    } else {
+        tensor.to_dtype(options.dtype)
+    }
+}
+
+/// Reorder interleaved (HWC) pixel values into `layout`
+fn arrange<T: Copy>(hwc: Vec<T>, channels: usize, layout: ImageLayout) -> Vec<T> {
+    match layout {
+        ImageLayout::HWC => hwc,
+        ImageLayout::CHW => (0..channels)
+            .flat_map(|c| hwc.iter().skip(c).step_by(channels).copied())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::{ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    /// A 3x2 image whose pixel (x, y) is (x, y, 100 + 10 * x + y)
+    fn encode(format: ImageFormat) -> Vec<u8> {
+        let image = RgbImage::from_fn(3, 2, |x, y| Rgb([x as u8, y as u8, 100 + 10 * x as u8 + y as u8]));
+        let mut data = Cursor::new(Vec::new());
+        image.write_to(&mut data, format).unwrap();
+        data.into_inner()
+    }
+
+    #[test]
+    fn test_decode_hwc_and_chw() {
+        let data = encode(ImageFormat::Png);
+        let hwc = deserialize(&data).unwrap();
+        assert_eq!(hwc.dtype(), DType::U8);
+        assert_eq!(hwc.shape().dims(), &[2, 3, 3]);
+        assert_eq!(&hwc.to_flatten_vec::<u8>().unwrap()[..6], &[0, 0, 100, 1, 0, 110]);
+
+        let chw = deserialize_with(&data, &ImageOptions::default().with_layout(ImageLayout::CHW)).unwrap();
+        assert_eq!(chw.shape().dims(), &[3, 2, 3]);
+        let values = chw.to_flatten_vec::<u8>().unwrap();
+        assert_eq!(&values[..6], &[0, 1, 2, 0, 1, 2]);
+        assert_eq!(&values[12..], &[100, 110, 120, 101, 111, 121]);
+
+        let gray = deserialize_with(&data, &ImageOptions::default().with_channels(Channels::Gray)).unwrap();
+        assert_eq!(gray.shape().dims(), &[2, 3, 1]);
+    }
+
+    #[test]
+    fn test_resize_and_normalize() {
+        let data = encode(ImageFormat::Jpeg);
+        let options = ImageOptions::default()
+            .with_dtype(DType::F32)
+            .with_resize(8, 4)
+            .with_layout(ImageLayout::CHW);
+        let tensor = deserialize_with(&data, &options).unwrap();
+        assert_eq!(tensor.shape().dims(), &[3, 4, 8]);
+        assert!(tensor
+            .to_flatten_vec::<f32>()
+            .unwrap()
+            .iter()
+            .all(|v| (0.0..=1.0).contains(v)));
+
+        let normalize = Normalize {
+            mean: vec![0.5],
+            std: vec![0.5],
+        };
+        let tensor = deserialize_with(&data, &options.clone().with_normalize(normalize)).unwrap();
+        assert!(tensor
+            .to_flatten_vec::<f32>()
+            .unwrap()
+            .iter()
+            .all(|v| (-1.0..=1.0).contains(v)));
+
+        assert!(deserialize_with(&data, &ImageOptions::default().with_normalize(Normalize::imagenet())).is_err());
+        assert!(deserialize(b"not an image").is_err());
+    }
+}
@@ -31,6 +31,7 @@ use serde_json::{Map, Value};
 use std::collections::HashMap;
 
 /// Load a single tensor from JSON file
+#[cfg(feature = "std")]
 pub fn load(path: impl AsRef<std::path::Path>) -> HoduResult<Tensor> {
     let data = std::fs::read_to_string(path.as_ref())
         .map_err(|e| HoduError::IoError(format!("Failed to read json file: {}", e)))?;
@@ -38,6 +39,7 @@ pub fn load(path: impl AsRef<std::path::Path>) -> HoduResult<Tensor> {
 }
 
 /// Save a single tensor to JSON file
+#[cfg(feature = "std")]
 pub fn save(tensor: &Tensor, path: impl AsRef<std::path::Path>) -> HoduResult<()> {
     let data = serialize(tensor)?;
     std::fs::write(path.as_ref(), data).map_err(|e| HoduError::IoError(format!("Failed to write json file: {}", e)))?;
@@ -45,6 +47,7 @@ pub fn save(tensor: &Tensor, path: impl AsRef<std::path::Path>) -> HoduResult<()
 }
 
 /// Load multiple named tensors from JSON file
+#[cfg(feature = "std")]
 pub fn load_many(path: impl AsRef<std::path::Path>) -> HoduResult<HashMap<String, Tensor>> {
     let data = std::fs::read_to_string(path.as_ref())
         .map_err(|e| HoduError::IoError(format!("Failed to read json file: {}", e)))?;
@@ -52,6 +55,7 @@ pub fn load_many(path: impl AsRef<std::path::Path>) -> HoduResult<HashMap<String
 }
 
 /// Save multiple named tensors to JSON file
+#[cfg(feature = "std")]
 pub fn save_many(tensors: &HashMap<String, Tensor>, path: impl AsRef<std::path::Path>) -> HoduResult<()> {
     let data = serialize_many(tensors)?;
     std::fs::write(path.as_ref(), data).map_err(|e| HoduError::IoError(format!("Failed to write json file: {}", e)))?;
@@ -99,7 +103,8 @@ pub fn deserialize_many(data: &str) -> HoduResult<HashMap<String, Tensor>> {
         .collect()
 }
 
-fn tensor_to_json(tensor: &Tensor) -> HoduResult<Value> {
+/// Convert a tensor to a JSON value with shape, dtype and data
+pub fn tensor_to_json(tensor: &Tensor) -> HoduResult<Value> {
     let shape: Vec<Value> = tensor.shape().dims().iter().map(|&d| Value::Number(d.into())).collect();
     let dtype_str = dtype_to_str(tensor.dtype());
     let data = tensor_data_to_json(tensor)?;
@@ -112,7 +117,8 @@ fn tensor_to_json(tensor: &Tensor) -> HoduResult<Value> {
     Ok(Value::Object(map))
 }
 
-fn json_to_tensor(value: &Value) -> HoduResult<Tensor> {
+/// Convert a JSON value with shape, dtype and data to a tensor
+pub fn json_to_tensor(value: &Value) -> HoduResult<Tensor> {
     let obj = value
         .as_object()
         .ok_or_else(|| HoduError::DeserializationFailed("Expected JSON object for tensor".into()))?;
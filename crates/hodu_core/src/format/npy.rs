@@ -0,0 +1,342 @@
+//! NumPy (.npy) format support
+//!
+//! Reads and writes single arrays in the format written by `numpy.save`: a magic string, a
+//! version, and a Python dict literal header followed by the raw array bytes.
+//!
+//! Loading accepts format versions 1.0 to 3.0, little-, big- and native-endian data, and
+//! Fortran-ordered arrays. Saving always writes little-endian C-ordered data.
+//!
+//! | NumPy | Hodu |
+//! |-------|------|
+//! | `b1` | bool |
+//! | `f2`, `f4`, `f8` | f16, f32, f64 |
+//! | `u1`, `u2`, `u4`, `u8` | u8, u16, u32, u64 |
+//! | `i1`, `i2`, `i4`, `i8` | i8, i16, i32, i64 |
+//!
+//! bf16 and the f8 types have no NumPy equivalent and cannot be saved.
+
+use crate::error::{HoduError, HoduResult};
+use crate::tensor::Tensor;
+use crate::types::{DType, Device, Shape};
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Header (magic, version, length and dict) is padded to a multiple of this
+const HEADER_ALIGN: usize = 64;
+
+/// Load a single tensor from .npy file
+#[cfg(feature = "std")]
+pub fn load(path: impl AsRef<std::path::Path>) -> HoduResult<Tensor> {
+    let data =
+        std::fs::read(path.as_ref()).map_err(|e| HoduError::IoError(format!("Failed to read npy file: {}", e)))?;
+    deserialize(&data)
+}
+
+/// Save a single tensor to .npy file
+#[cfg(feature = "std")]
+pub fn save(tensor: &Tensor, path: impl AsRef<std::path::Path>) -> HoduResult<()> {
+    let data = serialize(tensor)?;
+    std::fs::write(path.as_ref(), data).map_err(|e| HoduError::IoError(format!("Failed to write npy file: {}", e)))?;
+    Ok(())
+}
+
+/// Serialize a single tensor to bytes
+pub fn serialize(tensor: &Tensor) -> HoduResult<Vec<u8>> {
+    let descr = dtype_to_descr(tensor.dtype())?;
+    let shape = match tensor.shape().dims() {
+        [] => "()".to_string(),
+        [d] => format!("({},)", d),
+        dims => format!(
+            "({})",
+            dims.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")
+        ),
+    };
+    let mut header = format!("{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}", descr, shape);
+
+    // Version 1.0 stores the padded header length in a u16; longer headers need 2.0
+    let (version, len_size) = if header.len() + HEADER_ALIGN < u16::MAX as usize {
+        (1u8, 2)
+    } else {
+        (2u8, 4)
+    };
+    let prefix_len = MAGIC.len() + 2 + len_size;
+    let padding = (HEADER_ALIGN - (prefix_len + header.len() + 1) % HEADER_ALIGN) % HEADER_ALIGN;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    let data = tensor.to_bytes()?;
+    let mut bytes = Vec::with_capacity(prefix_len + header.len() + data.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&[version, 0]);
+    if version == 1 {
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    } else {
+        bytes.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    }
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend_from_slice(&data);
+    Ok(bytes)
+}
+
+/// Deserialize a single tensor from bytes
+pub fn deserialize(data: &[u8]) -> HoduResult<Tensor> {
+    let err = |msg: String| HoduError::DeserializationFailed(msg);
+
+    if data.len() < MAGIC.len() + 2 || &data[..MAGIC.len()] != MAGIC {
+        return Err(err("Not a npy file (bad magic)".into()));
+    }
+    let major = data[MAGIC.len()];
+    let len_start = MAGIC.len() + 2;
+    let (header_len, header_start) = match major {
+        1 if data.len() >= len_start + 2 => (
+            u16::from_le_bytes([data[len_start], data[len_start + 1]]) as usize,
+            len_start + 2,
+        ),
+        2 | 3 if data.len() >= len_start + 4 => (
+            u32::from_le_bytes([
+                data[len_start],
+                data[len_start + 1],
+                data[len_start + 2],
+                data[len_start + 3],
+            ]) as usize,
+            len_start + 4,
+        ),
+        1..=3 => return Err(err("Truncated npy header".into())),
+        _ => return Err(err(format!("Unsupported npy format version {}", major))),
+    };
+    let data_start = header_start
+        .checked_add(header_len)
+        .filter(|&end| end <= data.len())
+        .ok_or_else(|| err("Truncated npy header".into()))?;
+    let header = std::str::from_utf8(&data[header_start..data_start])
+        .map_err(|_| err("npy header is not valid UTF-8".into()))?;
+
+    let header = Header::parse(header)?;
+    let (dtype, endian) = descr_to_dtype(&header.descr)?;
+
+    let elem_size = dtype.size_in_bytes();
+    let size = header
+        .shape
+        .iter()
+        .try_fold(elem_size, |acc, &d| acc.checked_mul(d))
+        .ok_or_else(|| err(format!("npy shape {:?} is too large", header.shape)))?;
+    let payload = data[data_start..]
+        .get(..size)
+        .ok_or_else(|| err(format!("npy data is truncated: expected {} bytes", size)))?;
+
+    // Tensor bytes are little-endian; '|' only appears on single-byte types
+    let little_endian = match endian {
+        b'<' => true,
+        b'>' => false,
+        _ => cfg!(target_endian = "little"),
+    };
+    let swap = !little_endian && elem_size > 1;
+    let payload = if swap {
+        payload
+            .chunks_exact(elem_size)
+            .flat_map(|c| c.iter().rev().copied())
+            .collect()
+    } else {
+        payload.to_vec()
+    };
+
+    if header.fortran_order && header.shape.len() > 1 {
+        // Column-major data is the row-major data of the reversed shape, transposed
+        let reversed: Vec<usize> = header.shape.iter().rev().copied().collect();
+        let axes: Vec<usize> = (0..reversed.len()).rev().collect();
+        Tensor::from_bytes(&payload, Shape::new(&reversed), dtype, Device::CPU)?
+            .permute(&axes)?
+            .contiguous()
+    } else {
+        Tensor::from_bytes(&payload, Shape::new(&header.shape), dtype, Device::CPU)
+    }
+}
+
+/// Fields of the header dict
+struct Header {
+    descr: String,
+    fortran_order: bool,
+    shape: Vec<usize>,
+}
+
+impl Header {
+    /// Parse the dict literal, e.g. `{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3), }`
+    fn parse(header: &str) -> HoduResult<Self> {
+        let err = |msg: &str| HoduError::DeserializationFailed(format!("Invalid npy header: {}", msg));
+
+        let value_of = |key: &str| -> HoduResult<&str> {
+            let pattern = format!("'{}':", key);
+            let start = header
+                .find(&pattern)
+                .or_else(|| header.find(&format!("\"{}\":", key)))
+                .ok_or_else(|| err(&format!("missing '{}'", key)))?;
+            Ok(header[start + pattern.len()..].trim_start())
+        };
+
+        let descr = value_of("descr")?;
+        let quote = descr.chars().next().filter(|c| *c == '\'' || *c == '"');
+        let descr = quote
+            .and_then(|q| descr[1..].split(q).next())
+            .ok_or_else(|| err("'descr' must be a type string (structured dtypes are not supported)"))?
+            .to_string();
+
+        let fortran_order = match value_of("fortran_order")? {
+            v if v.starts_with("True") => true,
+            v if v.starts_with("False") => false,
+            _ => return Err(err("'fortran_order' must be True or False")),
+        };
+
+        let shape = value_of("shape")?;
+        let shape = shape
+            .strip_prefix('(')
+            .and_then(|s| s.split(')').next())
+            .ok_or_else(|| err("'shape' must be a tuple"))?;
+        let shape = shape
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.trim_end_matches('L').parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| err("'shape' must contain non-negative integers"))?;
+
+        Ok(Self {
+            descr,
+            fortran_order,
+            shape,
+        })
+    }
+}
+
+fn dtype_to_descr(dtype: DType) -> HoduResult<&'static str> {
+    let descr = match dtype {
+        DType::BOOL => "|b1",
+        DType::F16 => "<f2",
+        DType::F32 => "<f4",
+        #[cfg(feature = "f64")]
+        DType::F64 => "<f8",
+        DType::U8 => "|u1",
+        #[cfg(feature = "u16")]
+        DType::U16 => "<u2",
+        DType::U32 => "<u4",
+        #[cfg(feature = "u64")]
+        DType::U64 => "<u8",
+        DType::I8 => "|i1",
+        #[cfg(feature = "i16")]
+        DType::I16 => "<i2",
+        DType::I32 => "<i4",
+        #[cfg(feature = "i64")]
+        DType::I64 => "<i8",
+        _ => {
+            return Err(HoduError::UnsupportedDType {
+                dtype,
+                reason: "NumPy has no equivalent dtype".into(),
+            })
+        },
+    };
+    Ok(descr)
+}
+
+/// Map a descr such as `<f4` to a dtype and its byte order character
+fn descr_to_dtype(descr: &str) -> HoduResult<(DType, u8)> {
+    let bytes = descr.as_bytes();
+    let (endian, kind) = match bytes.first() {
+        Some(&c @ (b'<' | b'>' | b'|' | b'=')) => (c, &descr[1..]),
+        _ => (b'=', descr),
+    };
+
+    let dtype = match kind {
+        "b1" | "?" => DType::BOOL,
+        "f2" | "e" => DType::F16,
+        "f4" | "f" => DType::F32,
+        #[cfg(feature = "f64")]
+        "f8" | "d" => DType::F64,
+        "u1" | "B" => DType::U8,
+        #[cfg(feature = "u16")]
+        "u2" | "H" => DType::U16,
+        "u4" | "I" => DType::U32,
+        #[cfg(feature = "u64")]
+        "u8" | "Q" => DType::U64,
+        "i1" | "b" => DType::I8,
+        #[cfg(feature = "i16")]
+        "i2" | "h" => DType::I16,
+        "i4" | "i" => DType::I32,
+        #[cfg(feature = "i64")]
+        "i8" | "q" => DType::I64,
+        _ => {
+            return Err(HoduError::DeserializationFailed(format!(
+                "Unsupported npy dtype '{}'",
+                descr
+            )))
+        },
+    };
+    Ok((dtype, endian))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn npy_bytes(header: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn test_serialize_deserialize() {
+        let tensor = Tensor::from_slice(vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], [2, 3]).unwrap();
+        let data = serialize(&tensor).unwrap();
+        assert_eq!(&data[..MAGIC.len()], MAGIC);
+        let header_len = u16::from_le_bytes([data[8], data[9]]) as usize;
+        assert_eq!((10 + header_len) % HEADER_ALIGN, 0);
+        assert!(std::str::from_utf8(&data[10..10 + header_len])
+            .unwrap()
+            .starts_with("{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3), }"));
+
+        let restored = deserialize(&data).unwrap();
+        assert_eq!(restored.shape().dims(), &[2, 3]);
+        assert_eq!(restored.dtype(), DType::F32);
+        assert_eq!(restored.to_bytes().unwrap(), tensor.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_deserialize_big_endian() {
+        let data: Vec<u8> = [1i32, -2, 3].iter().flat_map(|v| v.to_be_bytes()).collect();
+        let bytes = npy_bytes("{'descr': '>i4', 'fortran_order': False, 'shape': (3,), }\n", &data);
+
+        let tensor = deserialize(&bytes).unwrap();
+        assert_eq!(tensor.dtype(), DType::I32);
+        assert_eq!(tensor.to_flatten_vec::<i32>().unwrap(), vec![1, -2, 3]);
+    }
+
+    #[test]
+    fn test_deserialize_fortran_order() {
+        // [[1, 2, 3], [4, 5, 6]] stored column by column
+        let data: Vec<u8> = [1.0f32, 4.0, 2.0, 5.0, 3.0, 6.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let bytes = npy_bytes("{'descr': '<f4', 'fortran_order': True, 'shape': (2, 3), }\n", &data);
+
+        let tensor = deserialize(&bytes).unwrap();
+        assert_eq!(tensor.shape().dims(), &[2, 3]);
+        assert_eq!(
+            tensor.to_flatten_vec::<f32>().unwrap(),
+            vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        assert!(deserialize(b"not a npy file").is_err());
+
+        let bytes = npy_bytes("{'descr': '<c8', 'fortran_order': False, 'shape': (1,), }\n", &[0; 8]);
+        assert!(deserialize(&bytes).is_err());
+
+        let bytes = npy_bytes("{'descr': '<f4', 'fortran_order': False, 'shape': (4,), }\n", &[0; 8]);
+        assert!(deserialize(&bytes).is_err());
+    }
+}
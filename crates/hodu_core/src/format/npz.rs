@@ -0,0 +1,112 @@
+//! NumPy (.npz) format support
+//!
+//! A zip archive of .npy files, as written by `numpy.savez` and `numpy.savez_compressed`.
+//! Each entry `name.npy` becomes the tensor `name`; see [`super::npy`] for the dtypes supported.
+//! Both stored and deflated entries load; saving stores entries uncompressed like `numpy.savez`.
+
+use super::npy;
+use crate::error::{HoduError, HoduResult};
+use crate::tensor::Tensor;
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Write};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+/// Load multiple named tensors from .npz file
+#[cfg(feature = "std")]
+pub fn load_many(path: impl AsRef<std::path::Path>) -> HoduResult<HashMap<String, Tensor>> {
+    let data =
+        std::fs::read(path.as_ref()).map_err(|e| HoduError::IoError(format!("Failed to read npz file: {}", e)))?;
+    deserialize_many(&data)
+}
+
+/// Save multiple named tensors to .npz file
+#[cfg(feature = "std")]
+pub fn save_many(tensors: &HashMap<String, Tensor>, path: impl AsRef<std::path::Path>) -> HoduResult<()> {
+    let data = serialize_many(tensors)?;
+    std::fs::write(path.as_ref(), data).map_err(|e| HoduError::IoError(format!("Failed to write npz file: {}", e)))?;
+    Ok(())
+}
+
+/// Serialize multiple named tensors to bytes
+pub fn serialize_many(tensors: &HashMap<String, Tensor>) -> HoduResult<Vec<u8>> {
+    let err = |e: zip::result::ZipError| HoduError::SerializationFailed(format!("Failed to write npz archive: {}", e));
+
+    // Sorted so the same tensors always produce the same archive
+    let mut names: Vec<&String> = tensors.keys().collect();
+    names.sort();
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(true);
+    for name in names {
+        let data = npy::serialize(&tensors[name])?;
+        writer.start_file(format!("{}.npy", name), options).map_err(err)?;
+        writer
+            .write_all(&data)
+            .map_err(|e| HoduError::IoError(format!("Failed to write npz entry '{}': {}", name, e)))?;
+    }
+    Ok(writer.finish().map_err(err)?.into_inner())
+}
+
+/// Deserialize multiple named tensors from bytes
+pub fn deserialize_many(data: &[u8]) -> HoduResult<HashMap<String, Tensor>> {
+    let err = |e: zip::result::ZipError| HoduError::DeserializationFailed(format!("Failed to read npz archive: {}", e));
+
+    let mut archive = ZipArchive::new(Cursor::new(data)).map_err(err)?;
+    let mut tensors = HashMap::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(err)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let entry_name = entry.name().to_string();
+        let name = entry_name.strip_suffix(".npy").unwrap_or(&entry_name).to_string();
+
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| HoduError::IoError(format!("Failed to read npz entry '{}': {}", entry_name, e)))?;
+        let tensor = npy::deserialize(&bytes)
+            .map_err(|e| HoduError::DeserializationFailed(format!("Invalid npz entry '{}': {}", entry_name, e)))?;
+        tensors.insert(name, tensor);
+    }
+    Ok(tensors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_deserialize_many() {
+        let mut tensors = HashMap::new();
+        tensors.insert("a".to_string(), Tensor::from_slice(vec![1.0f32, 2.0], [2]).unwrap());
+        tensors.insert(
+            "b".to_string(),
+            Tensor::from_slice(vec![3i32, 4, 5, 6], [2, 2]).unwrap(),
+        );
+
+        let data = serialize_many(&tensors).unwrap();
+        let restored = deserialize_many(&data).unwrap();
+
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored["a"].to_flatten_vec::<f32>().unwrap(), vec![1.0, 2.0]);
+        assert_eq!(restored["b"].shape().dims(), &[2, 2]);
+        assert_eq!(restored["b"].to_flatten_vec::<i32>().unwrap(), vec![3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_deserialize_deflated() {
+        let tensor = Tensor::from_slice(vec![0.5f32; 64], [8, 8]).unwrap();
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        writer.start_file("x.npy", options).unwrap();
+        writer.write_all(&npy::serialize(&tensor).unwrap()).unwrap();
+        let data = writer.finish().unwrap().into_inner();
+
+        let restored = deserialize_many(&data).unwrap();
+        assert_eq!(restored["x"].shape().dims(), &[8, 8]);
+        assert_eq!(restored["x"].to_flatten_vec::<f32>().unwrap(), vec![0.5; 64]);
+    }
+}
@@ -0,0 +1,1958 @@
+//! ONNX model import
+//!
+//! Converts an ONNX model (`.onnx`) into a [`Snapshot`] by replaying its graph on a
+//! [`CaptureBoard`]: graph inputs become snapshot inputs, initializers become constants and graph
+//! outputs become targets. Captured nodes keep the name of the ONNX node they came from.
+//!
+//! The common default-domain opset is covered: elementwise arithmetic and comparisons,
+//! activations, `MatMul`/`Gemm`, `Conv`, pooling, batch/instance/layer normalization, softmax and
+//! reductions, and the reshaping ops (`Reshape`, `Flatten`, `Transpose`, `Squeeze`, `Unsqueeze`,
+//! `Concat`, `Slice`, `Gather`, `Split`, `Expand`, `Pad`). Integer shape arithmetic (`Shape`,
+//! `Gather` on a shape, `Concat` of dims, ...) is evaluated during import and leaves no nodes
+//! behind. A model using any other op is rejected before conversion starts, with an error listing
+//! each unsupported op and the nodes that use it.
+//!
+//! Snapshots have static shapes, so symbolic input dimensions must be bound: see
+//! [`OnnxImporter::dim`]; unbound ones default to 1. `int64` tensors are narrowed to `i32`, the
+//! index type used throughout hodu. Initializers stored as external data are read relative to the
+//! model file.
+
+mod proto;
+
+use crate::error::{HoduError, HoduResult};
+use crate::scalar::Scalar;
+use crate::snapshot::capture::CaptureBoard;
+use crate::snapshot::Snapshot;
+use crate::tensor::Tensor;
+use crate::types::{DType, Device, Shape};
+use prost::Message;
+use proto::{data_type, AttributeProto, GraphProto, ModelProto, NodeProto, TensorProto};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Ops the importer can convert (default domain)
+const SUPPORTED_OPS: &[&str] = &[
+    "Abs",
+    "Add",
+    "ArgMax",
+    "ArgMin",
+    "AveragePool",
+    "BatchNormalization",
+    "Cast",
+    "Ceil",
+    "Clip",
+    "Concat",
+    "Constant",
+    "ConstantOfShape",
+    "Conv",
+    "Cos",
+    "Div",
+    "Dropout",
+    "Elu",
+    "Equal",
+    "Erf",
+    "Exp",
+    "Expand",
+    "Flatten",
+    "Floor",
+    "Gather",
+    "Gelu",
+    "Gemm",
+    "GlobalAveragePool",
+    "GlobalMaxPool",
+    "Greater",
+    "GreaterOrEqual",
+    "HardSigmoid",
+    "HardSwish",
+    "Identity",
+    "InstanceNormalization",
+    "LayerNormalization",
+    "LeakyRelu",
+    "Less",
+    "LessOrEqual",
+    "Log",
+    "LogSoftmax",
+    "MatMul",
+    "Max",
+    "MaxPool",
+    "Mean",
+    "Min",
+    "Mul",
+    "Neg",
+    "Pad",
+    "Pow",
+    "PRelu",
+    "Reciprocal",
+    "ReduceL1",
+    "ReduceL2",
+    "ReduceLogSumExp",
+    "ReduceMax",
+    "ReduceMean",
+    "ReduceMin",
+    "ReduceProd",
+    "ReduceSum",
+    "ReduceSumSquare",
+    "Relu",
+    "Reshape",
+    "Round",
+    "Shape",
+    "Sigmoid",
+    "Sign",
+    "Sin",
+    "Size",
+    "Slice",
+    "Softmax",
+    "Softplus",
+    "Softsign",
+    "Split",
+    "Sqrt",
+    "Squeeze",
+    "Sub",
+    "Sum",
+    "Tanh",
+    "Transpose",
+    "Unsqueeze",
+    "Where",
+];
+
+/// Load an ONNX model as a snapshot, with symbolic dimensions bound to 1
+#[cfg(feature = "std")]
+pub fn load(path: impl AsRef<Path>) -> HoduResult<Snapshot> {
+    OnnxImporter::new().load(path)
+}
+
+/// Convert an in-memory ONNX model, with symbolic dimensions bound to 1
+///
+/// Fails if any initializer is stored as external data.
+pub fn from_bytes(data: &[u8]) -> HoduResult<Snapshot> {
+    OnnxImporter::new().from_bytes(data)
+}
+
+/// ONNX to snapshot converter
+#[derive(Debug, Clone, Default)]
+pub struct OnnxImporter {
+    dims: HashMap<String, usize>,
+}
+
+impl OnnxImporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind a symbolic input dimension (e.g. `"batch_size"`) to a fixed size
+    pub fn dim(mut self, name: impl Into<String>, size: usize) -> Self {
+        self.dims.insert(name.into(), size);
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub fn load(&self, path: impl AsRef<Path>) -> HoduResult<Snapshot> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|e| HoduError::IoError(format!("Failed to read ONNX file: {}", e)))?;
+        let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        self.convert(&data, Some(base_dir))
+    }
+
+    pub fn from_bytes(&self, data: &[u8]) -> HoduResult<Snapshot> {
+        self.convert(data, None)
+    }
+
+    fn convert(&self, data: &[u8], base_dir: Option<PathBuf>) -> HoduResult<Snapshot> {
+        let model = ModelProto::decode(data)
+            .map_err(|e| HoduError::DeserializationFailed(format!("Invalid ONNX model: {}", e)))?;
+        let graph = model
+            .graph
+            .as_ref()
+            .ok_or_else(|| HoduError::DeserializationFailed("ONNX model has no graph".to_string()))?;
+        let opset = model
+            .opset_import
+            .iter()
+            .find(|o| o.domain.is_empty() || o.domain == "ai.onnx")
+            .map(|o| o.version)
+            .unwrap_or(1);
+
+        check_supported(graph)?;
+
+        let board = if graph.name.is_empty() {
+            CaptureBoard::new()
+        } else {
+            CaptureBoard::with_name(&graph.name)
+        };
+        board
+            .with_metadata("format", "onnx")
+            .with_metadata("opset", opset.to_string());
+        if !model.producer_name.is_empty() {
+            let producer = match model.producer_version.is_empty() {
+                true => model.producer_name.clone(),
+                false => format!("{} {}", model.producer_name, model.producer_version),
+            };
+            board.with_metadata("producer", producer);
+        }
+        for entry in &model.metadata_props {
+            board.with_metadata(format!("onnx.{}", entry.key), &entry.value);
+        }
+
+        let mut graph_builder = GraphBuilder {
+            board: &board,
+            opset,
+            tensors: HashMap::new(),
+            consts: HashMap::new(),
+            keep: Vec::new(),
+        };
+
+        board.open();
+        let result = graph_builder.build(self, graph, base_dir.as_deref());
+        board.close();
+        result?;
+
+        // Every constant must still be alive when the board is captured
+        let snapshot = board.capture();
+        drop(graph_builder);
+        Ok(snapshot)
+    }
+
+    fn input_shape(&self, input: &proto::ValueInfoProto) -> HoduResult<(Vec<usize>, i32)> {
+        let tensor_type = input
+            .r#type
+            .as_ref()
+            .and_then(|t| t.tensor_type.as_ref())
+            .ok_or_else(|| HoduError::UnsupportedOperation(format!("ONNX input '{}' is not a tensor", input.name)))?;
+        let shape = tensor_type.shape.as_ref().ok_or_else(|| {
+            HoduError::InvalidArgument(format!(
+                "ONNX input '{}' has no shape; snapshots need static shapes",
+                input.name
+            ))
+        })?;
+        let dims = shape
+            .dim
+            .iter()
+            .map(|dim| match (dim.dim_value, &dim.dim_param) {
+                (Some(value), _) if value >= 0 => Ok(value as usize),
+                (Some(value), _) => Err(HoduError::InvalidArgument(format!(
+                    "ONNX input '{}' has negative dimension {}",
+                    input.name, value
+                ))),
+                (None, Some(param)) => Ok(self.dims.get(param).copied().unwrap_or(1)),
+                (None, None) => Ok(1),
+            })
+            .collect::<HoduResult<Vec<_>>>()?;
+        Ok((dims, tensor_type.elem_type))
+    }
+}
+
+/// Reject the model up front if it uses any op the importer cannot convert
+fn check_supported(graph: &GraphProto) -> HoduResult<()> {
+    let mut unsupported: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (index, node) in graph.node.iter().enumerate() {
+        let default_domain = node.domain.is_empty() || node.domain == "ai.onnx";
+        if !default_domain || !SUPPORTED_OPS.contains(&node.op_type.as_str()) {
+            let op = match default_domain {
+                true => node.op_type.clone(),
+                false => format!("{}::{}", node.domain, node.op_type),
+            };
+            unsupported.entry(op).or_default().push(node_label(node, index));
+        }
+    }
+    if unsupported.is_empty() {
+        return Ok(());
+    }
+
+    let list = unsupported
+        .iter()
+        .map(|(op, nodes)| format!("{} (nodes: {})", op, nodes.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(HoduError::UnsupportedOperation(format!(
+        "unsupported ONNX ops: {}",
+        list
+    )))
+}
+
+/// The node's name, or its first output when unnamed
+fn node_label(node: &NodeProto, index: usize) -> String {
+    if !node.name.is_empty() {
+        node.name.clone()
+    } else if let Some(output) = node.output.first().filter(|o| !o.is_empty()) {
+        output.clone()
+    } else {
+        format!("#{}", index)
+    }
+}
+
+/// A small integer tensor known at import time (shapes, axes, indices)
+#[derive(Debug, Clone)]
+struct IntConst {
+    shape: Vec<usize>,
+    values: Vec<i64>,
+}
+
+impl IntConst {
+    fn vector(values: Vec<i64>) -> Self {
+        Self {
+            shape: vec![values.len()],
+            values,
+        }
+    }
+
+    fn to_tensor(&self) -> HoduResult<Tensor> {
+        let values: Vec<i32> = self
+            .values
+            .iter()
+            .map(|&v| v.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+            .collect();
+        Tensor::from_slice(values, Shape::new(&self.shape))
+    }
+}
+
+struct SliceParams {
+    starts: Vec<i64>,
+    ends: Vec<i64>,
+    axes: Option<Vec<i64>>,
+    steps: Vec<i64>,
+}
+
+struct GraphBuilder<'a> {
+    board: &'a CaptureBoard,
+    opset: i64,
+    tensors: HashMap<String, Tensor>,
+    consts: HashMap<String, IntConst>,
+    /// Tensors computed eagerly at import time, kept alive until capture
+    keep: Vec<Tensor>,
+}
+
+impl GraphBuilder<'_> {
+    fn build(&mut self, importer: &OnnxImporter, graph: &GraphProto, base_dir: Option<&Path>) -> HoduResult<()> {
+        for initializer in &graph.initializer {
+            let (tensor, int_const) = tensor_from_proto(initializer, base_dir)?;
+            if let Some(int_const) = int_const {
+                self.consts.insert(initializer.name.clone(), int_const);
+            }
+            self.tensors.insert(initializer.name.clone(), tensor);
+        }
+
+        for input in &graph.input {
+            if self.tensors.contains_key(&input.name) {
+                continue;
+            }
+            let (dims, elem_type) = importer.input_shape(input)?;
+            let tensor = Tensor::input(&input.name, Shape::new(&dims), onnx_dtype(elem_type)?)?;
+            self.tensors.insert(input.name.clone(), tensor);
+        }
+
+        for (index, node) in graph.node.iter().enumerate() {
+            self.node(node, index).map_err(|e| {
+                let label = node_label(node, index);
+                match e {
+                    HoduError::UnsupportedOperation(msg) => {
+                        HoduError::UnsupportedOperation(format!("ONNX node '{}' ({}): {}", label, node.op_type, msg))
+                    },
+                    e => HoduError::InvalidArgument(format!("ONNX node '{}' ({}): {}", label, node.op_type, e)),
+                }
+            })?;
+        }
+
+        for output in &graph.output {
+            let tensor = self.tensor(&output.name)?;
+            self.board.with_target(&output.name, tensor);
+        }
+        Ok(())
+    }
+
+    fn node(&mut self, node: &NodeProto, index: usize) -> HoduResult<()> {
+        if let Some(values) = self.fold(node)? {
+            for (name, value) in node.output.iter().zip(values) {
+                self.consts.insert(name.clone(), value);
+            }
+            return Ok(());
+        }
+
+        let outputs = self.convert(node)?;
+        let label = node_label(node, index);
+        let renames_input = matches!(node.op_type.as_str(), "Identity" | "Dropout");
+        for (name, tensor) in node.output.iter().zip(outputs) {
+            if name.is_empty() {
+                continue;
+            }
+            if !renames_input {
+                self.board.with_node_name(&tensor, &label);
+            }
+            self.tensors.insert(name.clone(), tensor);
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Value access
+    // ========================================================================
+
+    fn tensor(&mut self, name: &str) -> HoduResult<Tensor> {
+        if let Some(tensor) = self.tensors.get(name) {
+            return Ok(tensor.clone());
+        }
+        let int_const = self
+            .consts
+            .get(name)
+            .ok_or_else(|| HoduError::InvalidArgument(format!("unknown value '{}'", name)))?;
+        let tensor = int_const.to_tensor()?;
+        self.tensors.insert(name.to_string(), tensor.clone());
+        Ok(tensor)
+    }
+
+    fn shape(&self, name: &str) -> HoduResult<Vec<usize>> {
+        if let Some(int_const) = self.consts.get(name) {
+            return Ok(int_const.shape.clone());
+        }
+        self.tensors
+            .get(name)
+            .map(|t| t.shape().dims().to_vec())
+            .ok_or_else(|| HoduError::InvalidArgument(format!("unknown value '{}'", name)))
+    }
+
+    fn input(&mut self, node: &NodeProto, index: usize) -> HoduResult<Tensor> {
+        let name = input_name(node, index)
+            .ok_or_else(|| HoduError::InvalidArgument(format!("missing required input {}", index)))?;
+        self.tensor(name)
+    }
+
+    fn opt_input(&mut self, node: &NodeProto, index: usize) -> HoduResult<Option<Tensor>> {
+        match input_name(node, index) {
+            Some(name) => self.tensor(name).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// An optional integer input that must be known at import time
+    fn const_ints(&self, node: &NodeProto, index: usize) -> HoduResult<Option<Vec<i64>>> {
+        let Some(name) = input_name(node, index) else {
+            return Ok(None);
+        };
+        match self.consts.get(name) {
+            Some(int_const) => Ok(Some(int_const.values.clone())),
+            None => Err(HoduError::UnsupportedOperation(format!(
+                "input '{}' must be a constant integer tensor",
+                name
+            ))),
+        }
+    }
+
+    /// An optional scalar input that must be known at import time
+    fn const_scalar(&self, node: &NodeProto, index: usize) -> HoduResult<Option<f64>> {
+        let Some(name) = input_name(node, index) else {
+            return Ok(None);
+        };
+        if let Some(int_const) = self.consts.get(name) {
+            return Ok(int_const.values.first().map(|&v| v as f64));
+        }
+        let tensor = self
+            .tensors
+            .get(name)
+            .ok_or_else(|| HoduError::InvalidArgument(format!("unknown value '{}'", name)))?;
+        if !tensor.has_storage() || tensor.size() != 1 {
+            return Err(HoduError::UnsupportedOperation(format!(
+                "input '{}' must be a constant scalar",
+                name
+            )));
+        }
+        read_f64(tensor).map(|values| values.first().copied())
+    }
+
+    /// Run `f` outside the capture so constant preprocessing is computed once at import time
+    fn eager(&mut self, f: impl FnOnce() -> HoduResult<Vec<Tensor>>) -> HoduResult<Vec<Tensor>> {
+        self.board.close();
+        let result = f();
+        self.board.open();
+        let tensors = result?;
+        self.keep.extend(tensors.iter().cloned());
+        Ok(tensors)
+    }
+
+    // ========================================================================
+    // Import-time integer folding
+    // ========================================================================
+
+    fn fold(&self, node: &NodeProto) -> HoduResult<Option<Vec<IntConst>>> {
+        let attrs = Attrs(&node.attribute);
+        match node.op_type.as_str() {
+            "Shape" => {
+                let shape = self.shape(input_name(node, 0).unwrap_or_default())?;
+                let rank = shape.len() as i64;
+                let clamp = |v: i64| (if v < 0 { v + rank } else { v }).clamp(0, rank) as usize;
+                let start = clamp(attrs.int("start", 0));
+                let end = clamp(attrs.int("end", rank));
+                let dims = shape[start..end.max(start)].iter().map(|&d| d as i64).collect();
+                return Ok(Some(vec![IntConst::vector(dims)]));
+            },
+            "Size" => {
+                let shape = self.shape(input_name(node, 0).unwrap_or_default())?;
+                let size = shape.iter().product::<usize>() as i64;
+                return Ok(Some(vec![IntConst {
+                    shape: vec![],
+                    values: vec![size],
+                }]));
+            },
+            "Constant" => {
+                return Ok(constant_attr(&attrs)?
+                    .and_then(|(_, int_const)| int_const)
+                    .map(|c| vec![c]));
+            },
+            "ConstantOfShape" => {
+                // Only integer fills fold; the default fill is a float zero
+                let shape = input_name(node, 0).and_then(|name| self.consts.get(name));
+                let value = attrs.get("value").and_then(|a| a.t.as_ref());
+                let (Some(shape), Some(value)) = (shape, value) else {
+                    return Ok(None);
+                };
+                let Some(fill) = tensor_from_proto(value, None)?.1 else {
+                    return Ok(None);
+                };
+                let dims: Vec<usize> = shape.values.iter().map(|&d| d.max(0) as usize).collect();
+                let count = dims.iter().product();
+                return Ok(Some(vec![IntConst {
+                    shape: dims,
+                    values: vec![fill.values.first().copied().unwrap_or(0); count],
+                }]));
+            },
+            _ => {},
+        }
+
+        let foldable = matches!(
+            node.op_type.as_str(),
+            "Identity"
+                | "Cast"
+                | "Gather"
+                | "Unsqueeze"
+                | "Squeeze"
+                | "Concat"
+                | "Slice"
+                | "Reshape"
+                | "Add"
+                | "Sub"
+                | "Mul"
+                | "Div"
+        );
+        let inputs: Option<Vec<&IntConst>> = node
+            .input
+            .iter()
+            .filter(|name| !name.is_empty())
+            .map(|name| self.consts.get(name))
+            .collect();
+        let Some(inputs) = inputs.filter(|inputs| foldable && !inputs.is_empty()) else {
+            return Ok(None);
+        };
+        let first = inputs[0];
+
+        let folded = match node.op_type.as_str() {
+            "Identity" => first.clone(),
+            "Cast" => match attrs.int("to", 0) as i32 {
+                data_type::INT32 | data_type::INT64 => first.clone(),
+                _ => return Ok(None),
+            },
+            "Gather" => {
+                let indices = inputs.get(1).copied().ok_or_else(|| missing_input(1))?;
+                let axis = normalize_axis(attrs.int("axis", 0), first.shape.len())?;
+                let dim = first.shape[axis];
+                let outer: usize = first.shape[..axis].iter().product();
+                let inner: usize = first.shape[axis + 1..].iter().product();
+                let mut values = Vec::with_capacity(outer * indices.values.len() * inner);
+                for o in 0..outer {
+                    for &index in &indices.values {
+                        let index = normalize_index(index, dim)?;
+                        let start = (o * dim + index) * inner;
+                        values.extend_from_slice(&first.values[start..start + inner]);
+                    }
+                }
+                let mut shape = first.shape[..axis].to_vec();
+                shape.extend_from_slice(&indices.shape);
+                shape.extend_from_slice(&first.shape[axis + 1..]);
+                IntConst { shape, values }
+            },
+            "Unsqueeze" => IntConst {
+                shape: unsqueeze_shape(&first.shape, &self.axes(node, &attrs, 13)?.unwrap_or_default())?,
+                values: first.values.clone(),
+            },
+            "Squeeze" => IntConst {
+                shape: squeeze_shape(&first.shape, self.axes(node, &attrs, 13)?.as_deref())?,
+                values: first.values.clone(),
+            },
+            "Reshape" => {
+                let target = inputs.get(1).ok_or_else(|| missing_input(1))?;
+                IntConst {
+                    shape: reshape_shape(&first.shape, &target.values, attrs.int("allowzero", 0) != 0)?,
+                    values: first.values.clone(),
+                }
+            },
+            "Concat" => {
+                if first.shape.is_empty() {
+                    return Ok(None);
+                }
+                let axis = normalize_axis(attrs.int("axis", 0), first.shape.len())?;
+                let outer: usize = first.shape[..axis].iter().product();
+                let mut values = Vec::new();
+                for o in 0..outer {
+                    for input in &inputs {
+                        let chunk: usize = input.shape[axis..].iter().product();
+                        values.extend_from_slice(&input.values[o * chunk..(o + 1) * chunk]);
+                    }
+                }
+                let mut shape = first.shape.clone();
+                shape[axis] = inputs.iter().map(|input| input.shape[axis]).sum();
+                IntConst { shape, values }
+            },
+            "Slice" => {
+                if first.shape.len() != 1 {
+                    return Ok(None);
+                }
+                let SliceParams {
+                    starts, ends, steps, ..
+                } = self.slice_params(node, &attrs)?;
+                let (start, end) = slice_bounds(starts[0], ends[0], steps[0], first.values.len());
+                let mut values = Vec::new();
+                let mut i = start;
+                while (steps[0] > 0 && i < end) || (steps[0] < 0 && i > end) {
+                    values.push(first.values[i as usize]);
+                    i += steps[0];
+                }
+                IntConst::vector(values)
+            },
+            op => {
+                let rhs = inputs.get(1).ok_or_else(|| missing_input(1))?;
+                let (shape, len) = match (first.values.len(), rhs.values.len()) {
+                    (a, b) if a == b => (first.shape.clone(), a),
+                    (a, 1) => (first.shape.clone(), a),
+                    (1, b) => (rhs.shape.clone(), b),
+                    _ => return Ok(None),
+                };
+                let lhs_at = |i: usize| first.values[if first.values.len() == 1 { 0 } else { i }];
+                let rhs_at = |i: usize| rhs.values[if rhs.values.len() == 1 { 0 } else { i }];
+                let values = (0..len)
+                    .map(|i| {
+                        let (a, b) = (lhs_at(i), rhs_at(i));
+                        match op {
+                            "Add" => Ok(a.wrapping_add(b)),
+                            "Sub" => Ok(a.wrapping_sub(b)),
+                            "Mul" => Ok(a.wrapping_mul(b)),
+                            _ if b == 0 => Err(HoduError::InvalidArgument("integer division by zero".to_string())),
+                            _ => Ok(a / b),
+                        }
+                    })
+                    .collect::<HoduResult<Vec<_>>>()?;
+                IntConst { shape, values }
+            },
+        };
+        Ok(Some(vec![folded]))
+    }
+
+    // ========================================================================
+    // Op conversion
+    // ========================================================================
+
+    fn convert(&mut self, node: &NodeProto) -> HoduResult<Vec<Tensor>> {
+        let attrs = Attrs(&node.attribute);
+        let op = node.op_type.as_str();
+
+        let output = match op {
+            // Elementwise
+            "Add" | "Sub" | "Mul" | "Div" | "Pow" => {
+                let a = self.input(node, 0)?;
+                let mut b = self.input(node, 1)?;
+                if b.dtype() != a.dtype() {
+                    b = b.to_dtype(a.dtype())?;
+                }
+                match op {
+                    "Add" => a.add(&b)?,
+                    "Sub" => a.sub(&b)?,
+                    "Mul" => a.mul(&b)?,
+                    "Div" => a.div(&b)?,
+                    _ => a.pow(&b)?,
+                }
+            },
+            "Sum" | "Mean" | "Max" | "Min" => {
+                let mut acc = self.input(node, 0)?;
+                let count = node.input.len();
+                for index in 1..count {
+                    let x = self.input(node, index)?;
+                    acc = match op {
+                        "Max" => acc.maximum(&x)?,
+                        "Min" => acc.minimum(&x)?,
+                        _ => acc.add(&x)?,
+                    };
+                }
+                if op == "Mean" && count > 1 {
+                    acc = acc.div_scalar(scalar(&acc, count as f64))?;
+                }
+                acc
+            },
+            "Equal" | "Less" | "LessOrEqual" | "Greater" | "GreaterOrEqual" => {
+                let a = self.input(node, 0)?;
+                let b = self.input(node, 1)?;
+                match op {
+                    "Equal" => a.eq(&b)?,
+                    "Less" => a.lt(&b)?,
+                    "LessOrEqual" => a.le(&b)?,
+                    "Greater" => a.gt(&b)?,
+                    _ => a.ge(&b)?,
+                }
+            },
+            "Where" => {
+                let condition = self.input(node, 0)?;
+                let x = self.input(node, 1)?;
+                let y = self.input(node, 2)?;
+                x.where3(&condition, &y)?
+            },
+            "Identity" | "Dropout" => self.input(node, 0)?,
+            "Cast" => {
+                let dtype = onnx_dtype(attrs.int("to", 0) as i32)?;
+                self.input(node, 0)?.to_dtype(dtype)?
+            },
+
+            // Activations and unary math
+            "Relu" | "Sigmoid" | "Tanh" | "Exp" | "Log" | "Sqrt" | "Neg" | "Abs" | "Erf" | "Reciprocal" | "Floor"
+            | "Ceil" | "Round" | "Sin" | "Cos" | "Softplus" | "Softsign" | "Sign" | "HardSwish" => {
+                let x = self.input(node, 0)?;
+                match op {
+                    "Relu" => x.relu()?,
+                    "Sigmoid" => x.sigmoid()?,
+                    "Tanh" => x.tanh()?,
+                    "Exp" => x.exp()?,
+                    "Log" => x.ln()?,
+                    "Sqrt" => x.sqrt()?,
+                    "Neg" => x.neg()?,
+                    "Abs" => x.abs()?,
+                    "Erf" => x.erf()?,
+                    "Reciprocal" => x.recip()?,
+                    "Floor" => x.floor()?,
+                    "Ceil" => x.ceil()?,
+                    "Round" => x.round()?,
+                    "Sin" => x.sin()?,
+                    "Cos" => x.cos()?,
+                    "Softplus" => x.softplus()?,
+                    "Softsign" => x.softsign()?,
+                    "Sign" => x.sign()?,
+                    _ => x.hardsilu()?,
+                }
+            },
+            "Gelu" => {
+                let x = self.input(node, 0)?;
+                match attrs.string("approximate", "none").as_str() {
+                    "tanh" => x.gelu()?,
+                    _ => {
+                        // 0.5 * x * (1 + erf(x / sqrt(2)))
+                        let cdf = x
+                            .mul_scalar(scalar(&x, std::f64::consts::FRAC_1_SQRT_2))?
+                            .erf()?
+                            .add_scalar(scalar(&x, 1.0))?;
+                        x.mul(&cdf)?.mul_scalar(scalar(&x, 0.5))?
+                    },
+                }
+            },
+            "LeakyRelu" => {
+                let x = self.input(node, 0)?;
+                x.leaky_relu(scalar(&x, attrs.float("alpha", 0.01)))?
+            },
+            "Elu" => {
+                let x = self.input(node, 0)?;
+                x.elu(scalar(&x, attrs.float("alpha", 1.0)))?
+            },
+            "HardSigmoid" => {
+                let x = self.input(node, 0)?;
+                x.mul_scalar(scalar(&x, attrs.float("alpha", 0.2)))?
+                    .add_scalar(scalar(&x, attrs.float("beta", 0.5)))?
+                    .clamp(scalar(&x, 0.0), scalar(&x, 1.0))?
+            },
+            "PRelu" => {
+                // relu(x) - slope * relu(-x)
+                let x = self.input(node, 0)?;
+                let slope = self.input(node, 1)?;
+                x.relu()?.sub(&slope.mul(&x.neg()?.relu()?)?)?
+            },
+            "Clip" => {
+                let x = self.input(node, 0)?;
+                let (min, max) = if self.opset < 11 {
+                    (attrs.opt_float("min"), attrs.opt_float("max"))
+                } else {
+                    (self.const_scalar(node, 1)?, self.const_scalar(node, 2)?)
+                };
+                match (min, max) {
+                    (Some(min), Some(max)) => x.clamp(scalar(&x, min), scalar(&x, max))?,
+                    (Some(min), None) => x.clamp_min(scalar(&x, min))?,
+                    (None, Some(max)) => x.clamp_max(scalar(&x, max))?,
+                    (None, None) => x,
+                }
+            },
+            "Softmax" | "LogSoftmax" => self.softmax(node, &attrs)?,
+
+            // Linear algebra and convolution
+            "MatMul" => {
+                let a = self.input(node, 0)?;
+                let b = self.input(node, 1)?;
+                a.matmul(&b)?
+            },
+            "Gemm" => {
+                let mut a = self.input(node, 0)?;
+                let mut b = self.input(node, 1)?;
+                if attrs.int("transA", 0) != 0 {
+                    a = a.t()?;
+                }
+                if attrs.int("transB", 0) != 0 {
+                    b = b.t()?;
+                }
+                let mut y = a.matmul(&b)?;
+                let alpha = attrs.float("alpha", 1.0);
+                if alpha != 1.0 {
+                    y = y.mul_scalar(scalar(&y, alpha))?;
+                }
+                if let Some(mut c) = self.opt_input(node, 2)? {
+                    let beta = attrs.float("beta", 1.0);
+                    if beta != 1.0 {
+                        c = c.mul_scalar(scalar(&c, beta))?;
+                    }
+                    y = y.add(&c)?;
+                }
+                y
+            },
+            "Conv" => self.conv(node, &attrs)?,
+            "MaxPool" | "AveragePool" => self.pool(node, &attrs)?,
+            "GlobalAveragePool" | "GlobalMaxPool" => {
+                let x = self.input(node, 0)?;
+                let dims: Vec<usize> = (2..x.ndim()).collect();
+                match op {
+                    "GlobalAveragePool" => x.mean(&dims, true)?,
+                    _ => x.max(&dims, true)?,
+                }
+            },
+
+            // Normalization
+            "BatchNormalization" => self.batch_norm(node, &attrs)?,
+            "InstanceNormalization" => {
+                let x = self.input(node, 0)?;
+                let scale = channel_param(&self.input(node, 1)?, x.ndim())?;
+                let bias = channel_param(&self.input(node, 2)?, x.ndim())?;
+                let dims: Vec<usize> = (2..x.ndim()).collect();
+                let (normalized, _, _) = normalize(&x, &dims, attrs.float("epsilon", 1e-5))?;
+                normalized.mul(&scale)?.add(&bias)?
+            },
+            "LayerNormalization" => {
+                let x = self.input(node, 0)?;
+                let axis = normalize_axis(attrs.int("axis", -1), x.ndim())?;
+                let dims: Vec<usize> = (axis..x.ndim()).collect();
+                let (normalized, mean, inv_std) = normalize(&x, &dims, attrs.float("epsilon", 1e-5))?;
+                let mut y = normalized.mul(&self.input(node, 1)?)?;
+                if let Some(bias) = self.opt_input(node, 2)? {
+                    y = y.add(&bias)?;
+                }
+                return Ok(vec![y, mean, inv_std]);
+            },
+
+            // Reductions
+            "ReduceMean" | "ReduceSum" | "ReduceMax" | "ReduceMin" | "ReduceProd" | "ReduceL1" | "ReduceL2"
+            | "ReduceSumSquare" | "ReduceLogSumExp" => {
+                let x = self.input(node, 0)?;
+                // Axes moved from an attribute to an input in opset 13 for ReduceSum, 18 for the rest
+                let axes_as_input = if op == "ReduceSum" { 13 } else { 18 };
+                let axes = self.axes(node, &attrs, axes_as_input)?.unwrap_or_default();
+                if axes.is_empty() && attrs.int("noop_with_empty_axes", 0) != 0 {
+                    return Ok(vec![x]);
+                }
+                let dims = match axes.is_empty() {
+                    true => (0..x.ndim()).collect(),
+                    false => normalize_axes(&axes, x.ndim())?,
+                };
+                let keep = attrs.int("keepdims", 1) != 0;
+                match op {
+                    "ReduceMean" => x.mean(&dims, keep)?,
+                    "ReduceSum" => x.sum(&dims, keep)?,
+                    "ReduceMax" => x.max(&dims, keep)?,
+                    "ReduceMin" => x.min(&dims, keep)?,
+                    "ReduceProd" => x.prod(&dims, keep)?,
+                    "ReduceL1" => x.l1_norm(&dims, keep)?,
+                    "ReduceL2" => x.l2_norm(&dims, keep)?,
+                    "ReduceSumSquare" => x.square()?.sum(&dims, keep)?,
+                    _ => x.logsumexp(&dims, keep)?,
+                }
+            },
+            "ArgMax" | "ArgMin" => {
+                if attrs.int("select_last_index", 0) != 0 {
+                    return Err(HoduError::UnsupportedOperation(
+                        "select_last_index=1 is not supported".to_string(),
+                    ));
+                }
+                let x = self.input(node, 0)?;
+                let axis = normalize_axis(attrs.int("axis", 0), x.ndim())?;
+                let keep = attrs.int("keepdims", 1) != 0;
+                match op {
+                    "ArgMax" => x.argmax(&[axis], keep)?,
+                    _ => x.argmin(&[axis], keep)?,
+                }
+            },
+
+            // Shape manipulation
+            "Reshape" => {
+                let x = self.input(node, 0)?;
+                let target = self.const_ints(node, 1)?.ok_or_else(|| missing_input(1))?;
+                let shape = reshape_shape(x.shape().dims(), &target, attrs.int("allowzero", 0) != 0)?;
+                x.reshape(Shape::new(&shape))?
+            },
+            "Flatten" => {
+                let x = self.input(node, 0)?;
+                let dims = x.shape().dims().to_vec();
+                let axis = attrs.int("axis", 1);
+                let axis = if axis < 0 { axis + dims.len() as i64 } else { axis };
+                if axis < 0 || axis as usize > dims.len() {
+                    return Err(HoduError::InvalidArgument(format!("axis {} out of range", axis)));
+                }
+                let (outer, inner) = dims.split_at(axis as usize);
+                x.reshape([outer.iter().product::<usize>(), inner.iter().product::<usize>()])?
+            },
+            "Transpose" => {
+                let x = self.input(node, 0)?;
+                let perm = match attrs.ints("perm") {
+                    Some(perm) => normalize_axes(&perm, x.ndim())?,
+                    None => (0..x.ndim()).rev().collect(),
+                };
+                x.permute(&perm)?
+            },
+            "Squeeze" => {
+                let x = self.input(node, 0)?;
+                let axes = self.axes(node, &attrs, 13)?;
+                let shape = squeeze_shape(x.shape().dims(), axes.as_deref())?;
+                x.reshape(Shape::new(&shape))?
+            },
+            "Unsqueeze" => {
+                let x = self.input(node, 0)?;
+                let axes = self.axes(node, &attrs, 13)?.ok_or_else(|| missing_input(1))?;
+                let shape = unsqueeze_shape(x.shape().dims(), &axes)?;
+                x.reshape(Shape::new(&shape))?
+            },
+            "Concat" => {
+                let inputs = (0..node.input.len())
+                    .map(|index| self.input(node, index))
+                    .collect::<HoduResult<Vec<_>>>()?;
+                let axis = normalize_axis(attrs.int("axis", 0), inputs[0].ndim())?;
+                let refs: Vec<&Tensor> = inputs.iter().collect();
+                Tensor::concat(&refs, axis)?
+            },
+            "Split" => return self.split(node, &attrs),
+            "Slice" => self.slice(node, &attrs)?,
+            "Gather" => {
+                let data = self.input(node, 0)?;
+                let axis = normalize_axis(attrs.int("axis", 0), data.ndim())?;
+                let dim = data.shape().dims()[axis];
+                let name = input_name(node, 1).ok_or_else(|| missing_input(1))?;
+                let (indices, index_shape) = match self.consts.get(name) {
+                    Some(int_const) => {
+                        let values = int_const
+                            .values
+                            .iter()
+                            .map(|&i| normalize_index(i, dim).map(|i| i as i64))
+                            .collect::<HoduResult<Vec<_>>>()?;
+                        (IntConst::vector(values).to_tensor()?, int_const.shape.clone())
+                    },
+                    None => {
+                        let indices = self.tensor(name)?;
+                        let shape = indices.shape().dims().to_vec();
+                        (indices.reshape([indices.size()])?, shape)
+                    },
+                };
+                let gathered = data.index_select(axis, &indices)?;
+                let mut shape = data.shape().dims()[..axis].to_vec();
+                shape.extend_from_slice(&index_shape);
+                shape.extend_from_slice(&data.shape().dims()[axis + 1..]);
+                gathered.reshape(Shape::new(&shape))?
+            },
+            "Expand" => {
+                let x = self.input(node, 0)?;
+                let target = self.const_ints(node, 1)?.ok_or_else(|| missing_input(1))?;
+                let target = target.iter().map(|&d| d.max(0) as usize).collect::<Vec<_>>();
+                let shape = Shape::broadcast_shape(&x.shape(), &Shape::new(&target)).ok_or_else(|| {
+                    HoduError::InvalidArgument(format!("cannot expand {:?} to {:?}", x.shape().dims(), target))
+                })?;
+                x.broadcast(shape)?
+            },
+            "Pad" => self.pad(node, &attrs)?,
+            "Constant" => {
+                let (tensor, _) = constant_attr(&attrs)?
+                    .ok_or_else(|| HoduError::UnsupportedOperation("Constant without a value".to_string()))?;
+                tensor
+            },
+            "ConstantOfShape" => {
+                let shape = self.const_ints(node, 0)?.ok_or_else(|| missing_input(0))?;
+                let shape: Vec<usize> = shape.iter().map(|&d| d.max(0) as usize).collect();
+                let (value, dtype) = match attrs.get("value").and_then(|a| a.t.as_ref()) {
+                    Some(proto) => {
+                        let value = tensor_from_proto(proto, None)?.0;
+                        (read_f64(&value)?.first().copied().unwrap_or(0.0), value.dtype())
+                    },
+                    None => (0.0, DType::F32),
+                };
+                Tensor::full(Shape::new(&shape), Scalar::from(value).to_dtype(dtype))?
+            },
+            _ => return Err(HoduError::UnsupportedOperation(format!("op '{}' is not supported", op))),
+        };
+        Ok(vec![output])
+    }
+
+    fn softmax(&mut self, node: &NodeProto, attrs: &Attrs) -> HoduResult<Tensor> {
+        let x = self.input(node, 0)?;
+        let log = node.op_type == "LogSoftmax";
+        if self.opset >= 13 {
+            let axis = normalize_axis(attrs.int("axis", -1), x.ndim())?;
+            return if log { x.log_softmax(axis) } else { x.softmax(axis) };
+        }
+
+        // Before opset 13 the input is coerced to 2D at `axis` and normalized over the trailing part
+        let dims = x.shape().dims().to_vec();
+        let axis = normalize_axis(attrs.int("axis", 1), dims.len() + 1)?;
+        let (outer, inner) = dims.split_at(axis);
+        let flat = x.reshape([outer.iter().product::<usize>(), inner.iter().product::<usize>()])?;
+        let y = if log { flat.log_softmax(1)? } else { flat.softmax(1)? };
+        y.reshape(Shape::new(&dims))
+    }
+
+    fn conv(&mut self, node: &NodeProto, attrs: &Attrs) -> HoduResult<Tensor> {
+        let x = self.input(node, 0)?;
+        let weight = self.input(node, 1)?;
+        let bias = self.opt_input(node, 2)?;
+
+        let spatial = x.ndim().saturating_sub(2);
+        if !(1..=3).contains(&spatial) {
+            return Err(HoduError::UnsupportedOperation(format!(
+                "{}D convolution is not supported",
+                spatial
+            )));
+        }
+        let kernel = weight.shape().dims()[2..].to_vec();
+        let strides = attrs.ints("strides").unwrap_or_else(|| vec![1; spatial]);
+        let dilations = attrs.ints("dilations").unwrap_or_else(|| vec![1; spatial]);
+        let stride = uniform(&strides, "strides")?;
+        let dilation = uniform(&dilations, "dilations")?;
+        let pads = window_pads(attrs, &x.shape().dims()[2..], &kernel, &strides, &dilations)?;
+
+        // Symmetric padding goes to the kernel; anything else is padded explicitly first
+        let (x, padding) = match pads.iter().all(|&p| p == (pads[0].0, pads[0].0)) {
+            true => (x, pads[0].0),
+            false => {
+                let mut full = vec![(0, 0); 2];
+                full.extend_from_slice(&pads);
+                (x.pad_constant(&full, Scalar::zero(x.dtype()))?, 0)
+            },
+        };
+
+        let conv = |x: &Tensor, w: &Tensor| match spatial {
+            1 => x.conv1d(w, stride, padding, dilation),
+            2 => x.conv2d(w, stride, padding, dilation),
+            _ => x.conv3d(w, stride, padding, dilation),
+        };
+        let group = attrs.int("group", 1).max(1) as usize;
+        let mut y = if group == 1 {
+            conv(&x, &weight)?
+        } else {
+            let in_channels = x.shape().dims()[1];
+            let out_channels = weight.shape().dims()[0];
+            let xs = x.split(&vec![in_channels / group; group], 1)?;
+            let ws = weight.split(&vec![out_channels / group; group], 0)?;
+            let ys = xs
+                .iter()
+                .zip(&ws)
+                .map(|(x, w)| conv(x, w))
+                .collect::<HoduResult<Vec<_>>>()?;
+            Tensor::concat(&ys.iter().collect::<Vec<_>>(), 1)?
+        };
+        if let Some(bias) = bias {
+            y = y.add(&channel_param(&bias, y.ndim())?)?;
+        }
+        Ok(y)
+    }
+
+    fn pool(&mut self, node: &NodeProto, attrs: &Attrs) -> HoduResult<Tensor> {
+        let x = self.input(node, 0)?;
+        let spatial = x.ndim().saturating_sub(2);
+        let kernel: Vec<usize> = attrs
+            .ints("kernel_shape")
+            .ok_or_else(|| HoduError::InvalidArgument("missing kernel_shape".to_string()))?
+            .iter()
+            .map(|&k| k as usize)
+            .collect();
+        let strides = attrs.ints("strides").unwrap_or_else(|| vec![1; spatial]);
+        let dilations = attrs.ints("dilations").unwrap_or_else(|| vec![1; spatial]);
+        if dilations.iter().any(|&d| d != 1) {
+            return Err(HoduError::UnsupportedOperation(
+                "dilated pooling is not supported".to_string(),
+            ));
+        }
+        if node.op_type == "MaxPool" && node.output.get(1).is_some_and(|o| !o.is_empty()) {
+            return Err(HoduError::UnsupportedOperation(
+                "MaxPool indices output is not supported".to_string(),
+            ));
+        }
+
+        let input_dims = x.shape().dims()[2..].to_vec();
+        let mut pads = window_pads(attrs, &input_dims, &kernel, &strides, &dilations)?;
+        if attrs.int("ceil_mode", 0) != 0 {
+            // Extend the end padding so a partial last window still produces an output
+            for (i, pad) in pads.iter_mut().enumerate() {
+                let stride = strides[i] as usize;
+                let padded = input_dims[i] + pad.0 + pad.1;
+                let windows = padded.saturating_sub(kernel[i]).div_ceil(stride) + 1;
+                pad.1 += ((windows - 1) * stride + kernel[i]).saturating_sub(padded);
+            }
+        }
+
+        let mut window = vec![1, 1];
+        window.extend_from_slice(&kernel);
+        let mut window_strides = vec![1, 1];
+        window_strides.extend(strides.iter().map(|&s| s as usize));
+        let mut padding = vec![(0, 0); 2];
+        padding.extend_from_slice(&pads);
+        let (window, window_strides) = (Shape::new(&window), Shape::new(&window_strides));
+
+        if node.op_type == "MaxPool" {
+            return x.reduce_window(window, window_strides, &padding, "max");
+        }
+        let padded = pads.iter().any(|&(b, e)| b != 0 || e != 0);
+        if !padded || attrs.int("count_include_pad", 0) != 0 {
+            return x.reduce_window(window, window_strides, &padding, "mean");
+        }
+
+        // Average only over the real elements of each window
+        let ones = Tensor::ones(x.shape(), x.dtype())?;
+        let counts = self.eager(|| {
+            Ok(vec![ones.reduce_window(
+                window.clone(),
+                window_strides.clone(),
+                &padding,
+                "sum",
+            )?])
+        })?;
+        x.reduce_window(window, window_strides, &padding, "sum")?
+            .div(&counts[0])
+    }
+
+    fn batch_norm(&mut self, node: &NodeProto, attrs: &Attrs) -> HoduResult<Tensor> {
+        if attrs.int("training_mode", 0) != 0 {
+            return Err(HoduError::UnsupportedOperation(
+                "training_mode=1 is not supported".to_string(),
+            ));
+        }
+        let x = self.input(node, 0)?;
+        let params = (1..5)
+            .map(|index| self.input(node, index))
+            .collect::<HoduResult<Vec<_>>>()?;
+        let epsilon = attrs.float("epsilon", 1e-5);
+
+        // y = x * scale / sqrt(var + eps) + (bias - mean * scale / sqrt(var + eps))
+        let fold = |params: &[Tensor]| -> HoduResult<Vec<Tensor>> {
+            let (scale, bias, mean, var) = (&params[0], &params[1], &params[2], &params[3]);
+            let inv_std = var.add_scalar(scalar(var, epsilon))?.sqrt()?.recip()?;
+            let scale = scale.mul(&inv_std)?;
+            let shift = bias.sub(&mean.mul(&scale)?)?;
+            Ok(vec![scale, shift])
+        };
+        let folded = match params.iter().all(|p| p.has_storage()) {
+            true => self.eager(|| fold(&params))?,
+            false => fold(&params)?,
+        };
+        let scale = channel_param(&folded[0], x.ndim())?;
+        let shift = channel_param(&folded[1], x.ndim())?;
+        x.mul(&scale)?.add(&shift)
+    }
+
+    fn split(&mut self, node: &NodeProto, attrs: &Attrs) -> HoduResult<Vec<Tensor>> {
+        let x = self.input(node, 0)?;
+        let axis = normalize_axis(attrs.int("axis", 0), x.ndim())?;
+        let dim = x.shape().dims()[axis];
+        let sizes = match self.opset >= 13 {
+            true => self.const_ints(node, 1)?,
+            false => attrs.ints("split"),
+        };
+        let sizes: Vec<usize> = match sizes {
+            Some(sizes) => sizes.iter().map(|&s| s.max(0) as usize).collect(),
+            None => {
+                let parts = attrs.int("num_outputs", node.output.len() as i64).max(1) as usize;
+                let chunk = dim.div_ceil(parts);
+                (0..parts).map(|i| chunk.min(dim.saturating_sub(i * chunk))).collect()
+            },
+        };
+        x.split(&sizes, axis)
+    }
+
+    /// Slice parameters from attributes (before opset 10) or constant inputs
+    fn slice_params(&self, node: &NodeProto, attrs: &Attrs) -> HoduResult<SliceParams> {
+        let (starts, ends, axes, steps) = if self.opset < 10 {
+            (attrs.ints("starts"), attrs.ints("ends"), attrs.ints("axes"), None)
+        } else {
+            (
+                self.const_ints(node, 1)?,
+                self.const_ints(node, 2)?,
+                self.const_ints(node, 3)?,
+                self.const_ints(node, 4)?,
+            )
+        };
+        let starts = starts.ok_or_else(|| HoduError::InvalidArgument("missing starts".to_string()))?;
+        let ends = ends.ok_or_else(|| HoduError::InvalidArgument("missing ends".to_string()))?;
+        let steps = steps.unwrap_or_else(|| vec![1; starts.len()]);
+        if ends.len() != starts.len() || steps.len() != starts.len() || steps.contains(&0) {
+            return Err(HoduError::InvalidArgument("invalid slice parameters".to_string()));
+        }
+        Ok(SliceParams {
+            starts,
+            ends,
+            axes,
+            steps,
+        })
+    }
+
+    fn slice(&mut self, node: &NodeProto, attrs: &Attrs) -> HoduResult<Tensor> {
+        let mut x = self.input(node, 0)?;
+        let SliceParams {
+            starts,
+            ends,
+            axes,
+            steps,
+        } = self.slice_params(node, attrs)?;
+        let axes = match axes {
+            Some(axes) => normalize_axes(&axes, x.ndim())?,
+            None => (0..starts.len()).collect(),
+        };
+        for (i, &axis) in axes.iter().enumerate() {
+            let dim = x.shape().dims()[axis];
+            let (start, end) = slice_bounds(starts[i], ends[i], steps[i], dim);
+            if steps[i] > 0 {
+                if start == 0 && end == dim as i64 && steps[i] == 1 {
+                    continue;
+                }
+                x = x.slice(axis, start as i32, Some(end as i32), steps[i] as i32)?;
+            } else {
+                // Walk a negative step forwards over the flipped axis
+                let last = dim as i64 - 1;
+                x = x
+                    .flip(&[axis])?
+                    .slice(axis, (last - start) as i32, Some((last - end) as i32), -steps[i] as i32)?;
+            }
+        }
+        Ok(x)
+    }
+
+    fn pad(&mut self, node: &NodeProto, attrs: &Attrs) -> HoduResult<Tensor> {
+        let x = self.input(node, 0)?;
+        let rank = x.ndim();
+        let (pads, value, axes) = if self.opset < 11 {
+            let pads = attrs.ints("pads").or_else(|| attrs.ints("paddings"));
+            (pads, attrs.opt_float("value"), None)
+        } else {
+            (
+                self.const_ints(node, 1)?,
+                self.const_scalar(node, 2)?,
+                self.const_ints(node, 3)?,
+            )
+        };
+        let pads = pads.ok_or_else(|| HoduError::InvalidArgument("missing pads".to_string()))?;
+        let axes = match axes {
+            Some(axes) => normalize_axes(&axes, rank)?,
+            None => (0..rank).collect(),
+        };
+        if pads.len() != 2 * axes.len() {
+            return Err(HoduError::InvalidArgument(format!(
+                "expected {} pads, got {}",
+                2 * axes.len(),
+                pads.len()
+            )));
+        }
+        if pads.iter().any(|&p| p < 0) {
+            return Err(HoduError::UnsupportedOperation(
+                "negative pads are not supported".to_string(),
+            ));
+        }
+
+        let mut padding = vec![(0, 0); rank];
+        for (i, &axis) in axes.iter().enumerate() {
+            padding[axis] = (pads[i] as usize, pads[i + axes.len()] as usize);
+        }
+        let mode = match attrs.string("mode", "constant").as_str() {
+            "constant" => "constant",
+            "reflect" => "reflect",
+            "edge" => "edge",
+            "wrap" => "wrap",
+            mode => {
+                return Err(HoduError::UnsupportedOperation(format!(
+                    "pad mode '{}' is not supported",
+                    mode
+                )))
+            },
+        };
+        x.pad(&padding, mode, scalar(&x, value.unwrap_or(0.0)))
+    }
+
+    /// Axes from the `axes` attribute, or from input 1 from opset `as_input_since` on
+    fn axes(&self, node: &NodeProto, attrs: &Attrs, as_input_since: i64) -> HoduResult<Option<Vec<i64>>> {
+        match self.opset >= as_input_since {
+            true => self.const_ints(node, 1),
+            false => Ok(attrs.ints("axes")),
+        }
+    }
+}
+
+// ============================================================================
+// Attributes
+// ============================================================================
+
+struct Attrs<'a>(&'a [AttributeProto]);
+
+impl Attrs<'_> {
+    fn get(&self, name: &str) -> Option<&AttributeProto> {
+        self.0.iter().find(|a| a.name == name)
+    }
+
+    fn int(&self, name: &str, default: i64) -> i64 {
+        self.get(name).map(|a| a.i).unwrap_or(default)
+    }
+
+    fn float(&self, name: &str, default: f64) -> f64 {
+        self.opt_float(name).unwrap_or(default)
+    }
+
+    fn opt_float(&self, name: &str) -> Option<f64> {
+        self.get(name).map(|a| a.f as f64)
+    }
+
+    fn ints(&self, name: &str) -> Option<Vec<i64>> {
+        self.get(name).map(|a| a.ints.clone())
+    }
+
+    fn string(&self, name: &str, default: &str) -> String {
+        self.get(name)
+            .map(|a| String::from_utf8_lossy(&a.s).into_owned())
+            .unwrap_or_else(|| default.to_string())
+    }
+}
+
+/// The value of a `Constant` node, with its integer form when it has one
+fn constant_attr(attrs: &Attrs) -> HoduResult<Option<(Tensor, Option<IntConst>)>> {
+    if let Some(proto) = attrs.get("value").and_then(|a| a.t.as_ref()) {
+        return tensor_from_proto(proto, None).map(Some);
+    }
+    if let Some(attr) = attrs.get("value_int") {
+        let int_const = IntConst {
+            shape: vec![],
+            values: vec![attr.i],
+        };
+        return Ok(Some((int_const.to_tensor()?, Some(int_const))));
+    }
+    if let Some(attr) = attrs.get("value_ints") {
+        let int_const = IntConst::vector(attr.ints.clone());
+        return Ok(Some((int_const.to_tensor()?, Some(int_const))));
+    }
+    if let Some(attr) = attrs.get("value_float") {
+        return Ok(Some((Tensor::from_slice(vec![attr.f], Shape::scalar())?, None)));
+    }
+    if let Some(attr) = attrs.get("value_floats") {
+        let len = attr.floats.len();
+        return Ok(Some((Tensor::from_slice(attr.floats.clone(), [len])?, None)));
+    }
+    Ok(None)
+}
+
+// ============================================================================
+// Tensors and dtypes
+// ============================================================================
+
+fn onnx_dtype(data_type: i32) -> HoduResult<DType> {
+    let dtype = match data_type {
+        data_type::FLOAT => DType::F32,
+        data_type::FLOAT16 => DType::F16,
+        data_type::BFLOAT16 => DType::BF16,
+        #[cfg(feature = "f64")]
+        data_type::DOUBLE => DType::F64,
+        data_type::UINT8 => DType::U8,
+        data_type::INT8 => DType::I8,
+        #[cfg(feature = "u16")]
+        data_type::UINT16 => DType::U16,
+        #[cfg(feature = "i16")]
+        data_type::INT16 => DType::I16,
+        data_type::UINT32 => DType::U32,
+        #[cfg(feature = "u64")]
+        data_type::UINT64 => DType::U64,
+        data_type::INT32 | data_type::INT64 => DType::I32,
+        data_type::BOOL => DType::BOOL,
+        other => {
+            return Err(HoduError::UnsupportedOperation(format!(
+                "ONNX data type {} is not supported",
+                other
+            )))
+        },
+    };
+    Ok(dtype)
+}
+
+/// Bytes per element of an ONNX data type as stored in `raw_data`
+fn onnx_element_size(data_type: i32) -> usize {
+    match data_type {
+        data_type::DOUBLE | data_type::INT64 | data_type::UINT64 => 8,
+        data_type::FLOAT | data_type::INT32 | data_type::UINT32 => 4,
+        data_type::FLOAT16 | data_type::BFLOAT16 | data_type::INT16 | data_type::UINT16 => 2,
+        _ => 1,
+    }
+}
+
+fn tensor_from_proto(proto: &TensorProto, base_dir: Option<&Path>) -> HoduResult<(Tensor, Option<IntConst>)> {
+    let shape = proto
+        .dims
+        .iter()
+        .map(|&d| usize::try_from(d))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| HoduError::InvalidArgument(format!("tensor '{}' has a negative dimension", proto.name)))?;
+    let dtype = onnx_dtype(proto.data_type)?;
+    let size = onnx_element_size(proto.data_type);
+    let count: usize = shape.iter().product();
+
+    let bytes = if proto.data_location == 1 {
+        read_external(proto, base_dir)?
+    } else if !proto.raw_data.is_empty() {
+        proto.raw_data.clone()
+    } else {
+        match proto.data_type {
+            data_type::FLOAT => proto.float_data.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            data_type::DOUBLE => proto.double_data.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            data_type::INT64 => proto.int64_data.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            data_type::UINT32 | data_type::UINT64 => proto
+                .uint64_data
+                .iter()
+                .flat_map(|v| v.to_le_bytes()[..size].to_vec())
+                .collect(),
+            // Every narrower type is stored widened to int32
+            _ => proto
+                .int32_data
+                .iter()
+                .flat_map(|v| v.to_le_bytes()[..size].to_vec())
+                .collect::<Vec<u8>>(),
+        }
+    };
+    if bytes.len() != count * size {
+        return Err(HoduError::DeserializationFailed(format!(
+            "tensor '{}' has {} bytes of data, expected {}",
+            proto.name,
+            bytes.len(),
+            count * size
+        )));
+    }
+
+    match proto.data_type {
+        data_type::INT64 | data_type::INT32 => {
+            let values: Vec<i64> = match size {
+                8 => bytes
+                    .chunks_exact(8)
+                    .map(|c| i64::from_le_bytes(c.try_into().unwrap()))
+                    .collect(),
+                _ => bytes
+                    .chunks_exact(4)
+                    .map(|c| i32::from_le_bytes(c.try_into().unwrap()) as i64)
+                    .collect(),
+            };
+            let int_const = IntConst { shape, values };
+            Ok((int_const.to_tensor()?, Some(int_const)))
+        },
+        _ => Ok((
+            Tensor::from_bytes(&bytes, Shape::new(&shape), dtype, Device::CPU)?,
+            None,
+        )),
+    }
+}
+
+#[cfg(feature = "std")]
+fn read_external(proto: &TensorProto, base_dir: Option<&Path>) -> HoduResult<Vec<u8>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let base_dir = base_dir.ok_or_else(|| {
+        HoduError::InvalidArgument(format!(
+            "tensor '{}' uses external data, which needs the model to be loaded from a file",
+            proto.name
+        ))
+    })?;
+    let field = |key: &str| {
+        proto
+            .external_data
+            .iter()
+            .find(|e| e.key == key)
+            .map(|e| e.value.as_str())
+    };
+    let location = field("location")
+        .ok_or_else(|| HoduError::DeserializationFailed(format!("tensor '{}' has no external location", proto.name)))?;
+    let parse = |key: &str| -> HoduResult<Option<u64>> {
+        field(key)
+            .map(|v| {
+                v.parse::<u64>().map_err(|_| {
+                    HoduError::DeserializationFailed(format!("tensor '{}' has invalid external {}", proto.name, key))
+                })
+            })
+            .transpose()
+    };
+    let offset = parse("offset")?.unwrap_or(0);
+    let length = parse("length")?;
+
+    let path = base_dir.join(location);
+    let io_err =
+        |e: std::io::Error| HoduError::IoError(format!("Failed to read external data {}: {}", path.display(), e));
+    let mut file = std::fs::File::open(&path).map_err(io_err)?;
+    file.seek(SeekFrom::Start(offset)).map_err(io_err)?;
+    let mut bytes = Vec::new();
+    match length {
+        Some(length) => file.take(length).read_to_end(&mut bytes).map_err(io_err)?,
+        None => file.read_to_end(&mut bytes).map_err(io_err)?,
+    };
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "std"))]
+fn read_external(proto: &TensorProto, _base_dir: Option<&Path>) -> HoduResult<Vec<u8>> {
+    Err(HoduError::UnsupportedOperation(format!(
+        "tensor '{}' uses external data, which requires the `std` feature",
+        proto.name
+    )))
+}
+
+/// Values of a storage-backed tensor as f64
+fn read_f64(tensor: &Tensor) -> HoduResult<Vec<f64>> {
+    let values = match tensor.dtype() {
+        DType::F32 => tensor.to_flatten_vec::<f32>()?.into_iter().map(f64::from).collect(),
+        DType::F16 => tensor
+            .to_flatten_vec::<half::f16>()?
+            .into_iter()
+            .map(f64::from)
+            .collect(),
+        DType::BF16 => tensor
+            .to_flatten_vec::<half::bf16>()?
+            .into_iter()
+            .map(f64::from)
+            .collect(),
+        #[cfg(feature = "f64")]
+        DType::F64 => tensor.to_flatten_vec::<f64>()?,
+        DType::I32 => tensor.to_flatten_vec::<i32>()?.into_iter().map(f64::from).collect(),
+        DType::I8 => tensor.to_flatten_vec::<i8>()?.into_iter().map(f64::from).collect(),
+        DType::U8 => tensor.to_flatten_vec::<u8>()?.into_iter().map(f64::from).collect(),
+        DType::U32 => tensor.to_flatten_vec::<u32>()?.into_iter().map(f64::from).collect(),
+        dtype => {
+            return Err(HoduError::UnsupportedDType {
+                dtype,
+                reason: "expected a numeric constant".to_string(),
+            })
+        },
+    };
+    Ok(values)
+}
+
+fn scalar(x: &Tensor, value: f64) -> Scalar {
+    Scalar::from(value).to_dtype(x.dtype())
+}
+
+/// Reshape a per-channel `[C]` parameter to broadcast against an NC... tensor
+fn channel_param(param: &Tensor, rank: usize) -> HoduResult<Tensor> {
+    let mut shape = vec![param.size()];
+    shape.resize(rank.saturating_sub(1).max(1), 1);
+    param.reshape(Shape::new(&shape))
+}
+
+/// `(x - mean) / sqrt(var + eps)` over `dims`, with the mean and inverse std kept for reuse
+fn normalize(x: &Tensor, dims: &[usize], epsilon: f64) -> HoduResult<(Tensor, Tensor, Tensor)> {
+    let mean = x.mean(dims, true)?;
+    let centered = x.sub(&mean)?;
+    let var = centered.square()?.mean(dims, true)?;
+    let inv_std = var.add_scalar(scalar(x, epsilon))?.sqrt()?.recip()?;
+    Ok((centered.mul(&inv_std)?, mean, inv_std))
+}
+
+// ============================================================================
+// Shape helpers
+// ============================================================================
+
+fn input_name(node: &NodeProto, index: usize) -> Option<&str> {
+    node.input
+        .get(index)
+        .map(String::as_str)
+        .filter(|name| !name.is_empty())
+}
+
+fn missing_input(index: usize) -> HoduError {
+    HoduError::InvalidArgument(format!("missing required input {}", index))
+}
+
+fn normalize_axis(axis: i64, rank: usize) -> HoduResult<usize> {
+    let normalized = if axis < 0 { axis + rank as i64 } else { axis };
+    if normalized < 0 || normalized >= rank.max(1) as i64 {
+        return Err(HoduError::InvalidArgument(format!(
+            "axis {} out of range for rank {}",
+            axis, rank
+        )));
+    }
+    Ok(normalized as usize)
+}
+
+fn normalize_axes(axes: &[i64], rank: usize) -> HoduResult<Vec<usize>> {
+    axes.iter().map(|&axis| normalize_axis(axis, rank)).collect()
+}
+
+fn normalize_index(index: i64, dim: usize) -> HoduResult<usize> {
+    let normalized = if index < 0 { index + dim as i64 } else { index };
+    if normalized < 0 || normalized >= dim as i64 {
+        return Err(HoduError::InvalidArgument(format!(
+            "index {} out of range for dimension {}",
+            index, dim
+        )));
+    }
+    Ok(normalized as usize)
+}
+
+fn uniform(values: &[i64], what: &str) -> HoduResult<usize> {
+    match values.first() {
+        Some(&first) if values.iter().all(|&v| v == first) && first > 0 => Ok(first as usize),
+        None => Ok(1),
+        _ => Err(HoduError::UnsupportedOperation(format!(
+            "non-uniform {} {:?} are not supported",
+            what, values
+        ))),
+    }
+}
+
+/// Begin/end padding per spatial dim from `pads` or `auto_pad`
+fn window_pads(
+    attrs: &Attrs,
+    input: &[usize],
+    kernel: &[usize],
+    strides: &[i64],
+    dilations: &[i64],
+) -> HoduResult<Vec<(usize, usize)>> {
+    let spatial = input.len();
+    if kernel.len() != spatial || strides.len() != spatial || dilations.len() != spatial {
+        return Err(HoduError::InvalidArgument(
+            "kernel_shape, strides and dilations must match the spatial rank".to_string(),
+        ));
+    }
+    match attrs.string("auto_pad", "NOTSET").as_str() {
+        "NOTSET" => {
+            let pads = attrs.ints("pads").unwrap_or_else(|| vec![0; 2 * spatial]);
+            if pads.len() != 2 * spatial || pads.iter().any(|&p| p < 0) {
+                return Err(HoduError::InvalidArgument(format!("invalid pads {:?}", pads)));
+            }
+            Ok((0..spatial)
+                .map(|i| (pads[i] as usize, pads[i + spatial] as usize))
+                .collect())
+        },
+        "VALID" => Ok(vec![(0, 0); spatial]),
+        mode @ ("SAME_UPPER" | "SAME_LOWER") => Ok((0..spatial)
+            .map(|i| {
+                let stride = strides[i].max(1) as usize;
+                let extent = (kernel[i] - 1) * dilations[i].max(1) as usize + 1;
+                let output = input[i].div_ceil(stride);
+                let total = ((output - 1) * stride + extent).saturating_sub(input[i]);
+                match mode {
+                    "SAME_UPPER" => (total / 2, total - total / 2),
+                    _ => (total - total / 2, total / 2),
+                }
+            })
+            .collect()),
+        mode => Err(HoduError::UnsupportedOperation(format!(
+            "auto_pad '{}' is not supported",
+            mode
+        ))),
+    }
+}
+
+/// Clamped `(start, end)` of a slice along a dim of size `dim`, per the ONNX rules
+fn slice_bounds(start: i64, end: i64, step: i64, dim: usize) -> (i64, i64) {
+    let dim = dim as i64;
+    let start = if start < 0 { start + dim } else { start };
+    let end = if end < 0 { end.saturating_add(dim) } else { end };
+    match step > 0 {
+        true => (start.clamp(0, dim), end.clamp(0, dim)),
+        false => (start.clamp(0, dim - 1), end.clamp(-1, dim - 1)),
+    }
+}
+
+fn reshape_shape(input: &[usize], target: &[i64], allow_zero: bool) -> HoduResult<Vec<usize>> {
+    let size: usize = input.iter().product();
+    let mut inferred = None;
+    let mut shape = Vec::with_capacity(target.len());
+    for (i, &dim) in target.iter().enumerate() {
+        shape.push(match dim {
+            -1 if inferred.is_none() => {
+                inferred = Some(i);
+                1
+            },
+            0 if !allow_zero => *input
+                .get(i)
+                .ok_or_else(|| HoduError::InvalidArgument(format!("cannot copy dimension {} of {:?}", i, input)))?,
+            d if d >= 0 => d as usize,
+            _ => {
+                return Err(HoduError::InvalidArgument(format!(
+                    "invalid reshape target {:?}",
+                    target
+                )))
+            },
+        });
+    }
+    if let Some(i) = inferred {
+        let known: usize = shape.iter().product();
+        if known == 0 || !size.is_multiple_of(known) {
+            return Err(HoduError::InvalidArgument(format!(
+                "cannot reshape {:?} to {:?}",
+                input, target
+            )));
+        }
+        shape[i] = size / known;
+    }
+    Ok(shape)
+}
+
+fn squeeze_shape(input: &[usize], axes: Option<&[i64]>) -> HoduResult<Vec<usize>> {
+    let axes = match axes {
+        Some(axes) => normalize_axes(axes, input.len())?,
+        None => (0..input.len()).filter(|&i| input[i] == 1).collect(),
+    };
+    Ok(input
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !axes.contains(i))
+        .map(|(_, &d)| d)
+        .collect())
+}
+
+fn unsqueeze_shape(input: &[usize], axes: &[i64]) -> HoduResult<Vec<usize>> {
+    let rank = input.len() + axes.len();
+    let axes = normalize_axes(axes, rank)?;
+    let mut dims = input.iter();
+    Ok((0..rank)
+        .map(|i| match axes.contains(&i) {
+            true => 1,
+            false => *dims.next().unwrap_or(&1),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::proto::*;
+    use super::*;
+    use crate::snapshot::Interpreter;
+
+    fn attr_int(name: &str, i: i64) -> AttributeProto {
+        AttributeProto {
+            name: name.to_string(),
+            i,
+            r#type: 2,
+            ..Default::default()
+        }
+    }
+
+    fn attr_ints(name: &str, ints: &[i64]) -> AttributeProto {
+        AttributeProto {
+            name: name.to_string(),
+            ints: ints.to_vec(),
+            r#type: 7,
+            ..Default::default()
+        }
+    }
+
+    fn node(op: &str, name: &str, inputs: &[&str], outputs: &[&str], attribute: Vec<AttributeProto>) -> NodeProto {
+        NodeProto {
+            input: inputs.iter().map(|s| s.to_string()).collect(),
+            output: outputs.iter().map(|s| s.to_string()).collect(),
+            name: name.to_string(),
+            op_type: op.to_string(),
+            attribute,
+            ..Default::default()
+        }
+    }
+
+    fn float_tensor(name: &str, dims: &[i64], values: Vec<f32>) -> TensorProto {
+        TensorProto {
+            dims: dims.to_vec(),
+            data_type: data_type::FLOAT,
+            float_data: values,
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn value_info(name: &str, dims: &[Option<i64>], param: &str) -> ValueInfoProto {
+        let dim = dims
+            .iter()
+            .map(|d| TensorShapeDimension {
+                dim_value: *d,
+                dim_param: d.is_none().then(|| param.to_string()),
+            })
+            .collect();
+        ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(TypeProto {
+                tensor_type: Some(TypeProtoTensor {
+                    elem_type: data_type::FLOAT,
+                    shape: Some(TensorShapeProto { dim }),
+                }),
+            }),
+        }
+    }
+
+    fn model(nodes: Vec<NodeProto>, initializer: Vec<TensorProto>, input: ValueInfoProto) -> Vec<u8> {
+        ModelProto {
+            ir_version: 8,
+            producer_name: "test".to_string(),
+            opset_import: vec![OperatorSetIdProto {
+                domain: String::new(),
+                version: 17,
+            }],
+            graph: Some(GraphProto {
+                node: nodes,
+                name: "net".to_string(),
+                initializer,
+                input: vec![input],
+                output: vec![value_info("y", &[], "")],
+            }),
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn test_import_conv_relu_flatten_gemm() {
+        // Conv 1->2 channels (3x3, pad 1) -> Relu -> Shape-driven Reshape -> Gemm
+        let conv_w: Vec<f32> = (0..18).map(|i| (i as f32 - 9.0) * 0.1).collect();
+        let gemm_w: Vec<f32> = (0..3 * 32).map(|i| ((i % 7) as f32 - 3.0) * 0.05).collect();
+        let nodes = vec![
+            node(
+                "Conv",
+                "conv",
+                &["x", "conv_w", "conv_b"],
+                &["c"],
+                vec![attr_ints("kernel_shape", &[3, 3]), attr_ints("pads", &[1, 1, 1, 1])],
+            ),
+            node("Relu", "relu", &["c"], &["r"], vec![]),
+            node("Shape", "shape", &["r"], &["s"], vec![]),
+            node("Gather", "batch", &["s", "zero"], &["n"], vec![attr_int("axis", 0)]),
+            node("Unsqueeze", "unsq", &["n", "axes"], &["n1"], vec![]),
+            node(
+                "Concat",
+                "cat",
+                &["n1", "minus_one"],
+                &["target"],
+                vec![attr_int("axis", 0)],
+            ),
+            node("Reshape", "flatten", &["r", "target"], &["f"], vec![]),
+            node(
+                "Gemm",
+                "fc",
+                &["f", "gemm_w", "gemm_b"],
+                &["y"],
+                vec![attr_int("transB", 1)],
+            ),
+        ];
+        let int64 = |name: &str, dims: &[i64], values: &[i64]| TensorProto {
+            dims: dims.to_vec(),
+            data_type: data_type::INT64,
+            int64_data: values.to_vec(),
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let initializer = vec![
+            float_tensor("conv_w", &[2, 1, 3, 3], conv_w.clone()),
+            float_tensor("conv_b", &[2], vec![0.1, -0.2]),
+            float_tensor("gemm_w", &[3, 32], gemm_w.clone()),
+            float_tensor("gemm_b", &[3], vec![0.5, 0.0, -0.5]),
+            int64("zero", &[], &[0]),
+            int64("axes", &[1], &[0]),
+            int64("minus_one", &[1], &[-1]),
+        ];
+        let data = model(
+            nodes,
+            initializer,
+            value_info("x", &[None, Some(1), Some(4), Some(4)], "batch"),
+        );
+
+        let snapshot = OnnxImporter::new().dim("batch", 2).from_bytes(&data).unwrap();
+        assert_eq!(snapshot.name.as_deref(), Some("net"));
+        assert_eq!(snapshot.inputs[0].shape.dims(), &[2, 1, 4, 4]);
+        assert_eq!(snapshot.metadata.get("format").map(String::as_str), Some("onnx"));
+        assert!(snapshot.nodes.iter().any(|n| n.name.as_deref() == Some("fc")));
+        // The shape subgraph is folded away
+        assert!(!snapshot.nodes.iter().any(|n| n.name.as_deref() == Some("shape")));
+
+        let x = Tensor::randn([2, 1, 4, 4], 0.0f32, 1.0).unwrap();
+        let outputs = Interpreter::new(&snapshot).run(&[("x", &x)]).unwrap();
+
+        let conv_w = Tensor::from_slice(conv_w, [2, 1, 3, 3]).unwrap();
+        let conv_b = Tensor::from_slice(vec![0.1f32, -0.2], [1, 2, 1, 1]).unwrap();
+        let gemm_w = Tensor::from_slice(gemm_w, [3, 32]).unwrap();
+        let gemm_b = Tensor::from_slice(vec![0.5f32, 0.0, -0.5], [3]).unwrap();
+        let expected = x
+            .conv2d(&conv_w, 1, 1, 1)
+            .unwrap()
+            .add(&conv_b)
+            .unwrap()
+            .relu()
+            .unwrap()
+            .reshape([2, 32])
+            .unwrap()
+            .matmul(&gemm_w.t().unwrap())
+            .unwrap()
+            .add(&gemm_b)
+            .unwrap();
+
+        assert_eq!(outputs[0].0, "y");
+        assert_eq!(outputs[0].1.shape().dims(), &[2, 3]);
+        for (a, b) in outputs[0]
+            .1
+            .to_flatten_vec::<f32>()
+            .unwrap()
+            .iter()
+            .zip(expected.to_flatten_vec::<f32>().unwrap())
+        {
+            assert!((a - b).abs() < 1e-4, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_import_batch_norm_matches_formula() {
+        let nodes = vec![node(
+            "BatchNormalization",
+            "bn",
+            &["x", "scale", "bias", "mean", "var"],
+            &["y"],
+            vec![],
+        )];
+        let initializer = vec![
+            float_tensor("scale", &[2], vec![2.0, 0.5]),
+            float_tensor("bias", &[2], vec![1.0, -1.0]),
+            float_tensor("mean", &[2], vec![0.5, -0.5]),
+            float_tensor("var", &[2], vec![4.0, 0.25]),
+        ];
+        let data = model(nodes, initializer, value_info("x", &[Some(1), Some(2), Some(2)], ""));
+        let snapshot = from_bytes(&data).unwrap();
+
+        let x = Tensor::from_slice(vec![1.0f32, 2.0, 3.0, 4.0], [1, 2, 2]).unwrap();
+        let outputs = Interpreter::new(&snapshot).run(&[("x", &x)]).unwrap();
+        let y = outputs[0].1.to_flatten_vec::<f32>().unwrap();
+
+        let eps = 1e-5f32;
+        let expected = [
+            (1.0 - 0.5) / (4.0 + eps).sqrt() * 2.0 + 1.0,
+            (2.0 - 0.5) / (4.0 + eps).sqrt() * 2.0 + 1.0,
+            (3.0 + 0.5) / (0.25 + eps).sqrt() * 0.5 - 1.0,
+            (4.0 + 0.5) / (0.25 + eps).sqrt() * 0.5 - 1.0,
+        ];
+        for (a, b) in y.iter().zip(expected) {
+            assert!((a - b).abs() < 1e-4, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_ops_are_listed_with_node_names() {
+        let nodes = vec![
+            node("Relu", "relu", &["x"], &["a"], vec![]),
+            node("NonMaxSuppression", "nms", &["a"], &["b"], vec![]),
+            node("TopK", "topk_1", &["b"], &["c"], vec![]),
+            node("TopK", "topk_2", &["c"], &["y"], vec![]),
+        ];
+        let data = model(nodes, vec![], value_info("x", &[Some(4)], ""));
+
+        let err = from_bytes(&data).unwrap_err().to_string();
+        assert!(err.contains("NonMaxSuppression (nodes: nms)"), "{}", err);
+        assert!(err.contains("TopK (nodes: topk_1, topk_2)"), "{}", err);
+    }
+}
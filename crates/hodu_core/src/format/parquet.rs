@@ -0,0 +1,194 @@
+//! Apache Parquet format support (`parquet` feature)
+//!
+//! Loads the numeric top-level columns of a Parquet file as a [rows, columns] tensor. Only the
+//! selected columns are decoded. Booleans load as 0/1 and nulls as NaN.
+//!
+//! The dtype is inferred unless set in [`ParquetOptions`]: integer columns that fit in i32 load
+//! as i32, everything else as f32.
+
+use super::table::{self, Cell};
+use crate::error::{HoduError, HoduResult};
+use crate::tensor::Tensor;
+use crate::types::DType;
+use parquet::file::reader::{ChunkReader, FileReader, SerializedFileReader};
+use parquet::record::Field;
+use parquet::schema::types::Type;
+
+/// Options for loading Parquet tables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParquetOptions {
+    /// Columns to load, by name or zero-based index (`None` loads all of them)
+    pub columns: Option<Vec<String>>,
+    /// Dtype of the loaded tensor (`None` infers it)
+    pub dtype: Option<DType>,
+}
+
+impl ParquetOptions {
+    pub const DEFAULT: Self = Self {
+        columns: None,
+        dtype: None,
+    };
+
+    pub fn with_columns<S: Into<String>>(mut self, columns: impl IntoIterator<Item = S>) -> Self {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_dtype(mut self, dtype: DType) -> Self {
+        self.dtype = Some(dtype);
+        self
+    }
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Load a table from .parquet file with default options
+#[cfg(feature = "std")]
+pub fn load(path: impl AsRef<std::path::Path>) -> HoduResult<Tensor> {
+    load_with(path, &ParquetOptions::DEFAULT)
+}
+
+/// Load a table from .parquet file
+#[cfg(feature = "std")]
+pub fn load_with(path: impl AsRef<std::path::Path>, options: &ParquetOptions) -> HoduResult<Tensor> {
+    let file = std::fs::File::open(path.as_ref())
+        .map_err(|e| HoduError::IoError(format!("Failed to open parquet file: {}", e)))?;
+    read(file, options)
+}
+
+/// Deserialize a table from Parquet bytes with default options
+pub fn deserialize(data: &[u8]) -> HoduResult<Tensor> {
+    deserialize_with(data, &ParquetOptions::DEFAULT)
+}
+
+/// Deserialize a table from Parquet bytes
+pub fn deserialize_with(data: &[u8], options: &ParquetOptions) -> HoduResult<Tensor> {
+    read(bytes::Bytes::copy_from_slice(data), options)
+}
+
+fn read<R: ChunkReader + 'static>(input: R, options: &ParquetOptions) -> HoduResult<Tensor> {
+    let reader = SerializedFileReader::new(input).map_err(parquet_err)?;
+    let root = reader.metadata().file_metadata().schema_descr().root_schema();
+    let fields = root.get_fields();
+    let names: Vec<String> = fields.iter().map(|field| field.name().to_string()).collect();
+    let columns = table::select_columns(&names, fields.len(), options.columns.as_deref())?;
+
+    // Rows of the projection hold the selected columns in file order
+    let mut projected = columns.clone();
+    projected.sort_unstable();
+    projected.dedup();
+    let positions: Vec<usize> = columns
+        .iter()
+        .map(|column| projected.binary_search(column).unwrap_or_default())
+        .collect();
+    let projection = Type::group_type_builder(root.name())
+        .with_fields(projected.iter().map(|&i| fields[i].clone()).collect())
+        .build()
+        .map_err(parquet_err)?;
+
+    let total = reader.metadata().file_metadata().num_rows().max(0) as usize;
+    let mut cells = Vec::with_capacity(total * columns.len());
+    let mut rows = 0;
+    for row in reader.get_row_iter(Some(projection)).map_err(parquet_err)? {
+        let row = row.map_err(parquet_err)?;
+        let values: Vec<&Field> = row.get_column_iter().map(|(_, field)| field).collect();
+        for (&column, &position) in columns.iter().zip(&positions) {
+            cells.push(to_cell(values[position]).ok_or_else(|| {
+                HoduError::DeserializationFailed(format!(
+                    "Parquet column '{}' is not numeric (row {} is {})",
+                    names[column], rows, values[position]
+                ))
+            })?);
+        }
+        rows += 1;
+    }
+
+    table::to_tensor(cells, rows, columns.len(), options.dtype)
+}
+
+fn to_cell(field: &Field) -> Option<Cell> {
+    Some(match *field {
+        Field::Null => Cell::Float(f64::NAN),
+        Field::Bool(v) => Cell::Int(v as i64),
+        Field::Byte(v) => Cell::Int(v.into()),
+        Field::Short(v) => Cell::Int(v.into()),
+        Field::Int(v) => Cell::Int(v.into()),
+        Field::Long(v) => Cell::Int(v),
+        Field::UByte(v) => Cell::Int(v.into()),
+        Field::UShort(v) => Cell::Int(v.into()),
+        Field::UInt(v) => Cell::Int(v.into()),
+        Field::ULong(v) => i64::try_from(v).map(Cell::Int).unwrap_or(Cell::Float(v as f64)),
+        Field::Float16(v) => Cell::Float(f64::from(v.to_f32())),
+        Field::Float(v) => Cell::Float(v.into()),
+        Field::Double(v) => Cell::Float(v),
+        _ => return None,
+    })
+}
+
+fn parquet_err(e: parquet::errors::ParquetError) -> HoduError {
+    HoduError::DeserializationFailed(format!("Failed to read parquet data: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::data_type::{DoubleType, Int32Type};
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    fn write_table() -> Vec<u8> {
+        let schema = Arc::new(
+            parse_message_type("message table { REQUIRED INT32 id; REQUIRED DOUBLE score; REQUIRED INT32 age; }")
+                .unwrap(),
+        );
+        let mut data = Vec::new();
+        let mut writer = SerializedFileWriter::new(&mut data, schema, Default::default()).unwrap();
+        let mut group = writer.next_row_group().unwrap();
+
+        let mut column = group.next_column().unwrap().unwrap();
+        column.typed::<Int32Type>().write_batch(&[1, 2, 3], None, None).unwrap();
+        column.close().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<DoubleType>()
+            .write_batch(&[0.5, 1.5, 2.5], None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int32Type>()
+            .write_batch(&[30, 40, 50], None, None)
+            .unwrap();
+        column.close().unwrap();
+
+        group.close().unwrap();
+        writer.close().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_load_all_columns() {
+        let tensor = deserialize(&write_table()).unwrap();
+        assert_eq!(tensor.dtype(), DType::F32);
+        assert_eq!(tensor.shape().dims(), &[3, 3]);
+        assert_eq!(
+            tensor.to_flatten_vec::<f32>().unwrap(),
+            vec![1.0, 0.5, 30.0, 2.0, 1.5, 40.0, 3.0, 2.5, 50.0]
+        );
+    }
+
+    #[test]
+    fn test_selects_columns() {
+        let data = write_table();
+        let tensor = deserialize_with(&data, &ParquetOptions::default().with_columns(["age", "0"])).unwrap();
+        assert_eq!(tensor.dtype(), DType::I32);
+        assert_eq!(tensor.to_flatten_vec::<i32>().unwrap(), vec![30, 1, 40, 2, 50, 3]);
+
+        assert!(deserialize_with(&data, &ParquetOptions::default().with_columns(["height"])).is_err());
+    }
+}
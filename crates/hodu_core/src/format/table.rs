@@ -0,0 +1,108 @@
+//! Tabular data shared by the CSV and Parquet loaders
+//!
+//! Both read a table of numeric cells, select columns by name or index, and lay the result out as
+//! a row-major [rows, columns] tensor.
+
+use crate::error::{HoduError, HoduResult};
+use crate::tensor::Tensor;
+use crate::types::DType;
+
+/// A numeric table cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Cell {
+    Int(i64),
+    Float(f64),
+}
+
+impl Cell {
+    fn as_f64(self) -> f64 {
+        match self {
+            Self::Int(v) => v as f64,
+            Self::Float(v) => v,
+        }
+    }
+}
+
+/// Resolve column selectors against the table's column names
+///
+/// A selector matching a column name picks that column; otherwise it must be a zero-based index.
+/// Without selectors every column is kept.
+pub(crate) fn select_columns(names: &[String], count: usize, selectors: Option<&[String]>) -> HoduResult<Vec<usize>> {
+    let Some(selectors) = selectors else {
+        return Ok((0..count).collect());
+    };
+    if selectors.is_empty() {
+        return Err(HoduError::InvalidArgument("no columns selected".to_string()));
+    }
+
+    selectors
+        .iter()
+        .map(|selector| {
+            if let Some(index) = names.iter().position(|name| name == selector) {
+                return Ok(index);
+            }
+            match selector.parse::<usize>() {
+                Ok(index) if index < count => Ok(index),
+                _ => Err(HoduError::InvalidArgument(format!(
+                    "unknown column '{}' (columns: {:?})",
+                    selector, names
+                ))),
+            }
+        })
+        .collect()
+}
+
+/// Build a [rows, columns] tensor from row-major cells
+///
+/// Without an explicit dtype, tables of integers that fit in i32 become i32 and anything else
+/// becomes f32.
+pub(crate) fn to_tensor(cells: Vec<Cell>, rows: usize, columns: usize, dtype: Option<DType>) -> HoduResult<Tensor> {
+    let shape = [rows, columns];
+    let all_i32 = cells
+        .iter()
+        .all(|cell| matches!(cell, Cell::Int(v) if i32::try_from(*v).is_ok()));
+    let dtype = dtype.unwrap_or(if all_i32 { DType::I32 } else { DType::F32 });
+
+    let tensor = if dtype.is_float() {
+        #[cfg(feature = "f64")]
+        if dtype == DType::F64 {
+            let values: Vec<f64> = cells.into_iter().map(Cell::as_f64).collect();
+            return Tensor::from_slice(values, shape);
+        }
+        let values: Vec<f32> = cells.into_iter().map(|cell| cell.as_f64() as f32).collect();
+        Tensor::from_slice(values, shape)?
+    } else {
+        let values = integers(cells, dtype)?;
+        #[cfg(feature = "i64")]
+        if dtype == DType::I64 {
+            return Tensor::from_slice(values, shape);
+        }
+        let values = values
+            .into_iter()
+            .map(|v| {
+                i32::try_from(v)
+                    .map_err(|_| HoduError::InvalidArgument(format!("value {} is out of range for {}", v, dtype)))
+            })
+            .collect::<HoduResult<Vec<i32>>>()?;
+        Tensor::from_slice(values, shape)?
+    };
+
+    if tensor.dtype() == dtype {
+        Ok(tensor)
+    } else {
+        tensor.to_dtype(dtype)
+    }
+}
+
+fn integers(cells: Vec<Cell>, dtype: DType) -> HoduResult<Vec<i64>> {
+    cells
+        .into_iter()
+        .map(|cell| match cell {
+            Cell::Int(v) => Ok(v),
+            Cell::Float(v) => Err(HoduError::InvalidArgument(format!(
+                "value {} is not an integer, so the table can't be loaded as {}",
+                v, dtype
+            ))),
+        })
+        .collect()
+}
@@ -0,0 +1,240 @@
+//! WAV audio format support
+//!
+//! Reads RIFF/WAVE files as [channels, samples] f32 tensors scaled to [-1, 1], along with their
+//! sample rate. Loading accepts 8/16/24/32-bit PCM and 32/64-bit float samples, including the
+//! WAVE_FORMAT_EXTENSIBLE header. Saving writes 32-bit float samples.
+
+use crate::error::{HoduError, HoduResult};
+use crate::tensor::Tensor;
+use crate::types::DType;
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+/// Decoded audio
+#[derive(Debug, Clone)]
+pub struct Audio {
+    /// [channels, samples] f32 tensor
+    pub samples: Tensor,
+    /// Samples per second
+    pub sample_rate: u32,
+}
+
+impl Audio {
+    pub fn channels(&self) -> usize {
+        self.samples.shape().dims()[0]
+    }
+
+    /// Length in seconds
+    pub fn duration(&self) -> f64 {
+        self.samples.shape().dims()[1] as f64 / self.sample_rate as f64
+    }
+}
+
+/// Load audio from .wav file
+#[cfg(feature = "std")]
+pub fn load(path: impl AsRef<std::path::Path>) -> HoduResult<Audio> {
+    let data =
+        std::fs::read(path.as_ref()).map_err(|e| HoduError::IoError(format!("Failed to read wav file: {}", e)))?;
+    deserialize(&data)
+}
+
+/// Save a [channels, samples] tensor to .wav file
+#[cfg(feature = "std")]
+pub fn save(samples: &Tensor, sample_rate: u32, path: impl AsRef<std::path::Path>) -> HoduResult<()> {
+    let data = serialize(samples, sample_rate)?;
+    std::fs::write(path.as_ref(), data).map_err(|e| HoduError::IoError(format!("Failed to write wav file: {}", e)))?;
+    Ok(())
+}
+
+/// Serialize a [channels, samples] tensor to WAV bytes with 32-bit float samples
+pub fn serialize(samples: &Tensor, sample_rate: u32) -> HoduResult<Vec<u8>> {
+    let &[channels, frames] = samples.shape().dims() else {
+        return Err(HoduError::InvalidArgument(format!(
+            "audio must be [channels, samples], got {:?}",
+            samples.shape().dims()
+        )));
+    };
+    if channels == 0 || channels > u16::MAX as usize {
+        return Err(HoduError::InvalidArgument(format!(
+            "unsupported channel count {}",
+            channels
+        )));
+    }
+    let values = if samples.dtype() == DType::F32 {
+        samples.to_flatten_vec::<f32>()?
+    } else {
+        samples.to_dtype(DType::F32)?.to_flatten_vec::<f32>()?
+    };
+
+    let block_align = channels * 4;
+    let data_len = u32::try_from(frames * block_align)
+        .ok()
+        .filter(|len| *len <= u32::MAX - 36)
+        .ok_or_else(|| HoduError::SerializationFailed("audio is too long for a wav file".to_string()))?;
+
+    let mut data = Vec::with_capacity(44 + data_len as usize);
+    data.extend_from_slice(b"RIFF");
+    data.extend_from_slice(&(36 + data_len).to_le_bytes());
+    data.extend_from_slice(b"WAVEfmt ");
+    data.extend_from_slice(&16u32.to_le_bytes());
+    data.extend_from_slice(&FORMAT_FLOAT.to_le_bytes());
+    data.extend_from_slice(&(channels as u16).to_le_bytes());
+    data.extend_from_slice(&sample_rate.to_le_bytes());
+    data.extend_from_slice(&sample_rate.saturating_mul(block_align as u32).to_le_bytes());
+    data.extend_from_slice(&(block_align as u16).to_le_bytes());
+    data.extend_from_slice(&32u16.to_le_bytes());
+    data.extend_from_slice(b"data");
+    data.extend_from_slice(&data_len.to_le_bytes());
+    // Interleave channels frame by frame
+    for frame in 0..frames {
+        for channel in 0..channels {
+            data.extend_from_slice(&values[channel * frames + frame].to_le_bytes());
+        }
+    }
+    Ok(data)
+}
+
+/// Deserialize audio from WAV bytes
+pub fn deserialize(data: &[u8]) -> HoduResult<Audio> {
+    let invalid = |msg: &str| HoduError::DeserializationFailed(format!("Invalid wav file: {}", msg));
+    if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(invalid("missing RIFF/WAVE header"));
+    }
+
+    let mut format = None;
+    let mut samples = None;
+    let mut rest = &data[12..];
+    while rest.len() >= 8 {
+        let id = &rest[..4];
+        let len = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        // Writers that stream often leave the data length unset; the chunk then runs to the end
+        let body = &rest[8..];
+        let body = &body[..len.min(body.len())];
+        match id {
+            b"fmt " => format = Some(parse_format(body).ok_or_else(|| invalid("malformed fmt chunk"))?),
+            b"data" => samples = Some(body),
+            _ => {},
+        }
+        // Chunks are padded to an even length
+        rest = &rest[len.saturating_add(8 + (len & 1)).min(rest.len())..];
+    }
+
+    let format = format.ok_or_else(|| invalid("missing fmt chunk"))?;
+    let samples = samples.ok_or_else(|| invalid("missing data chunk"))?;
+    let channels = format.channels as usize;
+    let width = format.bits as usize / 8;
+    if channels == 0 || format.bits == 0 || !format.bits.is_multiple_of(8) {
+        return Err(invalid("bad channel count or sample size"));
+    }
+    let decode: fn(&[u8]) -> f32 = match (format.code, format.bits) {
+        (FORMAT_PCM, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+        (FORMAT_PCM, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+        (FORMAT_PCM, 24) => |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+        (FORMAT_PCM, 32) => |b| (i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64 / 2_147_483_648.0) as f32,
+        (FORMAT_FLOAT, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        (FORMAT_FLOAT, 64) => |b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+        (code, bits) => {
+            return Err(HoduError::UnsupportedOperation(format!(
+                "wav sample format {} with {} bits",
+                code, bits
+            )))
+        },
+    };
+
+    // A trailing partial frame is dropped
+    let frames = samples.len() / (channels * width);
+    let mut values = vec![0f32; channels * frames];
+    for (frame, bytes) in samples.chunks_exact(channels * width).enumerate() {
+        for (channel, sample) in bytes.chunks_exact(width).enumerate() {
+            values[channel * frames + frame] = decode(sample);
+        }
+    }
+
+    Ok(Audio {
+        samples: Tensor::from_slice(values, [channels, frames])?,
+        sample_rate: format.sample_rate,
+    })
+}
+
+struct Format {
+    code: u16,
+    channels: u16,
+    sample_rate: u32,
+    bits: u16,
+}
+
+fn parse_format(body: &[u8]) -> Option<Format> {
+    let u16_at = |i: usize| Some(u16::from_le_bytes(body.get(i..i + 2)?.try_into().ok()?));
+    let mut code = u16_at(0)?;
+    if code == FORMAT_EXTENSIBLE {
+        // The actual format is the first two bytes of the subformat GUID
+        code = u16_at(24)?;
+    }
+    Some(Format {
+        code,
+        channels: u16_at(2)?,
+        sample_rate: u32::from_le_bytes(body.get(4..8)?.try_into().ok()?),
+        bits: u16_at(14)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 16-bit PCM stereo file with the given interleaved samples
+    fn pcm16(samples: &[i16]) -> Vec<u8> {
+        let data_len = samples.len() as u32 * 2;
+        let mut data = Vec::new();
+        data.extend_from_slice(b"RIFF");
+        data.extend_from_slice(&(36 + data_len + 10).to_le_bytes());
+        data.extend_from_slice(b"WAVEfmt ");
+        data.extend_from_slice(&16u32.to_le_bytes());
+        for v in [FORMAT_PCM, 2] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        data.extend_from_slice(&16000u32.to_le_bytes());
+        data.extend_from_slice(&64000u32.to_le_bytes());
+        for v in [4u16, 16] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        // An odd-sized chunk before the data must be skipped with its padding byte
+        data.extend_from_slice(b"LIST\x01\x00\x00\x00x\x00");
+        data.extend_from_slice(b"data");
+        data.extend_from_slice(&data_len.to_le_bytes());
+        for s in samples {
+            data.extend_from_slice(&s.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_deserialize_pcm16() {
+        let audio = deserialize(&pcm16(&[0, 16384, -32768, 8192, 16384, -16384])).unwrap();
+        assert_eq!(audio.sample_rate, 16000);
+        assert_eq!(audio.channels(), 2);
+        assert_eq!(audio.samples.shape().dims(), &[2, 3]);
+        assert_eq!(
+            audio.samples.to_flatten_vec::<f32>().unwrap(),
+            vec![0.0, -1.0, 0.5, 0.5, 0.25, -0.5]
+        );
+        assert!((audio.duration() - 3.0 / 16000.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_serialize_roundtrip() {
+        let samples = Tensor::from_slice(vec![0.1f32, -0.2, 0.3, 0.4, 0.5, -0.6], [3, 2]).unwrap();
+        let audio = deserialize(&serialize(&samples, 44100).unwrap()).unwrap();
+        assert_eq!(audio.sample_rate, 44100);
+        assert_eq!(audio.samples.shape().dims(), &[3, 2]);
+        assert_eq!(
+            audio.samples.to_flatten_vec::<f32>().unwrap(),
+            samples.to_flatten_vec::<f32>().unwrap()
+        );
+
+        assert!(serialize(&Tensor::from_slice(vec![0.0f32; 4], [4]).unwrap(), 8000).is_err());
+        assert!(deserialize(b"RIFF\x00\x00\x00\x00WAVE").is_err());
+    }
+}
@@ -4,6 +4,8 @@ pub(crate) mod be_cpu;
 pub(crate) mod be_cuda;
 #[cfg(feature = "metal")]
 pub(crate) mod be_metal;
+#[cfg(feature = "wgpu")]
+pub(crate) mod be_wgpu;
 pub(crate) mod cache;
 pub mod einsum;
 pub mod error;
@@ -13,6 +15,7 @@ pub mod op_metadatas;
 pub mod op_params;
 pub mod ops;
 pub mod prelude;
+pub mod profiler;
 pub mod scalar;
 pub mod snapshot;
 pub mod tensor;
@@ -4,6 +4,7 @@
 //! - CPU backend (be_cpu)
 //! - CUDA backend (be_cuda)
 //! - Metal backend (be_metal)
+//! - WebGPU backend (be_wgpu)
 //! - JIT compilation
 //!
 //! Each operation type has a specific metadata format that matches the kernel expectations.
@@ -11,11 +12,11 @@
 //! Order follows ops.rs enum definitions:
 //! Binary -> BinaryLogical -> Cmp -> CmpScalar -> Unary -> UnaryLogical -> UnaryScalar
 //! -> Matrix -> Reduce -> Concat -> Split -> Indexing -> Conv -> Windowing -> Resize
-//! -> Padding -> Scan -> Sort -> Einsum -> ShapeMemory -> Cast -> Memory
+//! -> Padding -> Scan -> Sort -> Einsum -> ShapeMemory -> Cast -> Memory -> Quant
 
 use crate::{
     error::{HoduError, HoduResult},
-    types::Layout,
+    types::{BlockFormat, Layout, Shape},
 };
 
 // ============================================================================
@@ -290,6 +291,30 @@ pub fn matmul_metadata(lhs_layout: &Layout, rhs_layout: &Layout, output_layout:
     Ok(metadata)
 }
 
+/// Compute the output shape of a batched matmul: broadcast batch dims followed by `[M, N]`
+pub fn matmul_output_shape(lhs_layout: &Layout, rhs_layout: &Layout) -> HoduResult<Shape> {
+    let lhs_dims = lhs_layout.shape().dims();
+    let rhs_dims = rhs_layout.shape().dims();
+    let lhs_ndim = lhs_dims.len();
+    let rhs_ndim = rhs_dims.len();
+
+    if lhs_ndim < 2 || rhs_ndim < 2 {
+        return Err(HoduError::InvalidArgument("matmul requires at least 2D tensors".into()));
+    }
+
+    let batch_ndim = lhs_ndim.max(rhs_ndim) - 2;
+    let mut dims = Vec::with_capacity(batch_ndim + 2);
+    for i in 0..batch_ndim {
+        let lhs_dim = (i + lhs_ndim >= batch_ndim + 2).then(|| lhs_dims[i + lhs_ndim - 2 - batch_ndim]);
+        let rhs_dim = (i + rhs_ndim >= batch_ndim + 2).then(|| rhs_dims[i + rhs_ndim - 2 - batch_ndim]);
+        dims.push(lhs_dim.unwrap_or(1).max(rhs_dim.unwrap_or(1)));
+    }
+    dims.push(lhs_dims[lhs_ndim - 2]);
+    dims.push(rhs_dims[rhs_ndim - 1]);
+
+    Ok(Shape::from(dims))
+}
+
 /// Generate metadata for det operation (matrix determinant)
 ///
 /// Format:
@@ -509,8 +534,94 @@ pub fn dot_metadata(lhs_layout: &Layout, rhs_layout: &Layout) -> HoduResult<Vec<
 // Reduce Operations
 // ============================================================================
 
+/// Reduction plan: the fewest dims that describe a reduction over `layout`
+///
+/// Adjacent reduced dims, and adjacent kept dims, merge into one when a single stride walks
+/// both, and kept dims of size 1 are dropped, so reducing `[0, 2, 3]` of a contiguous NCHW
+/// tensor becomes a reduction over `[0, 2]` of `[N, C, H*W]`, done by one kernel launch.
+/// Output elements keep their row-major order, so the plan never needs `keep_dim`.
+///
+/// Reduced dims merged this way act as one: argmax/argmin over several adjacent dims index
+/// into their flattened extent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReducePlan {
+    pub dims: Vec<usize>,
+    pub strides: Vec<usize>,
+    pub offset: usize,
+    /// Whether each dim of the plan is reduced
+    pub reduced: Vec<bool>,
+}
+
+impl ReducePlan {
+    pub fn new(layout: &Layout, reduce_dims: &[usize]) -> Self {
+        let mut plan = Self {
+            dims: Vec::new(),
+            strides: Vec::new(),
+            offset: layout.offset(),
+            reduced: Vec::new(),
+        };
+
+        for (i, (&size, &stride)) in layout.shape().dims().iter().zip(layout.strides()).enumerate() {
+            let reduced = reduce_dims.contains(&i);
+            if size == 1 {
+                continue;
+            }
+            if let Some(last) = plan.dims.len().checked_sub(1) {
+                if plan.reduced[last] == reduced && plan.strides[last] == stride * size {
+                    plan.dims[last] *= size;
+                    plan.strides[last] = stride;
+                    continue;
+                }
+            }
+            plan.dims.push(size);
+            plan.strides.push(stride);
+            plan.reduced.push(reduced);
+        }
+
+        // Kernels index the first reduced dim, so keep one even if it only has one element
+        if !plan.reduced.contains(&true) {
+            plan.dims.push(1);
+            plan.strides.push(1);
+            plan.reduced.push(true);
+        }
+        plan
+    }
+
+    /// Dims of the output, in order
+    pub fn output_shape(&self) -> Vec<usize> {
+        let shape: Vec<usize> = (0..self.dims.len())
+            .filter(|&d| !self.reduced[d])
+            .map(|d| self.dims[d])
+            .collect();
+        if shape.is_empty() {
+            vec![1]
+        } else {
+            shape
+        }
+    }
+
+    /// Indices of the reduced dims
+    pub fn reduce_dims(&self) -> Vec<usize> {
+        (0..self.dims.len()).filter(|&d| self.reduced[d]).collect()
+    }
+
+    /// Number of input elements reduced into each output element
+    pub fn reduce_size(&self) -> usize {
+        self.dims
+            .iter()
+            .zip(&self.reduced)
+            .filter(|(_, &reduced)| reduced)
+            .map(|(&size, _)| size)
+            .product()
+    }
+}
+
 /// Generate metadata for reduce operations (sum, mean, max, min, prod, std, var, l2_norm, argmax, argmin, any, all)
 ///
+/// Describes the [`ReducePlan`] of the reduction rather than `layout` itself, so the dims below
+/// are the plan's and `keep_dim` is always 0; the output has the same elements in the same
+/// order either way.
+///
 /// Format:
 /// - metadata[0]: input_ndim
 /// - metadata[1..1+input_ndim]: input_shape
@@ -522,65 +633,32 @@ pub fn dot_metadata(lhs_layout: &Layout, rhs_layout: &Layout) -> HoduResult<Vec<
 /// - metadata[...]: reduce_dims
 /// - metadata[...]: keep_dim (0 or 1)
 /// - metadata[...]: reduce_size
-pub fn reduce_metadata(layout: &Layout, dims: &[usize], keep_dim: bool) -> Vec<usize> {
-    let input_shape = layout.shape();
-    let input_ndim = input_shape.ndim();
-
-    // Compute output shape
-    let mut output_shape_vec = Vec::new();
-    for i in 0..input_ndim {
-        if dims.contains(&i) {
-            if keep_dim {
-                output_shape_vec.push(1);
-            }
-        } else {
-            output_shape_vec.push(input_shape.dims()[i]);
-        }
-    }
-
-    // Handle empty output shape (reduce all dimensions without keep_dim)
-    if output_shape_vec.is_empty() {
-        output_shape_vec.push(1);
-    }
-
-    // Calculate reduce size
-    let mut reduce_size: usize = 1;
-    for &dim in dims {
-        reduce_size *= input_shape.dims()[dim];
-    }
+pub fn reduce_metadata(layout: &Layout, dims: &[usize]) -> Vec<usize> {
+    let plan = ReducePlan::new(layout, dims);
+    let input_ndim = plan.dims.len();
+    let output_shape = plan.output_shape();
+    let reduce_dims = plan.reduce_dims();
 
     let mut metadata =
-        Vec::with_capacity(1 + input_ndim + input_ndim + 1 + 1 + output_shape_vec.len() + 1 + dims.len() + 1 + 1);
+        Vec::with_capacity(1 + input_ndim + input_ndim + 1 + 1 + output_shape.len() + 1 + reduce_dims.len() + 1 + 1);
 
-    // input_ndim, input_shape
+    // input_ndim, input_shape, input_strides, input_offset
     metadata.push(input_ndim);
-    for &dim in input_shape.dims() {
-        metadata.push(dim);
-    }
-
-    // input_strides
-    for &stride in layout.strides() {
-        metadata.push(stride);
-    }
-
-    // input_offset
-    metadata.push(layout.offset());
+    metadata.extend(&plan.dims);
+    metadata.extend(&plan.strides);
+    metadata.push(plan.offset);
 
     // output_ndim, output_shape
-    metadata.push(output_shape_vec.len());
-    for &dim in &output_shape_vec {
-        metadata.push(dim);
-    }
+    metadata.push(output_shape.len());
+    metadata.extend(&output_shape);
 
     // num_reduce_dims, reduce_dims
-    metadata.push(dims.len());
-    for &dim in dims {
-        metadata.push(dim);
-    }
+    metadata.push(reduce_dims.len());
+    metadata.extend(&reduce_dims);
 
     // keep_dim, reduce_size
-    metadata.push(if keep_dim { 1 } else { 0 });
-    metadata.push(reduce_size);
+    metadata.push(0);
+    metadata.push(plan.reduce_size());
 
     metadata
 }
@@ -1654,3 +1732,142 @@ pub fn cast_metadata(layout: &Layout) -> Vec<usize> {
 pub fn contiguous_metadata(layout: &Layout) -> Vec<usize> {
     cast_metadata(layout)
 }
+
+// ============================================================================
+// Quant Operations
+// ============================================================================
+
+/// Generate metadata for block dequantization of a packed `u8` tensor
+///
+/// The packed bytes must be contiguous.
+///
+/// Format:
+/// - metadata[0]: num_blocks
+/// - metadata[1]: offset (bytes)
+pub fn block_dequantize_metadata(layout: &Layout, format: BlockFormat) -> HoduResult<Vec<usize>> {
+    if !layout.is_contiguous() {
+        return Err(HoduError::InvalidLayout {
+            reason: format!("packed {} data must be contiguous", format),
+        });
+    }
+    let dims = layout.shape().dims();
+    let packed = dims.last().copied().unwrap_or(0);
+    format.unpacked_dim(packed)?;
+
+    Ok(vec![layout.size() / format.block_bytes(), layout.offset()])
+}
+
+/// Generate metadata for a fused block-dequant matmul `lhs[..., K] @ dequant(weight[N, K])^T`
+///
+/// Leading lhs dimensions are folded into rows, so both operands must be contiguous.
+///
+/// Format:
+/// - metadata[0]: M (lhs rows)
+/// - metadata[1]: K (reduction size)
+/// - metadata[2]: N (weight rows)
+/// - metadata[3]: lhs offset (elements)
+/// - metadata[4]: weight offset (bytes)
+pub fn block_matmul_metadata(
+    lhs_layout: &Layout,
+    weight_layout: &Layout,
+    format: BlockFormat,
+) -> HoduResult<Vec<usize>> {
+    if !lhs_layout.is_contiguous() || !weight_layout.is_contiguous() {
+        return Err(HoduError::InvalidLayout {
+            reason: format!("{} matmul operands must be contiguous", format),
+        });
+    }
+
+    let lhs_dims = lhs_layout.shape().dims();
+    let weight_dims = weight_layout.shape().dims();
+    if lhs_dims.is_empty() || weight_dims.len() != 2 {
+        return Err(HoduError::InvalidLayout {
+            reason: format!(
+                "{} matmul expects lhs [..., K] and weight [N, packed K], got {:?} and {:?}",
+                format, lhs_dims, weight_dims
+            ),
+        });
+    }
+
+    let k = lhs_dims[lhs_dims.len() - 1];
+    if format.unpacked_dim(weight_dims[1])? != k {
+        return Err(HoduError::InvalidLayout {
+            reason: format!(
+                "{} weight holds {} values per row but lhs has K = {}",
+                format,
+                format.unpacked_dim(weight_dims[1])?,
+                k
+            ),
+        });
+    }
+
+    Ok(vec![
+        lhs_layout.size() / k,
+        k,
+        weight_dims[0],
+        lhs_layout.offset(),
+        weight_layout.offset(),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduce_plan_coalesces_dims() {
+        // NCHW over [0, 2, 3]: H and W merge, N stays apart from them
+        let layout = Layout::from_shape(&Shape::from(vec![2, 3, 4, 5]));
+        let plan = ReducePlan::new(&layout, &[0, 2, 3]);
+        assert_eq!(plan.dims, vec![2, 3, 20]);
+        assert_eq!(plan.strides, vec![60, 20, 1]);
+        assert_eq!(plan.reduce_dims(), vec![0, 2]);
+        assert_eq!(plan.output_shape(), vec![3]);
+        assert_eq!(plan.reduce_size(), 40);
+
+        // A transposed view only merges where the strides line up
+        let layout = Layout::from_shape(&Shape::from(vec![2, 3, 4]))
+            .permute(&[0, 2, 1])
+            .unwrap();
+        let plan = ReducePlan::new(&layout, &[1, 2]);
+        assert_eq!(plan.dims, vec![2, 4, 3]);
+        assert_eq!(plan.strides, vec![12, 1, 4]);
+        assert_eq!(plan.reduce_dims(), vec![1, 2]);
+
+        // Size-1 dims drop out; one reduced dim always remains
+        let layout = Layout::from_shape(&Shape::from(vec![1, 6, 1]));
+        let plan = ReducePlan::new(&layout, &[0, 2]);
+        assert_eq!(plan.dims, vec![6, 1]);
+        assert_eq!(plan.reduce_dims(), vec![1]);
+        assert_eq!(plan.output_shape(), vec![6]);
+        assert_eq!(plan.reduce_size(), 1);
+    }
+
+    #[test]
+    fn test_reduce_over_planned_dims() {
+        use crate::tensor::Tensor;
+
+        // Values far from zero in a permuted view: [2, 3, 4] seen as [4, 3, 2]
+        let data: Vec<f32> = (0..24).map(|i| 1000.0 + i as f32).collect();
+        let x = Tensor::from_slice(data.clone(), [2, 3, 4])
+            .unwrap()
+            .permute(&[2, 1, 0])
+            .unwrap();
+
+        let mean = x.mean(&[0, 2], false).unwrap().to_flatten_vec::<f32>().unwrap();
+        let var = x.var(&[0, 2], true).unwrap();
+        assert_eq!(var.shape().dims(), &[1, 3, 1]);
+        let var = var.to_flatten_vec::<f32>().unwrap();
+
+        for j in 0..3 {
+            let values: Vec<f32> = (0..2)
+                .flat_map(|i| (0..4).map(move |k| (i, k)))
+                .map(|(i, k)| data[i * 12 + j * 4 + k])
+                .collect();
+            let expected_mean = values.iter().sum::<f32>() / 8.0;
+            let expected_var = values.iter().map(|v| (v - expected_mean).powi(2)).sum::<f32>() / 8.0;
+            assert!((mean[j] - expected_mean).abs() < 1e-3);
+            assert!((var[j] - expected_var).abs() < 1e-3);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     scalar::Scalar,
     tensor::TensorId,
-    types::{DType, DynamicDimId},
+    types::{BlockFormat, DType, DynamicDimId},
 };
 
 // Binary Operations
@@ -400,6 +400,7 @@ pub struct TopKParams {
     pub dim: i32,
     pub largest: bool,
     pub sorted: bool,
+    /// The indices output; in a snapshot, the id of the node producing it
     pub indices_id: TensorId,
 }
 
@@ -528,6 +529,121 @@ pub struct ContiguousParams;
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetParams;
 
+// ControlFlow Operations
+
+/// Conditional execution of one of two subgraphs.
+///
+/// Inputs are `[predicate, operands...]`; the operands are passed positionally to the inputs of the
+/// selected branch. Both branches must produce the same number of targets with matching shapes and
+/// dtypes. An If op with several results is stored as one node per result, all sharing `group_id`.
+/// The branches are stored once in [`Snapshot::subgraphs`](crate::snapshot::Snapshot::subgraphs) and referenced by index from each node.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IfParams {
+    pub then_branch: usize,
+    pub else_branch: usize,
+    pub group_id: usize,
+    pub output_index: usize,
+}
+
+/// Loop over a body subgraph while a condition subgraph evaluates to true.
+///
+/// Inputs are the initial loop-carried values. `cond` maps the carried values to a single boolean
+/// scalar, `body` maps them to their next values. Results are the carried values after the last
+/// iteration, stored as one node per result sharing `group_id`. Like [`IfParams`], `cond` and
+/// `body` are indices into [`Snapshot::subgraphs`](crate::snapshot::Snapshot::subgraphs).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WhileParams {
+    pub cond: usize,
+    pub body: usize,
+    pub max_iterations: Option<usize>,
+    pub group_id: usize,
+    pub output_index: usize,
+}
+
+// Quant Operations
+
+/// Affine int8 quantization parameters: `q = clamp(round(x / scale) + zero_point, -128, 127)`.
+///
+/// Symmetric quantization is the special case `zero_point == 0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuantizeParams {
+    pub scale: f32,
+    pub zero_point: i32,
+}
+
+impl QuantizeParams {
+    pub fn new(scale: f32, zero_point: i32) -> Self {
+        Self { scale, zero_point }
+    }
+
+    /// Symmetric parameters mapping `[-abs_max, abs_max]` onto `[-127, 127]`.
+    pub fn symmetric(abs_max: f32) -> Self {
+        let abs_max = abs_max.abs();
+        let scale = if abs_max > 0.0 { abs_max / 127.0 } else { 1.0 };
+        Self { scale, zero_point: 0 }
+    }
+
+    /// Asymmetric parameters mapping `[min, max]` onto `[-128, 127]`. The range is widened to
+    /// include 0.0 so that zero is exactly representable.
+    pub fn asymmetric(min: f32, max: f32) -> Self {
+        let min = min.min(0.0);
+        let max = max.max(0.0);
+        let scale = if max > min { (max - min) / 255.0 } else { 1.0 };
+        let zero_point = (-128.0 - min / scale).round().clamp(-128.0, 127.0) as i32;
+        Self { scale, zero_point }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DequantizeParams {
+    pub scale: f32,
+    pub zero_point: i32,
+    pub dtype: DType,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatmulInt8Params;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockQuantizeParams {
+    pub format: BlockFormat,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockDequantizeParams {
+    pub format: BlockFormat,
+    pub dtype: DType,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockMatmulParams {
+    pub format: BlockFormat,
+}
+
+// Custom Operations
+
+/// Opaque operator executed by a plugin.
+///
+/// `name` is namespaced (`domain.op`, e.g. `acme.fused_gelu`) and is resolved at run time to a plugin
+/// that declares the `op.<name>` capability. `attributes` is JSON text passed to the plugin untouched.
+/// A custom op with several results is stored as one node per result, all sharing `group_id`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CustomParams {
+    pub name: String,
+    pub attributes: String,
+    pub group_id: usize,
+    pub output_index: usize,
+}
+
 // OpParams Enum
 
 #[derive(Debug, Clone)]
@@ -636,4 +752,19 @@ pub enum OpParams {
     // Memory
     Contiguous(ContiguousParams),
     Set(SetParams),
+
+    // ControlFlow
+    If(IfParams),
+    While(WhileParams),
+
+    // Quant
+    Quantize(QuantizeParams),
+    Dequantize(DequantizeParams),
+    MatmulInt8(MatmulInt8Params),
+    BlockQuantize(BlockQuantizeParams),
+    BlockDequantize(BlockDequantizeParams),
+    BlockMatmul(BlockMatmulParams),
+
+    // Custom
+    Custom(CustomParams),
 }
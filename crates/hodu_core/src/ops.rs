@@ -829,6 +829,60 @@ impl fmt::Debug for MemoryOp {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ControlFlowOp {
+    If,    // no-backprop, subgraph-carrying
+    While, // no-backprop, subgraph-carrying
+}
+
+impl fmt::Display for ControlFlowOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::If => write!(f, "if"),
+            Self::While => write!(f, "while"),
+        }
+    }
+}
+
+impl fmt::Debug for ControlFlowOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QuantOp {
+    Quantize,   // no-backprop
+    Dequantize, // no-backprop
+    MatmulInt8, // no-backprop
+
+    // block-quantized weights (packed u8 blocks, see `BlockFormat`)
+    BlockQuantize,   // no-backprop
+    BlockDequantize, // no-backprop
+    BlockMatmul,     // no-backprop
+}
+
+impl fmt::Display for QuantOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Quantize => write!(f, "quantize"),
+            Self::Dequantize => write!(f, "dequantize"),
+            Self::MatmulInt8 => write!(f, "matmul_int8"),
+            Self::BlockQuantize => write!(f, "block_quantize"),
+            Self::BlockDequantize => write!(f, "block_dequantize"),
+            Self::BlockMatmul => write!(f, "block_matmul"),
+        }
+    }
+}
+
+impl fmt::Debug for QuantOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
 #[derive(Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Op {
@@ -861,6 +915,10 @@ pub enum Op {
     Cast(CastOp),
     Memory(MemoryOp),
     Dummy,
+    // Appended after the original variants so snapshots written before them keep decoding
+    ControlFlow(ControlFlowOp),
+    Quant(QuantOp),
+    Custom,
 }
 
 impl fmt::Display for Op {
@@ -895,6 +953,9 @@ impl fmt::Display for Op {
             Self::Cast(op) => write!(f, "{}", op),
             Self::Memory(op) => write!(f, "{}", op),
             Self::Dummy => write!(f, "dummy"),
+            Self::ControlFlow(op) => write!(f, "{}", op),
+            Self::Quant(op) => write!(f, "{}", op),
+            Self::Custom => write!(f, "custom"),
         }
     }
 }
@@ -931,6 +992,9 @@ impl fmt::Debug for Op {
             Self::Cast(op) => write!(f, "Cast[{}]", op),
             Self::Memory(op) => write!(f, "Memory[{}]", op),
             Self::Dummy => write!(f, "Dummy"),
+            Self::ControlFlow(op) => write!(f, "ControlFlow[{}]", op),
+            Self::Quant(op) => write!(f, "Quant[{}]", op),
+            Self::Custom => write!(f, "Custom"),
         }
     }
 }
@@ -6,5 +6,5 @@
 pub use crate::error::HoduResult;
 pub use crate::scalar::Scalar;
 pub use crate::snapshot::capture::CaptureBoard;
-pub use crate::tensor::{get_runtime_device, set_runtime_device, Tensor};
+pub use crate::tensor::{get_runtime_device, is_lazy, lazy_scope, set_lazy, set_runtime_device, Tensor};
 pub use crate::types::*;
@@ -0,0 +1,336 @@
+//! Per-op profiler
+//!
+//! While a profile is recording, the snapshot interpreter times every node it executes and the
+//! backends log every allocation they serve. Durations come from the device's own clock: CUDA
+//! events recorded around the node's work, Metal command buffer GPU timestamps, and a host timer
+//! elsewhere. Timing a GPU node waits for its work to finish, so a profiled run is slower than a
+//! normal one and CUDA ops no longer overlap across streams.
+//!
+//! Profiles are per thread: only work issued from the thread that called [`start`] is recorded.
+//!
+//! ```ignore
+//! profiler::start()?;
+//! let outputs = Interpreter::new(&snapshot).run(&inputs)?;
+//! profiler::stop()?.save_chrome_trace("trace.json")?;
+//! ```
+
+use crate::{
+    error::{HoduError, HoduResult},
+    types::Device,
+};
+use std::{cell::RefCell, time::Instant};
+
+thread_local! {
+    static SESSION: RefCell<Option<Session>> = const { RefCell::new(None) };
+}
+
+struct Session {
+    origin: Instant,
+    profile: Profile,
+}
+
+impl Session {
+    fn elapsed_us(&self, at: Instant) -> f64 {
+        at.saturating_duration_since(self.origin).as_secs_f64() * 1e6
+    }
+}
+
+/// One timed op
+#[derive(Debug, Clone)]
+pub struct OpEvent {
+    pub name: String,
+    pub device: Device,
+    /// Microseconds from the start of the profile to the start of the op
+    pub start_us: f64,
+    pub duration_us: f64,
+    /// Bytes allocated while the op ran
+    pub allocated_bytes: usize,
+    /// Op metadata such as the node name, shapes and dtype
+    pub args: Vec<(String, String)>,
+}
+
+/// One buffer handed out by a backend
+#[derive(Debug, Clone)]
+pub struct AllocEvent {
+    pub device: Device,
+    /// Microseconds from the start of the profile
+    pub time_us: f64,
+    pub bytes: usize,
+    /// Served from the backend's buffer cache instead of a fresh driver allocation
+    pub cached: bool,
+}
+
+/// Everything recorded between [`start`] and [`stop`]
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub ops: Vec<OpEvent>,
+    pub allocs: Vec<AllocEvent>,
+}
+
+impl Profile {
+    /// Render as chrome://tracing (Trace Event Format) JSON
+    ///
+    /// Each device gets its own track; ops are complete events and allocations instant events.
+    #[cfg(feature = "serde")]
+    pub fn to_chrome_trace(&self) -> HoduResult<String> {
+        use serde_json::{json, Map, Value};
+
+        let mut devices: Vec<Device> = Vec::new();
+        let mut track = |device: Device| match devices.iter().position(|d| *d == device) {
+            Some(index) => index,
+            None => {
+                devices.push(device);
+                devices.len() - 1
+            },
+        };
+
+        let mut events = Vec::with_capacity(self.ops.len() + self.allocs.len());
+        for op in &self.ops {
+            let mut args: Map<String, Value> = op
+                .args
+                .iter()
+                .map(|(key, value)| (key.clone(), Value::from(value.as_str())))
+                .collect();
+            args.insert("allocated_bytes".into(), op.allocated_bytes.into());
+            events.push(json!({
+                "name": op.name,
+                "cat": "op",
+                "ph": "X",
+                "ts": op.start_us,
+                "dur": op.duration_us,
+                "pid": 0,
+                "tid": track(op.device),
+                "args": args,
+            }));
+        }
+        for alloc in &self.allocs {
+            events.push(json!({
+                "name": "alloc",
+                "cat": "memory",
+                "ph": "i",
+                "s": "t",
+                "ts": alloc.time_us,
+                "pid": 0,
+                "tid": track(alloc.device),
+                "args": { "bytes": alloc.bytes, "cached": alloc.cached },
+            }));
+        }
+        for (tid, device) in devices.iter().enumerate() {
+            events.push(json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": 0,
+                "tid": tid,
+                "args": { "name": device.to_string() },
+            }));
+        }
+
+        serde_json::to_string(&json!({ "traceEvents": events, "displayTimeUnit": "ms" }))
+            .map_err(|e| HoduError::SerializationFailed(format!("failed to write chrome trace: {}", e)))
+    }
+
+    /// Write [`to_chrome_trace`](Self::to_chrome_trace) to `path`
+    #[cfg(all(feature = "serde", feature = "std"))]
+    pub fn save_chrome_trace(&self, path: impl AsRef<std::path::Path>) -> HoduResult<()> {
+        std::fs::write(path.as_ref(), self.to_chrome_trace()?)
+            .map_err(|e| HoduError::IoError(format!("failed to write {}: {}", path.as_ref().display(), e)))
+    }
+}
+
+/// Start recording a profile on the current thread
+///
+/// Fails if a profile is already recording.
+pub fn start() -> HoduResult<()> {
+    SESSION.with(|session| {
+        let mut session = session.borrow_mut();
+        if session.is_some() {
+            return Err(HoduError::InvalidArgument("a profile is already recording".to_string()));
+        }
+        *session = Some(Session {
+            origin: Instant::now(),
+            profile: Profile::default(),
+        });
+        Ok(())
+    })
+}
+
+/// Stop recording on the current thread and return the profile
+pub fn stop() -> HoduResult<Profile> {
+    SESSION.with(|session| {
+        session
+            .borrow_mut()
+            .take()
+            .map(|session| session.profile)
+            .ok_or_else(|| HoduError::InvalidArgument("no profile is recording".to_string()))
+    })
+}
+
+/// Whether a profile is recording on the current thread
+#[inline]
+pub fn is_recording() -> bool {
+    SESSION.with(|session| session.borrow().is_some())
+}
+
+/// Log an allocation of `bytes` on `device`
+#[inline]
+pub(crate) fn record_alloc(device: Device, bytes: usize, cached: bool) {
+    SESSION.with(|session| {
+        // Only read the clock while recording: `Instant` is unavailable on wasm32-unknown-unknown
+        if let Some(session) = session.borrow_mut().as_mut() {
+            let time_us = session.elapsed_us(Instant::now());
+            session.profile.allocs.push(AllocEvent {
+                device,
+                time_us,
+                bytes,
+                cached,
+            });
+        }
+    });
+}
+
+/// Clock an op is timed with
+enum Clock {
+    Host,
+    #[cfg(feature = "cuda")]
+    Cuda(
+        std::sync::Arc<crate::be_cuda::device::CudaDevice>,
+        hodu_cuda_kernels::cuda::CudaEvent,
+    ),
+    #[cfg(feature = "metal")]
+    Metal,
+}
+
+/// Times one op on its device; started before the op's work is enqueued
+pub(crate) struct OpTimer {
+    device: Device,
+    host_start: Instant,
+    first_alloc: usize,
+    clock: Clock,
+}
+
+impl OpTimer {
+    pub(crate) fn start(device: Device) -> HoduResult<Self> {
+        let clock = Self::start_clock(device)?;
+        let first_alloc = SESSION.with(|session| {
+            session
+                .borrow()
+                .as_ref()
+                .map(|session| session.profile.allocs.len())
+                .unwrap_or(0)
+        });
+        Ok(Self {
+            device,
+            host_start: Instant::now(),
+            first_alloc,
+            clock,
+        })
+    }
+
+    fn start_clock(device: Device) -> HoduResult<Clock> {
+        #[cfg(feature = "cuda")]
+        if let Device::CUDA(id) = device {
+            let cuda = crate::be_cuda::device::CudaDevice::get(id)?;
+            let start = cuda.record_timing_event()?;
+            return Ok(Clock::Cuda(cuda, start));
+        }
+        #[cfg(feature = "metal")]
+        if device == Device::Metal {
+            // Drain earlier work so the next command buffer only holds this op
+            crate::be_metal::device::MetalDevice::global().wait_until_completed()?;
+            return Ok(Clock::Metal);
+        }
+        let _ = device;
+        Ok(Clock::Host)
+    }
+
+    /// Wait for the op's work to finish and record it under `name`
+    pub(crate) fn finish(self, name: String, args: Vec<(String, String)>) -> HoduResult<()> {
+        let duration_us = match self.clock {
+            Clock::Host => self.host_start.elapsed().as_secs_f64() * 1e6,
+            #[cfg(feature = "cuda")]
+            Clock::Cuda(cuda, start) => {
+                let end = cuda.record_timing_event()?;
+                let ms = start
+                    .elapsed_ms(&end)
+                    .map_err(|e| HoduError::BackendError(format!("Failed to time CUDA events: {:?}", e)))?;
+                ms as f64 * 1e3
+            },
+            #[cfg(feature = "metal")]
+            Clock::Metal => match crate::be_metal::device::MetalDevice::global().wait_until_completed_timed()? {
+                Some(seconds) => seconds * 1e6,
+                None => self.host_start.elapsed().as_secs_f64() * 1e6,
+            },
+        };
+
+        SESSION.with(|session| {
+            // Recording may have stopped while the op ran
+            if let Some(session) = session.borrow_mut().as_mut() {
+                let start_us = session.elapsed_us(self.host_start);
+                let allocated_bytes = session
+                    .profile
+                    .allocs
+                    .iter()
+                    .skip(self.first_alloc)
+                    .map(|alloc| alloc.bytes)
+                    .sum();
+                session.profile.ops.push(OpEvent {
+                    name,
+                    device: self.device,
+                    start_us,
+                    duration_us,
+                    allocated_bytes,
+                    args,
+                });
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::{
+        snapshot::{CaptureBoard, Interpreter},
+        tensor::Tensor,
+        types::DType,
+    };
+
+    #[test]
+    fn test_profile_interpreter_run() {
+        let board = CaptureBoard::new();
+        board.open();
+        let x = Tensor::input("x", [4, 8], DType::F32).unwrap();
+        let y = x.exp().unwrap().matmul(&x.transpose(0, 1).unwrap()).unwrap();
+        board.close();
+        board.with_target("y", y);
+        let snapshot = board.capture();
+
+        let input = Tensor::randn([4, 8], 0.0f32, 1.0).unwrap();
+        start().unwrap();
+        assert!(start().is_err());
+        let outputs = Interpreter::new(&snapshot).run(&[("x", &input)]);
+        let profile = stop().unwrap();
+        outputs.unwrap();
+        assert!(!is_recording());
+        assert!(stop().is_err());
+
+        let names: Vec<String> = profile.ops.iter().map(|op| op.name.clone()).collect();
+        let expected: Vec<String> = snapshot.nodes.iter().map(|node| node.op.to_string()).collect();
+        assert_eq!(names, expected);
+        assert!(profile
+            .ops
+            .iter()
+            .all(|op| op.device == Device::CPU && op.duration_us >= 0.0));
+        assert!(profile.ops.windows(2).all(|w| w[0].start_us <= w[1].start_us));
+        assert!(profile.ops.iter().map(|op| op.allocated_bytes).sum::<usize>() > 0);
+
+        let trace: serde_json::Value = serde_json::from_str(&profile.to_chrome_trace().unwrap()).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        let complete = events.iter().filter(|event| event["ph"] == "X").count();
+        assert_eq!(complete, profile.ops.len());
+        assert!(events
+            .iter()
+            .any(|event| event["ph"] == "M" && event["args"]["name"] == "cpu"));
+    }
+}
@@ -1,11 +1,25 @@
 pub mod capture;
+pub mod interpreter;
+#[cfg(feature = "serde")]
+mod legacy;
+#[cfg(feature = "std")]
+mod spill;
 
 pub use capture::{CaptureBoard, CaptureBoardId};
+pub use interpreter::{ConstantLoader, CustomOpHandler, Interpreter, NodeObserver};
 
 use crate::{
     ops::{Op, OpParams},
     types::{DType, Layout, Shape, SymbolicLayout},
 };
+use std::collections::BTreeMap;
+
+/// Version of the snapshot layout written by [`Snapshot::to_bytes`]
+///
+/// It prefixes the payload inside the envelope and goes up whenever a field or op variant is added
+/// in a way older builds can't decode. Files without the envelope predate the version and are
+/// decoded with the original layout.
+pub const FORMAT_VERSION: u32 = 1;
 
 /// Snapshot-local tensor ID (normalized from runtime TensorId)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -42,6 +56,15 @@ pub struct SnapshotConstant {
     pub data: Vec<u8>,
 }
 
+impl SnapshotConstant {
+    /// Whether the data is stored outside the snapshot, e.g. in weight shards
+    ///
+    /// Such constants keep their shape and dtype but carry no bytes.
+    pub fn is_external(&self) -> bool {
+        self.data.is_empty() && self.shape.size() * self.dtype.size_in_bytes() > 0
+    }
+}
+
 /// Snapshot node (operation)
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -55,6 +78,10 @@ pub struct SnapshotNode {
     pub output_dtype: DType,
     /// Symbolic output layout for operations with data-dependent output shapes
     pub symbolic_output_layout: Option<SymbolicLayout>,
+    /// Optional node name (e.g. the layer name of a converted model)
+    pub name: Option<String>,
+    /// Free-form annotations such as the source location of the node
+    pub metadata: BTreeMap<String, String>,
 }
 
 /// Hodu Snapshot - serializable IR representation
@@ -66,6 +93,10 @@ pub struct Snapshot {
     pub constants: Vec<SnapshotConstant>,
     pub targets: Vec<SnapshotTarget>,
     pub nodes: Vec<SnapshotNode>,
+    /// Model-level metadata (producer, original format, opset, ...)
+    pub metadata: BTreeMap<String, String>,
+    /// Subgraphs of the control-flow nodes, referenced by index from their params
+    pub subgraphs: Vec<Snapshot>,
 }
 
 impl Snapshot {
@@ -76,6 +107,8 @@ impl Snapshot {
             constants: Vec::new(),
             targets: Vec::new(),
             nodes: Vec::new(),
+            metadata: BTreeMap::new(),
+            subgraphs: Vec::new(),
         }
     }
 
@@ -86,30 +119,149 @@ impl Snapshot {
             constants: Vec::new(),
             targets: Vec::new(),
             nodes: Vec::new(),
+            metadata: BTreeMap::new(),
+            subgraphs: Vec::new(),
+        }
+    }
+
+    /// Find a node by its name
+    pub fn find_node(&self, name: &str) -> Option<&SnapshotNode> {
+        self.nodes.iter().find(|node| node.name.as_deref() == Some(name))
+    }
+
+    /// Indices of the top-level nodes selected by `pattern`, in execution order
+    ///
+    /// `pattern` is a comma-separated list of node indices (`12`), half-open index ranges (`3..8`)
+    /// and node name globs where `*` matches any run of characters (`encoder.*`).
+    pub fn select_nodes(&self, pattern: &str) -> Vec<usize> {
+        let items: Vec<&str> = pattern.split(',').map(str::trim).filter(|s| !s.is_empty()).collect();
+        (0..self.nodes.len())
+            .filter(|&index| {
+                items.iter().any(|item| {
+                    if let Ok(i) = item.parse::<usize>() {
+                        return i == index;
+                    }
+                    if let Some((start, end)) = item.split_once("..") {
+                        if let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                            return (start..end).contains(&index);
+                        }
+                    }
+                    self.nodes[index]
+                        .name
+                        .as_deref()
+                        .is_some_and(|name| glob_match(item, name))
+                })
+            })
+            .collect()
+    }
+
+    /// Names of all custom ops used by this snapshot, including inside control-flow subgraphs
+    pub fn custom_op_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_custom_op_names(&mut names);
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn collect_custom_op_names(&self, names: &mut Vec<String>) {
+        for node in &self.nodes {
+            if let Some(OpParams::Custom(p)) = &node.params {
+                names.push(p.name.clone());
+            }
         }
+        for subgraph in &self.subgraphs {
+            subgraph.collect_custom_op_names(names);
+        }
+    }
+
+    /// The control-flow subgraph stored at `index` in [`Self::subgraphs`]
+    pub fn subgraph(&self, index: usize) -> crate::error::HoduResult<&Snapshot> {
+        self.subgraphs.get(index).ok_or_else(|| {
+            crate::error::HoduError::InvalidArgument(format!(
+                "subgraph {} out of range ({} subgraphs)",
+                index,
+                self.subgraphs.len()
+            ))
+        })
     }
 
     #[cfg(feature = "serde")]
     pub fn to_bytes(&self) -> crate::error::HoduResult<Vec<u8>> {
-        postcard::to_allocvec(self).map_err(|e| crate::error::HoduError::SerializationFailed(e.to_string()))
+        self.to_bytes_with(crate::format::Compression::None)
     }
 
+    /// Serialize with the given payload compression; [`Self::from_bytes`] detects it
     #[cfg(feature = "serde")]
-    pub fn from_bytes(data: &[u8]) -> crate::error::HoduResult<Self> {
-        postcard::from_bytes(data).map_err(|e| crate::error::HoduError::DeserializationFailed(e.to_string()))
+    pub fn to_bytes_with(&self, compression: crate::format::Compression) -> crate::error::HoduResult<Vec<u8>> {
+        let bytes = postcard::to_allocvec(&(FORMAT_VERSION, self))
+            .map_err(|e| crate::error::HoduError::SerializationFailed(e.to_string()))?;
+        crate::format::envelope::seal(bytes, compression)
     }
 
     #[cfg(feature = "serde")]
+    pub fn from_bytes(data: &[u8]) -> crate::error::HoduResult<Self> {
+        use crate::error::HoduError;
+
+        let sealed = crate::format::envelope::is_sealed(data);
+        let data = crate::format::envelope::open(data)?;
+        if !sealed {
+            return legacy::from_bytes(&data);
+        }
+        let (version, body) =
+            postcard::take_from_bytes::<u32>(&data).map_err(|e| HoduError::DeserializationFailed(e.to_string()))?;
+        if version != FORMAT_VERSION {
+            return Err(HoduError::DeserializationFailed(format!(
+                "unsupported snapshot format version {} (this build reads version {})",
+                version, FORMAT_VERSION
+            )));
+        }
+        postcard::from_bytes(body).map_err(|e| HoduError::DeserializationFailed(e.to_string()))
+    }
+
+    #[cfg(all(feature = "serde", feature = "std"))]
     pub fn save(&self, path: impl AsRef<std::path::Path>) -> crate::error::HoduResult<()> {
-        let bytes = self.to_bytes()?;
+        self.save_with(path, crate::format::Compression::None)
+    }
+
+    #[cfg(all(feature = "serde", feature = "std"))]
+    pub fn save_with(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        compression: crate::format::Compression,
+    ) -> crate::error::HoduResult<()> {
+        let bytes = self.to_bytes_with(compression)?;
         std::fs::write(path, bytes).map_err(|e| crate::error::HoduError::IoError(e.to_string()))
     }
 
-    #[cfg(feature = "serde")]
+    /// Load a snapshot, reading the data of sharded constants from their weight shards
+    #[cfg(all(feature = "serde", feature = "std"))]
     pub fn load(path: impl AsRef<std::path::Path>) -> crate::error::HoduResult<Self> {
-        let bytes = std::fs::read(path).map_err(|e| crate::error::HoduError::IoError(e.to_string()))?;
-        Self::from_bytes(&bytes)
+        let (mut snapshot, weights) = crate::format::hdss::load_lazy(path)?;
+        if let Some(weights) = weights {
+            weights.load_into(&mut snapshot)?;
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Match `text` against a glob where `*` matches any (possibly empty) run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let Some((head, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(head) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let tail = parts.pop().unwrap_or("");
+    for part in parts {
+        match remaining.find(part) {
+            Some(pos) => remaining = &remaining[pos + part.len()..],
+            None => return false,
+        }
     }
+    remaining.len() >= tail.len() && remaining.ends_with(tail)
 }
 
 impl Default for Snapshot {
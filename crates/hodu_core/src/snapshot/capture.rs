@@ -1,7 +1,12 @@
 mod board;
+mod control_flow;
+mod custom;
 mod storage;
 
 pub use board::{CaptureBoard, CaptureBoardId, CapturedInput, CapturedOp, CapturedTarget};
+pub(crate) use control_flow::predicate_value;
+pub use control_flow::{cond, while_loop};
+pub use custom::{custom_op, is_valid_custom_op_name};
 pub use storage::{
     active_board_id, add_input_to_active, capture_operation, capture_operation_with_symbolic, is_active,
 };
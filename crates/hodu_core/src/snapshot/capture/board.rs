@@ -5,7 +5,7 @@ use crate::{
     tensor::{Tensor, TensorId},
     types::{DType, Layout, Shape, SymbolicLayout},
 };
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -45,6 +45,8 @@ pub struct CapturedOp {
     pub output_layout: Layout,
     /// Symbolic output layout for operations with data-dependent output shapes
     pub symbolic_output_layout: Option<SymbolicLayout>,
+    pub name: Option<String>,
+    pub metadata: BTreeMap<String, String>,
 }
 
 /// Internal board data structure stored in global storage
@@ -54,6 +56,9 @@ pub(super) struct CaptureBoard_ {
     pub(super) inputs: Vec<CapturedInput>,
     pub(super) targets: Vec<CapturedTarget>,
     pub(super) ops: Vec<CapturedOp>,
+    pub(super) metadata: BTreeMap<String, String>,
+    /// Control-flow subgraphs, referenced by index from the params of captured ops
+    pub(super) subgraphs: Vec<Snapshot>,
 }
 
 impl CaptureBoard_ {
@@ -79,6 +84,11 @@ impl CaptureBoard_ {
     pub(super) fn add_op(&mut self, op: CapturedOp) {
         self.ops.push(op);
     }
+
+    /// Get the op that produced `tensor_id`, if it was captured on this board
+    pub(super) fn op_mut(&mut self, tensor_id: TensorId) -> Option<&mut CapturedOp> {
+        self.ops.iter_mut().rev().find(|op| op.output_id == tensor_id)
+    }
 }
 
 /// CaptureBoard is a handle that references a board in global storage
@@ -93,6 +103,8 @@ impl CaptureBoard {
             inputs: Vec::new(),
             targets: Vec::new(),
             ops: Vec::new(),
+            metadata: BTreeMap::new(),
+            subgraphs: Vec::new(),
         };
         super::storage::register_board(board);
         Self(id)
@@ -106,6 +118,8 @@ impl CaptureBoard {
             inputs: Vec::new(),
             targets: Vec::new(),
             ops: Vec::new(),
+            metadata: BTreeMap::new(),
+            subgraphs: Vec::new(),
         };
         super::storage::register_board(board);
         Self(id)
@@ -118,6 +132,41 @@ impl CaptureBoard {
         self
     }
 
+    /// Add the gradient of `tensor` as a target
+    ///
+    /// The gradient must have been produced by a `backward()` call made while this board was active,
+    /// so the backward pass is recorded into the same snapshot as the forward pass.
+    pub fn with_grad_target(&self, name: impl Into<String>, tensor: &Tensor) -> HoduResult<&Self> {
+        let grad = tensor.grad()?;
+        Ok(self.with_target(name, grad))
+    }
+
+    /// Attach a model-level metadata entry to the snapshot
+    pub fn with_metadata(&self, key: impl Into<String>, value: impl Into<String>) -> &Self {
+        super::storage::add_metadata_to_board(self.0, key.into(), value.into());
+        self
+    }
+
+    /// Name the node that produced `tensor`
+    ///
+    /// Has no effect if `tensor` was not produced by an op captured on this board.
+    pub fn with_node_name(&self, tensor: &Tensor, name: impl Into<String>) -> &Self {
+        let name = name.into();
+        super::storage::annotate_op_in_board(self.0, tensor.id(), |op| op.name = Some(name));
+        self
+    }
+
+    /// Attach a metadata entry (e.g. `source` location) to the node that produced `tensor`
+    ///
+    /// Has no effect if `tensor` was not produced by an op captured on this board.
+    pub fn with_node_metadata(&self, tensor: &Tensor, key: impl Into<String>, value: impl Into<String>) -> &Self {
+        let (key, value) = (key.into(), value.into());
+        super::storage::annotate_op_in_board(self.0, tensor.id(), |op| {
+            op.metadata.insert(key, value);
+        });
+        self
+    }
+
     pub fn open(&self) {
         super::storage::set_active(Some(self.0));
     }
@@ -134,11 +183,12 @@ impl CaptureBoard {
         let board = super::storage::take_board(self.0).expect("Board not found in storage");
 
         let reachable = Self::compute_reachable(&board.targets, &board.ops);
-        let filtered_ops: Vec<_> = board
+        let mut filtered_ops: Vec<_> = board
             .ops
             .into_iter()
             .filter(|op| reachable.contains(&op.output_id))
             .collect();
+        let subgraphs = Self::retain_subgraphs(board.subgraphs, &mut filtered_ops);
 
         let offset = Self::compute_id_offset(&board.inputs, &board.targets, &filtered_ops);
         let constant_ids = Self::find_constants(&board.inputs, &filtered_ops, &reachable);
@@ -169,9 +219,17 @@ impl CaptureBoard {
             .map(|op| {
                 let output_dtype = crate::tensor::get_dtype(op.output_id).expect("Builder tensor must have dtype");
 
+                let params = match op.params {
+                    Some(OpParams::TopK(mut p)) => {
+                        p.indices_id = TensorId::from_usize(p.indices_id.as_usize() - offset);
+                        Some(OpParams::TopK(p))
+                    },
+                    params => params,
+                };
+
                 SnapshotNode {
                     op: op.op,
-                    params: op.params,
+                    params,
                     input_ids: op
                         .input_ids
                         .into_iter()
@@ -182,6 +240,8 @@ impl CaptureBoard {
                     output_layout: op.output_layout,
                     output_dtype,
                     symbolic_output_layout: op.symbolic_output_layout,
+                    name: op.name,
+                    metadata: op.metadata,
                 }
             })
             .collect();
@@ -192,7 +252,36 @@ impl CaptureBoard {
             constants: snapshot_constants,
             targets: snapshot_targets,
             nodes: snapshot_nodes,
+            metadata: board.metadata,
+            subgraphs,
+        }
+    }
+
+    /// Keep the subgraphs referenced by `ops`, renumbering the references to match
+    fn retain_subgraphs(subgraphs: Vec<Snapshot>, ops: &mut [CapturedOp]) -> Vec<Snapshot> {
+        let mut subgraphs: Vec<Option<Snapshot>> = subgraphs.into_iter().map(Some).collect();
+        let mut retained = Vec::new();
+        let mut renumbered = HashMap::new();
+        let mut renumber = |index: &mut usize| {
+            *index = *renumbered.entry(*index).or_insert_with(|| {
+                retained.push(subgraphs[*index].take().expect("subgraph index out of range"));
+                retained.len() - 1
+            });
+        };
+        for op in ops {
+            match &mut op.params {
+                Some(OpParams::If(p)) => {
+                    renumber(&mut p.then_branch);
+                    renumber(&mut p.else_branch);
+                },
+                Some(OpParams::While(p)) => {
+                    renumber(&mut p.cond);
+                    renumber(&mut p.body);
+                },
+                _ => {},
+            }
         }
+        retained
     }
 
     fn compute_reachable(targets: &[CapturedTarget], ops: &[CapturedOp]) -> HashSet<TensorId> {
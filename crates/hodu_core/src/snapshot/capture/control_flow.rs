@@ -0,0 +1,271 @@
+use super::board::CaptureBoard;
+use super::storage::{
+    active_board_id, add_input_to_active, add_subgraph_to_active, capture_operation, set_active, take_board,
+};
+use crate::{
+    error::{HoduError, HoduResult},
+    ops::{ControlFlowOp, IfParams, Op, OpParams, WhileParams},
+    snapshot::{Snapshot, SnapshotTensorId},
+    tensor::{create_builder_tensor, Tensor},
+    types::{DType, Layout, Shape},
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Group id shared by the per-output nodes of a multi-output op
+pub(super) fn next_group_id() -> usize {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    COUNTER.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Conditionally execute `then_fn` or `else_fn` on `operands`.
+///
+/// While a capture board is active, both branches are traced into subgraphs and recorded as a single
+/// `If` op whose result nodes share the subgraphs. Branches only see the tensors passed in `operands`; tensors captured from the enclosing
+/// scope are not visible inside the subgraphs. Outside of capture the predicate is evaluated eagerly.
+pub fn cond<T, E>(pred: &Tensor, operands: &[&Tensor], then_fn: T, else_fn: E) -> HoduResult<Vec<Tensor>>
+where
+    T: FnOnce(&[Tensor]) -> HoduResult<Vec<Tensor>>,
+    E: FnOnce(&[Tensor]) -> HoduResult<Vec<Tensor>>,
+{
+    if active_board_id().is_none() {
+        let args: Vec<Tensor> = operands.iter().map(|t| (*t).clone()).collect();
+        return if predicate_value(pred)? {
+            then_fn(&args)
+        } else {
+            else_fn(&args)
+        };
+    }
+
+    let then_branch = trace_subgraph("then", operands, then_fn)?;
+    let else_branch = trace_subgraph("else", operands, else_fn)?;
+
+    let then_signature = targets_signature(&then_branch)?;
+    let else_signature = targets_signature(&else_branch)?;
+    if then_signature != else_signature {
+        return Err(HoduError::InvalidArgument(format!(
+            "if branches must produce matching outputs, got {:?} and {:?}",
+            then_signature, else_signature
+        )));
+    }
+
+    let mut input_ids = vec![pred.id()];
+    let mut input_layouts = vec![pred.layout()];
+    for operand in operands {
+        input_ids.push(operand.id());
+        input_layouts.push(operand.layout());
+    }
+
+    let then_branch = add_subgraph_to_active(then_branch)?;
+    let else_branch = add_subgraph_to_active(else_branch)?;
+    let group_id = next_group_id();
+    then_signature
+        .into_iter()
+        .enumerate()
+        .map(|(output_index, (shape, dtype))| {
+            let params = OpParams::If(IfParams {
+                then_branch,
+                else_branch,
+                group_id,
+                output_index,
+            });
+            record_output(
+                Op::ControlFlow(ControlFlowOp::If),
+                params,
+                &input_ids,
+                &input_layouts,
+                shape,
+                dtype,
+            )
+        })
+        .collect()
+}
+
+/// Repeatedly apply `body_fn` to the loop-carried `operands` while `cond_fn` returns true.
+///
+/// While a capture board is active, the condition and body are traced into subgraphs and recorded as a
+/// single `While` op. `body_fn` must return one tensor per operand with unchanged shape and dtype.
+/// `max_iterations` bounds the trip count. Outside of capture the loop runs eagerly.
+pub fn while_loop<C, B>(
+    operands: &[&Tensor],
+    cond_fn: C,
+    body_fn: B,
+    max_iterations: Option<usize>,
+) -> HoduResult<Vec<Tensor>>
+where
+    C: Fn(&[Tensor]) -> HoduResult<Tensor>,
+    B: Fn(&[Tensor]) -> HoduResult<Vec<Tensor>>,
+{
+    if active_board_id().is_none() {
+        let mut carried: Vec<Tensor> = operands.iter().map(|t| (*t).clone()).collect();
+        let mut iterations = 0;
+        while max_iterations.is_none_or(|max| iterations < max) && predicate_value(&cond_fn(&carried)?)? {
+            let next = body_fn(&carried)?;
+            check_carried(&carried, &next)?;
+            carried = next;
+            iterations += 1;
+        }
+        return Ok(carried);
+    }
+
+    let cond = trace_subgraph("cond", operands, |args| cond_fn(args).map(|t| vec![t]))?;
+    let body = trace_subgraph("body", operands, body_fn)?;
+
+    let cond_signature = targets_signature(&cond)?;
+    if cond_signature.len() != 1 || cond_signature[0].0.size() != 1 {
+        return Err(HoduError::InvalidArgument(
+            "while condition must produce a single scalar".to_string(),
+        ));
+    }
+
+    let carried_signature: Vec<(Shape, DType)> = operands.iter().map(|t| (t.shape(), t.dtype())).collect();
+    let body_signature = targets_signature(&body)?;
+    if body_signature != carried_signature {
+        return Err(HoduError::InvalidArgument(format!(
+            "while body must preserve loop-carried values, expected {:?}, got {:?}",
+            carried_signature, body_signature
+        )));
+    }
+
+    let input_ids: Vec<_> = operands.iter().map(|t| t.id()).collect();
+    let input_layouts: Vec<_> = operands.iter().map(|t| t.layout()).collect();
+
+    let cond = add_subgraph_to_active(cond)?;
+    let body = add_subgraph_to_active(body)?;
+    let group_id = next_group_id();
+    carried_signature
+        .into_iter()
+        .enumerate()
+        .map(|(output_index, (shape, dtype))| {
+            let params = OpParams::While(WhileParams {
+                cond,
+                body,
+                max_iterations,
+                group_id,
+                output_index,
+            });
+            record_output(
+                Op::ControlFlow(ControlFlowOp::While),
+                params,
+                &input_ids,
+                &input_layouts,
+                shape,
+                dtype,
+            )
+        })
+        .collect()
+}
+
+/// Evaluate a single-element tensor as a boolean predicate
+pub(crate) fn predicate_value(pred: &Tensor) -> HoduResult<bool> {
+    let values = pred.to_dtype(DType::BOOL)?.to_flatten_vec::<bool>()?;
+    match values.as_slice() {
+        [value] => Ok(*value),
+        _ => Err(HoduError::InvalidArgument(format!(
+            "predicate must be a single-element tensor, got shape {:?}",
+            pred.shape()
+        ))),
+    }
+}
+
+fn check_carried(prev: &[Tensor], next: &[Tensor]) -> HoduResult<()> {
+    let matches = prev.len() == next.len()
+        && prev
+            .iter()
+            .zip(next)
+            .all(|(p, n)| p.shape() == n.shape() && p.dtype() == n.dtype());
+    if matches {
+        Ok(())
+    } else {
+        Err(HoduError::InvalidArgument(
+            "while body must preserve loop-carried values".to_string(),
+        ))
+    }
+}
+
+/// Trace `f` into a standalone snapshot whose inputs are placeholders shaped like `operands`.
+///
+/// The previously active board is suspended while tracing and restored afterwards.
+fn trace_subgraph<F>(name: &str, operands: &[&Tensor], f: F) -> HoduResult<Snapshot>
+where
+    F: FnOnce(&[Tensor]) -> HoduResult<Vec<Tensor>>,
+{
+    let outer = active_board_id();
+    let board = CaptureBoard::with_name(name);
+    board.open();
+
+    let result = operands
+        .iter()
+        .enumerate()
+        .map(|(i, operand)| {
+            let (_, arg) = create_builder_tensor(Layout::from_shape(&operand.shape()), operand.dtype(), false);
+            add_input_to_active(&format!("arg{}", i), arg.clone())?;
+            Ok(arg)
+        })
+        .collect::<HoduResult<Vec<_>>>()
+        .and_then(|args| f(&args));
+
+    set_active(outer);
+
+    match result {
+        Ok(outputs) => {
+            for (i, output) in outputs.into_iter().enumerate() {
+                board.with_target(format!("out{}", i), output);
+            }
+            Ok(board.capture())
+        },
+        Err(e) => {
+            take_board(board.id());
+            Err(e)
+        },
+    }
+}
+
+/// Shapes and dtypes of a subgraph's targets, in target order
+fn targets_signature(snapshot: &Snapshot) -> HoduResult<Vec<(Shape, DType)>> {
+    snapshot
+        .targets
+        .iter()
+        .map(|target| {
+            tensor_signature(snapshot, target.id)
+                .ok_or_else(|| HoduError::InternalError(format!("subgraph target '{}' has no producer", target.name)))
+        })
+        .collect()
+}
+
+fn tensor_signature(snapshot: &Snapshot, id: SnapshotTensorId) -> Option<(Shape, DType)> {
+    if let Some(node) = snapshot.nodes.iter().find(|n| n.output_id == id) {
+        return Some((node.output_layout.shape().clone(), node.output_dtype));
+    }
+    if let Some(input) = snapshot.inputs.iter().find(|i| i.id == id) {
+        return Some((input.shape.clone(), input.dtype));
+    }
+    snapshot
+        .constants
+        .iter()
+        .find(|c| c.id == id)
+        .map(|c| (c.shape.clone(), c.dtype))
+}
+
+/// Record one result of a multi-output op as its own node
+pub(super) fn record_output(
+    op: Op,
+    params: OpParams,
+    input_ids: &[crate::tensor::TensorId],
+    input_layouts: &[Layout],
+    shape: Shape,
+    dtype: DType,
+) -> HoduResult<Tensor> {
+    let result_layout = Layout::from_shape(&shape);
+    let (result_id, result_tensor) = create_builder_tensor(result_layout.clone(), dtype, false);
+
+    capture_operation(
+        op,
+        Some(params),
+        input_ids.to_vec(),
+        result_id,
+        input_layouts.to_vec(),
+        result_layout,
+    )?;
+
+    Ok(result_tensor)
+}
@@ -0,0 +1,64 @@
+use super::control_flow::{next_group_id, record_output};
+use super::storage::active_board_id;
+use crate::{
+    error::{HoduError, HoduResult},
+    ops::{CustomParams, Op, OpParams},
+    tensor::Tensor,
+    types::{DType, Shape},
+};
+
+/// Record an opaque custom op whose execution is delegated to a plugin.
+///
+/// `name` must be namespaced (`domain.op`) and `attributes` is JSON text forwarded to the plugin as-is.
+/// Since hodu cannot infer the results of an opaque op, `outputs` declares their shapes and dtypes.
+/// Custom ops only exist inside snapshots, so a capture board must be active.
+pub fn custom_op(
+    name: &str,
+    attributes: &str,
+    inputs: &[&Tensor],
+    outputs: &[(Shape, DType)],
+) -> HoduResult<Vec<Tensor>> {
+    if active_board_id().is_none() {
+        return Err(HoduError::UnsupportedOperation(format!(
+            "custom op '{}' can only be recorded while a capture board is active",
+            name
+        )));
+    }
+    if !is_valid_custom_op_name(name) {
+        return Err(HoduError::InvalidArgument(format!(
+            "invalid custom op name '{}', expected a namespaced name like 'domain.op'",
+            name
+        )));
+    }
+    if outputs.is_empty() {
+        return Err(HoduError::InvalidArgument(format!(
+            "custom op '{}' must declare at least one output",
+            name
+        )));
+    }
+
+    let input_ids: Vec<_> = inputs.iter().map(|t| t.id()).collect();
+    let input_layouts: Vec<_> = inputs.iter().map(|t| t.layout()).collect();
+
+    let group_id = next_group_id();
+    outputs
+        .iter()
+        .enumerate()
+        .map(|(output_index, (shape, dtype))| {
+            let params = OpParams::Custom(CustomParams {
+                name: name.to_string(),
+                attributes: attributes.to_string(),
+                group_id,
+                output_index,
+            });
+            record_output(Op::Custom, params, &input_ids, &input_layouts, shape.clone(), *dtype)
+        })
+        .collect()
+}
+
+/// Check that `name` is made of at least two non-empty `[A-Za-z0-9_-]` segments separated by dots
+pub fn is_valid_custom_op_name(name: &str) -> bool {
+    let mut segments = name.split('.');
+    let valid_segment = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    segments.clone().count() >= 2 && segments.all(valid_segment)
+}
@@ -2,6 +2,7 @@ use super::board::{CaptureBoardId, CaptureBoard_, CapturedOp};
 use crate::{
     error::{HoduError, HoduResult},
     ops::{Op, OpParams},
+    snapshot::Snapshot,
     tensor::{Tensor, TensorId},
     types::{Layout, SymbolicLayout},
 };
@@ -64,6 +65,22 @@ pub(super) fn add_target_to_board(id: CaptureBoardId, name: String, tensor: Tens
     }
 }
 
+/// Add a model-level metadata entry to a specific board
+pub(super) fn add_metadata_to_board(id: CaptureBoardId, key: String, value: String) {
+    if let Some(mut board) = BOARDS.get_mut(&id) {
+        board.metadata.insert(key, value);
+    }
+}
+
+/// Modify the op that produced `tensor_id` on a specific board
+pub(super) fn annotate_op_in_board(id: CaptureBoardId, tensor_id: TensorId, f: impl FnOnce(&mut CapturedOp)) {
+    if let Some(mut board) = BOARDS.get_mut(&id) {
+        if let Some(op) = board.op_mut(tensor_id) {
+            f(op);
+        }
+    }
+}
+
 /// Add an input to the currently active board
 pub fn add_input_to_active(name: &str, tensor: Tensor) -> HoduResult<()> {
     let board_id = active_board_id().ok_or(HoduError::CaptureNotActive)?;
@@ -75,6 +92,14 @@ pub fn add_input_to_active(name: &str, tensor: Tensor) -> HoduResult<()> {
     }
 }
 
+/// Add a control-flow subgraph to the currently active board, returning its index
+pub(super) fn add_subgraph_to_active(subgraph: Snapshot) -> HoduResult<usize> {
+    let board_id = active_board_id().ok_or(HoduError::CaptureNotActive)?;
+    let mut board = BOARDS.get_mut(&board_id).ok_or(HoduError::CaptureNotActive)?;
+    board.subgraphs.push(subgraph);
+    Ok(board.subgraphs.len() - 1)
+}
+
 /// Capture an operation to the currently active board
 pub fn capture_operation(
     op: Op,
@@ -110,6 +135,8 @@ pub fn capture_operation_with_symbolic(
         input_layouts,
         output_layout,
         symbolic_output_layout,
+        name: None,
+        metadata: Default::default(),
     };
 
     if let Some(mut board) = BOARDS.get_mut(&board_id) {
@@ -0,0 +1,1008 @@
+#[cfg(feature = "std")]
+use crate::snapshot::spill::SpillStore;
+use crate::{
+    be::storage::BackendStorage,
+    error::{HoduError, HoduResult},
+    ops::{ConvOp, CustomParams, IndexingOp, LinalgOp, MatrixOp, Op, OpParams, QuantOp, ScanOp, SortOp},
+    profiler::{self, OpTimer},
+    scalar::Scalar,
+    snapshot::{capture::predicate_value, Snapshot, SnapshotConstant, SnapshotNode, SnapshotTensorId},
+    tensor::{from_shared_storage_with, from_storage, Tensor},
+    types::{Device, Layout},
+};
+use std::collections::{hash_map::Entry, HashMap};
+
+/// Reference interpreter that executes a [`Snapshot`] node by node on a single device.
+///
+/// Nodes are dispatched directly to the backend storage of the target device, so the
+/// interpreter needs no compiled artifact and serves as the baseline for backend plugins.
+pub struct Interpreter<'a> {
+    snapshot: &'a Snapshot,
+    device: Device,
+    custom_op_handler: Option<&'a CustomOpHandler<'a>>,
+    node_observer: Option<&'a NodeObserver<'a>>,
+    constant_loader: Option<&'a ConstantLoader<'a>>,
+    #[cfg(feature = "std")]
+    memory_budget: Option<usize>,
+}
+
+/// Called with the index, node and result of every top-level node after it executes
+pub type NodeObserver<'a> = dyn Fn(usize, &SnapshotNode, &Tensor) -> HoduResult<()> + 'a;
+
+/// Executes a custom op node given its params and inputs, returning every result of the op
+pub type CustomOpHandler<'a> = dyn Fn(&CustomParams, &[Tensor]) -> HoduResult<Vec<Tensor>> + 'a;
+
+/// Loads a constant whose data is stored outside the snapshot, such as in weight shards
+pub type ConstantLoader<'a> = dyn Fn(&SnapshotConstant) -> HoduResult<Tensor> + 'a;
+
+/// Tensor values live during one execution of a snapshot
+#[derive(Default)]
+struct Frame {
+    values: HashMap<SnapshotTensorId, Tensor>,
+    /// Results of multi-output control-flow and custom ops, keyed by group id
+    groups: HashMap<usize, Vec<Tensor>>,
+    /// Set when running under a memory budget
+    #[cfg(feature = "std")]
+    spill: Option<SpillStore>,
+    /// External constants not read yet, by index into `snapshot.constants`
+    deferred: HashMap<SnapshotTensorId, usize>,
+}
+
+impl Frame {
+    fn get(&mut self, id: SnapshotTensorId) -> HoduResult<Tensor> {
+        if let Some(tensor) = self.values.get(&id) {
+            #[cfg(feature = "std")]
+            if let Some(spill) = &mut self.spill {
+                spill.touch(id);
+            }
+            return Ok(tensor.clone());
+        }
+        #[cfg(feature = "std")]
+        if let Some(tensor) = self.spill.as_mut().map(|spill| spill.reload(id)).transpose()?.flatten() {
+            self.values.insert(id, tensor.clone());
+            return Ok(tensor);
+        }
+        Err(HoduError::InternalError(format!(
+            "snapshot tensor {} has no value",
+            id.0
+        )))
+    }
+
+    fn insert(&mut self, id: SnapshotTensorId, tensor: Tensor) {
+        #[cfg(feature = "std")]
+        if let Some(spill) = &mut self.spill {
+            spill.insert(id, &tensor);
+        }
+        self.values.insert(id, tensor);
+    }
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(snapshot: &'a Snapshot) -> Self {
+        Self {
+            snapshot,
+            device: Device::CPU,
+            custom_op_handler: None,
+            node_observer: None,
+            constant_loader: None,
+            #[cfg(feature = "std")]
+            memory_budget: None,
+        }
+    }
+
+    pub fn device(mut self, device: Device) -> Self {
+        self.device = device;
+        self
+    }
+
+    /// Set the handler that executes custom op nodes (typically by forwarding them to a plugin)
+    pub fn custom_op_handler(mut self, handler: &'a CustomOpHandler<'a>) -> Self {
+        self.custom_op_handler = Some(handler);
+        self
+    }
+
+    /// Set an observer that sees every intermediate result, e.g. to dump selected nodes to disk
+    ///
+    /// Nodes inside control-flow subgraphs are not observed.
+    pub fn node_observer(mut self, observer: &'a NodeObserver<'a>) -> Self {
+        self.node_observer = Some(observer);
+        self
+    }
+
+    /// Set the loader for constants whose data is stored outside the snapshot
+    ///
+    /// Such constants (see [`SnapshotConstant::is_external`]) are loaded the first time a node
+    /// reads them instead of up front, so weight shards are only opened once they are needed.
+    pub fn constant_loader(mut self, loader: &'a ConstantLoader<'a>) -> Self {
+        self.constant_loader = Some(loader);
+        self
+    }
+
+    /// Keep the values held during execution under `bytes`
+    ///
+    /// Values no later node reads are freed as soon as possible, and when that is not enough the
+    /// least recently used ones are spilled to memory-mapped temp files and reloaded when read
+    /// again. Snapshots whose intermediates do not fit in memory then run slowly instead of
+    /// failing. Only the result of the node that just ran may exceed the budget.
+    #[cfg(feature = "std")]
+    pub fn memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = Some(bytes);
+        self
+    }
+
+    /// Run the snapshot with named inputs and return its targets in declaration order
+    pub fn run(&self, inputs: &[(&str, &Tensor)]) -> HoduResult<Vec<(String, Tensor)>> {
+        let ordered = self
+            .snapshot
+            .inputs
+            .iter()
+            .map(|spec| {
+                inputs
+                    .iter()
+                    .find(|(name, _)| *name == spec.name)
+                    .map(|(_, tensor)| (*tensor).clone())
+                    .ok_or_else(|| HoduError::InvalidArgument(format!("missing snapshot input '{}'", spec.name)))
+            })
+            .collect::<HoduResult<Vec<_>>>()?;
+
+        let outputs = self.execute(ordered)?;
+
+        Ok(self
+            .snapshot
+            .targets
+            .iter()
+            .map(|target| target.name.clone())
+            .zip(outputs)
+            .collect())
+    }
+
+    /// Run the snapshot with inputs given in `snapshot.inputs` order, returning targets in order
+    ///
+    /// With the `tracing` feature, the run is a `snapshot` span at debug level and every
+    /// top-level node a `node` span at trace level.
+    pub fn execute(&self, inputs: Vec<Tensor>) -> HoduResult<Vec<Tensor>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "snapshot",
+            device = ?self.device,
+            nodes = self.snapshot.nodes.len()
+        )
+        .entered();
+
+        if inputs.len() != self.snapshot.inputs.len() {
+            return Err(HoduError::InvalidArgument(format!(
+                "snapshot expects {} inputs, got {}",
+                self.snapshot.inputs.len(),
+                inputs.len()
+            )));
+        }
+
+        let mut frame = Frame {
+            #[cfg(feature = "std")]
+            spill: self
+                .memory_budget
+                .map(|budget| SpillStore::new(budget, self.device, self.snapshot))
+                .transpose()?,
+            ..Default::default()
+        };
+
+        for (spec, tensor) in self.snapshot.inputs.iter().zip(inputs) {
+            if tensor.shape() != spec.shape || tensor.dtype() != spec.dtype {
+                return Err(HoduError::InvalidArgument(format!(
+                    "input '{}' expects {:?} {:?}, got {:?} {:?}",
+                    spec.name,
+                    spec.shape,
+                    spec.dtype,
+                    tensor.shape(),
+                    tensor.dtype()
+                )));
+            }
+            frame.insert(spec.id, self.prepare_input(tensor)?);
+        }
+
+        for (index, constant) in self.snapshot.constants.iter().enumerate() {
+            if constant.is_external() && self.constant_loader.is_some() {
+                frame.deferred.insert(constant.id, index);
+                continue;
+            }
+            let tensor = self.load_constant(constant)?;
+            frame.insert(constant.id, tensor);
+            #[cfg(feature = "std")]
+            if let Some(spill) = &mut frame.spill {
+                spill.enforce(0, constant.id, &mut frame.values)?;
+            }
+        }
+
+        for (index, node) in self.snapshot.nodes.iter().enumerate() {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::trace_span!("node", index, op = %node.op).entered();
+            let output = if profiler::is_recording() {
+                let timer = OpTimer::start(self.device)?;
+                let output = self.execute_node(node, &mut frame)?;
+                timer.finish(node.op.to_string(), profile_args(index, node))?;
+                output
+            } else {
+                self.execute_node(node, &mut frame)?
+            };
+            if let Some(observer) = self.node_observer {
+                observer(index, node, &output)?;
+            }
+            frame.insert(node.output_id, output);
+            #[cfg(feature = "std")]
+            if let Some(spill) = &mut frame.spill {
+                spill.enforce(index + 1, node.output_id, &mut frame.values)?;
+            }
+        }
+
+        self.snapshot
+            .targets
+            .iter()
+            .map(|target| self.read(&mut frame, target.id))
+            .collect()
+    }
+
+    fn read(&self, frame: &mut Frame, id: SnapshotTensorId) -> HoduResult<Tensor> {
+        if let Some(index) = frame.deferred.remove(&id) {
+            let tensor = self.load_constant(&self.snapshot.constants[index])?;
+            frame.insert(id, tensor);
+        }
+        frame.get(id)
+    }
+
+    fn load_constant(&self, constant: &SnapshotConstant) -> HoduResult<Tensor> {
+        if !constant.is_external() {
+            return Tensor::from_bytes(&constant.data, constant.shape.clone(), constant.dtype, self.device);
+        }
+        let loader = self.constant_loader.ok_or_else(|| {
+            HoduError::InvalidArgument(format!(
+                "constant {} is stored outside the snapshot; load its weights or set a constant loader",
+                constant.id.0
+            ))
+        })?;
+        self.prepare_input(loader(constant)?)
+    }
+
+    fn prepare_input(&self, tensor: Tensor) -> HoduResult<Tensor> {
+        let tensor = if tensor.device() != self.device {
+            tensor.to_device(self.device)?
+        } else {
+            tensor
+        };
+        if tensor.is_contiguous() {
+            Ok(tensor)
+        } else {
+            tensor.contiguous()
+        }
+    }
+
+    fn nested<'b>(&'b self, snapshot: &'b Snapshot) -> Interpreter<'b> {
+        Interpreter {
+            snapshot,
+            device: self.device,
+            custom_op_handler: self.custom_op_handler,
+            node_observer: None,
+            constant_loader: self.constant_loader,
+            #[cfg(feature = "std")]
+            memory_budget: self.memory_budget,
+        }
+    }
+
+    fn execute_node(&self, node: &SnapshotNode, frame: &mut Frame) -> HoduResult<Tensor> {
+        let inputs = node
+            .input_ids
+            .iter()
+            .map(|&id| self.read(frame, id))
+            .collect::<HoduResult<Vec<_>>>()?;
+        let op = node.op.clone();
+
+        let storage = match &node.op {
+            Op::Binary(_) => binary(&inputs, |l, r, ll, rl| l.call_ops_binary(r, ll, rl, op))?,
+            Op::BinaryLogical(_) => binary(&inputs, |l, r, ll, rl| l.call_ops_binary_logical(r, ll, rl, op))?,
+            Op::BitwiseBinary(_) => binary(&inputs, |l, r, ll, rl| l.call_ops_bitwise_binary(r, ll, rl, op))?,
+            Op::Cmp(_) => binary(&inputs, |l, r, ll, rl| l.call_ops_cmp(r, ll, rl, op))?,
+            Op::Matrix(MatrixOp::Matmul) => binary(&inputs, |l, r, ll, rl| l.call_ops_matmul(r, ll, rl, op))?,
+            Op::Matrix(MatrixOp::Dot) => binary(&inputs, |l, r, ll, rl| l.call_ops_dot(r, ll, rl, op))?,
+
+            Op::Unary(_) => unary(&inputs, |s, l| s.call_ops_unary(l, op))?,
+            Op::UnaryLogical(_) => unary(&inputs, |s, l| s.call_ops_unary_logical(l, op))?,
+            Op::BitwiseUnary(_) => unary(&inputs, |s, l| s.call_ops_bitwise_unary(l, op))?,
+            Op::BitwiseUnaryScalar(_) => {
+                let Some(OpParams::BitwiseUnaryScalar(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                unary(&inputs, |s, l| s.call_ops_bitwise_unary_scalar(l, p.shift, op))?
+            },
+            Op::CmpScalar(_) => {
+                let Some(OpParams::CmpScalar(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                unary(&inputs, |s, l| s.call_ops_cmp_scalar(l, p.scalar, op))?
+            },
+            Op::UnaryScalar(_) => {
+                let Some(OpParams::UnaryScalar(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                unary(&inputs, |s, l| s.call_ops_unary_scalar(l, p.scalar, op))?
+            },
+
+            Op::Linalg(LinalgOp::Det) => unary(&inputs, |s, l| s.call_ops_det(l))?,
+            Op::Linalg(LinalgOp::Inv) => unary(&inputs, |s, l| s.call_ops_inv(l))?,
+            Op::Linalg(LinalgOp::Trace) => unary(&inputs, |s, l| s.call_ops_trace(l))?,
+
+            Op::Reduce(_) => {
+                let Some(OpParams::Reduce(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                let dims: Vec<usize> = p.dims.iter().map(|d| d.to_usize()).collect();
+                unary(&inputs, |s, l| s.call_ops_reduce(l, &dims, p.keep_dim, op))?
+            },
+
+            Op::Concat(_) => {
+                let Some(OpParams::Concat(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                let dim = normalize_dim(p.dim, inputs[0].ndim());
+                let storages = inputs
+                    .iter()
+                    .map(|t| t.with_storage(|s| Ok(s.clone())))
+                    .collect::<HoduResult<Vec<_>>>()?;
+                let layouts: Vec<Layout> = inputs.iter().map(|t| t.layout()).collect();
+                let layout_refs: Vec<&Layout> = layouts.iter().collect();
+                let other_refs: Vec<&BackendStorage> = storages[1..].iter().collect();
+                storages[0].call_ops_concat(&other_refs, &layout_refs, dim, op)?
+            },
+            Op::Split(_) => {
+                let Some(OpParams::Split(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                let dim = normalize_dim(p.dim, inputs[0].ndim());
+                let start = p.sizes[..p.output_index].iter().map(|s| s.to_usize()).sum();
+                let size = p.sizes[p.output_index].to_usize();
+                unary(&inputs, |s, l| s.call_ops_split(l, dim, start, size, op))?
+            },
+
+            Op::Indexing(indexing_op) => return self.execute_indexing(node, *indexing_op, &inputs),
+
+            Op::Conv(conv_op) => self.execute_conv(node, *conv_op, &inputs)?,
+
+            Op::Windowing(_) => {
+                let Some(OpParams::ReduceWindow(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                let padding: Vec<usize> = p.padding.iter().flat_map(|&(lo, hi)| [lo, hi]).collect();
+                unary(&inputs, |s, l| {
+                    s.call_ops_reduce_window(l, &p.window_shape, &p.strides, &padding, op)
+                })?
+            },
+            Op::Padding(_) => {
+                let Some(OpParams::Padding(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                let pad_before: Vec<usize> = p.padding.iter().map(|&(before, _)| before).collect();
+                let pad_after: Vec<usize> = p.padding.iter().map(|&(_, after)| after).collect();
+                unary(&inputs, |s, l| {
+                    s.call_ops_pad(l, &pad_before, &pad_after, p.pad_value, op)
+                })?
+            },
+            Op::Resize(_) => {
+                let Some(OpParams::Resize(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                let output_dims = node.output_layout.shape().dims().to_vec();
+                unary(&inputs, |s, l| {
+                    s.call_ops_resize(l, &output_dims, p.mode, p.coord_transform, p.nearest_mode)
+                })?
+            },
+            Op::Scan(scan_op) => {
+                let Some(OpParams::Scan(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                match scan_op {
+                    ScanOp::CumSum => unary(&inputs, |s, l| s.call_ops_cumsum(l, p.dim))?,
+                    ScanOp::CumProd => unary(&inputs, |s, l| s.call_ops_cumprod(l, p.dim))?,
+                }
+            },
+            Op::Sort(SortOp::TopK) => {
+                let Some(OpParams::TopK(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                let (values, indices) = inputs[0].topk(p.k, p.dim, p.largest, p.sorted)?;
+                return Ok(if p.indices_id.as_usize() == node.output_id.0 {
+                    indices
+                } else {
+                    values
+                });
+            },
+            Op::Einsum(_) => {
+                let Some(OpParams::Einsum(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                let refs: Vec<&Tensor> = inputs.iter().collect();
+                return Tensor::einsum(&p.equation, &refs);
+            },
+
+            Op::Shape(_) | Op::ShapeScalars(_) => return self.execute_view(node, &inputs),
+            Op::ShapeMemory(_) => {
+                let Some(OpParams::Flip(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                unary(&inputs, |s, l| s.call_ops_flip(l, &p.dims))?
+            },
+            Op::Cast(_) => {
+                let Some(OpParams::ToDType(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                unary(&inputs, |s, l| s.to_dtype(l, p.dtype))?
+            },
+            Op::Memory(_) => unary(&inputs, |s, l| s.contiguous(l))?,
+
+            Op::Quant(QuantOp::Quantize) => {
+                let Some(OpParams::Quantize(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                unary(&inputs, |s, l| s.call_ops_quantize(l, p.scale, p.zero_point))?
+            },
+            Op::Quant(QuantOp::Dequantize) => {
+                let Some(OpParams::Dequantize(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                unary(&inputs, |s, l| s.call_ops_dequantize(l, p.scale, p.zero_point, p.dtype))?
+            },
+            Op::Quant(QuantOp::MatmulInt8) => binary(&inputs, |l, r, ll, rl| l.call_ops_matmul_int8(r, ll, rl))?,
+            Op::Quant(QuantOp::BlockQuantize) => {
+                let Some(OpParams::BlockQuantize(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                unary(&inputs, |s, l| s.call_ops_block_quantize(l, p.format))?
+            },
+            Op::Quant(QuantOp::BlockDequantize) => {
+                let Some(OpParams::BlockDequantize(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                unary(&inputs, |s, l| s.call_ops_block_dequantize(l, p.format, p.dtype))?
+            },
+            Op::Quant(QuantOp::BlockMatmul) => {
+                let Some(OpParams::BlockMatmul(p)) = &node.params else {
+                    return Err(params_error(node));
+                };
+                binary(&inputs, |l, r, ll, rl| l.call_ops_block_matmul(r, ll, rl, p.format))?
+            },
+
+            Op::ControlFlow(_) => return self.execute_control_flow(node, inputs, frame),
+            Op::Custom => return self.execute_custom(node, inputs, frame),
+
+            Op::Dummy => {
+                return Err(HoduError::UnsupportedOperation(
+                    "dummy op cannot be executed".to_string(),
+                ))
+            },
+        };
+
+        let layout = Layout::from_shape(node.output_layout.shape());
+        Ok(from_storage(storage, layout, true, false, None))
+    }
+
+    fn execute_indexing(&self, node: &SnapshotNode, indexing_op: IndexingOp, inputs: &[Tensor]) -> HoduResult<Tensor> {
+        let op = node.op.clone();
+        let storage = match (indexing_op, &node.params) {
+            (IndexingOp::IndexSelect, Some(OpParams::IndexSelect(p))) => {
+                let dim = normalize_dim(p.dim, inputs[0].ndim());
+                binary(inputs, |s, i, sl, il| s.call_ops_index_select(sl, i, il, dim, op))?
+            },
+            (IndexingOp::Gather, Some(OpParams::Gather(p))) => {
+                let dim = normalize_dim(p.dim, inputs[0].ndim());
+                binary(inputs, |s, i, sl, il| s.call_ops_gather(sl, i, il, dim, op))?
+            },
+            (IndexingOp::IndexPut, Some(OpParams::IndexPut(p))) => {
+                let dim = normalize_dim(p.dim, inputs[0].ndim());
+                ternary(inputs, |s, i, v, sl, il, vl| {
+                    s.call_ops_index_put(sl, i, il, v, vl, dim, op)
+                })?
+            },
+            (IndexingOp::Scatter, Some(OpParams::Scatter(p))) => {
+                let dim = normalize_dim(p.dim, inputs[0].ndim());
+                ternary(inputs, |s, i, v, sl, il, vl| {
+                    s.call_ops_scatter(sl, i, il, v, vl, dim, op)
+                })?
+            },
+            (IndexingOp::ScatterAdd, Some(OpParams::ScatterAdd(p))) => {
+                let dim = normalize_dim(p.dim, inputs[0].ndim());
+                ternary(inputs, |s, i, v, sl, il, vl| {
+                    s.call_ops_scatter(sl, i, il, v, vl, dim, op)
+                })?
+            },
+            (IndexingOp::ScatterMax, Some(OpParams::ScatterMax(p))) => {
+                let dim = normalize_dim(p.dim, inputs[0].ndim());
+                ternary(inputs, |s, i, v, sl, il, vl| {
+                    s.call_ops_scatter(sl, i, il, v, vl, dim, op)
+                })?
+            },
+            (IndexingOp::ScatterMin, Some(OpParams::ScatterMin(p))) => {
+                let dim = normalize_dim(p.dim, inputs[0].ndim());
+                ternary(inputs, |s, i, v, sl, il, vl| {
+                    s.call_ops_scatter(sl, i, il, v, vl, dim, op)
+                })?
+            },
+            // Ops with data-dependent output shapes go through the tensor API
+            (IndexingOp::Onehot, Some(OpParams::Onehoto(p))) => {
+                return inputs[0].onehot(p.num_classes, p.axis, p.dtype);
+            },
+            (IndexingOp::Nonzero, _) => return inputs[0].nonzero(),
+            (IndexingOp::Unique, _) => return inputs[0].unique().map(|(values, _, _)| values),
+            (IndexingOp::Compress, Some(OpParams::Compress(p))) => {
+                return inputs[0].compress(&inputs[1], p.axis.map(|a| a.to_i32()));
+            },
+            _ => return Err(params_error(node)),
+        };
+
+        let layout = Layout::from_shape(node.output_layout.shape());
+        Ok(from_storage(storage, layout, true, false, None))
+    }
+
+    fn execute_conv(&self, node: &SnapshotNode, conv_op: ConvOp, inputs: &[Tensor]) -> HoduResult<BackendStorage> {
+        let op = Op::Conv(conv_op);
+        let (stride, padding, dilation) = match &node.params {
+            Some(OpParams::Conv1d(p)) => (vec![p.stride], vec![p.padding], vec![p.dilation]),
+            Some(OpParams::Conv2d(p)) => (vec![p.stride; 2], vec![p.padding; 2], vec![p.dilation; 2]),
+            Some(OpParams::Conv3d(p)) => (vec![p.stride; 3], vec![p.padding; 3], vec![p.dilation; 3]),
+            Some(OpParams::ConvTranspose1d(p)) => (vec![p.stride], vec![p.padding], vec![p.dilation]),
+            Some(OpParams::ConvTranspose2d(p)) => (vec![p.stride; 2], vec![p.padding; 2], vec![p.dilation; 2]),
+            Some(OpParams::ConvTranspose3d(p)) => (vec![p.stride; 3], vec![p.padding; 3], vec![p.dilation; 3]),
+            Some(OpParams::Conv1dGradWeight(p)) => (vec![p.stride], vec![p.padding], vec![p.dilation]),
+            Some(OpParams::Conv2dGradWeight(p)) => (vec![p.stride; 2], vec![p.padding; 2], vec![p.dilation; 2]),
+            Some(OpParams::Conv3dGradWeight(p)) => (vec![p.stride; 3], vec![p.padding; 3], vec![p.dilation; 3]),
+            Some(OpParams::ConvTranspose1dGradWeight(p)) => (vec![p.stride], vec![p.padding], vec![p.dilation]),
+            Some(OpParams::ConvTranspose2dGradWeight(p)) => {
+                (vec![p.stride; 2], vec![p.padding; 2], vec![p.dilation; 2])
+            },
+            Some(OpParams::ConvTranspose3dGradWeight(p)) => {
+                (vec![p.stride; 3], vec![p.padding; 3], vec![p.dilation; 3])
+            },
+            _ => return Err(params_error(node)),
+        };
+
+        match conv_op {
+            ConvOp::Conv1dGradWeight
+            | ConvOp::Conv2dGradWeight
+            | ConvOp::Conv3dGradWeight
+            | ConvOp::ConvTranspose1dGradWeight
+            | ConvOp::ConvTranspose2dGradWeight
+            | ConvOp::ConvTranspose3dGradWeight => {
+                let weight_shape = node.output_layout.shape();
+                binary(inputs, |s, g, sl, gl| {
+                    s.call_ops_conv_grad_weight(sl, g, gl, weight_shape, &stride, &padding, &dilation, op)
+                })
+            },
+            _ => binary(inputs, |s, w, sl, wl| {
+                s.call_ops_conv(sl, w, wl, &stride, &padding, &dilation, op)
+            }),
+        }
+    }
+
+    /// View ops reuse the input storage with the layout recorded at capture time.
+    fn execute_view(&self, node: &SnapshotNode, inputs: &[Tensor]) -> HoduResult<Tensor> {
+        let input = &inputs[0];
+        if node.input_layouts.first() == Some(&input.layout()) {
+            return Ok(from_shared_storage_with(input, node.output_layout.clone(), false));
+        }
+
+        // The input layout differs from capture time (e.g. after a data-dependent op),
+        // so recompute the view from the recorded output shape instead.
+        let shape = node.output_layout.shape().clone();
+        match &node.op {
+            Op::Shape(crate::ops::ShapeOp::Broadcast) => input.broadcast(shape),
+            Op::Shape(
+                crate::ops::ShapeOp::Reshape
+                | crate::ops::ShapeOp::Flatten
+                | crate::ops::ShapeOp::Squeeze
+                | crate::ops::ShapeOp::Unsqueeze,
+            ) => input.reshape(shape),
+            _ => Err(HoduError::UnsupportedOperation(format!(
+                "cannot replay {:?} on an input with a different layout than at capture time",
+                node.op
+            ))),
+        }
+    }
+
+    fn execute_control_flow(&self, node: &SnapshotNode, inputs: Vec<Tensor>, frame: &mut Frame) -> HoduResult<Tensor> {
+        let (group_id, output_index) = match &node.params {
+            Some(OpParams::If(p)) => (p.group_id, p.output_index),
+            Some(OpParams::While(p)) => (p.group_id, p.output_index),
+            _ => return Err(params_error(node)),
+        };
+
+        let outputs = match frame.groups.entry(group_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(self.run_control_flow(node, inputs)?),
+        };
+
+        outputs.get(output_index).cloned().ok_or_else(|| {
+            HoduError::InternalError(format!(
+                "control-flow output {} out of range for {:?}",
+                output_index, node.op
+            ))
+        })
+    }
+
+    fn run_control_flow(&self, node: &SnapshotNode, inputs: Vec<Tensor>) -> HoduResult<Vec<Tensor>> {
+        match &node.params {
+            Some(OpParams::If(p)) => {
+                let branch = if predicate_value(&inputs[0])? {
+                    p.then_branch
+                } else {
+                    p.else_branch
+                };
+                self.nested(self.snapshot.subgraph(branch)?)
+                    .execute(inputs[1..].to_vec())
+            },
+            Some(OpParams::While(p)) => {
+                let (cond, body) = (self.snapshot.subgraph(p.cond)?, self.snapshot.subgraph(p.body)?);
+                let mut carried = inputs;
+                let mut iterations = 0;
+                while p.max_iterations.is_none_or(|max| iterations < max) {
+                    let cond = self.nested(cond).execute(carried.clone())?;
+                    if !predicate_value(&cond[0])? {
+                        break;
+                    }
+                    carried = self.nested(body).execute(carried)?;
+                    iterations += 1;
+                }
+                Ok(carried)
+            },
+            _ => Err(params_error(node)),
+        }
+    }
+
+    fn execute_custom(&self, node: &SnapshotNode, inputs: Vec<Tensor>, frame: &mut Frame) -> HoduResult<Tensor> {
+        let Some(OpParams::Custom(params)) = &node.params else {
+            return Err(params_error(node));
+        };
+        let handler = self.custom_op_handler.ok_or_else(|| {
+            HoduError::UnsupportedOperation(format!("custom op '{}' requires a handler to execute it", params.name))
+        })?;
+
+        let outputs = match frame.groups.entry(params.group_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(handler(params, &inputs)?),
+        };
+
+        let output = outputs.get(params.output_index).cloned().ok_or_else(|| {
+            HoduError::InvalidArgument(format!(
+                "custom op '{}' returned {} outputs, expected at least {}",
+                params.name,
+                outputs.len(),
+                params.output_index + 1
+            ))
+        })?;
+        if output.shape() != *node.output_layout.shape() || output.dtype() != node.output_dtype {
+            return Err(HoduError::InvalidArgument(format!(
+                "custom op '{}' output {} expects {:?} {:?}, got {:?} {:?}",
+                params.name,
+                params.output_index,
+                node.output_layout.shape(),
+                node.output_dtype,
+                output.shape(),
+                output.dtype()
+            )));
+        }
+        self.prepare_input(output)
+    }
+}
+
+fn params_error(node: &SnapshotNode) -> HoduError {
+    HoduError::InvalidArgument(format!("missing or mismatched params for {:?}", node.op))
+}
+
+/// Op metadata attached to a node's profile event
+fn profile_args(index: usize, node: &SnapshotNode) -> Vec<(String, String)> {
+    let inputs: Vec<String> = node
+        .input_layouts
+        .iter()
+        .map(|layout| layout.shape().to_string())
+        .collect();
+    let mut args = vec![
+        ("node".to_string(), index.to_string()),
+        ("inputs".to_string(), inputs.join(", ")),
+        ("output".to_string(), node.output_layout.shape().to_string()),
+        ("dtype".to_string(), node.output_dtype.to_string()),
+    ];
+    if let Some(name) = &node.name {
+        args.push(("name".to_string(), name.clone()));
+    }
+    args.extend(node.metadata.iter().map(|(key, value)| (key.clone(), value.clone())));
+    args
+}
+
+fn normalize_dim(dim: Scalar, ndim: usize) -> usize {
+    let dim = dim.to_i32();
+    if dim < 0 {
+        (ndim as i32 + dim) as usize
+    } else {
+        dim as usize
+    }
+}
+
+fn unary(
+    inputs: &[Tensor],
+    f: impl FnOnce(&BackendStorage, &Layout) -> HoduResult<BackendStorage>,
+) -> HoduResult<BackendStorage> {
+    let layout = inputs[0].layout();
+    inputs[0].with_storage(|s| f(s, &layout))
+}
+
+fn binary(
+    inputs: &[Tensor],
+    f: impl FnOnce(&BackendStorage, &BackendStorage, &Layout, &Layout) -> HoduResult<BackendStorage>,
+) -> HoduResult<BackendStorage> {
+    let (lhs_layout, rhs_layout) = (inputs[0].layout(), inputs[1].layout());
+    inputs[0].with_storage(|lhs| inputs[1].with_storage(|rhs| f(lhs, rhs, &lhs_layout, &rhs_layout)))
+}
+
+#[allow(clippy::type_complexity)]
+fn ternary(
+    inputs: &[Tensor],
+    f: impl FnOnce(
+        &BackendStorage,
+        &BackendStorage,
+        &BackendStorage,
+        &Layout,
+        &Layout,
+        &Layout,
+    ) -> HoduResult<BackendStorage>,
+) -> HoduResult<BackendStorage> {
+    let (a_layout, b_layout, c_layout) = (inputs[0].layout(), inputs[1].layout(), inputs[2].layout());
+    inputs[0].with_storage(|a| {
+        inputs[1].with_storage(|b| inputs[2].with_storage(|c| f(a, b, c, &a_layout, &b_layout, &c_layout)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::capture::{cond, custom_op, while_loop, CaptureBoard};
+    use crate::types::DType;
+
+    #[test]
+    fn test_interpreter_matches_eager() {
+        let board = CaptureBoard::new();
+        board.open();
+        let x = Tensor::input("x", [2, 2], DType::F32).unwrap();
+        let y = x.add(&x).unwrap().relu().unwrap().sum(&[1], false).unwrap();
+        board.close();
+        board.with_target("y", y);
+        let snapshot = board.capture();
+
+        let input = Tensor::from_slice(vec![1.0f32, -2.0, 3.0, 4.0], [2, 2]).unwrap();
+        let outputs = Interpreter::new(&snapshot).run(&[("x", &input)]).unwrap();
+        let expected = input.add(&input).unwrap().relu().unwrap().sum(&[1], false).unwrap();
+
+        assert_eq!(outputs[0].0, "y");
+        assert_eq!(
+            outputs[0].1.to_flatten_vec::<f32>().unwrap(),
+            expected.to_flatten_vec::<f32>().unwrap()
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_memory_budget_matches_unbounded() {
+        let board = CaptureBoard::new();
+        board.open();
+        let x = Tensor::input("x", [8, 8], DType::F32).unwrap();
+        let a = x.exp().unwrap();
+        let b = a.matmul(&x).unwrap().tanh().unwrap();
+        let c = b.mul(&a).unwrap().sub(&x).unwrap();
+        let y = c.add(&a).unwrap().sum(&[0], false).unwrap();
+        board.close();
+        board.with_target("y", y);
+        board.with_target("b", b);
+        let snapshot = board.capture();
+
+        let input = Tensor::randn([8, 8], 0.0f32, 1.0).unwrap();
+        let expected = Interpreter::new(&snapshot).run(&[("x", &input)]).unwrap();
+        // A zero budget spills every value except the one just produced
+        let spilled = Interpreter::new(&snapshot)
+            .memory_budget(0)
+            .run(&[("x", &input)])
+            .unwrap();
+
+        for ((name, expected), (spilled_name, spilled)) in expected.iter().zip(&spilled) {
+            assert_eq!(name, spilled_name);
+            assert_eq!(
+                expected.to_flatten_vec::<f32>().unwrap(),
+                spilled.to_flatten_vec::<f32>().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_if_selects_branch() {
+        let board = CaptureBoard::new();
+        board.open();
+        let p = Tensor::input("p", [1], DType::BOOL).unwrap();
+        let x = Tensor::input("x", [3], DType::F32).unwrap();
+        let out = cond(
+            &p,
+            &[&x],
+            |args| Ok(vec![args[0].mul_scalar(2.0f32)?, args[0].clone()]),
+            |args| Ok(vec![args[0].neg()?, args[0].clone()]),
+        )
+        .unwrap();
+        board.close();
+        board
+            .with_target("out", out[0].clone())
+            .with_target("x", out[1].clone());
+        let snapshot = board.capture();
+        // Both result nodes refer to the same two branches
+        assert_eq!(snapshot.nodes.len(), 2);
+        assert_eq!(snapshot.subgraphs.len(), 2);
+
+        let x = Tensor::from_slice(vec![1.0f32, 2.0, 3.0], [3]).unwrap();
+        let interpreter = Interpreter::new(&snapshot);
+
+        let p = Tensor::from_slice(vec![true], [1]).unwrap();
+        let taken = interpreter.run(&[("p", &p), ("x", &x)]).unwrap();
+        assert_eq!(taken[0].1.to_flatten_vec::<f32>().unwrap(), vec![2.0, 4.0, 6.0]);
+        assert_eq!(taken[1].1.to_flatten_vec::<f32>().unwrap(), vec![1.0, 2.0, 3.0]);
+
+        let p = Tensor::from_slice(vec![false], [1]).unwrap();
+        let skipped = interpreter.run(&[("p", &p), ("x", &x)]).unwrap();
+        assert_eq!(skipped[0].1.to_flatten_vec::<f32>().unwrap(), vec![-1.0, -2.0, -3.0]);
+    }
+
+    #[test]
+    fn test_topk_of_i32_tensor() {
+        let board = CaptureBoard::new();
+        board.open();
+        let x = Tensor::input("x", [4], DType::I32).unwrap();
+        let (values, indices) = x.topk(2, 0, true, true).unwrap();
+        board.close();
+        board.with_target("indices", indices).with_target("values", values);
+        let snapshot = board.capture();
+
+        let x = Tensor::from_slice(vec![3i32, 9, 1, 7], [4]).unwrap();
+        let outputs = Interpreter::new(&snapshot).run(&[("x", &x)]).unwrap();
+        assert_eq!(outputs[0].1.to_flatten_vec::<i32>().unwrap(), vec![1, 3]);
+        assert_eq!(outputs[1].1.to_flatten_vec::<i32>().unwrap(), vec![9, 7]);
+    }
+
+    #[test]
+    fn test_while_loop_carries_values() {
+        let board = CaptureBoard::new();
+        board.open();
+        let i = Tensor::input("i", [1], DType::F32).unwrap();
+        let acc = Tensor::input("acc", [2], DType::F32).unwrap();
+        let outs = while_loop(
+            &[&i, &acc],
+            |args| args[0].lt_scalar(5.0f32),
+            |args| Ok(vec![args[0].add_scalar(1.0f32)?, args[1].mul_scalar(2.0f32)?]),
+            None,
+        )
+        .unwrap();
+        board.close();
+        board.with_target("i", outs[0].clone());
+        board.with_target("acc", outs[1].clone());
+        let snapshot = board.capture();
+
+        let i = Tensor::from_slice(vec![0.0f32], [1]).unwrap();
+        let acc = Tensor::from_slice(vec![1.0f32, 3.0], [2]).unwrap();
+        let outputs = Interpreter::new(&snapshot).run(&[("i", &i), ("acc", &acc)]).unwrap();
+
+        assert_eq!(outputs[0].1.to_flatten_vec::<f32>().unwrap(), vec![5.0]);
+        assert_eq!(outputs[1].1.to_flatten_vec::<f32>().unwrap(), vec![32.0, 96.0]);
+    }
+
+    #[test]
+    fn test_while_loop_eager_respects_max_iterations() {
+        let x = Tensor::from_slice(vec![1.0f32], [1]).unwrap();
+        let outs = while_loop(
+            &[&x],
+            |args| args[0].lt_scalar(100.0f32),
+            |args| Ok(vec![args[0].mul_scalar(2.0f32)?]),
+            Some(3),
+        )
+        .unwrap();
+        assert_eq!(outs[0].to_flatten_vec::<f32>().unwrap(), vec![8.0]);
+    }
+
+    #[test]
+    fn test_custom_op_dispatches_to_handler() {
+        let board = CaptureBoard::new();
+        board.open();
+        let x = Tensor::input("x", [3], DType::F32).unwrap();
+        let outs = custom_op("acme.shift", r#"{"by": 10}"#, &[&x], &[([3].into(), DType::F32)]).unwrap();
+        board.close();
+        board.with_target("y", outs[0].clone());
+        let snapshot = board.capture();
+        assert_eq!(snapshot.custom_op_names(), vec!["acme.shift".to_string()]);
+
+        let x = Tensor::from_slice(vec![1.0f32, 2.0, 3.0], [3]).unwrap();
+        assert!(Interpreter::new(&snapshot).run(&[("x", &x)]).is_err());
+
+        let handler = |params: &CustomParams, inputs: &[Tensor]| {
+            assert_eq!(params.attributes, r#"{"by": 10}"#);
+            Ok(vec![inputs[0].add_scalar(10.0f32)?])
+        };
+        let outputs = Interpreter::new(&snapshot)
+            .custom_op_handler(&handler)
+            .run(&[("x", &x)])
+            .unwrap();
+        assert_eq!(outputs[0].1.to_flatten_vec::<f32>().unwrap(), vec![11.0, 12.0, 13.0]);
+    }
+
+    #[test]
+    fn test_node_observer_sees_selected_intermediates() {
+        let board = CaptureBoard::new();
+        board.open();
+        let x = Tensor::input("x", [3], DType::F32).unwrap();
+        let h = x.neg().unwrap();
+        let a = h.relu().unwrap();
+        let y = a.exp().unwrap();
+        board.close();
+        board
+            .with_target("y", y.clone())
+            .with_node_name(&h, "block.add")
+            .with_node_name(&a, "block.relu")
+            .with_node_name(&y, "head.exp");
+        let snapshot = board.capture();
+
+        assert_eq!(snapshot.select_nodes("block.*"), vec![0, 1]);
+        assert_eq!(snapshot.select_nodes("*.exp, 0"), vec![0, 2]);
+        assert_eq!(snapshot.select_nodes("1..3"), vec![1, 2]);
+        assert!(snapshot.select_nodes("decoder.*").is_empty());
+
+        let selected = snapshot.select_nodes("block.relu");
+        let seen = std::cell::RefCell::new(Vec::new());
+        let observer = |index: usize, _: &SnapshotNode, tensor: &Tensor| -> HoduResult<()> {
+            if selected.contains(&index) {
+                seen.borrow_mut().push(tensor.to_flatten_vec::<f32>()?);
+            }
+            Ok(())
+        };
+        let input = Tensor::from_slice(vec![1.0f32, -2.0, 3.0], [3]).unwrap();
+        Interpreter::new(&snapshot)
+            .node_observer(&observer)
+            .run(&[("x", &input)])
+            .unwrap();
+
+        assert_eq!(seen.into_inner(), vec![vec![0.0, 2.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_backward_during_capture_records_training_step() {
+        let _ctx = crate::tensor::GradientContext::new();
+        let w_data = vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let x_data = vec![1.0f32, -1.0, 0.5, 2.0, 0.0, 1.0];
+
+        let w = Tensor::from_slice(w_data.clone(), [3, 2]).unwrap();
+        w.set_requires_grad(true).unwrap();
+        let board = CaptureBoard::new();
+        board.open();
+        let x = Tensor::input("x", [2, 3], DType::F32).unwrap();
+        let y = x.matmul(&w).unwrap().relu().unwrap();
+        let loss = y.mul(&y).unwrap().mean_all().unwrap();
+        loss.backward().unwrap();
+        board.close();
+        board.with_target("loss", loss).with_grad_target("grad_w", &w).unwrap();
+        let snapshot = board.capture();
+
+        let x = Tensor::from_slice(x_data.clone(), [2, 3]).unwrap();
+        let outputs = Interpreter::new(&snapshot).run(&[("x", &x)]).unwrap();
+        assert_eq!(outputs[1].0, "grad_w");
+
+        let w = Tensor::from_slice(w_data, [3, 2]).unwrap();
+        w.set_requires_grad(true).unwrap();
+        let y = x.matmul(&w).unwrap().relu().unwrap();
+        let loss = y.mul(&y).unwrap().mean_all().unwrap();
+        loss.backward().unwrap();
+
+        assert_eq!(
+            outputs[0].1.to_flatten_vec::<f32>().unwrap(),
+            loss.to_flatten_vec::<f32>().unwrap()
+        );
+        assert_eq!(
+            outputs[1].1.to_flatten_vec::<f32>().unwrap(),
+            w.grad().unwrap().to_flatten_vec::<f32>().unwrap()
+        );
+    }
+}
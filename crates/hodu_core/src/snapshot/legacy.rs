@@ -0,0 +1,117 @@
+//! Snapshots written before the layout was versioned
+//!
+//! These files have no envelope and hold a bare postcard snapshot whose nodes carry no name or
+//! metadata, with no model metadata or subgraphs either. Ops added since were appended to [`Op`],
+//! so the op and params encodings are shared with the current layout.
+
+use super::{Snapshot, SnapshotConstant, SnapshotInput, SnapshotNode, SnapshotTarget, SnapshotTensorId};
+use crate::{
+    error::{HoduError, HoduResult},
+    ops::{Op, OpParams, SortOp},
+    tensor::TensorId,
+    types::{DType, Layout, SymbolicLayout},
+};
+use std::collections::BTreeMap;
+
+#[derive(serde::Deserialize)]
+struct SnapshotV0 {
+    name: Option<String>,
+    inputs: Vec<SnapshotInput>,
+    constants: Vec<SnapshotConstant>,
+    targets: Vec<SnapshotTarget>,
+    nodes: Vec<SnapshotNodeV0>,
+}
+
+#[derive(serde::Deserialize)]
+struct SnapshotNodeV0 {
+    op: Op,
+    params: Option<OpParams>,
+    input_ids: Vec<SnapshotTensorId>,
+    output_id: SnapshotTensorId,
+    input_layouts: Vec<Layout>,
+    output_layout: Layout,
+    output_dtype: DType,
+    symbolic_output_layout: Option<SymbolicLayout>,
+}
+
+/// Decode a snapshot in the original layout
+pub(super) fn from_bytes(data: &[u8]) -> HoduResult<Snapshot> {
+    let snapshot: SnapshotV0 =
+        postcard::from_bytes(data).map_err(|e| HoduError::DeserializationFailed(e.to_string()))?;
+
+    let mut nodes: Vec<SnapshotNode> = snapshot
+        .nodes
+        .into_iter()
+        .map(|node| SnapshotNode {
+            op: node.op,
+            params: node.params,
+            input_ids: node.input_ids,
+            output_id: node.output_id,
+            input_layouts: node.input_layouts,
+            output_layout: node.output_layout,
+            output_dtype: node.output_dtype,
+            symbolic_output_layout: node.symbolic_output_layout,
+            name: None,
+            metadata: BTreeMap::new(),
+        })
+        .collect();
+    let mut snapshot = Snapshot {
+        name: snapshot.name,
+        inputs: snapshot.inputs,
+        constants: snapshot.constants,
+        targets: snapshot.targets,
+        nodes: Vec::new(),
+        metadata: BTreeMap::new(),
+        subgraphs: Vec::new(),
+    };
+    locate_topk_indices(&mut nodes, &snapshot);
+    snapshot.nodes = nodes;
+    Ok(snapshot)
+}
+
+/// Point `indices_id` of each TopK node at the node producing the indices
+///
+/// These files kept the runtime id there. TopK was captured as the values node followed by the
+/// indices node, whose ids were consecutive; when only the indices were reachable the lone node
+/// is told apart by its I32 output of a non-I32 input.
+fn locate_topk_indices(nodes: &mut [SnapshotNode], graph: &Snapshot) {
+    let is_topk = |node: &SnapshotNode| matches!(node.op, Op::Sort(SortOp::TopK));
+    for i in 0..nodes.len() {
+        if !is_topk(&nodes[i]) {
+            continue;
+        }
+        let twin = |other: &SnapshotNode| is_topk(other) && other.input_ids == nodes[i].input_ids;
+        let follows_values = i > 0 && twin(&nodes[i - 1]);
+        let precedes_indices = nodes.get(i + 1).is_some_and(twin);
+        let is_indices = follows_values
+            || (!precedes_indices
+                && nodes[i].output_dtype == DType::I32
+                && nodes[i]
+                    .input_ids
+                    .first()
+                    .and_then(|&id| dtype_of(id, nodes, graph))
+                    .is_some_and(|dtype| dtype != DType::I32));
+        let indices_id = match is_indices {
+            true => nodes[i].output_id.0,
+            false => nodes[i].output_id.0 + 1,
+        };
+        if let Some(OpParams::TopK(p)) = &mut nodes[i].params {
+            p.indices_id = TensorId::from_usize(indices_id);
+        }
+    }
+}
+
+fn dtype_of(id: SnapshotTensorId, nodes: &[SnapshotNode], graph: &Snapshot) -> Option<DType> {
+    graph
+        .inputs
+        .iter()
+        .find(|input| input.id == id)
+        .map(|input| input.dtype)
+        .or_else(|| graph.constants.iter().find(|c| c.id == id).map(|c| c.dtype))
+        .or_else(|| {
+            nodes
+                .iter()
+                .find(|node| node.output_id == id)
+                .map(|node| node.output_dtype)
+        })
+}
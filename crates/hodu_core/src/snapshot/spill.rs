@@ -0,0 +1,244 @@
+//! Spill-to-disk for interpreter values under a memory budget
+
+use crate::{
+    error::{HoduError, HoduResult},
+    snapshot::{Snapshot, SnapshotTensorId},
+    tensor::Tensor,
+    types::{DType, Device, Shape},
+};
+use std::{collections::HashMap, fs, path::PathBuf};
+
+/// A value that was written to disk
+struct Spilled {
+    path: PathBuf,
+    shape: Shape,
+    dtype: DType,
+}
+
+/// Keeps the resident values of one snapshot execution under a byte budget
+///
+/// Values no later node reads are dropped; if that is not enough, the least recently used
+/// values are written to temp files and reloaded through a read-only memory map when read again.
+/// A reloaded value keeps its file, so evicting it a second time costs no I/O.
+pub(crate) struct SpillStore {
+    budget: usize,
+    device: Device,
+    dir: tempfile::TempDir,
+    resident_bytes: usize,
+    clock: u64,
+    /// Logical time each resident value was last produced or read
+    last_touch: HashMap<SnapshotTensorId, u64>,
+    /// Index of the last node reading each value; targets are read after every node
+    last_read: HashMap<SnapshotTensorId, usize>,
+    spilled: HashMap<SnapshotTensorId, Spilled>,
+}
+
+impl SpillStore {
+    pub(crate) fn new(budget: usize, device: Device, snapshot: &Snapshot) -> HoduResult<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix("hodu_spill_")
+            .tempdir()
+            .map_err(|e| HoduError::IoError(format!("failed to create spill directory: {}", e)))?;
+
+        let mut last_read = HashMap::new();
+        for (index, node) in snapshot.nodes.iter().enumerate() {
+            for &id in &node.input_ids {
+                last_read.insert(id, index);
+            }
+        }
+        for target in &snapshot.targets {
+            last_read.insert(target.id, usize::MAX);
+        }
+
+        Ok(Self {
+            budget,
+            device,
+            dir,
+            resident_bytes: 0,
+            clock: 0,
+            last_touch: HashMap::new(),
+            last_read,
+            spilled: HashMap::new(),
+        })
+    }
+
+    /// Account for `tensor` becoming resident as `id`
+    pub(crate) fn insert(&mut self, id: SnapshotTensorId, tensor: &Tensor) {
+        self.resident_bytes += byte_size(tensor);
+        self.touch(id);
+    }
+
+    /// Mark a resident value as just used
+    pub(crate) fn touch(&mut self, id: SnapshotTensorId) {
+        self.clock += 1;
+        self.last_touch.insert(id, self.clock);
+    }
+
+    /// Load a spilled value back onto the device, or `None` if `id` was never spilled
+    pub(crate) fn reload(&mut self, id: SnapshotTensorId) -> HoduResult<Option<Tensor>> {
+        let Some(spilled) = self.spilled.get(&id) else {
+            return Ok(None);
+        };
+        let tensor = with_mapped(&spilled.path, |bytes| {
+            Tensor::from_bytes(bytes, spilled.shape.clone(), spilled.dtype, self.device)
+        })?;
+        self.insert(id, &tensor);
+        Ok(Some(tensor))
+    }
+
+    /// Bring the resident values back under the budget once `done` nodes have run
+    ///
+    /// `keep`, the value that was just produced, is never spilled.
+    pub(crate) fn enforce(
+        &mut self,
+        done: usize,
+        keep: SnapshotTensorId,
+        values: &mut HashMap<SnapshotTensorId, Tensor>,
+    ) -> HoduResult<()> {
+        let dead: Vec<SnapshotTensorId> = values
+            .keys()
+            .filter(|id| self.last_read.get(id).is_none_or(|&last| last < done))
+            .copied()
+            .collect();
+        for id in dead {
+            if let Some(tensor) = values.remove(&id) {
+                self.evict(id, &tensor);
+            }
+            if let Some(spilled) = self.spilled.remove(&id) {
+                let _ = fs::remove_file(spilled.path);
+            }
+        }
+
+        while self.resident_bytes > self.budget {
+            let victim = values
+                .keys()
+                .filter(|&&id| id != keep)
+                .min_by_key(|id| self.last_touch.get(id).copied().unwrap_or(0))
+                .copied();
+            let Some(victim) = victim else {
+                break;
+            };
+            let tensor = values.remove(&victim).expect("victim is resident");
+            if !self.spilled.contains_key(&victim) {
+                let path = self.dir.path().join(format!("{}.bin", victim.0));
+                fs::write(&path, tensor.to_bytes()?)
+                    .map_err(|e| HoduError::IoError(format!("failed to spill {}: {}", path.display(), e)))?;
+                self.spilled.insert(
+                    victim,
+                    Spilled {
+                        path,
+                        shape: tensor.shape(),
+                        dtype: tensor.dtype(),
+                    },
+                );
+            }
+            self.evict(victim, &tensor);
+        }
+        Ok(())
+    }
+
+    /// Number of values currently on disk
+    #[cfg(test)]
+    pub(crate) fn num_spilled(&self) -> usize {
+        self.spilled.len()
+    }
+
+    fn evict(&mut self, id: SnapshotTensorId, tensor: &Tensor) {
+        self.resident_bytes = self.resident_bytes.saturating_sub(byte_size(tensor));
+        self.last_touch.remove(&id);
+    }
+}
+
+fn byte_size(tensor: &Tensor) -> usize {
+    tensor.size() * tensor.dtype().size_in_bytes()
+}
+
+/// Run `f` on the contents of `path` through a read-only memory map
+#[cfg(unix)]
+fn with_mapped<R>(path: &std::path::Path, f: impl FnOnce(&[u8]) -> HoduResult<R>) -> HoduResult<R> {
+    use std::os::fd::AsRawFd;
+
+    let io_error = |e: std::io::Error| HoduError::IoError(format!("failed to map {}: {}", path.display(), e));
+    let file = fs::File::open(path).map_err(io_error)?;
+    let len = file.metadata().map_err(io_error)?.len() as usize;
+    if len == 0 {
+        return f(&[]);
+    }
+
+    // SAFETY: the file is private to the spill directory and not modified while mapped
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            file.as_raw_fd(),
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(io_error(std::io::Error::last_os_error()));
+    }
+    let result = f(unsafe { std::slice::from_raw_parts(ptr as *const u8, len) });
+    unsafe { libc::munmap(ptr, len) };
+    result
+}
+
+/// Run `f` on the contents of `path`
+#[cfg(not(unix))]
+fn with_mapped<R>(path: &std::path::Path, f: impl FnOnce(&[u8]) -> HoduResult<R>) -> HoduResult<R> {
+    let bytes = fs::read(path).map_err(|e| HoduError::IoError(format!("failed to read {}: {}", path.display(), e)))?;
+    f(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::CaptureBoard;
+
+    fn resident(store: &mut SpillStore, values: &mut HashMap<SnapshotTensorId, Tensor>, id: SnapshotTensorId) {
+        let tensor = Tensor::from_slice(vec![id.0 as f32; 4], [4]).unwrap();
+        store.insert(id, &tensor);
+        values.insert(id, tensor);
+    }
+
+    #[test]
+    fn test_spill_and_reload() {
+        let board = CaptureBoard::new();
+        board.open();
+        let x = Tensor::input("x", [4], DType::F32).unwrap();
+        let a = x.exp().unwrap();
+        let y = a.neg().unwrap().abs().unwrap();
+        board.close();
+        board.with_target("a", a);
+        board.with_target("y", y);
+        let snapshot = board.capture();
+        // exp, neg, abs: `exp` is a target, `neg` is only read by `abs`
+        let ids: Vec<SnapshotTensorId> = snapshot.nodes.iter().map(|node| node.output_id).collect();
+        assert_eq!(ids.len(), 3);
+
+        // Room for a single 4-element f32 value
+        let mut store = SpillStore::new(16, Device::CPU, &snapshot).unwrap();
+        let mut values = HashMap::new();
+        resident(&mut store, &mut values, ids[0]);
+        store.enforce(1, ids[0], &mut values).unwrap();
+        assert_eq!(store.num_spilled(), 0);
+
+        resident(&mut store, &mut values, ids[1]);
+        store.enforce(2, ids[1], &mut values).unwrap();
+        assert_eq!(store.num_spilled(), 1);
+        assert!(!values.contains_key(&ids[0]));
+
+        let a = store.reload(ids[0]).unwrap().unwrap();
+        assert_eq!(a.to_flatten_vec::<f32>().unwrap(), vec![ids[0].0 as f32; 4]);
+        assert!(store.reload(ids[1]).unwrap().is_none());
+        values.insert(ids[0], a);
+
+        // `neg` is dead once `abs` ran and is dropped; `exp` goes back to disk without a rewrite
+        resident(&mut store, &mut values, ids[2]);
+        store.enforce(3, ids[2], &mut values).unwrap();
+        assert_eq!(values.keys().copied().collect::<Vec<_>>(), vec![ids[2]]);
+        assert_eq!(store.num_spilled(), 1);
+        assert!(store.reload(ids[0]).unwrap().is_some());
+    }
+}
@@ -4,8 +4,10 @@ mod creation;
 mod creation_from_ops;
 mod creation_static;
 mod display;
+mod dlpack;
 pub mod gradient;
 mod internal;
+pub(crate) mod lazy;
 mod ops;
 mod registry;
 pub(crate) mod utils;
@@ -15,16 +17,19 @@ mod vec;
 pub(crate) use core::Tensor_;
 pub use core::{Tensor, TensorId};
 pub use creation::{get_runtime_device, set_runtime_device};
+pub use dlpack::{DLDataType, DLDevice, DLManagedTensor, DLTensor};
 pub use gradient::{is_computing_gradients, is_in_optimizer_step, set_optimizer_step_flag, ContextId, GradientContext};
+pub use lazy::{is_lazy, lazy_scope, set_lazy};
 
 // Re-export registry functions
 pub use registry::{exists, get, get_dtype, shrink_tensor_storage, tensor_count, with_tensor, with_tensor_mut};
 
 // Re-export internal functions for crate use
 pub(crate) use internal::{
-    create_builder_tensor, from_shared_storage_with, from_storage, from_storage_with_context, set_grad_tensor_id,
-    tensor_from_id,
+    create_builder_tensor, create_pending_tensor, from_shared_storage_with, from_storage, from_storage_arc,
+    from_storage_with_context, set_grad_tensor_id, tensor_from_id,
 };
+pub(crate) use lazy::LazyOp;
 
 // Re-export for submodules
 pub(crate) use registry::{get_all_tensor_ids, insert, remove};
@@ -5,27 +5,34 @@ use crate::{
     tensor::{internal::from_storage_with_context, Tensor},
     types::{DType, Device, Layout, Shape},
 };
-#[cfg(feature = "serde")]
+#[cfg(all(feature = "serde", feature = "std"))]
 use std::path::Path;
 
 impl Tensor {
     /// Convert tensor to raw bytes (little-endian)
     ///
-    /// Works with tensors on any device (CPU, CUDA, Metal).
+    /// Works with tensors on any device (CPU, CUDA, Metal, WebGPU).
     /// GPU tensors are automatically transferred to CPU for serialization.
     pub fn to_bytes(&self) -> HoduResult<Vec<u8>> {
         let cpu_storage = self.with_storage(|storage| storage.to_cpu_storage())?;
         let layout = self.layout();
 
         // Make contiguous if needed
-        let cpu_storage = if !layout.is_contiguous() {
+        if !layout.is_contiguous() {
             let contiguous = self.contiguous()?;
-            contiguous.with_storage(|storage| storage.to_cpu_storage())?
-        } else {
-            cpu_storage
-        };
+            let cpu_storage = contiguous.with_storage(|storage| storage.to_cpu_storage())?;
+            return Ok(storage_to_bytes(&cpu_storage));
+        }
 
-        Ok(storage_to_bytes(&cpu_storage))
+        // A contiguous view may still cover only part of its storage, e.g. a slice of the outer dim
+        let bytes = storage_to_bytes(&cpu_storage);
+        let elem_size = self.dtype().size_in_bytes();
+        let start = layout.offset() * elem_size;
+        let end = start + layout.shape().size() * elem_size;
+        if start == 0 && end == bytes.len() {
+            return Ok(bytes);
+        }
+        Ok(bytes[start..end].to_vec())
     }
 
     /// Create tensor from raw bytes on specified device
@@ -57,13 +64,15 @@ impl Tensor {
             Device::CUDA(_) => tensor.to_device(device),
             #[cfg(feature = "metal")]
             Device::Metal => tensor.to_device(device),
+            #[cfg(feature = "wgpu")]
+            Device::WebGPU => tensor.to_device(device),
             #[allow(unreachable_patterns)]
             _ => Ok(tensor),
         }
     }
 
-    /// Save tensor to file (format determined by extension: .hdt, .json)
-    #[cfg(feature = "serde")]
+    /// Save tensor to file (format determined by extension: .hdt, .json, .npy)
+    #[cfg(all(feature = "serde", feature = "std"))]
     pub fn save(&self, path: impl AsRef<Path>) -> HoduResult<()> {
         use crate::types::Format;
 
@@ -71,11 +80,12 @@ impl Tensor {
         match Format::from_path(path).unwrap_or_default() {
             Format::HDT => crate::format::hdt::save(self, path),
             Format::JSON => crate::format::json::save(self, path),
+            Format::NPY => crate::format::npy::save(self, path),
         }
     }
 
-    /// Load tensor from file (format determined by extension: .hdt, .json)
-    #[cfg(feature = "serde")]
+    /// Load tensor from file (format determined by extension: .hdt, .json, .npy)
+    #[cfg(all(feature = "serde", feature = "std"))]
     pub fn load(path: impl AsRef<Path>) -> HoduResult<Self> {
         use crate::types::Format;
 
@@ -83,6 +93,7 @@ impl Tensor {
         match Format::from_path(path).unwrap_or_default() {
             Format::HDT => crate::format::hdt::load(path),
             Format::JSON => crate::format::json::load(path),
+            Format::NPY => crate::format::npy::load(path),
         }
     }
 }
@@ -182,3 +193,24 @@ fn bytes_to_storage(data: &[u8], dtype: DType) -> HoduResult<CpuStorage> {
         }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_bytes_of_offset_view() {
+        let values: Vec<f32> = (0..12).map(|i| i as f32).collect();
+        let tensor = Tensor::from_slice(values.clone(), [4, 3]).unwrap();
+        // Rows 1 and 2: contiguous, but starting 3 elements into the storage
+        let rows = tensor.slice(0, 1, Some(3), 1).unwrap();
+        assert!(rows.layout().is_contiguous());
+        assert_eq!(rows.layout().offset(), 3);
+
+        let expected: Vec<u8> = values[3..9].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let bytes = rows.to_bytes().unwrap();
+        assert_eq!(bytes, expected);
+        let restored = Tensor::from_bytes(&bytes, [2, 3], DType::F32, Device::CPU).unwrap();
+        assert_eq!(restored.to_flatten_vec::<f32>().unwrap(), values[3..9]);
+    }
+}
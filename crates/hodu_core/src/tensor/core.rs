@@ -22,6 +22,11 @@ impl TensorId {
     pub fn as_usize(&self) -> usize {
         self.0
     }
+
+    /// Id recorded in a snapshot, where ids are local to the snapshot
+    pub(crate) fn from_usize(id: usize) -> Self {
+        Self(id)
+    }
 }
 
 #[repr(transparent)]
@@ -119,11 +124,13 @@ impl Tensor {
         self.0
     }
 
+    /// Whether the tensor holds data, or will once its pending lazy expression is realized
     pub(crate) fn has_storage(&self) -> bool {
-        registry::with_tensor(self.0, |t| t.storage.is_some()).unwrap_or(false)
+        registry::with_tensor(self.0, |t| t.storage.is_some()).unwrap_or(false) || super::lazy::is_pending(self.0)
     }
 
     pub(crate) fn with_storage<R>(&self, f: impl FnOnce(&BackendStorage) -> HoduResult<R>) -> HoduResult<R> {
+        self.realize()?;
         registry::with_tensor(self.0, |tensor_ref| {
             let storage = tensor_ref.storage.as_ref().ok_or(HoduError::StorageNotFound(self.0))?;
             f(storage.as_ref())
@@ -174,6 +181,9 @@ impl Tensor {
     }
 
     pub fn device(&self) -> Device {
+        if let Some(device) = super::lazy::pending_device(self.0) {
+            return device;
+        }
         self.with_storage(|storage| Ok(storage.device())).unwrap_or(Device::CPU)
     }
 
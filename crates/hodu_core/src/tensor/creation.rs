@@ -9,7 +9,7 @@ use crate::{
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 // Use AtomicUsize for lock-free access to runtime device with CUDA device ID
-// Device encoding: 0 = CPU, 1-16 = CUDA(0-15), 17 = Metal
+// Device encoding: 0 = CPU, 1-16 = CUDA(0-15), 17 = Metal, 18 = WebGPU
 static RUNTIME_DEVICE: AtomicUsize = AtomicUsize::new(0); // Default: CPU
 
 #[inline]
@@ -21,6 +21,8 @@ pub fn get_runtime_device() -> Device {
         1..=16 => Device::CUDA(encoded - 1),
         #[cfg(any(feature = "metal", feature = "metal-device"))]
         17 => Device::Metal,
+        #[cfg(feature = "wgpu")]
+        18 => Device::WebGPU,
         _ => Device::CPU, // Fallback
     }
 }
@@ -38,6 +40,8 @@ pub fn set_runtime_device(device: Device) {
         },
         #[cfg(any(feature = "metal", feature = "metal-device"))]
         Device::Metal => 17,
+        #[cfg(feature = "wgpu")]
+        Device::WebGPU => 18,
     };
     RUNTIME_DEVICE.store(encoded, Ordering::Relaxed);
 }
@@ -6,7 +6,7 @@ use crate::{
 use std::sync::atomic::AtomicUsize;
 
 impl Tensor {
-    pub fn input(name: &'static str, shape: impl Into<Shape>, dtype: DType) -> HoduResult<Self> {
+    pub fn input(name: &str, shape: impl Into<Shape>, dtype: DType) -> HoduResult<Self> {
         let shape = shape.into();
         if !crate::snapshot::capture::is_active() {
             return Err(HoduError::CaptureNotActive);
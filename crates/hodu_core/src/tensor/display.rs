@@ -389,6 +389,7 @@ where
 
 impl fmt::Display for Tensor {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let _ = self.realize();
         let has_storage =
             crate::tensor::with_tensor(self.0, |tensor_ref| tensor_ref.storage.is_some()).unwrap_or(false);
         if !has_storage {
@@ -528,6 +529,7 @@ impl fmt::Debug for Tensor {
         }
         write!(f, "], data=")?;
 
+        let _ = self.realize();
         let has_storage =
             crate::tensor::with_tensor(self.0, |tensor_ref| tensor_ref.storage.is_some()).unwrap_or(false);
         if !has_storage {
@@ -0,0 +1,432 @@
+//! DLPack interop
+//!
+//! [`Tensor::to_dlpack`] exports a tensor as a `DLManagedTensor` that shares its storage, so
+//! PyTorch, JAX, NumPy and other DLPack consumers can read it without a copy. [`Tensor::from_dlpack`]
+//! takes a `DLManagedTensor` back. Tensors exported by hodu are re-imported without a copy; memory
+//! owned by another framework is copied into hodu storage, since hodu storage owns its buffers.
+
+use super::{from_storage_arc, registry, Tensor};
+use crate::{
+    be::storage::BackendStorage,
+    error::{HoduError, HoduResult},
+    types::{DType, Device, Layout, Shape},
+};
+use std::ffi::c_void;
+use std::sync::Arc;
+
+const DL_CPU: i32 = 1;
+const DL_CUDA: i32 = 2;
+const DL_CUDA_HOST: i32 = 3;
+const DL_CUDA_MANAGED: i32 = 13;
+
+const DL_INT: u8 = 0;
+const DL_UINT: u8 = 1;
+const DL_FLOAT: u8 = 2;
+const DL_BFLOAT: u8 = 4;
+const DL_BOOL: u8 = 6;
+const DL_FLOAT8_E4M3FN: u8 = 10;
+#[cfg(feature = "f8e5m2")]
+const DL_FLOAT8_E5M2: u8 = 12;
+
+/// `DLDevice`: the device type (`kDLCPU`, `kDLCUDA`, ...) and its index
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DLDevice {
+    pub device_type: i32,
+    pub device_id: i32,
+}
+
+/// `DLDataType`: a type code (`kDLInt`, `kDLFloat`, ...), bit width and vector lanes
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DLDataType {
+    pub code: u8,
+    pub bits: u8,
+    pub lanes: u16,
+}
+
+/// `DLTensor`: a strided view of memory on a device
+///
+/// `shape` and `strides` point to `ndim` values; `strides` is in elements and may be null for a
+/// compact row-major tensor.
+#[repr(C)]
+#[derive(Debug)]
+pub struct DLTensor {
+    pub data: *mut c_void,
+    pub device: DLDevice,
+    pub ndim: i32,
+    pub dtype: DLDataType,
+    pub shape: *mut i64,
+    pub strides: *mut i64,
+    pub byte_offset: u64,
+}
+
+/// `DLManagedTensor`: a [`DLTensor`] with the deleter its consumer calls once done with it
+#[repr(C)]
+#[derive(Debug)]
+pub struct DLManagedTensor {
+    pub dl_tensor: DLTensor,
+    pub manager_ctx: *mut c_void,
+    pub deleter: Option<unsafe extern "C" fn(*mut DLManagedTensor)>,
+}
+
+/// What an exported tensor keeps alive until its deleter runs
+struct Export {
+    storage: Arc<BackendStorage>,
+    layout: Layout,
+    shape: Vec<i64>,
+    strides: Vec<i64>,
+}
+
+unsafe extern "C" fn delete_export(managed: *mut DLManagedTensor) {
+    if managed.is_null() {
+        return;
+    }
+    let managed = Box::from_raw(managed);
+    drop(Box::from_raw(managed.manager_ctx as *mut Export));
+}
+
+impl Tensor {
+    /// Export the tensor as a DLPack `DLManagedTensor` sharing its CPU or CUDA storage
+    ///
+    /// The consumer takes ownership and must call the returned tensor's deleter exactly once,
+    /// which releases hodu's reference to the storage.
+    pub fn to_dlpack(&self) -> HoduResult<*mut DLManagedTensor> {
+        // Realize pending lazy results before sharing the storage
+        self.with_storage(|_| Ok(()))?;
+        let (storage, layout) = registry::with_tensor(self.0, |t| (t.storage.clone(), t.layout.clone()))
+            .ok_or(HoduError::TensorNotFound(self.0))?;
+        let storage = storage.ok_or(HoduError::StorageNotFound(self.0))?;
+
+        let (data, device) = match storage.as_ref() {
+            BackendStorage::CPU(storage) => (
+                storage.as_ptr(),
+                DLDevice {
+                    device_type: DL_CPU,
+                    device_id: 0,
+                },
+            ),
+            #[cfg(feature = "cuda")]
+            BackendStorage::CUDA(storage) => (
+                storage.as_ptr(),
+                DLDevice {
+                    device_type: DL_CUDA,
+                    device_id: storage.device_id() as i32,
+                },
+            ),
+            #[allow(unreachable_patterns)]
+            other => {
+                return Err(HoduError::UnsupportedOperation(format!(
+                    "DLPack export of {} tensors; move the tensor to the CPU first",
+                    other.device()
+                )))
+            },
+        };
+        let dtype = storage.dtype();
+
+        let mut export = Box::new(Export {
+            shape: layout.shape().dims().iter().map(|&d| d as i64).collect(),
+            strides: layout.strides().iter().map(|&s| s as i64).collect(),
+            layout,
+            storage,
+        });
+        let dl_tensor = DLTensor {
+            data: data as *mut c_void,
+            device,
+            ndim: export.shape.len() as i32,
+            dtype: to_dl_dtype(dtype),
+            shape: export.shape.as_mut_ptr(),
+            strides: export.strides.as_mut_ptr(),
+            byte_offset: (export.layout.offset() * dtype.size_in_bytes()) as u64,
+        };
+        Ok(Box::into_raw(Box::new(DLManagedTensor {
+            dl_tensor,
+            manager_ctx: Box::into_raw(export) as *mut c_void,
+            deleter: Some(delete_export),
+        })))
+    }
+
+    /// Import a DLPack `DLManagedTensor`, taking ownership of it
+    ///
+    /// Tensors exported by [`Tensor::to_dlpack`] come back sharing their storage; others are
+    /// copied (CUDA memory stays on its device) and their deleter is called before returning.
+    ///
+    /// # Safety
+    ///
+    /// `managed` must point to a valid `DLManagedTensor` whose memory stays readable until its
+    /// deleter runs, and must not be used by the caller afterwards.
+    pub unsafe fn from_dlpack(managed: *mut DLManagedTensor) -> HoduResult<Self> {
+        if managed.is_null() {
+            return Err(HoduError::InvalidArgument("null DLManagedTensor".to_string()));
+        }
+
+        if (*managed)
+            .deleter
+            .is_some_and(|deleter| std::ptr::fn_addr_eq(deleter, delete_export as unsafe extern "C" fn(_)))
+        {
+            let managed = Box::from_raw(managed);
+            let export = Box::from_raw(managed.manager_ctx as *mut Export);
+            return Ok(from_storage_arc(export.storage, export.layout));
+        }
+
+        let result = copy_foreign(&(*managed).dl_tensor);
+        if let Some(deleter) = (*managed).deleter {
+            deleter(managed);
+        }
+        result
+    }
+}
+
+/// Copy a foreign DLPack tensor into a new contiguous hodu tensor on the same device
+unsafe fn copy_foreign(tensor: &DLTensor) -> HoduResult<Tensor> {
+    let dtype = from_dl_dtype(tensor.dtype)?;
+    let ndim = usize::try_from(tensor.ndim)
+        .map_err(|_| HoduError::InvalidArgument(format!("invalid DLPack ndim {}", tensor.ndim)))?;
+    let shape = if ndim == 0 {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(tensor.shape, ndim)
+            .iter()
+            .map(|&d| usize::try_from(d))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| HoduError::InvalidArgument("negative DLPack dimension".to_string()))?
+    };
+    let strides = if tensor.strides.is_null() || ndim == 0 {
+        Layout::from_shape(&Shape::new(&shape))
+            .strides()
+            .iter()
+            .map(|&s| s as i64)
+            .collect()
+    } else {
+        std::slice::from_raw_parts(tensor.strides, ndim).to_vec()
+    };
+
+    let elem = dtype.size_in_bytes();
+    let base = (tensor.data as *const u8).add(tensor.byte_offset as usize);
+    let bytes = if shape.contains(&0) {
+        Vec::new()
+    } else {
+        match tensor.device.device_type {
+            DL_CPU | DL_CUDA_HOST | DL_CUDA_MANAGED => gather(base, &shape, &strides, elem),
+            DL_CUDA => {
+                let (low, high) = span(&shape, &strides);
+                let host = copy_from_cuda(
+                    base.offset(low * elem as isize),
+                    (high - low) as usize * elem,
+                    tensor.device.device_id,
+                )?;
+                gather(host.as_ptr().offset(-low * elem as isize), &shape, &strides, elem)
+            },
+            other => {
+                return Err(HoduError::UnsupportedOperation(format!(
+                    "DLPack import from device type {}",
+                    other
+                )))
+            },
+        }
+    };
+
+    let device = match tensor.device.device_type {
+        #[cfg(feature = "cuda")]
+        DL_CUDA => Device::CUDA(tensor.device.device_id as usize),
+        _ => Device::CPU,
+    };
+    Tensor::from_bytes(&bytes, shape, dtype, device)
+}
+
+/// Element offsets (relative to the base) of the first and one past the last addressed element
+fn span(shape: &[usize], strides: &[i64]) -> (isize, isize) {
+    let (mut low, mut high) = (0isize, 0isize);
+    for (&dim, &stride) in shape.iter().zip(strides) {
+        let extent = (dim as isize - 1) * stride as isize;
+        if extent < 0 {
+            low += extent;
+        } else {
+            high += extent;
+        }
+    }
+    (low, high + 1)
+}
+
+/// Copy a strided host tensor into contiguous row-major bytes
+unsafe fn gather(base: *const u8, shape: &[usize], strides: &[i64], elem: usize) -> Vec<u8> {
+    let count: usize = shape.iter().product();
+    let mut bytes = Vec::with_capacity(count * elem);
+    let mut index = vec![0usize; shape.len()];
+    for _ in 0..count {
+        let offset: isize = index.iter().zip(strides).map(|(&i, &s)| i as isize * s as isize).sum();
+        bytes.extend_from_slice(std::slice::from_raw_parts(base.offset(offset * elem as isize), elem));
+        for axis in (0..shape.len()).rev() {
+            index[axis] += 1;
+            if index[axis] < shape[axis] {
+                break;
+            }
+            index[axis] = 0;
+        }
+    }
+    bytes
+}
+
+#[cfg(feature = "cuda")]
+unsafe fn copy_from_cuda(ptr: *const u8, len: usize, device_id: i32) -> HoduResult<Vec<u8>> {
+    use hodu_cuda_kernels::cudarc::driver::result;
+
+    let device = crate::be_cuda::device::CudaDevice::get(device_id as usize)?;
+    device
+        .context
+        .bind_to_thread()
+        .map_err(|e| HoduError::BackendError(format!("CUDA context bind failed: {:?}", e)))?;
+    let mut host = vec![0u8; len];
+    result::memcpy_dtoh_sync(&mut host, ptr as u64)
+        .map_err(|e| HoduError::BackendError(format!("CUDA memcpy_dtoh failed: {:?}", e)))?;
+    Ok(host)
+}
+
+#[cfg(not(feature = "cuda"))]
+unsafe fn copy_from_cuda(_ptr: *const u8, _len: usize, _device_id: i32) -> HoduResult<Vec<u8>> {
+    Err(HoduError::UnsupportedOperation(
+        "DLPack import of CUDA memory requires the `cuda` feature".to_string(),
+    ))
+}
+
+fn to_dl_dtype(dtype: DType) -> DLDataType {
+    let code = match dtype {
+        DType::BOOL => DL_BOOL,
+        DType::F8E4M3 => DL_FLOAT8_E4M3FN,
+        #[cfg(feature = "f8e5m2")]
+        DType::F8E5M2 => DL_FLOAT8_E5M2,
+        DType::BF16 => DL_BFLOAT,
+        dtype if dtype.is_float() => DL_FLOAT,
+        dtype if dtype.is_uint() => DL_UINT,
+        _ => DL_INT,
+    };
+    DLDataType {
+        code,
+        bits: (dtype.size_in_bytes() * 8) as u8,
+        lanes: 1,
+    }
+}
+
+fn from_dl_dtype(dtype: DLDataType) -> HoduResult<DType> {
+    let unsupported = || {
+        HoduError::UnsupportedOperation(format!(
+            "DLPack dtype (code {}, {} bits, {} lanes)",
+            dtype.code, dtype.bits, dtype.lanes
+        ))
+    };
+    if dtype.lanes != 1 {
+        return Err(unsupported());
+    }
+    Ok(match (dtype.code, dtype.bits) {
+        (DL_BOOL, 8) => DType::BOOL,
+        (DL_FLOAT8_E4M3FN, 8) => DType::F8E4M3,
+        #[cfg(feature = "f8e5m2")]
+        (DL_FLOAT8_E5M2, 8) => DType::F8E5M2,
+        (DL_BFLOAT, 16) => DType::BF16,
+        (DL_FLOAT, 16) => DType::F16,
+        (DL_FLOAT, 32) => DType::F32,
+        #[cfg(feature = "f64")]
+        (DL_FLOAT, 64) => DType::F64,
+        (DL_UINT, 8) => DType::U8,
+        #[cfg(feature = "u16")]
+        (DL_UINT, 16) => DType::U16,
+        (DL_UINT, 32) => DType::U32,
+        #[cfg(feature = "u64")]
+        (DL_UINT, 64) => DType::U64,
+        (DL_INT, 8) => DType::I8,
+        #[cfg(feature = "i16")]
+        (DL_INT, 16) => DType::I16,
+        (DL_INT, 32) => DType::I32,
+        #[cfg(feature = "i64")]
+        (DL_INT, 64) => DType::I64,
+        _ => return Err(unsupported()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn test_export_shares_storage() {
+        let tensor = Tensor::from_slice(vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], [2, 3]).unwrap();
+        let transposed = tensor.transpose(0, 1).unwrap();
+        let managed = transposed.to_dlpack().unwrap();
+
+        unsafe {
+            let dl = &(*managed).dl_tensor;
+            assert_eq!(
+                dl.device,
+                DLDevice {
+                    device_type: DL_CPU,
+                    device_id: 0
+                }
+            );
+            assert_eq!(
+                dl.dtype,
+                DLDataType {
+                    code: DL_FLOAT,
+                    bits: 32,
+                    lanes: 1
+                }
+            );
+            assert_eq!(std::slice::from_raw_parts(dl.shape, 2), &[3, 2]);
+            assert_eq!(std::slice::from_raw_parts(dl.strides, 2), &[1, 3]);
+            assert_eq!(*(dl.data as *const f32).add(1), 2.0);
+
+            // Re-importing an export reuses its storage
+            let data = dl.data;
+            let imported = Tensor::from_dlpack(managed).unwrap();
+            assert_eq!(imported.shape().dims(), &[3, 2]);
+            assert_eq!(
+                imported.to_flatten_vec::<f32>().unwrap(),
+                vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]
+            );
+            let again = imported.to_dlpack().unwrap();
+            assert_eq!((*again).dl_tensor.data, data);
+            ((*again).deleter.unwrap())(again);
+        }
+    }
+
+    static FOREIGN_DELETED: AtomicBool = AtomicBool::new(false);
+
+    unsafe extern "C" fn delete_foreign(managed: *mut DLManagedTensor) {
+        drop(Box::from_raw(managed));
+        FOREIGN_DELETED.store(true, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_import_copies_foreign_memory() {
+        let mut data = vec![10i32, 20, 30, 40, 50, 60];
+        let mut shape = [2i64, 2];
+        // The transpose of the last two columns of a 2x3 row-major buffer
+        let mut strides = [1i64, 3];
+        let managed = Box::into_raw(Box::new(DLManagedTensor {
+            dl_tensor: DLTensor {
+                data: data.as_mut_ptr() as *mut c_void,
+                device: DLDevice {
+                    device_type: DL_CPU,
+                    device_id: 0,
+                },
+                ndim: 2,
+                dtype: DLDataType {
+                    code: DL_INT,
+                    bits: 32,
+                    lanes: 1,
+                },
+                shape: shape.as_mut_ptr(),
+                strides: strides.as_mut_ptr(),
+                byte_offset: 4,
+            },
+            manager_ctx: std::ptr::null_mut(),
+            deleter: Some(delete_foreign),
+        }));
+
+        let tensor = unsafe { Tensor::from_dlpack(managed) }.unwrap();
+        assert!(FOREIGN_DELETED.load(Ordering::SeqCst));
+        data.fill(0);
+        assert_eq!(tensor.dtype(), DType::I32);
+        assert_eq!(tensor.to_flatten_vec::<i32>().unwrap(), vec![20, 50, 30, 60]);
+    }
+}
@@ -31,7 +31,9 @@ pub(super) fn initialize_tape(context_id: ContextId) {
 
 /// Remove a tape for a context and clean up context-owned tensors
 pub(super) fn remove_tape(context_id: ContextId) {
-    if let Some(tape) = GRADIENT_TAPES.lock().unwrap().remove(&context_id) {
+    // Release the lock before cleanup, which may prune tapes again when dropping gradients
+    let tape = GRADIENT_TAPES.lock().unwrap().remove(&context_id);
+    if let Some(tape) = tape {
         cleanup_context_tensors(context_id, &tape);
     }
 }
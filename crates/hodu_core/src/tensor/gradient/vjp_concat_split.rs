@@ -25,7 +25,7 @@ impl VjpCompute for ConcatOp {
             .iter()
             .map(|&id| {
                 let tensor = tensor_from_id(id);
-                tensor.shape().dims()[dim.to_usize()] as usize
+                tensor.shape().dims()[dim.to_usize()]
             })
             .collect();
 
@@ -42,31 +42,8 @@ impl VjpCompute for ShapeOp {
             },
             ShapeOp::Broadcast => {
                 // Sum over the broadcasted dimensions to get back to original shape
-                let mut result_grad = grad_tensor;
-
-                // Handle dimension differences (leading dimensions were added)
-                if grad_shape.dims().len() > input_shape.dims().len() {
-                    // Sum over leading dimensions that were added during broadcasting
-                    let dims_to_sum: Vec<usize> = (0..(grad_shape.dims().len() - input_shape.dims().len())).collect();
-                    for &dim in dims_to_sum.iter().rev() {
-                        result_grad = result_grad.sum(&[dim], false)?;
-                    }
-                }
-
-                // Handle size-1 dimensions that were broadcasted
-                let current_shape = result_grad.shape();
-                for (i, (&input_dim, &current_dim)) in
-                    input_shape.dims().iter().zip(current_shape.dims().iter()).enumerate()
-                {
-                    if input_dim == 1 && current_dim > 1 {
-                        // This dimension was broadcasted from size 1, sum it back
-                        result_grad = result_grad.sum(&[i], true)?; // keep_dim=true to maintain size 1
-                    }
-                }
-
-                // Final reshape to ensure exact match
-                let final_grad = result_grad.reshape(&input_shape)?;
-                Ok(vec![final_grad.id()])
+                let input_grad = grad_tensor.sum_to_shape(&input_shape)?;
+                Ok(vec![input_grad.id()])
             },
             ShapeOp::Transpose => {
                 // Reverse the transpose by finding which dimensions were swapped
@@ -209,7 +186,7 @@ impl VjpCompute for ShapeScalarsOp {
                         "Gradient shape rank must match input shape rank for slice".to_string(),
                     ));
                 }
-                if grad_shape.dims()[dim] as usize != indices_vec.len() {
+                if grad_shape.dims()[dim] != indices_vec.len() {
                     return Err(HoduError::InternalError(format!(
                         "Gradient shape[{}]={} does not match expected slice size={}",
                         dim,
@@ -48,6 +48,9 @@ pub(crate) fn from_storage_with_context(
 }
 
 pub(crate) fn from_shared_storage_with(source_tensor: &Tensor, layout: Layout, requires_grad: bool) -> Tensor {
+    // A view of a pending lazy tensor needs its data; a failed realization surfaces later as StorageNotFound
+    let _ = source_tensor.realize();
+
     let storage_arc = registry::with_tensor(source_tensor.id(), |tensor_ref| tensor_ref.storage.clone())
         .expect("Source tensor not found");
 
@@ -74,7 +77,39 @@ pub(crate) fn from_shared_storage_with(source_tensor: &Tensor, layout: Layout, r
     Tensor::from_id(tensor_id)
 }
 
+/// A runtime tensor over an existing storage, e.g. one kept alive outside the registry
+pub(crate) fn from_storage_arc(storage: Arc<BackendStorage>, layout: Layout) -> Tensor {
+    let owner_context = if gradient::is_computing_gradients() || gradient::is_in_optimizer_step() {
+        None
+    } else {
+        Some(gradient::get_active_context())
+    };
+
+    let tensor_ = Tensor_ {
+        storage: Some(storage),
+        layout,
+        dtype: None, // Runtime tensors get dtype from storage
+        requires_grad: false,
+        grad_tensor_id: None,
+        is_runtime: true,
+        is_gradient: false,
+        owner_context,
+        ref_count: AtomicUsize::new(1),
+    };
+
+    let tensor_id = TensorId::new();
+    registry::insert(tensor_id, tensor_);
+    Tensor::from_id(tensor_id)
+}
+
 pub(crate) fn create_builder_tensor(layout: Layout, dtype: DType, requires_grad: bool) -> (TensorId, Tensor) {
+    // Builder tensors are owned by the active context like runtime ones, so dropping an intermediate
+    // during capture keeps its tape entries and `backward()` can trace through the captured graph
+    let owner_context = if gradient::is_computing_gradients() || gradient::is_in_optimizer_step() {
+        None
+    } else {
+        Some(gradient::get_active_context())
+    };
     let tensor_ = Tensor_ {
         storage: None,
         layout,
@@ -83,7 +118,7 @@ pub(crate) fn create_builder_tensor(layout: Layout, dtype: DType, requires_grad:
         grad_tensor_id: None,
         is_runtime: false,
         is_gradient: false,
-        owner_context: None,
+        owner_context,
         ref_count: AtomicUsize::new(1),
     };
     let tensor_id = TensorId::new();
@@ -92,6 +127,29 @@ pub(crate) fn create_builder_tensor(layout: Layout, dtype: DType, requires_grad:
     (tensor_id, tensor)
 }
 
+pub(crate) fn create_pending_tensor(layout: Layout, dtype: DType) -> Tensor {
+    // Pending (lazy) tensors are runtime tensors whose storage is filled in when they are realized
+    let owner_context = if gradient::is_computing_gradients() || gradient::is_in_optimizer_step() {
+        None
+    } else {
+        Some(gradient::get_active_context())
+    };
+    let tensor_ = Tensor_ {
+        storage: None,
+        layout,
+        dtype: Some(dtype),
+        requires_grad: false,
+        grad_tensor_id: None,
+        is_runtime: true,
+        is_gradient: false,
+        owner_context,
+        ref_count: AtomicUsize::new(1),
+    };
+    let tensor_id = TensorId::new();
+    registry::insert(tensor_id, tensor_);
+    Tensor::from_id(tensor_id)
+}
+
 pub(crate) fn tensor_from_id(tensor_id: TensorId) -> Tensor {
     registry::with_tensor(tensor_id, |t| {
         t.ref_count.fetch_add(1, Ordering::Relaxed);
@@ -0,0 +1,539 @@
+//! Opt-in lazy evaluation with elementwise fusion
+//!
+//! While lazy mode is on for a thread, unary, unary-scalar and binary ops and sum/mean/max/min/prod
+//! reductions return *pending* tensors that only record their expression. A pending tensor is
+//! realized when its data is first needed (any other op, `to_vec`, printing, views) or explicitly
+//! with [`Tensor::realize`].
+//!
+//! Realizing a tensor compiles the pending elementwise chain below it, plus a reduction on top of
+//! it, into a single program. On CPU the program runs in blocks of rows along the leading
+//! dimension, so the intermediates of the chain only ever exist one block at a time; other
+//! devices run it as a single block.
+
+use super::{create_pending_tensor, gradient, Tensor, TensorId};
+use crate::{
+    be::storage::BackendStorage,
+    error::{HoduError, HoduResult},
+    ops::{BinaryOp, ConcatOp, Op, ReduceOp, UnaryOp, UnaryScalarOp},
+    scalar::Scalar,
+    types::{DType, Device, Layout, Shape},
+};
+use dashmap::DashMap;
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+};
+
+/// Elements per block when a fused program runs on CPU
+const BLOCK_ELEMENTS: usize = 1 << 16;
+
+/// Longest pending chain; inputs deeper than this are realized before new ops are recorded
+const MAX_PENDING_DEPTH: usize = 64;
+
+thread_local! {
+    static LAZY: Cell<bool> = const { Cell::new(false) };
+}
+
+// Pending expressions are global so pending tensors can move between threads like any other tensor
+static PENDING: LazyLock<DashMap<TensorId, LazyNode>> = LazyLock::new(DashMap::new);
+
+/// An op whose evaluation can be deferred
+#[derive(Clone)]
+pub(crate) enum LazyOp {
+    Unary(UnaryOp),
+    UnaryScalar(UnaryScalarOp, Scalar),
+    Binary(BinaryOp),
+    Reduce {
+        op: ReduceOp,
+        dims: Vec<usize>,
+        keep_dim: bool,
+    },
+}
+
+impl LazyOp {
+    fn is_elementwise(&self) -> bool {
+        !matches!(self, Self::Reduce { .. })
+    }
+}
+
+struct LazyNode {
+    op: LazyOp,
+    inputs: Vec<Tensor>,
+    device: Device,
+    depth: usize,
+}
+
+/// Whether lazy mode is on for the current thread
+pub fn is_lazy() -> bool {
+    LAZY.with(|lazy| lazy.get())
+}
+
+/// Turn lazy mode on or off for the current thread
+///
+/// Pending tensors created while it was on stay valid and are realized on demand.
+pub fn set_lazy(enabled: bool) {
+    LAZY.with(|lazy| lazy.set(enabled));
+}
+
+/// Run `f` with lazy mode on for the current thread
+pub fn lazy_scope<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_lazy(self.0);
+        }
+    }
+
+    let _restore = Restore(LAZY.with(|lazy| lazy.replace(true)));
+    f()
+}
+
+/// Whether an op producing a result that does (or does not) require grad should be deferred
+///
+/// Ops on the gradient tape always run eagerly.
+pub(crate) fn should_defer(requires_grad: bool) -> bool {
+    !requires_grad && is_lazy() && !crate::snapshot::capture::is_active() && !gradient::is_computing_gradients()
+}
+
+/// Record `op` on `inputs` and return a pending tensor of `shape` and `dtype`
+pub(crate) fn defer(op: LazyOp, inputs: &[&Tensor], shape: Shape, dtype: DType) -> HoduResult<Tensor> {
+    let device = inputs[0].device();
+
+    let mut depth = 0;
+    for input in inputs {
+        let input_depth = PENDING.get(&input.id()).map(|node| node.depth).unwrap_or(0);
+        if input_depth >= MAX_PENDING_DEPTH {
+            input.realize()?;
+        } else {
+            depth = depth.max(input_depth);
+        }
+    }
+
+    let tensor = create_pending_tensor(Layout::from_shape(&shape), dtype);
+    PENDING.insert(
+        tensor.id(),
+        LazyNode {
+            op,
+            inputs: inputs.iter().map(|&input| input.clone()).collect(),
+            device,
+            depth: depth + 1,
+        },
+    );
+    Ok(tensor)
+}
+
+pub(crate) fn is_pending(tensor_id: TensorId) -> bool {
+    PENDING.contains_key(&tensor_id)
+}
+
+pub(crate) fn pending_device(tensor_id: TensorId) -> Option<Device> {
+    PENDING.get(&tensor_id).map(|node| node.device)
+}
+
+/// Drop the pending expression of a tensor that left the registry
+pub(crate) fn forget(tensor_id: TensorId) {
+    // The node's inputs are dropped after the map entry is released
+    let _node = PENDING.remove(&tensor_id);
+}
+
+fn pending_node(tensor_id: TensorId) -> Option<(LazyOp, Vec<Tensor>)> {
+    PENDING
+        .get(&tensor_id)
+        .map(|node| (node.op.clone(), node.inputs.clone()))
+}
+
+impl Tensor {
+    /// Evaluate a pending lazy tensor now; a no-op for tensors that already hold data
+    pub fn realize(&self) -> HoduResult<()> {
+        let Some((op, inputs)) = pending_node(self.id()) else {
+            return Ok(());
+        };
+
+        let storage = Program::compile(self, op, &inputs)?.run(self.device())?;
+
+        super::with_tensor_mut(self.id(), |tensor_ref| {
+            tensor_ref.storage = Some(Arc::new(storage));
+            tensor_ref.dtype = None;
+        })
+        .ok_or(HoduError::TensorNotFound(self.id()))?;
+        forget(self.id());
+
+        Ok(())
+    }
+
+    /// Whether the tensor holds its data (`false` only for pending lazy tensors)
+    pub fn is_realized(&self) -> bool {
+        !is_pending(self.id())
+    }
+}
+
+// ============================================================================
+// Fused programs
+// ============================================================================
+
+/// One value of a fused program; values only read values before them
+enum Value {
+    /// Realized input, broadcast to the program shape
+    Leaf {
+        storage: Arc<BackendStorage>,
+        layout: Layout,
+    },
+    Unary {
+        op: UnaryOp,
+        input: usize,
+    },
+    UnaryScalar {
+        op: UnaryScalarOp,
+        scalar: Scalar,
+        input: usize,
+    },
+    Binary {
+        op: BinaryOp,
+        lhs: usize,
+        rhs: usize,
+    },
+}
+
+impl Value {
+    fn inputs(&self) -> Vec<usize> {
+        match self {
+            Self::Leaf { .. } => vec![],
+            Self::Unary { input, .. } | Self::UnaryScalar { input, .. } => vec![*input],
+            Self::Binary { lhs, rhs, .. } => vec![*lhs, *rhs],
+        }
+    }
+}
+
+/// Elementwise chain of one shape, optionally followed by a reduction; the last value is the root
+struct Program {
+    shape: Shape,
+    values: Vec<Value>,
+    reduce: Option<(ReduceOp, Vec<usize>, bool)>,
+    memo: HashMap<TensorId, usize>,
+}
+
+impl Program {
+    fn compile(target: &Tensor, op: LazyOp, inputs: &[Tensor]) -> HoduResult<Self> {
+        match op {
+            LazyOp::Reduce { op, dims, keep_dim } => {
+                let mut program = Self::new(inputs[0].shape());
+                program.value(&inputs[0])?;
+                program.reduce = Some((op, dims, keep_dim));
+                Ok(program)
+            },
+            op => {
+                let mut program = Self::new(target.shape());
+                program.node(op, inputs)?;
+                Ok(program)
+            },
+        }
+    }
+
+    fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            values: Vec::new(),
+            reduce: None,
+            memo: HashMap::new(),
+        }
+    }
+
+    /// Inline a pending elementwise tensor of the program shape, or load anything else as a leaf
+    fn value(&mut self, tensor: &Tensor) -> HoduResult<usize> {
+        if let Some(&index) = self.memo.get(&tensor.id()) {
+            return Ok(index);
+        }
+
+        let inlined = pending_node(tensor.id()).filter(|(op, _)| op.is_elementwise() && tensor.shape() == self.shape);
+        let index = match inlined {
+            Some((op, inputs)) => self.node(op, &inputs)?,
+            None => {
+                tensor.realize()?;
+                let storage = super::with_tensor(tensor.id(), |tensor_ref| tensor_ref.storage.clone())
+                    .flatten()
+                    .ok_or(HoduError::StorageNotFound(tensor.id()))?;
+                let layout = tensor.layout().broadcast_to(&self.shape)?;
+                self.push(Value::Leaf { storage, layout })
+            },
+        };
+
+        self.memo.insert(tensor.id(), index);
+        Ok(index)
+    }
+
+    fn node(&mut self, op: LazyOp, inputs: &[Tensor]) -> HoduResult<usize> {
+        let value = match op {
+            LazyOp::Unary(op) => Value::Unary {
+                op,
+                input: self.value(&inputs[0])?,
+            },
+            LazyOp::UnaryScalar(op, scalar) => Value::UnaryScalar {
+                op,
+                scalar,
+                input: self.value(&inputs[0])?,
+            },
+            LazyOp::Binary(op) => {
+                let lhs = self.value(&inputs[0])?;
+                let rhs = self.value(&inputs[1])?;
+                Value::Binary { op, lhs, rhs }
+            },
+            LazyOp::Reduce { .. } => {
+                return Err(HoduError::InternalError(
+                    "reductions cannot be inlined into a fused program".to_string(),
+                ))
+            },
+        };
+        Ok(self.push(value))
+    }
+
+    fn push(&mut self, value: Value) -> usize {
+        self.values.push(value);
+        self.values.len() - 1
+    }
+
+    fn run(&self, device: Device) -> HoduResult<BackendStorage> {
+        let dims = self.shape.dims();
+        let rows = dims.first().copied().unwrap_or(1);
+        let size = self.shape.size();
+        let block_rows = if device == Device::CPU && size > 0 && !dims.is_empty() {
+            (BLOCK_ELEMENTS / (size / rows)).clamp(1, rows)
+        } else {
+            rows
+        };
+
+        if block_rows >= rows {
+            let (storage, layout) = self.run_block(None)?;
+            return match &self.reduce {
+                Some((op, dims, keep_dim)) => storage.call_ops_reduce(&layout, dims, *keep_dim, Op::Reduce(*op)),
+                None => Arc::try_unwrap(storage)
+                    .map_err(|_| HoduError::InternalError("fused program root is shared".to_string())),
+            };
+        }
+
+        let blocks: Vec<(usize, usize)> = (0..rows)
+            .step_by(block_rows)
+            .map(|start| (start, (start + block_rows).min(rows)))
+            .collect();
+
+        let Some((op, dims, _)) = &self.reduce else {
+            let mut parts = Vec::with_capacity(blocks.len());
+            for &block in &blocks {
+                let (storage, layout) = self.run_block(Some(block))?;
+                parts.push((storage, layout));
+            }
+            return concat_rows(&parts);
+        };
+
+        // Blocks reduce with kept dims, so partial results line up with the final output
+        let reduces_rows = dims.contains(&0);
+        let block_op = match op {
+            ReduceOp::Mean if reduces_rows => ReduceOp::Sum,
+            op => *op,
+        };
+
+        let mut partials = Vec::with_capacity(blocks.len());
+        for &block in &blocks {
+            let (storage, layout) = self.run_block(Some(block))?;
+            let partial = storage.call_ops_reduce(&layout, dims, true, Op::Reduce(block_op))?;
+            let partial_layout = Layout::from_shape(&reduced_shape(layout.shape(), dims));
+            partials.push((Arc::new(partial), partial_layout));
+        }
+
+        if !reduces_rows {
+            return concat_rows(&partials);
+        }
+
+        let combine = match op {
+            ReduceOp::Sum | ReduceOp::Mean => BinaryOp::Add,
+            ReduceOp::Max => BinaryOp::Maximum,
+            ReduceOp::Min => BinaryOp::Minimum,
+            ReduceOp::Prod => BinaryOp::Mul,
+            op => {
+                return Err(HoduError::InternalError(format!(
+                    "{} cannot be fused across blocks",
+                    op
+                )))
+            },
+        };
+
+        let mut partials = partials.into_iter();
+        let (first, layout) = partials.next().expect("at least two blocks");
+        let mut result =
+            Arc::try_unwrap(first).map_err(|_| HoduError::InternalError("partial reduction is shared".to_string()))?;
+        for (partial, _) in partials {
+            result = result.call_ops_binary(&partial, &layout, &layout, Op::Binary(combine))?;
+        }
+
+        if *op == ReduceOp::Mean {
+            let count: usize = dims.iter().map(|&dim| self.shape.dims()[dim]).product();
+            let dtype = result.dtype();
+            result = result.call_ops_unary_scalar(
+                &layout,
+                Scalar::from_usize(count, dtype),
+                Op::UnaryScalar(UnaryScalarOp::DivScalar),
+            )?;
+        }
+
+        Ok(result)
+    }
+
+    /// Evaluate the elementwise chain on rows `start..end` (or everything), freeing values after their last use
+    fn run_block(&self, rows: Option<(usize, usize)>) -> HoduResult<(Arc<BackendStorage>, Layout)> {
+        let mut last_use = vec![0; self.values.len()];
+        for (index, value) in self.values.iter().enumerate() {
+            for input in value.inputs() {
+                last_use[input] = index;
+            }
+        }
+
+        let mut slots: Vec<Option<(Arc<BackendStorage>, Layout)>> = vec![None; self.values.len()];
+        for (index, value) in self.values.iter().enumerate() {
+            let slot = |input: usize| slots[input].as_ref().expect("value is computed before its uses");
+            let computed = match value {
+                Value::Leaf { storage, layout } => {
+                    let layout = match rows {
+                        Some((start, end)) => layout.slice(0, start as i32, Some(end as i32), 1)?,
+                        None => layout.clone(),
+                    };
+                    (Arc::clone(storage), layout)
+                },
+                Value::Unary { op, input } => {
+                    let (storage, layout) = slot(*input);
+                    let result = storage.call_ops_unary(layout, Op::Unary(*op))?;
+                    (Arc::new(result), Layout::from_shape(layout.shape()))
+                },
+                Value::UnaryScalar { op, scalar, input } => {
+                    let (storage, layout) = slot(*input);
+                    let result = storage.call_ops_unary_scalar(layout, *scalar, Op::UnaryScalar(*op))?;
+                    (Arc::new(result), Layout::from_shape(layout.shape()))
+                },
+                Value::Binary { op, lhs, rhs } => {
+                    let (lhs_storage, lhs_layout) = slot(*lhs);
+                    let (rhs_storage, rhs_layout) = slot(*rhs);
+                    let result = lhs_storage.call_ops_binary(rhs_storage, lhs_layout, rhs_layout, Op::Binary(*op))?;
+                    (Arc::new(result), Layout::from_shape(lhs_layout.shape()))
+                },
+            };
+
+            for input in value.inputs() {
+                if last_use[input] == index {
+                    slots[input] = None;
+                }
+            }
+            slots[index] = Some(computed);
+        }
+
+        slots
+            .pop()
+            .flatten()
+            .ok_or_else(|| HoduError::InternalError("empty fused program".to_string()))
+    }
+}
+
+fn reduced_shape(shape: &Shape, dims: &[usize]) -> Shape {
+    let dims: Vec<usize> = shape
+        .dims()
+        .iter()
+        .enumerate()
+        .map(|(dim, &size)| if dims.contains(&dim) { 1 } else { size })
+        .collect();
+    Shape::from(dims)
+}
+
+fn concat_rows(parts: &[(Arc<BackendStorage>, Layout)]) -> HoduResult<BackendStorage> {
+    let (first, _) = &parts[0];
+    let others: Vec<&BackendStorage> = parts[1..].iter().map(|(storage, _)| storage.as_ref()).collect();
+    let layouts: Vec<&Layout> = parts.iter().map(|(_, layout)| layout).collect();
+    first.call_ops_concat(&others, &layouts, 0, Op::Concat(ConcatOp::Concat))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(rows: usize, cols: usize) -> Tensor {
+        let data: Vec<f32> = (0..rows * cols).map(|i| ((i % 97) as f32 - 48.0) / 32.0).collect();
+        Tensor::from_slice(data, [rows, cols]).unwrap()
+    }
+
+    fn assert_close(lhs: &Tensor, rhs: &Tensor) {
+        assert_eq!(lhs.shape(), rhs.shape());
+        let lhs = lhs.to_flatten_vec::<f32>().unwrap();
+        let rhs = rhs.to_flatten_vec::<f32>().unwrap();
+        for (l, r) in lhs.iter().zip(&rhs) {
+            assert!((l - r).abs() <= 1e-3 * r.abs().max(1.0), "{} != {}", l, r);
+        }
+    }
+
+    #[test]
+    fn test_lazy_pending_until_realized() {
+        let x = ramp(4, 8);
+        let y = lazy_scope(|| x.mul_scalar(2.0f32).unwrap().exp().unwrap());
+        assert!(!y.is_realized());
+        assert_eq!(y.shape().dims(), &[4, 8]);
+
+        y.realize().unwrap();
+        assert!(y.is_realized());
+        assert_close(&y, &x.mul_scalar(2.0f32).unwrap().exp().unwrap());
+    }
+
+    #[test]
+    fn test_lazy_fused_chain_matches_eager() {
+        // 512 x 256 runs as two blocks on CPU
+        let x = ramp(512, 256);
+        let bias = ramp(1, 256);
+        let eager = x
+            .mul_scalar(0.5f32)
+            .unwrap()
+            .add(&bias)
+            .unwrap()
+            .tanh()
+            .unwrap()
+            .mul(&x)
+            .unwrap();
+
+        let lazy = lazy_scope(|| {
+            let h = x.mul_scalar(0.5f32).unwrap().add(&bias).unwrap();
+            h.tanh().unwrap().mul(&x).unwrap()
+        });
+        assert!(!lazy.is_realized());
+        assert_close(&lazy, &eager);
+    }
+
+    #[test]
+    fn test_lazy_fused_reductions_match_eager() {
+        let x = ramp(512, 256);
+        let eager = x.square().unwrap().add_scalar(1.0f32).unwrap();
+
+        for (dims, keep_dim) in [(vec![0], false), (vec![1], true), (vec![0, 1], false)] {
+            for op in [ReduceOp::Sum, ReduceOp::Mean, ReduceOp::Max, ReduceOp::Min] {
+                let reduce = |t: &Tensor| match op {
+                    ReduceOp::Sum => t.sum(&dims, keep_dim),
+                    ReduceOp::Mean => t.mean(&dims, keep_dim),
+                    ReduceOp::Max => t.max(&dims, keep_dim),
+                    _ => t.min(&dims, keep_dim),
+                };
+                let expected = reduce(&eager).unwrap();
+                let lazy = lazy_scope(|| reduce(&x.square().unwrap().add_scalar(1.0f32).unwrap()).unwrap());
+                assert!(!lazy.is_realized());
+                assert_close(&lazy, &expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_broadcast_of_pending_input() {
+        let x = ramp(8, 16);
+        let eager = x.add(&x.sum(&[1], true).unwrap()).unwrap();
+        let lazy = lazy_scope(|| x.add(&x.sum(&[1], true).unwrap()).unwrap());
+        assert_close(&lazy, &eager);
+
+        // Views realize the tensor they look at
+        let lazy = lazy_scope(|| x.neg().unwrap());
+        assert_close(
+            &lazy.transpose(0, 1).unwrap(),
+            &x.neg().unwrap().transpose(0, 1).unwrap(),
+        );
+    }
+}
@@ -10,6 +10,7 @@ mod linalg;
 mod matrix;
 mod normalization;
 mod padding;
+mod quant;
 mod reduce;
 mod resize;
 mod scan;
@@ -1,8 +1,10 @@
 use crate::{
-    error::HoduResult,
+    error::{HoduError, HoduResult},
     ops::{BinaryLogicalOp, BinaryLogicalParams, BinaryOp, BinaryParams, Op, OpParams},
-    tensor::{create_builder_tensor, from_storage_with_context, gradient, utils::broadcast_tensors2, Tensor},
-    types::DType,
+    tensor::{
+        create_builder_tensor, from_storage_with_context, gradient, lazy, utils::broadcast_tensors2, LazyOp, Tensor,
+    },
+    types::{DType, Shape},
     utils::valid::{
         validate_dtype_for_device, validate_dtype_for_op, validate_requires_grad_for_op, validate_same_device,
         validate_same_dtype,
@@ -18,6 +20,19 @@ macro_rules! binary_op {
             validate_dtype_for_op(self.dtype(), Op::Binary(BinaryOp::$op_name))?;
             let validate_requires_grad = validate_requires_grad_for_op(Op::Binary(BinaryOp::$op_name));
 
+            // Pending operands are broadcast when the fused program runs, not through views
+            if lazy::should_defer((self.is_requires_grad() || rhs.is_requires_grad()) && validate_requires_grad) {
+                let shape = Shape::broadcast_shape(&self.shape(), &rhs.shape()).ok_or_else(|| {
+                    HoduError::incompatible_shapes(self.shape(), rhs.shape(), Op::Binary(BinaryOp::$op_name))
+                })?;
+                return lazy::defer(
+                    LazyOp::Binary(BinaryOp::$op_name),
+                    &[self, rhs],
+                    shape,
+                    self.dtype(),
+                );
+            }
+
             let (lhs, rhs) = broadcast_tensors2(self, rhs)?;
 
             if crate::snapshot::capture::is_active() {
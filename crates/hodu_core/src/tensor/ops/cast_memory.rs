@@ -86,6 +86,8 @@ impl Tensor {
         // Replace storage of self with src's storage, preserving TensorId and gradient
         use crate::tensor::{with_tensor, with_tensor_mut};
 
+        src.realize()?;
+
         // Get src's storage (clone the Arc)
         let src_storage = with_tensor(src.id(), |t| t.storage.clone()).ok_or(HoduError::TensorNotFound(src.id()))?;
 
@@ -0,0 +1,401 @@
+use crate::{
+    error::{HoduError, HoduResult},
+    ops::{
+        BlockDequantizeParams, BlockMatmulParams, BlockQuantizeParams, DequantizeParams, MatmulInt8Params, Op,
+        OpParams, QuantOp, QuantizeParams,
+    },
+    tensor::{create_builder_tensor, from_storage_with_context, Tensor},
+    types::{BlockFormat, DType, Layout, Shape},
+    utils::valid::{validate_dtype_for_device, validate_dtype_for_op, validate_same_device},
+};
+
+impl Tensor {
+    /// Quantize a floating-point tensor to int8.
+    ///
+    /// Computes `q = clamp(round(x / scale) + zero_point, -128, 127)`, rounding half to even.
+    ///
+    /// # Input
+    /// - Floating-point tensor (BF16, F16, F32 or F64)
+    ///
+    /// # Output
+    /// - I8 tensor with the same shape
+    ///
+    /// # Example
+    /// ```ignore
+    /// let x = Tensor::from_slice(&[-1.0f32, 0.0, 0.5, 1.0], &[4])?;
+    /// let q = x.quantize(QuantizeParams::symmetric(1.0))?; // [-127, 0, 64, 127]
+    /// ```
+    pub fn quantize(&self, params: QuantizeParams) -> HoduResult<Self> {
+        let op = Op::Quant(QuantOp::Quantize);
+
+        validate_dtype_for_device(self.dtype(), self.device())?;
+        validate_dtype_for_device(DType::I8, self.device())?;
+        validate_dtype_for_op(self.dtype(), op.clone())?;
+        validate_quantize_params(params.scale, params.zero_point)?;
+
+        let self_layout = self.layout();
+        let result_layout = Layout::from_shape(&self.shape());
+
+        if crate::snapshot::capture::is_active() {
+            let (result_id, result_tensor) = create_builder_tensor(result_layout.clone(), DType::I8, false);
+
+            crate::snapshot::capture::capture_operation(
+                op,
+                Some(OpParams::Quantize(params)),
+                vec![self.id()],
+                result_id,
+                vec![self_layout],
+                result_layout,
+            )?;
+
+            Ok(result_tensor)
+        } else {
+            let storage =
+                self.with_storage(|storage| storage.call_ops_quantize(&self_layout, params.scale, params.zero_point))?;
+
+            Ok(from_storage_with_context(storage, result_layout, true, false))
+        }
+    }
+
+    /// Dequantize an int8 tensor to a floating-point dtype.
+    ///
+    /// Computes `x = (q - zero_point) * scale`.
+    ///
+    /// # Input
+    /// - I8 tensor
+    ///
+    /// # Output
+    /// - Tensor of `dtype` (BF16, F16, F32 or F64) with the same shape
+    ///
+    /// # Example
+    /// ```ignore
+    /// let params = QuantizeParams::symmetric(1.0);
+    /// let x = q.dequantize(params, DType::F32)?;
+    /// ```
+    pub fn dequantize(&self, params: QuantizeParams, dtype: DType) -> HoduResult<Self> {
+        let op = Op::Quant(QuantOp::Dequantize);
+
+        validate_dtype_for_device(self.dtype(), self.device())?;
+        validate_dtype_for_device(dtype, self.device())?;
+        validate_dtype_for_op(self.dtype(), op.clone())?;
+        // The output dtype follows the same rule as the quantize input
+        validate_dtype_for_op(dtype, Op::Quant(QuantOp::Quantize))
+            .map_err(|_| HoduError::UnsupportedDTypeForOp { dtype, op: op.clone() })?;
+        validate_quantize_params(params.scale, params.zero_point)?;
+
+        let self_layout = self.layout();
+        let result_layout = Layout::from_shape(&self.shape());
+
+        if crate::snapshot::capture::is_active() {
+            let (result_id, result_tensor) = create_builder_tensor(result_layout.clone(), dtype, false);
+
+            crate::snapshot::capture::capture_operation(
+                op,
+                Some(OpParams::Dequantize(DequantizeParams {
+                    scale: params.scale,
+                    zero_point: params.zero_point,
+                    dtype,
+                })),
+                vec![self.id()],
+                result_id,
+                vec![self_layout],
+                result_layout,
+            )?;
+
+            Ok(result_tensor)
+        } else {
+            let storage = self.with_storage(|storage| {
+                storage.call_ops_dequantize(&self_layout, params.scale, params.zero_point, dtype)
+            })?;
+
+            Ok(from_storage_with_context(storage, result_layout, true, false))
+        }
+    }
+
+    /// Int8 matrix multiplication with int32 accumulation.
+    ///
+    /// Batch dimensions broadcast like [`Tensor::matmul`]. Both operands must be at least 2D.
+    ///
+    /// # Input
+    /// - `self`: I8 tensor `[..., M, K]`
+    /// - `other`: I8 tensor `[..., K, N]`
+    ///
+    /// # Output
+    /// - I32 tensor `[..., M, N]`
+    ///
+    /// # Example
+    /// ```ignore
+    /// let a = x.quantize(QuantizeParams::symmetric(x_max))?;
+    /// let b = w.quantize(QuantizeParams::symmetric(w_max))?;
+    /// let acc = a.matmul_int8(&b)?; // multiply by x_scale * w_scale to dequantize
+    /// ```
+    pub fn matmul_int8(&self, other: &Self) -> HoduResult<Self> {
+        let op = Op::Quant(QuantOp::MatmulInt8);
+
+        validate_same_device(&[self, other], op.clone())?;
+        validate_dtype_for_device(DType::I32, self.device())?;
+        validate_dtype_for_op(self.dtype(), op.clone())?;
+        validate_dtype_for_op(other.dtype(), op.clone())?;
+
+        let lhs_shape = self.shape();
+        let rhs_shape = other.shape();
+        let lhs_dims = lhs_shape.dims();
+        let rhs_dims = rhs_shape.dims();
+        let lhs_ndim = lhs_dims.len();
+        let rhs_ndim = rhs_dims.len();
+
+        if lhs_ndim < 2 || rhs_ndim < 2 || lhs_dims[lhs_ndim - 1] != rhs_dims[rhs_ndim - 2] {
+            return Err(HoduError::incompatible_shapes(lhs_shape, rhs_shape, op));
+        }
+
+        // Broadcast both operands to the common batch dimensions
+        let lhs_batch_dims = &lhs_dims[..lhs_ndim - 2];
+        let rhs_batch_dims = &rhs_dims[..rhs_ndim - 2];
+        let batch_ndim = lhs_batch_dims.len().max(rhs_batch_dims.len());
+        let mut batch_dims = vec![0; batch_ndim];
+
+        for i in 0..batch_ndim {
+            let lhs_dim = lhs_batch_dims.len().checked_sub(1 + i).map_or(1, |d| lhs_batch_dims[d]);
+            let rhs_dim = rhs_batch_dims.len().checked_sub(1 + i).map_or(1, |d| rhs_batch_dims[d]);
+
+            if lhs_dim != 1 && rhs_dim != 1 && lhs_dim != rhs_dim {
+                return Err(HoduError::incompatible_shapes(lhs_shape, rhs_shape, op));
+            }
+            batch_dims[batch_ndim - 1 - i] = lhs_dim.max(rhs_dim);
+        }
+
+        let mut lhs_broadcast_dims = batch_dims.clone();
+        lhs_broadcast_dims.extend_from_slice(&lhs_dims[lhs_ndim - 2..]);
+        let mut rhs_broadcast_dims = batch_dims.clone();
+        rhs_broadcast_dims.extend_from_slice(&rhs_dims[rhs_ndim - 2..]);
+
+        let lhs_broadcasted = self.broadcast(Shape::from(lhs_broadcast_dims))?;
+        let rhs_broadcasted = other.broadcast(Shape::from(rhs_broadcast_dims))?;
+
+        let mut result_dims = batch_dims;
+        result_dims.push(lhs_dims[lhs_ndim - 2]);
+        result_dims.push(rhs_dims[rhs_ndim - 1]);
+
+        let result_layout = Layout::from_shape(&Shape::from(result_dims));
+        let lhs_layout = lhs_broadcasted.layout();
+        let rhs_layout = rhs_broadcasted.layout();
+
+        if crate::snapshot::capture::is_active() {
+            let (result_id, result_tensor) = create_builder_tensor(result_layout.clone(), DType::I32, false);
+
+            crate::snapshot::capture::capture_operation(
+                op,
+                Some(OpParams::MatmulInt8(MatmulInt8Params)),
+                vec![lhs_broadcasted.id(), rhs_broadcasted.id()],
+                result_id,
+                vec![lhs_layout, rhs_layout],
+                result_layout,
+            )?;
+
+            Ok(result_tensor)
+        } else {
+            let storage = lhs_broadcasted.with_storage(|lhs_storage| {
+                rhs_broadcasted
+                    .with_storage(|rhs_storage| lhs_storage.call_ops_matmul_int8(rhs_storage, &lhs_layout, &rhs_layout))
+            })?;
+
+            Ok(from_storage_with_context(storage, result_layout, true, false))
+        }
+    }
+
+    /// Pack a floating-point tensor into quantized blocks along its last dimension.
+    ///
+    /// Weights stored this way take `block_bytes / block_size` bytes per value (0.5625 for
+    /// [`BlockFormat::Q4_0`]) and are consumed by [`Tensor::block_matmul`].
+    ///
+    /// # Input
+    /// - Floating-point tensor `[..., K]` (BF16, F16, F32 or F64), `K` a multiple of the block size
+    ///
+    /// # Output
+    /// - U8 tensor `[..., K / block_size * block_bytes]`
+    ///
+    /// # Example
+    /// ```ignore
+    /// let w = Tensor::randn(&[4096, 4096], 0.0, 0.02)?;
+    /// let w_q4 = w.block_quantize(BlockFormat::Q4_0)?; // [4096, 2304] u8
+    /// ```
+    pub fn block_quantize(&self, format: BlockFormat) -> HoduResult<Self> {
+        let op = Op::Quant(QuantOp::BlockQuantize);
+
+        validate_dtype_for_device(self.dtype(), self.device())?;
+        validate_dtype_for_op(self.dtype(), op.clone())?;
+
+        let shape = self.shape();
+        let mut dims = shape.dims().to_vec();
+        let Some(last) = dims.last_mut() else {
+            return Err(HoduError::InvalidArgument(format!(
+                "{} needs at least a 1D tensor",
+                format
+            )));
+        };
+        *last = format.packed_dim(*last)?;
+
+        let self_layout = self.layout();
+        let result_layout = Layout::from_shape(&Shape::from(dims));
+
+        if crate::snapshot::capture::is_active() {
+            let (result_id, result_tensor) = create_builder_tensor(result_layout.clone(), DType::U8, false);
+
+            crate::snapshot::capture::capture_operation(
+                op,
+                Some(OpParams::BlockQuantize(BlockQuantizeParams { format })),
+                vec![self.id()],
+                result_id,
+                vec![self_layout],
+                result_layout,
+            )?;
+
+            Ok(result_tensor)
+        } else {
+            let storage = self.with_storage(|storage| storage.call_ops_block_quantize(&self_layout, format))?;
+
+            Ok(from_storage_with_context(storage, result_layout, true, false))
+        }
+    }
+
+    /// Unpack a block-quantized U8 tensor to a floating-point dtype.
+    ///
+    /// # Input
+    /// - U8 tensor `[..., P]` produced by [`Tensor::block_quantize`] with the same `format`
+    ///
+    /// # Output
+    /// - Tensor of `dtype` (BF16, F16, F32 or F64) `[..., P / block_bytes * block_size]`
+    pub fn block_dequantize(&self, format: BlockFormat, dtype: DType) -> HoduResult<Self> {
+        let op = Op::Quant(QuantOp::BlockDequantize);
+
+        validate_dtype_for_device(dtype, self.device())?;
+        validate_dtype_for_op(self.dtype(), op.clone())?;
+        // The output dtype follows the same rule as the block quantize input
+        validate_dtype_for_op(dtype, Op::Quant(QuantOp::BlockQuantize))
+            .map_err(|_| HoduError::UnsupportedDTypeForOp { dtype, op: op.clone() })?;
+
+        let shape = self.shape();
+        let mut dims = shape.dims().to_vec();
+        let Some(last) = dims.last_mut() else {
+            return Err(HoduError::InvalidArgument(format!(
+                "packed {} data needs at least a 1D tensor",
+                format
+            )));
+        };
+        *last = format.unpacked_dim(*last)?;
+
+        let packed = self.contiguous()?;
+        let packed_layout = packed.layout();
+        let result_layout = Layout::from_shape(&Shape::from(dims));
+
+        if crate::snapshot::capture::is_active() {
+            let (result_id, result_tensor) = create_builder_tensor(result_layout.clone(), dtype, false);
+
+            crate::snapshot::capture::capture_operation(
+                op,
+                Some(OpParams::BlockDequantize(BlockDequantizeParams { format, dtype })),
+                vec![packed.id()],
+                result_id,
+                vec![packed_layout],
+                result_layout,
+            )?;
+
+            Ok(result_tensor)
+        } else {
+            let storage =
+                packed.with_storage(|storage| storage.call_ops_block_dequantize(&packed_layout, format, dtype))?;
+
+            Ok(from_storage_with_context(storage, result_layout, true, false))
+        }
+    }
+
+    /// Multiply by a block-quantized weight, dequantizing it on the fly.
+    ///
+    /// Computes `self @ dequant(weight)^T`, the layout of a linear layer with an `[out, in]` weight.
+    /// The weight is never materialized in floating point.
+    ///
+    /// # Input
+    /// - `self`: Floating-point tensor `[..., K]` (BF16, F16, F32 or F64)
+    /// - `weight`: U8 tensor `[N, K / block_size * block_bytes]` from [`Tensor::block_quantize`]
+    ///
+    /// # Output
+    /// - Tensor `[..., N]` of the `self` dtype
+    ///
+    /// # Example
+    /// ```ignore
+    /// let w_q4 = w.block_quantize(BlockFormat::Q4_0)?; // w: [N, K]
+    /// let y = x.block_matmul(&w_q4, BlockFormat::Q4_0)?; // x: [B, T, K] -> y: [B, T, N]
+    /// ```
+    pub fn block_matmul(&self, weight: &Self, format: BlockFormat) -> HoduResult<Self> {
+        let op = Op::Quant(QuantOp::BlockMatmul);
+
+        validate_same_device(&[self, weight], op.clone())?;
+        validate_dtype_for_device(self.dtype(), self.device())?;
+        validate_dtype_for_op(self.dtype(), op.clone())?;
+        if weight.dtype() != DType::U8 {
+            return Err(HoduError::UnsupportedDTypeForOp {
+                dtype: weight.dtype(),
+                op,
+            });
+        }
+
+        let lhs_shape = self.shape();
+        let weight_shape = weight.shape();
+        let lhs_dims = lhs_shape.dims();
+        let weight_dims = weight_shape.dims();
+        if lhs_dims.is_empty()
+            || weight_dims.len() != 2
+            || format.unpacked_dim(weight_dims[1])? != lhs_dims[lhs_dims.len() - 1]
+        {
+            return Err(HoduError::incompatible_shapes(lhs_shape, weight_shape, op));
+        }
+
+        let mut result_dims = lhs_dims.to_vec();
+        *result_dims.last_mut().unwrap() = weight_dims[0];
+
+        let lhs = self.contiguous()?;
+        let weight = weight.contiguous()?;
+        let lhs_layout = lhs.layout();
+        let weight_layout = weight.layout();
+        let result_layout = Layout::from_shape(&Shape::from(result_dims));
+
+        if crate::snapshot::capture::is_active() {
+            let (result_id, result_tensor) = create_builder_tensor(result_layout.clone(), self.dtype(), false);
+
+            crate::snapshot::capture::capture_operation(
+                op,
+                Some(OpParams::BlockMatmul(BlockMatmulParams { format })),
+                vec![lhs.id(), weight.id()],
+                result_id,
+                vec![lhs_layout, weight_layout],
+                result_layout,
+            )?;
+
+            Ok(result_tensor)
+        } else {
+            let storage = lhs.with_storage(|lhs_storage| {
+                weight.with_storage(|weight_storage| {
+                    lhs_storage.call_ops_block_matmul(weight_storage, &lhs_layout, &weight_layout, format)
+                })
+            })?;
+
+            Ok(from_storage_with_context(storage, result_layout, true, false))
+        }
+    }
+}
+
+fn validate_quantize_params(scale: f32, zero_point: i32) -> HoduResult<()> {
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(HoduError::InvalidArgument(format!(
+            "quantization scale must be positive and finite, got {}",
+            scale
+        )));
+    }
+    if !(-128..=127).contains(&zero_point) {
+        return Err(HoduError::InvalidArgument(format!(
+            "int8 zero_point must be in [-128, 127], got {}",
+            zero_point
+        )));
+    }
+    Ok(())
+}
@@ -2,7 +2,7 @@ use crate::{
     error::HoduResult,
     ops::{Op, OpParams, ReduceOp, ReduceParams},
     scalar::Scalar,
-    tensor::{create_builder_tensor, from_storage_with_context, gradient, Tensor},
+    tensor::{create_builder_tensor, from_storage_with_context, gradient, lazy, LazyOp, Tensor},
     types::{DType, Layout, Shape},
     utils::valid::{validate_dtype_for_device, validate_dtype_for_op, validate_requires_grad_for_op},
 };
@@ -26,27 +26,20 @@ impl Tensor {
             return Ok(self.clone());
         }
 
-        let mut result = self.clone();
-
-        // If current shape has more dimensions than target, reduce leading dimensions
-        if current_dims.len() > target_dims.len() {
-            let dims_to_sum: Vec<usize> = (0..(current_dims.len() - target_dims.len())).collect();
-            for &dim in dims_to_sum.iter().rev() {
-                result = result.sum(&[dim], false)?;
-            }
-        }
-
-        let result_shape = result.shape();
-        let result_dims = result_shape.dims();
-
-        // Reduce dimensions where target is 1 but current is > 1
-        for (i, (&target_dim, &current_dim)) in target_dims.iter().zip(result_dims.iter()).enumerate() {
+        // Leading dims the target does not have, then dims the target has as size 1
+        let leading = current_dims.len().saturating_sub(target_dims.len());
+        let mut dims_to_sum: Vec<usize> = (0..leading).collect();
+        for (i, (&target_dim, &current_dim)) in target_dims.iter().zip(&current_dims[leading..]).enumerate() {
             if target_dim == 1 && current_dim > 1 {
-                result = result.sum(&[i], true)?;
+                dims_to_sum.push(leading + i);
             }
         }
 
-        Ok(result)
+        if dims_to_sum.is_empty() {
+            return self.reshape(target_shape);
+        }
+        // One reduction over all of them; keeping dims lets the reshape drop the leading ones
+        self.sum(&dims_to_sum, true)?.reshape(target_shape)
     }
 
     pub fn mean<D: Into<Scalar> + Copy>(&self, dims: &[D], keep_dim: bool) -> HoduResult<Self> {
@@ -206,6 +199,17 @@ impl Tensor {
             }
 
             Ok(result_tensor)
+        } else if matches!(
+            reduce_op,
+            ReduceOp::Sum | ReduceOp::Mean | ReduceOp::Max | ReduceOp::Min | ReduceOp::Prod
+        ) && lazy::should_defer(self.is_requires_grad() && validate_requires_grad)
+        {
+            let op = LazyOp::Reduce {
+                op: reduce_op,
+                dims: reduce_dims,
+                keep_dim,
+            };
+            lazy::defer(op, &[self], result_layout.shape().clone(), self.dtype())
         } else {
             let storage = self.with_storage(|storage| {
                 storage.call_ops_reduce(&self.layout(), &reduce_dims, keep_dim, Op::Reduce(reduce_op))
@@ -2,7 +2,7 @@ use crate::{
     error::HoduResult,
     ops::{Op, OpParams, UnaryLogicalOp, UnaryLogicalParams, UnaryOp, UnaryParams, UnaryScalarOp, UnaryScalarParams},
     scalar::Scalar,
-    tensor::{create_builder_tensor, from_storage_with_context, gradient, Tensor},
+    tensor::{create_builder_tensor, from_storage_with_context, gradient, lazy, LazyOp, Tensor},
     types::{DType, Layout},
     utils::valid::{validate_dtype_for_device, validate_dtype_for_op, validate_requires_grad_for_op},
 };
@@ -40,6 +40,13 @@ macro_rules! unary_op {
                 }
 
                 Ok(result_tensor)
+            } else if lazy::should_defer(self.is_requires_grad() && validate_requires_grad) {
+                lazy::defer(
+                    LazyOp::Unary(UnaryOp::$op_name),
+                    &[self],
+                    self.shape(),
+                    self.dtype(),
+                )
             } else {
                 let storage =
                     self.with_storage(|storage| storage.call_ops_unary(&input_layout, Op::Unary(UnaryOp::$op_name)))?;
@@ -134,6 +141,13 @@ macro_rules! unary_scalar_op {
                 }
 
                 Ok(result_tensor)
+            } else if lazy::should_defer(self.is_requires_grad() && validate_requires_grad) {
+                lazy::defer(
+                    LazyOp::UnaryScalar(UnaryScalarOp::$op_name, scalar_value),
+                    &[self],
+                    self.shape(),
+                    self.dtype(),
+                )
             } else {
                 let storage = self.with_storage(|storage| {
                     storage.call_ops_unary_scalar(
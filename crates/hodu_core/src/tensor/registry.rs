@@ -4,8 +4,13 @@ use std::sync::atomic::Ordering;
 use std::sync::LazyLock;
 
 static TENSORS: LazyLock<DashMap<TensorId, Tensor_>> = LazyLock::new(|| {
+    #[cfg(feature = "std")]
     let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(64);
-    let shard_count = cores.next_power_of_two();
+    // Without OS threads there is no contention to spread across shards
+    #[cfg(not(feature = "std"))]
+    let cores = 1usize;
+    // DashMap needs at least two shards, which a single core would otherwise not get
+    let shard_count = cores.next_power_of_two().max(2);
     DashMap::with_capacity_and_shard_amount(1 << 16, shard_count)
 });
 
@@ -24,6 +29,7 @@ pub(crate) fn remove(tensor_id: TensorId) {
         });
     }
     TENSORS.remove(&tensor_id);
+    super::lazy::forget(tensor_id);
 }
 
 pub fn get(tensor_id: TensorId) -> Option<dashmap::mapref::one::Ref<'static, TensorId, Tensor_>> {
@@ -1,4 +1,6 @@
+mod block_format;
 mod compiler;
+mod cpu_alloc;
 mod device;
 mod dim;
 mod dtype;
@@ -6,18 +8,22 @@ mod dynamic_registry;
 #[cfg(feature = "serde")]
 mod format;
 mod layout;
+mod precision;
 mod shape;
 mod symbolic_layout;
 mod symbolic_shape;
 
+pub use block_format::BlockFormat;
 pub use compiler::Compiler;
-pub use device::Device;
+pub use cpu_alloc::{get_cpu_alloc_config, set_cpu_alloc_config, CpuAllocConfig};
+pub use device::{Device, MemoryStats};
 pub use dim::{Dim, DynamicDimId};
 pub use dtype::DType;
 pub use dynamic_registry::{clear_resolved_dimensions, get_resolved_dimension, resolve_dimension};
 #[cfg(feature = "serde")]
 pub use format::Format;
 pub use layout::Layout;
+pub use precision::{get_precision, set_precision, Precision};
 pub use shape::Shape;
 pub use symbolic_layout::SymbolicLayout;
 pub use symbolic_shape::SymbolicShape;
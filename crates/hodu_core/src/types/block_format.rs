@@ -0,0 +1,72 @@
+use crate::error::{HoduError, HoduResult};
+use std::fmt;
+
+/// Packed block-quantized layout for weights held in a `u8` tensor
+///
+/// Values are grouped in blocks of [`block_size`](Self::block_size) consecutive elements along the
+/// last dimension, and each block is stored in [`block_bytes`](Self::block_bytes) bytes. A weight
+/// of logical shape `[..., K]` is therefore a `u8` tensor of shape
+/// `[..., K / block_size * block_bytes]`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockFormat {
+    /// 32 values per block: an f16 scale (`max|x| / 7`, little endian) followed by 16 bytes of
+    /// 4-bit codes, value `2j` in the low nibble of byte `j`; decodes as `x = (q - 8) * scale`
+    Q4_0,
+}
+
+impl fmt::Display for BlockFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Q4_0 => write!(f, "q4_0"),
+        }
+    }
+}
+
+impl fmt::Debug for BlockFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BlockFormat[{}]", self)
+    }
+}
+
+impl BlockFormat {
+    /// Number of values in one block
+    pub const fn block_size(&self) -> usize {
+        match self {
+            Self::Q4_0 => 32,
+        }
+    }
+
+    /// Number of bytes one packed block occupies
+    pub const fn block_bytes(&self) -> usize {
+        match self {
+            Self::Q4_0 => 18,
+        }
+    }
+
+    /// Packed byte length of a last dimension holding `dim` values
+    pub fn packed_dim(&self, dim: usize) -> HoduResult<usize> {
+        if dim == 0 || !dim.is_multiple_of(self.block_size()) {
+            return Err(HoduError::InvalidArgument(format!(
+                "{} needs the last dimension to be a multiple of {}, got {}",
+                self,
+                self.block_size(),
+                dim
+            )));
+        }
+        Ok(dim / self.block_size() * self.block_bytes())
+    }
+
+    /// Number of values held by a packed last dimension of `packed` bytes
+    pub fn unpacked_dim(&self, packed: usize) -> HoduResult<usize> {
+        if packed == 0 || !packed.is_multiple_of(self.block_bytes()) {
+            return Err(HoduError::InvalidArgument(format!(
+                "packed {} last dimension must be a multiple of {} bytes, got {}",
+                self,
+                self.block_bytes(),
+                packed
+            )));
+        }
+        Ok(packed / self.block_bytes() * self.block_size())
+    }
+}
@@ -0,0 +1,90 @@
+use crate::error::{HoduError, HoduResult};
+use std::sync::RwLock;
+
+/// Placement hints for buffers the CPU backend allocates for op results
+///
+/// Hints are applied on Linux before a buffer is first written, so its pages are faulted in
+/// where requested; other platforms ignore them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CpuAllocConfig {
+    /// Bind buffers to the memory of this NUMA node
+    pub numa_node: Option<usize>,
+    /// Ask for transparent huge pages on buffers of at least `huge_page_threshold` bytes
+    pub huge_pages: bool,
+    pub huge_page_threshold: usize,
+}
+
+impl CpuAllocConfig {
+    pub const DEFAULT: Self = Self {
+        numa_node: None,
+        huge_pages: false,
+        huge_page_threshold: 2 << 20,
+    };
+
+    pub fn with_numa_node(mut self, numa_node: Option<usize>) -> Self {
+        self.numa_node = numa_node;
+        self
+    }
+
+    pub fn with_huge_pages(mut self, huge_pages: bool) -> Self {
+        self.huge_pages = huge_pages;
+        self
+    }
+
+    pub fn with_huge_page_threshold(mut self, huge_page_threshold: usize) -> Self {
+        self.huge_page_threshold = huge_page_threshold;
+        self
+    }
+}
+
+impl Default for CpuAllocConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static CPU_ALLOC_CONFIG: RwLock<CpuAllocConfig> = RwLock::new(CpuAllocConfig::DEFAULT);
+
+/// Placement hints in effect for CPU allocations
+pub fn get_cpu_alloc_config() -> CpuAllocConfig {
+    CPU_ALLOC_CONFIG.read().map(|config| *config).unwrap_or_default()
+}
+
+/// Set the process-wide placement hints for CPU allocations
+///
+/// Fails if `numa_node` names a node this machine does not have.
+pub fn set_cpu_alloc_config(config: CpuAllocConfig) -> HoduResult<()> {
+    if let Some(node) = config.numa_node {
+        if !numa_node_exists(node) {
+            return Err(HoduError::InvalidArgument(format!("NUMA node {} does not exist", node)));
+        }
+    }
+    *CPU_ALLOC_CONFIG.write()? = config;
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn numa_node_exists(node: usize) -> bool {
+    // Kernels built without NUMA support expose no nodes; node 0 is then the whole machine
+    let nodes = std::path::Path::new("/sys/devices/system/node");
+    nodes.join(format!("node{}", node)).exists() || (node == 0 && !nodes.exists())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn numa_node_exists(node: usize) -> bool {
+    node == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_alloc_config_validates_node() {
+        let missing = CpuAllocConfig::DEFAULT.with_numa_node(Some(1 << 20));
+        assert!(set_cpu_alloc_config(missing).is_err());
+        assert_eq!(get_cpu_alloc_config().numa_node, None);
+
+        assert!(numa_node_exists(0));
+    }
+}
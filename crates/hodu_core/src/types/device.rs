@@ -8,6 +8,8 @@ pub enum Device {
     CUDA(usize),
     #[cfg(any(feature = "metal", feature = "metal-device"))]
     Metal,
+    #[cfg(feature = "wgpu")]
+    WebGPU,
 }
 
 impl fmt::Display for Device {
@@ -18,6 +20,8 @@ impl fmt::Display for Device {
             Device::CUDA(id) => write!(f, "cuda::{id}"),
             #[cfg(any(feature = "metal", feature = "metal-device"))]
             Device::Metal => write!(f, "Metal"),
+            #[cfg(feature = "wgpu")]
+            Device::WebGPU => write!(f, "webgpu"),
         }
     }
 }
@@ -28,6 +32,33 @@ impl fmt::Debug for Device {
     }
 }
 
+impl std::str::FromStr for Device {
+    type Err = crate::error::HoduError;
+
+    /// Parse a device string such as `"cpu"`, `"cuda::1"` or `"metal"`
+    ///
+    /// A bare `"cuda"` selects device 0.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::error::HoduError::InvalidArgument(format!("invalid device: {s:?}"));
+        let lower = s.trim().to_ascii_lowercase();
+        let (kind, index) = match lower.split_once("::") {
+            Some((kind, index)) => (kind, Some(index.parse::<usize>().map_err(|_| invalid())?)),
+            None => (lower.as_str(), None),
+        };
+
+        match (kind, index) {
+            ("cpu", None) => Ok(Device::CPU),
+            #[cfg(feature = "cuda")]
+            ("cuda", index) => Ok(Device::CUDA(index.unwrap_or(0))),
+            #[cfg(any(feature = "metal", feature = "metal-device"))]
+            ("metal", None) => Ok(Device::Metal),
+            #[cfg(feature = "wgpu")]
+            ("webgpu", None) => Ok(Device::WebGPU),
+            _ => Err(invalid()),
+        }
+    }
+}
+
 impl Device {
     pub fn is_cpu(&self) -> bool {
         matches!(self, Device::CPU)
@@ -42,4 +73,60 @@ impl Device {
     pub fn is_metal(&self) -> bool {
         matches!(self, Device::Metal)
     }
+
+    #[cfg(feature = "wgpu")]
+    pub fn is_webgpu(&self) -> bool {
+        matches!(self, Device::WebGPU)
+    }
+}
+
+impl Device {
+    /// Release memory cached by the device allocator that no live tensor is using
+    ///
+    /// No-op on devices without a caching allocator.
+    pub fn empty_cache(&self) -> crate::error::HoduResult<()> {
+        crate::be::device::BackendDevice::empty_cache(*self)
+    }
+
+    /// Allocation statistics of the device allocator
+    pub fn memory_stats(&self) -> crate::error::HoduResult<MemoryStats> {
+        crate::be::device::BackendDevice::memory_stats(*self)
+    }
+}
+
+/// Device allocator statistics, in bytes unless noted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Bytes held by live tensors
+    pub allocated_bytes: usize,
+    /// Bytes kept by the allocator for reuse
+    pub cached_bytes: usize,
+    /// Highest `allocated_bytes` seen so far
+    pub peak_allocated_bytes: usize,
+    /// Number of allocations served by the driver
+    pub num_allocs: usize,
+    /// Number of allocations served from the cache
+    pub num_cache_hits: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device() {
+        assert_eq!("cpu".parse::<Device>().unwrap(), Device::CPU);
+        assert_eq!(" CPU ".parse::<Device>().unwrap(), Device::CPU);
+        assert!("cpu::0".parse::<Device>().is_err());
+        assert!("tpu".parse::<Device>().is_err());
+    }
+
+    #[cfg(feature = "cuda")]
+    #[test]
+    fn test_parse_cuda_device() {
+        assert_eq!("cuda".parse::<Device>().unwrap(), Device::CUDA(0));
+        assert_eq!("cuda::1".parse::<Device>().unwrap(), Device::CUDA(1));
+        assert_eq!(Device::CUDA(3).to_string().parse::<Device>().unwrap(), Device::CUDA(3));
+        assert!("cuda::x".parse::<Device>().is_err());
+    }
 }
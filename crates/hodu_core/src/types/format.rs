@@ -6,6 +6,8 @@ pub enum Format {
     HDT,
     /// JSON - human-readable format
     JSON,
+    /// NumPy array format
+    NPY,
 }
 
 impl Format {
@@ -14,6 +16,7 @@ impl Format {
         match ext.to_lowercase().as_str() {
             "hdt" => Some(Self::HDT),
             "json" => Some(Self::JSON),
+            "npy" => Some(Self::NPY),
             _ => None,
         }
     }
@@ -28,6 +31,7 @@ impl Format {
         match self {
             Self::HDT => "hdt",
             Self::JSON => "json",
+            Self::NPY => "npy",
         }
     }
 }
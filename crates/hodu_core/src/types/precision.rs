@@ -0,0 +1,111 @@
+use std::{
+    cell::Cell,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+/// Accuracy/speed trade-offs matmul and convolution may take on GPU backends
+///
+/// The CPU backend always computes in full precision. Apple GPUs have no TF32 mode, so
+/// `allow_tf32` only affects CUDA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Precision {
+    /// Let f32 matmul and convolution run on TF32 tensor cores
+    pub allow_tf32: bool,
+    /// Accumulate f16/bf16 products in f32; when `false`, 16-bit accumulation is allowed
+    pub f16_accumulate_f32: bool,
+}
+
+impl Precision {
+    pub const DEFAULT: Self = Self {
+        allow_tf32: false,
+        f16_accumulate_f32: true,
+    };
+
+    pub fn with_allow_tf32(mut self, allow_tf32: bool) -> Self {
+        self.allow_tf32 = allow_tf32;
+        self
+    }
+
+    pub fn with_f16_accumulate_f32(mut self, f16_accumulate_f32: bool) -> Self {
+        self.f16_accumulate_f32 = f16_accumulate_f32;
+        self
+    }
+
+    /// Run `f` with this policy instead of the process-wide one on the current thread
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        struct Restore(Option<Precision>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                PRECISION_OVERRIDE.with(|current| current.set(self.0));
+            }
+        }
+
+        let _restore = Restore(PRECISION_OVERRIDE.with(|current| current.replace(Some(self))));
+        f()
+    }
+
+    fn encode(self) -> u8 {
+        self.allow_tf32 as u8 | (self.f16_accumulate_f32 as u8) << 1
+    }
+
+    fn decode(bits: u8) -> Self {
+        Self {
+            allow_tf32: bits & 1 != 0,
+            f16_accumulate_f32: bits & 2 != 0,
+        }
+    }
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+// Process-wide policy, bit-encoded for lock-free access
+static PRECISION: AtomicU8 = AtomicU8::new(0b10); // Default: f16 accumulates in f32
+
+thread_local! {
+    static PRECISION_OVERRIDE: Cell<Option<Precision>> = const { Cell::new(None) };
+}
+
+/// Policy in effect on the current thread
+#[inline]
+pub fn get_precision() -> Precision {
+    PRECISION_OVERRIDE
+        .with(|current| current.get())
+        .unwrap_or_else(|| Precision::decode(PRECISION.load(Ordering::Relaxed)))
+}
+
+/// Set the process-wide policy
+#[inline]
+pub fn set_precision(precision: Precision) {
+    PRECISION.store(precision.encode(), Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precision_encoding() {
+        assert_eq!(Precision::decode(PRECISION.load(Ordering::Relaxed)), Precision::DEFAULT);
+        for allow_tf32 in [false, true] {
+            for f16_accumulate_f32 in [false, true] {
+                let precision = Precision {
+                    allow_tf32,
+                    f16_accumulate_f32,
+                };
+                assert_eq!(Precision::decode(precision.encode()), precision);
+            }
+        }
+    }
+
+    #[test]
+    fn test_precision_scope() {
+        let fast = Precision::DEFAULT.with_allow_tf32(true).with_f16_accumulate_f32(false);
+        let inner = fast.scope(get_precision);
+        assert_eq!(inner, fast);
+        assert_ne!(get_precision(), fast);
+    }
+}
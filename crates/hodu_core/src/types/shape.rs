@@ -99,12 +99,15 @@ impl Shape {
         let max_ndim = lhs.ndim().max(rhs.ndim());
         let mut result_dims = SmallVec::with_capacity(max_ndim);
 
-        for i in 0..max_ndim {
-            let lhs_idx = lhs.ndim().saturating_sub(max_ndim - i);
-            let rhs_idx = rhs.ndim().saturating_sub(max_ndim - i);
+        // Shapes are aligned at their trailing dimensions; missing leading dimensions are 1
+        let dim_at = |shape: &Shape, i: usize| match (i + shape.ndim()).checked_sub(max_ndim) {
+            Some(idx) => shape.dims[idx],
+            None => 1,
+        };
 
-            let lhs_dim = lhs.dims.get(lhs_idx).copied().unwrap_or(1);
-            let rhs_dim = rhs.dims.get(rhs_idx).copied().unwrap_or(1);
+        for i in 0..max_ndim {
+            let lhs_dim = dim_at(lhs, i);
+            let rhs_dim = dim_at(rhs, i);
 
             if lhs_dim != rhs_dim && lhs_dim != 1 && rhs_dim != 1 {
                 return None;
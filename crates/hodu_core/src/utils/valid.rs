@@ -1,6 +1,8 @@
 use crate::{
     error::{HoduError, HoduResult},
-    ops::{BinaryOp, CmpOp, CmpScalarOp, IndexingOp, LinalgOp, Op, ReduceOp, UnaryOp, UnaryScalarOp, WindowingOp},
+    ops::{
+        BinaryOp, CmpOp, CmpScalarOp, IndexingOp, LinalgOp, Op, QuantOp, ReduceOp, UnaryOp, UnaryScalarOp, WindowingOp,
+    },
     tensor::Tensor,
     types::{DType, Device},
 };
@@ -23,6 +25,14 @@ pub fn validate_dtype_for_device(dtype: DType, device: Device) -> HoduResult<()>
                 _ => Ok(()),
             }
         },
+        #[cfg(feature = "wgpu")]
+        Device::WebGPU => {
+            // webgpu: WGSL only has 32-bit scalar types
+            match dtype {
+                DType::BOOL | DType::F32 | DType::I32 | DType::U32 => Ok(()),
+                _ => Err(HoduError::UnsupportedDTypeForDevice { dtype, device }),
+            }
+        },
     }
 }
 
@@ -384,6 +394,32 @@ pub fn validate_dtype_for_op(dtype: DType, op: Op) -> HoduResult<()> {
         Op::Shape(_) | Op::ShapeScalars(_) | Op::ShapeMemory(_) | Op::Cast(_) | Op::Memory(_) | Op::Dummy => {
             // All types supported
         },
+
+        // Control flow operations - dtypes are validated inside the subgraphs
+        Op::ControlFlow(_) => {},
+
+        // Quant operations - quantize and block matmul take a float input, the others take packed integers
+        Op::Quant(inner_op) => match inner_op {
+            QuantOp::Quantize | QuantOp::BlockQuantize | QuantOp::BlockMatmul => match dtype {
+                DType::BF16 | DType::F16 | DType::F32 => {},
+                #[cfg(feature = "f64")]
+                DType::F64 => {},
+                _ => return Err(HoduError::UnsupportedDTypeForOp { dtype, op }),
+            },
+            QuantOp::Dequantize | QuantOp::MatmulInt8 => {
+                if dtype != DType::I8 {
+                    return Err(HoduError::UnsupportedDTypeForOp { dtype, op });
+                }
+            },
+            QuantOp::BlockDequantize => {
+                if dtype != DType::U8 {
+                    return Err(HoduError::UnsupportedDTypeForOp { dtype, op });
+                }
+            },
+        },
+
+        // Custom operations - dtypes are validated by the plugin that executes them
+        Op::Custom => {},
     }
 
     Ok(())
@@ -496,6 +532,15 @@ pub fn validate_requires_grad_for_op(op: Op) -> bool {
         // Memory operations - no backprop
         Op::Memory(_) => false, // !
 
+        // Control flow operations - no backprop
+        Op::ControlFlow(_) => false, // !
+
+        // Quant operations - no backprop
+        Op::Quant(_) => false, // !
+
+        // Custom operations - executed by plugins, no backprop
+        Op::Custom => false, // !
+
         Op::Dummy => false,
     }
 }
@@ -1,7 +1,19 @@
 mod build_blas;
+mod build_mkl;
 mod build_openblas;
 
 fn main() {
+    println!("cargo:rustc-check-cfg=cfg(hodu_pure_rust)");
+
+    // Pure-Rust kernels replace the C sources when requested, and on wasm32 where no C toolchain
+    // or libm is available to link against
+    let target_arch = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    if std::env::var("CARGO_FEATURE_PURE_RUST").is_ok() || target_arch == "wasm32" {
+        println!("cargo:rustc-cfg=hodu_pure_rust");
+        println!("cargo:rerun-if-changed=build.rs");
+        return;
+    }
+
     let mut build = cc::Build::new();
 
     // Source files
@@ -17,6 +29,7 @@ fn main() {
         .file("kernels/ops_matrix.c")
         .file("kernels/ops_memory.c")
         .file("kernels/ops_padding.c")
+        .file("kernels/ops_quant.c")
         .file("kernels/ops_reduce.c")
         .file("kernels/ops_resize.c")
         .file("kernels/ops_scan.c")
@@ -81,6 +94,7 @@ fn main() {
     println!("cargo:rerun-if-changed=kernels/");
     for file in [
         "atomic.h",
+        "blas_utils.h",
         "constants.h",
         "math_utils.h",
         "simd_utils.h",
@@ -113,6 +127,8 @@ fn main() {
         "ops_memory.c",
         "ops_padding.h",
         "ops_padding.c",
+        "ops_quant.h",
+        "ops_quant.c",
         "ops_reduce.h",
         "ops_reduce.c",
         "ops_resize.h",
@@ -135,6 +151,18 @@ fn main() {
 }
 
 fn add_blas_impl(build: &mut cc::Build) {
+    // MKL and OpenBLAS share the CBLAS implementations
+    #[cfg(feature = "mkl")]
+    {
+        let mkl = build_mkl::MklConfig::detect();
+        if mkl.available {
+            build.file("kernels/ops_conv_openblas.c");
+            build.file("kernels/ops_matrix_openblas.c");
+            build.file("kernels/ops_unary_openblas.c");
+            return;
+        }
+    }
+
     #[cfg(feature = "openblas")]
     {
         let openblas = build_openblas::OpenBlasConfig::detect();
@@ -147,7 +175,7 @@ fn add_blas_impl(build: &mut cc::Build) {
         openblas.warn_if_not_found();
     }
 
-    #[cfg(not(feature = "openblas"))]
+    #[cfg(not(any(feature = "openblas", feature = "mkl")))]
     {
         let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
         if target_os == "macos" {
@@ -159,6 +187,16 @@ fn add_blas_impl(build: &mut cc::Build) {
 }
 
 fn configure_blas(build: &mut cc::Build) {
+    #[cfg(feature = "mkl")]
+    {
+        let mkl = build_mkl::MklConfig::detect();
+        if mkl.available {
+            mkl.apply_to_build(build);
+            return;
+        }
+        mkl.warn_if_not_found();
+    }
+
     #[cfg(feature = "openblas")]
     {
         let openblas = build_openblas::OpenBlasConfig::detect();
@@ -169,13 +207,22 @@ fn configure_blas(build: &mut cc::Build) {
         openblas.warn_if_not_found();
     }
 
-    #[cfg(not(feature = "openblas"))]
+    #[cfg(not(any(feature = "openblas", feature = "mkl")))]
     {
         build_blas::BlasConfig::detect().apply_to_build(build);
     }
 }
 
 fn link_blas() {
+    #[cfg(feature = "mkl")]
+    {
+        let mkl = build_mkl::MklConfig::detect();
+        if mkl.available {
+            mkl.setup_linking();
+            return;
+        }
+    }
+
     #[cfg(feature = "openblas")]
     {
         let openblas = build_openblas::OpenBlasConfig::detect();
@@ -184,7 +231,7 @@ fn link_blas() {
         }
     }
 
-    #[cfg(not(feature = "openblas"))]
+    #[cfg(not(any(feature = "openblas", feature = "mkl")))]
     {
         build_blas::BlasConfig::detect().setup_linking();
     }
@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 /// OS-provided BLAS configuration
 pub struct BlasConfig {
     target_os: String,
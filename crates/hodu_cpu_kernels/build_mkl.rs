@@ -0,0 +1,104 @@
+#![allow(dead_code)]
+
+/// Intel MKL configuration (`mkl` feature)
+pub struct MklConfig {
+    pub available: bool,
+    pub include_path: Option<String>,
+    pub lib_path: Option<String>,
+}
+
+impl MklConfig {
+    pub fn detect() -> Self {
+        match detect_mkl_paths() {
+            Some((include_path, lib_path)) => Self {
+                available: true,
+                include_path: Some(include_path),
+                lib_path,
+            },
+            None => Self::none(),
+        }
+    }
+
+    fn none() -> Self {
+        Self {
+            available: false,
+            include_path: None,
+            lib_path: None,
+        }
+    }
+
+    pub fn apply_to_build(&self, build: &mut cc::Build) {
+        if !self.available {
+            return;
+        }
+        build.define("USE_BLAS", None);
+        build.define("USE_MKL", None);
+        if let Some(ref path) = self.include_path {
+            build.include(path);
+        }
+    }
+
+    pub fn warn_if_not_found(&self) {
+        if !self.available {
+            println!("cargo:warning=MKL feature enabled but library not found");
+            println!("cargo:warning=Install oneMKL and set MKLROOT (e.g. source /opt/intel/oneapi/setvars.sh)");
+        }
+    }
+
+    pub fn setup_linking(&self) {
+        if !self.available {
+            return;
+        }
+
+        if let Some(ref path) = self.lib_path {
+            println!("cargo:rustc-link-search=native={}", path);
+        }
+
+        // Single dynamic library; threading layer is picked at runtime
+        println!("cargo:rustc-link-lib=mkl_rt");
+
+        let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+        if target_os == "linux" {
+            println!("cargo:rustc-link-lib=pthread");
+            println!("cargo:rustc-link-lib=m");
+            println!("cargo:rustc-link-lib=dl");
+        }
+    }
+}
+
+fn detect_mkl_paths() -> Option<(String, Option<String>)> {
+    use std::path::Path;
+
+    // Environment variables
+    if let Ok(include_dir) = std::env::var("MKL_INCLUDE_DIR") {
+        let lib_dir = std::env::var("MKL_LIB_DIR").ok();
+        return Some((include_dir, lib_dir));
+    }
+
+    // MKLROOT is set by the oneAPI environment scripts
+    let root = match std::env::var("MKLROOT") {
+        Ok(dir) => dir,
+        Err(_) if !is_cross_compile() => "/opt/intel/oneapi/mkl/latest".to_string(),
+        Err(_) => return None,
+    };
+
+    let root = Path::new(&root);
+    let include_dir = root.join("include");
+    if !include_dir.join("mkl_cblas.h").exists() {
+        return None;
+    }
+
+    let lib_dir = ["lib/intel64", "lib"]
+        .iter()
+        .map(|dir| root.join(dir))
+        .find(|dir| dir.exists())
+        .map(|dir| dir.to_string_lossy().to_string());
+
+    Some((include_dir.to_string_lossy().to_string(), lib_dir))
+}
+
+fn is_cross_compile() -> bool {
+    let target = std::env::var("TARGET").unwrap_or_default();
+    let host = std::env::var("HOST").unwrap_or_default();
+    target != host
+}
@@ -0,0 +1,34 @@
+#ifndef BLAS_UTILS_H
+#define BLAS_UTILS_H
+
+// ============================================================================
+// CBLAS Provider Selection
+// ============================================================================
+//
+// The *_openblas.c kernels only use the standard CBLAS interface, so they are
+// shared by every CBLAS provider. This header picks the provider's CBLAS header
+// and wraps its thread control:
+// - USE_MKL: Intel MKL (`mkl` feature)
+// - Otherwise: OpenBLAS (`openblas` feature)
+
+#if defined(USE_MKL)
+#include <mkl_cblas.h>
+#include <mkl_service.h>
+
+static inline int blas_get_num_threads(void) { return mkl_get_max_threads(); }
+
+static inline void blas_set_num_threads(int num_threads) { mkl_set_num_threads(num_threads); }
+
+#else
+#include <cblas.h>
+
+extern void openblas_set_num_threads(int num_threads);
+extern int openblas_get_num_threads(void);
+
+static inline int blas_get_num_threads(void) { return openblas_get_num_threads(); }
+
+static inline void blas_set_num_threads(int num_threads) { openblas_set_num_threads(num_threads); }
+
+#endif
+
+#endif // BLAS_UTILS_H
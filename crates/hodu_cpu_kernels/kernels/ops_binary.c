@@ -320,6 +320,38 @@
 #if SIMD_F32_WIDTH > 1
 
 #define IMPL_BINARY_OP_F32_SIMD(OP_NAME, SIMD_OP, SCALAR_OP)                                       \
+    typedef struct {                                                                               \
+        const f32_t *lhs;                                                                          \
+        const f32_t *rhs;                                                                          \
+        f32_t *output;                                                                             \
+        size_t start;                                                                              \
+        size_t end;                                                                                \
+    } binary_simd_##OP_NAME##_f32_args_t;                                                          \
+                                                                                                   \
+    static void *binary_simd_##OP_NAME##_f32_worker(void *arg) {                                   \
+        binary_simd_##OP_NAME##_f32_args_t *args = (binary_simd_##OP_NAME##_f32_args_t *)arg;      \
+        const f32_t *lhs_data = args->lhs;                                                         \
+        const f32_t *rhs_data = args->rhs;                                                         \
+        f32_t *out = args->output;                                                                 \
+        size_t i = args->start;                                                                    \
+        const size_t simd_end =                                                                    \
+            args->start + ((args->end - args->start) / SIMD_F32_WIDTH) * SIMD_F32_WIDTH;           \
+                                                                                                   \
+        for (; i < simd_end; i += SIMD_F32_WIDTH) {                                                \
+            simd_f32_t va = simd_f32_load(&lhs_data[i]);                                           \
+            simd_f32_t vb = simd_f32_load(&rhs_data[i]);                                           \
+            simd_f32_store(&out[i], SIMD_OP(va, vb));                                              \
+        }                                                                                          \
+                                                                                                   \
+        /* Scalar remainder */                                                                     \
+        for (; i < args->end; i++) {                                                               \
+            f32_t x = lhs_data[i];                                                                 \
+            f32_t y = rhs_data[i];                                                                 \
+            out[i] = SCALAR_OP;                                                                    \
+        }                                                                                          \
+        return NULL;                                                                               \
+    }                                                                                              \
+                                                                                                   \
     void hodu_cpu_##OP_NAME##_f32(const void *lhs, const void *rhs, void *output,                  \
                                   const size_t *metadata) {                                        \
         const f32_t *l = (const f32_t *)lhs;                                                       \
@@ -339,24 +371,31 @@
         bool rhs_cont = is_contiguous(num_dims, rhs_shape, rhs_strides);                           \
                                                                                                    \
         if (lhs_cont && rhs_cont) {                                                                \
-            /* SIMD path for contiguous data */                                                    \
-            const f32_t *lhs_data = l + lhs_offset;                                                \
-            const f32_t *rhs_data = r + rhs_offset;                                                \
-            size_t i = 0;                                                                          \
-            const size_t simd_end = (num_els / SIMD_F32_WIDTH) * SIMD_F32_WIDTH;                   \
-                                                                                                   \
-            for (; i < simd_end; i += SIMD_F32_WIDTH) {                                            \
-                simd_f32_t va = simd_f32_load(&lhs_data[i]);                                       \
-                simd_f32_t vb = simd_f32_load(&rhs_data[i]);                                       \
-                simd_f32_t vc = SIMD_OP(va, vb);                                                   \
-                simd_f32_store(&out[i], vc);                                                       \
-            }                                                                                      \
+            /* SIMD path for contiguous data, split across threads for large inputs */             \
+            binary_simd_##OP_NAME##_f32_args_t base = {l + lhs_offset, r + rhs_offset, out, 0,     \
+                                                        num_els};                                  \
+            const size_t min_work_per_thread = 100000;                                             \
+            size_t num_threads = get_optimal_threads(num_els, min_work_per_thread);                \
                                                                                                    \
-            /* Scalar remainder */                                                                 \
-            for (; i < num_els; i++) {                                                             \
-                f32_t x = lhs_data[i];                                                             \
-                f32_t y = rhs_data[i];                                                             \
-                out[i] = SCALAR_OP;                                                                \
+            if (num_threads > 1) {                                                                 \
+                thread_t threads[num_threads];                                                     \
+                binary_simd_##OP_NAME##_f32_args_t args[num_threads];                              \
+                                                                                                   \
+                size_t chunk_size = num_els / num_threads;                                         \
+                size_t remaining = num_els % num_threads;                                          \
+                                                                                                   \
+                for (size_t t = 0; t < num_threads; t++) {                                         \
+                    args[t] = base;                                                                \
+                    args[t].start = t * chunk_size + (t < remaining ? t : remaining);              \
+                    args[t].end = args[t].start + chunk_size + (t < remaining ? 1 : 0);            \
+                    thread_create(&threads[t], binary_simd_##OP_NAME##_f32_worker, &args[t]);      \
+                }                                                                                  \
+                                                                                                   \
+                for (size_t t = 0; t < num_threads; t++) {                                         \
+                    thread_join(threads[t]);                                                       \
+                }                                                                                  \
+            } else {                                                                               \
+                binary_simd_##OP_NAME##_f32_worker(&base);                                         \
             }                                                                                      \
         } else if (lhs_cont) {                                                                     \
             for (size_t i = 0; i < num_els; i++) {                                                 \
@@ -422,6 +461,38 @@ IMPL_BINARY_TO_BOOL(f32_t, f32, ge, x >= y)
 #if SIMD_F64_WIDTH > 1
 
 #define IMPL_BINARY_OP_F64_SIMD(OP_NAME, SIMD_OP, SCALAR_OP)                                       \
+    typedef struct {                                                                               \
+        const f64_t *lhs;                                                                          \
+        const f64_t *rhs;                                                                          \
+        f64_t *output;                                                                             \
+        size_t start;                                                                              \
+        size_t end;                                                                                \
+    } binary_simd_##OP_NAME##_f64_args_t;                                                          \
+                                                                                                   \
+    static void *binary_simd_##OP_NAME##_f64_worker(void *arg) {                                   \
+        binary_simd_##OP_NAME##_f64_args_t *args = (binary_simd_##OP_NAME##_f64_args_t *)arg;      \
+        const f64_t *lhs_data = args->lhs;                                                         \
+        const f64_t *rhs_data = args->rhs;                                                         \
+        f64_t *out = args->output;                                                                 \
+        size_t i = args->start;                                                                    \
+        const size_t simd_end =                                                                    \
+            args->start + ((args->end - args->start) / SIMD_F64_WIDTH) * SIMD_F64_WIDTH;           \
+                                                                                                   \
+        for (; i < simd_end; i += SIMD_F64_WIDTH) {                                                \
+            simd_f64_t va = simd_f64_load(&lhs_data[i]);                                           \
+            simd_f64_t vb = simd_f64_load(&rhs_data[i]);                                           \
+            simd_f64_store(&out[i], SIMD_OP(va, vb));                                              \
+        }                                                                                          \
+                                                                                                   \
+        /* Scalar remainder */                                                                     \
+        for (; i < args->end; i++) {                                                               \
+            f64_t x = lhs_data[i];                                                                 \
+            f64_t y = rhs_data[i];                                                                 \
+            out[i] = SCALAR_OP;                                                                    \
+        }                                                                                          \
+        return NULL;                                                                               \
+    }                                                                                              \
+                                                                                                   \
     void hodu_cpu_##OP_NAME##_f64(const void *lhs, const void *rhs, void *output,                  \
                                   const size_t *metadata) {                                        \
         const f64_t *l = (const f64_t *)lhs;                                                       \
@@ -441,22 +512,31 @@ IMPL_BINARY_TO_BOOL(f32_t, f32, ge, x >= y)
         bool rhs_cont = is_contiguous(num_dims, rhs_shape, rhs_strides);                           \
                                                                                                    \
         if (lhs_cont && rhs_cont) {                                                                \
-            const f64_t *lhs_data = l + lhs_offset;                                                \
-            const f64_t *rhs_data = r + rhs_offset;                                                \
-            size_t i = 0;                                                                          \
-            const size_t simd_end = (num_els / SIMD_F64_WIDTH) * SIMD_F64_WIDTH;                   \
-                                                                                                   \
-            for (; i < simd_end; i += SIMD_F64_WIDTH) {                                            \
-                simd_f64_t va = simd_f64_load(&lhs_data[i]);                                       \
-                simd_f64_t vb = simd_f64_load(&rhs_data[i]);                                       \
-                simd_f64_t vc = SIMD_OP(va, vb);                                                   \
-                simd_f64_store(&out[i], vc);                                                       \
-            }                                                                                      \
+            /* SIMD path for contiguous data, split across threads for large inputs */             \
+            binary_simd_##OP_NAME##_f64_args_t base = {l + lhs_offset, r + rhs_offset, out, 0,     \
+                                                        num_els};                                  \
+            const size_t min_work_per_thread = 100000;                                             \
+            size_t num_threads = get_optimal_threads(num_els, min_work_per_thread);                \
                                                                                                    \
-            for (; i < num_els; i++) {                                                             \
-                f64_t x = lhs_data[i];                                                             \
-                f64_t y = rhs_data[i];                                                             \
-                out[i] = SCALAR_OP;                                                                \
+            if (num_threads > 1) {                                                                 \
+                thread_t threads[num_threads];                                                     \
+                binary_simd_##OP_NAME##_f64_args_t args[num_threads];                              \
+                                                                                                   \
+                size_t chunk_size = num_els / num_threads;                                         \
+                size_t remaining = num_els % num_threads;                                          \
+                                                                                                   \
+                for (size_t t = 0; t < num_threads; t++) {                                         \
+                    args[t] = base;                                                                \
+                    args[t].start = t * chunk_size + (t < remaining ? t : remaining);              \
+                    args[t].end = args[t].start + chunk_size + (t < remaining ? 1 : 0);            \
+                    thread_create(&threads[t], binary_simd_##OP_NAME##_f64_worker, &args[t]);      \
+                }                                                                                  \
+                                                                                                   \
+                for (size_t t = 0; t < num_threads; t++) {                                         \
+                    thread_join(threads[t]);                                                       \
+                }                                                                                  \
+            } else {                                                                               \
+                binary_simd_##OP_NAME##_f64_worker(&base);                                         \
             }                                                                                      \
         } else if (lhs_cont) {                                                                     \
             for (size_t i = 0; i < num_els; i++) {                                                 \
@@ -295,6 +295,19 @@ CONV1D_OP(double, f64)
 CONV2D_OP(f32_t, f32_fallback)
 CONV2D_OP(f64_t, f64_fallback)
 
+#ifndef USE_BLAS
+// Non-BLAS version just calls fallback (BLAS versions in separate files)
+void hodu_cpu_conv2d_f32(const void *input, const void *weight, void *output,
+                         const size_t *metadata) {
+    hodu_cpu_conv2d_f32_fallback(input, weight, output, metadata);
+}
+
+void hodu_cpu_conv2d_f64(const void *input, const void *weight, void *output,
+                         const size_t *metadata) {
+    hodu_cpu_conv2d_f64_fallback(input, weight, output, metadata);
+}
+#endif
+
 CONV2D_OP_EXOTIC(f8e4m3_t, f8e4m3, F8E4M3_ZERO, f8e4m3_add, f8e4m3_mul)
 CONV2D_OP_EXOTIC(f8e5m2_t, f8e5m2, F8E5M2_ZERO, f8e5m2_add, f8e5m2_mul)
 CONV2D_OP_EXOTIC(bf16_t, bf16, BF16_ZERO, bf16_add, bf16_mul)
@@ -1352,6 +1365,19 @@ CONV1D_GRAD_WEIGHT_OP(double, f64, atomic_add_f64)
 CONV2D_GRAD_WEIGHT_OP(f32_t, f32_fallback, atomic_add_f32)
 CONV2D_GRAD_WEIGHT_OP(f64_t, f64_fallback, atomic_add_f64)
 
+#ifndef USE_BLAS
+// Non-BLAS version just calls fallback (BLAS versions in separate files)
+void hodu_cpu_conv2d_grad_weight_f32(const void *input, const void *grad_output,
+                                     void *grad_weight, const size_t *metadata) {
+    hodu_cpu_conv2d_grad_weight_f32_fallback(input, grad_output, grad_weight, metadata);
+}
+
+void hodu_cpu_conv2d_grad_weight_f64(const void *input, const void *grad_output,
+                                     void *grad_weight, const size_t *metadata) {
+    hodu_cpu_conv2d_grad_weight_f64_fallback(input, grad_output, grad_weight, metadata);
+}
+#endif
+
 CONV2D_GRAD_WEIGHT_OP_EXOTIC(f8e4m3_t, f8e4m3, F8E4M3_ZERO, f8e4m3_mul, atomic_add_f8e4m3)
 CONV2D_GRAD_WEIGHT_OP_EXOTIC(f8e5m2_t, f8e5m2, F8E5M2_ZERO, f8e5m2_mul, atomic_add_f8e5m2)
 CONV2D_GRAD_WEIGHT_OP_EXOTIC(bf16_t, bf16, BF16_ZERO, bf16_mul, atomic_add_bf16)
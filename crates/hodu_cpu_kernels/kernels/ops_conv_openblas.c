@@ -1,7 +1,7 @@
 #include "atomic.h"
+#include "blas_utils.h"
 #include "ops_conv.h"
 #include "types.h"
-#include <cblas.h>
 #include <stdint.h>
 #include <stdlib.h>
 #include <string.h>
@@ -80,7 +80,7 @@ static inline void im2col_f64(const double *input, double *col_buffer, size_t in
     }
 }
 
-// OpenBLAS-optimized conv2d for f32 using im2col + GEMM
+// CBLAS-optimized conv2d for f32 using im2col + GEMM
 void hodu_cpu_conv2d_f32(const void *input_ptr, const void *weight_ptr, void *output_ptr,
                          const size_t *metadata) {
     const float *input = (const float *)input_ptr;
@@ -137,7 +137,7 @@ void hodu_cpu_conv2d_f32(const void *input_ptr, const void *weight_ptr, void *ou
     free(col_buffer);
 }
 
-// OpenBLAS-optimized conv2d for f64 using im2col + GEMM
+// CBLAS-optimized conv2d for f64 using im2col + GEMM
 void hodu_cpu_conv2d_f64(const void *input_ptr, const void *weight_ptr, void *output_ptr,
                          const size_t *metadata) {
     const double *input = (const double *)input_ptr;
@@ -194,7 +194,7 @@ void hodu_cpu_conv2d_f64(const void *input_ptr, const void *weight_ptr, void *ou
     free(col_buffer);
 }
 
-// OpenBLAS-optimized conv2d_grad_weight for f32 using im2col + GEMM
+// CBLAS-optimized conv2d_grad_weight for f32 using im2col + GEMM
 void hodu_cpu_conv2d_grad_weight_f32(const void *input_ptr, const void *grad_output_ptr,
                                      void *grad_weight_ptr, const size_t *metadata) {
     const float *input = (const float *)input_ptr;
@@ -281,7 +281,7 @@ void hodu_cpu_conv2d_grad_weight_f32(const void *input_ptr, const void *grad_out
     free(col_buffer);
 }
 
-// OpenBLAS-optimized conv2d_grad_weight for f64 using im2col + GEMM
+// CBLAS-optimized conv2d_grad_weight for f64 using im2col + GEMM
 void hodu_cpu_conv2d_grad_weight_f64(const void *input_ptr, const void *grad_output_ptr,
                                      void *grad_weight_ptr, const size_t *metadata) {
     const double *input = (const double *)input_ptr;
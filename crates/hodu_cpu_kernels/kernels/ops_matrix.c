@@ -343,10 +343,21 @@ MATMUL_OP(f32_t, f32_fallback)
 MATMUL_OP(f64_t, f64_fallback)
 
 // F32/F64 matmul implementations are in separate BLAS-specific files:
-// - ops_matrix_openblas.c (OpenBLAS with thread control)
+// - ops_matrix_openblas.c (OpenBLAS or MKL through CBLAS)
 // - ops_matrix_blas_aarch64_apple_darwin.c (Accelerate framework)
 // These files provide matmul_f32() and matmul_f64() implementations
 
+#ifndef USE_BLAS
+// Non-BLAS version just calls fallback
+void hodu_cpu_matmul_f32(const void *lhs, const void *rhs, void *output, const size_t *metadata) {
+    hodu_cpu_matmul_f32_fallback(lhs, rhs, output, metadata);
+}
+
+void hodu_cpu_matmul_f64(const void *lhs, const void *rhs, void *output, const size_t *metadata) {
+    hodu_cpu_matmul_f64_fallback(lhs, rhs, output, metadata);
+}
+#endif
+
 // Exotic floating-point types use proper arithmetic
 MATMUL_OP_EXOTIC(f8e4m3_t, f8e4m3, F8E4M3_ZERO, f8e4m3_add, f8e4m3_mul)
 MATMUL_OP_EXOTIC(f8e5m2_t, f8e5m2, F8E5M2_ZERO, f8e5m2_add, f8e5m2_mul)
@@ -705,10 +716,21 @@ DOT_OP(f32_t, f32_fallback)
 DOT_OP(f64_t, f64_fallback)
 
 // F32/F64 dot implementations are in separate BLAS-specific files:
-// - ops_matrix_openblas.c (OpenBLAS)
+// - ops_matrix_openblas.c (OpenBLAS or MKL through CBLAS)
 // - ops_matrix_blas_aarch64_apple_darwin.c (Accelerate framework)
 // These files provide dot_f32() and dot_f64() implementations
 
+#ifndef USE_BLAS
+// Non-BLAS version just calls fallback
+void hodu_cpu_dot_f32(const void *lhs, const void *rhs, void *output, const size_t *metadata) {
+    hodu_cpu_dot_f32_fallback(lhs, rhs, output, metadata);
+}
+
+void hodu_cpu_dot_f64(const void *lhs, const void *rhs, void *output, const size_t *metadata) {
+    hodu_cpu_dot_f64_fallback(lhs, rhs, output, metadata);
+}
+#endif
+
 // Exotic floating-point types use simple correct implementation
 DOT_OP_EXOTIC(f8e4m3_t, f8e4m3, F8E4M3_ZERO, f8e4m3_add, f8e4m3_mul)
 DOT_OP_EXOTIC(f8e5m2_t, f8e5m2, F8E5M2_ZERO, f8e5m2_add, f8e5m2_mul)
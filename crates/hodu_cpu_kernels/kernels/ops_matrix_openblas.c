@@ -1,10 +1,9 @@
+#include "blas_utils.h"
 #include "ops_matrix.h"
 #include "types.h"
 #include <stdbool.h>
 #include <stdint.h>
 
-#include <cblas.h>
-
 // Forward declarations for fallback implementations
 extern void hodu_cpu_matmul_f32_fallback(const void *lhs_ptr, const void *rhs_ptr, void *output_ptr,
                                          const size_t *metadata);
@@ -15,7 +14,7 @@ extern void hodu_cpu_dot_f32_fallback(const void *lhs_ptr, const void *rhs_ptr,
 extern void hodu_cpu_dot_f64_fallback(const void *lhs_ptr, const void *rhs_ptr, void *output_ptr,
                                       const size_t *metadata);
 
-/// F32 matmul using OpenBLAS cblas_sgemm with thread control
+/// F32 matmul using CBLAS cblas_sgemm with thread control
 void hodu_cpu_matmul_f32(const void *lhs_ptr, const void *rhs_ptr, void *output_ptr,
                          const size_t *metadata) {
     const f32_t *lhs = (const f32_t *)lhs_ptr;
@@ -79,19 +78,17 @@ void hodu_cpu_matmul_f32(const void *lhs_ptr, const void *rhs_ptr, void *output_
 
         if (batch_ndim == 0) {
             // No batching - single BLAS call
-            // For small matrices, use single-threaded execution to avoid OpenBLAS overhead
+            // For small matrices, use single-threaded execution to avoid BLAS threading overhead
             size_t flops = M * N * K;
             if (flops < 1000000) { // threshold: 1M FLOPs
                 // Save current thread count and set to 1
-                extern void openblas_set_num_threads(int num_threads);
-                extern int openblas_get_num_threads(void);
-                int saved_threads = openblas_get_num_threads();
-                openblas_set_num_threads(1);
+                int saved_threads = blas_get_num_threads();
+                blas_set_num_threads(1);
 
                 cblas_sgemm(CblasRowMajor, trans_lhs, trans_rhs, M, N, K, 1.0f, lhs, lda, rhs, ldb,
                             0.0f, output, ldc);
 
-                openblas_set_num_threads(saved_threads);
+                blas_set_num_threads(saved_threads);
             } else {
                 cblas_sgemm(CblasRowMajor, trans_lhs, trans_rhs, M, N, K, 1.0f, lhs, lda, rhs, ldb,
                             0.0f, output, ldc);
@@ -105,15 +102,13 @@ void hodu_cpu_matmul_f32(const void *lhs_ptr, const void *rhs_ptr, void *output_
 
             size_t out_batch_stride = M * N;
 
-            // For small matrices, use single-threaded execution to avoid OpenBLAS overhead
+            // For small matrices, use single-threaded execution to avoid BLAS threading overhead
             size_t flops = M * N * K;
             int use_single_thread = (flops < 1000000);
-            extern void openblas_set_num_threads(int num_threads);
-            extern int openblas_get_num_threads(void);
             int saved_threads = 0;
             if (use_single_thread) {
-                saved_threads = openblas_get_num_threads();
-                openblas_set_num_threads(1);
+                saved_threads = blas_get_num_threads();
+                blas_set_num_threads(1);
             }
 
             for (size_t batch_idx = 0; batch_idx < total_batches; batch_idx++) {
@@ -143,7 +138,7 @@ void hodu_cpu_matmul_f32(const void *lhs_ptr, const void *rhs_ptr, void *output_
             }
 
             if (use_single_thread) {
-                openblas_set_num_threads(saved_threads);
+                blas_set_num_threads(saved_threads);
             }
         }
     } else {
@@ -152,7 +147,7 @@ void hodu_cpu_matmul_f32(const void *lhs_ptr, const void *rhs_ptr, void *output_
     }
 }
 
-/// F64 matmul using OpenBLAS cblas_dgemm with thread control
+/// F64 matmul using CBLAS cblas_dgemm with thread control
 void hodu_cpu_matmul_f64(const void *lhs_ptr, const void *rhs_ptr, void *output_ptr,
                          const size_t *metadata) {
     const f64_t *lhs = (const f64_t *)lhs_ptr;
@@ -210,18 +205,16 @@ void hodu_cpu_matmul_f64(const void *lhs_ptr, const void *rhs_ptr, void *output_
 
         if (batch_ndim == 0) {
             // No batching - single BLAS call
-            // For small matrices, use single-threaded execution to avoid OpenBLAS overhead
+            // For small matrices, use single-threaded execution to avoid BLAS threading overhead
             size_t flops = M * N * K;
             if (flops < 1000000) { // threshold: 1M FLOPs
-                extern void openblas_set_num_threads(int num_threads);
-                extern int openblas_get_num_threads(void);
-                int saved_threads = openblas_get_num_threads();
-                openblas_set_num_threads(1);
+                int saved_threads = blas_get_num_threads();
+                blas_set_num_threads(1);
 
                 cblas_dgemm(CblasRowMajor, trans_lhs, trans_rhs, M, N, K, 1.0, lhs, lda, rhs, ldb,
                             0.0, output, ldc);
 
-                openblas_set_num_threads(saved_threads);
+                blas_set_num_threads(saved_threads);
             } else {
                 cblas_dgemm(CblasRowMajor, trans_lhs, trans_rhs, M, N, K, 1.0, lhs, lda, rhs, ldb,
                             0.0, output, ldc);
@@ -235,15 +228,13 @@ void hodu_cpu_matmul_f64(const void *lhs_ptr, const void *rhs_ptr, void *output_
 
             size_t out_batch_stride = M * N;
 
-            // For small matrices, use single-threaded execution to avoid OpenBLAS overhead
+            // For small matrices, use single-threaded execution to avoid BLAS threading overhead
             size_t flops = M * N * K;
             int use_single_thread = (flops < 1000000);
-            extern void openblas_set_num_threads(int num_threads);
-            extern int openblas_get_num_threads(void);
             int saved_threads = 0;
             if (use_single_thread) {
-                saved_threads = openblas_get_num_threads();
-                openblas_set_num_threads(1);
+                saved_threads = blas_get_num_threads();
+                blas_set_num_threads(1);
             }
 
             for (size_t batch_idx = 0; batch_idx < total_batches; batch_idx++) {
@@ -273,7 +264,7 @@ void hodu_cpu_matmul_f64(const void *lhs_ptr, const void *rhs_ptr, void *output_
             }
 
             if (use_single_thread) {
-                openblas_set_num_threads(saved_threads);
+                blas_set_num_threads(saved_threads);
             }
         }
     } else {
@@ -282,7 +273,7 @@ void hodu_cpu_matmul_f64(const void *lhs_ptr, const void *rhs_ptr, void *output_
     }
 }
 
-/// F32 dot product using OpenBLAS cblas_sgemm
+/// F32 dot product using CBLAS cblas_sgemm
 void hodu_cpu_dot_f32(const void *lhs_ptr, const void *rhs_ptr, void *output_ptr,
                       const size_t *metadata) {
     const f32_t *lhs = (const f32_t *)lhs_ptr;
@@ -339,7 +330,7 @@ void hodu_cpu_dot_f32(const void *lhs_ptr, const void *rhs_ptr, void *output_ptr
     }
 }
 
-/// F64 dot product using OpenBLAS cblas_dgemm
+/// F64 dot product using CBLAS cblas_dgemm
 void hodu_cpu_dot_f64(const void *lhs_ptr, const void *rhs_ptr, void *output_ptr,
                       const size_t *metadata) {
     const f64_t *lhs = (const f64_t *)lhs_ptr;
@@ -10,11 +10,90 @@
 // Memory operations handle tensor memory layout transformations.
 // The contiguous operation copies strided tensor data to contiguous memory.
 
+// ============================================================================
+// TILED TRANSPOSE
+// ============================================================================
+//
+// A permuted view whose unit-stride dim is not the innermost one reads or writes with a large
+// stride on every element of a naive copy. Such copies are done tile by tile instead: each
+// TRANSPOSE_TILE x TRANSPOSE_TILE tile of the (unit-stride dim, innermost dim) plane is read
+// along the input's unit stride and written along the output's, and both stay in cache.
+
+#define TRANSPOSE_TILE 32
+
+typedef struct {
+    size_t axis;       // dim the input walks with unit stride
+    size_t rows;       // dims[axis]
+    size_t cols;       // dims[num_dims - 1]
+    size_t col_stride; // input stride of the innermost dim
+    size_t row_stride; // output stride of `axis`
+    size_t row_tiles;
+    size_t col_tiles;
+    size_t num_tiles;
+} transpose_plan_t;
+
+/// Returns true and fills `plan` if the layout is a transpose the tiled copy handles
+static bool find_transpose_plan(size_t num_dims, const size_t *dims, const size_t *strides,
+                                transpose_plan_t *plan) {
+    if (num_dims < 2) {
+        return false;
+    }
+    const size_t last = num_dims - 1;
+    if (strides[last] == 1 || dims[last] < 2) {
+        return false;
+    }
+
+    for (size_t axis = 0; axis < last; axis++) {
+        if (strides[axis] != 1 || dims[axis] < 2) {
+            continue;
+        }
+        size_t row_stride = 1;
+        for (size_t d = axis + 1; d < num_dims; d++) {
+            row_stride *= dims[d];
+        }
+        size_t num_els = 1;
+        for (size_t d = 0; d < num_dims; d++) {
+            num_els *= dims[d];
+        }
+
+        plan->axis = axis;
+        plan->rows = dims[axis];
+        plan->cols = dims[last];
+        plan->col_stride = strides[last];
+        plan->row_stride = row_stride;
+        plan->row_tiles = (plan->rows + TRANSPOSE_TILE - 1) / TRANSPOSE_TILE;
+        plan->col_tiles = (plan->cols + TRANSPOSE_TILE - 1) / TRANSPOSE_TILE;
+        plan->num_tiles = num_els / (plan->rows * plan->cols) * plan->row_tiles * plan->col_tiles;
+        return plan->num_tiles > 0;
+    }
+    return false;
+}
+
+/// Input and output offsets of the plane selected by `outer`, an index over the other dims
+static void transpose_plane_bases(const transpose_plan_t *plan, size_t outer, size_t num_dims,
+                                  const size_t *dims, const size_t *strides, size_t *in_base,
+                                  size_t *out_base) {
+    const size_t last = num_dims - 1;
+    size_t in = 0, out = 0, out_stride = 1;
+    for (size_t d = num_dims; d-- > 0;) {
+        if (d != last && d != plan->axis) {
+            size_t idx = outer % dims[d];
+            outer /= dims[d];
+            in += idx * strides[d];
+            out += idx * out_stride;
+        }
+        out_stride *= dims[d];
+    }
+    *in_base = in;
+    *out_base = out;
+}
+
 /**
  * @brief Macro to implement contiguous copy operation
  *
  * Copies tensor data from potentially strided layout to contiguous memory.
- * Optimizes for already-contiguous tensors with memcpy.
+ * Optimizes for already-contiguous tensors with memcpy and for transposed views with a
+ * cache-tiled copy.
  */
 #define IMPL_CONTIGUOUS_OP(TYPE, TYPE_SUFFIX)                                                      \
     typedef struct {                                                                               \
@@ -33,6 +112,41 @@
         return NULL;                                                                               \
     }                                                                                              \
                                                                                                    \
+    typedef struct {                                                                               \
+        const TYPE *input;                                                                         \
+        TYPE *output;                                                                              \
+        const transpose_plan_t *plan;                                                              \
+        size_t num_dims;                                                                           \
+        const size_t *dims;                                                                        \
+        const size_t *strides;                                                                     \
+        size_t offset;                                                                             \
+        size_t start;                                                                              \
+        size_t end;                                                                                \
+    } transpose_##TYPE_SUFFIX##_args_t;                                                            \
+                                                                                                   \
+    static void *transpose_##TYPE_SUFFIX##_worker(void *arg) {                                     \
+        transpose_##TYPE_SUFFIX##_args_t *args = (transpose_##TYPE_SUFFIX##_args_t *)arg;          \
+        const transpose_plan_t *plan = args->plan;                                                 \
+        const size_t plane_tiles = plan->row_tiles * plan->col_tiles;                              \
+        for (size_t t = args->start; t < args->end; t++) {                                         \
+            size_t in_base, out_base;                                                              \
+            transpose_plane_bases(plan, t / plane_tiles, args->num_dims, args->dims,               \
+                                  args->strides, &in_base, &out_base);                             \
+            const size_t r0 = (t % plane_tiles) / plan->col_tiles * TRANSPOSE_TILE;                \
+            const size_t c0 = (t % plan->col_tiles) * TRANSPOSE_TILE;                              \
+            const size_t r1 = r0 + TRANSPOSE_TILE < plan->rows ? r0 + TRANSPOSE_TILE : plan->rows; \
+            const size_t c1 = c0 + TRANSPOSE_TILE < plan->cols ? c0 + TRANSPOSE_TILE : plan->cols; \
+            for (size_t c = c0; c < c1; c++) {                                                     \
+                const TYPE *src = args->input + args->offset + in_base + c * plan->col_stride;     \
+                TYPE *dst = args->output + out_base + c;                                           \
+                for (size_t r = r0; r < r1; r++) {                                                 \
+                    dst[r * plan->row_stride] = src[r];                                            \
+                }                                                                                  \
+            }                                                                                      \
+        }                                                                                          \
+        return NULL;                                                                               \
+    }                                                                                              \
+                                                                                                   \
     void hodu_cpu_contiguous_##TYPE_SUFFIX(const void *input, void *output,                        \
                                            const size_t *metadata) {                               \
         const size_t num_els = metadata[0];                                                        \
@@ -45,10 +159,45 @@
         const size_t offset = (num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;                     \
                                                                                                    \
         bool contiguous = is_contiguous(num_dims, dims, strides);                                  \
+        transpose_plan_t plan;                                                                     \
                                                                                                    \
         if (contiguous) {                                                                          \
             /* Fast path: already contiguous, use memcpy */                                        \
             memcpy(out, in + offset, num_els * sizeof(TYPE));                                      \
+        } else if (find_transpose_plan(num_dims, dims, strides, &plan)) {                          \
+            /* Transposed view: copy tile by tile, tiles split across threads */                   \
+            const size_t min_work_per_thread = 100000;                                             \
+            size_t num_threads = get_optimal_threads(num_els, min_work_per_thread);                \
+            if (num_threads > plan.num_tiles) {                                                    \
+                num_threads = plan.num_tiles;                                                      \
+            }                                                                                      \
+                                                                                                   \
+            transpose_##TYPE_SUFFIX##_args_t args[num_threads];                                    \
+            size_t chunk_size = plan.num_tiles / num_threads;                                      \
+            size_t remaining = plan.num_tiles % num_threads;                                       \
+            for (size_t t = 0; t < num_threads; t++) {                                             \
+                args[t].input = in;                                                                \
+                args[t].output = out;                                                              \
+                args[t].plan = &plan;                                                              \
+                args[t].num_dims = num_dims;                                                       \
+                args[t].dims = dims;                                                               \
+                args[t].strides = strides;                                                         \
+                args[t].offset = offset;                                                           \
+                args[t].start = t * chunk_size + (t < remaining ? t : remaining);                  \
+                args[t].end = args[t].start + chunk_size + (t < remaining ? 1 : 0);                \
+            }                                                                                      \
+                                                                                                   \
+            if (num_threads > 1) {                                                                 \
+                thread_t threads[num_threads];                                                     \
+                for (size_t t = 0; t < num_threads; t++) {                                         \
+                    thread_create(&threads[t], transpose_##TYPE_SUFFIX##_worker, &args[t]);        \
+                }                                                                                  \
+                for (size_t t = 0; t < num_threads; t++) {                                         \
+                    thread_join(threads[t]);                                                       \
+                }                                                                                  \
+            } else {                                                                               \
+                transpose_##TYPE_SUFFIX##_worker(&args[0]);                                        \
+            }                                                                                      \
         } else {                                                                                   \
             /* Slow path: strided access */                                                        \
             const size_t min_work_per_thread = 100000;                                             \
@@ -0,0 +1,413 @@
+#include "ops_quant.h"
+#include "thread_utils.h"
+#include "types.h"
+#include <math.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// ============================================================================
+// QUANTIZE / DEQUANTIZE
+// ============================================================================
+//
+// Affine int8 quantization:
+//   quantize:   q = clamp(round_half_even(x / scale) + zero_point, -128, 127)
+//   dequantize: x = (q - zero_point) * scale
+//
+// The input may be strided; the output is always written contiguously.
+
+static inline int8_t quantize_value(float x, float scale, int32_t zero_point) {
+    float q = nearbyintf(x / scale) + (float)zero_point;
+    if (!(q >= -128.0f))
+        q = -128.0f;
+    if (q > 127.0f)
+        q = 127.0f;
+    return (int8_t)q;
+}
+
+#define QUANTIZE_OP(TYPE, TYPE_SUFFIX, TO_FLOAT)                                                   \
+    void hodu_cpu_quantize_##TYPE_SUFFIX(const void *input, void *output, const size_t *metadata,  \
+                                         float scale, int32_t zero_point) {                        \
+        const TYPE *in = (const TYPE *)input;                                                      \
+        int8_t *out = (int8_t *)output;                                                            \
+                                                                                                   \
+        const size_t num_els = metadata[0];                                                        \
+        const size_t num_dims = metadata[1];                                                       \
+        const size_t *dims = metadata + 2;                                                         \
+        const size_t *strides = metadata + 2 + num_dims;                                           \
+        const size_t offset = (num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;                     \
+                                                                                                   \
+        if (is_contiguous(num_dims, dims, strides)) {                                              \
+            for (size_t i = 0; i < num_els; i++) {                                                 \
+                TYPE x = in[offset + i];                                                           \
+                out[i] = quantize_value(TO_FLOAT, scale, zero_point);                              \
+            }                                                                                      \
+        } else {                                                                                   \
+            for (size_t i = 0; i < num_els; i++) {                                                 \
+                TYPE x = in[offset + get_strided_index(i, num_dims, dims, strides)];               \
+                out[i] = quantize_value(TO_FLOAT, scale, zero_point);                              \
+            }                                                                                      \
+        }                                                                                          \
+    }
+
+#define DEQUANTIZE_OP(TYPE, TYPE_SUFFIX, FROM_FLOAT)                                               \
+    void hodu_cpu_dequantize_##TYPE_SUFFIX(const void *input, void *output,                        \
+                                           const size_t *metadata, float scale,                    \
+                                           int32_t zero_point) {                                   \
+        const int8_t *in = (const int8_t *)input;                                                  \
+        TYPE *out = (TYPE *)output;                                                                \
+                                                                                                   \
+        const size_t num_els = metadata[0];                                                        \
+        const size_t num_dims = metadata[1];                                                       \
+        const size_t *dims = metadata + 2;                                                         \
+        const size_t *strides = metadata + 2 + num_dims;                                           \
+        const size_t offset = (num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;                     \
+                                                                                                   \
+        bool contiguous = is_contiguous(num_dims, dims, strides);                                  \
+        for (size_t i = 0; i < num_els; i++) {                                                     \
+            size_t idx = contiguous ? i : get_strided_index(i, num_dims, dims, strides);           \
+            float x = (float)((int32_t)in[offset + idx] - zero_point) * scale;                     \
+            out[i] = FROM_FLOAT;                                                                   \
+        }                                                                                          \
+    }
+
+QUANTIZE_OP(bf16_t, bf16, bf16_to_float(x))
+QUANTIZE_OP(f16_t, f16, f16_to_float(x))
+QUANTIZE_OP(f32_t, f32, x)
+QUANTIZE_OP(f64_t, f64, (float)x)
+
+DEQUANTIZE_OP(bf16_t, bf16, float_to_bf16(x))
+DEQUANTIZE_OP(f16_t, f16, float_to_f16(x))
+DEQUANTIZE_OP(f32_t, f32, x)
+DEQUANTIZE_OP(f64_t, f64, (f64_t)x)
+
+// ============================================================================
+// INT8 MATRIX MULTIPLICATION
+// ============================================================================
+//
+// Same metadata layout and broadcasting rules as hodu_cpu_matmul_*, but the products are widened to
+// int32 before accumulation so that K * 127 * 127 cannot overflow for any practical K.
+
+typedef struct {
+    const int8_t *lhs;
+    const int8_t *rhs;
+    int32_t *output;
+    size_t start_row;
+    size_t end_row;
+    size_t K, N;
+} matmul_int8_args_t;
+
+static void *matmul_int8_worker(void *arg) {
+    matmul_int8_args_t *args = (matmul_int8_args_t *)arg;
+    for (size_t i = args->start_row; i < args->end_row; i++) {
+        for (size_t j = 0; j < args->N; j++) {
+            int32_t sum = 0;
+            for (size_t k = 0; k < args->K; k++) {
+                sum += (int32_t)args->lhs[i * args->K + k] * (int32_t)args->rhs[k * args->N + j];
+            }
+            args->output[i * args->N + j] = sum;
+        }
+    }
+    return NULL;
+}
+
+void hodu_cpu_matmul_int8_i8(const void *lhs_ptr, const void *rhs_ptr, void *output_ptr,
+                             const size_t *metadata) {
+    const int8_t *lhs = (const int8_t *)lhs_ptr;
+    const int8_t *rhs = (const int8_t *)rhs_ptr;
+    int32_t *output = (int32_t *)output_ptr;
+
+    const size_t num_els = metadata[0];
+    const size_t lhs_ndim = metadata[1];
+    const size_t rhs_ndim = metadata[2];
+    const size_t batch_ndim = metadata[3];
+
+    const size_t *lhs_shape = metadata + 4;
+    const size_t *rhs_shape = lhs_shape + lhs_ndim;
+    const size_t *batch_shape = rhs_shape + rhs_ndim;
+    const size_t *lhs_strides = batch_shape + batch_ndim;
+    const size_t *rhs_strides = lhs_strides + lhs_ndim;
+    const size_t lhs_offset = *(rhs_strides + rhs_ndim);
+    const size_t rhs_offset = *(rhs_strides + rhs_ndim + 1);
+    const size_t M = *(rhs_strides + rhs_ndim + 2);
+    const size_t K = *(rhs_strides + rhs_ndim + 3);
+    const size_t N = *(rhs_strides + rhs_ndim + 4);
+
+    size_t lhs_batch_ndim = lhs_ndim - 2;
+    size_t rhs_batch_ndim = rhs_ndim - 2;
+
+    bool is_contiguous = (lhs_strides[lhs_ndim - 1] == 1 && rhs_strides[rhs_ndim - 1] == 1 &&
+                          lhs_strides[lhs_ndim - 2] == K && rhs_strides[rhs_ndim - 2] == N &&
+                          lhs_offset == 0 && rhs_offset == 0);
+
+    if (is_contiguous && batch_ndim == 0) {
+        /* Fast path: no batching, contiguous - split rows across threads */
+        size_t num_threads = get_optimal_threads(M, 128);
+        if (num_threads > 32)
+            num_threads = 32;
+        if (num_threads < 1)
+            num_threads = 1;
+
+        thread_t threads[32];
+        matmul_int8_args_t thread_args[32];
+        size_t rows_per_thread = M / num_threads;
+        size_t remaining_rows = M % num_threads;
+
+        for (size_t t = 0; t < num_threads; t++) {
+            thread_args[t].lhs = lhs;
+            thread_args[t].rhs = rhs;
+            thread_args[t].output = output;
+            thread_args[t].K = K;
+            thread_args[t].N = N;
+            thread_args[t].start_row = t * rows_per_thread;
+            thread_args[t].end_row = (t + 1) * rows_per_thread;
+            if (t == num_threads - 1)
+                thread_args[t].end_row += remaining_rows;
+        }
+
+        if (num_threads > 1) {
+            for (size_t t = 0; t < num_threads; t++) {
+                thread_create(&threads[t], matmul_int8_worker, &thread_args[t]);
+            }
+            for (size_t t = 0; t < num_threads; t++) {
+                thread_join(threads[t]);
+            }
+        } else {
+            matmul_int8_worker(&thread_args[0]);
+        }
+        return;
+    }
+
+    /* General path with batching/striding */
+    for (size_t idx = 0; idx < num_els; idx++) {
+        size_t mn = idx % (M * N);
+        size_t batch_idx = idx / (M * N);
+        size_t i = mn / N;
+        size_t j = mn % N;
+
+        size_t batch_indices[16];
+        size_t temp = batch_idx;
+        for (int d = (int)batch_ndim - 1; d >= 0; d--) {
+            batch_indices[d] = temp % batch_shape[d];
+            temp /= batch_shape[d];
+        }
+
+        size_t lhs_base = lhs_offset;
+        for (size_t d = 0; d < lhs_batch_ndim; d++) {
+            size_t batch_dim_idx = batch_ndim - lhs_batch_ndim + d;
+            size_t b = (lhs_shape[d] == 1) ? 0 : batch_indices[batch_dim_idx];
+            lhs_base += b * lhs_strides[d];
+        }
+        lhs_base += i * lhs_strides[lhs_ndim - 2];
+
+        size_t rhs_base = rhs_offset;
+        for (size_t d = 0; d < rhs_batch_ndim; d++) {
+            size_t batch_dim_idx = batch_ndim - rhs_batch_ndim + d;
+            size_t b = (rhs_shape[d] == 1) ? 0 : batch_indices[batch_dim_idx];
+            rhs_base += b * rhs_strides[d];
+        }
+        rhs_base += j * rhs_strides[rhs_ndim - 1];
+
+        int32_t sum = 0;
+        for (size_t k = 0; k < K; k++) {
+            int32_t a = lhs[lhs_base + k * lhs_strides[lhs_ndim - 1]];
+            int32_t b = rhs[rhs_base + k * rhs_strides[rhs_ndim - 2]];
+            sum += a * b;
+        }
+        output[idx] = sum;
+    }
+}
+
+// ============================================================================
+// 4-BIT BLOCK QUANTIZATION (Q4_0)
+// ============================================================================
+//
+// Every block of 32 consecutive values along the last dimension is stored in 18 bytes:
+//   bytes [0, 2):  scale as little-endian f16, scale = max|x| / 7
+//   bytes [2, 18): 4-bit codes, byte j holds value 2j in the low nibble and 2j+1 in the high one
+// and decodes as x = (q - 8) * scale.
+
+static inline float q4_0_block_scale(const uint8_t *block) {
+    return f16_to_float((f16_t)(block[0] | ((uint16_t)block[1] << 8)));
+}
+
+static inline void q4_0_decode_block(const uint8_t *block, float *dst) {
+    const float d = q4_0_block_scale(block);
+    const uint8_t *codes = block + 2;
+    for (size_t j = 0; j < Q4_0_BLOCK_SIZE / 2; j++) {
+        dst[2 * j] = (float)((int32_t)(codes[j] & 0x0F) - 8) * d;
+        dst[2 * j + 1] = (float)((int32_t)(codes[j] >> 4) - 8) * d;
+    }
+}
+
+static inline void q4_0_encode_block(const float *src, uint8_t *block) {
+    float amax = 0.0f;
+    for (size_t i = 0; i < Q4_0_BLOCK_SIZE; i++) {
+        float a = fabsf(src[i]);
+        if (a > amax)
+            amax = a;
+    }
+
+    // Quantize against the f16-rounded scale so that decoding reproduces the encoder's grid
+    f16_t d_bits = float_to_f16(amax / 7.0f);
+    block[0] = (uint8_t)(d_bits & 0xFF);
+    block[1] = (uint8_t)(d_bits >> 8);
+    const float d = f16_to_float(d_bits);
+
+    uint8_t *codes = block + 2;
+    for (size_t j = 0; j < Q4_0_BLOCK_SIZE / 2; j++) {
+        uint8_t q[2];
+        for (size_t h = 0; h < 2; h++) {
+            float v = d > 0.0f ? nearbyintf(src[2 * j + h] / d) + 8.0f : 8.0f;
+            if (!(v >= 0.0f))
+                v = 0.0f;
+            if (v > 15.0f)
+                v = 15.0f;
+            q[h] = (uint8_t)v;
+        }
+        codes[j] = (uint8_t)(q[0] | (q[1] << 4));
+    }
+}
+
+#define QUANTIZE_Q4_0_OP(TYPE, TYPE_SUFFIX, TO_FLOAT)                                              \
+    void hodu_cpu_quantize_q4_0_##TYPE_SUFFIX(const void *input, void *output,                     \
+                                              const size_t *metadata) {                            \
+        const TYPE *in = (const TYPE *)input;                                                      \
+        uint8_t *out = (uint8_t *)output;                                                          \
+                                                                                                   \
+        const size_t num_els = metadata[0];                                                        \
+        const size_t num_dims = metadata[1];                                                       \
+        const size_t *dims = metadata + 2;                                                         \
+        const size_t *strides = metadata + 2 + num_dims;                                           \
+        const size_t offset = (num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;                     \
+                                                                                                   \
+        bool contiguous = is_contiguous(num_dims, dims, strides);                                  \
+        float values[Q4_0_BLOCK_SIZE];                                                             \
+        for (size_t b = 0; b < num_els / Q4_0_BLOCK_SIZE; b++) {                                   \
+            for (size_t i = 0; i < Q4_0_BLOCK_SIZE; i++) {                                         \
+                size_t flat = b * Q4_0_BLOCK_SIZE + i;                                             \
+                size_t idx = contiguous ? flat : get_strided_index(flat, num_dims, dims, strides); \
+                TYPE x = in[offset + idx];                                                         \
+                values[i] = TO_FLOAT;                                                              \
+            }                                                                                      \
+            q4_0_encode_block(values, out + b * Q4_0_BLOCK_BYTES);                                 \
+        }                                                                                          \
+    }
+
+#define DEQUANTIZE_Q4_0_OP(TYPE, TYPE_SUFFIX, FROM_FLOAT)                                          \
+    void hodu_cpu_dequantize_q4_0_##TYPE_SUFFIX(const void *input, void *output,                   \
+                                                const size_t *metadata) {                          \
+        const uint8_t *in = (const uint8_t *)input + metadata[1];                                  \
+        TYPE *out = (TYPE *)output;                                                                \
+                                                                                                   \
+        const size_t num_blocks = metadata[0];                                                     \
+        float values[Q4_0_BLOCK_SIZE];                                                             \
+        for (size_t b = 0; b < num_blocks; b++) {                                                  \
+            q4_0_decode_block(in + b * Q4_0_BLOCK_BYTES, values);                                  \
+            for (size_t i = 0; i < Q4_0_BLOCK_SIZE; i++) {                                         \
+                float x = values[i];                                                               \
+                out[b * Q4_0_BLOCK_SIZE + i] = FROM_FLOAT;                                         \
+            }                                                                                      \
+        }                                                                                          \
+    }
+
+QUANTIZE_Q4_0_OP(bf16_t, bf16, bf16_to_float(x))
+QUANTIZE_Q4_0_OP(f16_t, f16, f16_to_float(x))
+QUANTIZE_Q4_0_OP(f32_t, f32, x)
+QUANTIZE_Q4_0_OP(f64_t, f64, (float)x)
+
+DEQUANTIZE_Q4_0_OP(bf16_t, bf16, float_to_bf16(x))
+DEQUANTIZE_Q4_0_OP(f16_t, f16, float_to_f16(x))
+DEQUANTIZE_Q4_0_OP(f32_t, f32, x)
+DEQUANTIZE_Q4_0_OP(f64_t, f64, (f64_t)x)
+
+// ============================================================================
+// FUSED Q4_0 DEQUANT-MATMUL
+// ============================================================================
+//
+// out[M, N] = lhs[M, K] @ dequant(weight[N, K])^T. Work is split over the N weight rows: each
+// thread decodes one weight row at a time into a float scratch row and reuses it for all M lhs
+// rows, so the packed weights are read exactly once.
+
+#define MATMUL_Q4_0_OP(TYPE, TYPE_SUFFIX, TO_FLOAT, FROM_FLOAT)                                    \
+    typedef struct {                                                                               \
+        const TYPE *lhs;                                                                           \
+        const uint8_t *weight;                                                                     \
+        TYPE *output;                                                                              \
+        size_t M, K, N;                                                                            \
+        size_t start_col, end_col;                                                                 \
+    } matmul_q4_0_##TYPE_SUFFIX##_args_t;                                                          \
+                                                                                                   \
+    static void *matmul_q4_0_##TYPE_SUFFIX##_worker(void *arg) {                                   \
+        matmul_q4_0_##TYPE_SUFFIX##_args_t *args = (matmul_q4_0_##TYPE_SUFFIX##_args_t *)arg;      \
+        const size_t M = args->M, K = args->K, N = args->N;                                        \
+        const size_t row_bytes = K / Q4_0_BLOCK_SIZE * Q4_0_BLOCK_BYTES;                           \
+        float *w = (float *)malloc(K * sizeof(float));                                             \
+        if (!w)                                                                                    \
+            return NULL;                                                                           \
+        for (size_t n = args->start_col; n < args->end_col; n++) {                                 \
+            const uint8_t *w_row = args->weight + n * row_bytes;                                   \
+            for (size_t b = 0; b < K / Q4_0_BLOCK_SIZE; b++) {                                     \
+                q4_0_decode_block(w_row + b * Q4_0_BLOCK_BYTES, w + b * Q4_0_BLOCK_SIZE);          \
+            }                                                                                      \
+            for (size_t i = 0; i < M; i++) {                                                       \
+                const TYPE *x_row = args->lhs + i * K;                                             \
+                float acc = 0.0f;                                                                  \
+                for (size_t k = 0; k < K; k++) {                                                   \
+                    TYPE x = x_row[k];                                                             \
+                    acc += (TO_FLOAT) * w[k];                                                      \
+                }                                                                                  \
+                float x = acc;                                                                     \
+                args->output[i * N + n] = FROM_FLOAT;                                              \
+            }                                                                                      \
+        }                                                                                          \
+        free(w);                                                                                   \
+        return NULL;                                                                               \
+    }                                                                                              \
+                                                                                                   \
+    void hodu_cpu_matmul_q4_0_##TYPE_SUFFIX(const void *lhs, const void *weight, void *output,     \
+                                            const size_t *metadata) {                              \
+        const size_t M = metadata[0];                                                              \
+        const size_t K = metadata[1];                                                              \
+        const size_t N = metadata[2];                                                              \
+                                                                                                   \
+        size_t num_threads = get_optimal_threads(N, 16);                                           \
+        if (num_threads > 32)                                                                      \
+            num_threads = 32;                                                                      \
+        if (num_threads < 1)                                                                       \
+            num_threads = 1;                                                                       \
+                                                                                                   \
+        thread_t threads[32];                                                                      \
+        matmul_q4_0_##TYPE_SUFFIX##_args_t thread_args[32];                                        \
+        size_t cols_per_thread = N / num_threads;                                                  \
+        size_t remaining_cols = N % num_threads;                                                   \
+                                                                                                   \
+        for (size_t t = 0; t < num_threads; t++) {                                                 \
+            thread_args[t].lhs = (const TYPE *)lhs + metadata[3];                                  \
+            thread_args[t].weight = (const uint8_t *)weight + metadata[4];                         \
+            thread_args[t].output = (TYPE *)output;                                                \
+            thread_args[t].M = M;                                                                  \
+            thread_args[t].K = K;                                                                  \
+            thread_args[t].N = N;                                                                  \
+            thread_args[t].start_col = t * cols_per_thread;                                        \
+            thread_args[t].end_col = (t + 1) * cols_per_thread;                                    \
+            if (t == num_threads - 1)                                                              \
+                thread_args[t].end_col += remaining_cols;                                          \
+        }                                                                                          \
+                                                                                                   \
+        if (num_threads > 1) {                                                                     \
+            for (size_t t = 0; t < num_threads; t++) {                                             \
+                thread_create(&threads[t], matmul_q4_0_##TYPE_SUFFIX##_worker, &thread_args[t]);   \
+            }                                                                                      \
+            for (size_t t = 0; t < num_threads; t++) {                                             \
+                thread_join(threads[t]);                                                           \
+            }                                                                                      \
+        } else {                                                                                   \
+            matmul_q4_0_##TYPE_SUFFIX##_worker(&thread_args[0]);                                   \
+        }                                                                                          \
+    }
+
+MATMUL_Q4_0_OP(bf16_t, bf16, bf16_to_float(x), float_to_bf16(x))
+MATMUL_Q4_0_OP(f16_t, f16, f16_to_float(x), float_to_f16(x))
+MATMUL_Q4_0_OP(f32_t, f32, x, x)
+MATMUL_Q4_0_OP(f64_t, f64, (float)x, (f64_t)x)
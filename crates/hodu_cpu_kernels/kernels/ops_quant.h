@@ -0,0 +1,131 @@
+/**
+ * @file ops_quant.h
+ * @brief Int8 quantization operations header
+ *
+ * Provides affine int8 quantization operations:
+ * - quantize: Map floating-point values to int8 (q = clamp(round(x / scale) + zero_point))
+ * - dequantize: Map int8 values back to floating point (x = (q - zero_point) * scale)
+ * - matmul_int8: Batched int8 x int8 matrix multiplication accumulated in int32
+ *
+ * Symmetric quantization is the special case zero_point = 0.
+ *
+ * And 4-bit block quantization of weights (Q4_0):
+ * - quantize_q4_0: Pack blocks of 32 floating-point values into 18 bytes each
+ * - dequantize_q4_0: Unpack Q4_0 blocks to floating point
+ * - matmul_q4_0: Fused dequant-matmul against a packed [N, K] weight
+ */
+
+#ifndef HODU_CPU_KERNELS_OPS_QUANT_H
+#define HODU_CPU_KERNELS_OPS_QUANT_H
+
+#include "utils.h"
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// ============================================================================
+// QUANTIZE / DEQUANTIZE
+// ============================================================================
+//
+// Quantize kernels are named after the floating-point source type, dequantize kernels after the
+// floating-point destination type. The int8 side is always i8.
+//
+// Parameters:
+//   input      - Pointer to input tensor data (may be strided)
+//   output     - Pointer to contiguous output buffer (pre-allocated)
+//   metadata   - Array describing the input layout (see below)
+//   scale      - Quantization step
+//   zero_point - Integer value that represents 0.0
+//
+// Metadata layout:
+// - metadata[0]: num_els (total number of elements)
+// - metadata[1]: num_dims (number of dimensions)
+// - metadata[2..2+num_dims]: shape
+// - metadata[2+num_dims..2+2*num_dims]: strides
+// - metadata[2+2*num_dims]: offset
+
+void hodu_cpu_quantize_bf16(const void *input, void *output, const size_t *metadata, float scale,
+                            int32_t zero_point);
+void hodu_cpu_quantize_f16(const void *input, void *output, const size_t *metadata, float scale,
+                           int32_t zero_point);
+void hodu_cpu_quantize_f32(const void *input, void *output, const size_t *metadata, float scale,
+                           int32_t zero_point);
+void hodu_cpu_quantize_f64(const void *input, void *output, const size_t *metadata, float scale,
+                           int32_t zero_point);
+
+void hodu_cpu_dequantize_bf16(const void *input, void *output, const size_t *metadata,
+                              float scale, int32_t zero_point);
+void hodu_cpu_dequantize_f16(const void *input, void *output, const size_t *metadata, float scale,
+                             int32_t zero_point);
+void hodu_cpu_dequantize_f32(const void *input, void *output, const size_t *metadata, float scale,
+                             int32_t zero_point);
+void hodu_cpu_dequantize_f64(const void *input, void *output, const size_t *metadata, float scale,
+                             int32_t zero_point);
+
+// ============================================================================
+// INT8 MATRIX MULTIPLICATION
+// ============================================================================
+//
+// Computes C[..., i, j] = sum_k (int32)A[..., i, k] * (int32)B[..., k, j] with i8 inputs and an
+// i32 output. Uses the same metadata layout as hodu_cpu_matmul_*.
+
+void hodu_cpu_matmul_int8_i8(const void *lhs, const void *rhs, void *output,
+                             const size_t *metadata);
+
+// ============================================================================
+// 4-BIT BLOCK QUANTIZATION (Q4_0)
+// ============================================================================
+//
+// A Q4_0 block holds 32 consecutive values along the last dimension in 18 bytes: a little-endian
+// f16 scale (max|x| / 7) followed by 16 bytes of 4-bit codes (value 2j in the low nibble of byte
+// j, value 2j+1 in the high nibble). Values decode as x = (code - 8) * scale.
+//
+// quantize_q4_0 metadata: same layout as quantize; num_els and the last dimension must be
+// multiples of Q4_0_BLOCK_SIZE.
+//
+// dequantize_q4_0 metadata:
+// - metadata[0]: num_blocks
+// - metadata[1]: byte offset of the first block
+
+#define Q4_0_BLOCK_SIZE 32
+#define Q4_0_BLOCK_BYTES 18
+
+void hodu_cpu_quantize_q4_0_bf16(const void *input, void *output, const size_t *metadata);
+void hodu_cpu_quantize_q4_0_f16(const void *input, void *output, const size_t *metadata);
+void hodu_cpu_quantize_q4_0_f32(const void *input, void *output, const size_t *metadata);
+void hodu_cpu_quantize_q4_0_f64(const void *input, void *output, const size_t *metadata);
+
+void hodu_cpu_dequantize_q4_0_bf16(const void *input, void *output, const size_t *metadata);
+void hodu_cpu_dequantize_q4_0_f16(const void *input, void *output, const size_t *metadata);
+void hodu_cpu_dequantize_q4_0_f32(const void *input, void *output, const size_t *metadata);
+void hodu_cpu_dequantize_q4_0_f64(const void *input, void *output, const size_t *metadata);
+
+// ============================================================================
+// FUSED Q4_0 DEQUANT-MATMUL
+// ============================================================================
+//
+// Computes out[M, N] = lhs[M, K] @ dequant(weight)^T where weight is a packed [N, K] Q4_0 matrix
+// (N rows of K / 32 blocks). lhs must be contiguous; leading batch dimensions are folded into M.
+//
+// Metadata layout:
+// - metadata[0]: M (lhs rows)
+// - metadata[1]: K (reduction size, multiple of Q4_0_BLOCK_SIZE)
+// - metadata[2]: N (weight rows)
+// - metadata[3]: lhs offset (elements)
+// - metadata[4]: weight offset (bytes)
+
+void hodu_cpu_matmul_q4_0_bf16(const void *lhs, const void *weight, void *output,
+                               const size_t *metadata);
+void hodu_cpu_matmul_q4_0_f16(const void *lhs, const void *weight, void *output,
+                              const size_t *metadata);
+void hodu_cpu_matmul_q4_0_f32(const void *lhs, const void *weight, void *output,
+                              const size_t *metadata);
+void hodu_cpu_matmul_q4_0_f64(const void *lhs, const void *weight, void *output,
+                              const size_t *metadata);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // HODU_CPU_KERNELS_OPS_QUANT_H
@@ -816,315 +816,48 @@ REDUCE_OP(uint32_t, uint32_t, prod_u32, 1u, acc *= val)
 REDUCE_OP(uint64_t, uint64_t, prod_u64, 1u, acc *= val)
 
 // ============================================================================
-// STANDARD DEVIATION REDUCTION
+// MEAN, VARIANCE AND STANDARD DEVIATION REDUCTIONS
 // ============================================================================
 //
-// Computes population standard deviation: sqrt(E[X²] - E[X]²)
-// Two-pass algorithm: accumulate sum and sum of squares, then compute std.
+// Accumulated with Welford's online algorithm in double precision: a running mean and a running
+// sum of squared deviations from it. Unlike E[X²] - E[X]², the variance stays accurate when the
+// mean is large relative to the spread, and low precision inputs are never summed in their own
+// type. var and std are population statistics.
 //
 // Metadata layout: Same as generic reduction operations
 
-/// Macro to implement standard deviation reduction (float types only)
-///
-/// @param TYPE C float type
-/// @param TYPE_SUFFIX Suffix for function naming
-#define REDUCE_STD_OP(TYPE, TYPE_SUFFIX)                                                           \
-    void hodu_cpu_std_##TYPE_SUFFIX(const void *input_ptr, void *output_ptr,                       \
-                                    const size_t *metadata) {                                      \
-        const TYPE *input = (const TYPE *)input_ptr;                                               \
-        TYPE *output = (TYPE *)output_ptr;                                                         \
-                                                                                                   \
-        const size_t num_dims = metadata[0];                                                       \
-        const size_t *dims = metadata + 1;                                                         \
-        const size_t *strides = metadata + 1 + num_dims;                                           \
-        const size_t offset = metadata[1 + 2 * num_dims];                                          \
-        const size_t output_shape_len = metadata[2 + 2 * num_dims];                                \
-        const size_t *output_shape = metadata + 3 + 2 * num_dims;                                  \
-        const size_t num_reduce_dims = metadata[3 + 2 * num_dims + output_shape_len];              \
-        const size_t *reduce_dims = metadata + 4 + 2 * num_dims + output_shape_len;                \
-        const size_t keep_dim_val =                                                                \
-            metadata[4 + 2 * num_dims + output_shape_len + num_reduce_dims];                       \
-        const bool keep_dim = (keep_dim_val != 0);                                                 \
-        const size_t reduce_size =                                                                 \
-            metadata[5 + 2 * num_dims + output_shape_len + num_reduce_dims];                       \
-        size_t num_els = 1;                                                                        \
-        for (size_t i = 0; i < output_shape_len; i++) {                                            \
-            num_els *= output_shape[i];                                                            \
-        }                                                                                          \
-                                                                                                   \
-        for (size_t output_idx = 0; output_idx < num_els; output_idx++) {                          \
-            TYPE sum = 0;                                                                          \
-            TYPE sum_squares = 0;                                                                  \
-                                                                                                   \
-            size_t output_indices[16];                                                             \
-            size_t temp = output_idx;                                                              \
-            for (int d = (int)output_shape_len - 1; d >= 0; d--) {                                 \
-                output_indices[d] = temp % output_shape[d];                                        \
-                temp /= output_shape[d];                                                           \
-            }                                                                                      \
-                                                                                                   \
-            size_t input_indices[16];                                                              \
-            if (keep_dim) {                                                                        \
-                for (size_t i = 0; i < num_dims; i++) {                                            \
-                    input_indices[i] = output_indices[i];                                          \
-                }                                                                                  \
-            } else {                                                                               \
-                size_t out_idx = 0;                                                                \
-                for (size_t in_dim = 0; in_dim < num_dims; in_dim++) {                             \
-                    bool is_reduced = false;                                                       \
-                    for (size_t r = 0; r < num_reduce_dims; r++) {                                 \
-                        if (reduce_dims[r] == in_dim) {                                            \
-                            is_reduced = true;                                                     \
-                            break;                                                                 \
-                        }                                                                          \
-                    }                                                                              \
-                    if (is_reduced) {                                                              \
-                        input_indices[in_dim] = 0;                                                 \
-                    } else {                                                                       \
-                        input_indices[in_dim] =                                                    \
-                            (out_idx < output_shape_len) ? output_indices[out_idx] : 0;            \
-                        out_idx++;                                                                 \
-                    }                                                                              \
-                }                                                                                  \
-            }                                                                                      \
-                                                                                                   \
-            for (size_t reduced_idx = 0; reduced_idx < reduce_size; reduced_idx++) {               \
-                size_t temp_reduced = reduced_idx;                                                 \
-                for (int i = (int)num_reduce_dims - 1; i >= 0; i--) {                              \
-                    size_t dim = reduce_dims[i];                                                   \
-                    input_indices[dim] = temp_reduced % dims[dim];                                 \
-                    temp_reduced /= dims[dim];                                                     \
-                }                                                                                  \
-                                                                                                   \
-                size_t flat_index = offset;                                                        \
-                for (size_t i = 0; i < num_dims; i++) {                                            \
-                    flat_index += input_indices[i] * strides[i];                                   \
-                }                                                                                  \
-                                                                                                   \
-                TYPE val = input[flat_index];                                                      \
-                sum += val;                                                                        \
-                sum_squares += val * val;                                                          \
-            }                                                                                      \
-                                                                                                   \
-            TYPE mean = sum / (TYPE)reduce_size;                                                   \
-            TYPE variance = (sum_squares / (TYPE)reduce_size) - (mean * mean);                     \
-            output[output_idx] = sqrt(variance);                                                   \
-        }                                                                                          \
-    }
-
-/// Macro to implement standard deviation reduction for exotic types
-///
-/// @param TYPE C float type
-/// @param TYPE_SUFFIX Suffix for function naming
-/// @param ZERO Zero constant for the type
-/// @param ADD_FN Addition function
-/// @param MUL_FN Multiplication function
-/// @param DIV_FN Division function
-/// @param SUB_FN Subtraction function
-/// @param SQRT_FN Square root function
-#define REDUCE_STD_OP_EXOTIC(TYPE, TYPE_SUFFIX, ZERO, ADD_FN, MUL_FN, DIV_FN, SUB_FN, SQRT_FN)     \
-    void hodu_cpu_std_##TYPE_SUFFIX(const void *input_ptr, void *output_ptr,                       \
-                                    const size_t *metadata) {                                      \
-        const TYPE *input = (const TYPE *)input_ptr;                                               \
-        TYPE *output = (TYPE *)output_ptr;                                                         \
-                                                                                                   \
-        const size_t num_dims = metadata[0];                                                       \
-        const size_t *dims = metadata + 1;                                                         \
-        const size_t *strides = metadata + 1 + num_dims;                                           \
-        const size_t offset = metadata[1 + 2 * num_dims];                                          \
-        const size_t output_shape_len = metadata[2 + 2 * num_dims];                                \
-        const size_t *output_shape = metadata + 3 + 2 * num_dims;                                  \
-        const size_t num_reduce_dims = metadata[3 + 2 * num_dims + output_shape_len];              \
-        const size_t *reduce_dims = metadata + 4 + 2 * num_dims + output_shape_len;                \
-        const size_t keep_dim_val =                                                                \
-            metadata[4 + 2 * num_dims + output_shape_len + num_reduce_dims];                       \
-        const bool keep_dim = (keep_dim_val != 0);                                                 \
-        const size_t reduce_size =                                                                 \
-            metadata[5 + 2 * num_dims + output_shape_len + num_reduce_dims];                       \
-        size_t num_els = 1;                                                                        \
-        for (size_t i = 0; i < output_shape_len; i++) {                                            \
-            num_els *= output_shape[i];                                                            \
-        }                                                                                          \
-                                                                                                   \
-        for (size_t output_idx = 0; output_idx < num_els; output_idx++) {                          \
-            TYPE sum = ZERO;                                                                       \
-            TYPE sum_squares = ZERO;                                                               \
-                                                                                                   \
-            size_t output_indices[16];                                                             \
-            size_t temp = output_idx;                                                              \
-            for (int d = (int)output_shape_len - 1; d >= 0; d--) {                                 \
-                output_indices[d] = temp % output_shape[d];                                        \
-                temp /= output_shape[d];                                                           \
-            }                                                                                      \
-                                                                                                   \
-            size_t input_indices[16];                                                              \
-            if (keep_dim) {                                                                        \
-                for (size_t i = 0; i < num_dims; i++) {                                            \
-                    input_indices[i] = output_indices[i];                                          \
-                }                                                                                  \
-            } else {                                                                               \
-                size_t out_idx = 0;                                                                \
-                for (size_t in_dim = 0; in_dim < num_dims; in_dim++) {                             \
-                    bool is_reduced = false;                                                       \
-                    for (size_t r = 0; r < num_reduce_dims; r++) {                                 \
-                        if (reduce_dims[r] == in_dim) {                                            \
-                            is_reduced = true;                                                     \
-                            break;                                                                 \
-                        }                                                                          \
-                    }                                                                              \
-                    if (is_reduced) {                                                              \
-                        input_indices[in_dim] = 0;                                                 \
-                    } else {                                                                       \
-                        input_indices[in_dim] =                                                    \
-                            (out_idx < output_shape_len) ? output_indices[out_idx] : 0;            \
-                        out_idx++;                                                                 \
-                    }                                                                              \
-                }                                                                                  \
-            }                                                                                      \
-                                                                                                   \
-            for (size_t reduced_idx = 0; reduced_idx < reduce_size; reduced_idx++) {               \
-                size_t temp_reduced = reduced_idx;                                                 \
-                for (int i = (int)num_reduce_dims - 1; i >= 0; i--) {                              \
-                    size_t dim = reduce_dims[i];                                                   \
-                    input_indices[dim] = temp_reduced % dims[dim];                                 \
-                    temp_reduced /= dims[dim];                                                     \
-                }                                                                                  \
-                                                                                                   \
-                size_t flat_index = offset;                                                        \
-                for (size_t i = 0; i < num_dims; i++) {                                            \
-                    flat_index += input_indices[i] * strides[i];                                   \
-                }                                                                                  \
-                                                                                                   \
-                TYPE val = input[flat_index];                                                      \
-                sum = ADD_FN(sum, val);                                                            \
-                sum_squares = ADD_FN(sum_squares, MUL_FN(val, val));                               \
-            }                                                                                      \
-                                                                                                   \
-            float reduce_size_f = (float)reduce_size;                                              \
-            TYPE reduce_size_typed = float_to_##TYPE_SUFFIX(reduce_size_f);                        \
-            TYPE mean = DIV_FN(sum, reduce_size_typed);                                            \
-            TYPE variance = SUB_FN(DIV_FN(sum_squares, reduce_size_typed), MUL_FN(mean, mean));    \
-            output[output_idx] = SQRT_FN(variance);                                                \
-        }                                                                                          \
-    }
+/// Running mean and sum of squared deviations from it
+typedef struct {
+    size_t count;
+    double mean;
+    double m2;
+} welford_t;
+
+static inline void welford_update(welford_t *w, double x) {
+    w->count++;
+    double delta = x - w->mean;
+    w->mean += delta / (double)w->count;
+    w->m2 += delta * (x - w->mean);
+}
 
-REDUCE_STD_OP_EXOTIC(f8e4m3_t, f8e4m3, F8E4M3_ZERO, f8e4m3_add, f8e4m3_mul, f8e4m3_div, f8e4m3_sub,
-                     f8e4m3_sqrt)
-REDUCE_STD_OP_EXOTIC(f8e5m2_t, f8e5m2, F8E5M2_ZERO, f8e5m2_add, f8e5m2_mul, f8e5m2_div, f8e5m2_sub,
-                     f8e5m2_sqrt)
-REDUCE_STD_OP_EXOTIC(bf16_t, bf16, BF16_ZERO, bf16_add, bf16_mul, bf16_div, bf16_sub, bf16_sqrt)
-REDUCE_STD_OP_EXOTIC(f16_t, f16, F16_ZERO, f16_add, f16_mul, f16_div, f16_sub, f16_sqrt)
-REDUCE_STD_OP(f32_t, f32)
-REDUCE_STD_OP(f64_t, f64)
+static inline double welford_mean(welford_t w) { return w.count > 0 ? w.mean : NAN; }
+static inline double welford_var(welford_t w) {
+    return w.count > 0 ? w.m2 / (double)w.count : NAN;
+}
+static inline double welford_std(welford_t w) { return sqrt(welford_var(w)); }
 
-// ============================================================================
-// VARIANCE REDUCTION
-// ============================================================================
-//
-// Computes population variance: E[X²] - E[X]²
-// Same as std but without the sqrt.
-//
-// Metadata layout: Same as generic reduction operations
+#define TO_DOUBLE(x) ((double)(x))
 
-/// Macro to implement variance reduction (float types only)
+/// Macro to implement a Welford-accumulated reduction
 ///
 /// @param TYPE C float type
+/// @param OP_NAME Reduction name (mean, var or std)
 /// @param TYPE_SUFFIX Suffix for function naming
-#define REDUCE_VAR_OP(TYPE, TYPE_SUFFIX)                                                           \
-    void hodu_cpu_var_##TYPE_SUFFIX(const void *input_ptr, void *output_ptr,                       \
-                                    const size_t *metadata) {                                      \
-        const TYPE *input = (const TYPE *)input_ptr;                                               \
-        TYPE *output = (TYPE *)output_ptr;                                                         \
-                                                                                                   \
-        const size_t num_dims = metadata[0];                                                       \
-        const size_t *dims = metadata + 1;                                                         \
-        const size_t *strides = metadata + 1 + num_dims;                                           \
-        const size_t offset = metadata[1 + 2 * num_dims];                                          \
-        const size_t output_shape_len = metadata[2 + 2 * num_dims];                                \
-        const size_t *output_shape = metadata + 3 + 2 * num_dims;                                  \
-        const size_t num_reduce_dims = metadata[3 + 2 * num_dims + output_shape_len];              \
-        const size_t *reduce_dims = metadata + 4 + 2 * num_dims + output_shape_len;                \
-        const size_t keep_dim_val =                                                                \
-            metadata[4 + 2 * num_dims + output_shape_len + num_reduce_dims];                       \
-        const bool keep_dim = (keep_dim_val != 0);                                                 \
-        const size_t reduce_size =                                                                 \
-            metadata[5 + 2 * num_dims + output_shape_len + num_reduce_dims];                       \
-        size_t num_els = 1;                                                                        \
-        for (size_t i = 0; i < output_shape_len; i++) {                                            \
-            num_els *= output_shape[i];                                                            \
-        }                                                                                          \
-                                                                                                   \
-        for (size_t output_idx = 0; output_idx < num_els; output_idx++) {                          \
-            TYPE sum = 0;                                                                          \
-            TYPE sum_squares = 0;                                                                  \
-                                                                                                   \
-            size_t output_indices[16];                                                             \
-            size_t temp = output_idx;                                                              \
-            for (int d = (int)output_shape_len - 1; d >= 0; d--) {                                 \
-                output_indices[d] = temp % output_shape[d];                                        \
-                temp /= output_shape[d];                                                           \
-            }                                                                                      \
-                                                                                                   \
-            size_t input_indices[16];                                                              \
-            if (keep_dim) {                                                                        \
-                for (size_t i = 0; i < num_dims; i++) {                                            \
-                    input_indices[i] = output_indices[i];                                          \
-                }                                                                                  \
-            } else {                                                                               \
-                size_t out_idx = 0;                                                                \
-                for (size_t in_dim = 0; in_dim < num_dims; in_dim++) {                             \
-                    bool is_reduced = false;                                                       \
-                    for (size_t r = 0; r < num_reduce_dims; r++) {                                 \
-                        if (reduce_dims[r] == in_dim) {                                            \
-                            is_reduced = true;                                                     \
-                            break;                                                                 \
-                        }                                                                          \
-                    }                                                                              \
-                    if (is_reduced) {                                                              \
-                        input_indices[in_dim] = 0;                                                 \
-                    } else {                                                                       \
-                        input_indices[in_dim] =                                                    \
-                            (out_idx < output_shape_len) ? output_indices[out_idx] : 0;            \
-                        out_idx++;                                                                 \
-                    }                                                                              \
-                }                                                                                  \
-            }                                                                                      \
-                                                                                                   \
-            for (size_t reduced_idx = 0; reduced_idx < reduce_size; reduced_idx++) {               \
-                size_t temp_reduced = reduced_idx;                                                 \
-                for (int i = (int)num_reduce_dims - 1; i >= 0; i--) {                              \
-                    size_t dim = reduce_dims[i];                                                   \
-                    input_indices[dim] = temp_reduced % dims[dim];                                 \
-                    temp_reduced /= dims[dim];                                                     \
-                }                                                                                  \
-                                                                                                   \
-                size_t flat_index = offset;                                                        \
-                for (size_t i = 0; i < num_dims; i++) {                                            \
-                    flat_index += input_indices[i] * strides[i];                                   \
-                }                                                                                  \
-                                                                                                   \
-                TYPE val = input[flat_index];                                                      \
-                sum += val;                                                                        \
-                sum_squares += val * val;                                                          \
-            }                                                                                      \
-                                                                                                   \
-            TYPE mean = sum / (TYPE)reduce_size;                                                   \
-            output[output_idx] = (sum_squares / (TYPE)reduce_size) - (mean * mean);                \
-        }                                                                                          \
-    }
-
-/// Macro to implement variance reduction for exotic types
-///
-/// @param TYPE C float type
-/// @param TYPE_SUFFIX Suffix for function naming
-/// @param ZERO Zero constant for the type
-/// @param ADD_FN Addition function
-/// @param MUL_FN Multiplication function
-/// @param DIV_FN Division function
-/// @param SUB_FN Subtraction function
-#define REDUCE_VAR_OP_EXOTIC(TYPE, TYPE_SUFFIX, ZERO, ADD_FN, MUL_FN, DIV_FN, SUB_FN)              \
-    void hodu_cpu_var_##TYPE_SUFFIX(const void *input_ptr, void *output_ptr,                       \
-                                    const size_t *metadata) {                                      \
+/// @param TO_DOUBLE_FN Conversion from TYPE to double
+/// @param FROM_DOUBLE_FN Conversion from double to TYPE
+#define REDUCE_WELFORD_OP(TYPE, OP_NAME, TYPE_SUFFIX, TO_DOUBLE_FN, FROM_DOUBLE_FN)                \
+    void hodu_cpu_##OP_NAME##_##TYPE_SUFFIX(const void *input_ptr, void *output_ptr,               \
+                                            const size_t *metadata) {                              \
         const TYPE *input = (const TYPE *)input_ptr;                                               \
         TYPE *output = (TYPE *)output_ptr;                                                         \
                                                                                                    \
@@ -1147,8 +880,7 @@ REDUCE_STD_OP(f64_t, f64)
         }                                                                                          \
                                                                                                    \
         for (size_t output_idx = 0; output_idx < num_els; output_idx++) {                          \
-            TYPE sum = ZERO;                                                                       \
-            TYPE sum_squares = ZERO;                                                               \
+            welford_t w = {0, 0.0, 0.0};                                                           \
                                                                                                    \
             size_t output_indices[16];                                                             \
             size_t temp = output_idx;                                                              \
@@ -1195,313 +927,25 @@ REDUCE_STD_OP(f64_t, f64)
                     flat_index += input_indices[i] * strides[i];                                   \
                 }                                                                                  \
                                                                                                    \
-                TYPE val = input[flat_index];                                                      \
-                sum = ADD_FN(sum, val);                                                            \
-                sum_squares = ADD_FN(sum_squares, MUL_FN(val, val));                               \
+                welford_update(&w, TO_DOUBLE_FN(input[flat_index]));                               \
             }                                                                                      \
                                                                                                    \
-            float reduce_size_f = (float)reduce_size;                                              \
-            TYPE reduce_size_typed = float_to_##TYPE_SUFFIX(reduce_size_f);                        \
-            TYPE mean = DIV_FN(sum, reduce_size_typed);                                            \
-            output[output_idx] =                                                                   \
-                SUB_FN(DIV_FN(sum_squares, reduce_size_typed), MUL_FN(mean, mean));                \
-        }                                                                                          \
-    }
-
-REDUCE_VAR_OP_EXOTIC(f8e4m3_t, f8e4m3, F8E4M3_ZERO, f8e4m3_add, f8e4m3_mul, f8e4m3_div, f8e4m3_sub)
-REDUCE_VAR_OP_EXOTIC(f8e5m2_t, f8e5m2, F8E5M2_ZERO, f8e5m2_add, f8e5m2_mul, f8e5m2_div, f8e5m2_sub)
-REDUCE_VAR_OP_EXOTIC(bf16_t, bf16, BF16_ZERO, bf16_add, bf16_mul, bf16_div, bf16_sub)
-REDUCE_VAR_OP_EXOTIC(f16_t, f16, F16_ZERO, f16_add, f16_mul, f16_div, f16_sub)
-
-// SIMD-optimized var_f32 (variance: E[X^2] - E[X]^2)
-void hodu_cpu_var_f32(const void *input_ptr, void *output_ptr, const size_t *metadata) {
-    const f32_t *input = (const f32_t *)input_ptr;
-    f32_t *output = (f32_t *)output_ptr;
-
-    const size_t num_dims = metadata[0];
-    const size_t *dims = metadata + 1;
-    const size_t *strides = metadata + 1 + num_dims;
-    const size_t offset = metadata[1 + 2 * num_dims];
-    const size_t output_shape_len = metadata[2 + 2 * num_dims];
-    const size_t *output_shape = metadata + 3 + 2 * num_dims;
-    const size_t num_reduce_dims = metadata[3 + 2 * num_dims + output_shape_len];
-    const size_t *reduce_dims = metadata + 4 + 2 * num_dims + output_shape_len;
-    const size_t keep_dim_val = metadata[4 + 2 * num_dims + output_shape_len + num_reduce_dims];
-    const bool keep_dim = (keep_dim_val != 0);
-    const size_t reduce_size = metadata[5 + 2 * num_dims + output_shape_len + num_reduce_dims];
-
-    size_t num_els = 1;
-    for (size_t i = 0; i < output_shape_len; i++) {
-        num_els *= output_shape[i];
-    }
-
-    for (size_t output_idx = 0; output_idx < num_els; output_idx++) {
-        f32_t sum = 0.0f;
-        f32_t sum_squares = 0.0f;
-
-        size_t output_indices[16];
-        size_t temp = output_idx;
-        for (int d = (int)output_shape_len - 1; d >= 0; d--) {
-            output_indices[d] = temp % output_shape[d];
-            temp /= output_shape[d];
-        }
-
-        size_t input_indices[16];
-        if (keep_dim) {
-            for (size_t i = 0; i < num_dims; i++) {
-                input_indices[i] = output_indices[i];
-            }
-        } else {
-            size_t out_idx = 0;
-            for (size_t in_dim = 0; in_dim < num_dims; in_dim++) {
-                bool is_reduced = false;
-                for (size_t r = 0; r < num_reduce_dims; r++) {
-                    if (reduce_dims[r] == in_dim) {
-                        is_reduced = true;
-                        break;
-                    }
-                }
-                if (is_reduced) {
-                    input_indices[in_dim] = 0;
-                } else {
-                    input_indices[in_dim] =
-                        (out_idx < output_shape_len) ? output_indices[out_idx] : 0;
-                    out_idx++;
-                }
-            }
-        }
-
-#if SIMD_F32_WIDTH > 1
-        bool use_simd = (num_reduce_dims == 1) && (reduce_dims[0] == num_dims - 1) &&
-                        (strides[num_dims - 1] == 1) && (reduce_size >= SIMD_F32_WIDTH);
-
-        if (use_simd) {
-            size_t flat_base = offset;
-            for (size_t i = 0; i < num_dims - 1; i++) {
-                flat_base += input_indices[i] * strides[i];
-            }
-
-            simd_f32_t vsum = simd_f32_set1(0.0f);
-            simd_f32_t vsum_sq = simd_f32_set1(0.0f);
-            const size_t simd_end = (reduce_size / SIMD_F32_WIDTH) * SIMD_F32_WIDTH;
-
-            for (size_t i = 0; i < simd_end; i += SIMD_F32_WIDTH) {
-                simd_f32_t v = simd_f32_load(&input[flat_base + i]);
-                vsum = simd_f32_add(vsum, v);
-                vsum_sq = simd_f32_fmadd(v, v, vsum_sq); // v*v + vsum_sq
-            }
-            sum = simd_f32_reduce_add(vsum);
-            sum_squares = simd_f32_reduce_add(vsum_sq);
-
-            for (size_t i = simd_end; i < reduce_size; i++) {
-                f32_t val = input[flat_base + i];
-                sum += val;
-                sum_squares += val * val;
-            }
-        } else
-#endif
-        {
-            for (size_t reduced_idx = 0; reduced_idx < reduce_size; reduced_idx++) {
-                size_t temp_reduced = reduced_idx;
-                for (int i = (int)num_reduce_dims - 1; i >= 0; i--) {
-                    size_t dim = reduce_dims[i];
-                    input_indices[dim] = temp_reduced % dims[dim];
-                    temp_reduced /= dims[dim];
-                }
-
-                size_t flat_index = offset;
-                for (size_t i = 0; i < num_dims; i++) {
-                    flat_index += input_indices[i] * strides[i];
-                }
-
-                f32_t val = input[flat_index];
-                sum += val;
-                sum_squares += val * val;
-            }
-        }
-
-        f32_t mean = sum / (f32_t)reduce_size;
-        output[output_idx] = (sum_squares / (f32_t)reduce_size) - (mean * mean);
-    }
-}
-
-// SIMD-optimized var_f64 (variance: E[X^2] - E[X]^2)
-void hodu_cpu_var_f64(const void *input_ptr, void *output_ptr, const size_t *metadata) {
-    const f64_t *input = (const f64_t *)input_ptr;
-    f64_t *output = (f64_t *)output_ptr;
-
-    const size_t num_dims = metadata[0];
-    const size_t *dims = metadata + 1;
-    const size_t *strides = metadata + 1 + num_dims;
-    const size_t offset = metadata[1 + 2 * num_dims];
-    const size_t output_shape_len = metadata[2 + 2 * num_dims];
-    const size_t *output_shape = metadata + 3 + 2 * num_dims;
-    const size_t num_reduce_dims = metadata[3 + 2 * num_dims + output_shape_len];
-    const size_t *reduce_dims = metadata + 4 + 2 * num_dims + output_shape_len;
-    const size_t keep_dim_val = metadata[4 + 2 * num_dims + output_shape_len + num_reduce_dims];
-    const bool keep_dim = (keep_dim_val != 0);
-    const size_t reduce_size = metadata[5 + 2 * num_dims + output_shape_len + num_reduce_dims];
-
-    size_t num_els = 1;
-    for (size_t i = 0; i < output_shape_len; i++) {
-        num_els *= output_shape[i];
-    }
-
-    for (size_t output_idx = 0; output_idx < num_els; output_idx++) {
-        f64_t sum = 0.0;
-        f64_t sum_squares = 0.0;
-
-        size_t output_indices[16];
-        size_t temp = output_idx;
-        for (int d = (int)output_shape_len - 1; d >= 0; d--) {
-            output_indices[d] = temp % output_shape[d];
-            temp /= output_shape[d];
-        }
-
-        size_t input_indices[16];
-        if (keep_dim) {
-            for (size_t i = 0; i < num_dims; i++) {
-                input_indices[i] = output_indices[i];
-            }
-        } else {
-            size_t out_idx = 0;
-            for (size_t in_dim = 0; in_dim < num_dims; in_dim++) {
-                bool is_reduced = false;
-                for (size_t r = 0; r < num_reduce_dims; r++) {
-                    if (reduce_dims[r] == in_dim) {
-                        is_reduced = true;
-                        break;
-                    }
-                }
-                if (is_reduced) {
-                    input_indices[in_dim] = 0;
-                } else {
-                    input_indices[in_dim] =
-                        (out_idx < output_shape_len) ? output_indices[out_idx] : 0;
-                    out_idx++;
-                }
-            }
-        }
-
-#if SIMD_F64_WIDTH > 1
-        bool use_simd = (num_reduce_dims == 1) && (reduce_dims[0] == num_dims - 1) &&
-                        (strides[num_dims - 1] == 1) && (reduce_size >= SIMD_F64_WIDTH);
-
-        if (use_simd) {
-            size_t flat_base = offset;
-            for (size_t i = 0; i < num_dims - 1; i++) {
-                flat_base += input_indices[i] * strides[i];
-            }
-
-            simd_f64_t vsum = simd_f64_set1(0.0);
-            simd_f64_t vsum_sq = simd_f64_set1(0.0);
-            const size_t simd_end = (reduce_size / SIMD_F64_WIDTH) * SIMD_F64_WIDTH;
-
-            for (size_t i = 0; i < simd_end; i += SIMD_F64_WIDTH) {
-                simd_f64_t v = simd_f64_load(&input[flat_base + i]);
-                vsum = simd_f64_add(vsum, v);
-                vsum_sq = simd_f64_fmadd(v, v, vsum_sq); // v*v + vsum_sq
-            }
-            sum = simd_f64_reduce_add(vsum);
-            sum_squares = simd_f64_reduce_add(vsum_sq);
-
-            for (size_t i = simd_end; i < reduce_size; i++) {
-                f64_t val = input[flat_base + i];
-                sum += val;
-                sum_squares += val * val;
-            }
-        } else
-#endif
-        {
-            for (size_t reduced_idx = 0; reduced_idx < reduce_size; reduced_idx++) {
-                size_t temp_reduced = reduced_idx;
-                for (int i = (int)num_reduce_dims - 1; i >= 0; i--) {
-                    size_t dim = reduce_dims[i];
-                    input_indices[dim] = temp_reduced % dims[dim];
-                    temp_reduced /= dims[dim];
-                }
-
-                size_t flat_index = offset;
-                for (size_t i = 0; i < num_dims; i++) {
-                    flat_index += input_indices[i] * strides[i];
-                }
-
-                f64_t val = input[flat_index];
-                sum += val;
-                sum_squares += val * val;
-            }
-        }
-
-        f64_t mean = sum / (f64_t)reduce_size;
-        output[output_idx] = (sum_squares / (f64_t)reduce_size) - (mean * mean);
-    }
-}
-
-// ============================================================================
-// MEAN REDUCTION
-// ============================================================================
-//
-// Computes arithmetic mean by calling sum and dividing by reduce_size.
-//
-// Metadata layout: Same as generic reduction operations
-
-/// Macro to implement mean reduction (float types only)
-///
-/// @param TYPE C float type
-/// @param TYPE_SUFFIX Suffix for function naming
-#define REDUCE_MEAN_OP(TYPE, TYPE_SUFFIX)                                                          \
-    void hodu_cpu_mean_##TYPE_SUFFIX(const void *input_ptr, void *output_ptr,                      \
-                                     const size_t *metadata) {                                     \
-        const size_t num_dims = metadata[0];                                                       \
-        const size_t output_shape_len = metadata[2 + 2 * num_dims];                                \
-        const size_t *output_shape = metadata + 3 + 2 * num_dims;                                  \
-        const size_t num_reduce_dims = metadata[3 + 2 * num_dims + output_shape_len];              \
-        const size_t reduce_size =                                                                 \
-            metadata[5 + 2 * num_dims + output_shape_len + num_reduce_dims];                       \
-        size_t num_els = 1;                                                                        \
-        for (size_t i = 0; i < output_shape_len; i++) {                                            \
-            num_els *= output_shape[i];                                                            \
-        }                                                                                          \
-        hodu_cpu_sum_##TYPE_SUFFIX(input_ptr, output_ptr, metadata);                               \
-        TYPE *output = (TYPE *)output_ptr;                                                         \
-        for (size_t i = 0; i < num_els; i++) {                                                     \
-            output[i] /= (TYPE)reduce_size;                                                        \
-        }                                                                                          \
-    }
-
-/// Macro to implement mean reduction for exotic types
-///
-/// @param TYPE C float type
-/// @param TYPE_SUFFIX Suffix for function naming
-/// @param DIV_FN Division function
-#define REDUCE_MEAN_OP_EXOTIC(TYPE, TYPE_SUFFIX, DIV_FN)                                           \
-    void hodu_cpu_mean_##TYPE_SUFFIX(const void *input_ptr, void *output_ptr,                      \
-                                     const size_t *metadata) {                                     \
-        const size_t num_dims = metadata[0];                                                       \
-        const size_t output_shape_len = metadata[2 + 2 * num_dims];                                \
-        const size_t *output_shape = metadata + 3 + 2 * num_dims;                                  \
-        const size_t num_reduce_dims = metadata[3 + 2 * num_dims + output_shape_len];              \
-        const size_t reduce_size =                                                                 \
-            metadata[5 + 2 * num_dims + output_shape_len + num_reduce_dims];                       \
-        size_t num_els = 1;                                                                        \
-        for (size_t i = 0; i < output_shape_len; i++) {                                            \
-            num_els *= output_shape[i];                                                            \
-        }                                                                                          \
-        hodu_cpu_sum_##TYPE_SUFFIX(input_ptr, output_ptr, metadata);                               \
-        TYPE *output = (TYPE *)output_ptr;                                                         \
-        float reduce_size_f = (float)reduce_size;                                                  \
-        TYPE reduce_size_typed = float_to_##TYPE_SUFFIX(reduce_size_f);                            \
-        for (size_t i = 0; i < num_els; i++) {                                                     \
-            output[i] = DIV_FN(output[i], reduce_size_typed);                                      \
+            output[output_idx] = FROM_DOUBLE_FN(welford_##OP_NAME(w));                             \
         }                                                                                          \
     }
 
-REDUCE_MEAN_OP_EXOTIC(f8e4m3_t, f8e4m3, f8e4m3_div)
-REDUCE_MEAN_OP_EXOTIC(f8e5m2_t, f8e5m2, f8e5m2_div)
-REDUCE_MEAN_OP_EXOTIC(bf16_t, bf16, bf16_div)
-REDUCE_MEAN_OP_EXOTIC(f16_t, f16, f16_div)
-REDUCE_MEAN_OP(f32_t, f32)
-REDUCE_MEAN_OP(f64_t, f64)
+/// Declare mean, var and std for one float type
+#define REDUCE_WELFORD_OPS(TYPE, TYPE_SUFFIX, TO_DOUBLE_FN, FROM_DOUBLE_FN)                        \
+    REDUCE_WELFORD_OP(TYPE, mean, TYPE_SUFFIX, TO_DOUBLE_FN, FROM_DOUBLE_FN)                       \
+    REDUCE_WELFORD_OP(TYPE, var, TYPE_SUFFIX, TO_DOUBLE_FN, FROM_DOUBLE_FN)                        \
+    REDUCE_WELFORD_OP(TYPE, std, TYPE_SUFFIX, TO_DOUBLE_FN, FROM_DOUBLE_FN)
+
+REDUCE_WELFORD_OPS(f8e4m3_t, f8e4m3, f8e4m3_to_float, float_to_f8e4m3)
+REDUCE_WELFORD_OPS(f8e5m2_t, f8e5m2, f8e5m2_to_float, float_to_f8e5m2)
+REDUCE_WELFORD_OPS(bf16_t, bf16, bf16_to_float, float_to_bf16)
+REDUCE_WELFORD_OPS(f16_t, f16, f16_to_float, float_to_f16)
+REDUCE_WELFORD_OPS(f32_t, f32, TO_DOUBLE, (f32_t))
+REDUCE_WELFORD_OPS(f64_t, f64, TO_DOUBLE, (f64_t))
 
 // ============================================================================
 // NORM REDUCTION (L2 NORM)
@@ -220,6 +220,99 @@
         }                                                                                          \
     }
 
+/**
+ * @brief Macro to implement SIMD-vectorized scalar operations
+ *
+ * Same contract as IMPL_UNARY_WITH_SCALAR. The contiguous path (including in-place)
+ * processes SIMD lanes at a time, split across threads for large inputs; the tail and
+ * strided inputs use FUNC.
+ *
+ * @param TYPE C type of the tensor elements (f32_t or f64_t)
+ * @param TYPE_SUFFIX Suffix for the function name and simd_utils.h helpers (f32 or f64)
+ * @param OP_NAME Operation name (e.g., add_scalar)
+ * @param WIDTH SIMD lane count (SIMD_F32_WIDTH or SIMD_F64_WIDTH)
+ * @param SIMD_OP simd_utils.h binary op applied to the element and scalar vectors
+ * @param FUNC Expression using 'x' (element) and 'const_val' (scalar)
+ */
+#define IMPL_UNARY_WITH_SCALAR_SIMD(TYPE, TYPE_SUFFIX, OP_NAME, WIDTH, SIMD_OP, FUNC)              \
+    typedef struct {                                                                               \
+        const TYPE *input;                                                                         \
+        TYPE *output;                                                                              \
+        TYPE const_val;                                                                            \
+        size_t start;                                                                              \
+        size_t end;                                                                                \
+    } unary_scalar_##OP_NAME##_##TYPE_SUFFIX##_args_t;                                             \
+                                                                                                   \
+    static void *unary_scalar_##OP_NAME##_##TYPE_SUFFIX##_worker(void *arg) {                      \
+        unary_scalar_##OP_NAME##_##TYPE_SUFFIX##_args_t *args =                                    \
+            (unary_scalar_##OP_NAME##_##TYPE_SUFFIX##_args_t *)arg;                                \
+        const TYPE *src = args->input;                                                             \
+        TYPE *out = args->output;                                                                  \
+        TYPE const_val = args->const_val;                                                          \
+        simd_##TYPE_SUFFIX##_t vc = simd_##TYPE_SUFFIX##_set1(const_val);                          \
+        size_t i = args->start;                                                                    \
+        const size_t simd_end = args->start + ((args->end - args->start) / WIDTH) * WIDTH;         \
+        for (; i < simd_end; i += WIDTH) {                                                         \
+            simd_##TYPE_SUFFIX##_t v = simd_##TYPE_SUFFIX##_load(&src[i]);                         \
+            simd_##TYPE_SUFFIX##_store(&out[i], SIMD_OP(v, vc));                                   \
+        }                                                                                          \
+        for (; i < args->end; i++) {                                                               \
+            TYPE x = src[i];                                                                       \
+            out[i] = FUNC;                                                                         \
+        }                                                                                          \
+        return NULL;                                                                               \
+    }                                                                                              \
+                                                                                                   \
+    void hodu_cpu_##OP_NAME##_##TYPE_SUFFIX(const void *input, void *output,                       \
+                                            const size_t *metadata, const void *scalar) {          \
+        const size_t num_els = metadata[0];                                                        \
+        const size_t num_dims = metadata[1];                                                       \
+        const TYPE *in = (const TYPE *)input;                                                      \
+        TYPE *out = (TYPE *)output;                                                                \
+        TYPE const_val = *(const TYPE *)scalar;                                                    \
+                                                                                                   \
+        const size_t *dims = metadata + 2;                                                         \
+        const size_t *strides = metadata ? metadata + 2 + num_dims : NULL;                         \
+        const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;         \
+                                                                                                   \
+        bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);            \
+                                                                                                   \
+        if (contiguous) {                                                                          \
+            unary_scalar_##OP_NAME##_##TYPE_SUFFIX##_args_t base = {in ? in + offset : out, out,   \
+                                                                    const_val, 0, num_els};        \
+            const size_t min_work_per_thread = 100000;                                             \
+            size_t num_threads = get_optimal_threads(num_els, min_work_per_thread);                \
+                                                                                                   \
+            if (num_threads > 1) {                                                                 \
+                thread_t threads[num_threads];                                                     \
+                unary_scalar_##OP_NAME##_##TYPE_SUFFIX##_args_t args[num_threads];                 \
+                                                                                                   \
+                size_t chunk_size = num_els / num_threads;                                         \
+                size_t remaining = num_els % num_threads;                                          \
+                                                                                                   \
+                for (size_t t = 0; t < num_threads; t++) {                                         \
+                    args[t] = base;                                                                \
+                    args[t].start = t * chunk_size + (t < remaining ? t : remaining);              \
+                    args[t].end = args[t].start + chunk_size + (t < remaining ? 1 : 0);            \
+                    thread_create(&threads[t], unary_scalar_##OP_NAME##_##TYPE_SUFFIX##_worker,    \
+                                  &args[t]);                                                       \
+                }                                                                                  \
+                                                                                                   \
+                for (size_t t = 0; t < num_threads; t++) {                                         \
+                    thread_join(threads[t]);                                                       \
+                }                                                                                  \
+            } else {                                                                               \
+                unary_scalar_##OP_NAME##_##TYPE_SUFFIX##_worker(&base);                            \
+            }                                                                                      \
+        } else {                                                                                   \
+            for (size_t i = 0; i < num_els; i++) {                                                 \
+                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);         \
+                TYPE x = in ? in[strided_i] : out[i];                                              \
+                out[i] = FUNC;                                                                     \
+            }                                                                                      \
+        }                                                                                          \
+    }
+
 /**
  * @brief Macro to implement unary operations that output boolean values
  *
@@ -457,39 +550,27 @@ void hodu_cpu_neg_f32(const void *input, void *output, const size_t *metadata) {
     const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
-    if (contiguous && in && offset == 0) {
+    if (contiguous) {
+        const f32_t *src = in ? in + offset : out;
 #if SIMD_F32_WIDTH > 1
         const size_t simd_end = (num_els / SIMD_F32_WIDTH) * SIMD_F32_WIDTH;
         for (size_t i = 0; i < simd_end; i += SIMD_F32_WIDTH) {
-            simd_f32_t v = simd_f32_load(&in[i]);
+            simd_f32_t v = simd_f32_load(&src[i]);
             simd_f32_store(&out[i], simd_f32_neg(v));
         }
         for (size_t i = simd_end; i < num_els; i++) {
-            out[i] = -in[i];
+            out[i] = -src[i];
         }
 #else
         for (size_t i = 0; i < num_els; i++) {
-            out[i] = -in[i];
+            out[i] = -src[i];
         }
 #endif
     } else {
-        // Fallback for non-contiguous or offset cases
-        if (contiguous) {
-            if (in) {
-                for (size_t i = 0; i < num_els; i++) {
-                    out[i] = -in[offset + i];
-                }
-            } else {
-                for (size_t i = 0; i < num_els; i++) {
-                    out[i] = -out[i];
-                }
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
-                f32_t x = in ? in[strided_i] : out[i];
-                out[i] = -x;
-            }
+        for (size_t i = 0; i < num_els; i++) {
+            size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
+            f32_t x = in ? in[strided_i] : out[i];
+            out[i] = -x;
         }
     }
 }
@@ -505,39 +586,27 @@ void hodu_cpu_abs_f32(const void *input, void *output, const size_t *metadata) {
     const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
-    if (contiguous && in && offset == 0) {
+    if (contiguous) {
+        const f32_t *src = in ? in + offset : out;
 #if SIMD_F32_WIDTH > 1
         const size_t simd_end = (num_els / SIMD_F32_WIDTH) * SIMD_F32_WIDTH;
         for (size_t i = 0; i < simd_end; i += SIMD_F32_WIDTH) {
-            simd_f32_t v = simd_f32_load(&in[i]);
+            simd_f32_t v = simd_f32_load(&src[i]);
             simd_f32_store(&out[i], simd_f32_abs(v));
         }
         for (size_t i = simd_end; i < num_els; i++) {
-            out[i] = fabsf(in[i]);
+            out[i] = fabsf(src[i]);
         }
 #else
         for (size_t i = 0; i < num_els; i++) {
-            out[i] = fabsf(in[i]);
+            out[i] = fabsf(src[i]);
         }
 #endif
     } else {
-        // Fallback
-        if (contiguous) {
-            if (in) {
-                for (size_t i = 0; i < num_els; i++) {
-                    out[i] = fabsf(in[offset + i]);
-                }
-            } else {
-                for (size_t i = 0; i < num_els; i++) {
-                    out[i] = fabsf(out[i]);
-                }
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
-                f32_t x = in ? in[strided_i] : out[i];
-                out[i] = fabsf(x);
-            }
+        for (size_t i = 0; i < num_els; i++) {
+            size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
+            f32_t x = in ? in[strided_i] : out[i];
+            out[i] = fabsf(x);
         }
     }
 }
@@ -556,40 +625,27 @@ void hodu_cpu_square_f32(const void *input, void *output, const size_t *metadata
     const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
-    if (contiguous && in && offset == 0) {
+    if (contiguous) {
+        const f32_t *src = in ? in + offset : out;
 #if SIMD_F32_WIDTH > 1
         const size_t simd_end = (num_els / SIMD_F32_WIDTH) * SIMD_F32_WIDTH;
         for (size_t i = 0; i < simd_end; i += SIMD_F32_WIDTH) {
-            simd_f32_t v = simd_f32_load(&in[i]);
+            simd_f32_t v = simd_f32_load(&src[i]);
             simd_f32_store(&out[i], simd_f32_mul(v, v));
         }
         for (size_t i = simd_end; i < num_els; i++) {
-            out[i] = in[i] * in[i];
+            out[i] = src[i] * src[i];
         }
 #else
         for (size_t i = 0; i < num_els; i++) {
-            out[i] = in[i] * in[i];
+            out[i] = src[i] * src[i];
         }
 #endif
     } else {
-        // Fallback
-        if (contiguous) {
-            if (in) {
-                for (size_t i = 0; i < num_els; i++) {
-                    f32_t x = in[offset + i];
-                    out[i] = x * x;
-                }
-            } else {
-                for (size_t i = 0; i < num_els; i++) {
-                    out[i] = out[i] * out[i];
-                }
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
-                f32_t x = in ? in[strided_i] : out[i];
-                out[i] = x * x;
-            }
+        for (size_t i = 0; i < num_els; i++) {
+            size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
+            f32_t x = in ? in[strided_i] : out[i];
+            out[i] = x * x;
         }
     }
 }
@@ -605,39 +661,27 @@ void hodu_cpu_sqrt_f32(const void *input, void *output, const size_t *metadata)
     const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
-    if (contiguous && in && offset == 0) {
+    if (contiguous) {
+        const f32_t *src = in ? in + offset : out;
 #if SIMD_F32_WIDTH > 1
         const size_t simd_end = (num_els / SIMD_F32_WIDTH) * SIMD_F32_WIDTH;
         for (size_t i = 0; i < simd_end; i += SIMD_F32_WIDTH) {
-            simd_f32_t v = simd_f32_load(&in[i]);
+            simd_f32_t v = simd_f32_load(&src[i]);
             simd_f32_store(&out[i], simd_f32_sqrt(v));
         }
         for (size_t i = simd_end; i < num_els; i++) {
-            out[i] = sqrtf(in[i]);
+            out[i] = sqrtf(src[i]);
         }
 #else
         for (size_t i = 0; i < num_els; i++) {
-            out[i] = sqrtf(in[i]);
+            out[i] = sqrtf(src[i]);
         }
 #endif
     } else {
-        // Fallback
-        if (contiguous) {
-            if (in) {
-                for (size_t i = 0; i < num_els; i++) {
-                    out[i] = sqrtf(in[offset + i]);
-                }
-            } else {
-                for (size_t i = 0; i < num_els; i++) {
-                    out[i] = sqrtf(out[i]);
-                }
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
-                f32_t x = in ? in[strided_i] : out[i];
-                out[i] = sqrtf(x);
-            }
+        for (size_t i = 0; i < num_els; i++) {
+            size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
+            f32_t x = in ? in[strided_i] : out[i];
+            out[i] = sqrtf(x);
         }
     }
 }
@@ -656,44 +700,30 @@ void hodu_cpu_relu_f32(const void *input, void *output, const size_t *metadata)
     const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
-    if (contiguous && in && offset == 0) {
+    if (contiguous) {
+        const f32_t *src = in ? in + offset : out;
 #if SIMD_F32_WIDTH > 1
         simd_f32_t vzero = simd_f32_set1(0.0f);
         const size_t simd_end = (num_els / SIMD_F32_WIDTH) * SIMD_F32_WIDTH;
         for (size_t i = 0; i < simd_end; i += SIMD_F32_WIDTH) {
-            simd_f32_t v = simd_f32_load(&in[i]);
+            simd_f32_t v = simd_f32_load(&src[i]);
             simd_f32_store(&out[i], simd_f32_max(v, vzero));
         }
         for (size_t i = simd_end; i < num_els; i++) {
-            f32_t x = in[i];
+            f32_t x = src[i];
             out[i] = (x > 0.0f) ? x : 0.0f;
         }
 #else
         for (size_t i = 0; i < num_els; i++) {
-            f32_t x = in[i];
+            f32_t x = src[i];
             out[i] = (x > 0.0f) ? x : 0.0f;
         }
 #endif
     } else {
-        // Fallback
-        if (contiguous) {
-            if (in) {
-                for (size_t i = 0; i < num_els; i++) {
-                    f32_t x = in[offset + i];
-                    out[i] = (x > 0.0f) ? x : 0.0f;
-                }
-            } else {
-                for (size_t i = 0; i < num_els; i++) {
-                    f32_t x = out[i];
-                    out[i] = (x > 0.0f) ? x : 0.0f;
-                }
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
-                f32_t x = in ? in[strided_i] : out[i];
-                out[i] = (x > 0.0f) ? x : 0.0f;
-            }
+        for (size_t i = 0; i < num_els; i++) {
+            size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
+            f32_t x = in ? in[strided_i] : out[i];
+            out[i] = (x > 0.0f) ? x : 0.0f;
         }
     }
 }
@@ -744,8 +774,13 @@ IMPL_UNARY_TO_BOOL(f32_t, f32, isinf, isinf(x))
 IMPL_UNARY_TO_BOOL(f32_t, f32, isfinite, isfinite(x))
 
 // Scalar arithmetic operations
+#if SIMD_F32_WIDTH > 1
+IMPL_UNARY_WITH_SCALAR_SIMD(f32_t, f32, add_scalar, SIMD_F32_WIDTH, simd_f32_add, x + const_val)
+IMPL_UNARY_WITH_SCALAR_SIMD(f32_t, f32, sub_scalar, SIMD_F32_WIDTH, simd_f32_sub, x - const_val)
+#else
 IMPL_UNARY_WITH_SCALAR(f32_t, f32, add_scalar, x + const_val)
 IMPL_UNARY_WITH_SCALAR(f32_t, f32, sub_scalar, x - const_val)
+#endif
 
 // mul_scalar_f32: Fallback implementation (BLAS versions in separate files)
 void hodu_cpu_mul_scalar_f32_fallback(const void *input, void *output, const size_t *metadata,
@@ -763,16 +798,17 @@ void hodu_cpu_mul_scalar_f32_fallback(const void *input, void *output, const siz
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
     if (contiguous) {
-        if (in) {
-            for (size_t i = 0; i < num_els; i++) {
-                f32_t x = in[offset + i];
-                out[i] = x * const_val;
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                f32_t x = out[i];
-                out[i] = x * const_val;
-            }
+        const f32_t *src = in ? in + offset : out;
+        size_t i = 0;
+#if SIMD_F32_WIDTH > 1
+        simd_f32_t vc = simd_f32_set1(const_val);
+        const size_t simd_end = (num_els / SIMD_F32_WIDTH) * SIMD_F32_WIDTH;
+        for (; i < simd_end; i += SIMD_F32_WIDTH) {
+            simd_f32_store(&out[i], simd_f32_mul(simd_f32_load(&src[i]), vc));
+        }
+#endif
+        for (; i < num_els; i++) {
+            out[i] = src[i] * const_val;
         }
     } else {
         for (size_t i = 0; i < num_els; i++) {
@@ -791,7 +827,11 @@ void hodu_cpu_mul_scalar_f32(const void *input, void *output, const size_t *meta
 }
 #endif
 
+#if SIMD_F32_WIDTH > 1
+IMPL_UNARY_WITH_SCALAR_SIMD(f32_t, f32, div_scalar, SIMD_F32_WIDTH, simd_f32_div, x / const_val)
+#else
 IMPL_UNARY_WITH_SCALAR(f32_t, f32, div_scalar, x / const_val)
+#endif
 IMPL_UNARY_WITH_SCALAR(f32_t, f32, rem_scalar, fmodf(x, const_val))
 IMPL_UNARY_WITH_SCALAR(f32_t, f32, pow_scalar, powf_opt(x, const_val))
 IMPL_UNARY_WITH_SCALAR(f32_t, f32, maximum_scalar, MAXIMUM(x, const_val))
@@ -820,41 +860,27 @@ void hodu_cpu_neg_f64(const void *input, void *output, const size_t *metadata) {
     const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
-    if (contiguous && in && offset == 0) {
+    if (contiguous) {
+        const f64_t *src = in ? in + offset : out;
 #if SIMD_F64_WIDTH > 1
         const size_t simd_end = (num_els / SIMD_F64_WIDTH) * SIMD_F64_WIDTH;
         for (size_t i = 0; i < simd_end; i += SIMD_F64_WIDTH) {
-            simd_f64_t v = simd_f64_load(&in[i]);
+            simd_f64_t v = simd_f64_load(&src[i]);
             simd_f64_store(&out[i], simd_f64_neg(v));
         }
         for (size_t i = simd_end; i < num_els; i++) {
-            out[i] = -in[i];
+            out[i] = -src[i];
         }
 #else
         for (size_t i = 0; i < num_els; i++) {
-            out[i] = -in[i];
+            out[i] = -src[i];
         }
 #endif
     } else {
-        // Fallback for non-contiguous or offset cases
-        if (contiguous) {
-            if (in) {
-                for (size_t i = 0; i < num_els; i++) {
-                    f64_t x = in[offset + i];
-                    out[i] = -x;
-                }
-            } else {
-                for (size_t i = 0; i < num_els; i++) {
-                    f64_t x = out[i];
-                    out[i] = -x;
-                }
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
-                f64_t x = in ? in[strided_i] : out[i];
-                out[i] = -x;
-            }
+        for (size_t i = 0; i < num_els; i++) {
+            size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
+            f64_t x = in ? in[strided_i] : out[i];
+            out[i] = -x;
         }
     }
 }
@@ -870,41 +896,27 @@ void hodu_cpu_abs_f64(const void *input, void *output, const size_t *metadata) {
     const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
-    if (contiguous && in && offset == 0) {
+    if (contiguous) {
+        const f64_t *src = in ? in + offset : out;
 #if SIMD_F64_WIDTH > 1
         const size_t simd_end = (num_els / SIMD_F64_WIDTH) * SIMD_F64_WIDTH;
         for (size_t i = 0; i < simd_end; i += SIMD_F64_WIDTH) {
-            simd_f64_t v = simd_f64_load(&in[i]);
+            simd_f64_t v = simd_f64_load(&src[i]);
             simd_f64_store(&out[i], simd_f64_abs(v));
         }
         for (size_t i = simd_end; i < num_els; i++) {
-            out[i] = fabs(in[i]);
+            out[i] = fabs(src[i]);
         }
 #else
         for (size_t i = 0; i < num_els; i++) {
-            out[i] = fabs(in[i]);
+            out[i] = fabs(src[i]);
         }
 #endif
     } else {
-        // Fallback for non-contiguous or offset cases
-        if (contiguous) {
-            if (in) {
-                for (size_t i = 0; i < num_els; i++) {
-                    f64_t x = in[offset + i];
-                    out[i] = fabs(x);
-                }
-            } else {
-                for (size_t i = 0; i < num_els; i++) {
-                    f64_t x = out[i];
-                    out[i] = fabs(x);
-                }
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
-                f64_t x = in ? in[strided_i] : out[i];
-                out[i] = fabs(x);
-            }
+        for (size_t i = 0; i < num_els; i++) {
+            size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
+            f64_t x = in ? in[strided_i] : out[i];
+            out[i] = fabs(x);
         }
     }
 }
@@ -923,43 +935,29 @@ void hodu_cpu_square_f64(const void *input, void *output, const size_t *metadata
     const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
-    if (contiguous && in && offset == 0) {
+    if (contiguous) {
+        const f64_t *src = in ? in + offset : out;
 #if SIMD_F64_WIDTH > 1
         const size_t simd_end = (num_els / SIMD_F64_WIDTH) * SIMD_F64_WIDTH;
         for (size_t i = 0; i < simd_end; i += SIMD_F64_WIDTH) {
-            simd_f64_t v = simd_f64_load(&in[i]);
+            simd_f64_t v = simd_f64_load(&src[i]);
             simd_f64_store(&out[i], simd_f64_mul(v, v));
         }
         for (size_t i = simd_end; i < num_els; i++) {
-            f64_t x = in[i];
+            f64_t x = src[i];
             out[i] = x * x;
         }
 #else
         for (size_t i = 0; i < num_els; i++) {
-            f64_t x = in[i];
+            f64_t x = src[i];
             out[i] = x * x;
         }
 #endif
     } else {
-        // Fallback for non-contiguous or offset cases
-        if (contiguous) {
-            if (in) {
-                for (size_t i = 0; i < num_els; i++) {
-                    f64_t x = in[offset + i];
-                    out[i] = x * x;
-                }
-            } else {
-                for (size_t i = 0; i < num_els; i++) {
-                    f64_t x = out[i];
-                    out[i] = x * x;
-                }
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
-                f64_t x = in ? in[strided_i] : out[i];
-                out[i] = x * x;
-            }
+        for (size_t i = 0; i < num_els; i++) {
+            size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
+            f64_t x = in ? in[strided_i] : out[i];
+            out[i] = x * x;
         }
     }
 }
@@ -975,41 +973,27 @@ void hodu_cpu_sqrt_f64(const void *input, void *output, const size_t *metadata)
     const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
-    if (contiguous && in && offset == 0) {
+    if (contiguous) {
+        const f64_t *src = in ? in + offset : out;
 #if SIMD_F64_WIDTH > 1
         const size_t simd_end = (num_els / SIMD_F64_WIDTH) * SIMD_F64_WIDTH;
         for (size_t i = 0; i < simd_end; i += SIMD_F64_WIDTH) {
-            simd_f64_t v = simd_f64_load(&in[i]);
+            simd_f64_t v = simd_f64_load(&src[i]);
             simd_f64_store(&out[i], simd_f64_sqrt(v));
         }
         for (size_t i = simd_end; i < num_els; i++) {
-            out[i] = sqrt(in[i]);
+            out[i] = sqrt(src[i]);
         }
 #else
         for (size_t i = 0; i < num_els; i++) {
-            out[i] = sqrt(in[i]);
+            out[i] = sqrt(src[i]);
         }
 #endif
     } else {
-        // Fallback for non-contiguous or offset cases
-        if (contiguous) {
-            if (in) {
-                for (size_t i = 0; i < num_els; i++) {
-                    f64_t x = in[offset + i];
-                    out[i] = sqrt(x);
-                }
-            } else {
-                for (size_t i = 0; i < num_els; i++) {
-                    f64_t x = out[i];
-                    out[i] = sqrt(x);
-                }
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
-                f64_t x = in ? in[strided_i] : out[i];
-                out[i] = sqrt(x);
-            }
+        for (size_t i = 0; i < num_els; i++) {
+            size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
+            f64_t x = in ? in[strided_i] : out[i];
+            out[i] = sqrt(x);
         }
     }
 }
@@ -1027,44 +1011,30 @@ void hodu_cpu_relu_f64(const void *input, void *output, const size_t *metadata)
     const size_t offset = (metadata && num_dims > 0) ? metadata[2 + 2 * num_dims] : 0;
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
-    if (contiguous && in && offset == 0) {
+    if (contiguous) {
+        const f64_t *src = in ? in + offset : out;
 #if SIMD_F64_WIDTH > 1
         simd_f64_t vzero = simd_f64_set1(0.0);
         const size_t simd_end = (num_els / SIMD_F64_WIDTH) * SIMD_F64_WIDTH;
         for (size_t i = 0; i < simd_end; i += SIMD_F64_WIDTH) {
-            simd_f64_t v = simd_f64_load(&in[i]);
+            simd_f64_t v = simd_f64_load(&src[i]);
             simd_f64_store(&out[i], simd_f64_max(v, vzero));
         }
         for (size_t i = simd_end; i < num_els; i++) {
-            f64_t x = in[i];
+            f64_t x = src[i];
             out[i] = (x > 0.0) ? x : 0.0;
         }
 #else
         for (size_t i = 0; i < num_els; i++) {
-            f64_t x = in[i];
+            f64_t x = src[i];
             out[i] = (x > 0.0) ? x : 0.0;
         }
 #endif
     } else {
-        // Fallback for non-contiguous or offset cases
-        if (contiguous) {
-            if (in) {
-                for (size_t i = 0; i < num_els; i++) {
-                    f64_t x = in[offset + i];
-                    out[i] = (x > 0.0) ? x : 0.0;
-                }
-            } else {
-                for (size_t i = 0; i < num_els; i++) {
-                    f64_t x = out[i];
-                    out[i] = (x > 0.0) ? x : 0.0;
-                }
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
-                f64_t x = in ? in[strided_i] : out[i];
-                out[i] = (x > 0.0) ? x : 0.0;
-            }
+        for (size_t i = 0; i < num_els; i++) {
+            size_t strided_i = offset + get_strided_index(i, num_dims, dims, strides);
+            f64_t x = in ? in[strided_i] : out[i];
+            out[i] = (x > 0.0) ? x : 0.0;
         }
     }
 }
@@ -1110,8 +1080,13 @@ IMPL_UNARY_TO_BOOL(f64_t, f64, isnan, isnan(x))
 IMPL_UNARY_TO_BOOL(f64_t, f64, isinf, isinf(x))
 IMPL_UNARY_TO_BOOL(f64_t, f64, isfinite, isfinite(x))
 
+#if SIMD_F64_WIDTH > 1
+IMPL_UNARY_WITH_SCALAR_SIMD(f64_t, f64, add_scalar, SIMD_F64_WIDTH, simd_f64_add, x + const_val)
+IMPL_UNARY_WITH_SCALAR_SIMD(f64_t, f64, sub_scalar, SIMD_F64_WIDTH, simd_f64_sub, x - const_val)
+#else
 IMPL_UNARY_WITH_SCALAR(f64_t, f64, add_scalar, x + const_val)
 IMPL_UNARY_WITH_SCALAR(f64_t, f64, sub_scalar, x - const_val)
+#endif
 
 // mul_scalar_f64: Fallback implementation (BLAS versions in separate files)
 void hodu_cpu_mul_scalar_f64_fallback(const void *input, void *output, const size_t *metadata,
@@ -1129,16 +1104,17 @@ void hodu_cpu_mul_scalar_f64_fallback(const void *input, void *output, const siz
     bool contiguous = (metadata == NULL) || is_contiguous(num_dims, dims, strides);
 
     if (contiguous) {
-        if (in) {
-            for (size_t i = 0; i < num_els; i++) {
-                f64_t x = in[offset + i];
-                out[i] = x * const_val;
-            }
-        } else {
-            for (size_t i = 0; i < num_els; i++) {
-                f64_t x = out[i];
-                out[i] = x * const_val;
-            }
+        const f64_t *src = in ? in + offset : out;
+        size_t i = 0;
+#if SIMD_F64_WIDTH > 1
+        simd_f64_t vc = simd_f64_set1(const_val);
+        const size_t simd_end = (num_els / SIMD_F64_WIDTH) * SIMD_F64_WIDTH;
+        for (; i < simd_end; i += SIMD_F64_WIDTH) {
+            simd_f64_store(&out[i], simd_f64_mul(simd_f64_load(&src[i]), vc));
+        }
+#endif
+        for (; i < num_els; i++) {
+            out[i] = src[i] * const_val;
         }
     } else {
         for (size_t i = 0; i < num_els; i++) {
@@ -1157,7 +1133,11 @@ void hodu_cpu_mul_scalar_f64(const void *input, void *output, const size_t *meta
 }
 #endif
 
+#if SIMD_F64_WIDTH > 1
+IMPL_UNARY_WITH_SCALAR_SIMD(f64_t, f64, div_scalar, SIMD_F64_WIDTH, simd_f64_div, x / const_val)
+#else
 IMPL_UNARY_WITH_SCALAR(f64_t, f64, div_scalar, x / const_val)
+#endif
 IMPL_UNARY_WITH_SCALAR(f64_t, f64, rem_scalar, fmod(x, const_val))
 IMPL_UNARY_WITH_SCALAR(f64_t, f64, pow_scalar, pow_opt(x, const_val))
 IMPL_UNARY_WITH_SCALAR(f64_t, f64, maximum_scalar, MAXIMUM(x, const_val))
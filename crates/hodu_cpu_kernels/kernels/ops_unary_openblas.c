@@ -1,7 +1,7 @@
+#include "blas_utils.h"
 #include "ops_unary.h"
 #include "types.h"
 #include "utils.h"
-#include <cblas.h>
 
 // Forward declarations for fallback
 extern void hodu_cpu_mul_scalar_f32_fallback(const void *input, void *output,
@@ -9,7 +9,7 @@ extern void hodu_cpu_mul_scalar_f32_fallback(const void *input, void *output,
 extern void hodu_cpu_mul_scalar_f64_fallback(const void *input, void *output,
                                              const size_t *metadata, const void *scalar);
 
-// mul_scalar_f32: OpenBLAS-optimized version
+// mul_scalar_f32: CBLAS-optimized version
 void hodu_cpu_mul_scalar_f32(const void *input, void *output, const size_t *metadata,
                              const void *scalar) {
     const size_t num_els = metadata[0];
@@ -42,7 +42,7 @@ void hodu_cpu_mul_scalar_f32(const void *input, void *output, const size_t *meta
     hodu_cpu_mul_scalar_f32_fallback(input, output, metadata, scalar);
 }
 
-// mul_scalar_f64: OpenBLAS-optimized version
+// mul_scalar_f64: CBLAS-optimized version
 void hodu_cpu_mul_scalar_f64(const void *input, void *output, const size_t *metadata,
                              const void *scalar) {
     const size_t num_els = metadata[0];
@@ -50,7 +50,53 @@
 // F32 SIMD Operations
 // ============================================================================
 
-#ifdef SIMD_AVX2
+#if defined(SIMD_AVX512)
+#define SIMD_F32_WIDTH 16
+typedef __m512 simd_f32_t;
+
+static inline simd_f32_t simd_f32_load(const float *ptr) { return _mm512_loadu_ps(ptr); }
+
+static inline void simd_f32_store(float *ptr, simd_f32_t v) { _mm512_storeu_ps(ptr, v); }
+
+static inline simd_f32_t simd_f32_add(simd_f32_t a, simd_f32_t b) { return _mm512_add_ps(a, b); }
+
+static inline simd_f32_t simd_f32_sub(simd_f32_t a, simd_f32_t b) { return _mm512_sub_ps(a, b); }
+
+static inline simd_f32_t simd_f32_mul(simd_f32_t a, simd_f32_t b) { return _mm512_mul_ps(a, b); }
+
+static inline simd_f32_t simd_f32_div(simd_f32_t a, simd_f32_t b) { return _mm512_div_ps(a, b); }
+
+static inline simd_f32_t simd_f32_set1(float a) { return _mm512_set1_ps(a); }
+
+// AVX-512F always includes FMA
+static inline simd_f32_t simd_f32_fmadd(simd_f32_t a, simd_f32_t b, simd_f32_t c) {
+    return _mm512_fmadd_ps(a, b, c);
+}
+
+// Horizontal sum for matmul dot product
+static inline float simd_f32_reduce_add(simd_f32_t v) { return _mm512_reduce_add_ps(v); }
+
+static inline simd_f32_t simd_f32_max(simd_f32_t a, simd_f32_t b) { return _mm512_max_ps(a, b); }
+
+static inline simd_f32_t simd_f32_min(simd_f32_t a, simd_f32_t b) { return _mm512_min_ps(a, b); }
+
+// Horizontal max for reduction
+static inline float simd_f32_reduce_max(simd_f32_t v) { return _mm512_reduce_max_ps(v); }
+
+// Horizontal min for reduction
+static inline float simd_f32_reduce_min(simd_f32_t v) { return _mm512_reduce_min_ps(v); }
+
+// Additional unary operations
+static inline simd_f32_t simd_f32_abs(simd_f32_t v) { return _mm512_abs_ps(v); }
+
+static inline simd_f32_t simd_f32_neg(simd_f32_t v) {
+    return _mm512_sub_ps(_mm512_setzero_ps(), v);
+}
+
+static inline simd_f32_t simd_f32_sqrt(simd_f32_t v) { return _mm512_sqrt_ps(v); }
+
+// The 256-bit float ops below only need AVX; FMA is used when the target has it
+#elif defined(SIMD_AVX2) || defined(SIMD_AVX)
 #define SIMD_F32_WIDTH 8
 typedef __m256 simd_f32_t;
 
@@ -207,12 +253,17 @@ static inline simd_f32_t simd_f32_sub(simd_f32_t a, simd_f32_t b) { return vsubq
 
 static inline simd_f32_t simd_f32_mul(simd_f32_t a, simd_f32_t b) { return vmulq_f32(a, b); }
 
+#if defined(__aarch64__)
+// Exact division so SIMD results match the scalar tail and strided paths
+static inline simd_f32_t simd_f32_div(simd_f32_t a, simd_f32_t b) { return vdivq_f32(a, b); }
+#else
 static inline simd_f32_t simd_f32_div(simd_f32_t a, simd_f32_t b) {
-    // ARM NEON doesn't have direct div, use reciprocal estimate + Newton-Raphson
+    // ARMv7 NEON doesn't have direct div, use reciprocal estimate + Newton-Raphson
     float32x4_t recip = vrecpeq_f32(b);
     recip = vmulq_f32(vrecpsq_f32(b, recip), recip);
     return vmulq_f32(a, recip);
 }
+#endif
 
 static inline simd_f32_t simd_f32_set1(float a) { return vdupq_n_f32(a); }
 
@@ -251,7 +302,51 @@ static inline simd_f32_t simd_f32_sqrt(simd_f32_t v) { return vsqrtq_f32(v); }
 // F64 SIMD Operations
 // ============================================================================
 
-#ifdef SIMD_AVX2
+#if defined(SIMD_AVX512)
+#define SIMD_F64_WIDTH 8
+typedef __m512d simd_f64_t;
+
+static inline simd_f64_t simd_f64_load(const double *ptr) { return _mm512_loadu_pd(ptr); }
+
+static inline void simd_f64_store(double *ptr, simd_f64_t v) { _mm512_storeu_pd(ptr, v); }
+
+static inline simd_f64_t simd_f64_add(simd_f64_t a, simd_f64_t b) { return _mm512_add_pd(a, b); }
+
+static inline simd_f64_t simd_f64_sub(simd_f64_t a, simd_f64_t b) { return _mm512_sub_pd(a, b); }
+
+static inline simd_f64_t simd_f64_mul(simd_f64_t a, simd_f64_t b) { return _mm512_mul_pd(a, b); }
+
+static inline simd_f64_t simd_f64_div(simd_f64_t a, simd_f64_t b) { return _mm512_div_pd(a, b); }
+
+static inline simd_f64_t simd_f64_set1(double a) { return _mm512_set1_pd(a); }
+
+static inline simd_f64_t simd_f64_fmadd(simd_f64_t a, simd_f64_t b, simd_f64_t c) {
+    return _mm512_fmadd_pd(a, b, c);
+}
+
+// Horizontal sum for matmul dot product
+static inline double simd_f64_reduce_add(simd_f64_t v) { return _mm512_reduce_add_pd(v); }
+
+static inline simd_f64_t simd_f64_max(simd_f64_t a, simd_f64_t b) { return _mm512_max_pd(a, b); }
+
+static inline simd_f64_t simd_f64_min(simd_f64_t a, simd_f64_t b) { return _mm512_min_pd(a, b); }
+
+// Horizontal max for reduction
+static inline double simd_f64_reduce_max(simd_f64_t v) { return _mm512_reduce_max_pd(v); }
+
+// Horizontal min for reduction
+static inline double simd_f64_reduce_min(simd_f64_t v) { return _mm512_reduce_min_pd(v); }
+
+// Additional unary operations for f64
+static inline simd_f64_t simd_f64_abs(simd_f64_t v) { return _mm512_abs_pd(v); }
+
+static inline simd_f64_t simd_f64_neg(simd_f64_t v) {
+    return _mm512_sub_pd(_mm512_setzero_pd(), v);
+}
+
+static inline simd_f64_t simd_f64_sqrt(simd_f64_t v) { return _mm512_sqrt_pd(v); }
+
+#elif defined(SIMD_AVX2) || defined(SIMD_AVX)
 #define SIMD_F64_WIDTH 4
 typedef __m256d simd_f64_t;
 
@@ -0,0 +1,620 @@
+//! Pure-Rust kernels
+//!
+//! Built in place of the C sources when the `pure-rust` feature is enabled and on wasm32, where no
+//! C toolchain or libm is available. Every kernel is exported under the symbol and ABI of its C
+//! counterpart, so the dispatch code in `kernels` links against either implementation unchanged.
+//!
+//! The loops are single-threaded and favour clarity over speed. Floating-point types are computed
+//! in f64 and rounded back once; integer arithmetic wraps, and the C kernels' conventions for
+//! division by zero, unsigned subtraction and bool arithmetic are kept so that results match.
+
+mod ops_binary;
+mod ops_bitwise;
+mod ops_cast;
+mod ops_concat_split;
+mod ops_conv;
+mod ops_einsum;
+mod ops_indexing;
+mod ops_linalg;
+mod ops_matrix;
+mod ops_memory;
+mod ops_padding;
+mod ops_quant;
+mod ops_reduce;
+mod ops_resize;
+mod ops_scan;
+mod ops_shape_memory;
+mod ops_sort;
+mod ops_unary;
+mod ops_windowing;
+mod storage;
+
+use float8::{F8E4M3, F8E5M2};
+use half::{bf16, f16};
+
+/// Invoke `$kernel!($($args)* suffix, Type)` for every dtype
+macro_rules! all_types {
+    ($kernel:ident!($($args:tt)*)) => {
+        $kernel!($($args)* bool, $crate::fallback::Bool);
+        $crate::fallback::numeric_types!($kernel!($($args)*));
+    };
+}
+
+/// Invoke `$kernel!($($args)* suffix, Type)` for every dtype except bool
+macro_rules! numeric_types {
+    ($kernel:ident!($($args:tt)*)) => {
+        $crate::fallback::float_types!($kernel!($($args)*));
+        $crate::fallback::int_types!($kernel!($($args)*));
+    };
+}
+
+/// Invoke `$kernel!($($args)* suffix, Type)` for every floating-point dtype
+macro_rules! float_types {
+    ($kernel:ident!($($args:tt)*)) => {
+        $kernel!($($args)* f8e4m3, float8::F8E4M3);
+        $kernel!($($args)* f8e5m2, float8::F8E5M2);
+        $kernel!($($args)* bf16, half::bf16);
+        $kernel!($($args)* f16, half::f16);
+        $kernel!($($args)* f32, f32);
+        $kernel!($($args)* f64, f64);
+    };
+}
+
+/// Invoke `$kernel!($($args)* suffix, Type)` for every integer dtype
+macro_rules! int_types {
+    ($kernel:ident!($($args:tt)*)) => {
+        $kernel!($($args)* u8, u8);
+        $kernel!($($args)* u16, u16);
+        $kernel!($($args)* u32, u32);
+        $kernel!($($args)* u64, u64);
+        $kernel!($($args)* i8, i8);
+        $kernel!($($args)* i16, i16);
+        $kernel!($($args)* i32, i32);
+        $kernel!($($args)* i64, i64);
+    };
+}
+
+pub(crate) use {all_types, float_types, int_types, numeric_types};
+
+/// A C `bool` as the kernels store it: one byte, 0 or 1
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub(crate) struct Bool(u8);
+
+impl Bool {
+    pub(crate) const FALSE: Self = Self(0);
+    pub(crate) const TRUE: Self = Self(1);
+
+    pub(crate) fn new(value: bool) -> Self {
+        Self(value as u8)
+    }
+
+    pub(crate) fn get(self) -> bool {
+        self.0 != 0
+    }
+}
+
+/// Arithmetic with the semantics of the C kernels
+///
+/// Implemented for the type each element is computed in: f64 for floats, the type itself for
+/// integers and bool.
+pub(crate) trait Arith: Copy + PartialOrd {
+    const ZERO: Self;
+    const ONE: Self;
+    /// Identity of `maximum`
+    const LOWEST: Self;
+    /// Identity of `minimum`
+    const HIGHEST: Self;
+
+    fn add(self, rhs: Self) -> Self;
+    fn sub(self, rhs: Self) -> Self;
+    fn mul(self, rhs: Self) -> Self;
+    fn div(self, rhs: Self) -> Self;
+    fn pow(self, rhs: Self) -> Self;
+    fn neg(self) -> Self;
+    fn abs(self) -> Self;
+
+    fn maximum(self, rhs: Self) -> Self {
+        if self > rhs {
+            self
+        } else {
+            rhs
+        }
+    }
+
+    fn minimum(self, rhs: Self) -> Self {
+        if self < rhs {
+            self
+        } else {
+            rhs
+        }
+    }
+
+    /// `sub` against a scalar, which differs from the tensor form for bool
+    fn sub_scalar(self, rhs: Self) -> Self {
+        self.sub(rhs)
+    }
+
+    /// `pow` against a scalar, which goes through float for integers and differs for bool
+    fn pow_scalar(self, rhs: Self) -> Self {
+        self.pow(rhs)
+    }
+}
+
+impl Arith for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const LOWEST: Self = f64::NEG_INFINITY;
+    const HIGHEST: Self = f64::INFINITY;
+
+    fn add(self, rhs: Self) -> Self {
+        self + rhs
+    }
+
+    fn sub(self, rhs: Self) -> Self {
+        self - rhs
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        self * rhs
+    }
+
+    fn div(self, rhs: Self) -> Self {
+        self / rhs
+    }
+
+    fn pow(self, rhs: Self) -> Self {
+        pow_opt(self, rhs)
+    }
+
+    fn neg(self) -> Self {
+        -self
+    }
+
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+}
+
+macro_rules! impl_int_arith {
+    ($($ty:ty: $signed:literal),* $(,)?) => {
+        $(
+            impl Arith for $ty {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const LOWEST: Self = <$ty>::MIN;
+                const HIGHEST: Self = <$ty>::MAX;
+
+                fn add(self, rhs: Self) -> Self {
+                    self.wrapping_add(rhs)
+                }
+
+                fn sub(self, rhs: Self) -> Self {
+                    if $signed {
+                        self.wrapping_sub(rhs)
+                    } else {
+                        self.saturating_sub(rhs)
+                    }
+                }
+
+                fn mul(self, rhs: Self) -> Self {
+                    self.wrapping_mul(rhs)
+                }
+
+                fn div(self, rhs: Self) -> Self {
+                    if rhs == 0 {
+                        0
+                    } else {
+                        self.wrapping_div(rhs)
+                    }
+                }
+
+                fn pow(self, rhs: Self) -> Self {
+                    #[allow(unused_comparisons)]
+                    if rhs < 0 {
+                        return 0;
+                    }
+                    let (mut base, mut exp, mut result) = (self, rhs as u64, 1 as $ty);
+                    while exp > 0 {
+                        if exp & 1 == 1 {
+                            result = result.wrapping_mul(base);
+                        }
+                        base = base.wrapping_mul(base);
+                        exp >>= 1;
+                    }
+                    result
+                }
+
+                fn neg(self) -> Self {
+                    self.wrapping_neg()
+                }
+
+                fn abs(self) -> Self {
+                    #[allow(unused_comparisons)]
+                    if self < 0 {
+                        self.wrapping_neg()
+                    } else {
+                        self
+                    }
+                }
+
+                fn pow_scalar(self, rhs: Self) -> Self {
+                    pow_opt(self as f64, rhs as f64) as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_int_arith!(u8: false, u16: false, u32: false, u64: false, i8: true, i16: true, i32: true, i64: true);
+
+impl Arith for Bool {
+    const ZERO: Self = Bool::FALSE;
+    const ONE: Self = Bool::TRUE;
+    const LOWEST: Self = Bool::FALSE;
+    const HIGHEST: Self = Bool::TRUE;
+
+    fn add(self, rhs: Self) -> Self {
+        Bool::new(self.get() || rhs.get())
+    }
+
+    fn sub(self, rhs: Self) -> Self {
+        Bool::new(self.get() && !rhs.get())
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        Bool::new(self.get() && rhs.get())
+    }
+
+    fn div(self, rhs: Self) -> Self {
+        Bool::new(self.get() && rhs.get())
+    }
+
+    fn pow(self, rhs: Self) -> Self {
+        Bool::new(self.get() || !rhs.get())
+    }
+
+    fn neg(self) -> Self {
+        Bool::new(!self.get())
+    }
+
+    fn abs(self) -> Self {
+        self
+    }
+
+    fn sub_scalar(self, rhs: Self) -> Self {
+        Bool::new(self.get() != rhs.get())
+    }
+
+    fn pow_scalar(self, rhs: Self) -> Self {
+        Bool::new(self.get() && rhs.get())
+    }
+}
+
+/// An element type the kernels are instantiated for
+pub(crate) trait Element: Copy + PartialOrd + 'static {
+    /// Type the element is computed in
+    type Acc: Arith;
+    const IS_FLOAT: bool;
+
+    fn widen(self) -> Self::Acc;
+    fn narrow(acc: Self::Acc) -> Self;
+    fn to_f64(self) -> f64;
+    /// Convert like a C cast: floats round, integers truncate and saturate, bool tests `!= 0`
+    fn from_f64(value: f64) -> Self;
+    fn to_i128(self) -> i128;
+    /// Convert like a C integer cast, wrapping on overflow
+    fn from_i128(value: i128) -> Self;
+    fn is_zero(self) -> bool;
+}
+
+macro_rules! impl_float_element {
+    ($($ty:ty: $to:expr, $from:expr);* $(;)?) => {
+        $(
+            impl Element for $ty {
+                type Acc = f64;
+                const IS_FLOAT: bool = true;
+
+                fn widen(self) -> f64 {
+                    self.to_f64()
+                }
+
+                fn narrow(acc: f64) -> Self {
+                    Self::from_f64(acc)
+                }
+
+                fn to_f64(self) -> f64 {
+                    let to: fn($ty) -> f64 = $to;
+                    to(self)
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    let from: fn(f64) -> $ty = $from;
+                    from(value)
+                }
+
+                fn to_i128(self) -> i128 {
+                    Element::to_f64(self) as i128
+                }
+
+                fn from_i128(value: i128) -> Self {
+                    Self::from_f64(value as f64)
+                }
+
+                fn is_zero(self) -> bool {
+                    Element::to_f64(self) == 0.0
+                }
+            }
+        )*
+    };
+}
+
+impl_float_element! {
+    F8E4M3: |x| F8E4M3::to_f64(&x), F8E4M3::from_f64;
+    F8E5M2: |x| F8E5M2::to_f64(&x), F8E5M2::from_f64;
+    bf16: bf16::to_f64, bf16::from_f64;
+    f16: f16::to_f64, f16::from_f64;
+    f32: |x| x as f64, |x| x as f32;
+    f64: |x| x, |x| x;
+}
+
+macro_rules! impl_int_element {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Element for $ty {
+                type Acc = $ty;
+                const IS_FLOAT: bool = false;
+
+                fn widen(self) -> Self {
+                    self
+                }
+
+                fn narrow(acc: Self) -> Self {
+                    acc
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    value as $ty
+                }
+
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+
+                fn from_i128(value: i128) -> Self {
+                    value as $ty
+                }
+
+                fn is_zero(self) -> bool {
+                    self == 0
+                }
+            }
+        )*
+    };
+}
+
+impl_int_element!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl Element for Bool {
+    type Acc = Bool;
+    const IS_FLOAT: bool = false;
+
+    fn widen(self) -> Self {
+        self
+    }
+
+    fn narrow(acc: Self) -> Self {
+        acc
+    }
+
+    fn to_f64(self) -> f64 {
+        self.get() as u8 as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        Bool::new(value != 0.0)
+    }
+
+    fn to_i128(self) -> i128 {
+        self.get() as i128
+    }
+
+    fn from_i128(value: i128) -> Self {
+        Bool::new(value != 0)
+    }
+
+    fn is_zero(self) -> bool {
+        !self.get()
+    }
+}
+
+/// `base^exponent` with the shortcuts of the C kernels' `pow_opt`
+pub(crate) fn pow_opt(base: f64, exponent: f64) -> f64 {
+    if exponent == 0.0 {
+        return 1.0;
+    }
+    if base == 0.0 {
+        return if exponent > 0.0 { 0.0 } else { f64::INFINITY };
+    }
+    if base == 1.0 {
+        return 1.0;
+    }
+    if exponent.floor() == exponent && exponent > -32.0 && exponent < 32.0 {
+        let result = base.powi(exponent.abs() as i32);
+        return if exponent < 0.0 { 1.0 / result } else { result };
+    }
+    if base < 0.0 {
+        return f64::NAN;
+    }
+    base.powf(exponent)
+}
+
+/// Error function, from its Taylor series near zero and a continued fraction for erfc beyond
+pub(crate) fn erf(x: f64) -> f64 {
+    let a = x.abs();
+    if a.is_nan() {
+        return x;
+    }
+    let y = if a < 3.0 {
+        let (mut term, mut sum, x2) = (a, a, a * a);
+        let mut n = 0.0;
+        while term.abs() > 1e-17 * sum.abs() {
+            n += 1.0;
+            term *= -x2 / n;
+            sum += term / (2.0 * n + 1.0);
+        }
+        sum * std::f64::consts::FRAC_2_SQRT_PI
+    } else if a < 6.0 {
+        let mut t = a;
+        for n in (1..=60).rev() {
+            t = a + (n as f64 / 2.0) / t;
+        }
+        1.0 - (-a * a).exp() / (std::f64::consts::PI.sqrt() * t)
+    } else {
+        1.0
+    };
+    y.copysign(x)
+}
+
+/// Kernel metadata, read the way the C kernels index their `size_t` array
+#[derive(Clone, Copy)]
+pub(crate) struct Meta(*const usize);
+
+impl Meta {
+    pub(crate) fn new(metadata: *const usize) -> Self {
+        Self(metadata)
+    }
+
+    /// # Safety
+    ///
+    /// The metadata must hold at least `index + 1` values.
+    pub(crate) unsafe fn get(self, index: usize) -> usize {
+        *self.0.add(index)
+    }
+
+    /// # Safety
+    ///
+    /// The metadata must hold at least `start + len` values.
+    pub(crate) unsafe fn slice<'a>(self, start: usize, len: usize) -> &'a [usize] {
+        std::slice::from_raw_parts(self.0.add(start), len)
+    }
+}
+
+/// Offset of the `index`-th element, in row-major order, of a view with `shape` and `strides`
+pub(crate) fn strided_index(mut index: usize, shape: &[usize], strides: &[usize]) -> usize {
+    let mut offset = 0;
+    for (&dim, &stride) in shape.iter().zip(strides).rev() {
+        offset += (index % dim) * stride;
+        index /= dim;
+    }
+    offset
+}
+
+/// Whether a view with `shape` and `strides` is laid out row-major without gaps
+pub(crate) fn is_contiguous(shape: &[usize], strides: &[usize]) -> bool {
+    let mut expected = 1;
+    for (&dim, &stride) in shape.iter().zip(strides).rev() {
+        if dim > 1 && stride != expected {
+            return false;
+        }
+        expected *= dim;
+    }
+    true
+}
+
+/// Offsets of every element of a strided view, in row-major order
+pub(crate) fn strided_offsets<'a>(
+    num_els: usize,
+    shape: &'a [usize],
+    strides: &'a [usize],
+    offset: usize,
+) -> impl Iterator<Item = usize> + 'a {
+    let contiguous = is_contiguous(shape, strides);
+    (0..num_els).map(move |i| {
+        if contiguous {
+            offset + i
+        } else {
+            offset + strided_index(i, shape, strides)
+        }
+    })
+}
+
+/// Strided view described by the unary metadata layout `[num_els, ndim, shape, strides, offset]`
+pub(crate) struct Layout<'a> {
+    pub(crate) num_els: usize,
+    pub(crate) shape: &'a [usize],
+    pub(crate) strides: &'a [usize],
+    pub(crate) offset: usize,
+}
+
+impl Layout<'_> {
+    /// # Safety
+    ///
+    /// The metadata must follow the unary layout.
+    pub(crate) unsafe fn unary(meta: Meta) -> Self {
+        let num_els = meta.get(0);
+        let ndim = meta.get(1);
+        Self {
+            num_els,
+            shape: meta.slice(2, ndim),
+            strides: meta.slice(2 + ndim, ndim),
+            offset: if ndim > 0 { meta.get(2 + 2 * ndim) } else { 0 },
+        }
+    }
+
+    pub(crate) fn offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        strided_offsets(self.num_els, self.shape, self.strides, self.offset)
+    }
+}
+
+/// Row-major multi-index of `index` within `shape`
+pub(crate) fn unravel(mut index: usize, shape: &[usize], coords: &mut [usize]) {
+    for (coord, &dim) in coords.iter_mut().zip(shape).rev() {
+        *coord = index % dim;
+        index /= dim;
+    }
+}
+
+/// Row-major strides of a contiguous tensor with `shape`
+pub(crate) fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for d in (0..shape.len().saturating_sub(1)).rev() {
+        strides[d] = strides[d + 1] * shape[d + 1];
+    }
+    strides
+}
+
+/// Normalize a possibly negative index against `size`, or `None` if it is out of range
+pub(crate) fn normalize_index(index: i32, size: usize) -> Option<usize> {
+    let index = if index < 0 {
+        index as i64 + size as i64
+    } else {
+        index as i64
+    };
+    (index >= 0 && (index as usize) < size).then_some(index as usize)
+}
+
+/// Borrow the output buffer of a kernel
+///
+/// # Safety
+///
+/// `output` must be valid for writing `len` elements of `T`.
+pub(crate) unsafe fn output<'a, T>(output: *mut std::ffi::c_void, len: usize) -> &'a mut [T] {
+    if len == 0 {
+        return &mut [];
+    }
+    std::slice::from_raw_parts_mut(output as *mut T, len)
+}
+
+/// Read element `index` of a kernel input
+///
+/// # Safety
+///
+/// `input` must be valid for reading `index + 1` elements of `T`.
+pub(crate) unsafe fn read<T: Copy>(input: *const std::ffi::c_void, index: usize) -> T {
+    *(input as *const T).add(index)
+}
@@ -0,0 +1,87 @@
+//! Element-wise binary operations
+
+use super::{all_types, output, read, strided_offsets, Arith, Bool, Element, Meta};
+use core::ffi::c_void;
+
+/// Apply `f` to every pair of elements, writing a contiguous output
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the binary kernel layout.
+pub(super) unsafe fn binary<T: Element, O>(
+    lhs: *const c_void,
+    rhs: *const c_void,
+    out: *mut c_void,
+    metadata: *const usize,
+    f: impl Fn(T, T) -> O,
+) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let lhs_shape = meta.slice(2, ndim);
+    let rhs_shape = meta.slice(2 + ndim, ndim);
+    let lhs_strides = meta.slice(2 + 2 * ndim, ndim);
+    let rhs_strides = meta.slice(2 + 3 * ndim, ndim);
+    let lhs_offset = meta.get(2 + 4 * ndim);
+    let rhs_offset = meta.get(2 + 4 * ndim + 1);
+
+    let out = output::<O>(out, num_els);
+    let lhs_offsets = strided_offsets(num_els, lhs_shape, lhs_strides, lhs_offset);
+    let rhs_offsets = strided_offsets(num_els, rhs_shape, rhs_strides, rhs_offset);
+    for ((o, l), r) in out.iter_mut().zip(lhs_offsets).zip(rhs_offsets) {
+        *o = f(read(lhs, l), read(rhs, r));
+    }
+}
+
+macro_rules! binary_op {
+    ($op:ident, $f:expr; $suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_ $op _ $suffix>](
+                lhs: *const c_void,
+                rhs: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                let f: fn(<$ty as Element>::Acc, <$ty as Element>::Acc) -> <$ty as Element>::Acc = $f;
+                binary(lhs, rhs, output, metadata, |x: $ty, y: $ty| <$ty>::narrow(f(x.widen(), y.widen())));
+            }
+        }
+    };
+}
+
+macro_rules! binary_to_bool_op {
+    ($op:ident, $f:expr; $suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_ $op _ $suffix>](
+                lhs: *const c_void,
+                rhs: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                let f: fn($ty, $ty) -> bool = $f;
+                binary(lhs, rhs, output, metadata, |x: $ty, y: $ty| Bool::new(f(x, y)));
+            }
+        }
+    };
+}
+
+all_types!(binary_op!(add, Arith::add;));
+all_types!(binary_op!(sub, Arith::sub;));
+all_types!(binary_op!(mul, Arith::mul;));
+all_types!(binary_op!(div, Arith::div;));
+all_types!(binary_op!(pow, Arith::pow;));
+all_types!(binary_op!(maximum, Arith::maximum;));
+all_types!(binary_op!(minimum, Arith::minimum;));
+
+all_types!(binary_to_bool_op!(logical_and, |x, y| !x.is_zero() && !y.is_zero();));
+all_types!(binary_to_bool_op!(logical_or, |x, y| !x.is_zero() || !y.is_zero();));
+all_types!(binary_to_bool_op!(logical_xor, |x, y| x.is_zero() != y.is_zero();));
+
+all_types!(binary_to_bool_op!(eq, |x, y| x == y;));
+all_types!(binary_to_bool_op!(ne, |x, y| x != y;));
+all_types!(binary_to_bool_op!(lt, |x, y| x < y;));
+all_types!(binary_to_bool_op!(le, |x, y| x <= y;));
+all_types!(binary_to_bool_op!(gt, |x, y| x > y;));
+all_types!(binary_to_bool_op!(ge, |x, y| x >= y;));
@@ -0,0 +1,92 @@
+//! Bitwise logic and shifts on integers
+
+use super::{int_types, ops_binary::binary, ops_unary::unary};
+use core::ffi::c_void;
+
+macro_rules! bitwise_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_bitwise_and_ $suffix>](lhs: *const c_void, rhs: *const c_void, output: *mut c_void, metadata: *const usize) {
+                binary(lhs, rhs, output, metadata, |x: $ty, y: $ty| x & y);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_bitwise_or_ $suffix>](lhs: *const c_void, rhs: *const c_void, output: *mut c_void, metadata: *const usize) {
+                binary(lhs, rhs, output, metadata, |x: $ty, y: $ty| x | y);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_bitwise_xor_ $suffix>](lhs: *const c_void, rhs: *const c_void, output: *mut c_void, metadata: *const usize) {
+                binary(lhs, rhs, output, metadata, |x: $ty, y: $ty| x ^ y);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_bitwise_not_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                unary(input, output, metadata, |x: $ty| !x);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_shl_ $suffix>](lhs: *const c_void, rhs: *const c_void, output: *mut c_void, metadata: *const usize) {
+                binary(lhs, rhs, output, metadata, |x: $ty, y: $ty| shl(x, u32::try_from(y).unwrap_or(u32::MAX)));
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_shr_ $suffix>](lhs: *const c_void, rhs: *const c_void, output: *mut c_void, metadata: *const usize) {
+                binary(lhs, rhs, output, metadata, |x: $ty, y: $ty| shr(x, u32::try_from(y).unwrap_or(u32::MAX)));
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_shl_scalar_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize, shift: u32) {
+                unary(input, output, metadata, |x: $ty| shl(x, shift));
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_shr_scalar_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize, shift: u32) {
+                unary(input, output, metadata, |x: $ty| shr(x, shift));
+            }
+        }
+    };
+}
+
+trait Shift: Copy {
+    fn checked_shl(self, shift: u32) -> Option<Self>;
+    fn checked_shr(self, shift: u32) -> Option<Self>;
+    /// Result of shifting right by the full width: 0, or -1 for negative signed values
+    fn shr_all(self) -> Self;
+}
+
+macro_rules! impl_shift {
+    ($($ty:ty),*) => {
+        $(
+            impl Shift for $ty {
+                fn checked_shl(self, shift: u32) -> Option<Self> {
+                    <$ty>::checked_shl(self, shift)
+                }
+
+                fn checked_shr(self, shift: u32) -> Option<Self> {
+                    <$ty>::checked_shr(self, shift)
+                }
+
+                fn shr_all(self) -> Self {
+                    self >> (<$ty>::BITS - 1) >> 1
+                }
+            }
+        )*
+    };
+}
+
+impl_shift!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+/// Shift left, shifting every bit out when `shift` is at least the width of the type
+fn shl<T: Shift + Default>(x: T, shift: u32) -> T {
+    x.checked_shl(shift).unwrap_or_default()
+}
+
+/// Shift right, shifting every bit out (sign-filling for signed types) when `shift` is at least
+/// the width of the type
+fn shr<T: Shift>(x: T, shift: u32) -> T {
+    x.checked_shr(shift).unwrap_or_else(|| x.shr_all())
+}
+
+int_types!(bitwise_op!());
@@ -0,0 +1,47 @@
+//! Casts between every pair of dtypes
+
+use super::{all_types, read, Element, Layout, Meta};
+use core::ffi::c_void;
+
+/// Convert like a C cast: through f64 when either side is a float, as integers otherwise
+fn convert<X: Element, Y: Element>(x: X) -> Y {
+    if X::IS_FLOAT || Y::IS_FLOAT {
+        Y::from_f64(x.to_f64())
+    } else {
+        Y::from_i128(x.to_i128())
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the unary kernel layout.
+unsafe fn cast<X: Element, Y: Element>(input: *const c_void, output: *mut c_void, metadata: *const usize) {
+    let layout = Layout::unary(Meta::new(metadata));
+    let out = output as *mut Y;
+    for (i, offset) in layout.offsets().enumerate() {
+        *out.add(i) = convert(read::<X>(input, offset));
+    }
+}
+
+macro_rules! cast_op {
+    ($from:ident, $from_ty:ty; $to:ident, $to_ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_cast_ $from _to_ $to>](
+                input: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                cast::<$from_ty, $to_ty>(input, output, metadata);
+            }
+        }
+    };
+}
+
+macro_rules! cast_from {
+    ($from:ident, $from_ty:ty) => {
+        all_types!(cast_op!($from, $from_ty;));
+    };
+}
+
+all_types!(cast_from!());
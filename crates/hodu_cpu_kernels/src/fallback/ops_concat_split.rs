@@ -0,0 +1,90 @@
+//! Concatenating tensors along a dimension and splitting one back out
+
+use super::{all_types, read, unravel, Meta};
+use core::ffi::c_void;
+
+/// # Safety
+///
+/// Pointers and metadata must follow the concat kernel layout.
+unsafe fn concat<T: Copy>(input: *const c_void, output: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let out_shape = meta.slice(2, ndim);
+    let concat_dim = meta.get(2 + ndim);
+    let num_inputs = meta.get(3 + ndim);
+    let input_shapes = meta.slice(4 + ndim, num_inputs * ndim);
+    let input_strides = meta.slice(4 + ndim + num_inputs * ndim, num_inputs * ndim);
+    let input_offsets = meta.slice(4 + ndim + 2 * num_inputs * ndim, num_inputs);
+    let buffer_offsets = meta.slice(4 + ndim + 2 * num_inputs * ndim + num_inputs, num_inputs);
+
+    let out = output as *mut T;
+    let mut indices = vec![0; ndim];
+    for id in 0..num_els {
+        unravel(id, out_shape, &mut indices);
+        let position = indices[concat_dim];
+        let (mut tensor, mut start) = (0, 0);
+        for i in 0..num_inputs {
+            let size = input_shapes[i * ndim + concat_dim];
+            if position < start + size {
+                tensor = i;
+                break;
+            }
+            start += size;
+        }
+
+        let strides = &input_strides[tensor * ndim..(tensor + 1) * ndim];
+        let mut index = input_offsets[tensor];
+        for (d, (&i, &stride)) in indices.iter().zip(strides).enumerate() {
+            index += if d == concat_dim { i - start } else { i } * stride;
+        }
+        *out.add(id) = read::<T>(input, buffer_offsets[tensor] + index);
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the split kernel layout.
+unsafe fn split<T: Copy>(input: *const c_void, output: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let in_shape = meta.slice(2, ndim);
+    let in_strides = meta.slice(2 + ndim, ndim);
+    let offset = meta.get(2 + 2 * ndim);
+    let split_dim = meta.get(3 + 2 * ndim);
+    let out_size = meta.get(4 + 2 * ndim);
+    let split_offset = meta.get(5 + 2 * ndim);
+
+    let mut out_shape = in_shape.to_vec();
+    out_shape[split_dim] = out_size;
+
+    let out = output as *mut T;
+    let mut indices = vec![0; ndim];
+    for id in 0..num_els {
+        unravel(id, &out_shape, &mut indices);
+        let mut index = offset;
+        for (d, (&i, &stride)) in indices.iter().zip(in_strides).enumerate() {
+            index += if d == split_dim { i + split_offset } else { i } * stride;
+        }
+        *out.add(id) = read::<T>(input, index);
+    }
+}
+
+macro_rules! concat_split_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_concat_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                concat::<$ty>(input, output, metadata);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_split_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                split::<$ty>(input, output, metadata);
+            }
+        }
+    };
+}
+
+all_types!(concat_split_op!());
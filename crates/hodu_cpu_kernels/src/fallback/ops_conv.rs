@@ -0,0 +1,212 @@
+//! Convolutions, transposed convolutions and their weight gradients over 1 to 3 spatial dimensions
+
+use super::{float_types, output, read, strided_index, unravel, Element, Meta};
+use core::ffi::c_void;
+
+/// Every `(source, kernel, target)` triple of flat spatial positions where
+/// `source * stride - padding + kernel * dilation` lands inside `target_shape`
+///
+/// For a convolution the source is the output position and the target the input one; a
+/// transposed convolution swaps them.
+fn taps(
+    source_shape: &[usize],
+    target_shape: &[usize],
+    kernel_shape: &[usize],
+    stride: &[usize],
+    padding: &[usize],
+    dilation: &[usize],
+) -> Vec<(usize, usize, usize)> {
+    let ndim = source_shape.len();
+    let (mut source, mut kernel) = (vec![0; ndim], vec![0; ndim]);
+    let mut taps = Vec::new();
+    for s in 0..source_shape.iter().product() {
+        unravel(s, source_shape, &mut source);
+        'kernel: for k in 0..kernel_shape.iter().product() {
+            unravel(k, kernel_shape, &mut kernel);
+            let mut t = 0;
+            for d in 0..ndim {
+                match (source[d] * stride[d] + kernel[d] * dilation[d]).checked_sub(padding[d]) {
+                    Some(position) if position < target_shape[d] => t = t * target_shape[d] + position,
+                    _ => continue 'kernel,
+                }
+            }
+            taps.push((s, k, t));
+        }
+    }
+    taps
+}
+
+/// Forward convolution, or transposed convolution with a `[IC, OC, ...]` weight
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the conv kernel layout for `spatial` dimensions.
+unsafe fn conv<T: Element>(
+    input: *const c_void,
+    weight: *const c_void,
+    out: *mut c_void,
+    metadata: *const usize,
+    spatial: usize,
+    transposed: bool,
+) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let (batch, in_channels, out_channels) = (meta.get(1), meta.get(2), meta.get(3));
+    let in_shape = meta.slice(4, spatial);
+    let kernel_shape = meta.slice(4 + spatial, spatial);
+    let out_shape = meta.slice(4 + 2 * spatial, spatial);
+    let stride = meta.slice(4 + 3 * spatial, spatial);
+    let padding = meta.slice(4 + 4 * spatial, spatial);
+    let dilation = meta.slice(4 + 5 * spatial, spatial);
+    let in_offset = meta.get(4 + 6 * spatial);
+    let weight_offset = meta.get(5 + 6 * spatial);
+
+    let in_size: usize = in_shape.iter().product();
+    let kernel_size: usize = kernel_shape.iter().product();
+    let out_size: usize = out_shape.iter().product();
+    let taps = if transposed {
+        taps(in_shape, out_shape, kernel_shape, stride, padding, dilation)
+    } else {
+        taps(out_shape, in_shape, kernel_shape, stride, padding, dilation)
+    };
+
+    let mut acc = vec![0.0; num_els];
+    for b in 0..batch {
+        for ic in 0..in_channels {
+            let in_base = in_offset + (b * in_channels + ic) * in_size;
+            for oc in 0..out_channels {
+                let out_base = (b * out_channels + oc) * out_size;
+                let weight_channel = if transposed {
+                    ic * out_channels + oc
+                } else {
+                    oc * in_channels + ic
+                };
+                let weight_base = weight_offset + weight_channel * kernel_size;
+                for &(s, k, t) in &taps {
+                    let (i, o) = if transposed { (s, t) } else { (t, s) };
+                    acc[out_base + o] +=
+                        read::<T>(input, in_base + i).to_f64() * read::<T>(weight, weight_base + k).to_f64();
+                }
+            }
+        }
+    }
+    for (o, acc) in output::<T>(out, num_els).iter_mut().zip(acc) {
+        *o = T::from_f64(acc);
+    }
+}
+
+/// Gradient of a convolution, or a transposed one, with respect to its weight
+///
+/// The input is read contiguously from its offset; grad_output may be strided.
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the conv grad_weight kernel layout.
+unsafe fn conv_grad_weight<T: Element>(
+    input: *const c_void,
+    grad_output: *const c_void,
+    grad_weight: *mut c_void,
+    metadata: *const usize,
+    transposed: bool,
+) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let spatial = meta.get(2);
+    let in_shape = meta.slice(3, ndim);
+    let grad_out_shape = meta.slice(3 + ndim, ndim);
+    let weight_shape = meta.slice(3 + 2 * ndim, ndim);
+    let grad_out_strides = meta.slice(3 + 4 * ndim, ndim);
+    let in_offset = meta.get(3 + 5 * ndim);
+    let grad_out_offset = meta.get(4 + 5 * ndim);
+    let stride = meta.slice(5 + 5 * ndim, spatial);
+    let padding = meta.slice(5 + 5 * ndim + spatial, spatial);
+    let dilation = meta.slice(5 + 5 * ndim + 2 * spatial, spatial);
+
+    let (batch, in_channels, out_channels) = (in_shape[0], in_shape[1], grad_out_shape[1]);
+    let (in_shape, out_shape, kernel_shape) = (&in_shape[2..], &grad_out_shape[2..], &weight_shape[2..]);
+    let in_size: usize = in_shape.iter().product();
+    let kernel_size: usize = kernel_shape.iter().product();
+    let out_offsets: Vec<usize> = (0..out_shape.iter().product())
+        .map(|o| strided_index(o, out_shape, &grad_out_strides[2..]))
+        .collect();
+    let taps = if transposed {
+        taps(in_shape, out_shape, kernel_shape, stride, padding, dilation)
+    } else {
+        taps(out_shape, in_shape, kernel_shape, stride, padding, dilation)
+    };
+
+    let mut acc = vec![0.0; num_els];
+    for b in 0..batch {
+        for ic in 0..in_channels {
+            let in_base = in_offset + (b * in_channels + ic) * in_size;
+            for oc in 0..out_channels {
+                let grad_out_base = grad_out_offset + b * grad_out_strides[0] + oc * grad_out_strides[1];
+                let weight_channel = if transposed {
+                    ic * out_channels + oc
+                } else {
+                    oc * in_channels + ic
+                };
+                let weight_base = weight_channel * kernel_size;
+                for &(s, k, t) in &taps {
+                    let (i, o) = if transposed { (s, t) } else { (t, s) };
+                    acc[weight_base + k] += read::<T>(input, in_base + i).to_f64()
+                        * read::<T>(grad_output, grad_out_base + out_offsets[o]).to_f64();
+                }
+            }
+        }
+    }
+    for (w, acc) in output::<T>(grad_weight, num_els).iter_mut().zip(acc) {
+        *w = T::from_f64(acc);
+    }
+}
+
+macro_rules! conv_op {
+    ($dims:literal; $suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_conv $dims d_ $suffix>](
+                input: *const c_void,
+                weight: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                conv::<$ty>(input, weight, output, metadata, $dims, false);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_conv_transpose $dims d_ $suffix>](
+                input: *const c_void,
+                weight: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                conv::<$ty>(input, weight, output, metadata, $dims, true);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_conv $dims d_grad_weight_ $suffix>](
+                input: *const c_void,
+                grad_output: *const c_void,
+                grad_weight: *mut c_void,
+                metadata: *const usize,
+            ) {
+                conv_grad_weight::<$ty>(input, grad_output, grad_weight, metadata, false);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_conv_transpose $dims d_grad_weight_ $suffix>](
+                input: *const c_void,
+                grad_output: *const c_void,
+                grad_weight: *mut c_void,
+                metadata: *const usize,
+            ) {
+                conv_grad_weight::<$ty>(input, grad_output, grad_weight, metadata, true);
+            }
+        }
+    };
+}
+
+float_types!(conv_op!(1;));
+float_types!(conv_op!(2;));
+float_types!(conv_op!(3;));
@@ -0,0 +1,86 @@
+//! Einstein summation over any number of inputs
+
+use super::{numeric_types, read, Arith, Element, Meta};
+use core::ffi::c_void;
+
+/// Layout of one einsum operand
+struct Operand<'a> {
+    strides: &'a [usize],
+    offset: usize,
+    /// Index id of each dimension
+    dim_to_index: &'a [usize],
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the einsum kernel layout.
+unsafe fn einsum<T: Element>(inputs: *const *const c_void, output: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let num_inputs = meta.get(1);
+    let num_indices = meta.get(2);
+    let num_contraction = meta.get(3);
+    let out_ndim = meta.get(4);
+    let out_shape = meta.slice(5, out_ndim);
+
+    let mut pos = 5 + out_ndim;
+    let mut operands = Vec::with_capacity(num_inputs);
+    for _ in 0..num_inputs {
+        let ndim = meta.get(pos);
+        operands.push(Operand {
+            strides: meta.slice(pos + 1 + ndim, ndim),
+            offset: meta.get(pos + 1 + 2 * ndim),
+            dim_to_index: meta.slice(pos + 2 + 2 * ndim, ndim),
+        });
+        pos += 2 + 3 * ndim;
+    }
+    let contraction_ids = meta.slice(pos, num_contraction);
+    let index_sizes = meta.slice(pos + num_contraction, num_indices);
+    let out_ids = meta.slice(pos + num_contraction + num_indices, out_ndim);
+    let num_contraction_els: usize = contraction_ids.iter().map(|&id| index_sizes[id]).product();
+
+    let out = output as *mut T;
+    let mut values = vec![0; num_indices];
+    for o in 0..num_els {
+        let mut remaining = o;
+        for d in (0..out_ndim).rev() {
+            values[out_ids[d]] = remaining % out_shape[d];
+            remaining /= out_shape[d];
+        }
+        let mut sum = T::Acc::ZERO;
+        for c in 0..num_contraction_els {
+            let mut remaining = c;
+            for &id in contraction_ids.iter().rev() {
+                values[id] = remaining % index_sizes[id];
+                remaining /= index_sizes[id];
+            }
+            let product = operands.iter().enumerate().fold(T::Acc::ONE, |product, (i, operand)| {
+                let index = operand
+                    .dim_to_index
+                    .iter()
+                    .zip(operand.strides)
+                    .fold(operand.offset, |acc, (&id, &stride)| acc + values[id] * stride);
+                product.mul(read::<T>(*inputs.add(i), index).widen())
+            });
+            sum = sum.add(product);
+        }
+        *out.add(o) = T::narrow(sum);
+    }
+}
+
+macro_rules! einsum_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_einsum_ $suffix>](
+                inputs: *const *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                einsum::<$ty>(inputs, output, metadata);
+            }
+        }
+    };
+}
+
+numeric_types!(einsum_op!());
@@ -0,0 +1,427 @@
+//! Selecting, gathering and scattering elements by index, and index-producing operations
+
+use super::{
+    all_types, contiguous_strides, normalize_index, numeric_types, output, read, strided_index, unravel, Arith,
+    Element, Layout, Meta,
+};
+use core::cmp::Ordering;
+use core::ffi::c_void;
+
+/// # Safety
+///
+/// Pointers and metadata must follow the index_select kernel layout.
+unsafe fn index_select<T: Element>(
+    input: *const c_void,
+    indices: *const i32,
+    out: *mut c_void,
+    metadata: *const usize,
+) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let in_shape = meta.slice(2, ndim);
+    let in_strides = meta.slice(2 + ndim, ndim);
+    let offset = meta.get(2 + 2 * ndim);
+    let dim = meta.get(3 + 2 * ndim);
+    let num_indices = meta.get(4 + 2 * ndim);
+
+    let mut out_shape = in_shape.to_vec();
+    out_shape[dim] = num_indices;
+    let out = output::<T>(out, num_els);
+    let mut coords = vec![0; ndim];
+    for (o, value) in out.iter_mut().enumerate() {
+        unravel(o, &out_shape, &mut coords);
+        *value = match normalize_index(*indices.add(coords[dim]), in_shape[dim]) {
+            Some(selected) => {
+                coords[dim] = selected;
+                let index = coords.iter().zip(in_strides).map(|(c, s)| c * s).sum::<usize>();
+                read(input, offset + index)
+            },
+            None => T::from_f64(0.0),
+        };
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the index_put kernel layout.
+unsafe fn index_put<T: Element>(
+    input: *const c_void,
+    indices: *const i32,
+    values: *const c_void,
+    out: *mut c_void,
+    metadata: *const usize,
+) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let shape = meta.slice(2, ndim);
+    let in_strides = meta.slice(2 + ndim, ndim);
+    let values_strides = meta.slice(2 + 2 * ndim, ndim);
+    let in_offset = meta.get(2 + 3 * ndim);
+    let values_offset = meta.get(3 + 3 * ndim);
+    let dim = meta.get(4 + 3 * ndim);
+    let num_indices = meta.get(5 + 3 * ndim);
+
+    // The first index naming each position along `dim` decides which slice of values lands there
+    let mut source = vec![None; shape[dim]];
+    for i in (0..num_indices).rev() {
+        if let Some(target) = normalize_index(*indices.add(i), shape[dim]) {
+            source[target] = Some(i);
+        }
+    }
+
+    let out = output::<T>(out, num_els);
+    let mut coords = vec![0; ndim];
+    for (o, value) in out.iter_mut().enumerate() {
+        unravel(o, shape, &mut coords);
+        *value = match source[coords[dim]] {
+            Some(i) => {
+                coords[dim] = i;
+                let index = coords.iter().zip(values_strides).map(|(c, s)| c * s).sum::<usize>();
+                read(values, values_offset + index)
+            },
+            None => read(input, in_offset + strided_index(o, shape, in_strides)),
+        };
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the gather kernel layout.
+unsafe fn gather<T: Element>(input: *const c_void, indices: *const i32, out: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let out_shape = meta.slice(2, ndim);
+    let in_shape = meta.slice(2 + ndim, ndim);
+    let in_strides = meta.slice(2 + 2 * ndim, ndim);
+    let indices_strides = meta.slice(2 + 3 * ndim, ndim);
+    let in_offset = meta.get(2 + 4 * ndim);
+    let indices_offset = meta.get(3 + 4 * ndim);
+    let dim = meta.get(4 + 4 * ndim);
+
+    let out = output::<T>(out, num_els);
+    let mut coords = vec![0; ndim];
+    for (o, value) in out.iter_mut().enumerate() {
+        unravel(o, out_shape, &mut coords);
+        let index = *indices.add(indices_offset + strided_index(o, out_shape, indices_strides));
+        *value = match normalize_index(index, in_shape[dim]) {
+            Some(selected) => {
+                coords[dim] = selected;
+                let index = coords.iter().zip(in_strides).map(|(c, s)| c * s).sum::<usize>();
+                read(input, in_offset + index)
+            },
+            None => T::from_f64(0.0),
+        };
+    }
+}
+
+/// Copy the input into a contiguous output, then combine each src element into the position its
+/// index names along `dim` with `f`
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the scatter kernel layout.
+unsafe fn scatter_with<T: Element>(
+    input: *const c_void,
+    indices: *const i32,
+    src: *const c_void,
+    out: *mut c_void,
+    metadata: *const usize,
+    f: impl Fn(T, T) -> T,
+) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let in_shape = meta.slice(2, ndim);
+    let in_strides = meta.slice(2 + ndim, ndim);
+    let src_shape = meta.slice(2 + 2 * ndim, ndim);
+    let src_strides = meta.slice(2 + 3 * ndim, ndim);
+    let indices_strides = meta.slice(2 + 4 * ndim, ndim);
+    let in_offset = meta.get(2 + 5 * ndim);
+    let src_offset = meta.get(3 + 5 * ndim);
+    let indices_offset = meta.get(4 + 5 * ndim);
+    let dim = meta.get(5 + 5 * ndim);
+
+    let in_num_els: usize = in_shape.iter().product();
+    let out = output::<T>(out, in_num_els);
+    for (i, value) in out.iter_mut().enumerate() {
+        *value = read(input, in_offset + strided_index(i, in_shape, in_strides));
+    }
+
+    let out_strides = contiguous_strides(in_shape);
+    let mut coords = vec![0; ndim];
+    for s in 0..num_els {
+        let index = *indices.add(indices_offset + strided_index(s, src_shape, indices_strides));
+        let Some(target) = normalize_index(index, in_shape[dim]) else {
+            continue;
+        };
+        unravel(s, src_shape, &mut coords);
+        coords[dim] = target;
+        let o = coords.iter().zip(&out_strides).map(|(c, s)| c * s).sum::<usize>();
+        if o >= in_num_els {
+            continue;
+        }
+        out[o] = f(out[o], read(src, src_offset + strided_index(s, src_shape, src_strides)));
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the onehot kernel layout.
+unsafe fn onehot<T: Element>(indices: *const i32, out: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let num_input_els = meta.get(1);
+    let num_classes = meta.get(2);
+    let axis = meta.get(3);
+    let out_ndim = meta.get(4);
+    let out_shape = meta.slice(5, out_ndim);
+
+    let out = output::<T>(out, num_els);
+    out.fill(T::from_f64(0.0));
+    let out_strides = contiguous_strides(out_shape);
+    let in_shape: Vec<usize> = (0..out_ndim).filter(|&d| d != axis).map(|d| out_shape[d]).collect();
+    let in_strides: Vec<usize> = (0..out_ndim).filter(|&d| d != axis).map(|d| out_strides[d]).collect();
+    for i in 0..num_input_els {
+        if let Some(class) = normalize_index(*indices.add(i), num_classes) {
+            out[class * out_strides[axis] + strided_index(i, &in_shape, &in_strides)] = T::from_f64(1.0);
+        }
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the unary kernel layout.
+unsafe fn nonzero_count<T: Element>(input: *const c_void, metadata: *const usize) -> usize {
+    let layout = Layout::unary(Meta::new(metadata));
+    layout
+        .offsets()
+        .filter(|&offset| !read::<T>(input, offset).is_zero())
+        .count()
+}
+
+/// Write the multi-index of every nonzero element, in row-major order
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the unary kernel layout, with room for every index.
+unsafe fn nonzero_fill<T: Element>(input: *const c_void, out: *mut i32, metadata: *const usize) {
+    let layout = Layout::unary(Meta::new(metadata));
+    let ndim = layout.shape.len();
+    let mut coords = vec![0; ndim];
+    let mut written = 0;
+    for (i, offset) in layout.offsets().enumerate() {
+        if !read::<T>(input, offset).is_zero() {
+            unravel(i, layout.shape, &mut coords);
+            for (d, &coord) in coords.iter().enumerate() {
+                *out.add(written * ndim + d) = coord as i32;
+            }
+            written += 1;
+        }
+    }
+}
+
+/// Write the sorted distinct values, the position of each element among them and their counts
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the unary kernel layout, with room for `num_els` values.
+unsafe fn unique<T: Element>(
+    input: *const c_void,
+    values: *mut c_void,
+    inverse: *mut i32,
+    counts: *mut i32,
+    metadata: *const usize,
+) -> usize {
+    let layout = Layout::unary(Meta::new(metadata));
+    let elements: Vec<T> = layout.offsets().map(|offset| read(input, offset)).collect();
+    let mut order: Vec<usize> = (0..elements.len()).collect();
+    order.sort_by(|&a, &b| elements[a].partial_cmp(&elements[b]).unwrap_or(Ordering::Equal));
+
+    let values = values as *mut T;
+    let mut num_unique = 0;
+    for (i, &index) in order.iter().enumerate() {
+        let value = elements[index];
+        if i == 0 || value.to_f64() != elements[order[i - 1]].to_f64() {
+            *values.add(num_unique) = value;
+            *counts.add(num_unique) = 0;
+            num_unique += 1;
+        }
+        *counts.add(num_unique - 1) += 1;
+        *inverse.add(index) = (num_unique - 1) as i32;
+    }
+    num_unique
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the compress kernel layout.
+unsafe fn compress<T: Element>(input: *const c_void, condition: *const bool, out: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let shape = meta.slice(2, ndim);
+    let strides = meta.slice(2 + ndim, ndim);
+    let offset = meta.get(2 + 2 * ndim);
+    let condition_size = meta.get(3 + 2 * ndim);
+    let axis_flag = meta.get(4 + 2 * ndim);
+    let condition = core::slice::from_raw_parts(condition, condition_size);
+
+    let out = out as *mut T;
+    let mut written = 0;
+    if axis_flag == 0 {
+        for (i, _) in condition
+            .iter()
+            .enumerate()
+            .take(num_els)
+            .filter(|(_, &selected)| selected)
+        {
+            *out.add(written) = read(input, offset + strided_index(i, shape, strides));
+            written += 1;
+        }
+        return;
+    }
+
+    let axis = meta.get(5 + 2 * ndim);
+    let outer_size: usize = shape[..axis].iter().product();
+    let inner_size: usize = shape[axis + 1..].iter().product();
+    for outer in 0..outer_size {
+        let outer_offset = offset + strided_index(outer, &shape[..axis], &strides[..axis]);
+        for (c, _) in condition.iter().enumerate().filter(|(_, &selected)| selected) {
+            for inner in 0..inner_size {
+                let index =
+                    outer_offset + c * strides[axis] + strided_index(inner, &shape[axis + 1..], &strides[axis + 1..]);
+                *out.add(written) = read(input, index);
+                written += 1;
+            }
+        }
+    }
+}
+
+macro_rules! indexing_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_index_select_ $suffix>](
+                input: *const c_void,
+                indices: *const i32,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                index_select::<$ty>(input, indices, output, metadata);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_index_put_ $suffix>](
+                input: *const c_void,
+                indices: *const i32,
+                values: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                index_put::<$ty>(input, indices, values, output, metadata);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_gather_ $suffix>](
+                input: *const c_void,
+                indices: *const i32,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                gather::<$ty>(input, indices, output, metadata);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_scatter_ $suffix>](
+                input: *const c_void,
+                indices: *const i32,
+                src: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                scatter_with::<$ty>(input, indices, src, output, metadata, |_, src| src);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_onehot_ $suffix>](indices: *const i32, output: *mut c_void, metadata: *const usize) {
+                onehot::<$ty>(indices, output, metadata);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_nonzero_count_ $suffix>](input: *const c_void, metadata: *const usize) -> usize {
+                nonzero_count::<$ty>(input, metadata)
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_nonzero_fill_ $suffix>](input: *const c_void, output: *mut i32, metadata: *const usize) {
+                nonzero_fill::<$ty>(input, output, metadata);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_unique_ $suffix>](
+                input: *const c_void,
+                values: *mut c_void,
+                inverse: *mut i32,
+                counts: *mut i32,
+                metadata: *const usize,
+            ) -> usize {
+                unique::<$ty>(input, values, inverse, counts, metadata)
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_compress_ $suffix>](
+                input: *const c_void,
+                condition: *const bool,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                compress::<$ty>(input, condition, output, metadata);
+            }
+        }
+    };
+}
+
+macro_rules! scatter_reduce_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_scatter_add_ $suffix>](
+                input: *const c_void,
+                indices: *const i32,
+                src: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                scatter_with::<$ty>(input, indices, src, output, metadata, |x, y| <$ty>::narrow(x.widen().add(y.widen())));
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_scatter_max_ $suffix>](
+                input: *const c_void,
+                indices: *const i32,
+                src: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                scatter_with::<$ty>(input, indices, src, output, metadata, |x, y| if y > x { y } else { x });
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_scatter_min_ $suffix>](
+                input: *const c_void,
+                indices: *const i32,
+                src: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                scatter_with::<$ty>(input, indices, src, output, metadata, |x, y| if y < x { y } else { x });
+            }
+        }
+    };
+}
+
+all_types!(indexing_op!());
+numeric_types!(scatter_reduce_op!());
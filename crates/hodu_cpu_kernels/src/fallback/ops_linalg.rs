@@ -0,0 +1,204 @@
+//! Determinant, inverse and trace of batched square matrices
+
+use super::{numeric_types, read, Arith, Element, Meta};
+use core::ffi::c_void;
+
+/// Visit each matrix of a batched `[..., N, N]` input, loaded row-major in its compute type
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the linalg kernel layout.
+unsafe fn for_each_matrix<T: Element>(
+    input: *const c_void,
+    metadata: *const usize,
+    mut f: impl FnMut(usize, usize, &mut [T::Acc]),
+) {
+    let meta = Meta::new(metadata);
+    let batch_size = meta.get(0);
+    let n = meta.get(1);
+    let ndim = meta.get(2);
+    let shape = meta.slice(3, ndim);
+    let strides = meta.slice(3 + ndim, ndim);
+    let offset = meta.get(3 + 2 * ndim);
+    let row_stride = if ndim >= 2 { strides[ndim - 2] } else { n };
+    let col_stride = if ndim >= 1 { strides[ndim - 1] } else { 1 };
+
+    let mut matrix = vec![T::Acc::ZERO; n * n];
+    for batch in 0..batch_size {
+        let mut batch_offset = offset;
+        let mut remaining = batch;
+        for d in (0..ndim.saturating_sub(2)).rev() {
+            batch_offset += remaining % shape[d] * strides[d];
+            remaining /= shape[d];
+        }
+        for i in 0..n {
+            for j in 0..n {
+                matrix[i * n + j] = read::<T>(input, batch_offset + i * row_stride + j * col_stride).widen();
+            }
+        }
+        f(batch, n, &mut matrix);
+    }
+}
+
+/// Index of the row at or below `k` with the largest magnitude in column `k`
+fn pivot_row<A: Arith>(matrix: &[A], width: usize, k: usize, n: usize) -> usize {
+    let mut pivot = k;
+    let mut max = matrix[k * width + k].abs();
+    for i in k + 1..n {
+        let value = matrix[i * width + k].abs();
+        if value > max {
+            max = value;
+            pivot = i;
+        }
+    }
+    pivot
+}
+
+fn swap_rows<A>(matrix: &mut [A], width: usize, a: usize, b: usize) {
+    for j in 0..width {
+        matrix.swap(a * width + j, b * width + j);
+    }
+}
+
+/// Determinant by cofactor expansion up to 3x3 and LU decomposition with partial pivoting beyond
+fn det<T: Element>(m: &mut [T::Acc], n: usize) -> T::Acc {
+    match n {
+        1 => m[0],
+        2 => m[0].mul(m[3]).sub(m[1].mul(m[2])),
+        3 => {
+            let [a, b, c, d, e, f, g, h, i] = [m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7], m[8]];
+            a.mul(e.mul(i).sub(f.mul(h)))
+                .sub(b.mul(d.mul(i).sub(f.mul(g))))
+                .add(c.mul(d.mul(h).sub(e.mul(g))))
+        },
+        _ => {
+            let epsilon = T::from_f64(1e-15).widen();
+            let mut det = T::Acc::ONE;
+            let mut swaps = 0;
+            for k in 0..n {
+                let pivot_row = pivot_row(m, n, k, n);
+                if pivot_row != k {
+                    swap_rows(m, n, k, pivot_row);
+                    swaps += 1;
+                }
+                let pivot = m[k * n + k];
+                if pivot.abs() < epsilon {
+                    return T::Acc::ZERO;
+                }
+                det = det.mul(pivot);
+                for i in k + 1..n {
+                    let factor = m[i * n + k].div(pivot);
+                    for j in k..n {
+                        m[i * n + j] = m[i * n + j].sub(factor.mul(m[k * n + j]));
+                    }
+                }
+            }
+            if swaps % 2 != 0 {
+                det.neg()
+            } else {
+                det
+            }
+        },
+    }
+}
+
+/// Inverse by the adjugate up to 3x3 and Gauss-Jordan elimination with partial pivoting beyond
+fn inv<A: Arith>(m: &[A], n: usize, out: &mut [A]) {
+    match n {
+        1 => out[0] = A::ONE.div(m[0]),
+        2 => {
+            let inv_det = A::ONE.div(m[0].mul(m[3]).sub(m[1].mul(m[2])));
+            out[0] = m[3].mul(inv_det);
+            out[1] = m[1].neg().mul(inv_det);
+            out[2] = m[2].neg().mul(inv_det);
+            out[3] = m[0].mul(inv_det);
+        },
+        3 => {
+            let [a, b, c, d, e, f, g, h, i] = [m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7], m[8]];
+            let det = a
+                .mul(e.mul(i).sub(f.mul(h)))
+                .sub(b.mul(d.mul(i).sub(f.mul(g))))
+                .add(c.mul(d.mul(h).sub(e.mul(g))));
+            let inv_det = A::ONE.div(det);
+            let adjugate = [
+                e.mul(i).sub(f.mul(h)),
+                c.mul(h).sub(b.mul(i)),
+                b.mul(f).sub(c.mul(e)),
+                f.mul(g).sub(d.mul(i)),
+                a.mul(i).sub(c.mul(g)),
+                c.mul(d).sub(a.mul(f)),
+                d.mul(h).sub(e.mul(g)),
+                b.mul(g).sub(a.mul(h)),
+                a.mul(e).sub(b.mul(d)),
+            ];
+            for (o, x) in out.iter_mut().zip(adjugate) {
+                *o = x.mul(inv_det);
+            }
+        },
+        _ => {
+            let width = 2 * n;
+            let mut aug = vec![A::ZERO; n * width];
+            for i in 0..n {
+                aug[i * width..i * width + n].copy_from_slice(&m[i * n..(i + 1) * n]);
+                aug[i * width + n + i] = A::ONE;
+            }
+            for k in 0..n {
+                let pivot_row = pivot_row(&aug, width, k, n);
+                if pivot_row != k {
+                    swap_rows(&mut aug, width, k, pivot_row);
+                }
+                let pivot = aug[k * width + k];
+                for j in 0..width {
+                    aug[k * width + j] = aug[k * width + j].div(pivot);
+                }
+                for i in (0..n).filter(|&i| i != k) {
+                    let factor = aug[i * width + k];
+                    for j in 0..width {
+                        aug[i * width + j] = aug[i * width + j].sub(factor.mul(aug[k * width + j]));
+                    }
+                }
+            }
+            for i in 0..n {
+                out[i * n..(i + 1) * n].copy_from_slice(&aug[i * width + n..(i + 1) * width]);
+            }
+        },
+    }
+}
+
+macro_rules! linalg_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_det_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                let out = output as *mut $ty;
+                for_each_matrix::<$ty>(input, metadata, |batch, n, m| {
+                    *out.add(batch) = <$ty>::narrow(det::<$ty>(m, n));
+                });
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_inv_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                let out = output as *mut $ty;
+                let mut inverse = Vec::new();
+                for_each_matrix::<$ty>(input, metadata, |batch, n, m| {
+                    inverse.resize(n * n, Arith::ZERO);
+                    inv(m, n, &mut inverse);
+                    for (i, &x) in inverse.iter().enumerate() {
+                        *out.add(batch * n * n + i) = <$ty>::narrow(x);
+                    }
+                });
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_trace_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                let out = output as *mut $ty;
+                for_each_matrix::<$ty>(input, metadata, |batch, n, m| {
+                    let trace = (0..n).fold(Arith::ZERO, |acc: <$ty as Element>::Acc, i| acc.add(m[i * n + i]));
+                    *out.add(batch) = <$ty>::narrow(trace);
+                });
+            }
+        }
+    };
+}
+
+numeric_types!(linalg_op!());
@@ -0,0 +1,122 @@
+//! Batched matrix multiplication and 2D dot products
+
+use super::{numeric_types, read, unravel, Arith, Element, Meta};
+use core::ffi::c_void;
+
+/// Batched `lhs @ rhs` with broadcast batch dimensions, accumulating with `mac` from `zero`
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the matmul kernel layout.
+pub(super) unsafe fn matmul_with<L: Copy, R: Copy, A: Copy, O>(
+    lhs: *const c_void,
+    rhs: *const c_void,
+    output: *mut c_void,
+    metadata: *const usize,
+    zero: A,
+    mac: impl Fn(A, L, R) -> A,
+    finish: impl Fn(A) -> O,
+) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let lhs_ndim = meta.get(1);
+    let rhs_ndim = meta.get(2);
+    let batch_ndim = meta.get(3);
+    let lhs_shape = meta.slice(4, lhs_ndim);
+    let rhs_shape = meta.slice(4 + lhs_ndim, rhs_ndim);
+    let batch_shape = meta.slice(4 + lhs_ndim + rhs_ndim, batch_ndim);
+    let base = 4 + lhs_ndim + rhs_ndim + batch_ndim;
+    let lhs_strides = meta.slice(base, lhs_ndim);
+    let rhs_strides = meta.slice(base + lhs_ndim, rhs_ndim);
+    let base = base + lhs_ndim + rhs_ndim;
+    let (lhs_offset, rhs_offset) = (meta.get(base), meta.get(base + 1));
+    let (m, k, n) = (meta.get(base + 2), meta.get(base + 3), meta.get(base + 4));
+
+    /// Offset of the batch a matrix operand contributes, broadcasting size-1 dimensions
+    fn batch_offset(batch: &[usize], shape: &[usize], strides: &[usize], offset: usize) -> usize {
+        let ndim = shape.len() - 2;
+        let skip = batch.len() - ndim;
+        (0..ndim).fold(offset, |acc, d| {
+            acc + if shape[d] == 1 { 0 } else { batch[skip + d] * strides[d] }
+        })
+    }
+
+    let out = output as *mut O;
+    let mut batch = vec![0; batch_ndim];
+    for idx in 0..num_els {
+        let (batch_idx, mn) = (idx / (m * n), idx % (m * n));
+        let (i, j) = (mn / n, mn % n);
+        unravel(batch_idx, batch_shape, &mut batch);
+        let lhs_base = batch_offset(&batch, lhs_shape, lhs_strides, lhs_offset) + i * lhs_strides[lhs_ndim - 2];
+        let rhs_base = batch_offset(&batch, rhs_shape, rhs_strides, rhs_offset) + j * rhs_strides[rhs_ndim - 1];
+        let mut acc = zero;
+        for p in 0..k {
+            acc = mac(
+                acc,
+                read(lhs, lhs_base + p * lhs_strides[lhs_ndim - 1]),
+                read(rhs, rhs_base + p * rhs_strides[rhs_ndim - 2]),
+            );
+        }
+        *out.add(idx) = finish(acc);
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the dot kernel layout.
+unsafe fn dot<T: Element>(lhs: *const c_void, rhs: *const c_void, output: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let (m, k, n) = (meta.get(0), meta.get(1), meta.get(2));
+    let (lhs_stride_m, lhs_stride_k) = (meta.get(3), meta.get(4));
+    let (rhs_stride_k, rhs_stride_n) = (meta.get(5), meta.get(6));
+    let (lhs_offset, rhs_offset) = (meta.get(7), meta.get(8));
+
+    let out = output as *mut T;
+    for i in 0..m {
+        for j in 0..n {
+            let mut acc = T::Acc::ZERO;
+            for p in 0..k {
+                let x = read::<T>(lhs, lhs_offset + i * lhs_stride_m + p * lhs_stride_k);
+                let y = read::<T>(rhs, rhs_offset + p * rhs_stride_k + j * rhs_stride_n);
+                acc = acc.add(x.widen().mul(y.widen()));
+            }
+            *out.add(i * n + j) = T::narrow(acc);
+        }
+    }
+}
+
+macro_rules! matrix_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_matmul_ $suffix>](
+                lhs: *const c_void,
+                rhs: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                matmul_with(
+                    lhs,
+                    rhs,
+                    output,
+                    metadata,
+                    Arith::ZERO,
+                    |acc: <$ty as Element>::Acc, x: $ty, y: $ty| acc.add(x.widen().mul(y.widen())),
+                    <$ty>::narrow,
+                );
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_dot_ $suffix>](
+                lhs: *const c_void,
+                rhs: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                dot::<$ty>(lhs, rhs, output, metadata);
+            }
+        }
+    };
+}
+
+numeric_types!(matrix_op!());
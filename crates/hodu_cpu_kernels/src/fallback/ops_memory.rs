@@ -0,0 +1,21 @@
+//! Copying a strided view into a contiguous buffer
+
+use super::{all_types, read, Layout, Meta};
+use core::ffi::c_void;
+
+macro_rules! contiguous_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_contiguous_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                let layout = Layout::unary(Meta::new(metadata));
+                let out = output as *mut $ty;
+                for (i, offset) in layout.offsets().enumerate() {
+                    *out.add(i) = read::<$ty>(input, offset);
+                }
+            }
+        }
+    };
+}
+
+all_types!(contiguous_op!());
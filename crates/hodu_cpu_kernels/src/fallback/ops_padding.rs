@@ -0,0 +1,103 @@
+//! Padding a contiguous tensor with a constant, reflected, replicated or wrapped border
+
+use super::{all_types, read, Meta};
+use core::ffi::c_void;
+
+fn reflect_index(index: isize, size: usize) -> Option<usize> {
+    let mut index = index.unsigned_abs();
+    if index >= size {
+        let period = 2 * (size - 1);
+        index = if period > 0 { index % period } else { 0 };
+        if index >= size {
+            index = period - index;
+        }
+    }
+    Some(index)
+}
+
+fn replicate_index(index: isize, size: usize) -> Option<usize> {
+    Some(index.clamp(0, size as isize - 1) as usize)
+}
+
+fn circular_index(index: isize, size: usize) -> Option<usize> {
+    Some(index.rem_euclid(size as isize) as usize)
+}
+
+fn constant_index(index: isize, size: usize) -> Option<usize> {
+    (index >= 0 && (index as usize) < size).then_some(index as usize)
+}
+
+/// Fill the output, mapping each padded coordinate back into the input with `map`
+///
+/// A coordinate `map` rejects, which only the constant mode does, takes `pad_value`.
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the padding kernel layout.
+unsafe fn pad<T: Copy>(
+    input: *const c_void,
+    output: *mut c_void,
+    metadata: *const usize,
+    pad_value: Option<T>,
+    map: fn(isize, usize) -> Option<usize>,
+) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let in_shape = meta.slice(2, ndim);
+    let out_shape = meta.slice(2 + ndim, ndim);
+    let pad_before = meta.slice(2 + 2 * ndim, ndim);
+
+    let out = output as *mut T;
+    'elements: for i in 0..num_els {
+        let (mut remaining, mut index, mut stride) = (i, 0, 1);
+        for d in (0..ndim).rev() {
+            let coord = (remaining % out_shape[d]) as isize - pad_before[d] as isize;
+            remaining /= out_shape[d];
+            match map(coord, in_shape[d]) {
+                Some(coord) => index += coord * stride,
+                None => {
+                    if let Some(value) = pad_value {
+                        *out.add(i) = value;
+                    }
+                    continue 'elements;
+                },
+            }
+            stride *= in_shape[d];
+        }
+        *out.add(i) = read::<T>(input, index);
+    }
+}
+
+macro_rules! pad_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_pad_constant_ $suffix>](
+                input: *const c_void,
+                output: *mut c_void,
+                pad_value: *const c_void,
+                metadata: *const usize,
+            ) {
+                pad::<$ty>(input, output, metadata, Some(read(pad_value, 0)), constant_index);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_pad_reflect_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                pad::<$ty>(input, output, metadata, None, reflect_index);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_pad_replicate_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                pad::<$ty>(input, output, metadata, None, replicate_index);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_pad_circular_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                pad::<$ty>(input, output, metadata, None, circular_index);
+            }
+        }
+    };
+}
+
+all_types!(pad_op!());
@@ -0,0 +1,221 @@
+//! Affine int8 and 4-bit block quantization, and the matmuls that consume them
+//!
+//! Values are computed in f32, as the C kernels do, so that rounding lands on the same codes.
+
+use super::{output, read, Element, Layout, Meta};
+use core::ffi::c_void;
+use half::f16;
+
+/// Values per Q4_0 block
+const Q4_0_BLOCK_SIZE: usize = 32;
+/// Bytes per Q4_0 block: an f16 scale followed by 16 bytes of packed 4-bit codes
+const Q4_0_BLOCK_BYTES: usize = 18;
+
+fn to_f32<T: Element>(x: T) -> f32 {
+    x.to_f64() as f32
+}
+
+fn from_f32<T: Element>(x: f32) -> T {
+    T::from_f64(x as f64)
+}
+
+fn quantize_value(x: f32, scale: f32, zero_point: i32) -> i8 {
+    let q = (x / scale).round_ties_even() + zero_point as f32;
+    if q >= -128.0 {
+        q.min(127.0) as i8
+    } else {
+        -128
+    }
+}
+
+/// Decode one Q4_0 block into `dst`
+fn q4_0_decode_block(block: &[u8], dst: &mut [f32]) {
+    let d = f16::from_le_bytes([block[0], block[1]]).to_f32();
+    for (j, &code) in block[2..Q4_0_BLOCK_BYTES].iter().enumerate() {
+        dst[2 * j] = ((code & 0x0F) as i32 - 8) as f32 * d;
+        dst[2 * j + 1] = ((code >> 4) as i32 - 8) as f32 * d;
+    }
+}
+
+/// Encode 32 values into one Q4_0 block
+fn q4_0_encode_block(src: &[f32], block: &mut [u8]) {
+    let amax = src
+        .iter()
+        .fold(0.0f32, |amax, x| if x.abs() > amax { x.abs() } else { amax });
+    // Quantize against the f16-rounded scale so that decoding reproduces the encoder's grid
+    let scale = f16::from_f32(amax / 7.0);
+    block[..2].copy_from_slice(&scale.to_le_bytes());
+    let d = scale.to_f32();
+    let code = |x: f32| {
+        let v = if d > 0.0 { (x / d).round_ties_even() + 8.0 } else { 8.0 };
+        if v >= 0.0 {
+            v.min(15.0) as u8
+        } else {
+            0
+        }
+    };
+    for (j, byte) in block[2..Q4_0_BLOCK_BYTES].iter_mut().enumerate() {
+        *byte = code(src[2 * j]) | code(src[2 * j + 1]) << 4;
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the unary kernel layout.
+unsafe fn quantize<T: Element>(input: *const c_void, out: *mut c_void, metadata: *const usize, scale: f32, zp: i32) {
+    let layout = Layout::unary(Meta::new(metadata));
+    let out = output::<i8>(out, layout.num_els);
+    for (o, offset) in out.iter_mut().zip(layout.offsets()) {
+        *o = quantize_value(to_f32(read::<T>(input, offset)), scale, zp);
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the unary kernel layout.
+unsafe fn dequantize<T: Element>(input: *const c_void, out: *mut c_void, metadata: *const usize, scale: f32, zp: i32) {
+    let layout = Layout::unary(Meta::new(metadata));
+    let out = output::<T>(out, layout.num_els);
+    for (o, offset) in out.iter_mut().zip(layout.offsets()) {
+        *o = from_f32((read::<i8>(input, offset) as i32 - zp) as f32 * scale);
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the unary kernel layout, with a multiple of 32 elements.
+unsafe fn quantize_q4_0<T: Element>(input: *const c_void, out: *mut c_void, metadata: *const usize) {
+    let layout = Layout::unary(Meta::new(metadata));
+    let num_blocks = layout.num_els / Q4_0_BLOCK_SIZE;
+    let out = output::<u8>(out, num_blocks * Q4_0_BLOCK_BYTES);
+    let mut values = [0.0; Q4_0_BLOCK_SIZE];
+    let mut offsets = layout.offsets();
+    for block in out.chunks_exact_mut(Q4_0_BLOCK_BYTES) {
+        for (value, offset) in values.iter_mut().zip(&mut offsets) {
+            *value = to_f32(read::<T>(input, offset));
+        }
+        q4_0_encode_block(&values, block);
+    }
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the dequantize_q4_0 kernel layout.
+unsafe fn dequantize_q4_0<T: Element>(input: *const c_void, out: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let num_blocks = meta.get(0);
+    let blocks = core::slice::from_raw_parts((input as *const u8).add(meta.get(1)), num_blocks * Q4_0_BLOCK_BYTES);
+    let out = output::<T>(out, num_blocks * Q4_0_BLOCK_SIZE);
+    let mut values = [0.0; Q4_0_BLOCK_SIZE];
+    for (block, out) in blocks
+        .chunks_exact(Q4_0_BLOCK_BYTES)
+        .zip(out.chunks_exact_mut(Q4_0_BLOCK_SIZE))
+    {
+        q4_0_decode_block(block, &mut values);
+        for (o, &x) in out.iter_mut().zip(&values) {
+            *o = from_f32(x);
+        }
+    }
+}
+
+/// `out[M, N] = lhs[M, K] @ dequant(weight[N, K])^T`, decoding each weight row once
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the matmul_q4_0 kernel layout.
+unsafe fn matmul_q4_0<T: Element>(lhs: *const c_void, weight: *const c_void, out: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let (m, k, n) = (meta.get(0), meta.get(1), meta.get(2));
+    let lhs_offset = meta.get(3);
+    let row_bytes = k / Q4_0_BLOCK_SIZE * Q4_0_BLOCK_BYTES;
+    let weight = core::slice::from_raw_parts((weight as *const u8).add(meta.get(4)), n * row_bytes);
+
+    let out = output::<T>(out, m * n);
+    let mut w = vec![0.0; k];
+    for (j, row) in weight.chunks_exact(row_bytes).enumerate() {
+        for (block, dst) in row
+            .chunks_exact(Q4_0_BLOCK_BYTES)
+            .zip(w.chunks_exact_mut(Q4_0_BLOCK_SIZE))
+        {
+            q4_0_decode_block(block, dst);
+        }
+        for i in 0..m {
+            let acc = w.iter().enumerate().fold(0.0f32, |acc, (p, &w)| {
+                acc + to_f32(read::<T>(lhs, lhs_offset + i * k + p)) * w
+            });
+            out[i * n + j] = from_f32(acc);
+        }
+    }
+}
+
+/// Batched int8 matmul with int32 accumulation and output
+#[no_mangle]
+unsafe extern "C" fn hodu_cpu_matmul_int8_i8(
+    lhs: *const c_void,
+    rhs: *const c_void,
+    output: *mut c_void,
+    metadata: *const usize,
+) {
+    super::ops_matrix::matmul_with(
+        lhs,
+        rhs,
+        output,
+        metadata,
+        0i32,
+        |acc, x: i8, y: i8| acc.wrapping_add(x as i32 * y as i32),
+        |acc| acc,
+    );
+}
+
+macro_rules! quant_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_quantize_ $suffix>](
+                input: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+                scale: f32,
+                zp: i32,
+            ) {
+                quantize::<$ty>(input, output, metadata, scale, zp);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_dequantize_ $suffix>](
+                input: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+                scale: f32,
+                zp: i32,
+            ) {
+                dequantize::<$ty>(input, output, metadata, scale, zp);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_quantize_q4_0_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                quantize_q4_0::<$ty>(input, output, metadata);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_dequantize_q4_0_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                dequantize_q4_0::<$ty>(input, output, metadata);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_matmul_q4_0_ $suffix>](
+                lhs: *const c_void,
+                weight: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+            ) {
+                matmul_q4_0::<$ty>(lhs, weight, output, metadata);
+            }
+        }
+    };
+}
+
+quant_op!(bf16, half::bf16);
+quant_op!(f16, half::f16);
+quant_op!(f32, f32);
+quant_op!(f64, f64);
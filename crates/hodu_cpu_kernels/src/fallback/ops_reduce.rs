@@ -0,0 +1,150 @@
+//! Reductions over a set of dimensions
+
+use super::{all_types, float_types, numeric_types, read, unravel, Arith, Bool, Element, Meta};
+use core::ffi::c_void;
+
+/// Reduce every output position over its slice of the input
+///
+/// `f` receives the reduced elements in order, each paired with its index along the first reduced
+/// dimension.
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the reduce kernel layout.
+unsafe fn reduce<T: Element, O>(
+    input: *const c_void,
+    output: *mut c_void,
+    metadata: *const usize,
+    f: impl Fn(&[(usize, T)]) -> O,
+) {
+    let meta = Meta::new(metadata);
+    let ndim = meta.get(0);
+    let dims = meta.slice(1, ndim);
+    let strides = meta.slice(1 + ndim, ndim);
+    let offset = meta.get(1 + 2 * ndim);
+    let out_ndim = meta.get(2 + 2 * ndim);
+    let out_shape = meta.slice(3 + 2 * ndim, out_ndim);
+    let num_reduce_dims = meta.get(3 + 2 * ndim + out_ndim);
+    let reduce_dims = meta.slice(4 + 2 * ndim + out_ndim, num_reduce_dims);
+    let keep_dim = meta.get(4 + 2 * ndim + out_ndim + num_reduce_dims) != 0;
+    let reduce_size = meta.get(5 + 2 * ndim + out_ndim + num_reduce_dims);
+    let num_els: usize = out_shape.iter().product();
+
+    let out = output as *mut O;
+    let mut out_indices = vec![0; out_ndim];
+    let mut in_indices = vec![0; ndim];
+    let mut values = Vec::with_capacity(reduce_size);
+    for o in 0..num_els {
+        unravel(o, out_shape, &mut out_indices);
+        if keep_dim {
+            in_indices.iter_mut().zip(&out_indices).for_each(|(i, &o)| *i = o);
+        } else {
+            let mut kept = out_indices.iter();
+            for (d, index) in in_indices.iter_mut().enumerate() {
+                *index = if reduce_dims.contains(&d) {
+                    0
+                } else {
+                    kept.next().copied().unwrap_or(0)
+                };
+            }
+        }
+
+        values.clear();
+        for r in 0..reduce_size {
+            let mut remaining = r;
+            for &d in reduce_dims.iter().rev() {
+                in_indices[d] = remaining % dims[d];
+                remaining /= dims[d];
+            }
+            let index = offset + in_indices.iter().zip(strides).map(|(i, s)| i * s).sum::<usize>();
+            let position = reduce_dims.first().map_or(0, |&d| in_indices[d]);
+            values.push((position, read::<T>(input, index)));
+        }
+        *out.add(o) = f(&values);
+    }
+}
+
+fn fold<T: Element>(values: &[(usize, T)], init: T::Acc, f: impl Fn(T::Acc, T::Acc) -> T::Acc) -> T {
+    T::narrow(values.iter().fold(init, |acc, &(_, x)| f(acc, x.widen())))
+}
+
+/// Population mean and variance by Welford's method, NaN when empty
+fn welford<T: Element>(values: &[(usize, T)]) -> (f64, f64) {
+    if values.is_empty() {
+        return (f64::NAN, f64::NAN);
+    }
+    let (mut mean, mut m2) = (0.0, 0.0);
+    for (n, &(_, x)) in values.iter().enumerate() {
+        let x = x.to_f64();
+        let delta = x - mean;
+        mean += delta / (n + 1) as f64;
+        m2 += delta * (x - mean);
+    }
+    (mean, m2 / values.len() as f64)
+}
+
+fn logsumexp<T: Element>(values: &[(usize, T)]) -> f64 {
+    let max = values
+        .iter()
+        .map(|&(_, x)| x.to_f64())
+        .fold(f64::NEG_INFINITY, f64::max);
+    if max.is_infinite() {
+        return max;
+    }
+    max + values.iter().map(|&(_, x)| (x.to_f64() - max).exp()).sum::<f64>().ln()
+}
+
+/// Index of the first extreme element, where `better(x, best)` replaces the current one
+fn arg<T: Element>(values: &[(usize, T)], better: impl Fn(T, T) -> bool) -> i32 {
+    let mut best: Option<(usize, T)> = None;
+    for &(index, x) in values {
+        if best.is_none_or(|(_, b)| better(x, b)) {
+            best = Some((index, x));
+        }
+    }
+    best.map_or(0, |(index, _)| index as i32)
+}
+
+macro_rules! reduce_op {
+    ($op:ident -> $out:ty, $f:expr; $suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_ $op _ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                let f: fn(&[(usize, $ty)]) -> $out = $f;
+                reduce(input, output, metadata, f);
+            }
+        }
+    };
+    ($op:ident, $f:expr; $suffix:ident, $ty:ty) => {
+        reduce_op!($op -> $ty, $f; $suffix, $ty);
+    };
+}
+
+macro_rules! float_reduce_op {
+    ($op:ident, $f:expr; $suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_ $op _ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                let f: fn(&[(usize, $ty)]) -> f64 = $f;
+                reduce(input, output, metadata, |values: &[(usize, $ty)]| <$ty>::from_f64(f(values)));
+            }
+        }
+    };
+}
+
+numeric_types!(reduce_op!(sum, |values| fold(values, Arith::ZERO, Arith::add);));
+numeric_types!(reduce_op!(prod, |values| fold(values, Arith::ONE, Arith::mul);));
+numeric_types!(reduce_op!(max, |values| fold(values, Arith::LOWEST, Arith::maximum);));
+numeric_types!(reduce_op!(min, |values| fold(values, Arith::HIGHEST, Arith::minimum);));
+
+float_types!(float_reduce_op!(mean, |values| welford(values).0;));
+float_types!(float_reduce_op!(var, |values| welford(values).1;));
+float_types!(float_reduce_op!(std, |values| welford(values).1.sqrt();));
+float_types!(float_reduce_op!(norm, |values| values.iter().map(|&(_, x)| x.to_f64().powi(2)).sum::<f64>().sqrt();));
+float_types!(float_reduce_op!(logsum, |values| values.iter().map(|&(_, x)| x.to_f64()).sum::<f64>().ln();));
+float_types!(float_reduce_op!(logsumexp, logsumexp;));
+
+all_types!(reduce_op!(argmax -> i32, |values| arg(values, |x, best| x > best);));
+all_types!(reduce_op!(argmin -> i32, |values| arg(values, |x, best| x < best);));
+all_types!(reduce_op!(any -> Bool, |values| Bool::new(values.iter().any(|&(_, x)| !x.is_zero()));));
+all_types!(reduce_op!(all -> Bool, |values| Bool::new(values.iter().all(|&(_, x)| !x.is_zero()));));
@@ -0,0 +1,141 @@
+//! Nearest, linear and cubic resizing of the spatial dimensions of NC... tensors
+
+use super::{float_types, read, unravel, Element, Meta};
+use core::ffi::c_void;
+
+const MODE_LINEAR: usize = 1;
+const MODE_CUBIC: usize = 2;
+
+const COORD_HALF_PIXEL: usize = 0;
+const COORD_ASYMMETRIC: usize = 1;
+const COORD_ALIGN_CORNERS: usize = 2;
+const COORD_PYTORCH_HALF_PIXEL: usize = 3;
+
+const NEAREST_CEIL: usize = 1;
+const NEAREST_ROUND_PREFER_FLOOR: usize = 2;
+const NEAREST_ROUND_PREFER_CEIL: usize = 3;
+
+/// Dimensions before the spatial ones: batch and channel
+const SPATIAL_START: usize = 2;
+
+/// Map an output coordinate to the input, in f32 like the C kernels so the same pixels are picked
+fn transform_coord(out_coord: usize, in_size: usize, out_size: usize, coord_transform: usize) -> f32 {
+    let out_coord = out_coord as f32;
+    if in_size == out_size {
+        return out_coord;
+    }
+    let scale = in_size as f32 / out_size as f32;
+    match coord_transform {
+        COORD_HALF_PIXEL => (out_coord + 0.5) * scale - 0.5,
+        COORD_ASYMMETRIC => out_coord * scale,
+        COORD_ALIGN_CORNERS if out_size == 1 => 0.0,
+        COORD_ALIGN_CORNERS => out_coord * (in_size - 1) as f32 / (out_size - 1) as f32,
+        COORD_PYTORCH_HALF_PIXEL if out_size == 1 => 0.0,
+        COORD_PYTORCH_HALF_PIXEL => (out_coord + 0.5) * scale - 0.5,
+        _ => out_coord * scale,
+    }
+}
+
+fn round_nearest(coord: f32, max_index: usize, nearest_mode: usize) -> usize {
+    let rounded = match nearest_mode {
+        NEAREST_CEIL => coord.ceil(),
+        NEAREST_ROUND_PREFER_FLOOR if coord == coord.floor() + 0.5 => coord.floor(),
+        NEAREST_ROUND_PREFER_FLOOR | NEAREST_ROUND_PREFER_CEIL => coord.round(),
+        _ => coord.floor(),
+    };
+    if rounded < 0.0 {
+        0
+    } else if rounded > max_index as f32 {
+        max_index
+    } else {
+        rounded as usize
+    }
+}
+
+fn clamp_index(index: isize, max_index: usize) -> usize {
+    index.clamp(0, max_index as isize) as usize
+}
+
+/// Catmull-Rom spline through `p` evaluated at `t` between `p[1]` and `p[2]`
+fn cubic_interp(p: [f64; 4], t: f64) -> f64 {
+    let a = -0.5 * p[0] + 1.5 * p[1] - 1.5 * p[2] + 0.5 * p[3];
+    let b = p[0] - 2.5 * p[1] + 2.0 * p[2] - 0.5 * p[3];
+    let c = -0.5 * p[0] + 0.5 * p[2];
+    a * t * t * t + b * t * t + c * t + p[1]
+}
+
+/// # Safety
+///
+/// Pointers and metadata must follow the resize kernel layout.
+unsafe fn resize<T: Element>(input: *const c_void, output: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let in_shape = meta.slice(2, ndim);
+    let in_strides = meta.slice(2 + ndim, ndim);
+    let offset = meta.get(2 + 2 * ndim);
+    let out_shape = meta.slice(3 + 2 * ndim, ndim);
+    let mode = meta.get(3 + 3 * ndim);
+    let coord_transform = meta.get(4 + 3 * ndim);
+    let nearest_mode = meta.get(5 + 3 * ndim);
+    let num_spatial = ndim - SPATIAL_START;
+
+    let out = output as *mut T;
+    let value = |index: usize| read::<T>(input, offset + index).to_f64();
+    let mut out_coords = vec![0; ndim];
+    let mut coords = vec![0.0; num_spatial];
+    for o in 0..num_els {
+        unravel(o, out_shape, &mut out_coords);
+        let base = out_coords[0] * in_strides[0] + out_coords[1] * in_strides[1];
+        for (s, coord) in coords.iter_mut().enumerate() {
+            let d = SPATIAL_START + s;
+            *coord = transform_coord(out_coords[d], in_shape[d], out_shape[d], coord_transform);
+        }
+        // Clamped input position `delta` steps from the floor of the coordinate along spatial dim `s`
+        let position = |s: usize, delta: isize| {
+            let d = SPATIAL_START + s;
+            clamp_index(coords[s].floor() as isize + delta, in_shape[d] - 1) * in_strides[d]
+        };
+        let fraction = |s: usize| (coords[s] - coords[s].floor()) as f64;
+
+        *out.add(o) = if mode == MODE_LINEAR && (num_spatial == 2 || num_spatial == 3) {
+            // Weight each corner of the surrounding cell by its distance along every dimension
+            let mut result = 0.0;
+            for corner in 0..1usize << num_spatial {
+                let (mut index, mut weight) = (base, 1.0);
+                for s in 0..num_spatial {
+                    let upper = corner >> (num_spatial - 1 - s) & 1;
+                    index += position(s, upper as isize);
+                    weight *= if upper == 1 { fraction(s) } else { 1.0 - fraction(s) };
+                }
+                result += weight * value(index);
+            }
+            T::from_f64(result)
+        } else if mode == MODE_CUBIC && num_spatial == 2 {
+            let rows = [-1, 0, 1, 2].map(|dy| {
+                let row = base + position(0, dy);
+                cubic_interp([-1, 0, 1, 2].map(|dx| value(row + position(1, dx))), fraction(1))
+            });
+            T::from_f64(cubic_interp(rows, fraction(0)))
+        } else {
+            let index = coords.iter().enumerate().fold(base, |index, (s, &coord)| {
+                let d = SPATIAL_START + s;
+                index + round_nearest(coord, in_shape[d] - 1, nearest_mode) * in_strides[d]
+            });
+            read(input, offset + index)
+        };
+    }
+}
+
+macro_rules! resize_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_resize_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                resize::<$ty>(input, output, metadata);
+            }
+        }
+    };
+}
+
+float_types!(resize_op!());
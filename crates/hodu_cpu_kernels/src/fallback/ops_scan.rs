@@ -0,0 +1,76 @@
+//! Cumulative sums and products along a dimension
+
+use super::{contiguous_strides, numeric_types, read, Arith, Element, Meta};
+use core::ffi::c_void;
+
+/// Scan a strided input along `dim` into a contiguous output, combining with `f` from `init`
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the scan kernel layout.
+unsafe fn scan<T: Element>(
+    input: *const c_void,
+    output: *mut c_void,
+    metadata: *const usize,
+    init: T::Acc,
+    f: fn(T::Acc, T::Acc) -> T::Acc,
+) {
+    let meta = Meta::new(metadata);
+    let ndim = meta.get(1);
+    let shape = meta.slice(2, ndim);
+    let strides = meta.slice(2 + ndim, ndim);
+    let offset = meta.get(2 + 2 * ndim);
+    let out = output as *mut T;
+    if ndim == 0 {
+        *out = read(input, offset);
+        return;
+    }
+    let dim = meta.get(3 + 2 * ndim);
+    let out_strides = contiguous_strides(shape);
+
+    let outer_size: usize = shape[..dim].iter().product();
+    let inner_size: usize = shape[dim + 1..].iter().product();
+    for outer in 0..outer_size {
+        for inner in 0..inner_size {
+            let (mut in_base, mut out_base) = (offset, 0);
+            let mut remaining = outer;
+            for d in (0..dim).rev() {
+                let coord = remaining % shape[d];
+                remaining /= shape[d];
+                in_base += coord * strides[d];
+                out_base += coord * out_strides[d];
+            }
+            let mut remaining = inner;
+            for d in (dim + 1..ndim).rev() {
+                let coord = remaining % shape[d];
+                remaining /= shape[d];
+                in_base += coord * strides[d];
+                out_base += coord * out_strides[d];
+            }
+
+            let mut acc = init;
+            for s in 0..shape[dim] {
+                acc = f(acc, read::<T>(input, in_base + s * strides[dim]).widen());
+                *out.add(out_base + s * out_strides[dim]) = T::narrow(acc);
+            }
+        }
+    }
+}
+
+macro_rules! scan_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_cumsum_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                scan::<$ty>(input, output, metadata, Arith::ZERO, Arith::add);
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_cumprod_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                scan::<$ty>(input, output, metadata, Arith::ONE, Arith::mul);
+            }
+        }
+    };
+}
+
+numeric_types!(scan_op!());
@@ -0,0 +1,41 @@
+//! Reversing a contiguous tensor along a set of dimensions
+
+use super::{all_types, read, Meta};
+use core::ffi::c_void;
+
+/// # Safety
+///
+/// Metadata must be `[num_els, ndim, shape, flip_mask]` with a contiguous input.
+unsafe fn flip<T: Copy>(input: *const c_void, output: *mut c_void, metadata: *const usize) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let shape = meta.slice(2, ndim);
+    let flip_mask = meta.slice(2 + ndim, ndim);
+
+    let out = output as *mut T;
+    for i in 0..num_els {
+        let (mut remaining, mut index, mut stride) = (i, 0, 1);
+        for d in (0..ndim).rev() {
+            let coord = remaining % shape[d];
+            remaining /= shape[d];
+            let coord = if flip_mask[d] != 0 { shape[d] - 1 - coord } else { coord };
+            index += coord * stride;
+            stride *= shape[d];
+        }
+        *out.add(i) = read::<T>(input, index);
+    }
+}
+
+macro_rules! flip_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_flip_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                flip::<$ty>(input, output, metadata);
+            }
+        }
+    };
+}
+
+all_types!(flip_op!());
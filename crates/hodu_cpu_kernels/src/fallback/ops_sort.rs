@@ -0,0 +1,66 @@
+//! Top-k selection along the last dimension
+
+use super::{numeric_types, read, Meta};
+use core::cmp::Ordering;
+use core::ffi::c_void;
+
+/// Write the `k` largest or smallest elements of each row, with their indices
+///
+/// Results always come out sorted, which also satisfies an unsorted request.
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the topk kernel layout.
+unsafe fn topk<T: Copy + PartialOrd>(
+    input: *const c_void,
+    values: *mut c_void,
+    indices: *mut c_void,
+    metadata: *const usize,
+) {
+    let meta = Meta::new(metadata);
+    let k = meta.get(1);
+    let row_size = meta.get(2);
+    let outer_size = meta.get(3);
+    let largest = meta.get(4) != 0;
+    let offset = meta.get(6);
+
+    let values = values as *mut T;
+    let indices = indices as *mut i32;
+    let mut order: Vec<usize> = Vec::with_capacity(row_size);
+    for outer in 0..outer_size {
+        let row = offset + outer * row_size;
+        order.clear();
+        order.extend(0..row_size);
+        order.sort_by(|&a, &b| {
+            let (a, b) = (read::<T>(input, row + a), read::<T>(input, row + b));
+            let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+            if largest {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        for (i, &index) in order.iter().take(k).enumerate() {
+            *values.add(outer * k + i) = read(input, row + index);
+            *indices.add(outer * k + i) = index as i32;
+        }
+    }
+}
+
+macro_rules! topk_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_topk_ $suffix>](
+                input: *const c_void,
+                values: *mut c_void,
+                indices: *mut c_void,
+                metadata: *const usize,
+            ) {
+                topk::<$ty>(input, values, indices, metadata);
+            }
+        }
+    };
+}
+
+numeric_types!(topk_op!());
@@ -0,0 +1,231 @@
+//! Element-wise unary operations, with and without a scalar operand
+
+use super::{all_types, erf, float_types, read, Arith, Bool, Element, Layout, Meta};
+use core::ffi::c_void;
+
+/// Apply `f` to every element, writing a contiguous output
+///
+/// A null `input` applies `f` in place to the first `num_els` elements of `out`.
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the unary kernel layout.
+pub(super) unsafe fn unary<T: Element, O>(
+    input: *const c_void,
+    out: *mut c_void,
+    metadata: *const usize,
+    f: impl Fn(T) -> O,
+) {
+    let layout = Layout::unary(Meta::new(metadata));
+    let out = out as *mut O;
+    if input.is_null() {
+        for i in 0..layout.num_els {
+            let x = *(out as *const T).add(i);
+            *out.add(i) = f(x);
+        }
+    } else {
+        for (i, offset) in layout.offsets().enumerate() {
+            *out.add(i) = f(read(input, offset));
+        }
+    }
+}
+
+/// Apply `f` in f64, for the floating-point math functions
+macro_rules! float_op {
+    ($op:ident, $f:expr; $suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_ $op _ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                let f: fn(f64) -> f64 = $f;
+                unary(input, output, metadata, |x: $ty| <$ty>::from_f64(f(x.to_f64())));
+            }
+        }
+    };
+}
+
+/// Apply `f` to the element itself
+macro_rules! element_op {
+    ($op:ident; $suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_ $op _ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                unary(input, output, metadata, $op::<$ty>);
+            }
+        }
+    };
+}
+
+macro_rules! to_bool_op {
+    ($op:ident, $f:expr; $suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_ $op _ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                let f: fn($ty) -> bool = $f;
+                unary(input, output, metadata, |x: $ty| Bool::new(f(x)));
+            }
+        }
+    };
+}
+
+macro_rules! scalar_op {
+    ($op:ident, $f:expr; $suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_ $op _ $suffix>](
+                input: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+                scalar: *const c_void,
+            ) {
+                let f: fn(<$ty as Element>::Acc, <$ty as Element>::Acc) -> <$ty as Element>::Acc = $f;
+                let c = read::<$ty>(scalar, 0).widen();
+                unary(input, output, metadata, |x: $ty| <$ty>::narrow(f(x.widen(), c)));
+            }
+        }
+    };
+}
+
+macro_rules! cmp_scalar_op {
+    ($op:ident, $f:expr; $suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_ $op _ $suffix>](
+                input: *const c_void,
+                output: *mut c_void,
+                metadata: *const usize,
+                scalar: *const c_void,
+            ) {
+                let f: fn($ty, $ty) -> bool = $f;
+                let c = read::<$ty>(scalar, 0);
+                unary(input, output, metadata, |x: $ty| Bool::new(f(x, c)));
+            }
+        }
+    };
+}
+
+fn abs<T: Element>(x: T) -> T {
+    T::narrow(x.widen().abs())
+}
+
+fn neg<T: Element>(x: T) -> T {
+    T::narrow(x.widen().neg())
+}
+
+fn square<T: Element>(x: T) -> T {
+    let x = x.widen();
+    T::narrow(x.mul(x))
+}
+
+fn sign<T: Element>(x: T) -> T {
+    let x = x.widen();
+    T::narrow(if x > T::Acc::ZERO {
+        T::Acc::ONE
+    } else if x < T::Acc::ZERO {
+        T::Acc::ZERO.sub(T::Acc::ONE)
+    } else {
+        T::Acc::ZERO
+    })
+}
+
+fn sqrt<T: Element>(x: T) -> T {
+    let x = x.to_f64();
+    T::from_f64(if T::IS_FLOAT { x.sqrt() } else { x.abs().sqrt() })
+}
+
+fn recip<T: Element>(x: T) -> T {
+    if T::IS_FLOAT || !x.is_zero() {
+        T::from_f64(1.0 / x.to_f64())
+    } else {
+        x
+    }
+}
+
+fn softsign<T: Element>(x: T) -> T {
+    let x = x.to_f64();
+    T::from_f64(x / (1.0 + x.abs()))
+}
+
+fn softplus(x: f64) -> f64 {
+    x.exp().ln_1p()
+}
+
+fn hardsigmoid(x: f64) -> f64 {
+    ((x + 3.0) / 6.0).clamp(0.0, 1.0)
+}
+
+fn gelu(x: f64) -> f64 {
+    0.5 * x * (1.0 + (0.7978845608 * (x + 0.044715 * x * x * x)).tanh())
+}
+
+fn selu(x: f64) -> f64 {
+    const ALPHA: f64 = 1.6732632423543772848170429916717;
+    const SCALE: f64 = 1.0507009873554804934193349852946;
+    SCALE * if x > 0.0 { x } else { ALPHA * x.exp_m1() }
+}
+
+all_types!(element_op!(abs;));
+element_op!(neg; bool, Bool);
+float_types!(element_op!(neg;));
+element_op!(neg; i8, i8);
+element_op!(neg; i16, i16);
+element_op!(neg; i32, i32);
+element_op!(neg; i64, i64);
+all_types!(element_op!(sign;));
+all_types!(element_op!(square;));
+all_types!(element_op!(sqrt;));
+all_types!(element_op!(recip;));
+all_types!(element_op!(softsign;));
+
+float_types!(float_op!(relu, |x| if x > 0.0 { x } else { 0.0 };));
+float_types!(float_op!(sigmoid, |x| 1.0 / (1.0 + (-x).exp());));
+float_types!(float_op!(hardsigmoid, hardsigmoid;));
+float_types!(float_op!(gelu, gelu;));
+float_types!(float_op!(softplus, softplus;));
+float_types!(float_op!(silu, |x| x / (1.0 + (-x).exp());));
+float_types!(float_op!(hardsilu, |x| x * hardsigmoid(x);));
+float_types!(float_op!(mish, |x| x * softplus(x).tanh();));
+float_types!(float_op!(selu, selu;));
+float_types!(float_op!(celu, |x| x.max(0.0) + x.exp_m1().min(0.0);));
+
+float_types!(float_op!(sin, f64::sin;));
+float_types!(float_op!(cos, f64::cos;));
+float_types!(float_op!(tan, f64::tan;));
+float_types!(float_op!(asin, f64::asin;));
+float_types!(float_op!(acos, f64::acos;));
+float_types!(float_op!(atan, f64::atan;));
+float_types!(float_op!(sinh, f64::sinh;));
+float_types!(float_op!(cosh, f64::cosh;));
+float_types!(float_op!(tanh, f64::tanh;));
+float_types!(float_op!(asinh, f64::asinh;));
+float_types!(float_op!(acosh, f64::acosh;));
+float_types!(float_op!(atanh, f64::atanh;));
+float_types!(float_op!(exp, f64::exp;));
+float_types!(float_op!(exp2, f64::exp2;));
+float_types!(float_op!(exp10, |x| (x * std::f64::consts::LN_10).exp();));
+float_types!(float_op!(ln, f64::ln;));
+float_types!(float_op!(log2, f64::log2;));
+float_types!(float_op!(log10, f64::log10;));
+float_types!(float_op!(ceil, f64::ceil;));
+float_types!(float_op!(floor, f64::floor;));
+float_types!(float_op!(round, f64::round;));
+float_types!(float_op!(erf, erf;));
+
+all_types!(to_bool_op!(logical_not, |x| x.is_zero();));
+float_types!(to_bool_op!(isnan, |x| x.to_f64().is_nan();));
+float_types!(to_bool_op!(isinf, |x| x.to_f64().is_infinite();));
+float_types!(to_bool_op!(isfinite, |x| x.to_f64().is_finite();));
+
+all_types!(scalar_op!(add_scalar, Arith::add;));
+all_types!(scalar_op!(sub_scalar, Arith::sub_scalar;));
+all_types!(scalar_op!(mul_scalar, Arith::mul;));
+all_types!(scalar_op!(div_scalar, Arith::div;));
+all_types!(scalar_op!(pow_scalar, Arith::pow_scalar;));
+all_types!(scalar_op!(maximum_scalar, Arith::maximum;));
+all_types!(scalar_op!(minimum_scalar, Arith::minimum;));
+
+all_types!(cmp_scalar_op!(eq_scalar, |x, c| x == c;));
+all_types!(cmp_scalar_op!(ne_scalar, |x, c| x != c;));
+all_types!(cmp_scalar_op!(lt_scalar, |x, c| x < c;));
+all_types!(cmp_scalar_op!(le_scalar, |x, c| x <= c;));
+all_types!(cmp_scalar_op!(gt_scalar, |x, c| x > c;));
+all_types!(cmp_scalar_op!(ge_scalar, |x, c| x >= c;));
@@ -0,0 +1,88 @@
+//! Reductions over sliding windows
+
+use super::{float_types, numeric_types, read, unravel, Arith, Element, Meta};
+use core::ffi::c_void;
+
+/// Fold every window of the input with `f` from `init`, skipping positions in the padding
+///
+/// Each window's result goes through `finish` along with the full window size.
+///
+/// # Safety
+///
+/// Pointers and metadata must follow the windowing kernel layout.
+unsafe fn reduce_window<T: Element>(
+    input: *const c_void,
+    output: *mut c_void,
+    metadata: *const usize,
+    init: T::Acc,
+    f: fn(T::Acc, T::Acc) -> T::Acc,
+    finish: fn(T::Acc, usize) -> T,
+) {
+    let meta = Meta::new(metadata);
+    let num_els = meta.get(0);
+    let ndim = meta.get(1);
+    let in_shape = meta.slice(2, ndim);
+    let in_strides = meta.slice(2 + ndim, ndim);
+    let offset = meta.get(2 + 2 * ndim);
+    let window_shape = meta.slice(3 + 2 * ndim, ndim);
+    let strides = meta.slice(3 + 3 * ndim, ndim);
+    let padding = meta.slice(3 + 4 * ndim, 2 * ndim);
+    let out_shape = meta.slice(3 + 6 * ndim, ndim);
+    let window_size: usize = window_shape.iter().product();
+
+    let out = output as *mut T;
+    let mut out_coords = vec![0; ndim];
+    let mut window_coords = vec![0; ndim];
+    for o in 0..num_els {
+        unravel(o, out_shape, &mut out_coords);
+        let mut acc = init;
+        'window: for w in 0..window_size {
+            unravel(w, window_shape, &mut window_coords);
+            let mut index = offset;
+            for d in 0..ndim {
+                let position = (out_coords[d] * strides[d] + window_coords[d]).checked_sub(padding[2 * d]);
+                match position {
+                    Some(position) if position < in_shape[d] => index += position * in_strides[d],
+                    _ => continue 'window,
+                }
+            }
+            acc = f(acc, read::<T>(input, index).widen());
+        }
+        *out.add(o) = finish(acc, window_size);
+    }
+}
+
+macro_rules! reduce_window_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_reduce_window_max_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                reduce_window::<$ty>(input, output, metadata, Arith::LOWEST, Arith::maximum, |acc, _| <$ty>::narrow(acc));
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_reduce_window_min_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                reduce_window::<$ty>(input, output, metadata, Arith::HIGHEST, Arith::minimum, |acc, _| <$ty>::narrow(acc));
+            }
+
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_reduce_window_sum_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                reduce_window::<$ty>(input, output, metadata, Arith::ZERO, Arith::add, |acc, _| <$ty>::narrow(acc));
+            }
+        }
+    };
+}
+
+macro_rules! reduce_window_mean_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_reduce_window_mean_ $suffix>](input: *const c_void, output: *mut c_void, metadata: *const usize) {
+                reduce_window::<$ty>(input, output, metadata, 0.0, Arith::add, |sum, size| <$ty>::from_f64(sum / size as f64));
+            }
+        }
+    };
+}
+
+numeric_types!(reduce_window_op!());
+float_types!(reduce_window_mean_op!());
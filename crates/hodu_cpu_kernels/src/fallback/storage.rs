@@ -0,0 +1,22 @@
+//! Filling a strided view with a constant
+
+use super::{all_types, read, Layout, Meta};
+use core::ffi::c_void;
+
+macro_rules! const_set_op {
+    ($suffix:ident, $ty:ty) => {
+        paste::paste! {
+            #[no_mangle]
+            unsafe extern "C" fn [<hodu_cpu_const_set_ $suffix>](output: *mut c_void, metadata: *const usize, value: *const c_void) {
+                let layout = Layout::unary(Meta::new(metadata));
+                let value = read::<$ty>(value, 0);
+                let out = output as *mut $ty;
+                for offset in layout.offsets() {
+                    *out.add(offset) = value;
+                }
+            }
+        }
+    };
+}
+
+all_types!(const_set_op!());
@@ -13,6 +13,7 @@ pub mod ops_linalg;
 pub mod ops_matrix;
 pub mod ops_memory;
 pub mod ops_padding;
+pub mod ops_quant;
 pub mod ops_reduce;
 pub mod ops_resize;
 pub mod ops_scan;
@@ -37,6 +38,7 @@ pub use ops_linalg::*;
 pub use ops_matrix::*;
 pub use ops_memory::*;
 pub use ops_padding::*;
+pub use ops_quant::*;
 pub use ops_reduce::*;
 pub use ops_resize::*;
 pub use ops_scan::*;
@@ -0,0 +1,352 @@
+//! Int8 quantization operations
+//!
+//! This module provides:
+//! - `quantize`: Affine quantization of floating-point tensors to int8
+//! - `dequantize`: Affine dequantization of int8 tensors to floating point
+//! - `matmul_int8`: Batched int8 × int8 matrix multiplication accumulated in int32
+//!
+//! Quantize kernels are named after the floating-point source type (e.g. `quantize::F32`),
+//! dequantize kernels after the floating-point destination type (e.g. `dequantize::F32`).
+//! The only int8 matmul kernel is `matmul_int8::I8`.
+//!
+//! 4-bit block quantization (Q4_0, 32 values per 18-byte block):
+//! - `quantize_q4_0`: Pack floating-point values into Q4_0 blocks
+//! - `dequantize_q4_0`: Unpack Q4_0 blocks to floating point
+//! - `matmul_q4_0`: Fused dequant-matmul against a packed weight, named after the activation type
+
+use crate::{error::Result, kernels::macros::ops};
+use core::ffi::c_void;
+
+// Define all quantization operations using the macro
+ops!(
+    quantize,
+    dequantize,
+    matmul_int8,
+    quantize_q4_0,
+    dequantize_q4_0,
+    matmul_q4_0
+);
+
+/// Execute a quantize operation
+///
+/// Computes `q = clamp(round(x / scale) + zero_point, -128, 127)` elementwise, rounding half to
+/// even. Symmetric quantization uses `zero_point = 0`.
+///
+/// # Arguments
+/// * `kernel_name` - The quantize kernel to execute (e.g., quantize::F32)
+/// * `input` - Pointer to floating-point input tensor
+/// * `output` - Pointer to i8 output buffer
+/// * `metadata` - Tensor metadata array (see layout below)
+/// * `scale` - Quantization step
+/// * `zero_point` - Integer value that represents 0.0
+///
+/// # Metadata layout
+/// - metadata[0]: num_els (total number of elements)
+/// - metadata[1]: num_dims (number of dimensions)
+/// - metadata[2..2+num_dims]: shape
+/// - metadata[2+num_dims..2+2*num_dims]: strides
+/// - metadata[2+2*num_dims]: offset
+///
+/// # Safety
+/// This function uses unsafe FFI calls to C kernels. Caller must ensure:
+/// - All pointers are valid and properly aligned
+/// - Metadata accurately describes tensor layout
+/// - Output buffer has sufficient capacity (num_els i8 elements)
+///
+/// # Returns
+/// Returns `Ok(())` on success.
+pub fn call_ops_quantize(
+    kernel_name: crate::kernels::macros::Kernel,
+    input: *const c_void,
+    output: *mut c_void,
+    metadata: &[usize],
+    scale: f32,
+    zero_point: i32,
+) -> Result<()> {
+    unsafe {
+        dispatch_quantize(kernel_name.0, input, output, metadata.as_ptr(), scale, zero_point);
+    }
+
+    Ok(())
+}
+
+/// Execute a dequantize operation
+///
+/// Computes `x = (q - zero_point) * scale` elementwise.
+///
+/// # Arguments
+/// * `kernel_name` - The dequantize kernel to execute (e.g., dequantize::F32)
+/// * `input` - Pointer to i8 input tensor
+/// * `output` - Pointer to floating-point output buffer
+/// * `metadata` - Tensor metadata array (same layout as quantize)
+/// * `scale` - Quantization step
+/// * `zero_point` - Integer value that represents 0.0
+///
+/// # Safety
+/// This function uses unsafe FFI calls to C kernels. Caller must ensure:
+/// - All pointers are valid and properly aligned
+/// - Metadata accurately describes tensor layout
+/// - Output buffer has sufficient capacity (num_els elements of the destination type)
+///
+/// # Returns
+/// Returns `Ok(())` on success.
+pub fn call_ops_dequantize(
+    kernel_name: crate::kernels::macros::Kernel,
+    input: *const c_void,
+    output: *mut c_void,
+    metadata: &[usize],
+    scale: f32,
+    zero_point: i32,
+) -> Result<()> {
+    unsafe {
+        dispatch_dequantize(kernel_name.0, input, output, metadata.as_ptr(), scale, zero_point);
+    }
+
+    Ok(())
+}
+
+/// Execute an int8 matrix multiplication
+///
+/// Performs `C = A @ B` on i8 operands with i32 accumulation and an i32 output. Batch
+/// dimensions broadcast exactly like `matmul`.
+///
+/// # Arguments
+/// * `kernel_name` - The kernel to execute (matmul_int8::I8)
+/// * `lhs` - Pointer to i8 left-hand side tensor
+/// * `rhs` - Pointer to i8 right-hand side tensor
+/// * `output` - Pointer to i32 output buffer
+/// * `metadata` - Tensor metadata array (same layout as `call_ops_matmul`)
+///
+/// # Safety
+/// This function uses unsafe FFI calls to C kernels. Caller must ensure:
+/// - All pointers are valid and properly aligned
+/// - Metadata accurately describes tensor layouts
+/// - Output buffer has sufficient capacity (num_els i32 elements)
+///
+/// # Returns
+/// Returns `Ok(())` on success.
+pub fn call_ops_matmul_int8(
+    kernel_name: crate::kernels::macros::Kernel,
+    lhs: *const c_void,
+    rhs: *const c_void,
+    output: *mut c_void,
+    metadata: &[usize],
+) -> Result<()> {
+    unsafe {
+        dispatch_matmul_int8(kernel_name.0, lhs, rhs, output, metadata.as_ptr());
+    }
+
+    Ok(())
+}
+
+/// Execute a Q4_0 quantize operation
+///
+/// Packs every 32 consecutive values into an 18-byte block (f16 scale + 16 bytes of 4-bit codes).
+///
+/// # Arguments
+/// * `kernel_name` - The kernel to execute (e.g., quantize_q4_0::F32)
+/// * `input` - Pointer to floating-point input tensor
+/// * `output` - Pointer to u8 output buffer
+/// * `metadata` - Tensor metadata array (same layout as quantize)
+///
+/// # Safety
+/// This function uses unsafe FFI calls to C kernels. Caller must ensure:
+/// - All pointers are valid and properly aligned
+/// - num_els and the last dimension are multiples of 32
+/// - Output buffer has sufficient capacity (num_els / 32 * 18 bytes)
+///
+/// # Returns
+/// Returns `Ok(())` on success.
+pub fn call_ops_quantize_q4_0(
+    kernel_name: crate::kernels::macros::Kernel,
+    input: *const c_void,
+    output: *mut c_void,
+    metadata: &[usize],
+) -> Result<()> {
+    unsafe {
+        dispatch_block_quant(kernel_name.0, input, output, metadata.as_ptr());
+    }
+
+    Ok(())
+}
+
+/// Execute a Q4_0 dequantize operation
+///
+/// # Arguments
+/// * `kernel_name` - The kernel to execute (e.g., dequantize_q4_0::F32)
+/// * `input` - Pointer to contiguous packed Q4_0 blocks
+/// * `output` - Pointer to floating-point output buffer
+/// * `metadata` - `[num_blocks, byte_offset]`
+///
+/// # Safety
+/// This function uses unsafe FFI calls to C kernels. Caller must ensure:
+/// - All pointers are valid and properly aligned
+/// - Output buffer has sufficient capacity (num_blocks * 32 elements)
+///
+/// # Returns
+/// Returns `Ok(())` on success.
+pub fn call_ops_dequantize_q4_0(
+    kernel_name: crate::kernels::macros::Kernel,
+    input: *const c_void,
+    output: *mut c_void,
+    metadata: &[usize],
+) -> Result<()> {
+    unsafe {
+        dispatch_block_quant(kernel_name.0, input, output, metadata.as_ptr());
+    }
+
+    Ok(())
+}
+
+/// Execute a fused Q4_0 dequant-matmul
+///
+/// Computes `out[M, N] = lhs[M, K] @ dequant(weight)^T` where `weight` is a packed `[N, K]`
+/// Q4_0 matrix. The weight is never materialized in floating point.
+///
+/// # Arguments
+/// * `kernel_name` - The kernel to execute (e.g., matmul_q4_0::F32)
+/// * `lhs` - Pointer to contiguous floating-point lhs
+/// * `weight` - Pointer to contiguous packed Q4_0 weight
+/// * `output` - Pointer to output buffer of the lhs type
+/// * `metadata` - `[M, K, N, lhs_offset, weight_byte_offset]`
+///
+/// # Safety
+/// This function uses unsafe FFI calls to C kernels. Caller must ensure:
+/// - All pointers are valid and properly aligned
+/// - K is a multiple of 32
+/// - Output buffer has sufficient capacity (M * N elements)
+///
+/// # Returns
+/// Returns `Ok(())` on success.
+pub fn call_ops_matmul_q4_0(
+    kernel_name: crate::kernels::macros::Kernel,
+    lhs: *const c_void,
+    weight: *const c_void,
+    output: *mut c_void,
+    metadata: &[usize],
+) -> Result<()> {
+    unsafe {
+        dispatch_matmul_q4_0(kernel_name.0, lhs, weight, output, metadata.as_ptr());
+    }
+
+    Ok(())
+}
+
+// Quantize extern C declarations
+extern "C" {
+    fn hodu_cpu_quantize_bf16(input: *const c_void, output: *mut c_void, metadata: *const usize, scale: f32, zp: i32);
+    fn hodu_cpu_quantize_f16(input: *const c_void, output: *mut c_void, metadata: *const usize, scale: f32, zp: i32);
+    fn hodu_cpu_quantize_f32(input: *const c_void, output: *mut c_void, metadata: *const usize, scale: f32, zp: i32);
+    fn hodu_cpu_quantize_f64(input: *const c_void, output: *mut c_void, metadata: *const usize, scale: f32, zp: i32);
+}
+
+// Dequantize extern C declarations
+extern "C" {
+    fn hodu_cpu_dequantize_bf16(input: *const c_void, output: *mut c_void, metadata: *const usize, scale: f32, zp: i32);
+    fn hodu_cpu_dequantize_f16(input: *const c_void, output: *mut c_void, metadata: *const usize, scale: f32, zp: i32);
+    fn hodu_cpu_dequantize_f32(input: *const c_void, output: *mut c_void, metadata: *const usize, scale: f32, zp: i32);
+    fn hodu_cpu_dequantize_f64(input: *const c_void, output: *mut c_void, metadata: *const usize, scale: f32, zp: i32);
+}
+
+// Int8 matmul extern C declarations
+extern "C" {
+    fn hodu_cpu_matmul_int8_i8(lhs: *const c_void, rhs: *const c_void, output: *mut c_void, metadata: *const usize);
+}
+
+// Q4_0 extern C declarations
+extern "C" {
+    fn hodu_cpu_quantize_q4_0_bf16(input: *const c_void, output: *mut c_void, metadata: *const usize);
+    fn hodu_cpu_quantize_q4_0_f16(input: *const c_void, output: *mut c_void, metadata: *const usize);
+    fn hodu_cpu_quantize_q4_0_f32(input: *const c_void, output: *mut c_void, metadata: *const usize);
+    fn hodu_cpu_quantize_q4_0_f64(input: *const c_void, output: *mut c_void, metadata: *const usize);
+    fn hodu_cpu_dequantize_q4_0_bf16(input: *const c_void, output: *mut c_void, metadata: *const usize);
+    fn hodu_cpu_dequantize_q4_0_f16(input: *const c_void, output: *mut c_void, metadata: *const usize);
+    fn hodu_cpu_dequantize_q4_0_f32(input: *const c_void, output: *mut c_void, metadata: *const usize);
+    fn hodu_cpu_dequantize_q4_0_f64(input: *const c_void, output: *mut c_void, metadata: *const usize);
+    fn hodu_cpu_matmul_q4_0_bf16(
+        lhs: *const c_void,
+        weight: *const c_void,
+        output: *mut c_void,
+        metadata: *const usize,
+    );
+    fn hodu_cpu_matmul_q4_0_f16(lhs: *const c_void, weight: *const c_void, output: *mut c_void, metadata: *const usize);
+    fn hodu_cpu_matmul_q4_0_f32(lhs: *const c_void, weight: *const c_void, output: *mut c_void, metadata: *const usize);
+    fn hodu_cpu_matmul_q4_0_f64(lhs: *const c_void, weight: *const c_void, output: *mut c_void, metadata: *const usize);
+}
+
+unsafe fn dispatch_quantize(
+    name: &str,
+    input: *const c_void,
+    output: *mut c_void,
+    metadata: *const usize,
+    scale: f32,
+    zero_point: i32,
+) {
+    match name {
+        "hodu_cpu_quantize_bf16" => hodu_cpu_quantize_bf16(input, output, metadata, scale, zero_point),
+        "hodu_cpu_quantize_f16" => hodu_cpu_quantize_f16(input, output, metadata, scale, zero_point),
+        "hodu_cpu_quantize_f32" => hodu_cpu_quantize_f32(input, output, metadata, scale, zero_point),
+        "hodu_cpu_quantize_f64" => hodu_cpu_quantize_f64(input, output, metadata, scale, zero_point),
+        _ => panic!("Unsupported quantize kernel: {}", name),
+    }
+}
+
+unsafe fn dispatch_dequantize(
+    name: &str,
+    input: *const c_void,
+    output: *mut c_void,
+    metadata: *const usize,
+    scale: f32,
+    zero_point: i32,
+) {
+    match name {
+        "hodu_cpu_dequantize_bf16" => hodu_cpu_dequantize_bf16(input, output, metadata, scale, zero_point),
+        "hodu_cpu_dequantize_f16" => hodu_cpu_dequantize_f16(input, output, metadata, scale, zero_point),
+        "hodu_cpu_dequantize_f32" => hodu_cpu_dequantize_f32(input, output, metadata, scale, zero_point),
+        "hodu_cpu_dequantize_f64" => hodu_cpu_dequantize_f64(input, output, metadata, scale, zero_point),
+        _ => panic!("Unsupported dequantize kernel: {}", name),
+    }
+}
+
+unsafe fn dispatch_matmul_int8(
+    name: &str,
+    lhs: *const c_void,
+    rhs: *const c_void,
+    output: *mut c_void,
+    metadata: *const usize,
+) {
+    match name {
+        "hodu_cpu_matmul_int8_i8" => hodu_cpu_matmul_int8_i8(lhs, rhs, output, metadata),
+        _ => panic!("Unsupported matmul_int8 kernel: {}", name),
+    }
+}
+
+unsafe fn dispatch_block_quant(name: &str, input: *const c_void, output: *mut c_void, metadata: *const usize) {
+    match name {
+        "hodu_cpu_quantize_q4_0_bf16" => hodu_cpu_quantize_q4_0_bf16(input, output, metadata),
+        "hodu_cpu_quantize_q4_0_f16" => hodu_cpu_quantize_q4_0_f16(input, output, metadata),
+        "hodu_cpu_quantize_q4_0_f32" => hodu_cpu_quantize_q4_0_f32(input, output, metadata),
+        "hodu_cpu_quantize_q4_0_f64" => hodu_cpu_quantize_q4_0_f64(input, output, metadata),
+        "hodu_cpu_dequantize_q4_0_bf16" => hodu_cpu_dequantize_q4_0_bf16(input, output, metadata),
+        "hodu_cpu_dequantize_q4_0_f16" => hodu_cpu_dequantize_q4_0_f16(input, output, metadata),
+        "hodu_cpu_dequantize_q4_0_f32" => hodu_cpu_dequantize_q4_0_f32(input, output, metadata),
+        "hodu_cpu_dequantize_q4_0_f64" => hodu_cpu_dequantize_q4_0_f64(input, output, metadata),
+        _ => panic!("Unsupported block quantization kernel: {}", name),
+    }
+}
+
+unsafe fn dispatch_matmul_q4_0(
+    name: &str,
+    lhs: *const c_void,
+    weight: *const c_void,
+    output: *mut c_void,
+    metadata: *const usize,
+) {
+    match name {
+        "hodu_cpu_matmul_q4_0_bf16" => hodu_cpu_matmul_q4_0_bf16(lhs, weight, output, metadata),
+        "hodu_cpu_matmul_q4_0_f16" => hodu_cpu_matmul_q4_0_f16(lhs, weight, output, metadata),
+        "hodu_cpu_matmul_q4_0_f32" => hodu_cpu_matmul_q4_0_f32(lhs, weight, output, metadata),
+        "hodu_cpu_matmul_q4_0_f64" => hodu_cpu_matmul_q4_0_f64(lhs, weight, output, metadata),
+        _ => panic!("Unsupported matmul_q4_0 kernel: {}", name),
+    }
+}
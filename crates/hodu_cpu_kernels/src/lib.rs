@@ -8,6 +8,8 @@
 pub const KERNELS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/kernels");
 
 mod error;
+#[cfg(hodu_pure_rust)]
+mod fallback;
 pub mod jit_symbols;
 mod kernels;
 
@@ -215,3 +215,73 @@ fn test_negative_values_f32() {
     let output = run_binary(&lhs, &rhs, add::F32);
     assert_eq!(approx(output, 4), vec![0.0, 0.0, 0.0, 0.0]);
 }
+
+// Contiguous fast path vs strided fallback
+fn run_binary_layout(
+    lhs: &[f32],
+    rhs: &[f32],
+    shape: &[usize],
+    lhs_strides: &[usize],
+    rhs_strides: &[usize],
+    offsets: (usize, usize),
+    kernel: Kernel,
+) -> Vec<f32> {
+    let num_els: usize = shape.iter().product();
+    let mut output = vec![0.0f32; num_els];
+
+    let mut metadata = vec![num_els, shape.len()];
+    metadata.extend(shape); // lhs_shape
+    metadata.extend(shape); // rhs_shape
+    metadata.extend(lhs_strides);
+    metadata.extend(rhs_strides);
+    metadata.push(offsets.0);
+    metadata.push(offsets.1);
+
+    call_ops_binary(
+        kernel,
+        lhs.as_ptr() as *const core::ffi::c_void,
+        rhs.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+    )
+    .unwrap();
+    output
+}
+
+#[test]
+fn test_add_f32_contiguous_with_offset_and_tail() {
+    // 37 elements leaves a scalar tail for every SIMD width
+    let lhs: Vec<f32> = (0..40).map(|i| i as f32).collect();
+    let rhs: Vec<f32> = (0..40).map(|i| (i * 10) as f32).collect();
+    let output = run_binary_layout(&lhs, &rhs, &[37], &[1], &[1], (3, 1), add::F32);
+    let expected: Vec<f32> = (0..37).map(|i| lhs[3 + i] + rhs[1 + i]).collect();
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_div_f32_contiguous_matches_scalar() {
+    let lhs: Vec<f32> = (1..=37).map(|i| i as f32 * 0.7).collect();
+    let rhs: Vec<f32> = (1..=37).map(|i| i as f32 * 0.3 + 1.0).collect();
+    let output = run_binary(&lhs, &rhs, div::F32);
+    let expected: Vec<f32> = lhs.iter().zip(&rhs).map(|(x, y)| x / y).collect();
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_mul_f32_strided_rhs() {
+    // rhs is a transposed [3, 2] buffer viewed as [2, 3]
+    let lhs = vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let rhs = vec![1.0f32, 4.0, 2.0, 5.0, 3.0, 6.0];
+    let output = run_binary_layout(&lhs, &rhs, &[2, 3], &[3, 1], &[1, 2], (0, 0), mul::F32);
+    assert_eq!(output, vec![1.0, 4.0, 9.0, 16.0, 25.0, 36.0]);
+}
+
+#[test]
+fn test_sub_f32_large_contiguous() {
+    // Large enough to be split across threads
+    let n = 300_003;
+    let lhs: Vec<f32> = (0..n).map(|i| (i % 1000) as f32).collect();
+    let rhs: Vec<f32> = (0..n).map(|i| (i % 7) as f32).collect();
+    let output = run_binary(&lhs, &rhs, sub::F32);
+    assert!(output.iter().enumerate().all(|(i, &v)| v == lhs[i] - rhs[i]));
+}
@@ -15,6 +15,7 @@ fn calculate_strides(shape: &[usize]) -> Vec<usize> {
 // Header: [num_output_els, num_inputs, num_total_indices, num_contraction_indices, output_ndim, output_shape...]
 // Per-input: [input_ndim, input_shape..., input_strides..., input_offset, dim_to_index_map...]
 // Index info: [contraction_index_ids..., index_sizes..., output_index_ids...]
+#[allow(clippy::too_many_arguments)]
 fn build_einsum_metadata(
     num_inputs: usize,
     input_shapes: &[&[usize]],
@@ -843,12 +843,7 @@ fn test_onehot_f32_1d() {
     let num_dims_out = 2;
     let output_shape = vec![3, 4];
 
-    let mut metadata = Vec::new();
-    metadata.push(num_els);
-    metadata.push(num_input_els);
-    metadata.push(num_classes);
-    metadata.push(axis);
-    metadata.push(num_dims_out);
+    let mut metadata = vec![num_els, num_input_els, num_classes, axis, num_dims_out];
     metadata.extend(&output_shape);
 
     call_ops_onehot(
@@ -886,12 +881,7 @@ fn test_onehot_f32_axis0() {
     let num_dims_out = 2;
     let output_shape = vec![3, 2];
 
-    let mut metadata = Vec::new();
-    metadata.push(num_els);
-    metadata.push(num_input_els);
-    metadata.push(num_classes);
-    metadata.push(axis);
-    metadata.push(num_dims_out);
+    let mut metadata = vec![num_els, num_input_els, num_classes, axis, num_dims_out];
     metadata.extend(&output_shape);
 
     call_ops_onehot(
@@ -925,12 +915,7 @@ fn test_onehot_i32() {
     let num_dims_out = 2;
     let output_shape = vec![3, 3];
 
-    let mut metadata = Vec::new();
-    metadata.push(num_els);
-    metadata.push(num_input_els);
-    metadata.push(num_classes);
-    metadata.push(axis);
-    metadata.push(num_dims_out);
+    let mut metadata = vec![num_els, num_input_els, num_classes, axis, num_dims_out];
     metadata.extend(&output_shape);
 
     call_ops_onehot(
@@ -967,12 +952,7 @@ fn test_onehot_f32_2d_input() {
     let num_dims_out = 3;
     let output_shape = vec![2, 2, 3];
 
-    let mut metadata = Vec::new();
-    metadata.push(num_els);
-    metadata.push(num_input_els);
-    metadata.push(num_classes);
-    metadata.push(axis);
-    metadata.push(num_dims_out);
+    let mut metadata = vec![num_els, num_input_els, num_classes, axis, num_dims_out];
     metadata.extend(&output_shape);
 
     call_ops_onehot(
@@ -0,0 +1,76 @@
+use hodu_cpu_kernels::*;
+
+fn contiguous_metadata(shape: &[usize], strides: &[usize], offset: usize) -> Vec<usize> {
+    let mut metadata = Vec::new();
+    metadata.push(shape.iter().product());
+    metadata.push(shape.len());
+    metadata.extend(shape);
+    metadata.extend(strides);
+    metadata.push(offset);
+    metadata
+}
+
+/// Reference copy: walk the output in row-major order and gather through the strides
+fn strided_copy(input: &[f32], shape: &[usize], strides: &[usize], offset: usize) -> Vec<f32> {
+    let num_els: usize = shape.iter().product();
+    (0..num_els)
+        .map(|i| {
+            let mut rem = i;
+            let mut index = offset;
+            for d in (0..shape.len()).rev() {
+                index += (rem % shape[d]) * strides[d];
+                rem /= shape[d];
+            }
+            input[index]
+        })
+        .collect()
+}
+
+fn run_contiguous(input: &[f32], shape: &[usize], strides: &[usize], offset: usize) -> Vec<f32> {
+    let mut output = vec![0.0f32; shape.iter().product()];
+    call_ops_contiguous(
+        contiguous::F32,
+        input.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &contiguous_metadata(shape, strides, offset),
+    )
+    .unwrap();
+    output
+}
+
+#[test]
+fn test_contiguous_f32_transposed_2d() {
+    // Input: [[1, 2, 3], [4, 5, 6]] viewed transposed as 3x2
+    // Output: [[1, 4], [2, 5], [3, 6]]
+    let input = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let output = run_contiguous(&input, &[3, 2], &[1, 3], 0);
+    assert_eq!(output, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+}
+
+#[test]
+fn test_contiguous_f32_transposed_tiled() {
+    // Planes larger than one tile and not a multiple of it
+    let (rows, cols) = (70, 45);
+    let input: Vec<f32> = (0..rows * cols).map(|i| i as f32).collect();
+    let output = run_contiguous(&input, &[cols, rows], &[1, cols], 0);
+    assert_eq!(output, strided_copy(&input, &[cols, rows], &[1, cols], 0));
+}
+
+#[test]
+fn test_contiguous_f32_permuted_batched_with_offset() {
+    // [2, 3, 40, 50] permuted to [3, 50, 2, 40], starting at an offset of 7 elements
+    let shape = [3, 50, 2, 40];
+    let strides = [2000, 1, 6000, 50];
+    let input: Vec<f32> = (0..7 + 2 * 3 * 40 * 50).map(|i| i as f32).collect();
+    let output = run_contiguous(&input, &shape, &strides, 7);
+    assert_eq!(output, strided_copy(&input, &shape, &strides, 7));
+}
+
+#[test]
+fn test_contiguous_f32_transposed_large() {
+    // Large enough to split the tiles across threads
+    let (rows, cols) = (513, 700);
+    let input: Vec<f32> = (0..rows * cols).map(|i| i as f32).collect();
+    let output = run_contiguous(&input, &[cols, rows], &[1, cols], 0);
+    assert_eq!(output, strided_copy(&input, &[cols, rows], &[1, cols], 0));
+}
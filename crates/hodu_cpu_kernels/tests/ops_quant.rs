@@ -0,0 +1,279 @@
+use hodu_cpu_kernels::*;
+
+// ============================================================================
+// QUANTIZE Tests
+// ============================================================================
+
+#[test]
+fn test_quantize_f32_symmetric() {
+    let input = [0.0f32, 0.5, -0.5, 1.26, -1.26, 100.0, -100.0];
+    let mut output = [0i8; 7];
+
+    // [num_els, num_dims, shape, strides, offset]
+    let metadata = vec![7, 1, 7, 1, 0];
+
+    call_ops_quantize(
+        quantize::F32,
+        input.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+        0.01,
+        0,
+    )
+    .unwrap();
+
+    assert_eq!(output, [0, 50, -50, 126, -126, 127, -128]);
+}
+
+#[test]
+fn test_quantize_f32_asymmetric_strided() {
+    // 2x2 matrix read transposed: [[0, 2], [1, 3]] in memory -> logical [0, 1, 2, 3]
+    let input = [0.0f32, 2.0, 1.0, 3.0];
+    let mut output = [0i8; 4];
+
+    let metadata = vec![4, 2, 2, 2, 1, 2, 0];
+
+    call_ops_quantize(
+        quantize::F32,
+        input.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+        0.5,
+        -10,
+    )
+    .unwrap();
+
+    assert_eq!(output, [-10, -8, -6, -4]);
+}
+
+// ============================================================================
+// DEQUANTIZE Tests
+// ============================================================================
+
+#[test]
+fn test_dequantize_f32() {
+    let input = [-128i8, -10, 0, 127];
+    let mut output = [0.0f32; 4];
+
+    let metadata = vec![4, 1, 4, 1, 0];
+
+    call_ops_dequantize(
+        dequantize::F32,
+        input.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+        0.5,
+        -10,
+    )
+    .unwrap();
+
+    assert_eq!(output, [-59.0, 0.0, 5.0, 68.5]);
+}
+
+#[test]
+fn test_quantize_dequantize_roundtrip_f32() {
+    let input = [-1.0f32, -0.25, 0.0, 0.3, 0.99];
+    let mut quantized = [0i8; 5];
+    let mut output = [0.0f32; 5];
+    let scale = 1.0 / 127.0;
+
+    let metadata = vec![5, 1, 5, 1, 0];
+
+    call_ops_quantize(
+        quantize::F32,
+        input.as_ptr() as *const core::ffi::c_void,
+        quantized.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+        scale,
+        0,
+    )
+    .unwrap();
+    call_ops_dequantize(
+        dequantize::F32,
+        quantized.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+        scale,
+        0,
+    )
+    .unwrap();
+
+    for (x, y) in input.iter().zip(output.iter()) {
+        assert!((x - y).abs() <= scale / 2.0 + 1e-6, "{} vs {}", x, y);
+    }
+}
+
+// ============================================================================
+// MATMUL_INT8 Tests
+// ============================================================================
+
+#[test]
+fn test_matmul_int8_2d() {
+    // A: 2x3 = [[1, 2, 3], [4, 5, 6]], B: 3x2 = [[7, 8], [9, 10], [11, 12]]
+    let lhs = [1i8, 2, 3, 4, 5, 6];
+    let rhs = [7i8, 8, 9, 10, 11, 12];
+    let mut output = [0i32; 4];
+
+    // [num_els, lhs_ndim, rhs_ndim, batch_ndim, lhs_shape, rhs_shape, lhs_strides, rhs_strides,
+    //  lhs_offset, rhs_offset, M, K, N]
+    let metadata = vec![4, 2, 2, 0, 2, 3, 3, 2, 3, 1, 2, 1, 0, 0, 2, 3, 2];
+
+    call_ops_matmul_int8(
+        matmul_int8::I8,
+        lhs.as_ptr() as *const core::ffi::c_void,
+        rhs.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+    )
+    .unwrap();
+
+    assert_eq!(output, [58, 64, 139, 154]);
+}
+
+#[test]
+fn test_matmul_int8_no_overflow() {
+    // 1x4 @ 4x1 with extreme values; the i8 product would wrap, the i32 accumulator must not
+    let lhs = [127i8, 127, -128, -128];
+    let rhs = [127i8, 127, -128, -128];
+    let mut output = [0i32; 1];
+
+    let metadata = vec![1, 2, 2, 0, 1, 4, 4, 1, 4, 1, 1, 1, 0, 0, 1, 4, 1];
+
+    call_ops_matmul_int8(
+        matmul_int8::I8,
+        lhs.as_ptr() as *const core::ffi::c_void,
+        rhs.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+    )
+    .unwrap();
+
+    assert_eq!(output, [2 * 127 * 127 + 2 * 128 * 128]);
+}
+
+#[test]
+fn test_matmul_int8_broadcast_batch() {
+    // lhs: [2, 1, 2], rhs: [2, 1] broadcast over the batch
+    let lhs = [1i8, 2, -3, 4];
+    let rhs = [5i8, -6];
+    let mut output = [0i32; 2];
+
+    // batch_shape = [2]; lhs strides [2, 2, 1]; rhs strides [1, 1]
+    let metadata = vec![2, 3, 2, 1, 2, 1, 2, 2, 1, 2, 2, 2, 1, 1, 1, 0, 0, 1, 2, 1];
+
+    call_ops_matmul_int8(
+        matmul_int8::I8,
+        lhs.as_ptr() as *const core::ffi::c_void,
+        rhs.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+    )
+    .unwrap();
+
+    assert_eq!(output, [5 - 12, -15 - 24]);
+}
+
+// ============================================================================
+// Q4_0 Tests
+// ============================================================================
+
+#[test]
+fn test_quantize_q4_0_block_layout() {
+    // One block: values -7..=7 scaled by 0.5, padded with zeros
+    let mut input = [0.0f32; 32];
+    for (i, v) in input.iter_mut().enumerate().take(15) {
+        *v = (i as f32 - 7.0) * 0.5;
+    }
+    let mut output = [0u8; 18];
+
+    let metadata = vec![32, 1, 32, 1, 0];
+
+    call_ops_quantize_q4_0(
+        quantize_q4_0::F32,
+        input.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+    )
+    .unwrap();
+
+    // scale = 3.5 / 7 = 0.5 -> f16 0x3800
+    assert_eq!(&output[..2], &[0x00, 0x38]);
+    // codes are value / 0.5 + 8 -> 1, 2, ..., 15, then 8 for the zeros
+    assert_eq!(output[2], 1 | (2 << 4));
+    assert_eq!(output[9], 15 | (8 << 4));
+    assert_eq!(output[17], 8 | (8 << 4));
+}
+
+#[test]
+fn test_q4_0_roundtrip() {
+    let input: Vec<f32> = (0..64).map(|i| ((i as f32) * 0.37).sin() * 3.0).collect();
+    let mut packed = [0u8; 36];
+    let mut output = [0.0f32; 64];
+
+    call_ops_quantize_q4_0(
+        quantize_q4_0::F32,
+        input.as_ptr() as *const core::ffi::c_void,
+        packed.as_mut_ptr() as *mut core::ffi::c_void,
+        &[64, 2, 2, 32, 32, 1, 0],
+    )
+    .unwrap();
+    call_ops_dequantize_q4_0(
+        dequantize_q4_0::F32,
+        packed.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &[2, 0],
+    )
+    .unwrap();
+
+    // Each value is within half a quantization step of its block's scale
+    for block in 0..2 {
+        let range = &input[block * 32..(block + 1) * 32];
+        let amax = range.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        let step = amax / 7.0;
+        for i in block * 32..(block + 1) * 32 {
+            assert!((input[i] - output[i]).abs() <= step * 0.5 + 1e-3);
+        }
+    }
+}
+
+#[test]
+fn test_matmul_q4_0_matches_dequantized() {
+    let (m, k, n) = (3, 64, 5);
+    let lhs: Vec<f32> = (0..m * k).map(|i| ((i % 7) as f32 - 3.0) * 0.25).collect();
+    let weight: Vec<f32> = (0..n * k).map(|i| ((i as f32) * 0.11).cos()).collect();
+
+    let mut packed = vec![0u8; n * k / 32 * 18];
+    call_ops_quantize_q4_0(
+        quantize_q4_0::F32,
+        weight.as_ptr() as *const core::ffi::c_void,
+        packed.as_mut_ptr() as *mut core::ffi::c_void,
+        &[n * k, 2, n, k, k, 1, 0],
+    )
+    .unwrap();
+
+    let mut dequantized = vec![0.0f32; n * k];
+    call_ops_dequantize_q4_0(
+        dequantize_q4_0::F32,
+        packed.as_ptr() as *const core::ffi::c_void,
+        dequantized.as_mut_ptr() as *mut core::ffi::c_void,
+        &[n * k / 32, 0],
+    )
+    .unwrap();
+
+    let mut output = vec![0.0f32; m * n];
+    call_ops_matmul_q4_0(
+        matmul_q4_0::F32,
+        lhs.as_ptr() as *const core::ffi::c_void,
+        packed.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &[m, k, n, 0, 0],
+    )
+    .unwrap();
+
+    for i in 0..m {
+        for j in 0..n {
+            let expected: f32 = (0..k).map(|x| lhs[i * k + x] * dequantized[j * k + x]).sum();
+            assert!((output[i * n + j] - expected).abs() < 1e-4);
+        }
+    }
+}
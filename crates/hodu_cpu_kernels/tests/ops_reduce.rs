@@ -250,6 +250,37 @@ fn test_reduce_mean_f32() {
     assert_eq!(approx(output, 4), vec![4.0, 10.0]);
 }
 
+// reduce - var with a large mean (E[X^2] - E[X]^2 cancels to garbage in f32)
+#[test]
+fn test_reduce_var_f32_large_mean() {
+    // [1e4 + 1, 1e4 + 2, ..., 1e4 + 8] -> var = 5.25
+    let input: Vec<f32> = (1..=8).map(|i| 10000.0 + i as f32).collect();
+    let shape = vec![8];
+    let reduce_dims = vec![0];
+    let mut output = vec![0.0f32; 1];
+
+    let mut metadata = vec![shape.len()];
+    metadata.extend(&shape);
+    metadata.extend(&calculate_strides(&shape));
+    metadata.push(0);
+    metadata.push(1);
+    metadata.push(1);
+    metadata.push(reduce_dims.len());
+    metadata.extend(&reduce_dims);
+    metadata.push(0);
+    metadata.push(8);
+
+    call_ops_reduce(
+        var::F32,
+        input.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+    )
+    .unwrap();
+
+    assert_eq!(approx(output, 4), vec![5.25]);
+}
+
 // reduce - max
 #[test]
 fn test_reduce_max_f32() {
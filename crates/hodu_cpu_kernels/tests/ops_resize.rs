@@ -186,7 +186,7 @@ fn test_resize_bilinear_half_pixel_f32() {
 
     // Just check output is within expected range
     for &val in &output {
-        assert!(val >= 0.0 && val <= 20.0);
+        assert!((0.0..=20.0).contains(&val));
     }
 }
 
@@ -222,7 +222,7 @@ fn test_resize_bicubic_f32() {
 
     // Check output is within expected range (cubic can overshoot slightly)
     for &val in &output {
-        assert!(val >= 0.0 && val <= 20.0);
+        assert!((0.0..=20.0).contains(&val));
     }
 }
 
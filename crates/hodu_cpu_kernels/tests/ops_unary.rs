@@ -242,7 +242,10 @@ fn test_selu_f32() {
     // selu(x) = scale * (max(0,x) + min(0, alpha*(exp(x)-1)))
     // SELU_ALPHA = 1.6732632423543772848170429916717
     // SELU_SCALE = 1.0507009873554804934193349852946
+    // The constants as published, rounded to f32 by the compiler
+    #[allow(clippy::excessive_precision)]
     let alpha = 1.6732632423543772848170429916717f32;
+    #[allow(clippy::excessive_precision)]
     let scale = 1.0507009873554804934193349852946f32;
     let expected: Vec<f32> = input
         .iter()
@@ -576,3 +579,69 @@ fn test_ge_scalar_f32() {
     let output = run_unary_scalar_to_bool(&input, ge_scalar::F32, 3.0);
     assert_eq!(output, vec![0, 0, 1, 1]);
 }
+
+// Contiguous fast path vs strided fallback
+fn unary_layout_metadata(shape: &[usize], strides: &[usize], offset: usize) -> Vec<usize> {
+    let mut metadata = vec![shape.iter().product(), shape.len()];
+    metadata.extend(shape);
+    metadata.extend(strides);
+    metadata.push(offset);
+    metadata
+}
+
+#[test]
+fn test_neg_f32_contiguous_with_offset() {
+    // 37 elements leaves a scalar tail for every SIMD width
+    let input: Vec<f32> = (0..40).map(|i| i as f32).collect();
+    let mut output = vec![0.0f32; 37];
+    let metadata = unary_layout_metadata(&[37], &[1], 3);
+    call_ops_unary(
+        neg::F32,
+        input.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+    )
+    .unwrap();
+    let expected: Vec<f32> = (3..40).map(|i| -(i as f32)).collect();
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_relu_f32_strided() {
+    // transposed [3, 2] buffer viewed as [2, 3]
+    let input = [1.0f32, -4.0, -2.0, 5.0, 3.0, -6.0];
+    let mut output = vec![0.0f32; 6];
+    let metadata = unary_layout_metadata(&[2, 3], &[1, 2], 0);
+    call_ops_unary(
+        relu::F32,
+        input.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+    )
+    .unwrap();
+    assert_eq!(output, vec![1.0, 0.0, 3.0, 0.0, 5.0, 0.0]);
+}
+
+fn check_scalar_op_with_offset(kernel: Kernel, reference: impl Fn(f32) -> f32) {
+    let input: Vec<f32> = (0..40).map(|i| i as f32 * 0.5).collect();
+    let metadata = unary_layout_metadata(&[37], &[1], 2);
+    let mut output = vec![0.0f32; 37];
+    call_ops_unary_scalar(
+        kernel,
+        input.as_ptr() as *const core::ffi::c_void,
+        output.as_mut_ptr() as *mut core::ffi::c_void,
+        &metadata,
+        3.0f32,
+    )
+    .unwrap();
+    let expected: Vec<f32> = input[2..39].iter().map(|&x| reference(x)).collect();
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_scalar_ops_f32_contiguous_with_offset() {
+    check_scalar_op_with_offset(add_scalar::F32, |x| x + 3.0);
+    check_scalar_op_with_offset(sub_scalar::F32, |x| x - 3.0);
+    check_scalar_op_with_offset(mul_scalar::F32, |x| x * 3.0);
+    check_scalar_op_with_offset(div_scalar::F32, |x| x / 3.0);
+}
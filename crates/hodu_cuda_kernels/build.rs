@@ -24,6 +24,7 @@ fn main() {
         "ops_matrix.cu",
         "ops_memory.cu",
         "ops_padding.cu",
+        "ops_quant.cu",
         "ops_reduce.cu",
         "ops_resize.cu",
         "ops_scan.cu",
@@ -26,6 +26,73 @@
         }                                                                                          \
     }
 
+// Tiled transpose for permuted views whose unit-stride dim is not the innermost one
+//
+// Each block stages a TRANSPOSE_TILE x TRANSPOSE_TILE tile of the (axis, innermost dim) plane in
+// shared memory: it is read with consecutive threads walking the input's unit stride and written
+// with consecutive threads walking the output's, so both sides are coalesced. The padding column
+// avoids shared memory bank conflicts on the transposed read.
+//
+// Metadata is the contiguous layout followed by `axis`, the dim with unit input stride.
+// Grid: (col tiles, row tiles, planes), where planes may exceed gridDim.z.
+#define TRANSPOSE_TILE 32
+#define TRANSPOSE_ROWS 8
+
+#define TRANSPOSE_TILED_OP(TYPENAME, FN_NAME)                                                      \
+    extern "C" __global__ void hodu_cuda_##FN_NAME(const TYPENAME *input, TYPENAME *out,           \
+                                                   const size_t *metadata) {                       \
+        __shared__ TYPENAME tile[TRANSPOSE_TILE][TRANSPOSE_TILE + 1];                              \
+                                                                                                   \
+        const size_t num_els = metadata[0];                                                        \
+        const size_t num_dims = metadata[1];                                                       \
+        const size_t *dims = metadata + 2;                                                         \
+        const size_t *strides = metadata + 2 + num_dims;                                           \
+        const size_t offset = metadata[2 + 2 * num_dims];                                          \
+        const size_t axis = metadata[3 + 2 * num_dims];                                            \
+        const size_t last = num_dims - 1;                                                          \
+        const size_t rows = dims[axis];                                                            \
+        const size_t cols = dims[last];                                                            \
+        const size_t col_stride = strides[last];                                                   \
+        const size_t num_planes = num_els / (rows * cols);                                         \
+                                                                                                   \
+        const size_t r0 = blockIdx.y * TRANSPOSE_TILE;                                             \
+        const size_t c0 = blockIdx.x * TRANSPOSE_TILE;                                             \
+                                                                                                   \
+        for (size_t plane = blockIdx.z; plane < num_planes; plane += gridDim.z) {                  \
+            size_t in_base = offset, out_base = 0, out_stride = 1, row_stride = 1;                 \
+            size_t rem = plane;                                                                    \
+            for (size_t d = num_dims; d-- > 0;) {                                                  \
+                if (d == axis) {                                                                   \
+                    row_stride = out_stride;                                                       \
+                } else if (d != last) {                                                            \
+                    size_t idx = rem % dims[d];                                                    \
+                    rem /= dims[d];                                                                \
+                    in_base += idx * strides[d];                                                   \
+                    out_base += idx * out_stride;                                                  \
+                }                                                                                  \
+                out_stride *= dims[d];                                                             \
+            }                                                                                      \
+                                                                                                   \
+            for (unsigned int j = threadIdx.y; j < TRANSPOSE_TILE; j += TRANSPOSE_ROWS) {          \
+                size_t r = r0 + threadIdx.x;                                                       \
+                size_t c = c0 + j;                                                                 \
+                if (r < rows && c < cols) {                                                        \
+                    tile[j][threadIdx.x] = input[in_base + r + c * col_stride];                    \
+                }                                                                                  \
+            }                                                                                      \
+            __syncthreads();                                                                       \
+                                                                                                   \
+            for (unsigned int j = threadIdx.y; j < TRANSPOSE_TILE; j += TRANSPOSE_ROWS) {          \
+                size_t r = r0 + j;                                                                 \
+                size_t c = c0 + threadIdx.x;                                                       \
+                if (r < rows && c < cols) {                                                        \
+                    out[out_base + r * row_stride + c] = tile[threadIdx.x][j];                     \
+                }                                                                                  \
+            }                                                                                      \
+            __syncthreads();                                                                       \
+        }                                                                                          \
+    }
+
 #define COPY_OP(TYPENAME, FN_NAME)                                                                 \
     extern "C" __global__ void hodu_cuda_##FN_NAME(const TYPENAME *input, TYPENAME *out,           \
                                                    const size_t *metadata) {                       \
@@ -65,6 +132,22 @@ CONTIGUOUS_OP(int16_t, contiguous_i16)
 CONTIGUOUS_OP(int32_t, contiguous_i32)
 CONTIGUOUS_OP(int64_t, contiguous_i64)
 
+TRANSPOSE_TILED_OP(bool, transpose_tiled_bool)
+TRANSPOSE_TILED_OP(__nv_fp8_e4m3, transpose_tiled_f8e4m3)
+TRANSPOSE_TILED_OP(__nv_fp8_e5m2, transpose_tiled_f8e5m2)
+TRANSPOSE_TILED_OP(__nv_bfloat16, transpose_tiled_bf16)
+TRANSPOSE_TILED_OP(__half, transpose_tiled_f16)
+TRANSPOSE_TILED_OP(float, transpose_tiled_f32)
+TRANSPOSE_TILED_OP(double, transpose_tiled_f64)
+TRANSPOSE_TILED_OP(uint8_t, transpose_tiled_u8)
+TRANSPOSE_TILED_OP(uint16_t, transpose_tiled_u16)
+TRANSPOSE_TILED_OP(uint32_t, transpose_tiled_u32)
+TRANSPOSE_TILED_OP(uint64_t, transpose_tiled_u64)
+TRANSPOSE_TILED_OP(int8_t, transpose_tiled_i8)
+TRANSPOSE_TILED_OP(int16_t, transpose_tiled_i16)
+TRANSPOSE_TILED_OP(int32_t, transpose_tiled_i32)
+TRANSPOSE_TILED_OP(int64_t, transpose_tiled_i64)
+
 COPY_OP(bool, copy_bool)
 COPY_OP(__nv_fp8_e4m3, copy_f8e4m3)
 COPY_OP(__nv_fp8_e5m2, copy_f8e5m2)
@@ -0,0 +1,273 @@
+#include "math.cuh"
+#include "utils.cuh"
+#include <cuda_bf16.h>
+#include <cuda_fp16.h>
+#include <stdint.h>
+
+// ============================================================================
+// QUANTIZE / DEQUANTIZE
+// ============================================================================
+//
+// Affine int8 quantization:
+//   quantize:   q = clamp(round_half_even(x / scale) + zero_point, -128, 127)
+//   dequantize: x = (q - zero_point) * scale
+//
+// Metadata layout (same as cast):
+// - metadata[0]: num_els
+// - metadata[1]: num_dims
+// - metadata[2..2+num_dims]: shape
+// - metadata[2+num_dims..2+2*num_dims]: strides
+// - metadata[2+2*num_dims]: offset
+
+__device__ __forceinline__ int8_t quantize_value(float x, float scale, int32_t zero_point) {
+    float q = rintf(x / scale) + (float)zero_point;
+    return (int8_t)fminf(fmaxf(q, -128.0f), 127.0f);
+}
+
+#define QUANTIZE_OP(TYPENAME, TYPE_SUFFIX)                                                         \
+    extern "C" __global__ void hodu_cuda_quantize_##TYPE_SUFFIX(                                   \
+        const TYPENAME *input, int8_t *out, const size_t *metadata, const float scale,             \
+        const int32_t zero_point) {                                                                \
+        const size_t num_els = metadata[0];                                                        \
+        const size_t num_dims = metadata[1];                                                       \
+        const size_t *dims = metadata + 2;                                                         \
+        const size_t *strides = metadata + 2 + num_dims;                                           \
+        const size_t offset = metadata[2 + 2 * num_dims];                                          \
+        bool cont = is_contiguous(num_dims, dims, strides);                                        \
+        for (uint32_t i = blockIdx.x * blockDim.x + threadIdx.x; i < num_els;                      \
+             i += blockDim.x * gridDim.x) {                                                        \
+            uint32_t idx = offset + (cont ? i : get_strided_index(i, num_dims, dims, strides));    \
+            out[i] = quantize_value(to_float(input[idx]), scale, zero_point);                      \
+        }                                                                                          \
+    }
+
+#define DEQUANTIZE_OP(TYPENAME, TYPE_SUFFIX)                                                       \
+    extern "C" __global__ void hodu_cuda_dequantize_##TYPE_SUFFIX(                                 \
+        const int8_t *input, TYPENAME *out, const size_t *metadata, const float scale,             \
+        const int32_t zero_point) {                                                                \
+        const size_t num_els = metadata[0];                                                        \
+        const size_t num_dims = metadata[1];                                                       \
+        const size_t *dims = metadata + 2;                                                         \
+        const size_t *strides = metadata + 2 + num_dims;                                           \
+        const size_t offset = metadata[2 + 2 * num_dims];                                          \
+        bool cont = is_contiguous(num_dims, dims, strides);                                        \
+        for (uint32_t i = blockIdx.x * blockDim.x + threadIdx.x; i < num_els;                      \
+             i += blockDim.x * gridDim.x) {                                                        \
+            uint32_t idx = offset + (cont ? i : get_strided_index(i, num_dims, dims, strides));    \
+            float x = (float)((int32_t)input[idx] - zero_point) * scale;                           \
+            out[i] = from_float<TYPENAME>(x);                                                      \
+        }                                                                                          \
+    }
+
+QUANTIZE_OP(__nv_bfloat16, bf16)
+QUANTIZE_OP(__half, f16)
+QUANTIZE_OP(float, f32)
+QUANTIZE_OP(double, f64)
+
+DEQUANTIZE_OP(__nv_bfloat16, bf16)
+DEQUANTIZE_OP(__half, f16)
+DEQUANTIZE_OP(float, f32)
+DEQUANTIZE_OP(double, f64)
+
+// ============================================================================
+// INT8 MATRIX MULTIPLICATION
+// ============================================================================
+//
+// i8 x i8 -> i32 batched matmul with the same metadata layout and launch geometry as
+// hodu_cuda_matmul_* (16x16 tiles, one z-block per output batch). Products are widened to int32
+// before accumulation.
+
+#define QUANT_TILE_SIZE 16
+
+extern "C" __global__ void hodu_cuda_matmul_int8_i8(const int8_t *lhs, const int8_t *rhs,
+                                                    int32_t *output, const size_t *metadata) {
+    const size_t lhs_ndim = metadata[1];
+    const size_t rhs_ndim = metadata[2];
+    const size_t batch_ndim = metadata[3];
+
+    const size_t *lhs_shape = metadata + 4;
+    const size_t *rhs_shape = lhs_shape + lhs_ndim;
+    const size_t *batch_shape = rhs_shape + rhs_ndim;
+    const size_t *lhs_strides = batch_shape + batch_ndim;
+    const size_t *rhs_strides = lhs_strides + lhs_ndim;
+    const size_t lhs_offset = rhs_strides[rhs_ndim];
+    const size_t rhs_offset = rhs_strides[rhs_ndim + 1];
+    const size_t M = rhs_strides[rhs_ndim + 2];
+    const size_t K = rhs_strides[rhs_ndim + 3];
+    const size_t N = rhs_strides[rhs_ndim + 4];
+
+    const size_t lhs_batch_ndim = lhs_ndim - 2;
+    const size_t rhs_batch_ndim = rhs_ndim - 2;
+
+    const size_t batch_idx = blockIdx.z;
+    const size_t row = blockIdx.y * QUANT_TILE_SIZE + threadIdx.y;
+    const size_t col = blockIdx.x * QUANT_TILE_SIZE + threadIdx.x;
+
+    // Resolve broadcast batch offsets
+    size_t lhs_base = lhs_offset;
+    size_t rhs_base = rhs_offset;
+    size_t temp = batch_idx;
+    for (int d = (int)batch_ndim - 1; d >= 0; d--) {
+        size_t b = temp % batch_shape[d];
+        temp /= batch_shape[d];
+
+        int lhs_d = d - (int)(batch_ndim - lhs_batch_ndim);
+        if (lhs_d >= 0 && lhs_shape[lhs_d] != 1)
+            lhs_base += b * lhs_strides[lhs_d];
+
+        int rhs_d = d - (int)(batch_ndim - rhs_batch_ndim);
+        if (rhs_d >= 0 && rhs_shape[rhs_d] != 1)
+            rhs_base += b * rhs_strides[rhs_d];
+    }
+
+    const size_t lhs_rs = lhs_strides[lhs_ndim - 2];
+    const size_t lhs_cs = lhs_strides[lhs_ndim - 1];
+    const size_t rhs_rs = rhs_strides[rhs_ndim - 2];
+    const size_t rhs_cs = rhs_strides[rhs_ndim - 1];
+
+    __shared__ int32_t lhs_tile[QUANT_TILE_SIZE][QUANT_TILE_SIZE];
+    __shared__ int32_t rhs_tile[QUANT_TILE_SIZE][QUANT_TILE_SIZE];
+
+    int32_t sum = 0;
+    for (size_t t = 0; t < K; t += QUANT_TILE_SIZE) {
+        size_t lk = t + threadIdx.x;
+        size_t rk = t + threadIdx.y;
+        lhs_tile[threadIdx.y][threadIdx.x] =
+            (row < M && lk < K) ? (int32_t)lhs[lhs_base + row * lhs_rs + lk * lhs_cs] : 0;
+        rhs_tile[threadIdx.y][threadIdx.x] =
+            (rk < K && col < N) ? (int32_t)rhs[rhs_base + rk * rhs_rs + col * rhs_cs] : 0;
+        __syncthreads();
+
+#pragma unroll
+        for (int k = 0; k < QUANT_TILE_SIZE; k++) {
+            sum += lhs_tile[threadIdx.y][k] * rhs_tile[k][threadIdx.x];
+        }
+        __syncthreads();
+    }
+
+    if (row < M && col < N) {
+        output[batch_idx * M * N + row * N + col] = sum;
+    }
+}
+
+// ============================================================================
+// 4-BIT BLOCK QUANTIZATION (Q4_0)
+// ============================================================================
+//
+// Every block of 32 consecutive values along the last dimension is stored in 18 bytes:
+//   bytes [0, 2):  scale as little-endian f16, scale = max|x| / 7
+//   bytes [2, 18): 4-bit codes, byte j holds value 2j in the low nibble and 2j+1 in the high one
+// and decodes as x = (q - 8) * scale.
+//
+// dequantize_q4_0 metadata: [num_blocks, byte_offset]
+// matmul_q4_0 metadata: [M, K, N, lhs_offset, weight_byte_offset]
+
+#define Q4_0_BLOCK_SIZE 32
+#define Q4_0_BLOCK_BYTES 18
+
+__device__ __forceinline__ float q4_0_block_scale(const uint8_t *block) {
+    return __half2float(__ushort_as_half((unsigned short)(block[0] | (block[1] << 8))));
+}
+
+__device__ __forceinline__ float q4_0_value(const uint8_t *block, float scale, size_t j) {
+    uint8_t code = block[2 + j / 2];
+    code = (j & 1) ? (code >> 4) : (code & 0x0F);
+    return (float)((int32_t)code - 8) * scale;
+}
+
+#define QUANTIZE_Q4_0_OP(TYPENAME, TYPE_SUFFIX)                                                    \
+    extern "C" __global__ void hodu_cuda_quantize_q4_0_##TYPE_SUFFIX(                              \
+        const TYPENAME *input, uint8_t *out, const size_t *metadata) {                             \
+        const size_t num_els = metadata[0];                                                        \
+        const size_t num_dims = metadata[1];                                                       \
+        const size_t *dims = metadata + 2;                                                         \
+        const size_t *strides = metadata + 2 + num_dims;                                           \
+        const size_t offset = metadata[2 + 2 * num_dims];                                          \
+        bool cont = is_contiguous(num_dims, dims, strides);                                        \
+        const size_t num_blocks = num_els / Q4_0_BLOCK_SIZE;                                       \
+        for (uint32_t b = blockIdx.x * blockDim.x + threadIdx.x; b < num_blocks;                   \
+             b += blockDim.x * gridDim.x) {                                                        \
+            float values[Q4_0_BLOCK_SIZE];                                                         \
+            float amax = 0.0f;                                                                     \
+            for (uint32_t i = 0; i < Q4_0_BLOCK_SIZE; i++) {                                       \
+                uint32_t flat = b * Q4_0_BLOCK_SIZE + i;                                           \
+                uint32_t idx =                                                                     \
+                    offset + (cont ? flat : get_strided_index(flat, num_dims, dims, strides));     \
+                values[i] = to_float(input[idx]);                                                  \
+                amax = fmaxf(amax, fabsf(values[i]));                                              \
+            }                                                                                      \
+            uint8_t *block = out + (size_t)b * Q4_0_BLOCK_BYTES;                                   \
+            unsigned short d_bits = __half_as_ushort(__float2half(amax / 7.0f));                   \
+            block[0] = (uint8_t)(d_bits & 0xFF);                                                   \
+            block[1] = (uint8_t)(d_bits >> 8);                                                     \
+            const float d = __half2float(__ushort_as_half(d_bits));                                \
+            for (uint32_t j = 0; j < Q4_0_BLOCK_SIZE / 2; j++) {                                   \
+                float lo = d > 0.0f ? rintf(values[2 * j] / d) + 8.0f : 8.0f;                      \
+                float hi = d > 0.0f ? rintf(values[2 * j + 1] / d) + 8.0f : 8.0f;                  \
+                uint8_t q_lo = (uint8_t)fminf(fmaxf(lo, 0.0f), 15.0f);                             \
+                uint8_t q_hi = (uint8_t)fminf(fmaxf(hi, 0.0f), 15.0f);                             \
+                block[2 + j] = (uint8_t)(q_lo | (q_hi << 4));                                      \
+            }                                                                                      \
+        }                                                                                          \
+    }
+
+#define DEQUANTIZE_Q4_0_OP(TYPENAME, TYPE_SUFFIX)                                                  \
+    extern "C" __global__ void hodu_cuda_dequantize_q4_0_##TYPE_SUFFIX(                            \
+        const uint8_t *input, TYPENAME *out, const size_t *metadata) {                             \
+        const size_t num_els = metadata[0] * Q4_0_BLOCK_SIZE;                                      \
+        const uint8_t *in = input + metadata[1];                                                   \
+        for (uint32_t i = blockIdx.x * blockDim.x + threadIdx.x; i < num_els;                      \
+             i += blockDim.x * gridDim.x) {                                                        \
+            const uint8_t *block = in + (size_t)(i / Q4_0_BLOCK_SIZE) * Q4_0_BLOCK_BYTES;          \
+            float x = q4_0_value(block, q4_0_block_scale(block), i % Q4_0_BLOCK_SIZE);             \
+            out[i] = from_float<TYPENAME>(x);                                                      \
+        }                                                                                          \
+    }
+
+// One thread per output element; each thread streams one lhs row against one packed weight row
+#define MATMUL_Q4_0_OP(TYPENAME, TYPE_SUFFIX)                                                      \
+    extern "C" __global__ void hodu_cuda_matmul_q4_0_##TYPE_SUFFIX(                                \
+        const TYPENAME *lhs, const uint8_t *weight, TYPENAME *out, const size_t *metadata) {       \
+        const size_t M = metadata[0];                                                              \
+        const size_t K = metadata[1];                                                              \
+        const size_t N = metadata[2];                                                              \
+        const TYPENAME *x = lhs + metadata[3];                                                     \
+        const uint8_t *w = weight + metadata[4];                                                   \
+        const size_t num_blocks = K / Q4_0_BLOCK_SIZE;                                             \
+        for (size_t idx = blockIdx.x * blockDim.x + threadIdx.x; idx < M * N;                      \
+             idx += blockDim.x * gridDim.x) {                                                      \
+            const size_t i = idx / N;                                                              \
+            const size_t n = idx % N;                                                              \
+            const TYPENAME *x_row = x + i * K;                                                     \
+            const uint8_t *w_row = w + n * num_blocks * Q4_0_BLOCK_BYTES;                          \
+            float acc = 0.0f;                                                                      \
+            for (size_t b = 0; b < num_blocks; b++) {                                              \
+                const uint8_t *block = w_row + b * Q4_0_BLOCK_BYTES;                               \
+                const float d = q4_0_block_scale(block);                                           \
+                float partial = 0.0f;                                                              \
+                for (size_t j = 0; j < Q4_0_BLOCK_SIZE / 2; j++) {                                 \
+                    uint8_t code = block[2 + j];                                                   \
+                    const TYPENAME *xs = x_row + b * Q4_0_BLOCK_SIZE + 2 * j;                      \
+                    partial += to_float(xs[0]) * (float)((int32_t)(code & 0x0F) - 8);              \
+                    partial += to_float(xs[1]) * (float)((int32_t)(code >> 4) - 8);                \
+                }                                                                                  \
+                acc += partial * d;                                                                \
+            }                                                                                      \
+            out[idx] = from_float<TYPENAME>(acc);                                                  \
+        }                                                                                          \
+    }
+
+QUANTIZE_Q4_0_OP(__nv_bfloat16, bf16)
+QUANTIZE_Q4_0_OP(__half, f16)
+QUANTIZE_Q4_0_OP(float, f32)
+QUANTIZE_Q4_0_OP(double, f64)
+
+DEQUANTIZE_Q4_0_OP(__nv_bfloat16, bf16)
+DEQUANTIZE_Q4_0_OP(__half, f16)
+DEQUANTIZE_Q4_0_OP(float, f32)
+DEQUANTIZE_Q4_0_OP(double, f64)
+
+MATMUL_Q4_0_OP(__nv_bfloat16, bf16)
+MATMUL_Q4_0_OP(__half, f16)
+MATMUL_Q4_0_OP(float, f32)
+MATMUL_Q4_0_OP(double, f64)
@@ -74,7 +74,18 @@
         }                                                                                          \
     }
 
-#define REDUCE_MEAN_OP(IN_TYPENAME, OUT_TYPENAME, FN_NAME)                                         \
+// ============================================================================
+// MEAN, VARIANCE AND STANDARD DEVIATION: Welford accumulation
+// ============================================================================
+//
+// A running mean and a running sum of squared deviations from it (m2), so the variance does
+// not suffer the cancellation of E[X^2] - E[X]^2. var and std are population statistics.
+
+#define WELFORD_MEAN (count > 0 ? mean : NAN)
+#define WELFORD_VAR (m2 / (float)count)
+#define WELFORD_STD sqrtf(m2 / (float)count)
+
+#define REDUCE_WELFORD_OP(IN_TYPENAME, OUT_TYPENAME, FN_NAME, FINALIZE)                            \
     extern "C" __global__ void hodu_cuda_##FN_NAME(const IN_TYPENAME *input, OUT_TYPENAME *output, \
                                                    const size_t *metadata) {                       \
         const size_t num_dims = metadata[0];                                                       \
@@ -95,7 +106,9 @@
         }                                                                                          \
         for (uint32_t output_idx = blockIdx.x * blockDim.x + threadIdx.x; output_idx < num_els;    \
              output_idx += blockDim.x * gridDim.x) {                                               \
-            float sum = 0.0f;                                                                      \
+            size_t count = 0;                                                                      \
+            float mean = 0.0f;                                                                     \
+            float m2 = 0.0f;                                                                       \
             size_t output_indices[16];                                                             \
             size_t temp = output_idx;                                                              \
             for (int d = (int)output_shape_len - 1; d >= 0; d--) {                                 \
@@ -137,9 +150,13 @@
                 for (size_t i = 0; i < num_dims; i++) {                                            \
                     flat_index += input_indices[i] * strides[i];                                   \
                 }                                                                                  \
-                sum += to_float(input[flat_index]);                                                \
+                float x = to_float(input[flat_index]);                                             \
+                count++;                                                                           \
+                float delta = x - mean;                                                            \
+                mean += delta / (float)count;                                                      \
+                m2 += delta * (x - mean);                                                          \
             }                                                                                      \
-            output[output_idx] = from_float<OUT_TYPENAME>(sum / (float)reduce_size);               \
+            output[output_idx] = from_float<OUT_TYPENAME>(FINALIZE);                               \
         }                                                                                          \
     }
 
@@ -571,12 +588,26 @@ REDUCE_OP(uint16_t, uint16_t, prod_u16, 1.0f, acc *= val)
 REDUCE_OP(uint32_t, uint32_t, prod_u32, 1.0f, acc *= val)
 REDUCE_OP(uint64_t, uint64_t, prod_u64, 1.0f, acc *= val)
 
-REDUCE_MEAN_OP(__nv_fp8_e4m3, __nv_fp8_e4m3, mean_f8e4m3)
-REDUCE_MEAN_OP(__nv_fp8_e5m2, __nv_fp8_e5m2, mean_f8e5m2)
-REDUCE_MEAN_OP(__nv_bfloat16, __nv_bfloat16, mean_bf16)
-REDUCE_MEAN_OP(__half, __half, mean_f16)
-REDUCE_MEAN_OP(float, float, mean_f32)
-REDUCE_MEAN_OP(double, double, mean_f64)
+REDUCE_WELFORD_OP(__nv_fp8_e4m3, __nv_fp8_e4m3, mean_f8e4m3, WELFORD_MEAN)
+REDUCE_WELFORD_OP(__nv_fp8_e5m2, __nv_fp8_e5m2, mean_f8e5m2, WELFORD_MEAN)
+REDUCE_WELFORD_OP(__nv_bfloat16, __nv_bfloat16, mean_bf16, WELFORD_MEAN)
+REDUCE_WELFORD_OP(__half, __half, mean_f16, WELFORD_MEAN)
+REDUCE_WELFORD_OP(float, float, mean_f32, WELFORD_MEAN)
+REDUCE_WELFORD_OP(double, double, mean_f64, WELFORD_MEAN)
+
+REDUCE_WELFORD_OP(__nv_fp8_e4m3, __nv_fp8_e4m3, var_f8e4m3, WELFORD_VAR)
+REDUCE_WELFORD_OP(__nv_fp8_e5m2, __nv_fp8_e5m2, var_f8e5m2, WELFORD_VAR)
+REDUCE_WELFORD_OP(__nv_bfloat16, __nv_bfloat16, var_bf16, WELFORD_VAR)
+REDUCE_WELFORD_OP(__half, __half, var_f16, WELFORD_VAR)
+REDUCE_WELFORD_OP(float, float, var_f32, WELFORD_VAR)
+REDUCE_WELFORD_OP(double, double, var_f64, WELFORD_VAR)
+
+REDUCE_WELFORD_OP(__nv_fp8_e4m3, __nv_fp8_e4m3, std_f8e4m3, WELFORD_STD)
+REDUCE_WELFORD_OP(__nv_fp8_e5m2, __nv_fp8_e5m2, std_f8e5m2, WELFORD_STD)
+REDUCE_WELFORD_OP(__nv_bfloat16, __nv_bfloat16, std_bf16, WELFORD_STD)
+REDUCE_WELFORD_OP(__half, __half, std_f16, WELFORD_STD)
+REDUCE_WELFORD_OP(float, float, std_f32, WELFORD_STD)
+REDUCE_WELFORD_OP(double, double, std_f64, WELFORD_STD)
 
 REDUCE_NORM_OP(__nv_fp8_e4m3, __nv_fp8_e4m3, norm_f8e4m3)
 REDUCE_NORM_OP(__nv_fp8_e5m2, __nv_fp8_e5m2, norm_f8e5m2)
@@ -1,6 +1,8 @@
 // Re-export cudarc types
+pub use cudarc::driver::sys::CUevent_flags;
 pub use cudarc::driver::{
-    CudaContext, CudaFunction, CudaModule, CudaSlice, CudaStream, DeviceRepr, LaunchConfig, PushKernelArg,
+    CudaContext, CudaEvent, CudaFunction, CudaModule, CudaSlice, CudaStream, DevicePtr, DeviceRepr, LaunchConfig,
+    PushKernelArg,
 };
 pub use cudarc::nvrtc::Ptx;
 
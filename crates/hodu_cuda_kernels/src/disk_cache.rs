@@ -0,0 +1,151 @@
+//! On-disk cache of compiled kernel modules
+//!
+//! PTX embedded at build time is JIT-compiled by the driver for the current GPU the first
+//! time a module is loaded. The resulting cubin is written to
+//! `~/.hodu/cache/kernels/cuda`, keyed by a hash of the PTX and the device's compute
+//! capability, so later processes load it directly. Set `HODU_KERNEL_CACHE_DIR` to use
+//! another directory or `HODU_KERNEL_CACHE=0` to disable the cache.
+
+use crate::{cuda::*, error::CudaKernelError, source::Source};
+use cudarc::driver::sys;
+use std::{
+    ffi::{c_void, CString},
+    path::PathBuf,
+};
+
+/// Directory cubins are cached in, or `None` when caching is disabled
+pub fn cache_dir() -> Option<PathBuf> {
+    if std::env::var("HODU_KERNEL_CACHE").is_ok_and(|v| v == "0") {
+        return None;
+    }
+    if let Ok(dir) = std::env::var("HODU_KERNEL_CACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(
+        PathBuf::from(home)
+            .join(".hodu")
+            .join("cache")
+            .join("kernels")
+            .join("cuda"),
+    )
+}
+
+/// FNV-1a, stable across builds unlike `DefaultHasher`
+fn hash(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0xcbf29ce484222325, |h, &b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+fn cubin_path(context: &Arc<CudaContext>, source: Source, ptx: &str) -> Option<PathBuf> {
+    let (major, minor) = context.compute_capability().ok()?;
+    let file = format!("{:?}-{:016x}-sm_{}{}.cubin", source, hash(ptx.as_bytes()), major, minor);
+    Some(cache_dir()?.join(file))
+}
+
+/// JIT-link `ptx` into a cubin for `context`'s device
+fn link_cubin(context: &Arc<CudaContext>, ptx: &str) -> Result<Vec<u8>, CudaKernelError> {
+    let err = |what: &str, e: sys::CUresult| CudaKernelError::LaunchError(format!("{} failed: {:?}", what, e));
+    let check = |what: &str, r: sys::CUresult| match r {
+        sys::CUresult::CUDA_SUCCESS => Ok(()),
+        e => Err(err(what, e)),
+    };
+
+    context
+        .bind_to_thread()
+        .map_err(|e| CudaKernelError::LaunchError(format!("Failed to bind context: {:?}", e)))?;
+
+    let ptx = CString::new(ptx).map_err(|e| CudaKernelError::InvalidKernel(e.to_string()))?;
+    let mut state = std::ptr::null_mut();
+    unsafe {
+        check(
+            "cuLinkCreate",
+            sys::cuLinkCreate_v2(0, std::ptr::null_mut(), std::ptr::null_mut(), &mut state),
+        )?;
+
+        let result = (|| {
+            check(
+                "cuLinkAddData",
+                sys::cuLinkAddData_v2(
+                    state,
+                    sys::CUjitInputType::CU_JIT_INPUT_PTX,
+                    ptx.as_ptr() as *mut c_void,
+                    ptx.as_bytes_with_nul().len(),
+                    std::ptr::null(),
+                    0,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                ),
+            )?;
+
+            let mut cubin = std::ptr::null_mut();
+            let mut size = 0;
+            check("cuLinkComplete", sys::cuLinkComplete(state, &mut cubin, &mut size))?;
+            // The image is owned by the link state, so copy it out before destroying it
+            Ok(std::slice::from_raw_parts(cubin as *const u8, size).to_vec())
+        })();
+
+        sys::cuLinkDestroy(state);
+        result
+    }
+}
+
+/// Load the module for `source`, going through the on-disk cubin cache when enabled
+///
+/// Any cache failure falls back to loading `ptx` directly.
+pub(crate) fn load_module(
+    context: &Arc<CudaContext>,
+    source: Source,
+    ptx: &Ptx,
+) -> Result<Arc<CudaModule>, CudaKernelError> {
+    let load_ptx = || {
+        context
+            .load_module(ptx.clone())
+            .map_err(|e| CudaKernelError::LaunchError(format!("Failed to load module: {:?}", e)))
+    };
+
+    let ptx_src = ptx.to_src();
+    let Some(path) = cubin_path(context, source, &ptx_src) else {
+        return load_ptx();
+    };
+
+    if path.exists() {
+        if let Ok(module) = context.load_module(Ptx::from_file(&path)) {
+            return Ok(module);
+        }
+        // Stale or truncated entry
+        let _ = std::fs::remove_file(&path);
+    }
+
+    let Ok(cubin) = link_cubin(context, &ptx_src) else {
+        return load_ptx();
+    };
+
+    // Write to a temporary file first so concurrent processes never see a partial cubin
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&tmp, &cubin))
+        .and_then(|_| std::fs::rename(&tmp, &path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+        return load_ptx();
+    }
+
+    context.load_module(Ptx::from_file(&path)).or_else(|_| load_ptx())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_is_stable() {
+        assert_eq!(hash(b""), 0xcbf29ce484222325);
+        assert_eq!(hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(hash(b"ptx"), hash(b"ptx "));
+    }
+}
@@ -104,6 +104,8 @@ pub struct Kernels {
     ptxs: RwLock<Ptxs>,
     modules: RwLock<Modules>,
     functions: RwLock<Functions>,
+    #[cfg(feature = "cublas")]
+    blas: RwLock<HashMap<usize, Arc<cudarc::cublas::CudaBlas>>>,
 }
 
 impl Default for Kernels {
@@ -125,6 +127,8 @@ impl Kernels {
             ptxs,
             modules,
             functions,
+            #[cfg(feature = "cublas")]
+            blas: RwLock::new(HashMap::new()),
         }
     }
 
@@ -141,6 +145,7 @@ impl Kernels {
             Source::OpsMatrix => crate::source::get_ops_matrix(),
             Source::OpsMemory => crate::source::get_ops_memory(),
             Source::OpsPadding => crate::source::get_ops_padding(),
+            Source::OpsQuant => crate::source::get_ops_quant(),
             Source::OpsReduce => crate::source::get_ops_reduce(),
             Source::OpsResize => crate::source::get_ops_resize(),
             Source::OpsScan => crate::source::get_ops_scan(),
@@ -191,9 +196,7 @@ impl Kernels {
             } else {
                 drop(modules);
                 let ptx = self.load_ptx(source)?;
-                let module = context
-                    .load_module(ptx)
-                    .map_err(|e| CudaKernelError::LaunchError(format!("Failed to load module: {:?}", e)))?;
+                let module = crate::disk_cache::load_module(context, source, &ptx)?;
 
                 let mut modules = self.modules.write_compat().map_err(CudaKernelError::Message)?;
                 modules.insert(source, module.clone());
@@ -211,4 +214,28 @@ impl Kernels {
 
         Ok(func)
     }
+
+    /// cuBLAS handle bound to `stream`, created on first use
+    #[cfg(feature = "cublas")]
+    pub fn cublas(&self, stream: &Arc<CudaStream>) -> Result<Arc<cudarc::cublas::CudaBlas>, CudaKernelError> {
+        let key = stream.cu_stream() as usize;
+
+        {
+            let blas = self.blas.read_compat().map_err(CudaKernelError::Message)?;
+            if let Some(handle) = blas.get(&key) {
+                return Ok(handle.clone());
+            }
+        }
+
+        let mut blas = self.blas.write_compat().map_err(CudaKernelError::Message)?;
+        if let Some(handle) = blas.get(&key) {
+            return Ok(handle.clone());
+        }
+
+        let handle = cudarc::cublas::CudaBlas::new(stream.clone())
+            .map_err(|e| CudaKernelError::LaunchError(format!("Failed to create cuBLAS: {:?}", e)))?;
+        let handle = Arc::new(handle);
+        blas.insert(key, handle.clone());
+        Ok(handle)
+    }
 }
@@ -7,12 +7,17 @@ pub mod ops_bitwise;
 pub mod ops_cast;
 pub mod ops_concat_split;
 pub mod ops_conv;
+#[cfg(feature = "cudnn")]
+pub mod ops_conv_cudnn;
 pub mod ops_einsum;
 pub mod ops_indexing;
 pub mod ops_linalg;
 pub mod ops_matrix;
+#[cfg(feature = "cublas")]
+pub mod ops_matrix_cublas;
 pub mod ops_memory;
 pub mod ops_padding;
+pub mod ops_quant;
 pub mod ops_reduce;
 pub mod ops_resize;
 pub mod ops_scan;
@@ -31,12 +36,17 @@ pub use ops_bitwise::*;
 pub use ops_cast::*;
 pub use ops_concat_split::*;
 pub use ops_conv::*;
+#[cfg(feature = "cudnn")]
+pub use ops_conv_cudnn::*;
 pub use ops_einsum::*;
 pub use ops_indexing::*;
 pub use ops_linalg::*;
 pub use ops_matrix::*;
+#[cfg(feature = "cublas")]
+pub use ops_matrix_cublas::*;
 pub use ops_memory::*;
 pub use ops_padding::*;
+pub use ops_quant::*;
 pub use ops_reduce::*;
 pub use ops_resize::*;
 pub use ops_scan::*;
@@ -76,7 +76,7 @@ where
         shared_mem_bytes: 0,
     };
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -0,0 +1,173 @@
+use crate::{
+    cuda::*,
+    error::{CudaKernelError, Result},
+    kernel::Kernels,
+    kernels::ops_conv::call_ops_conv,
+    precision::{current_precision, Precision},
+};
+use cudarc::cudnn::{sys, ConvForward, Cudnn, CudnnDataType, CudnnError};
+use half::{bf16, f16};
+use std::{cell::RefCell, collections::HashMap};
+
+/// Multiply-adds below which the hand-written kernel is used instead of cuDNN
+///
+/// cuDNN algorithm selection and workspace allocation dominate for tiny convolutions.
+pub const CUDNN_MIN_WORK: usize = 64 * 64 * 64;
+
+thread_local! {
+    // cuDNN handles are not thread-safe, so each thread keeps one per stream
+    static CUDNN_HANDLES: RefCell<HashMap<usize, Arc<Cudnn>>> = RefCell::new(HashMap::new());
+}
+
+fn cudnn_handle(stream: &Arc<CudaStream>) -> Result<Arc<Cudnn>> {
+    let key = stream.cu_stream() as usize;
+    CUDNN_HANDLES.with(|handles| {
+        let mut handles = handles.borrow_mut();
+        if let Some(handle) = handles.get(&key) {
+            return Ok(handle.clone());
+        }
+
+        let handle = Cudnn::new(stream.clone())
+            .map_err(|e| CudaKernelError::LaunchError(format!("Failed to create cuDNN: {:?}", e)))?;
+        handles.insert(key, handle.clone());
+        Ok(handle)
+    })
+}
+
+fn cudnn_err(e: CudnnError) -> CudaKernelError {
+    CudaKernelError::LaunchError(format!("cuDNN conv2d failed: {:?}", e))
+}
+
+/// Number of multiply-adds of a conv2d described by `metadata`
+fn conv2d_work(metadata: &[usize]) -> usize {
+    let num_els = metadata[0];
+    let in_channels = metadata[2];
+    let kernel_height = metadata[6];
+    let kernel_width = metadata[7];
+
+    num_els * in_channels * kernel_height * kernel_width
+}
+
+/// Execute conv2d using cuDNN, accumulating in `C`
+fn call_ops_conv2d_cudnn<T, C>(
+    context: &Arc<CudaContext>,
+    input: &CudaSlice<T>,
+    weight: &CudaSlice<T>,
+    output: &mut CudaSlice<T>,
+    metadata: &[usize],
+    (alpha, beta): (T, T),
+    precision: Precision,
+) -> Result<()>
+where
+    T: CudnnDataType + cudarc::driver::DeviceRepr,
+    C: CudnnDataType,
+{
+    let dim = |i: usize| metadata[i] as i32;
+    let input_offset = metadata[16];
+    let weight_offset = metadata[17];
+
+    let stream = crate::stream::current_stream(context);
+    let cudnn = cudnn_handle(&stream)?;
+    let nchw = sys::cudnnTensorFormat_t::CUDNN_TENSOR_NCHW;
+
+    // [batch, channels, height, width]
+    let x = cudnn
+        .create_4d_tensor::<T>(nchw, [dim(1), dim(2), dim(4), dim(5)])
+        .map_err(cudnn_err)?;
+    let w = cudnn
+        .create_4d_filter::<T>(nchw, [dim(3), dim(2), dim(6), dim(7)])
+        .map_err(cudnn_err)?;
+    let y = cudnn
+        .create_4d_tensor::<T>(nchw, [dim(1), dim(3), dim(8), dim(9)])
+        .map_err(cudnn_err)?;
+    let mut conv = cudnn
+        .create_conv2d::<C>(
+            [dim(12), dim(13)],
+            [dim(10), dim(11)],
+            [dim(14), dim(15)],
+            sys::cudnnConvolutionMode_t::CUDNN_CROSS_CORRELATION,
+        )
+        .map_err(cudnn_err)?;
+    // cuDNN may use TF32 tensor cores for f32 unless restricted to FMA math
+    if T::DATA_TYPE == sys::cudnnDataType_t::CUDNN_DATA_FLOAT && !precision.allow_tf32 {
+        conv.set_math_type(sys::cudnnMathType_t::CUDNN_FMA_MATH)
+            .map_err(cudnn_err)?;
+    }
+
+    let op = ConvForward {
+        conv: &conv,
+        x: &x,
+        w: &w,
+        y: &y,
+    };
+    let algo = op.pick_algorithm().map_err(cudnn_err)?;
+    let workspace_size = op.get_workspace_size(algo).map_err(cudnn_err)?;
+
+    let mut workspace = if workspace_size > 0 {
+        Some(
+            stream
+                .alloc_zeros::<u8>(workspace_size)
+                .map_err(|e| CudaKernelError::MemoryError(format!("Failed to allocate workspace: {:?}", e)))?,
+        )
+    } else {
+        None
+    };
+
+    let input_view = input.slice(input_offset..);
+    let weight_view = weight.slice(weight_offset..);
+
+    unsafe {
+        op.launch(
+            algo,
+            workspace.as_mut(),
+            (alpha, beta),
+            &input_view,
+            &weight_view,
+            output,
+        )
+    }
+    .map_err(cudnn_err)
+}
+
+macro_rules! impl_cudnn_conv2d {
+    ($ty:ty, $compute:ty, $reduced:ty, $one:expr, $zero:expr) => {
+        paste::paste! {
+            #[doc = "cuDNN-accelerated conv2d for " $ty]
+            ///
+            /// Convolutions smaller than [`CUDNN_MIN_WORK`] use the hand-written kernel instead,
+            /// as does any configuration cuDNN rejects. Arguments and metadata are the same as
+            /// [`call_ops_conv`] with the conv2d metadata layout.
+            pub fn [<call_ops_conv2d_cudnn_ $ty>](
+                kernel: crate::kernels::macros::Kernel,
+                kernels: &Kernels,
+                context: &Arc<CudaContext>,
+                input: &CudaSlice<$ty>,
+                weight: &CudaSlice<$ty>,
+                output: &mut CudaSlice<$ty>,
+                metadata: &[usize],
+            ) -> Result<()> {
+                if conv2d_work(metadata) >= CUDNN_MIN_WORK {
+                    let precision = current_precision();
+                    let scale = ($one, $zero);
+                    let result = if precision.f16_accumulate_f32 {
+                        call_ops_conv2d_cudnn::<$ty, $compute>(context, input, weight, output, metadata, scale, precision)
+                    } else {
+                        call_ops_conv2d_cudnn::<$ty, $reduced>(context, input, weight, output, metadata, scale, precision)
+                    };
+                    if result.is_ok() {
+                        return Ok(());
+                    }
+                }
+
+                call_ops_conv(kernel, kernels, context, input, weight, output, metadata)
+            }
+        }
+    };
+}
+
+// Half precision types accumulate in f32 unless the precision policy allows f16
+// accumulation, which cuDNN only supports for f16
+impl_cudnn_conv2d!(bf16, f32, f32, bf16::ONE, bf16::ZERO);
+impl_cudnn_conv2d!(f16, f32, f16, f16::ONE, f16::ZERO);
+impl_cudnn_conv2d!(f32, f32, f32, 1.0, 0.0);
+impl_cudnn_conv2d!(f64, f64, f64, 1.0, 0.0);
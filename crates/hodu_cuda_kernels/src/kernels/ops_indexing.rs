@@ -56,7 +56,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -116,7 +116,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -175,7 +175,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -237,7 +237,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -292,7 +292,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -340,7 +340,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -390,7 +390,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -432,7 +432,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -476,7 +476,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -516,7 +516,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -555,7 +555,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -590,7 +590,7 @@ pub fn call_unique_prefix_sum(
 ) -> Result<()> {
     let func = kernels.load_function(context, Source::OpsIndexing, "hodu_cuda_unique_prefix_sum")?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -631,7 +631,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -680,7 +680,7 @@ where
 {
     let func = kernels.load_function(context, Source::OpsIndexing, kernel.0)?;
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -0,0 +1,448 @@
+use crate::{
+    cuda::*,
+    error::{CudaKernelError, Result},
+    kernel::Kernels,
+    kernels::ops_matrix::{call_ops_dot_kernel, call_ops_matmul_kernel},
+    precision::{current_precision, Precision},
+};
+use cudarc::cublas::{
+    result::{gemm_strided_batched_ex, CublasError},
+    sys::{self, cublasOperation_t},
+    CudaBlas, Gemm, GemmConfig, StridedBatchedConfig,
+};
+use cudarc::driver::{CudaView, DevicePtrMut};
+use half::{bf16, f16};
+
+/// Multiply-adds below which the hand-written kernels are used instead of cuBLAS
+///
+/// For tiny problems a single tiled launch is cheaper than cuBLAS heuristics and its
+/// launch sequence.
+pub const CUBLAS_MIN_WORK: usize = 32 * 32 * 32;
+
+/// Helper trait to enable cuBLAS GEMM for supported types
+trait CublasGemm: cudarc::driver::DeviceRepr + Sized {
+    /// Data and compute type for accumulating in `Self` instead of f32, if cuBLAS supports it
+    const REDUCED_COMPUTE: Option<(sys::cudaDataType_t, sys::cublasComputeType_t)>;
+
+    fn one() -> Self;
+    fn zero() -> Self;
+
+    fn cublas_gemm<A, B, C>(
+        blas: &CudaBlas,
+        cfg: GemmConfig<Self>,
+        lhs: &A,
+        rhs: &B,
+        output: &mut C,
+    ) -> core::result::Result<(), cudarc::cublas::result::CublasError>
+    where
+        A: cudarc::driver::DevicePtr<Self>,
+        B: cudarc::driver::DevicePtr<Self>,
+        C: cudarc::driver::DevicePtrMut<Self>;
+
+    fn cublas_gemm_batched<A, B, C>(
+        blas: &CudaBlas,
+        cfg: StridedBatchedConfig<Self>,
+        lhs: &A,
+        rhs: &B,
+        output: &mut C,
+    ) -> core::result::Result<(), cudarc::cublas::result::CublasError>
+    where
+        A: cudarc::driver::DevicePtr<Self>,
+        B: cudarc::driver::DevicePtr<Self>,
+        C: cudarc::driver::DevicePtrMut<Self>;
+}
+
+macro_rules! impl_cublas_gemm {
+    ($ty:ty, $one:expr, $zero:expr, $reduced:expr) => {
+        impl CublasGemm for $ty {
+            const REDUCED_COMPUTE: Option<(sys::cudaDataType_t, sys::cublasComputeType_t)> = $reduced;
+
+            fn one() -> Self {
+                $one
+            }
+
+            fn zero() -> Self {
+                $zero
+            }
+
+            fn cublas_gemm<A, B, C>(
+                blas: &CudaBlas,
+                cfg: GemmConfig<Self>,
+                lhs: &A,
+                rhs: &B,
+                output: &mut C,
+            ) -> core::result::Result<(), cudarc::cublas::result::CublasError>
+            where
+                A: cudarc::driver::DevicePtr<Self>,
+                B: cudarc::driver::DevicePtr<Self>,
+                C: cudarc::driver::DevicePtrMut<Self>,
+            {
+                unsafe { blas.gemm(cfg, lhs, rhs, output) }
+            }
+
+            fn cublas_gemm_batched<A, B, C>(
+                blas: &CudaBlas,
+                cfg: StridedBatchedConfig<Self>,
+                lhs: &A,
+                rhs: &B,
+                output: &mut C,
+            ) -> core::result::Result<(), cudarc::cublas::result::CublasError>
+            where
+                A: cudarc::driver::DevicePtr<Self>,
+                B: cudarc::driver::DevicePtr<Self>,
+                C: cudarc::driver::DevicePtrMut<Self>,
+            {
+                unsafe { blas.gemm_strided_batched(cfg, lhs, rhs, output) }
+            }
+        }
+    };
+}
+
+impl_cublas_gemm!(bf16, bf16::ONE, bf16::ZERO, None);
+impl_cublas_gemm!(
+    f16,
+    f16::ONE,
+    f16::ZERO,
+    Some((
+        sys::cudaDataType_t::CUDA_R_16F,
+        sys::cublasComputeType_t::CUBLAS_COMPUTE_16F
+    ))
+);
+impl_cublas_gemm!(f32, 1.0, 0.0, None);
+impl_cublas_gemm!(f64, 1.0, 0.0, None);
+
+/// Apply the TF32 part of `precision` to `blas` before a GEMM
+fn set_math_mode(blas: &CudaBlas, precision: Precision) -> Result<()> {
+    let mode = if precision.allow_tf32 {
+        sys::cublasMath_t::CUBLAS_TF32_TENSOR_OP_MATH
+    } else {
+        sys::cublasMath_t::CUBLAS_DEFAULT_MATH
+    };
+    unsafe { sys::cublasSetMathMode(*blas.handle(), mode) }
+        .result()
+        .map_err(|e| CudaKernelError::LaunchError(format!("cublasSetMathMode failed: {:?}", e)))
+}
+
+/// Strided-batched GEMM with the data and compute types in `types`
+///
+/// `alpha` and `beta` are passed as `T`, so the compute type must match `T`.
+fn gemm_reduced<T: CublasGemm>(
+    blas: &CudaBlas,
+    stream: &Arc<CudaStream>,
+    (data_type, compute_type): (sys::cudaDataType_t, sys::cublasComputeType_t),
+    cfg: StridedBatchedConfig<T>,
+    lhs: &CudaView<T>,
+    rhs: &CudaView<T>,
+    output: &mut CudaSlice<T>,
+) -> core::result::Result<(), CublasError> {
+    let (a, _record_a) = lhs.device_ptr(stream);
+    let (b, _record_b) = rhs.device_ptr(stream);
+    let (c, _record_c) = output.device_ptr_mut(stream);
+    unsafe {
+        gemm_strided_batched_ex(
+            *blas.handle(),
+            cfg.gemm.transa,
+            cfg.gemm.transb,
+            cfg.gemm.m,
+            cfg.gemm.n,
+            cfg.gemm.k,
+            &cfg.gemm.alpha as *const T as *const _,
+            a as *const _,
+            data_type,
+            cfg.gemm.lda,
+            cfg.stride_a,
+            b as *const _,
+            data_type,
+            cfg.gemm.ldb,
+            cfg.stride_b,
+            &cfg.gemm.beta as *const T as *const _,
+            c as *mut _,
+            data_type,
+            cfg.gemm.ldc,
+            cfg.stride_c,
+            cfg.batch_size,
+            compute_type,
+            sys::cublasGemmAlgo_t::CUBLAS_GEMM_DEFAULT,
+        )
+    }
+}
+
+/// Maps a row-major `rows x cols` operand onto a cuBLAS operand and leading dimension
+///
+/// cuBLAS is column-major and the problem is solved as `C^T = B^T * A^T`, so a row-major
+/// operand is used as-is (`CUBLAS_OP_N`) and a column-major one (e.g. a transposed view)
+/// with `CUBLAS_OP_T`. Other layouts return `None`.
+fn gemm_operand(rows: usize, cols: usize, row_stride: usize, col_stride: usize) -> Option<(cublasOperation_t, i32)> {
+    if col_stride == 1 && (rows == 1 || row_stride >= cols) {
+        Some((cublasOperation_t::CUBLAS_OP_N, row_stride.max(cols) as i32))
+    } else if row_stride == 1 && (cols == 1 || col_stride >= rows) {
+        Some((cublasOperation_t::CUBLAS_OP_T, col_stride.max(rows) as i32))
+    } else {
+        None
+    }
+}
+
+/// Single stride stepping an operand through the output batch, if one exists
+///
+/// Operands without batch dimensions (or with all of them equal to 1) are broadcast with a
+/// zero stride. Otherwise the operand batch shape must match the output and its batch
+/// dimensions must be uniformly spaced.
+fn batch_stride(shape: &[usize], strides: &[usize], batch_shape: &[usize]) -> Option<i64> {
+    let operand_batch = &shape[..shape.len() - 2];
+    let operand_strides = &strides[..strides.len() - 2];
+
+    if operand_batch.iter().all(|&dim| dim == 1) {
+        return Some(0);
+    }
+    if operand_batch != batch_shape {
+        return None;
+    }
+
+    let mut stride = None;
+    let mut expected = None;
+    for (&dim, &dim_stride) in operand_batch.iter().zip(operand_strides).rev() {
+        if dim == 1 {
+            continue;
+        }
+        match expected {
+            None => stride = Some(dim_stride),
+            Some(expected) if expected != dim_stride => return None,
+            Some(_) => {},
+        }
+        expected = Some(dim_stride * dim);
+    }
+
+    Some(stride.unwrap_or(0) as i64)
+}
+
+/// Number of multiply-adds of a matmul described by `metadata`
+fn matmul_work(metadata: &[usize]) -> usize {
+    let lhs_ndim = metadata[1];
+    let rhs_ndim = metadata[2];
+    let batch_ndim = metadata[3];
+
+    let batch_start = 4 + lhs_ndim + rhs_ndim;
+    let num_batches: usize = metadata[batch_start..batch_start + batch_ndim].iter().product();
+
+    let metadata_base = 4 + lhs_ndim + rhs_ndim + batch_ndim + lhs_ndim + rhs_ndim;
+    let m = metadata[metadata_base + 2];
+    let k = metadata[metadata_base + 3];
+    let n = metadata[metadata_base + 4];
+
+    num_batches * m * k * n
+}
+
+/// Execute matmul using cuBLAS (for bf16/f16/f32/f64)
+fn call_ops_matmul_cublas<T>(
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    lhs: &CudaSlice<T>,
+    rhs: &CudaSlice<T>,
+    output: &mut CudaSlice<T>,
+    metadata: &[usize],
+) -> Result<()>
+where
+    T: CublasGemm,
+{
+    let lhs_ndim = metadata[1];
+    let rhs_ndim = metadata[2];
+    let batch_ndim = metadata[3];
+
+    let lhs_shape = &metadata[4..4 + lhs_ndim];
+    let rhs_shape = &metadata[4 + lhs_ndim..4 + lhs_ndim + rhs_ndim];
+    let batch_shape = &metadata[4 + lhs_ndim + rhs_ndim..4 + lhs_ndim + rhs_ndim + batch_ndim];
+
+    let strides_base = 4 + lhs_ndim + rhs_ndim + batch_ndim;
+    let lhs_strides = &metadata[strides_base..strides_base + lhs_ndim];
+    let rhs_strides = &metadata[strides_base + lhs_ndim..strides_base + lhs_ndim + rhs_ndim];
+
+    let metadata_base = strides_base + lhs_ndim + rhs_ndim;
+    let lhs_offset = metadata[metadata_base];
+    let rhs_offset = metadata[metadata_base + 1];
+    let m = metadata[metadata_base + 2];
+    let k = metadata[metadata_base + 3];
+    let n = metadata[metadata_base + 4];
+
+    let num_batches: usize = batch_shape.iter().product();
+
+    let unsupported = || CudaKernelError::InvalidInput("matmul layout is not supported by cuBLAS".into());
+
+    // cuBLAS is column-major, so lhs and rhs are swapped
+    let (transb, ldb) =
+        gemm_operand(m, k, lhs_strides[lhs_ndim - 2], lhs_strides[lhs_ndim - 1]).ok_or_else(unsupported)?;
+    let (transa, lda) =
+        gemm_operand(k, n, rhs_strides[rhs_ndim - 2], rhs_strides[rhs_ndim - 1]).ok_or_else(unsupported)?;
+    let stride_b = batch_stride(lhs_shape, lhs_strides, batch_shape).ok_or_else(unsupported)?;
+    let stride_a = batch_stride(rhs_shape, rhs_strides, batch_shape).ok_or_else(unsupported)?;
+
+    let stream = crate::stream::current_stream(context);
+    let blas = kernels.cublas(&stream)?;
+    let precision = current_precision();
+    set_math_mode(&blas, precision)?;
+
+    let lhs_view = lhs.slice(lhs_offset..);
+    let rhs_view = rhs.slice(rhs_offset..);
+
+    let gemm = GemmConfig {
+        transa,
+        transb,
+        m: n as i32,
+        n: m as i32,
+        k: k as i32,
+        alpha: T::one(),
+        lda,
+        ldb,
+        beta: T::zero(),
+        ldc: n as i32,
+    };
+
+    let cfg = StridedBatchedConfig {
+        gemm,
+        batch_size: num_batches as i32,
+        stride_a,
+        stride_b,
+        stride_c: (m * n) as i64,
+    };
+
+    let result = match T::REDUCED_COMPUTE.filter(|_| !precision.f16_accumulate_f32) {
+        Some(types) => gemm_reduced(&blas, &stream, types, cfg, &rhs_view, &lhs_view, output),
+        None if num_batches == 1 => T::cublas_gemm(&blas, cfg.gemm, &rhs_view, &lhs_view, output),
+        None => T::cublas_gemm_batched(&blas, cfg, &rhs_view, &lhs_view, output),
+    };
+
+    result.map_err(|e| CudaKernelError::LaunchError(format!("cuBLAS GEMM failed: {:?}", e)))
+}
+
+macro_rules! impl_cublas_matmul {
+    ($ty:ty) => {
+        paste::paste! {
+            #[doc = "cuBLAS-accelerated matmul for " $ty]
+            ///
+            /// Problems smaller than [`CUBLAS_MIN_WORK`] and layouts cuBLAS can't express
+            /// (e.g. broadcast batch dimensions) use the hand-written kernel instead.
+            /// Arguments and metadata are the same as [`call_ops_matmul`](super::call_ops_matmul).
+            pub fn [<call_ops_matmul_cublas_ $ty>](
+                kernel: crate::kernels::macros::Kernel,
+                kernels: &Kernels,
+                context: &Arc<CudaContext>,
+                lhs: &CudaSlice<$ty>,
+                rhs: &CudaSlice<$ty>,
+                output: &mut CudaSlice<$ty>,
+                metadata: &[usize],
+            ) -> Result<()> {
+                if matmul_work(metadata) >= CUBLAS_MIN_WORK
+                    && call_ops_matmul_cublas(kernels, context, lhs, rhs, output, metadata).is_ok()
+                {
+                    return Ok(());
+                }
+
+                call_ops_matmul_kernel(kernel, kernels, context, lhs, rhs, output, metadata)
+            }
+        }
+    };
+}
+
+impl_cublas_matmul!(bf16);
+impl_cublas_matmul!(f16);
+impl_cublas_matmul!(f32);
+impl_cublas_matmul!(f64);
+
+/// Execute dot using cuBLAS (for bf16/f16/f32/f64)
+fn call_ops_dot_cublas<T>(
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    lhs: &CudaSlice<T>,
+    rhs: &CudaSlice<T>,
+    output: &mut CudaSlice<T>,
+    metadata: &[usize],
+) -> Result<()>
+where
+    T: CublasGemm,
+{
+    let m = metadata[0];
+    let k = metadata[1];
+    let n = metadata[2];
+    let lhs_stride_m = metadata[3];
+    let lhs_stride_k = metadata[4];
+    let rhs_stride_k = metadata[5];
+    let rhs_stride_n = metadata[6];
+    let lhs_offset = metadata[7];
+    let rhs_offset = metadata[8];
+
+    let unsupported = || CudaKernelError::InvalidInput("dot layout is not supported by cuBLAS".into());
+
+    // cuBLAS is column-major, so lhs and rhs are swapped
+    let (transb, ldb) = gemm_operand(m, k, lhs_stride_m, lhs_stride_k).ok_or_else(unsupported)?;
+    let (transa, lda) = gemm_operand(k, n, rhs_stride_k, rhs_stride_n).ok_or_else(unsupported)?;
+
+    let stream = crate::stream::current_stream(context);
+    let blas = kernels.cublas(&stream)?;
+    let precision = current_precision();
+    set_math_mode(&blas, precision)?;
+
+    let lhs_view = lhs.slice(lhs_offset..);
+    let rhs_view = rhs.slice(rhs_offset..);
+
+    let gemm = GemmConfig {
+        transa,
+        transb,
+        m: n as i32,
+        n: m as i32,
+        k: k as i32,
+        alpha: T::one(),
+        lda,
+        ldb,
+        beta: T::zero(),
+        ldc: n as i32,
+    };
+
+    let result = match T::REDUCED_COMPUTE.filter(|_| !precision.f16_accumulate_f32) {
+        Some(types) => {
+            let cfg = StridedBatchedConfig {
+                gemm,
+                batch_size: 1,
+                stride_a: 0,
+                stride_b: 0,
+                stride_c: 0,
+            };
+            gemm_reduced(&blas, &stream, types, cfg, &rhs_view, &lhs_view, output)
+        },
+        None => T::cublas_gemm(&blas, gemm, &rhs_view, &lhs_view, output),
+    };
+
+    result.map_err(|e| CudaKernelError::LaunchError(format!("cuBLAS GEMM failed: {:?}", e)))
+}
+
+macro_rules! impl_cublas_dot {
+    ($ty:ty) => {
+        paste::paste! {
+            #[doc = "cuBLAS-accelerated dot for " $ty]
+            ///
+            /// Problems smaller than [`CUBLAS_MIN_WORK`] and layouts cuBLAS can't express use
+            /// the hand-written kernel instead. Arguments and metadata are the same as
+            /// [`call_ops_dot`](super::call_ops_dot).
+            pub fn [<call_ops_dot_cublas_ $ty>](
+                kernel: crate::kernels::macros::Kernel,
+                kernels: &Kernels,
+                context: &Arc<CudaContext>,
+                lhs: &CudaSlice<$ty>,
+                rhs: &CudaSlice<$ty>,
+                output: &mut CudaSlice<$ty>,
+                metadata: &[usize],
+            ) -> Result<()> {
+                if metadata[0] * metadata[1] * metadata[2] >= CUBLAS_MIN_WORK
+                    && call_ops_dot_cublas(kernels, context, lhs, rhs, output, metadata).is_ok()
+                {
+                    return Ok(());
+                }
+
+                call_ops_dot_kernel(kernel, kernels, context, lhs, rhs, output, metadata)
+            }
+        }
+    };
+}
+
+impl_cublas_dot!(bf16);
+impl_cublas_dot!(f16);
+impl_cublas_dot!(f32);
+impl_cublas_dot!(f64);
@@ -23,6 +23,9 @@ ops!(contiguous, copy);
 /// - metadata[2..2+num_dims]: shape (dimensions of the tensor)
 /// - metadata[2+num_dims..2+2*num_dims]: strides (stride for each dimension)
 /// - metadata[2+2*num_dims]: offset (starting offset in input buffer)
+///
+/// Transposed views, whose unit-stride dim is not the innermost one, are copied by a
+/// shared-memory tiled kernel instead of the strided one.
 pub fn call_ops_contiguous<T>(
     kernel: crate::kernels::macros::Kernel,
     kernels: &Kernels,
@@ -34,6 +37,10 @@ pub fn call_ops_contiguous<T>(
 where
     T: cudarc::driver::DeviceRepr,
 {
+    if let Some(axis) = transpose_axis(metadata) {
+        return call_transpose_tiled(kernel, kernels, context, input, output, metadata, axis);
+    }
+
     let func = kernels.load_function(context, Source::OpsMemory, kernel.0)?;
 
     let num_els = metadata[0];
@@ -46,7 +53,7 @@ where
         shared_mem_bytes: 0,
     };
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -61,6 +68,76 @@ where
     Ok(())
 }
 
+/// Side of the square tile staged in shared memory by the tiled transpose kernel
+const TRANSPOSE_TILE: usize = 32;
+/// Rows of the tile each thread block covers per pass
+const TRANSPOSE_ROWS: u32 = 8;
+/// Grid y/z dimension limit
+const MAX_GRID_DIM: usize = 65535;
+
+/// Unit-stride dim of a transposed view, if the tiled transpose kernel handles the layout
+fn transpose_axis(metadata: &[usize]) -> Option<usize> {
+    let num_dims = metadata[1];
+    if num_dims < 2 || metadata[0] == 0 {
+        return None;
+    }
+    let dims = &metadata[2..2 + num_dims];
+    let strides = &metadata[2 + num_dims..2 + 2 * num_dims];
+    let last = num_dims - 1;
+    if strides[last] == 1 || dims[last] < 2 {
+        return None;
+    }
+    (0..last).find(|&d| strides[d] == 1 && dims[d] >= 2 && dims[d].div_ceil(TRANSPOSE_TILE) <= MAX_GRID_DIM)
+}
+
+fn call_transpose_tiled<T>(
+    kernel: crate::kernels::macros::Kernel,
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    input: &CudaSlice<T>,
+    output: &mut CudaSlice<T>,
+    metadata: &[usize],
+    axis: usize,
+) -> Result<()>
+where
+    T: cudarc::driver::DeviceRepr,
+{
+    let name = kernel.0.replacen("contiguous", "transpose_tiled", 1);
+    let func = kernels.load_function(context, Source::OpsMemory, name)?;
+
+    let num_dims = metadata[1];
+    let rows = metadata[2 + axis];
+    let cols = metadata[2 + num_dims - 1];
+    let num_planes = metadata[0] / (rows * cols);
+
+    let cfg = LaunchConfig {
+        grid_dim: (
+            cols.div_ceil(TRANSPOSE_TILE) as u32,
+            rows.div_ceil(TRANSPOSE_TILE) as u32,
+            num_planes.min(MAX_GRID_DIM) as u32,
+        ),
+        block_dim: (TRANSPOSE_TILE as u32, TRANSPOSE_ROWS, 1),
+        shared_mem_bytes: 0,
+    };
+
+    let mut tiled_metadata = metadata.to_vec();
+    tiled_metadata.push(axis);
+
+    let stream = crate::stream::current_stream(context);
+    let metadata_dev = stream
+        .memcpy_stod(&tiled_metadata)
+        .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
+
+    unsafe {
+        func.launch(&stream, cfg, |args| {
+            args.arg(input).arg(output).arg(&metadata_dev);
+        })
+        .map_err(|e| CudaKernelError::LaunchError(format!("Failed to launch kernel: {:?}", e)))?;
+    }
+
+    Ok(())
+}
+
 /// Execute a copy operation with strided tensor support
 ///
 /// # Arguments
@@ -99,7 +176,7 @@ where
         shared_mem_bytes: 0,
     };
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
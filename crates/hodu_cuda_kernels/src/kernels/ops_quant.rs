@@ -0,0 +1,335 @@
+use crate::{
+    cuda::*,
+    error::{CudaKernelError, Result},
+    kernel::Kernels,
+    kernels::macros::ops,
+    source::Source,
+};
+
+ops!(
+    quantize,
+    dequantize,
+    matmul_int8,
+    quantize_q4_0,
+    dequantize_q4_0,
+    matmul_q4_0
+);
+
+/// Execute an int8 quantize operation
+///
+/// Computes `q = clamp(round(x / scale) + zero_point, -128, 127)` elementwise, rounding half to
+/// even. Kernels are named after the floating-point input type (e.g. `quantize::F32`).
+///
+/// # Arguments
+/// * `kernel` - The quantize kernel (e.g., quantize::F32)
+/// * `kernels` - Kernel cache for managing compiled kernels
+/// * `context` - CUDA context to execute on
+/// * `input` - Floating-point input tensor device slice
+/// * `output` - i8 output device slice
+/// * `metadata` - Host slice containing metadata describing tensor layout
+/// * `scale` - Quantization step
+/// * `zero_point` - Integer value that represents 0.0
+///
+/// # Metadata layout
+/// - metadata[0]: num_els (total number of elements to process)
+/// - metadata[1]: num_dims (number of dimensions)
+/// - metadata[2..2+num_dims]: shape (dimensions of the tensor)
+/// - metadata[2+num_dims..2+2*num_dims]: strides (stride for each dimension)
+/// - metadata[2+2*num_dims]: offset (starting offset in input buffer)
+pub fn call_ops_quantize<T>(
+    kernel: crate::kernels::macros::Kernel,
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    input: &CudaSlice<T>,
+    output: &mut CudaSlice<i8>,
+    metadata: &[usize],
+    scale: f32,
+    zero_point: i32,
+) -> Result<()>
+where
+    T: cudarc::driver::DeviceRepr,
+{
+    call_ops_affine(kernel, kernels, context, input, output, metadata, scale, zero_point)
+}
+
+/// Execute an int8 dequantize operation
+///
+/// Computes `x = (q - zero_point) * scale` elementwise. Kernels are named after the
+/// floating-point output type (e.g. `dequantize::F32`).
+///
+/// # Arguments
+/// * `kernel` - The dequantize kernel (e.g., dequantize::F32)
+/// * `kernels` - Kernel cache for managing compiled kernels
+/// * `context` - CUDA context to execute on
+/// * `input` - i8 input tensor device slice
+/// * `output` - Floating-point output device slice
+/// * `metadata` - Host slice containing metadata describing tensor layout (same as quantize)
+/// * `scale` - Quantization step
+/// * `zero_point` - Integer value that represents 0.0
+pub fn call_ops_dequantize<T>(
+    kernel: crate::kernels::macros::Kernel,
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    input: &CudaSlice<i8>,
+    output: &mut CudaSlice<T>,
+    metadata: &[usize],
+    scale: f32,
+    zero_point: i32,
+) -> Result<()>
+where
+    T: cudarc::driver::DeviceRepr,
+{
+    call_ops_affine(kernel, kernels, context, input, output, metadata, scale, zero_point)
+}
+
+fn call_ops_affine<I, O>(
+    kernel: crate::kernels::macros::Kernel,
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    input: &CudaSlice<I>,
+    output: &mut CudaSlice<O>,
+    metadata: &[usize],
+    scale: f32,
+    zero_point: i32,
+) -> Result<()>
+where
+    I: cudarc::driver::DeviceRepr,
+    O: cudarc::driver::DeviceRepr,
+{
+    let func = kernels.load_function(context, Source::OpsQuant, kernel.0)?;
+
+    let num_els = metadata[0];
+    let block_size = 256u32;
+    let grid_size = (num_els as u32).div_ceil(block_size).max(1);
+
+    let cfg = LaunchConfig {
+        grid_dim: (grid_size, 1, 1),
+        block_dim: (block_size, 1, 1),
+        shared_mem_bytes: 0,
+    };
+
+    let stream = crate::stream::current_stream(context);
+    let metadata_dev = stream
+        .memcpy_stod(metadata)
+        .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
+
+    unsafe {
+        func.launch(&stream, cfg, |args| {
+            args.arg(input)
+                .arg(output)
+                .arg(&metadata_dev)
+                .arg(&scale)
+                .arg(&zero_point);
+        })
+        .map_err(|e| CudaKernelError::LaunchError(format!("Failed to launch kernel: {:?}", e)))?;
+    }
+
+    Ok(())
+}
+
+/// Execute an int8 batched matrix multiplication
+///
+/// Computes `C = A @ B` for i8 operands with i32 accumulation and an i32 output. Batch dimensions
+/// broadcast exactly like `matmul`.
+///
+/// # Arguments
+/// * `kernel` - The kernel to execute (matmul_int8::I8)
+/// * `kernels` - Kernel cache for managing compiled kernels
+/// * `context` - CUDA context to execute on
+/// * `lhs` - i8 left-hand side device slice
+/// * `rhs` - i8 right-hand side device slice
+/// * `output` - i32 output device slice
+/// * `metadata` - Host slice containing metadata (same layout as `call_ops_matmul`)
+pub fn call_ops_matmul_int8(
+    kernel: crate::kernels::macros::Kernel,
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    lhs: &CudaSlice<i8>,
+    rhs: &CudaSlice<i8>,
+    output: &mut CudaSlice<i32>,
+    metadata: &[usize],
+) -> Result<()> {
+    let func = kernels.load_function(context, Source::OpsQuant, kernel.0)?;
+
+    let lhs_ndim = metadata[1];
+    let rhs_ndim = metadata[2];
+    let batch_ndim = metadata[3];
+
+    let metadata_base = 4 + lhs_ndim + rhs_ndim + batch_ndim + lhs_ndim + rhs_ndim;
+    let m = metadata[metadata_base + 2];
+    let n = metadata[metadata_base + 4];
+
+    let num_batches: usize = metadata[4 + lhs_ndim + rhs_ndim..4 + lhs_ndim + rhs_ndim + batch_ndim]
+        .iter()
+        .product();
+
+    // 16x16 tiles, one z-block per output batch
+    const TILE_SIZE: u32 = 16;
+
+    let cfg = LaunchConfig {
+        grid_dim: (
+            (n as u32).div_ceil(TILE_SIZE).max(1),
+            (m as u32).div_ceil(TILE_SIZE).max(1),
+            num_batches as u32,
+        ),
+        block_dim: (TILE_SIZE, TILE_SIZE, 1),
+        shared_mem_bytes: 0,
+    };
+
+    let stream = crate::stream::current_stream(context);
+    let metadata_dev = stream
+        .memcpy_stod(metadata)
+        .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
+
+    unsafe {
+        func.launch(&stream, cfg, |args| {
+            args.arg(lhs).arg(rhs).arg(output).arg(&metadata_dev);
+        })
+        .map_err(|e| CudaKernelError::LaunchError(format!("Failed to launch kernel: {:?}", e)))?;
+    }
+
+    Ok(())
+}
+
+/// Execute a Q4_0 quantize operation
+///
+/// Packs every 32 consecutive values into an 18-byte block (f16 scale + 16 bytes of 4-bit codes).
+/// One thread encodes one block.
+///
+/// # Arguments
+/// * `kernel` - The kernel to execute (e.g., quantize_q4_0::F32)
+/// * `kernels` - Kernel cache for managing compiled kernels
+/// * `context` - CUDA context to execute on
+/// * `input` - Floating-point input tensor device slice
+/// * `output` - u8 output device slice (num_els / 32 * 18 bytes)
+/// * `metadata` - Host slice with the same layout as quantize; num_els and the last dimension must
+///   be multiples of 32
+pub fn call_ops_quantize_q4_0<T>(
+    kernel: crate::kernels::macros::Kernel,
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    input: &CudaSlice<T>,
+    output: &mut CudaSlice<u8>,
+    metadata: &[usize],
+) -> Result<()>
+where
+    T: cudarc::driver::DeviceRepr,
+{
+    let num_blocks = metadata[0] / 32;
+    call_ops_block(kernel, kernels, context, input, output, metadata, num_blocks)
+}
+
+/// Execute a Q4_0 dequantize operation
+///
+/// # Arguments
+/// * `kernel` - The kernel to execute (e.g., dequantize_q4_0::F32)
+/// * `kernels` - Kernel cache for managing compiled kernels
+/// * `context` - CUDA context to execute on
+/// * `input` - Packed Q4_0 device slice
+/// * `output` - Floating-point output device slice (num_blocks * 32 elements)
+/// * `metadata` - `[num_blocks, byte_offset]`
+pub fn call_ops_dequantize_q4_0<T>(
+    kernel: crate::kernels::macros::Kernel,
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    input: &CudaSlice<u8>,
+    output: &mut CudaSlice<T>,
+    metadata: &[usize],
+) -> Result<()>
+where
+    T: cudarc::driver::DeviceRepr,
+{
+    let num_els = metadata[0] * 32;
+    call_ops_block(kernel, kernels, context, input, output, metadata, num_els)
+}
+
+fn call_ops_block<I, O>(
+    kernel: crate::kernels::macros::Kernel,
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    input: &CudaSlice<I>,
+    output: &mut CudaSlice<O>,
+    metadata: &[usize],
+    num_threads: usize,
+) -> Result<()>
+where
+    I: cudarc::driver::DeviceRepr,
+    O: cudarc::driver::DeviceRepr,
+{
+    let func = kernels.load_function(context, Source::OpsQuant, kernel.0)?;
+
+    let block_size = 256u32;
+    let grid_size = (num_threads as u32).div_ceil(block_size).max(1);
+
+    let cfg = LaunchConfig {
+        grid_dim: (grid_size, 1, 1),
+        block_dim: (block_size, 1, 1),
+        shared_mem_bytes: 0,
+    };
+
+    let stream = crate::stream::current_stream(context);
+    let metadata_dev = stream
+        .memcpy_stod(metadata)
+        .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
+
+    unsafe {
+        func.launch(&stream, cfg, |args| {
+            args.arg(input).arg(output).arg(&metadata_dev);
+        })
+        .map_err(|e| CudaKernelError::LaunchError(format!("Failed to launch kernel: {:?}", e)))?;
+    }
+
+    Ok(())
+}
+
+/// Execute a fused Q4_0 dequant-matmul
+///
+/// Computes `out[M, N] = lhs[M, K] @ dequant(weight)^T` where `weight` is a packed `[N, K]`
+/// Q4_0 matrix. The weight is decoded on the fly and never materialized in floating point.
+///
+/// # Arguments
+/// * `kernel` - The kernel to execute (e.g., matmul_q4_0::F32)
+/// * `kernels` - Kernel cache for managing compiled kernels
+/// * `context` - CUDA context to execute on
+/// * `lhs` - Contiguous floating-point lhs device slice
+/// * `weight` - Packed Q4_0 weight device slice
+/// * `output` - Output device slice of the lhs type (M * N elements)
+/// * `metadata` - `[M, K, N, lhs_offset, weight_byte_offset]`
+pub fn call_ops_matmul_q4_0<T>(
+    kernel: crate::kernels::macros::Kernel,
+    kernels: &Kernels,
+    context: &Arc<CudaContext>,
+    lhs: &CudaSlice<T>,
+    weight: &CudaSlice<u8>,
+    output: &mut CudaSlice<T>,
+    metadata: &[usize],
+) -> Result<()>
+where
+    T: cudarc::driver::DeviceRepr,
+{
+    let func = kernels.load_function(context, Source::OpsQuant, kernel.0)?;
+
+    let num_els = metadata[0] * metadata[2];
+    let block_size = 256u32;
+    let grid_size = (num_els as u32).div_ceil(block_size).max(1);
+
+    let cfg = LaunchConfig {
+        grid_dim: (grid_size, 1, 1),
+        block_dim: (block_size, 1, 1),
+        shared_mem_bytes: 0,
+    };
+
+    let stream = crate::stream::current_stream(context);
+    let metadata_dev = stream
+        .memcpy_stod(metadata)
+        .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
+
+    unsafe {
+        func.launch(&stream, cfg, |args| {
+            args.arg(lhs).arg(weight).arg(output).arg(&metadata_dev);
+        })
+        .map_err(|e| CudaKernelError::LaunchError(format!("Failed to launch kernel: {:?}", e)))?;
+    }
+
+    Ok(())
+}
@@ -6,7 +6,7 @@ use crate::{
     source::Source,
 };
 
-ops!(sum, max, min, prod, mean, norm, logsum, logsumexp, argmax, argmin, all, any);
+ops!(sum, max, min, prod, mean, var, std, norm, logsum, logsumexp, argmax, argmin, all, any);
 
 /// Execute a reduce operation on a tensor
 ///
@@ -61,7 +61,7 @@ where
         shared_mem_bytes: 0,
     };
 
-    let stream = context.default_stream();
+    let stream = crate::stream::current_stream(context);
     let metadata_dev = stream
         .memcpy_stod(metadata)
         .map_err(|e| CudaKernelError::MemoryError(format!("Failed to copy metadata: {:?}", e)))?;
@@ -1,8 +1,12 @@
 pub mod cuda;
+pub mod disk_cache;
 pub mod error;
 pub mod kernel;
 pub mod kernels;
+pub mod peer;
+pub mod precision;
 pub mod source;
+pub mod stream;
 pub use cudarc;
 
 pub use cuda::*;
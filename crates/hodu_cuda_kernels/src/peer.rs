@@ -0,0 +1,98 @@
+//! Peer-to-peer access and copies between CUDA devices
+
+use crate::{
+    cuda::*,
+    error::{CudaKernelError, Result},
+};
+use cudarc::driver::{result, sys, DevicePtrMut};
+
+fn driver_err(what: &str, e: impl std::fmt::Debug) -> CudaKernelError {
+    CudaKernelError::MemoryError(format!("{}: {:?}", what, e))
+}
+
+/// Whether `context`'s device can directly access memory on `peer`'s device
+pub fn can_access_peer(context: &Arc<CudaContext>, peer: &Arc<CudaContext>) -> Result<bool> {
+    let mut can_access = 0;
+    unsafe { sys::cuDeviceCanAccessPeer(&mut can_access, context.cu_device(), peer.cu_device()) }
+        .result()
+        .map_err(|e| driver_err("cuDeviceCanAccessPeer failed", e))?;
+    Ok(can_access != 0)
+}
+
+/// Let kernels and copies on `context` access memory allocated on `peer`
+///
+/// Returns `false` when the devices have no peer path; copies between them still work but
+/// are staged through host memory by the driver.
+pub fn enable_peer_access(context: &Arc<CudaContext>, peer: &Arc<CudaContext>) -> Result<bool> {
+    if !can_access_peer(context, peer)? {
+        return Ok(false);
+    }
+
+    context
+        .bind_to_thread()
+        .map_err(|e| driver_err("Failed to bind context", e))?;
+    match unsafe { sys::cuCtxEnablePeerAccess(peer.cu_ctx(), 0) } {
+        sys::CUresult::CUDA_SUCCESS | sys::CUresult::CUDA_ERROR_PEER_ACCESS_ALREADY_ENABLED => Ok(true),
+        e => Err(driver_err("cuCtxEnablePeerAccess failed", e)),
+    }
+}
+
+/// Copy `src` into `dst` where the two slices may live on different devices
+///
+/// The copy is enqueued on `src`'s stream and ordered against pending work on both slices,
+/// so neither side has to synchronize with the host.
+pub fn copy_peer<T: DeviceRepr>(src: &CudaSlice<T>, dst: &mut CudaSlice<T>) -> Result<()> {
+    if src.len() != dst.len() {
+        return Err(CudaKernelError::InvalidInput(format!(
+            "peer copy length mismatch: {} vs {}",
+            src.len(),
+            dst.len()
+        )));
+    }
+
+    let src_stream = src.stream().clone();
+    let dst_stream = dst.stream().clone();
+    let wait = |stream: &Arc<CudaStream>, event: &CudaEvent| {
+        // cudarc only waits on events from the same context; the driver allows any
+        unsafe {
+            result::stream::wait_event(
+                stream.cu_stream(),
+                event.cu_event(),
+                sys::CUevent_wait_flags::CU_EVENT_WAIT_DEFAULT,
+            )
+        }
+        .map_err(|e| driver_err("cuStreamWaitEvent failed", e))
+    };
+
+    // Earlier work on the destination buffer must finish before it is overwritten
+    let dst_ready = dst_stream
+        .record_event(None)
+        .map_err(|e| driver_err("Failed to record event", e))?;
+    wait(&src_stream, &dst_ready)?;
+
+    let (src_ptr, _src_record) = src.device_ptr(&src_stream);
+    let (dst_ptr, _dst_record) = dst.device_ptr_mut(&dst_stream);
+
+    src_stream
+        .context()
+        .bind_to_thread()
+        .map_err(|e| driver_err("Failed to bind context", e))?;
+    unsafe {
+        sys::cuMemcpyPeerAsync(
+            dst_ptr,
+            dst_stream.context().cu_ctx(),
+            src_ptr,
+            src_stream.context().cu_ctx(),
+            src.num_bytes(),
+            src_stream.cu_stream(),
+        )
+    }
+    .result()
+    .map_err(|e| driver_err("cuMemcpyPeerAsync failed", e))?;
+
+    // Later work on the destination stream sees the copied data
+    let copied = src_stream
+        .record_event(None)
+        .map_err(|e| driver_err("Failed to record event", e))?;
+    wait(&dst_stream, &copied)
+}
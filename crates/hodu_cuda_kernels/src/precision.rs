@@ -0,0 +1,55 @@
+//! Precision policy honored by matmul and convolution
+//!
+//! Library-backed paths read [`current_precision`], which is [`Precision::DEFAULT`] unless a
+//! [`PrecisionGuard`] is active on the calling thread.
+
+use std::cell::Cell;
+
+/// Accuracy/speed trade-offs matmul and convolution may take
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Precision {
+    /// Let f32 matmul and convolution run on TF32 tensor cores
+    pub allow_tf32: bool,
+    /// Accumulate f16/bf16 products in f32; when `false`, 16-bit accumulation is allowed
+    pub f16_accumulate_f32: bool,
+}
+
+impl Precision {
+    pub const DEFAULT: Self = Self {
+        allow_tf32: false,
+        f16_accumulate_f32: true,
+    };
+
+    /// Make this policy current on this thread until the returned guard is dropped
+    pub fn enter(self) -> PrecisionGuard {
+        PrecisionGuard {
+            previous: CURRENT_PRECISION.with(|current| current.replace(self)),
+        }
+    }
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+thread_local! {
+    static CURRENT_PRECISION: Cell<Precision> = const { Cell::new(Precision::DEFAULT) };
+}
+
+/// Policy kernels launched from this thread use
+pub fn current_precision() -> Precision {
+    CURRENT_PRECISION.with(|current| current.get())
+}
+
+/// Restores the previously current policy when dropped
+pub struct PrecisionGuard {
+    previous: Precision,
+}
+
+impl Drop for PrecisionGuard {
+    fn drop(&mut self) {
+        CURRENT_PRECISION.with(|current| current.set(self.previous));
+    }
+}
@@ -16,6 +16,7 @@ pub enum Source {
     OpsMatrix,
     OpsMemory,
     OpsPadding,
+    OpsQuant,
     OpsReduce,
     OpsResize,
     OpsScan,
@@ -0,0 +1,131 @@
+//! Per-device stream pools and the stream kernels are launched on
+//!
+//! Every `call_ops_*` function enqueues its work on [`current_stream`], which is the
+//! context's default stream unless a [`StreamGuard`] is active on the calling thread.
+//! Dependencies between streams are tracked per buffer: cudarc records a CUDA event
+//! whenever a `CudaSlice` is read or written, and any other stream touching the slice
+//! waits on that event first.
+
+use crate::{
+    cuda::*,
+    error::{CudaKernelError, Result},
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+thread_local! {
+    // Context ordinal -> stream set by the innermost active `StreamGuard`
+    static CURRENT_STREAMS: RefCell<HashMap<usize, Arc<CudaStream>>> = RefCell::new(HashMap::new());
+}
+
+/// Stream that work for `context` is enqueued on from this thread
+pub fn current_stream(context: &Arc<CudaContext>) -> Arc<CudaStream> {
+    CURRENT_STREAMS
+        .with(|streams| streams.borrow().get(&context.ordinal()).cloned())
+        .unwrap_or_else(|| context.default_stream())
+}
+
+/// Makes a stream current for its context on this thread until dropped
+///
+/// Guards nest; dropping one restores the stream that was current before it.
+pub struct StreamGuard {
+    ordinal: usize,
+    previous: Option<Arc<CudaStream>>,
+}
+
+impl StreamGuard {
+    pub fn new(stream: Arc<CudaStream>) -> Self {
+        let ordinal = stream.context().ordinal();
+        let previous = CURRENT_STREAMS.with(|streams| streams.borrow_mut().insert(ordinal, stream));
+        Self { ordinal, previous }
+    }
+}
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        CURRENT_STREAMS.with(|streams| {
+            let mut streams = streams.borrow_mut();
+            match self.previous.take() {
+                Some(previous) => streams.insert(self.ordinal, previous),
+                None => streams.remove(&self.ordinal),
+            };
+        });
+    }
+}
+
+/// Fixed set of non-blocking streams for one device
+///
+/// Compute work is spread over the pool round-robin, while host/device copies go through a
+/// dedicated transfer stream so they can overlap with kernels.
+#[derive(Debug)]
+pub struct StreamPool {
+    context: Arc<CudaContext>,
+    compute: Vec<Arc<CudaStream>>,
+    transfer: Arc<CudaStream>,
+    next: AtomicUsize,
+}
+
+impl StreamPool {
+    /// Create a pool with `num_streams` compute streams plus one transfer stream
+    pub fn new(context: &Arc<CudaContext>, num_streams: usize) -> Result<Self> {
+        let new_stream = || {
+            context
+                .new_stream()
+                .map_err(|e| CudaKernelError::LaunchError(format!("Failed to create stream: {:?}", e)))
+        };
+
+        let compute = (0..num_streams.max(1))
+            .map(|_| new_stream())
+            .collect::<Result<Vec<_>>>()?;
+        let transfer = new_stream()?;
+
+        Ok(Self {
+            context: context.clone(),
+            compute,
+            transfer,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn context(&self) -> &Arc<CudaContext> {
+        &self.context
+    }
+
+    /// Number of compute streams
+    pub fn len(&self) -> usize {
+        self.compute.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.compute.is_empty()
+    }
+
+    /// Next compute stream, round-robin
+    pub fn next_stream(&self) -> Arc<CudaStream> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.compute.len();
+        self.compute[index].clone()
+    }
+
+    /// Stream used for host/device copies
+    pub fn transfer_stream(&self) -> &Arc<CudaStream> {
+        &self.transfer
+    }
+
+    /// Make the next compute stream current on this thread until the guard is dropped
+    pub fn enter(&self) -> StreamGuard {
+        StreamGuard::new(self.next_stream())
+    }
+
+    /// Block until all work queued on the pool's streams has finished
+    pub fn synchronize(&self) -> Result<()> {
+        for stream in self.compute.iter().chain(std::iter::once(&self.transfer)) {
+            stream
+                .synchronize()
+                .map_err(|e| CudaKernelError::LaunchError(format!("Failed to synchronize stream: {:?}", e)))?;
+        }
+        Ok(())
+    }
+}
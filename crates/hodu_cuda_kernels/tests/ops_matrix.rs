@@ -260,3 +260,58 @@ fn matmul_f32_batch() {
     //        = [[16, 17], [22, 23]]
     assert_eq!(approx(results, 4), vec![4.0, 5.0, 10.0, 11.0, 16.0, 17.0, 22.0, 23.0]);
 }
+
+#[cfg(feature = "cublas")]
+#[test]
+fn matmul_f32_cublas_transposed_rhs() {
+    let kernels = kernels();
+
+    let device = device();
+    let stream = device.default_stream();
+
+    // Large enough to take the cuBLAS path
+    let m = 64;
+    let k = 48;
+    let n = 80;
+
+    let lhs: Vec<f32> = (0..m * k).map(|i| (i % 7) as f32 - 3.0).collect();
+    // rhs is stored as [n, k] and viewed as [k, n] through strides [1, k]
+    let rhs_t: Vec<f32> = (0..n * k).map(|i| (i % 5) as f32 - 2.0).collect();
+
+    let lhs_dev = stream.memcpy_stod(&lhs).unwrap();
+    let rhs_dev = stream.memcpy_stod(&rhs_t).unwrap();
+
+    let num_els = m * n;
+    let mut output: cudarc::driver::CudaSlice<f32> = unsafe { stream.alloc(num_els).unwrap() };
+
+    let metadata = vec![
+        num_els, 2, 2, 0, m, k, // lhs shape
+        k, n, // rhs shape
+        k, 1, // lhs strides
+        1, k, // rhs strides (transposed)
+        0, 0, // offsets
+        m, k, n, // M, K, N
+    ];
+
+    call_ops_matmul_cublas_f32(
+        matmul::F32,
+        &kernels,
+        &device,
+        &lhs_dev,
+        &rhs_dev,
+        &mut output,
+        &metadata,
+    )
+    .unwrap();
+
+    let mut results = vec![0.0f32; num_els];
+    stream.memcpy_dtoh(&output, &mut results).unwrap();
+
+    let mut expected = vec![0.0f32; num_els];
+    for i in 0..m {
+        for j in 0..n {
+            expected[i * n + j] = (0..k).map(|p| lhs[i * k + p] * rhs_t[j * k + p]).sum();
+        }
+    }
+    assert_eq!(approx(results, 2), approx(expected, 2));
+}
@@ -163,3 +163,37 @@ fn contiguous_3d_f32() {
     stream.memcpy_dtoh(&output, &mut results).unwrap();
     assert_eq!(results, input);
 }
+
+#[test]
+fn contiguous_permuted_tiled_f32() {
+    let kernels = kernels();
+
+    let device = device();
+    let stream = device.default_stream();
+
+    // [2, 40, 70] permuted to [70, 2, 40]: planes larger than one tile and not a multiple of it
+    let shape = [70, 2, 40];
+    let strides = [1, 2800, 70];
+    let input: Vec<f32> = (0..2 * 40 * 70).map(|i| i as f32).collect();
+    let input_dev = stream.memcpy_stod(&input).unwrap();
+    let mut output: cudarc::driver::CudaSlice<f32> = unsafe { stream.alloc(input.len()).unwrap() };
+
+    let mut metadata = vec![input.len(), shape.len()];
+    metadata.extend(&shape);
+    metadata.extend(&strides);
+    metadata.push(0);
+
+    call_ops_contiguous(contiguous::F32, &kernels, &device, &input_dev, &mut output, &metadata).unwrap();
+
+    let mut results = vec![0.0f32; input.len()];
+    stream.memcpy_dtoh(&output, &mut results).unwrap();
+    let mut expected = Vec::with_capacity(input.len());
+    for i in 0..70 {
+        for b in 0..2 {
+            for j in 0..40 {
+                expected.push(input[b * 2800 + j * 70 + i]);
+            }
+        }
+    }
+    assert_eq!(results, expected);
+}
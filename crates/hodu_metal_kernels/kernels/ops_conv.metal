@@ -31,7 +31,7 @@ using namespace metal;
 // - metadata[10]: input_offset
 // - metadata[11]: weight_offset
 
-#define CONV1D_OP(TYPENAME, FN_NAME)                                                               \
+#define CONV1D_OP(TYPENAME, ACC_TYPENAME, FN_NAME)                                                 \
     kernel void hodu_metal_##FN_NAME(                                                              \
         const device TYPENAME *input [[buffer(0)]], const device TYPENAME *weight [[buffer(1)]],   \
         device TYPENAME *output [[buffer(2)]], constant size_t *metadata [[buffer(3)]],            \
@@ -56,7 +56,7 @@ using namespace metal;
             const size_t oc = (idx / out_width) % out_channels;                                    \
             const size_t b = idx / (out_width * out_channels);                                     \
                                                                                                    \
-            TYPENAME sum = 0;                                                                      \
+            ACC_TYPENAME sum = 0;                                                                  \
             for (size_t ic = 0; ic < in_channels; ic++) {                                          \
                 for (size_t kw = 0; kw < kernel_width; kw++) {                                     \
                     const int iw = (int)(ow * stride) - (int)padding + (int)(kw * dilation);       \
@@ -66,17 +66,20 @@ using namespace metal;
                         const size_t weight_idx = weight_offset +                                  \
                                                   oc * in_channels * kernel_width +                \
                                                   ic * kernel_width + kw;                          \
-                        sum += input[input_idx] * weight[weight_idx];                              \
+                        sum += ACC_TYPENAME(input[input_idx]) * ACC_TYPENAME(weight[weight_idx]);  \
                     }                                                                              \
                 }                                                                                  \
             }                                                                                      \
-            output[idx] = sum;                                                                     \
+            output[idx] = TYPENAME(sum);                                                           \
         }                                                                                          \
     }
 
-CONV1D_OP(bfloat, conv1d_bf16)
-CONV1D_OP(half, conv1d_f16)
-CONV1D_OP(float, conv1d_f32)
+// Half precision types accumulate in float; `_acc16` variants accumulate in the input type
+CONV1D_OP(bfloat, float, conv1d_bf16)
+CONV1D_OP(bfloat, bfloat, conv1d_bf16_acc16)
+CONV1D_OP(half, float, conv1d_f16)
+CONV1D_OP(half, half, conv1d_f16_acc16)
+CONV1D_OP(float, float, conv1d_f32)
 
 // ============================================================================
 // CONV2D OPERATIONS
@@ -107,7 +110,7 @@ CONV1D_OP(float, conv1d_f32)
 // - metadata[16]: input_offset
 // - metadata[17]: weight_offset
 
-#define CONV2D_OP(TYPENAME, FN_NAME)                                                               \
+#define CONV2D_OP(TYPENAME, ACC_TYPENAME, FN_NAME)                                                 \
     kernel void hodu_metal_##FN_NAME(                                                              \
         const device TYPENAME *input [[buffer(0)]], const device TYPENAME *weight [[buffer(1)]],   \
         device TYPENAME *output [[buffer(2)]], constant size_t *metadata [[buffer(3)]],            \
@@ -139,7 +142,7 @@ CONV1D_OP(float, conv1d_f32)
             const size_t oc = (idx / (out_width * out_height)) % out_channels;                     \
             const size_t b = idx / (out_width * out_height * out_channels);                        \
                                                                                                    \
-            TYPENAME sum = 0;                                                                      \
+            ACC_TYPENAME sum = 0;                                                                  \
             for (size_t ic = 0; ic < in_channels; ic++) {                                          \
                 for (size_t kh = 0; kh < kernel_height; kh++) {                                    \
                     for (size_t kw = 0; kw < kernel_width; kw++) {                                 \
@@ -154,18 +157,21 @@ CONV1D_OP(float, conv1d_f32)
                             const size_t weight_idx =                                              \
                                 weight_offset + oc * in_channels * kernel_height * kernel_width +  \
                                 ic * kernel_height * kernel_width + kh * kernel_width + kw;        \
-                            sum += input[input_idx] * weight[weight_idx];                          \
+                            sum += ACC_TYPENAME(input[input_idx]) *                                \
+                                   ACC_TYPENAME(weight[weight_idx]);                               \
                         }                                                                          \
                     }                                                                              \
                 }                                                                                  \
             }                                                                                      \
-            output[idx] = sum;                                                                     \
+            output[idx] = TYPENAME(sum);                                                           \
         }                                                                                          \
     }
 
-CONV2D_OP(bfloat, conv2d_bf16)
-CONV2D_OP(half, conv2d_f16)
-CONV2D_OP(float, conv2d_f32)
+CONV2D_OP(bfloat, float, conv2d_bf16)
+CONV2D_OP(bfloat, bfloat, conv2d_bf16_acc16)
+CONV2D_OP(half, float, conv2d_f16)
+CONV2D_OP(half, half, conv2d_f16_acc16)
+CONV2D_OP(float, float, conv2d_f32)
 
 // ============================================================================
 // CONV3D OPERATIONS
@@ -202,7 +208,7 @@ CONV2D_OP(float, conv2d_f32)
 // - metadata[22]: input_offset
 // - metadata[23]: weight_offset
 
-#define CONV3D_OP(TYPENAME, FN_NAME)                                                               \
+#define CONV3D_OP(TYPENAME, ACC_TYPENAME, FN_NAME)                                                 \
     kernel void hodu_metal_##FN_NAME(                                                              \
         const device TYPENAME *input [[buffer(0)]], const device TYPENAME *weight [[buffer(1)]],   \
         device TYPENAME *output [[buffer(2)]], constant size_t *metadata [[buffer(3)]],            \
@@ -241,7 +247,7 @@ CONV2D_OP(float, conv2d_f32)
             const size_t oc = (idx / (out_width * out_height * out_depth)) % out_channels;         \
             const size_t b = idx / (out_width * out_height * out_depth * out_channels);            \
                                                                                                    \
-            TYPENAME sum = 0;                                                                      \
+            ACC_TYPENAME sum = 0;                                                                  \
             for (size_t ic = 0; ic < in_channels; ic++) {                                          \
                 for (size_t kd = 0; kd < kernel_depth; kd++) {                                     \
                     for (size_t kh = 0; kh < kernel_height; kh++) {                                \
@@ -265,19 +271,22 @@ CONV2D_OP(float, conv2d_f32)
                                         kernel_width +                                             \
                                     ic * kernel_depth * kernel_height * kernel_width +             \
                                     kd * kernel_height * kernel_width + kh * kernel_width + kw;    \
-                                sum += input[input_idx] * weight[weight_idx];                      \
+                                sum += ACC_TYPENAME(input[input_idx]) *                            \
+                                       ACC_TYPENAME(weight[weight_idx]);                           \
                             }                                                                      \
                         }                                                                          \
                     }                                                                              \
                 }                                                                                  \
             }                                                                                      \
-            output[idx] = sum;                                                                     \
+            output[idx] = TYPENAME(sum);                                                           \
         }                                                                                          \
     }
 
-CONV3D_OP(bfloat, conv3d_bf16)
-CONV3D_OP(half, conv3d_f16)
-CONV3D_OP(float, conv3d_f32)
+CONV3D_OP(bfloat, float, conv3d_bf16)
+CONV3D_OP(bfloat, bfloat, conv3d_bf16_acc16)
+CONV3D_OP(half, float, conv3d_f16)
+CONV3D_OP(half, half, conv3d_f16_acc16)
+CONV3D_OP(float, float, conv3d_f32)
 
 // ============================================================================
 // CONV_TRANSPOSE1D OPERATIONS
@@ -294,7 +303,7 @@ CONV3D_OP(float, conv3d_f32)
 // - stride, padding, dilation
 // - input_offset, weight_offset
 
-#define CONV_TRANSPOSE1D_OP(TYPENAME, FN_NAME)                                                     \
+#define CONV_TRANSPOSE1D_OP(TYPENAME, ACC_TYPENAME, FN_NAME)                                       \
     kernel void hodu_metal_##FN_NAME(                                                              \
         const device TYPENAME *input [[buffer(0)]], const device TYPENAME *weight [[buffer(1)]],   \
         device TYPENAME *output [[buffer(2)]], constant size_t *metadata [[buffer(3)]],            \
@@ -319,7 +328,7 @@ CONV3D_OP(float, conv3d_f32)
             const size_t oc = (idx / out_width) % out_channels;                                    \
             const size_t b = idx / (out_width * out_channels);                                     \
                                                                                                    \
-            TYPENAME sum = 0;                                                                      \
+            ACC_TYPENAME sum = 0;                                                                  \
             for (size_t ic = 0; ic < in_channels; ic++) {                                          \
                 for (size_t kw = 0; kw < kernel_width; kw++) {                                     \
                     const int tmp = (int)ow + (int)padding - (int)(kw * dilation);                 \
@@ -331,18 +340,21 @@ CONV3D_OP(float, conv3d_f32)
                             const size_t weight_idx = weight_offset +                              \
                                                       ic * out_channels * kernel_width +           \
                                                       oc * kernel_width + kw;                      \
-                            sum += input[input_idx] * weight[weight_idx];                          \
+                            sum += ACC_TYPENAME(input[input_idx]) *                                \
+                                   ACC_TYPENAME(weight[weight_idx]);                               \
                         }                                                                          \
                     }                                                                              \
                 }                                                                                  \
             }                                                                                      \
-            output[idx] = sum;                                                                     \
+            output[idx] = TYPENAME(sum);                                                           \
         }                                                                                          \
     }
 
-CONV_TRANSPOSE1D_OP(bfloat, conv_transpose1d_bf16)
-CONV_TRANSPOSE1D_OP(half, conv_transpose1d_f16)
-CONV_TRANSPOSE1D_OP(float, conv_transpose1d_f32)
+CONV_TRANSPOSE1D_OP(bfloat, float, conv_transpose1d_bf16)
+CONV_TRANSPOSE1D_OP(bfloat, bfloat, conv_transpose1d_bf16_acc16)
+CONV_TRANSPOSE1D_OP(half, float, conv_transpose1d_f16)
+CONV_TRANSPOSE1D_OP(half, half, conv_transpose1d_f16_acc16)
+CONV_TRANSPOSE1D_OP(float, float, conv_transpose1d_f32)
 
 // ============================================================================
 // CONV_TRANSPOSE2D OPERATIONS
@@ -361,7 +373,7 @@ CONV_TRANSPOSE1D_OP(float, conv_transpose1d_f32)
 // - dilation_h, dilation_w
 // - input_offset, weight_offset
 
-#define CONV_TRANSPOSE2D_OP(TYPENAME, FN_NAME)                                                     \
+#define CONV_TRANSPOSE2D_OP(TYPENAME, ACC_TYPENAME, FN_NAME)                                       \
     kernel void hodu_metal_##FN_NAME(                                                              \
         const device TYPENAME *input [[buffer(0)]], const device TYPENAME *weight [[buffer(1)]],   \
         device TYPENAME *output [[buffer(2)]], constant size_t *metadata [[buffer(3)]],            \
@@ -393,7 +405,7 @@ CONV_TRANSPOSE1D_OP(float, conv_transpose1d_f32)
             const size_t oc = (idx / (out_width * out_height)) % out_channels;                     \
             const size_t b = idx / (out_width * out_height * out_channels);                        \
                                                                                                    \
-            TYPENAME sum = 0;                                                                      \
+            ACC_TYPENAME sum = 0;                                                                  \
             for (size_t ic = 0; ic < in_channels; ic++) {                                          \
                 for (size_t kh = 0; kh < kernel_height; kh++) {                                    \
                     for (size_t kw = 0; kw < kernel_width; kw++) {                                 \
@@ -410,19 +422,22 @@ CONV_TRANSPOSE1D_OP(float, conv_transpose1d_f32)
                                     weight_offset +                                                \
                                     ic * out_channels * kernel_height * kernel_width +             \
                                     oc * kernel_height * kernel_width + kh * kernel_width + kw;    \
-                                sum += input[input_idx] * weight[weight_idx];                      \
+                                sum += ACC_TYPENAME(input[input_idx]) *                            \
+                                       ACC_TYPENAME(weight[weight_idx]);                           \
                             }                                                                      \
                         }                                                                          \
                     }                                                                              \
                 }                                                                                  \
             }                                                                                      \
-            output[idx] = sum;                                                                     \
+            output[idx] = TYPENAME(sum);                                                           \
         }                                                                                          \
     }
 
-CONV_TRANSPOSE2D_OP(bfloat, conv_transpose2d_bf16)
-CONV_TRANSPOSE2D_OP(half, conv_transpose2d_f16)
-CONV_TRANSPOSE2D_OP(float, conv_transpose2d_f32)
+CONV_TRANSPOSE2D_OP(bfloat, float, conv_transpose2d_bf16)
+CONV_TRANSPOSE2D_OP(bfloat, bfloat, conv_transpose2d_bf16_acc16)
+CONV_TRANSPOSE2D_OP(half, float, conv_transpose2d_f16)
+CONV_TRANSPOSE2D_OP(half, half, conv_transpose2d_f16_acc16)
+CONV_TRANSPOSE2D_OP(float, float, conv_transpose2d_f32)
 
 // ============================================================================
 // CONV_TRANSPOSE3D OPERATIONS
@@ -443,7 +458,7 @@ CONV_TRANSPOSE2D_OP(float, conv_transpose2d_f32)
 // - dilation_d, dilation_h, dilation_w
 // - input_offset, weight_offset
 
-#define CONV_TRANSPOSE3D_OP(TYPENAME, FN_NAME)                                                     \
+#define CONV_TRANSPOSE3D_OP(TYPENAME, ACC_TYPENAME, FN_NAME)                                       \
     kernel void hodu_metal_##FN_NAME(                                                              \
         const device TYPENAME *input [[buffer(0)]], const device TYPENAME *weight [[buffer(1)]],   \
         device TYPENAME *output [[buffer(2)]], constant size_t *metadata [[buffer(3)]],            \
@@ -482,7 +497,7 @@ CONV_TRANSPOSE2D_OP(float, conv_transpose2d_f32)
             const size_t oc = (idx / (out_width * out_height * out_depth)) % out_channels;         \
             const size_t b = idx / (out_width * out_height * out_depth * out_channels);            \
                                                                                                    \
-            TYPENAME sum = 0;                                                                      \
+            ACC_TYPENAME sum = 0;                                                                  \
             for (size_t ic = 0; ic < in_channels; ic++) {                                          \
                 for (size_t kd = 0; kd < kernel_depth; kd++) {                                     \
                     for (size_t kh = 0; kh < kernel_height; kh++) {                                \
@@ -509,20 +524,23 @@ CONV_TRANSPOSE2D_OP(float, conv_transpose2d_f32)
                                         oc * kernel_depth * kernel_height * kernel_width +         \
                                         kd * kernel_height * kernel_width + kh * kernel_width +    \
                                         kw;                                                        \
-                                    sum += input[input_idx] * weight[weight_idx];                  \
+                                    sum += ACC_TYPENAME(input[input_idx]) *                        \
+                                           ACC_TYPENAME(weight[weight_idx]);                       \
                                 }                                                                  \
                             }                                                                      \
                         }                                                                          \
                     }                                                                              \
                 }                                                                                  \
             }                                                                                      \
-            output[idx] = sum;                                                                     \
+            output[idx] = TYPENAME(sum);                                                           \
         }                                                                                          \
     }
 
-CONV_TRANSPOSE3D_OP(bfloat, conv_transpose3d_bf16)
-CONV_TRANSPOSE3D_OP(half, conv_transpose3d_f16)
-CONV_TRANSPOSE3D_OP(float, conv_transpose3d_f32)
+CONV_TRANSPOSE3D_OP(bfloat, float, conv_transpose3d_bf16)
+CONV_TRANSPOSE3D_OP(bfloat, bfloat, conv_transpose3d_bf16_acc16)
+CONV_TRANSPOSE3D_OP(half, float, conv_transpose3d_f16)
+CONV_TRANSPOSE3D_OP(half, half, conv_transpose3d_f16_acc16)
+CONV_TRANSPOSE3D_OP(float, float, conv_transpose3d_f32)
 
 // ============================================================================
 // CONV1D_GRAD_WEIGHT OPERATIONS
@@ -33,7 +33,7 @@ using namespace metal;
 // - metadata[...+3]: K (cols of lhs / rows of rhs)
 // - metadata[...+4]: N (cols of rhs matrix)
 
-#define MATMUL_OP(TYPENAME, FN_NAME)                                                               \
+#define MATMUL_OP(TYPENAME, ACC_TYPENAME, FN_NAME)                                                 \
     kernel void hodu_metal_##FN_NAME(                                                              \
         const device TYPENAME *lhs [[buffer(0)]], const device TYPENAME *rhs [[buffer(1)]],        \
         device TYPENAME *output [[buffer(2)]], constant size_t *metadata [[buffer(3)]],            \
@@ -103,7 +103,7 @@ using namespace metal;
             rhs_base_offset += rhs_batch_indices[d] * rhs_strides[d];                              \
         }                                                                                          \
                                                                                                    \
-        TYPENAME sum = 0;                                                                          \
+        ACC_TYPENAME sum = 0;                                                                      \
                                                                                                    \
         /* Loop over tiles */                                                                      \
         size_t num_tiles = (K + TILE_SIZE - 1) / TILE_SIZE;                                        \
@@ -135,8 +135,8 @@ using namespace metal;
                                                                                                    \
             /* Compute partial dot product for this tile */                                        \
             for (size_t k = 0; k < TILE_SIZE; k++) {                                               \
-                sum += lhs_tile[thread_position_in_threadgroup.y][k] *                             \
-                       rhs_tile[k][thread_position_in_threadgroup.x];                              \
+                sum += ACC_TYPENAME(lhs_tile[thread_position_in_threadgroup.y][k]) *               \
+                       ACC_TYPENAME(rhs_tile[k][thread_position_in_threadgroup.x]);                \
             }                                                                                      \
                                                                                                    \
             /* Synchronize before loading next tile */                                             \
@@ -146,22 +146,25 @@ using namespace metal;
         /* Write result */                                                                         \
         if (row < M && col < N) {                                                                  \
             size_t output_idx = batch_idx * (M * N) + row * N + col;                               \
-            output[output_idx] = sum;                                                              \
+            output[output_idx] = TYPENAME(sum);                                                    \
         }                                                                                          \
     }
 
 // Define matmul operations for all types
-MATMUL_OP(bfloat, matmul_bf16)
-MATMUL_OP(half, matmul_f16)
-MATMUL_OP(float, matmul_f32)
-MATMUL_OP(int8_t, matmul_i8)
-MATMUL_OP(int16_t, matmul_i16)
-MATMUL_OP(int32_t, matmul_i32)
-MATMUL_OP(int64_t, matmul_i64)
-MATMUL_OP(uint8_t, matmul_u8)
-MATMUL_OP(uint16_t, matmul_u16)
-MATMUL_OP(uint32_t, matmul_u32)
-MATMUL_OP(uint64_t, matmul_u64)
+// Half precision types accumulate in float; `_acc16` variants accumulate in the input type
+MATMUL_OP(bfloat, float, matmul_bf16)
+MATMUL_OP(bfloat, bfloat, matmul_bf16_acc16)
+MATMUL_OP(half, float, matmul_f16)
+MATMUL_OP(half, half, matmul_f16_acc16)
+MATMUL_OP(float, float, matmul_f32)
+MATMUL_OP(int8_t, int8_t, matmul_i8)
+MATMUL_OP(int16_t, int16_t, matmul_i16)
+MATMUL_OP(int32_t, int32_t, matmul_i32)
+MATMUL_OP(int64_t, int64_t, matmul_i64)
+MATMUL_OP(uint8_t, uint8_t, matmul_u8)
+MATMUL_OP(uint16_t, uint16_t, matmul_u16)
+MATMUL_OP(uint32_t, uint32_t, matmul_u32)
+MATMUL_OP(uint64_t, uint64_t, matmul_u64)
 
 // ============================================================================
 // TILED 2D DOT PRODUCT (Optimized with threadgroup memory)
@@ -195,7 +198,7 @@ MATMUL_OP(uint64_t, matmul_u64)
 #define DOT_TILE_SIZE 32
 #define THREADS_PER_TILE (DOT_TILE_SIZE / BLOCK_M)
 
-#define DOT_OP(TYPENAME, FN_NAME)                                                                  \
+#define DOT_OP(TYPENAME, ACC_TYPENAME, FN_NAME)                                                    \
     kernel void hodu_metal_##FN_NAME(                                                              \
         const device TYPENAME *lhs [[buffer(0)]], const device TYPENAME *rhs [[buffer(1)]],        \
         device TYPENAME *output [[buffer(2)]], constant size_t &num_els [[buffer(3)]],             \
@@ -225,7 +228,7 @@ MATMUL_OP(uint64_t, matmul_u64)
                           thread_position_in_threadgroup.x * BLOCK_N;                              \
                                                                                                    \
         /* Register blocking: accumulate 4x4 block */                                              \
-        TYPENAME sums[BLOCK_M][BLOCK_N];                                                           \
+        ACC_TYPENAME sums[BLOCK_M][BLOCK_N];                                                       \
         for (size_t i = 0; i < BLOCK_M; i++) {                                                     \
             for (size_t j = 0; j < BLOCK_N; j++) {                                                 \
                 sums[i][j] = 0;                                                                    \
@@ -288,7 +291,7 @@ MATMUL_OP(uint64_t, matmul_u64)
                 /* Outer product */                                                                \
                 _Pragma("unroll") for (size_t i = 0; i < BLOCK_M; i++) {                           \
                     _Pragma("unroll") for (size_t j = 0; j < BLOCK_N; j++) {                       \
-                        sums[i][j] += lhs_vals[i] * rhs_vals[j];                                   \
+                        sums[i][j] += ACC_TYPENAME(lhs_vals[i]) * ACC_TYPENAME(rhs_vals[j]);       \
                     }                                                                              \
                 }                                                                                  \
             }                                                                                      \
@@ -303,21 +306,24 @@ MATMUL_OP(uint64_t, matmul_u64)
                 size_t global_row = base_row + i;                                                  \
                 size_t global_col = base_col + j;                                                  \
                 if (global_row < M && global_col < N) {                                            \
-                    output[global_row * N + global_col] = sums[i][j];                              \
+                    output[global_row * N + global_col] = TYPENAME(sums[i][j]);                    \
                 }                                                                                  \
             }                                                                                      \
         }                                                                                          \
     }
 
 // Define tiled dot operations for all types
-DOT_OP(bfloat, dot_bf16)
-DOT_OP(half, dot_f16)
-DOT_OP(float, dot_f32)
-DOT_OP(int8_t, dot_i8)
-DOT_OP(int16_t, dot_i16)
-DOT_OP(int32_t, dot_i32)
-DOT_OP(int64_t, dot_i64)
-DOT_OP(uint8_t, dot_u8)
-DOT_OP(uint16_t, dot_u16)
-DOT_OP(uint32_t, dot_u32)
-DOT_OP(uint64_t, dot_u64)
+// Half precision types accumulate in float; `_acc16` variants accumulate in the input type
+DOT_OP(bfloat, float, dot_bf16)
+DOT_OP(bfloat, bfloat, dot_bf16_acc16)
+DOT_OP(half, float, dot_f16)
+DOT_OP(half, half, dot_f16_acc16)
+DOT_OP(float, float, dot_f32)
+DOT_OP(int8_t, int8_t, dot_i8)
+DOT_OP(int16_t, int16_t, dot_i16)
+DOT_OP(int32_t, int32_t, dot_i32)
+DOT_OP(int64_t, int64_t, dot_i64)
+DOT_OP(uint8_t, uint8_t, dot_u8)
+DOT_OP(uint16_t, uint16_t, dot_u16)
+DOT_OP(uint32_t, uint32_t, dot_u32)
+DOT_OP(uint64_t, uint64_t, dot_u64)
@@ -31,6 +31,69 @@ using namespace metal;
         }                                                                                          \
     }
 
+// Tiled transpose for permuted views whose unit-stride dim is not the innermost one
+//
+// Each threadgroup stages a TRANSPOSE_TILE x TRANSPOSE_TILE tile of the (axis, innermost dim)
+// plane in threadgroup memory: it is read with consecutive threads walking the input's unit
+// stride and written with consecutive threads walking the output's. The padding column avoids
+// bank conflicts on the transposed read.
+//
+// Metadata is the contiguous layout followed by `axis`, the dim with unit input stride.
+// Grid: (col tiles, row tiles, planes) threadgroups of (TRANSPOSE_TILE, TRANSPOSE_ROWS, 1).
+#define TRANSPOSE_TILE 32
+#define TRANSPOSE_ROWS 8
+
+#define TRANSPOSE_TILED_OP(TYPENAME, FN_NAME)                                                      \
+    kernel void hodu_metal_##FN_NAME(                                                              \
+        const device TYPENAME *input [[buffer(0)]], device TYPENAME *output [[buffer(1)]],         \
+        constant size_t *metadata [[buffer(2)]], uint3 tid [[thread_position_in_threadgroup]],     \
+        uint3 tgid [[threadgroup_position_in_grid]]) {                                             \
+        threadgroup TYPENAME tile[TRANSPOSE_TILE][TRANSPOSE_TILE + 1];                             \
+                                                                                                   \
+        const size_t num_dims = metadata[1];                                                       \
+        const constant size_t *dims = metadata + 2;                                                \
+        const constant size_t *strides = metadata + 2 + num_dims;                                  \
+        const size_t offset = metadata[2 + 2 * num_dims];                                          \
+        const size_t axis = metadata[3 + 2 * num_dims];                                            \
+        const size_t last = num_dims - 1;                                                          \
+        const size_t rows = dims[axis];                                                            \
+        const size_t cols = dims[last];                                                            \
+        const size_t col_stride = strides[last];                                                   \
+                                                                                                   \
+        size_t in_base = offset, out_base = 0, out_stride = 1, row_stride = 1;                     \
+        size_t rem = tgid.z;                                                                       \
+        for (size_t d = num_dims; d-- > 0;) {                                                      \
+            if (d == axis) {                                                                       \
+                row_stride = out_stride;                                                           \
+            } else if (d != last) {                                                                \
+                size_t idx = rem % dims[d];                                                        \
+                rem /= dims[d];                                                                    \
+                in_base += idx * strides[d];                                                       \
+                out_base += idx * out_stride;                                                      \
+            }                                                                                      \
+            out_stride *= dims[d];                                                                 \
+        }                                                                                          \
+                                                                                                   \
+        const size_t r0 = tgid.y * TRANSPOSE_TILE;                                                 \
+        const size_t c0 = tgid.x * TRANSPOSE_TILE;                                                 \
+        for (uint j = tid.y; j < TRANSPOSE_TILE; j += TRANSPOSE_ROWS) {                            \
+            size_t r = r0 + tid.x;                                                                 \
+            size_t c = c0 + j;                                                                     \
+            if (r < rows && c < cols) {                                                            \
+                tile[j][tid.x] = input[in_base + r + c * col_stride];                              \
+            }                                                                                      \
+        }                                                                                          \
+        threadgroup_barrier(mem_flags::mem_threadgroup);                                           \
+                                                                                                   \
+        for (uint j = tid.y; j < TRANSPOSE_TILE; j += TRANSPOSE_ROWS) {                            \
+            size_t r = r0 + j;                                                                     \
+            size_t c = c0 + tid.x;                                                                 \
+            if (r < rows && c < cols) {                                                            \
+                output[out_base + r * row_stride + c] = tile[tid.x][j];                            \
+            }                                                                                      \
+        }                                                                                          \
+    }
+
 // Copy operation - simple element-wise copy (for already contiguous data)
 #define COPY_OP(TYPENAME, FN_NAME)                                                                 \
     kernel void hodu_metal_##FN_NAME(                                                              \
@@ -58,6 +121,23 @@ CONTIGUOUS_OP(int16_t, contiguous_i16);
 CONTIGUOUS_OP(int32_t, contiguous_i32);
 CONTIGUOUS_OP(int64_t, contiguous_i64);
 
+// ============================================================================
+// Tiled transpose operations for all data types
+// ============================================================================
+
+TRANSPOSE_TILED_OP(bool, transpose_tiled_bool);
+TRANSPOSE_TILED_OP(bfloat, transpose_tiled_bf16);
+TRANSPOSE_TILED_OP(half, transpose_tiled_f16);
+TRANSPOSE_TILED_OP(float, transpose_tiled_f32);
+TRANSPOSE_TILED_OP(uint8_t, transpose_tiled_u8);
+TRANSPOSE_TILED_OP(uint16_t, transpose_tiled_u16);
+TRANSPOSE_TILED_OP(uint32_t, transpose_tiled_u32);
+TRANSPOSE_TILED_OP(uint64_t, transpose_tiled_u64);
+TRANSPOSE_TILED_OP(int8_t, transpose_tiled_i8);
+TRANSPOSE_TILED_OP(int16_t, transpose_tiled_i16);
+TRANSPOSE_TILED_OP(int32_t, transpose_tiled_i32);
+TRANSPOSE_TILED_OP(int64_t, transpose_tiled_i64);
+
 // ============================================================================
 // Simple copy operations for all data types
 // ============================================================================
@@ -0,0 +1,270 @@
+#include "./headers/utils.metal"
+#include <metal_stdlib>
+
+using namespace metal;
+
+// Int8 Quantization Operations
+// ============================
+// Affine int8 quantization:
+//   quantize:   q = clamp(round_half_even(x / scale) + zero_point, -128, 127)
+//   dequantize: x = (q - zero_point) * scale
+//
+// Metadata Layout (same as cast):
+// - metadata[0]: num_els (total number of elements)
+// - metadata[1]: num_dims (number of dimensions)
+// - metadata[2..2+num_dims]: shape
+// - metadata[2+num_dims..2+2*num_dims]: strides
+// - metadata[2+2*num_dims]: offset
+//
+// Buffer Layout:
+// - buffer(0): input tensor
+// - buffer(1): output tensor (contiguous)
+// - buffer(2): metadata (constant size_t*)
+// - buffer(3): scale (constant float&)
+// - buffer(4): zero_point (constant int&)
+
+#define QUANTIZE_OP(TYPENAME, FN_NAME)                                                             \
+    kernel void hodu_metal_##FN_NAME(                                                              \
+        const device TYPENAME *input [[buffer(0)]], device int8_t *output [[buffer(1)]],           \
+        constant size_t *metadata [[buffer(2)]], constant float &scale [[buffer(3)]],              \
+        constant int &zero_point [[buffer(4)]], uint id [[thread_position_in_grid]]) {             \
+        const size_t num_els = metadata[0];                                                        \
+        if (id >= num_els)                                                                         \
+            return;                                                                                \
+                                                                                                   \
+        const size_t num_dims = metadata[1];                                                       \
+        const constant size_t *dims = metadata + 2;                                                \
+        const constant size_t *strides = metadata + 2 + num_dims;                                  \
+        const size_t offset = metadata[2 + 2 * num_dims];                                          \
+                                                                                                   \
+        size_t idx = is_contiguous(num_dims, dims, strides)                                        \
+                         ? offset + id                                                             \
+                         : offset + get_strided_index(id, num_dims, dims, strides);                \
+        float q = rint(float(input[idx]) / scale) + float(zero_point);                             \
+        output[id] = int8_t(clamp(q, -128.0f, 127.0f));                                            \
+    }
+
+#define DEQUANTIZE_OP(TYPENAME, FN_NAME)                                                           \
+    kernel void hodu_metal_##FN_NAME(                                                              \
+        const device int8_t *input [[buffer(0)]], device TYPENAME *output [[buffer(1)]],           \
+        constant size_t *metadata [[buffer(2)]], constant float &scale [[buffer(3)]],              \
+        constant int &zero_point [[buffer(4)]], uint id [[thread_position_in_grid]]) {             \
+        const size_t num_els = metadata[0];                                                        \
+        if (id >= num_els)                                                                         \
+            return;                                                                                \
+                                                                                                   \
+        const size_t num_dims = metadata[1];                                                       \
+        const constant size_t *dims = metadata + 2;                                                \
+        const constant size_t *strides = metadata + 2 + num_dims;                                  \
+        const size_t offset = metadata[2 + 2 * num_dims];                                          \
+                                                                                                   \
+        size_t idx = is_contiguous(num_dims, dims, strides)                                        \
+                         ? offset + id                                                             \
+                         : offset + get_strided_index(id, num_dims, dims, strides);                \
+        output[id] = TYPENAME(float(int(input[idx]) - zero_point) * scale);                        \
+    }
+
+QUANTIZE_OP(bfloat, quantize_bf16)
+QUANTIZE_OP(half, quantize_f16)
+QUANTIZE_OP(float, quantize_f32)
+
+DEQUANTIZE_OP(bfloat, dequantize_bf16)
+DEQUANTIZE_OP(half, dequantize_f16)
+DEQUANTIZE_OP(float, dequantize_f32)
+
+// ============================================================================
+// INT8 MATRIX MULTIPLICATION
+// ============================================================================
+//
+// i8 x i8 -> i32 batched matmul. Same metadata layout and dispatch geometry as matmul (16x16
+// threadgroups, one z-slice per output batch). Products are widened to int before accumulation.
+
+#define QUANT_TILE_SIZE 16
+
+kernel void hodu_metal_matmul_int8_i8(const device int8_t *lhs [[buffer(0)]],
+                                      const device int8_t *rhs [[buffer(1)]],
+                                      device int *output [[buffer(2)]],
+                                      constant size_t *metadata [[buffer(3)]],
+                                      uint3 tid [[thread_position_in_threadgroup]],
+                                      uint3 gid [[threadgroup_position_in_grid]]) {
+    const size_t lhs_ndim = metadata[1];
+    const size_t rhs_ndim = metadata[2];
+    const size_t batch_ndim = metadata[3];
+
+    const constant size_t *lhs_shape = metadata + 4;
+    const constant size_t *rhs_shape = lhs_shape + lhs_ndim;
+    const constant size_t *batch_shape = rhs_shape + rhs_ndim;
+    const constant size_t *lhs_strides = batch_shape + batch_ndim;
+    const constant size_t *rhs_strides = lhs_strides + lhs_ndim;
+    const size_t lhs_offset = rhs_strides[rhs_ndim];
+    const size_t rhs_offset = rhs_strides[rhs_ndim + 1];
+    const size_t M = rhs_strides[rhs_ndim + 2];
+    const size_t K = rhs_strides[rhs_ndim + 3];
+    const size_t N = rhs_strides[rhs_ndim + 4];
+
+    const size_t lhs_batch_ndim = lhs_ndim - 2;
+    const size_t rhs_batch_ndim = rhs_ndim - 2;
+
+    threadgroup int lhs_tile[QUANT_TILE_SIZE][QUANT_TILE_SIZE];
+    threadgroup int rhs_tile[QUANT_TILE_SIZE][QUANT_TILE_SIZE];
+
+    size_t batch_idx = gid.z;
+    size_t row = gid.y * QUANT_TILE_SIZE + tid.y;
+    size_t col = gid.x * QUANT_TILE_SIZE + tid.x;
+
+    /* Resolve broadcast batch offsets */
+    size_t lhs_base = lhs_offset;
+    size_t rhs_base = rhs_offset;
+    size_t temp = batch_idx;
+    for (int d = (int)batch_ndim - 1; d >= 0; d--) {
+        size_t b = temp % batch_shape[d];
+        temp /= batch_shape[d];
+
+        int lhs_d = d - (int)(batch_ndim - lhs_batch_ndim);
+        if (lhs_d >= 0 && lhs_shape[lhs_d] != 1)
+            lhs_base += b * lhs_strides[lhs_d];
+
+        int rhs_d = d - (int)(batch_ndim - rhs_batch_ndim);
+        if (rhs_d >= 0 && rhs_shape[rhs_d] != 1)
+            rhs_base += b * rhs_strides[rhs_d];
+    }
+
+    const size_t lhs_rs = lhs_strides[lhs_ndim - 2];
+    const size_t lhs_cs = lhs_strides[lhs_ndim - 1];
+    const size_t rhs_rs = rhs_strides[rhs_ndim - 2];
+    const size_t rhs_cs = rhs_strides[rhs_ndim - 1];
+
+    int sum = 0;
+    for (size_t t = 0; t < K; t += QUANT_TILE_SIZE) {
+        size_t lk = t + tid.x;
+        size_t rk = t + tid.y;
+        lhs_tile[tid.y][tid.x] =
+            (row < M && lk < K) ? int(lhs[lhs_base + row * lhs_rs + lk * lhs_cs]) : 0;
+        rhs_tile[tid.y][tid.x] =
+            (rk < K && col < N) ? int(rhs[rhs_base + rk * rhs_rs + col * rhs_cs]) : 0;
+        threadgroup_barrier(mem_flags::mem_threadgroup);
+
+        for (size_t k = 0; k < QUANT_TILE_SIZE; k++) {
+            sum += lhs_tile[tid.y][k] * rhs_tile[k][tid.x];
+        }
+        threadgroup_barrier(mem_flags::mem_threadgroup);
+    }
+
+    if (row < M && col < N) {
+        output[batch_idx * M * N + row * N + col] = sum;
+    }
+}
+
+// ============================================================================
+// 4-BIT BLOCK QUANTIZATION (Q4_0)
+// ============================================================================
+//
+// Every block of 32 consecutive values along the last dimension is stored in 18 bytes:
+//   bytes [0, 2):  scale as little-endian f16, scale = max|x| / 7
+//   bytes [2, 18): 4-bit codes, byte j holds value 2j in the low nibble and 2j+1 in the high one
+// and decodes as x = (q - 8) * scale.
+//
+// quantize_q4_0: metadata as cast, one thread per block
+// dequantize_q4_0: metadata [num_blocks, byte_offset], one thread per element
+// matmul_q4_0: metadata [M, K, N, lhs_offset, weight_byte_offset], one thread per output element
+
+#define Q4_0_BLOCK_SIZE 32
+#define Q4_0_BLOCK_BYTES 18
+
+inline float q4_0_block_scale(const device uint8_t *block) {
+    return float(as_type<half>(ushort(block[0] | (ushort(block[1]) << 8))));
+}
+
+#define QUANTIZE_Q4_0_OP(TYPENAME, FN_NAME)                                                        \
+    kernel void hodu_metal_##FN_NAME(                                                              \
+        const device TYPENAME *input [[buffer(0)]], device uint8_t *output [[buffer(1)]],          \
+        constant size_t *metadata [[buffer(2)]], uint b [[thread_position_in_grid]]) {             \
+        const size_t num_els = metadata[0];                                                        \
+        if (b >= num_els / Q4_0_BLOCK_SIZE)                                                        \
+            return;                                                                                \
+                                                                                                   \
+        const size_t num_dims = metadata[1];                                                       \
+        const constant size_t *dims = metadata + 2;                                                \
+        const constant size_t *strides = metadata + 2 + num_dims;                                  \
+        const size_t offset = metadata[2 + 2 * num_dims];                                          \
+        const bool cont = is_contiguous(num_dims, dims, strides);                                  \
+                                                                                                   \
+        float values[Q4_0_BLOCK_SIZE];                                                             \
+        float amax = 0.0f;                                                                         \
+        for (uint i = 0; i < Q4_0_BLOCK_SIZE; i++) {                                               \
+            size_t flat = size_t(b) * Q4_0_BLOCK_SIZE + i;                                         \
+            size_t idx = cont ? flat : get_strided_index(flat, num_dims, dims, strides);           \
+            values[i] = float(input[offset + idx]);                                                \
+            amax = max(amax, fabs(values[i]));                                                     \
+        }                                                                                          \
+                                                                                                   \
+        device uint8_t *block = output + size_t(b) * Q4_0_BLOCK_BYTES;                             \
+        ushort d_bits = as_type<ushort>(half(amax / 7.0f));                                        \
+        block[0] = uint8_t(d_bits & 0xFF);                                                         \
+        block[1] = uint8_t(d_bits >> 8);                                                           \
+        const float d = float(as_type<half>(d_bits));                                              \
+        for (uint j = 0; j < Q4_0_BLOCK_SIZE / 2; j++) {                                           \
+            float lo = d > 0.0f ? rint(values[2 * j] / d) + 8.0f : 8.0f;                           \
+            float hi = d > 0.0f ? rint(values[2 * j + 1] / d) + 8.0f : 8.0f;                       \
+            uint8_t q_lo = uint8_t(clamp(lo, 0.0f, 15.0f));                                        \
+            uint8_t q_hi = uint8_t(clamp(hi, 0.0f, 15.0f));                                        \
+            block[2 + j] = uint8_t(q_lo | (q_hi << 4));                                            \
+        }                                                                                          \
+    }
+
+#define DEQUANTIZE_Q4_0_OP(TYPENAME, FN_NAME)                                                      \
+    kernel void hodu_metal_##FN_NAME(                                                              \
+        const device uint8_t *input [[buffer(0)]], device TYPENAME *output [[buffer(1)]],          \
+        constant size_t *metadata [[buffer(2)]], uint id [[thread_position_in_grid]]) {            \
+        if (id >= metadata[0] * Q4_0_BLOCK_SIZE)                                                   \
+            return;                                                                                \
+                                                                                                   \
+        const device uint8_t *block =                                                              \
+            input + metadata[1] + size_t(id / Q4_0_BLOCK_SIZE) * Q4_0_BLOCK_BYTES;                 \
+        const uint j = id % Q4_0_BLOCK_SIZE;                                                       \
+        uint8_t code = block[2 + j / 2];                                                           \
+        code = (j & 1) ? (code >> 4) : (code & 0x0F);                                              \
+        output[id] = TYPENAME(float(int(code) - 8) * q4_0_block_scale(block));                     \
+    }
+
+#define MATMUL_Q4_0_OP(TYPENAME, FN_NAME)                                                          \
+    kernel void hodu_metal_##FN_NAME(                                                              \
+        const device TYPENAME *lhs [[buffer(0)]], const device uint8_t *weight [[buffer(1)]],      \
+        device TYPENAME *output [[buffer(2)]], constant size_t *metadata [[buffer(3)]],            \
+        uint id [[thread_position_in_grid]]) {                                                     \
+        const size_t M = metadata[0];                                                              \
+        const size_t K = metadata[1];                                                              \
+        const size_t N = metadata[2];                                                              \
+        if (id >= M * N)                                                                           \
+            return;                                                                                \
+                                                                                                   \
+        const size_t num_blocks = K / Q4_0_BLOCK_SIZE;                                             \
+        const device TYPENAME *x_row = lhs + metadata[3] + (id / N) * K;                           \
+        const device uint8_t *w_row =                                                              \
+            weight + metadata[4] + (id % N) * num_blocks * Q4_0_BLOCK_BYTES;                       \
+        float acc = 0.0f;                                                                          \
+        for (size_t b = 0; b < num_blocks; b++) {                                                  \
+            const device uint8_t *block = w_row + b * Q4_0_BLOCK_BYTES;                            \
+            float partial = 0.0f;                                                                  \
+            for (uint j = 0; j < Q4_0_BLOCK_SIZE / 2; j++) {                                       \
+                uint8_t code = block[2 + j];                                                       \
+                const device TYPENAME *xs = x_row + b * Q4_0_BLOCK_SIZE + 2 * j;                   \
+                partial += float(xs[0]) * float(int(code & 0x0F) - 8);                             \
+                partial += float(xs[1]) * float(int(code >> 4) - 8);                               \
+            }                                                                                      \
+            acc += partial * q4_0_block_scale(block);                                              \
+        }                                                                                          \
+        output[id] = TYPENAME(acc);                                                                \
+    }
+
+QUANTIZE_Q4_0_OP(bfloat, quantize_q4_0_bf16)
+QUANTIZE_Q4_0_OP(half, quantize_q4_0_f16)
+QUANTIZE_Q4_0_OP(float, quantize_q4_0_f32)
+
+DEQUANTIZE_Q4_0_OP(bfloat, dequantize_q4_0_bf16)
+DEQUANTIZE_Q4_0_OP(half, dequantize_q4_0_f16)
+DEQUANTIZE_Q4_0_OP(float, dequantize_q4_0_f32)
+
+MATMUL_Q4_0_OP(bfloat, matmul_q4_0_bf16)
+MATMUL_Q4_0_OP(half, matmul_q4_0_f16)
+MATMUL_Q4_0_OP(float, matmul_q4_0_f32)
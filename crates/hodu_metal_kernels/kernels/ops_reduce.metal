@@ -172,10 +172,17 @@ REDUCE_OP(uint32_t, uint32_t, prod_u32, 1u, acc *= val)
 REDUCE_OP(uint64_t, uint64_t, prod_u64, 1u, acc *= val)
 
 // ============================================================================
-// MEAN OPERATIONS (computed as sum / count)
+// MEAN, VARIANCE AND STANDARD DEVIATION (Welford accumulation)
 // ============================================================================
+//
+// A running mean and a running sum of squared deviations from it (m2), so the variance does
+// not suffer the cancellation of E[X^2] - E[X]^2. var and std are population statistics.
+
+#define WELFORD_MEAN (count > 0 ? mean : NAN)
+#define WELFORD_VAR (m2 / (float)count)
+#define WELFORD_STD sqrt(m2 / (float)count)
 
-#define REDUCE_MEAN_OP(IN_TYPENAME, OUT_TYPENAME, FN_NAME)                                         \
+#define REDUCE_WELFORD_OP(IN_TYPENAME, OUT_TYPENAME, FN_NAME, FINALIZE)                            \
     kernel void hodu_metal_##FN_NAME(                                                              \
         const device IN_TYPENAME *input [[buffer(0)]], device OUT_TYPENAME *output [[buffer(1)]],  \
         constant size_t *metadata [[buffer(2)]], uint thread_index [[thread_position_in_grid]],    \
@@ -200,7 +207,9 @@ REDUCE_OP(uint64_t, uint64_t, prod_u64, 1u, acc *= val)
         for (uint output_idx = thread_index; output_idx < num_els;                                 \
              output_idx += threads_per_grid) {                                                     \
                                                                                                    \
-            OUT_TYPENAME sum = 0;                                                                  \
+            size_t count = 0;                                                                      \
+            float mean = 0.0f;                                                                     \
+            float m2 = 0.0f;                                                                       \
                                                                                                    \
             size_t output_indices[16];                                                             \
             size_t temp = output_idx;                                                              \
@@ -247,17 +256,28 @@ REDUCE_OP(uint64_t, uint64_t, prod_u64, 1u, acc *= val)
                     flat_index += input_indices[i] * strides[i];                                   \
                 }                                                                                  \
                                                                                                    \
-                OUT_TYPENAME val = (OUT_TYPENAME)input[flat_index];                                \
-                sum += val;                                                                        \
+                float x = (float)input[flat_index];                                                \
+                count++;                                                                           \
+                float delta = x - mean;                                                            \
+                mean += delta / (float)count;                                                      \
+                m2 += delta * (x - mean);                                                          \
             }                                                                                      \
                                                                                                    \
-            output[output_idx] = sum / (OUT_TYPENAME)reduce_size;                                  \
+            output[output_idx] = (OUT_TYPENAME)(FINALIZE);                                         \
         }                                                                                          \
     }
 
-REDUCE_MEAN_OP(bfloat, bfloat, mean_bf16)
-REDUCE_MEAN_OP(half, half, mean_f16)
-REDUCE_MEAN_OP(float, float, mean_f32)
+REDUCE_WELFORD_OP(bfloat, bfloat, mean_bf16, WELFORD_MEAN)
+REDUCE_WELFORD_OP(half, half, mean_f16, WELFORD_MEAN)
+REDUCE_WELFORD_OP(float, float, mean_f32, WELFORD_MEAN)
+
+REDUCE_WELFORD_OP(bfloat, bfloat, var_bf16, WELFORD_VAR)
+REDUCE_WELFORD_OP(half, half, var_f16, WELFORD_VAR)
+REDUCE_WELFORD_OP(float, float, var_f32, WELFORD_VAR)
+
+REDUCE_WELFORD_OP(bfloat, bfloat, std_bf16, WELFORD_STD)
+REDUCE_WELFORD_OP(half, half, std_f16, WELFORD_STD)
+REDUCE_WELFORD_OP(float, float, std_f32, WELFORD_STD)
 
 // ============================================================================
 // L2 NORM OPERATIONS (sqrt of sum of squares)
@@ -0,0 +1,52 @@
+//! On-disk cache of compiled pipelines
+//!
+//! Each library source gets a Metal binary archive under `~/.hodu/cache/kernels/metal`,
+//! keyed by a hash of the source and the device name. Pipelines found in the archive skip
+//! backend compilation; new ones are added and the archive is written back. Set
+//! `HODU_KERNEL_CACHE_DIR` to use another directory or `HODU_KERNEL_CACHE=0` to disable the
+//! cache.
+
+use crate::{metal::Device, source::Source};
+use std::path::PathBuf;
+
+/// Directory archives are cached in, or `None` when caching is disabled
+pub fn cache_dir() -> Option<PathBuf> {
+    if std::env::var("HODU_KERNEL_CACHE").is_ok_and(|v| v == "0") {
+        return None;
+    }
+    if let Ok(dir) = std::env::var("HODU_KERNEL_CACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(
+        PathBuf::from(home)
+            .join(".hodu")
+            .join("cache")
+            .join("kernels")
+            .join("metal"),
+    )
+}
+
+/// FNV-1a, stable across builds unlike `DefaultHasher`
+fn hash(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0xcbf29ce484222325, |h, &b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Archive file for the library compiled from `source_content` on `device`
+pub(crate) fn archive_path(device: &Device, source: Source, source_content: &str) -> Option<PathBuf> {
+    let device_name: String = device
+        .name()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let file = format!(
+        "{:?}-{:016x}-{}.metalarchive",
+        source,
+        hash(source_content.as_bytes()),
+        device_name
+    );
+    Some(cache_dir()?.join(file))
+}
@@ -1,6 +1,6 @@
 use crate::{
     error::MetalKernelError,
-    metal::{ComputePipeline, ConstantValues, Device, Function, Library},
+    metal::{BinaryArchive, ComputePipeline, ConstantValues, Device, Function, Library},
     source::Source,
 };
 use objc2_metal::{MTLCompileOptions, MTLMathMode};
@@ -54,11 +54,13 @@ impl From<String> for KernelName {
 
 type Libraries = HashMap<Source, Library>;
 type Pipelines = HashMap<(KernelName, Option<ConstantValues>), ComputePipeline>;
+type Archives = HashMap<Source, Option<BinaryArchive>>;
 
 #[derive(Debug)]
 pub struct Kernels {
     libraries: RwLock<Libraries>,
     pipelines: RwLock<Pipelines>,
+    archives: RwLock<Archives>,
 }
 
 impl Default for Kernels {
@@ -71,7 +73,12 @@ impl Kernels {
     pub fn new() -> Self {
         let libraries = RwLock::new(Libraries::new());
         let pipelines = RwLock::new(Pipelines::new());
-        Self { libraries, pipelines }
+        let archives = RwLock::new(Archives::new());
+        Self {
+            libraries,
+            pipelines,
+            archives,
+        }
     }
 
     fn get_library_source(&self, source: Source) -> &'static str {
@@ -87,6 +94,7 @@ impl Kernels {
             Source::Matrix => crate::source::get_matrix(),
             Source::Memory => crate::source::get_memory(),
             Source::Padding => crate::source::get_padding(),
+            Source::Quant => crate::source::get_quant(),
             Source::Reduce => crate::source::get_reduce(),
             Source::Resize => crate::source::get_resize(),
             Source::Scan => crate::source::get_scan(),
@@ -130,6 +138,41 @@ impl Kernels {
         Ok(func)
     }
 
+    /// On-disk binary archive for `source`, opened on first use
+    fn load_archive(&self, device: &Device, source: Source) -> Result<Option<BinaryArchive>, MetalKernelError> {
+        let mut archives = self.archives.write()?;
+        if let Some(archive) = archives.get(&source) {
+            return Ok(archive.clone());
+        }
+
+        let archive = crate::disk_cache::archive_path(device, source, self.get_library_source(source))
+            .and_then(|path| device.new_binary_archive(&path).ok());
+        archives.insert(source, archive.clone());
+        Ok(archive)
+    }
+
+    /// Create the pipeline for `func`, going through the on-disk archive when enabled
+    fn new_pipeline(
+        &self,
+        device: &Device,
+        source: Source,
+        func: &Function,
+    ) -> Result<ComputePipeline, MetalKernelError> {
+        if let Some(archive) = self.load_archive(device, source)? {
+            if let Ok(pipeline) = device.new_compute_pipeline_state_from_archive(func, &archive) {
+                return Ok(pipeline);
+            }
+            // Cache failures only cost the compilation we would have done anyway
+            if archive.add_compute_function(func).is_ok() {
+                let _ = archive.serialize();
+            }
+        }
+
+        device
+            .new_compute_pipeline_state_with_function(func)
+            .map_err(|e| MetalKernelError::FailedToCreatePipeline(e.to_string()))
+    }
+
     /// Load the give pipeline
     /// loads the library from source, then gets the function [`name`] from
     /// that source
@@ -147,9 +190,7 @@ impl Kernels {
         } else {
             let (name, constants) = key;
             let func = self.load_function(device, source, name.as_ref(), constants.as_ref())?;
-            let pipeline = device
-                .new_compute_pipeline_state_with_function(&func)
-                .map_err(|e| MetalKernelError::FailedToCreatePipeline(e.to_string()))?;
+            let pipeline = self.new_pipeline(device, source, &func)?;
             pipelines.insert((name, constants), pipeline.clone());
 
             Ok(pipeline)
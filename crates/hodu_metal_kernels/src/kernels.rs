@@ -10,6 +10,7 @@ mod ops_linalg;
 mod ops_matrix;
 mod ops_memory;
 mod ops_padding;
+mod ops_quant;
 mod ops_reduce;
 mod ops_resize;
 mod ops_scan;
@@ -34,6 +35,7 @@ pub use ops_linalg::*;
 pub use ops_matrix::*;
 pub use ops_memory::*;
 pub use ops_padding::*;
+pub use ops_quant::*;
 pub use ops_reduce::*;
 pub use ops_resize::*;
 pub use ops_scan::*;
@@ -3,6 +3,7 @@ use crate::{
     kernel::Kernels,
     kernels::macros::ops,
     metal::{Buffer, ComputeCommandEncoder, Device},
+    precision::accumulate_variant,
     set_params,
     source::Source,
     utils::{linear_split, BufferOffset, EncoderProvider},
@@ -110,7 +111,7 @@ pub fn call_ops_conv(
     output: &Buffer,
     metadata: &[usize],
 ) -> Result<(), MetalKernelError> {
-    let pipeline = kernels.load_pipeline(device, Source::Conv, kernel.0)?;
+    let pipeline = kernels.load_pipeline(device, Source::Conv, accumulate_variant(kernel))?;
 
     let num_els = metadata[0];
 
@@ -3,6 +3,7 @@ use crate::{
     kernel::Kernels,
     kernels::macros::ops,
     metal::{Buffer, ComputeCommandEncoder, Device},
+    precision::accumulate_variant,
     set_params,
     source::Source,
     utils::{BufferOffset, EncoderProvider},
@@ -74,7 +75,7 @@ pub fn call_ops_matmul(
     output: &Buffer,
     metadata: &[usize],
 ) -> Result<(), MetalKernelError> {
-    let pipeline = kernels.load_pipeline(device, Source::Matrix, kernel.0)?;
+    let pipeline = kernels.load_pipeline(device, Source::Matrix, accumulate_variant(kernel))?;
 
     let encoder = ep.encoder();
     let encoder: &ComputeCommandEncoder = encoder.as_ref();
@@ -190,7 +191,7 @@ pub fn call_ops_dot(
     n: usize,
     metadata: &[usize],
 ) -> Result<(), MetalKernelError> {
-    let pipeline = kernels.load_pipeline(device, Source::Matrix, kernel.0)?;
+    let pipeline = kernels.load_pipeline(device, Source::Matrix, accumulate_variant(kernel))?;
 
     let num_els = m * n;
 
@@ -7,7 +7,7 @@ use crate::{
     source::Source,
     utils::{linear_split, BufferOffset, EncoderProvider},
 };
-use objc2_metal::MTLResourceUsage;
+use objc2_metal::{MTLResourceUsage, MTLSize};
 
 ops!(contiguous, copy);
 
@@ -50,6 +50,9 @@ ops!(contiguous, copy);
 /// call_ops_contiguous(&device, &command_buffer, &kernels, contiguous::F32,
 ///                 input_buffer, &output, &metadata)?;
 /// ```
+///
+/// Transposed views, whose unit-stride dim is not the innermost one, are copied by a
+/// threadgroup-memory tiled kernel instead of the strided one.
 pub fn call_ops_contiguous(
     kernel: Kernel,
     kernels: &Kernels,
@@ -59,6 +62,10 @@ pub fn call_ops_contiguous(
     output: &Buffer,
     metadata: &[usize],
 ) -> Result<(), MetalKernelError> {
+    if let Some(axis) = transpose_axis(metadata) {
+        return call_transpose_tiled(kernel, kernels, device, ep, input, output, metadata, axis);
+    }
+
     let pipeline = kernels.load_pipeline(device, Source::Memory, kernel.0)?;
 
     let num_els = metadata[0];
@@ -82,6 +89,76 @@ pub fn call_ops_contiguous(
     Ok(())
 }
 
+/// Side of the square tile staged in threadgroup memory by the tiled transpose kernel
+const TRANSPOSE_TILE: usize = 32;
+/// Rows of the tile each threadgroup covers per pass
+const TRANSPOSE_ROWS: usize = 8;
+
+/// Unit-stride dim of a transposed view, if the tiled transpose kernel handles the layout
+fn transpose_axis(metadata: &[usize]) -> Option<usize> {
+    let num_dims = metadata[1];
+    if num_dims < 2 || metadata[0] == 0 {
+        return None;
+    }
+    let dims = &metadata[2..2 + num_dims];
+    let strides = &metadata[2 + num_dims..2 + 2 * num_dims];
+    let last = num_dims - 1;
+    if strides[last] == 1 || dims[last] < 2 {
+        return None;
+    }
+    (0..last).find(|&d| strides[d] == 1 && dims[d] >= 2)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn call_transpose_tiled(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    ep: impl EncoderProvider,
+    input: BufferOffset,
+    output: &Buffer,
+    metadata: &[usize],
+    axis: usize,
+) -> Result<(), MetalKernelError> {
+    let name = kernel.0.replacen("contiguous", "transpose_tiled", 1);
+    let pipeline = kernels.load_pipeline(device, Source::Memory, name)?;
+
+    let num_dims = metadata[1];
+    let rows = metadata[2 + axis];
+    let cols = metadata[2 + num_dims - 1];
+    let num_planes = metadata[0] / (rows * cols);
+
+    let mut tiled_metadata = metadata.to_vec();
+    tiled_metadata.push(axis);
+
+    let encoder = ep.encoder();
+    let encoder: &ComputeCommandEncoder = encoder.as_ref();
+    encoder.set_compute_pipeline_state(&pipeline);
+
+    // Metal kernel signature:
+    // buffer(0): input
+    // buffer(1): output
+    // buffer(2): metadata followed by axis
+    set_params!(encoder, (&input, output, tiled_metadata.as_slice()));
+
+    encoder.use_resource(input.buffer, MTLResourceUsage::Read);
+    encoder.use_resource(output, MTLResourceUsage::Write);
+
+    let threadgroup_size = MTLSize {
+        width: TRANSPOSE_TILE,
+        height: TRANSPOSE_ROWS,
+        depth: 1,
+    };
+    let threadgroup_count = MTLSize {
+        width: cols.div_ceil(TRANSPOSE_TILE),
+        height: rows.div_ceil(TRANSPOSE_TILE),
+        depth: num_planes,
+    };
+    encoder.dispatch_thread_groups(threadgroup_count, threadgroup_size);
+
+    Ok(())
+}
+
 /// Executes a simple element-wise copy operation from input buffer to output buffer.
 ///
 /// This is a straightforward parallel copy operation that copies `num_els` elements
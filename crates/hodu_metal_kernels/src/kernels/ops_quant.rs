@@ -0,0 +1,257 @@
+use crate::{
+    error::MetalKernelError,
+    kernel::Kernels,
+    kernels::macros::ops,
+    metal::{Buffer, ComputeCommandEncoder, Device},
+    set_params,
+    source::Source,
+    utils::{linear_split, BufferOffset, EncoderProvider},
+};
+use objc2_metal::{MTLResourceUsage, MTLSize};
+
+ops!(
+    quantize,
+    dequantize,
+    matmul_int8,
+    quantize_q4_0,
+    dequantize_q4_0,
+    matmul_q4_0
+);
+
+/// Executes an int8 quantize or dequantize operation using Metal compute pipeline.
+///
+/// Quantize computes `q = clamp(round(x / scale) + zero_point, -128, 127)` (kernels named after
+/// the floating-point input, e.g. `quantize::F32`). Dequantize computes `x = (q - zero_point) * scale`
+/// (kernels named after the floating-point output, e.g. `dequantize::F32`).
+///
+/// # Arguments
+/// * `kernel` - Quantize or dequantize kernel
+/// * `kernels` - Kernel cache
+/// * `device` - Metal device to execute on
+/// * `ep` - Encoder provider (command buffer)
+/// * `input` - Input tensor buffer with offset
+/// * `output` - Contiguous output buffer
+/// * `metadata` - Metadata describing tensor shape and layout (same layout as cast)
+/// * `scale` - Quantization step
+/// * `zero_point` - Integer value that represents 0.0
+#[allow(clippy::too_many_arguments)]
+pub fn call_ops_quantize(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    ep: impl EncoderProvider,
+    input: BufferOffset,
+    output: &Buffer,
+    metadata: &[usize],
+    scale: f32,
+    zero_point: i32,
+) -> Result<(), MetalKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Quant, kernel.0)?;
+
+    let num_els = metadata[0];
+
+    let encoder = ep.encoder();
+    let encoder: &ComputeCommandEncoder = encoder.as_ref();
+    encoder.set_compute_pipeline_state(&pipeline);
+
+    // Metal kernel signature:
+    // buffer(0): input
+    // buffer(1): output
+    // buffer(2): metadata
+    // buffer(3): scale
+    // buffer(4): zero_point
+    set_params!(encoder, (&input, output, metadata, scale, zero_point));
+
+    encoder.use_resource(input.buffer, MTLResourceUsage::Read);
+    encoder.use_resource(output, MTLResourceUsage::Write);
+
+    let (thread_group_count, thread_group_size) = linear_split(&pipeline, num_els);
+    encoder.dispatch_thread_groups(thread_group_count, thread_group_size);
+
+    Ok(())
+}
+
+/// Executes an int8 batched matrix multiplication using Metal compute pipeline.
+///
+/// Computes `C = A @ B` for i8 operands with i32 accumulation and an i32 output. Batch dimensions
+/// broadcast exactly like [`call_ops_matmul`](super::call_ops_matmul), and the metadata layout is
+/// the same.
+///
+/// # Arguments
+/// * `kernel` - The kernel to execute (matmul_int8::I8)
+/// * `kernels` - Kernel cache
+/// * `device` - Metal device to execute on
+/// * `ep` - Encoder provider (command buffer)
+/// * `lhs` - i8 left input tensor with shape [..., M, K]
+/// * `rhs` - i8 right input tensor with shape [..., K, N]
+/// * `output` - i32 output buffer with shape [..., M, N]
+/// * `metadata` - Matmul metadata
+#[allow(clippy::too_many_arguments)]
+pub fn call_ops_matmul_int8(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    ep: impl EncoderProvider,
+    lhs: BufferOffset,
+    rhs: BufferOffset,
+    output: &Buffer,
+    metadata: &[usize],
+) -> Result<(), MetalKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Quant, kernel.0)?;
+
+    let encoder = ep.encoder();
+    let encoder: &ComputeCommandEncoder = encoder.as_ref();
+    encoder.set_compute_pipeline_state(&pipeline);
+    set_params!(encoder, (&lhs, &rhs, output, metadata));
+
+    encoder.use_resource(lhs.buffer, MTLResourceUsage::Read);
+    encoder.use_resource(rhs.buffer, MTLResourceUsage::Read);
+    encoder.use_resource(output, MTLResourceUsage::Write);
+
+    let lhs_ndim = metadata[1];
+    let rhs_ndim = metadata[2];
+    let batch_ndim = metadata[3];
+
+    let metadata_base = 4 + lhs_ndim + rhs_ndim + batch_ndim + lhs_ndim + rhs_ndim;
+    let m = metadata[metadata_base + 2];
+    let n = metadata[metadata_base + 4];
+
+    let num_batches: usize = metadata[4 + lhs_ndim + rhs_ndim..4 + lhs_ndim + rhs_ndim + batch_ndim]
+        .iter()
+        .product();
+
+    const TILE_SIZE: usize = 16;
+    let threadgroup_size = MTLSize {
+        width: TILE_SIZE,
+        height: TILE_SIZE,
+        depth: 1,
+    };
+
+    let threadgroup_count = MTLSize {
+        width: n.div_ceil(TILE_SIZE).max(1),
+        height: m.div_ceil(TILE_SIZE).max(1),
+        depth: num_batches,
+    };
+
+    encoder.dispatch_thread_groups(threadgroup_count, threadgroup_size);
+
+    Ok(())
+}
+
+/// Executes a Q4_0 quantize operation using Metal compute pipeline.
+///
+/// Packs every 32 consecutive values into an 18-byte block (f16 scale + 16 bytes of 4-bit codes),
+/// one thread per block.
+///
+/// # Arguments
+/// * `kernel` - Quantize kernel (e.g. `quantize_q4_0::F32`)
+/// * `kernels` - Kernel cache
+/// * `device` - Metal device to execute on
+/// * `ep` - Encoder provider (command buffer)
+/// * `input` - Floating-point input buffer with offset
+/// * `output` - Packed output buffer (num_els / 32 * 18 bytes)
+/// * `metadata` - Same layout as cast; num_els and the last dimension must be multiples of 32
+pub fn call_ops_quantize_q4_0(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    ep: impl EncoderProvider,
+    input: BufferOffset,
+    output: &Buffer,
+    metadata: &[usize],
+) -> Result<(), MetalKernelError> {
+    let num_blocks = metadata[0] / 32;
+    call_ops_block(kernel, kernels, device, ep, input, output, metadata, num_blocks)
+}
+
+/// Executes a Q4_0 dequantize operation using Metal compute pipeline.
+///
+/// # Arguments
+/// * `kernel` - Dequantize kernel (e.g. `dequantize_q4_0::F32`)
+/// * `kernels` - Kernel cache
+/// * `device` - Metal device to execute on
+/// * `ep` - Encoder provider (command buffer)
+/// * `input` - Packed Q4_0 buffer
+/// * `output` - Floating-point output buffer (num_blocks * 32 elements)
+/// * `metadata` - `[num_blocks, byte_offset]`
+pub fn call_ops_dequantize_q4_0(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    ep: impl EncoderProvider,
+    input: BufferOffset,
+    output: &Buffer,
+    metadata: &[usize],
+) -> Result<(), MetalKernelError> {
+    let num_els = metadata[0] * 32;
+    call_ops_block(kernel, kernels, device, ep, input, output, metadata, num_els)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn call_ops_block(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    ep: impl EncoderProvider,
+    input: BufferOffset,
+    output: &Buffer,
+    metadata: &[usize],
+    num_threads: usize,
+) -> Result<(), MetalKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Quant, kernel.0)?;
+
+    let encoder = ep.encoder();
+    let encoder: &ComputeCommandEncoder = encoder.as_ref();
+    encoder.set_compute_pipeline_state(&pipeline);
+    set_params!(encoder, (&input, output, metadata));
+
+    encoder.use_resource(input.buffer, MTLResourceUsage::Read);
+    encoder.use_resource(output, MTLResourceUsage::Write);
+
+    let (thread_group_count, thread_group_size) = linear_split(&pipeline, num_threads);
+    encoder.dispatch_thread_groups(thread_group_count, thread_group_size);
+
+    Ok(())
+}
+
+/// Executes a fused Q4_0 dequant-matmul using Metal compute pipeline.
+///
+/// Computes `out[M, N] = lhs[M, K] @ dequant(weight)^T` where `weight` is a packed `[N, K]`
+/// Q4_0 matrix, one thread per output element.
+///
+/// # Arguments
+/// * `kernel` - The kernel to execute (e.g. `matmul_q4_0::F32`)
+/// * `kernels` - Kernel cache
+/// * `device` - Metal device to execute on
+/// * `ep` - Encoder provider (command buffer)
+/// * `lhs` - Contiguous floating-point lhs buffer
+/// * `weight` - Packed Q4_0 weight buffer
+/// * `output` - Output buffer of the lhs type (M * N elements)
+/// * `metadata` - `[M, K, N, lhs_offset, weight_byte_offset]`
+#[allow(clippy::too_many_arguments)]
+pub fn call_ops_matmul_q4_0(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    ep: impl EncoderProvider,
+    lhs: BufferOffset,
+    weight: BufferOffset,
+    output: &Buffer,
+    metadata: &[usize],
+) -> Result<(), MetalKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Quant, kernel.0)?;
+
+    let encoder = ep.encoder();
+    let encoder: &ComputeCommandEncoder = encoder.as_ref();
+    encoder.set_compute_pipeline_state(&pipeline);
+    set_params!(encoder, (&lhs, &weight, output, metadata));
+
+    encoder.use_resource(lhs.buffer, MTLResourceUsage::Read);
+    encoder.use_resource(weight.buffer, MTLResourceUsage::Read);
+    encoder.use_resource(output, MTLResourceUsage::Write);
+
+    let (thread_group_count, thread_group_size) = linear_split(&pipeline, metadata[0] * metadata[2]);
+    encoder.dispatch_thread_groups(thread_group_count, thread_group_size);
+
+    Ok(())
+}
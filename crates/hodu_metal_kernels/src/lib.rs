@@ -1,10 +1,12 @@
 // https://github.com/huggingface/candle/blob/main/candle-metal-kernels
 
+pub mod disk_cache;
 pub mod dtype;
 pub mod error;
 pub mod kernel;
 pub mod kernels;
 pub mod metal;
+pub mod precision;
 pub mod source;
 pub mod utils;
 
@@ -1,3 +1,4 @@
+mod binary_archive;
 mod buffer;
 mod command_buffer;
 mod commands;
@@ -6,6 +7,7 @@ mod device;
 mod encoder;
 mod library;
 
+pub use binary_archive::*;
 pub use buffer::*;
 pub use command_buffer::*;
 pub use commands::*;
@@ -0,0 +1,65 @@
+use crate::{error::MetalKernelError, metal::Function};
+use objc2::{rc::Retained, runtime::ProtocolObject};
+use objc2_foundation::{NSString, NSURL};
+use objc2_metal::{MTLBinaryArchive, MTLComputePipelineDescriptor};
+use std::path::{Path, PathBuf};
+
+/// Archive of compiled pipeline binaries that can be serialized to disk
+#[derive(Clone, Debug)]
+pub struct BinaryArchive {
+    raw: Retained<ProtocolObject<dyn MTLBinaryArchive>>,
+    path: PathBuf,
+}
+unsafe impl Send for BinaryArchive {}
+unsafe impl Sync for BinaryArchive {}
+
+impl AsRef<ProtocolObject<dyn MTLBinaryArchive>> for BinaryArchive {
+    fn as_ref(&self) -> &ProtocolObject<dyn MTLBinaryArchive> {
+        &self.raw
+    }
+}
+
+pub(crate) fn file_url(path: &Path) -> Retained<NSURL> {
+    NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()))
+}
+
+impl BinaryArchive {
+    pub fn new(raw: Retained<ProtocolObject<dyn MTLBinaryArchive>>, path: PathBuf) -> BinaryArchive {
+        BinaryArchive { raw, path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Compile `function` into the archive
+    pub fn add_compute_function(&self, function: &Function) -> Result<(), MetalKernelError> {
+        let descriptor = MTLComputePipelineDescriptor::new();
+        descriptor.setComputeFunction(Some(function.as_ref()));
+        self.raw
+            .addComputePipelineFunctionsWithDescriptor_error(&descriptor)
+            .map_err(|e| MetalKernelError::FailedToCreatePipeline(e.to_string()))
+    }
+
+    /// Write the archive to its file
+    ///
+    /// The archive is written to a temporary file and renamed into place, so concurrent
+    /// processes never read a partial archive.
+    pub fn serialize(&self) -> Result<(), MetalKernelError> {
+        let io_err = |e: std::io::Error| MetalKernelError::FailedToCreateResource(e.to_string());
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+
+        let tmp = self.path.with_extension(format!("{}.tmp", std::process::id()));
+        let result = self
+            .raw
+            .serializeToURL_error(&file_url(&tmp))
+            .map_err(|e| MetalKernelError::FailedToCreateResource(e.to_string()))
+            .and_then(|_| std::fs::rename(&tmp, &self.path).map_err(io_err));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+        result
+    }
+}
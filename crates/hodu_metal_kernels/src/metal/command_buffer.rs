@@ -57,6 +57,11 @@ impl CommandBuffer {
     pub fn wait_until_completed(&self) {
         self.raw.waitUntilCompleted()
     }
+
+    /// Seconds the GPU spent executing this command buffer, once it has completed
+    pub fn gpu_duration(&self) -> f64 {
+        self.raw.GPUEndTime() - self.raw.GPUStartTime()
+    }
 }
 
 impl AsRef<ProtocolObject<dyn MTLCommandBuffer>> for CommandBuffer {
@@ -85,7 +85,10 @@ impl Commands {
         Ok((flushed, command_buffer.clone()))
     }
 
-    pub fn wait_until_completed(&mut self) -> Result<(), MetalKernelError> {
+    /// Commit the current command buffer and block until it has finished
+    ///
+    /// Returns the command buffer that was waited on, if there was one.
+    pub fn wait_until_completed(&mut self) -> Result<Option<CommandBuffer>, MetalKernelError> {
         let command_buffer = {
             let mut command_buffers = self.command_buffers.lock()?;
 
@@ -97,7 +100,7 @@ impl Commands {
                 None
             }
         };
-        if let Some(command_buffer) = command_buffer {
+        if let Some(command_buffer) = &command_buffer {
             // Only commit and wait if it needed
             match command_buffer.status() {
                 MTLCommandBufferStatus::NotEnqueued | MTLCommandBufferStatus::Enqueued => {
@@ -123,6 +126,6 @@ impl Commands {
             let mut command_buffers = self.command_buffers.lock()?;
             command_buffers.insert(command_buffer);
         }
-        Ok(())
+        Ok(command_buffer)
     }
 }
@@ -1,11 +1,15 @@
+use super::binary_archive::file_url;
 use crate::{
     error::MetalKernelError,
-    metal::{Buffer, CommandQueue, ComputePipeline, Function, Library, MTLResourceOptions},
+    metal::{BinaryArchive, Buffer, CommandQueue, ComputePipeline, Function, Library, MTLResourceOptions},
 };
 use objc2::{rc::Retained, runtime::ProtocolObject};
-use objc2_foundation::NSString;
-use objc2_metal::{MTLCompileOptions, MTLCreateSystemDefaultDevice, MTLDevice};
-use std::{ffi::c_void, ptr};
+use objc2_foundation::{NSArray, NSString};
+use objc2_metal::{
+    MTLBinaryArchiveDescriptor, MTLCompileOptions, MTLComputePipelineDescriptor, MTLCreateSystemDefaultDevice,
+    MTLDevice, MTLPipelineOption,
+};
+use std::{ffi::c_void, path::Path, ptr};
 
 #[derive(Clone, Debug)]
 pub struct Device {
@@ -25,6 +29,10 @@ impl Device {
         self.as_ref().registryID()
     }
 
+    pub fn name(&self) -> String {
+        self.as_ref().name().to_string()
+    }
+
     pub fn all() -> Vec<Self> {
         MTLCreateSystemDefaultDevice()
             .into_iter()
@@ -82,6 +90,53 @@ impl Device {
         Ok(ComputePipeline::new(raw))
     }
 
+    /// Open the binary archive at `path`, or create an empty one if the file is missing or
+    /// unreadable
+    pub fn new_binary_archive(&self, path: &Path) -> Result<BinaryArchive, MetalKernelError> {
+        let create = |url| {
+            let descriptor = MTLBinaryArchiveDescriptor::new();
+            descriptor.setUrl(url);
+            self.as_ref()
+                .newBinaryArchiveWithDescriptor_error(&descriptor)
+                .map_err(|e| MetalKernelError::FailedToCreateResource(e.to_string()))
+        };
+
+        let raw = if path.exists() {
+            match create(Some(&file_url(path))) {
+                Ok(raw) => raw,
+                Err(_) => {
+                    // Written by another OS or driver version, or truncated
+                    let _ = std::fs::remove_file(path);
+                    create(None)?
+                },
+            }
+        } else {
+            create(None)?
+        };
+        Ok(BinaryArchive::new(raw, path.to_path_buf()))
+    }
+
+    /// Create a pipeline for `function` from its compiled binary in `archive`, failing if the
+    /// archive does not contain it
+    pub fn new_compute_pipeline_state_from_archive(
+        &self,
+        function: &Function,
+        archive: &BinaryArchive,
+    ) -> Result<ComputePipeline, MetalKernelError> {
+        let descriptor = MTLComputePipelineDescriptor::new();
+        descriptor.setComputeFunction(Some(function.as_ref()));
+        descriptor.setBinaryArchives(Some(&NSArray::from_slice(&[archive.as_ref()])));
+        let raw = self
+            .as_ref()
+            .newComputePipelineStateWithDescriptor_options_reflection_error(
+                &descriptor,
+                MTLPipelineOption::FailOnBinaryArchiveMiss,
+                None,
+            )
+            .map_err(|e| MetalKernelError::FailedToCreatePipeline(e.to_string()))?;
+        Ok(ComputePipeline::new(raw))
+    }
+
     pub fn new_command_queue(&self) -> Result<CommandQueue, MetalKernelError> {
         let raw = self.as_ref().newCommandQueue().unwrap();
         Ok(raw)
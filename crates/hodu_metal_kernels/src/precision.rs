@@ -0,0 +1,69 @@
+//! Precision policy honored by matmul and convolution
+//!
+//! Kernels read [`current_precision`], which is [`Precision::DEFAULT`] unless a
+//! [`PrecisionGuard`] is active on the calling thread. Apple GPUs have no TF32 mode, so
+//! only the accumulation setting has an effect here.
+
+use crate::{kernel::KernelName, kernels::macros::Kernel};
+use std::cell::Cell;
+
+/// Accuracy/speed trade-offs matmul and convolution may take
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Precision {
+    /// Let f32 matmul and convolution run on TF32 tensor cores
+    pub allow_tf32: bool,
+    /// Accumulate f16/bf16 products in f32; when `false`, 16-bit accumulation is allowed
+    pub f16_accumulate_f32: bool,
+}
+
+impl Precision {
+    pub const DEFAULT: Self = Self {
+        allow_tf32: false,
+        f16_accumulate_f32: true,
+    };
+
+    /// Make this policy current on this thread until the returned guard is dropped
+    pub fn enter(self) -> PrecisionGuard {
+        PrecisionGuard {
+            previous: CURRENT_PRECISION.with(|current| current.replace(self)),
+        }
+    }
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+thread_local! {
+    static CURRENT_PRECISION: Cell<Precision> = const { Cell::new(Precision::DEFAULT) };
+}
+
+/// Policy kernels launched from this thread use
+pub fn current_precision() -> Precision {
+    CURRENT_PRECISION.with(|current| current.get())
+}
+
+/// Restores the previously current policy when dropped
+pub struct PrecisionGuard {
+    previous: Precision,
+}
+
+impl Drop for PrecisionGuard {
+    fn drop(&mut self) {
+        CURRENT_PRECISION.with(|current| current.set(self.previous));
+    }
+}
+
+/// Name of the variant of `kernel` to launch under the current policy
+///
+/// Only for kernels that define an `_acc16` variant for their f16/bf16 instances.
+pub(crate) fn accumulate_variant(kernel: Kernel) -> KernelName {
+    let half = kernel.0.ends_with("_f16") || kernel.0.ends_with("_bf16");
+    if half && !current_precision().f16_accumulate_f32 {
+        KernelName::Value(format!("{}_acc16", kernel.0))
+    } else {
+        KernelName::Ref(kernel.0)
+    }
+}
@@ -17,6 +17,7 @@ const LINALG_SRC: &str = include_str!("../kernels/ops_linalg.metal");
 const MATRIX_SRC: &str = include_str!("../kernels/ops_matrix.metal");
 const MEMORY_SRC: &str = include_str!("../kernels/ops_memory.metal");
 const PADDING_SRC: &str = include_str!("../kernels/ops_padding.metal");
+const QUANT_SRC: &str = include_str!("../kernels/ops_quant.metal");
 const REDUCE_SRC: &str = include_str!("../kernels/ops_reduce.metal");
 const RESIZE_SRC: &str = include_str!("../kernels/ops_resize.metal");
 const SCAN_SRC: &str = include_str!("../kernels/ops_scan.metal");
@@ -49,6 +50,7 @@ static LINALG: OnceLock<String> = OnceLock::new();
 static MATRIX: OnceLock<String> = OnceLock::new();
 static MEMORY: OnceLock<String> = OnceLock::new();
 static PADDING: OnceLock<String> = OnceLock::new();
+static QUANT: OnceLock<String> = OnceLock::new();
 static REDUCE: OnceLock<String> = OnceLock::new();
 static RESIZE: OnceLock<String> = OnceLock::new();
 static SCAN: OnceLock<String> = OnceLock::new();
@@ -101,6 +103,10 @@ pub fn get_padding() -> &'static str {
     PADDING.get_or_init(|| combine_source(PADDING_SRC))
 }
 
+pub fn get_quant() -> &'static str {
+    QUANT.get_or_init(|| combine_source(QUANT_SRC))
+}
+
 pub fn get_reduce() -> &'static str {
     REDUCE.get_or_init(|| combine_source(REDUCE_SRC))
 }
@@ -142,6 +148,7 @@ pub enum Source {
     Matrix,
     Memory,
     Padding,
+    Quant,
     Reduce,
     Resize,
     Scan,
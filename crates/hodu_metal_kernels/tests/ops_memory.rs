@@ -147,3 +147,50 @@ fn contiguous_transposed_f32() {
     // Expected: [[1, 4], [2, 5], [3, 6]] in row-major = [1, 4, 2, 5, 3, 6]
     assert_eq!(results, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
 }
+
+#[test]
+fn contiguous_permuted_tiled_f32() {
+    let device = device();
+    let kernels = Kernels::new();
+    let command_queue = device.new_command_queue().unwrap();
+    let command_buffer = create_command_buffer(&command_queue).unwrap();
+
+    // [2, 40, 70] permuted to [70, 2, 40]: planes larger than one tile and not a multiple of it
+    let shape = [70, 2, 40];
+    let strides = [1, 2800, 70];
+    let input: Vec<f32> = (0..2 * 40 * 70).map(|i| i as f32).collect();
+    let input_buffer = new_buffer(&device, &input);
+    let output = device
+        .new_buffer(std::mem::size_of_val(&input[..]), RESOURCE_OPTIONS)
+        .unwrap();
+
+    let mut metadata = vec![input.len(), shape.len()];
+    metadata.extend(&shape);
+    metadata.extend(&strides);
+    metadata.push(0);
+
+    call_ops_contiguous(
+        contiguous::F32,
+        &kernels,
+        &device,
+        &command_buffer,
+        BufferOffset::zero_offset(&input_buffer),
+        &output,
+        &metadata,
+    )
+    .unwrap();
+
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+
+    let results: Vec<f32> = read_to_vec(&output, input.len());
+    let mut expected = Vec::with_capacity(input.len());
+    for i in 0..70 {
+        for b in 0..2 {
+            for j in 0..40 {
+                expected.push(input[b * 2800 + j * 70 + i]);
+            }
+        }
+    }
+    assert_eq!(results, expected);
+}
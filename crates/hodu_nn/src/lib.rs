@@ -4,6 +4,8 @@ mod modules;
 pub mod optimizer;
 mod optimizers;
 pub mod prelude;
+#[cfg(feature = "serde")]
+pub mod state_dict;
 pub use losses::{
     binary_cross_entropy::{BCELoss, BCEWithLogitsLoss},
     cross_entropy::CrossEntropyLoss,
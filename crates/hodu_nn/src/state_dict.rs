@@ -0,0 +1,68 @@
+//! Saving and loading module parameters
+//!
+//! Modules expose their parameters positionally, so a state dict names each parameter by its
+//! index in [`Module::parameters`] ("0", "1", ...). It is stored as a .hdta archive.
+
+use crate::module::Module;
+use hodu_core::{
+    error::{HoduError, HoduResult},
+    format::hdta,
+    tensor::Tensor,
+};
+use std::{collections::HashMap, path::Path};
+
+/// Collect the parameters of `module` keyed by their position
+pub fn state_dict<I>(module: &impl Module<I>) -> HashMap<String, Tensor> {
+    module
+        .parameters()
+        .into_iter()
+        .enumerate()
+        .map(|(i, param)| (i.to_string(), param.clone()))
+        .collect()
+}
+
+/// Save the parameters of `module` to a .hdta file
+pub fn save<I>(module: &impl Module<I>, path: impl AsRef<Path>) -> HoduResult<()> {
+    hdta::save(&state_dict(module), path)
+}
+
+/// Load parameters saved with [`save`] into `module` in place
+///
+/// Every parameter must have a matching entry with the same shape and dtype.
+pub fn load<I>(module: &impl Module<I>, path: impl AsRef<Path>) -> HoduResult<()> {
+    let archive = hdta::Archive::open(path)?;
+    let params = module.parameters();
+    if archive.entries().len() != params.len() {
+        return Err(HoduError::InvalidArgument(format!(
+            "state dict has {} entries but module has {} parameters",
+            archive.entries().len(),
+            params.len()
+        )));
+    }
+
+    for (i, param) in params.into_iter().enumerate() {
+        let name = i.to_string();
+        let entry = archive
+            .entry(&name)
+            .ok_or_else(|| HoduError::InvalidArgument(format!("state dict is missing parameter '{}'", name)))?;
+        if entry.shape != param.shape().dims() || entry.dtype != param.dtype() {
+            return Err(HoduError::InvalidArgument(format!(
+                "parameter '{}' is {:?} {} in the state dict but {:?} {} in the module",
+                name,
+                entry.shape,
+                entry.dtype,
+                param.shape().dims(),
+                param.dtype()
+            )));
+        }
+
+        let value = archive.get(&name)?;
+        let value = if value.device() != param.device() {
+            value.to_device(param.device())?
+        } else {
+            value
+        };
+        param.set_(&value)?;
+    }
+    Ok(())
+}
@@ -3,6 +3,7 @@
 //! Types for backend plugins that execute models on various devices.
 
 use std::fmt;
+use std::process::Command;
 
 /// Target device for plugin execution
 ///
@@ -171,6 +172,40 @@ pub fn current_host_triple() -> &'static str {
     return "unknown";
 }
 
+/// Check if a tool is available on the system
+///
+/// Tool names are validated to prevent command injection:
+/// - Must not be empty
+/// - Must not contain path separators (`/`, `\`)
+/// - Must not contain shell metacharacters
+/// - Must not contain null bytes or control characters
+///
+/// Returns `false` for invalid tool names without executing anything.
+pub fn is_tool_available(tool: &str) -> bool {
+    // Validate tool name to prevent command injection
+    if tool.is_empty() {
+        return false;
+    }
+    // Reject path separators (prevents executing arbitrary paths)
+    if tool.contains('/') || tool.contains('\\') {
+        return false;
+    }
+    // Reject shell metacharacters and control characters
+    const FORBIDDEN_CHARS: &[char] = &[
+        '\0', '\n', '\r', '\t', ' ', '&', '|', ';', '$', '`', '(', ')', '{', '}', '[', ']', '<', '>', '\'', '"', '!',
+        '*', '?', '#', '~', '^',
+    ];
+    if tool.chars().any(|c| c.is_control() || FORBIDDEN_CHARS.contains(&c)) {
+        return false;
+    }
+
+    Command::new(tool)
+        .arg("--version")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -264,4 +299,12 @@ mod tests {
         );
         assert_eq!(BuildTargetError::EmptyDevice.to_string(), "Device cannot be empty");
     }
+
+    #[test]
+    fn test_is_tool_available_rejects_unsafe_names() {
+        assert!(!is_tool_available(""));
+        assert!(!is_tool_available("/bin/sh"));
+        assert!(!is_tool_available("cc; rm -rf /"));
+        assert!(!is_tool_available("$(id)"));
+    }
 }
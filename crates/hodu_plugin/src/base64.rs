@@ -0,0 +1,119 @@
+//! Standard base64 (RFC 4648, padded) for binary data carried in JSON messages
+
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Error decoding base64 text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError(String);
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid base64: {}", self.0)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Encode bytes as padded base64
+pub fn encode(data: &[u8]) -> String {
+    // Use saturating arithmetic to prevent overflow on 32-bit systems with huge data
+    let capacity = data.len().saturating_add(2) / 3 * 4;
+    let mut result = Vec::with_capacity(capacity);
+
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as usize;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as usize;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as usize;
+
+        result.push(ALPHABET[b0 >> 2]);
+        result.push(ALPHABET[((b0 & 0x03) << 4) | (b1 >> 4)]);
+
+        if chunk.len() > 1 {
+            result.push(ALPHABET[((b1 & 0x0f) << 2) | (b2 >> 6)]);
+        } else {
+            result.push(b'=');
+        }
+
+        if chunk.len() > 2 {
+            result.push(ALPHABET[b2 & 0x3f]);
+        } else {
+            result.push(b'=');
+        }
+    }
+
+    // SAFETY: base64 encoding only produces ASCII characters, so this is always valid UTF-8
+    String::from_utf8(result).expect("base64 encoding produces only ASCII characters")
+}
+
+/// Decode padded base64
+pub fn decode(text: &str) -> Result<Vec<u8>, DecodeError> {
+    let bytes = text.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return Err(DecodeError(format!("length {} is not a multiple of 4", bytes.len())));
+    }
+
+    let mut result = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (i, quad) in bytes.chunks(4).enumerate() {
+        let last = i == bytes.len() / 4 - 1;
+        let padding = quad.iter().rev().take_while(|&&b| b == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return Err(DecodeError(format!("unexpected padding at byte {}", i * 4)));
+        }
+
+        let mut value = 0u32;
+        for (j, &b) in quad[..4 - padding].iter().enumerate() {
+            let sextet = sextet(b).ok_or_else(|| DecodeError(format!("invalid character at byte {}", i * 4 + j)))?;
+            value |= sextet << (18 - 6 * j);
+        }
+        result.push((value >> 16) as u8);
+        if padding < 2 {
+            result.push((value >> 8) as u8);
+        }
+        if padding < 1 {
+            result.push(value as u8);
+        }
+    }
+    Ok(result)
+}
+
+fn sextet(b: u8) -> Option<u32> {
+    match b {
+        b'A'..=b'Z' => Some((b - b'A') as u32),
+        b'a'..=b'z' => Some((b - b'a') as u32 + 26),
+        b'0'..=b'9' => Some((b - b'0') as u32 + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for data in [
+            &b""[..],
+            b"f",
+            b"fo",
+            b"foo",
+            b"foob",
+            b"fooba",
+            b"foobar",
+            &[0, 255, 128, 7],
+        ] {
+            assert_eq!(decode(&encode(data)).unwrap(), data);
+        }
+        assert_eq!(encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode(b"fo"), "Zm8=");
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(decode("Zm8").is_err());
+        assert!(decode("Zm8*").is_err());
+        assert!(decode("Zg==Zg==").is_err());
+        assert!(decode("Z===").is_err());
+    }
+}
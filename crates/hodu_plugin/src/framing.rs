@@ -0,0 +1,243 @@
+//! Message framing for JSON-RPC over stdio
+//!
+//! Both sides start out writing one JSON message per line. During `initialize` the CLI lists
+//! the framings it accepts and the plugin picks one; from the initialize response onwards both
+//! sides write with the chosen framing. Readers accept either framing at any time, so there is
+//! no window where a message can be misread while the switch happens.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Read, Write};
+
+/// Header announcing the byte length of a Content-Length framed message
+const CONTENT_LENGTH_HEADER: &str = "content-length";
+
+/// How messages are delimited on the wire
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Framing {
+    /// One JSON message per line
+    ///
+    /// Every side speaks this before negotiation.
+    #[default]
+    Line,
+    /// `Content-Length: N\r\n\r\n` followed by exactly N bytes of JSON, as in LSP
+    ///
+    /// The reader knows the message size up front instead of scanning for a newline, so large
+    /// payloads are read in one go and oversized ones are skipped without being buffered.
+    ContentLength,
+}
+
+impl Framing {
+    /// Write one message in this framing (does not flush)
+    pub fn write_message<W: Write + ?Sized>(self, writer: &mut W, message: &str) -> io::Result<()> {
+        match self {
+            Framing::Line => writeln!(writer, "{}", message),
+            Framing::ContentLength => {
+                write!(writer, "Content-Length: {}\r\n\r\n", message.len())?;
+                writer.write_all(message.as_bytes())
+            },
+        }
+    }
+}
+
+/// One message read from the wire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// A complete message body
+    Message(String),
+    /// A message of the given size that exceeded the limit and was discarded
+    TooLarge(usize),
+    /// A whole message that was not valid UTF-8, with the reason
+    ///
+    /// The message was consumed, so the next read starts at the message after it.
+    Invalid(String),
+}
+
+/// Read the next message, detecting its framing
+///
+/// A line starting with `{` or `[` is a line-delimited message; anything else starts a header
+/// block that must carry `Content-Length`. Blank lines between messages are skipped. Messages
+/// longer than `max_len` bytes are returned as [`Frame::TooLarge`], and messages that are not
+/// UTF-8 as [`Frame::Invalid`].
+///
+/// Returns `None` at end of input.
+pub fn read_message<R: BufRead + ?Sized>(reader: &mut R, max_len: usize) -> io::Result<Option<Frame>> {
+    let mut bytes = Vec::new();
+    loop {
+        bytes.clear();
+        if reader.read_until(b'\n', &mut bytes)? == 0 {
+            return Ok(None);
+        }
+        // A line message is known whole once its newline is read, however broken its bytes are
+        let line = match std::str::from_utf8(&bytes) {
+            Ok(line) => line,
+            Err(e) if bytes.starts_with(b"{") || bytes.starts_with(b"[") => {
+                return Ok(Some(Frame::Invalid(format!("Message is not UTF-8: {}", e))));
+            },
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        if trimmed.trim().is_empty() {
+            continue;
+        }
+
+        if is_line_message(trimmed) {
+            if trimmed.len() > max_len {
+                return Ok(Some(Frame::TooLarge(trimmed.len())));
+            }
+            return Ok(Some(Frame::Message(trimmed.to_string())));
+        }
+
+        let len = read_content_length(reader, trimmed)?;
+        if len > max_len {
+            let skipped = io::copy(&mut reader.take(len as u64), &mut io::sink())?;
+            if skipped < len as u64 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Message body ended early"));
+            }
+            return Ok(Some(Frame::TooLarge(len)));
+        }
+        let mut body = vec![0; len];
+        reader.read_exact(&mut body)?;
+        return Ok(Some(match String::from_utf8(body) {
+            Ok(body) => Frame::Message(body),
+            Err(e) => Frame::Invalid(format!("Message is not UTF-8: {}", e.utf8_error())),
+        }));
+    }
+}
+
+/// Whether the first line of a message is a whole line-delimited message
+///
+/// Otherwise it starts a Content-Length header block.
+pub fn is_line_message(line: &str) -> bool {
+    line.trim_start().starts_with(['{', '['])
+}
+
+/// Parse one header line (without its line ending)
+///
+/// Returns the length for a `Content-Length` header and `None` for any other header.
+pub fn parse_header(line: &str) -> io::Result<Option<usize>> {
+    let (name, value) = line
+        .split_once(':')
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid header: {}", line)))?;
+    if !name.trim().eq_ignore_ascii_case(CONTENT_LENGTH_HEADER) {
+        return Ok(None);
+    }
+    value
+        .trim()
+        .parse::<usize>()
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid Content-Length: {}", e)))
+}
+
+/// Parse a header block starting at `first`, consuming it up to the blank line that ends it
+fn read_content_length<R: BufRead + ?Sized>(reader: &mut R, first: &str) -> io::Result<usize> {
+    let mut len = parse_header(first)?;
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if reader.read_line(&mut header)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Header block ended early"));
+        }
+        let header = header.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            break;
+        }
+        len = parse_header(header)?.or(len);
+    }
+    len.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length header"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(input: &[u8], max_len: usize) -> Vec<Frame> {
+        let mut reader = input;
+        let mut frames = Vec::new();
+        while let Some(frame) = read_message(&mut reader, max_len).unwrap() {
+            frames.push(frame);
+        }
+        frames
+    }
+
+    #[test]
+    fn test_round_trip_both_framings() {
+        let messages = [r#"{"id":1}"#, r#"{"text":"a\nb"}"#, r#"[{"id":2}]"#];
+        let mut wire = Vec::new();
+        for (i, message) in messages.iter().enumerate() {
+            let framing = if i % 2 == 0 {
+                Framing::ContentLength
+            } else {
+                Framing::Line
+            };
+            framing.write_message(&mut wire, message).unwrap();
+        }
+
+        let frames = read_all(&wire, usize::MAX);
+        let expected: Vec<Frame> = messages.iter().map(|m| Frame::Message(m.to_string())).collect();
+        assert_eq!(frames, expected);
+    }
+
+    #[test]
+    fn test_content_length_body_with_newlines() {
+        let body = "{\n  \"id\": 1\n}";
+        let wire = format!(
+            "Content-Type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        assert_eq!(
+            read_all(wire.as_bytes(), usize::MAX),
+            vec![Frame::Message(body.to_string())]
+        );
+    }
+
+    #[test]
+    fn test_too_large_is_skipped() {
+        let large = format!(r#"{{"pad":"{}"}}"#, "x".repeat(64));
+        let mut wire = Vec::new();
+        Framing::ContentLength.write_message(&mut wire, &large).unwrap();
+        Framing::Line.write_message(&mut wire, &large).unwrap();
+        Framing::ContentLength.write_message(&mut wire, "{}").unwrap();
+
+        let frames = read_all(&wire, 16);
+        assert_eq!(
+            frames,
+            vec![
+                Frame::TooLarge(large.len()),
+                Frame::TooLarge(large.len()),
+                Frame::Message("{}".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_skipped() {
+        let mut wire = b"{\"id\": \"\xff\"}\n".to_vec();
+        wire.extend_from_slice(b"Content-Length: 3\r\n\r\n{\xc3}");
+        wire.extend_from_slice(b"{}\n");
+
+        let frames = read_all(&wire, usize::MAX);
+        assert!(matches!(&frames[0], Frame::Invalid(reason) if reason.starts_with("Message is not UTF-8")));
+        assert!(matches!(frames[1], Frame::Invalid(_)));
+        assert_eq!(frames[2], Frame::Message("{}".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let mut reader = &b"garbage\r\n\r\n{}"[..];
+        assert!(read_message(&mut reader, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_missing_content_length() {
+        let mut reader = &b"Content-Type: application/json\r\n\r\n{}"[..];
+        assert!(read_message(&mut reader, usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_truncated_body() {
+        let mut reader = &b"Content-Length: 10\r\n\r\n{}"[..];
+        assert!(read_message(&mut reader, usize::MAX).is_err());
+    }
+}
@@ -0,0 +1,423 @@
+//! Remote plugin index: the static JSON file plugins are searched and installed from
+//!
+//! The index is served over HTTPS and lists every published plugin with its releases. A release
+//! names the plugin protocol it speaks (`major.minor` of [`PLUGIN_VERSION`]) and carries a zip
+//! per target triple holding the plugin executable, its `manifest.json` and optionally a detached
+//! signature, with the SHA-256 of the zip. Releases may also name a git tag to build from, for
+//! targets without a prebuilt zip:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "plugins": [{
+//!     "name": "hodu-backend-cpu",
+//!     "description": "CPU backend",
+//!     "keywords": ["cpu"],
+//!     "releases": [{
+//!       "version": "0.1.2",
+//!       "plugin": "0.1",
+//!       "git": { "url": "https://github.com/daminstudio/hodu-plugins", "tag": "cpu-v0.1.2", "path": "cpu" },
+//!       "targets": {
+//!         "x86_64-unknown-linux-gnu": {
+//!           "url": "https://example.com/hodu-backend-cpu-0.1.2-x86_64-unknown-linux-gnu.zip",
+//!           "sha256": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+//!         }
+//!       }
+//!     }]
+//!   }]
+//! }
+//! ```
+//!
+//! Versions are requested like Cargo dependencies: `hodu-backend-cpu@0.1` resolves to the newest
+//! release matching `^0.1` that speaks the host's protocol.
+
+use crate::PLUGIN_VERSION;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Newest index format this crate reads
+pub const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// Index error
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum IndexError {
+    /// The index is not valid JSON or does not match the format
+    Parse(serde_json::Error),
+    /// The index uses a newer format than [`INDEX_FORMAT_VERSION`]
+    UnsupportedFormat(u32),
+    /// A requested version is not a valid semver requirement
+    InvalidRequirement { requirement: String, error: semver::Error },
+    /// No release matches the requested version
+    NoMatchingVersion {
+        name: String,
+        requirement: String,
+        available: Vec<String>,
+    },
+    /// Releases match the requested version, but none speaks the host's protocol
+    NoCompatibleVersion {
+        name: String,
+        protocol: String,
+        available: Vec<String>,
+    },
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "invalid plugin index: {}", e),
+            Self::UnsupportedFormat(version) => write!(
+                f,
+                "plugin index format {} is newer than this hodu supports ({}); update hodu",
+                version, INDEX_FORMAT_VERSION
+            ),
+            Self::InvalidRequirement { requirement, error } => {
+                write!(f, "invalid version requirement '{}': {}", requirement, error)
+            },
+            Self::NoMatchingVersion {
+                name,
+                requirement,
+                available,
+            } => write!(
+                f,
+                "no release of '{}' matches '{}'\n\nAvailable versions:\n  {}",
+                name,
+                requirement,
+                available.join("\n  ")
+            ),
+            Self::NoCompatibleVersion {
+                name,
+                protocol,
+                available,
+            } => write!(
+                f,
+                "no release of '{}' is compatible with plugin protocol {}\n\nAvailable versions:\n  {}",
+                name,
+                protocol,
+                available.join("\n  ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IndexError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse(e) => Some(e),
+            Self::InvalidRequirement { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Plugin protocol the host speaks: `major.minor` of [`PLUGIN_VERSION`]
+pub fn host_protocol() -> String {
+    let mut parts = PLUGIN_VERSION.split('.');
+    match (parts.next(), parts.next()) {
+        (Some(major), Some(minor)) => format!("{}.{}", major, minor),
+        _ => PLUGIN_VERSION.to_string(),
+    }
+}
+
+/// Split `name@requirement` into the plugin name and version requirement
+///
+/// A missing requirement, or `latest`, matches every stable release.
+pub fn parse_spec(spec: &str) -> Result<(&str, VersionReq), IndexError> {
+    match spec.split_once('@') {
+        None => Ok((spec, VersionReq::STAR)),
+        Some((name, "" | "latest")) => Ok((name, VersionReq::STAR)),
+        Some((name, requirement)) => {
+            VersionReq::parse(requirement)
+                .map(|req| (name, req))
+                .map_err(|error| IndexError::InvalidRequirement {
+                    requirement: requirement.to_string(),
+                    error,
+                })
+        },
+    }
+}
+
+/// The plugin index
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginIndex {
+    /// Format version
+    pub version: u32,
+    #[serde(default)]
+    pub plugins: Vec<IndexPlugin>,
+}
+
+impl PluginIndex {
+    /// Parse an index, rejecting formats newer than [`INDEX_FORMAT_VERSION`]
+    pub fn parse(json: &str) -> Result<Self, IndexError> {
+        let index: Self = serde_json::from_str(json).map_err(IndexError::Parse)?;
+        if index.version > INDEX_FORMAT_VERSION {
+            return Err(IndexError::UnsupportedFormat(index.version));
+        }
+        Ok(index)
+    }
+
+    /// Find a plugin by exact name
+    pub fn find(&self, name: &str) -> Option<&IndexPlugin> {
+        self.plugins.iter().find(|p| p.name == name)
+    }
+
+    /// Plugins whose name, description or keywords contain `query` (case-insensitive)
+    ///
+    /// An exact name match comes first, then name matches, then the rest, each by name.
+    /// An empty query returns every plugin.
+    pub fn search(&self, query: &str) -> Vec<&IndexPlugin> {
+        let query = query.to_lowercase();
+        let mut found: Vec<_> = self
+            .plugins
+            .iter()
+            .filter_map(|p| {
+                let name = p.name.to_lowercase();
+                let rank = if name == query {
+                    0
+                } else if name.contains(&query) {
+                    1
+                } else if p
+                    .description
+                    .iter()
+                    .chain(&p.keywords)
+                    .any(|text| text.to_lowercase().contains(&query))
+                {
+                    2
+                } else {
+                    return None;
+                };
+                Some((rank, p))
+            })
+            .collect();
+        found.sort_by(|(a_rank, a), (b_rank, b)| a_rank.cmp(b_rank).then_with(|| a.name.cmp(&b.name)));
+        found.into_iter().map(|(_, p)| p).collect()
+    }
+}
+
+/// A plugin listed in the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexPlugin {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub releases: Vec<IndexRelease>,
+}
+
+impl IndexPlugin {
+    /// Newest release matching `requirement` that speaks `protocol`
+    ///
+    /// Yanked releases and releases whose version is not valid semver are never picked.
+    pub fn resolve(&self, requirement: &VersionReq, protocol: &str) -> Result<&IndexRelease, IndexError> {
+        let matching: Vec<_> = self
+            .releases
+            .iter()
+            .filter(|r| !r.yanked)
+            .filter_map(|r| r.semver().map(|v| (v, r)))
+            .filter(|(v, _)| requirement.matches(v))
+            .collect();
+        let available = || self.releases.iter().map(IndexRelease::describe).collect();
+
+        if matching.is_empty() {
+            return Err(IndexError::NoMatchingVersion {
+                name: self.name.clone(),
+                requirement: requirement.to_string(),
+                available: available(),
+            });
+        }
+        matching
+            .into_iter()
+            .filter(|(_, r)| r.plugin == protocol)
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, r)| r)
+            .ok_or_else(|| IndexError::NoCompatibleVersion {
+                name: self.name.clone(),
+                protocol: protocol.to_string(),
+                available: available(),
+            })
+    }
+
+    /// Newest stable release that speaks `protocol`
+    pub fn latest(&self, protocol: &str) -> Option<&IndexRelease> {
+        self.resolve(&VersionReq::STAR, protocol).ok()
+    }
+}
+
+/// A published version of a plugin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexRelease {
+    pub version: String,
+    /// Plugin protocol the release speaks (e.g., "0.1" means compatible with 0.1.x)
+    pub plugin: String,
+    /// Withdrawn: still listed, but never resolved to
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub yanked: bool,
+    /// Source to build from on targets without a prebuilt zip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitSource>,
+    /// Prebuilt zips by target triple
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub targets: BTreeMap<String, TargetArtifact>,
+}
+
+impl IndexRelease {
+    /// The release version, if it is valid semver
+    pub fn semver(&self) -> Option<Version> {
+        Version::parse(&self.version).ok()
+    }
+
+    /// Prebuilt zip for a target triple
+    pub fn target(&self, triple: &str) -> Option<&TargetArtifact> {
+        self.targets.get(triple)
+    }
+
+    /// `version (protocol x.y)`, marked when yanked
+    pub fn describe(&self) -> String {
+        format!(
+            "{} (protocol {}){}",
+            self.version,
+            self.plugin,
+            if self.yanked { " [yanked]" } else { "" }
+        )
+    }
+}
+
+/// Git source of a release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitSource {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Subdirectory holding the plugin crate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Prebuilt zip of a release for one target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetArtifact {
+    pub url: String,
+    /// Hex SHA-256 of the zip
+    pub sha256: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(version: &str, plugin: &str) -> IndexRelease {
+        IndexRelease {
+            version: version.to_string(),
+            plugin: plugin.to_string(),
+            yanked: false,
+            git: None,
+            targets: BTreeMap::new(),
+        }
+    }
+
+    fn plugin(name: &str, releases: Vec<IndexRelease>) -> IndexPlugin {
+        IndexPlugin {
+            name: name.to_string(),
+            description: None,
+            license: None,
+            repository: None,
+            keywords: vec![],
+            releases,
+        }
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let (name, req) = parse_spec("hodu-backend-cpu").unwrap();
+        assert_eq!((name, req), ("hodu-backend-cpu", VersionReq::STAR));
+        assert_eq!(parse_spec("cpu@latest").unwrap().1, VersionReq::STAR);
+
+        let (name, req) = parse_spec("cpu@0.2").unwrap();
+        assert_eq!(name, "cpu");
+        assert!(req.matches(&Version::new(0, 2, 5)));
+        assert!(!req.matches(&Version::new(0, 3, 0)));
+
+        assert!(matches!(
+            parse_spec("cpu@not-a-version"),
+            Err(IndexError::InvalidRequirement { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut yanked = release("0.2.3", "0.1");
+        yanked.yanked = true;
+        let p = plugin(
+            "cpu",
+            vec![
+                release("0.2.0", "0.1"),
+                release("0.2.2", "0.1"),
+                release("0.10.0", "0.2"),
+                release("0.3.0-beta.1", "0.1"),
+                yanked,
+            ],
+        );
+
+        // Newest by semver, not by position or string order
+        assert_eq!(p.latest("0.1").unwrap().version, "0.2.2");
+        assert_eq!(p.latest("0.2").unwrap().version, "0.10.0");
+        assert!(p.latest("0.9").is_none());
+
+        let (_, req) = parse_spec("cpu@=0.2.0").unwrap();
+        assert_eq!(p.resolve(&req, "0.1").unwrap().version, "0.2.0");
+
+        let (_, req) = parse_spec("cpu@0.3.0-beta.1").unwrap();
+        assert_eq!(p.resolve(&req, "0.1").unwrap().version, "0.3.0-beta.1");
+
+        let (_, req) = parse_spec("cpu@0.5").unwrap();
+        assert!(matches!(
+            p.resolve(&req, "0.1"),
+            Err(IndexError::NoMatchingVersion { available, .. }) if available.len() == 5
+        ));
+        let (_, req) = parse_spec("cpu@0.10").unwrap();
+        assert!(matches!(
+            p.resolve(&req, "0.1"),
+            Err(IndexError::NoCompatibleVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_and_search() {
+        let json = r#"{
+            "version": 1,
+            "plugins": [
+                { "name": "hodu-format-onnx", "description": "ONNX models", "keywords": ["model"] },
+                { "name": "hodu-backend-cpu", "keywords": ["onnx-runtime"],
+                  "releases": [{ "version": "0.1.0", "plugin": "0.1",
+                                 "targets": { "x86_64-unknown-linux-gnu": { "url": "https://x/cpu.zip", "sha256": "ab" } } }] },
+                { "name": "onnx", "description": "Shorthand" }
+            ]
+        }"#;
+        let index = PluginIndex::parse(json).unwrap();
+        let cpu = index.find("hodu-backend-cpu").unwrap();
+        assert_eq!(
+            cpu.releases[0].target("x86_64-unknown-linux-gnu").unwrap().url,
+            "https://x/cpu.zip"
+        );
+        assert!(cpu.releases[0].target("aarch64-apple-darwin").is_none());
+
+        let names: Vec<_> = index.search("ONNX").iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["onnx", "hodu-format-onnx", "hodu-backend-cpu"]);
+        assert_eq!(index.search("").len(), 3);
+        assert!(index.search("cuda").is_empty());
+
+        assert!(matches!(
+            PluginIndex::parse(r#"{ "version": 2, "plugins": [] }"#),
+            Err(IndexError::UnsupportedFormat(2))
+        ));
+        assert!(matches!(PluginIndex::parse("[]"), Err(IndexError::Parse(_))));
+    }
+}
@@ -4,14 +4,26 @@
 //! shared between hodu-cli and plugins.
 
 pub mod backend;
+pub mod base64;
 pub mod error;
+pub mod framing;
+pub mod index;
+pub mod manifest;
+pub mod memory;
 pub mod rpc;
+pub mod sandbox;
+pub mod signing;
 pub mod tensor;
+pub mod trace;
 
 // Re-export commonly used types
-pub use backend::{current_host_triple, device_type, parse_device_id, BuildTarget, BuildTargetError, Device};
+pub use backend::{
+    current_host_triple, device_type, is_tool_available, parse_device_id, BuildTarget, BuildTargetError, Device,
+};
 pub use error::{PluginError, PluginResult};
+pub use framing::{read_message, Frame, Framing};
 pub use rpc::*;
+pub use sandbox::SandboxPolicy;
 pub use tensor::{ParseDTypeError, PluginDType, TensorData, TensorDataError};
 
 /// Plugin protocol version for compatibility checking (semver string)
@@ -0,0 +1,585 @@
+//! Plugin manifest (manifest.json) schema and validation
+//!
+//! A plugin ships a `manifest.json` next to its executable describing what it provides.
+//! [`manifest_schema`] describes the file as a JSON Schema, for editors and third-party tooling;
+//! [`validate_manifest`] checks a manifest against the same rules and reports every problem with
+//! the line and column it is on, so `hodu plugin install` can reject a broken manifest with a
+//! message pointing at the mistake:
+//!
+//! ```ignore
+//! let source = std::fs::read_to_string("manifest.json")?;
+//! for error in validate_manifest(&source) {
+//!     eprintln!("manifest.json:{}", error);
+//! }
+//! ```
+
+use crate::rpc::methods;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Name of the manifest file, next to the plugin executable
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Capabilities a manifest may declare, besides `op.<name>` custom ops
+pub const KNOWN_CAPABILITIES: &[&str] = &[
+    methods::FORMAT_LOAD_MODEL,
+    methods::FORMAT_SAVE_MODEL,
+    methods::FORMAT_LOAD_TENSOR,
+    methods::FORMAT_SAVE_TENSOR,
+    methods::FORMAT_STREAM_LOAD_TENSOR,
+    methods::BACKEND_RUN,
+    methods::BACKEND_BUILD,
+    methods::BACKEND_LOAD_SESSION,
+    methods::BACKEND_RUN_SESSION,
+    methods::BACKEND_CLOSE_SESSION,
+    methods::BACKEND_BENCHMARK,
+    methods::BACKEND_PROFILE,
+    methods::BACKEND_QUANTIZE,
+];
+
+/// Pattern versions must match: `MAJOR.MINOR.PATCH`, optionally followed by a pre-release or
+/// build suffix
+const VERSION_PATTERN: &str = r"^[0-9]+\.[0-9]+\.[0-9]+([-+].+)?$";
+
+/// Check if a capability is one a manifest may declare
+pub fn is_known_capability(capability: &str) -> bool {
+    KNOWN_CAPABILITIES.contains(&capability)
+        || capability
+            .strip_prefix(methods::CUSTOM_OP_PREFIX)
+            .is_some_and(|name| !name.is_empty())
+}
+
+/// Check if a version matches [`VERSION_PATTERN`]
+fn is_valid_version(version: &str) -> bool {
+    let (core, suffix) = match version.find(['-', '+']) {
+        Some(at) => (&version[..at], Some(&version[at + 1..])),
+        None => (version, None),
+    };
+    let parts: Vec<&str> = core.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()))
+        && suffix.is_none_or(|s| !s.is_empty())
+}
+
+/// JSON Schema (draft 2020-12) describing manifest.json
+///
+/// [`validate_manifest`] enforces the same rules.
+pub fn manifest_schema() -> Value {
+    let strings = |description: &str| {
+        json!({
+            "type": "array",
+            "description": description,
+            "items": { "type": "string", "minLength": 1 },
+        })
+    };
+    let version =
+        |description: &str| json!({ "type": "string", "description": description, "pattern": VERSION_PATTERN });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Hodu plugin manifest",
+        "type": "object",
+        "required": ["name", "version"],
+        "properties": {
+            "name": { "type": "string", "description": "Plugin name", "minLength": 1 },
+            "version": version("Plugin version"),
+            "description": { "type": "string", "description": "Short description of the plugin" },
+            "license": { "type": "string", "description": "License identifier, e.g. \"MIT\"" },
+            "plugin_version": version("Version of the plugin protocol the plugin was built against"),
+            "capabilities": {
+                "type": "array",
+                "description": "Methods the plugin implements; custom ops are declared as `op.<name>`",
+                "items": {
+                    "type": "string",
+                    "anyOf": [
+                        { "enum": KNOWN_CAPABILITIES },
+                        { "pattern": format!("^{}.+$", methods::CUSTOM_OP_PREFIX.replace('.', r"\.")) },
+                    ],
+                },
+            },
+            "devices": strings("Devices a backend runs on, e.g. \"cpu\" or \"cuda::0\""),
+            "extensions": strings("File extensions a format plugin handles, without the dot"),
+            "dependencies": strings("Plugins this plugin needs installed"),
+            "supported_targets": {
+                "type": "array",
+                "description": "Targets a backend can build for",
+                "items": {
+                    "type": "object",
+                    "required": ["triple"],
+                    "properties": {
+                        "triple": { "type": "string", "description": "Target triple", "minLength": 1 },
+                        "requires": strings("Tools the build needs; \"clang|gcc\" accepts either"),
+                        "host_only": strings("Glob patterns of the host triples that can build the target"),
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// A problem found in a manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestError {
+    /// Line of the offending value, from 1
+    pub line: usize,
+    /// Column of the offending value, from 1
+    pub column: usize,
+    /// Field the problem is in, e.g. `supported_targets[0].triple`; empty for the whole manifest
+    pub path: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}:{}: {}", self.line, self.column, self.message)
+        } else {
+            write!(f, "{}:{}: {}: {}", self.line, self.column, self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+/// Check a manifest's source against [`manifest_schema`]
+///
+/// Returns every problem found, in the order they appear; an empty list means the manifest is
+/// valid. Fields the schema does not describe are allowed.
+pub fn validate_manifest(source: &str) -> Vec<ManifestError> {
+    let value: Value = match serde_json::from_str(source) {
+        Ok(value) => value,
+        Err(e) => {
+            return vec![ManifestError {
+                line: e.line(),
+                column: e.column(),
+                path: String::new(),
+                message: format!("invalid JSON: {}", strip_position(&e.to_string())),
+            }]
+        },
+    };
+
+    let mut validator = Validator {
+        positions: Positions::scan(source),
+        errors: Vec::new(),
+    };
+    validator.manifest(&value);
+    validator.errors.sort_by_key(|error| (error.line, error.column));
+    validator.errors
+}
+
+/// Drop the " at line L column C" serde_json appends, since errors carry the position
+fn strip_position(message: &str) -> &str {
+    message.rfind(" at line ").map_or(message, |at| &message[..at])
+}
+
+/// Walks a parsed manifest, collecting errors
+struct Validator {
+    positions: Positions,
+    errors: Vec<ManifestError>,
+}
+
+/// A field being validated: its JSON pointer and the path shown in errors
+#[derive(Clone)]
+struct Field {
+    pointer: String,
+    path: String,
+}
+
+impl Field {
+    fn root() -> Self {
+        Self {
+            pointer: String::new(),
+            path: String::new(),
+        }
+    }
+
+    fn key(&self, key: &str) -> Self {
+        Self {
+            pointer: format!("{}/{}", self.pointer, key.replace('~', "~0").replace('/', "~1")),
+            path: if self.path.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", self.path, key)
+            },
+        }
+    }
+
+    fn index(&self, index: usize) -> Self {
+        Self {
+            pointer: format!("{}/{}", self.pointer, index),
+            path: format!("{}[{}]", self.path, index),
+        }
+    }
+}
+
+impl Validator {
+    fn error(&mut self, field: &Field, message: impl Into<String>) {
+        let (line, column) = self.positions.get(&field.pointer);
+        self.errors.push(ManifestError {
+            line,
+            column,
+            path: field.path.clone(),
+            message: message.into(),
+        });
+    }
+
+    fn manifest(&mut self, value: &Value) {
+        let root = Field::root();
+        let Some(object) = self.object(&root, value) else {
+            return;
+        };
+        for required in ["name", "version"] {
+            if !object.contains_key(required) {
+                self.error(&root, format!("missing required field '{}'", required));
+            }
+        }
+
+        for (key, value) in object {
+            let field = root.key(key);
+            match key.as_str() {
+                "name" => {
+                    self.non_empty_string(&field, value);
+                },
+                "version" | "plugin_version" => {
+                    if let Some(version) = self.string(&field, value) {
+                        if !is_valid_version(version) {
+                            self.error(
+                                &field,
+                                format!("'{}' is not a version of the form MAJOR.MINOR.PATCH", version),
+                            );
+                        }
+                    }
+                },
+                "description" | "license" => {
+                    self.string(&field, value);
+                },
+                "capabilities" => {
+                    for (field, capability) in self.array(&field, value) {
+                        if let Some(capability) = self.string(&field, capability) {
+                            if !is_known_capability(capability) {
+                                self.error(
+                                    &field,
+                                    format!(
+                                        "unknown capability '{}' (expected one of {}, or {}<name> for a custom op)",
+                                        capability,
+                                        KNOWN_CAPABILITIES.join(", "),
+                                        methods::CUSTOM_OP_PREFIX
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                },
+                "devices" | "extensions" | "dependencies" => self.strings(&field, value),
+                "supported_targets" => {
+                    for (field, target) in self.array(&field, value) {
+                        self.target(&field, target);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    fn target(&mut self, field: &Field, value: &Value) {
+        let Some(object) = self.object(field, value) else {
+            return;
+        };
+        if !object.contains_key("triple") {
+            self.error(field, "missing required field 'triple'");
+        }
+        for (key, value) in object {
+            let field = field.key(key);
+            match key.as_str() {
+                "triple" => {
+                    self.non_empty_string(&field, value);
+                },
+                "requires" | "host_only" => self.strings(&field, value),
+                _ => {},
+            }
+        }
+    }
+
+    fn object<'v>(&mut self, field: &Field, value: &'v Value) -> Option<&'v serde_json::Map<String, Value>> {
+        let object = value.as_object();
+        if object.is_none() {
+            self.error(field, format!("expected an object, found {}", kind(value)));
+        }
+        object
+    }
+
+    fn array<'v>(&mut self, field: &Field, value: &'v Value) -> Vec<(Field, &'v Value)> {
+        match value.as_array() {
+            Some(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (field.index(i), item))
+                .collect(),
+            None => {
+                self.error(field, format!("expected an array, found {}", kind(value)));
+                Vec::new()
+            },
+        }
+    }
+
+    fn string<'v>(&mut self, field: &Field, value: &'v Value) -> Option<&'v str> {
+        let string = value.as_str();
+        if string.is_none() {
+            self.error(field, format!("expected a string, found {}", kind(value)));
+        }
+        string
+    }
+
+    fn non_empty_string(&mut self, field: &Field, value: &Value) {
+        if self.string(field, value).is_some_and(str::is_empty) {
+            self.error(field, "must not be empty");
+        }
+    }
+
+    fn strings(&mut self, field: &Field, value: &Value) {
+        for (field, item) in self.array(field, value) {
+            self.non_empty_string(&field, item);
+        }
+    }
+}
+
+/// Describe a value's JSON type for error messages
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Line and column of every value in a JSON document, by JSON pointer
+struct Positions(HashMap<String, (usize, usize)>);
+
+impl Positions {
+    /// Record where each value of `source` starts; `source` must be valid JSON
+    fn scan(source: &str) -> Self {
+        let mut scanner = Scanner {
+            chars: source.chars().peekable(),
+            line: 1,
+            column: 1,
+            positions: HashMap::new(),
+        };
+        scanner.value(String::new());
+        Self(scanner.positions)
+    }
+
+    fn get(&self, pointer: &str) -> (usize, usize) {
+        self.0.get(pointer).copied().unwrap_or((1, 1))
+    }
+}
+
+struct Scanner<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+    positions: HashMap<String, (usize, usize)>,
+}
+
+impl Scanner<'_> {
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn value(&mut self, pointer: String) {
+        self.skip_whitespace();
+        let Some(&first) = self.chars.peek() else {
+            return;
+        };
+        self.positions.insert(pointer.clone(), (self.line, self.column));
+        match first {
+            '{' => {
+                self.bump();
+                loop {
+                    self.skip_whitespace();
+                    match self.chars.peek() {
+                        Some('"') => {
+                            let key = self.string();
+                            self.skip_whitespace();
+                            self.bump(); // ':'
+                            self.value(format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1")));
+                            self.skip_whitespace();
+                            if self.chars.peek() == Some(&',') {
+                                self.bump();
+                            }
+                        },
+                        Some(_) => {
+                            self.bump(); // '}'
+                            return;
+                        },
+                        None => return,
+                    }
+                }
+            },
+            '[' => {
+                self.bump();
+                let mut index = 0;
+                loop {
+                    self.skip_whitespace();
+                    match self.chars.peek() {
+                        Some(']') => {
+                            self.bump();
+                            return;
+                        },
+                        Some(_) => {
+                            self.value(format!("{}/{}", pointer, index));
+                            index += 1;
+                            self.skip_whitespace();
+                            if self.chars.peek() == Some(&',') {
+                                self.bump();
+                            }
+                        },
+                        None => return,
+                    }
+                }
+            },
+            '"' => {
+                self.string();
+            },
+            _ => {
+                while self
+                    .chars
+                    .peek()
+                    .is_some_and(|c| !c.is_whitespace() && !matches!(c, ',' | ']' | '}'))
+                {
+                    self.bump();
+                }
+            },
+        }
+    }
+
+    /// Read a string, returning its unescaped contents
+    fn string(&mut self) -> String {
+        let mut contents = String::new();
+        self.bump(); // opening quote
+        while let Some(c) = self.bump() {
+            match c {
+                '"' => break,
+                '\\' => match self.bump() {
+                    Some('n') => contents.push('\n'),
+                    Some('t') => contents.push('\t'),
+                    Some('r') => contents.push('\r'),
+                    Some('b') => contents.push('\u{8}'),
+                    Some('f') => contents.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.bump()).collect();
+                        let decoded = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                        contents.push(decoded.unwrap_or(char::REPLACEMENT_CHARACTER));
+                    },
+                    Some(escaped) => contents.push(escaped),
+                    None => break,
+                },
+                c => contents.push(c),
+            }
+        }
+        contents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_manifest() {
+        let source = r#"{
+            "name": "hodu-backend-cpu",
+            "version": "0.1.0-beta.1",
+            "plugin_version": "0.1.0",
+            "capabilities": ["backend.run", "backend.build", "op.fused_gelu"],
+            "devices": ["cpu"],
+            "supported_targets": [{ "triple": "x86_64-unknown-linux-gnu", "requires": ["clang|gcc"] }],
+            "homepage": "https://example.com"
+        }"#;
+        assert_eq!(validate_manifest(source), Vec::new());
+    }
+
+    #[test]
+    fn test_errors_point_at_values() {
+        let source = "{\n  \"name\": \"my-plugin\",\n  \"version\": \"1.0\",\n  \"capabilities\": [\"backend.run\", \"backend.rnu\"],\n  \"supported_targets\": [{ \"requires\": \"clang\" }]\n}";
+        let errors = validate_manifest(source);
+        let found: Vec<(usize, usize, &str)> = errors.iter().map(|e| (e.line, e.column, e.path.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (3, 14, "version"),
+                (4, 35, "capabilities[1]"),
+                (5, 25, "supported_targets[0]"),
+                (5, 39, "supported_targets[0].requires"),
+            ]
+        );
+        assert!(errors[1].message.contains("unknown capability 'backend.rnu'"));
+        assert_eq!(
+            errors[3].to_string(),
+            "5:39: supported_targets[0].requires: expected an array, found a string"
+        );
+    }
+
+    #[test]
+    fn test_missing_fields_and_syntax_errors() {
+        let errors = validate_manifest("\n  { \"capabilities\": [] }");
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0].line, errors[0].column), (2, 3));
+        assert!(errors[0].message.contains("'name'"));
+
+        let errors = validate_manifest("{\n  \"name\": \"x\",\n  \"version\": 1.0.0\n}");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 3);
+        assert!(errors[0].message.starts_with("invalid JSON"));
+        assert!(!errors[0].message.contains("at line"));
+    }
+
+    #[test]
+    fn test_schema_matches_validation() {
+        let schema = manifest_schema();
+        assert_eq!(schema["required"], json!(["name", "version"]));
+        let properties = schema["properties"].as_object().unwrap();
+        for field in [
+            "name",
+            "version",
+            "description",
+            "license",
+            "plugin_version",
+            "capabilities",
+            "devices",
+            "extensions",
+            "dependencies",
+            "supported_targets",
+        ] {
+            assert!(properties.contains_key(field), "{} missing from schema", field);
+        }
+        let capabilities = &schema["properties"]["capabilities"]["items"]["anyOf"];
+        assert_eq!(
+            capabilities[0]["enum"].as_array().unwrap().len(),
+            KNOWN_CAPABILITIES.len()
+        );
+        assert_eq!(capabilities[1]["pattern"], r"^op\..+$");
+
+        assert!(is_valid_version("1.2.3") && is_valid_version("1.2.3+build.5"));
+        assert!(!is_valid_version("1.2") && !is_valid_version("1.2.x") && !is_valid_version("1.2.3-"));
+    }
+}
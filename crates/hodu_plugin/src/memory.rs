@@ -0,0 +1,67 @@
+//! Process memory usage
+//!
+//! Read from `/proc/self/status` on Linux. Other platforms report no memory usage.
+
+/// Resident memory of the process in bytes
+pub fn resident_memory() -> Option<u64> {
+    proc_status_bytes("VmRSS:")
+}
+
+/// Peak resident memory of the process in bytes
+pub fn peak_memory() -> Option<u64> {
+    proc_status_bytes("VmHWM:")
+}
+
+/// Reset the process's peak resident memory to its current resident memory
+///
+/// Call it before the work to measure, so [`peak_memory`] covers that work only. Best effort:
+/// when the kernel refuses, the peak keeps covering the whole process lifetime.
+#[cfg(target_os = "linux")]
+pub fn reset_peak_memory() {
+    // Writing 5 to clear_refs resets VmHWM to the current resident size
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn reset_peak_memory() {}
+
+/// A memory field of /proc/self/status in bytes
+#[cfg(target_os = "linux")]
+fn proc_status_bytes(field: &str) -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with(field))?;
+    let kib: u64 = line
+        .trim_start_matches(field)
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn proc_status_bytes(_field: &str) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_peak_memory_covers_resident_memory() {
+        let resident = resident_memory().unwrap();
+        let peak = peak_memory().unwrap();
+        assert!(resident > 0);
+        assert!(peak >= resident);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "linux"))]
+    fn test_memory_unavailable() {
+        assert_eq!(resident_memory(), None);
+        assert_eq!(peak_memory(), None);
+    }
+}
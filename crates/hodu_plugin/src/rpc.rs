@@ -2,6 +2,9 @@
 //!
 //! This module defines the message types for CLI <-> Plugin communication over stdio.
 
+use crate::framing::Framing;
+use crate::sandbox::SandboxPolicy;
+use crate::tensor::{PluginDType, TensorData};
 use serde::{Deserialize, Serialize};
 
 /// JSON-RPC version string
@@ -17,6 +20,9 @@ pub const PROTOCOL_VERSION: &str = "1.0.0";
 /// Maximum number of capabilities a plugin can declare
 pub const MAX_CAPABILITIES: usize = 50;
 
+/// Maximum number of protocol features either side can list
+pub const MAX_FEATURES: usize = 50;
+
 /// Maximum number of extensions (model or tensor) a plugin can support
 pub const MAX_EXTENSIONS: usize = 100;
 
@@ -39,7 +45,18 @@ pub const MAX_TENSOR_NAME_LEN: usize = 255;
 pub const MAX_ERROR_STRING_LEN: usize = 64 * 1024;
 
 /// Reserved field names in error data
-const RESERVED_ERROR_FIELDS: &[&str] = &["cause", "hints", "details", "context", "original_data"];
+const RESERVED_ERROR_FIELDS: &[&str] = &[
+    "cause",
+    "hints",
+    "details",
+    "context",
+    "original_data",
+    "kind",
+    "retryable",
+    "node",
+    "tensor",
+    "retry_after_ms",
+];
 
 /// Maximum metadata description length (1KB)
 pub const MAX_METADATA_DESCRIPTION_LEN: usize = 1024;
@@ -59,6 +76,31 @@ pub const MAX_METADATA_VERSION_LEN: usize = 64;
 /// Maximum target triple length (128 bytes)
 pub const MAX_TARGET_TRIPLE_LEN: usize = 128;
 
+/// Maximum length for session IDs
+pub const MAX_SESSION_ID_LEN: usize = 128;
+
+/// Maximum timed or warmup iterations of one benchmark (100,000)
+pub const MAX_BENCHMARK_ITERATIONS: u32 = 100_000;
+
+/// Maximum bytes of tensor data carried inline by one streamed tensor chunk (4MB)
+///
+/// Base64 grows the data by a third, keeping each chunk well under the 10MB stream item limit.
+pub const MAX_TENSOR_CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Default largest tensor, in bytes of data, either side accepts inline (64KB)
+pub const DEFAULT_INLINE_TENSOR_LIMIT: u64 = 64 * 1024;
+
+/// Maximum inline tensor limit either side may offer (4MB)
+///
+/// Like streamed chunks, inline tensors stay well under the 16MB message limit after base64.
+pub const MAX_INLINE_TENSOR_LIMIT: u64 = MAX_TENSOR_CHUNK_SIZE;
+
+/// Maximum calibration samples in a quantize request
+pub const MAX_CALIBRATION_SAMPLES: usize = 10_000;
+
+/// Maximum bit width of a quantized tensor
+pub const MAX_QUANTIZE_BITS: u8 = 16;
+
 /// Truncate a string to at most `max_bytes` bytes, respecting UTF-8 character boundaries.
 ///
 /// Returns a new String if truncation is needed, or the original String if within limit.
@@ -538,6 +580,237 @@ pub mod error_codes {
     pub const TENSOR_ERROR: i32 = -32006;
     /// Request was cancelled by client
     pub const REQUEST_CANCELLED: i32 = -32007;
+    /// Missing or wrong auth token for a plugin that requires one
+    pub const UNAUTHORIZED: i32 = -32008;
+    /// Plugin is at its rate or concurrency limit for the method; retry later
+    pub const BUSY: i32 = -32009;
+    /// Plugin is draining in-flight work after `shutdown` and takes no new requests
+    pub const SHUTTING_DOWN: i32 = -32010;
+    /// No open session has the given ID (never loaded, closed, or lost with a plugin restart)
+    pub const SESSION_NOT_FOUND: i32 = -32011;
+    /// A path is outside the directories the plugin's sandbox allows
+    pub const ACCESS_DENIED: i32 = -32012;
+    /// The plugin ran out of the memory or CPU time its limits allow
+    pub const RESOURCE_EXHAUSTED: i32 = -32013;
+
+    /// What an error code means, and what usually fixes it
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ErrorCodeInfo {
+        /// The code
+        pub code: i32,
+        /// Constant name of the code, e.g. `"TENSOR_ERROR"`
+        pub name: &'static str,
+        /// What went wrong
+        pub description: &'static str,
+        /// Whether the same request may succeed if sent again, unless the error says otherwise
+        pub retryable: bool,
+        /// What usually fixes it
+        pub remediation: &'static str,
+    }
+
+    /// Every code above, for tools that explain errors without hard-coding them
+    pub const CATALOG: &[ErrorCodeInfo] = &[
+        ErrorCodeInfo {
+            code: PARSE_ERROR,
+            name: "PARSE_ERROR",
+            description: "invalid JSON was received",
+            retryable: false,
+            remediation: "The CLI and plugin disagree on the wire format; reinstall the plugin",
+        },
+        ErrorCodeInfo {
+            code: INVALID_REQUEST,
+            name: "INVALID_REQUEST",
+            description: "the message is not a valid request",
+            retryable: false,
+            remediation: "The CLI and plugin disagree on the protocol; update the plugin",
+        },
+        ErrorCodeInfo {
+            code: METHOD_NOT_FOUND,
+            name: "METHOD_NOT_FOUND",
+            description: "the plugin does not implement the method",
+            retryable: false,
+            remediation: "Use a plugin that lists the method in its capabilities",
+        },
+        ErrorCodeInfo {
+            code: INVALID_PARAMS,
+            name: "INVALID_PARAMS",
+            description: "the request parameters are invalid",
+            retryable: false,
+            remediation: "Check the arguments against the command's --help",
+        },
+        ErrorCodeInfo {
+            code: INTERNAL_ERROR,
+            name: "INTERNAL_ERROR",
+            description: "the plugin failed internally",
+            retryable: false,
+            remediation: "Report it to the plugin's authors, with the plugin log if there is one",
+        },
+        ErrorCodeInfo {
+            code: PLUGIN_ERROR,
+            name: "PLUGIN_ERROR",
+            description: "the plugin reported an error",
+            retryable: false,
+            remediation: "See the message for details",
+        },
+        ErrorCodeInfo {
+            code: NOT_SUPPORTED,
+            name: "NOT_SUPPORTED",
+            description: "the plugin does not support the feature",
+            retryable: false,
+            remediation: "Use another plugin, or leave out the option needing the feature",
+        },
+        ErrorCodeInfo {
+            code: FILE_NOT_FOUND,
+            name: "FILE_NOT_FOUND",
+            description: "a file does not exist",
+            retryable: false,
+            remediation: "Check the path exists and is readable",
+        },
+        ErrorCodeInfo {
+            code: INVALID_FORMAT,
+            name: "INVALID_FORMAT",
+            description: "a file is not in the expected format",
+            retryable: false,
+            remediation: "Check the file extension matches its contents",
+        },
+        ErrorCodeInfo {
+            code: DEVICE_NOT_AVAILABLE,
+            name: "DEVICE_NOT_AVAILABLE",
+            description: "the device is not available",
+            retryable: false,
+            remediation: "Pick an available device; `hodu doctor` lists them",
+        },
+        ErrorCodeInfo {
+            code: MODEL_ERROR,
+            name: "MODEL_ERROR",
+            description: "the model could not be loaded or processed",
+            retryable: false,
+            remediation: "Check the model with `hodu inspect`",
+        },
+        ErrorCodeInfo {
+            code: TENSOR_ERROR,
+            name: "TENSOR_ERROR",
+            description: "a tensor could not be loaded or processed",
+            retryable: false,
+            remediation: "Check the tensor's shape and dtype match what the model expects",
+        },
+        ErrorCodeInfo {
+            code: REQUEST_CANCELLED,
+            name: "REQUEST_CANCELLED",
+            description: "the request was cancelled",
+            retryable: true,
+            remediation: "Run the command again to retry",
+        },
+        ErrorCodeInfo {
+            code: UNAUTHORIZED,
+            name: "UNAUTHORIZED",
+            description: "the auth token is missing or wrong",
+            retryable: false,
+            remediation: "Set the token the plugin daemon was started with",
+        },
+        ErrorCodeInfo {
+            code: BUSY,
+            name: "BUSY",
+            description: "the plugin is at its rate or concurrency limit",
+            retryable: true,
+            remediation: "Retry after a short wait, or send fewer requests at once",
+        },
+        ErrorCodeInfo {
+            code: SHUTTING_DOWN,
+            name: "SHUTTING_DOWN",
+            description: "the plugin is shutting down",
+            retryable: true,
+            remediation: "Retry once the plugin has restarted",
+        },
+        ErrorCodeInfo {
+            code: SESSION_NOT_FOUND,
+            name: "SESSION_NOT_FOUND",
+            description: "no open session has the ID",
+            retryable: false,
+            remediation: "Load the model into a new session",
+        },
+        ErrorCodeInfo {
+            code: ACCESS_DENIED,
+            name: "ACCESS_DENIED",
+            description: "a path is outside the plugin's sandbox",
+            retryable: false,
+            remediation: "Use paths under the current directory, or trust the plugin with `hodu plugin trust <name>`",
+        },
+        ErrorCodeInfo {
+            code: RESOURCE_EXHAUSTED,
+            name: "RESOURCE_EXHAUSTED",
+            description: "the plugin exceeded its memory or CPU time limit",
+            retryable: false,
+            remediation: "Use a smaller model or input, or raise the limit with `hodu plugin limit <name>`",
+        },
+    ];
+
+    /// Look up a code in the [`CATALOG`]
+    pub fn lookup(code: i32) -> Option<&'static ErrorCodeInfo> {
+        CATALOG.iter().find(|info| info.code == code)
+    }
+}
+
+/// What kind of failure an error is, finer-grained than its code
+///
+/// Sent as `data.kind`. Kinds added later read as [`ErrorKind::Other`] in older peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// A tensor has a different shape than expected
+    ShapeMismatch,
+    /// A tensor has a different dtype than expected
+    DtypeMismatch,
+    /// A required input tensor was not given
+    MissingInput,
+    /// The model uses an op the plugin cannot run
+    UnsupportedOp,
+    /// The model uses a dtype the plugin or device cannot handle
+    UnsupportedDtype,
+    /// The device or host ran out of memory
+    OutOfMemory,
+    /// The device failed or disappeared mid-request
+    DeviceLost,
+    /// An operation took longer than allowed
+    Timeout,
+    /// Reading or writing a file failed
+    Io,
+    /// The model is malformed
+    InvalidModel,
+    /// A bug in the plugin
+    Internal,
+    /// Any other failure
+    #[serde(other)]
+    Other,
+}
+
+/// Structured view of [`RpcError::data`]
+///
+/// Every field is optional, so errors from plugins that attach nothing, or attach other fields,
+/// read as an empty `ErrorData`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ErrorData {
+    /// Kind of failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ErrorKind>,
+    /// Whether sending the same request again may succeed (the code's default if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retryable: Option<bool>,
+    /// Name of the graph node that failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// Name of the tensor at fault
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tensor: Option<String>,
+    /// Underlying causes, innermost last (see [`RpcError::with_cause`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+    /// Recovery hints (see [`RpcError::with_hint`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<String>,
+    /// When to retry a [`BUSY`](error_codes::BUSY) error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
 }
 
 // ============================================================================
@@ -559,6 +832,8 @@ pub mod methods {
     pub const FORMAT_LOAD_TENSOR: &str = "format.load_tensor";
     /// Save a tensor to file
     pub const FORMAT_SAVE_TENSOR: &str = "format.save_tensor";
+    /// Load a tensor file, streaming its data in chunks
+    pub const FORMAT_STREAM_LOAD_TENSOR: &str = "format.stream_load_tensor";
 
     /// Run model inference
     pub const BACKEND_RUN: &str = "backend.run";
@@ -568,14 +843,109 @@ pub mod methods {
     pub const BACKEND_SUPPORTED_DEVICES: &str = "backend.supported_devices";
     /// Query supported build targets
     pub const BACKEND_SUPPORTED_TARGETS: &str = "backend.supported_targets";
+    /// Load a compiled model once and keep it for later runs
+    pub const BACKEND_LOAD_SESSION: &str = "backend.load_session";
+    /// Run inference on a loaded session
+    pub const BACKEND_RUN_SESSION: &str = "backend.run_session";
+    /// Release a loaded session
+    pub const BACKEND_CLOSE_SESSION: &str = "backend.close_session";
+    /// Time repeated runs of a compiled model
+    pub const BACKEND_BENCHMARK: &str = "backend.benchmark";
+    /// Run inference once, recording per-op timings and memory
+    pub const BACKEND_PROFILE: &str = "backend.profile";
+    /// Quantize a snapshot, calibrating activations on sample inputs
+    pub const BACKEND_QUANTIZE: &str = "backend.quantize";
+
+    /// Prefix of custom op methods; a plugin declaring `op.<name>` executes the custom op `<name>`
+    pub const CUSTOM_OP_PREFIX: &str = "op.";
 
     /// Progress notification (plugin -> CLI)
     pub const NOTIFY_PROGRESS: &str = "$/progress";
     /// Log message notification (plugin -> CLI)
     pub const NOTIFY_LOG: &str = "$/log";
+    /// Streamed result notification (plugin -> CLI)
+    pub const NOTIFY_STREAM: &str = "$/stream";
 
     /// Cancel a running request (CLI -> plugin)
     pub const CANCEL: &str = "$/cancel";
+    /// Allow a stream to send more chunks (CLI -> plugin)
+    pub const STREAM_CREDIT: &str = "$/stream_credit";
+    /// Fetch request metrics in Prometheus text format (CLI -> plugin)
+    pub const METRICS: &str = "$/metrics";
+    /// Stop a plugin daemon once the current connection ends (CLI -> plugin)
+    pub const EXIT: &str = "$/exit";
+    /// Report uptime, in-flight requests, and memory use (CLI -> plugin)
+    pub const STATUS: &str = "$/status";
+
+    /// Load a model file through the format plugin for its extension (plugin -> CLI)
+    pub const HOST_LOAD_MODEL: &str = "host.load_model";
+    /// Load a tensor file through the format plugin for its extension (plugin -> CLI)
+    pub const HOST_LOAD_TENSOR: &str = "host.load_tensor";
+}
+
+// ============================================================================
+// Daemon Mode
+// ============================================================================
+
+/// Environment variable asking a plugin to serve as a daemon at an address
+///
+/// Holds `unix:///path/to/socket` or `tcp://host:port`. The CLI sets it on plugins it keeps
+/// pooled across commands, and the SDK's `run()` listens there instead of serving stdio.
+pub const LISTEN_ENV: &str = "HODU_PLUGIN_LISTEN";
+
+/// Environment variable with the seconds a plugin daemon waits for a connection before exiting
+pub const IDLE_TIMEOUT_ENV: &str = "HODU_PLUGIN_IDLE_TIMEOUT";
+
+// ============================================================================
+// Feature Flags
+// ============================================================================
+
+/// Optional protocol features negotiated in `initialize`
+///
+/// The CLI lists the features it supports in `InitializeParams::features` and the plugin answers
+/// with those it supports too in `InitializeResult::features`. Either side only uses a feature
+/// once it is negotiated, so new features roll out without breaking older peers, which never
+/// list them.
+pub mod features {
+    /// `$/stream` notifications carrying results before the response
+    pub const STREAMING: &str = "streaming";
+    /// Credit-based flow control for `$/stream`
+    ///
+    /// Each stream may send [`INITIAL_STREAM_CREDITS`](super::INITIAL_STREAM_CREDITS) chunks
+    /// after it opens, and one more for every credit the CLI grants with `$/stream_credit` as it
+    /// consumes them, so a fast plugin cannot fill the CLI's memory with unread chunks.
+    pub const STREAM_CREDITS: &str = "stream_credits";
+    /// `host.*` requests from the plugin while the CLI waits on a call
+    pub const HOST_CALLS: &str = "host_calls";
+    /// Tensor inputs and outputs embedded in messages instead of written to files
+    ///
+    /// Each side states the largest tensor it accepts inline in `inline_tensor_limit`; the
+    /// smaller of the two applies (see [`negotiate_inline_tensor_limit`](super::negotiate_inline_tensor_limit)).
+    pub const INLINE_TENSORS: &str = "inline_tensors";
+}
+
+/// Features in both `ours` and `theirs`, in the order of `ours`
+pub fn negotiate_features<S: AsRef<str>>(ours: &[S], theirs: &[String]) -> Vec<String> {
+    let mut negotiated: Vec<String> = Vec::new();
+    for feature in ours.iter().map(AsRef::as_ref) {
+        if theirs.iter().any(|theirs| theirs == feature) && !negotiated.iter().any(|f| f == feature) {
+            negotiated.push(feature.to_string());
+        }
+    }
+    negotiated
+}
+
+/// Largest tensor, in bytes of data, that may be sent inline once both sides offered
+/// [`features::INLINE_TENSORS`] with these limits (the default for an unstated limit)
+///
+/// Returns 0, meaning files only, when the feature was not negotiated.
+pub fn negotiate_inline_tensor_limit(negotiated: bool, ours: Option<u64>, theirs: Option<u64>) -> u64 {
+    if !negotiated {
+        return 0;
+    }
+    let ours = ours.unwrap_or(DEFAULT_INLINE_TENSOR_LIMIT);
+    let theirs = theirs.unwrap_or(DEFAULT_INLINE_TENSOR_LIMIT);
+    ours.min(theirs).min(MAX_INLINE_TENSOR_LIMIT)
 }
 
 // ============================================================================
@@ -606,6 +976,12 @@ pub mod methods {
 /// let params = InitializeParams {
 ///     plugin_version: "0.1.0".to_string(),
 ///     protocol_version: "1.0.0".to_string(),
+///     framings: vec![Framing::ContentLength],
+///     auth_token: None,
+///     config: None,
+///     features: vec![features::STREAMING.to_string()],
+///     inline_tensor_limit: None,
+///     sandbox: None,
 /// };
 /// assert!(params.validate().is_ok());
 /// ```
@@ -621,6 +997,27 @@ pub struct InitializeParams {
     /// Plugins should verify this matches their expected protocol version.
     /// Protocol version changes indicate changes to the RPC message format.
     pub protocol_version: String,
+    /// Framings the CLI can read, most preferred first
+    ///
+    /// Empty for CLIs that only speak line-delimited JSON.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub framings: Vec<Framing>,
+    /// Shared secret for plugins served over a socket with an auth token configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_token: Option<String>,
+    /// Plugin configuration, from the user's config file and command-line overrides
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<serde_json::Value>,
+    /// Optional protocol features the CLI supports (see [`features`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Largest tensor in bytes the CLI accepts inline with [`features::INLINE_TENSORS`]
+    /// ([`DEFAULT_INLINE_TENSOR_LIMIT`] if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_tensor_limit: Option<u64>,
+    /// Directories the plugin may read and write; absent for trusted plugins, which may use any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxPolicy>,
 }
 
 impl InitializeParams {
@@ -845,6 +1242,21 @@ pub struct InitializeResult {
     /// Optional plugin metadata
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<PluginMetadataRpc>,
+    /// Framing both sides write with after this response
+    ///
+    /// Absent from plugins that predate negotiation, which only speak line-delimited JSON.
+    #[serde(default)]
+    pub framing: Framing,
+    /// JSON Schema of the configuration the plugin accepts in `initialize`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_schema: Option<serde_json::Value>,
+    /// Protocol features both sides support, which either side may use from now on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Largest tensor in bytes the plugin accepts inline with [`features::INLINE_TENSORS`]
+    /// ([`DEFAULT_INLINE_TENSOR_LIMIT`] if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_tensor_limit: Option<u64>,
 }
 
 impl InitializeResult {
@@ -871,6 +1283,12 @@ impl InitializeResult {
                 ),
             ));
         }
+        if self.features.len() > MAX_FEATURES {
+            return Err(ValidationError::too_many_items(
+                "features",
+                format!("too many features ({} > {})", self.features.len(), MAX_FEATURES),
+            ));
+        }
         if let Some(ref exts) = self.model_extensions {
             if exts.len() > MAX_EXTENSIONS {
                 return Err(ValidationError::too_many_items(
@@ -969,6 +1387,71 @@ pub struct LoadTensorResult {
     pub tensor_path: String,
 }
 
+/// Name of the `$/stream` carrying the tensor of `format.stream_load_tensor`
+pub const TENSOR_STREAM: &str = "tensor";
+
+/// Stream load tensor request params
+///
+/// Request to load a tensor file too large to convert in one piece. The plugin sends the tensor
+/// as [`TensorChunk`] items on the [`TENSOR_STREAM`] stream of the request: the header first,
+/// then the data in order, before responding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamLoadTensorParams {
+    /// Path to the input tensor file
+    pub path: String,
+    /// Preferred bytes of data per inline chunk (plugin default if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_size: Option<u64>,
+}
+
+impl StreamLoadTensorParams {
+    /// Validate the parameters
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_path(&self.path, "path")?;
+        if let Some(chunk_size) = self.chunk_size {
+            if chunk_size == 0 || chunk_size > MAX_TENSOR_CHUNK_SIZE {
+                return Err(ValidationError::out_of_range(
+                    "chunk_size",
+                    format!("chunk_size must be 1-{}, got {}", MAX_TENSOR_CHUNK_SIZE, chunk_size),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One item of a streamed tensor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TensorChunk {
+    /// Shape and dtype of the tensor, always the first item
+    Header { shape: Vec<usize>, dtype: PluginDType },
+    /// Raw little-endian tensor data starting at byte `offset`, base64 encoded
+    Data { offset: u64, data: String },
+    /// `len` bytes of tensor data starting at byte `offset`, written to the start of the file at
+    /// `path` (e.g. under /dev/shm) instead of sent inline
+    ///
+    /// The CLI deletes the file once it has read it.
+    Shared { offset: u64, path: String, len: u64 },
+}
+
+impl TensorChunk {
+    /// Inline chunk of `data` starting at byte `offset`
+    pub fn data(offset: u64, data: &[u8]) -> Self {
+        Self::Data {
+            offset,
+            data: crate::base64::encode(data),
+        }
+    }
+}
+
+/// Stream load tensor response result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamLoadTensorResult {
+    /// Bytes of tensor data sent, for the CLI to check nothing was lost
+    pub total_bytes: u64,
+}
+
 /// Save tensor request params
 ///
 /// Request to convert a tensor to a specific format.
@@ -1001,6 +1484,9 @@ pub struct RunParams {
     pub device: String,
     /// Input tensors to feed into the model
     pub inputs: Vec<TensorInput>,
+    /// Precision overrides for this run (backend defaults if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<PrecisionParams>,
 }
 
 impl RunParams {
@@ -1057,13 +1543,34 @@ impl RunParams {
     }
 }
 
+/// Precision overrides for a run
+///
+/// Unset fields keep the backend's current setting. Backends without TF32 or 16-bit
+/// accumulation ignore the corresponding field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrecisionParams {
+    /// Let f32 matmul and convolution run on TF32 tensor cores
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_tf32: Option<bool>,
+    /// Accumulate f16/bf16 products in f32
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub f16_accumulate_f32: Option<bool>,
+}
+
 /// Input tensor reference for model execution
+///
+/// The tensor is either in the .hdt file at `path`, or, once [`features::INLINE_TENSORS`] is
+/// negotiated, carried in `data` with an empty `path`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TensorInput {
     /// Input tensor name (must match model input name)
     pub name: String,
-    /// Path to tensor file (.hdt)
+    /// Path to tensor file (.hdt), empty for an inline tensor
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub path: String,
+    /// The tensor itself, for small tensors sent inline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<InlineTensor>,
 }
 
 impl TensorInput {
@@ -1072,6 +1579,16 @@ impl TensorInput {
         Self {
             name: name.into(),
             path: path.into(),
+            data: None,
+        }
+    }
+
+    /// Create new tensor input carrying `tensor` inline
+    pub fn inline(name: impl Into<String>, tensor: &TensorData) -> Self {
+        Self {
+            name: name.into(),
+            path: String::new(),
+            data: Some(InlineTensor::from(tensor)),
         }
     }
 
@@ -1100,173 +1617,824 @@ impl TensorInput {
                 ),
             ));
         }
-        validate_path(&self.path, "path")
+        validate_tensor_location(&self.path, self.data.as_ref())
     }
 }
 
-/// Backend run response result
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RunResult {
-    /// Output tensors from model execution
-    pub outputs: Vec<TensorOutput>,
-}
-
-/// Output tensor reference from model execution
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TensorOutput {
-    /// Output tensor name
-    pub name: String,
-    /// Path to output tensor file (.hdt)
-    pub path: String,
+/// A tensor embedded in a message, for tensors below the negotiated inline limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InlineTensor {
+    /// Tensor shape
+    pub shape: Vec<usize>,
+    /// Element type
+    pub dtype: PluginDType,
+    /// Raw little-endian tensor data, base64 encoded
+    pub data: String,
 }
 
-impl TensorOutput {
-    /// Create new tensor output
-    pub fn new(name: impl Into<String>, path: impl Into<String>) -> Self {
-        Self {
-            name: name.into(),
-            path: path.into(),
-        }
+impl InlineTensor {
+    /// Bytes of tensor data once decoded
+    pub fn size_bytes(&self) -> usize {
+        self.data.len() / 4 * 3 - self.data.bytes().rev().take_while(|&b| b == b'=').count()
     }
 
-    /// Create new tensor output with validation
-    ///
-    /// Returns `Err(ValidationError)` if the name or path is invalid.
-    pub fn new_checked(name: impl Into<String>, path: impl Into<String>) -> Result<Self, ValidationError> {
-        let output = Self::new(name, path);
-        output.validate()?;
-        Ok(output)
+    /// Decode the tensor, checking its data matches its shape and dtype
+    pub fn decode(&self) -> Result<TensorData, ValidationError> {
+        let data = crate::base64::decode(&self.data).map_err(|e| ValidationError::other("data", e.to_string()))?;
+        TensorData::new_checked(data, self.shape.clone(), self.dtype)
+            .map_err(|e| ValidationError::other("data", e.to_string()))
     }
+}
 
-    /// Validate the tensor output
-    pub fn validate(&self) -> Result<(), ValidationError> {
-        if !self.is_valid_name() {
-            return Err(ValidationError::invalid_chars(
-                "name",
-                format!(
-                    "invalid tensor name (must be non-empty, max {} bytes, no control chars or path separators)",
-                    MAX_TENSOR_NAME_LEN
-                ),
-            ));
+impl From<&TensorData> for InlineTensor {
+    fn from(tensor: &TensorData) -> Self {
+        Self {
+            shape: tensor.shape.clone(),
+            dtype: tensor.dtype,
+            data: crate::base64::encode(&tensor.data),
         }
-        validate_path(&self.path, "path")
     }
+}
 
-    /// Validate tensor name (non-empty, no control chars, no path separators, within length limit)
-    pub fn is_valid_name(&self) -> bool {
-        is_valid_tensor_name(&self.name)
+/// Validate that a tensor is either in a file or inline, and not both
+fn validate_tensor_location(path: &str, data: Option<&InlineTensor>) -> Result<(), ValidationError> {
+    match data {
+        Some(_) if !path.is_empty() => Err(ValidationError::other("path", "an inline tensor must not have a path")),
+        Some(inline) if !inline.data.len().is_multiple_of(4) => {
+            Err(ValidationError::other("data", "inline tensor data is not valid base64"))
+        },
+        Some(inline) if inline.size_bytes() as u64 > MAX_INLINE_TENSOR_LIMIT => Err(ValidationError::too_long(
+            "data",
+            format!(
+                "inline tensor too large ({} > {} bytes)",
+                inline.size_bytes(),
+                MAX_INLINE_TENSOR_LIMIT
+            ),
+        )),
+        Some(_) => Ok(()),
+        None => validate_path(path, "path"),
     }
 }
 
-/// Backend build request params
+/// Backend run response result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunResult {
+    /// Output tensors from model execution
+    pub outputs: Vec<TensorOutput>,
+}
+
+/// Backend load session request params
 ///
-/// Request to AOT compile a model for a specific target.
+/// Loads a compiled library once so later `backend.run_session` calls skip reading the weights.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BuildParams {
-    /// Path to the snapshot file to compile
+pub struct LoadSessionParams {
+    /// Path to compiled library (.dylib, .so, .dll)
+    pub library_path: String,
+    /// Path to snapshot (needed for input/output metadata)
     pub snapshot_path: String,
-    /// Target triple (e.g., "x86_64-apple-darwin", "aarch64-unknown-linux-gnu")
-    pub target: String,
     /// Target device (e.g., "cpu", "cuda::0", "metal")
     pub device: String,
-    /// Output format (e.g., "sharedlib", "staticlib")
-    pub format: String,
-    /// Path for the compiled output
-    pub output_path: String,
+    /// Precision overrides for every run of the session (backend defaults if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<PrecisionParams>,
 }
 
-impl BuildParams {
+impl LoadSessionParams {
     /// Validate the parameters
     pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_path(&self.library_path, "library_path")?;
         validate_path(&self.snapshot_path, "snapshot_path")?;
-        validate_non_empty(&self.target, "target")?;
-        validate_non_empty(&self.device, "device")?;
-        validate_non_empty(&self.format, "format")?;
-        validate_path(&self.output_path, "output_path")
+        validate_non_empty(&self.device, "device")
     }
 }
 
-/// Backend list targets response result
+/// Backend load session response result
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ListTargetsResult {
-    /// List of supported target triples
-    pub targets: Vec<String>,
-    /// Human-readable formatted list of targets (for display)
-    pub formatted: String,
+pub struct LoadSessionResult {
+    /// ID naming the session in later calls, chosen by the plugin
+    pub session_id: String,
 }
 
-/// Progress notification params (plugin -> CLI)
-///
-/// Sent by plugins to report progress during long-running operations.
+/// Backend run session request params
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProgressParams {
-    /// Progress percentage (0-100), None for indeterminate progress
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub percent: Option<u8>,
-    /// Human-readable progress message
-    pub message: String,
+pub struct RunSessionParams {
+    /// Session returned by `backend.load_session`
+    pub session_id: String,
+    /// Input tensors to feed into the model
+    pub inputs: Vec<TensorInput>,
 }
 
-impl ProgressParams {
-    /// Validate the parameters
+impl RunSessionParams {
+    /// Validate the parameters (stops at first error)
     pub fn validate(&self) -> Result<(), ValidationError> {
-        if let Some(percent) = self.percent {
-            if percent > 100 {
-                return Err(ValidationError::out_of_range(
-                    "percent",
-                    format!("percent must be 0-100, got {}", percent),
-                ));
-            }
+        validate_session_id(&self.session_id)?;
+        if self.inputs.len() > MAX_INPUTS {
+            return Err(ValidationError::too_many_items(
+                "inputs",
+                format!("too many inputs ({} > {})", self.inputs.len(), MAX_INPUTS),
+            ));
         }
-        validate_non_empty(&self.message, "message")
+        for (i, input) in self.inputs.iter().enumerate() {
+            input.validate().map_err(|mut e| {
+                e.field = format!("inputs[{}].{}", i, e.field);
+                e
+            })?;
+        }
+        Ok(())
     }
 }
 
-/// Valid log levels for LogParams
-pub const VALID_LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
-
-/// Log notification params (plugin -> CLI)
-///
-/// Sent by plugins to emit log messages to the CLI.
+/// Backend close session request params
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LogParams {
-    /// Log level: "error", "warn", "info", "debug", "trace"
-    pub level: String,
-    /// Log message content
-    pub message: String,
+pub struct CloseSessionParams {
+    /// Session returned by `backend.load_session`
+    pub session_id: String,
 }
 
-impl LogParams {
-    /// Create new log params
-    pub fn new(level: impl Into<String>, message: impl Into<String>) -> Self {
-        Self {
-            level: level.into(),
-            message: message.into(),
-        }
-    }
-
-    /// Create new log params with validation
-    ///
-    /// Returns `Err(ValidationError)` if the level or message is invalid.
-    pub fn new_checked(level: impl Into<String>, message: impl Into<String>) -> Result<Self, ValidationError> {
-        let params = Self::new(level, message);
-        params.validate()?;
-        Ok(params)
-    }
-
+impl CloseSessionParams {
     /// Validate the parameters
     pub fn validate(&self) -> Result<(), ValidationError> {
-        if !self.is_valid_level() {
-            return Err(ValidationError::invalid_chars(
-                "level",
-                format!(
-                    "invalid log level '{}', expected one of: {}",
-                    self.level,
-                    VALID_LOG_LEVELS.join(", ")
-                ),
-            ));
-        }
-        validate_non_empty(&self.message, "message")
+        validate_session_id(&self.session_id)
+    }
+}
+
+/// Validate a session ID (non-empty, within length limit, no control chars)
+fn validate_session_id(session_id: &str) -> Result<(), ValidationError> {
+    validate_non_empty(session_id, "session_id")?;
+    if session_id.len() > MAX_SESSION_ID_LEN {
+        return Err(ValidationError::too_long(
+            "session_id",
+            format!("too long ({} > {} bytes)", session_id.len(), MAX_SESSION_ID_LEN),
+        ));
+    }
+    if session_id.chars().any(|c| c.is_control()) {
+        return Err(ValidationError::invalid_chars(
+            "session_id",
+            "contains control characters",
+        ));
+    }
+    Ok(())
+}
+
+/// Backend benchmark request params
+///
+/// Runs the model `warmup` times untimed, then `iterations` times timed, with the same inputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkParams {
+    /// Path to compiled library (.dylib, .so, .dll)
+    pub library_path: String,
+    /// Path to snapshot (needed for input/output metadata)
+    pub snapshot_path: String,
+    /// Target device (e.g., "cpu", "cuda::0", "metal")
+    pub device: String,
+    /// Input tensors fed to every iteration
+    pub inputs: Vec<TensorInput>,
+    /// Number of timed iterations
+    pub iterations: u32,
+    /// Number of untimed iterations run first
+    #[serde(default)]
+    pub warmup: u32,
+    /// Precision overrides for every iteration (backend defaults if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<PrecisionParams>,
+}
+
+impl BenchmarkParams {
+    /// Validate the parameters (stops at first error)
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_path(&self.library_path, "library_path")?;
+        validate_path(&self.snapshot_path, "snapshot_path")?;
+        validate_non_empty(&self.device, "device")?;
+        if self.iterations == 0 || self.iterations > MAX_BENCHMARK_ITERATIONS {
+            return Err(ValidationError::out_of_range(
+                "iterations",
+                format!(
+                    "iterations must be 1-{}, got {}",
+                    MAX_BENCHMARK_ITERATIONS, self.iterations
+                ),
+            ));
+        }
+        if self.warmup > MAX_BENCHMARK_ITERATIONS {
+            return Err(ValidationError::out_of_range(
+                "warmup",
+                format!("warmup must be 0-{}, got {}", MAX_BENCHMARK_ITERATIONS, self.warmup),
+            ));
+        }
+        if self.inputs.len() > MAX_INPUTS {
+            return Err(ValidationError::too_many_items(
+                "inputs",
+                format!("too many inputs ({} > {})", self.inputs.len(), MAX_INPUTS),
+            ));
+        }
+        for (i, input) in self.inputs.iter().enumerate() {
+            input.validate().map_err(|mut e| {
+                e.field = format!("inputs[{}].{}", i, e.field);
+                e
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Backend benchmark response result
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    /// Latency of every timed iteration in milliseconds, in run order
+    pub latencies_ms: Vec<f64>,
+    /// Timed iterations per second of wall time
+    pub throughput: f64,
+    /// Peak memory while benchmarking in bytes (device memory on accelerators), None if unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory_bytes: Option<u64>,
+}
+
+impl BenchmarkResult {
+    /// Latency at percentile `p` (0-100) in milliseconds, or None without iterations
+    ///
+    /// Uses the nearest-rank method, so `percentile_ms(50.0)` is the median and
+    /// `percentile_ms(100.0)` the slowest iteration.
+    pub fn percentile_ms(&self, p: f64) -> Option<f64> {
+        if self.latencies_ms.is_empty() {
+            return None;
+        }
+        let mut sorted = self.latencies_ms.clone();
+        sorted.sort_by(f64::total_cmp);
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    /// Mean latency in milliseconds, or None without iterations
+    pub fn mean_ms(&self) -> Option<f64> {
+        if self.latencies_ms.is_empty() {
+            return None;
+        }
+        Some(self.latencies_ms.iter().sum::<f64>() / self.latencies_ms.len() as f64)
+    }
+}
+
+/// Backend profile request params, the same as `backend.run`
+pub type ProfileParams = RunParams;
+
+/// Backend profile response result
+///
+/// Times are in microseconds from the start of the profile. Backends time ops on the device's
+/// own clock where they can.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileResult {
+    /// Output tensors from model execution
+    pub outputs: Vec<TensorOutput>,
+    /// Timed ops or kernels, in execution order
+    pub ops: Vec<ProfiledOp>,
+    /// Buffers allocated during the run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allocs: Vec<ProfiledAlloc>,
+    /// Peak memory during the run in bytes (device memory on accelerators), None if unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory_bytes: Option<u64>,
+}
+
+/// One timed op of a profile
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfiledOp {
+    /// Op name (e.g., "matmul", "add")
+    pub name: String,
+    /// Index of the snapshot node the op executes, if it maps to one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<usize>,
+    /// Kernel that ran, for backends whose kernels do not map one-to-one to ops
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kernel: Option<String>,
+    /// Device the op ran on (e.g., "cpu", "cuda::0")
+    pub device: String,
+    /// Microseconds from the start of the profile to the start of the op
+    pub start_us: f64,
+    pub duration_us: f64,
+    /// Bytes allocated while the op ran
+    #[serde(default)]
+    pub allocated_bytes: u64,
+    /// Op metadata such as shapes and dtype
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub args: std::collections::BTreeMap<String, String>,
+}
+
+/// One buffer allocated during a profile
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfiledAlloc {
+    /// Device the buffer lives on
+    pub device: String,
+    /// Microseconds from the start of the profile
+    pub time_us: f64,
+    pub bytes: u64,
+    /// Served from the backend's buffer cache instead of a fresh driver allocation
+    #[serde(default)]
+    pub cached: bool,
+}
+
+impl ProfileResult {
+    /// Render as chrome://tracing (Trace Event Format) JSON
+    ///
+    /// Each device gets its own track; ops are complete events and allocations instant events.
+    pub fn to_chrome_trace(&self) -> String {
+        use serde_json::{json, Map, Value};
+
+        let mut devices: Vec<&str> = Vec::new();
+        let mut events = Vec::with_capacity(self.ops.len() + self.allocs.len());
+        for op in &self.ops {
+            let tid = trace_track(&mut devices, &op.device);
+            let mut args: Map<String, Value> = op
+                .args
+                .iter()
+                .map(|(key, value)| (key.clone(), Value::from(value.as_str())))
+                .collect();
+            if let Some(node) = op.node {
+                args.insert("node".into(), node.into());
+            }
+            if let Some(kernel) = &op.kernel {
+                args.insert("kernel".into(), kernel.as_str().into());
+            }
+            args.insert("allocated_bytes".into(), op.allocated_bytes.into());
+            events.push(json!({
+                "name": op.name,
+                "cat": "op",
+                "ph": "X",
+                "ts": op.start_us,
+                "dur": op.duration_us,
+                "pid": 0,
+                "tid": tid,
+                "args": args,
+            }));
+        }
+        for alloc in &self.allocs {
+            let tid = trace_track(&mut devices, &alloc.device);
+            events.push(json!({
+                "name": "alloc",
+                "cat": "memory",
+                "ph": "i",
+                "s": "t",
+                "ts": alloc.time_us,
+                "pid": 0,
+                "tid": tid,
+                "args": { "bytes": alloc.bytes, "cached": alloc.cached },
+            }));
+        }
+        for (tid, device) in devices.iter().enumerate() {
+            events.push(json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": 0,
+                "tid": tid,
+                "args": { "name": device },
+            }));
+        }
+
+        json!({ "traceEvents": events, "displayTimeUnit": "ms" }).to_string()
+    }
+}
+
+/// Index of the trace track for `device`, adding a track for a new device
+fn trace_track<'a>(devices: &mut Vec<&'a str>, device: &'a str) -> usize {
+    match devices.iter().position(|d| *d == device) {
+        Some(index) => index,
+        None => {
+            devices.push(device);
+            devices.len() - 1
+        },
+    }
+}
+
+/// Output tensor reference from model execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TensorOutput {
+    /// Output tensor name
+    pub name: String,
+    /// Path to output tensor file (.hdt), empty for an inline tensor
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub path: String,
+    /// The tensor itself, for small tensors sent inline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<InlineTensor>,
+}
+
+impl TensorOutput {
+    /// Create new tensor output
+    pub fn new(name: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            path: path.into(),
+            data: None,
+        }
+    }
+
+    /// Create new tensor output carrying `tensor` inline
+    pub fn inline(name: impl Into<String>, tensor: &TensorData) -> Self {
+        Self {
+            name: name.into(),
+            path: String::new(),
+            data: Some(InlineTensor::from(tensor)),
+        }
+    }
+
+    /// Create new tensor output with validation
+    ///
+    /// Returns `Err(ValidationError)` if the name or path is invalid.
+    pub fn new_checked(name: impl Into<String>, path: impl Into<String>) -> Result<Self, ValidationError> {
+        let output = Self::new(name, path);
+        output.validate()?;
+        Ok(output)
+    }
+
+    /// Validate the tensor output
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if !self.is_valid_name() {
+            return Err(ValidationError::invalid_chars(
+                "name",
+                format!(
+                    "invalid tensor name (must be non-empty, max {} bytes, no control chars or path separators)",
+                    MAX_TENSOR_NAME_LEN
+                ),
+            ));
+        }
+        validate_tensor_location(&self.path, self.data.as_ref())
+    }
+
+    /// Validate tensor name (non-empty, no control chars, no path separators, within length limit)
+    pub fn is_valid_name(&self) -> bool {
+        is_valid_tensor_name(&self.name)
+    }
+}
+
+/// Custom op request params
+///
+/// Request to execute a single custom op node. Sent to the `op.<name>` method of the plugin
+/// that declared it; the response is a [`RunResult`] with one output per op result, in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomOpParams {
+    /// Namespaced custom op name (e.g., "acme.fused_gelu")
+    pub name: String,
+    /// Op attributes as recorded in the snapshot
+    #[serde(default)]
+    pub attributes: serde_json::Value,
+    /// Target device (e.g., "cpu", "cuda::0", "metal")
+    pub device: String,
+    /// Input tensors of the node, in order
+    pub inputs: Vec<TensorInput>,
+}
+
+impl CustomOpParams {
+    /// Validate the parameters
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_non_empty(&self.name, "name")?;
+        validate_non_empty(&self.device, "device")?;
+        if self.inputs.len() > MAX_INPUTS {
+            return Err(ValidationError::too_many_items(
+                "inputs",
+                format!("too many inputs ({} > {})", self.inputs.len(), MAX_INPUTS),
+            ));
+        }
+        for (i, input) in self.inputs.iter().enumerate() {
+            input.validate().map_err(|mut e| {
+                e.field = format!("inputs[{}].{}", i, e.field);
+                e
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Quantization schemes every backend implementing `backend.quantize` should understand
+///
+/// - `dynamic`: weights are quantized ahead of time, activations at run time
+/// - `static`: weights and activations are quantized ahead of time, with activation ranges taken
+///   from the calibration samples
+/// - `weight-only`: only weights are quantized, and dequantized before use
+///
+/// Backends may accept other schemes of their own.
+pub const QUANTIZE_SCHEMES: &[&str] = &["dynamic", "static", "weight-only"];
+
+/// Backend quantize request params
+///
+/// Request to write a quantized copy of a snapshot. Each calibration sample is one set of model
+/// inputs; backends run the float model on them to pick activation ranges and to measure the
+/// accuracy of the quantized model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizeParams {
+    /// Path to the snapshot to quantize
+    pub snapshot_path: String,
+    /// Path for the quantized snapshot
+    pub output_path: String,
+    /// Device to calibrate on (e.g., "cpu", "cuda::0", "metal")
+    pub device: String,
+    /// Quantization scheme (see [`QUANTIZE_SCHEMES`])
+    pub scheme: String,
+    /// Bit width of quantized values
+    pub bits: u8,
+    /// Calibration samples, each one set of input tensors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calibration: Vec<Vec<TensorInput>>,
+}
+
+impl QuantizeParams {
+    /// Validate the parameters (stops at first error)
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_path(&self.snapshot_path, "snapshot_path")?;
+        validate_path(&self.output_path, "output_path")?;
+        validate_non_empty(&self.device, "device")?;
+        validate_non_empty(&self.scheme, "scheme")?;
+        if self.bits == 0 || self.bits > MAX_QUANTIZE_BITS {
+            return Err(ValidationError::out_of_range(
+                "bits",
+                format!("bits must be 1-{}, got {}", MAX_QUANTIZE_BITS, self.bits),
+            ));
+        }
+        if self.scheme == "static" && self.calibration.is_empty() {
+            return Err(ValidationError::empty(
+                "calibration",
+                "static quantization needs calibration samples",
+            ));
+        }
+        if self.calibration.len() > MAX_CALIBRATION_SAMPLES {
+            return Err(ValidationError::too_many_items(
+                "calibration",
+                format!(
+                    "too many calibration samples ({} > {})",
+                    self.calibration.len(),
+                    MAX_CALIBRATION_SAMPLES
+                ),
+            ));
+        }
+        for (i, sample) in self.calibration.iter().enumerate() {
+            if sample.len() > MAX_INPUTS {
+                return Err(ValidationError::too_many_items(
+                    format!("calibration[{}]", i),
+                    format!("too many inputs ({} > {})", sample.len(), MAX_INPUTS),
+                ));
+            }
+            for (j, input) in sample.iter().enumerate() {
+                input.validate().map_err(|mut e| {
+                    e.field = format!("calibration[{}][{}].{}", i, j, e.field);
+                    e
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Backend quantize response result
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuantizeResult {
+    /// Path of the quantized snapshot
+    pub snapshot_path: String,
+    /// Number of nodes quantized
+    #[serde(default)]
+    pub quantized_nodes: usize,
+    /// Accuracy of every model output on the calibration samples, empty without samples
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accuracy: Vec<OutputAccuracy>,
+}
+
+/// Error of one quantized model output against the float model
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutputAccuracy {
+    /// Output tensor name
+    pub name: String,
+    /// Largest absolute difference of any element
+    pub max_abs_error: f64,
+    /// Mean absolute difference over all elements
+    pub mean_abs_error: f64,
+    /// Signal to quantization noise ratio in decibels, None when the outputs are identical
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sqnr_db: Option<f64>,
+}
+
+/// Backend build request params
+///
+/// Request to AOT compile a model for a specific target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildParams {
+    /// Path to the snapshot file to compile
+    pub snapshot_path: String,
+    /// Target triple (e.g., "x86_64-apple-darwin", "aarch64-unknown-linux-gnu")
+    pub target: String,
+    /// Target device (e.g., "cpu", "cuda::0", "metal")
+    pub device: String,
+    /// Output format (e.g., "sharedlib", "staticlib")
+    pub format: String,
+    /// Path for the compiled output
+    pub output_path: String,
+}
+
+impl BuildParams {
+    /// Validate the parameters
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_path(&self.snapshot_path, "snapshot_path")?;
+        validate_non_empty(&self.target, "target")?;
+        validate_non_empty(&self.device, "device")?;
+        validate_non_empty(&self.format, "format")?;
+        validate_path(&self.output_path, "output_path")
+    }
+}
+
+/// Backend list targets response result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListTargetsResult {
+    /// List of supported target triples
+    pub targets: Vec<String>,
+    /// Human-readable formatted list of targets (for display)
+    pub formatted: String,
+}
+
+/// Progress notification params (plugin -> CLI)
+///
+/// Sent by plugins to report progress during long-running operations.
+///
+/// `percent` covers the whole operation. Operations that run as a pipeline (e.g. load, compile,
+/// run) can also name the current stage and report its own progress, as a percent, as
+/// items done out of a total, or both.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProgressParams {
+    /// Progress percentage (0-100), None for indeterminate progress
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u8>,
+    /// Human-readable progress message
+    pub message: String,
+    /// Name of the current stage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage: Option<String>,
+    /// Progress within the current stage (0-100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage_percent: Option<u8>,
+    /// Items completed in the current stage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub done: Option<u64>,
+    /// Total items in the current stage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    /// Estimated seconds until the operation completes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eta_secs: Option<f64>,
+}
+
+impl ProgressParams {
+    /// Create progress params for the whole operation
+    pub fn new(percent: Option<u8>, message: impl Into<String>) -> Self {
+        Self {
+            percent,
+            message: message.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the current stage and its percent complete
+    pub fn with_stage(mut self, stage: impl Into<String>, stage_percent: Option<u8>) -> Self {
+        self.stage = Some(stage.into());
+        self.stage_percent = stage_percent;
+        self
+    }
+
+    /// Set the items completed out of the total in the current stage
+    pub fn with_items(mut self, done: u64, total: u64) -> Self {
+        self.done = Some(done);
+        self.total = Some(total);
+        self
+    }
+
+    /// Set the estimated time until the operation completes
+    pub fn with_eta(mut self, eta: std::time::Duration) -> Self {
+        self.eta_secs = Some(eta.as_secs_f64());
+        self
+    }
+
+    /// Progress within the current stage, derived from the item counts when not given
+    pub fn effective_stage_percent(&self) -> Option<u8> {
+        self.stage_percent.or(match (self.done, self.total) {
+            (Some(done), Some(total)) if total > 0 => Some((done.min(total) * 100 / total) as u8),
+            _ => None,
+        })
+    }
+
+    /// Validate the parameters
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(percent) = self.percent {
+            if percent > 100 {
+                return Err(ValidationError::out_of_range(
+                    "percent",
+                    format!("percent must be 0-100, got {}", percent),
+                ));
+            }
+        }
+        if let Some(stage_percent) = self.stage_percent {
+            if stage_percent > 100 {
+                return Err(ValidationError::out_of_range(
+                    "stage_percent",
+                    format!("stage_percent must be 0-100, got {}", stage_percent),
+                ));
+            }
+        }
+        if let Some(stage) = &self.stage {
+            validate_non_empty(stage, "stage")?;
+        }
+        if let (Some(done), Some(total)) = (self.done, self.total) {
+            if done > total {
+                return Err(ValidationError::out_of_range(
+                    "done",
+                    format!("done ({}) exceeds total ({})", done, total),
+                ));
+            }
+        }
+        if let Some(eta) = self.eta_secs {
+            if !eta.is_finite() || eta < 0.0 {
+                return Err(ValidationError::out_of_range(
+                    "eta_secs",
+                    format!("eta_secs must be a non-negative number, got {}", eta),
+                ));
+            }
+        }
+        validate_non_empty(&self.message, "message")
+    }
+}
+
+/// Valid log levels for LogParams
+pub const VALID_LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Environment variable naming the most verbose log level a plugin should forward
+///
+/// The CLI sets it on plugins it starts when it records their logs, and the SDK's tracing
+/// forwarder reads it. Values are those of [`VALID_LOG_LEVELS`].
+pub const TRACE_LEVEL_ENV: &str = "HODU_PLUGIN_TRACE";
+
+/// Log notification params (plugin -> CLI)
+///
+/// Sent by plugins to emit log messages to the CLI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogParams {
+    /// Log level: "error", "warn", "info", "debug", "trace"
+    pub level: String,
+    /// Log message content
+    pub message: String,
+    /// Origin of the message, such as the tracing target or module path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Names of the spans the message was logged in, outermost first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<String>,
+    /// Structured fields of the message and its spans
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl LogParams {
+    /// Create new log params
+    pub fn new(level: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            level: level.into(),
+            message: message.into(),
+            target: None,
+            spans: Vec::new(),
+            fields: serde_json::Map::new(),
+        }
+    }
+
+    /// Set where the message came from
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Set the enclosing spans, outermost first
+    pub fn with_spans(mut self, spans: Vec<String>) -> Self {
+        self.spans = spans;
+        self
+    }
+
+    /// Add a structured field
+    pub fn with_field(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.fields.insert(key.into(), value.into());
+        self
+    }
+
+    /// Create new log params with validation
+    ///
+    /// Returns `Err(ValidationError)` if the level or message is invalid.
+    pub fn new_checked(level: impl Into<String>, message: impl Into<String>) -> Result<Self, ValidationError> {
+        let params = Self::new(level, message);
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Validate the parameters
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if !self.is_valid_level() {
+            return Err(ValidationError::invalid_chars(
+                "level",
+                format!(
+                    "invalid log level '{}', expected one of: {}",
+                    self.level,
+                    VALID_LOG_LEVELS.join(", ")
+                ),
+            ));
+        }
+        validate_non_empty(&self.message, "message")
     }
 
     /// Check if the log level is valid
@@ -1284,6 +2452,53 @@ impl LogParams {
     }
 }
 
+/// Stream notification params (plugin -> CLI)
+///
+/// Carries one event of a stream of results that a handler sends before its response, such as
+/// generated tokens or per-batch outputs. A stream is opened, receives any number of chunks in
+/// order, and is closed, all before the response to `request_id` is sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamParams {
+    /// Request whose handler produces the stream
+    pub request_id: RequestId,
+    /// Stream name, distinguishing several streams of one request
+    pub stream: String,
+    /// What happened on the stream
+    #[serde(flatten)]
+    pub event: StreamEvent,
+}
+
+/// One event on a `$/stream`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StreamEvent {
+    /// The stream started; no chunks precede it
+    Open,
+    /// The next item, numbered from 0
+    Chunk { index: u64, data: serde_json::Value },
+    /// The stream ended after `total_chunks` items
+    Close { total_chunks: u64 },
+}
+
+/// Chunks each stream may send before the CLI grants more, with [`features::STREAM_CREDITS`]
+pub const INITIAL_STREAM_CREDITS: u64 = 16;
+
+/// Stream credit params (CLI -> plugin)
+///
+/// Lets a stream send `credits` more chunks. The CLI grants credits as it consumes chunks, so
+/// no more than [`INITIAL_STREAM_CREDITS`] are ever waiting to be read. Opening and closing a
+/// stream take no credit. Like `$/cancel`, it is sent as a request with an ID of its own and
+/// not answered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamCreditParams {
+    /// Request whose handler produces the stream
+    pub request_id: RequestId,
+    /// Stream name
+    pub stream: String,
+    /// Chunks the stream may send on top of those already allowed
+    pub credits: u64,
+}
+
 /// Cancel request params (CLI -> plugin)
 ///
 /// Sent by CLI to request cancellation of an in-progress operation.
@@ -1291,6 +2506,79 @@ impl LogParams {
 pub struct CancelParams {
     /// Request ID of the operation to cancel
     pub id: RequestId,
+    /// Why the operation is cancelled; plugins assume [`CancelReason::UserAbort`] when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<CancelReason>,
+}
+
+/// Why a request was cancelled
+///
+/// Reasons added later read as [`CancelReason::Other`] in older peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelReason {
+    /// The user interrupted the CLI, e.g. with Ctrl+C
+    UserAbort,
+    /// The request ran past the plugin's handler timeout or the CLI's request timeout
+    Timeout,
+    /// The plugin is shutting down and the request did not finish in the drain period
+    Shutdown,
+    /// The CLI went away, so nobody is left to read the result
+    Disconnected,
+    /// A reason this version does not know
+    #[serde(other)]
+    Other,
+}
+
+/// Metrics result (plugin -> CLI)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsResult {
+    /// Metrics in the Prometheus text exposition format
+    pub text: String,
+}
+
+/// Status result (plugin -> CLI)
+///
+/// Answered even while another request is running, which then shows in `active_requests`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusResult {
+    /// Plugin name
+    pub name: String,
+    /// Plugin version
+    pub version: String,
+    /// Process ID of the plugin
+    pub pid: u32,
+    /// Time since the plugin started, in milliseconds
+    pub uptime_ms: u64,
+    /// Requests being handled, oldest first
+    #[serde(default)]
+    pub active_requests: Vec<ActiveRequestStatus>,
+    /// Protocol features negotiated with the current client
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Methods the plugin has handlers for, sorted
+    #[serde(default)]
+    pub handlers: Vec<String>,
+    /// Resident memory of the plugin process in bytes, where the platform reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resident_memory_bytes: Option<u64>,
+    /// Peak resident memory of the plugin process in bytes, where the platform reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory_bytes: Option<u64>,
+}
+
+/// A request being handled, in a [`StatusResult`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveRequestStatus {
+    /// Request ID
+    pub id: RequestId,
+    /// Method being handled
+    pub method: String,
+    /// Time since the request arrived, in milliseconds
+    pub elapsed_ms: u64,
+    /// Whether the request was cancelled and is winding down
+    #[serde(default)]
+    pub cancelled: bool,
 }
 
 // ============================================================================
@@ -1506,17 +2794,23 @@ impl Notification {
     /// | `notify_progress()` | Clamps > 100 | SDK convenience function |
     /// | `ProgressParams::validate()` | Rejects > 100 | Validating untrusted input |
     pub fn progress(percent: Option<u8>, message: impl Into<String>) -> Self {
+        Self::progress_with(ProgressParams::new(percent, message))
+    }
+
+    /// Create a progress notification with stage, item counts and ETA
+    ///
+    /// Percents are clamped like [`progress`](Self::progress), and a negative or non-finite
+    /// ETA is dropped.
+    pub fn progress_with(mut params: ProgressParams) -> Self {
         // Clamp percent to valid range 0-100 (for convenience; strict validation available via ProgressParams::validate())
-        let percent = percent.map(|p| p.min(100));
-        let message = message.into();
+        params.percent = params.percent.map(|p| p.min(100));
+        params.stage_percent = params.stage_percent.map(|p| p.min(100));
+        params.eta_secs = params.eta_secs.filter(|eta| eta.is_finite() && *eta >= 0.0);
 
-        // Use json! macro which is infallible for primitive types
+        // Serializing plain data with string keys cannot fail
         Self::new(
             methods::NOTIFY_PROGRESS,
-            Some(serde_json::json!({
-                "percent": percent,
-                "message": message
-            })),
+            Some(serde_json::to_value(params).expect("progress params serialize to JSON")),
         )
     }
 
@@ -1538,6 +2832,29 @@ impl Notification {
             })),
         )
     }
+
+    /// Create a log notification with a target, spans and structured fields
+    pub fn log_with(params: LogParams) -> Self {
+        // Serializing plain data with string keys cannot fail
+        Self::new(
+            methods::NOTIFY_LOG,
+            Some(serde_json::to_value(params).expect("log params serialize to JSON")),
+        )
+    }
+
+    /// Create a stream notification
+    pub fn stream(request_id: RequestId, stream: impl Into<String>, event: StreamEvent) -> Self {
+        let params = StreamParams {
+            request_id,
+            stream: stream.into(),
+            event,
+        };
+        // Serializing plain data with string keys cannot fail
+        Self::new(
+            methods::NOTIFY_STREAM,
+            Some(serde_json::to_value(params).expect("stream params serialize to JSON")),
+        )
+    }
 }
 
 impl RpcError {
@@ -1602,6 +2919,49 @@ impl RpcError {
         Self::new(error_codes::REQUEST_CANCELLED, "Request cancelled")
     }
 
+    /// Create an unauthorized error (-32008) - auth token missing or wrong
+    pub fn unauthorized() -> Self {
+        Self::new(error_codes::UNAUTHORIZED, "Unauthorized: invalid auth token")
+    }
+
+    /// Create a busy error (-32009) - rate or concurrency limit reached
+    ///
+    /// `retry_after` is sent as `data.retry_after_ms` when the plugin knows when to retry.
+    pub fn busy(msg: impl Into<String>, retry_after: Option<std::time::Duration>) -> Self {
+        match retry_after {
+            Some(delay) => Self::with_data(
+                error_codes::BUSY,
+                msg,
+                serde_json::json!({ "retry_after_ms": delay.as_millis() as u64 }),
+            ),
+            None => Self::new(error_codes::BUSY, msg),
+        }
+    }
+
+    /// Create a shutting down error (-32010) - request arrived after `shutdown`
+    pub fn shutting_down() -> Self {
+        Self::new(error_codes::SHUTTING_DOWN, "Plugin is shutting down")
+    }
+
+    /// Create an access denied error (-32012) - path outside the plugin's sandbox
+    pub fn access_denied(message: impl Into<String>) -> Self {
+        Self::new(error_codes::ACCESS_DENIED, message)
+    }
+
+    /// Create a resource exhausted error (-32013) - memory or CPU time limit reached
+    pub fn resource_exhausted(message: impl Into<String>) -> Self {
+        Self::new(error_codes::RESOURCE_EXHAUSTED, message)
+    }
+
+    /// Create a session not found error (-32011)
+    pub fn session_not_found(session_id: impl Into<String>) -> Self {
+        let session_id = session_id.into();
+        Self::new(
+            error_codes::SESSION_NOT_FOUND,
+            format!("Session not found: {}", session_id),
+        )
+    }
+
     /// Create a device not available error (-32004)
     pub fn device_not_available(device: impl Into<String>) -> Self {
         let device = device.into();
@@ -1828,12 +3188,18 @@ impl RpcError {
     ///     .with_field("expected", serde_json::json!([1, 3, 224, 224]))
     ///     .with_field("actual", serde_json::json!([1, 3, 256, 256]));
     /// ```
-    pub fn with_field(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+    pub fn with_field(self, key: impl Into<String>, value: serde_json::Value) -> Self {
         let key = key.into();
         // Warn if using reserved field names (they might be overwritten by other methods)
         if RESERVED_ERROR_FIELDS.contains(&key.as_str()) {
             log::warn!("with_field() using reserved field name '{}', may be overwritten", key);
         }
+        self.insert_field(key, value)
+    }
+
+    /// Insert a field into the error data, keeping non-object data under `original_data`
+    fn insert_field(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        let key = key.into();
         self.data = Some(match self.data {
             Some(mut data) => {
                 if let Some(obj) = data.as_object_mut() {
@@ -1848,6 +3214,55 @@ impl RpcError {
         self
     }
 
+    /// Set the kind of failure, sent as `data.kind`
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let error = RpcError::tensor_error("Input 'x' has shape [1, 3] but the model expects [1, 4]")
+    ///     .with_kind(ErrorKind::ShapeMismatch)
+    ///     .with_tensor("x");
+    /// ```
+    pub fn with_kind(self, kind: ErrorKind) -> Self {
+        self.insert_field("kind", serde_json::json!(kind))
+    }
+
+    /// State whether sending the same request again may succeed, overriding the code's default
+    pub fn with_retryable(self, retryable: bool) -> Self {
+        self.insert_field("retryable", serde_json::json!(retryable))
+    }
+
+    /// Name the graph node that failed, sent as `data.node`
+    pub fn with_node(self, node: impl Into<String>) -> Self {
+        self.insert_field("node", serde_json::json!(node.into()))
+    }
+
+    /// Name the tensor at fault, sent as `data.tensor`
+    pub fn with_tensor(self, tensor: impl Into<String>) -> Self {
+        self.insert_field("tensor", serde_json::json!(tensor.into()))
+    }
+
+    /// Read the structured fields of the error data
+    pub fn error_data(&self) -> ErrorData {
+        self.data
+            .as_ref()
+            .filter(|data| data.is_object())
+            .and_then(|data| serde_json::from_value(data.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether sending the same request again may succeed
+    ///
+    /// Uses `data.retryable` if the plugin set it, and the code's [catalog](error_codes::CATALOG)
+    /// entry otherwise.
+    pub fn is_retryable(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data.get("retryable"))
+            .and_then(|retryable| retryable.as_bool())
+            .unwrap_or_else(|| error_codes::lookup(self.code).is_some_and(|info| info.retryable))
+    }
+
     /// Add a details string to the error
     ///
     /// Shortcut for `.with_field("details", ...)`.
@@ -1881,6 +3296,16 @@ impl From<&str> for RequestId {
     }
 }
 
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestId::Number(n) => write!(f, "{}", n),
+            RequestId::String(s) => write!(f, "{}", s),
+            RequestId::Null => write!(f, "null"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1938,6 +3363,53 @@ mod tests {
         assert!(notification.params.is_some());
     }
 
+    #[test]
+    fn test_notification_progress_with_stage() {
+        let params = ProgressParams::new(Some(40), "Compiling")
+            .with_stage("compile", Some(150))
+            .with_items(3, 12)
+            .with_eta(std::time::Duration::from_secs(9));
+        let notification = Notification::progress_with(params);
+        let parsed: ProgressParams = serde_json::from_value(notification.params.unwrap()).unwrap();
+        assert_eq!(parsed.stage.as_deref(), Some("compile"));
+        assert_eq!(parsed.stage_percent, Some(100));
+        assert_eq!((parsed.done, parsed.total), (Some(3), Some(12)));
+        assert_eq!(parsed.eta_secs, Some(9.0));
+
+        // Plain progress keeps its original shape
+        let plain: ProgressParams =
+            serde_json::from_value(serde_json::json!({"percent": 5, "message": "Loading"})).unwrap();
+        assert_eq!(plain, ProgressParams::new(Some(5), "Loading"));
+    }
+
+    #[test]
+    fn test_progress_params_validate() {
+        assert!(ProgressParams::new(None, "Running").with_items(2, 4).validate().is_ok());
+        assert!(ProgressParams::new(None, "Running")
+            .with_items(5, 4)
+            .validate()
+            .is_err());
+        assert!(ProgressParams::new(None, "Running")
+            .with_stage("run", Some(101))
+            .validate()
+            .is_err());
+        assert!(ProgressParams::new(None, "Running")
+            .with_stage("", None)
+            .validate()
+            .is_err());
+
+        let mut params = ProgressParams::new(None, "Running");
+        params.eta_secs = Some(f64::NAN);
+        assert!(params.validate().is_err());
+
+        assert_eq!(
+            ProgressParams::new(None, "Running")
+                .with_items(3, 4)
+                .effective_stage_percent(),
+            Some(75)
+        );
+    }
+
     #[test]
     fn test_notification_log() {
         let notification = Notification::log("info", "Test message");
@@ -1945,6 +3417,70 @@ mod tests {
         assert!(notification.params.is_some());
     }
 
+    #[test]
+    fn test_notification_log_with_fields() {
+        let params = LogParams::new("debug", "loaded weights")
+            .with_target("my_plugin::loader")
+            .with_spans(vec!["request".into(), "load".into()])
+            .with_field("bytes", 1024);
+        let notification = Notification::log_with(params);
+        let params = notification.params.unwrap();
+        assert_eq!(params["fields"]["bytes"], 1024);
+        assert_eq!(params["spans"], serde_json::json!(["request", "load"]));
+
+        // Plain logs omit the structured parts, so older CLIs still parse them
+        let plain = Notification::log("info", "hi").params.unwrap();
+        let parsed: LogParams = serde_json::from_value(plain).unwrap();
+        assert!(parsed.target.is_none() && parsed.spans.is_empty() && parsed.fields.is_empty());
+    }
+
+    #[test]
+    fn test_notification_stream() {
+        let notification = Notification::stream(
+            RequestId::Number(7),
+            "tokens",
+            StreamEvent::Chunk {
+                index: 2,
+                data: serde_json::json!("hello"),
+            },
+        );
+        assert_eq!(notification.method, methods::NOTIFY_STREAM);
+        let params = notification.params.unwrap();
+        assert_eq!(
+            params,
+            serde_json::json!({ "request_id": 7, "stream": "tokens", "event": "chunk", "index": 2, "data": "hello" })
+        );
+
+        let parsed: StreamParams = serde_json::from_value(params).unwrap();
+        assert_eq!(parsed.request_id, RequestId::Number(7));
+        assert_eq!(
+            parsed.event,
+            StreamEvent::Chunk {
+                index: 2,
+                data: serde_json::json!("hello")
+            }
+        );
+
+        let close: StreamParams =
+            serde_json::from_str(r#"{"request_id":"a","stream":"s","event":"close","total_chunks":3}"#).unwrap();
+        assert_eq!(close.event, StreamEvent::Close { total_chunks: 3 });
+    }
+
+    #[test]
+    fn test_stream_credit_params() {
+        let params = StreamCreditParams {
+            request_id: RequestId::Number(7),
+            stream: "tokens".to_string(),
+            credits: 8,
+        };
+        let value = serde_json::to_value(&params).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "request_id": 7, "stream": "tokens", "credits": 8 })
+        );
+        assert_eq!(serde_json::from_value::<StreamCreditParams>(value).unwrap(), params);
+    }
+
     #[test]
     fn test_rpc_error_factories() {
         let err = RpcError::method_not_found("test.method");
@@ -1961,6 +3497,98 @@ mod tests {
 
         let err = RpcError::cancelled();
         assert_eq!(err.code, error_codes::REQUEST_CANCELLED);
+
+        let err = RpcError::busy("slow down", Some(std::time::Duration::from_millis(250)));
+        assert_eq!(err.code, error_codes::BUSY);
+        assert_eq!(err.data, Some(serde_json::json!({ "retry_after_ms": 250 })));
+        assert_eq!(RpcError::busy("full", None).data, None);
+
+        let err = RpcError::shutting_down();
+        assert_eq!(err.code, error_codes::SHUTTING_DOWN);
+    }
+
+    #[test]
+    fn test_structured_error_data() {
+        let err = RpcError::tensor_error("shape mismatch")
+            .with_kind(ErrorKind::ShapeMismatch)
+            .with_tensor("x")
+            .with_node("conv1")
+            .with_hint("reshape x");
+        let data = err.error_data();
+        assert_eq!(data.kind, Some(ErrorKind::ShapeMismatch));
+        assert_eq!(data.tensor.as_deref(), Some("x"));
+        assert_eq!(data.node.as_deref(), Some("conv1"));
+        assert_eq!(data.hints, vec!["reshape x".to_string()]);
+        assert!(!err.is_retryable());
+
+        // Kinds from newer peers, and data without structured fields, still read
+        let err = RpcError::with_data(
+            error_codes::MODEL_ERROR,
+            "failed",
+            serde_json::json!({ "kind": "melted" }),
+        );
+        assert_eq!(err.error_data().kind, Some(ErrorKind::Other));
+        assert_eq!(RpcError::internal_error("x").error_data(), ErrorData::default());
+        let err = RpcError::with_data(error_codes::MODEL_ERROR, "failed", serde_json::json!("opaque"));
+        assert_eq!(err.error_data(), ErrorData::default());
+
+        // The catalog default applies unless the error says otherwise
+        assert!(RpcError::busy("full", None).is_retryable());
+        assert!(!RpcError::busy("full", None).with_retryable(false).is_retryable());
+        assert!(RpcError::model_error("flaky").with_retryable(true).is_retryable());
+    }
+
+    #[test]
+    fn test_cancel_reason() {
+        let params = CancelParams {
+            id: RequestId::Number(3),
+            reason: Some(CancelReason::Timeout),
+        };
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(json["reason"], "timeout");
+
+        // Older CLIs send no reason, newer ones may send reasons we do not know
+        let parsed: CancelParams = serde_json::from_str(r#"{"id":3}"#).unwrap();
+        assert_eq!(parsed.reason, None);
+        let parsed: CancelParams = serde_json::from_str(r#"{"id":3,"reason":"power_loss"}"#).unwrap();
+        assert_eq!(parsed.reason, Some(CancelReason::Other));
+    }
+
+    #[test]
+    fn test_status_result_serialization() {
+        let status = StatusResult {
+            name: "hodu-backend-cpu".to_string(),
+            version: "0.1.0".to_string(),
+            pid: 42,
+            uptime_ms: 1500,
+            active_requests: vec![ActiveRequestStatus {
+                id: RequestId::Number(7),
+                method: methods::BACKEND_RUN.to_string(),
+                elapsed_ms: 250,
+                cancelled: false,
+            }],
+            features: vec![features::STREAMING.to_string()],
+            handlers: vec![methods::BACKEND_RUN.to_string()],
+            resident_memory_bytes: None,
+            peak_memory_bytes: None,
+        };
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["active_requests"][0]["method"], "backend.run");
+        // Memory is left out where the platform does not report it
+        assert!(json.get("resident_memory_bytes").is_none());
+
+        let parsed: StatusResult = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.active_requests[0].id, RequestId::Number(7));
+    }
+
+    #[test]
+    fn test_error_catalog() {
+        let mut codes: Vec<i32> = error_codes::CATALOG.iter().map(|info| info.code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), error_codes::CATALOG.len());
+        assert_eq!(error_codes::lookup(error_codes::BUSY).unwrap().name, "BUSY");
+        assert!(error_codes::lookup(12345).is_none());
     }
 
     #[test]
@@ -2119,6 +3747,7 @@ mod tests {
             snapshot_path: "/path/to/snapshot.hdss".to_string(),
             device: "cpu".to_string(),
             inputs: vec![],
+            precision: None,
         };
         assert!(params.validate().is_ok());
 
@@ -2128,6 +3757,7 @@ mod tests {
             snapshot_path: "/path/to/snapshot.hdss".to_string(),
             device: "".to_string(),
             inputs: vec![],
+            precision: None,
         };
         assert!(params.validate().is_err());
 
@@ -2140,8 +3770,235 @@ mod tests {
                 .map(|i| TensorInput {
                     name: format!("input{}", i),
                     path: format!("/path/to/input{}.hdt", i),
+                    data: None,
                 })
                 .collect(),
+            precision: None,
+        };
+        assert!(params.validate().is_err());
+    }
+
+    #[test]
+    fn test_session_params_validate() {
+        let params = LoadSessionParams {
+            library_path: "/path/to/lib.dylib".to_string(),
+            snapshot_path: "/path/to/snapshot.hdss".to_string(),
+            device: "cpu".to_string(),
+            precision: None,
+        };
+        assert!(params.validate().is_ok());
+
+        let params = RunSessionParams {
+            session_id: "session-1".to_string(),
+            inputs: vec![TensorInput::new("x", "/path/to/x.hdt")],
+        };
+        assert!(params.validate().is_ok());
+
+        // Empty session ID
+        let params = RunSessionParams {
+            session_id: "".to_string(),
+            inputs: vec![],
+        };
+        assert!(params.validate().is_err());
+
+        // Session ID too long
+        let params = CloseSessionParams {
+            session_id: "s".repeat(MAX_SESSION_ID_LEN + 1),
+        };
+        assert!(params.validate().is_err());
+
+        let err = RpcError::session_not_found("session-1");
+        assert_eq!(err.code, error_codes::SESSION_NOT_FOUND);
+        assert!(err.message.contains("session-1"));
+    }
+
+    #[test]
+    fn test_benchmark_params_validate() {
+        let mut params = BenchmarkParams {
+            library_path: "/path/to/lib.so".to_string(),
+            snapshot_path: "/path/to/snapshot.hdss".to_string(),
+            device: "cpu".to_string(),
+            inputs: vec![TensorInput::new("x", "/path/to/x.hdt")],
+            iterations: 100,
+            warmup: 10,
+            precision: None,
+        };
+        assert!(params.validate().is_ok());
+
+        params.iterations = 0;
+        assert_eq!(params.validate().unwrap_err().field, "iterations");
+
+        params.iterations = 1;
+        params.warmup = MAX_BENCHMARK_ITERATIONS + 1;
+        assert_eq!(params.validate().unwrap_err().field, "warmup");
+
+        // Warmup defaults to none
+        let json = r#"{"library_path":"a.so","snapshot_path":"a.hdss","device":"cpu","inputs":[],"iterations":5}"#;
+        let params: BenchmarkParams = serde_json::from_str(json).unwrap();
+        assert_eq!(params.warmup, 0);
+    }
+
+    #[test]
+    fn test_tensor_chunk_serialization() {
+        let header = TensorChunk::Header {
+            shape: vec![2, 3],
+            dtype: PluginDType::F32,
+        };
+        let json = serde_json::to_value(&header).unwrap();
+        assert_eq!(json["kind"], "header");
+        assert_eq!(serde_json::from_value::<TensorChunk>(json).unwrap(), header);
+
+        let chunk = TensorChunk::data(8, b"foobar");
+        let json = serde_json::to_value(&chunk).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"kind": "data", "offset": 8, "data": "Zm9vYmFy"})
+        );
+
+        let params = StreamLoadTensorParams {
+            path: "/path/to/huge.npy".to_string(),
+            chunk_size: Some(MAX_TENSOR_CHUNK_SIZE + 1),
+        };
+        assert_eq!(params.validate().unwrap_err().field, "chunk_size");
+    }
+
+    #[test]
+    fn test_quantize_params_validate() {
+        let mut params = QuantizeParams {
+            snapshot_path: "/path/to/model.hdss".to_string(),
+            output_path: "/path/to/model.int8.hdss".to_string(),
+            device: "cpu".to_string(),
+            scheme: "dynamic".to_string(),
+            bits: 8,
+            calibration: Vec::new(),
+        };
+        assert!(params.validate().is_ok());
+
+        params.bits = MAX_QUANTIZE_BITS + 1;
+        assert_eq!(params.validate().unwrap_err().field, "bits");
+
+        params.bits = 8;
+        params.scheme = "static".to_string();
+        assert_eq!(params.validate().unwrap_err().field, "calibration");
+
+        params.calibration = vec![
+            vec![TensorInput::new("x", "/path/to/x.hdt")],
+            vec![TensorInput::new("", "/path/to/y.hdt")],
+        ];
+        assert_eq!(params.validate().unwrap_err().field, "calibration[1][0].name");
+
+        params.calibration.truncate(1);
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn test_benchmark_result_stats() {
+        let result = BenchmarkResult {
+            latencies_ms: vec![4.0, 1.0, 3.0, 2.0],
+            throughput: 400.0,
+            peak_memory_bytes: None,
+        };
+        assert_eq!(result.mean_ms(), Some(2.5));
+        assert_eq!(result.percentile_ms(0.0), Some(1.0));
+        assert_eq!(result.percentile_ms(50.0), Some(2.0));
+        assert_eq!(result.percentile_ms(75.0), Some(3.0));
+        assert_eq!(result.percentile_ms(100.0), Some(4.0));
+        assert_eq!(BenchmarkResult::default().percentile_ms(50.0), None);
+    }
+
+    #[test]
+    fn test_profile_result_chrome_trace() {
+        let result = ProfileResult {
+            outputs: vec![],
+            ops: vec![
+                ProfiledOp {
+                    name: "matmul".to_string(),
+                    node: Some(0),
+                    device: "cuda::0".to_string(),
+                    start_us: 0.0,
+                    duration_us: 12.5,
+                    allocated_bytes: 64,
+                    ..Default::default()
+                },
+                ProfiledOp {
+                    name: "add".to_string(),
+                    kernel: Some("fused_add_relu".to_string()),
+                    device: "cpu".to_string(),
+                    start_us: 12.5,
+                    duration_us: 1.0,
+                    ..Default::default()
+                },
+            ],
+            allocs: vec![ProfiledAlloc {
+                device: "cuda::0".to_string(),
+                time_us: 1.0,
+                bytes: 64,
+                cached: false,
+            }],
+            peak_memory_bytes: Some(64),
+        };
+
+        let trace: serde_json::Value = serde_json::from_str(&result.to_chrome_trace()).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        // Two ops, one allocation, one track name per device
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0]["tid"], 0);
+        assert_eq!(events[0]["args"]["node"], 0);
+        assert_eq!(events[1]["tid"], 1);
+        assert_eq!(events[1]["args"]["kernel"], "fused_add_relu");
+        assert_eq!(events[2]["tid"], 0);
+        assert_eq!(events[4]["args"]["name"], "cpu");
+
+        // Optional fields are omitted and default when absent
+        let json = serde_json::to_string(&result.ops[1]).unwrap();
+        assert!(!json.contains("node"));
+        let op: ProfiledOp =
+            serde_json::from_str(r#"{"name":"add","device":"cpu","start_us":0,"duration_us":1}"#).unwrap();
+        assert_eq!(op.allocated_bytes, 0);
+    }
+
+    #[test]
+    fn test_run_params_precision_serde() {
+        // Requests from clients that predate precision overrides still parse
+        let json = r#"{"library_path":"a.so","snapshot_path":"a.hdss","device":"cuda::0","inputs":[]}"#;
+        let params: RunParams = serde_json::from_str(json).unwrap();
+        assert_eq!(params.precision, None);
+
+        let precision = PrecisionParams {
+            allow_tf32: Some(true),
+            f16_accumulate_f32: None,
+        };
+        let json = serde_json::to_string(&precision).unwrap();
+        assert_eq!(json, r#"{"allow_tf32":true}"#);
+        assert_eq!(serde_json::from_str::<PrecisionParams>(&json).unwrap(), precision);
+    }
+
+    #[test]
+    fn test_custom_op_params_validate() {
+        // Valid params
+        let params = CustomOpParams {
+            name: "acme.fused_gelu".to_string(),
+            attributes: serde_json::json!({ "approximate": true }),
+            device: "cpu".to_string(),
+            inputs: vec![TensorInput::new("x", "/path/to/x.hdt")],
+        };
+        assert!(params.validate().is_ok());
+
+        // Empty name
+        let params = CustomOpParams {
+            name: "".to_string(),
+            attributes: serde_json::Value::Null,
+            device: "cpu".to_string(),
+            inputs: vec![],
+        };
+        assert!(params.validate().is_err());
+
+        // Invalid input name
+        let params = CustomOpParams {
+            name: "acme.fused_gelu".to_string(),
+            attributes: serde_json::Value::Null,
+            device: "cpu".to_string(),
+            inputs: vec![TensorInput::new("", "/path/to/x.hdt")],
         };
         assert!(params.validate().is_err());
     }
@@ -2185,6 +4042,7 @@ mod tests {
         let input = TensorInput {
             name: "input0".to_string(),
             path: "/path/to/tensor.hdt".to_string(),
+            data: None,
         };
         assert!(input.validate().is_ok());
 
@@ -2192,6 +4050,7 @@ mod tests {
         let input = TensorInput {
             name: "".to_string(),
             path: "/path/to/tensor.hdt".to_string(),
+            data: None,
         };
         assert!(input.validate().is_err());
 
@@ -2199,6 +4058,7 @@ mod tests {
         let input = TensorInput {
             name: "input/0".to_string(),
             path: "/path/to/tensor.hdt".to_string(),
+            data: None,
         };
         assert!(input.validate().is_err());
 
@@ -2206,6 +4066,7 @@ mod tests {
         let input = TensorInput {
             name: "input0".to_string(),
             path: "".to_string(),
+            data: None,
         };
         assert!(input.validate().is_err());
     }
@@ -2216,6 +4077,7 @@ mod tests {
         let output = TensorOutput {
             name: "output0".to_string(),
             path: "/path/to/output.hdt".to_string(),
+            data: None,
         };
         assert!(output.validate().is_ok());
 
@@ -2223,6 +4085,7 @@ mod tests {
         let output = TensorOutput {
             name: "".to_string(),
             path: "/path/to/output.hdt".to_string(),
+            data: None,
         };
         assert!(output.validate().is_err());
 
@@ -2230,16 +4093,96 @@ mod tests {
         let output = TensorOutput {
             name: "output\\0".to_string(),
             path: "/path/to/output.hdt".to_string(),
+            data: None,
         };
         assert!(output.validate().is_err());
     }
 
+    #[test]
+    fn test_inline_tensor() {
+        let tensor = TensorData::new(vec![1, 2, 3, 4, 5, 6, 7, 8], vec![2], PluginDType::F32);
+        let input = TensorInput::inline("x", &tensor);
+        assert!(input.validate().is_ok());
+        assert_eq!(input.data.as_ref().unwrap().size_bytes(), 8);
+
+        // Inline tensors carry no path, and round trip through JSON
+        let json = serde_json::to_value(&input).unwrap();
+        assert!(json.get("path").is_none());
+        let parsed: TensorInput = serde_json::from_value(json).unwrap();
+        let decoded = parsed.data.unwrap().decode().unwrap();
+        assert_eq!(decoded.data, tensor.data);
+        assert_eq!(decoded.shape, vec![2]);
+
+        // Data must match shape and dtype
+        let mut bad = InlineTensor::from(&tensor);
+        bad.shape = vec![3];
+        assert!(bad.decode().is_err());
+
+        // A tensor is in a file or inline, not both
+        let mut both = TensorOutput::inline("y", &tensor);
+        assert!(both.validate().is_ok());
+        both.path = "/path/to/y.hdt".to_string();
+        assert!(both.validate().is_err());
+    }
+
+    #[test]
+    fn test_negotiate_inline_tensor_limit() {
+        assert_eq!(negotiate_inline_tensor_limit(false, Some(1024), Some(1024)), 0);
+        assert_eq!(
+            negotiate_inline_tensor_limit(true, None, None),
+            DEFAULT_INLINE_TENSOR_LIMIT
+        );
+        assert_eq!(negotiate_inline_tensor_limit(true, Some(1024), None), 1024);
+        assert_eq!(
+            negotiate_inline_tensor_limit(true, Some(u64::MAX), Some(u64::MAX)),
+            MAX_INLINE_TENSOR_LIMIT
+        );
+    }
+
+    #[test]
+    fn test_initialize_config_roundtrip() {
+        let params = InitializeParams {
+            plugin_version: "1.0.0".to_string(),
+            protocol_version: "1.0.0".to_string(),
+            framings: Vec::new(),
+            auth_token: None,
+            config: Some(serde_json::json!({ "threads": 4 })),
+            features: Vec::new(),
+            inline_tensor_limit: None,
+            sandbox: None,
+        };
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(json["config"]["threads"], 4);
+
+        // Older CLIs send no config
+        let parsed: InitializeParams =
+            serde_json::from_value(serde_json::json!({ "plugin_version": "1.0.0", "protocol_version": "1.0.0" }))
+                .unwrap();
+        assert!(parsed.config.is_none());
+    }
+
+    #[test]
+    fn test_negotiate_features() {
+        let cli = vec![features::HOST_CALLS.to_string(), "binary_frames".to_string()];
+        let negotiated = negotiate_features(&[features::STREAMING, features::HOST_CALLS, features::HOST_CALLS], &cli);
+        assert_eq!(negotiated, vec![features::HOST_CALLS.to_string()]);
+
+        // Older peers list nothing, so nothing is negotiated
+        assert!(negotiate_features(&[features::STREAMING], &[]).is_empty());
+    }
+
     #[test]
     fn test_initialize_params_validate() {
         // Valid params
         let params = InitializeParams {
             plugin_version: "1.0.0".to_string(),
             protocol_version: "1.0.0".to_string(),
+            framings: Vec::new(),
+            auth_token: None,
+            config: None,
+            features: Vec::new(),
+            inline_tensor_limit: None,
+            sandbox: None,
         };
         assert!(params.validate().is_ok());
 
@@ -2247,6 +4190,12 @@ mod tests {
         let params = InitializeParams {
             plugin_version: "".to_string(),
             protocol_version: "1.0.0".to_string(),
+            framings: Vec::new(),
+            auth_token: None,
+            config: None,
+            features: Vec::new(),
+            inline_tensor_limit: None,
+            sandbox: None,
         };
         assert!(params.validate().is_err());
 
@@ -2254,6 +4203,12 @@ mod tests {
         let params = InitializeParams {
             plugin_version: "1.0.0".to_string(),
             protocol_version: "".to_string(),
+            framings: Vec::new(),
+            auth_token: None,
+            config: None,
+            features: Vec::new(),
+            inline_tensor_limit: None,
+            sandbox: None,
         };
         assert!(params.validate().is_err());
     }
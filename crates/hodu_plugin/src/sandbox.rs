@@ -0,0 +1,158 @@
+//! Filesystem sandbox for untrusted plugins
+//!
+//! The CLI sends a [`SandboxPolicy`] in `initialize` listing the directories a plugin may read
+//! and write. The SDK's file helpers refuse paths outside them, and the CLI refuses paths outside
+//! them that the plugin hands back (output tensors, snapshots, shared chunks, host loads).
+//!
+//! This is not an OS-level sandbox: a plugin writing files with `std::fs` directly is not
+//! stopped. It keeps well-behaved plugins inside their roots and stops the CLI from reading or
+//! deleting files on a plugin's behalf that the plugin had no business pointing it at.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directories a plugin may read and write below
+///
+/// Paths are checked after resolving symlinks and `..`, so a link inside a root that points
+/// outside it does not let the plugin out.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxPolicy {
+    /// Directories (or files) the plugin may read
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read: Vec<String>,
+    /// Directories (or files) the plugin may read and write
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub write: Vec<String>,
+}
+
+impl SandboxPolicy {
+    /// Create a policy that allows nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow reading below `root`
+    pub fn allow_read(&mut self, root: impl AsRef<Path>) {
+        push_root(&mut self.read, root.as_ref());
+    }
+
+    /// Allow reading and writing below `root`
+    pub fn allow_write(&mut self, root: impl AsRef<Path>) {
+        push_root(&mut self.write, root.as_ref());
+    }
+
+    /// Whether `path` is below a read or write root
+    pub fn allows_read(&self, path: impl AsRef<Path>) -> bool {
+        is_below_any(path.as_ref(), self.read.iter().chain(&self.write))
+    }
+
+    /// Whether `path` is below a write root
+    pub fn allows_write(&self, path: impl AsRef<Path>) -> bool {
+        is_below_any(path.as_ref(), &self.write)
+    }
+
+    /// Fail with `PermissionDenied` unless `path` may be read
+    pub fn check_read(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if self.allows_read(path) {
+            return Ok(());
+        }
+        Err(denied(path, "read"))
+    }
+
+    /// Fail with `PermissionDenied` unless `path` may be written
+    pub fn check_write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if self.allows_write(path) {
+            return Ok(());
+        }
+        Err(denied(path, "write"))
+    }
+}
+
+fn push_root(roots: &mut Vec<String>, root: &Path) {
+    let root = resolve(root).unwrap_or_else(|| root.to_path_buf());
+    let root = root.to_string_lossy().into_owned();
+    if !roots.contains(&root) {
+        roots.push(root);
+    }
+}
+
+fn is_below_any<'a>(path: &Path, roots: impl IntoIterator<Item = &'a String>) -> bool {
+    let Some(path) = resolve(path) else {
+        return false;
+    };
+    roots
+        .into_iter()
+        .filter_map(|root| resolve(Path::new(root)))
+        .any(|root| path.starts_with(root))
+}
+
+fn denied(path: &Path, access: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("'{}' is outside the plugin sandbox ({} denied)", path.display(), access),
+    )
+}
+
+/// Absolute form of `path` with symlinks and `..` resolved, for paths that may not exist yet
+///
+/// The longest existing prefix is canonicalized and the rest appended; `..` in the part that
+/// does not exist yet cannot be resolved, so such paths give `None`.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+    let mut existing = absolute.as_path();
+    let mut missing = Vec::new();
+    loop {
+        if let Ok(mut resolved) = existing.canonicalize() {
+            resolved.extend(missing.iter().rev());
+            return Some(resolved);
+        }
+        missing.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandbox_roots() {
+        let dir = std::env::temp_dir().join(format!("hodu_sandbox_test_{}", std::process::id()));
+        let readable = dir.join("models");
+        let writable = dir.join("out");
+        std::fs::create_dir_all(&readable).unwrap();
+        std::fs::create_dir_all(&writable).unwrap();
+
+        let mut policy = SandboxPolicy::new();
+        policy.allow_read(&readable);
+        policy.allow_write(&writable);
+
+        assert!(policy.allows_read(readable.join("model.onnx")));
+        assert!(!policy.allows_write(readable.join("model.onnx")));
+        // Files that do not exist yet are judged by where they would be created
+        assert!(policy.allows_write(writable.join("new/output.hdt")));
+        assert!(policy.allows_read(writable.join("output.hdt")));
+        assert!(!policy.allows_read(dir.join("secret")));
+        assert!(!policy.allows_read(writable.join("../secret")));
+        assert!(!policy.allows_write(writable.join("missing/../../secret")));
+        assert_eq!(
+            policy.check_read(dir.join("secret")).unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&dir, writable.join("escape")).unwrap();
+            assert!(!policy.allows_write(writable.join("escape/secret")));
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,416 @@
+//! Ed25519 detached signatures over plugin binaries and built artifacts
+//!
+//! A publisher signs a file with their [`SigningKey`] and ships the [`DetachedSignature`] next to
+//! it as `<file>.sig` (see [`signature_path`]). Whoever installs the file checks it against the
+//! public keys they trust, kept as [`TrustedKeys`]:
+//!
+//! ```ignore
+//! let key = SigningKey::generate()?;
+//! key.sign_file("hodu-backend-cuda")?; // writes hodu-backend-cuda.sig
+//!
+//! let mut trusted = TrustedKeys::default();
+//! trusted.add(key.public_key(), "hodu release");
+//! let signer = verify_file("hodu-backend-cuda", &trusted)?;
+//! ```
+
+use crate::base64;
+use ed25519_dalek::{Signer, Verifier};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Algorithm named in signature files
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// Extension appended to a signed file's name for its signature
+pub const SIGNATURE_EXTENSION: &str = "sig";
+
+/// Path of the detached signature for `path`, e.g. `plugin.sig` for `plugin`
+pub fn signature_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(SIGNATURE_EXTENSION);
+    path.with_file_name(name)
+}
+
+/// Signature creation or verification error
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SignatureError {
+    /// Reading or writing a key or signature failed
+    Io(std::io::Error),
+    /// A key or signature could not be decoded
+    Malformed(String),
+    /// The signature uses an algorithm other than [`SIGNATURE_ALGORITHM`]
+    UnsupportedAlgorithm(String),
+    /// The file has no signature next to it
+    Missing(PathBuf),
+    /// The signature was made with a key that is not trusted
+    UntrustedKey(PublicKey),
+    /// The signature does not match the data
+    Invalid,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "io error: {}", e),
+            Self::Malformed(msg) => write!(f, "malformed: {}", msg),
+            Self::UnsupportedAlgorithm(algorithm) => write!(f, "unsupported signature algorithm '{}'", algorithm),
+            Self::Missing(path) => write!(f, "no signature at {}", path.display()),
+            Self::UntrustedKey(key) => write!(f, "signed with untrusted key {}", key),
+            Self::Invalid => write!(f, "signature does not match the signed data"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SignatureError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Secret key signing files
+pub struct SigningKey(ed25519_dalek::SigningKey);
+
+impl SigningKey {
+    /// Generate a new key from the operating system's random source
+    pub fn generate() -> Result<Self, SignatureError> {
+        let mut seed = [0u8; ed25519_dalek::SECRET_KEY_LENGTH];
+        getrandom::fill(&mut seed)
+            .map_err(|e| SignatureError::Io(std::io::Error::other(format!("no random source: {}", e))))?;
+        Ok(Self(ed25519_dalek::SigningKey::from_bytes(&seed)))
+    }
+
+    /// Decode a key written by [`to_base64`](Self::to_base64)
+    pub fn from_base64(text: &str) -> Result<Self, SignatureError> {
+        let bytes =
+            base64::decode(text.trim()).map_err(|e| SignatureError::Malformed(format!("signing key: {}", e)))?;
+        let seed = bytes
+            .try_into()
+            .map_err(|_| SignatureError::Malformed("signing key must be 32 bytes".to_string()))?;
+        Ok(Self(ed25519_dalek::SigningKey::from_bytes(&seed)))
+    }
+
+    /// Encode the key as base64
+    pub fn to_base64(&self) -> String {
+        base64::encode(self.0.as_bytes())
+    }
+
+    /// Load a key saved with [`save`](Self::save)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SignatureError> {
+        Self::from_base64(&std::fs::read_to_string(path)?)
+    }
+
+    /// Save the key, readable only by the owner on Unix
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SignatureError> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        std::io::Write::write_all(&mut file, format!("{}\n", self.to_base64()).as_bytes())?;
+        Ok(())
+    }
+
+    /// The public key verifying this key's signatures
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.0.verifying_key().to_bytes())
+    }
+
+    /// Sign `data`
+    pub fn sign(&self, data: &[u8]) -> DetachedSignature {
+        DetachedSignature {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            public_key: self.public_key().to_base64(),
+            signature: base64::encode(&self.0.sign(data).to_bytes()),
+        }
+    }
+
+    /// Sign the file at `path`, saving the signature to its [`signature_path`]
+    pub fn sign_file(&self, path: impl AsRef<Path>) -> Result<DetachedSignature, SignatureError> {
+        let path = path.as_ref();
+        let signature = self.sign(&std::fs::read(path)?);
+        signature.save(signature_path(path))?;
+        Ok(signature)
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the secret
+        f.debug_tuple("SigningKey").field(&self.public_key()).finish()
+    }
+}
+
+/// Public key verifying signatures
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey([u8; ed25519_dalek::PUBLIC_KEY_LENGTH]);
+
+impl PublicKey {
+    /// Decode a key written by [`to_base64`](Self::to_base64)
+    pub fn from_base64(text: &str) -> Result<Self, SignatureError> {
+        let bytes = base64::decode(text.trim()).map_err(|e| SignatureError::Malformed(format!("public key: {}", e)))?;
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| SignatureError::Malformed("public key must be 32 bytes".to_string()))?;
+        let key = Self(bytes);
+        key.verifying_key()?;
+        Ok(key)
+    }
+
+    fn verifying_key(&self) -> Result<ed25519_dalek::VerifyingKey, SignatureError> {
+        ed25519_dalek::VerifyingKey::from_bytes(&self.0)
+            .map_err(|e| SignatureError::Malformed(format!("public key: {}", e)))
+    }
+
+    /// Encode the key as base64
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.0)
+    }
+
+    /// Short hexadecimal ID of the key, for display
+    pub fn id(&self) -> String {
+        self.0[..8].iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.id())
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PublicKey({})", self.id())
+    }
+}
+
+/// Signature kept apart from the data it signs (a `.sig` file)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetachedSignature {
+    /// Always [`SIGNATURE_ALGORITHM`]
+    pub algorithm: String,
+    /// Base64 public key of the signer
+    pub public_key: String,
+    /// Base64 signature
+    pub signature: String,
+}
+
+impl DetachedSignature {
+    /// Load a signature file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SignatureError> {
+        serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| SignatureError::Malformed(format!("signature file: {}", e)))
+    }
+
+    /// Save as a signature file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SignatureError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| SignatureError::Malformed(e.to_string()))?;
+        std::fs::write(path, json + "\n")?;
+        Ok(())
+    }
+
+    /// The key that made the signature
+    pub fn signer(&self) -> Result<PublicKey, SignatureError> {
+        PublicKey::from_base64(&self.public_key)
+    }
+
+    /// Check that the signature was made over `data` by one of the `trusted` keys
+    ///
+    /// Returns the name the signer's key is trusted under.
+    pub fn verify<'t>(&self, data: &[u8], trusted: &'t TrustedKeys) -> Result<&'t str, SignatureError> {
+        if self.algorithm != SIGNATURE_ALGORITHM {
+            return Err(SignatureError::UnsupportedAlgorithm(self.algorithm.clone()));
+        }
+        let signer = self.signer()?;
+        let name = trusted.name_of(&signer).ok_or(SignatureError::UntrustedKey(signer))?;
+        let bytes =
+            base64::decode(&self.signature).map_err(|e| SignatureError::Malformed(format!("signature: {}", e)))?;
+        let signature = ed25519_dalek::Signature::from_slice(&bytes)
+            .map_err(|_| SignatureError::Malformed("signature must be 64 bytes".to_string()))?;
+        signer
+            .verifying_key()?
+            .verify(data, &signature)
+            .map_err(|_| SignatureError::Invalid)?;
+        Ok(name)
+    }
+}
+
+/// Check the file at `path` against the signature at its [`signature_path`]
+///
+/// Returns the name the signer's key is trusted under.
+pub fn verify_file(path: impl AsRef<Path>, trusted: &TrustedKeys) -> Result<&str, SignatureError> {
+    let path = path.as_ref();
+    let sig_path = signature_path(path);
+    if !sig_path.exists() {
+        return Err(SignatureError::Missing(sig_path));
+    }
+    let signature = DetachedSignature::load(&sig_path)?;
+    signature.verify(&std::fs::read(path)?, trusted)
+}
+
+/// Public keys whose signatures are accepted, each under a name
+///
+/// Saved as text, one `<base64 key> <name>` per line; blank lines and lines starting with `#`
+/// are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedKeys {
+    keys: Vec<(PublicKey, String)>,
+}
+
+impl TrustedKeys {
+    /// Parse the text form
+    pub fn parse(text: &str) -> Result<Self, SignatureError> {
+        let mut trusted = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, name) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let key = PublicKey::from_base64(key)
+                .map_err(|e| SignatureError::Malformed(format!("line {}: {}", number + 1, e)))?;
+            trusted.add(key, name.trim());
+        }
+        Ok(trusted)
+    }
+
+    /// Load trusted keys; a missing file trusts no key
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SignatureError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save in the text form
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SignatureError> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// Trust `key` under `name`, replacing the name if it is trusted already
+    pub fn add(&mut self, key: PublicKey, name: impl Into<String>) {
+        let name = name.into();
+        match self.keys.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => *existing = name,
+            None => self.keys.push((key, name)),
+        }
+    }
+
+    /// Stop trusting `key`, returning whether it was trusted
+    pub fn remove(&mut self, key: &PublicKey) -> bool {
+        let before = self.keys.len();
+        self.keys.retain(|(k, _)| k != key);
+        self.keys.len() != before
+    }
+
+    /// Name `key` is trusted under, if it is trusted
+    pub fn name_of(&self, key: &PublicKey) -> Option<&str> {
+        self.keys.iter().find(|(k, _)| k == key).map(|(_, name)| name.as_str())
+    }
+
+    /// Trusted keys and their names, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = (&PublicKey, &str)> {
+        self.keys.iter().map(|(key, name)| (key, name.as_str()))
+    }
+
+    /// Check if no key is trusted
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl fmt::Display for TrustedKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, name) in &self.keys {
+            writeln!(f, "{} {}", key.to_base64(), name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = SigningKey::generate().unwrap();
+        let signature = key.sign(b"plugin binary");
+
+        let mut trusted = TrustedKeys::default();
+        assert!(matches!(
+            signature.verify(b"plugin binary", &trusted),
+            Err(SignatureError::UntrustedKey(k)) if k == key.public_key()
+        ));
+
+        trusted.add(key.public_key(), "release");
+        assert_eq!(signature.verify(b"plugin binary", &trusted).unwrap(), "release");
+        assert!(matches!(
+            signature.verify(b"tampered binary", &trusted),
+            Err(SignatureError::Invalid)
+        ));
+
+        let mut other = signature.clone();
+        other.algorithm = "rsa".to_string();
+        assert!(matches!(
+            other.verify(b"plugin binary", &trusted),
+            Err(SignatureError::UnsupportedAlgorithm(_))
+        ));
+    }
+
+    #[test]
+    fn test_keys_round_trip() {
+        let key = SigningKey::generate().unwrap();
+        let decoded = SigningKey::from_base64(&key.to_base64()).unwrap();
+        assert_eq!(decoded.public_key(), key.public_key());
+        assert!(!format!("{:?}", key).contains(&key.to_base64()));
+
+        let mut trusted = TrustedKeys::default();
+        trusted.add(key.public_key(), "hodu release");
+        let text = format!("# trusted plugin publishers\n\n{}", trusted);
+        let parsed = TrustedKeys::parse(&text).unwrap();
+        assert_eq!(parsed.name_of(&key.public_key()), Some("hodu release"));
+
+        assert!(TrustedKeys::parse("not-a-key someone").is_err());
+        assert!(PublicKey::from_base64(&base64::encode(&[0u8; 16])).is_err());
+    }
+
+    #[test]
+    fn test_sign_file() {
+        let dir = std::env::temp_dir().join(format!("hodu-signing-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("plugin");
+        std::fs::write(&path, b"binary").unwrap();
+
+        let key = SigningKey::generate().unwrap();
+        let mut trusted = TrustedKeys::default();
+        trusted.add(key.public_key(), "me");
+        assert!(matches!(verify_file(&path, &trusted), Err(SignatureError::Missing(_))));
+
+        key.sign_file(&path).unwrap();
+        assert_eq!(signature_path(&path), dir.join("plugin.sig"));
+        assert_eq!(verify_file(&path, &trusted).unwrap(), "me");
+
+        std::fs::write(&path, b"patched").unwrap();
+        assert!(matches!(verify_file(&path, &trusted), Err(SignatureError::Invalid)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,554 @@
+//! Recorded JSON-RPC exchanges between the CLI and plugins
+//!
+//! With [`RPC_TRACE_ENV`] set, the CLI appends every message it exchanges with a plugin to a
+//! trace file, one [`TraceEntry`] per line. A [`Replay`] sends the recorded requests of one
+//! connection to another plugin build and compares its responses with the recorded ones, so a
+//! change in protocol behavior shows up as a difference:
+//!
+//! ```ignore
+//! let trace = Trace::load("run.trace")?;
+//! let mut replay = Replay::new(trace.plugin("hodu-backend-cpu").unwrap());
+//! while let Some((method, params)) = replay.next_request() {
+//!     let result = client.request(&method, params);
+//!     replay.check(result);
+//! }
+//! assert!(replay.finish().is_match());
+//! ```
+
+use crate::rpc::{methods, InitializeParams, Request, RequestId, Response, RpcError};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::BufRead;
+use std::path::Path;
+
+/// Environment variable naming the file the CLI records plugin exchanges to
+pub const RPC_TRACE_ENV: &str = "HODU_RPC_TRACE";
+
+/// Which way a recorded message went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// Sent by the CLI
+    ToPlugin,
+    /// Sent by the plugin
+    FromPlugin,
+}
+
+/// One recorded message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// Connection the message was exchanged on, unique within the trace
+    pub connection: String,
+    /// Which way the message went
+    pub direction: Direction,
+    /// Milliseconds since the connection was opened
+    pub elapsed_ms: u64,
+    /// The message itself
+    pub message: serde_json::Value,
+}
+
+/// A trace file, read back
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    /// Recorded messages, in the order they were written
+    pub entries: Vec<TraceEntry>,
+}
+
+impl Trace {
+    /// Read a trace file
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be read or a line is not a trace entry.
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Self::read(std::io::BufReader::new(file))
+    }
+
+    /// Read trace entries, one per line
+    ///
+    /// # Errors
+    /// Returns error if reading fails or a line is not a trace entry.
+    pub fn read(reader: impl BufRead) -> std::io::Result<Self> {
+        let mut entries = Vec::new();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("line {}: invalid trace entry: {}", number + 1, e),
+                )
+            })?;
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+
+    /// Connections in the trace, in the order they were opened
+    pub fn connections(&self) -> Vec<&str> {
+        let mut connections: Vec<&str> = Vec::new();
+        for entry in &self.entries {
+            if !connections.contains(&entry.connection.as_str()) {
+                connections.push(&entry.connection);
+            }
+        }
+        connections
+    }
+
+    /// Messages of `connection`, in order
+    pub fn connection(&self, connection: &str) -> Vec<TraceEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.connection == connection)
+            .cloned()
+            .collect()
+    }
+
+    /// Name the plugin on `connection` gave in its `initialize` response
+    pub fn plugin_name(&self, connection: &str) -> Option<String> {
+        let entries = self.connection(connection);
+        let initialize = recorded_requests(&entries)
+            .into_iter()
+            .find(|(request, _)| request.method == methods::INITIALIZE)?;
+        let result = initialize.1?.result?;
+        result.get("name")?.as_str().map(str::to_string)
+    }
+
+    /// Messages of the first connection to the plugin called `name`
+    pub fn plugin(&self, name: &str) -> Option<Vec<TraceEntry>> {
+        self.connections()
+            .into_iter()
+            .find(|connection| self.plugin_name(connection).as_deref() == Some(name))
+            .map(|connection| self.connection(connection))
+    }
+}
+
+/// Requests the CLI sent, each with the response the plugin gave, in the order they were sent
+fn recorded_requests(entries: &[TraceEntry]) -> Vec<(Request, Option<Response>)> {
+    let mut requests = Vec::new();
+    let mut responses: HashMap<RequestId, Response> = HashMap::new();
+    for entry in entries {
+        let message = &entry.message;
+        match entry.direction {
+            // Answers to requests from the plugin have no method
+            Direction::ToPlugin if message.get("method").is_some() => {
+                if let Ok(request) = serde_json::from_value::<Request>(message.clone()) {
+                    requests.push(request);
+                }
+            },
+            Direction::FromPlugin if message.get("method").is_none() => {
+                if let Ok(response) = serde_json::from_value::<Response>(message.clone()) {
+                    responses.insert(response.id.clone(), response);
+                }
+            },
+            _ => {},
+        }
+    }
+    requests
+        .into_iter()
+        .map(|request| {
+            let response = responses.remove(&request.id);
+            (request, response)
+        })
+        .collect()
+}
+
+// ============================================================================
+// Replay
+// ============================================================================
+
+/// Fields that differ between runs of the same plugin, ignored by default
+///
+/// Output files are named after the plugin's process and sessions get fresh ids. Later requests
+/// that reuse a recorded value of one of these fields are sent the replayed value instead.
+pub const DEFAULT_IGNORED: &[&str] = &["/outputs/*/path", "/session_id"];
+
+/// Recorded requests, sent again one at a time and checked against the recorded responses
+///
+/// Call [`next_request`](Self::next_request), send the request, and pass the result to
+/// [`check`](Self::check), until there are no requests left. `initialize`, `shutdown` and the
+/// `$/` requests are not replayed: the replaying client sends its own `initialize` and
+/// `shutdown`, and cancellations, metrics and status depend on timing. Requests that were
+/// cancelled in the recording are skipped for the same reason.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    initialize: Option<InitializeParams>,
+    pending: VecDeque<(Request, Option<Response>)>,
+    /// The request sent last, awaiting `check`
+    current: Option<(Request, Option<Response>)>,
+    ignored: Vec<String>,
+    /// Recorded values of ignored fields, and what the replay got instead
+    substitutions: HashMap<String, String>,
+    report: ReplayReport,
+}
+
+impl Replay {
+    /// Replay the requests of one connection, as returned by [`Trace::connection`]
+    pub fn new(entries: Vec<TraceEntry>) -> Self {
+        let mut initialize = None;
+        let mut pending = VecDeque::new();
+        let mut skipped = 0;
+        for (request, response) in recorded_requests(&entries) {
+            let cancelled = response
+                .as_ref()
+                .and_then(|response| response.error.as_ref())
+                .is_some_and(|error| error.code == crate::rpc::error_codes::REQUEST_CANCELLED);
+            if request.method == methods::INITIALIZE {
+                initialize = request.params.and_then(|params| serde_json::from_value(params).ok());
+            } else if request.method == methods::SHUTDOWN || request.method.starts_with("$/") || cancelled {
+                skipped += 1;
+            } else {
+                pending.push_back((request, response));
+            }
+        }
+        Self {
+            initialize,
+            pending,
+            current: None,
+            ignored: DEFAULT_IGNORED.iter().map(|path| path.to_string()).collect(),
+            substitutions: HashMap::new(),
+            report: ReplayReport {
+                replayed: 0,
+                skipped,
+                differences: Vec::new(),
+            },
+        }
+    }
+
+    /// Also ignore the result field at `path`, e.g. `/outputs/*/data`
+    ///
+    /// Paths are JSON pointers whose `*` segments match any array index or object key.
+    pub fn ignore(mut self, path: impl Into<String>) -> Self {
+        self.ignored.push(path.into());
+        self
+    }
+
+    /// The `initialize` params the CLI sent, if the recording includes them
+    pub fn initialize_params(&self) -> Option<&InitializeParams> {
+        self.initialize.as_ref()
+    }
+
+    /// Requests left to replay
+    pub fn remaining(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// The next request to send, as method and params
+    ///
+    /// Recorded values of ignored fields in the params are replaced with the values the replay
+    /// got, so e.g. a session opened during the replay is the one later requests use.
+    pub fn next_request(&mut self) -> Option<(String, Option<serde_json::Value>)> {
+        let (request, response) = self.pending.pop_front()?;
+        let mut params = request.params.clone();
+        if let Some(params) = &mut params {
+            substitute(params, &self.substitutions);
+        }
+        let method = request.method.clone();
+        self.current = Some((request, response));
+        Some((method, params))
+    }
+
+    /// Compare the result of the request [`next_request`](Self::next_request) returned with
+    /// the recorded response
+    ///
+    /// Errors match if their codes do; messages may be reworded between builds.
+    pub fn check(&mut self, result: Result<serde_json::Value, RpcError>) {
+        let Some((request, response)) = self.current.take() else {
+            return;
+        };
+        self.report.replayed += 1;
+        let Some(response) = response else {
+            // The recording ended before the plugin answered; nothing to compare with
+            return;
+        };
+
+        let mut differences = Vec::new();
+        match (response.error, response.result, result) {
+            (Some(expected), _, Err(actual)) => {
+                if expected.code != actual.code {
+                    differences.push(("/error/code".to_string(), expected.code.into(), actual.code.into()));
+                }
+            },
+            (Some(expected), _, Ok(actual)) => {
+                differences.push(("/error".to_string(), error_value(&expected), actual));
+            },
+            (None, expected, Err(actual)) => {
+                differences.push(("/error".to_string(), expected.unwrap_or_default(), error_value(&actual)));
+            },
+            (None, expected, Ok(actual)) => {
+                let expected = expected.unwrap_or_default();
+                compare(
+                    "",
+                    &expected,
+                    &actual,
+                    &self.ignored,
+                    &mut differences,
+                    &mut self.substitutions,
+                );
+            },
+        }
+
+        for (path, expected, actual) in differences {
+            self.report.differences.push(Difference {
+                method: request.method.clone(),
+                id: request.id.clone(),
+                path,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    /// How the replay went
+    ///
+    /// Requests never checked count as skipped.
+    pub fn finish(mut self) -> ReplayReport {
+        self.report.skipped += self.pending.len() + usize::from(self.current.is_some());
+        self.report
+    }
+}
+
+fn error_value(error: &RpcError) -> serde_json::Value {
+    serde_json::to_value(error).unwrap_or_default()
+}
+
+/// Record where `actual` differs from `expected`, below `path`
+fn compare(
+    path: &str,
+    expected: &serde_json::Value,
+    actual: &serde_json::Value,
+    ignored: &[String],
+    differences: &mut Vec<(String, serde_json::Value, serde_json::Value)>,
+    substitutions: &mut HashMap<String, String>,
+) {
+    use serde_json::Value;
+
+    if ignored.iter().any(|pattern| pointer_matches(pattern, path)) {
+        if let (Value::String(expected), Value::String(actual)) = (expected, actual) {
+            if expected != actual {
+                substitutions.insert(expected.clone(), actual.clone());
+            }
+        }
+        return;
+    }
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            let keys = expected
+                .keys()
+                .chain(actual.keys().filter(|key| !expected.contains_key(*key)));
+            for key in keys {
+                let child = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                let expected = expected.get(key).unwrap_or(&Value::Null);
+                let actual = actual.get(key).unwrap_or(&Value::Null);
+                compare(&child, expected, actual, ignored, differences, substitutions);
+            }
+        },
+        (Value::Array(expected_items), Value::Array(actual_items)) if expected_items.len() == actual_items.len() => {
+            for (i, (expected, actual)) in expected_items.iter().zip(actual_items).enumerate() {
+                compare(
+                    &format!("{}/{}", path, i),
+                    expected,
+                    actual,
+                    ignored,
+                    differences,
+                    substitutions,
+                );
+            }
+        },
+        _ if expected != actual => differences.push((path.to_string(), expected.clone(), actual.clone())),
+        _ => {},
+    }
+}
+
+/// Whether `pointer` matches `pattern`, whose `*` segments match any single segment
+fn pointer_matches(pattern: &str, pointer: &str) -> bool {
+    let mut pattern = pattern.split('/');
+    let mut pointer = pointer.split('/');
+    loop {
+        match (pattern.next(), pointer.next()) {
+            (None, None) => return true,
+            (Some(p), Some(s)) if p == "*" || p == s => {},
+            _ => return false,
+        }
+    }
+}
+
+/// Replace every string in `value` that has a substitution
+fn substitute(value: &mut serde_json::Value, substitutions: &HashMap<String, String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Some(replacement) = substitutions.get(s.as_str()) {
+                *s = replacement.clone();
+            }
+        },
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|item| substitute(item, substitutions)),
+        serde_json::Value::Object(fields) => fields.values_mut().for_each(|field| substitute(field, substitutions)),
+        _ => {},
+    }
+}
+
+/// A result field that differs from the recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// Method of the request
+    pub method: String,
+    /// ID of the request in the recording
+    pub id: RequestId,
+    /// JSON pointer to the field within the result, or `/error` for a changed outcome
+    pub path: String,
+    /// Recorded value
+    pub expected: serde_json::Value,
+    /// Replayed value
+    pub actual: serde_json::Value,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() { "/" } else { &self.path };
+        write!(
+            f,
+            "{} (request {}) {}: recorded {}, got {}",
+            self.method, self.id, path, self.expected, self.actual
+        )
+    }
+}
+
+/// How a [`Replay`] went
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayReport {
+    /// Requests sent and checked
+    pub replayed: usize,
+    /// Recorded requests not replayed
+    pub skipped: usize,
+    /// Where the responses differed from the recording
+    pub differences: Vec<Difference>,
+}
+
+impl ReplayReport {
+    /// Whether every response matched the recording
+    pub fn is_match(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(direction: Direction, message: serde_json::Value) -> TraceEntry {
+        TraceEntry {
+            connection: "hodu-backend-test#1".to_string(),
+            direction,
+            elapsed_ms: 0,
+            message,
+        }
+    }
+
+    fn recording() -> Vec<TraceEntry> {
+        use Direction::*;
+        vec![
+            entry(
+                ToPlugin,
+                json!({"jsonrpc": "2.0", "method": "initialize", "params": {"plugin_version": "0.1.0", "protocol_version": "1.0.0"}, "id": 1}),
+            ),
+            entry(
+                FromPlugin,
+                json!({"jsonrpc": "2.0", "result": {"name": "hodu-backend-test"}, "id": 1}),
+            ),
+            entry(
+                ToPlugin,
+                json!({"jsonrpc": "2.0", "method": "backend.load_session", "params": {"library_path": "m.so"}, "id": 2}),
+            ),
+            entry(
+                FromPlugin,
+                json!({"jsonrpc": "2.0", "method": "$/progress", "params": {"message": "loading"}}),
+            ),
+            entry(
+                FromPlugin,
+                json!({"jsonrpc": "2.0", "result": {"session_id": "s-1"}, "id": 2}),
+            ),
+            entry(
+                ToPlugin,
+                json!({"jsonrpc": "2.0", "method": "backend.run_session", "params": {"session_id": "s-1"}, "id": 3}),
+            ),
+            entry(
+                FromPlugin,
+                json!({"jsonrpc": "2.0", "result": {"outputs": [{"name": "y", "path": "/tmp/a.hdt"}]}, "id": 3}),
+            ),
+            entry(
+                ToPlugin,
+                json!({"jsonrpc": "2.0", "method": "backend.run_session", "params": {"session_id": "s-1"}, "id": 4}),
+            ),
+            entry(
+                FromPlugin,
+                json!({"jsonrpc": "2.0", "error": {"code": -32007, "message": "Request cancelled"}, "id": 4}),
+            ),
+            entry(
+                ToPlugin,
+                json!({"jsonrpc": "2.0", "method": "backend.close_session", "params": {"session_id": "s-1"}, "id": 5}),
+            ),
+            entry(
+                FromPlugin,
+                json!({"jsonrpc": "2.0", "error": {"code": -32602, "message": "bad"}, "id": 5}),
+            ),
+            entry(ToPlugin, json!({"jsonrpc": "2.0", "method": "shutdown", "id": 6})),
+        ]
+    }
+
+    #[test]
+    fn test_trace_lines_round_trip() {
+        let lines: String = recording()
+            .iter()
+            .map(|entry| serde_json::to_string(entry).unwrap() + "\n")
+            .collect();
+        assert!(lines.starts_with(r#"{"connection":"hodu-backend-test#1","direction":"to_plugin""#));
+        let trace = Trace::read(lines.as_bytes()).unwrap();
+        assert_eq!(trace.entries, recording());
+        assert_eq!(trace.connections(), ["hodu-backend-test#1"]);
+        assert_eq!(
+            trace.plugin_name("hodu-backend-test#1").as_deref(),
+            Some("hodu-backend-test")
+        );
+        assert!(trace.plugin("hodu-backend-other").is_none());
+    }
+
+    #[test]
+    fn test_replay_substitutes_and_compares() {
+        let mut replay = Replay::new(recording());
+        assert_eq!(replay.initialize_params().unwrap().protocol_version, "1.0.0");
+        assert_eq!(replay.remaining(), 3);
+
+        let (method, _) = replay.next_request().unwrap();
+        assert_eq!(method, "backend.load_session");
+        replay.check(Ok(json!({"session_id": "s-9"})));
+
+        // The session opened in the replay is used from now on
+        let (_, params) = replay.next_request().unwrap();
+        assert_eq!(params.unwrap()["session_id"], "s-9");
+        replay.check(Ok(json!({"outputs": [{"name": "z", "path": "/tmp/b.hdt"}]})));
+
+        let (_, params) = replay.next_request().unwrap();
+        assert_eq!(params.unwrap()["session_id"], "s-9");
+        replay.check(Err(RpcError::new(-32602, "reworded")));
+        assert!(replay.next_request().is_none());
+
+        let report = replay.finish();
+        assert_eq!((report.replayed, report.skipped), (3, 2));
+        assert_eq!(report.differences.len(), 1);
+        assert_eq!(report.differences[0].path, "/outputs/0/name");
+        assert_eq!(report.differences[0].expected, "y");
+        assert!(!report.is_match());
+    }
+
+    #[test]
+    fn test_pointer_patterns() {
+        assert!(pointer_matches("/outputs/*/path", "/outputs/3/path"));
+        assert!(!pointer_matches("/outputs/*/path", "/outputs/3/name"));
+        assert!(!pointer_matches("/outputs/*", "/outputs/3/path"));
+        assert!(pointer_matches("/session_id", "/session_id"));
+    }
+}
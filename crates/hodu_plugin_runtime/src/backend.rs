@@ -3,8 +3,10 @@
 //! This module provides the runtime for loading and executing backend plugins.
 //! Backend plugins handle model inference execution and AOT compilation.
 
+use crate::child::tie_to_parent;
 use crate::client::{CancellationHandle, ClientError, PluginClient, DEFAULT_TIMEOUT};
 use crate::registry::{PluginEntry, PluginRegistry, RegistryError};
+use crate::types::PluginSource;
 use hodu_plugin::rpc::InitializeResult;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -25,9 +27,9 @@ pub struct PluginManager {
     timeout: Duration,
 }
 
-/// A managed plugin process
+/// A managed plugin process, or a connection to a remote plugin daemon
 struct ManagedPlugin {
-    child: Child,
+    child: Option<Child>,
     client: PluginClient,
     info: InitializeResult,
 }
@@ -101,24 +103,34 @@ impl PluginManager {
         self.get_plugin(&entry.name)
     }
 
-    /// Spawn a plugin process
+    /// Spawn a plugin process, or connect to it if it runs remotely
     fn spawn_plugin(&self, entry: &PluginEntry) -> Result<ManagedPlugin, ManagerError> {
-        let binary_path = self.plugins_dir.join(&entry.name).join(&entry.binary);
-
-        if !binary_path.exists() {
-            return Err(ManagerError::BinaryNotFound(binary_path.to_string_lossy().to_string()));
-        }
-
-        // Spawn process
-        let mut child = Command::new(&binary_path)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::inherit())
-            .spawn()
-            .map_err(|e| ManagerError::Spawn(e.to_string()))?;
-
-        // Create client
-        let mut client = PluginClient::new(&mut child).map_err(ManagerError::Client)?;
+        let (child, mut client) = match &entry.source {
+            PluginSource::Remote { endpoint } => {
+                let client = PluginClient::connect_remote(endpoint).map_err(ManagerError::Client)?;
+                (None, client)
+            },
+            _ => {
+                let binary_path = self.plugins_dir.join(&entry.name).join(&entry.binary);
+
+                if !binary_path.exists() {
+                    return Err(ManagerError::BinaryNotFound(binary_path.to_string_lossy().to_string()));
+                }
+
+                // Spawn process
+                let mut child = Command::new(&binary_path)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::inherit())
+                    .spawn()
+                    .map_err(|e| ManagerError::Spawn(e.to_string()))?;
+                tie_to_parent(&child);
+
+                // Create client
+                let client = PluginClient::new(&mut child).map_err(ManagerError::Client)?;
+                (Some(child), client)
+            },
+        };
 
         // Set timeout
         client.set_timeout(self.timeout);
@@ -133,7 +145,9 @@ impl PluginManager {
     pub fn shutdown_plugin(&mut self, name: &str) -> Result<(), ManagerError> {
         if let Some(mut managed) = self.processes.remove(name) {
             let _ = managed.client.shutdown();
-            let _ = managed.child.wait();
+            if let Some(mut child) = managed.child {
+                let _ = child.wait();
+            }
         }
         Ok(())
     }
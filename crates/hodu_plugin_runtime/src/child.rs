@@ -0,0 +1,79 @@
+//! Tying plugin processes to the lifetime of the CLI
+//!
+//! Plugins shut themselves down when their stdin closes or, on Unix, when they notice they have
+//! been reparented. Windows has neither reliable signal, so there every spawned plugin is placed
+//! in a job object that the OS kills once the CLI's last handle to it closes, including when the
+//! CLI crashes.
+
+use std::process::Child;
+
+/// Make `child` exit when this process does
+///
+/// Best effort: failures are logged and the child keeps running unbound. A no-op outside
+/// Windows, where plugins detect the CLI exiting on their own.
+pub fn tie_to_parent(child: &Child) {
+    #[cfg(windows)]
+    windows::assign_to_job(child);
+    #[cfg(not(windows))]
+    let _ = child;
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use std::sync::OnceLock;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+        JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// Job shared by every plugin, stored as an address since `HANDLE` is not `Send`
+    ///
+    /// The handle is never closed; the OS closes it when this process exits, which kills the job.
+    fn job() -> Option<HANDLE> {
+        static JOB: OnceLock<Option<usize>> = OnceLock::new();
+        JOB.get_or_init(|| unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job.is_null() {
+                eprintln!(
+                    "Warning: Failed to create plugin job object: {}",
+                    std::io::Error::last_os_error()
+                );
+                return None;
+            }
+            let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            let ok = SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &info as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            );
+            if ok == 0 {
+                eprintln!(
+                    "Warning: Failed to configure plugin job object: {}",
+                    std::io::Error::last_os_error()
+                );
+                CloseHandle(job);
+                return None;
+            }
+            Some(job as usize)
+        })
+        .map(|job| job as HANDLE)
+    }
+
+    pub(super) fn assign_to_job(child: &Child) {
+        let Some(job) = job() else {
+            return;
+        };
+        if unsafe { AssignProcessToJobObject(job, child.as_raw_handle() as HANDLE) } == 0 {
+            eprintln!(
+                "Warning: Failed to bind plugin process {} to the CLI: {}",
+                child.id(),
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
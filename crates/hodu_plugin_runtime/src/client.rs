@@ -1,38 +1,170 @@
 //! JSON-RPC client for plugin communication
 //!
 //! This module provides the client-side JSON-RPC implementation for communicating
-//! with plugin processes over stdio.
+//! with plugin processes over stdio, or with plugin daemons over TCP and Unix domain sockets.
 
 use hodu_plugin::rpc::{
-    methods, BuildParams, CancelParams, InitializeParams, InitializeResult, ListTargetsResult, LoadModelParams,
-    LoadModelResult, LoadTensorParams, LoadTensorResult, LogParams, Notification, Request, RequestId, Response,
-    RpcError, RunParams, RunResult, SaveModelParams, SaveTensorParams, TensorInput, JSONRPC_VERSION, PROTOCOL_VERSION,
+    error_codes, features, methods, negotiate_inline_tensor_limit, BenchmarkParams, BenchmarkResult, BuildParams,
+    CancelParams, CancelReason, CloseSessionParams, CustomOpParams, InitializeParams, InitializeResult,
+    ListTargetsResult, LoadModelParams, LoadModelResult, LoadSessionParams, LoadSessionResult, LoadTensorParams,
+    LoadTensorResult, LogParams, MetricsResult, Notification, PrecisionParams, ProfileParams, ProfileResult,
+    QuantizeParams, QuantizeResult, Request, RequestId, Response, RpcError, RunParams, RunResult, RunSessionParams,
+    SaveModelParams, SaveTensorParams, StatusResult, StreamCreditParams, StreamEvent, StreamLoadTensorParams,
+    StreamLoadTensorResult, StreamParams, TensorInput, DEFAULT_INLINE_TENSOR_LIMIT, INITIAL_STREAM_CREDITS,
+    JSONRPC_VERSION, PROTOCOL_VERSION,
 };
-use hodu_plugin::PLUGIN_VERSION;
-use std::io::{BufRead, BufReader, Write};
-use std::process::{Child, ChildStdin};
+use hodu_plugin::trace::{Direction, TraceEntry};
+use hodu_plugin::{read_message, Frame, Framing, SandboxPolicy, PLUGIN_VERSION};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::Child;
 use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Default timeout for RPC requests (5 minutes)
 pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
 
+/// Environment variable holding the token presented to remote plugins
+pub const AUTH_TOKEN_ENV: &str = "HODU_PLUGIN_TOKEN";
+
 /// Notification handler callback type
 pub type NotificationHandler = Box<dyn Fn(&str, Option<&serde_json::Value>) + Send>;
 
+/// Handler for requests the plugin sends to the CLI while a call is in progress
+pub type RequestHandler = Box<dyn FnMut(&str, Option<serde_json::Value>) -> Result<serde_json::Value, RpcError> + Send>;
+
+/// Handler for `$/stream` results the plugin pushes while a call is in progress
+pub type StreamHandler = Box<dyn FnMut(&StreamParams) + Send>;
+
+/// Check run when the plugin's connection closes, returning the error to report instead
+///
+/// Used to tell a plugin that was killed at a resource limit from one that crashed.
+pub type ExitCheck = Box<dyn FnMut() -> Option<RpcError> + Send>;
+
+/// Address of a plugin daemon started with `listen_tcp` or `listen_unix`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginEndpoint {
+    /// `tcp://host:port`
+    Tcp(String),
+    /// `unix:///path/to/socket`
+    Unix(PathBuf),
+}
+
+impl std::str::FromStr for PluginEndpoint {
+    type Err = ClientError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(addr) = s.strip_prefix("tcp://") {
+            if !addr.is_empty() {
+                return Ok(PluginEndpoint::Tcp(addr.to_string()));
+            }
+        } else if let Some(path) = s.strip_prefix("unix://") {
+            if !path.is_empty() {
+                return Ok(PluginEndpoint::Unix(PathBuf::from(path)));
+            }
+        }
+        Err(ClientError::InvalidEndpoint(s.to_string()))
+    }
+}
+
+impl std::fmt::Display for PluginEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginEndpoint::Tcp(addr) => write!(f, "tcp://{}", addr),
+            PluginEndpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+/// Appends every message exchanged on a connection to a trace file, one
+/// [`TraceEntry`] per line
+///
+/// Recording is best effort: a message that cannot be written is left out of the trace rather
+/// than failing the request. Auth tokens are redacted.
+#[derive(Clone)]
+pub struct RpcRecorder {
+    file: Arc<Mutex<File>>,
+    connection: String,
+    start: Instant,
+}
+
+impl RpcRecorder {
+    /// Record to `file`, which connections may share, labelling entries with `connection`
+    pub fn new(file: Arc<Mutex<File>>, connection: impl Into<String>) -> Self {
+        Self {
+            file,
+            connection: connection.into(),
+            start: Instant::now(),
+        }
+    }
+
+    fn record(&self, direction: Direction, json: &str) {
+        let mut message = serde_json::from_str(json).unwrap_or_else(|_| serde_json::Value::String(json.to_string()));
+        if let Some(token) = message.pointer_mut("/params/auth_token") {
+            *token = serde_json::Value::String("<redacted>".to_string());
+        }
+        let entry = TraceEntry {
+            connection: self.connection.clone(),
+            direction,
+            elapsed_ms: self.start.elapsed().as_millis() as u64,
+            message,
+        };
+        let Ok(mut line) = serde_json::to_string(&entry) else {
+            return;
+        };
+        line.push('\n');
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+/// Write half of the plugin connection together with the framing negotiated for it
+struct PluginWriter {
+    writer: Box<dyn Write + Send>,
+    framing: Framing,
+    recorder: Option<RpcRecorder>,
+}
+
+impl PluginWriter {
+    /// Serialize and send one message
+    fn send<T: serde::Serialize>(&mut self, message: &T) -> Result<(), ClientError> {
+        let json = serde_json::to_string(message).map_err(ClientError::Serialize)?;
+        if let Some(recorder) = &self.recorder {
+            recorder.record(Direction::ToPlugin, &json);
+        }
+        self.framing
+            .write_message(&mut self.writer, &json)
+            .map_err(ClientError::Io)?;
+        self.writer.flush().map_err(ClientError::Io)
+    }
+
+    /// Close the write half so the plugin reads end of input; later sends are discarded
+    fn close(&mut self) {
+        self.writer = Box::new(std::io::sink());
+    }
+}
+
 /// Handle for cancelling requests from another thread (e.g., signal handler)
 #[derive(Clone)]
 pub struct CancellationHandle {
-    stdin: Arc<Mutex<ChildStdin>>,
+    writer: Arc<Mutex<PluginWriter>>,
     current_request_id: Arc<AtomicI64>,
     next_id: Arc<AtomicI64>,
 }
 
 impl CancellationHandle {
-    /// Cancel the current request if one is in progress
+    /// Cancel the current request if one is in progress, as the user asked
     pub fn cancel(&self) -> Result<(), ClientError> {
+        self.cancel_with(CancelReason::UserAbort)
+    }
+
+    /// Cancel the current request if one is in progress, telling the plugin why
+    pub fn cancel_with(&self, reason: CancelReason) -> Result<(), ClientError> {
         let current_id = self.current_request_id.load(Ordering::SeqCst);
         if current_id == 0 {
             return Ok(()); // No request in progress
@@ -40,6 +172,7 @@ impl CancellationHandle {
 
         let params = CancelParams {
             id: RequestId::Number(current_id),
+            reason: Some(reason),
         };
         let request = Request::new(
             methods::CANCEL,
@@ -47,22 +180,41 @@ impl CancellationHandle {
             RequestId::Number(self.next_id.fetch_add(1, Ordering::SeqCst)),
         );
 
-        let mut stdin = self.stdin.lock().map_err(|_| ClientError::LockError)?;
-        let json = serde_json::to_string(&request).map_err(ClientError::Serialize)?;
-        writeln!(stdin, "{}", json).map_err(ClientError::Io)?;
-        stdin.flush().map_err(ClientError::Io)?;
-        Ok(())
+        self.writer.lock().map_err(|_| ClientError::LockError)?.send(&request)
+    }
+
+    /// Whether a request is in progress
+    pub fn is_busy(&self) -> bool {
+        self.current_request_id.load(Ordering::SeqCst) != 0
     }
 }
 
 /// JSON-RPC client for communicating with a plugin process
 pub struct PluginClient {
-    stdin: Arc<Mutex<ChildStdin>>,
-    line_receiver: mpsc::Receiver<Result<String, std::io::Error>>,
+    writer: Arc<Mutex<PluginWriter>>,
+    message_receiver: mpsc::Receiver<Result<String, std::io::Error>>,
     next_id: Arc<AtomicI64>,
     current_request_id: Arc<AtomicI64>,
     notification_handler: Option<NotificationHandler>,
+    request_handler: Option<RequestHandler>,
+    stream_handler: Option<StreamHandler>,
+    exit_check: Option<ExitCheck>,
+    /// Set once the connection is found closed; no request can succeed after that
+    closed: bool,
+    config: Option<serde_json::Value>,
+    /// Directories the plugin is confined to, for untrusted plugins
+    sandbox: Option<SandboxPolicy>,
+    /// Extra features to offer in `initialize`, beyond those this client implements
+    extra_features: Vec<String>,
+    /// Features agreed on in `initialize`
+    negotiated_features: Vec<String>,
+    /// Largest tensor in bytes either side sends inline (0 until negotiated)
+    inline_tensor_limit: u64,
     timeout: Duration,
+    auth_token: Option<String>,
+    recorder: Option<RpcRecorder>,
+    /// Chunks consumed per open stream since credit was last granted for them
+    stream_consumed: HashMap<(RequestId, String), u64>,
 }
 
 impl PluginClient {
@@ -70,20 +222,65 @@ impl PluginClient {
     pub fn new(child: &mut Child) -> Result<Self, ClientError> {
         let stdin = child.stdin.take().ok_or(ClientError::NoStdin)?;
         let stdout = child.stdout.take().ok_or(ClientError::NoStdout)?;
+        Ok(Self::from_streams(stdout, Box::new(stdin)))
+    }
+
+    /// Connect to a plugin daemon
+    ///
+    /// Call [`set_auth_token`](Self::set_auth_token) before [`initialize`](Self::initialize)
+    /// if the daemon requires one.
+    pub fn connect(endpoint: &PluginEndpoint) -> Result<Self, ClientError> {
+        match endpoint {
+            PluginEndpoint::Tcp(addr) => {
+                let stream = std::net::TcpStream::connect(addr).map_err(ClientError::Io)?;
+                let _ = stream.set_nodelay(true);
+                let writer = stream.try_clone().map_err(ClientError::Io)?;
+                Ok(Self::from_streams(stream, Box::new(writer)))
+            },
+            #[cfg(unix)]
+            PluginEndpoint::Unix(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path).map_err(ClientError::Io)?;
+                let writer = stream.try_clone().map_err(ClientError::Io)?;
+                Ok(Self::from_streams(stream, Box::new(writer)))
+            },
+            #[cfg(not(unix))]
+            PluginEndpoint::Unix(_) => Err(ClientError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Unix domain sockets are not supported on this platform",
+            ))),
+        }
+    }
 
-        // Spawn a reader thread that sends lines through a channel
+    /// Connect to the daemon at `endpoint` (e.g. `tcp://host:port`), presenting the token in
+    /// [`AUTH_TOKEN_ENV`] if it is set
+    pub fn connect_remote(endpoint: &str) -> Result<Self, ClientError> {
+        let mut client = Self::connect(&endpoint.parse()?)?;
+        if let Ok(token) = std::env::var(AUTH_TOKEN_ENV) {
+            client.set_auth_token(token);
+        }
+        Ok(client)
+    }
+
+    fn from_streams<R: Read + Send + 'static>(reader: R, writer: Box<dyn Write + Send>) -> Self {
+        // Spawn a reader thread that sends messages through a channel
+        // The reader detects each message's framing, so it needs no notice when it switches
         let (tx, rx) = mpsc::channel();
         std::thread::spawn(move || {
-            let mut reader = BufReader::new(stdout);
+            let mut reader = BufReader::new(reader);
             loop {
-                let mut line = String::new();
-                match reader.read_line(&mut line) {
-                    Ok(0) => break, // EOF
-                    Ok(_) => {
-                        if tx.send(Ok(line)).is_err() {
+                match read_message(&mut reader, usize::MAX) {
+                    Ok(None) => break, // EOF
+                    Ok(Some(Frame::Message(message))) => {
+                        if tx.send(Ok(message)).is_err() {
                             break; // Receiver dropped
                         }
                     },
+                    Ok(Some(Frame::TooLarge(_))) => unreachable!("no message exceeds usize::MAX"),
+                    // A plugin writing broken output is not worth reading on
+                    Ok(Some(Frame::Invalid(reason))) => {
+                        let _ = tx.send(Err(std::io::Error::new(std::io::ErrorKind::InvalidData, reason)));
+                        break;
+                    },
                     Err(e) => {
                         let _ = tx.send(Err(e));
                         break;
@@ -92,14 +289,91 @@ impl PluginClient {
             }
         });
 
-        Ok(Self {
-            stdin: Arc::new(Mutex::new(stdin)),
-            line_receiver: rx,
+        Self {
+            writer: Arc::new(Mutex::new(PluginWriter {
+                writer,
+                framing: Framing::Line,
+                recorder: None,
+            })),
+            message_receiver: rx,
             next_id: Arc::new(AtomicI64::new(1)),
             current_request_id: Arc::new(AtomicI64::new(0)),
             notification_handler: None,
+            request_handler: None,
+            stream_handler: None,
+            exit_check: None,
+            closed: false,
+            config: None,
+            sandbox: None,
+            extra_features: Vec::new(),
+            negotiated_features: Vec::new(),
+            inline_tensor_limit: 0,
             timeout: DEFAULT_TIMEOUT,
-        })
+            auth_token: None,
+            recorder: None,
+            stream_consumed: HashMap::new(),
+        }
+    }
+
+    /// Record every message exchanged from now on, including `initialize` if set before it
+    pub fn set_recorder(&mut self, recorder: RpcRecorder) {
+        if let Ok(mut writer) = self.writer.lock() {
+            writer.recorder = Some(recorder.clone());
+        }
+        self.recorder = Some(recorder);
+    }
+
+    /// Set the token presented to plugins that require one
+    pub fn set_auth_token(&mut self, token: impl Into<String>) {
+        self.auth_token = Some(token.into());
+    }
+
+    /// Set the configuration sent to the plugin in `initialize`
+    pub fn set_config(&mut self, config: serde_json::Value) {
+        self.config = Some(config);
+    }
+
+    /// Confine the plugin to the directories of `policy`, sent in `initialize`
+    pub fn set_sandbox(&mut self, policy: SandboxPolicy) {
+        self.sandbox = Some(policy);
+    }
+
+    /// The sandbox the plugin was confined to, if any
+    pub fn sandbox(&self) -> Option<&SandboxPolicy> {
+        self.sandbox.as_ref()
+    }
+
+    /// Check a path the plugin handed back, such as an output file, before reading it
+    ///
+    /// A sandboxed plugin may only point at files it could have written itself; any path is
+    /// accepted from a plugin without a sandbox.
+    pub fn check_plugin_path(&self, path: impl AsRef<std::path::Path>) -> Result<(), ClientError> {
+        match &self.sandbox {
+            Some(policy) => policy.check_write(path).map_err(ClientError::Io),
+            None => Ok(()),
+        }
+    }
+
+    /// Offer an additional protocol feature in `initialize`, for hosts that implement it themselves
+    pub fn add_feature(&mut self, feature: impl Into<String>) {
+        self.extra_features.push(feature.into());
+    }
+
+    /// Protocol features negotiated with the plugin (empty before `initialize`)
+    pub fn features(&self) -> &[String] {
+        &self.negotiated_features
+    }
+
+    /// Whether a protocol feature was negotiated with the plugin
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.negotiated_features.iter().any(|f| f == feature)
+    }
+
+    /// Largest tensor in bytes to send inline rather than through a file
+    ///
+    /// 0 before `initialize`, or if the plugin does not accept inline tensors.
+    pub fn inline_tensor_limit(&self) -> u64 {
+        self.inline_tensor_limit
     }
 
     /// Set the timeout for RPC requests
@@ -110,7 +384,7 @@ impl PluginClient {
     /// Get a cancellation handle for use from another thread (e.g., Ctrl+C handler)
     pub fn cancellation_handle(&self) -> CancellationHandle {
         CancellationHandle {
-            stdin: Arc::clone(&self.stdin),
+            writer: Arc::clone(&self.writer),
             current_request_id: Arc::clone(&self.current_request_id),
             next_id: Arc::clone(&self.next_id),
         }
@@ -121,15 +395,76 @@ impl PluginClient {
         self.notification_handler = Some(handler);
     }
 
+    /// Set the handler answering requests from the plugin (without one they fail as not found)
+    pub fn set_request_handler(&mut self, handler: RequestHandler) {
+        self.request_handler = Some(handler);
+    }
+
+    /// Set the handler receiving streamed results (without one they go to the notification handler)
+    pub fn set_stream_handler(&mut self, handler: StreamHandler) {
+        self.stream_handler = Some(handler);
+    }
+
+    /// Remove the stream handler, returning it if one was set
+    pub fn take_stream_handler(&mut self) -> Option<StreamHandler> {
+        self.stream_handler.take()
+    }
+
+    /// Set the check run when the plugin closes its connection mid-request
+    pub fn set_exit_check(&mut self, check: ExitCheck) {
+        self.exit_check = Some(check);
+    }
+
+    /// Whether the plugin closed its connection, e.g. because it crashed
+    ///
+    /// The client has to be replaced by a new one to keep using the plugin.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// The error for a connection the plugin closed, explained by the exit check if it can
+    fn connection_closed(&mut self) -> ClientError {
+        match self.exit_check.as_mut().and_then(|check| check()) {
+            Some(error) => ClientError::Rpc(error),
+            None => ClientError::ConnectionClosed,
+        }
+    }
+
     /// Initialize the plugin and validate version compatibility
     pub fn initialize(&mut self) -> Result<InitializeResult, ClientError> {
+        // Requests from the plugin can only be answered with a handler to answer them
+        let mut features = vec![
+            features::STREAMING.to_string(),
+            features::STREAM_CREDITS.to_string(),
+            features::INLINE_TENSORS.to_string(),
+        ];
+        if self.request_handler.is_some() {
+            features.push(features::HOST_CALLS.to_string());
+        }
+        features.extend(self.extra_features.iter().cloned());
+
         let params = InitializeParams {
             plugin_version: PLUGIN_VERSION.to_string(),
             protocol_version: PROTOCOL_VERSION.to_string(),
+            framings: vec![Framing::ContentLength, Framing::Line],
+            auth_token: self.auth_token.clone(),
+            config: self.config.clone(),
+            features,
+            inline_tensor_limit: Some(DEFAULT_INLINE_TENSOR_LIMIT),
+            sandbox: self.sandbox.clone(),
         };
 
         let result: InitializeResult = self.call(methods::INITIALIZE, Some(params))?;
 
+        // The plugin writes with the chosen framing from here on; follow suit
+        self.writer.lock().map_err(|_| ClientError::LockError)?.framing = result.framing;
+        self.negotiated_features = result.features.clone();
+        self.inline_tensor_limit = negotiate_inline_tensor_limit(
+            self.has_feature(features::INLINE_TENSORS),
+            Some(DEFAULT_INLINE_TENSOR_LIMIT),
+            result.inline_tensor_limit,
+        );
+
         // Validate protocol version compatibility
         // - For 0.x.y: major.minor must match (unstable API)
         // - For >= 1.0.0: major must match
@@ -159,10 +494,18 @@ impl PluginClient {
             None,
             RequestId::Number(self.next_id.fetch_add(1, Ordering::SeqCst)),
         );
-        let mut stdin = self.stdin.lock().map_err(|_| ClientError::LockError)?;
-        let json = serde_json::to_string(&request).map_err(ClientError::Serialize)?;
-        writeln!(stdin, "{}", json).map_err(ClientError::Io)?;
-        stdin.flush().map_err(ClientError::Io)?;
+        let mut writer = self.writer.lock().map_err(|_| ClientError::LockError)?;
+        writer.send(&request)?;
+        // The plugin's stdin reader only returns at end of input, and it must return for the plugin to exit
+        writer.close();
+        Ok(())
+    }
+
+    /// Ask a plugin daemon to stop once this connection ends
+    ///
+    /// The daemon closes the connection after answering, so the client is unusable afterwards.
+    pub fn exit(&mut self) -> Result<(), ClientError> {
+        self.call::<(), serde_json::Value>(methods::EXIT, None)?;
         Ok(())
     }
 
@@ -195,6 +538,23 @@ impl PluginClient {
         self.call(methods::FORMAT_LOAD_TENSOR, Some(params))
     }
 
+    /// Load a tensor file using format plugin, receiving its data as a stream
+    ///
+    /// Only plugins listing `format.stream_load_tensor` in their capabilities support it. The
+    /// tensor arrives through the stream handler as `TensorChunk` items on the `tensor` stream.
+    #[cfg(feature = "format")]
+    pub fn stream_load_tensor(
+        &mut self,
+        path: &str,
+        chunk_size: Option<u64>,
+    ) -> Result<StreamLoadTensorResult, ClientError> {
+        let params = StreamLoadTensorParams {
+            path: path.to_string(),
+            chunk_size,
+        };
+        self.call(methods::FORMAT_STREAM_LOAD_TENSOR, Some(params))
+    }
+
     /// Save a tensor to file using format plugin
     #[cfg(feature = "format")]
     pub fn save_tensor(&mut self, tensor_path: &str, output_path: &str) -> Result<(), ClientError> {
@@ -211,6 +571,8 @@ impl PluginClient {
     // ========================================================================
 
     /// Run model inference using backend plugin
+    ///
+    /// `precision` overrides the backend's precision policy for this run only.
     #[cfg(feature = "backend")]
     pub fn run(
         &mut self,
@@ -218,16 +580,99 @@ impl PluginClient {
         snapshot_path: &str,
         device: &str,
         inputs: Vec<TensorInput>,
+        precision: Option<PrecisionParams>,
     ) -> Result<RunResult, ClientError> {
         let params = RunParams {
             library_path: library_path.to_string(),
             snapshot_path: snapshot_path.to_string(),
             device: device.to_string(),
             inputs,
+            precision,
         };
         self.call(methods::BACKEND_RUN, Some(params))
     }
 
+    /// Run model inference once, recording per-op timings and memory
+    ///
+    /// Only plugins listing `backend.profile` in their capabilities support it.
+    #[cfg(feature = "backend")]
+    pub fn profile(
+        &mut self,
+        library_path: &str,
+        snapshot_path: &str,
+        device: &str,
+        inputs: Vec<TensorInput>,
+        precision: Option<PrecisionParams>,
+    ) -> Result<ProfileResult, ClientError> {
+        let params = ProfileParams {
+            library_path: library_path.to_string(),
+            snapshot_path: snapshot_path.to_string(),
+            device: device.to_string(),
+            inputs,
+            precision,
+        };
+        self.call(methods::BACKEND_PROFILE, Some(params))
+    }
+
+    /// Load a compiled model once for repeated runs, returning the session ID
+    ///
+    /// Only plugins listing `backend.load_session` in their capabilities support sessions.
+    #[cfg(feature = "backend")]
+    pub fn load_session(
+        &mut self,
+        library_path: &str,
+        snapshot_path: &str,
+        device: &str,
+        precision: Option<PrecisionParams>,
+    ) -> Result<String, ClientError> {
+        let params = LoadSessionParams {
+            library_path: library_path.to_string(),
+            snapshot_path: snapshot_path.to_string(),
+            device: device.to_string(),
+            precision,
+        };
+        let result: LoadSessionResult = self.call(methods::BACKEND_LOAD_SESSION, Some(params))?;
+        Ok(result.session_id)
+    }
+
+    /// Run model inference on a session opened with [`load_session`](Self::load_session)
+    #[cfg(feature = "backend")]
+    pub fn run_session(&mut self, session_id: &str, inputs: Vec<TensorInput>) -> Result<RunResult, ClientError> {
+        let params = RunSessionParams {
+            session_id: session_id.to_string(),
+            inputs,
+        };
+        self.call(methods::BACKEND_RUN_SESSION, Some(params))
+    }
+
+    /// Release a session, freeing the model it keeps loaded
+    #[cfg(feature = "backend")]
+    pub fn close_session(&mut self, session_id: &str) -> Result<(), ClientError> {
+        let params = CloseSessionParams {
+            session_id: session_id.to_string(),
+        };
+        self.call::<_, serde_json::Value>(methods::BACKEND_CLOSE_SESSION, Some(params))?;
+        Ok(())
+    }
+
+    /// Time repeated runs of a compiled model
+    ///
+    /// Only plugins listing `backend.benchmark` in their capabilities support it. The whole
+    /// benchmark is one call, so the client timeout must cover every iteration.
+    #[cfg(feature = "backend")]
+    pub fn benchmark(&mut self, params: BenchmarkParams) -> Result<BenchmarkResult, ClientError> {
+        self.call(methods::BACKEND_BENCHMARK, Some(params))
+    }
+
+    /// Write a quantized copy of a snapshot
+    ///
+    /// Only plugins listing `backend.quantize` in their capabilities support it. Calibration runs
+    /// within the call, so the client timeout must cover every sample.
+    #[cfg(feature = "backend")]
+    pub fn quantize(&mut self, params: QuantizeParams) -> Result<QuantizeResult, ClientError> {
+        self.call(methods::BACKEND_QUANTIZE, Some(params))
+    }
+
     /// Build (AOT compile) model using backend plugin
     #[cfg(feature = "backend")]
     pub fn build(
@@ -255,6 +700,44 @@ impl PluginClient {
         self.call(methods::BACKEND_SUPPORTED_TARGETS, Some(serde_json::json!({})))
     }
 
+    /// Fetch the plugin's request metrics in Prometheus text format
+    pub fn metrics(&mut self) -> Result<String, ClientError> {
+        let result: MetricsResult = self.call(methods::METRICS, None::<()>)?;
+        Ok(result.text)
+    }
+
+    /// Fetch the plugin's uptime, requests in flight, and memory use
+    pub fn status(&mut self) -> Result<StatusResult, ClientError> {
+        self.call(methods::STATUS, None::<()>)
+    }
+
+    /// Execute a custom op using the plugin that declared `op.<name>`
+    #[cfg(feature = "backend")]
+    pub fn custom_op(
+        &mut self,
+        name: &str,
+        attributes: serde_json::Value,
+        device: &str,
+        inputs: Vec<TensorInput>,
+    ) -> Result<RunResult, ClientError> {
+        let params = CustomOpParams {
+            name: name.to_string(),
+            attributes,
+            device: device.to_string(),
+            inputs,
+        };
+        self.call(&format!("{}{}", methods::CUSTOM_OP_PREFIX, name), Some(params))
+    }
+
+    /// Send a request with untyped params and result, e.g. to replay a recorded one
+    pub fn request(
+        &mut self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, ClientError> {
+        self.call(method, params)
+    }
+
     // ========================================================================
     // Internal
     // ========================================================================
@@ -284,29 +767,38 @@ impl PluginClient {
         };
 
         // Send request
-        {
-            let mut stdin = self.stdin.lock().map_err(|_| ClientError::LockError)?;
-            let json = serde_json::to_string(&request).map_err(ClientError::Serialize)?;
-            writeln!(stdin, "{}", json).map_err(ClientError::Io)?;
-            stdin.flush().map_err(ClientError::Io)?;
+        let sent = self.writer.lock().map_err(|_| ClientError::LockError)?.send(&request);
+        if let Err(e) = sent {
+            self.closed |= matches!(e, ClientError::Io(_));
+            return Err(e);
         }
 
         // Read response, handling notifications along the way
         loop {
-            let line = match self.line_receiver.recv_timeout(self.timeout) {
-                Ok(Ok(line)) => line,
-                Ok(Err(e)) => return Err(ClientError::Io(e)),
-                Err(RecvTimeoutError::Timeout) => return Err(ClientError::Timeout(self.timeout)),
-                Err(RecvTimeoutError::Disconnected) => return Err(ClientError::ConnectionClosed),
+            let message = match self.message_receiver.recv_timeout(self.timeout) {
+                Ok(Ok(message)) => message,
+                Ok(Err(e)) => {
+                    self.closed = true;
+                    return Err(ClientError::Io(e));
+                },
+                Err(RecvTimeoutError::Timeout) => {
+                    // Best effort: let the plugin stop the work and clean up, as nobody will read the result
+                    let _ = self.cancellation_handle().cancel_with(CancelReason::Timeout);
+                    self.current_request_id.store(0, Ordering::SeqCst);
+                    return Err(ClientError::Timeout(self.timeout));
+                },
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.closed = true;
+                    return Err(self.connection_closed());
+                },
             };
-
-            if line.is_empty() {
-                return Err(ClientError::ConnectionClosed);
+            if let Some(recorder) = &self.recorder {
+                recorder.record(Direction::FromPlugin, &message);
             }
 
             // Try to parse as JSON first
             let value: serde_json::Value =
-                serde_json::from_str(&line).map_err(|e| ClientError::Parse(e.to_string()))?;
+                serde_json::from_str(&message).map_err(|e| ClientError::Parse(e.to_string()))?;
 
             // Check if it's a notification (no "id" field) or response (has "id" field)
             if value.get("id").is_none() {
@@ -317,11 +809,27 @@ impl PluginClient {
                 continue; // Keep reading for the actual response
             }
 
+            // A request from the plugin, which waits for our answer before it can respond
+            if value.get("method").is_some() {
+                let request: Request = serde_json::from_value(value).map_err(|e| ClientError::Parse(e.to_string()))?;
+                let response = self.handle_request(request);
+                self.writer
+                    .lock()
+                    .map_err(|_| ClientError::LockError)?
+                    .send(&response)?;
+                continue;
+            }
+
+            let response: Response = serde_json::from_str(&message).map_err(|e| ClientError::Parse(e.to_string()))?;
+
+            // A late answer to an earlier request whose caller gave up on it, e.g. after a timeout
+            if matches!(response.id, RequestId::Number(n) if n < id_num) {
+                continue;
+            }
+
             // It's a response - clear current request ID
             self.current_request_id.store(0, Ordering::SeqCst);
 
-            let response: Response = serde_json::from_str(&line).map_err(|e| ClientError::Parse(e.to_string()))?;
-
             // Verify ID matches
             if response.id != id {
                 return Err(ClientError::IdMismatch);
@@ -338,8 +846,86 @@ impl PluginClient {
         }
     }
 
+    /// Answer a request from the plugin
+    fn handle_request(&mut self, request: Request) -> Response {
+        let result = match &mut self.request_handler {
+            Some(handler) => handler(&request.method, request.params),
+            None => Err(RpcError::method_not_found(&request.method)),
+        };
+        match result {
+            Ok(value) => Response::success(request.id, value),
+            Err(error) => Response::error(request.id, error),
+        }
+    }
+
     /// Handle a notification from the plugin
-    fn handle_notification(&self, notification: &Notification) {
+    fn handle_notification(&mut self, notification: &Notification) {
+        if notification.method == methods::NOTIFY_STREAM {
+            let params = notification
+                .params
+                .as_ref()
+                .and_then(|params| serde_json::from_value::<StreamParams>(params.clone()).ok());
+            if let Some(handler) = &mut self.stream_handler {
+                if let Some(p) = &params {
+                    handler(p);
+                }
+            } else {
+                self.dispatch_notification(notification);
+            }
+            // The chunk has been handled, so the plugin may send another
+            if let Some(p) = &params {
+                self.consume_stream_event(p);
+            }
+            return;
+        }
+        self.dispatch_notification(notification);
+    }
+
+    /// Grant the plugin credit for consumed stream chunks, once half the window is consumed
+    fn consume_stream_event(&mut self, params: &StreamParams) {
+        if !self.has_feature(features::STREAM_CREDITS) {
+            return;
+        }
+        let key = (params.request_id.clone(), params.stream.clone());
+        match params.event {
+            StreamEvent::Open => {
+                self.stream_consumed.insert(key, 0);
+            },
+            StreamEvent::Close { .. } => {
+                self.stream_consumed.remove(&key);
+            },
+            StreamEvent::Chunk { .. } => {
+                let consumed = self.stream_consumed.entry(key).or_default();
+                *consumed += 1;
+                if *consumed < INITIAL_STREAM_CREDITS / 2 {
+                    return;
+                }
+                let credit = StreamCreditParams {
+                    request_id: params.request_id.clone(),
+                    stream: params.stream.clone(),
+                    credits: std::mem::take(consumed),
+                };
+                // Sent with an ID of its own, as `$/cancel` is; it is not answered
+                let request = Request::new(
+                    methods::STREAM_CREDIT,
+                    serde_json::to_value(credit).ok(),
+                    RequestId::Number(self.next_id.fetch_add(1, Ordering::SeqCst)),
+                );
+                // Without the credit the stream stalls, until the request times out
+                let sent = self
+                    .writer
+                    .lock()
+                    .map_err(|_| ClientError::LockError)
+                    .and_then(|mut writer| writer.send(&request));
+                if let Err(e) = sent {
+                    eprintln!("Warning: Failed to grant stream credit: {}", e);
+                }
+            },
+        }
+    }
+
+    /// Hand a notification to the notification handler, or print logs without one
+    fn dispatch_notification(&mut self, notification: &Notification) {
         if let Some(handler) = &self.notification_handler {
             handler(&notification.method, notification.params.as_ref());
         } else {
@@ -374,6 +960,7 @@ pub enum ClientError {
     ProtocolMismatch { cli: String, plugin: String },
     LockError,
     Timeout(Duration),
+    InvalidEndpoint(String),
 }
 
 /// Check if two protocol versions are compatible
@@ -412,7 +999,10 @@ impl std::fmt::Display for ClientError {
             ClientError::ConnectionClosed => write!(f, "Plugin connection closed unexpectedly"),
             ClientError::IdMismatch => write!(f, "Response ID does not match request"),
             ClientError::NoResult => write!(f, "Response has no result"),
-            ClientError::Rpc(e) => write!(f, "RPC error ({}): {}", e.code, e.message),
+            ClientError::Rpc(e) => match error_codes::lookup(e.code) {
+                Some(info) => write!(f, "RPC error ({} {}): {}", e.code, info.name, e.message),
+                None => write!(f, "RPC error ({}): {}", e.code, e.message),
+            },
             ClientError::ProtocolMismatch { cli, plugin } => write!(
                 f,
                 "Protocol version mismatch: CLI ({}) incompatible with plugin ({})",
@@ -422,8 +1012,52 @@ impl std::fmt::Display for ClientError {
             ClientError::Timeout(duration) => {
                 write!(f, "Plugin request timed out after {} seconds", duration.as_secs())
             },
+            ClientError::InvalidEndpoint(endpoint) => write!(
+                f,
+                "Invalid plugin endpoint '{}' (expected tcp://host:port or unix:///path)",
+                endpoint
+            ),
         }
     }
 }
 
 impl std::error::Error for ClientError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::TcpListener;
+
+    /// A plugin over TCP that answers `slow` only after `delay` and everything else at once
+    fn slow_plugin(delay: Duration) -> PluginEndpoint {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = PluginEndpoint::Tcp(listener.local_addr().unwrap().to_string());
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                let request: Request = serde_json::from_str(&line).unwrap();
+                match request.method.as_str() {
+                    methods::CANCEL => continue,
+                    "slow" => std::thread::sleep(delay),
+                    _ => {},
+                }
+                let response = Response::success(request.id, serde_json::json!(request.method));
+                writeln!(writer, "{}", serde_json::to_string(&response).unwrap()).unwrap();
+            }
+        });
+        endpoint
+    }
+
+    #[test]
+    fn test_request_after_timeout_skips_late_response() {
+        let mut client = PluginClient::connect(&slow_plugin(Duration::from_millis(300))).unwrap();
+        client.set_timeout(Duration::from_millis(100));
+
+        assert!(matches!(client.request("slow", None), Err(ClientError::Timeout(_))));
+        // The answer to `slow` arrives while this request waits, and is not taken for its own
+        client.set_timeout(Duration::from_secs(5));
+        assert_eq!(client.request("fast", None).unwrap(), serde_json::json!("fast"));
+    }
+}
@@ -0,0 +1,108 @@
+//! Host side of plugin-to-CLI requests
+//!
+//! While the CLI waits on a call, the plugin may send requests back over the same connection.
+//! [`HostServices`] answers the `host.*` methods by delegating to other installed plugins, so a
+//! backend can, for example, read an input tensor in a format only a format plugin understands.
+
+use crate::client::ClientError;
+use crate::format::{ManagerError, PluginManager};
+use hodu_plugin::rpc::{methods, LoadModelParams, LoadTensorParams, RpcError};
+use hodu_plugin::SandboxPolicy;
+use std::path::Path;
+
+/// Answers `host.*` requests from plugins
+///
+/// Format plugins are started on first use and kept alive for later requests. They run in their
+/// own manager and cannot make host requests themselves, so delegation never recurses.
+#[derive(Default)]
+pub struct HostServices {
+    formats: Option<PluginManager>,
+}
+
+impl HostServices {
+    /// Create host services; no plugin is started until a request needs one
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer one request from a plugin
+    pub fn handle(&mut self, method: &str, params: Option<serde_json::Value>) -> Result<serde_json::Value, RpcError> {
+        match method {
+            methods::HOST_LOAD_MODEL => {
+                let params: LoadModelParams = parse_params(params)?;
+                let ext = extension(&params.path)?;
+                let result = self
+                    .formats()?
+                    .get_for_model_extension(&ext)
+                    .map_err(manager_error)?
+                    .load_model(&params.path)
+                    .map_err(client_error)?;
+                serde_json::to_value(result).map_err(|e| RpcError::internal_error(e.to_string()))
+            },
+            methods::HOST_LOAD_TENSOR => {
+                let params: LoadTensorParams = parse_params(params)?;
+                let ext = extension(&params.path)?;
+                let result = self
+                    .formats()?
+                    .get_for_tensor_extension(&ext)
+                    .map_err(manager_error)?
+                    .load_tensor(&params.path)
+                    .map_err(client_error)?;
+                serde_json::to_value(result).map_err(|e| RpcError::internal_error(e.to_string()))
+            },
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    /// Answer one request from a sandboxed plugin, which may only load files it may read itself
+    pub fn handle_sandboxed(
+        &mut self,
+        method: &str,
+        params: Option<serde_json::Value>,
+        sandbox: &SandboxPolicy,
+    ) -> Result<serde_json::Value, RpcError> {
+        // Both `host.*` loads name their file in `path`
+        if let Some(path) = params.as_ref().and_then(|p| p.get("path")).and_then(|p| p.as_str()) {
+            sandbox
+                .check_read(path)
+                .map_err(|e| RpcError::access_denied(e.to_string()))?;
+        }
+        self.handle(method, params)
+    }
+
+    fn formats(&mut self) -> Result<&mut PluginManager, RpcError> {
+        if self.formats.is_none() {
+            self.formats = Some(PluginManager::new().map_err(manager_error)?);
+        }
+        Ok(self.formats.as_mut().expect("format manager initialized above"))
+    }
+}
+
+fn parse_params<P: serde::de::DeserializeOwned>(params: Option<serde_json::Value>) -> Result<P, RpcError> {
+    let params = params.ok_or_else(|| RpcError::invalid_params("Missing params"))?;
+    serde_json::from_value(params).map_err(|e| RpcError::invalid_params(e.to_string()))
+}
+
+fn extension(path: &str) -> Result<String, RpcError> {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_string)
+        .ok_or_else(|| RpcError::invalid_params(format!("Cannot pick a format for '{}' without an extension", path)))
+}
+
+fn manager_error(e: ManagerError) -> RpcError {
+    match e {
+        ManagerError::NoFormatForExtension(_) => RpcError::not_supported(e.to_string()),
+        ManagerError::Client(e) => client_error(e),
+        e => RpcError::internal_error(e.to_string()),
+    }
+}
+
+/// Pass the delegate plugin's own error through unchanged
+fn client_error(e: ClientError) -> RpcError {
+    match e {
+        ClientError::Rpc(error) => error,
+        e => RpcError::internal_error(e.to_string()),
+    }
+}
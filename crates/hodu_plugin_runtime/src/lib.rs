@@ -22,15 +22,26 @@
 
 #[cfg(feature = "backend")]
 pub mod backend;
+mod child;
 mod client;
 #[cfg(feature = "format")]
 pub mod format;
+#[cfg(feature = "format")]
+mod host;
+mod limits;
 mod registry;
 #[cfg(all(feature = "format", feature = "backend"))]
 mod runtime;
 mod types;
 
-pub use client::{CancellationHandle, ClientError, PluginClient, DEFAULT_TIMEOUT};
+pub use child::tie_to_parent;
+pub use client::{
+    CancellationHandle, ClientError, ExitCheck, PluginClient, PluginEndpoint, RpcRecorder, AUTH_TOKEN_ENV,
+    DEFAULT_TIMEOUT,
+};
+#[cfg(feature = "format")]
+pub use host::HostServices;
+pub use limits::ResourceLimits;
 pub use registry::{detect_plugin_type, PluginDetectError, PluginRegistry, RegistryError};
 #[cfg(all(feature = "format", feature = "backend"))]
 pub use runtime::{Model, Runtime, RuntimeError};
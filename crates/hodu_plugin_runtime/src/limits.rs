@@ -0,0 +1,244 @@
+//! Memory and CPU time limits for plugin processes
+//!
+//! On Unix the limits are rlimits the child sets on itself before it execs the plugin:
+//! `RLIMIT_DATA` for memory and `RLIMIT_CPU` for CPU time. On Windows the plugin is placed in a
+//! job object of its own with per-process memory and user time limits. A plugin over its memory
+//! limit fails to allocate, which aborts a Rust plugin, and one over its CPU time is killed;
+//! [`ResourceLimits::exhausted`] recognizes both from the exit status, so the user is told which
+//! limit was hit rather than that the connection closed.
+
+use hodu_plugin::rpc::RpcError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::{Child, Command, ExitStatus};
+use std::time::{Duration, Instant};
+
+/// How long a plugin that closed its connection gets to exit before its status is given up on
+const EXIT_WAIT: Duration = Duration::from_secs(1);
+
+/// Resources a plugin process may use, set per plugin in the registry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Memory the plugin may allocate, in megabytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// CPU time the plugin may use over its lifetime, in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_secs: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Whether no limit is set
+    pub fn is_empty(&self) -> bool {
+        self.memory_mb.is_none() && self.cpu_secs.is_none()
+    }
+
+    /// Make the process `command` spawns start with these limits (Unix)
+    ///
+    /// A no-op elsewhere; call [`bind`](Self::bind) on the spawned child too.
+    pub fn apply(&self, command: &mut Command) {
+        #[cfg(unix)]
+        unix::apply(self, command);
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+
+    /// Hold the spawned `child` to these limits (Windows)
+    ///
+    /// Best effort: failures are logged and the child runs unlimited. A no-op outside Windows,
+    /// where [`apply`](Self::apply) sets the limits before the plugin starts.
+    pub fn bind(&self, child: &Child) {
+        #[cfg(windows)]
+        windows::bind(self, child);
+        #[cfg(not(windows))]
+        let _ = child;
+    }
+
+    /// The error to report for a plugin that exited with `status`, if it ran out of a resource
+    pub fn exhausted(&self, status: ExitStatus) -> Option<RpcError> {
+        let (limit, cause) = self.exhausted_limit(status)?;
+        Some(RpcError::resource_exhausted(format!(
+            "Plugin exceeded its {} limit ({})",
+            limit, cause
+        )))
+    }
+
+    /// Wait briefly for `child`, whose connection just closed, and check how it exited
+    ///
+    /// Returns `None` if it exited for another reason or is still running.
+    pub fn check_exit(&self, child: &mut Child) -> Option<RpcError> {
+        let deadline = Instant::now() + EXIT_WAIT;
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => return self.exhausted(status),
+                Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+                _ => return None,
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn exhausted_limit(&self, status: ExitStatus) -> Option<(String, String)> {
+        use std::os::unix::process::ExitStatusExt;
+        let memory = self.memory_mb.map(|mb| format!("{} MB memory", mb));
+        let cpu = self.cpu_secs.map(|secs| format!("{} s CPU time", secs));
+        let (limit, signal) = match status.signal()? {
+            libc::SIGXCPU => (cpu?, "SIGXCPU"),
+            // The hard CPU limit and the OOM killer both kill outright
+            libc::SIGKILL => (memory.or(cpu)?, "SIGKILL"),
+            // A failed allocation aborts a Rust plugin, and may crash one written in C
+            libc::SIGABRT => (memory?, "aborted, most likely on a failed allocation"),
+            libc::SIGSEGV => (memory?, "crashed, most likely on a failed allocation"),
+            _ => return None,
+        };
+        Some((limit, signal.to_string()))
+    }
+
+    #[cfg(windows)]
+    fn exhausted_limit(&self, status: ExitStatus) -> Option<(String, String)> {
+        /// Exit code of a process the job killed at its time limit
+        const ERROR_NOT_ENOUGH_QUOTA: u32 = 1816;
+        /// Exit code of an abort, as on a failed allocation
+        const STATUS_STACK_BUFFER_OVERRUN: u32 = 0xC000_0409;
+        const STATUS_NO_MEMORY: u32 = 0xC000_0017;
+        let code = status.code()? as u32;
+        match code {
+            ERROR_NOT_ENOUGH_QUOTA => Some((
+                format!("{} s CPU time", self.cpu_secs?),
+                "killed by its job".to_string(),
+            )),
+            STATUS_STACK_BUFFER_OVERRUN | STATUS_NO_MEMORY => Some((
+                format!("{} MB memory", self.memory_mb?),
+                "aborted, most likely on a failed allocation".to_string(),
+            )),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn exhausted_limit(&self, _status: ExitStatus) -> Option<(String, String)> {
+        None
+    }
+}
+
+impl fmt::Display for ResourceLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(mb) = self.memory_mb {
+            parts.push(format!("{} MB memory", mb));
+        }
+        if let Some(secs) = self.cpu_secs {
+            parts.push(format!("{} s CPU time", secs));
+        }
+        if parts.is_empty() {
+            return write!(f, "none");
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::ResourceLimits;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    pub(super) fn apply(limits: &ResourceLimits, command: &mut Command) {
+        if limits.is_empty() {
+            return;
+        }
+        let memory = limits
+            .memory_mb
+            .map(|mb| mb.saturating_mul(1024 * 1024) as libc::rlim_t);
+        let cpu = limits.cpu_secs.map(|secs| secs as libc::rlim_t);
+        // SAFETY: setrlimit is async-signal-safe, and nothing is allocated between fork and exec
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(bytes) = memory {
+                    let limit = libc::rlimit {
+                        rlim_cur: bytes,
+                        rlim_max: bytes,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_DATA, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                if let Some(secs) = cpu {
+                    // SIGXCPU at the soft limit tells the CLI why; the hard limit kills a plugin ignoring it
+                    let limit = libc::rlimit {
+                        rlim_cur: secs,
+                        rlim_max: secs.saturating_add(1),
+                    };
+                    if libc::setrlimit(libc::RLIMIT_CPU, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::ResourceLimits;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+        JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE, JOB_OBJECT_LIMIT_PROCESS_MEMORY,
+        JOB_OBJECT_LIMIT_PROCESS_TIME,
+    };
+
+    /// Place `child` in a job of its own, nested in the one every plugin shares
+    ///
+    /// The handle is never closed; the OS closes it when this process exits, which kills the job.
+    pub(super) fn bind(limits: &ResourceLimits, child: &Child) {
+        if limits.is_empty() {
+            return;
+        }
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job.is_null() {
+                warn(child, "create");
+                return;
+            }
+            let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            if let Some(mb) = limits.memory_mb {
+                info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+                info.ProcessMemoryLimit = mb.saturating_mul(1024 * 1024) as usize;
+            }
+            if let Some(secs) = limits.cpu_secs {
+                // In 100-nanosecond ticks
+                info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+                info.BasicLimitInformation.PerProcessUserTimeLimit = secs.saturating_mul(10_000_000) as i64;
+            }
+            let ok = SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &info as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            );
+            if ok == 0 {
+                warn(child, "configure");
+                CloseHandle(job);
+                return;
+            }
+            if AssignProcessToJobObject(job, child.as_raw_handle() as HANDLE) == 0 {
+                warn(child, "assign");
+                CloseHandle(job);
+            }
+        }
+    }
+
+    fn warn(child: &Child, step: &str) {
+        eprintln!(
+            "Warning: Failed to {} the job limiting plugin process {}: {}",
+            step,
+            child.id(),
+            std::io::Error::last_os_error()
+        );
+    }
+}
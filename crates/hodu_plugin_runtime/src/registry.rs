@@ -1,6 +1,7 @@
 //! Plugin registry - storage and lookup for installed plugins
 
 pub use super::types::{DetectedPluginType, PluginEntry, PluginType};
+use crate::limits::ResourceLimits;
 use fs2::FileExt;
 use serde::{Deserialize, Serialize};
 use std::fs::OpenOptions;
@@ -172,6 +173,26 @@ impl PluginRegistry {
         }
     }
 
+    /// Mark a plugin as trusted or, to sandbox it, untrusted
+    pub fn set_trusted(&mut self, name: &str, trusted: bool) -> bool {
+        if let Some(plugin) = self.find_mut(name) {
+            plugin.trusted = trusted;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Set the resource limits of a plugin's process
+    pub fn set_limits(&mut self, name: &str, limits: ResourceLimits) -> bool {
+        if let Some(plugin) = self.find_mut(name) {
+            plugin.limits = limits;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Check if all dependencies of a plugin are installed and enabled
     pub fn check_dependencies(&self, name: &str) -> Result<(), Vec<String>> {
         let plugin = match self.find(name) {
@@ -226,6 +247,14 @@ impl PluginRegistry {
                 .any(|e| e.trim_start_matches('.').to_lowercase() == ext_normalized)
         })
     }
+
+    /// Find a plugin that executes the given custom op (enabled only, any plugin type)
+    pub fn find_custom_op(&self, name: &str) -> Option<&PluginEntry> {
+        self.plugins
+            .iter()
+            .filter(|p| p.enabled)
+            .find(|p| p.capabilities.custom_ops.iter().any(|op| op == name))
+    }
 }
 
 /// Registry errors
@@ -309,6 +338,9 @@ pub fn detect_plugin_type(path: &std::path::Path) -> Result<DetectedPluginType,
                 .map(|s| s == "format.load_tensor" || s == "format.save_tensor")
                 .unwrap_or(false)
         });
+        let has_custom_ops = caps
+            .iter()
+            .any(|c| c.as_str().map(|s| s.starts_with("op.")).unwrap_or(false));
 
         if has_backend {
             return Ok(DetectedPluginType::Backend {
@@ -328,6 +360,13 @@ pub fn detect_plugin_type(path: &std::path::Path) -> Result<DetectedPluginType,
                 version,
                 plugin_version,
             });
+        } else if has_custom_ops {
+            // Plugins that only provide custom ops are treated as backends
+            return Ok(DetectedPluginType::Backend {
+                name,
+                version,
+                plugin_version,
+            });
         } else {
             // Default to ModelFormat if type cannot be determined
             return Ok(DetectedPluginType::ModelFormat {
@@ -21,9 +21,9 @@ use crate::client::ClientError;
 use crate::format;
 use hodu_core::format::hdt;
 use hodu_core::tensor::Tensor;
-use hodu_core::types::DType;
-use hodu_plugin::rpc::TensorInput;
-use hodu_plugin::tensor::PluginDType;
+use hodu_core::types::{get_precision, DType, Device, Shape};
+use hodu_plugin::rpc::{PrecisionParams, TensorInput};
+use hodu_plugin::tensor::{PluginDType, TensorData};
 use std::path::{Path, PathBuf};
 
 /// High-level runtime for model loading and execution
@@ -121,6 +121,9 @@ impl Runtime {
     /// * `device` - Device to run on (e.g., "cpu", "cuda::0", "metal")
     /// * `backend` - Backend plugin name (e.g., "aot-cpu"). If empty or not found, auto-selects based on device.
     ///
+    /// The calling thread's [`Precision`](hodu_core::types::Precision) is sent along, so
+    /// `Precision::scope` overrides it for a single run.
+    ///
     /// # Returns
     ///
     /// Vector of output tensors with their names
@@ -146,37 +149,68 @@ impl Runtime {
                 .map_err(RuntimeError::Backend)?
         };
 
-        // Prepare inputs - save to temp files
+        // Prepare inputs - small ones go inline, the rest to temp files
         let temp_dir = std::env::temp_dir().join("hodu_runtime");
         std::fs::create_dir_all(&temp_dir).map_err(|e| RuntimeError::Io(e.to_string()))?;
+        let inline_limit = client.inline_tensor_limit();
 
         let mut tensor_inputs = Vec::new();
         for (name, tensor) in inputs {
+            let size = (tensor.size() * tensor.dtype().size_in_bytes()) as u64;
+            if inline_limit > 0 && size <= inline_limit {
+                let data = tensor
+                    .to_bytes()
+                    .map_err(|e| RuntimeError::Other(format!("Failed to read input tensor: {}", e)))?;
+                let tensor_data = TensorData::new(
+                    data,
+                    tensor.shape().dims().to_vec(),
+                    core_dtype_to_plugin(tensor.dtype()),
+                );
+                tensor_inputs.push(TensorInput::inline(*name, &tensor_data));
+                continue;
+            }
+
             let input_path = temp_dir.join(format!("{}.hdt", name));
             hdt::save(tensor, &input_path)
                 .map_err(|e| RuntimeError::Other(format!("Failed to save input tensor: {}", e)))?;
 
-            tensor_inputs.push(TensorInput {
-                name: name.to_string(),
-                path: input_path.to_string_lossy().to_string(),
-            });
+            tensor_inputs.push(TensorInput::new(*name, input_path.to_string_lossy()));
         }
 
         // Run inference
+        let precision = get_precision();
         let result = client
             .run(
                 "", // lib_path - empty means backend handles caching
                 model.snapshot_path.to_string_lossy().as_ref(),
                 device,
                 tensor_inputs,
+                Some(PrecisionParams {
+                    allow_tf32: Some(precision.allow_tf32),
+                    f16_accumulate_f32: Some(precision.f16_accumulate_f32),
+                }),
             )
             .map_err(RuntimeError::Client)?;
 
         // Load output tensors
         let mut outputs = Vec::new();
         for output in result.outputs {
-            let tensor = hdt::load(&output.path)
-                .map_err(|e| RuntimeError::Other(format!("Failed to load output tensor: {}", e)))?;
+            let tensor = match &output.data {
+                Some(inline) => {
+                    let data = inline
+                        .decode()
+                        .map_err(|e| RuntimeError::Other(format!("Invalid inline output tensor: {}", e)))?;
+                    Tensor::from_bytes(
+                        &data.data,
+                        Shape::new(&data.shape),
+                        plugin_dtype_to_core(data.dtype),
+                        Device::CPU,
+                    )
+                    .map_err(|e| RuntimeError::Other(format!("Failed to create output tensor: {}", e)))?
+                },
+                None => hdt::load(&output.path)
+                    .map_err(|e| RuntimeError::Other(format!("Failed to load output tensor: {}", e)))?,
+            };
             outputs.push((output.name, tensor));
         }
 
@@ -237,7 +271,6 @@ impl std::fmt::Display for RuntimeError {
 impl std::error::Error for RuntimeError {}
 
 // Helper functions for dtype conversion
-#[allow(dead_code)]
 fn core_dtype_to_plugin(dtype: DType) -> PluginDType {
     match dtype {
         DType::BOOL => PluginDType::BOOL,
@@ -258,7 +291,6 @@ fn core_dtype_to_plugin(dtype: DType) -> PluginDType {
     }
 }
 
-#[allow(dead_code)]
 fn plugin_dtype_to_core(dtype: PluginDType) -> DType {
     match dtype {
         PluginDType::BOOL => DType::BOOL,
@@ -1,6 +1,8 @@
 //! Plugin types and data structures
 
+use crate::limits::ResourceLimits;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// A single plugin entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,7 +22,7 @@ pub struct PluginEntry {
     pub plugin_type: PluginType,
     /// Plugin capabilities
     pub capabilities: PluginCapabilities,
-    /// Executable binary filename (e.g., "hodu-plugin-onnx")
+    /// Executable binary filename (e.g., "hodu-plugin-onnx"); empty for remote plugins
     pub binary: String,
     /// Installation source
     pub source: PluginSource,
@@ -31,6 +33,12 @@ pub struct PluginEntry {
     /// Whether the plugin is enabled (default: true)
     #[serde(default = "default_enabled")]
     pub enabled: bool,
+    /// Whether the plugin may use any file (default: true); untrusted plugins are sandboxed
+    #[serde(default = "default_trusted")]
+    pub trusted: bool,
+    /// Memory and CPU time the plugin process may use (default: unlimited)
+    #[serde(default, skip_serializing_if = "ResourceLimits::is_empty")]
+    pub limits: ResourceLimits,
     /// Plugin dependencies (other plugin names)
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub dependencies: Vec<String>,
@@ -40,6 +48,10 @@ fn default_enabled() -> bool {
     true
 }
 
+fn default_trusted() -> bool {
+    true
+}
+
 /// Plugin type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -85,6 +97,11 @@ pub struct PluginCapabilities {
     /// Supported tensor file extensions (e.g., ["npy", "npz"])
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tensor_extensions: Vec<String>,
+
+    // Custom op capabilities
+    /// Custom ops executed via `op.<name>` RPC methods (e.g., ["acme.fused_gelu"])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_ops: Vec<String>,
 }
 
 impl PluginCapabilities {
@@ -101,6 +118,7 @@ impl PluginCapabilities {
             save_tensor: None,
             model_extensions: Vec::new(),
             tensor_extensions: Vec::new(),
+            custom_ops: Vec::new(),
         }
     }
 
@@ -117,6 +135,7 @@ impl PluginCapabilities {
             save_tensor: None,
             model_extensions,
             tensor_extensions: Vec::new(),
+            custom_ops: Vec::new(),
         }
     }
 
@@ -133,12 +152,13 @@ impl PluginCapabilities {
             save_tensor: Some(save_tensor),
             model_extensions: Vec::new(),
             tensor_extensions,
+            custom_ops: Vec::new(),
         }
     }
 }
 
 /// Installation source
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum PluginSource {
     /// From crates.io
@@ -150,9 +170,31 @@ pub enum PluginSource {
         tag: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         subdir: Option<String>,
+        /// Commit that was checked out and built
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
     },
     /// From local path
     Local { path: String },
+    /// Prebuilt binary downloaded from a plugin index
+    Index {
+        url: String,
+        /// Version requirement updates keep to (e.g., "^0.2"); any version if unset
+        #[serde(skip_serializing_if = "Option::is_none")]
+        requirement: Option<String>,
+        /// SHA-256 of the release's prebuilt zips by target triple, as listed in the index
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        checksums: BTreeMap<String, String>,
+    },
+    /// Daemon reached over a socket instead of a spawned binary
+    Remote { endpoint: String },
+}
+
+impl PluginSource {
+    /// Whether the plugin runs as a daemon the CLI connects to
+    pub fn is_remote(&self) -> bool {
+        matches!(self, PluginSource::Remote { .. })
+    }
 }
 
 impl std::fmt::Display for PluginSource {
@@ -167,6 +209,14 @@ impl std::fmt::Display for PluginSource {
                 }
             },
             PluginSource::Local { path } => write!(f, "local:{}", path),
+            PluginSource::Index { url, requirement, .. } => {
+                if let Some(r) = requirement {
+                    write!(f, "index:{}@{}", url, r)
+                } else {
+                    write!(f, "index:{}", url)
+                }
+            },
+            PluginSource::Remote { endpoint } => write!(f, "remote:{}", endpoint),
         }
     }
 }
@@ -0,0 +1,183 @@
+// Shared helpers prepended to every kernel module.
+//
+// Every module declares a `metadata: array<u32>` storage binding; the indexing
+// helpers below read shapes and strides from it.
+
+const WORKGROUP_SIZE: u32 = 256u;
+
+const LN_10: f32 = 2.302585092994046;
+const INV_LN_10: f32 = 0.4342944819032518;
+const PI: f32 = 3.141592653589793;
+
+// SELU constants (from the original paper)
+const SELU_ALPHA: f32 = 1.6732632423543772;
+const SELU_SCALE: f32 = 1.0507009873554805;
+
+// Large grids are dispatched as a 2D grid of workgroups; this flattens it back
+fn thread_index(gid: vec3<u32>, num_workgroups: vec3<u32>) -> u32 {
+    return gid.x + gid.y * num_workgroups.x * WORKGROUP_SIZE;
+}
+
+// Element offset of the `idx`-th logical element of a strided tensor
+fn strided_offset(idx: u32, num_dims: u32, shape_at: u32, strides_at: u32) -> u32 {
+    var rem = idx;
+    var offset = 0u;
+    for (var d = num_dims; d > 0u; d--) {
+        let dim = metadata[shape_at + d - 1u];
+        offset += (rem % dim) * metadata[strides_at + d - 1u];
+        rem = rem / dim;
+    }
+    return offset;
+}
+
+fn f32_inf() -> f32 {
+    return bitcast<f32>(0x7f800000u);
+}
+
+fn f32_nan() -> f32 {
+    return bitcast<f32>(0x7fc00000u);
+}
+
+fn m_isnan(x: f32) -> bool {
+    return (bitcast<u32>(x) & 0x7fffffffu) > 0x7f800000u;
+}
+
+fn m_isinf(x: f32) -> bool {
+    return (bitcast<u32>(x) & 0x7fffffffu) == 0x7f800000u;
+}
+
+fn m_isfinite(x: f32) -> bool {
+    return (bitcast<u32>(x) & 0x7fffffffu) < 0x7f800000u;
+}
+
+fn m_pow_int_f32(base: f32, exponent: u32) -> f32 {
+    var result = 1.0;
+    var b = base;
+    var e = exponent;
+    while (e > 0u) {
+        if ((e & 1u) != 0u) {
+            result *= b;
+        }
+        e = e >> 1u;
+        b *= b;
+    }
+    return result;
+}
+
+// Matches C `powf` for integral exponents of negative bases
+fn m_pow_float(base: f32, exponent: f32) -> f32 {
+    if (exponent == 0.0) {
+        return 1.0;
+    }
+    if (base == 0.0) {
+        return select(f32_inf(), 0.0, exponent > 0.0);
+    }
+    if (base == 1.0) {
+        return 1.0;
+    }
+    if (exponent == 1.0) {
+        return base;
+    }
+    if (floor(exponent) == exponent && abs(exponent) < 4294967296.0) {
+        if (exponent >= 0.0) {
+            return m_pow_int_f32(base, u32(exponent));
+        }
+        return 1.0 / m_pow_int_f32(base, u32(-exponent));
+    }
+    if (base < 0.0) {
+        return f32_nan();
+    }
+    return pow(base, exponent);
+}
+
+fn m_pow_i32(base: i32, exponent: i32) -> i32 {
+    var result = 1;
+    var b = base;
+    var e = exponent;
+    while (e > 0) {
+        if ((e & 1) != 0) {
+            result *= b;
+        }
+        e = e >> 1u;
+        b *= b;
+    }
+    return result;
+}
+
+fn m_pow_u32(base: u32, exponent: u32) -> u32 {
+    var result = 1u;
+    var b = base;
+    var e = exponent;
+    while (e > 0u) {
+        if ((e & 1u) != 0u) {
+            result *= b;
+        }
+        e = e >> 1u;
+        b *= b;
+    }
+    return result;
+}
+
+fn m_tan(v: f32) -> f32 {
+    var x = v % (2.0 * PI);
+    if (x > PI) {
+        x -= 2.0 * PI;
+    } else if (x < -PI) {
+        x += 2.0 * PI;
+    }
+    if (abs(abs(x) - PI / 2.0) < 1e-6) {
+        return select(-1e6, 1e6, x > 0.0);
+    }
+    return sin(x) / cos(x);
+}
+
+// Half away from zero, like C `roundf` (WGSL `round` rounds half to even)
+fn m_round(x: f32) -> f32 {
+    return sign(x) * floor(abs(x) + 0.5);
+}
+
+// Abramowitz and Stegun formula 7.1.26, maximum error 1.5e-7
+fn m_erf(v: f32) -> f32 {
+    let a1 = 0.254829592;
+    let a2 = -0.284496736;
+    let a3 = 1.421413741;
+    let a4 = -1.453152027;
+    let a5 = 1.061405429;
+    let p = 0.3275911;
+
+    let s = select(-1.0, 1.0, v >= 0.0);
+    let x = abs(v);
+    let t = 1.0 / (1.0 + p * x);
+    let y = 1.0 - (((((a5 * t + a4) * t) + a3) * t + a2) * t + a1) * t * exp(-x * x);
+    return s * y;
+}
+
+fn m_sigmoid(x: f32) -> f32 {
+    return 1.0 / (1.0 + exp(-x));
+}
+
+fn m_gelu(x: f32) -> f32 {
+    return 0.5 * x * (1.0 + tanh(0.7978845608 * (x + 0.044715 * x * x * x)));
+}
+
+fn m_softplus(x: f32) -> f32 {
+    return log(1.0 + exp(x));
+}
+
+fn m_hardsigmoid(x: f32) -> f32 {
+    return max(0.0, min(1.0, (x + 3.0) / 6.0));
+}
+
+fn m_softsign(x: f32) -> f32 {
+    return x / (1.0 + abs(x));
+}
+
+// SELU: scale * (max(0,x) + min(0, alpha*(exp(x)-1)))
+fn m_selu(x: f32) -> f32 {
+    return SELU_SCALE * select(SELU_ALPHA * (exp(x) - 1.0), x, x > 0.0);
+}
+
+// CELU: max(0,x) + min(0, alpha*(exp(x/alpha)-1)) with alpha=1.0
+fn m_celu(x: f32) -> f32 {
+    return max(0.0, x) + min(0.0, exp(x) - 1.0);
+}
@@ -0,0 +1,43 @@
+// Binary Operations
+// =================
+// Element-wise binary operations (arithmetic, logical and comparison) on two
+// tensors that were already broadcast to the output shape via their strides.
+//
+// Metadata Layout (Total: 2 + num_dims * 4 + 2):
+// - metadata[0]: num_els (total number of output elements)
+// - metadata[1]: num_dims (number of dimensions)
+// - metadata[2..2+num_dims]: lhs_shape
+// - metadata[2+num_dims..2+2*num_dims]: rhs_shape
+// - metadata[2+2*num_dims..2+3*num_dims]: lhs_strides
+// - metadata[2+3*num_dims..2+4*num_dims]: rhs_strides
+// - metadata[2+4*num_dims]: lhs_offset
+// - metadata[2+4*num_dims+1]: rhs_offset
+//
+// Template parameters:
+// - IN: storage type of the inputs
+// - OUT: storage type of the output
+// - FN: expression over `x` (lhs) and `y` (rhs) producing OUT
+
+@group(0) @binding(0) var<storage, read> lhs: array<$IN>;
+@group(0) @binding(1) var<storage, read> rhs: array<$IN>;
+@group(0) @binding(2) var<storage, read_write> output: array<$OUT>;
+@group(0) @binding(3) var<storage, read> metadata: array<u32>;
+
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn main(@builtin(global_invocation_id) gid: vec3<u32>, @builtin(num_workgroups) num_workgroups: vec3<u32>) {
+    let id = thread_index(gid, num_workgroups);
+    let num_els = metadata[0];
+    if (id >= num_els) {
+        return;
+    }
+
+    let num_dims = metadata[1];
+    let lhs_offset = metadata[2u + 4u * num_dims];
+    let rhs_offset = metadata[2u + 4u * num_dims + 1u];
+    let lhs_i = lhs_offset + strided_offset(id, num_dims, 2u, 2u + 2u * num_dims);
+    let rhs_i = rhs_offset + strided_offset(id, num_dims, 2u + num_dims, 2u + 3u * num_dims);
+
+    let x = lhs[lhs_i];
+    let y = rhs[rhs_i];
+    output[id] = $FN;
+}
@@ -0,0 +1,33 @@
+// Cast Operations
+// ===============
+// Converts a strided tensor to another dtype, writing a contiguous output.
+//
+// Metadata Layout (Total: 2 + num_dims * 2 + 1):
+// - metadata[0]: num_els (total number of elements)
+// - metadata[1]: num_dims (number of dimensions)
+// - metadata[2..2+num_dims]: shape
+// - metadata[2+num_dims..2+2*num_dims]: strides
+// - metadata[2+2*num_dims]: offset
+//
+// Template parameters:
+// - IN: storage type of the input
+// - OUT: storage type of the output
+// - FN: expression converting `x` to OUT
+
+@group(0) @binding(0) var<storage, read> input: array<$IN>;
+@group(0) @binding(1) var<storage, read_write> output: array<$OUT>;
+@group(0) @binding(2) var<storage, read> metadata: array<u32>;
+
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn main(@builtin(global_invocation_id) gid: vec3<u32>, @builtin(num_workgroups) num_workgroups: vec3<u32>) {
+    let id = thread_index(gid, num_workgroups);
+    let num_els = metadata[0];
+    if (id >= num_els) {
+        return;
+    }
+
+    let num_dims = metadata[1];
+    let offset = metadata[2u + 2u * num_dims];
+    let x = input[offset + strided_offset(id, num_dims, 2u, 2u + num_dims)];
+    output[id] = $FN;
+}
@@ -0,0 +1,44 @@
+// Dot
+// ===
+// 2D matrix multiplication. Each thread computes one output element.
+//
+// Metadata Layout (Total: 9):
+// - metadata[0]: M
+// - metadata[1]: K
+// - metadata[2]: N
+// - metadata[3]: lhs_stride_m
+// - metadata[4]: lhs_stride_k
+// - metadata[5]: rhs_stride_k
+// - metadata[6]: rhs_stride_n
+// - metadata[7]: lhs_offset
+// - metadata[8]: rhs_offset
+//
+// Template parameters:
+// - T: storage type of the inputs and the output
+
+@group(0) @binding(0) var<storage, read> lhs: array<$T>;
+@group(0) @binding(1) var<storage, read> rhs: array<$T>;
+@group(0) @binding(2) var<storage, read_write> output: array<$T>;
+@group(0) @binding(3) var<storage, read> metadata: array<u32>;
+
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn main(@builtin(global_invocation_id) gid: vec3<u32>, @builtin(num_workgroups) num_workgroups: vec3<u32>) {
+    let id = thread_index(gid, num_workgroups);
+    let m = metadata[0];
+    let k = metadata[1];
+    let n = metadata[2];
+    if (id >= m * n) {
+        return;
+    }
+
+    let row = id / n;
+    let col = id % n;
+    let lhs_base = metadata[7] + row * metadata[3];
+    let rhs_base = metadata[8] + col * metadata[6];
+
+    var acc = $T(0);
+    for (var i = 0u; i < k; i++) {
+        acc += lhs[lhs_base + i * metadata[4]] * rhs[rhs_base + i * metadata[5]];
+    }
+    output[id] = acc;
+}
@@ -0,0 +1,88 @@
+// Matmul
+// ======
+// Batched matrix multiplication with broadcast batch dimensions. Each thread
+// computes one output element.
+//
+// Metadata Layout:
+// - metadata[0]: num_els
+// - metadata[1]: lhs_ndim
+// - metadata[2]: rhs_ndim
+// - metadata[3]: batch_ndim
+// - metadata[4..4+lhs_ndim]: lhs_shape
+// - metadata[...+rhs_ndim]: rhs_shape
+// - metadata[...+batch_ndim]: batch_shape
+// - metadata[...+lhs_ndim]: lhs_strides
+// - metadata[...+rhs_ndim]: rhs_strides
+// - metadata[...]: lhs_offset, rhs_offset
+// - metadata[...]: M, K, N
+//
+// Template parameters:
+// - T: storage type of the inputs and the output
+
+@group(0) @binding(0) var<storage, read> lhs: array<$T>;
+@group(0) @binding(1) var<storage, read> rhs: array<$T>;
+@group(0) @binding(2) var<storage, read_write> output: array<$T>;
+@group(0) @binding(3) var<storage, read> metadata: array<u32>;
+
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn main(@builtin(global_invocation_id) gid: vec3<u32>, @builtin(num_workgroups) num_workgroups: vec3<u32>) {
+    let id = thread_index(gid, num_workgroups);
+    let num_els = metadata[0];
+    if (id >= num_els) {
+        return;
+    }
+
+    let lhs_ndim = metadata[1];
+    let rhs_ndim = metadata[2];
+    let batch_ndim = metadata[3];
+    let lhs_shape_at = 4u;
+    let rhs_shape_at = lhs_shape_at + lhs_ndim;
+    let batch_shape_at = rhs_shape_at + rhs_ndim;
+    let lhs_strides_at = batch_shape_at + batch_ndim;
+    let rhs_strides_at = lhs_strides_at + lhs_ndim;
+    let tail = rhs_strides_at + rhs_ndim;
+
+    let m = metadata[tail + 2u];
+    let k = metadata[tail + 3u];
+    let n = metadata[tail + 4u];
+
+    let col = id % n;
+    let row = (id / n) % m;
+    var batch = id / (m * n);
+
+    var lhs_base = metadata[tail];
+    var rhs_base = metadata[tail + 1u];
+    let lhs_batch_ndim = lhs_ndim - 2u;
+    let rhs_batch_ndim = rhs_ndim - 2u;
+    for (var d = batch_ndim; d > 0u; d--) {
+        let b = d - 1u;
+        let size = metadata[batch_shape_at + b];
+        let coord = batch % size;
+        batch = batch / size;
+
+        // Batch dims are right-aligned; size-1 dims broadcast
+        if (b + lhs_batch_ndim >= batch_ndim) {
+            let ld = b + lhs_batch_ndim - batch_ndim;
+            if (metadata[lhs_shape_at + ld] != 1u) {
+                lhs_base += coord * metadata[lhs_strides_at + ld];
+            }
+        }
+        if (b + rhs_batch_ndim >= batch_ndim) {
+            let rd = b + rhs_batch_ndim - batch_ndim;
+            if (metadata[rhs_shape_at + rd] != 1u) {
+                rhs_base += coord * metadata[rhs_strides_at + rd];
+            }
+        }
+    }
+
+    let lhs_stride_m = metadata[lhs_strides_at + lhs_ndim - 2u];
+    let lhs_stride_k = metadata[lhs_strides_at + lhs_ndim - 1u];
+    let rhs_stride_k = metadata[rhs_strides_at + rhs_ndim - 2u];
+    let rhs_stride_n = metadata[rhs_strides_at + rhs_ndim - 1u];
+
+    var acc = $T(0);
+    for (var i = 0u; i < k; i++) {
+        acc += lhs[lhs_base + row * lhs_stride_m + i * lhs_stride_k] * rhs[rhs_base + i * rhs_stride_k + col * rhs_stride_n];
+    }
+    output[id] = acc;
+}
@@ -0,0 +1,89 @@
+// Reduce Operations
+// =================
+// Reductions over an arbitrary set of dimensions. Each thread produces one
+// output element by walking every reduced position.
+//
+// Metadata Layout:
+// - metadata[0]: input_ndim
+// - metadata[1..1+input_ndim]: input_shape
+// - metadata[1+input_ndim..1+2*input_ndim]: input_strides
+// - metadata[1+2*input_ndim]: input_offset
+// - metadata[2+2*input_ndim]: output_ndim
+// - metadata[3+2*input_ndim..]: output_shape
+// - metadata[...]: num_reduce_dims
+// - metadata[...]: reduce_dims
+// - metadata[...]: keep_dim (0 or 1)
+// - metadata[...]: reduce_size
+//
+// Template parameters:
+// - T: storage type of the input and the output
+// - INIT: accumulator declarations
+// - STEP: statements folding `x` into the accumulators
+// - FINAL: statements declaring `result` from the accumulators and `n`
+
+@group(0) @binding(0) var<storage, read> input: array<$T>;
+@group(0) @binding(1) var<storage, read_write> output: array<$T>;
+@group(0) @binding(2) var<storage, read> metadata: array<u32>;
+
+fn is_reduce_dim(dim: u32, reduce_dims_at: u32, num_reduce_dims: u32) -> bool {
+    for (var i = 0u; i < num_reduce_dims; i++) {
+        if (metadata[reduce_dims_at + i] == dim) {
+            return true;
+        }
+    }
+    return false;
+}
+
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn main(@builtin(global_invocation_id) gid: vec3<u32>, @builtin(num_workgroups) num_workgroups: vec3<u32>) {
+    let id = thread_index(gid, num_workgroups);
+
+    let ndim = metadata[0];
+    let shape_at = 1u;
+    let strides_at = 1u + ndim;
+    let offset = metadata[1u + 2u * ndim];
+    let output_ndim = metadata[2u + 2u * ndim];
+    let output_shape_at = 3u + 2u * ndim;
+    let num_reduce_dims = metadata[output_shape_at + output_ndim];
+    let reduce_dims_at = output_shape_at + output_ndim + 1u;
+    let reduce_size = metadata[reduce_dims_at + num_reduce_dims + 1u];
+
+    var num_els = 1u;
+    for (var i = 0u; i < output_ndim; i++) {
+        num_els *= metadata[output_shape_at + i];
+    }
+    if (id >= num_els) {
+        return;
+    }
+
+    // Output elements enumerate the kept dims in order, so walking them from
+    // the innermost dim recovers the input offset of the reduction window
+    var rem = id;
+    var base = offset;
+    for (var d = ndim; d > 0u; d--) {
+        let dim = d - 1u;
+        if (is_reduce_dim(dim, reduce_dims_at, num_reduce_dims)) {
+            continue;
+        }
+        let size = metadata[shape_at + dim];
+        base += (rem % size) * metadata[strides_at + dim];
+        rem = rem / size;
+    }
+
+    $INIT
+    for (var r = 0u; r < reduce_size; r++) {
+        var rrem = r;
+        var roff = 0u;
+        for (var i = num_reduce_dims; i > 0u; i--) {
+            let dim = metadata[reduce_dims_at + i - 1u];
+            let size = metadata[shape_at + dim];
+            roff += (rrem % size) * metadata[strides_at + dim];
+            rrem = rrem / size;
+        }
+        let x = input[base + roff];
+        $STEP
+    }
+    let n = reduce_size;
+    $FINAL
+    output[id] = result;
+}
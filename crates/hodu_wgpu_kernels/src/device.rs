@@ -0,0 +1,119 @@
+use crate::error::WgpuKernelError;
+use wgpu::util::DeviceExt;
+
+/// Usage shared by every tensor buffer: bound as storage and copied in and out
+pub const BUFFER_USAGES: wgpu::BufferUsages = wgpu::BufferUsages::STORAGE
+    .union(wgpu::BufferUsages::COPY_SRC)
+    .union(wgpu::BufferUsages::COPY_DST);
+
+/// Bindings must not be empty, so zero-sized tensors still get one word
+const MIN_BUFFER_SIZE: u64 = 4;
+
+#[derive(Debug)]
+pub struct Device {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    info: wgpu::AdapterInfo,
+}
+
+impl Device {
+    /// Requests the default adapter of the platform (Vulkan, Metal, DX12, GL or the browser's WebGPU).
+    pub fn system_default() -> Result<Self, WgpuKernelError> {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok_or(WgpuKernelError::AdapterNotFound)?;
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("hodu"),
+                required_features: wgpu::Features::empty(),
+                required_limits: adapter.limits(),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        ))
+        .map_err(|e| WgpuKernelError::RequestDeviceError(e.to_string()))?;
+
+        Ok(Self {
+            device,
+            queue,
+            info: adapter.get_info(),
+        })
+    }
+
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    pub fn info(&self) -> &wgpu::AdapterInfo {
+        &self.info
+    }
+
+    pub fn new_buffer(&self, size_in_bytes: usize, label: &str) -> wgpu::Buffer {
+        self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: (size_in_bytes as u64).max(MIN_BUFFER_SIZE),
+            usage: BUFFER_USAGES,
+            mapped_at_creation: false,
+        })
+    }
+
+    pub fn new_buffer_with_data<T: bytemuck::Pod>(&self, data: &[T], label: &str) -> wgpu::Buffer {
+        if data.is_empty() {
+            return self.new_buffer(0, label);
+        }
+        self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: bytemuck::cast_slice(data),
+            usage: BUFFER_USAGES,
+        })
+    }
+
+    /// Copies `count` elements from the start of `buffer` back to the host.
+    ///
+    /// Blocks until all previously submitted work has finished.
+    pub fn read_buffer<T: bytemuck::Pod>(
+        &self,
+        buffer: &wgpu::Buffer,
+        count: usize,
+    ) -> Result<Vec<T>, WgpuKernelError> {
+        let size = (count * std::mem::size_of::<T>()) as u64;
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("read_buffer"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("read_buffer"),
+        });
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        let slice = staging.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|e| WgpuKernelError::BufferMapError(e.to_string()))?
+            .map_err(|e| WgpuKernelError::BufferMapError(e.to_string()))?;
+
+        let data = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        staging.unmap();
+        Ok(data)
+    }
+}
@@ -0,0 +1,40 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum WgpuKernelError {
+    AdapterNotFound,
+    RequestDeviceError(String),
+    LockError(String),
+    UnknownKernel(String),
+    UnsupportedDTypeForOp(String, String),
+    FailedToCreatePipeline(String),
+    BufferMapError(String),
+    MetadataOverflow(usize),
+}
+
+impl fmt::Display for WgpuKernelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AdapterNotFound => write!(f, "No WebGPU adapter available"),
+            Self::RequestDeviceError(msg) => write!(f, "Failed to request WebGPU device: {}", msg),
+            Self::LockError(msg) => write!(f, "Could not lock resource: {}", msg),
+            Self::UnknownKernel(name) => write!(f, "Unknown kernel: {}", name),
+            Self::UnsupportedDTypeForOp(dtype, op) => {
+                write!(f, "Unsupported dtype {} for operation {}", dtype, op)
+            },
+            Self::FailedToCreatePipeline(msg) => write!(f, "Failed to create pipeline: {}", msg),
+            Self::BufferMapError(msg) => write!(f, "Failed to map buffer: {}", msg),
+            Self::MetadataOverflow(value) => {
+                write!(f, "Metadata value {} does not fit in a 32-bit kernel argument", value)
+            },
+        }
+    }
+}
+
+impl std::error::Error for WgpuKernelError {}
+
+impl<T> From<std::sync::PoisonError<T>> for WgpuKernelError {
+    fn from(e: std::sync::PoisonError<T>) -> Self {
+        Self::LockError(e.to_string())
+    }
+}
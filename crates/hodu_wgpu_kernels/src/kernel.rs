@@ -0,0 +1,65 @@
+use crate::{
+    device::Device,
+    error::WgpuKernelError,
+    source::{self, Source},
+};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+type Pipelines = HashMap<String, Arc<wgpu::ComputePipeline>>;
+
+/// Compiled compute pipelines, keyed by kernel name
+#[derive(Debug)]
+pub struct Kernels {
+    pipelines: RwLock<Pipelines>,
+}
+
+impl Default for Kernels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Kernels {
+    pub fn new() -> Self {
+        Self {
+            pipelines: RwLock::new(Pipelines::new()),
+        }
+    }
+
+    pub fn load_pipeline(
+        &self,
+        device: &Device,
+        source: Source,
+        name: &str,
+    ) -> Result<Arc<wgpu::ComputePipeline>, WgpuKernelError> {
+        if let Some(pipeline) = self.pipelines.read()?.get(name) {
+            return Ok(pipeline.clone());
+        }
+
+        let code = source::render(source, name)?;
+        let device = device.device();
+
+        // Surface WGSL and pipeline errors as results instead of the device's uncaptured error handler
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Wgsl(code.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(name),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(WgpuKernelError::FailedToCreatePipeline(format!("{}: {}", name, error)));
+        }
+
+        let pipeline = Arc::new(pipeline);
+        self.pipelines.write()?.insert(name.to_string(), pipeline.clone());
+        Ok(pipeline)
+    }
+}
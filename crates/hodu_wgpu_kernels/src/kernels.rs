@@ -0,0 +1,18 @@
+pub mod macros;
+mod ops_binary;
+mod ops_cast;
+mod ops_matrix;
+mod ops_memory;
+mod ops_reduce;
+mod ops_unary;
+
+// Export the shared Kernel type
+pub use macros::Kernel;
+
+// Export all operations from each module
+pub use ops_binary::*;
+pub use ops_cast::*;
+pub use ops_matrix::*;
+pub use ops_memory::*;
+pub use ops_reduce::*;
+pub use ops_unary::*;
@@ -0,0 +1,21 @@
+// Define Kernel type once, shared across all modules
+#[derive(Clone, Copy)]
+pub struct Kernel(pub &'static str);
+
+macro_rules! ops{
+    ($($name:ident),+) => {
+        // Import Kernel from the kernels::macros module
+        use $crate::kernels::macros::Kernel;
+
+        $(
+        pub mod $name {
+            use $crate::kernels::macros::Kernel;
+            pub const BOOL: Kernel = Kernel(concat!("hodu_wgpu_", stringify!($name), "_bool"));
+            pub const F32: Kernel = Kernel(concat!("hodu_wgpu_", stringify!($name), "_f32"));
+            pub const U32: Kernel = Kernel(concat!("hodu_wgpu_", stringify!($name), "_u32"));
+            pub const I32: Kernel = Kernel(concat!("hodu_wgpu_", stringify!($name), "_i32"));
+        }
+        )+
+    };
+}
+pub(crate) use ops;
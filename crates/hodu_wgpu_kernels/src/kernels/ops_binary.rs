@@ -0,0 +1,61 @@
+use crate::{
+    device::Device,
+    error::WgpuKernelError,
+    kernel::Kernels,
+    kernels::macros::ops,
+    source::Source,
+    utils::{dispatch, metadata_buffer},
+};
+
+ops!(
+    add,
+    sub,
+    mul,
+    div,
+    rem,
+    pow,
+    minimum,
+    maximum,
+    eq,
+    ne,
+    le,
+    lt,
+    ge,
+    gt,
+    logical_and,
+    logical_or,
+    logical_xor
+);
+
+/// Executes a binary operation on two input tensors.
+///
+/// Arithmetic kernels write the input dtype; logical and comparison kernels write `bool`.
+///
+/// # Metadata Layout
+/// - `metadata[0]`: num_els (total number of output elements)
+/// - `metadata[1]`: num_dims (number of dimensions)
+/// - `metadata[2..2+num_dims]`: lhs_shape
+/// - `metadata[2+num_dims..2+2*num_dims]`: rhs_shape
+/// - `metadata[2+2*num_dims..2+3*num_dims]`: lhs_strides
+/// - `metadata[2+3*num_dims..2+4*num_dims]`: rhs_strides
+/// - `metadata[2+4*num_dims]`: lhs_offset
+/// - `metadata[2+4*num_dims+1]`: rhs_offset
+pub fn call_ops_binary(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    lhs: &wgpu::Buffer,
+    rhs: &wgpu::Buffer,
+    output: &wgpu::Buffer,
+    metadata: &[usize],
+) -> Result<(), WgpuKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Binary, kernel.0)?;
+    let metadata_buffer = metadata_buffer(device, metadata)?;
+    dispatch(
+        device,
+        &pipeline,
+        kernel.0,
+        &[lhs, rhs, output, &metadata_buffer],
+        metadata[0],
+    )
+}
@@ -0,0 +1,37 @@
+use crate::{
+    device::Device,
+    error::WgpuKernelError,
+    kernel::Kernels,
+    kernels::macros::Kernel,
+    source::Source,
+    utils::{dispatch, metadata_buffer},
+};
+
+/// Converts a strided tensor to another dtype, writing a contiguous output.
+///
+/// Kernel names have the form `hodu_wgpu_cast_{src}_to_{dst}`.
+///
+/// # Metadata Layout
+/// - `metadata[0]`: num_els (total number of elements)
+/// - `metadata[1]`: num_dims (number of dimensions)
+/// - `metadata[2..2+num_dims]`: shape
+/// - `metadata[2+num_dims..2+2*num_dims]`: strides
+/// - `metadata[2+2*num_dims]`: offset
+pub fn call_ops_cast(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    input: &wgpu::Buffer,
+    output: &wgpu::Buffer,
+    metadata: &[usize],
+) -> Result<(), WgpuKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Cast, kernel.0)?;
+    let metadata_buffer = metadata_buffer(device, metadata)?;
+    dispatch(
+        device,
+        &pipeline,
+        kernel.0,
+        &[input, output, &metadata_buffer],
+        metadata[0],
+    )
+}
@@ -0,0 +1,66 @@
+use crate::{
+    device::Device,
+    error::WgpuKernelError,
+    kernel::Kernels,
+    kernels::macros::ops,
+    source::Source,
+    utils::{dispatch, metadata_buffer},
+};
+
+ops!(matmul, dot);
+
+/// Executes a batched matrix multiplication with broadcast batch dimensions.
+///
+/// # Metadata Layout
+/// - `metadata[0]`: num_els
+/// - `metadata[1]`: lhs_ndim
+/// - `metadata[2]`: rhs_ndim
+/// - `metadata[3]`: batch_ndim
+/// - `metadata[4..4+lhs_ndim]`: lhs_shape
+/// - followed by rhs_shape, batch_shape, lhs_strides, rhs_strides
+/// - followed by lhs_offset, rhs_offset, M, K, N
+pub fn call_ops_matmul(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    lhs: &wgpu::Buffer,
+    rhs: &wgpu::Buffer,
+    output: &wgpu::Buffer,
+    metadata: &[usize],
+) -> Result<(), WgpuKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Matmul, kernel.0)?;
+    let metadata_buffer = metadata_buffer(device, metadata)?;
+    dispatch(
+        device,
+        &pipeline,
+        kernel.0,
+        &[lhs, rhs, output, &metadata_buffer],
+        metadata[0],
+    )
+}
+
+/// Executes a 2D matrix multiplication.
+///
+/// # Metadata Layout
+/// - `metadata[0..3]`: M, K, N
+/// - `metadata[3..7]`: lhs_stride_m, lhs_stride_k, rhs_stride_k, rhs_stride_n
+/// - `metadata[7..9]`: lhs_offset, rhs_offset
+pub fn call_ops_dot(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    lhs: &wgpu::Buffer,
+    rhs: &wgpu::Buffer,
+    output: &wgpu::Buffer,
+    metadata: &[usize],
+) -> Result<(), WgpuKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Dot, kernel.0)?;
+    let metadata_buffer = metadata_buffer(device, metadata)?;
+    dispatch(
+        device,
+        &pipeline,
+        kernel.0,
+        &[lhs, rhs, output, &metadata_buffer],
+        metadata[0] * metadata[2],
+    )
+}
@@ -0,0 +1,61 @@
+use crate::{
+    device::Device,
+    error::WgpuKernelError,
+    kernel::Kernels,
+    kernels::macros::ops,
+    source::Source,
+    utils::{dispatch, metadata_buffer, scalar_buffer},
+};
+
+ops!(contiguous, const_set);
+
+/// Copies a strided tensor into a contiguous output buffer.
+///
+/// # Metadata Layout
+/// - `metadata[0]`: num_els (total number of elements)
+/// - `metadata[1]`: num_dims (number of dimensions)
+/// - `metadata[2..2+num_dims]`: shape
+/// - `metadata[2+num_dims..2+2*num_dims]`: strides
+/// - `metadata[2+2*num_dims]`: offset
+pub fn call_ops_contiguous(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    input: &wgpu::Buffer,
+    output: &wgpu::Buffer,
+    metadata: &[usize],
+) -> Result<(), WgpuKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Memory, kernel.0)?;
+    let metadata_buffer = metadata_buffer(device, metadata)?;
+    dispatch(
+        device,
+        &pipeline,
+        kernel.0,
+        &[input, output, &metadata_buffer],
+        metadata[0],
+    )
+}
+
+/// Writes a scalar into every element addressed by the strided layout of `output`, in place.
+///
+/// `scalar_bits` is the raw 32-bit pattern of the value. The metadata layout is the same as
+/// [`call_ops_contiguous`].
+pub fn call_const_set(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    output: &wgpu::Buffer,
+    metadata: &[usize],
+    scalar_bits: u32,
+) -> Result<(), WgpuKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::ConstSet, kernel.0)?;
+    let metadata_buffer = metadata_buffer(device, metadata)?;
+    let scalar = scalar_buffer(device, scalar_bits);
+    dispatch(
+        device,
+        &pipeline,
+        kernel.0,
+        &[output, &metadata_buffer, &scalar],
+        metadata[0],
+    )
+}
@@ -0,0 +1,43 @@
+use crate::{
+    device::Device,
+    error::WgpuKernelError,
+    kernel::Kernels,
+    kernels::macros::ops,
+    source::Source,
+    utils::{dispatch, metadata_buffer},
+};
+
+ops!(sum, max, min, prod, mean, norm, logsum, logsumexp);
+
+/// Executes a reduction over the dimensions listed in the metadata.
+///
+/// `std`, `var`, `norm`, `logsum` and `logsumexp` are only available for `f32`. `std` and `var` have no
+/// named constants (they would shadow `std`), use `Kernel("hodu_wgpu_var_f32")`.
+///
+/// # Metadata Layout
+/// - `metadata[0]`: input_ndim
+/// - `metadata[1..1+input_ndim]`: input_shape
+/// - `metadata[1+input_ndim..1+2*input_ndim]`: input_strides
+/// - `metadata[1+2*input_ndim]`: input_offset
+/// - `metadata[2+2*input_ndim]`: output_ndim
+/// - followed by output_shape, num_reduce_dims, reduce_dims, keep_dim, reduce_size
+pub fn call_ops_reduce(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    input: &wgpu::Buffer,
+    output: &wgpu::Buffer,
+    metadata: &[usize],
+) -> Result<(), WgpuKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Reduce, kernel.0)?;
+    let metadata_buffer = metadata_buffer(device, metadata)?;
+
+    let input_ndim = metadata[0];
+    let output_ndim = metadata[2 + 2 * input_ndim];
+    let output_shape_start = 3 + 2 * input_ndim;
+    let num_els = metadata[output_shape_start..output_shape_start + output_ndim]
+        .iter()
+        .product();
+
+    dispatch(device, &pipeline, kernel.0, &[input, output, &metadata_buffer], num_els)
+}
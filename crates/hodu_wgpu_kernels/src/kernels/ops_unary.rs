@@ -0,0 +1,123 @@
+use crate::{
+    device::Device,
+    error::WgpuKernelError,
+    kernel::Kernels,
+    kernels::macros::ops,
+    source::Source,
+    utils::{dispatch, metadata_buffer, scalar_buffer},
+};
+
+ops!(
+    eq_scalar,
+    ne_scalar,
+    lt_scalar,
+    le_scalar,
+    gt_scalar,
+    ge_scalar,
+    neg,
+    abs,
+    sign,
+    softsign,
+    square,
+    sqrt,
+    recip,
+    relu,
+    sigmoid,
+    hardsigmoid,
+    gelu,
+    softplus,
+    silu,
+    hardsilu,
+    mish,
+    selu,
+    celu,
+    sin,
+    cos,
+    tan,
+    asin,
+    acos,
+    atan,
+    sinh,
+    cosh,
+    tanh,
+    asinh,
+    acosh,
+    atanh,
+    exp,
+    exp2,
+    exp10,
+    ln,
+    log2,
+    log10,
+    ceil,
+    floor,
+    round,
+    erf,
+    logical_not,
+    isnan,
+    isinf,
+    isfinite,
+    add_scalar,
+    sub_scalar,
+    mul_scalar,
+    div_scalar,
+    rem_scalar,
+    pow_scalar,
+    maximum_scalar,
+    minimum_scalar,
+    leaky_relu,
+    elu,
+    prelu
+);
+
+/// Executes a unary operation on a strided input tensor, writing a contiguous output.
+///
+/// # Metadata Layout
+/// - `metadata[0]`: num_els (total number of elements)
+/// - `metadata[1]`: num_dims (number of dimensions)
+/// - `metadata[2..2+num_dims]`: shape
+/// - `metadata[2+num_dims..2+2*num_dims]`: strides
+/// - `metadata[2+2*num_dims]`: offset
+pub fn call_ops_unary(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    input: &wgpu::Buffer,
+    output: &wgpu::Buffer,
+    metadata: &[usize],
+) -> Result<(), WgpuKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::Unary, kernel.0)?;
+    let metadata_buffer = metadata_buffer(device, metadata)?;
+    dispatch(
+        device,
+        &pipeline,
+        kernel.0,
+        &[input, output, &metadata_buffer],
+        metadata[0],
+    )
+}
+
+/// Executes a unary operation with a scalar operand (`add_scalar`, `leaky_relu`, `eq_scalar`, ...).
+///
+/// `scalar_bits` is the raw 32-bit pattern of the scalar in the input dtype (`f32::to_bits`, `i32 as u32`,
+/// 0 or 1 for `bool`). The metadata layout is the same as [`call_ops_unary`].
+pub fn call_ops_unary_scalar(
+    kernel: Kernel,
+    kernels: &Kernels,
+    device: &Device,
+    input: &wgpu::Buffer,
+    output: &wgpu::Buffer,
+    metadata: &[usize],
+    scalar_bits: u32,
+) -> Result<(), WgpuKernelError> {
+    let pipeline = kernels.load_pipeline(device, Source::UnaryScalar, kernel.0)?;
+    let metadata_buffer = metadata_buffer(device, metadata)?;
+    let scalar = scalar_buffer(device, scalar_bits);
+    dispatch(
+        device,
+        &pipeline,
+        kernel.0,
+        &[input, output, &metadata_buffer, &scalar],
+        metadata[0],
+    )
+}
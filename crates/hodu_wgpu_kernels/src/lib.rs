@@ -0,0 +1,8 @@
+pub mod device;
+pub mod error;
+pub mod kernel;
+pub mod kernels;
+pub mod source;
+pub mod utils;
+
+pub use wgpu;
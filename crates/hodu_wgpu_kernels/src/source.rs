@@ -0,0 +1,406 @@
+//! WGSL sources and kernel specialization.
+//!
+//! WGSL has no templates or preprocessor, so every kernel is a module rendered from a template in
+//! `kernels/`: `$`-placeholders are replaced by the storage types and the expression of the op named
+//! by the kernel (`hodu_wgpu_{op}_{dtype}`), and the shared helpers are prepended.
+//!
+//! `bool` tensors are stored as one `u32` (0 or 1) per element, since WGSL has no 8-bit storage.
+
+use crate::error::WgpuKernelError;
+
+const UTILS: &str = include_str!("../kernels/headers/utils.wgsl");
+
+const BINARY_SRC: &str = include_str!("../kernels/ops_binary.wgsl");
+const CAST_SRC: &str = include_str!("../kernels/ops_cast.wgsl");
+const CONST_SET_SRC: &str = include_str!("../kernels/ops_const_set.wgsl");
+const DOT_SRC: &str = include_str!("../kernels/ops_dot.wgsl");
+const MATMUL_SRC: &str = include_str!("../kernels/ops_matmul.wgsl");
+const MEMORY_SRC: &str = include_str!("../kernels/ops_memory.wgsl");
+const REDUCE_SRC: &str = include_str!("../kernels/ops_reduce.wgsl");
+const UNARY_SRC: &str = include_str!("../kernels/ops_unary.wgsl");
+const UNARY_SCALAR_SRC: &str = include_str!("../kernels/ops_unary_scalar.wgsl");
+
+pub const KERNEL_PREFIX: &str = "hodu_wgpu_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Source {
+    Binary,
+    Cast,
+    ConstSet,
+    Dot,
+    Matmul,
+    Memory,
+    Reduce,
+    Unary,
+    UnaryScalar,
+}
+
+/// Element types with a WGSL representation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ty {
+    Bool,
+    F32,
+    I32,
+    U32,
+}
+
+impl Ty {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "bool" => Some(Self::Bool),
+            "f32" => Some(Self::F32),
+            "i32" => Some(Self::I32),
+            "u32" => Some(Self::U32),
+            _ => None,
+        }
+    }
+
+    fn storage(self) -> &'static str {
+        match self {
+            Self::Bool | Self::U32 => "u32",
+            Self::F32 => "f32",
+            Self::I32 => "i32",
+        }
+    }
+
+    fn zero(self) -> &'static str {
+        match self {
+            Self::Bool | Self::U32 => "0u",
+            Self::F32 => "0.0",
+            Self::I32 => "0",
+        }
+    }
+
+    fn one(self) -> &'static str {
+        match self {
+            Self::Bool | Self::U32 => "1u",
+            Self::F32 => "1.0",
+            Self::I32 => "1",
+        }
+    }
+
+    fn lowest(self) -> &'static str {
+        match self {
+            Self::Bool | Self::U32 => "0u",
+            Self::F32 => "-f32_inf()",
+            Self::I32 => "i32(-2147483648)",
+        }
+    }
+
+    fn highest(self) -> &'static str {
+        match self {
+            Self::Bool => "1u",
+            Self::U32 => "4294967295u",
+            Self::F32 => "f32_inf()",
+            Self::I32 => "2147483647",
+        }
+    }
+}
+
+fn to_bool(condition: &str) -> String {
+    format!("select(0u, 1u, {})", condition)
+}
+
+/// Renders the WGSL module for `kernel_name` from the template of `source`.
+pub fn render(source: Source, kernel_name: &str) -> Result<String, WgpuKernelError> {
+    let unknown = || WgpuKernelError::UnknownKernel(kernel_name.to_string());
+    let name = kernel_name.strip_prefix(KERNEL_PREFIX).ok_or_else(unknown)?;
+
+    let body = if source == Source::Cast {
+        let (src, dst) = name
+            .strip_prefix("cast_")
+            .and_then(|rest| rest.split_once("_to_"))
+            .ok_or_else(unknown)?;
+        let (src_ty, dst_ty) = match (Ty::parse(src), Ty::parse(dst)) {
+            (Some(src_ty), Some(dst_ty)) => (src_ty, dst_ty),
+            _ => {
+                return Err(WgpuKernelError::UnsupportedDTypeForOp(
+                    format!("{}->{}", src, dst),
+                    "cast".into(),
+                ))
+            },
+        };
+        CAST_SRC
+            .replace("$IN", src_ty.storage())
+            .replace("$OUT", dst_ty.storage())
+            .replace("$FN", &cast_expr(src_ty, dst_ty))
+    } else {
+        let (op, dtype) = name.rsplit_once('_').ok_or_else(unknown)?;
+        let unsupported = || WgpuKernelError::UnsupportedDTypeForOp(dtype.to_string(), op.to_string());
+        let ty = Ty::parse(dtype).ok_or_else(unsupported)?;
+
+        match source {
+            Source::Binary => {
+                let (expr, out) = binary_expr(op, ty).ok_or_else(unsupported)?;
+                BINARY_SRC
+                    .replace("$IN", ty.storage())
+                    .replace("$OUT", out.storage())
+                    .replace("$FN", &expr)
+            },
+            Source::Unary => {
+                let (expr, out) = unary_expr(op, ty).ok_or_else(unsupported)?;
+                UNARY_SRC
+                    .replace("$IN", ty.storage())
+                    .replace("$OUT", out.storage())
+                    .replace("$FN", &expr)
+            },
+            Source::UnaryScalar => {
+                let (expr, out) = unary_scalar_expr(op, ty).ok_or_else(unsupported)?;
+                UNARY_SCALAR_SRC
+                    .replace("$IN", ty.storage())
+                    .replace("$OUT", out.storage())
+                    .replace("$FN", &expr)
+            },
+            Source::Matmul | Source::Dot => {
+                if ty == Ty::Bool {
+                    return Err(unsupported());
+                }
+                let src = if source == Source::Matmul { MATMUL_SRC } else { DOT_SRC };
+                src.replace("$T", ty.storage())
+            },
+            Source::Reduce => {
+                let [init, step, fin] = reduce_parts(op, ty).ok_or_else(unsupported)?;
+                REDUCE_SRC
+                    .replace("$INIT", &init)
+                    .replace("$STEP", &step)
+                    .replace("$FINAL", &fin)
+                    .replace("$T", ty.storage())
+            },
+            Source::Memory => MEMORY_SRC.replace("$T", ty.storage()),
+            Source::ConstSet => CONST_SET_SRC.replace("$T", ty.storage()),
+            Source::Cast => unreachable!(),
+        }
+    };
+
+    Ok(format!("{}\n{}", UTILS, body))
+}
+
+fn binary_expr(op: &str, ty: Ty) -> Option<(String, Ty)> {
+    let zero = ty.zero();
+    let logical = |cond: String| Some((to_bool(&cond), Ty::Bool));
+    let cmp = |sym: &str| Some((to_bool(&format!("x {} y", sym)), Ty::Bool));
+
+    match op {
+        "logical_and" => return logical(format!("x != {zero} && y != {zero}")),
+        "logical_or" => return logical(format!("x != {zero} || y != {zero}")),
+        "logical_xor" => return logical(format!("(x != {zero}) != (y != {zero})")),
+        "eq" => return cmp("=="),
+        "ne" => return cmp("!="),
+        "lt" => return cmp("<"),
+        "le" => return cmp("<="),
+        "gt" => return cmp(">"),
+        "ge" => return cmp(">="),
+        _ => {},
+    }
+
+    let expr = match (op, ty) {
+        ("add", Ty::Bool) => "x | y",
+        ("sub", Ty::Bool) => "x ^ y",
+        ("mul" | "div" | "minimum", Ty::Bool) => "x & y",
+        ("rem", Ty::Bool) => "x & (y ^ 1u)",
+        ("maximum", Ty::Bool) => "x | y",
+        ("pow", Ty::Bool) => return None,
+        ("sub", Ty::U32) => "select(0u, x - y, x > y)",
+        ("add", _) => "x + y",
+        ("sub", _) => "x - y",
+        ("mul", _) => "x * y",
+        ("div", _) => "x / y",
+        ("rem", _) => "x % y",
+        ("pow", Ty::F32) => "m_pow_float(x, y)",
+        ("pow", Ty::I32) => "m_pow_i32(x, y)",
+        ("pow", Ty::U32) => "m_pow_u32(x, y)",
+        ("maximum", _) => "max(x, y)",
+        ("minimum", _) => "min(x, y)",
+        _ => return None,
+    };
+    Some((expr.to_string(), ty))
+}
+
+fn unary_expr(op: &str, ty: Ty) -> Option<(String, Ty)> {
+    let zero = ty.zero();
+    match op {
+        "logical_not" => return Some((to_bool(&format!("x == {zero}")), Ty::Bool)),
+        "isnan" | "isinf" | "isfinite" => {
+            let expr = match ty {
+                Ty::F32 => to_bool(&format!("m_{}(x)", op)),
+                _ if op == "isfinite" => "1u".to_string(),
+                _ => "0u".to_string(),
+            };
+            return Some((expr, Ty::Bool));
+        },
+        _ => {},
+    }
+
+    let expr = match ty {
+        Ty::F32 => match op {
+            "neg" => "-x",
+            "abs" => "abs(x)",
+            "sign" => "sign(x)",
+            "softsign" => "m_softsign(x)",
+            "square" => "x * x",
+            "sqrt" => "sqrt(x)",
+            "recip" => "1.0 / x",
+            "relu" => "select(0.0, x, x > 0.0)",
+            "sigmoid" => "m_sigmoid(x)",
+            "hardsigmoid" => "m_hardsigmoid(x)",
+            "gelu" => "m_gelu(x)",
+            "softplus" => "m_softplus(x)",
+            "silu" => "x * m_sigmoid(x)",
+            "hardsilu" => "x * m_hardsigmoid(x)",
+            "mish" => "x * tanh(m_softplus(x))",
+            "selu" => "m_selu(x)",
+            "celu" => "m_celu(x)",
+            "sin" => "sin(x)",
+            "cos" => "cos(x)",
+            "tan" => "m_tan(x)",
+            "asin" => "asin(x)",
+            "acos" => "acos(x)",
+            "atan" => "atan(x)",
+            "sinh" => "sinh(x)",
+            "cosh" => "cosh(x)",
+            "tanh" => "tanh(x)",
+            "asinh" => "asinh(x)",
+            "acosh" => "acosh(x)",
+            "atanh" => "atanh(x)",
+            "exp" => "exp(x)",
+            "exp2" => "exp2(x)",
+            "exp10" => "exp(x * LN_10)",
+            "ln" => "log(x)",
+            "log2" => "log2(x)",
+            "log10" => "log(x) * INV_LN_10",
+            "ceil" => "ceil(x)",
+            "floor" => "floor(x)",
+            "round" => "m_round(x)",
+            "erf" => "m_erf(x)",
+            _ => return None,
+        },
+        Ty::I32 => match op {
+            "neg" => "-x",
+            "abs" => "abs(x)",
+            "sign" => "sign(x)",
+            "softsign" => "i32(m_softsign(f32(x)))",
+            "square" => "x * x",
+            "sqrt" => "i32(sqrt(f32(abs(x))))",
+            "relu" => "max(x, 0)",
+            _ => return None,
+        },
+        Ty::U32 => match op {
+            "abs" | "relu" => "x",
+            "sign" => "min(x, 1u)",
+            "softsign" => "u32(m_softsign(f32(x)))",
+            "square" => "x * x",
+            "sqrt" => "u32(sqrt(f32(x)))",
+            _ => return None,
+        },
+        Ty::Bool => match op {
+            "neg" => "x ^ 1u",
+            "abs" | "sign" | "square" | "sqrt" | "relu" => "x",
+            _ => return None,
+        },
+    };
+    Some((expr.to_string(), ty))
+}
+
+fn unary_scalar_expr(op: &str, ty: Ty) -> Option<(String, Ty)> {
+    let cmp = |sym: &str| Some((to_bool(&format!("x {} c", sym)), Ty::Bool));
+    match op {
+        "eq_scalar" => return cmp("=="),
+        "ne_scalar" => return cmp("!="),
+        "lt_scalar" => return cmp("<"),
+        "le_scalar" => return cmp("<="),
+        "gt_scalar" => return cmp(">"),
+        "ge_scalar" => return cmp(">="),
+        _ => {},
+    }
+
+    let expr = match (op, ty) {
+        ("add_scalar" | "maximum_scalar", Ty::Bool) => "x | c",
+        ("sub_scalar", Ty::Bool) => "x ^ c",
+        ("mul_scalar" | "div_scalar" | "pow_scalar" | "minimum_scalar", Ty::Bool) => "x & c",
+        ("rem_scalar", Ty::Bool) => "x & (c ^ 1u)",
+        ("sub_scalar", Ty::U32) => "select(0u, x - c, x > c)",
+        ("add_scalar", _) => "x + c",
+        ("sub_scalar", _) => "x - c",
+        ("mul_scalar", _) => "x * c",
+        ("div_scalar", _) => "x / c",
+        ("rem_scalar", _) => "x % c",
+        ("pow_scalar", Ty::F32) => "m_pow_float(x, c)",
+        ("pow_scalar", Ty::I32) => "i32(m_pow_float(f32(x), f32(c)))",
+        ("pow_scalar", Ty::U32) => "u32(m_pow_float(f32(x), f32(c)))",
+        ("maximum_scalar", _) => "max(x, c)",
+        ("minimum_scalar", _) => "min(x, c)",
+        ("leaky_relu" | "prelu", Ty::F32) => "select(c * x, x, x > 0.0)",
+        ("elu", Ty::F32) => "select(c * (exp(x) - 1.0), x, x > 0.0)",
+        _ => return None,
+    };
+    Some((expr.to_string(), ty))
+}
+
+/// Accumulator declarations, per-element step and final statements of a reduction
+fn reduce_parts(op: &str, ty: Ty) -> Option<[String; 3]> {
+    let n = match ty {
+        Ty::F32 => "f32(n)",
+        Ty::I32 => "i32(n)",
+        _ => "n",
+    };
+    let parts = |init: String, step: &str, fin: String| Some([init, step.to_string(), fin]);
+
+    if ty == Ty::Bool {
+        return None;
+    }
+
+    match op {
+        "sum" => parts(format!("var acc = {};", ty.zero()), "acc += x;", "let result = acc;".into()),
+        "mean" => parts(
+            format!("var acc = {};", ty.zero()),
+            "acc += x;",
+            format!("let result = acc / {};", n),
+        ),
+        "prod" => parts(format!("var acc = {};", ty.one()), "acc *= x;", "let result = acc;".into()),
+        "max" => parts(
+            format!("var acc = {};", ty.lowest()),
+            "acc = max(acc, x);",
+            "let result = acc;".into(),
+        ),
+        "min" => parts(
+            format!("var acc = {};", ty.highest()),
+            "acc = min(acc, x);",
+            "let result = acc;".into(),
+        ),
+        _ if ty != Ty::F32 => None,
+        // Population statistics, accumulated with Welford's algorithm
+        "var" | "std" => {
+            let variance = "m2 / f32(n)";
+            let result = if op == "std" {
+                format!("sqrt({})", variance)
+            } else {
+                variance.to_string()
+            };
+            parts(
+                "var count = 0.0;\n    var mean = 0.0;\n    var m2 = 0.0;".into(),
+                "count += 1.0;\n        let delta = x - mean;\n        mean += delta / count;\n        m2 += delta * (x - mean);",
+                format!("let result = {};", result),
+            )
+        },
+        "norm" => parts("var sq = 0.0;".into(), "sq += x * x;", "let result = sqrt(sq);".into()),
+        "logsum" => parts("var acc = 0.0;".into(), "acc += x;", "let result = log(acc);".into()),
+        // Online log-sum-exp keeps a running max to avoid overflow
+        "logsumexp" => parts(
+            "var m = -f32_inf();\n    var s = 0.0;".into(),
+            "if (x > m) {\n            s = s * exp(m - x) + 1.0;\n            m = x;\n        } else {\n            s += exp(x - m);\n        }",
+            "let result = m + log(s);".into(),
+        ),
+        _ => None,
+    }
+}
+
+fn cast_expr(src: Ty, dst: Ty) -> String {
+    match (src, dst) {
+        _ if src == dst => "x".to_string(),
+        (_, Ty::Bool) => to_bool(&format!("x != {}", src.zero())),
+        (Ty::Bool, Ty::U32) => "x".to_string(),
+        (Ty::I32, Ty::U32) => "bitcast<u32>(x)".to_string(),
+        (Ty::U32, Ty::I32) => "bitcast<i32>(x)".to_string(),
+        _ => format!("{}(x)", dst.storage()),
+    }
+}
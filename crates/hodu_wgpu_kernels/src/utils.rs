@@ -0,0 +1,72 @@
+use crate::{device::Device, error::WgpuKernelError};
+
+/// Threads per workgroup, matching `WORKGROUP_SIZE` in the WGSL helpers
+pub const WORKGROUP_SIZE: u32 = 256;
+
+/// Uploads kernel metadata as the `array<u32>` every kernel reads its layout from
+pub fn metadata_buffer(device: &Device, metadata: &[usize]) -> Result<wgpu::Buffer, WgpuKernelError> {
+    let words = metadata
+        .iter()
+        .map(|&value| u32::try_from(value).map_err(|_| WgpuKernelError::MetadataOverflow(value)))
+        .collect::<Result<Vec<u32>, _>>()?;
+    Ok(device.new_buffer_with_data(&words, "metadata"))
+}
+
+/// Uploads a scalar argument given as its raw 32-bit pattern
+pub fn scalar_buffer(device: &Device, bits: u32) -> wgpu::Buffer {
+    device.new_buffer_with_data(&[bits], "scalar")
+}
+
+/// Workgroup grid covering `num_threads`; grids beyond the per-dimension limit spill into y
+pub fn linear_split(device: &Device, num_threads: usize) -> (u32, u32) {
+    let groups = num_threads.div_ceil(WORKGROUP_SIZE as usize) as u32;
+    let max = device.device().limits().max_compute_workgroups_per_dimension;
+    if groups <= max {
+        (groups, 1)
+    } else {
+        (max, groups.div_ceil(max))
+    }
+}
+
+/// Binds `buffers` in order to group 0 and submits one dispatch of `pipeline` over `num_threads`.
+pub fn dispatch(
+    device: &Device,
+    pipeline: &wgpu::ComputePipeline,
+    label: &str,
+    buffers: &[&wgpu::Buffer],
+    num_threads: usize,
+) -> Result<(), WgpuKernelError> {
+    if num_threads == 0 {
+        return Ok(());
+    }
+
+    let entries: Vec<wgpu::BindGroupEntry> = buffers
+        .iter()
+        .enumerate()
+        .map(|(binding, buffer)| wgpu::BindGroupEntry {
+            binding: binding as u32,
+            resource: buffer.as_entire_binding(),
+        })
+        .collect();
+    let bind_group = device.device().create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(label),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &entries,
+    });
+
+    let mut encoder = device
+        .device()
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(label) });
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some(label),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        let (x, y) = linear_split(device, num_threads);
+        pass.dispatch_workgroups(x, y, 1);
+    }
+    device.queue().submit([encoder.finish()]);
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use hodu_wgpu_kernels::{device::Device, kernel::Kernels, kernels::*};
+
+// Tests are skipped on machines without a usable adapter
+fn device() -> Option<Device> {
+    Device::system_default().ok()
+}
+
+fn run_binary<T: bytemuck::Pod, O: bytemuck::Pod>(lhs: &[T], rhs: &[T], kernel: Kernel) -> Option<Vec<O>> {
+    assert_eq!(lhs.len(), rhs.len());
+    let device = device()?;
+    let kernels = Kernels::new();
+    let left = device.new_buffer_with_data(lhs, "lhs");
+    let right = device.new_buffer_with_data(rhs, "rhs");
+    let output = device.new_buffer(std::mem::size_of::<O>() * lhs.len(), "output");
+
+    let shape = vec![lhs.len()];
+    let strides = vec![1];
+    let mut metadata = vec![lhs.len(), shape.len()];
+    metadata.extend(&shape);
+    metadata.extend(&shape);
+    metadata.extend(&strides);
+    metadata.extend(&strides);
+    metadata.push(0); // lhs offset
+    metadata.push(0); // rhs offset
+
+    call_ops_binary(kernel, &kernels, &device, &left, &right, &output, &metadata).unwrap();
+    Some(device.read_buffer(&output, lhs.len()).unwrap())
+}
+
+#[test]
+fn binary_add_f32() {
+    let lhs = [1.0f32, 2.0, 3.0, 4.0];
+    let rhs = [0.5f32, 1.5, -3.0, 10.0];
+    if let Some(result) = run_binary::<f32, f32>(&lhs, &rhs, add::F32) {
+        assert_eq!(result, vec![1.5, 3.5, 0.0, 14.0]);
+    }
+}
+
+#[test]
+fn binary_sub_u32_saturates() {
+    let lhs = [5u32, 1, 7];
+    let rhs = [2u32, 3, 7];
+    if let Some(result) = run_binary::<u32, u32>(&lhs, &rhs, sub::U32) {
+        assert_eq!(result, vec![3, 0, 0]);
+    }
+}
+
+#[test]
+fn binary_pow_i32() {
+    let lhs = [2i32, -3, 5];
+    let rhs = [10i32, 3, 0];
+    if let Some(result) = run_binary::<i32, i32>(&lhs, &rhs, pow::I32) {
+        assert_eq!(result, vec![1024, -27, 1]);
+    }
+}
+
+#[test]
+fn binary_cmp_f32() {
+    let lhs = [1.0f32, 2.0, 3.0];
+    let rhs = [2.0f32, 2.0, 2.0];
+    if let Some(result) = run_binary::<f32, u32>(&lhs, &rhs, lt::F32) {
+        assert_eq!(result, vec![1, 0, 0]);
+    }
+}
+
+#[test]
+fn binary_logical_xor_bool() {
+    let lhs = [0u32, 1, 0, 1];
+    let rhs = [0u32, 0, 1, 1];
+    if let Some(result) = run_binary::<u32, u32>(&lhs, &rhs, logical_xor::BOOL) {
+        assert_eq!(result, vec![0, 1, 1, 0]);
+    }
+}
+
+#[test]
+fn binary_broadcast_strides() {
+    // lhs is [2, 3], rhs is a [3] row broadcast with a zero stride
+    let Some(device) = device() else { return };
+    let kernels = Kernels::new();
+    let lhs = device.new_buffer_with_data(&[1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], "lhs");
+    let rhs = device.new_buffer_with_data(&[10.0f32, 20.0, 30.0], "rhs");
+    let output = device.new_buffer(4 * 6, "output");
+    let metadata = [6, 2, 2, 3, 2, 3, 3, 1, 0, 1, 0, 0];
+
+    call_ops_binary(mul::F32, &kernels, &device, &lhs, &rhs, &output, &metadata).unwrap();
+    let result: Vec<f32> = device.read_buffer(&output, 6).unwrap();
+    assert_eq!(result, vec![10.0, 40.0, 90.0, 40.0, 100.0, 180.0]);
+}
@@ -0,0 +1,49 @@
+use hodu_wgpu_kernels::{device::Device, kernel::Kernels, kernels::*};
+
+// Tests are skipped on machines without a usable adapter
+fn device() -> Option<Device> {
+    Device::system_default().ok()
+}
+
+fn run_cast<T: bytemuck::Pod, O: bytemuck::Pod>(input: &[T], kernel: Kernel) -> Option<Vec<O>> {
+    let device = device()?;
+    let kernels = Kernels::new();
+    let input_buffer = device.new_buffer_with_data(input, "input");
+    let output = device.new_buffer(std::mem::size_of::<O>() * input.len(), "output");
+
+    let metadata = [input.len(), 1, input.len(), 1, 0];
+    call_ops_cast(kernel, &kernels, &device, &input_buffer, &output, &metadata).unwrap();
+    Some(device.read_buffer(&output, input.len()).unwrap())
+}
+
+#[test]
+fn cast_f32_to_i32() {
+    let input = [1.7f32, -2.2, 0.0];
+    if let Some(result) = run_cast::<f32, i32>(&input, Kernel("hodu_wgpu_cast_f32_to_i32")) {
+        assert_eq!(result, vec![1, -2, 0]);
+    }
+}
+
+#[test]
+fn cast_f32_to_bool() {
+    let input = [0.0f32, 0.5, -1.0];
+    if let Some(result) = run_cast::<f32, u32>(&input, Kernel("hodu_wgpu_cast_f32_to_bool")) {
+        assert_eq!(result, vec![0, 1, 1]);
+    }
+}
+
+#[test]
+fn cast_unknown_dtype() {
+    let Some(device) = device() else { return };
+    let kernels = Kernels::new();
+    let buffer = device.new_buffer(4, "buffer");
+    let result = call_ops_cast(
+        Kernel("hodu_wgpu_cast_f64_to_f32"),
+        &kernels,
+        &device,
+        &buffer,
+        &buffer,
+        &[1, 1, 1, 1, 0],
+    );
+    assert!(result.is_err());
+}
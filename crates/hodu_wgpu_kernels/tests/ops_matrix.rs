@@ -0,0 +1,67 @@
+use hodu_wgpu_kernels::{device::Device, kernel::Kernels, kernels::*};
+
+// Tests are skipped on machines without a usable adapter
+fn device() -> Option<Device> {
+    Device::system_default().ok()
+}
+
+#[test]
+fn matmul_batched_f32() {
+    let Some(device) = device() else { return };
+    let kernels = Kernels::new();
+
+    // [2, 2, 3] x [3, 2] -> [2, 2, 2], rhs broadcast over the batch
+    let lhs: Vec<f32> = (1..=12).map(|v| v as f32).collect();
+    let rhs = [1.0f32, 0.0, 0.0, 1.0, 1.0, 1.0];
+    let lhs_buffer = device.new_buffer_with_data(&lhs, "lhs");
+    let rhs_buffer = device.new_buffer_with_data(&rhs, "rhs");
+    let output = device.new_buffer(4 * 8, "output");
+
+    let mut metadata = vec![8, 3, 2, 1];
+    metadata.extend([2, 2, 3]); // lhs shape
+    metadata.extend([3, 2]); // rhs shape
+    metadata.extend([2]); // batch shape
+    metadata.extend([6, 3, 1]); // lhs strides
+    metadata.extend([2, 1]); // rhs strides
+    metadata.extend([0, 0]); // offsets
+    metadata.extend([2, 3, 2]); // M, K, N
+
+    call_ops_matmul(
+        matmul::F32,
+        &kernels,
+        &device,
+        &lhs_buffer,
+        &rhs_buffer,
+        &output,
+        &metadata,
+    )
+    .unwrap();
+    let result: Vec<f32> = device.read_buffer(&output, 8).unwrap();
+    assert_eq!(result, vec![4.0, 5.0, 10.0, 11.0, 16.0, 17.0, 22.0, 23.0]);
+}
+
+#[test]
+fn dot_i32() {
+    let Some(device) = device() else { return };
+    let kernels = Kernels::new();
+
+    let lhs = [1i32, 2, 3, 4];
+    let rhs = [5i32, 6, 7, 8];
+    let lhs_buffer = device.new_buffer_with_data(&lhs, "lhs");
+    let rhs_buffer = device.new_buffer_with_data(&rhs, "rhs");
+    let output = device.new_buffer(4 * 4, "output");
+
+    let metadata = [2, 2, 2, 2, 1, 2, 1, 0, 0];
+    call_ops_dot(
+        dot::I32,
+        &kernels,
+        &device,
+        &lhs_buffer,
+        &rhs_buffer,
+        &output,
+        &metadata,
+    )
+    .unwrap();
+    let result: Vec<i32> = device.read_buffer(&output, 4).unwrap();
+    assert_eq!(result, vec![19, 22, 43, 50]);
+}
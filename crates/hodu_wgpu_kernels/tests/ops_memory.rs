@@ -0,0 +1,35 @@
+use hodu_wgpu_kernels::{device::Device, kernel::Kernels, kernels::*};
+
+// Tests are skipped on machines without a usable adapter
+fn device() -> Option<Device> {
+    Device::system_default().ok()
+}
+
+#[test]
+fn contiguous_transposed_f32() {
+    let Some(device) = device() else { return };
+    let kernels = Kernels::new();
+
+    // [2, 3] viewed as its [3, 2] transpose
+    let input = device.new_buffer_with_data(&[1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], "input");
+    let output = device.new_buffer(4 * 6, "output");
+    let metadata = [6, 2, 3, 2, 1, 3, 0];
+
+    call_ops_contiguous(contiguous::F32, &kernels, &device, &input, &output, &metadata).unwrap();
+    let result: Vec<f32> = device.read_buffer(&output, 6).unwrap();
+    assert_eq!(result, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+}
+
+#[test]
+fn const_set_strided_i32() {
+    let Some(device) = device() else { return };
+    let kernels = Kernels::new();
+
+    // Fill every other element of a [6] buffer
+    let output = device.new_buffer_with_data(&[0i32; 6], "output");
+    let metadata = [3, 1, 3, 2, 0];
+
+    call_const_set(const_set::I32, &kernels, &device, &output, &metadata, (-7i32) as u32).unwrap();
+    let result: Vec<i32> = device.read_buffer(&output, 6).unwrap();
+    assert_eq!(result, vec![-7, 0, -7, 0, -7, 0]);
+}
@@ -0,0 +1,64 @@
+use hodu_wgpu_kernels::{device::Device, kernel::Kernels, kernels::*};
+
+// Tests are skipped on machines without a usable adapter
+fn device() -> Option<Device> {
+    Device::system_default().ok()
+}
+
+fn approx(v: Vec<f32>, digits: i32) -> Vec<f32> {
+    let b = 10f32.powi(digits);
+    v.iter().map(|t| f32::round(t * b) / b).collect()
+}
+
+// Reduce a contiguous [rows, cols] tensor over `dim`
+fn run_reduce<T: bytemuck::Pod>(input: &[T], rows: usize, cols: usize, dim: usize, kernel: Kernel) -> Option<Vec<T>> {
+    let device = device()?;
+    let kernels = Kernels::new();
+    let input_buffer = device.new_buffer_with_data(input, "input");
+
+    let output_shape = if dim == 0 { cols } else { rows };
+    let reduce_size = if dim == 0 { rows } else { cols };
+    let output = device.new_buffer(std::mem::size_of::<T>() * output_shape, "output");
+
+    let metadata = [2, rows, cols, cols, 1, 0, 1, output_shape, 1, dim, 0, reduce_size];
+    call_ops_reduce(kernel, &kernels, &device, &input_buffer, &output, &metadata).unwrap();
+    Some(device.read_buffer(&output, output_shape).unwrap())
+}
+
+#[test]
+fn reduce_sum_f32() {
+    let input = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+    if let Some(result) = run_reduce(&input, 2, 3, 1, sum::F32) {
+        assert_eq!(result, vec![6.0, 15.0]);
+    }
+    if let Some(result) = run_reduce(&input, 2, 3, 0, sum::F32) {
+        assert_eq!(result, vec![5.0, 7.0, 9.0]);
+    }
+}
+
+#[test]
+fn reduce_max_i32() {
+    let input = [-1i32, -5, 3, 2, 8, -9];
+    if let Some(result) = run_reduce(&input, 2, 3, 1, max::I32) {
+        assert_eq!(result, vec![3, 8]);
+    }
+}
+
+#[test]
+fn reduce_mean_var_f32() {
+    let input = [1.0f32, 2.0, 3.0, 4.0];
+    if let Some(result) = run_reduce(&input, 1, 4, 1, mean::F32) {
+        assert_eq!(result, vec![2.5]);
+    }
+    if let Some(result) = run_reduce(&input, 1, 4, 1, Kernel("hodu_wgpu_var_f32")) {
+        assert_eq!(approx(result, 4), vec![1.25]);
+    }
+}
+
+#[test]
+fn reduce_logsumexp_f32() {
+    let input = [1.0f32, 2.0, 3.0];
+    if let Some(result) = run_reduce(&input, 1, 3, 1, logsumexp::F32) {
+        assert_eq!(approx(result, 3), vec![3.408]);
+    }
+}
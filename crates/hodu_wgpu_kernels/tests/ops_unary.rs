@@ -0,0 +1,106 @@
+use hodu_wgpu_kernels::{device::Device, kernel::Kernels, kernels::*};
+
+// Tests are skipped on machines without a usable adapter
+fn device() -> Option<Device> {
+    Device::system_default().ok()
+}
+
+fn approx(v: Vec<f32>, digits: i32) -> Vec<f32> {
+    let b = 10f32.powi(digits);
+    v.iter().map(|t| f32::round(t * b) / b).collect()
+}
+
+fn contiguous_metadata(len: usize) -> Vec<usize> {
+    vec![len, 1, len, 1, 0]
+}
+
+fn run_unary<T: bytemuck::Pod, O: bytemuck::Pod>(input: &[T], kernel: Kernel) -> Option<Vec<O>> {
+    let device = device()?;
+    let kernels = Kernels::new();
+    let input_buffer = device.new_buffer_with_data(input, "input");
+    let output = device.new_buffer(std::mem::size_of::<O>() * input.len(), "output");
+
+    let metadata = contiguous_metadata(input.len());
+    call_ops_unary(kernel, &kernels, &device, &input_buffer, &output, &metadata).unwrap();
+    Some(device.read_buffer(&output, input.len()).unwrap())
+}
+
+fn run_unary_scalar<T: bytemuck::Pod, O: bytemuck::Pod>(
+    input: &[T],
+    scalar_bits: u32,
+    kernel: Kernel,
+) -> Option<Vec<O>> {
+    let device = device()?;
+    let kernels = Kernels::new();
+    let input_buffer = device.new_buffer_with_data(input, "input");
+    let output = device.new_buffer(std::mem::size_of::<O>() * input.len(), "output");
+
+    let metadata = contiguous_metadata(input.len());
+    call_ops_unary_scalar(
+        kernel,
+        &kernels,
+        &device,
+        &input_buffer,
+        &output,
+        &metadata,
+        scalar_bits,
+    )
+    .unwrap();
+    Some(device.read_buffer(&output, input.len()).unwrap())
+}
+
+#[test]
+fn unary_exp_f32() {
+    let input = [0.0f32, 0.5, -1.0];
+    if let Some(result) = run_unary::<f32, f32>(&input, exp::F32) {
+        assert_eq!(approx(result, 3), vec![1.0, 1.649, 0.368]);
+    }
+}
+
+#[test]
+fn unary_round_half_away_from_zero() {
+    let input = [0.5f32, 1.5, 2.5, -2.5];
+    if let Some(result) = run_unary::<f32, f32>(&input, round::F32) {
+        assert_eq!(result, vec![1.0, 2.0, 3.0, -3.0]);
+    }
+}
+
+#[test]
+fn unary_neg_i32() {
+    let input = [1i32, -2, 0];
+    if let Some(result) = run_unary::<i32, i32>(&input, neg::I32) {
+        assert_eq!(result, vec![-1, 2, 0]);
+    }
+}
+
+#[test]
+fn unary_isnan_f32() {
+    let input = [1.0f32, f32::NAN, f32::INFINITY];
+    if let Some(result) = run_unary::<f32, u32>(&input, isnan::F32) {
+        assert_eq!(result, vec![0, 1, 0]);
+    }
+}
+
+#[test]
+fn unary_scalar_add_f32() {
+    let input = [1.0f32, 2.0, 3.0];
+    if let Some(result) = run_unary_scalar::<f32, f32>(&input, 0.5f32.to_bits(), add_scalar::F32) {
+        assert_eq!(result, vec![1.5, 2.5, 3.5]);
+    }
+}
+
+#[test]
+fn unary_scalar_leaky_relu_f32() {
+    let input = [-2.0f32, 0.0, 3.0];
+    if let Some(result) = run_unary_scalar::<f32, f32>(&input, 0.1f32.to_bits(), leaky_relu::F32) {
+        assert_eq!(approx(result, 4), vec![-0.2, 0.0, 3.0]);
+    }
+}
+
+#[test]
+fn unary_scalar_cmp_i32() {
+    let input = [-1i32, 0, 1];
+    if let Some(result) = run_unary_scalar::<i32, u32>(&input, 0, ge_scalar::I32) {
+        assert_eq!(result, vec![0, 1, 1]);
+    }
+}
@@ -0,0 +1,29 @@
+//! Generates the C header from the exported functions into `OUT_DIR`.
+//!
+//! The checked-in `include/hodu.h` is only rewritten when `HODU_CAPI_WRITE_HEADER` is set;
+//! the `header` test fails while it is out of date.
+
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=HODU_CAPI_WRITE_HEADER");
+
+    let crate_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).expect("invalid cbindgen.toml");
+
+    let bindings = cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("failed to generate the C header");
+
+    bindings.write_to_file(out_dir.join("hodu.h"));
+
+    if std::env::var_os("HODU_CAPI_WRITE_HEADER").is_some() && bindings.write_to_file(crate_dir.join("include/hodu.h"))
+    {
+        println!("cargo:warning=updated include/hodu.h");
+    }
+}
@@ -0,0 +1,256 @@
+/* hodu C API. Generated by cbindgen from hodu-capi; do not edit. */
+
+#ifndef HODU_H
+#define HODU_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Result of a fallible call; anything but `HODU_STATUS_OK` leaves a message for
+// `hodu_last_error`
+typedef enum HoduStatus {
+  HODU_STATUS_OK = 0,
+  // A null pointer, non-UTF-8 string, unknown dtype or device, or other bad argument
+  HODU_STATUS_INVALID_ARGUMENT = 1,
+  // A file could not be read or written
+  HODU_STATUS_IO = 2,
+  // Data could not be serialized or deserialized
+  HODU_STATUS_FORMAT = 3,
+  // Shapes, sizes or axes do not fit together
+  HODU_STATUS_SHAPE = 4,
+  // A dtype is not supported for the requested operation
+  HODU_STATUS_DTYPE = 5,
+  // A device is not available or not supported
+  HODU_STATUS_DEVICE = 6,
+  // The operation is not supported
+  HODU_STATUS_UNSUPPORTED = 7,
+  // A caller-provided buffer is too small
+  HODU_STATUS_BUFFER_TOO_SMALL = 8,
+  // A backend or kernel failed
+  HODU_STATUS_BACKEND = 9,
+  // Any other failure, including a panic caught at the API boundary
+  HODU_STATUS_INTERNAL = 10,
+} HoduStatus;
+
+// A captured computation graph owned by the caller, released with [`hodu_snapshot_free`]
+typedef struct HoduSnapshot HoduSnapshot;
+
+// A tensor owned by the caller, released with [`hodu_tensor_free`]
+typedef struct HoduTensor HoduTensor;
+
+// Element type of a tensor, one of the `HODU_DTYPE_*` constants
+typedef uint32_t HoduDType;
+
+#define HODU_DTYPE_BOOL 0
+
+#define HODU_DTYPE_F8E4M3 1
+
+#define HODU_DTYPE_F8E5M2 2
+
+#define HODU_DTYPE_BF16 3
+
+#define HODU_DTYPE_F16 4
+
+#define HODU_DTYPE_F32 5
+
+#define HODU_DTYPE_F64 6
+
+#define HODU_DTYPE_U8 7
+
+#define HODU_DTYPE_U16 8
+
+#define HODU_DTYPE_U32 9
+
+#define HODU_DTYPE_U64 10
+
+#define HODU_DTYPE_I8 11
+
+#define HODU_DTYPE_I16 12
+
+#define HODU_DTYPE_I32 13
+
+#define HODU_DTYPE_I64 14
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Version of the library as a NUL-terminated string
+const char *hodu_version(void);
+
+// Message of the last failed call on the calling thread, or null if no call has failed
+//
+// The string stays valid until the next failing call on the same thread.
+const char *hodu_last_error(void);
+
+// Static description of `status`
+const char *hodu_status_str(enum HoduStatus status);
+
+// Load a snapshot from an .hdss file
+//
+// # Safety
+//
+// `path` must be a NUL-terminated string and `out` valid for writing a pointer.
+enum HoduStatus hodu_snapshot_load(const char *path, struct HoduSnapshot **out);
+
+// Load a snapshot from the `len` bytes of an .hdss file held in memory
+//
+// # Safety
+//
+// `data` must be valid for reading `len` bytes and `out` for writing a pointer.
+enum HoduStatus hodu_snapshot_from_bytes(const uint8_t *data,
+                                         size_t len,
+                                         struct HoduSnapshot **out);
+
+// Save a snapshot to an .hdss file
+//
+// # Safety
+//
+// `snapshot` must be a live handle and `path` a NUL-terminated string.
+enum HoduStatus hodu_snapshot_save(const struct HoduSnapshot *snapshot, const char *path);
+
+// Release a snapshot; null is ignored
+//
+// # Safety
+//
+// `snapshot` must be null or a handle from this library that has not been freed.
+void hodu_snapshot_free(struct HoduSnapshot *snapshot);
+
+// Number of inputs the snapshot declares, or 0 if it is null
+//
+// # Safety
+//
+// `snapshot` must be null or a live handle.
+size_t hodu_snapshot_num_inputs(const struct HoduSnapshot *snapshot);
+
+// Name of input `index`, or null if out of range
+//
+// The string lives as long as the snapshot.
+//
+// # Safety
+//
+// `snapshot` must be null or a live handle.
+const char *hodu_snapshot_input_name(const struct HoduSnapshot *snapshot, size_t index);
+
+// Number of targets (outputs) the snapshot declares, or 0 if it is null
+//
+// # Safety
+//
+// `snapshot` must be null or a live handle.
+size_t hodu_snapshot_num_targets(const struct HoduSnapshot *snapshot);
+
+// Name of target `index`, or null if out of range
+//
+// The string lives as long as the snapshot.
+//
+// # Safety
+//
+// `snapshot` must be null or a live handle.
+const char *hodu_snapshot_target_name(const struct HoduSnapshot *snapshot, size_t index);
+
+// Run the snapshot in-process on `device` ("cpu" when null)
+//
+// Inputs are passed as `num_inputs` parallel names and tensors. `outputs` receives one new tensor
+// per target, in target order, and must hold [`hodu_snapshot_num_targets`] entries; nothing is
+// written on failure.
+//
+// # Safety
+//
+// `snapshot` must be a live handle, `input_names` and `inputs` valid for reading `num_inputs`
+// entries, `device` null or a NUL-terminated string, and `outputs` valid for writing
+// `num_outputs` pointers.
+enum HoduStatus hodu_snapshot_run(const struct HoduSnapshot *snapshot,
+                                  const char *const *input_names,
+                                  const struct HoduTensor *const *inputs,
+                                  size_t num_inputs,
+                                  const char *device,
+                                  struct HoduTensor **outputs,
+                                  size_t num_outputs);
+
+// Create a CPU tensor by copying `nbytes` bytes of little-endian, row-major element data
+//
+// `nbytes` must equal the element count of `shape` times the size of `dtype`. A scalar has
+// `ndim` 0, in which case `shape` may be null.
+//
+// # Safety
+//
+// `data` must be valid for reading `nbytes` bytes, `shape` for reading `ndim` values, and
+// `out` for writing a pointer.
+enum HoduStatus hodu_tensor_new(const void *data,
+                                size_t nbytes,
+                                const size_t *shape,
+                                size_t ndim,
+                                HoduDType dtype,
+                                struct HoduTensor **out);
+
+// Release a tensor; null is ignored
+//
+// # Safety
+//
+// `tensor` must be null or a handle from this library that has not been freed.
+void hodu_tensor_free(struct HoduTensor *tensor);
+
+// Element type of `tensor`, or `HODU_DTYPE_F32` if it is null
+//
+// # Safety
+//
+// `tensor` must be null or a live handle.
+HoduDType hodu_tensor_dtype(const struct HoduTensor *tensor);
+
+// Number of dimensions of `tensor`, or 0 if it is null
+//
+// # Safety
+//
+// `tensor` must be null or a live handle.
+size_t hodu_tensor_ndim(const struct HoduTensor *tensor);
+
+// Number of elements in `tensor`, or 0 if it is null
+//
+// # Safety
+//
+// `tensor` must be null or a live handle.
+size_t hodu_tensor_numel(const struct HoduTensor *tensor);
+
+// Size in bytes of the data [`hodu_tensor_copy_data`] writes, or 0 if `tensor` is null
+//
+// # Safety
+//
+// `tensor` must be null or a live handle.
+size_t hodu_tensor_nbytes(const struct HoduTensor *tensor);
+
+// Copy the dimensions of `tensor` into `dims`, which holds `capacity` values
+//
+// # Safety
+//
+// `tensor` must be a live handle and `dims` valid for writing `capacity` values.
+enum HoduStatus hodu_tensor_shape(const struct HoduTensor *tensor, size_t *dims, size_t capacity);
+
+// Copy the little-endian, row-major data of `tensor` into `out`, which holds `nbytes` bytes
+//
+// `nbytes` must be at least [`hodu_tensor_nbytes`]. Tensors on other devices are copied back
+// to the host.
+//
+// # Safety
+//
+// `tensor` must be a live handle and `out` valid for writing `nbytes` bytes.
+enum HoduStatus hodu_tensor_copy_data(const struct HoduTensor *tensor, void *out, size_t nbytes);
+
+// Load a tensor from an .hdt, .npy or .json file, picked by extension
+//
+// # Safety
+//
+// `path` must be a NUL-terminated string and `out` valid for writing a pointer.
+enum HoduStatus hodu_tensor_load(const char *path, struct HoduTensor **out);
+
+// Save a tensor to an .hdt, .npy or .json file, picked by extension
+//
+// # Safety
+//
+// `tensor` must be a live handle and `path` a NUL-terminated string.
+enum HoduStatus hodu_tensor_save(const struct HoduTensor *tensor, const char *path);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* HODU_H */
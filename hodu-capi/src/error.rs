@@ -0,0 +1,133 @@
+use hodu_core::error::HoduError;
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Result of a fallible call; anything but `HODU_STATUS_OK` leaves a message for
+/// `hodu_last_error`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HoduStatus {
+    Ok = 0,
+    /// A null pointer, non-UTF-8 string, unknown dtype or device, or other bad argument
+    InvalidArgument = 1,
+    /// A file could not be read or written
+    Io = 2,
+    /// Data could not be serialized or deserialized
+    Format = 3,
+    /// Shapes, sizes or axes do not fit together
+    Shape = 4,
+    /// A dtype is not supported for the requested operation
+    Dtype = 5,
+    /// A device is not available or not supported
+    Device = 6,
+    /// The operation is not supported
+    Unsupported = 7,
+    /// A caller-provided buffer is too small
+    BufferTooSmall = 8,
+    /// A backend or kernel failed
+    Backend = 9,
+    /// Any other failure, including a panic caught at the API boundary
+    Internal = 10,
+}
+
+impl HoduStatus {
+    fn as_str(self) -> &'static std::ffi::CStr {
+        match self {
+            Self::Ok => c"ok",
+            Self::InvalidArgument => c"invalid argument",
+            Self::Io => c"i/o error",
+            Self::Format => c"format error",
+            Self::Shape => c"shape error",
+            Self::Dtype => c"dtype error",
+            Self::Device => c"device error",
+            Self::Unsupported => c"unsupported operation",
+            Self::BufferTooSmall => c"buffer too small",
+            Self::Backend => c"backend error",
+            Self::Internal => c"internal error",
+        }
+    }
+}
+
+/// Error returned by the functions behind the C API
+pub(crate) struct Error {
+    status: HoduStatus,
+    message: String,
+}
+
+impl Error {
+    pub(crate) fn new(status: HoduStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn invalid(message: impl Into<String>) -> Self {
+        Self::new(HoduStatus::InvalidArgument, message)
+    }
+}
+
+impl From<HoduError> for Error {
+    fn from(error: HoduError) -> Self {
+        let status = match &error {
+            HoduError::DeviceMismatch { .. }
+            | HoduError::DeviceConflictInOp { .. }
+            | HoduError::UnsupportedDevice(_) => HoduStatus::Device,
+            HoduError::DTypeMismatch { .. }
+            | HoduError::DTypeConflictInOp { .. }
+            | HoduError::UnsupportedDType { .. }
+            | HoduError::UnsupportedDTypeForOp { .. }
+            | HoduError::UnsupportedDTypeForDevice { .. } => HoduStatus::Dtype,
+            HoduError::ShapeMismatch(_)
+            | HoduError::SizeMismatch { .. }
+            | HoduError::IncompatibleShapes(_)
+            | HoduError::InvalidLayout { .. }
+            | HoduError::InvalidAxis { .. } => HoduStatus::Shape,
+            HoduError::BackendError(_) | HoduError::CpuKernelError(_) => HoduStatus::Backend,
+            HoduError::IoError(_) | HoduError::FileNotFound(_) => HoduStatus::Io,
+            HoduError::InvalidArgument(_) => HoduStatus::InvalidArgument,
+            HoduError::SerializationFailed(_) | HoduError::DeserializationFailed(_) => HoduStatus::Format,
+            HoduError::UnsupportedOperation(_) => HoduStatus::Unsupported,
+            _ => HoduStatus::Internal,
+        };
+        Self::new(status, error.to_string())
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Run `f` at the API boundary, recording its error and catching panics
+pub(crate) fn guard(f: impl FnOnce() -> Result<(), Error>) -> HoduStatus {
+    let error = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return HoduStatus::Ok,
+        Ok(Err(error)) => error,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            Error::new(HoduStatus::Internal, format!("panic: {}", message))
+        },
+    };
+    let message = CString::new(error.message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    error.status
+}
+
+/// Message of the last failed call on this thread, or null if none failed
+pub(crate) fn last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Static description of `status`
+pub(crate) fn status_str(status: HoduStatus) -> *const c_char {
+    status.as_str().as_ptr()
+}
@@ -0,0 +1,90 @@
+//! C API for embedding hodu inference
+//!
+//! Exposes tensors, snapshots and the hdt/npy/json/hdss formats behind opaque handles so C, C++,
+//! Go and other languages with a C FFI can load a captured model and run it in-process. The
+//! matching header is checked in as `include/hodu.h`; run the build with `HODU_CAPI_WRITE_HEADER=1`
+//! to regenerate it after changing the exported functions.
+//!
+//! Fallible functions return a [`HoduStatus`]; on failure the message is available from
+//! [`hodu_last_error`] on the same thread until the next failing call. Handles returned through
+//! `out` parameters are owned by the caller and released with their `*_free` function.
+
+mod error;
+mod snapshot;
+mod tensor;
+
+pub use error::HoduStatus;
+pub use snapshot::*;
+pub use tensor::*;
+
+use error::Error;
+use std::ffi::{c_char, CStr};
+
+/// Version of the library as a NUL-terminated string
+#[no_mangle]
+pub extern "C" fn hodu_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Message of the last failed call on the calling thread, or null if no call has failed
+///
+/// The string stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn hodu_last_error() -> *const c_char {
+    error::last_error()
+}
+
+/// Static description of `status`
+#[no_mangle]
+pub extern "C" fn hodu_status_str(status: HoduStatus) -> *const c_char {
+    error::status_str(status)
+}
+
+/// Borrow a handle passed in by the caller
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a live `T`.
+pub(crate) unsafe fn handle<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, Error> {
+    ptr.as_ref().ok_or_else(|| Error::invalid(format!("{} is null", name)))
+}
+
+/// Borrow a NUL-terminated UTF-8 string passed in by the caller
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string.
+pub(crate) unsafe fn string<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, Error> {
+    if ptr.is_null() {
+        return Err(Error::invalid(format!("{} is null", name)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| Error::invalid(format!("{} is not valid UTF-8", name)))
+}
+
+/// Store a new handle in an `out` parameter
+///
+/// # Safety
+///
+/// `out` must be null or valid for a pointer write.
+pub(crate) unsafe fn put<T>(out: *mut *mut T, value: T) -> Result<(), Error> {
+    if out.is_null() {
+        return Err(Error::invalid("out is null"));
+    }
+    *out = Box::into_raw(Box::new(value));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_checked_in_header_is_current() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/hodu.h"));
+        let checked_in = include_str!("../include/hodu.h");
+        assert!(
+            generated == checked_in,
+            "include/hodu.h is out of date; rebuild with HODU_CAPI_WRITE_HEADER=1"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use crate::error::{guard, Error, HoduStatus};
+use crate::tensor::{slice, HoduTensor};
+use crate::{handle, put, string};
+use hodu_core::snapshot::{Interpreter, Snapshot};
+use hodu_core::tensor::Tensor;
+use hodu_core::types::Device;
+use std::ffi::{c_char, CString};
+
+/// A captured computation graph owned by the caller, released with [`hodu_snapshot_free`]
+pub struct HoduSnapshot {
+    snapshot: Snapshot,
+    inputs: Vec<CString>,
+    targets: Vec<CString>,
+}
+
+impl HoduSnapshot {
+    fn new(snapshot: Snapshot) -> Self {
+        let names = |names: Vec<&String>| {
+            names
+                .into_iter()
+                .map(|name| CString::new(name.replace('\0', " ")).unwrap_or_default())
+                .collect()
+        };
+        Self {
+            inputs: names(snapshot.inputs.iter().map(|input| &input.name).collect()),
+            targets: names(snapshot.targets.iter().map(|target| &target.name).collect()),
+            snapshot,
+        }
+    }
+}
+
+/// Load a snapshot from an .hdss file
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string and `out` valid for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_snapshot_load(path: *const c_char, out: *mut *mut HoduSnapshot) -> HoduStatus {
+    guard(|| {
+        let snapshot = Snapshot::load(string(path, "path")?)?;
+        put(out, HoduSnapshot::new(snapshot))
+    })
+}
+
+/// Load a snapshot from the `len` bytes of an .hdss file held in memory
+///
+/// # Safety
+///
+/// `data` must be valid for reading `len` bytes and `out` for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_snapshot_from_bytes(
+    data: *const u8,
+    len: usize,
+    out: *mut *mut HoduSnapshot,
+) -> HoduStatus {
+    guard(|| {
+        let snapshot = Snapshot::from_bytes(slice(data, len, "data")?)?;
+        put(out, HoduSnapshot::new(snapshot))
+    })
+}
+
+/// Save a snapshot to an .hdss file
+///
+/// # Safety
+///
+/// `snapshot` must be a live handle and `path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_snapshot_save(snapshot: *const HoduSnapshot, path: *const c_char) -> HoduStatus {
+    guard(|| {
+        let snapshot = handle(snapshot, "snapshot")?;
+        snapshot.snapshot.save(string(path, "path")?)?;
+        Ok(())
+    })
+}
+
+/// Release a snapshot; null is ignored
+///
+/// # Safety
+///
+/// `snapshot` must be null or a handle from this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_snapshot_free(snapshot: *mut HoduSnapshot) {
+    if !snapshot.is_null() {
+        drop(Box::from_raw(snapshot));
+    }
+}
+
+/// Number of inputs the snapshot declares, or 0 if it is null
+///
+/// # Safety
+///
+/// `snapshot` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_snapshot_num_inputs(snapshot: *const HoduSnapshot) -> usize {
+    snapshot.as_ref().map_or(0, |snapshot| snapshot.inputs.len())
+}
+
+/// Name of input `index`, or null if out of range
+///
+/// The string lives as long as the snapshot.
+///
+/// # Safety
+///
+/// `snapshot` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_snapshot_input_name(snapshot: *const HoduSnapshot, index: usize) -> *const c_char {
+    snapshot
+        .as_ref()
+        .and_then(|snapshot| snapshot.inputs.get(index))
+        .map_or(std::ptr::null(), |name| name.as_ptr())
+}
+
+/// Number of targets (outputs) the snapshot declares, or 0 if it is null
+///
+/// # Safety
+///
+/// `snapshot` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_snapshot_num_targets(snapshot: *const HoduSnapshot) -> usize {
+    snapshot.as_ref().map_or(0, |snapshot| snapshot.targets.len())
+}
+
+/// Name of target `index`, or null if out of range
+///
+/// The string lives as long as the snapshot.
+///
+/// # Safety
+///
+/// `snapshot` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_snapshot_target_name(snapshot: *const HoduSnapshot, index: usize) -> *const c_char {
+    snapshot
+        .as_ref()
+        .and_then(|snapshot| snapshot.targets.get(index))
+        .map_or(std::ptr::null(), |name| name.as_ptr())
+}
+
+/// Run the snapshot in-process on `device` ("cpu" when null)
+///
+/// Inputs are passed as `num_inputs` parallel names and tensors. `outputs` receives one new tensor
+/// per target, in target order, and must hold [`hodu_snapshot_num_targets`] entries; nothing is
+/// written on failure.
+///
+/// # Safety
+///
+/// `snapshot` must be a live handle, `input_names` and `inputs` valid for reading `num_inputs`
+/// entries, `device` null or a NUL-terminated string, and `outputs` valid for writing
+/// `num_outputs` pointers.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_snapshot_run(
+    snapshot: *const HoduSnapshot,
+    input_names: *const *const c_char,
+    inputs: *const *const HoduTensor,
+    num_inputs: usize,
+    device: *const c_char,
+    outputs: *mut *mut HoduTensor,
+    num_outputs: usize,
+) -> HoduStatus {
+    guard(|| {
+        let snapshot = handle(snapshot, "snapshot")?;
+        let device = if device.is_null() {
+            Device::CPU
+        } else {
+            string(device, "device")?.parse::<Device>()?
+        };
+        if num_outputs < snapshot.targets.len() {
+            return Err(Error::new(
+                HoduStatus::BufferTooSmall,
+                format!(
+                    "snapshot has {} targets but outputs holds {}",
+                    snapshot.targets.len(),
+                    num_outputs
+                ),
+            ));
+        }
+        if !snapshot.targets.is_empty() && outputs.is_null() {
+            return Err(Error::invalid("outputs is null"));
+        }
+
+        let names = slice(input_names, num_inputs, "input_names")?;
+        let tensors = slice(inputs, num_inputs, "inputs")?;
+        let mut named: Vec<(&str, &Tensor)> = Vec::with_capacity(num_inputs);
+        for (i, (&name, &tensor)) in names.iter().zip(tensors).enumerate() {
+            named.push((
+                string(name, &format!("input_names[{}]", i))?,
+                &handle(tensor, &format!("inputs[{}]", i))?.0,
+            ));
+        }
+
+        let mut results = Interpreter::new(&snapshot.snapshot).device(device).run(&named)?;
+        let mut ordered = Vec::with_capacity(snapshot.snapshot.targets.len());
+        for target in &snapshot.snapshot.targets {
+            let index = results
+                .iter()
+                .position(|(name, _)| *name == target.name)
+                .ok_or_else(|| {
+                    Error::new(
+                        HoduStatus::Internal,
+                        format!("target '{}' was not produced", target.name),
+                    )
+                })?;
+            ordered.push(results.swap_remove(index).1);
+        }
+        for (i, tensor) in ordered.into_iter().enumerate() {
+            *outputs.add(i) = Box::into_raw(Box::new(HoduTensor(tensor)));
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tensor::{hodu_tensor_copy_data, hodu_tensor_free, hodu_tensor_new, HODU_DTYPE_F32};
+    use hodu_core::snapshot::CaptureBoard;
+    use hodu_core::types::DType;
+    use std::ffi::c_void;
+    use std::ptr;
+
+    #[test]
+    fn test_snapshot_run() {
+        let board = CaptureBoard::with_name("capi");
+        board.open();
+        let x = Tensor::input("x", [2], DType::F32).unwrap();
+        let y = x.add(&x).unwrap();
+        board.with_target("y", y);
+        board.close();
+        let bytes = board.capture().to_bytes().unwrap();
+
+        let mut snapshot = ptr::null_mut();
+        unsafe {
+            assert_eq!(
+                hodu_snapshot_from_bytes(bytes.as_ptr(), bytes.len(), &mut snapshot),
+                HoduStatus::Ok
+            );
+            assert_eq!(hodu_snapshot_num_inputs(snapshot), 1);
+            assert_eq!(hodu_snapshot_num_targets(snapshot), 1);
+            assert!(hodu_snapshot_target_name(snapshot, 1).is_null());
+
+            let data = [1.5f32, -2.0];
+            let shape = [2usize];
+            let mut x = ptr::null_mut();
+            let status = hodu_tensor_new(
+                data.as_ptr() as *const c_void,
+                8,
+                shape.as_ptr(),
+                1,
+                HODU_DTYPE_F32,
+                &mut x,
+            );
+            assert_eq!(status, HoduStatus::Ok);
+
+            let names = [hodu_snapshot_input_name(snapshot, 0)];
+            let inputs = [x as *const HoduTensor];
+            let mut outputs = [ptr::null_mut(); 1];
+            let status = hodu_snapshot_run(
+                snapshot,
+                names.as_ptr(),
+                inputs.as_ptr(),
+                1,
+                ptr::null(),
+                outputs.as_mut_ptr(),
+                1,
+            );
+            assert_eq!(status, HoduStatus::Ok);
+
+            let mut result = [0f32; 2];
+            let status = hodu_tensor_copy_data(outputs[0], result.as_mut_ptr() as *mut c_void, 8);
+            assert_eq!(status, HoduStatus::Ok);
+            assert_eq!(result, [3.0, -4.0]);
+
+            hodu_tensor_free(outputs[0]);
+            hodu_tensor_free(x);
+            hodu_snapshot_free(snapshot);
+        }
+    }
+}
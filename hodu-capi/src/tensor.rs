@@ -0,0 +1,322 @@
+use crate::error::{guard, Error, HoduStatus};
+use crate::{handle, put, string};
+use hodu_core::format::{hdt, json, npy};
+use hodu_core::tensor::Tensor;
+use hodu_core::types::{DType, Device};
+use std::ffi::{c_char, c_void};
+use std::path::Path;
+
+/// A tensor owned by the caller, released with [`hodu_tensor_free`]
+pub struct HoduTensor(pub(crate) Tensor);
+
+/// Element type of a tensor, one of the `HODU_DTYPE_*` constants
+pub type HoduDType = u32;
+
+pub const HODU_DTYPE_BOOL: HoduDType = 0;
+pub const HODU_DTYPE_F8E4M3: HoduDType = 1;
+pub const HODU_DTYPE_F8E5M2: HoduDType = 2;
+pub const HODU_DTYPE_BF16: HoduDType = 3;
+pub const HODU_DTYPE_F16: HoduDType = 4;
+pub const HODU_DTYPE_F32: HoduDType = 5;
+pub const HODU_DTYPE_F64: HoduDType = 6;
+pub const HODU_DTYPE_U8: HoduDType = 7;
+pub const HODU_DTYPE_U16: HoduDType = 8;
+pub const HODU_DTYPE_U32: HoduDType = 9;
+pub const HODU_DTYPE_U64: HoduDType = 10;
+pub const HODU_DTYPE_I8: HoduDType = 11;
+pub const HODU_DTYPE_I16: HoduDType = 12;
+pub const HODU_DTYPE_I32: HoduDType = 13;
+pub const HODU_DTYPE_I64: HoduDType = 14;
+
+pub(crate) fn dtype_from_raw(dtype: HoduDType) -> Result<DType, Error> {
+    Ok(match dtype {
+        HODU_DTYPE_BOOL => DType::BOOL,
+        HODU_DTYPE_F8E4M3 => DType::F8E4M3,
+        HODU_DTYPE_F8E5M2 => DType::F8E5M2,
+        HODU_DTYPE_BF16 => DType::BF16,
+        HODU_DTYPE_F16 => DType::F16,
+        HODU_DTYPE_F32 => DType::F32,
+        HODU_DTYPE_F64 => DType::F64,
+        HODU_DTYPE_U8 => DType::U8,
+        HODU_DTYPE_U16 => DType::U16,
+        HODU_DTYPE_U32 => DType::U32,
+        HODU_DTYPE_U64 => DType::U64,
+        HODU_DTYPE_I8 => DType::I8,
+        HODU_DTYPE_I16 => DType::I16,
+        HODU_DTYPE_I32 => DType::I32,
+        HODU_DTYPE_I64 => DType::I64,
+        _ => return Err(Error::invalid(format!("unknown dtype {}", dtype))),
+    })
+}
+
+/// Borrow `len` elements passed in by the caller, allowing null when empty
+///
+/// # Safety
+///
+/// `ptr` must be null or valid for reading `len` elements.
+pub(crate) unsafe fn slice<'a, T>(ptr: *const T, len: usize, name: &str) -> Result<&'a [T], Error> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(Error::invalid(format!("{} is null", name))),
+        (false, _) => Ok(std::slice::from_raw_parts(ptr, len)),
+    }
+}
+
+/// Create a CPU tensor by copying `nbytes` bytes of little-endian, row-major element data
+///
+/// `nbytes` must equal the element count of `shape` times the size of `dtype`. A scalar has
+/// `ndim` 0, in which case `shape` may be null.
+///
+/// # Safety
+///
+/// `data` must be valid for reading `nbytes` bytes, `shape` for reading `ndim` values, and
+/// `out` for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_tensor_new(
+    data: *const c_void,
+    nbytes: usize,
+    shape: *const usize,
+    ndim: usize,
+    dtype: HoduDType,
+    out: *mut *mut HoduTensor,
+) -> HoduStatus {
+    guard(|| {
+        let data = slice(data as *const u8, nbytes, "data")?;
+        let shape = slice(shape, ndim, "shape")?.to_vec();
+        let tensor = Tensor::from_bytes(data, shape, dtype_from_raw(dtype)?, Device::CPU)?;
+        put(out, HoduTensor(tensor))
+    })
+}
+
+/// Release a tensor; null is ignored
+///
+/// # Safety
+///
+/// `tensor` must be null or a handle from this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_tensor_free(tensor: *mut HoduTensor) {
+    if !tensor.is_null() {
+        drop(Box::from_raw(tensor));
+    }
+}
+
+/// Element type of `tensor`, or `HODU_DTYPE_F32` if it is null
+///
+/// # Safety
+///
+/// `tensor` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_tensor_dtype(tensor: *const HoduTensor) -> HoduDType {
+    tensor
+        .as_ref()
+        .map_or(HODU_DTYPE_F32, |tensor| tensor.0.dtype() as u8 as HoduDType)
+}
+
+/// Number of dimensions of `tensor`, or 0 if it is null
+///
+/// # Safety
+///
+/// `tensor` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_tensor_ndim(tensor: *const HoduTensor) -> usize {
+    tensor.as_ref().map_or(0, |tensor| tensor.0.ndim())
+}
+
+/// Number of elements in `tensor`, or 0 if it is null
+///
+/// # Safety
+///
+/// `tensor` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_tensor_numel(tensor: *const HoduTensor) -> usize {
+    tensor.as_ref().map_or(0, |tensor| tensor.0.size())
+}
+
+/// Size in bytes of the data [`hodu_tensor_copy_data`] writes, or 0 if `tensor` is null
+///
+/// # Safety
+///
+/// `tensor` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_tensor_nbytes(tensor: *const HoduTensor) -> usize {
+    tensor
+        .as_ref()
+        .map_or(0, |tensor| tensor.0.size() * tensor.0.dtype().size_in_bytes())
+}
+
+/// Copy the dimensions of `tensor` into `dims`, which holds `capacity` values
+///
+/// # Safety
+///
+/// `tensor` must be a live handle and `dims` valid for writing `capacity` values.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_tensor_shape(tensor: *const HoduTensor, dims: *mut usize, capacity: usize) -> HoduStatus {
+    guard(|| {
+        let shape = handle(tensor, "tensor")?.0.shape();
+        let shape = shape.dims();
+        if shape.len() > capacity {
+            return Err(Error::new(
+                HoduStatus::BufferTooSmall,
+                format!("shape has {} dimensions but dims holds {}", shape.len(), capacity),
+            ));
+        }
+        if !shape.is_empty() {
+            if dims.is_null() {
+                return Err(Error::invalid("dims is null"));
+            }
+            std::ptr::copy_nonoverlapping(shape.as_ptr(), dims, shape.len());
+        }
+        Ok(())
+    })
+}
+
+/// Copy the little-endian, row-major data of `tensor` into `out`, which holds `nbytes` bytes
+///
+/// `nbytes` must be at least [`hodu_tensor_nbytes`]. Tensors on other devices are copied back
+/// to the host.
+///
+/// # Safety
+///
+/// `tensor` must be a live handle and `out` valid for writing `nbytes` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_tensor_copy_data(
+    tensor: *const HoduTensor,
+    out: *mut c_void,
+    nbytes: usize,
+) -> HoduStatus {
+    guard(|| {
+        let bytes = handle(tensor, "tensor")?.0.to_bytes()?;
+        if bytes.len() > nbytes {
+            return Err(Error::new(
+                HoduStatus::BufferTooSmall,
+                format!("tensor data is {} bytes but out holds {}", bytes.len(), nbytes),
+            ));
+        }
+        if !bytes.is_empty() {
+            if out.is_null() {
+                return Err(Error::invalid("out is null"));
+            }
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), out as *mut u8, bytes.len());
+        }
+        Ok(())
+    })
+}
+
+/// Load a tensor from an .hdt, .npy or .json file, picked by extension
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string and `out` valid for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_tensor_load(path: *const c_char, out: *mut *mut HoduTensor) -> HoduStatus {
+    guard(|| {
+        let path = Path::new(string(path, "path")?);
+        let tensor = match extension(path)?.as_str() {
+            "hdt" => hdt::load(path)?,
+            "npy" => npy::load(path)?,
+            _ => json::load(path)?,
+        };
+        put(out, HoduTensor(tensor))
+    })
+}
+
+/// Save a tensor to an .hdt, .npy or .json file, picked by extension
+///
+/// # Safety
+///
+/// `tensor` must be a live handle and `path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hodu_tensor_save(tensor: *const HoduTensor, path: *const c_char) -> HoduStatus {
+    guard(|| {
+        let tensor = &handle(tensor, "tensor")?.0;
+        let path = Path::new(string(path, "path")?);
+        match extension(path)?.as_str() {
+            "hdt" => hdt::save(tensor, path)?,
+            "npy" => npy::save(tensor, path)?,
+            _ => json::save(tensor, path)?,
+        }
+        Ok(())
+    })
+}
+
+fn extension(path: &Path) -> Result<String, Error> {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "hdt" | "npy" | "json" => Ok(ext),
+        _ => Err(Error::new(
+            HoduStatus::Unsupported,
+            format!(
+                "unsupported tensor format '{}', expected .hdt, .npy or .json",
+                path.display()
+            ),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_tensor_round_trip() {
+        let data = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let shape = [2usize, 3];
+        let mut tensor = ptr::null_mut();
+        unsafe {
+            let status = hodu_tensor_new(
+                data.as_ptr() as *const c_void,
+                size_of_val(&data),
+                shape.as_ptr(),
+                shape.len(),
+                HODU_DTYPE_F32,
+                &mut tensor,
+            );
+            assert_eq!(status, HoduStatus::Ok);
+            assert_eq!(hodu_tensor_dtype(tensor), HODU_DTYPE_F32);
+            assert_eq!(hodu_tensor_numel(tensor), 6);
+            assert_eq!(hodu_tensor_nbytes(tensor), 24);
+
+            let mut dims = [0usize; 1];
+            assert_eq!(
+                hodu_tensor_shape(tensor, dims.as_mut_ptr(), 1),
+                HoduStatus::BufferTooSmall
+            );
+            let mut dims = [0usize; 2];
+            assert_eq!(hodu_tensor_shape(tensor, dims.as_mut_ptr(), 2), HoduStatus::Ok);
+            assert_eq!(dims, shape);
+
+            let mut copy = [0f32; 6];
+            let status = hodu_tensor_copy_data(tensor, copy.as_mut_ptr() as *mut c_void, size_of_val(&copy));
+            assert_eq!(status, HoduStatus::Ok);
+            assert_eq!(copy, data);
+            hodu_tensor_free(tensor);
+        }
+    }
+
+    #[test]
+    fn test_tensor_errors() {
+        let data = [1.0f32, 2.0];
+        let shape = [3usize];
+        let mut tensor = ptr::null_mut();
+        unsafe {
+            let status = hodu_tensor_new(data.as_ptr() as *const c_void, 8, shape.as_ptr(), 1, 99, &mut tensor);
+            assert_eq!(status, HoduStatus::InvalidArgument);
+            let status = hodu_tensor_new(
+                data.as_ptr() as *const c_void,
+                8,
+                shape.as_ptr(),
+                1,
+                HODU_DTYPE_F32,
+                &mut tensor,
+            );
+            assert_ne!(status, HoduStatus::Ok);
+            assert!(tensor.is_null());
+            assert!(!crate::hodu_last_error().is_null());
+        }
+    }
+}
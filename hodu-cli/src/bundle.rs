@@ -0,0 +1,296 @@
+//! Single-file model bundles (`.hpk`)
+//!
+//! A bundle is a tar archive whose first entry, `bundle.json`, is the manifest:
+//!
+//! ```json
+//! {
+//!   "format_version": 1,
+//!   "model": "resnet50.onnx",
+//!   "snapshot": "model.hdss",
+//!   "files": [{ "path": "model.hdss", "size": 1024, "sha256": "..." }],
+//!   "plugins": [{ "name": "hodu-backend-aot-cpu", "version": "^0.3.1" }],
+//!   "run": { "device": "cpu", "backend": "hodu-backend-aot-cpu" }
+//! }
+//! ```
+//!
+//! The snapshot and, for a sharded snapshot, its weight manifest and shards follow, each listed in
+//! `files` with its size and SHA256. Unpacking checks every file against the manifest and rejects
+//! files it doesn't list, so a truncated or altered bundle fails before it runs. To check who made
+//! a bundle, sign it with `hodu plugin sign`.
+
+use crate::errors::{self, ErrorClass};
+use crate::plugins::PluginRegistry;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tempfile::TempDir;
+
+/// File extension of bundles
+pub const BUNDLE_EXTENSION: &str = "hpk";
+
+/// Name of the manifest, the first entry of a bundle
+const MANIFEST_NAME: &str = "bundle.json";
+
+/// Version of the bundle format written by this build
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Manifest of a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    /// Name of the packed model
+    pub model: String,
+    /// Path of the snapshot in the bundle
+    pub snapshot: String,
+    /// Every file in the bundle besides the manifest
+    #[serde(default)]
+    pub files: Vec<BundleFile>,
+    /// Plugins the model needs to run
+    #[serde(default)]
+    pub plugins: Vec<PluginRequirement>,
+    /// Options `hodu run` uses when they are not given on the command line
+    #[serde(default)]
+    pub run: RunDefaults,
+    /// When the bundle was packed (RFC 3339)
+    pub created: String,
+}
+
+/// A file in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// A plugin a bundle needs, with a semver requirement on its version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginRequirement {
+    pub name: String,
+    pub version: String,
+}
+
+/// Default run options stored in a bundle
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_tf32: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub f16_accumulate: bool,
+    /// Seconds a plugin may take to answer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Plugin config overrides (`PLUGIN.KEY=VALUE`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugin_config: Vec<String>,
+}
+
+/// Whether `path` names a bundle
+pub fn is_bundle(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(BUNDLE_EXTENSION))
+}
+
+/// Write a bundle of `files` (path in the bundle, file on disk) to `path`
+///
+/// `manifest.files` is filled in from `files`. The bundle is written next to `path` and renamed
+/// into place, so an interrupted pack leaves no partial bundle behind.
+pub fn write_bundle(
+    path: &Path,
+    manifest: &mut BundleManifest,
+    files: &[(String, PathBuf)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    manifest.files.clear();
+    for (name, source) in files {
+        check_entry_path(name)?;
+        let (size, sha256) = hash_file(source)?;
+        manifest.files.push(BundleFile {
+            path: name.clone(),
+            size,
+            sha256,
+        });
+    }
+
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let temp = tempfile::Builder::new()
+        .prefix(".hodu_pack_")
+        .tempfile_in(dir)
+        .map_err(|e| format!("Failed to create a temp file in {}: {}", dir.display(), e))?;
+    let mut builder = tar::Builder::new(std::io::BufWriter::new(temp.as_file()));
+    let json = serde_json::to_vec_pretty(manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, MANIFEST_NAME, json.as_slice())?;
+    for (name, source) in files {
+        let mut file =
+            std::fs::File::open(source).map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+        builder.append_file(name, &mut file)?;
+    }
+    builder.into_inner()?.flush()?;
+    temp.persist(path)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e.error))?;
+    Ok(())
+}
+
+/// A bundle unpacked to a temporary directory, removed when dropped
+pub struct UnpackedBundle {
+    dir: TempDir,
+    manifest: BundleManifest,
+}
+
+impl UnpackedBundle {
+    /// Unpack the bundle at `path`, checking every file against its manifest
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let corrupt = |message: String| errors::classified(ErrorClass::Io, format!("{}: {}", path.display(), message));
+        let file = std::fs::File::open(path)
+            .map_err(|e| errors::classified(ErrorClass::Io, format!("Failed to open {}: {}", path.display(), e)))?;
+        let mut archive = tar::Archive::new(std::io::BufReader::new(file));
+        let mut entries = archive.entries().map_err(|e| corrupt(e.to_string()))?;
+
+        let mut first = entries
+            .next()
+            .ok_or_else(|| corrupt("empty bundle".to_string()))?
+            .map_err(|e| corrupt(e.to_string()))?;
+        if first.path().map_err(|e| corrupt(e.to_string()))?.as_ref() != Path::new(MANIFEST_NAME) {
+            return Err(corrupt(format!("not a bundle (no {} first)", MANIFEST_NAME)));
+        }
+        let mut json = Vec::new();
+        first.read_to_end(&mut json).map_err(|e| corrupt(e.to_string()))?;
+        let manifest: BundleManifest =
+            serde_json::from_slice(&json).map_err(|e| corrupt(format!("invalid {}: {}", MANIFEST_NAME, e)))?;
+        if manifest.format_version > FORMAT_VERSION {
+            return Err(corrupt(format!(
+                "bundle format {} is newer than this hodu supports ({}); update hodu",
+                manifest.format_version, FORMAT_VERSION
+            )));
+        }
+
+        let dir = tempfile::Builder::new()
+            .prefix("hodu_bundle_")
+            .tempdir()
+            .map_err(|e| format!("Failed to create temp directory for bundle: {}", e))?;
+        let mut unpacked: Vec<&str> = Vec::new();
+        for entry in entries {
+            let mut entry = entry.map_err(|e| corrupt(e.to_string()))?;
+            let name = entry
+                .path()
+                .map_err(|e| corrupt(e.to_string()))?
+                .to_string_lossy()
+                .into_owned();
+            let expected = manifest
+                .files
+                .iter()
+                .find(|file| file.path == name)
+                .ok_or_else(|| corrupt(format!("{} is not listed in the manifest", name)))?;
+            check_entry_path(&name).map_err(|e| corrupt(e.to_string()))?;
+
+            let dest = dir.path().join(&name);
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut out = std::fs::File::create(&dest)?;
+            let mut hasher = Sha256::new();
+            let mut buffer = vec![0u8; 1 << 16];
+            let mut size = 0u64;
+            loop {
+                let read = entry.read(&mut buffer).map_err(|e| corrupt(e.to_string()))?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+                out.write_all(&buffer[..read])?;
+                size += read as u64;
+            }
+            if size != expected.size || hex::encode(hasher.finalize()) != expected.sha256 {
+                return Err(corrupt(format!("{} does not match its checksum", name)));
+            }
+            unpacked.push(&expected.path);
+        }
+
+        if let Some(missing) = manifest
+            .files
+            .iter()
+            .find(|file| !unpacked.contains(&file.path.as_str()))
+        {
+            return Err(corrupt(format!("{} is missing", missing.path)));
+        }
+        if !manifest.files.iter().any(|file| file.path == manifest.snapshot) {
+            return Err(corrupt(format!("snapshot {} is missing", manifest.snapshot)));
+        }
+        Ok(Self { dir, manifest })
+    }
+
+    pub fn manifest(&self) -> &BundleManifest {
+        &self.manifest
+    }
+
+    /// Path of the unpacked snapshot
+    pub fn snapshot_path(&self) -> PathBuf {
+        self.dir.path().join(&self.manifest.snapshot)
+    }
+
+    /// Check that every plugin the bundle needs is installed in a matching version
+    pub fn check_plugins(&self, registry: &PluginRegistry) -> Result<(), Box<dyn std::error::Error>> {
+        for required in &self.manifest.plugins {
+            let install_hint = format!(
+                "install it with `hodu plugin install {}@{}`",
+                required.name, required.version
+            );
+            let entry = registry.find(&required.name).ok_or_else(|| {
+                errors::classified(
+                    ErrorClass::Plugin,
+                    format!(
+                        "The bundle needs plugin '{}' {}; {}",
+                        required.name, required.version, install_hint
+                    ),
+                )
+            })?;
+            let requirement = VersionReq::parse(&required.version).map_err(|e| {
+                format!(
+                    "Invalid version requirement for '{}' in the bundle: {}",
+                    required.name, e
+                )
+            })?;
+            let matches = Version::parse(&entry.version).is_ok_and(|version| requirement.matches(&version));
+            if !matches {
+                return Err(errors::classified(
+                    ErrorClass::Plugin,
+                    format!(
+                        "The bundle needs plugin '{}' {}, but {} is installed; {}",
+                        required.name, required.version, entry.version, install_hint
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reject bundle paths that could land outside the unpack directory
+fn check_entry_path(name: &str) -> Result<(), String> {
+    let path = Path::new(name);
+    if name == MANIFEST_NAME || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("Invalid path in bundle: {}", name));
+    }
+    Ok(())
+}
+
+/// Size and SHA256 of the file at `path`
+fn hash_file(path: &Path) -> Result<(u64, String), Box<dyn std::error::Error>> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut file, &mut hasher)?;
+    Ok((size, hex::encode(hasher.finalize())))
+}
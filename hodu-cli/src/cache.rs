@@ -0,0 +1,278 @@
+//! Content-addressed cache of build artifacts
+//!
+//! An artifact is stored under `<cache_dir>/<plugin>/<key>[.<ext>]`, where the key is a SHA256
+//! hash of everything that changes what the backend produces: the snapshot and its weight shards,
+//! the [`BuildTarget`], the plugin's name and version, the output format and the plugin's config.
+//! `hodu build` and `hodu run` look the key up before asking the plugin to build, and a
+//! `<key>.json` file next to each artifact records what it was built from for `hodu cache ls`.
+
+use crate::config::Settings;
+use crate::output;
+use fs2::FileExt;
+use hodu_core::format::hdss::ShardedWeights;
+use hodu_plugin::BuildTarget;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Largest snapshot hashed for a key, unless `HODU_MAX_SNAPSHOT_SIZE` (bytes) says otherwise
+const DEFAULT_MAX_SNAPSHOT_SIZE: u64 = 10 * 1024 * 1024 * 1024;
+
+/// What an artifact is built from, besides the snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactSpec {
+    /// Backend plugin name
+    pub plugin: String,
+    /// Backend plugin version, from `initialize`
+    pub plugin_version: String,
+    /// Target triple
+    pub triple: String,
+    /// Target device
+    pub device: String,
+    /// Output format (`sharedlib`, `executable`, ...)
+    pub format: String,
+    /// Other options that change the artifact, such as the plugin's config
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub options: serde_json::Value,
+}
+
+impl ArtifactSpec {
+    pub fn new(plugin: &str, plugin_version: &str, target: &BuildTarget, format: &str) -> Self {
+        Self {
+            plugin: plugin.to_string(),
+            plugin_version: plugin_version.to_string(),
+            triple: target.triple.clone(),
+            device: target.device.clone(),
+            format: format.to_string(),
+            options: serde_json::Value::Null,
+        }
+    }
+
+    /// Add options that change the artifact
+    pub fn with_options(mut self, options: serde_json::Value) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+/// A cached artifact, as recorded next to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// Hash the artifact is stored under
+    pub key: String,
+    /// Artifact file name in the plugin's cache directory
+    pub file: String,
+    /// Name of the model it was built from
+    pub model: String,
+    #[serde(flatten)]
+    pub spec: ArtifactSpec,
+    /// When it was built (RFC 3339)
+    pub created: String,
+    /// Artifact file
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// Artifact size in bytes
+    #[serde(skip)]
+    pub size: u64,
+}
+
+/// Hash the snapshot at `snapshot_path`, its weight shards and `spec` into a cache key
+pub fn artifact_key(
+    snapshot_path: &Path,
+    weights: Option<&ShardedWeights>,
+    spec: &ArtifactSpec,
+) -> Result<String, Box<dyn std::error::Error>> {
+    // Use a single open file handle to avoid TOCTOU race conditions
+    let max_snapshot_size = std::env::var("HODU_MAX_SNAPSHOT_SIZE")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MAX_SNAPSHOT_SIZE);
+    let snapshot_file = std::fs::File::open(snapshot_path).map_err(|e| format!("Failed to open snapshot: {}", e))?;
+    let snapshot_size = snapshot_file
+        .metadata()
+        .map_err(|e| format!("Failed to read snapshot metadata: {}", e))?
+        .len();
+    if snapshot_size > max_snapshot_size {
+        return Err(format!(
+            "Snapshot file too large: {} bytes (max: {} bytes, set HODU_MAX_SNAPSHOT_SIZE to override)",
+            snapshot_size, max_snapshot_size
+        )
+        .into());
+    }
+
+    let mut hasher = Sha256::new();
+    std::io::copy(
+        &mut std::io::BufReader::new(snapshot_file).take(snapshot_size),
+        &mut hasher,
+    )
+    .map_err(|e| format!("Failed to read snapshot: {}", e))?;
+    // Shards of the snapshot are read by the plugin, so they are part of the key too
+    if let Some(weights) = weights {
+        for shard in weights.shard_paths() {
+            let mut file = std::fs::File::open(&shard)
+                .map_err(|e| format!("Failed to open weight shard '{}': {}", shard.display(), e))?;
+            std::io::copy(&mut file, &mut hasher)
+                .map_err(|e| format!("Failed to read weight shard '{}': {}", shard.display(), e))?;
+        }
+    }
+    hasher.update(serde_json::to_vec(spec)?);
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Cached artifacts in the configured cache directory
+pub struct ArtifactCache {
+    dir: PathBuf,
+}
+
+impl ArtifactCache {
+    /// Open the cache in `cache_dir` from ~/.hodu/config.toml
+    pub fn open() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::at(Settings::load()?.cache_dir()))
+    }
+
+    /// Open the cache in `dir`
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory of the artifacts built by `plugin`
+    pub fn plugin_dir(&self, plugin: &str) -> PathBuf {
+        self.dir.join(plugin)
+    }
+
+    /// Path of the artifact stored under `key`, with the file extension `extension` (if any)
+    fn artifact_path(&self, plugin: &str, key: &str, extension: &str) -> PathBuf {
+        let name = match extension {
+            "" => key.to_string(),
+            extension => format!("{}.{}", key, extension),
+        };
+        self.plugin_dir(plugin).join(name)
+    }
+
+    /// Return the artifact for `key`, calling `build` with its path first if it isn't cached
+    ///
+    /// A file lock keeps concurrent commands from building the same artifact twice. Returns the
+    /// artifact's path and whether it was already cached.
+    pub fn get_or_build(
+        &self,
+        key: &str,
+        extension: &str,
+        model: &str,
+        spec: &ArtifactSpec,
+        build: impl FnOnce(&Path) -> Result<(), Box<dyn std::error::Error>>,
+    ) -> Result<(PathBuf, bool), Box<dyn std::error::Error>> {
+        let dir = self.plugin_dir(&spec.plugin);
+        std::fs::create_dir_all(&dir)?;
+        let path = self.artifact_path(&spec.plugin, key, extension);
+        let entry_path = dir.join(format!("{}.json", key));
+
+        let lock_path = dir.join(format!("{}.lock", key));
+        let lock_file = std::fs::File::create(&lock_path)?;
+        lock_file.lock_exclusive()?;
+        // Check inside lock to prevent TOCTOU race; an artifact without its entry was interrupted
+        let hit = path.exists() && entry_path.exists();
+        let result = if hit {
+            Ok(())
+        } else {
+            build(&path).and_then(|()| {
+                let entry = CacheEntry {
+                    key: key.to_string(),
+                    file: path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    model: model.to_string(),
+                    spec: spec.clone(),
+                    created: chrono::Utc::now().to_rfc3339(),
+                    path: path.clone(),
+                    size: 0,
+                };
+                std::fs::write(&entry_path, serde_json::to_string_pretty(&entry)?)?;
+                Ok(())
+            })
+        };
+        lock_file.unlock()?;
+        // Clean up lock file (best effort)
+        let _ = std::fs::remove_file(&lock_path);
+        result.map(|()| (path, hit))
+    }
+
+    /// Remove the artifact stored under `key` and its entry, if they are cached
+    pub fn remove(&self, plugin: &str, key: &str, extension: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let entry_path = self.plugin_dir(plugin).join(format!("{}.json", key));
+        for path in [entry_path, self.artifact_path(plugin, key, extension)] {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {},
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+                Err(e) => return Err(format!("Failed to remove {}: {}", path.display(), e).into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Every cached artifact, newest first
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, Box<dyn std::error::Error>> {
+        let mut entries = Vec::new();
+        let plugin_dirs = match std::fs::read_dir(&self.dir) {
+            Ok(dirs) => dirs,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(entries),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.dir.display(), e).into()),
+        };
+        for plugin_dir in plugin_dirs.filter_map(Result::ok).map(|entry| entry.path()) {
+            if !plugin_dir.is_dir() {
+                continue;
+            }
+            for file in std::fs::read_dir(&plugin_dir)?
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+            {
+                if file.extension().and_then(|e| e.to_str()) != Some("json") {
+                    continue;
+                }
+                let entry = std::fs::read_to_string(&file)
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<CacheEntry>(&content).ok());
+                let Some(mut entry) = entry else {
+                    output::warning(&format!("Skipping unreadable cache entry {}", file.display()));
+                    continue;
+                };
+                let artifact = plugin_dir.join(&entry.file);
+                if !artifact.is_file() {
+                    continue;
+                }
+                entry.size = std::fs::metadata(&artifact)?.len();
+                entry.path = artifact;
+                entries.push(entry);
+            }
+        }
+        entries.sort_by(|a, b| b.created.cmp(&a.created));
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_keeps_other_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArtifactCache::at(dir.path());
+        let spec = ArtifactSpec::new("aot-cpu", "0.1.0", &BuildTarget::host("cpu"), "sharedlib");
+        for key in ["a", "b"] {
+            cache
+                .get_or_build(key, "so", key, &spec, |path| Ok(std::fs::write(path, key)?))
+                .unwrap();
+        }
+
+        cache.remove("aot-cpu", "a", "so").unwrap();
+        cache.remove("aot-cpu", "missing", "so").unwrap();
+        let keys: Vec<String> = cache.entries().unwrap().into_iter().map(|entry| entry.key).collect();
+        assert_eq!(keys, ["b"]);
+        let (_, hit) = cache
+            .get_or_build("a", "so", "a", &spec, |path| Ok(std::fs::write(path, "a")?))
+            .unwrap();
+        assert!(!hit);
+    }
+}
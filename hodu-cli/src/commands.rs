@@ -1,10 +1,21 @@
+pub mod bench;
 pub mod build;
+pub mod cache;
 pub mod clean;
+pub mod compare_backends;
 pub mod completions;
+pub mod config;
 pub mod convert;
+pub mod diff;
 pub mod doctor;
 pub mod inspect;
+pub mod model;
+pub mod pack;
 pub mod plugin;
+pub mod quantize;
+pub mod repl;
 pub mod run;
+pub mod serve;
 pub mod setup;
+pub mod trace;
 pub mod version;
@@ -0,0 +1,510 @@
+//! Bench command - time repeated runs of a model on a backend plugin or the interpreter
+//!
+//! Backends implementing `backend.benchmark` run the timing loop themselves, so the latencies
+//! exclude RPC overhead and include their peak memory. Other backends are timed from the CLI
+//! with one `backend.run` per iteration. With `--interpreter` the model runs on the reference
+//! interpreter in the CLI process.
+//!
+//! Reports are printed as a table, JSON or CSV. With `--output`, a JSON report replaces the file
+//! while a CSV row is appended to it, so CI jobs can keep one file of results across runs.
+
+use super::run::{
+    compile_cached, find_backend_plugin, find_model_format_plugin, load_model_snapshot, parse_device, parse_inputs,
+    plugin_supports, run_in_process, save_inputs,
+};
+use crate::config::Settings;
+use crate::errors::{self, ErrorClass};
+use crate::output;
+use crate::plugins::{load_registry, PluginClient, PluginManager, PluginRegistry};
+use crate::utils::path_to_str;
+use clap::Args;
+use hodu_core::format::hdss::{self, ShardedWeights};
+use hodu_core::snapshot::Snapshot;
+use hodu_plugin::memory::{peak_memory, reset_peak_memory};
+use hodu_plugin::rpc::{methods, BenchmarkParams, BenchmarkResult, PrecisionParams, MAX_BENCHMARK_ITERATIONS};
+use hodu_plugin::TensorData;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Percentiles reported for the latency
+const PERCENTILES: [f64; 4] = [50.0, 90.0, 95.0, 99.0];
+
+/// Report formats accepted by `--format`
+const FORMATS: [&str; 3] = ["pretty", "json", "csv"];
+
+/// Name reported as the backend of `--interpreter` runs
+const INTERPRETER: &str = "interpreter";
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Model file (.onnx, .hdss, etc.)
+    pub model: PathBuf,
+
+    /// Input tensor (name=path, or name=table.csv#col,col to pick columns), can be repeated
+    #[arg(short, long = "input", value_name = "NAME=PATH")]
+    pub input: Vec<String>,
+
+    /// Input tensors (comma-separated: a=path,b=path)
+    #[arg(long = "inputs", value_name = "INPUTS", value_delimiter = ',')]
+    pub inputs: Vec<String>,
+
+    /// Execution device (cpu, metal, cuda::0); default: `device` in ~/.hodu/config.toml, else cpu
+    #[arg(short, long)]
+    pub device: Option<String>,
+
+    /// Backend plugin to use (default: `backend` in ~/.hodu/config.toml, else auto-select)
+    #[arg(long)]
+    pub backend: Option<String>,
+
+    /// Run on the built-in reference interpreter instead of a backend plugin (cpu only)
+    #[arg(long, conflicts_with = "backend")]
+    pub interpreter: bool,
+
+    /// Number of timed iterations
+    #[arg(short = 'n', long, default_value_t = 100)]
+    pub iterations: u32,
+
+    /// Number of untimed iterations run first
+    #[arg(long, default_value_t = 10)]
+    pub warmup: u32,
+
+    /// Output format (pretty, json, csv)
+    #[arg(short, long, default_value = "pretty")]
+    pub format: String,
+
+    /// Write the report to a file instead of stdout (json replaces it, csv appends a row)
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Timeout in seconds for plugin operations, covering the whole benchmark (default: 300)
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Override a plugin config value from ~/.hodu/config.toml, can be repeated
+    #[arg(long = "plugin-config", value_name = "PLUGIN.KEY=VALUE")]
+    pub plugin_config: Vec<String>,
+
+    /// Let f32 matmul and convolution run on TF32 tensor cores
+    #[arg(long)]
+    pub allow_tf32: bool,
+
+    /// Let f16/bf16 matmul and convolution accumulate in 16 bits instead of f32
+    #[arg(long)]
+    pub f16_accumulate: bool,
+}
+
+pub fn execute(mut args: BenchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if output::is_json() {
+        args.format = "json".to_string();
+    }
+    if args.iterations == 0 || args.iterations > MAX_BENCHMARK_ITERATIONS {
+        return Err(format!(
+            "Iterations must be between 1 and {} (got: {})",
+            MAX_BENCHMARK_ITERATIONS, args.iterations
+        )
+        .into());
+    }
+    if args.warmup > MAX_BENCHMARK_ITERATIONS {
+        return Err(format!(
+            "Warmup must be at most {} iterations (got: {})",
+            MAX_BENCHMARK_ITERATIONS, args.warmup
+        )
+        .into());
+    }
+    if !FORMATS.contains(&args.format.as_str()) {
+        return Err(format!(
+            "Unknown output format: {} (expected one of: {})",
+            args.format,
+            FORMATS.join(", ")
+        )
+        .into());
+    }
+    if args.output.is_some() && args.format == "pretty" {
+        return Err("--output needs --format json or csv".into());
+    }
+
+    let extension = args
+        .model
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    let registry = load_registry()?;
+    let format_plugin = find_model_format_plugin(extension.as_deref(), &registry)?;
+    let settings = Settings::load()?;
+    let device = parse_device(&settings.device(args.device.as_deref()))?;
+    let backend_plugin = if args.interpreter {
+        if device != "cpu" {
+            return Err(format!("The interpreter only runs on cpu (got: {})", device).into());
+        }
+        None
+    } else {
+        Some(find_backend_plugin(
+            &settings.backend(args.backend.as_deref()),
+            &device,
+            &registry,
+        )?)
+    };
+
+    let mut manager = match args.timeout {
+        Some(secs) => PluginManager::with_timeout(secs)?,
+        None => PluginManager::new()?,
+    };
+    manager.add_config_overrides(&args.plugin_config)?;
+    manager.allow_read(&args.model);
+
+    let model_name = args
+        .model
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| args.model.display().to_string());
+    // The temp file keeps an imported ONNX model's snapshot alive until the benchmark completes
+    let (snapshot_path, _onnx_snapshot) = load_model_snapshot(&args.model, format_plugin, &model_name, &mut manager)?;
+    let (snapshot, weights) = hdss::load_lazy(&snapshot_path)?;
+    let all_inputs: Vec<String> = args.input.iter().chain(args.inputs.iter()).cloned().collect();
+    let inputs = parse_inputs(&all_inputs, &snapshot)?;
+
+    // Starts the backend, so its cancellation handle is available below
+    let supports_benchmark = match backend_plugin {
+        Some(plugin) => plugin_supports(&mut manager, &plugin.name, methods::BACKEND_BENCHMARK)?,
+        None => false,
+    };
+
+    // Set up Ctrl+C handler for cancellation
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let handle = backend_plugin.and_then(|plugin| manager.get_cancellation_handle(&plugin.name));
+    {
+        let cancelled = Arc::clone(&cancelled);
+        if let Err(e) = ctrlc::set_handler(move || {
+            cancelled.store(true, Ordering::SeqCst);
+            eprintln!("\nCancelling...");
+            if let Some(handle) = &handle {
+                if let Err(cancel_err) = handle.cancel() {
+                    eprintln!("Warning: Failed to send cancellation: {}", cancel_err);
+                }
+            }
+        }) {
+            output::warning(&format!(
+                "Failed to set Ctrl+C handler: {}. Cancellation may not work.",
+                e
+            ));
+        }
+    }
+
+    let (backend_name, result, start) = match backend_plugin {
+        Some(backend_plugin) => {
+            let library_path = compile_cached(
+                &mut manager,
+                &backend_plugin.name,
+                &snapshot_path,
+                weights.as_ref(),
+                &model_name,
+                &device,
+            )?;
+            let backend_client = manager.get_plugin(&backend_plugin.name)?;
+
+            // Inputs are written once and reused by every iteration
+            let (input_refs, _temp_files) = save_inputs(&inputs, backend_client.inline_tensor_limit())?;
+
+            let params = BenchmarkParams {
+                library_path: path_to_str(&library_path)?.to_string(),
+                snapshot_path: path_to_str(&snapshot_path)?.to_string(),
+                device: device.clone(),
+                inputs: input_refs,
+                iterations: args.iterations,
+                warmup: args.warmup,
+                precision: precision_params(&args),
+            };
+            output::benchmarking(&format!(
+                "{} ({}, {} iterations, {} warmup)",
+                model_name, device, args.iterations, args.warmup
+            ));
+            let start = Instant::now();
+            let result = if supports_benchmark {
+                backend_client.benchmark(params)
+            } else {
+                output::warning(&format!(
+                    "Backend '{}' does not support backend.benchmark; timing backend.run from the CLI",
+                    backend_plugin.name
+                ));
+                benchmark_runs(backend_client, params, &cancelled)
+            };
+            (backend_plugin.name.clone(), result.map_err(Into::into), start)
+        },
+        None => {
+            output::benchmarking(&format!(
+                "{} ({}, {} iterations, {} warmup)",
+                model_name, INTERPRETER, args.iterations, args.warmup
+            ));
+            let start = Instant::now();
+            let result = benchmark_interpreter(
+                &snapshot,
+                weights.as_ref(),
+                &inputs,
+                &args,
+                &registry,
+                &mut manager,
+                &cancelled,
+            );
+            (INTERPRETER.to_string(), result, start)
+        },
+    };
+    if cancelled.load(Ordering::SeqCst) {
+        return Err(errors::classified(ErrorClass::Cancelled, "Operation cancelled by user"));
+    }
+    let result: BenchmarkResult = result?;
+    output::finished(&format!(
+        "{} iterations in {}",
+        result.latencies_ms.len(),
+        output::format_duration(start.elapsed().as_secs_f64())
+    ));
+
+    let report = Report {
+        model: &model_name,
+        backend: &backend_name,
+        device: &device,
+        args: &args,
+        result: &result,
+    };
+    match args.format.as_str() {
+        "json" => write_json(&report, args.output.as_deref()),
+        "csv" => write_csv(&report, args.output.as_deref()),
+        _ => {
+            print_pretty(&result);
+            Ok(())
+        },
+    }
+}
+
+/// Time one `backend.run` per iteration, for backends without `backend.benchmark`
+///
+/// The latencies include the RPC round trip and the backend loading the library on every call,
+/// and the peak memory is unknown.
+fn benchmark_runs(
+    client: &mut PluginClient,
+    params: BenchmarkParams,
+    cancelled: &AtomicBool,
+) -> Result<BenchmarkResult, crate::plugins::ClientError> {
+    let run = |client: &mut PluginClient| {
+        client.run(
+            &params.library_path,
+            &params.snapshot_path,
+            &params.device,
+            params.inputs.clone(),
+            params.precision,
+        )
+    };
+
+    time_iterations(params.warmup, params.iterations, cancelled, || run(client).map(drop))
+}
+
+/// Time the reference interpreter in the CLI process
+///
+/// Every iteration converts the inputs to tensors and the outputs back, like a backend's
+/// `backend.run`. Custom ops are forwarded to their plugins, so their RPCs are included in the
+/// latencies. The peak memory is the CLI process's resident peak over the benchmark.
+fn benchmark_interpreter(
+    snapshot: &Snapshot,
+    weights: Option<&ShardedWeights>,
+    inputs: &HashMap<String, TensorData>,
+    args: &BenchArgs,
+    registry: &PluginRegistry,
+    manager: &mut PluginManager,
+    cancelled: &AtomicBool,
+) -> Result<BenchmarkResult, Box<dyn std::error::Error>> {
+    let device = "cpu".to_string();
+    let dumps = HashMap::new();
+
+    reset_peak_memory();
+    let mut result = time_iterations(args.warmup, args.iterations, cancelled, || {
+        run_in_process(snapshot, weights, inputs, &dumps, false, &device, registry, manager).map(drop)
+    })?;
+    result.peak_memory_bytes = peak_memory();
+    Ok(result)
+}
+
+/// Run `warmup` untimed and then `iterations` timed calls of `run`
+///
+/// Stops early when cancelled, returning the latencies timed so far. The peak memory is left
+/// unknown.
+fn time_iterations<E>(
+    warmup: u32,
+    iterations: u32,
+    cancelled: &AtomicBool,
+    mut run: impl FnMut() -> Result<(), E>,
+) -> Result<BenchmarkResult, E> {
+    for _ in 0..warmup {
+        if cancelled.load(Ordering::SeqCst) {
+            return Ok(BenchmarkResult::default());
+        }
+        run()?;
+    }
+    let mut latencies_ms = Vec::with_capacity(iterations as usize);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        if cancelled.load(Ordering::SeqCst) {
+            break;
+        }
+        let iteration = Instant::now();
+        run()?;
+        latencies_ms.push(iteration.elapsed().as_secs_f64() * 1000.0);
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    Ok(BenchmarkResult {
+        throughput: if elapsed > 0.0 {
+            latencies_ms.len() as f64 / elapsed
+        } else {
+            0.0
+        },
+        latencies_ms,
+        peak_memory_bytes: None,
+    })
+}
+
+/// Precision overrides requested on the command line, if any
+fn precision_params(args: &BenchArgs) -> Option<PrecisionParams> {
+    (args.allow_tf32 || args.f16_accumulate).then(|| PrecisionParams {
+        allow_tf32: args.allow_tf32.then_some(true),
+        f16_accumulate_f32: args.f16_accumulate.then_some(false),
+    })
+}
+
+fn print_pretty(result: &BenchmarkResult) {
+    let ms = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{:.3} ms", v));
+    println!("{:<12} {}", "mean", ms(result.mean_ms()));
+    println!("{:<12} {}", "min", ms(result.percentile_ms(0.0)));
+    for p in PERCENTILES {
+        println!("{:<12} {}", format!("p{}", p), ms(result.percentile_ms(p)));
+    }
+    println!("{:<12} {}", "max", ms(result.percentile_ms(100.0)));
+    println!("{:<12} {:.2} runs/s", "throughput", result.throughput);
+    if let Some(bytes) = result.peak_memory_bytes {
+        println!("{:<12} {}", "peak memory", output::format_size(bytes as usize));
+    }
+}
+
+/// What a report describes, shared by the JSON and CSV writers
+struct Report<'a> {
+    model: &'a str,
+    backend: &'a str,
+    device: &'a str,
+    args: &'a BenchArgs,
+    result: &'a BenchmarkResult,
+}
+
+impl Report<'_> {
+    /// The summarized latencies in milliseconds, in report order
+    fn latency_ms(&self) -> Vec<(String, Option<f64>)> {
+        let mut latency = vec![
+            ("mean".to_string(), self.result.mean_ms()),
+            ("min".to_string(), self.result.percentile_ms(0.0)),
+        ];
+        for p in PERCENTILES {
+            latency.push((format!("p{}", p), self.result.percentile_ms(p)));
+        }
+        latency.push(("max".to_string(), self.result.percentile_ms(100.0)));
+        latency
+    }
+}
+
+fn write_json(report: &Report, path: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let latency: serde_json::Map<String, serde_json::Value> = report
+        .latency_ms()
+        .into_iter()
+        .map(|(name, value)| (name, serde_json::json!(value)))
+        .collect();
+
+    let json = serde_json::json!({
+        "model": report.model,
+        "backend": report.backend,
+        "device": report.device,
+        "iterations": report.args.iterations,
+        "warmup": report.args.warmup,
+        "latency_ms": latency,
+        "throughput": report.result.throughput,
+        "peak_memory_bytes": report.result.peak_memory_bytes,
+        "latencies_ms": report.result.latencies_ms,
+    });
+    let json = serde_json::to_string_pretty(&json)?;
+    match path {
+        Some(path) => {
+            std::fs::write(path, json + "\n").map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            output::info(&format!("Wrote benchmark results to {}", path.display()));
+        },
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+/// Write a header and one row of summary columns, appending the row to an existing file
+///
+/// Rows start with a Unix timestamp so results appended across runs stay ordered.
+fn write_csv(report: &Report, path: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let latency = report.latency_ms();
+    let mut header: Vec<String> = ["timestamp", "model", "backend", "device", "iterations", "warmup"]
+        .map(String::from)
+        .to_vec();
+    header.extend(latency.iter().map(|(name, _)| format!("{}_ms", name)));
+    header.extend(["throughput", "peak_memory_bytes"].map(String::from));
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut row = vec![
+        timestamp.to_string(),
+        csv_field(report.model),
+        csv_field(report.backend),
+        csv_field(report.device),
+        report.args.iterations.to_string(),
+        report.args.warmup.to_string(),
+    ];
+    row.extend(
+        latency
+            .iter()
+            .map(|(_, value)| value.map_or_else(String::new, |v| format!("{:.6}", v))),
+    );
+    row.push(format!("{:.6}", report.result.throughput));
+    row.push(
+        report
+            .result
+            .peak_memory_bytes
+            .map_or_else(String::new, |b| b.to_string()),
+    );
+
+    let (header, row) = (header.join(","), row.join(","));
+    match path {
+        Some(path) => {
+            let is_new = std::fs::metadata(path).map_or(true, |m| m.len() == 0);
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+            if is_new {
+                writeln!(file, "{}", header)?;
+            }
+            writeln!(file, "{}", row)?;
+            output::info(&format!("Appended benchmark results to {}", path.display()));
+        },
+        None => {
+            println!("{}", header);
+            println!("{}", row);
+        },
+    }
+    Ok(())
+}
+
+/// Quote a CSV field containing a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
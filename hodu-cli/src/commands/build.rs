@@ -1,12 +1,20 @@
 //! Build command - AOT compile models using backend plugins
 //!
-//! This command uses JSON-RPC based plugins to compile models.
+//! This command uses JSON-RPC based plugins to compile models. Several `--target` triples or
+//! `--device`s build every combination of them, see [`matrix`].
 
+mod matrix;
+
+use crate::cache::{artifact_key, ArtifactCache};
+use crate::commands::run::backend_artifact_spec;
+use crate::config::Settings;
+use crate::errors::{self, ErrorClass};
 use crate::output;
-use crate::plugins::{load_registry, PluginManager, PluginRegistry};
-use crate::utils::path_to_str;
+use crate::plugins::{load_registry, PluginClient, PluginEntry, PluginManager, PluginRegistry};
+use crate::utils::{import_onnx, path_to_str};
+use crate::watch;
 use clap::Args;
-use hodu_core::snapshot::Snapshot;
+use hodu_core::format::{hdss, hdss::ShardedWeights};
 use hodu_plugin::BuildTarget;
 use std::path::{Path, PathBuf};
 
@@ -15,23 +23,25 @@ pub struct BuildArgs {
     /// Model file (.onnx, .hdss, etc.)
     pub model: Option<PathBuf>,
 
-    /// Output file path
+    /// Output file path, or the output directory when building several targets
     #[arg(short, long)]
     pub output: Option<PathBuf>,
 
-    /// Target triple (default: current system)
-    #[arg(short, long)]
-    pub target: Option<String>,
+    /// Target triple, can be repeated or comma-separated (default: current system)
+    #[arg(short, long, value_delimiter = ',')]
+    pub target: Vec<String>,
 
-    /// Target device (cpu, metal, cuda::0)
-    #[arg(short, long, default_value = "cpu")]
-    pub device: String,
+    /// Target device (cpu, metal, cuda::0), can be repeated or comma-separated; default: `device`
+    /// in ~/.hodu/config.toml, else cpu
+    #[arg(short, long, value_delimiter = ',')]
+    pub device: Vec<String>,
 
-    /// Backend plugin name (default: auto-detect by device)
+    /// Backend plugin name (default: `backend` in ~/.hodu/config.toml, else auto-detect by device)
     #[arg(short, long)]
     pub backend: Option<String>,
 
-    /// Output format (sharedlib, staticlib, object, metallib, ptx)
+    /// Output format (sharedlib, staticlib, object, metallib, ptx); several targets default to
+    /// sharedlib
     #[arg(short, long)]
     pub format: Option<String>,
 
@@ -54,38 +64,114 @@ pub struct BuildArgs {
     /// Timeout in seconds for plugin operations (default: 300)
     #[arg(long, value_name = "SECONDS")]
     pub timeout: Option<u64>,
+
+    /// Override a plugin config value from ~/.hodu/config.toml, can be repeated
+    #[arg(long = "plugin-config", value_name = "PLUGIN.KEY=VALUE")]
+    pub plugin_config: Vec<String>,
+
+    /// Write plugin logs and tracing events to a file as JSON lines
+    #[arg(long, value_name = "PATH")]
+    pub trace_file: Option<PathBuf>,
+
+    /// Build even if the artifact cache has this build, and leave the cache untouched
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Builds run at once when building several targets, each with its own plugin process
+    #[arg(short = 'j', long, default_value_t = 4)]
+    pub jobs: usize,
+
+    /// Rebuild whenever the model or a plugin it uses changes
+    #[arg(long, conflicts_with = "list_targets")]
+    pub watch: bool,
 }
 
 pub fn execute(args: BuildArgs) -> Result<(), Box<dyn std::error::Error>> {
     let registry = load_registry()?;
 
-    // Normalize device (lowercase)
-    let device = args.device.to_lowercase();
+    // Normalize devices (lowercase)
+    let settings = Settings::load()?;
+    let mut devices: Vec<String> = Vec::new();
+    for device in &args.device {
+        let device = device.to_lowercase();
+        if !devices.contains(&device) {
+            devices.push(device);
+        }
+    }
+    if devices.is_empty() {
+        devices.push(settings.device(None).to_lowercase());
+    }
 
-    // Find backend: explicit --backend or auto-detect by device
-    let backend_name = match &args.backend {
-        Some(name) => find_backend_by_name(name, &registry)?.name.clone(),
-        None => find_builder_backend(&device, &registry)?.name.clone(),
-    };
+    // Find backends: explicit or configured backend, or auto-detect by device
+    let backend = settings.backend(args.backend.as_deref());
+    let mut backends: Vec<(String, String)> = Vec::new();
+    for device in devices {
+        let backend_name = match &backend {
+            Some(name) => find_backend_by_name(name, &registry)?.name.clone(),
+            None => find_builder_backend(&device, &registry)?.name.clone(),
+        };
+        backends.push((device, backend_name));
+    }
 
     // Handle --list-targets
     if args.list_targets {
-        return list_targets(&backend_name);
+        let mut listed: Vec<&str> = Vec::new();
+        for (_, backend_name) in &backends {
+            if !listed.contains(&backend_name.as_str()) {
+                list_targets(backend_name)?;
+                listed.push(backend_name);
+            }
+        }
+        return Ok(());
+    }
+
+    // Every target triple for every device
+    let triples: Vec<Option<&str>> = match args.target.is_empty() {
+        true => vec![None],
+        false => args.target.iter().map(|triple| Some(triple.as_str())).collect(),
+    };
+    let mut jobs: Vec<BuildJob> = Vec::new();
+    for triple in triples {
+        for (device, backend_name) in &backends {
+            let target = match triple {
+                Some(triple) => BuildTarget::new(triple, device.clone()),
+                None => BuildTarget::host(device.clone()),
+            };
+            if !jobs.iter().any(|job| job.target == target) {
+                jobs.push(BuildJob {
+                    backend: backend_name.clone(),
+                    target,
+                });
+            }
+        }
     }
 
     // For normal build, model and output are required
-    let model = args.model.ok_or("Model file is required for building")?;
-    let output = args.output.ok_or("Output path is required (use -o/--output)")?;
+    let model = args.model.clone().ok_or("Model file is required for building")?;
+    let output = args.output.clone().ok_or("Output path is required (use -o/--output)")?;
 
     if !model.exists() {
-        return Err(format!("Model file not found: {}", model.display()).into());
+        return Err(errors::classified(
+            ErrorClass::Io,
+            format!("Model file not found: {}", model.display()),
+        ));
     }
 
     // Validate output path
     if output.as_os_str().is_empty() {
         return Err("Output path cannot be empty".into());
     }
-    if output.is_dir() {
+    if jobs.len() > 1 {
+        if output.exists() && !output.is_dir() {
+            return Err(format!(
+                "Output path must be a directory when building several targets: {}",
+                output.display()
+            )
+            .into());
+        }
+        std::fs::create_dir_all(&output)
+            .map_err(|e| format!("Cannot create output directory {}: {}", output.display(), e))?;
+    } else if output.is_dir() {
         return Err(format!("Output path is a directory: {}", output.display()).into());
     }
     if let Some(parent) = output.parent() {
@@ -121,7 +207,8 @@ pub fn execute(args: BuildArgs) -> Result<(), Box<dyn std::error::Error>> {
         Some("hdss") => None,
         Some(ext) => {
             let plugin = registry.find_model_format_by_extension(ext);
-            if plugin.is_none() {
+            // Without a plugin, .onnx falls back to the builtin importer
+            if plugin.is_none() && ext != "onnx" {
                 return Err(format!("No model format plugin found for .{}", ext).into());
             }
             plugin
@@ -129,64 +216,176 @@ pub fn execute(args: BuildArgs) -> Result<(), Box<dyn std::error::Error>> {
         None => return Err("Model file has no extension".into()),
     };
 
+    let mut manager = plugin_manager(&args, &model, &output)?;
+
+    if args.watch {
+        let mut plugins: Vec<String> = format_plugin.map(|plugin| plugin.name.clone()).into_iter().collect();
+        for job in &jobs {
+            if !plugins.contains(&job.backend) {
+                plugins.push(job.backend.clone());
+            }
+        }
+        return watch::watch(&mut manager, [model.clone()], &plugins, |manager, _| {
+            build_model(&model, &output, format_plugin, &jobs, &args, &registry, manager)
+        });
+    }
+    build_model(&model, &output, format_plugin, &jobs, &args, &registry, &mut manager)
+}
+
+/// One build of the model
+#[derive(Debug, Clone)]
+struct BuildJob {
+    backend: String,
+    target: BuildTarget,
+}
+
+/// Create a plugin manager with the command's timeout, config overrides and file access
+fn plugin_manager(args: &BuildArgs, model: &Path, output: &Path) -> Result<PluginManager, Box<dyn std::error::Error>> {
     // Create plugin manager with optional timeout
     let mut manager = match args.timeout {
         Some(secs) => PluginManager::with_timeout(secs)?,
         None => PluginManager::new()?,
     };
+    manager.add_config_overrides(&args.plugin_config)?;
+    manager.allow_read(model);
+    manager.allow_write(output);
+    if let Some(path) = &args.trace_file {
+        manager.set_trace_file(path)?;
+    }
+    Ok(manager)
+}
 
+/// Convert the model to a snapshot if needed and compile it with the backend
+fn build_model(
+    model: &Path,
+    output: &Path,
+    format_plugin: Option<&PluginEntry>,
+    jobs: &[BuildJob],
+    args: &BuildArgs,
+    registry: &PluginRegistry,
+    manager: &mut PluginManager,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let extension = model.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
     // Load model (using format plugin if needed)
-    let snapshot_path = if let Some(format_entry) = format_plugin {
-        let display_name = model
-            .file_name()
-            .map(|n| n.to_string_lossy().into_owned())
-            .unwrap_or_else(|| model.display().to_string());
+    let display_name = model
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| model.display().to_string());
+    let (snapshot_path, _onnx_snapshot) = if let Some(format_entry) = format_plugin {
         output::loading(&display_name);
         let client = manager.get_plugin(&format_entry.name)?;
-        let result = client.load_model(path_to_str(&model)?)?;
-        PathBuf::from(result.snapshot_path)
+        let result = client.load_model(path_to_str(model)?)?;
+        client.check_plugin_path(&result.snapshot_path)?;
+        (PathBuf::from(result.snapshot_path), None)
+    } else if extension.as_deref() == Some("onnx") {
+        output::loading(&display_name);
+        let temp_file = import_onnx(model)?;
+        (temp_file.path().to_path_buf(), Some(temp_file))
     } else {
-        model.clone()
+        (model.to_path_buf(), None)
     };
 
     // Validate snapshot is loadable before building
-    Snapshot::load(&snapshot_path)?;
+    let (_, weights) = hdss::load_lazy(&snapshot_path)?;
+
+    if let [job] = jobs {
+        // Determine build format from arg or output extension
+        let format = determine_format(&args.format, output);
+        let start = std::time::Instant::now();
+        let cached = build_artifact(
+            manager,
+            job,
+            &format,
+            &snapshot_path,
+            weights.as_ref(),
+            &display_name,
+            args,
+            output,
+        )?;
+
+        let duration = start.elapsed().as_secs_f64();
+        output::finished(&format!(
+            "{} target(s) in {}",
+            format,
+            output::format_duration(duration)
+        ));
+        if output::is_json() {
+            output::print_json(&serde_json::json!({
+                "model": model.display().to_string(),
+                "output": output.display().to_string(),
+                "backend": job.backend,
+                "target": job.target.triple,
+                "device": job.target.device,
+                "format": format,
+                "cached": cached,
+                "elapsed_secs": duration,
+            }))?;
+        }
+        return Ok(());
+    }
 
-    // Determine build format from arg or output extension
-    let format = determine_format(&args.format, &output);
+    matrix::build_matrix(
+        model,
+        output,
+        jobs,
+        &snapshot_path,
+        weights.as_ref(),
+        &display_name,
+        args,
+        registry,
+        manager,
+    )
+}
 
-    // Determine build target
-    let build_target = match &args.target {
-        Some(triple) => BuildTarget::new(triple.clone(), device.clone()),
-        None => BuildTarget::host(device.clone()),
+/// Build `job` into `dest`, through the artifact cache unless `--no-cache`
+///
+/// Returns whether the artifact came from the cache.
+#[allow(clippy::too_many_arguments)]
+fn build_artifact(
+    manager: &mut PluginManager,
+    job: &BuildJob,
+    format: &str,
+    snapshot_path: &Path,
+    weights: Option<&ShardedWeights>,
+    display_name: &str,
+    args: &BuildArgs,
+    dest: &Path,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let build_target = &job.target;
+    let build = |client: &mut PluginClient, path: &Path| -> Result<(), Box<dyn std::error::Error>> {
+        output::compiling(&format!(
+            "{} ({}, {})",
+            display_name, build_target.triple, build_target.device
+        ));
+        // Call backend.build via JSON-RPC
+        client.build(
+            path_to_str(snapshot_path)?,
+            &build_target.triple,
+            &build_target.device,
+            format,
+            path_to_str(path)?,
+        )?;
+        Ok(())
     };
+    if args.no_cache {
+        build(manager.get_plugin(&job.backend)?, dest)?;
+        return Ok(false);
+    }
 
-    // Build message
-    let model_name = model
-        .file_name()
-        .map(|n| n.to_string_lossy().into_owned())
-        .unwrap_or_else(|| model.display().to_string());
-    output::compiling(&format!("{} ({}, {})", model_name, build_target.triple, device));
-
-    // Call backend.build via JSON-RPC
-    let start = std::time::Instant::now();
-    let client = manager.get_plugin(&backend_name)?;
-    client.build(
-        path_to_str(&snapshot_path)?,
-        &build_target.triple,
-        &build_target.device,
-        &format,
-        path_to_str(&output)?,
-    )?;
-
-    let duration = start.elapsed().as_secs_f64();
-    output::finished(&format!(
-        "{} target(s) in {}",
-        format,
-        output::format_duration(duration)
-    ));
-
-    Ok(())
+    let mut spec = backend_artifact_spec(manager, &job.backend, build_target, format)?;
+    spec.options["opt_level"] = args.opt_level.into();
+    spec.options["standalone"] = args.standalone.into();
+    let key = artifact_key(snapshot_path, weights, &spec)?;
+    let extension = dest.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let client = manager.get_plugin(&job.backend)?;
+    let (artifact, hit) =
+        ArtifactCache::open()?.get_or_build(&key, extension, display_name, &spec, |path| build(client, path))?;
+    if hit {
+        output::cached(display_name);
+    }
+    std::fs::copy(&artifact, dest)
+        .map_err(|e| format!("Failed to copy {} to {}: {}", artifact.display(), dest.display(), e))?;
+    Ok(hit)
 }
 
 fn list_targets(backend_name: &str) -> Result<(), Box<dyn std::error::Error>> {
@@ -194,6 +393,10 @@ fn list_targets(backend_name: &str) -> Result<(), Box<dyn std::error::Error>> {
     let client = manager.get_plugin(backend_name)?;
 
     let result = client.list_targets()?;
+    if output::is_json() {
+        output::print_json(&serde_json::json!({ "backend": backend_name, "targets": result.targets }))?;
+        return Ok(());
+    }
 
     println!("Supported build targets for {} backend:\n", backend_name);
     println!("{}", result.formatted);
@@ -201,7 +404,7 @@ fn list_targets(backend_name: &str) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn find_backend_by_name<'a>(
+pub(crate) fn find_backend_by_name<'a>(
     name: &str,
     registry: &'a PluginRegistry,
 ) -> Result<&'a crate::plugins::PluginEntry, Box<dyn std::error::Error>> {
@@ -220,7 +423,7 @@ fn find_backend_by_name<'a>(
         }
     }
 
-    Err(format!(
+    let message = format!(
         "Backend '{}' not found or does not support building.\n\nInstalled backends:\n{}",
         name,
         registry
@@ -228,11 +431,11 @@ fn find_backend_by_name<'a>(
             .map(|p| format!("  {} - builder: {}", p.name, p.capabilities.builder.unwrap_or(false)))
             .collect::<Vec<_>>()
             .join("\n")
-    )
-    .into())
+    );
+    Err(errors::classified(ErrorClass::Plugin, message))
 }
 
-fn find_builder_backend<'a>(
+pub(crate) fn find_builder_backend<'a>(
     device: &str,
     registry: &'a PluginRegistry,
 ) -> Result<&'a crate::plugins::PluginEntry, Box<dyn std::error::Error>> {
@@ -244,7 +447,7 @@ fn find_builder_backend<'a>(
         }
     }
 
-    Err(format!(
+    let message = format!(
         "No builder backend found for device '{}'\n\nInstalled backends:\n{}",
         device,
         registry
@@ -257,8 +460,8 @@ fn find_builder_backend<'a>(
             ))
             .collect::<Vec<_>>()
             .join("\n")
-    )
-    .into())
+    );
+    Err(errors::classified(ErrorClass::Plugin, message))
 }
 
 fn determine_format(format_arg: &Option<String>, output: &Path) -> String {
@@ -283,3 +486,27 @@ fn determine_format(format_arg: &Option<String>, output: &Path) -> String {
         _ => "sharedlib".to_string(),
     }
 }
+
+/// File extension of an artifact in `format` for the `triple` platform, the reverse of
+/// [`determine_format`]
+fn artifact_extension(format: &str, triple: &str) -> &'static str {
+    let windows = triple.contains("windows");
+    match format {
+        "sharedlib" if windows => "dll",
+        "sharedlib" if triple.contains("apple") => "dylib",
+        "sharedlib" => "so",
+        "staticlib" if windows => "lib",
+        "staticlib" => "a",
+        "object" if windows => "obj",
+        "object" => "o",
+        "executable" if windows => "exe",
+        "metallib" => "metallib",
+        "ptx" => "ptx",
+        "cubin" => "cubin",
+        "llvmir" => "ll",
+        "llvmbitcode" => "bc",
+        "wgsl" => "wgsl",
+        "spirv" => "spv",
+        _ => "",
+    }
+}
@@ -0,0 +1,238 @@
+//! Building several targets in one `hodu build`
+//!
+//! Every `--target` triple is built for every `--device`. Up to `--jobs` builds run at once, each
+//! worker with its own plugin processes, so a backend compiles several targets in parallel. A
+//! backend with resource limits from `hodu plugin limit` builds its targets one at a time instead,
+//! so the limits hold for the whole matrix.
+//!
+//! Artifacts are written to the output directory as `<model>-<triple>-<device>[.<ext>]`, next to a
+//! `manifest.json` listing the artifact of each target, with its size and SHA256, and the targets
+//! that failed.
+
+use super::{artifact_extension, build_artifact, plugin_manager, BuildArgs, BuildJob};
+use crate::output;
+use crate::plugins::{PluginManager, PluginRegistry};
+use hodu_core::format::hdss::ShardedWeights;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+/// File name of the manifest written to the output directory
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Format built when `--format` is not given
+const DEFAULT_FORMAT: &str = "sharedlib";
+
+/// A built artifact, as listed in the manifest
+#[derive(Serialize)]
+struct Artifact {
+    target: String,
+    device: String,
+    backend: String,
+    /// File name in the output directory
+    file: String,
+    bytes: u64,
+    sha256: String,
+    /// Whether the artifact came from the artifact cache
+    cached: bool,
+    elapsed_secs: f64,
+}
+
+/// A build that failed, as listed in the manifest
+#[derive(Serialize)]
+struct Failure {
+    target: String,
+    device: String,
+    backend: String,
+    error: String,
+}
+
+/// Shared by every build of the matrix
+struct Matrix<'a> {
+    format: &'a str,
+    /// Model file name without its extension, starting every artifact's name
+    stem: &'a str,
+    snapshot_path: &'a Path,
+    weights: Option<&'a ShardedWeights>,
+    display_name: &'a str,
+    args: &'a BuildArgs,
+    output: &'a Path,
+}
+
+/// Build every job into the `output` directory and write its manifest
+#[allow(clippy::too_many_arguments)]
+pub(super) fn build_matrix(
+    model: &Path,
+    output: &Path,
+    jobs: &[BuildJob],
+    snapshot_path: &Path,
+    weights: Option<&ShardedWeights>,
+    display_name: &str,
+    args: &BuildArgs,
+    registry: &PluginRegistry,
+    manager: &mut PluginManager,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let format = args
+        .format
+        .as_deref()
+        .map_or_else(|| DEFAULT_FORMAT.to_string(), str::to_lowercase);
+    let stem = model
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "model".to_string());
+    let matrix = Matrix {
+        format: &format,
+        stem: &stem,
+        snapshot_path,
+        weights,
+        display_name,
+        args,
+        output,
+    };
+
+    let start = Instant::now();
+    let workers = args.jobs.clamp(1, jobs.len());
+    let results: Vec<Result<Artifact, String>> = if workers == 1 {
+        jobs.iter()
+            .map(|job| build_job(manager, job, &matrix).map_err(|e| e.to_string()))
+            .collect()
+    } else {
+        // Held while a backend with resource limits builds
+        let limited: HashMap<&str, Mutex<()>> = jobs
+            .iter()
+            .filter(|job| {
+                registry
+                    .find(&job.backend)
+                    .is_some_and(|entry| !entry.limits.is_empty())
+            })
+            .map(|job| (job.backend.as_str(), Mutex::new(())))
+            .collect();
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<Artifact, String>>>> = Mutex::new(jobs.iter().map(|_| None).collect());
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    let mut manager = plugin_manager(args, model, output).map_err(|e| e.to_string());
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(job) = jobs.get(index) else {
+                            break;
+                        };
+                        let _turn = limited
+                            .get(job.backend.as_str())
+                            .map(|lock| lock.lock().unwrap_or_else(PoisonError::into_inner));
+                        let result = match &mut manager {
+                            Ok(manager) => build_job(manager, job, &matrix).map_err(|e| e.to_string()),
+                            Err(e) => Err(e.clone()),
+                        };
+                        results.lock().unwrap_or_else(PoisonError::into_inner)[index] = Some(result);
+                    }
+                });
+            }
+        });
+        results
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err("Not built".to_string())))
+            .collect()
+    };
+
+    let mut artifacts = Vec::new();
+    let mut failed = Vec::new();
+    for (job, result) in jobs.iter().zip(results) {
+        match result {
+            Ok(artifact) => artifacts.push(artifact),
+            Err(error) => {
+                output::error(&format!(
+                    "{} ({}, {}): {}",
+                    display_name, job.target.triple, job.target.device, error
+                ));
+                failed.push(Failure {
+                    target: job.target.triple.clone(),
+                    device: job.target.device.clone(),
+                    backend: job.backend.clone(),
+                    error,
+                });
+            },
+        }
+    }
+
+    let duration = start.elapsed().as_secs_f64();
+    let manifest = serde_json::json!({
+        "model": model.display().to_string(),
+        "format": format,
+        "elapsed_secs": duration,
+        "artifacts": artifacts,
+        "failed": failed,
+    });
+    let manifest_path = output.join(MANIFEST_FILE);
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+        .map_err(|e| format!("Failed to write {}: {}", manifest_path.display(), e))?;
+
+    output::finished(&format!(
+        "{} of {} targets in {}, manifest at {}",
+        artifacts.len(),
+        jobs.len(),
+        output::format_duration(duration),
+        manifest_path.display()
+    ));
+    if output::is_json() {
+        output::print_json(&manifest)?;
+    }
+    if !failed.is_empty() {
+        return Err(format!("{} of {} targets failed to build", failed.len(), jobs.len()).into());
+    }
+    Ok(())
+}
+
+/// Build one target into the output directory
+fn build_job(
+    manager: &mut PluginManager,
+    job: &BuildJob,
+    matrix: &Matrix<'_>,
+) -> Result<Artifact, Box<dyn std::error::Error>> {
+    let mut file = format!(
+        "{}-{}-{}",
+        matrix.stem,
+        job.target.triple,
+        job.target.device.replace("::", "")
+    );
+    let extension = artifact_extension(matrix.format, &job.target.triple);
+    if !extension.is_empty() {
+        file = format!("{}.{}", file, extension);
+    }
+    let dest = matrix.output.join(&file);
+
+    let start = Instant::now();
+    let cached = build_artifact(
+        manager,
+        job,
+        matrix.format,
+        matrix.snapshot_path,
+        matrix.weights,
+        matrix.display_name,
+        matrix.args,
+        &dest,
+    )?;
+    let elapsed_secs = start.elapsed().as_secs_f64();
+
+    let mut hasher = Sha256::new();
+    let mut artifact = std::fs::File::open(&dest).map_err(|e| format!("Failed to open {}: {}", dest.display(), e))?;
+    let bytes = std::io::copy(&mut artifact, &mut hasher)?;
+    Ok(Artifact {
+        target: job.target.triple.clone(),
+        device: job.target.device.clone(),
+        backend: job.backend.clone(),
+        file,
+        bytes,
+        sha256: hex::encode(hasher.finalize()),
+        cached,
+        elapsed_secs,
+    })
+}
@@ -0,0 +1,88 @@
+//! Cache command - inspect the build artifact cache
+
+use crate::cache::ArtifactCache;
+use crate::output;
+use crate::plugins::{backend_plugin_name, BACKEND_PREFIX};
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub command: CacheCommands,
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// List cached build artifacts, newest first
+    Ls(LsArgs),
+}
+
+#[derive(Args)]
+pub struct LsArgs {
+    /// Only list artifacts built by this backend
+    #[arg(short, long)]
+    pub backend: Option<String>,
+}
+
+pub fn execute(args: CacheArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        CacheCommands::Ls(args) => list(args),
+    }
+}
+
+fn list(args: LsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut entries = ArtifactCache::open()?.entries()?;
+    if let Some(backend) = &args.backend {
+        let names = [backend.clone(), backend_plugin_name(backend)];
+        entries.retain(|entry| names.contains(&entry.spec.plugin));
+    }
+
+    if output::is_json() {
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                let mut value = serde_json::to_value(entry).unwrap_or_default();
+                value["path"] = entry.path.display().to_string().into();
+                value["bytes"] = entry.size.into();
+                value
+            })
+            .collect();
+        output::print_json(&entries)?;
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No cached build artifacts.");
+        return Ok(());
+    }
+    println!(
+        "{:<12}  {:<20} {:<10} {:<28} {:<8} {:<10} {:>9}  MODEL",
+        "KEY", "BACKEND", "VERSION", "TARGET", "DEVICE", "FORMAT", "SIZE"
+    );
+    let mut total = 0;
+    for entry in &entries {
+        total += entry.size;
+        println!(
+            "{:<12}  {:<20} {:<10} {:<28} {:<8} {:<10} {:>9}  {}",
+            &entry.key[..entry.key.len().min(12)],
+            entry
+                .spec
+                .plugin
+                .strip_prefix(BACKEND_PREFIX)
+                .unwrap_or(&entry.spec.plugin),
+            entry.spec.plugin_version,
+            entry.spec.triple,
+            entry.spec.device,
+            entry.spec.format,
+            output::format_size(entry.size as usize),
+            entry.model
+        );
+    }
+    println!();
+    println!(
+        "{} artifacts, {} total",
+        entries.len(),
+        output::format_size(total as usize)
+    );
+    Ok(())
+}
@@ -1,5 +1,6 @@
 //! Clean command - remove cached build artifacts
 
+use crate::config::Settings;
 use crate::output;
 use crate::plugins::BACKEND_PREFIX;
 use clap::Args;
@@ -32,45 +33,54 @@ pub fn execute(args: CleanArgs) -> Result<(), Box<dyn std::error::Error>> {
     let hodu_dir = dirs::home_dir()
         .ok_or("Could not determine home directory")?
         .join(".hodu");
+    let cache_dir = Settings::load()?.cache_dir().to_path_buf();
 
-    if !hodu_dir.exists() {
-        println!("Nothing to clean.");
-        return Ok(());
-    }
-
-    let cache_dir = hodu_dir.join("cache");
-
+    let mut cleaned = Vec::new();
     if args.all {
-        // Clean everything
-        clean_directory(&hodu_dir, "all hodu data", args.dry_run)?;
+        // Clean everything, including a cache configured outside ~/.hodu
+        if !cache_dir.starts_with(&hodu_dir) && cache_dir.exists() {
+            cleaned.push(clean_directory(&cache_dir, "build cache", args.dry_run)?);
+        }
+        if hodu_dir.exists() {
+            cleaned.push(clean_directory(&hodu_dir, "all hodu data", args.dry_run)?);
+        }
+        if cleaned.is_empty() {
+            nothing_to_clean();
+        }
     } else if let Some(backend) = &args.backend {
         // Clean specific backend
         let backend_cache = cache_dir.join(backend);
-        if backend_cache.exists() {
-            clean_directory(&backend_cache, &format!("{} cache", backend), args.dry_run)?;
-        } else {
-            // Try with prefix
-            let prefixed = format!("{}{}-plugin", BACKEND_PREFIX, backend);
-            let backend_cache = cache_dir.join(&prefixed);
-            if backend_cache.exists() {
-                clean_directory(&backend_cache, &format!("{} cache", backend), args.dry_run)?;
-            } else {
-                println!("No cache found for backend '{}'", backend);
-            }
+        // Try with prefix
+        let prefixed = cache_dir.join(format!("{}{}-plugin", BACKEND_PREFIX, backend));
+        match [backend_cache, prefixed].into_iter().find(|path| path.exists()) {
+            Some(path) => cleaned.push(clean_directory(&path, &format!("{} cache", backend), args.dry_run)?),
+            None => {
+                if !output::is_json() {
+                    println!("No cache found for backend '{}'", backend);
+                }
+            },
         }
-    } else {
+    } else if cache_dir.exists() {
         // Clean all caches (default)
-        if cache_dir.exists() {
-            clean_directory(&cache_dir, "build cache", args.dry_run)?;
-        } else {
-            println!("Nothing to clean.");
-        }
+        cleaned.push(clean_directory(&cache_dir, "build cache", args.dry_run)?);
+    } else {
+        nothing_to_clean();
     }
 
+    if output::is_json() {
+        output::print_json(&serde_json::json!({ "dry_run": args.dry_run, "removed": cleaned }))?;
+    }
     Ok(())
 }
 
-fn clean_directory(path: &Path, name: &str, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+fn nothing_to_clean() {
+    if !output::is_json() {
+        println!("Nothing to clean.");
+    }
+}
+
+/// Remove `path`, or only report it on a dry run, returning what was (or would be) removed
+fn clean_directory(path: &Path, name: &str, dry_run: bool) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
     let (size, file_count) = dir_stats(path)?;
     let size_str = output::format_size(size);
 
@@ -89,7 +99,11 @@ fn clean_directory(path: &Path, name: &str, dry_run: bool) -> Result<(), Box<dyn
         output::removed(name);
     }
 
-    Ok(())
+    Ok(serde_json::json!({
+        "path": path.display().to_string(),
+        "bytes": size,
+        "files": file_count,
+    }))
 }
 
 /// Remove directory with progress indication for large directories
@@ -146,7 +160,7 @@ fn remove_dir_recursive(path: &Path, deleted: &AtomicUsize, total: usize) -> Res
 }
 
 /// Get directory size and file count
-fn dir_stats(path: &Path) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+pub(crate) fn dir_stats(path: &Path) -> Result<(usize, usize), Box<dyn std::error::Error>> {
     let mut size = 0;
     let mut count = 0;
     // Skip symlinks to avoid cycles and double-counting
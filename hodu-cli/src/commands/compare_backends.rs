@@ -0,0 +1,414 @@
+//! Compare-backends command - run a model on several backends and compare their outputs
+//!
+//! The first backend is the reference: every output of the other backends is compared with its
+//! output using the tolerance of `hodu diff`. `interpreter` names the reference interpreter, so a
+//! backend plugin can be checked against it. A backend may pick its device with `NAME@DEVICE`.
+//!
+//! The command fails when any output differs, so it can gate CI jobs.
+
+use super::diff::{compare, format_error, Outcome, Tolerance};
+use super::run::{
+    compile_cached, find_backend_plugin, find_model_format_plugin, load_model_snapshot, parse_device, parse_inputs,
+    run_in_process, run_inputs, Runner,
+};
+use crate::config::Settings;
+use crate::errors::{self, ErrorClass};
+use crate::output::{self, colors};
+use crate::plugins::{load_registry, PluginManager, PluginRegistry};
+use crate::utils::{path_to_str, plugin_dtype_to_core};
+use clap::Args;
+use hodu_core::format::hdss::{self, ShardedWeights};
+use hodu_core::snapshot::Snapshot;
+use hodu_core::tensor::Tensor;
+use hodu_core::types::{Device as CoreDevice, Shape};
+use hodu_plugin::{Device, TensorData};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Backend name selecting the reference interpreter
+pub(crate) const INTERPRETER: &str = "interpreter";
+
+#[derive(Args)]
+pub struct CompareBackendsArgs {
+    /// Model file (.onnx, .hdss, etc.)
+    pub model: PathBuf,
+
+    /// Backend to run on, as NAME or NAME@DEVICE, repeated; the first one is the reference
+    #[arg(short, long = "backend", value_name = "NAME[@DEVICE]", required = true)]
+    pub backends: Vec<String>,
+
+    /// Input tensor (name=path, or name=table.csv#col,col to pick columns), can be repeated
+    #[arg(short, long = "input", value_name = "NAME=PATH")]
+    pub input: Vec<String>,
+
+    /// Input tensors (comma-separated: a=path,b=path)
+    #[arg(long = "inputs", value_name = "INPUTS", value_delimiter = ',')]
+    pub inputs: Vec<String>,
+
+    /// Device of the backends not naming one (cpu, metal, cuda::0); default: `device` in ~/.hodu/config.toml, else cpu
+    #[arg(short, long)]
+    pub device: Option<String>,
+
+    /// Relative tolerance, scaled by the reference value
+    #[arg(long, default_value_t = 1e-5)]
+    pub rtol: f64,
+
+    /// Absolute tolerance
+    #[arg(long, default_value_t = 1e-8)]
+    pub atol: f64,
+
+    /// Mismatching elements to list per output
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub show: usize,
+
+    /// Output format (pretty, json)
+    #[arg(short, long, default_value = "pretty")]
+    pub format: String,
+
+    /// Timeout in seconds for plugin operations (default: 300)
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Override a plugin config value from ~/.hodu/config.toml, can be repeated
+    #[arg(long = "plugin-config", value_name = "PLUGIN.KEY=VALUE")]
+    pub plugin_config: Vec<String>,
+}
+
+/// A backend to run the model on
+pub(crate) struct Target {
+    /// Plugin name, or [`INTERPRETER`]
+    pub name: String,
+    pub device: Device,
+}
+
+impl Target {
+    pub fn label(&self) -> String {
+        format!("{}@{}", self.name, self.device)
+    }
+}
+
+/// One output of one backend compared with the reference
+struct Row {
+    output: String,
+    backend: String,
+    outcome: Outcome,
+}
+
+pub fn execute(mut args: CompareBackendsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if output::is_json() {
+        args.format = "json".to_string();
+    }
+    let tolerance = Tolerance {
+        rtol: args.rtol,
+        atol: args.atol,
+    };
+    tolerance.validate()?;
+    if args.backends.len() < 2 {
+        return Err(format!(
+            "Comparing needs at least two backends (got: {}); use '{}' for the reference interpreter",
+            args.backends.len(),
+            INTERPRETER
+        )
+        .into());
+    }
+
+    let extension = args
+        .model
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    let registry = load_registry()?;
+    let format_plugin = find_model_format_plugin(extension.as_deref(), &registry)?;
+    let device = Settings::load()?.device(args.device.as_deref());
+    let targets = args
+        .backends
+        .iter()
+        .map(|backend| parse_target(backend, &device, &registry))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut manager = match args.timeout {
+        Some(secs) => PluginManager::with_timeout(secs)?,
+        None => PluginManager::new()?,
+    };
+    manager.add_config_overrides(&args.plugin_config)?;
+    manager.allow_read(&args.model);
+
+    let model_name = args
+        .model
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| args.model.display().to_string());
+    // The temp file keeps an imported ONNX model's snapshot alive until every backend has run
+    let (snapshot_path, _onnx_snapshot) = load_model_snapshot(&args.model, format_plugin, &model_name, &mut manager)?;
+    let (snapshot, weights) = hdss::load_lazy(&snapshot_path)?;
+    let all_inputs: Vec<String> = args.input.iter().chain(args.inputs.iter()).cloned().collect();
+    let inputs = parse_inputs(&all_inputs, &snapshot)?;
+
+    // Custom ops are executed by the plugins declaring them, which backends can't compile
+    let custom_ops = snapshot.custom_op_names();
+    if !custom_ops.is_empty() {
+        if let Some(target) = targets.iter().find(|target| target.name != INTERPRETER) {
+            return Err(format!(
+                "Backend '{}' cannot run custom ops ({}); they only run on the {}",
+                target.name,
+                custom_ops.join(", "),
+                INTERPRETER
+            )
+            .into());
+        }
+    }
+
+    let mut outputs = Vec::with_capacity(targets.len());
+    for target in &targets {
+        output::running(&format!("{} ({})", model_name, target.label()));
+        let start = std::time::Instant::now();
+        let result = run_target(
+            target,
+            &snapshot,
+            &snapshot_path,
+            weights.as_ref(),
+            &model_name,
+            &inputs,
+            &registry,
+            &mut manager,
+        )
+        .map_err(|e| format!("{} failed: {}", target.label(), e))?;
+        output::finished(&format!(
+            "{} in {}",
+            target.label(),
+            output::format_duration(start.elapsed().as_secs_f64())
+        ));
+        outputs.push(result);
+    }
+
+    let (reference, others) = outputs.split_first().ok_or("No backend to compare")?;
+    let mut rows = Vec::new();
+    for target in snapshot.targets.iter().map(|target| &target.name) {
+        for (backend, tensors) in targets[1..].iter().zip(others) {
+            let outcome = match (tensors.get(target), reference.get(target)) {
+                (Some(a), Some(b)) => compare(a, b, tolerance, args.show)?,
+                (a, _) => Outcome::Missing { in_a: a.is_some() },
+            };
+            rows.push(Row {
+                output: target.clone(),
+                backend: backend.label(),
+                outcome,
+            });
+        }
+    }
+
+    match args.format.as_str() {
+        "json" => print_json(&rows, &targets[0], &args)?,
+        _ => print_pretty(&rows, &targets[0]),
+    }
+
+    let failed = rows.iter().filter(|row| !row.outcome.passed()).count();
+    if failed > 0 {
+        return Err(errors::classified(
+            ErrorClass::CheckFailed,
+            format!(
+                "{} of {} outputs differ from {}",
+                failed,
+                rows.len(),
+                targets[0].label()
+            ),
+        ));
+    }
+    output::finished(&format!(
+        "all outputs match {} within rtol {} and atol {}",
+        targets[0].label(),
+        args.rtol,
+        args.atol
+    ));
+    Ok(())
+}
+
+/// Resolve `NAME[@DEVICE]`, checking that the backend plugin is installed
+pub(crate) fn parse_target(
+    spec: &str,
+    default_device: &str,
+    registry: &PluginRegistry,
+) -> Result<Target, Box<dyn std::error::Error>> {
+    let (name, device) = spec.split_once('@').unwrap_or((spec, default_device));
+    if name.is_empty() {
+        return Err(format!("Invalid backend: '{}' (expected NAME or NAME@DEVICE)", spec).into());
+    }
+    let device = parse_device(device)?;
+    if name == INTERPRETER {
+        if device != "cpu" {
+            return Err(format!("The {} only runs on cpu (got: {})", INTERPRETER, device).into());
+        }
+        return Ok(Target {
+            name: INTERPRETER.to_string(),
+            device,
+        });
+    }
+    let plugin = find_backend_plugin(&Some(name.to_string()), &device, registry)?;
+    Ok(Target {
+        name: plugin.name.clone(),
+        device,
+    })
+}
+
+/// Run the model on one backend, returning its outputs by target name
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_target(
+    target: &Target,
+    snapshot: &Snapshot,
+    snapshot_path: &Path,
+    weights: Option<&ShardedWeights>,
+    model_name: &str,
+    inputs: &HashMap<String, TensorData>,
+    registry: &PluginRegistry,
+    manager: &mut PluginManager,
+) -> Result<HashMap<String, Tensor>, Box<dyn std::error::Error>> {
+    let dumps = HashMap::new();
+    let outputs = if target.name == INTERPRETER {
+        run_in_process(
+            snapshot,
+            weights,
+            inputs,
+            &dumps,
+            false,
+            &target.device,
+            registry,
+            manager,
+        )?
+        .0
+    } else {
+        let library_path = compile_cached(
+            manager,
+            &target.name,
+            snapshot_path,
+            weights,
+            model_name,
+            &target.device,
+        )?;
+        let client = manager.get_plugin(&target.name)?;
+        let runner = Runner::Library {
+            library_path: path_to_str(&library_path)?,
+            snapshot_path: path_to_str(snapshot_path)?,
+            device: &target.device,
+            precision: None,
+        };
+        run_inputs(client, &runner, inputs, &dumps)?
+    };
+
+    outputs
+        .into_iter()
+        .map(|(name, data)| {
+            let tensor = Tensor::from_bytes(
+                &data.data,
+                Shape::new(&data.shape),
+                plugin_dtype_to_core(data.dtype)?,
+                CoreDevice::CPU,
+            )?;
+            Ok((name, tensor))
+        })
+        .collect()
+}
+
+fn print_pretty(rows: &[Row], reference: &Target) {
+    let use_color = output::supports_color();
+    println!("Reference: {}", reference.label());
+    println!();
+
+    let output_width = rows.iter().map(|row| row.output.len()).max().unwrap_or(0).max(6);
+    let backend_width = rows.iter().map(|row| row.backend.len()).max().unwrap_or(0).max(7);
+    println!(
+        "{:<ow$}  {:<bw$}  {:>10}  {:>10}  {:>10}  {:>10}  STATUS",
+        "OUTPUT",
+        "BACKEND",
+        "MAX ABS",
+        "MEAN ABS",
+        "MAX REL",
+        "MISMATCH",
+        ow = output_width,
+        bw = backend_width
+    );
+    for row in rows {
+        let (errors, status) = match &row.outcome {
+            Outcome::Missing { .. } => (
+                ["-".to_string(), "-".to_string(), "-".to_string(), "-".to_string()],
+                "missing".to_string(),
+            ),
+            Outcome::Shape { a, b } => (
+                ["-".to_string(), "-".to_string(), "-".to_string(), "-".to_string()],
+                format!("shape {:?} vs {:?}", a, b),
+            ),
+            Outcome::Compared(c) => (
+                [
+                    format_error(c.max_abs),
+                    format_error(c.mean_abs),
+                    format_error(c.max_rel),
+                    format!("{}/{}", c.mismatches, c.elements()),
+                ],
+                if c.mismatches == 0 { "ok" } else { "differs" }.to_string(),
+            ),
+        };
+        let status = match (use_color, row.outcome.passed()) {
+            (false, _) => status,
+            (true, true) => format!("{}{}{}", colors::GREEN, status, colors::RESET),
+            (true, false) => format!("{}{}{}", colors::RED, status, colors::RESET),
+        };
+        println!(
+            "{:<ow$}  {:<bw$}  {:>10}  {:>10}  {:>10}  {:>10}  {}",
+            row.output,
+            row.backend,
+            errors[0],
+            errors[1],
+            errors[2],
+            errors[3],
+            status,
+            ow = output_width,
+            bw = backend_width
+        );
+    }
+
+    // Where the differing outputs go wrong
+    for row in rows {
+        let Outcome::Compared(c) = &row.outcome else {
+            continue;
+        };
+        if c.locations.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{} on {}:", row.output, row.backend);
+        for (index, a, b) in &c.locations {
+            println!(
+                "  {:<16} {} vs {} (diff {})",
+                format!("{:?}", index),
+                a,
+                b,
+                format_error((a - b).abs())
+            );
+        }
+        if c.mismatches > c.locations.len() {
+            println!("  ... {} more", c.mismatches - c.locations.len());
+        }
+    }
+}
+
+fn print_json(rows: &[Row], reference: &Target, args: &CompareBackendsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let comparisons: Vec<_> = rows
+        .iter()
+        .map(|row| {
+            let mut comparison = row.outcome.to_json();
+            comparison["output"] = serde_json::json!(row.output);
+            comparison["backend"] = serde_json::json!(row.backend);
+            comparison
+        })
+        .collect();
+
+    let report = serde_json::json!({
+        "model": args.model.display().to_string(),
+        "reference": reference.label(),
+        "rtol": args.rtol,
+        "atol": args.atol,
+        "passed": rows.iter().all(|row| row.outcome.passed()),
+        "comparisons": comparisons,
+    });
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
@@ -0,0 +1,260 @@
+//! Config command - read and edit ~/.hodu/config.toml
+//!
+//! Top-level settings (`backend`, `device`, ...) are shown as resolved, with the environment
+//! variable or default they came from. Dotted keys reach the `[plugins.<name>]` and `[pool]`
+//! tables.
+
+use super::plugin::{print_empty, print_info_row, print_section};
+use crate::config::{check_value, find_setting, Kind, Settings, Source, SETTINGS};
+use crate::output;
+use crate::plugins::{parse_edit_value, set_value, unset_value, ConfigError, PluginConfig};
+use clap::{Args, Subcommand};
+use std::path::Path;
+
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print the value of a setting
+    Get(GetArgs),
+
+    /// Set a value in the config file
+    Set(SetArgs),
+
+    /// Remove a value from the config file
+    Unset(UnsetArgs),
+
+    /// List every setting with where its value comes from, then the plugin and pool tables
+    List,
+}
+
+#[derive(Args)]
+pub struct GetArgs {
+    /// Setting (`device`) or dotted key (`plugins.hodu-backend-llvm.threads`, `pool.enabled`)
+    pub key: String,
+}
+
+#[derive(Args)]
+pub struct SetArgs {
+    /// Setting (`device`) or dotted key (`plugins.hodu-backend-llvm.threads`, `pool.enabled`)
+    pub key: String,
+
+    /// Value, read as TOML (`8`, `true`, `"text"`) and otherwise as a plain string
+    pub value: String,
+}
+
+#[derive(Args)]
+pub struct UnsetArgs {
+    /// Setting or dotted key to remove
+    pub key: String,
+}
+
+pub fn execute(args: ConfigArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let path = PluginConfig::default_path()?;
+    match args.command {
+        ConfigCommands::Get(args) => get(&path, &args.key),
+        ConfigCommands::Set(args) => set(&path, &args.key, &args.value),
+        ConfigCommands::Unset(args) => unset(&path, &args.key),
+        ConfigCommands::List => list(&path),
+    }
+}
+
+fn get(path: &Path, key: &str) -> Result<(), Box<dyn std::error::Error>> {
+    check_key(key)?;
+    let (value, source) = if find_setting(key).is_some() {
+        let settings = Settings::load_from(path)?;
+        let (_, value, source) = settings
+            .resolved()
+            .into_iter()
+            .find(|(setting, _, _)| *setting == key)
+            .expect("every setting is resolved");
+        (value, Some(source))
+    } else {
+        PluginConfig::load_from(path)?;
+        let value = file_values(path)?
+            .into_iter()
+            .find(|(set, _)| set == key)
+            .map(|(_, value)| value);
+        (value, None)
+    };
+
+    if output::is_json() {
+        output::print_json(&serde_json::json!({
+            "key": key,
+            "value": value,
+            "source": source.map(|source| source.to_string()),
+        }))?;
+    } else {
+        match value {
+            Some(value) => println!("{}", value),
+            None => return Err(format!("`{}` is not set", key).into()),
+        }
+    }
+    Ok(())
+}
+
+fn set(path: &Path, key: &str, raw: &str) -> Result<(), Box<dyn std::error::Error>> {
+    check_key(key)?;
+    let value = match find_setting(key) {
+        Some(setting) => {
+            check_value(setting, raw).map_err(|e| format!("`{}` {}", key, e))?;
+            match setting.kind {
+                Kind::Seconds => toml_edit::Value::from(raw.trim().parse::<i64>()?),
+                Kind::Text | Kind::Path => toml_edit::Value::from(raw.trim()),
+            }
+        },
+        None => parse_edit_value(raw),
+    };
+
+    // Keep the previous file if the new value makes it invalid
+    let previous = match std::fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(ConfigError::Io(format!("{}: {}", path.display(), e)).into()),
+    };
+    set_value(path, key, value)?;
+    if let Err(e) = PluginConfig::load_from(path) {
+        match &previous {
+            Some(content) => std::fs::write(path, content)?,
+            None => std::fs::remove_file(path)?,
+        }
+        return Err(e.into());
+    }
+
+    if output::is_json() {
+        output::print_json(
+            &serde_json::json!({ "key": key, "value": raw.trim(), "path": path.display().to_string() }),
+        )?;
+    } else {
+        output::updated(&format!("{} = {}", key, raw.trim()));
+    }
+    Ok(())
+}
+
+fn unset(path: &Path, key: &str) -> Result<(), Box<dyn std::error::Error>> {
+    check_key(key)?;
+    let removed = unset_value(path, key)?;
+    if output::is_json() {
+        output::print_json(&serde_json::json!({ "key": key, "removed": removed }))?;
+    } else if removed {
+        output::removed(key);
+    } else {
+        output::skipping(&format!("{} is not set", key));
+    }
+    Ok(())
+}
+
+fn list(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let settings = Settings::load_from(path)?;
+    PluginConfig::load_from(path)?;
+    let values = file_values(path)?;
+    let (plugins, pool): (Vec<_>, Vec<_>) = values
+        .into_iter()
+        .filter(|(key, _)| find_setting(key).is_none())
+        .partition(|(key, _)| key.starts_with("plugins."));
+    // Keys within their table
+    let strip = |values: Vec<(String, String)>, table: &str| -> Vec<(String, String)> {
+        values
+            .into_iter()
+            .map(|(key, value)| (key[table.len() + 1..].to_string(), value))
+            .collect()
+    };
+    let (plugins, pool) = (strip(plugins, "plugins"), strip(pool, "pool"));
+
+    if output::is_json() {
+        let settings: Vec<_> = settings
+            .resolved()
+            .into_iter()
+            .map(|(key, value, source)| serde_json::json!({ "key": key, "value": value, "source": source.to_string() }))
+            .collect();
+        let table = |values: &[(String, String)]| -> serde_json::Map<String, serde_json::Value> {
+            values
+                .iter()
+                .map(|(key, value)| (key.clone(), serde_json::Value::String(value.clone())))
+                .collect()
+        };
+        output::print_json(&serde_json::json!({
+            "path": path.display().to_string(),
+            "settings": settings,
+            "plugins": table(&plugins),
+            "pool": table(&pool),
+        }))?;
+        return Ok(());
+    }
+
+    let use_color = output::supports_color();
+    print_section(&format!("Settings ({})", path.display()), use_color);
+    for ((key, value, source), setting) in settings.resolved().into_iter().zip(SETTINGS) {
+        let value = match (value, source) {
+            (None, _) => "(unset)".to_string(),
+            (Some(value), Source::File) => value,
+            (Some(value), source) => format!("{} ({})", value, source),
+        };
+        print_info_row(key, &format!("{}  # {}", value, setting.description), use_color);
+    }
+
+    for (title, values) in [("Plugins", &plugins), ("Pool", &pool)] {
+        println!();
+        print_section(title, use_color);
+        if values.is_empty() {
+            print_empty(use_color);
+        }
+        for (key, value) in values.iter() {
+            print_info_row(key, value, use_color);
+        }
+    }
+    Ok(())
+}
+
+/// Reject keys that are neither a setting nor inside the `plugins` or `pool` tables
+fn check_key(key: &str) -> Result<(), ConfigError> {
+    if key.split('.').any(str::is_empty) {
+        return Err(ConfigError::InvalidKey(key.to_string()));
+    }
+    let parts = key.split('.').count();
+    let known = match key.split_once('.') {
+        None => find_setting(key).is_some(),
+        Some(("plugins", _)) => parts >= 3,
+        Some(("pool", _)) => parts == 2,
+        Some(_) => false,
+    };
+    if known {
+        return Ok(());
+    }
+    let settings: Vec<&str> = SETTINGS.iter().map(|setting| setting.key).collect();
+    Err(ConfigError::InvalidKey(format!(
+        "{} (expected one of {}, plugins.<plugin>.<key> or pool.<key>)",
+        key,
+        settings.join(", ")
+    )))
+}
+
+/// Every leaf of the config file as a dotted key and a TOML value
+fn file_values(path: &Path) -> Result<Vec<(String, String)>, ConfigError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(ConfigError::Io(format!("{}: {}", path.display(), e))),
+    };
+    let table: toml::Table = content
+        .parse()
+        .map_err(|e| ConfigError::Parse(format!("{}: {}", path.display(), e)))?;
+    let mut values = Vec::new();
+    flatten(&table, "", &mut values);
+    Ok(values)
+}
+
+fn flatten(table: &toml::Table, prefix: &str, out: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = format!("{}{}", prefix, key);
+        match value {
+            toml::Value::Table(nested) => flatten(nested, &format!("{}.", key), out),
+            toml::Value::String(text) if prefix.is_empty() => out.push((key, text.clone())),
+            value => out.push((key, value.to_string())),
+        }
+    }
+}
@@ -1,10 +1,21 @@
 //! Convert command - convert models and tensors between formats
+//!
+//! Builtin formats are handled in-process, and every other extension is routed through the format
+//! plugin registered for it. Tensors can be permuted, reshaped and cast on the way.
 
+use super::run::plugin_supports;
+use crate::errors::{self, ErrorClass};
 use crate::output;
 use crate::plugins::{load_registry, PluginManager, PluginRegistry};
-use crate::tensor::{load_tensor_data, save_tensor_data};
-use crate::utils::{core_dtype_to_plugin, path_to_str, plugin_dtype_to_core};
+use crate::tensor::stream_tensor_to_hdt;
+use crate::utils::{import_onnx, path_to_str, plugin_dtype_to_core};
 use clap::Args;
+use hodu_core::format::{hdss, hdt, json, npy, npz, set_encryption_key, EncryptionKey};
+use hodu_core::snapshot::Snapshot;
+use hodu_core::tensor::Tensor;
+use hodu_core::types::Shape;
+use hodu_plugin::rpc::methods;
+use hodu_plugin::PluginDType;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -22,10 +33,8 @@ fn validate_file_magic(path: &Path, extension: &str) -> Result<(), String> {
         .map_err(|e| format!("Failed to read file: {}", e))?;
 
     let valid = match extension {
-        // HDT tensor format - starts with "HDT\x00" (Hodu Tensor)
-        "hdt" => bytes_read >= 4 && &magic[..4] == b"HDT\x00",
-        // HDSS snapshot format - starts with "HDSS" (Hodu Snapshot)
-        "hdss" => bytes_read >= 4 && &magic[..4] == b"HDSS",
+        // HDT tensors and HDSS snapshots - start with the "HDE\x00" checksummed envelope
+        "hdt" | "hdss" => bytes_read >= 4 && &magic[..4] == b"HDE\x00",
         // JSON - starts with whitespace or '{' or '['
         "json" => {
             bytes_read > 0 && {
@@ -50,14 +59,65 @@ fn validate_file_magic(path: &Path, extension: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Parse a byte size with an optional KB/MB/GB suffix (powers of 1024)
+fn parse_byte_size(s: &str) -> Result<usize, String> {
+    let upper = s.trim().to_ascii_uppercase();
+    let (digits, unit) = match upper.find(|c: char| !c.is_ascii_digit()) {
+        Some(pos) => upper.split_at(pos),
+        None => (upper.as_str(), ""),
+    };
+    let scale: usize = match unit.trim() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        other => return Err(format!("unknown size unit '{}'", other)),
+    };
+    let value: usize = digits.parse().map_err(|_| format!("invalid size '{}'", s))?;
+    value
+        .checked_mul(scale)
+        .filter(|&size| size > 0)
+        .ok_or_else(|| format!("invalid size '{}'", s))
+}
+
+/// Parse a tensor dtype name (f32, f16, bf16, i8, ...)
+fn parse_dtype(s: &str) -> Result<PluginDType, String> {
+    s.parse::<PluginDType>().map_err(|e| e.to_string())
+}
+
 #[derive(Args)]
 pub struct ConvertArgs {
     /// Input file
     pub input: PathBuf,
 
     /// Output file
-    #[arg(short, long)]
-    pub output: PathBuf,
+    #[arg(required_unless_present = "output_file")]
+    pub output: Option<PathBuf>,
+
+    /// Output file, instead of the positional argument
+    #[arg(short = 'o', long = "output", value_name = "OUTPUT", conflicts_with = "output")]
+    pub output_file: Option<PathBuf>,
+
+    /// Cast a tensor to this dtype (f32, f16, bf16, i8, ...)
+    #[arg(long, value_parser = parse_dtype)]
+    pub dtype: Option<PluginDType>,
+
+    /// Reorder the dimensions of a tensor (e.g. 0,2,3,1 turns NCHW into NHWC)
+    #[arg(long, value_name = "AXES", value_delimiter = ',')]
+    pub permute: Option<Vec<usize>>,
+
+    /// Reshape a tensor, keeping its elements in order; one dimension may be -1 to infer it
+    #[arg(long, value_name = "SHAPE", value_delimiter = ',', allow_hyphen_values = true)]
+    pub reshape: Option<Vec<i64>>,
+
+    /// Split the weights of a .hdss output into .hdta shards of at most this size (e.g. 2GB)
+    #[arg(long, value_name = "SIZE", value_parser = parse_byte_size)]
+    pub shard_size: Option<usize>,
+
+    /// Encrypt a .hdt or .hdss output with the AES-256 key in this file (32 raw bytes or 64 hex
+    /// digits); loading it needs HODU_ENCRYPTION_KEY or HODU_ENCRYPTION_KEY_FILE
+    #[arg(long, value_name = "KEY_FILE")]
+    pub encrypt: Option<PathBuf>,
 
     /// Verbose output
     #[arg(short, long)]
@@ -66,8 +126,16 @@ pub struct ConvertArgs {
 
 pub fn execute(args: ConvertArgs) -> Result<(), Box<dyn std::error::Error>> {
     if !args.input.exists() {
-        return Err(format!("Input file not found: {}", args.input.display()).into());
+        return Err(errors::classified(
+            ErrorClass::Io,
+            format!("Input file not found: {}", args.input.display()),
+        ));
     }
+    let output = args
+        .output
+        .as_deref()
+        .or(args.output_file.as_deref())
+        .ok_or("Missing output file")?;
 
     let input_ext = args
         .input
@@ -76,13 +144,26 @@ pub fn execute(args: ConvertArgs) -> Result<(), Box<dyn std::error::Error>> {
         .map(|e| e.to_lowercase())
         .ok_or("Input file has no extension")?;
 
-    let output_ext = args
-        .output
+    let output_ext = output
         .extension()
         .and_then(|e| e.to_str())
         .map(|e| e.to_lowercase())
         .ok_or("Output file has no extension")?;
 
+    if args.shard_size.is_some() && output_ext != "hdss" {
+        return Err("--shard-size only applies to .hdss outputs".into());
+    }
+
+    if let Some(key_file) = &args.encrypt {
+        if !matches!(output_ext.as_str(), "hdt" | "hdss") {
+            return Err("--encrypt only applies to .hdt and .hdss outputs".into());
+        }
+        if args.shard_size.is_some() {
+            return Err("--encrypt can't be combined with --shard-size".into());
+        }
+        set_encryption_key(Some(EncryptionKey::from_file(key_file)?));
+    }
+
     // Validate input file format by checking magic bytes
     if let Err(e) = validate_file_magic(&args.input, &input_ext) {
         output::warning(&e);
@@ -92,14 +173,19 @@ pub fn execute(args: ConvertArgs) -> Result<(), Box<dyn std::error::Error>> {
 
     // Determine conversion type (model or tensor)
     let is_model = is_model_format(&input_ext) || is_model_format(&output_ext);
+    if is_model && (args.dtype.is_some() || args.permute.is_some() || args.reshape.is_some()) {
+        return Err("--dtype, --permute and --reshape only apply to tensor conversions".into());
+    }
 
-    if args.verbose {
+    if args.verbose && !output::is_json() {
         println!("Input: {} (.{})", args.input.display(), input_ext);
-        println!("Output: {} (.{})", args.output.display(), output_ext);
+        println!("Output: {} (.{})", output.display(), output_ext);
         println!("Type: {}", if is_model { "model" } else { "tensor" });
     }
 
     let mut manager = PluginManager::new()?;
+    manager.allow_read(&args.input);
+    manager.allow_write(output);
 
     output::converting(&format!(
         "{} -> .{}",
@@ -108,10 +194,18 @@ pub fn execute(args: ConvertArgs) -> Result<(), Box<dyn std::error::Error>> {
     ));
 
     if is_model {
-        convert_model(&args, &input_ext, &output_ext, &registry, &mut manager)
+        convert_model(&args, output, &input_ext, &output_ext, &registry, &mut manager)?;
     } else {
-        convert_tensor(&args, &input_ext, &output_ext, &registry, &mut manager)
+        convert_tensor(&args, output, &input_ext, &output_ext, &registry, &mut manager)?;
+    }
+    if output::is_json() {
+        output::print_json(&serde_json::json!({
+            "input": args.input.display().to_string(),
+            "output": output.display().to_string(),
+            "type": if is_model { "model" } else { "tensor" },
+        }))?;
     }
+    Ok(())
 }
 
 fn is_model_format(ext: &str) -> bool {
@@ -120,20 +214,24 @@ fn is_model_format(ext: &str) -> bool {
 
 fn convert_model(
     args: &ConvertArgs,
+    output: &Path,
     input_ext: &str,
     output_ext: &str,
     registry: &PluginRegistry,
     manager: &mut PluginManager,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Step 1: Load input model to snapshot
-    let snapshot_path = if input_ext == "hdss" {
+    let plugin = registry.find_model_format_by_extension(input_ext);
+    let (snapshot_path, _onnx_snapshot) = if input_ext == "hdss" {
         // Already a snapshot
-        args.input.clone()
+        (args.input.clone(), None)
+    } else if input_ext == "onnx" && plugin.is_none() {
+        // Builtin importer
+        let temp_file = import_onnx(&args.input)?;
+        (temp_file.path().to_path_buf(), Some(temp_file))
     } else {
         // Use model format plugin to load
-        let plugin = registry
-            .find_model_format_by_extension(input_ext)
-            .ok_or_else(|| format!("No model format plugin for .{}", input_ext))?;
+        let plugin = plugin.ok_or_else(|| format!("No model format plugin for .{}", input_ext))?;
 
         if !plugin.capabilities.load_model.unwrap_or(false) {
             let caps = format_capabilities(&plugin.capabilities);
@@ -146,13 +244,18 @@ fn convert_model(
 
         let client = manager.get_plugin(&plugin.name)?;
         let result = client.load_model(path_to_str(&args.input)?)?;
-        PathBuf::from(result.snapshot_path)
+        client.check_plugin_path(&result.snapshot_path)?;
+        (PathBuf::from(result.snapshot_path), None)
     };
 
     // Step 2: Save to output format
     if output_ext == "hdss" {
-        // Just copy the snapshot
-        std::fs::copy(&snapshot_path, &args.output)?;
+        // Rewrite rather than copy so the weights of a sharded input end up in the output
+        let snapshot = Snapshot::load(&snapshot_path)?;
+        match args.shard_size {
+            Some(size) => hdss::save_sharded(&snapshot, output, size)?,
+            None => snapshot.save(output)?,
+        }
     } else {
         // Use model format plugin to save
         let plugin = registry
@@ -169,72 +272,60 @@ fn convert_model(
         }
 
         let client = manager.get_plugin(&plugin.name)?;
-        client.save_model(path_to_str(&snapshot_path)?, path_to_str(&args.output)?)?;
+        client.save_model(path_to_str(&snapshot_path)?, path_to_str(output)?)?;
     }
 
     output::finished(&format!(
         "{} -> {}",
         args.input.file_name().unwrap_or_default().to_string_lossy(),
-        args.output.file_name().unwrap_or_default().to_string_lossy()
+        output.file_name().unwrap_or_default().to_string_lossy()
     ));
     Ok(())
 }
 
 fn convert_tensor(
     args: &ConvertArgs,
+    output: &Path,
     input_ext: &str,
     output_ext: &str,
     registry: &PluginRegistry,
     manager: &mut PluginManager,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use hodu_core::format::json;
-    use hodu_core::tensor::Tensor;
-    use hodu_core::types::{Device as CoreDevice, Shape};
-    use hodu_plugin::TensorData;
+    let transformed = args.dtype.is_some() || args.permute.is_some() || args.reshape.is_some();
 
     // Step 1: Load input tensor
-    let tensor_data = match input_ext {
-        "hdt" => load_tensor_data(&args.input)?,
-        "json" => {
-            let tensor = json::load(&args.input).map_err(|e| e.to_string())?;
-            let shape = tensor.shape().dims().to_vec();
-            let dtype = core_dtype_to_plugin(tensor.dtype());
-            let data = tensor.to_bytes().map_err(|e| e.to_string())?;
-            TensorData::new(data, shape, dtype)
-        },
-        _ => {
-            // Use tensor format plugin
-            let plugin = registry
-                .find_tensor_format_by_extension(input_ext)
-                .ok_or_else(|| format!("No tensor format plugin for .{}", input_ext))?;
-
-            if !plugin.capabilities.load_tensor.unwrap_or(false) {
-                return Err(format!("Plugin {} doesn't support loading tensors", plugin.name).into());
+    // Importing to .hdt streams the tensor straight to disk, so it never has to fit in memory
+    if output_ext == "hdt" && !transformed && !has_builtin_reader(input_ext, registry) {
+        if let Some(plugin) = registry.find_tensor_format_by_extension(input_ext) {
+            if plugin.capabilities.load_tensor.unwrap_or(false)
+                && plugin_supports(manager, &plugin.name, methods::FORMAT_STREAM_LOAD_TENSOR)?
+            {
+                let client = manager.get_plugin(&plugin.name)?;
+                let bytes = stream_tensor_to_hdt(client, &args.input, output)?;
+                output::finished(&format!(
+                    "{} -> {} ({})",
+                    args.input.file_name().unwrap_or_default().to_string_lossy(),
+                    output.file_name().unwrap_or_default().to_string_lossy(),
+                    output::format_size(bytes as usize)
+                ));
+                return Ok(());
             }
+        }
+    }
+    let tensor = load_tensor(&args.input, registry, manager)?;
 
-            let client = manager.get_plugin(&plugin.name)?;
-            let result = client.load_tensor(path_to_str(&args.input)?)?;
-            load_tensor_data(&result.tensor_path)?
-        },
-    };
+    // Step 2: Apply layout and dtype options
+    let tensor = transform_tensor(tensor, args)?;
 
-    // Step 2: Save to output format
+    // Step 3: Save to output format
+    let output_plugin = registry.find_tensor_format_by_extension(output_ext);
     match output_ext {
-        "hdt" => {
-            save_tensor_data(&tensor_data, &args.output)?;
-        },
-        "json" => {
-            let shape = Shape::new(&tensor_data.shape);
-            let dtype = plugin_dtype_to_core(tensor_data.dtype)?;
-            let tensor =
-                Tensor::from_bytes(&tensor_data.data, shape, dtype, CoreDevice::CPU).map_err(|e| e.to_string())?;
-            json::save(&tensor, &args.output).map_err(|e| e.to_string())?;
-        },
+        "hdt" => hdt::save(&tensor, output)?,
+        "json" => json::save(&tensor, output)?,
+        "npy" if output_plugin.is_none() => npy::save(&tensor, output)?,
         _ => {
             // Use tensor format plugin
-            let plugin = registry
-                .find_tensor_format_by_extension(output_ext)
-                .ok_or_else(|| format!("No tensor format plugin for .{}", output_ext))?;
+            let plugin = output_plugin.ok_or_else(|| format!("No tensor format plugin for .{}", output_ext))?;
 
             if !plugin.capabilities.save_tensor.unwrap_or(false) {
                 return Err(format!("Plugin {} doesn't support saving tensors", plugin.name).into());
@@ -244,22 +335,133 @@ fn convert_tensor(
             let temp_file = NamedTempFile::with_prefix("hodu_convert_")
                 .map_err(|e| format!("Failed to create temp file: {}", e))?;
             let temp_path = temp_file.path();
-            save_tensor_data(&tensor_data, temp_path)?;
+            hdt::save(&tensor, temp_path)?;
 
             let client = manager.get_plugin(&plugin.name)?;
-            client.save_tensor(path_to_str(temp_path)?, path_to_str(&args.output)?)?;
+            client.save_tensor(path_to_str(temp_path)?, path_to_str(output)?)?;
             // temp_file automatically cleans up on drop
         },
     }
 
     output::finished(&format!(
-        "{} -> {}",
+        "{} -> {} ({:?} {})",
         args.input.file_name().unwrap_or_default().to_string_lossy(),
-        args.output.file_name().unwrap_or_default().to_string_lossy()
+        output.file_name().unwrap_or_default().to_string_lossy(),
+        tensor.shape().dims(),
+        tensor.dtype()
     ));
     Ok(())
 }
 
+/// Whether a builtin reader loads tensor files with this extension
+///
+/// .hdt and .json files always load in-process, NumPy files only when no plugin claims them.
+fn has_builtin_reader(ext: &str, registry: &PluginRegistry) -> bool {
+    match ext {
+        "hdt" | "json" => true,
+        "npy" | "npz" => registry.find_tensor_format_by_extension(ext).is_none(),
+        _ => false,
+    }
+}
+
+/// Load a tensor file with a builtin reader, or the tensor format plugin for its extension
+pub(crate) fn load_tensor(
+    path: &Path,
+    registry: &PluginRegistry,
+    manager: &mut PluginManager,
+) -> Result<Tensor, Box<dyn std::error::Error>> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .ok_or_else(|| format!("{} has no extension", path.display()))?;
+
+    if has_builtin_reader(&ext, registry) {
+        return match ext.as_str() {
+            "hdt" => Ok(hdt::load(path).map_err(|e| format!("Failed to load HDT: {}", e))?),
+            "json" => Ok(json::load(path).map_err(|e| format!("Failed to load JSON tensor: {}", e))?),
+            "npy" => Ok(npy::load(path).map_err(|e| format!("Failed to load NPY: {}", e))?),
+            _ => load_single_npz(path),
+        };
+    }
+
+    // Use tensor format plugin
+    let plugin = registry
+        .find_tensor_format_by_extension(&ext)
+        .ok_or_else(|| format!("No tensor format plugin for .{}", ext))?;
+    if !plugin.capabilities.load_tensor.unwrap_or(false) {
+        return Err(format!("Plugin {} doesn't support loading tensors", plugin.name).into());
+    }
+    let client = manager.get_plugin(&plugin.name)?;
+    let result = client.load_tensor(path_to_str(path)?)?;
+    client.check_plugin_path(&result.tensor_path)?;
+    Ok(hdt::load(&result.tensor_path).map_err(|e| format!("Failed to load HDT: {}", e))?)
+}
+
+/// Load the only array of an .npz archive
+fn load_single_npz(path: &Path) -> Result<Tensor, Box<dyn std::error::Error>> {
+    let tensors = npz::load_many(path).map_err(|e| format!("Failed to load NPZ: {}", e))?;
+    if tensors.len() != 1 {
+        let mut names: Vec<&String> = tensors.keys().collect();
+        names.sort();
+        return Err(format!(
+            "NPZ input must contain exactly one array, found {}: {:?}",
+            tensors.len(),
+            names
+        )
+        .into());
+    }
+    Ok(tensors.into_values().next().ok_or("NPZ file is empty")?)
+}
+
+/// Apply `--permute`, `--reshape` and `--dtype`, in that order
+fn transform_tensor(mut tensor: Tensor, args: &ConvertArgs) -> Result<Tensor, Box<dyn std::error::Error>> {
+    if let Some(axes) = &args.permute {
+        let rank = tensor.shape().dims().len();
+        let mut sorted = axes.clone();
+        sorted.sort_unstable();
+        if !sorted.iter().copied().eq(0..rank) {
+            return Err(format!(
+                "--permute must list every axis of the rank {} tensor once (got: {:?})",
+                rank, axes
+            )
+            .into());
+        }
+        let axes: Vec<i32> = axes.iter().map(|&axis| axis as i32).collect();
+        tensor = tensor.permute(&axes)?.contiguous()?;
+    }
+    if let Some(shape) = &args.reshape {
+        let dims = resolve_shape(shape, tensor.size())?;
+        tensor = tensor.reshape(Shape::new(&dims))?;
+    }
+    if let Some(dtype) = args.dtype {
+        tensor = tensor.to_dtype(plugin_dtype_to_core(dtype)?)?;
+    }
+    Ok(tensor)
+}
+
+/// Resolve a `--reshape` shape for a tensor of `numel` elements, inferring a -1 dimension
+fn resolve_shape(shape: &[i64], numel: usize) -> Result<Vec<usize>, String> {
+    let invalid = || format!("Cannot reshape {} elements to {:?}", numel, shape);
+    let mut inferred = None;
+    let mut known = 1usize;
+    for (i, &dim) in shape.iter().enumerate() {
+        match dim {
+            -1 if inferred.is_none() => inferred = Some(i),
+            -1 => return Err("--reshape takes at most one -1 dimension".to_string()),
+            d if d < 0 => return Err(format!("Invalid dimension in --reshape: {}", d)),
+            d => known = known.checked_mul(d as usize).ok_or_else(invalid)?,
+        }
+    }
+    let mut dims: Vec<usize> = shape.iter().map(|&d| d.max(0) as usize).collect();
+    match inferred {
+        Some(i) if known > 0 && numel.is_multiple_of(known) => dims[i] = numel / known,
+        None if known == numel => {},
+        _ => return Err(invalid()),
+    }
+    Ok(dims)
+}
+
 /// Format plugin capabilities as a comma-separated string
 fn format_capabilities(caps: &crate::plugins::PluginCapabilities) -> String {
     let mut list = Vec::new();
@@ -0,0 +1,398 @@
+//! Diff command - compare tensors numerically
+//!
+//! Elements match when `|a - b| <= atol + rtol * |b|`, the second file being the reference, as in
+//! numpy's `allclose`. NaNs match NaNs and infinities match infinities of the same sign. Values are
+//! compared as f64, so tensors of different dtypes can be compared, e.g. the outputs of an f16
+//! backend against an f32 one. `.hdta` archives are compared tensor by tensor, by name.
+//!
+//! The command fails when any tensor differs, so it can gate CI jobs.
+
+use super::convert::load_tensor;
+use crate::errors::{self, ErrorClass};
+use crate::output::{self, colors};
+use crate::plugins::{load_registry, PluginManager};
+use clap::Args;
+use hodu_core::format::hdta;
+use hodu_core::tensor::Tensor;
+use hodu_core::types::DType;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Tensor file to check (.hdt, .json, .npy, .npz, .hdta or a plugin format)
+    pub a: PathBuf,
+
+    /// Reference tensor file, in the same or another format
+    pub b: PathBuf,
+
+    /// Relative tolerance, scaled by the reference value
+    #[arg(long, default_value_t = 1e-5)]
+    pub rtol: f64,
+
+    /// Absolute tolerance
+    #[arg(long, default_value_t = 1e-8)]
+    pub atol: f64,
+
+    /// Mismatching elements to list per tensor
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub show: usize,
+
+    /// Output format (pretty, json)
+    #[arg(short, long, default_value = "pretty")]
+    pub format: String,
+}
+
+/// Elements match when `|a - b| <= atol + rtol * |b|`
+#[derive(Clone, Copy)]
+pub(crate) struct Tolerance {
+    pub rtol: f64,
+    pub atol: f64,
+}
+
+impl Tolerance {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !(self.rtol >= 0.0 && self.atol >= 0.0) {
+            return Err("--rtol and --atol must be non-negative".into());
+        }
+        Ok(())
+    }
+}
+
+/// How a pair of tensors compares
+pub(crate) enum Outcome {
+    /// The tensor is only in one of the archives
+    Missing {
+        in_a: bool,
+    },
+    /// The shapes differ, so the elements can't be compared
+    Shape {
+        a: Vec<usize>,
+        b: Vec<usize>,
+    },
+    Compared(Comparison),
+}
+
+/// Element-wise comparison of two tensors of the same shape
+///
+/// The errors cover the pairs of finite values; relative errors also skip zero references.
+pub(crate) struct Comparison {
+    pub shape: Vec<usize>,
+    pub dtypes: (DType, DType),
+    pub max_abs: f64,
+    pub mean_abs: f64,
+    pub max_rel: f64,
+    pub mean_rel: f64,
+    pub mismatches: usize,
+    /// The first mismatching elements: (index, a, b)
+    pub locations: Vec<(Vec<usize>, f64, f64)>,
+}
+
+/// Outcome of a tensor, with its name for archives
+type NamedOutcome = (Option<String>, Outcome);
+
+impl Comparison {
+    pub fn elements(&self) -> usize {
+        self.shape.iter().product()
+    }
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        matches!(self, Self::Compared(comparison) if comparison.mismatches == 0)
+    }
+
+    /// Status, shapes, dtypes and errors of the comparison, `a` being the checked tensor and `b`
+    /// the reference
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::Missing { in_a } => serde_json::json!({
+                "status": "missing",
+                "in": if *in_a { "a" } else { "b" },
+            }),
+            Self::Shape { a, b } => serde_json::json!({
+                "status": "shape_mismatch",
+                "shape_a": a,
+                "shape_b": b,
+            }),
+            Self::Compared(c) => {
+                let locations: Vec<_> = c
+                    .locations
+                    .iter()
+                    .map(|(index, a, b)| serde_json::json!({ "index": index, "a": a, "b": b }))
+                    .collect();
+                serde_json::json!({
+                    "status": if c.mismatches == 0 { "match" } else { "mismatch" },
+                    "shape": c.shape,
+                    "dtype_a": c.dtypes.0.to_string(),
+                    "dtype_b": c.dtypes.1.to_string(),
+                    "elements": c.elements(),
+                    "max_abs_error": c.max_abs,
+                    "mean_abs_error": c.mean_abs,
+                    "max_rel_error": c.max_rel,
+                    "mean_rel_error": c.mean_rel,
+                    "mismatches": c.mismatches,
+                    "locations": locations,
+                })
+            },
+        }
+    }
+}
+
+pub fn execute(mut args: DiffArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if output::is_json() {
+        args.format = "json".to_string();
+    }
+    let tolerance = Tolerance {
+        rtol: args.rtol,
+        atol: args.atol,
+    };
+    tolerance.validate()?;
+    for path in [&args.a, &args.b] {
+        if !path.exists() {
+            return Err(errors::classified(
+                ErrorClass::Io,
+                format!("File not found: {}", path.display()),
+            ));
+        }
+    }
+
+    let registry = load_registry()?;
+    let mut manager = PluginManager::new()?;
+    manager.allow_read(&args.a);
+    manager.allow_read(&args.b);
+
+    output::comparing(&format!(
+        "{} with {} (rtol {}, atol {})",
+        file_name(&args.a),
+        file_name(&args.b),
+        args.rtol,
+        args.atol
+    ));
+
+    let outcomes = match (is_archive(&args.a), is_archive(&args.b)) {
+        (true, true) => compare_archives(&args, tolerance)?,
+        (false, false) => {
+            let a = load_tensor(&args.a, &registry, &mut manager)?;
+            let b = load_tensor(&args.b, &registry, &mut manager)?;
+            vec![(None, compare(&a, &b, tolerance, args.show)?)]
+        },
+        _ => return Err("An .hdta archive can only be compared with another .hdta archive".into()),
+    };
+
+    match args.format.as_str() {
+        "json" => print_json(&outcomes, &args)?,
+        _ => print_pretty(&outcomes),
+    }
+
+    let failed = outcomes.iter().filter(|(_, outcome)| !outcome.passed()).count();
+    if failed > 0 {
+        return Err(errors::classified(
+            ErrorClass::CheckFailed,
+            format!("{} of {} tensors differ", failed, outcomes.len()),
+        ));
+    }
+    output::finished(&format!(
+        "{} within rtol {} and atol {}",
+        match outcomes.len() {
+            1 => "tensors match".to_string(),
+            n => format!("all {} tensors match", n),
+        },
+        args.rtol,
+        args.atol
+    ));
+    Ok(())
+}
+
+fn is_archive(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("hdta"))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+/// Compare the tensors of two archives by name, in name order
+fn compare_archives(args: &DiffArgs, tolerance: Tolerance) -> Result<Vec<NamedOutcome>, Box<dyn std::error::Error>> {
+    let load = |path: &Path| -> Result<HashMap<String, Tensor>, Box<dyn std::error::Error>> {
+        Ok(hdta::load(path).map_err(|e| format!("Failed to load {}: {}", path.display(), e))?)
+    };
+    let (a, b) = (load(&args.a)?, load(&args.b)?);
+    let names: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let outcome = match (a.get(name), b.get(name)) {
+                (Some(a), Some(b)) => compare(a, b, tolerance, args.show)?,
+                (a, _) => Outcome::Missing { in_a: a.is_some() },
+            };
+            Ok((Some(name.clone()), outcome))
+        })
+        .collect()
+}
+
+/// Compare `a` with the reference `b`, keeping the locations of the first `show` mismatches
+pub(crate) fn compare(
+    a: &Tensor,
+    b: &Tensor,
+    tolerance: Tolerance,
+    show: usize,
+) -> Result<Outcome, Box<dyn std::error::Error>> {
+    let shape = b.shape().dims().to_vec();
+    if a.shape().dims() != shape.as_slice() {
+        return Ok(Outcome::Shape {
+            a: a.shape().dims().to_vec(),
+            b: shape,
+        });
+    }
+
+    let values = |tensor: &Tensor| -> Result<Vec<f64>, Box<dyn std::error::Error>> {
+        Ok(tensor.to_dtype(DType::F64)?.to_flatten_vec::<f64>()?)
+    };
+    let (a_values, b_values) = (values(a)?, values(b)?);
+
+    let mut comparison = Comparison {
+        shape,
+        dtypes: (a.dtype(), b.dtype()),
+        max_abs: 0.0,
+        mean_abs: 0.0,
+        max_rel: 0.0,
+        mean_rel: 0.0,
+        mismatches: 0,
+        locations: Vec::new(),
+    };
+    let (mut abs_sum, mut abs_count, mut rel_sum, mut rel_count) = (0.0, 0usize, 0.0, 0usize);
+    for (index, (&a, &b)) in a_values.iter().zip(&b_values).enumerate() {
+        let matches = if a.is_nan() || b.is_nan() {
+            a.is_nan() && b.is_nan()
+        } else if a.is_infinite() || b.is_infinite() {
+            a == b
+        } else {
+            let abs = (a - b).abs();
+            comparison.max_abs = comparison.max_abs.max(abs);
+            abs_sum += abs;
+            abs_count += 1;
+            if b != 0.0 {
+                let rel = abs / b.abs();
+                comparison.max_rel = comparison.max_rel.max(rel);
+                rel_sum += rel;
+                rel_count += 1;
+            }
+            abs <= tolerance.atol + tolerance.rtol * b.abs()
+        };
+        if !matches {
+            comparison.mismatches += 1;
+            if comparison.locations.len() < show {
+                comparison.locations.push((unravel(index, &comparison.shape), a, b));
+            }
+        }
+    }
+    if abs_count > 0 {
+        comparison.mean_abs = abs_sum / abs_count as f64;
+    }
+    if rel_count > 0 {
+        comparison.mean_rel = rel_sum / rel_count as f64;
+    }
+    Ok(Outcome::Compared(comparison))
+}
+
+/// Multi-dimensional index of a row-major flat index
+fn unravel(mut flat: usize, shape: &[usize]) -> Vec<usize> {
+    let mut index = vec![0; shape.len()];
+    for (i, &dim) in shape.iter().enumerate().rev() {
+        if dim > 0 {
+            index[i] = flat % dim;
+            flat /= dim;
+        }
+    }
+    index
+}
+
+fn print_pretty(outcomes: &[NamedOutcome]) {
+    let use_color = output::supports_color();
+    for (i, (name, outcome)) in outcomes.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let title = name.as_deref().unwrap_or("tensor");
+        let heading = match outcome {
+            Outcome::Missing { in_a: true } => format!("{} only in the first file", title),
+            Outcome::Missing { in_a: false } => format!("{} only in the second file", title),
+            Outcome::Shape { a, b } => format!("{} shapes differ: {:?} vs {:?}", title, a, b),
+            Outcome::Compared(c) if c.dtypes.0 == c.dtypes.1 => format!("{} {:?} {}", title, c.shape, c.dtypes.0),
+            Outcome::Compared(c) => format!("{} {:?} {} vs {}", title, c.shape, c.dtypes.0, c.dtypes.1),
+        };
+        let color = if outcome.passed() { colors::GREEN } else { colors::RED };
+        if use_color {
+            println!("{}{}{}{}", colors::BOLD, color, heading, colors::RESET);
+        } else {
+            println!("{}", heading);
+        }
+
+        let Outcome::Compared(comparison) = outcome else {
+            continue;
+        };
+        println!(
+            "  {:<10} {:<14} {:<10} {}",
+            "max abs",
+            format_error(comparison.max_abs),
+            "mean abs",
+            format_error(comparison.mean_abs)
+        );
+        println!(
+            "  {:<10} {:<14} {:<10} {}",
+            "max rel",
+            format_error(comparison.max_rel),
+            "mean rel",
+            format_error(comparison.mean_rel)
+        );
+        let elements = comparison.elements();
+        println!(
+            "  {:<10} {} of {} ({:.2}%)",
+            "mismatch",
+            comparison.mismatches,
+            elements,
+            comparison.mismatches as f64 * 100.0 / elements.max(1) as f64
+        );
+        for (index, a, b) in &comparison.locations {
+            println!(
+                "    {:<16} {} vs {} (diff {})",
+                format!("{:?}", index),
+                a,
+                b,
+                format_error((a - b).abs())
+            );
+        }
+        if comparison.mismatches > comparison.locations.len() {
+            println!("    ... {} more", comparison.mismatches - comparison.locations.len());
+        }
+    }
+}
+
+/// An error in scientific notation, as shown in reports
+pub(crate) fn format_error(value: f64) -> String {
+    format!("{:.3e}", value)
+}
+
+fn print_json(outcomes: &[NamedOutcome], args: &DiffArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let tensors: Vec<_> = outcomes
+        .iter()
+        .map(|(name, outcome)| {
+            let mut tensor = outcome.to_json();
+            tensor["name"] = serde_json::json!(name);
+            tensor
+        })
+        .collect();
+
+    let report = serde_json::json!({
+        "a": args.a.display().to_string(),
+        "b": args.b.display().to_string(),
+        "rtol": args.rtol,
+        "atol": args.atol,
+        "passed": outcomes.iter().all(|(_, outcome)| outcome.passed()),
+        "tensors": tensors,
+    });
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
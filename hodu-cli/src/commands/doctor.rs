@@ -1,12 +1,41 @@
-//! Doctor command - diagnose available devices and buildable targets on this host
+//! Doctor command - diagnose the plugin setup, devices, toolchains and caches on this host
+//!
+//! Every check prints a line, and problems it can suggest a fix for are listed at the end.
 
+use crate::commands::clean::dir_stats;
+use crate::commands::plugin::get_plugins_dir;
+use crate::config::Settings;
 use crate::output::{self, colors};
-use crate::plugins::{load_registry, PluginManager};
-use hodu_plugin::current_host_triple;
+use crate::plugins::{load_registry, ClientError, PluginManager, PluginRegistry, ProcessError};
+use hodu_plugin::rpc::PROTOCOL_VERSION;
+use hodu_plugin::{current_host_triple, device_type, is_tool_available};
+use std::path::Path;
+
+/// Use shorter timeout for doctor diagnostics (10 seconds per plugin operation)
+const DOCTOR_TIMEOUT_SECS: u64 = 10;
+
+/// Cache size above which `hodu clean` is suggested
+const LARGE_CACHE_BYTES: usize = 1024 * 1024 * 1024;
+
+/// Toolchains backends build with: (name, tools with `|` between alternatives, device type needing it)
+const TOOLCHAINS: &[(&str, &str, Option<&str>)] = &[
+    ("C compiler", "clang|gcc|cc", None),
+    ("CUDA", "nvcc", Some("cuda")),
+    ("Metal", "xcrun", Some("metal")),
+];
+
+/// Outcome of a single check
+#[derive(Clone, Copy)]
+pub(crate) enum Check {
+    Ok,
+    Warn,
+    Fail,
+}
 
 pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
     let host = current_host_triple();
     let use_color = output::supports_color();
+    let mut fixes = Vec::new();
 
     // Header
     if use_color {
@@ -16,10 +45,181 @@ pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
     }
     println!();
 
-    let registry = load_registry()?;
+    print_section_header("Registry", use_color);
+    let registry = match load_registry() {
+        Ok(registry) => Some(registry),
+        Err(e) => {
+            print_check(
+                Check::Fail,
+                &format!("failed to load the plugin registry: {}", e),
+                use_color,
+            );
+            fixes.push(
+                "Restore ~/.hodu/plugins.json, or remove it and run `hodu plugin sync` to reinstall from the lockfile"
+                    .to_string(),
+            );
+            None
+        },
+    };
+    if let Some(registry) = &registry {
+        check_registry(registry, use_color, &mut fixes)?;
+    }
+    println!();
+
+    if let Some(registry) = &registry {
+        let mut manager = PluginManager::with_timeout(DOCTOR_TIMEOUT_SECS)?;
+        check_plugins(registry, &mut manager, use_color, &mut fixes);
+        show_backends(registry, &mut manager, use_color, &mut fixes);
+    }
+
+    check_toolchains(registry.as_ref(), use_color, &mut fixes);
+    check_cache(use_color, &mut fixes)?;
+
+    print_section_header("Fixes", use_color);
+    if fixes.is_empty() {
+        print_check(Check::Ok, "no problems found", use_color);
+    } else {
+        for fix in &fixes {
+            println!("  • {}", fix);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that installed binaries exist and dependencies are met
+fn check_registry(
+    registry: &PluginRegistry,
+    use_color: bool,
+    fixes: &mut Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let plugins_dir = get_plugins_dir()?;
+    let mut healthy = true;
+
+    for plugin in &registry.plugins {
+        // Remote plugins have no binary
+        let binary_path = plugins_dir.join(&plugin.name).join(&plugin.binary);
+        if !plugin.source.is_remote() && !binary_path.exists() {
+            print_check(
+                Check::Fail,
+                &format!("{}: binary not found: {}", plugin.name, binary_path.display()),
+                use_color,
+            );
+            fixes.push(format!(
+                "Reinstall {0}: `hodu plugin remove {0}`, then install it again",
+                plugin.name
+            ));
+            healthy = false;
+        }
+
+        if !plugin.enabled {
+            continue;
+        }
+        for dep in &plugin.dependencies {
+            match registry.find(dep) {
+                Some(entry) if entry.enabled => {},
+                Some(_) => {
+                    print_check(
+                        Check::Fail,
+                        &format!("{}: dependency {} is disabled", plugin.name, dep),
+                        use_color,
+                    );
+                    fixes.push(format!(
+                        "Enable {} for {}: `hodu plugin enable {}`",
+                        dep, plugin.name, dep
+                    ));
+                    healthy = false;
+                },
+                None => {
+                    print_check(
+                        Check::Fail,
+                        &format!("{}: dependency {} is not installed", plugin.name, dep),
+                        use_color,
+                    );
+                    fixes.push(format!(
+                        "Install {} for {}: `hodu plugin install {}`",
+                        dep, plugin.name, dep
+                    ));
+                    healthy = false;
+                },
+            }
+        }
+    }
+
+    if healthy {
+        let enabled = registry.plugins.iter().filter(|p| p.enabled).count();
+        print_check(
+            Check::Ok,
+            &format!("{} plugins installed, {} enabled", registry.plugins.len(), enabled),
+            use_color,
+        );
+    }
+    Ok(())
+}
+
+/// Spawn and initialize every enabled plugin to check it starts and speaks a compatible protocol
+fn check_plugins(registry: &PluginRegistry, manager: &mut PluginManager, use_color: bool, fixes: &mut Vec<String>) {
+    let enabled: Vec<_> = registry.plugins.iter().filter(|p| p.enabled).collect();
+    if enabled.is_empty() {
+        return;
+    }
+
+    print_section_header("Plugins", use_color);
+    for plugin in enabled {
+        match manager.get_plugin(&plugin.name) {
+            Ok(_) => {
+                let protocol = manager
+                    .get_info(&plugin.name)
+                    .map(|info| info.protocol_version.clone())
+                    .unwrap_or_default();
+                print_check(
+                    Check::Ok,
+                    &format!("{} v{} (protocol {})", plugin.name, plugin.version, protocol),
+                    use_color,
+                );
+            },
+            Err(ProcessError::Client(ClientError::ProtocolMismatch { plugin: protocol, .. })) => {
+                print_check(
+                    Check::Fail,
+                    &format!(
+                        "{} v{}: protocol {} is incompatible with {}",
+                        plugin.name, plugin.version, protocol, PROTOCOL_VERSION
+                    ),
+                    use_color,
+                );
+                fixes.push(format!(
+                    "Update {0} to a release built for this hodu: `hodu plugin update {0}`",
+                    plugin.name
+                ));
+            },
+            // Already reported with a fix by the registry check
+            Err(e @ ProcessError::BinaryNotFound(_)) => {
+                print_check(
+                    Check::Fail,
+                    &format!("{} v{}: failed to start: {}", plugin.name, plugin.version, e),
+                    use_color,
+                );
+            },
+            Err(e) => {
+                print_check(
+                    Check::Fail,
+                    &format!("{} v{}: failed to start: {}", plugin.name, plugin.version, e),
+                    use_color,
+                );
+                fixes.push(format!(
+                    "Reinstall {0}, or disable it until it is fixed: `hodu plugin disable {0}`",
+                    plugin.name
+                ));
+            },
+        }
+    }
+    println!();
+}
 
+/// Show available devices and buildable targets of the backend plugins
+fn show_backends(registry: &PluginRegistry, manager: &mut PluginManager, use_color: bool, fixes: &mut Vec<String>) {
     // Collect backend plugins
-    let backends: Vec<_> = registry.backends().collect();
+    let backends: Vec<_> = registry.backends().filter(|p| p.enabled).collect();
 
     if backends.is_empty() {
         if use_color {
@@ -28,9 +228,8 @@ pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
             println!("No backend plugins installed.");
         }
         println!();
-        println!("Install a backend plugin:");
-        println!("  hodu plugin install aot-cpu");
-        return Ok(());
+        fixes.push("Install a backend plugin: `hodu plugin install aot-cpu`".to_string());
+        return;
     }
 
     // Show available devices
@@ -38,9 +237,6 @@ pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut has_devices = false;
     for plugin in &backends {
-        if !plugin.enabled {
-            continue;
-        }
         for device in &plugin.capabilities.devices {
             if use_color {
                 println!(
@@ -64,29 +260,19 @@ pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
     println!();
 
     // Show buildable targets per plugin
+    let builders: Vec<_> = backends
+        .iter()
+        .filter(|p| p.capabilities.builder.unwrap_or(false))
+        .collect();
+    if builders.is_empty() {
+        return;
+    }
     print_section_header("Buildable Targets", use_color);
 
-    // Use shorter timeout for doctor diagnostics (10 seconds per plugin operation)
-    const DOCTOR_TIMEOUT_SECS: u64 = 10;
-    let mut manager = PluginManager::with_timeout(DOCTOR_TIMEOUT_SECS)?;
-
-    for plugin in &backends {
-        if !plugin.enabled {
-            continue;
-        }
-
-        // Check if plugin has build capability
-        if !plugin.capabilities.builder.unwrap_or(false) {
+    for plugin in builders {
+        // Plugins that failed to start were reported above
+        let Ok(client) = manager.get_plugin(&plugin.name) else {
             continue;
-        }
-
-        // Get plugin client and fetch targets
-        let client = match manager.get_plugin(&plugin.name) {
-            Ok(c) => c,
-            Err(e) => {
-                eprintln!("  Warning: Failed to connect to plugin '{}': {}", plugin.name, e);
-                continue;
-            },
         };
 
         match client.list_targets() {
@@ -127,11 +313,116 @@ pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
             },
         }
     }
+    println!();
+}
+
+/// Check for the toolchains backends build with; a missing one is a problem only if a backend targets its device
+fn check_toolchains(registry: Option<&PluginRegistry>, use_color: bool, fixes: &mut Vec<String>) {
+    print_section_header("Toolchains", use_color);
+
+    for &(name, tools, device) in TOOLCHAINS {
+        // Metal only exists on macOS
+        if device == Some("metal") && !cfg!(target_os = "macos") {
+            continue;
+        }
+        if let Some(tool) = tools.split('|').find(|tool| is_tool_available(tool)) {
+            print_check(Check::Ok, &format!("{} ({})", name, tool), use_color);
+            continue;
+        }
+
+        let users: Vec<&str> = match (registry, device) {
+            (Some(registry), Some(device)) => registry
+                .backends()
+                .filter(|p| p.enabled)
+                .filter(|p| p.capabilities.devices.iter().any(|d| device_type(d) == Some(device)))
+                .map(|p| p.name.as_str())
+                .collect(),
+            _ => Vec::new(),
+        };
+        if users.is_empty() {
+            print_check(Check::Warn, &format!("{} (not found)", name), use_color);
+        } else {
+            print_check(
+                Check::Fail,
+                &format!("{} (not found, needed by {})", name, users.join(", ")),
+                use_color,
+            );
+            fixes.push(format!("Install the {} toolchain and put `{}` on PATH", name, tools));
+        }
+    }
+    println!();
+}
+
+/// Show the size of each backend's build cache
+fn check_cache(use_color: bool, fixes: &mut Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    print_section_header("Cache", use_color);
+
+    let settings = Settings::load()?;
+    let cache_dir = settings.cache_dir();
+    if !cache_dir.is_dir() {
+        println!("  (empty)");
+        println!();
+        return Ok(());
+    }
 
+    let mut entries: Vec<_> = std::fs::read_dir(cache_dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    entries.sort();
+
+    let mut total = 0;
+    for path in &entries {
+        let (size, files) = dir_stats(path)?;
+        total += size;
+        println!(
+            "  {:<32} {:>10} ({} files)",
+            file_name(path),
+            output::format_size(size),
+            files
+        );
+    }
+    let check = if total > LARGE_CACHE_BYTES {
+        Check::Warn
+    } else {
+        Check::Ok
+    };
+    print_check(
+        check,
+        &format!("{} in {}", output::format_size(total), cache_dir.display()),
+        use_color,
+    );
+    if total > LARGE_CACHE_BYTES {
+        fixes.push(format!(
+            "Free {} of build cache: `hodu clean`",
+            output::format_size(total)
+        ));
+    }
+    println!();
     Ok(())
 }
 
-fn print_section_header(title: &str, use_color: bool) {
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+pub(crate) fn print_check(check: Check, message: &str, use_color: bool) {
+    let (mark, color) = match check {
+        Check::Ok => ("✓", colors::GREEN),
+        Check::Warn => ("!", colors::YELLOW),
+        Check::Fail => ("✗", colors::RED),
+    };
+    if use_color {
+        println!("  {}{}{} {}", color, mark, colors::RESET, message);
+    } else {
+        println!("  {} {}", mark, message);
+    }
+}
+
+pub(crate) fn print_section_header(title: &str, use_color: bool) {
     if use_color {
         println!("{}{}{}{}", colors::BOLD, colors::CYAN, title, colors::RESET);
     } else {
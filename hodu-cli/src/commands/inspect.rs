@@ -1,35 +1,54 @@
 //! Inspect command - examine model and tensor files
 //!
-//! This command inspects model and tensor files, optionally using format plugins.
+//! This command inspects model and tensor files, optionally using format plugins. Models are
+//! summarized by their signatures, parameters per dtype, op histogram and graph depth, tensors by
+//! their shape and value statistics.
 
+mod summary;
+
+pub(crate) use summary::{op_label, TensorStats};
+
+use crate::errors::{self, ErrorClass};
 use crate::output::{self, colors};
 use crate::plugins::{load_registry, PluginManager};
 use crate::tensor::load_tensor_data;
-use crate::utils::path_to_str;
+use crate::utils::{path_to_str, plugin_dtype_to_core};
 use clap::Args;
-use hodu_core::format::hdt;
+use hodu_core::format::{hdt, hdta};
 use hodu_core::ops::Op;
 use hodu_core::snapshot::Snapshot;
 use hodu_core::tensor::Tensor;
+use hodu_core::types::{Device as CoreDevice, Shape};
 use std::path::{Path, PathBuf};
+use summary::ModelSummary;
 
 #[derive(Args)]
 pub struct InspectArgs {
     /// File to inspect (.hdss, .hdt, .json, .onnx, etc.)
     pub file: PathBuf,
 
-    /// Verbose output
+    /// Verbose output (every node and constant, and the full snapshot in JSON)
     #[arg(short, long)]
     pub verbose: bool,
 
     /// Output format (pretty, json)
     #[arg(short, long, default_value = "pretty")]
     pub format: String,
+
+    /// Shorthand for --format json
+    #[arg(long, conflicts_with = "format")]
+    pub json: bool,
 }
 
-pub fn execute(args: InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
+pub fn execute(mut args: InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.json || output::is_json() {
+        args.format = "json".to_string();
+    }
     if !args.file.exists() {
-        return Err(format!("File not found: {}", args.file.display()).into());
+        return Err(errors::classified(
+            ErrorClass::Io,
+            format!("File not found: {}", args.file.display()),
+        ));
     }
 
     let ext = args
@@ -49,32 +68,113 @@ pub fn execute(args: InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
     match ext.as_str() {
         "hdss" => inspect_hdss(&args),
         "hdt" => inspect_hdt(&args),
+        "hdta" => inspect_hdta(&args),
         "json" => inspect_json_tensor(&args),
+        // Without a plugin, .onnx falls back to the builtin importer
+        "onnx" if load_registry()?.find_model_format_by_extension("onnx").is_none() => inspect_onnx(&args),
         _ => inspect_with_plugin(&args, &ext),
     }
 }
 
 fn inspect_hdss(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
+    // Weight shards aren't needed to describe the graph
+    let (snapshot, _) =
+        hodu_core::format::hdss::load_lazy(&args.file).map_err(|e| format!("Failed to load snapshot: {}", e))?;
+    print_snapshot(args, snapshot, None)
+}
+
+fn inspect_onnx(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let snapshot =
+        hodu_core::format::onnx::load(&args.file).map_err(|e| format!("Failed to import ONNX model: {}", e))?;
+    print_snapshot(args, snapshot, None)
+}
+
+/// Print a model summary, `via` naming the format plugin that imported it
+fn print_snapshot(args: &InspectArgs, snapshot: Snapshot, via: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
     let use_color = output::supports_color();
-    let snapshot = Snapshot::load(&args.file).map_err(|e| format!("Failed to load snapshot: {}", e))?;
+    let summary = ModelSummary::new(&snapshot);
+    let (param_tensors, param_count, param_bytes) = summary.total_params();
 
     if args.format == "json" {
-        println!("{}", serde_json::to_string_pretty(&snapshot)?);
+        let inputs: Vec<_> = snapshot
+            .inputs
+            .iter()
+            .map(|input| {
+                serde_json::json!({
+                    "name": input.name,
+                    "shape": input.shape.dims(),
+                    "dtype": input.dtype.to_string(),
+                })
+            })
+            .collect();
+        let outputs: Vec<_> = summary
+            .outputs
+            .iter()
+            .map(|(name, signature)| {
+                serde_json::json!({
+                    "name": name,
+                    "shape": signature.as_ref().map(|s| s.shape.to_json()),
+                    "dtype": signature.as_ref().map(|s| s.dtype.to_string()),
+                })
+            })
+            .collect();
+        let by_dtype: Vec<_> = summary
+            .params
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "dtype": p.dtype.to_string(),
+                    "tensors": p.tensors,
+                    "count": p.elements,
+                    "size_bytes": p.bytes,
+                })
+            })
+            .collect();
+        let ops: Vec<_> = summary
+            .ops
+            .iter()
+            .map(|(op, count)| serde_json::json!({ "op": op, "count": count }))
+            .collect();
+
+        let mut report = serde_json::json!({
+            "file": args.file.display().to_string(),
+            "name": snapshot.name,
+            "metadata": snapshot.metadata,
+            "inputs": inputs,
+            "outputs": outputs,
+            "parameters": {
+                "tensors": param_tensors,
+                "count": param_count,
+                "size_bytes": param_bytes,
+                "by_dtype": by_dtype,
+            },
+            "nodes": snapshot.nodes.len(),
+            "depth": summary.depth,
+            "ops": ops,
+        });
+        if let Some(plugin) = via {
+            report["plugin"] = serde_json::json!(plugin);
+        }
+        if args.verbose {
+            report["snapshot"] = serde_json::to_value(&snapshot)?;
+        }
+        println!("{}", serde_json::to_string_pretty(&report)?);
         return Ok(());
     }
 
     // Header
     let model_name = snapshot.name.as_deref().unwrap_or("unnamed");
+    let detail = match via {
+        Some(plugin) => format!("via {}", plugin),
+        None => format_file_size(&args.file),
+    };
     if use_color {
-        println!(
-            "{}{}{} {}",
-            colors::BOLD,
-            model_name,
-            colors::RESET,
-            format_file_size(&args.file)
-        );
+        println!("{}{}{} {}", colors::BOLD, model_name, colors::RESET, detail);
     } else {
-        println!("{} {}", model_name, format_file_size(&args.file));
+        println!("{} {}", model_name, detail);
+    }
+    for (key, value) in &snapshot.metadata {
+        println!("  {}: {}", key, output::sanitize_for_terminal(value));
     }
     println!();
 
@@ -83,22 +183,77 @@ fn inspect_hdss(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
     for input in &snapshot.inputs {
         print_tensor_row(
             &input.name,
-            input.shape.dims(),
-            &format!("{:?}", input.dtype),
+            &format!("{:?}", input.shape.dims()),
+            &input.dtype.to_string(),
             use_color,
         );
     }
     println!();
 
-    // Constants (if any)
-    if !snapshot.constants.is_empty() {
+    // Outputs
+    print_section_header("Outputs", summary.outputs.len(), use_color);
+    for (name, signature) in &summary.outputs {
+        let (shape, dtype) = match signature {
+            Some(signature) => (signature.shape.to_string(), signature.dtype.to_string()),
+            None => ("?".to_string(), "?".to_string()),
+        };
+        if use_color {
+            println!(
+                "  {}→{} {:<16} {} {}{}{}",
+                colors::GREEN,
+                colors::RESET,
+                name,
+                shape,
+                colors::CYAN,
+                dtype,
+                colors::RESET
+            );
+        } else {
+            println!("  → {:<16} {} {}", name, shape, dtype);
+        }
+    }
+    println!();
+
+    // Parameters, per dtype
+    if !summary.params.is_empty() {
+        print_section_header("Parameters", param_tensors, use_color);
+        let mut rows: Vec<_> = summary
+            .params
+            .iter()
+            .map(|p| (p.dtype.to_string(), p.elements, p.bytes))
+            .collect();
+        if rows.len() > 1 {
+            rows.push(("total".to_string(), param_count, param_bytes));
+        }
+        for (label, elements, bytes) in rows {
+            let count = format!("{} params", format_number(elements));
+            if use_color {
+                println!(
+                    "  {}•{} {:<16} {:<16} {}{}{}",
+                    colors::CYAN,
+                    colors::RESET,
+                    label,
+                    count,
+                    colors::YELLOW,
+                    output::format_size(bytes),
+                    colors::RESET
+                );
+            } else {
+                println!("  • {:<16} {:<16} {}", label, count, output::format_size(bytes));
+            }
+        }
+        println!();
+    }
+
+    // Constants, one by one
+    if args.verbose && !snapshot.constants.is_empty() {
         print_section_header("Constants", snapshot.constants.len(), use_color);
         for constant in &snapshot.constants {
             let name = constant.name.as_deref().unwrap_or("(unnamed)");
-            let size_str = output::format_size(constant.data.len());
+            let size_str = output::format_size(constant.shape.size() * constant.dtype.size_in_bytes());
             if use_color {
                 println!(
-                    "  {}•{} {:<16} {:?} {}{:?}{} {}{}{}",
+                    "  {}•{} {:<16} {:?} {}{}{} {}{}{}",
                     colors::CYAN,
                     colors::RESET,
                     name,
@@ -112,7 +267,7 @@ fn inspect_hdss(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
                 );
             } else {
                 println!(
-                    "  • {:<16} {:?} {:?} {}",
+                    "  • {:<16} {:?} {} {}",
                     name,
                     constant.shape.dims(),
                     constant.dtype,
@@ -123,61 +278,53 @@ fn inspect_hdss(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
         println!();
     }
 
-    // Outputs
-    print_section_header("Outputs", snapshot.targets.len(), use_color);
-    for target in &snapshot.targets {
+    // Nodes, as a histogram of ops
+    print_section_header("Graph", snapshot.nodes.len(), use_color);
+    if use_color {
+        println!("  {}Depth{} {}", colors::CYAN, colors::RESET, summary.depth);
+    } else {
+        println!("  Depth {}", summary.depth);
+    }
+    for (op, count) in &summary.ops {
         if use_color {
-            println!("  {}→{} {}", colors::GREEN, colors::RESET, target.name);
+            println!(
+                "  {}•{} {:<24} {}×{}{}",
+                colors::CYAN,
+                colors::RESET,
+                op,
+                colors::YELLOW,
+                count,
+                colors::RESET
+            );
         } else {
-            println!("  → {}", target.name);
+            println!("  • {:<24} ×{}", op, count);
         }
     }
-    println!();
 
-    // Nodes
-    print_section_header("Graph", snapshot.nodes.len(), use_color);
     if args.verbose {
+        println!();
+        print_section_header("Nodes", snapshot.nodes.len(), use_color);
         for (i, node) in snapshot.nodes.iter().enumerate() {
             let op_str = format_op(&node.op);
+            let name = node
+                .name
+                .as_deref()
+                .map(|n| format!(" ({})", output::sanitize_for_terminal(n)))
+                .unwrap_or_default();
             if use_color {
                 println!(
-                    "  {}[{:3}]{} {} {}→ {:?}{}",
+                    "  {}[{:3}]{} {}{} {}→ {:?}{}",
                     colors::CYAN,
                     i,
                     colors::RESET,
                     op_str,
+                    name,
                     colors::YELLOW,
                     node.output_id,
                     colors::RESET
                 );
             } else {
-                println!("  [{:3}] {} → {:?}", i, op_str, node.output_id);
-            }
-        }
-    } else {
-        // Group by op category
-        let mut categories = std::collections::HashMap::new();
-        for node in &snapshot.nodes {
-            let cat = get_op_category(&node.op);
-            *categories.entry(cat).or_insert(0) += 1;
-        }
-
-        let mut sorted: Vec<_> = categories.into_iter().collect();
-        sorted.sort_by(|a, b| b.1.cmp(&a.1));
-
-        for (cat, count) in sorted {
-            if use_color {
-                println!(
-                    "  {}•{} {:<20} {}×{}{}",
-                    colors::CYAN,
-                    colors::RESET,
-                    cat,
-                    colors::YELLOW,
-                    count,
-                    colors::RESET
-                );
-            } else {
-                println!("  • {:<20} ×{}", cat, count);
+                println!("  [{:3}] {}{} → {:?}", i, op_str, name, node.output_id);
             }
         }
     }
@@ -190,6 +337,60 @@ fn inspect_hdt(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
     print_tensor_info(&tensor, &args.file, args.format == "json")
 }
 
+fn inspect_hdta(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let archive = hdta::Archive::open(&args.file).map_err(|e| format!("Failed to load HDTA: {}", e))?;
+    let entries = archive.entries();
+
+    if args.format == "json" {
+        let tensors: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "name": entry.name,
+                    "shape": entry.shape,
+                    "dtype": entry.dtype.to_string(),
+                    "size_bytes": entry.size_in_bytes()
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::json!({ "file": args.file.display().to_string(), "tensors": tensors })
+        );
+        return Ok(());
+    }
+
+    let use_color = output::supports_color();
+    let filename = args.file.file_name().unwrap_or_default().to_string_lossy();
+    let total: usize = entries.iter().map(|entry| entry.size_in_bytes()).sum();
+    if use_color {
+        println!("{}{}{}", colors::BOLD, filename, colors::RESET);
+        println!();
+        println!(
+            "  {}Tensors{} {} ({})",
+            colors::CYAN,
+            colors::RESET,
+            entries.len(),
+            output::format_size(total)
+        );
+    } else {
+        println!("{}", filename);
+        println!();
+        println!("  Tensors {} ({})", entries.len(), output::format_size(total));
+    }
+    for entry in entries {
+        println!(
+            "  • {:<24} {:<6} {:?} {}",
+            entry.name,
+            entry.dtype.to_string(),
+            entry.shape,
+            output::format_size(entry.size_in_bytes())
+        );
+    }
+
+    Ok(())
+}
+
 fn inspect_json_tensor(args: &InspectArgs) -> Result<(), Box<dyn std::error::Error>> {
     use hodu_core::format::json;
     let tensor = json::load(&args.file).map_err(|e| format!("Failed to load JSON tensor: {}", e))?;
@@ -202,40 +403,51 @@ fn print_tensor_info(tensor: &Tensor, path: &Path, as_json: bool) -> Result<(),
     let shape = tensor_shape.dims();
     let dtype = tensor.dtype();
     let numel: usize = shape.iter().product();
-    let size_bytes = tensor.to_bytes().map(|b| b.len()).unwrap_or(0);
+    let size_bytes = numel * dtype.size_in_bytes();
+    let stats = TensorStats::new(tensor).map_err(|e| format!("Failed to compute tensor statistics: {}", e))?;
 
     if as_json {
         println!(
             "{}",
-            serde_json::json!({
+            serde_json::to_string_pretty(&serde_json::json!({
                 "file": path.display().to_string(),
                 "shape": shape,
-                "dtype": format!("{:?}", dtype),
+                "dtype": dtype.to_string(),
                 "numel": numel,
-                "size_bytes": size_bytes
-            })
+                "size_bytes": size_bytes,
+                "min": stats.min,
+                "max": stats.max,
+                "mean": stats.mean,
+                "nan_count": stats.nan_count,
+                "inf_count": stats.inf_count,
+            }))?
         );
+        return Ok(());
+    }
+
+    let filename = path.file_name().unwrap_or_default().to_string_lossy();
+    let rows = [
+        ("Shape", format!("{:?}", shape)),
+        ("DType", dtype.to_string()),
+        ("Elements", format_number(numel)),
+        ("Size", output::format_size(size_bytes)),
+        ("Min", format_value(stats.min)),
+        ("Max", format_value(stats.max)),
+        ("Mean", format_value(stats.mean)),
+        ("NaN", stats.nan_count.to_string()),
+        ("Inf", stats.inf_count.to_string()),
+    ];
+    if use_color {
+        println!("{}{}{}", colors::BOLD, filename, colors::RESET);
     } else {
-        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        println!("{}", filename);
+    }
+    println!();
+    for (label, value) in rows {
         if use_color {
-            println!("{}{}{}", colors::BOLD, filename, colors::RESET);
-            println!();
-            println!("  {}Shape{} {:?}", colors::CYAN, colors::RESET, shape);
-            println!("  {}DType{} {:?}", colors::CYAN, colors::RESET, dtype);
-            println!("  {}Elements{} {}", colors::CYAN, colors::RESET, format_number(numel));
-            println!(
-                "  {}Size{} {}",
-                colors::CYAN,
-                colors::RESET,
-                output::format_size(size_bytes)
-            );
+            println!("  {}{:<8}{} {}", colors::CYAN, label, colors::RESET, value);
         } else {
-            println!("{}", filename);
-            println!();
-            println!("  Shape    {:?}", shape);
-            println!("  DType    {:?}", dtype);
-            println!("  Elements {}", format_number(numel));
-            println!("  Size     {}", output::format_size(size_bytes));
+            println!("  {:<8} {}", label, value);
         }
     }
 
@@ -243,13 +455,12 @@ fn print_tensor_info(tensor: &Tensor, path: &Path, as_json: bool) -> Result<(),
 }
 
 fn inspect_with_plugin(args: &InspectArgs, ext: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let use_color = output::supports_color();
-
     // Create plugin manager and get format plugin by extension
     let mut manager = PluginManager::new()?;
+    manager.allow_read(&args.file);
     let client = manager.get_format_for_extension(ext).map_err(|_| {
         format!(
-            "No plugin found for '.{}' format.\n\nBuiltin formats: .hdss, .hdt, .json\n\nInstall a format plugin:\n  hodu plugin install --git <url>",
+            "No plugin found for '.{}' format.\n\nBuiltin formats: .hdss, .onnx, .hdt, .hdta, .json\n\nInstall a format plugin:\n  hodu plugin install --git <url>",
             ext
         )
     })?;
@@ -266,108 +477,29 @@ fn inspect_with_plugin(args: &InspectArgs, ext: &str) -> Result<(), Box<dyn std:
     // Try to load as model first
     if plugin_entry.capabilities.load_model.unwrap_or(false) {
         let result = client.load_model(path_to_str(&args.file)?)?;
+        client.check_plugin_path(&result.snapshot_path)?;
 
         // Load the snapshot from the temp path
         let snapshot = Snapshot::load(&result.snapshot_path).map_err(|e| format!("Failed to load snapshot: {}", e))?;
 
-        if args.format == "json" {
-            println!("{}", serde_json::to_string_pretty(&snapshot)?);
-        } else {
-            // Header
-            let model_name = snapshot.name.as_deref().unwrap_or("unnamed");
-            if use_color {
-                println!(
-                    "{}{}{} {}via {}{}",
-                    colors::BOLD,
-                    model_name,
-                    colors::RESET,
-                    colors::CYAN,
-                    plugin_entry.name,
-                    colors::RESET
-                );
-            } else {
-                println!("{} via {}", model_name, plugin_entry.name);
-            }
-            println!();
-
-            // Inputs
-            print_section_header("Inputs", snapshot.inputs.len(), use_color);
-            for input in &snapshot.inputs {
-                print_tensor_row(
-                    &input.name,
-                    input.shape.dims(),
-                    &format!("{:?}", input.dtype),
-                    use_color,
-                );
-            }
-            println!();
-
-            // Outputs
-            print_section_header("Outputs", snapshot.targets.len(), use_color);
-            for target in &snapshot.targets {
-                if use_color {
-                    println!("  {}→{} {}", colors::GREEN, colors::RESET, target.name);
-                } else {
-                    println!("  → {}", target.name);
-                }
-            }
-            println!();
-
-            // Nodes summary
-            print_section_header("Graph", snapshot.nodes.len(), use_color);
-            if args.verbose {
-                for (i, node) in snapshot.nodes.iter().enumerate() {
-                    let op_str = format_op(&node.op);
-                    if use_color {
-                        println!("  {}[{:3}]{} {}", colors::CYAN, i, colors::RESET, op_str);
-                    } else {
-                        println!("  [{:3}] {}", i, op_str);
-                    }
-                }
-            }
-        }
-        return Ok(());
+        return print_snapshot(args, snapshot, Some(&plugin_entry.name));
     }
 
     // Try to load as tensor
     if plugin_entry.capabilities.load_tensor.unwrap_or(false) {
         let result = client.load_tensor(path_to_str(&args.file)?)?;
+        client.check_plugin_path(&result.tensor_path)?;
 
         // Load tensor data from path
         let tensor_data = load_tensor_data(&result.tensor_path).map_err(|e| format!("Failed to load tensor: {}", e))?;
 
-        if args.format == "json" {
-            println!(
-                "{}",
-                serde_json::json!({
-                    "file": args.file.display().to_string(),
-                    "shape": tensor_data.shape,
-                    "dtype": tensor_data.dtype.name(),
-                    "size_bytes": tensor_data.data.len()
-                })
-            );
-        } else {
-            let filename = args.file.file_name().unwrap_or_default().to_string_lossy();
-            if use_color {
-                println!("{}{}{}", colors::BOLD, filename, colors::RESET);
-                println!();
-                println!("  {}Shape{} {:?}", colors::CYAN, colors::RESET, tensor_data.shape);
-                println!("  {}DType{} {}", colors::CYAN, colors::RESET, tensor_data.dtype.name());
-                println!(
-                    "  {}Size{} {}",
-                    colors::CYAN,
-                    colors::RESET,
-                    output::format_size(tensor_data.data.len())
-                );
-            } else {
-                println!("{}", filename);
-                println!();
-                println!("  Shape {:?}", tensor_data.shape);
-                println!("  DType {}", tensor_data.dtype.name());
-                println!("  Size  {}", output::format_size(tensor_data.data.len()));
-            }
-        }
-        return Ok(());
+        let tensor = Tensor::from_bytes(
+            &tensor_data.data,
+            Shape::new(&tensor_data.shape),
+            plugin_dtype_to_core(tensor_data.dtype)?,
+            CoreDevice::CPU,
+        )?;
+        return print_tensor_info(&tensor, &args.file, args.format == "json");
     }
 
     Err(format!(
@@ -395,10 +527,10 @@ fn print_section_header(title: &str, count: usize, use_color: bool) {
     }
 }
 
-fn print_tensor_row(name: &str, shape: &[usize], dtype: &str, use_color: bool) {
+fn print_tensor_row(name: &str, shape: &str, dtype: &str, use_color: bool) {
     if use_color {
         println!(
-            "  {}•{} {:<16} {:?} {}{}{}",
+            "  {}•{} {:<16} {} {}{}{}",
             colors::CYAN,
             colors::RESET,
             name,
@@ -408,7 +540,7 @@ fn print_tensor_row(name: &str, shape: &[usize], dtype: &str, use_color: bool) {
             colors::RESET
         );
     } else {
-        println!("  • {:<16} {:?} {}", name, shape, dtype);
+        println!("  • {:<16} {} {}", name, shape, dtype);
     }
 }
 
@@ -439,40 +571,6 @@ fn format_op(op: &Op) -> String {
     }
 }
 
-fn get_op_category(op: &Op) -> &'static str {
-    match op {
-        Op::Binary(_) => "Binary",
-        Op::BinaryLogical(_) => "BinaryLogical",
-        Op::BitwiseBinary(_) => "BitwiseBinary",
-        Op::BitwiseUnary(_) => "BitwiseUnary",
-        Op::BitwiseUnaryScalar(_) => "BitwiseUnaryScalar",
-        Op::Cmp(_) => "Compare",
-        Op::CmpScalar(_) => "CmpScalar",
-        Op::Unary(_) => "Unary",
-        Op::UnaryLogical(_) => "UnaryLogical",
-        Op::UnaryScalar(_) => "UnaryScalar",
-        Op::Matrix(_) => "Matrix",
-        Op::Linalg(_) => "Linalg",
-        Op::Reduce(_) => "Reduce",
-        Op::Concat(_) => "Concat",
-        Op::Split(_) => "Split",
-        Op::Indexing(_) => "Indexing",
-        Op::Conv(_) => "Conv",
-        Op::Windowing(_) => "Windowing",
-        Op::Resize(_) => "Resize",
-        Op::Padding(_) => "Padding",
-        Op::Scan(_) => "Scan",
-        Op::Sort(_) => "Sort",
-        Op::Einsum(_) => "Einsum",
-        Op::Shape(_) => "Shape",
-        Op::ShapeScalars(_) => "ShapeScalars",
-        Op::ShapeMemory(_) => "ShapeMemory",
-        Op::Cast(_) => "Cast",
-        Op::Memory(_) => "Memory",
-        Op::Dummy => "Dummy",
-    }
-}
-
 fn format_number(n: usize) -> String {
     if n >= 1_000_000_000 {
         format!("{:.2}B", n as f64 / 1_000_000_000.0)
@@ -485,6 +583,17 @@ fn format_number(n: usize) -> String {
     }
 }
 
+/// Format a tensor statistic, switching to scientific notation for very small or large values
+pub(crate) fn format_value(value: Option<f64>) -> String {
+    match value {
+        None => "-".to_string(),
+        Some(v) if v == 0.0 || !v.is_finite() => v.to_string(),
+        Some(v) if v.abs() < 1e-4 || v.abs() >= 1e9 => format!("{:.4e}", v),
+        Some(v) if v.fract() == 0.0 => format!("{}", v),
+        Some(v) => format!("{:.6}", v),
+    }
+}
+
 fn format_file_size(path: &Path) -> String {
     std::fs::metadata(path)
         .map(|m| output::format_size(m.len() as usize))
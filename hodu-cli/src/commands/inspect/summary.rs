@@ -0,0 +1,233 @@
+//! Model and tensor statistics shown by `hodu inspect`
+//!
+//! Op counts and parameters cover the nodes and constants of control-flow subgraphs too, while the
+//! graph depth and the output signatures describe the top-level graph.
+
+use super::format_op;
+use hodu_core::error::HoduResult;
+use hodu_core::ops::OpParams;
+use hodu_core::snapshot::{Snapshot, SnapshotNode};
+use hodu_core::tensor::Tensor;
+use hodu_core::types::DType;
+use std::collections::HashMap;
+
+/// Parameters (constants) of one dtype
+pub(super) struct DTypeParams {
+    pub dtype: DType,
+    pub tensors: usize,
+    pub elements: usize,
+    pub bytes: usize,
+}
+
+/// Shape of a graph tensor, symbolic when it depends on the data
+pub(super) enum SignatureShape {
+    Static(Vec<usize>),
+    Symbolic(String),
+}
+
+impl SignatureShape {
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::Static(dims) => serde_json::json!(dims),
+            Self::Symbolic(shape) => serde_json::json!(shape),
+        }
+    }
+}
+
+impl std::fmt::Display for SignatureShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Static(dims) => write!(f, "{:?}", dims),
+            Self::Symbolic(shape) => write!(f, "{}", shape),
+        }
+    }
+}
+
+/// Shape and dtype of a graph tensor
+pub(super) struct Signature {
+    pub shape: SignatureShape,
+    pub dtype: DType,
+}
+
+pub(super) struct ModelSummary {
+    /// Node count per op, most frequent first
+    pub ops: Vec<(String, usize)>,
+    /// Parameter totals per dtype, largest first
+    pub params: Vec<DTypeParams>,
+    /// Signature of every target, `None` when no input, constant or node produces it
+    pub outputs: Vec<(String, Option<Signature>)>,
+    /// Longest chain of nodes from the inputs and constants to any tensor
+    pub depth: usize,
+}
+
+impl ModelSummary {
+    pub fn new(snapshot: &Snapshot) -> Self {
+        let mut ops = HashMap::new();
+        let mut params: HashMap<DType, DTypeParams> = HashMap::new();
+        for_each_graph(snapshot, &mut |graph| {
+            for node in &graph.nodes {
+                *ops.entry(op_label(node)).or_insert(0) += 1;
+            }
+            for constant in &graph.constants {
+                let entry = params.entry(constant.dtype).or_insert(DTypeParams {
+                    dtype: constant.dtype,
+                    tensors: 0,
+                    elements: 0,
+                    bytes: 0,
+                });
+                entry.tensors += 1;
+                entry.elements += constant.shape.size();
+                entry.bytes += constant.shape.size() * constant.dtype.size_in_bytes();
+            }
+        });
+
+        let mut ops: Vec<_> = ops.into_iter().collect();
+        ops.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let mut params: Vec<_> = params.into_values().collect();
+        params.sort_by(|a, b| {
+            b.bytes
+                .cmp(&a.bytes)
+                .then_with(|| a.dtype.to_string().cmp(&b.dtype.to_string()))
+        });
+
+        let mut signatures = signatures(snapshot);
+        let outputs = snapshot
+            .targets
+            .iter()
+            .map(|target| (target.name.clone(), signatures.remove(&target.id.0)))
+            .collect();
+
+        Self {
+            ops,
+            params,
+            outputs,
+            depth: graph_depth(snapshot),
+        }
+    }
+
+    /// Parameter totals over all dtypes: (tensors, elements, bytes)
+    pub fn total_params(&self) -> (usize, usize, usize) {
+        self.params.iter().fold((0, 0, 0), |(tensors, elements, bytes), p| {
+            (tensors + p.tensors, elements + p.elements, bytes + p.bytes)
+        })
+    }
+}
+
+/// Visit a snapshot and every control-flow subgraph in it
+fn for_each_graph<'a>(snapshot: &'a Snapshot, visit: &mut impl FnMut(&'a Snapshot)) {
+    visit(snapshot);
+    for subgraph in &snapshot.subgraphs {
+        for_each_graph(subgraph, visit);
+    }
+}
+
+/// Histogram key of a node: its op, with the op name for custom ops
+pub(crate) fn op_label(node: &SnapshotNode) -> String {
+    match &node.params {
+        Some(OpParams::Custom(p)) => format!("Custom[{}]", p.name),
+        _ => format_op(&node.op),
+    }
+}
+
+/// Signatures of every tensor of the top-level graph, by tensor id
+fn signatures(snapshot: &Snapshot) -> HashMap<usize, Signature> {
+    let mut signatures = HashMap::new();
+    for input in &snapshot.inputs {
+        let shape = SignatureShape::Static(input.shape.dims().to_vec());
+        signatures.insert(
+            input.id.0,
+            Signature {
+                shape,
+                dtype: input.dtype,
+            },
+        );
+    }
+    for constant in &snapshot.constants {
+        let shape = SignatureShape::Static(constant.shape.dims().to_vec());
+        signatures.insert(
+            constant.id.0,
+            Signature {
+                shape,
+                dtype: constant.dtype,
+            },
+        );
+    }
+    for node in &snapshot.nodes {
+        let shape = match &node.symbolic_output_layout {
+            Some(layout) => SignatureShape::Symbolic(layout.shape().to_string()),
+            None => SignatureShape::Static(node.output_layout.shape().dims().to_vec()),
+        };
+        signatures.insert(
+            node.output_id.0,
+            Signature {
+                shape,
+                dtype: node.output_dtype,
+            },
+        );
+    }
+    signatures
+}
+
+/// Length of the longest chain of nodes in the top-level graph
+///
+/// Nodes are stored in execution order, so every input is produced before the node reading it.
+fn graph_depth(snapshot: &Snapshot) -> usize {
+    let mut depths: HashMap<usize, usize> = HashMap::new();
+    let mut max_depth = 0;
+    for node in &snapshot.nodes {
+        let depth = 1 + node
+            .input_ids
+            .iter()
+            .filter_map(|id| depths.get(&id.0))
+            .max()
+            .copied()
+            .unwrap_or(0);
+        depths.insert(node.output_id.0, depth);
+        max_depth = max_depth.max(depth);
+    }
+    max_depth
+}
+
+/// Value statistics of a tensor
+pub(crate) struct TensorStats {
+    /// Smallest value that isn't NaN
+    pub min: Option<f64>,
+    /// Largest value that isn't NaN
+    pub max: Option<f64>,
+    /// Mean of the values that aren't NaN
+    pub mean: Option<f64>,
+    pub nan_count: usize,
+    pub inf_count: usize,
+}
+
+impl TensorStats {
+    pub fn new(tensor: &Tensor) -> HoduResult<Self> {
+        let values = tensor.to_dtype(DType::F64)?.to_flatten_vec::<f64>()?;
+        let mut stats = Self {
+            min: None,
+            max: None,
+            mean: None,
+            nan_count: 0,
+            inf_count: 0,
+        };
+        let mut sum = 0.0;
+        let mut count = 0usize;
+        for value in values {
+            if value.is_nan() {
+                stats.nan_count += 1;
+                continue;
+            }
+            if value.is_infinite() {
+                stats.inf_count += 1;
+            }
+            stats.min = Some(stats.min.map_or(value, |min| min.min(value)));
+            stats.max = Some(stats.max.map_or(value, |max| max.max(value)));
+            sum += value;
+            count += 1;
+        }
+        if count > 0 {
+            stats.mean = Some(sum / count as f64);
+        }
+        Ok(stats)
+    }
+}
@@ -0,0 +1,48 @@
+//! Model command - check a model before a long run or build
+
+mod validate;
+
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+pub use validate::validate_model;
+
+#[derive(Args)]
+pub struct ModelArgs {
+    #[command(subcommand)]
+    pub command: ModelCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ModelCommands {
+    /// Verify a model's graph and shapes, and check it against a backend's declared capabilities
+    Validate(ValidateArgs),
+}
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Model file (.onnx, .hdss, etc.)
+    pub model: PathBuf,
+
+    /// Device the model would run or be built for (cpu, metal, cuda::0)
+    #[arg(short, long)]
+    pub device: Option<String>,
+
+    /// Backend plugin to check against (default: the one `hodu run` or `hodu build` would pick)
+    #[arg(long)]
+    pub backend: Option<String>,
+
+    /// Check building the model, as `hodu build` would, instead of running it
+    #[arg(long)]
+    pub build: bool,
+
+    /// Target triple to check building for (default: the host)
+    #[arg(long, requires = "build")]
+    pub target: Option<String>,
+}
+
+pub fn execute(args: ModelArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        ModelCommands::Validate(args) => validate_model(args),
+    }
+}
@@ -0,0 +1,768 @@
+//! `hodu model validate` - find what would fail a run or build before starting it
+//!
+//! Three passes, each reporting every problem it finds rather than stopping at the first:
+//!
+//! - the verifier checks the graph is well formed: every tensor is defined once, by an input, a
+//!   constant or a node, before any node reads it; targets exist; sharded weights are present
+//! - shape inference recomputes the output shape of elementwise, matrix, reduce and concat nodes
+//!   from their inputs, and checks every node read its inputs with the shapes they were defined
+//!   with; other ops keep the shapes recorded at capture
+//! - the backend check holds the model against what the backend plugin declared when installed:
+//!   running or building, the device and target, and who executes each custom op
+//!
+//! Control-flow subgraphs are verified and shape-checked like the top-level graph.
+
+use super::ValidateArgs;
+use crate::commands::build::{find_backend_by_name, find_builder_backend};
+use crate::commands::doctor::{print_check, print_section_header, Check};
+use crate::commands::inspect::op_label;
+use crate::commands::run::{
+    find_backend_plugin, find_model_format_plugin, interpreter_device, load_model_snapshot, parse_device,
+};
+use crate::config::Settings;
+use crate::errors::{self, ErrorClass};
+use crate::output::{self, colors};
+use crate::plugins::{load_registry, PluginEntry, PluginManager, PluginRegistry};
+use hodu_core::format::hdss;
+use hodu_core::ops::{MatrixOp, Op, OpParams};
+use hodu_core::snapshot::{Snapshot, SnapshotNode};
+use hodu_core::types::DType;
+use hodu_plugin::current_host_triple;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Which pass found a problem
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Pass {
+    Verifier,
+    Shapes,
+    Backend,
+}
+
+#[derive(serde::Serialize)]
+struct Problem {
+    pass: Pass,
+    /// Node the problem is at, as `node 3 (Binary[add])`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node: Option<String>,
+    message: String,
+}
+
+/// Shape and dtype of a tensor, without a shape when it depends on the data
+#[derive(Clone)]
+struct Signature {
+    dims: Option<Vec<usize>>,
+    dtype: DType,
+}
+
+/// Output shape of a node as inferred from its inputs
+enum Inferred {
+    Dims(Vec<usize>),
+    /// The inputs can't go together
+    Invalid(String),
+    /// No rule for the op, or an input shape is unknown
+    Unknown,
+}
+
+#[derive(Default)]
+struct Report {
+    problems: Vec<Problem>,
+    nodes: usize,
+    /// Nodes whose output shape was recomputed
+    inferred: usize,
+    /// Node count per op label, over all graphs
+    ops: BTreeMap<String, usize>,
+    /// Names of the custom ops, over all graphs
+    custom_ops: BTreeSet<String>,
+}
+
+impl Report {
+    fn add(&mut self, pass: Pass, node: Option<&str>, message: String) {
+        self.problems.push(Problem {
+            pass,
+            node: node.map(str::to_string),
+            message,
+        });
+    }
+
+    fn count(&self, pass: Pass) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| std::mem::discriminant(&problem.pass) == std::mem::discriminant(&pass))
+            .count()
+    }
+}
+
+/// How the chosen backend handles one op
+#[derive(serde::Serialize)]
+struct OpSupport {
+    op: String,
+    count: usize,
+    supported: bool,
+    support: String,
+}
+
+pub fn validate_model(args: ValidateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let extension = args
+        .model
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    let registry = load_registry()?;
+    let format_plugin = find_model_format_plugin(extension.as_deref(), &registry)?;
+
+    let mut manager = PluginManager::new()?;
+    manager.allow_read(&args.model);
+    let model_name = args
+        .model
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| args.model.display().to_string());
+    let (snapshot_path, _onnx_snapshot) = load_model_snapshot(&args.model, format_plugin, &model_name, &mut manager)?;
+    let (snapshot, weights) = hdss::load_lazy(&snapshot_path)?;
+
+    let settings = Settings::load()?;
+    let device = parse_device(&settings.device(args.device.as_deref()))?;
+    let backend_name = settings.backend(args.backend.as_deref());
+    let target = args.target.clone().unwrap_or_else(|| current_host_triple().to_string());
+    let action = match args.build {
+        true => format!("build for {} on {}", target, device),
+        false => format!("run on {}", device),
+    };
+    output::validating(&format!("{} ({})", model_name, action));
+
+    let mut report = Report::default();
+    check_graph(&snapshot, "", &mut report);
+    let external = snapshot.constants.iter().filter(|c| c.is_external()).count();
+    match &weights {
+        None if external > 0 => report.add(
+            Pass::Verifier,
+            None,
+            format!(
+                "{} constants are stored in weight shards, but the model has no weight manifest",
+                external
+            ),
+        ),
+        Some(weights) => {
+            for shard in weights.shard_paths().iter().filter(|shard| !shard.exists()) {
+                report.add(
+                    Pass::Verifier,
+                    None,
+                    format!("weight shard {} is missing", shard.display()),
+                );
+            }
+        },
+        None => {},
+    }
+
+    // The backend `hodu run` or `hodu build` would pick
+    let backend = match (args.build, &backend_name) {
+        (true, Some(name)) => find_backend_by_name(name, &registry),
+        (true, None) => find_builder_backend(&device, &registry),
+        (false, _) => find_backend_plugin(&backend_name, &device, &registry),
+    };
+    let backend = match backend {
+        Ok(entry) => {
+            check_backend(entry, &args, &device, &target, &mut report);
+            Some(entry)
+        },
+        Err(e) => {
+            let message = e.to_string();
+            let first_line = message.lines().next().unwrap_or_default().to_string();
+            report.add(Pass::Backend, None, first_line);
+            None
+        },
+    };
+    let ops = op_support(&report, backend, &registry, &args, &device);
+    for op in ops.iter().filter(|op| !op.supported) {
+        report.add(Pass::Backend, None, format!("{}: {}", op.op, op.support));
+    }
+    let outputs = output_signatures(&snapshot);
+
+    if output::is_json() {
+        output::print_json(&serde_json::json!({
+            "model": model_name,
+            "mode": if args.build { "build" } else { "run" },
+            "device": device,
+            "target": args.build.then_some(&target),
+            "backend": backend.map(|entry| serde_json::json!({ "name": entry.name, "version": entry.version })),
+            "nodes": report.nodes,
+            "inferred": report.inferred,
+            "ops": ops,
+            "outputs": outputs
+                .iter()
+                .map(|(name, signature)| serde_json::json!({
+                    "name": name,
+                    "shape": signature.as_ref().and_then(|s| s.dims.clone()),
+                    "dtype": signature.as_ref().map(|s| s.dtype.to_string()),
+                }))
+                .collect::<Vec<_>>(),
+            "problems": report.problems,
+            "compatible": report.problems.is_empty(),
+        }))?;
+    } else {
+        print_report(&snapshot, &report, backend, &ops, &outputs, &args, &device);
+    }
+
+    if !report.problems.is_empty() {
+        return Err(errors::classified(
+            ErrorClass::CheckFailed,
+            format!("{} problem(s) found in {}", report.problems.len(), model_name),
+        ));
+    }
+    output::finished(&format!("{} can {}", model_name, action));
+    Ok(())
+}
+
+/// Verify one graph and infer its shapes, recursing into control-flow subgraphs
+fn check_graph(graph: &Snapshot, location: &str, report: &mut Report) {
+    let mut defined: HashMap<usize, Signature> = HashMap::new();
+    let mut names = HashSet::new();
+    for input in &graph.inputs {
+        if !names.insert(input.name.as_str()) {
+            report.add(
+                Pass::Verifier,
+                None,
+                format!("{}input '{}' is declared twice", location, input.name),
+            );
+        }
+        let signature = Signature {
+            dims: Some(input.shape.dims().to_vec()),
+            dtype: input.dtype,
+        };
+        if defined.insert(input.id.0, signature).is_some() {
+            report.add(
+                Pass::Verifier,
+                None,
+                format!("{}input '{}' redefines tensor {}", location, input.name, input.id.0),
+            );
+        }
+    }
+    for constant in &graph.constants {
+        let signature = Signature {
+            dims: Some(constant.shape.dims().to_vec()),
+            dtype: constant.dtype,
+        };
+        if defined.insert(constant.id.0, signature).is_some() {
+            report.add(
+                Pass::Verifier,
+                None,
+                format!("{}a constant redefines tensor {}", location, constant.id.0),
+            );
+        }
+    }
+
+    for (index, node) in graph.nodes.iter().enumerate() {
+        report.nodes += 1;
+        let label = op_label(node);
+        *report.ops.entry(label.clone()).or_insert(0) += 1;
+        let at = match &node.name {
+            Some(name) => format!("{}node {} ({} '{}')", location, index, label, name),
+            None => format!("{}node {} ({})", location, index, label),
+        };
+
+        if node.input_layouts.len() != node.input_ids.len() {
+            report.add(
+                Pass::Verifier,
+                Some(&at),
+                format!(
+                    "has {} inputs but {} input layouts",
+                    node.input_ids.len(),
+                    node.input_layouts.len()
+                ),
+            );
+        }
+
+        let mut inputs = Vec::new();
+        for (i, id) in node.input_ids.iter().enumerate() {
+            let Some(signature) = defined.get(&id.0) else {
+                report.add(
+                    Pass::Verifier,
+                    Some(&at),
+                    format!(
+                        "reads tensor {}, which no input, constant or earlier node defines",
+                        id.0
+                    ),
+                );
+                inputs.push(None);
+                continue;
+            };
+            if let (Some(dims), Some(layout)) = (&signature.dims, node.input_layouts.get(i)) {
+                if layout.shape().dims() != dims.as_slice() {
+                    report.add(
+                        Pass::Shapes,
+                        Some(&at),
+                        format!(
+                            "reads input {} as {:?}, but tensor {} is {:?}",
+                            i,
+                            layout.shape().dims(),
+                            id.0,
+                            dims
+                        ),
+                    );
+                }
+            }
+            inputs.push(Some(signature.clone()));
+        }
+
+        match &node.params {
+            Some(OpParams::Custom(params)) => {
+                report.custom_ops.insert(params.name.clone());
+            },
+            Some(OpParams::If(params)) => {
+                for (role, index) in [("then branch", params.then_branch), ("else branch", params.else_branch)] {
+                    check_subgraph(graph, index, role, &at, report);
+                }
+            },
+            Some(OpParams::While(params)) => {
+                for (role, index) in [("condition", params.cond), ("body", params.body)] {
+                    check_subgraph(graph, index, role, &at, report);
+                }
+            },
+            _ if matches!(node.op, Op::Custom | Op::ControlFlow(_)) => {
+                report.add(Pass::Verifier, Some(&at), "is missing its parameters".to_string());
+            },
+            _ => {},
+        }
+
+        let recorded = node.output_layout.shape().dims().to_vec();
+        if node.symbolic_output_layout.is_none() {
+            let inputs: Option<Vec<Signature>> = inputs.into_iter().collect();
+            match inputs.map_or(Inferred::Unknown, |inputs| infer(node, &inputs)) {
+                Inferred::Dims(dims) => {
+                    report.inferred += 1;
+                    if dims != recorded {
+                        report.add(
+                            Pass::Shapes,
+                            Some(&at),
+                            format!("outputs {:?}, but its inputs give {:?}", recorded, dims),
+                        );
+                    }
+                },
+                Inferred::Invalid(message) => report.add(Pass::Shapes, Some(&at), message),
+                Inferred::Unknown => {},
+            }
+        }
+
+        let signature = Signature {
+            dims: node.symbolic_output_layout.is_none().then_some(recorded),
+            dtype: node.output_dtype,
+        };
+        if defined.insert(node.output_id.0, signature).is_some() {
+            report.add(
+                Pass::Verifier,
+                Some(&at),
+                format!("redefines tensor {}", node.output_id.0),
+            );
+        }
+    }
+
+    for (index, subgraph) in graph.subgraphs.iter().enumerate() {
+        check_graph(subgraph, &format!("{}subgraph {}: ", location, index), report);
+    }
+
+    let mut names = HashSet::new();
+    for target in &graph.targets {
+        if !names.insert(target.name.as_str()) {
+            report.add(
+                Pass::Verifier,
+                None,
+                format!("{}output '{}' is declared twice", location, target.name),
+            );
+        }
+        if !defined.contains_key(&target.id.0) {
+            report.add(
+                Pass::Verifier,
+                None,
+                format!(
+                    "{}output '{}' is tensor {}, which nothing defines",
+                    location, target.name, target.id.0
+                ),
+            );
+        }
+    }
+}
+
+/// Report a control-flow node whose `role` subgraph isn't stored in `graph`
+fn check_subgraph(graph: &Snapshot, index: usize, role: &str, at: &str, report: &mut Report) {
+    if index >= graph.subgraphs.len() {
+        report.add(
+            Pass::Verifier,
+            Some(at),
+            format!(
+                "refers to subgraph {} as its {}, but the graph has {}",
+                index,
+                role,
+                graph.subgraphs.len()
+            ),
+        );
+    }
+}
+
+/// Output shape of `node` from the signatures of its inputs
+fn infer(node: &SnapshotNode, inputs: &[Signature]) -> Inferred {
+    let dims: Vec<&[usize]> = inputs.iter().filter_map(|input| input.dims.as_deref()).collect();
+    if dims.len() != inputs.len() {
+        return Inferred::Unknown;
+    }
+    let arity = |expected: usize| match inputs.len() == expected {
+        true => Ok(()),
+        false => Err(Inferred::Invalid(format!(
+            "takes {} inputs but has {}",
+            expected,
+            inputs.len()
+        ))),
+    };
+
+    match &node.op {
+        Op::Binary(_) | Op::BinaryLogical(_) | Op::BitwiseBinary(_) | Op::Cmp(_) => {
+            if let Err(invalid) = arity(2) {
+                return invalid;
+            }
+            if inputs[0].dtype != inputs[1].dtype {
+                return Inferred::Invalid(format!("combines {} and {} inputs", inputs[0].dtype, inputs[1].dtype));
+            }
+            match broadcast(dims[0], dims[1]) {
+                Some(dims) => Inferred::Dims(dims),
+                None => Inferred::Invalid(format!("cannot broadcast {:?} with {:?}", dims[0], dims[1])),
+            }
+        },
+        Op::Unary(_)
+        | Op::UnaryLogical(_)
+        | Op::UnaryScalar(_)
+        | Op::CmpScalar(_)
+        | Op::BitwiseUnary(_)
+        | Op::BitwiseUnaryScalar(_)
+        | Op::Cast(_) => match arity(1) {
+            Ok(()) => Inferred::Dims(dims[0].to_vec()),
+            Err(invalid) => invalid,
+        },
+        Op::Matrix(MatrixOp::Matmul | MatrixOp::Dot) => {
+            if let Err(invalid) = arity(2) {
+                return invalid;
+            }
+            if inputs[0].dtype != inputs[1].dtype {
+                return Inferred::Invalid(format!("multiplies {} by {}", inputs[0].dtype, inputs[1].dtype));
+            }
+            let (lhs, rhs) = (dims[0], dims[1]);
+            if lhs.len() < 2 || rhs.len() < 2 {
+                return Inferred::Unknown;
+            }
+            let (k_lhs, k_rhs) = (lhs[lhs.len() - 1], rhs[rhs.len() - 2]);
+            if k_lhs != k_rhs {
+                return Inferred::Invalid(format!(
+                    "multiplies {:?} by {:?}: inner dimensions {} and {} differ",
+                    lhs, rhs, k_lhs, k_rhs
+                ));
+            }
+            match broadcast(&lhs[..lhs.len() - 2], &rhs[..rhs.len() - 2]) {
+                Some(mut out) => {
+                    out.extend([lhs[lhs.len() - 2], rhs[rhs.len() - 1]]);
+                    Inferred::Dims(out)
+                },
+                None => Inferred::Invalid(format!("cannot broadcast the batch dims of {:?} and {:?}", lhs, rhs)),
+            }
+        },
+        Op::Reduce(_) => {
+            let Some(OpParams::Reduce(params)) = &node.params else {
+                return Inferred::Invalid("is missing its reduce parameters".to_string());
+            };
+            if let Err(invalid) = arity(1) {
+                return invalid;
+            }
+            let input = dims[0];
+            let mut reduced = Vec::new();
+            for dim in &params.dims {
+                match normalize_dim(dim.to_i64(), input.len()) {
+                    Some(dim) => reduced.push(dim),
+                    None => {
+                        return Inferred::Invalid(format!(
+                            "reduces dim {} of a {}-dimensional input",
+                            dim.to_i64(),
+                            input.len()
+                        ))
+                    },
+                }
+            }
+            if reduced.is_empty() {
+                return Inferred::Unknown;
+            }
+            let out: Vec<usize> = input
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &size)| match (reduced.contains(&i), params.keep_dim) {
+                    (false, _) => Some(size),
+                    (true, true) => Some(1),
+                    (true, false) => None,
+                })
+                .collect();
+            // How a reduction to no dims is stored differs between ops
+            match out.is_empty() {
+                true => Inferred::Unknown,
+                false => Inferred::Dims(out),
+            }
+        },
+        Op::Concat(_) => {
+            let Some(OpParams::Concat(params)) = &node.params else {
+                return Inferred::Invalid("is missing its concat parameters".to_string());
+            };
+            let Some(first) = dims.first() else {
+                return Inferred::Invalid("concatenates no inputs".to_string());
+            };
+            let Some(axis) = normalize_dim(params.dim.to_i64(), first.len()) else {
+                return Inferred::Invalid(format!(
+                    "concatenates along dim {} of {}-dimensional inputs",
+                    params.dim.to_i64(),
+                    first.len()
+                ));
+            };
+            let mut out = first.to_vec();
+            for other in &dims[1..] {
+                let compatible = other.len() == first.len()
+                    && other
+                        .iter()
+                        .zip(first.iter())
+                        .enumerate()
+                        .all(|(i, (a, b))| i == axis || a == b);
+                if !compatible {
+                    return Inferred::Invalid(format!("concatenates {:?} and {:?} along dim {}", first, other, axis));
+                }
+                out[axis] += other[axis];
+            }
+            Inferred::Dims(out)
+        },
+        _ => Inferred::Unknown,
+    }
+}
+
+/// Broadcast two shapes aligned at their trailing dims, `None` if they don't broadcast
+fn broadcast(lhs: &[usize], rhs: &[usize]) -> Option<Vec<usize>> {
+    let ndim = lhs.len().max(rhs.len());
+    let dim_at = |dims: &[usize], i: usize| (i + dims.len()).checked_sub(ndim).map_or(1, |i| dims[i]);
+    (0..ndim)
+        .map(|i| match (dim_at(lhs, i), dim_at(rhs, i)) {
+            (a, b) if a == b || b == 1 => Some(a),
+            (1, b) => Some(b),
+            _ => None,
+        })
+        .collect()
+}
+
+fn normalize_dim(dim: i64, ndim: usize) -> Option<usize> {
+    let dim = if dim < 0 { dim + ndim as i64 } else { dim };
+    (0..ndim as i64).contains(&dim).then_some(dim as usize)
+}
+
+/// Check what the backend declared against running or building on the device
+fn check_backend(entry: &PluginEntry, args: &ValidateArgs, device: &str, target: &str, report: &mut Report) {
+    let caps = &entry.capabilities;
+    if args.build && caps.builder != Some(true) {
+        report.add(Pass::Backend, None, format!("{} does not build models", entry.name));
+    }
+    if !args.build && caps.runner != Some(true) {
+        report.add(Pass::Backend, None, format!("{} does not run models", entry.name));
+    }
+    if !caps.devices.is_empty() && !caps.devices.iter().any(|d| d.eq_ignore_ascii_case(device)) {
+        report.add(
+            Pass::Backend,
+            None,
+            format!(
+                "{} supports devices {}, not {}",
+                entry.name,
+                caps.devices.join(", "),
+                device
+            ),
+        );
+    }
+    if args.build && !caps.targets.is_empty() && !caps.targets.iter().any(|t| t == target) {
+        report.add(
+            Pass::Backend,
+            None,
+            format!("{} builds for {}, not {}", entry.name, caps.targets.join(", "), target),
+        );
+    }
+}
+
+/// How the backend would handle each op of the model
+///
+/// Core ops are implemented by every backend. A run executes custom ops in-process on the CPU
+/// through the plugins declaring them, while a build needs the backend to declare them itself.
+fn op_support(
+    report: &Report,
+    backend: Option<&PluginEntry>,
+    registry: &PluginRegistry,
+    args: &ValidateArgs,
+    device: &str,
+) -> Vec<OpSupport> {
+    report
+        .ops
+        .iter()
+        .map(|(label, &count)| {
+            let custom = report
+                .custom_ops
+                .iter()
+                .find(|name| *label == format!("Custom[{}]", name));
+            let (supported, support) = match (custom, backend) {
+                (None, _) => (true, "core op".to_string()),
+                (Some(_), None) => (false, "no backend to check against".to_string()),
+                (Some(name), Some(backend)) if args.build => match backend.capabilities.custom_ops.contains(name) {
+                    true => (true, format!("declared by {}", backend.name)),
+                    false => (false, format!("{} does not declare this custom op", backend.name)),
+                },
+                (Some(name), Some(_)) => match registry.find_custom_op(name) {
+                    None => (false, "no installed plugin declares this custom op".to_string()),
+                    Some(plugin) if interpreter_device(device).is_err() => (
+                        false,
+                        format!(
+                            "runs in-process through {}, which this build cannot run on {}",
+                            plugin.name, device
+                        ),
+                    ),
+                    Some(plugin) => (true, format!("runs in-process through {}", plugin.name)),
+                },
+            };
+            OpSupport {
+                op: label.clone(),
+                count,
+                supported,
+                support,
+            }
+        })
+        .collect()
+}
+
+/// Shape and dtype of each output of the top-level graph
+fn output_signatures(snapshot: &Snapshot) -> Vec<(String, Option<Signature>)> {
+    let mut signatures: HashMap<usize, Signature> = HashMap::new();
+    for input in &snapshot.inputs {
+        signatures.insert(
+            input.id.0,
+            Signature {
+                dims: Some(input.shape.dims().to_vec()),
+                dtype: input.dtype,
+            },
+        );
+    }
+    for constant in &snapshot.constants {
+        signatures.insert(
+            constant.id.0,
+            Signature {
+                dims: Some(constant.shape.dims().to_vec()),
+                dtype: constant.dtype,
+            },
+        );
+    }
+    for node in &snapshot.nodes {
+        signatures.insert(
+            node.output_id.0,
+            Signature {
+                dims: node
+                    .symbolic_output_layout
+                    .is_none()
+                    .then(|| node.output_layout.shape().dims().to_vec()),
+                dtype: node.output_dtype,
+            },
+        );
+    }
+    snapshot
+        .targets
+        .iter()
+        .map(|target| (target.name.clone(), signatures.get(&target.id.0).cloned()))
+        .collect()
+}
+
+fn print_report(
+    snapshot: &Snapshot,
+    report: &Report,
+    backend: Option<&PluginEntry>,
+    ops: &[OpSupport],
+    outputs: &[(String, Option<Signature>)],
+    args: &ValidateArgs,
+    device: &str,
+) {
+    let use_color = output::supports_color();
+    let print_problems = |pass: Pass| {
+        for problem in report
+            .problems
+            .iter()
+            .filter(|p| std::mem::discriminant(&p.pass) == std::mem::discriminant(&pass))
+        {
+            let message = match &problem.node {
+                Some(node) => format!("{}: {}", node, problem.message),
+                None => problem.message.clone(),
+            };
+            print_check(Check::Fail, &message, use_color);
+        }
+    };
+
+    print_section_header("Verifier", use_color);
+    if report.count(Pass::Verifier) == 0 {
+        print_check(
+            Check::Ok,
+            &format!(
+                "{} nodes, {} inputs, {} constants and {} outputs are well formed",
+                report.nodes,
+                snapshot.inputs.len(),
+                snapshot.constants.len(),
+                snapshot.targets.len()
+            ),
+            use_color,
+        );
+    }
+    print_problems(Pass::Verifier);
+    println!();
+
+    print_section_header("Shapes", use_color);
+    if report.count(Pass::Shapes) == 0 {
+        print_check(
+            Check::Ok,
+            &format!(
+                "every node reads its inputs as defined; {} of {} output shapes inferred",
+                report.inferred, report.nodes
+            ),
+            use_color,
+        );
+    }
+    print_problems(Pass::Shapes);
+    for (name, signature) in outputs {
+        let shape = match signature {
+            Some(Signature {
+                dims: Some(dims),
+                dtype,
+            }) => format!("{:?} {}", dims, dtype),
+            Some(Signature { dims: None, dtype }) => format!("(data-dependent) {}", dtype),
+            None => "(undefined)".to_string(),
+        };
+        match use_color {
+            true => println!("  {}→{} {:<16} {}", colors::GREEN, colors::RESET, name, shape),
+            false => println!("  → {:<16} {}", name, shape),
+        }
+    }
+    println!();
+
+    match backend {
+        Some(entry) => print_section_header(&format!("Backend {} {}", entry.name, entry.version), use_color),
+        None => print_section_header("Backend", use_color),
+    }
+    if let (Some(entry), 0) = (backend, report.count(Pass::Backend)) {
+        let action = match args.build {
+            true => format!(
+                "builds for {} on {}",
+                args.target.as_deref().unwrap_or(current_host_triple()),
+                device
+            ),
+            false => format!("runs on {}", device),
+        };
+        print_check(Check::Ok, &format!("{} {}", entry.name, action), use_color);
+    }
+    print_problems(Pass::Backend);
+    for op in ops {
+        let check = if op.supported { Check::Ok } else { Check::Fail };
+        print_check(
+            check,
+            &format!("{:<24} ×{:<5} {}", op.op, op.count, op.support),
+            use_color,
+        );
+    }
+    println!();
+}
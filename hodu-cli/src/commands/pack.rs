@@ -0,0 +1,187 @@
+//! Pack command - bundle a model into a single `.hpk` file
+//!
+//! The bundle holds the model's snapshot and weight shards, the plugins it needs and the run
+//! options to use by default, so `hodu run model.hpk` runs it as packed. See [`crate::bundle`].
+
+use super::run::{find_backend_plugin, find_model_format_plugin, load_model_snapshot, parse_device};
+use crate::bundle::{write_bundle, BundleManifest, PluginRequirement, RunDefaults, BUNDLE_EXTENSION, FORMAT_VERSION};
+use crate::errors::{self, ErrorClass};
+use crate::output;
+use crate::plugins::{load_registry, PluginEntry, PluginManager, PluginRegistry};
+use clap::Args;
+use hodu_core::format::hdss::{self, WEIGHTS_METADATA_KEY};
+use hodu_plugin::index::parse_spec;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the snapshot inside a bundle
+const SNAPSHOT_NAME: &str = "model.hdss";
+
+#[derive(Args)]
+pub struct PackArgs {
+    /// Model file (.onnx, .hdss, etc.)
+    pub model: PathBuf,
+
+    /// Bundle to write (default: <model>.hpk next to the model)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Device `hodu run` uses by default (cpu, metal, cuda::0)
+    #[arg(short, long)]
+    pub device: Option<String>,
+
+    /// Backend plugin the bundle needs and `hodu run` uses by default
+    #[arg(long)]
+    pub backend: Option<String>,
+
+    /// Another plugin the bundle needs (NAME[@REQUIREMENT], default: compatible with the installed
+    /// version), can be repeated
+    #[arg(long = "plugin", value_name = "NAME[@REQUIREMENT]")]
+    pub plugins: Vec<String>,
+
+    /// Let f32 matmul and convolution run on TF32 tensor cores by default
+    #[arg(long)]
+    pub allow_tf32: bool,
+
+    /// Let f16/bf16 matmul and convolution accumulate in 16 bits by default
+    #[arg(long)]
+    pub f16_accumulate: bool,
+
+    /// Default timeout in seconds for plugin operations
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Default plugin config override, can be repeated
+    #[arg(long = "plugin-config", value_name = "PLUGIN.KEY=VALUE")]
+    pub plugin_config: Vec<String>,
+}
+
+pub fn execute(args: PackArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let output_path = args
+        .output
+        .clone()
+        .unwrap_or_else(|| args.model.with_extension(BUNDLE_EXTENSION));
+    let extension = args
+        .model
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    let registry = load_registry()?;
+    let format_plugin = find_model_format_plugin(extension.as_deref(), &registry)?;
+
+    let mut manager = PluginManager::new()?;
+    manager.allow_read(&args.model);
+    let model_name = args
+        .model
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| args.model.display().to_string());
+    // The temp file keeps an imported ONNX model's snapshot alive until it is packed
+    let (snapshot_path, _onnx_snapshot) = load_model_snapshot(&args.model, format_plugin, &model_name, &mut manager)?;
+    let (snapshot, weights) = hdss::load_lazy(&snapshot_path)?;
+
+    // The snapshot keeps its weight manifest and shards at the same paths relative to it
+    let mut files = vec![(SNAPSHOT_NAME.to_string(), snapshot_path.clone())];
+    if let (Some(manifest), Some(weights)) = (snapshot.metadata.get(WEIGHTS_METADATA_KEY), &weights) {
+        let snapshot_dir = snapshot_path.parent().unwrap_or(Path::new(""));
+        files.push((manifest.clone(), snapshot_dir.join(manifest)));
+        for shard in weights.shard_paths() {
+            let name = shard
+                .strip_prefix(snapshot_dir)
+                .ok()
+                .filter(|name| name.components().all(|c| matches!(c, Component::Normal(_))))
+                .ok_or_else(|| {
+                    format!(
+                        "Weight shard {} is outside the snapshot's directory and cannot be packed",
+                        shard.display()
+                    )
+                })?;
+            files.push((name.to_string_lossy().replace('\\', "/"), shard));
+        }
+    }
+
+    // The backend named on the command line, plugins declaring the model's custom ops, and any
+    // other plugin asked for
+    let device = parse_device(args.device.as_deref().unwrap_or("cpu"))?;
+    let mut plugins: Vec<PluginRequirement> = Vec::new();
+    if let Some(backend) = &args.backend {
+        let entry = find_backend_plugin(&Some(backend.clone()), &device, &registry)?;
+        plugins.push(compatible_with(entry));
+    }
+    for op in snapshot.custom_op_names() {
+        let entry = registry.find_custom_op(&op).ok_or_else(|| {
+            errors::classified(
+                ErrorClass::Plugin,
+                format!("No installed plugin declares the model's custom op '{}'", op),
+            )
+        })?;
+        plugins.push(compatible_with(entry));
+    }
+    for spec in &args.plugins {
+        let (name, requirement) = parse_spec(spec)?;
+        let entry = find_plugin(&registry, name)?;
+        plugins.push(match spec.contains('@') {
+            true => PluginRequirement {
+                name: entry.name.clone(),
+                version: requirement.to_string(),
+            },
+            false => compatible_with(entry),
+        });
+    }
+    let mut seen = Vec::new();
+    plugins.retain(|plugin| {
+        let first = !seen.contains(&plugin.name);
+        seen.push(plugin.name.clone());
+        first
+    });
+
+    let mut manifest = BundleManifest {
+        format_version: FORMAT_VERSION,
+        model: model_name.clone(),
+        snapshot: SNAPSHOT_NAME.to_string(),
+        files: Vec::new(),
+        plugins,
+        run: RunDefaults {
+            device: args.device.clone(),
+            backend: args.backend.clone(),
+            allow_tf32: args.allow_tf32,
+            f16_accumulate: args.f16_accumulate,
+            timeout: args.timeout,
+            plugin_config: args.plugin_config.clone(),
+        },
+        created: chrono::Utc::now().to_rfc3339(),
+    };
+
+    output::packing(&model_name);
+    write_bundle(&output_path, &mut manifest, &files)?;
+    let size = std::fs::metadata(&output_path)?.len();
+
+    output::finished(&format!(
+        "{} ({})",
+        output_path.display(),
+        output::format_size(size as usize)
+    ));
+    if output::is_json() {
+        output::print_json(&serde_json::json!({
+            "bundle": output_path.display().to_string(),
+            "bytes": size,
+            "manifest": manifest,
+        }))?;
+    }
+    Ok(())
+}
+
+/// Require a version compatible with the installed one
+fn compatible_with(entry: &PluginEntry) -> PluginRequirement {
+    PluginRequirement {
+        name: entry.name.clone(),
+        version: format!("^{}", entry.version),
+    }
+}
+
+fn find_plugin<'a>(registry: &'a PluginRegistry, name: &str) -> Result<&'a PluginEntry, Box<dyn std::error::Error>> {
+    registry
+        .find(name)
+        .or_else(|| registry.find(&crate::plugins::backend_plugin_name(name)))
+        .or_else(|| registry.find(&crate::plugins::format_plugin_name(name)))
+        .ok_or_else(|| errors::classified(ErrorClass::Plugin, format!("Plugin '{}' not found.", name)))
+}
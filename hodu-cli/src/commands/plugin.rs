@@ -2,18 +2,34 @@
 //!
 //! This command manages JSON-RPC based plugins as standalone executables.
 
+mod config;
+mod index;
 mod install;
+mod lock;
+mod new;
+mod pool;
+mod signing;
+mod status;
 mod update;
 
 use crate::output;
 use crate::plugins::{
     backend_plugin_name, format_plugin_name, load_registry, load_registry_mut, PluginManager, PluginRegistry,
-    PluginSource,
+    ResourceLimits,
 };
 use clap::{Args, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub use install::{get_plugins_dir, install_from_git, install_from_path, install_from_registry};
+pub use config::config_plugin;
+pub use index::{index_info, install_from_index, search_plugins};
+pub use install::{
+    get_plugins_dir, install_from_git, install_from_local, install_from_path, install_remote, is_archive,
+};
+pub use lock::sync_plugins;
+pub use new::{new_plugin, PluginKind};
+pub use pool::{ps_plugins, stop_plugins};
+pub use signing::{keygen_plugin, keys_plugin, sign_plugin};
+pub use status::status_plugin;
 pub use update::update_plugins;
 
 #[derive(Args)]
@@ -27,40 +43,134 @@ pub enum PluginCommands {
     /// List installed plugins
     List,
 
-    /// Show plugin info (spawns plugin to get runtime info)
+    /// Show plugin info (spawns plugin to get runtime info, or reads the plugin index if not installed)
     Info(InfoArgs),
 
+    /// Search the plugin index
+    Search(SearchArgs),
+
+    /// Show what a running plugin is doing: uptime, requests in flight, memory
+    Status(StatusArgs),
+
     /// Install a plugin
     Install(InstallArgs),
 
+    /// Create a plugin project from the SDK templates
+    New(NewArgs),
+
+    /// Register a plugin daemon running at a socket address
+    Connect(ConnectArgs),
+
     /// Remove a plugin
     Remove(RemoveArgs),
 
     /// Update plugins
     Update(UpdateArgs),
 
+    /// Install the plugins recorded in a hodu-plugins.lock file
+    Sync(SyncArgs),
+
     /// Enable a plugin
     Enable(EnableArgs),
 
     /// Disable a plugin
     Disable(DisableArgs),
 
-    /// Verify plugin integrity (check binaries exist, dependencies satisfied)
+    /// Let a plugin access the filesystem freely (the default)
+    Trust(TrustArgs),
+
+    /// Confine a plugin to the files the command hands it, its own directory and scratch space
+    Untrust(UntrustArgs),
+
+    /// Show or set the memory and CPU time a plugin's process may use
+    Limit(LimitArgs),
+
+    /// Show or edit a plugin's configuration in ~/.hodu/config.toml
+    Config(ConfigArgs),
+
+    /// Verify plugin integrity (check binaries exist and match their signatures, dependencies satisfied)
     Verify,
+
+    /// Generate a key for signing plugin binaries and artifacts
+    Keygen(KeygenArgs),
+
+    /// Sign plugin binaries or artifacts, writing a detached `<file>.sig` next to each
+    Sign(SignArgs),
+
+    /// Show or edit the public keys trusted to sign plugins
+    Keys(KeysArgs),
+
+    /// List plugin daemons kept running by the pool
+    Ps,
+
+    /// Stop idle plugin daemons
+    Stop(StopArgs),
 }
 
 #[derive(Args)]
 pub struct InfoArgs {
     /// Plugin name
     pub name: String,
+
+    /// Show the plugin's entry in the plugin index even if it is installed
+    #[arg(long)]
+    pub remote: bool,
+}
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Text to look for in plugin names, descriptions and keywords (lists every plugin if omitted)
+    pub query: Option<String>,
+}
+
+#[derive(Args)]
+pub struct StatusArgs {
+    /// Plugin name
+    pub name: String,
+
+    /// Output format (pretty, json)
+    #[arg(short, long, default_value = "pretty")]
+    pub format: String,
+}
+
+#[derive(Args)]
+pub struct NewArgs {
+    /// Plugin name; the hodu-backend- or hodu-format- prefix is added if missing
+    pub name: String,
+
+    /// Kind of plugin
+    #[arg(long, value_enum, default_value = "backend")]
+    pub kind: PluginKind,
+
+    /// Directory to create (default: ./<name>)
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Description for Cargo.toml and manifest.json
+    #[arg(long)]
+    pub description: Option<String>,
+
+    /// License for Cargo.toml and manifest.json
+    #[arg(long, default_value = "MIT")]
+    pub license: String,
+
+    /// File extension a format plugin handles, without the dot (default: from the name)
+    #[arg(long)]
+    pub extension: Option<String>,
+
+    /// Do not initialize a git repository
+    #[arg(long)]
+    pub no_git: bool,
 }
 
 #[derive(Args)]
 pub struct InstallArgs {
-    /// Plugin name (from official registry)
+    /// Plugin name from the plugin index, optionally with a version requirement (name@0.2), or a
+    /// path with a separator (./plugin) or a .tar.gz/.tgz/.tar/.zip archive to install without
+    /// network access; other local directories take --path
     pub name: Option<String>,
 
-    /// Install from local path
+    /// Install from local path (a directory or archive)
     #[arg(long)]
     pub path: Option<PathBuf>,
 
@@ -87,6 +197,22 @@ pub struct InstallArgs {
     /// Show detailed build output
     #[arg(long, short = 'v')]
     pub verbose: bool,
+
+    /// Refuse a prebuilt plugin whose binary is not signed by a trusted key
+    #[arg(long, conflicts_with = "git")]
+    pub require_signature: bool,
+}
+
+#[derive(Args)]
+pub struct ConnectArgs {
+    /// Daemon address (tcp://host:port or unix:///path/to/socket)
+    ///
+    /// The token in HODU_PLUGIN_TOKEN is presented to daemons that require one.
+    pub endpoint: String,
+
+    /// Replace an installed plugin with the same name
+    #[arg(long)]
+    pub force: bool,
 }
 
 #[derive(Args)]
@@ -97,10 +223,20 @@ pub struct RemoveArgs {
 
 #[derive(Args)]
 pub struct UpdateArgs {
-    /// Plugin name (update all if not specified)
+    /// Plugin name (update all if not specified), optionally with a new version requirement (name@0.3)
     pub name: Option<String>,
 }
 
+#[derive(Args)]
+pub struct SyncArgs {
+    /// Lockfile to install from (default: ~/.hodu/hodu-plugins.lock)
+    pub lockfile: Option<PathBuf>,
+
+    /// Also remove installed plugins the lockfile does not list
+    #[arg(long)]
+    pub prune: bool,
+}
+
 #[derive(Args)]
 pub struct EnableArgs {
     /// Plugin name
@@ -113,16 +249,125 @@ pub struct DisableArgs {
     pub name: String,
 }
 
+#[derive(Args)]
+pub struct TrustArgs {
+    /// Plugin name
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct UntrustArgs {
+    /// Plugin name
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct LimitArgs {
+    /// Plugin name
+    pub name: String,
+
+    /// Memory the plugin may allocate, in megabytes
+    #[arg(long, value_name = "MB", value_parser = clap::value_parser!(u64).range(1..))]
+    pub memory: Option<u64>,
+
+    /// CPU time the plugin process may use, in seconds
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub cpu_time: Option<u64>,
+
+    /// Remove every limit
+    #[arg(long, conflicts_with_all = ["memory", "cpu_time"])]
+    pub clear: bool,
+}
+
+#[derive(Args)]
+pub struct KeygenArgs {
+    /// File to write the secret key to; the public key goes to `<file>.pub`
+    pub output: PathBuf,
+
+    /// Overwrite existing key files
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct SignArgs {
+    /// Files to sign
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Secret key written by `hodu plugin keygen`
+    #[arg(long)]
+    pub key: PathBuf,
+}
+
+#[derive(Args)]
+pub struct KeysArgs {
+    /// Trust a public key (base64, or a `.pub` file)
+    #[arg(long, value_name = "KEY")]
+    pub add: Option<String>,
+
+    /// Name to show for the added key, e.g. its publisher
+    #[arg(long, requires = "add")]
+    pub name: Option<String>,
+
+    /// Stop trusting a public key (base64, or a `.pub` file)
+    #[arg(long, value_name = "KEY")]
+    pub remove: Option<String>,
+}
+
+#[derive(Args)]
+pub struct StopArgs {
+    /// Plugin name
+    #[arg(required_unless_present = "all")]
+    pub name: Option<String>,
+
+    /// Stop the daemons of every plugin
+    #[arg(long, conflicts_with = "name")]
+    pub all: bool,
+}
+
+#[derive(Args)]
+pub struct ConfigArgs {
+    /// Plugin name
+    pub name: String,
+
+    /// Set a config value (KEY=VALUE), can be repeated
+    #[arg(long, value_name = "KEY=VALUE")]
+    pub set: Vec<String>,
+
+    /// Remove a config value, can be repeated
+    #[arg(long, value_name = "KEY")]
+    pub unset: Vec<String>,
+
+    /// Print the plugin's config schema as JSON
+    #[arg(long, conflicts_with_all = ["set", "unset"])]
+    pub schema: bool,
+}
+
 pub fn execute(args: PluginArgs) -> Result<(), Box<dyn std::error::Error>> {
     match args.command {
         PluginCommands::List => list_plugins(),
         PluginCommands::Info(info_args) => info_plugin(info_args),
+        PluginCommands::Search(search_args) => search_plugins(search_args),
+        PluginCommands::Status(status_args) => status_plugin(status_args),
         PluginCommands::Install(install_args) => do_install(install_args),
+        PluginCommands::New(new_args) => new_plugin(new_args),
+        PluginCommands::Connect(connect_args) => install_remote(&connect_args.endpoint, connect_args.force),
         PluginCommands::Remove(remove_args) => remove_plugin(remove_args),
         PluginCommands::Update(update_args) => update_plugins(update_args.name.as_deref()),
+        PluginCommands::Sync(sync_args) => sync_plugins(sync_args),
         PluginCommands::Enable(enable_args) => enable_plugin(enable_args),
         PluginCommands::Disable(disable_args) => disable_plugin(disable_args),
+        PluginCommands::Trust(trust_args) => set_plugin_trust(&trust_args.name, true),
+        PluginCommands::Untrust(untrust_args) => set_plugin_trust(&untrust_args.name, false),
+        PluginCommands::Limit(limit_args) => limit_plugin(limit_args),
+        PluginCommands::Config(config_args) => config_plugin(config_args),
         PluginCommands::Verify => verify_plugins(),
+        PluginCommands::Keygen(keygen_args) => keygen_plugin(keygen_args),
+        PluginCommands::Sign(sign_args) => sign_plugin(sign_args),
+        PluginCommands::Keys(keys_args) => keys_plugin(keys_args),
+        PluginCommands::Ps => ps_plugins(),
+        PluginCommands::Stop(stop_args) => stop_plugins(stop_args.name.as_deref()),
     }
 }
 
@@ -170,6 +415,11 @@ fn list_plugins() -> Result<(), Box<dyn std::error::Error>> {
     let use_color = output::supports_color();
 
     let registry = load_registry()?;
+    if output::is_json() {
+        // Entries as recorded in plugins.json, disabled ones included
+        output::print_json(&serde_json::json!({ "plugins": registry.plugins }))?;
+        return Ok(());
+    }
 
     // Backend plugins
     print_section("Backend Plugins", use_color);
@@ -230,7 +480,7 @@ fn list_plugins() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn print_section(title: &str, use_color: bool) {
+pub(crate) fn print_section(title: &str, use_color: bool) {
     use output::colors;
     if use_color {
         println!("{}{}{}{}", colors::BOLD, colors::CYAN, title, colors::RESET);
@@ -239,7 +489,7 @@ fn print_section(title: &str, use_color: bool) {
     }
 }
 
-fn print_empty(use_color: bool) {
+pub(crate) fn print_empty(use_color: bool) {
     use output::colors;
     if use_color {
         println!("  {}(none){}", colors::YELLOW, colors::RESET);
@@ -308,17 +558,9 @@ fn info_plugin(args: InfoArgs) -> Result<(), Box<dyn std::error::Error>> {
     let use_color = output::supports_color();
 
     let registry = load_registry()?;
-
-    // Find plugin
-    let plugin = registry.find(&args.name).or_else(|| {
-        let backend_name = backend_plugin_name(&args.name);
-        let format_name = format_plugin_name(&args.name);
-        registry.find(&backend_name).or_else(|| registry.find(&format_name))
-    });
-
-    let plugin = match plugin {
-        Some(p) => p,
-        None => return Err(format!("Plugin '{}' not found.", args.name).into()),
+    let plugin = match find_plugin(&registry, &args.name) {
+        Ok(plugin) if !args.remote => plugin,
+        _ => return index_info(&args.name),
     };
 
     // Header
@@ -361,6 +603,12 @@ fn info_plugin(args: InfoArgs) -> Result<(), Box<dyn std::error::Error>> {
         "disabled".to_string()
     };
     print_info_row("Status", &status, use_color);
+    if !plugin.trusted {
+        print_info_row("Filesystem", "sandboxed (hodu plugin trust to lift)", use_color);
+    }
+    if !plugin.limits.is_empty() {
+        print_info_row("Limits", &plugin.limits.to_string(), use_color);
+    }
     println!();
 
     // Spawn plugin to get runtime info
@@ -406,6 +654,11 @@ fn info_plugin(args: InfoArgs) -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
+        // Protocol features both sides agreed on
+        if !info.features.is_empty() {
+            print_info_row("Features", &info.features.join(", "), use_color);
+        }
+
         // Format capabilities nicely
         let caps_str = info
             .capabilities
@@ -473,7 +726,30 @@ fn info_plugin(args: InfoArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn print_info_row(label: &str, value: &str, use_color: bool) {
+/// Find an installed plugin by its full name, or its name without the backend or format prefix
+fn find_plugin<'a>(
+    registry: &'a PluginRegistry,
+    name: &str,
+) -> Result<&'a hodu_plugin_runtime::PluginEntry, Box<dyn std::error::Error>> {
+    registry
+        .find(name)
+        .or_else(|| registry.find(&backend_plugin_name(name)))
+        .or_else(|| registry.find(&format_plugin_name(name)))
+        .ok_or_else(|| format!("Plugin '{}' not found.", name).into())
+}
+
+/// Format an uptime as its two largest units, e.g. `2h 5m`
+fn format_uptime(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        3600..=86399 => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d {}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
+pub(crate) fn print_info_row(label: &str, value: &str, use_color: bool) {
     use output::colors;
     if use_color {
         println!("  {}{:<12}{} {}", colors::CYAN, label, colors::RESET, value);
@@ -483,27 +759,46 @@ fn print_info_row(label: &str, value: &str, use_color: bool) {
 }
 
 fn do_install(args: InstallArgs) -> Result<(), Box<dyn std::error::Error>> {
-    if let Some(path) = &args.path {
-        let source = PluginSource::Local {
-            path: path.canonicalize()?.to_string_lossy().to_string(),
-        };
-        install_from_path(path, args.debug, args.force, args.verbose, source)
+    let local = args
+        .path
+        .clone()
+        .or_else(|| args.name.as_deref().and_then(local_install_path));
+    if let Some(path) = &local {
+        install_from_local(path, args.debug, args.force, args.verbose, args.require_signature)
     } else if let Some(git) = &args.git {
         install_from_git(
             git,
             args.subdir.as_deref(),
             args.tag.as_deref(),
+            None,
             args.debug,
             args.force,
             args.verbose,
         )
     } else if let Some(name) = &args.name {
-        install_from_registry(name, args.tag.as_deref(), args.debug, args.force, args.verbose)
+        install_from_index(
+            name,
+            args.tag.as_deref(),
+            args.debug,
+            args.force,
+            args.verbose,
+            args.require_signature,
+        )
     } else {
         Err("No plugin specified. Use <name>, --path, or --git.".into())
     }
 }
 
+/// The path a bare `hodu plugin install <name>` argument names, if it isn't a plugin name
+///
+/// A plugin name never holds a path separator, so `./plugin` or `dist/plugin` is a path, as is
+/// `plugin.tar.gz`. Whether the name exists on disk doesn't matter: `aot-cpu` is installed from the
+/// index even next to an `aot-cpu` checkout, which takes `--path aot-cpu`.
+fn local_install_path(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    (path.components().count() > 1 || is_archive(path)).then(|| path.to_path_buf())
+}
+
 fn remove_plugin(args: RemoveArgs) -> Result<(), Box<dyn std::error::Error>> {
     let (mut registry, registry_path) = load_registry_mut()?;
 
@@ -538,6 +833,10 @@ fn remove_plugin(args: RemoveArgs) -> Result<(), Box<dyn std::error::Error>> {
 
     output::removing(&format!("{} v{}", name, version));
 
+    // Daemons would keep running the deleted binary
+    #[cfg(unix)]
+    crate::plugins::stop_daemons(|plugin| plugin == name)?;
+
     if plugin_dir.exists() {
         std::fs::remove_dir_all(&plugin_dir)?;
     }
@@ -545,6 +844,7 @@ fn remove_plugin(args: RemoveArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Remove from registry
     registry.remove(&name);
     registry.save(&registry_path)?;
+    lock::write_lockfile(&registry)?;
 
     output::removed(&format!("{} v{}", name, version));
     Ok(())
@@ -594,20 +894,78 @@ fn disable_plugin(args: DisableArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn set_plugin_trust(plugin: &str, trusted: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut registry, registry_path) = load_registry_mut()?;
+
+    // Try to find plugin with various name formats
+    let name = find_plugin_name(&registry, plugin)?;
+
+    if !registry.set_trusted(&name, trusted) {
+        return Err(format!("Plugin '{}' not found.", plugin).into());
+    }
+    registry.save(&registry_path)?;
+    // Pooled daemons are sent the new policy when next handed out, so they keep running
+    if trusted {
+        output::finished(&format!("trusted {}", name));
+    } else {
+        output::finished(&format!("sandboxed {}", name));
+    }
+    Ok(())
+}
+
+fn limit_plugin(args: LimitArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut registry, registry_path) = load_registry_mut()?;
+
+    // Try to find plugin with various name formats
+    let name = find_plugin_name(&registry, &args.name)?;
+    let mut limits = registry
+        .find(&name)
+        .map(|entry| entry.limits)
+        .ok_or_else(|| format!("Plugin '{}' not found.", args.name))?;
+
+    if !args.clear && args.memory.is_none() && args.cpu_time.is_none() {
+        println!("{}: {}", name, limits);
+        return Ok(());
+    }
+    if args.clear {
+        limits = ResourceLimits::default();
+    }
+    if let Some(mb) = args.memory {
+        limits.memory_mb = Some(mb);
+    }
+    if let Some(secs) = args.cpu_time {
+        limits.cpu_secs = Some(secs);
+    }
+
+    registry.set_limits(&name, limits);
+    registry.save(&registry_path)?;
+    output::finished(&format!("limits of {}: {}", name, limits));
+    Ok(())
+}
+
 fn verify_plugins() -> Result<(), Box<dyn std::error::Error>> {
     let registry = load_registry()?;
     let plugins_dir = get_plugins_dir()?;
+    let trusted = signing::load_trusted_keys()?;
 
     let mut issues = Vec::new();
     let mut ok_count = 0;
+    let mut signed_count = 0;
 
     for plugin in &registry.plugins {
         let mut plugin_issues = Vec::new();
 
-        // Check if binary exists
+        // Check if binary exists (remote plugins have none)
         let binary_path = plugins_dir.join(&plugin.name).join(&plugin.binary);
-        if !binary_path.exists() {
+        if !plugin.source.is_remote() && !binary_path.exists() {
             plugin_issues.push(format!("binary not found: {}", binary_path.display()));
+        } else if !plugin.source.is_remote() {
+            // A signed binary must still match its signature
+            match signing::signature_status(&binary_path, &trusted) {
+                Ok(Some(_)) => signed_count += 1,
+                Ok(None) => {},
+                Err(issue) => plugin_issues.push(issue),
+            }
         }
 
         // Check dependencies (only for enabled plugins)
@@ -635,7 +993,7 @@ fn verify_plugins() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     if issues.is_empty() {
-        println!("All {} plugins verified OK.", ok_count);
+        println!("All {} plugins verified OK ({} signed).", ok_count, signed_count);
     } else {
         println!(
             "Verified {} plugins, {} with issues:",
@@ -710,3 +1068,26 @@ fn list_installed_plugins(registry: &PluginRegistry) -> String {
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_install_path_ignores_cwd() {
+        let dir = std::env::temp_dir().join(format!("hodu-install-cwd-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("aot-cpu")).unwrap();
+        std::fs::write(dir.join("hodu-format-onnx"), b"").unwrap();
+        let previous = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&dir).unwrap();
+
+        let names = ["aot-cpu", "hodu-format-onnx", "./aot-cpu", "aot-cpu.tar.gz"].map(local_install_path);
+
+        std::env::set_current_dir(previous).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(names[0], None);
+        assert_eq!(names[1], None);
+        assert_eq!(names[2], Some(PathBuf::from("./aot-cpu")));
+        assert_eq!(names[3], Some(PathBuf::from("aot-cpu.tar.gz")));
+    }
+}
@@ -0,0 +1,124 @@
+//! Plugin config logic - show and edit a plugin's table in ~/.hodu/config.toml
+
+use super::{find_plugin_name, print_empty, print_info_row, print_section, ConfigArgs};
+use crate::output;
+use crate::plugins::{
+    check_against_schema, load_registry, parse_value, set_config_value, unset_config_value, PluginConfig, PluginManager,
+};
+
+pub fn config_plugin(args: ConfigArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = load_registry()?;
+    let name = find_plugin_name(&registry, &args.name)?;
+    let path = PluginConfig::default_path()?;
+    // Fail on a malformed file before editing it
+    PluginConfig::load_from(&path)?;
+
+    // Start the plugin without its config, so a bad value can still be fixed here
+    let mut manager = PluginManager::without_config()?;
+    manager.get_plugin(&name)?;
+    let schema = manager.get_info(&name).and_then(|info| info.config_schema.clone());
+    manager.shutdown_all();
+
+    if args.schema {
+        let schema = schema.ok_or_else(|| format!("Plugin '{}' does not declare a config schema", name))?;
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+
+    for assignment in &args.set {
+        let (key, raw) = assignment
+            .split_once('=')
+            .map(|(key, raw)| (key.trim(), raw))
+            .ok_or_else(|| format!("Invalid assignment '{}' (expected KEY=VALUE)", assignment))?;
+        if let Some(schema) = &schema {
+            check_against_schema(schema, key, &parse_value(raw))?;
+        }
+        set_config_value(&path, &name, key, raw)?;
+        output::updated(&format!("{}.{} = {}", name, key, raw.trim()));
+    }
+    for key in &args.unset {
+        if unset_config_value(&path, &name, key)? {
+            output::removed(&format!("{}.{}", name, key));
+        } else {
+            output::skipping(&format!("{}.{} is not set", name, key));
+        }
+    }
+    if !args.set.is_empty() || !args.unset.is_empty() {
+        return Ok(());
+    }
+
+    let use_color = output::supports_color();
+    let config = PluginConfig::load_from(&path)?;
+    let mut values = Vec::new();
+    if let Some(table) = config.file_table(&name) {
+        flatten_values(table, "", &mut values);
+    }
+
+    print_section(&format!("{} ({})", name, path.display()), use_color);
+    match schema.as_ref().filter(|schema| schema.get("properties").is_some()) {
+        Some(schema) => {
+            let mut settings = Vec::new();
+            flatten_schema(schema, "", &mut settings);
+            for (key, property) in &settings {
+                let mut row = match values.iter().find(|(set, _)| set == key) {
+                    Some((_, value)) => value.clone(),
+                    None => match property.get("default") {
+                        Some(default) => format!("{} (default)", default),
+                        None => "(unset)".to_string(),
+                    },
+                };
+                if let Some(description) = property.get("description").and_then(|d| d.as_str()) {
+                    row = format!("{}  # {}", row, description);
+                }
+                print_info_row(key, &row, use_color);
+            }
+            for (key, value) in values
+                .iter()
+                .filter(|(key, _)| !settings.iter().any(|(known, _)| known == key))
+            {
+                print_info_row(key, &format!("{} (not used by the plugin)", value), use_color);
+            }
+            if settings.is_empty() && values.is_empty() {
+                print_empty(use_color);
+            }
+        },
+        None => {
+            if values.is_empty() {
+                print_empty(use_color);
+            }
+            for (key, value) in &values {
+                print_info_row(key, value, use_color);
+            }
+            println!();
+            output::info(&format!("{} does not declare a config schema", name));
+        },
+    }
+
+    Ok(())
+}
+
+/// Collect the leaves of a config table as dotted keys and TOML values
+fn flatten_values(table: &toml::Table, prefix: &str, out: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = format!("{}{}", prefix, key);
+        match value {
+            toml::Value::Table(nested) => flatten_values(nested, &format!("{}.", key), out),
+            value => out.push((key, value.to_string())),
+        }
+    }
+}
+
+/// Collect the leaf properties of a config schema as dotted keys
+fn flatten_schema(schema: &serde_json::Value, prefix: &str, out: &mut Vec<(String, serde_json::Value)>) {
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return;
+    };
+    for (key, property) in properties {
+        let key = format!("{}{}", prefix, key);
+        if property.get("properties").is_some() {
+            flatten_schema(property, &format!("{}.", key), out);
+        } else {
+            out.push((key, property.clone()));
+        }
+    }
+}
@@ -0,0 +1,459 @@
+//! Plugin index: search, info and install by name from the remote plugin index
+
+use super::install::{install_from_git, install_from_path, parse_manifest};
+use super::{print_info_row, print_section, SearchArgs};
+use crate::output;
+use crate::plugins::{backend_plugin_name, format_plugin_name, load_registry, PluginSource};
+use hodu_plugin::current_host_triple;
+use hodu_plugin::index::{host_protocol, parse_spec, IndexPlugin, IndexRelease, PluginIndex, TargetArtifact};
+use semver::VersionReq;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Official plugin index URL
+pub const PLUGIN_INDEX_URL: &str = "https://raw.githubusercontent.com/daminstudio/hodu-plugins/main/index.json";
+
+/// Environment variable overriding [`PLUGIN_INDEX_URL`] with another HTTPS URL or a local file
+pub const PLUGIN_INDEX_ENV: &str = "HODU_PLUGIN_INDEX";
+
+/// Maximum index size (16MB)
+const MAX_INDEX_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Maximum prebuilt plugin zip size (512MB)
+const MAX_DOWNLOAD_SIZE: u64 = 512 * 1024 * 1024;
+
+/// URL of the plugin index in use
+pub fn index_url() -> String {
+    std::env::var(PLUGIN_INDEX_ENV)
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| PLUGIN_INDEX_URL.to_string())
+}
+
+/// Fetch and parse the plugin index
+pub fn fetch_index() -> Result<PluginIndex, Box<dyn std::error::Error>> {
+    fetch_index_from(&index_url())
+}
+
+/// Fetch and parse the plugin index at `url`
+pub fn fetch_index_from(url: &str) -> Result<PluginIndex, Box<dyn std::error::Error>> {
+    let body = read_location(url, MAX_INDEX_SIZE).map_err(|e| format!("Failed to fetch plugin index: {}", e))?;
+    let body = String::from_utf8(body).map_err(|_| "Failed to read plugin index: not UTF-8")?;
+    Ok(PluginIndex::parse(&body)?)
+}
+
+/// Read an HTTPS URL, or a local file for mirrors on disk
+///
+/// Plain HTTP is refused: the index is what vouches for the checksums of the downloads.
+fn read_location(location: &str, limit: u64) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if location.starts_with("https://") {
+        Ok(ureq::get(location)
+            .call()?
+            .body_mut()
+            .with_config()
+            .limit(limit)
+            .read_to_vec()?)
+    } else if location.starts_with("http://") {
+        Err(format!("refusing insecure URL {} (use https://)", location).into())
+    } else {
+        let metadata = std::fs::metadata(location)?;
+        if metadata.len() > limit {
+            return Err(format!(
+                "{} too large: {} bytes (max: {} bytes)",
+                location,
+                metadata.len(),
+                limit
+            )
+            .into());
+        }
+        Ok(std::fs::read(location)?)
+    }
+}
+
+/// Find a plugin in the index, also trying the `hodu-backend-` and `hodu-format-` prefixes
+fn find_indexed<'a>(index: &'a PluginIndex, name: &str) -> Result<&'a IndexPlugin, Box<dyn std::error::Error>> {
+    if let Some(plugin) = index
+        .find(name)
+        .or_else(|| index.find(&backend_plugin_name(name)))
+        .or_else(|| index.find(&format_plugin_name(name)))
+    {
+        return Ok(plugin);
+    }
+
+    const MAX_SHOWN_PLUGINS: usize = 20;
+    let mut candidates = index.search(name);
+    let heading = if candidates.is_empty() {
+        candidates = index.plugins.iter().collect();
+        "Available plugins"
+    } else {
+        "Did you mean"
+    };
+    let shown: Vec<_> = candidates
+        .iter()
+        .take(MAX_SHOWN_PLUGINS)
+        .map(|p| match &p.description {
+            Some(desc) => format!("{} - {}", p.name, truncate(desc, 60)),
+            None => p.name.clone(),
+        })
+        .collect();
+    let suffix = if candidates.len() > MAX_SHOWN_PLUGINS {
+        format!("\n  (and {} more)", candidates.len() - MAX_SHOWN_PLUGINS)
+    } else {
+        String::new()
+    };
+    Err(format!(
+        "Plugin '{}' not found in the plugin index.\n\n{}:\n  {}{}",
+        name,
+        heading,
+        shown.join("\n  "),
+        suffix
+    )
+    .into())
+}
+
+/// Truncate to at most `max` bytes on a character boundary, marking the cut with `...`
+fn truncate(text: &str, max: usize) -> String {
+    if text.len() <= max {
+        return text.to_string();
+    }
+    let end = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .take_while(|&i| i <= max)
+        .last()
+        .unwrap_or(0);
+    format!("{}...", &text[..end])
+}
+
+/// Install a plugin by `name[@version]` from the plugin index
+///
+/// The newest release matching the version requirement that speaks the host's plugin protocol is
+/// installed from its prebuilt zip for the host's target triple. A release without one, or a
+/// `--debug` or `--tag` install, is built from the release's git source instead. The requirement
+/// is recorded, and `hodu plugin update` keeps to it.
+pub fn install_from_index(
+    spec: &str,
+    tag_override: Option<&str>,
+    debug: bool,
+    force: bool,
+    verbose: bool,
+    require_signature: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (name, requirement) = parse_spec(spec)?;
+
+    output::fetching(&format!("plugin index for '{}'", name));
+    let url = index_url();
+    let index = fetch_index_from(&url)?;
+    let plugin = find_indexed(&index, name)?;
+    let release = plugin.resolve(&requirement, &host_protocol())?;
+
+    if tag_override.is_some() || debug {
+        return install_source(plugin, release, tag_override, debug, force, verbose, require_signature);
+    }
+    let requirement = (requirement != VersionReq::STAR).then(|| requirement.to_string());
+    install_release(&url, plugin, release, requirement, force, verbose, require_signature)
+}
+
+/// Install a resolved release: its prebuilt zip for the host's target triple, or else its source
+pub fn install_release(
+    url: &str,
+    plugin: &IndexPlugin,
+    release: &IndexRelease,
+    requirement: Option<String>,
+    force: bool,
+    verbose: bool,
+    require_signature: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let triple = current_host_triple();
+    let Some(artifact) = release.target(triple) else {
+        output::info(&format!(
+            "no prebuilt binary of {} v{} for {}, building from source",
+            plugin.name, release.version, triple
+        ));
+        return install_source(plugin, release, None, false, force, verbose, require_signature);
+    };
+
+    let source = PluginSource::Index {
+        url: url.to_string(),
+        requirement,
+        checksums: release
+            .targets
+            .iter()
+            .map(|(triple, artifact)| (triple.clone(), artifact.sha256.clone()))
+            .collect(),
+    };
+    install_prebuilt(plugin, release, artifact, source, force, verbose, require_signature)
+}
+
+/// Install the exact release a lockfile records
+///
+/// The release is installed even if it was yanked since, but not if the index now lists a
+/// different checksum for the host's zip than the lockfile does.
+pub fn install_locked(
+    url: &str,
+    name: &str,
+    version: &str,
+    requirement: Option<String>,
+    checksums: &BTreeMap<String, String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let index = fetch_index_from(url)?;
+    let plugin = index
+        .find(name)
+        .ok_or_else(|| format!("Plugin '{}' is no longer in the plugin index at {}", name, url))?;
+    let release = plugin
+        .releases
+        .iter()
+        .find(|r| r.version == version)
+        .ok_or_else(|| format!("{} v{} is no longer in the plugin index at {}", name, version, url))?;
+
+    let triple = current_host_triple();
+    if let Some(artifact) = release.target(triple) {
+        match checksums.get(triple) {
+            Some(locked) if !locked.eq_ignore_ascii_case(&artifact.sha256) => {
+                return Err(format!(
+                    "The plugin index lists a different checksum for {} v{} ({}) than the lockfile\n  \
+                     locked: {}\n  index:  {}",
+                    name, version, triple, locked, artifact.sha256
+                )
+                .into());
+            },
+            Some(_) => {},
+            None => output::warning(&format!(
+                "lockfile has no checksum of {} v{} for {}; trusting the plugin index",
+                name, version, triple
+            )),
+        }
+    }
+    install_release(url, plugin, release, requirement, true, false, false)
+}
+
+/// Build a release from its git source
+fn install_source(
+    plugin: &IndexPlugin,
+    release: &IndexRelease,
+    tag_override: Option<&str>,
+    debug: bool,
+    force: bool,
+    verbose: bool,
+    require_signature: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let git = release.git.as_ref().ok_or_else(|| {
+        format!(
+            "{} v{} has no prebuilt binary for {} and no source to build from.\n\nPrebuilt targets:\n  {}",
+            plugin.name,
+            release.version,
+            current_host_triple(),
+            release.targets.keys().cloned().collect::<Vec<_>>().join("\n  ")
+        )
+    })?;
+    if require_signature {
+        return Err(format!(
+            "{} v{} would be built from source, which is never signed (remove --require-signature to build it)",
+            plugin.name, release.version
+        )
+        .into());
+    }
+    let tag = tag_override.or(git.tag.as_deref());
+    install_from_git(&git.url, git.path.as_deref(), tag, None, debug, force, verbose)
+}
+
+/// Download a release's prebuilt zip, check it against the index and install it
+fn install_prebuilt(
+    plugin: &IndexPlugin,
+    release: &IndexRelease,
+    artifact: &TargetArtifact,
+    source: PluginSource,
+    force: bool,
+    verbose: bool,
+    require_signature: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    output::downloading(&format!(
+        "{} v{} ({})",
+        plugin.name,
+        release.version,
+        current_host_triple()
+    ));
+    let data = read_location(&artifact.url, MAX_DOWNLOAD_SIZE)
+        .map_err(|e| format!("Failed to download {}: {}", artifact.url, e))?;
+
+    let checksum = hex::encode(Sha256::digest(&data));
+    if !checksum.eq_ignore_ascii_case(&artifact.sha256) {
+        return Err(format!(
+            "Checksum mismatch for {}\n  expected: {}\n  actual:   {}",
+            artifact.url, artifact.sha256, checksum
+        )
+        .into());
+    }
+    output::verified(&format!("sha256 {}", checksum));
+
+    let temp_dir =
+        TempDir::with_prefix("hodu_plugin_").map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    zip::ZipArchive::new(std::io::Cursor::new(data))
+        .and_then(|mut archive| archive.extract_unwrapped_root_dir(temp_dir.path(), zip::read::root_dir_common_filter))
+        .map_err(|e| format!("Failed to extract {}: {}", artifact.url, e))?;
+
+    // The index and the zip must agree on what is being installed
+    check_contents(temp_dir.path(), plugin, release)?;
+
+    install_from_path(temp_dir.path(), false, force, verbose, require_signature, source)
+    // temp_dir is automatically cleaned up when dropped
+}
+
+/// Check that an extracted zip holds the plugin and version the index lists it as
+fn check_contents(dir: &Path, plugin: &IndexPlugin, release: &IndexRelease) -> Result<(), Box<dyn std::error::Error>> {
+    let manifest_path = dir.join("manifest.json");
+    if !manifest_path.exists() {
+        return Err(format!(
+            "Prebuilt zip of {} v{} has no manifest.json",
+            plugin.name, release.version
+        )
+        .into());
+    }
+    let (name, version, ..) = parse_manifest(&manifest_path)?;
+    if name != plugin.name || version != release.version {
+        return Err(format!(
+            "Prebuilt zip of {} v{} holds {} v{}",
+            plugin.name, release.version, name, version
+        )
+        .into());
+    }
+    Ok(())
+}
+
+pub fn search_plugins(args: SearchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use output::colors;
+    let use_color = output::supports_color();
+
+    let index = fetch_index()?;
+    let query = args.query.as_deref().unwrap_or("");
+    let found = index.search(query);
+    if found.is_empty() {
+        println!("No plugins matching '{}'.", query);
+        return Ok(());
+    }
+
+    let installed = load_registry()?;
+    let protocol = host_protocol();
+    let triple = current_host_triple();
+    let name_width = found.iter().map(|p| p.name.len()).max().unwrap_or(0);
+
+    for plugin in &found {
+        let latest = plugin.latest(&protocol);
+        let version = latest.map_or("-", |r| r.version.as_str());
+        let mut notes = Vec::new();
+        if let Some(entry) = installed.find(&plugin.name) {
+            notes.push(format!("installed v{}", entry.version));
+        }
+        match latest {
+            None => notes.push(format!("no release for protocol {}", protocol)),
+            Some(release) if release.target(triple).is_none() => notes.push("source only".to_string()),
+            Some(_) => {},
+        }
+        let description = plugin
+            .description
+            .as_deref()
+            .map(|d| truncate(d, 60))
+            .unwrap_or_default();
+        let notes = if notes.is_empty() {
+            String::new()
+        } else {
+            format!("  ({})", notes.join(", "))
+        };
+
+        if use_color {
+            println!(
+                "  {}{:<name_width$}{}  {}{:<10}{}  {}{}{}{}",
+                colors::BOLD,
+                plugin.name,
+                colors::RESET,
+                colors::CYAN,
+                version,
+                colors::RESET,
+                description,
+                colors::YELLOW,
+                notes,
+                colors::RESET
+            );
+        } else {
+            println!(
+                "  {:<name_width$}  {:<10}  {}{}",
+                plugin.name, version, description, notes
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Show a plugin's entry in the plugin index
+pub fn index_info(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    use output::colors;
+    let use_color = output::supports_color();
+
+    let index = fetch_index()?;
+    let plugin = find_indexed(&index, name)?;
+    let protocol = host_protocol();
+    let triple = current_host_triple();
+    let latest = plugin.latest(&protocol);
+
+    // Header
+    let version = latest.map_or("-", |r| r.version.as_str());
+    if use_color {
+        println!(
+            "{}{}{} {}v{}{}",
+            colors::BOLD,
+            plugin.name,
+            colors::RESET,
+            colors::CYAN,
+            version,
+            colors::RESET
+        );
+    } else {
+        println!("{} v{}", plugin.name, version);
+    }
+    if let Some(desc) = &plugin.description {
+        println!("{}", desc);
+    }
+    println!();
+
+    // Info section
+    print_section("Info", use_color);
+    if let Some(lic) = &plugin.license {
+        print_info_row("License", lic, use_color);
+    }
+    if let Some(repo) = &plugin.repository {
+        print_info_row("Repository", repo, use_color);
+    }
+    if !plugin.keywords.is_empty() {
+        print_info_row("Keywords", &plugin.keywords.join(", "), use_color);
+    }
+    let installed = load_registry()?;
+    let installed = match installed.find(&plugin.name) {
+        Some(entry) => format!("v{} ({})", entry.version, entry.source),
+        None => "no".to_string(),
+    };
+    print_info_row("Installed", &installed, use_color);
+    println!();
+
+    // Releases, newest first
+    print_section("Releases", use_color);
+    let mut releases: Vec<_> = plugin.releases.iter().collect();
+    releases.sort_by_key(|r| std::cmp::Reverse(r.semver()));
+    if releases.is_empty() {
+        println!("  (none)");
+    }
+    for release in releases {
+        let mut notes = Vec::new();
+        if release.plugin != protocol {
+            notes.push("incompatible".to_string());
+        }
+        if release.target(triple).is_some() {
+            notes.push(format!("prebuilt for {}", triple));
+        } else if release.git.is_some() {
+            notes.push("source".to_string());
+        }
+        println!("  {}  {}", release.describe(), notes.join(", "));
+    }
+    Ok(())
+}
@@ -1,12 +1,16 @@
 //! Plugin installation logic
 
+use super::lock::write_lockfile;
+use super::signing::check_signature;
 use crate::output;
 use crate::plugins::{
-    detect_plugin_type, get_registry_path, DetectedPluginType, PluginCapabilities, PluginEntry, PluginRegistry,
-    PluginSource, PluginType,
+    detect_plugin_type, get_registry_path, DetectedPluginType, PluginCapabilities, PluginClient, PluginEntry,
+    PluginRegistry, PluginSource, PluginType,
 };
 use fs2::FileExt;
-use hodu_plugin::PLUGIN_VERSION;
+use hodu_plugin::manifest::validate_manifest;
+use hodu_plugin::signing::signature_path;
+use hodu_plugin::{methods::CUSTOM_OP_PREFIX, PLUGIN_VERSION};
 use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -46,184 +50,7 @@ impl Drop for LockFileGuard {
 const MAX_MANIFEST_SIZE: u64 = 1024 * 1024;
 
 /// Parsed manifest info: (name, version, plugin_version, plugin_type, capabilities)
-type ManifestInfo = (String, String, String, PluginType, PluginCapabilities);
-
-/// Official plugin registry URL
-pub const PLUGIN_REGISTRY_URL: &str = "https://raw.githubusercontent.com/daminstudio/hodu-plugins/main/plugins.toml";
-
-/// Version entry in the registry
-#[derive(Debug, serde::Deserialize)]
-pub struct PluginVersionEntry {
-    pub version: String,
-    pub tag: String,
-    /// Plugin protocol version requirement (e.g., "0.1" means compatible with 0.1.x)
-    pub plugin: String,
-}
-
-/// Plugin entry in the registry
-#[derive(Debug, serde::Deserialize)]
-pub struct RegistryPlugin {
-    pub name: String,
-    pub description: Option<String>,
-    pub git: String,
-    pub path: Option<String>,
-    pub versions: Vec<PluginVersionEntry>,
-}
-
-/// Registry file structure
-#[derive(Debug, serde::Deserialize)]
-pub struct PluginRegistryFile {
-    pub plugin: Vec<RegistryPlugin>,
-}
-
-pub fn install_from_registry(
-    name_with_version: &str,
-    tag_override: Option<&str>,
-    debug: bool,
-    force: bool,
-    verbose: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Parse name@version syntax
-    let (name, requested_version) = if let Some(at_pos) = name_with_version.find('@') {
-        let n = &name_with_version[..at_pos];
-        let v = &name_with_version[at_pos + 1..];
-        (n, Some(v))
-    } else {
-        (name_with_version, None)
-    };
-
-    output::fetching(&format!("plugin registry for '{}'", name));
-
-    // Fetch registry
-    let body = ureq::get(PLUGIN_REGISTRY_URL)
-        .call()
-        .map_err(|e| format!("Failed to fetch plugin registry: {}", e))?
-        .body_mut()
-        .read_to_string()
-        .map_err(|e| format!("Failed to read registry: {}", e))?;
-
-    let registry: PluginRegistryFile = toml::from_str(&body).map_err(|e| format!("Failed to parse registry: {}", e))?;
-
-    // Find plugin
-    const MAX_SHOWN_PLUGINS: usize = 20;
-    const MAX_DESC_LEN: usize = 60;
-    let plugin = registry.plugin.iter().find(|p| p.name == name).ok_or_else(|| {
-        let total = registry.plugin.len();
-        let available: Vec<_> = registry
-            .plugin
-            .iter()
-            .take(MAX_SHOWN_PLUGINS)
-            .map(|p| {
-                if let Some(desc) = &p.description {
-                    let truncated = if desc.len() > MAX_DESC_LEN {
-                        format!(
-                            "{}...",
-                            &desc[..desc
-                                .char_indices()
-                                .take_while(|(i, _)| *i < MAX_DESC_LEN)
-                                .last()
-                                .map(|(i, _)| i)
-                                .unwrap_or(0)]
-                        )
-                    } else {
-                        desc.clone()
-                    };
-                    format!("{} - {}", p.name, truncated)
-                } else {
-                    p.name.clone()
-                }
-            })
-            .collect();
-        let suffix = if total > MAX_SHOWN_PLUGINS {
-            format!("\n  (and {} more)", total - MAX_SHOWN_PLUGINS)
-        } else {
-            String::new()
-        };
-        format!(
-            "Plugin '{}' not found in registry.\n\nAvailable plugins:\n  {}{}",
-            name,
-            available.join("\n  "),
-            suffix
-        )
-    })?;
-
-    // Get host plugin version (major.minor)
-    let host_version_parts: Vec<&str> = PLUGIN_VERSION.split('.').collect();
-    let host_major_minor = if host_version_parts.len() >= 2 {
-        format!("{}.{}", host_version_parts[0], host_version_parts[1])
-    } else {
-        PLUGIN_VERSION.to_string()
-    };
-
-    // Filter compatible versions (same major.minor)
-    let compatible_versions: Vec<_> = plugin
-        .versions
-        .iter()
-        .filter(|v| v.plugin == host_major_minor)
-        .collect();
-
-    // Determine the tag to use
-    let tag = if let Some(t) = tag_override {
-        // --tag flag takes precedence
-        Some(t.to_string())
-    } else if let Some(ver) = requested_version {
-        // @version syntax
-        if ver == "latest" {
-            // Use the first compatible version (latest)
-            if compatible_versions.is_empty() {
-                return Err(format!(
-                    "No compatible version found for plugin protocol {}.\n\nAvailable versions:\n  {}",
-                    host_major_minor,
-                    plugin
-                        .versions
-                        .iter()
-                        .map(|v| format!("{} (protocol {})", v.version, v.plugin))
-                        .collect::<Vec<_>>()
-                        .join("\n  ")
-                )
-                .into());
-            }
-            compatible_versions.first().map(|v| v.tag.clone())
-        } else {
-            // Find specific version
-            let version_entry = plugin.versions.iter().find(|v| v.version == ver).ok_or_else(|| {
-                let available: Vec<_> = plugin.versions.iter().map(|v| v.version.as_str()).collect();
-                format!(
-                    "Version '{}' not found for plugin '{}'.\n\nAvailable versions:\n  {}",
-                    ver,
-                    name,
-                    available.join("\n  ")
-                )
-            })?;
-            Some(version_entry.tag.clone())
-        }
-    } else {
-        // No version specified, use latest compatible
-        if compatible_versions.is_empty() {
-            return Err(format!(
-                "No compatible version found for plugin protocol {}.\n\nAvailable versions:\n  {}",
-                host_major_minor,
-                plugin
-                    .versions
-                    .iter()
-                    .map(|v| format!("{} (protocol {})", v.version, v.plugin))
-                    .collect::<Vec<_>>()
-                    .join("\n  ")
-            )
-            .into());
-        }
-        compatible_versions.first().map(|v| v.tag.clone())
-    };
-
-    install_from_git(
-        &plugin.git,
-        plugin.path.as_deref(),
-        tag.as_deref(),
-        debug,
-        force,
-        verbose,
-    )
-}
+pub(super) type ManifestInfo = (String, String, String, PluginType, PluginCapabilities);
 
 /// Validate a subdirectory path for safety
 ///
@@ -286,6 +113,7 @@ pub fn install_from_git(
     url: &str,
     subdir: Option<&str>,
     tag: Option<&str>,
+    rev: Option<&str>,
     debug: bool,
     force: bool,
     verbose: bool,
@@ -308,7 +136,7 @@ pub fn install_from_git(
     if !verbose {
         git_cmd.arg("-q"); // Quiet unless verbose
     }
-    if tag.is_none() {
+    if tag.is_none() && rev.is_none() {
         git_cmd.arg("--depth").arg("1");
     }
     git_cmd.arg(url).arg(temp_dir.path());
@@ -332,8 +160,8 @@ pub fn install_from_git(
         return Err(format!("Failed to clone repository: {}", url).into());
     }
 
-    // Checkout tag/branch if specified (quietly)
-    if let Some(t) = tag {
+    // Checkout commit or tag/branch if specified (quietly)
+    if let Some(t) = rev.or(tag) {
         let status = Command::new("git")
             .arg("checkout")
             .arg("-q")
@@ -371,122 +199,127 @@ pub fn install_from_git(
         return Err("Security error: install path escapes temp directory".into());
     }
 
+    // Record the commit built, so the lockfile pins it even when a branch moves
+    let rev = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(temp_dir.path())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+
     // Install from the cloned path
     let source = PluginSource::Git {
         url: url.to_string(),
         tag: tag.map(|t| t.to_string()),
         subdir: subdir.map(|s| s.to_string()),
+        rev,
     };
-    install_from_path(&install_path, debug, force, verbose, source)
+    install_from_path(&install_path, debug, force, verbose, false, source)
     // temp_dir is automatically cleaned up when dropped
 }
 
-pub fn install_from_path(
+/// Archive extensions `hodu plugin install` accepts in place of a plugin directory
+pub const ARCHIVE_EXTENSIONS: &[&str] = &[".tar.gz", ".tgz", ".tar", ".zip"];
+
+/// Check if a path names a plugin archive, by extension
+pub fn is_archive(path: &Path) -> bool {
+    let name = path.to_string_lossy().to_lowercase();
+    ARCHIVE_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
+
+/// Install a plugin from a local directory or archive, without network access
+///
+/// An archive is unpacked to a temporary directory first; it holds a prebuilt plugin
+/// (`manifest.json`, the executable and optionally its signature), either at its root or in a
+/// single top-level directory. The source recorded is the directory or archive itself.
+pub fn install_from_local(
     path: &Path,
     debug: bool,
     force: bool,
     verbose: bool,
-    source: PluginSource,
+    require_signature: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let path = path.canonicalize()?;
-
-    // Check if it's a Cargo project
-    let cargo_toml = path.join("Cargo.toml");
-    if !cargo_toml.exists() {
-        return Err(format!("No Cargo.toml found at {}", path.display()).into());
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Failed to access {}: {}", path.display(), e))?;
+    let source = PluginSource::Local {
+        path: canonical.to_string_lossy().to_string(),
+    };
+    if canonical.is_dir() {
+        return install_from_path(&canonical, debug, force, verbose, require_signature, source);
     }
 
-    // Check Cargo.toml size before reading (reuse manifest size limit)
-    let cargo_size = std::fs::metadata(&cargo_toml)?.len();
-    if cargo_size > MAX_MANIFEST_SIZE {
+    let temp_dir =
+        TempDir::with_prefix("hodu_plugin_").map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let plugin_dir = extract_archive(&canonical, temp_dir.path())?;
+    install_from_path(&plugin_dir, debug, force, verbose, require_signature, source)
+    // temp_dir is automatically cleaned up when dropped
+}
+
+/// Unpack a plugin archive into `dest`, returning the directory holding the plugin
+///
+/// Entries that would land outside `dest` are not written.
+fn extract_archive(archive: &Path, dest: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let name = archive.to_string_lossy().to_lowercase();
+    let file = File::open(archive)?;
+    let unpacked = if name.ends_with(".zip") {
+        zip::ZipArchive::new(file)
+            .and_then(|mut zip| zip.extract(dest))
+            .map_err(|e| e.to_string())
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        tar::Archive::new(flate2::read::GzDecoder::new(file))
+            .unpack(dest)
+            .map_err(|e| e.to_string())
+    } else if name.ends_with(".tar") {
+        tar::Archive::new(file).unpack(dest).map_err(|e| e.to_string())
+    } else {
         return Err(format!(
-            "Cargo.toml too large: {} bytes (max: {} bytes)",
-            cargo_size, MAX_MANIFEST_SIZE
+            "Unsupported plugin archive {} (expected {})",
+            archive.display(),
+            ARCHIVE_EXTENSIONS.join(", ")
         )
         .into());
-    }
-
-    // Parse Cargo.toml to get the package name
-    let cargo_content = std::fs::read_to_string(&cargo_toml)?;
-    let package_name = parse_package_name(&cargo_content)
-        .ok_or_else(|| format!("Could not find package name in {}", cargo_toml.display()))?;
-
-    // Build the plugin with cargo (as executable)
-    output::compiling(&package_name);
-    let mut cargo_cmd = Command::new("cargo");
-    cargo_cmd.arg("build");
-    cargo_cmd.arg("-p").arg(&package_name);
-    if !debug {
-        cargo_cmd.arg("--release");
-    }
-    if !verbose {
-        cargo_cmd.arg("-q"); // Quiet unless verbose
-    }
-    cargo_cmd.current_dir(&path);
-
-    let cmd_output = cargo_cmd.output()?;
-    if !cmd_output.status.success() {
-        output::error("build failed");
-        let stderr = String::from_utf8_lossy(&cmd_output.stderr);
-        let stdout = String::from_utf8_lossy(&cmd_output.stdout);
-        let mut msg = String::from("Failed to build plugin:");
-        if !stderr.is_empty() {
-            msg.push_str("\n--- stderr ---\n");
-            msg.push_str(&stderr);
-        }
-        if !stdout.is_empty() {
-            msg.push_str("\n--- stdout ---\n");
-            msg.push_str(&stdout);
+    };
+    unpacked.map_err(|e| format!("Failed to unpack {}: {}", archive.display(), e))?;
+
+    // Archives made with `tar czf plugin.tar.gz my-plugin/` hold a single top-level directory
+    if !dest.join("manifest.json").exists() && !dest.join("Cargo.toml").exists() {
+        let entries: Vec<_> = std::fs::read_dir(dest)?.collect::<Result<_, _>>()?;
+        if let [entry] = entries.as_slice() {
+            if entry.file_type()?.is_dir() {
+                return Ok(entry.path());
+            }
         }
-        return Err(msg.into());
     }
+    Ok(dest.to_path_buf())
+}
 
-    // Find the built executable
-    let profile = if debug { "debug" } else { "release" };
-
-    // Try multiple possible target directories
-    let possible_target_dirs = vec![
-        path.join("target").join(profile),
-        path.parent()
-            .map(|p| p.join("target").join(profile))
-            .unwrap_or_default(),
-    ];
-
-    let mut bin_path = None;
-    for target_dir in &possible_target_dirs {
-        if !target_dir.exists() {
-            continue;
-        }
-
-        // Look for executable matching the package name
-        let candidate = target_dir.join(&package_name);
-        if candidate.exists() {
-            bin_path = Some(candidate);
-            break;
-        }
+pub fn install_from_path(
+    path: &Path,
+    debug: bool,
+    force: bool,
+    verbose: bool,
+    require_signature: bool,
+    source: PluginSource,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.canonicalize()?;
 
-        // On Windows, add .exe
-        #[cfg(windows)]
-        {
-            let candidate = target_dir.join(format!("{}.exe", package_name));
-            if candidate.exists() {
-                bin_path = Some(candidate);
-                break;
-            }
-        }
-    }
+    // A Cargo project is built; any other directory must hold a prebuilt plugin
+    let prebuilt = !path.join("Cargo.toml").exists();
+    let bin_path = if prebuilt {
+        find_prebuilt_binary(&path)?
+    } else {
+        build_plugin(&path, debug, verbose)?
+    };
 
-    let bin_path = bin_path.ok_or_else(|| {
-        format!(
-            "No executable found for package '{}'. Checked: {:?}",
-            package_name, possible_target_dirs
-        )
-    })?;
+    // Check the signature before the binary is run to detect its type, or installed
+    check_signature(&bin_path, prebuilt, require_signature)?;
 
     // Read manifest.json if it exists, or detect from binary
     let manifest_path = path.join("manifest.json");
     let (name, version, plugin_version, plugin_type, capabilities) = if manifest_path.exists() {
-        parse_manifest(&manifest_path, &package_name)?
+        parse_manifest(&manifest_path)?
     } else {
         // Try to detect from binary (spawn and initialize)
         let detected = detect_plugin_type(&bin_path)?;
@@ -629,6 +462,16 @@ pub fn install_from_path(
         }
     }
 
+    // Keep the signature next to the installed binary for `hodu plugin verify`
+    let source_signature = signature_path(&bin_path);
+    let dest_signature = signature_path(&dest_path);
+    if source_signature.exists() {
+        std::fs::copy(&source_signature, &dest_signature)?;
+    } else if dest_signature.exists() {
+        // Left by a previous install, it would not match the new binary
+        std::fs::remove_file(&dest_signature)?;
+    }
+
     // Make executable on Unix
     #[cfg(unix)]
     {
@@ -672,12 +515,16 @@ pub fn install_from_path(
         installed_at: chrono_now(),
         plugin_version,
         enabled: true,
+        // Reinstalling keeps a plugin sandboxed and limited
+        trusted: registry.find(&name).is_none_or(|existing| existing.trusted),
+        limits: registry.find(&name).map(|existing| existing.limits).unwrap_or_default(),
         dependencies: dependencies.clone(),
     };
 
     // Update registry
     registry.upsert(entry);
     registry.save(&registry_path)?;
+    write_lockfile(&registry)?;
 
     // Check dependencies after installation
     if !dependencies.is_empty() {
@@ -690,6 +537,218 @@ pub fn install_from_path(
     Ok(())
 }
 
+/// Register a plugin daemon reachable at `endpoint` instead of installing a binary
+///
+/// The daemon is asked for its name and capabilities once; later commands connect to it
+/// whenever the plugin is used.
+pub fn install_remote(endpoint: &str, force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    output::installing(endpoint);
+
+    let mut client = PluginClient::connect_remote(endpoint)?;
+    client.set_timeout(std::time::Duration::from_secs(30));
+    let info = client.initialize()?;
+    let _ = client.shutdown();
+
+    let has = |cap: &str| info.capabilities.iter().any(|c| c == cap);
+    let custom_ops: Vec<String> = info
+        .capabilities
+        .iter()
+        .filter_map(|c| c.strip_prefix(CUSTOM_OP_PREFIX).map(String::from))
+        .collect();
+    let is_backend = info.capabilities.iter().any(|c| c.starts_with("backend."));
+    let has_model_caps = has("format.load_model") || has("format.save_model");
+    let has_tensor_caps = has("format.load_tensor") || has("format.save_tensor");
+
+    // Plugins that only provide custom ops are registered as backends
+    let (plugin_type, mut capabilities) =
+        if is_backend || (!has_model_caps && !has_tensor_caps && !custom_ops.is_empty()) {
+            let devices = info.devices.clone().unwrap_or_default();
+            (
+                PluginType::Backend,
+                PluginCapabilities::backend(has("backend.run"), has("backend.build"), devices, vec![]),
+            )
+        } else if has_model_caps {
+            let extensions = info.model_extensions.clone().unwrap_or_default();
+            (
+                PluginType::ModelFormat,
+                PluginCapabilities::model_format(has("format.load_model"), has("format.save_model"), extensions),
+            )
+        } else if has_tensor_caps {
+            let extensions = info.tensor_extensions.clone().unwrap_or_default();
+            (
+                PluginType::TensorFormat,
+                PluginCapabilities::tensor_format(has("format.load_tensor"), has("format.save_tensor"), extensions),
+            )
+        } else {
+            return Err(format!("Plugin at {} reports no recognized capabilities", endpoint).into());
+        };
+    capabilities.custom_ops = custom_ops;
+
+    let registry_path = get_registry_path()?;
+    let lock_path = registry_path.with_extension("lock");
+    let lock_file = File::create(&lock_path).map_err(|e| format!("Failed to create lock file: {}", e))?;
+    lock_file
+        .lock_exclusive()
+        .map_err(|e| format!("Failed to acquire lock (another installation in progress?): {}", e))?;
+    let _lock_guard = LockFileGuard::new(lock_path, lock_file);
+    let mut registry = PluginRegistry::load(&registry_path)?;
+
+    if let Some(existing) = registry.find(&info.name) {
+        if !force {
+            return Err(format!(
+                "Plugin {} v{} is already installed. Use --force to replace it.",
+                existing.name, existing.version
+            )
+            .into());
+        }
+    }
+
+    let metadata = info.metadata.unwrap_or_default();
+    registry.upsert(PluginEntry {
+        name: info.name.clone(),
+        version: info.version.clone(),
+        description: metadata.description,
+        license: metadata.license,
+        plugin_type,
+        capabilities,
+        binary: String::new(),
+        source: PluginSource::Remote {
+            endpoint: endpoint.to_string(),
+        },
+        installed_at: chrono_now(),
+        plugin_version: info.plugin_version,
+        enabled: true,
+        trusted: registry.find(&info.name).is_none_or(|existing| existing.trusted),
+        limits: registry
+            .find(&info.name)
+            .map(|existing| existing.limits)
+            .unwrap_or_default(),
+        dependencies: Vec::new(),
+    });
+    registry.save(&registry_path)?;
+    write_lockfile(&registry)?;
+
+    output::installed(&format!("{} v{} ({})", info.name, info.version, endpoint));
+    Ok(())
+}
+
+/// Build the Cargo project at `path`, returning the executable it produced
+fn build_plugin(path: &Path, debug: bool, verbose: bool) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let cargo_toml = path.join("Cargo.toml");
+
+    // Check Cargo.toml size before reading (reuse manifest size limit)
+    let cargo_size = std::fs::metadata(&cargo_toml)?.len();
+    if cargo_size > MAX_MANIFEST_SIZE {
+        return Err(format!(
+            "Cargo.toml too large: {} bytes (max: {} bytes)",
+            cargo_size, MAX_MANIFEST_SIZE
+        )
+        .into());
+    }
+
+    // Parse Cargo.toml to get the package name
+    let cargo_content = std::fs::read_to_string(&cargo_toml)?;
+    let package_name = parse_package_name(&cargo_content)
+        .ok_or_else(|| format!("Could not find package name in {}", cargo_toml.display()))?;
+
+    // Build the plugin with cargo (as executable)
+    output::compiling(&package_name);
+    let mut cargo_cmd = Command::new("cargo");
+    cargo_cmd.arg("build");
+    cargo_cmd.arg("-p").arg(&package_name);
+    if !debug {
+        cargo_cmd.arg("--release");
+    }
+    if !verbose {
+        cargo_cmd.arg("-q"); // Quiet unless verbose
+    }
+    cargo_cmd.current_dir(path);
+
+    let cmd_output = cargo_cmd.output()?;
+    if !cmd_output.status.success() {
+        output::error("build failed");
+        let stderr = String::from_utf8_lossy(&cmd_output.stderr);
+        let stdout = String::from_utf8_lossy(&cmd_output.stdout);
+        let mut msg = String::from("Failed to build plugin:");
+        if !stderr.is_empty() {
+            msg.push_str("\n--- stderr ---\n");
+            msg.push_str(&stderr);
+        }
+        if !stdout.is_empty() {
+            msg.push_str("\n--- stdout ---\n");
+            msg.push_str(&stdout);
+        }
+        return Err(msg.into());
+    }
+
+    // Find the built executable
+    let profile = if debug { "debug" } else { "release" };
+
+    // Try multiple possible target directories
+    let possible_target_dirs = vec![
+        path.join("target").join(profile),
+        path.parent()
+            .map(|p| p.join("target").join(profile))
+            .unwrap_or_default(),
+    ];
+
+    let mut bin_path = None;
+    for target_dir in &possible_target_dirs {
+        if !target_dir.exists() {
+            continue;
+        }
+
+        // Look for executable matching the package name
+        let candidate = target_dir.join(&package_name);
+        if candidate.exists() {
+            bin_path = Some(candidate);
+            break;
+        }
+
+        // On Windows, add .exe
+        #[cfg(windows)]
+        {
+            let candidate = target_dir.join(format!("{}.exe", package_name));
+            if candidate.exists() {
+                bin_path = Some(candidate);
+                break;
+            }
+        }
+    }
+
+    let bin_path = bin_path.ok_or_else(|| {
+        format!(
+            "No executable found for package '{}'. Checked: {:?}",
+            package_name, possible_target_dirs
+        )
+    })?;
+
+    Ok(bin_path)
+}
+
+/// Find the executable of a prebuilt plugin: the one named after the plugin in its manifest.json
+fn find_prebuilt_binary(path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let manifest_path = path.join("manifest.json");
+    if !manifest_path.exists() {
+        return Err(format!(
+            "No Cargo.toml or manifest.json found at {}. A prebuilt plugin needs a manifest.json \
+             next to its executable.",
+            path.display()
+        )
+        .into());
+    }
+    let (name, ..) = parse_manifest(&manifest_path)?;
+
+    let mut candidates = vec![path.join(&name)];
+    if cfg!(windows) {
+        candidates.insert(0, path.join(format!("{}.exe", name)));
+    }
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| format!("No executable named '{}' found at {}", name, path.display()).into())
+}
+
 /// Read manifest file with size limit check
 fn read_manifest_checked(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
     let metadata = std::fs::metadata(path)?;
@@ -704,28 +763,24 @@ fn read_manifest_checked(path: &Path) -> Result<String, Box<dyn std::error::Erro
     Ok(std::fs::read_to_string(path)?)
 }
 
-fn parse_manifest(manifest_path: &Path, package_name: &str) -> Result<ManifestInfo, Box<dyn std::error::Error>> {
+/// Reject a manifest that does not match the manifest schema, listing every problem in it
+fn check_manifest(path: &Path, content: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let errors = validate_manifest(content);
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let errors: Vec<String> = errors.iter().map(|e| format!("  {}:{}", path.display(), e)).collect();
+    Err(format!("Invalid manifest.json:\n{}", errors.join("\n")).into())
+}
+
+pub(super) fn parse_manifest(manifest_path: &Path) -> Result<ManifestInfo, Box<dyn std::error::Error>> {
     let manifest_content = read_manifest_checked(manifest_path)?;
+    check_manifest(manifest_path, &manifest_content)?;
     let manifest: serde_json::Value = serde_json::from_str(&manifest_content)?;
 
-    // Warn on missing recommended fields
-    let name = match manifest["name"].as_str() {
-        Some(n) => n.to_string(),
-        None => {
-            eprintln!(
-                "Warning: manifest.json missing 'name' field, using package name '{}'",
-                package_name
-            );
-            package_name.to_string()
-        },
-    };
-    let version = match manifest["version"].as_str() {
-        Some(v) => v.to_string(),
-        None => {
-            eprintln!("Warning: manifest.json missing 'version' field, using default '0.1.0'");
-            "0.1.0".to_string()
-        },
-    };
+    // `name` and `version` are required by the schema
+    let name = manifest["name"].as_str().unwrap_or_default().to_string();
+    let version = manifest["version"].as_str().unwrap_or_default().to_string();
     let plugin_version = match manifest["plugin_version"].as_str() {
         Some(v) => v.to_string(),
         None => {
@@ -765,55 +820,67 @@ fn parse_manifest(manifest_path: &Path, package_name: &str) -> Result<ManifestIn
         })
         .unwrap_or(false);
 
+    // Custom ops are declared as `op.<name>` capabilities
+    let custom_ops: Vec<String> = caps
+        .map(|c| {
+            c.iter()
+                .filter_map(|v| v.as_str()?.strip_prefix(CUSTOM_OP_PREFIX).map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
     let extensions: Vec<String> = manifest["extensions"]
         .as_array()
         .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
         .unwrap_or_default();
 
-    let (plugin_type, capabilities) = if is_backend {
-        let devices: Vec<String> = manifest["devices"]
-            .as_array()
-            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
-            .unwrap_or_default();
-        let runner = caps
-            .map(|c| c.iter().any(|v| v.as_str() == Some("backend.run")))
-            .unwrap_or(false);
-        let builder = caps
-            .map(|c| c.iter().any(|v| v.as_str() == Some("backend.build")))
-            .unwrap_or(false);
-        (
-            PluginType::Backend,
-            PluginCapabilities::backend(runner, builder, devices, vec![]),
-        )
-    } else if has_model_caps {
-        let load_model = caps
-            .map(|c| c.iter().any(|v| v.as_str() == Some("format.load_model")))
-            .unwrap_or(false);
-        let save_model = caps
-            .map(|c| c.iter().any(|v| v.as_str() == Some("format.save_model")))
-            .unwrap_or(false);
-        (
-            PluginType::ModelFormat,
-            PluginCapabilities::model_format(load_model, save_model, extensions),
-        )
-    } else if has_tensor_caps {
-        let load_tensor = caps
-            .map(|c| c.iter().any(|v| v.as_str() == Some("format.load_tensor")))
-            .unwrap_or(false);
-        let save_tensor = caps
-            .map(|c| c.iter().any(|v| v.as_str() == Some("format.save_tensor")))
-            .unwrap_or(false);
-        (
-            PluginType::TensorFormat,
-            PluginCapabilities::tensor_format(load_tensor, save_tensor, extensions),
-        )
-    } else {
-        // No recognized capabilities - reject invalid manifest instead of silent fallback
-        return Err("Invalid manifest.json: no recognized capabilities found. \
+    // Plugins that only provide custom ops are registered as backends
+    let (plugin_type, mut capabilities) =
+        if is_backend || (!has_model_caps && !has_tensor_caps && !custom_ops.is_empty()) {
+            let devices: Vec<String> = manifest["devices"]
+                .as_array()
+                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                .unwrap_or_default();
+            let runner = caps
+                .map(|c| c.iter().any(|v| v.as_str() == Some("backend.run")))
+                .unwrap_or(false);
+            let builder = caps
+                .map(|c| c.iter().any(|v| v.as_str() == Some("backend.build")))
+                .unwrap_or(false);
+            (
+                PluginType::Backend,
+                PluginCapabilities::backend(runner, builder, devices, vec![]),
+            )
+        } else if has_model_caps {
+            let load_model = caps
+                .map(|c| c.iter().any(|v| v.as_str() == Some("format.load_model")))
+                .unwrap_or(false);
+            let save_model = caps
+                .map(|c| c.iter().any(|v| v.as_str() == Some("format.save_model")))
+                .unwrap_or(false);
+            (
+                PluginType::ModelFormat,
+                PluginCapabilities::model_format(load_model, save_model, extensions),
+            )
+        } else if has_tensor_caps {
+            let load_tensor = caps
+                .map(|c| c.iter().any(|v| v.as_str() == Some("format.load_tensor")))
+                .unwrap_or(false);
+            let save_tensor = caps
+                .map(|c| c.iter().any(|v| v.as_str() == Some("format.save_tensor")))
+                .unwrap_or(false);
+            (
+                PluginType::TensorFormat,
+                PluginCapabilities::tensor_format(load_tensor, save_tensor, extensions),
+            )
+        } else {
+            // No recognized capabilities - reject invalid manifest instead of silent fallback
+            return Err("Invalid manifest.json: no recognized capabilities found. \
              Expected one or more of: backend.run, backend.build, format.load_model, \
-             format.save_model, format.load_tensor, format.save_tensor"
-            .into());
-    };
+             format.save_model, format.load_tensor, format.save_tensor, op.<name>"
+                .into());
+        };
+    capabilities.custom_ops = custom_ops;
 
     Ok((name, version, plugin_version, plugin_type, capabilities))
 }
@@ -847,16 +914,3 @@ pub fn parse_package_name(content: &str) -> Option<String> {
     let cargo: CargoToml = toml::from_str(content).ok()?;
     cargo.package?.name
 }
-
-/// Fetch official registry
-pub fn fetch_official_registry() -> Result<PluginRegistryFile, Box<dyn std::error::Error>> {
-    let body = ureq::get(PLUGIN_REGISTRY_URL)
-        .call()
-        .map_err(|e| format!("Failed to fetch plugin registry: {}", e))?
-        .body_mut()
-        .read_to_string()
-        .map_err(|e| format!("Failed to read registry: {}", e))?;
-
-    let registry: PluginRegistryFile = toml::from_str(&body).map_err(|e| format!("Failed to parse registry: {}", e))?;
-    Ok(registry)
-}
@@ -0,0 +1,185 @@
+//! Plugin lockfile (hodu-plugins.lock) and `hodu plugin sync`
+//!
+//! The lockfile records the exact version and source of every installed plugin: the commit of a
+//! plugin built from git, and the checksums of the prebuilt zips of one from the plugin index.
+//! It is rewritten whenever a plugin is installed or removed. Copied to another machine,
+//! `hodu plugin sync <file>` installs the same set there.
+
+use super::index::install_locked;
+use super::install::{install_from_git, install_from_local, install_remote};
+use super::{remove_plugin, RemoveArgs, SyncArgs};
+use crate::output;
+use crate::plugins::{load_registry, PluginRegistry, PluginSource};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the lockfile
+pub const LOCKFILE_NAME: &str = "hodu-plugins.lock";
+
+/// Comment at the top of a written lockfile
+const LOCKFILE_HEADER: &str = "# This file is generated by hodu and records the installed plugins.\n\
+                               # Run `hodu plugin sync <this file>` to install the same plugins elsewhere.\n\n";
+
+/// Path of the lockfile kept for the installed plugins (~/.hodu/hodu-plugins.lock)
+pub fn lockfile_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    Ok(home.join(".hodu").join(LOCKFILE_NAME))
+}
+
+/// Lockfile contents
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Schema version for future compatibility
+    pub version: u32,
+    #[serde(default, rename = "plugin")]
+    pub plugins: Vec<LockedPlugin>,
+}
+
+/// A plugin pinned by the lockfile
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LockedPlugin {
+    pub name: String,
+    pub version: String,
+    pub source: PluginSource,
+}
+
+impl Lockfile {
+    /// Current schema version
+    pub const CURRENT_VERSION: u32 = 1;
+
+    /// Lock the plugins of a registry, by name
+    pub fn from_registry(registry: &PluginRegistry) -> Self {
+        let mut plugins: Vec<_> = registry
+            .plugins
+            .iter()
+            .map(|p| LockedPlugin {
+                name: p.name.clone(),
+                version: p.version.clone(),
+                source: p.source.clone(),
+            })
+            .collect();
+        plugins.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            version: Self::CURRENT_VERSION,
+            plugins,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let lockfile: Self =
+            toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+        if lockfile.version > Self::CURRENT_VERSION {
+            return Err(format!(
+                "{} has lockfile version {}, newer than this hodu supports ({}); update hodu",
+                path.display(),
+                lockfile.version,
+                Self::CURRENT_VERSION
+            )
+            .into());
+        }
+        Ok(lockfile)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string_pretty(self)?;
+        std::fs::write(path, format!("{}{}", LOCKFILE_HEADER, content))?;
+        Ok(())
+    }
+}
+
+/// Rewrite the lockfile after the installed plugins changed
+pub fn write_lockfile(registry: &PluginRegistry) -> Result<(), Box<dyn std::error::Error>> {
+    Lockfile::from_registry(registry).save(&lockfile_path()?)
+}
+
+pub fn sync_plugins(args: SyncArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let path = match args.lockfile {
+        Some(path) => path,
+        None => lockfile_path()?,
+    };
+    let lockfile = Lockfile::load(&path)?;
+    let registry = load_registry()?;
+
+    let mut synced = 0;
+    let mut failed = Vec::new();
+    for locked in &lockfile.plugins {
+        let current = registry
+            .find(&locked.name)
+            .is_some_and(|p| p.version == locked.version && p.source == locked.source);
+        if current {
+            continue;
+        }
+
+        output::installing(&format!("{} v{} ({})", locked.name, locked.version, locked.source));
+        match install_locked_plugin(locked) {
+            Ok(()) => synced += 1,
+            Err(e) => {
+                output::warning(&format!("Failed to install {}: {}", locked.name, e));
+                failed.push(locked.name.as_str());
+            },
+        }
+    }
+
+    let mut pruned = 0;
+    if args.prune {
+        for plugin in &registry.plugins {
+            if !lockfile.plugins.iter().any(|locked| locked.name == plugin.name) {
+                remove_plugin(RemoveArgs {
+                    name: plugin.name.clone(),
+                })?;
+                pruned += 1;
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(format!(
+            "Failed to sync {} of {} plugins: {}",
+            failed.len(),
+            lockfile.plugins.len(),
+            failed.join(", ")
+        )
+        .into());
+    }
+    output::finished(&format!(
+        "{} plugins in sync with {} ({} installed, {} removed)",
+        lockfile.plugins.len(),
+        path.display(),
+        synced,
+        pruned
+    ));
+    Ok(())
+}
+
+/// Install a plugin exactly as the lockfile records it
+fn install_locked_plugin(locked: &LockedPlugin) -> Result<(), Box<dyn std::error::Error>> {
+    match &locked.source {
+        PluginSource::Index {
+            url,
+            requirement,
+            checksums,
+        } => install_locked(url, &locked.name, &locked.version, requirement.clone(), checksums),
+        PluginSource::Git { url, tag, subdir, rev } => install_from_git(
+            url,
+            subdir.as_deref(),
+            tag.as_deref(),
+            rev.as_deref(),
+            false,
+            true,
+            false,
+        ),
+        PluginSource::Local { path } => {
+            let path_buf = PathBuf::from(path);
+            if !path_buf.exists() {
+                return Err(format!("Source path does not exist here: {}", path).into());
+            }
+            install_from_local(&path_buf, false, true, false, false)
+        },
+        PluginSource::Remote { endpoint } => install_remote(endpoint, true),
+        PluginSource::CratesIo => Err("crates.io source (reinstall with --git or --path)".into()),
+    }
+}
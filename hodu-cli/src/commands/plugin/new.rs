@@ -0,0 +1,172 @@
+//! `hodu plugin new`: create a plugin project from the SDK templates
+
+use super::NewArgs;
+use crate::output;
+use crate::plugins::{BACKEND_PREFIX, FORMAT_PREFIX};
+use clap::ValueEnum;
+use hodu_plugin::index::host_protocol;
+use hodu_plugin::manifest::validate_manifest;
+use hodu_plugin::PLUGIN_VERSION;
+use std::path::Path;
+use std::process::Command;
+
+const CARGO_TOML: &str = include_str!("../../../../hodu-plugin-sdk/template/Cargo.toml.template");
+const MANIFEST_BACKEND: &str = include_str!("../../../../hodu-plugin-sdk/template/manifest.backend.json");
+const MANIFEST_MODEL_FORMAT: &str = include_str!("../../../../hodu-plugin-sdk/template/manifest.model_format.json");
+const MANIFEST_TENSOR_FORMAT: &str = include_str!("../../../../hodu-plugin-sdk/template/manifest.tensor_format.json");
+const MAIN_BACKEND: &str = include_str!("../../../../hodu-plugin-sdk/template/src/main.backend.rs");
+const MAIN_MODEL_FORMAT: &str = include_str!("../../../../hodu-plugin-sdk/template/src/main.model_format.rs");
+const MAIN_TENSOR_FORMAT: &str = include_str!("../../../../hodu-plugin-sdk/template/src/main.tensor_format.rs");
+
+/// Kind of plugin to create
+#[derive(Clone, Copy, ValueEnum)]
+pub enum PluginKind {
+    Backend,
+    ModelFormat,
+    TensorFormat,
+}
+
+impl PluginKind {
+    /// Prefix of the plugin's name
+    fn prefix(self) -> &'static str {
+        match self {
+            PluginKind::Backend => BACKEND_PREFIX,
+            PluginKind::ModelFormat | PluginKind::TensorFormat => FORMAT_PREFIX,
+        }
+    }
+
+    /// Template manifest.json and src/main.rs
+    fn templates(self) -> (&'static str, &'static str) {
+        match self {
+            PluginKind::Backend => (MANIFEST_BACKEND, MAIN_BACKEND),
+            PluginKind::ModelFormat => (MANIFEST_MODEL_FORMAT, MAIN_MODEL_FORMAT),
+            PluginKind::TensorFormat => (MANIFEST_TENSOR_FORMAT, MAIN_TENSOR_FORMAT),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PluginKind::Backend => "backend",
+            PluginKind::ModelFormat => "model format",
+            PluginKind::TensorFormat => "tensor format",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            PluginKind::Backend => "Backend plugin for Hodu",
+            PluginKind::ModelFormat => "Model format plugin for Hodu",
+            PluginKind::TensorFormat => "Tensor format plugin for Hodu",
+        }
+    }
+}
+
+pub fn new_plugin(args: NewArgs) -> Result<(), Box<dyn std::error::Error>> {
+    // Installed plugins are found by their prefixed name, so `new cuda` creates hodu-backend-cuda
+    let prefix = args.kind.prefix();
+    let name = if args.name.starts_with(prefix) {
+        args.name.clone()
+    } else {
+        format!("{}{}", prefix, args.name)
+    };
+    validate_name(&name)?;
+
+    let extension = args
+        .extension
+        .clone()
+        .unwrap_or_else(|| name[prefix.len()..].replace(['-', '_'], ""));
+    if extension.is_empty() || !extension.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!(
+            "Invalid extension '{}': use letters and digits only, without the dot (e.g. --extension onnx)",
+            extension
+        )
+        .into());
+    }
+
+    let dir = args.path.clone().unwrap_or_else(|| name.clone().into());
+    if dir.exists() {
+        return Err(format!("{} already exists", dir.display()).into());
+    }
+
+    let description = args.description.as_deref().unwrap_or(args.kind.description());
+    let render = |template: &str| {
+        template
+            .replace("{{NAME}}", &name)
+            .replace("{{SDK_VERSION}}", &host_protocol())
+            .replace("{{PLUGIN_VERSION}}", PLUGIN_VERSION)
+            .replace("{{DESCRIPTION}}", &escape(description))
+            .replace("{{LICENSE}}", &escape(&args.license))
+            .replace("{{EXTENSION}}", &extension)
+    };
+    let (manifest_template, main_template) = args.kind.templates();
+    let manifest = render(manifest_template);
+
+    // The templates and manifest rules live apart; catch them drifting before writing anything
+    if let Some(error) = validate_manifest(&manifest).first() {
+        return Err(format!("Template manifest.json is invalid: {}", error).into());
+    }
+
+    std::fs::create_dir_all(dir.join("src"))?;
+    std::fs::write(dir.join("Cargo.toml"), render(CARGO_TOML))?;
+    std::fs::write(dir.join("manifest.json"), manifest)?;
+    std::fs::write(dir.join("src").join("main.rs"), render(main_template))?;
+    std::fs::write(dir.join(".gitignore"), "/target\n")?;
+
+    if !args.no_git && !in_git_repository(&dir) {
+        init_git(&dir);
+    }
+
+    output::finished(&format!(
+        "created {} plugin `{}` at {}",
+        args.kind.label(),
+        name,
+        dir.display()
+    ));
+    println!();
+    println!("Next steps:");
+    println!("  cd {}", dir.display());
+    println!("  hodu plugin install --path .");
+    Ok(())
+}
+
+/// Plugin names become crate and binary names
+fn validate_name(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        && !name.ends_with(['-', '_']);
+    if !valid {
+        return Err(format!(
+            "Invalid plugin name '{}': use lowercase letters, digits, '-' and '_', starting with a letter",
+            name
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Escape text for a string in both JSON and TOML
+fn escape(text: &str) -> String {
+    let quoted = serde_json::Value::String(text.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Check if a directory is inside a git work tree, like `cargo new` does before running `git init`
+fn in_git_repository(dir: &Path) -> bool {
+    let Some(parent) = dir.canonicalize().ok().and_then(|d| d.parent().map(Path::to_path_buf)) else {
+        return false;
+    };
+    Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(parent)
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+fn init_git(dir: &Path) {
+    let status = Command::new("git").args(["init", "-q"]).current_dir(dir).status();
+    if !status.is_ok_and(|s| s.success()) {
+        output::warning("Failed to run `git init`; the project was created without a repository");
+    }
+}
@@ -0,0 +1,84 @@
+//! Plugin daemon pool commands (ps, stop)
+
+#[cfg(unix)]
+use crate::output;
+#[cfg(unix)]
+use crate::plugins::{backend_plugin_name, format_plugin_name};
+
+#[cfg(unix)]
+use super::format_uptime;
+
+#[cfg(unix)]
+pub fn ps_plugins() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::plugins::{list_daemons, PluginConfig};
+
+    let daemons = list_daemons()?;
+    if daemons.is_empty() {
+        println!("No plugin daemons running.");
+        if !PluginConfig::load()?.pool().enabled {
+            println!("Enable the pool with `enabled = true` under [pool] in ~/.hodu/config.toml.");
+        }
+        return Ok(());
+    }
+
+    println!(
+        "{:<28} {:>4} {:>8}  {:<6} {:>8}  VERSION",
+        "NAME", "SLOT", "PID", "STATUS", "UPTIME"
+    );
+    let now = chrono::Utc::now();
+    for daemon in daemons {
+        let state = &daemon.state;
+        let uptime = chrono::DateTime::parse_from_rfc3339(&state.started_at)
+            .map(|started| format_uptime((now - started.with_timezone(&chrono::Utc)).num_seconds()))
+            .unwrap_or_else(|_| "-".to_string());
+        println!(
+            "{:<28} {:>4} {:>8}  {:<6} {:>8}  {}",
+            state.plugin,
+            state.slot,
+            state.pid,
+            if daemon.busy { "busy" } else { "idle" },
+            uptime,
+            state.version
+        );
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+pub fn stop_plugins(name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::plugins::stop_daemons;
+
+    // Daemons of removed plugins are stopped too, so names are matched without the registry
+    let names: Vec<String> = match name {
+        Some(name) => vec![name.to_string(), backend_plugin_name(name), format_plugin_name(name)],
+        None => Vec::new(),
+    };
+    let report = stop_daemons(|plugin| names.is_empty() || names.iter().any(|name| name == plugin))?;
+
+    for state in &report.busy {
+        output::warning(&format!(
+            "{} (slot {}, pid {}) is in use, leaving it running",
+            state.plugin, state.slot, state.pid
+        ));
+    }
+    if report.stopped.is_empty() && report.busy.is_empty() {
+        match name {
+            Some(name) => println!("No daemons of '{}' running.", name),
+            None => println!("No plugin daemons running."),
+        }
+    }
+    for state in &report.stopped {
+        output::stopped(&format!("{} (slot {}, pid {})", state.plugin, state.slot, state.pid));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn ps_plugins() -> Result<(), Box<dyn std::error::Error>> {
+    Err("The plugin daemon pool is only supported on Unix".into())
+}
+
+#[cfg(not(unix))]
+pub fn stop_plugins(_name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    Err("The plugin daemon pool is only supported on Unix".into())
+}
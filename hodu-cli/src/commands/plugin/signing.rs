@@ -0,0 +1,160 @@
+//! Plugin signing commands (keygen, sign, keys) and signature checks for install and verify
+
+use super::{KeygenArgs, KeysArgs, SignArgs};
+use crate::output;
+use hodu_plugin::signing::{signature_path, verify_file, PublicKey, SignatureError, SigningKey, TrustedKeys};
+use std::path::{Path, PathBuf};
+
+/// Path of the public keys trusted to sign plugins (~/.hodu/trusted_keys)
+pub fn trusted_keys_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    Ok(home.join(".hodu").join("trusted_keys"))
+}
+
+/// Load the public keys trusted to sign plugins
+pub fn load_trusted_keys() -> Result<TrustedKeys, Box<dyn std::error::Error>> {
+    let path = trusted_keys_path()?;
+    TrustedKeys::load(&path).map_err(|e| format!("Failed to load {}: {}", path.display(), e).into())
+}
+
+/// Check the signature of a plugin binary about to be installed
+///
+/// A binary without a signature is installed unless `require_signature` is set; a signature that
+/// does not verify always fails the install. Returns the name the signer is trusted under.
+pub fn check_signature(
+    bin_path: &Path,
+    prebuilt: bool,
+    require_signature: bool,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let trusted = load_trusted_keys()?;
+    match verify_file(bin_path, &trusted) {
+        Ok(signer) => {
+            output::verified(&format!("signature of {}", signer));
+            Ok(Some(signer.to_string()))
+        },
+        Err(SignatureError::Missing(_)) if require_signature => Err(format!(
+            "{} is not signed (remove --require-signature to install it anyway)",
+            bin_path.display()
+        )
+        .into()),
+        Err(SignatureError::Missing(_)) => {
+            // Binaries built here from source are never signed
+            if prebuilt {
+                output::warning(&format!("{} is not signed", bin_path.display()));
+            }
+            Ok(None)
+        },
+        Err(SignatureError::UntrustedKey(key)) => Err(format!(
+            "{} is signed with untrusted key {}. If you trust its publisher, run \
+             `hodu plugin keys --add <KEY>` with their public key and install again.",
+            bin_path.display(),
+            key
+        )
+        .into()),
+        Err(e) => Err(format!("Signature check failed for {}: {}", bin_path.display(), e).into()),
+    }
+}
+
+/// Describe the signature of an installed binary for `hodu plugin verify`
+///
+/// Returns the signer's name, `None` for an unsigned binary, or the problem found.
+pub fn signature_status(bin_path: &Path, trusted: &TrustedKeys) -> Result<Option<String>, String> {
+    match verify_file(bin_path, trusted) {
+        Ok(signer) => Ok(Some(signer.to_string())),
+        Err(SignatureError::Missing(_)) => Ok(None),
+        Err(e) => Err(format!("signature: {}", e)),
+    }
+}
+
+pub fn keygen_plugin(args: KeygenArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let public_path = public_key_path(&args.output);
+    if !args.force {
+        for path in [&args.output, &public_path] {
+            if path.exists() {
+                return Err(format!("{} already exists. Use --force to overwrite it.", path.display()).into());
+            }
+        }
+    }
+
+    let key = SigningKey::generate()?;
+    key.save(&args.output)?;
+    let public_key = key.public_key();
+    std::fs::write(&public_path, format!("{}\n", public_key.to_base64()))?;
+
+    output::finished(&format!(
+        "key {} (secret: {}, public: {})",
+        public_key,
+        args.output.display(),
+        public_path.display()
+    ));
+    println!("{}", public_key.to_base64());
+    Ok(())
+}
+
+/// Public key file written next to a secret key file
+fn public_key_path(secret: &Path) -> PathBuf {
+    let mut name = secret.file_name().unwrap_or_default().to_os_string();
+    name.push(".pub");
+    secret.with_file_name(name)
+}
+
+pub fn sign_plugin(args: SignArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let key = SigningKey::load(&args.key).map_err(|e| format!("Failed to load {}: {}", args.key.display(), e))?;
+    for file in &args.files {
+        key.sign_file(file)
+            .map_err(|e| format!("Failed to sign {}: {}", file.display(), e))?;
+        output::signed(&format!(
+            "{} with key {} ({})",
+            file.display(),
+            key.public_key(),
+            signature_path(file).display()
+        ));
+    }
+    Ok(())
+}
+
+pub fn keys_plugin(args: KeysArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let path = trusted_keys_path()?;
+    let mut trusted = load_trusted_keys()?;
+
+    if args.add.is_none() && args.remove.is_none() {
+        if trusted.is_empty() {
+            println!("No trusted signing keys. Add one with `hodu plugin keys --add <KEY>`.");
+        }
+        for (key, name) in trusted.iter() {
+            println!("{}  {}  {}", key, key.to_base64(), name);
+        }
+        return Ok(());
+    }
+
+    if let Some(key) = &args.remove {
+        let key = parse_public_key(key)?;
+        if !trusted.remove(&key) {
+            return Err(format!("Key {} is not trusted.", key).into());
+        }
+        output::removed(&format!("trusted key {}", key));
+    }
+    if let Some(key) = &args.add {
+        let key = parse_public_key(key)?;
+        let name = args.name.clone().unwrap_or_default();
+        trusted.add(key, name.as_str());
+        output::finished(&format!("trusting key {} {}", key, name));
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    trusted.save(&path)?;
+    Ok(())
+}
+
+/// Parse a base64 public key, or read it from a `.pub` file
+fn parse_public_key(key: &str) -> Result<PublicKey, Box<dyn std::error::Error>> {
+    let path = Path::new(key);
+    let text = if path.is_file() {
+        std::fs::read_to_string(path)?
+    } else {
+        key.to_string()
+    };
+    PublicKey::from_base64(&text).map_err(|e| format!("Invalid public key '{}': {}", key, e).into())
+}
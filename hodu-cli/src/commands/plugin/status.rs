@@ -0,0 +1,71 @@
+//! Plugin status command - ask a running plugin what it is doing (`$/status`)
+
+use super::{find_plugin, format_uptime, print_info_row, print_section, StatusArgs};
+use crate::output;
+use crate::plugins::{load_registry, PluginManager};
+use hodu_plugin::rpc::StatusResult;
+
+pub fn status_plugin(mut args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if output::is_json() {
+        args.format = "json".to_string();
+    }
+    let registry = load_registry()?;
+    let plugin = find_plugin(&registry, &args.name)?;
+
+    // Connects to the plugin's daemon when it has one, otherwise spawns it
+    let mut manager = PluginManager::new()?;
+    let status = manager.get_plugin(&plugin.name)?.status()?;
+
+    match args.format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&status)?),
+        _ => print_status(&status),
+    }
+    Ok(())
+}
+
+fn print_status(status: &StatusResult) {
+    let use_color = output::supports_color();
+
+    println!(
+        "{} v{} (pid {})",
+        output::sanitize_for_terminal(&status.name),
+        output::sanitize_for_terminal(&status.version),
+        status.pid
+    );
+    println!();
+
+    print_section("Status", use_color);
+    print_info_row("Uptime", &format_uptime((status.uptime_ms / 1000) as i64), use_color);
+    let memory = match (status.resident_memory_bytes, status.peak_memory_bytes) {
+        (Some(rss), Some(peak)) => format!(
+            "{} (peak {})",
+            output::format_size(rss as usize),
+            output::format_size(peak as usize)
+        ),
+        (Some(rss), None) => output::format_size(rss as usize),
+        _ => "unknown".to_string(),
+    };
+    print_info_row("Memory", &memory, use_color);
+    if !status.features.is_empty() {
+        print_info_row("Features", &status.features.join(", "), use_color);
+    }
+    print_info_row("Handlers", &status.handlers.join(", "), use_color);
+    println!();
+
+    print_section("Active Requests", use_color);
+    if status.active_requests.is_empty() {
+        println!("  (none)");
+        return;
+    }
+    println!("  {:<12} {:<32} {:>10}", "ID", "METHOD", "ELAPSED");
+    for request in &status.active_requests {
+        let elapsed = output::format_duration(request.elapsed_ms as f64 / 1000.0);
+        println!(
+            "  {:<12} {:<32} {:>10}{}",
+            output::sanitize_for_terminal(&request.id.to_string()),
+            output::sanitize_for_terminal(&request.method),
+            elapsed,
+            if request.cancelled { "  (cancelled)" } else { "" }
+        );
+    }
+}
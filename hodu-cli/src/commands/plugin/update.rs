@@ -1,14 +1,29 @@
 //! Plugin update logic
 
-use super::install::{fetch_official_registry, install_from_git, install_from_path, install_from_registry};
+use super::index::{fetch_index_from, index_url, install_release};
+use super::install::{install_from_git, install_from_local};
 use crate::output;
 use crate::plugins::{backend_plugin_name, format_plugin_name, load_registry, PluginSource};
-use hodu_plugin::PLUGIN_VERSION;
+use hodu_plugin::index::{host_protocol, parse_spec};
+use semver::{Version, VersionReq};
 use std::path::PathBuf;
 
-pub fn update_plugins(name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+/// Update plugins, or the one named by `spec` (`name[@requirement]`)
+///
+/// Plugins in the plugin index move to the newest compatible release matching their version
+/// requirement. A requirement in `spec` replaces the recorded one (`@latest` removes it), and
+/// may move the plugin to an older release.
+pub fn update_plugins(spec: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
     let registry = load_registry()?;
 
+    let (name, new_requirement) = match spec {
+        Some(spec) => {
+            let (name, requirement) = parse_spec(spec)?;
+            (Some(name), spec.contains('@').then_some(requirement))
+        },
+        None => (None, None),
+    };
+
     let plugins_to_update: Vec<_> = if let Some(name) = name {
         // Update specific plugin
         match registry.find(name) {
@@ -36,12 +51,13 @@ pub fn update_plugins(name: Option<&str>) -> Result<(), Box<dyn std::error::Erro
         return Ok(());
     }
 
-    // Try to fetch the official registry for version info
-    let official_registry = match fetch_official_registry() {
-        Ok(reg) => Some(reg),
+    // Try to fetch the plugin index for version info
+    let url = index_url();
+    let index = match fetch_index_from(&url) {
+        Ok(index) => Some(index),
         Err(e) => {
             output::warning(&format!(
-                "Failed to fetch official registry: {}. Falling back to source-based update.",
+                "Failed to fetch plugin index: {}. Falling back to source-based update.",
                 e
             ));
             None
@@ -51,51 +67,79 @@ pub fn update_plugins(name: Option<&str>) -> Result<(), Box<dyn std::error::Erro
     for plugin in plugins_to_update {
         output::updating(&plugin.name);
 
-        // Check if plugin is from official registry and has a newer version
-        if let Some(ref reg) = official_registry {
-            if let Some(reg_plugin) = reg.plugin.iter().find(|p| p.name == plugin.name) {
-                // Get host plugin version (major.minor)
-                let host_version_parts: Vec<&str> = PLUGIN_VERSION.split('.').collect();
-                let host_major_minor = if host_version_parts.len() >= 2 {
-                    format!("{}.{}", host_version_parts[0], host_version_parts[1])
-                } else {
-                    PLUGIN_VERSION.to_string()
-                };
+        // Remote plugins are updated wherever their daemon runs
+        if let PluginSource::Remote { endpoint } = &plugin.source {
+            println!("  Skipped: remote plugin at {}", endpoint);
+            continue;
+        }
 
-                // Find latest compatible version
-                if let Some(latest) = reg_plugin.versions.iter().find(|v| v.plugin == host_major_minor) {
-                    if latest.version != plugin.version {
-                        println!(
-                            "  {} -> {} (protocol {})",
-                            plugin.version, latest.version, latest.plugin
-                        );
-                        install_from_registry(&plugin.name, None, false, true, false)?;
-                        continue;
-                    } else {
-                        println!("  Already at latest compatible version: {}", plugin.version);
-                        continue;
-                    }
-                }
+        // Check if plugin is in the plugin index and has a newer version
+        if let Some(indexed) = index.as_ref().and_then(|index| index.find(&plugin.name)) {
+            let requirement = match (&new_requirement, &plugin.source) {
+                (Some(req), _) => (*req != VersionReq::STAR).then(|| req.to_string()),
+                (None, PluginSource::Index { requirement, .. }) => requirement.clone(),
+                (None, _) => None,
+            };
+            let req = match &requirement {
+                Some(r) => VersionReq::parse(r).map_err(|e| format!("Invalid version requirement '{}': {}", r, e))?,
+                None => VersionReq::STAR,
+            };
+            let release = match indexed.resolve(&req, &host_protocol()) {
+                Ok(release) => release,
+                Err(e) => {
+                    output::warning(&format!("{}: {}", plugin.name, e));
+                    continue;
+                },
+            };
+
+            let installed = Version::parse(&plugin.version).ok();
+            let newer = match (release.semver(), &installed) {
+                (Some(latest), Some(installed)) => latest > *installed,
+                _ => release.version != plugin.version,
+            };
+            // A version outside the requirement is replaced, even by an older release
+            let allowed = installed.as_ref().is_some_and(|v| req.matches(v));
+            if newer || !allowed {
+                println!(
+                    "  {} -> {} (protocol {})",
+                    plugin.version, release.version, release.plugin
+                );
+                install_release(&url, indexed, release, requirement, true, false, false)?;
+            } else if req == VersionReq::STAR {
+                println!("  Already at latest compatible version: {}", plugin.version);
+            } else {
+                println!("  Already at latest version matching {}: {}", req, plugin.version);
             }
+            continue;
+        }
+        if new_requirement.is_some() {
+            return Err(format!(
+                "Plugin '{}' is not in the plugin index; version requirements only apply to indexed plugins.",
+                plugin.name
+            )
+            .into());
         }
 
         // Fallback to source-based update
         match &plugin.source {
-            PluginSource::Git { url, tag, subdir } => {
-                install_from_git(url, subdir.as_deref(), tag.as_deref(), false, true, false)?;
+            PluginSource::Git { url, tag, subdir, .. } => {
+                install_from_git(url, subdir.as_deref(), tag.as_deref(), None, false, true, false)?;
             },
             PluginSource::Local { path } => {
                 let path_buf = PathBuf::from(path);
                 if path_buf.exists() {
-                    let source = PluginSource::Local { path: path.clone() };
-                    install_from_path(&path_buf, false, true, false, source)?;
+                    install_from_local(&path_buf, false, true, false, false)?;
                 } else {
                     println!("  Warning: Source path no longer exists: {}", path_buf.display());
                 }
             },
+            PluginSource::Index { .. } => {
+                println!("  Skipped: not found in the plugin index");
+            },
             PluginSource::CratesIo => {
                 println!("  Skipped: crates.io source (reinstall with --git or --path)");
             },
+            PluginSource::Remote { .. } => {}, // Skipped above
         }
     }
 
@@ -0,0 +1,233 @@
+//! Quantize command - write a quantized copy of a model through a backend plugin
+//!
+//! The backend does the quantization itself (`backend.quantize`), calibrating on the samples
+//! given with `--calibration` and reporting how far the quantized outputs drift from the float
+//! model's on them.
+
+use super::run::{
+    find_backend_plugin, find_model_format_plugin, load_model_snapshot, parse_device, parse_inputs, plugin_supports,
+    save_inputs,
+};
+use crate::config::Settings;
+use crate::errors::{self, ErrorClass};
+use crate::output;
+use crate::plugins::{load_registry, PluginManager};
+use crate::utils::path_to_str;
+use clap::Args;
+use hodu_core::format::hdss;
+use hodu_plugin::rpc::{methods, QuantizeParams, QuantizeResult};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tempfile::NamedTempFile;
+
+#[derive(Args)]
+pub struct QuantizeArgs {
+    /// Model file (.onnx, .hdss, etc.)
+    pub model: PathBuf,
+
+    /// Output snapshot (default: <model>.q<bits>.hdss next to the model)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Quantization scheme (dynamic, static, weight-only, or one the backend defines)
+    #[arg(short, long, default_value = "dynamic")]
+    pub scheme: String,
+
+    /// Bit width of quantized values
+    #[arg(short, long, default_value_t = 8)]
+    pub bits: u8,
+
+    /// One calibration sample of model inputs (name=path,name=path), can be repeated
+    #[arg(short, long = "calibration", value_name = "NAME=PATH,...")]
+    pub calibration: Vec<String>,
+
+    /// Device to calibrate on (cpu, metal, cuda::0); default: `device` in ~/.hodu/config.toml, else cpu
+    #[arg(short, long)]
+    pub device: Option<String>,
+
+    /// Backend plugin to use (default: `backend` in ~/.hodu/config.toml, else auto-select)
+    #[arg(long)]
+    pub backend: Option<String>,
+
+    /// Output format of the accuracy report (pretty, json)
+    #[arg(short, long, default_value = "pretty")]
+    pub format: String,
+
+    /// Timeout in seconds for plugin operations, covering all calibration (default: 300)
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Override a plugin config value from ~/.hodu/config.toml, can be repeated
+    #[arg(long = "plugin-config", value_name = "PLUGIN.KEY=VALUE")]
+    pub plugin_config: Vec<String>,
+}
+
+pub fn execute(mut args: QuantizeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if output::is_json() {
+        args.format = "json".to_string();
+    }
+    let extension = args
+        .model
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    let registry = load_registry()?;
+    let format_plugin = find_model_format_plugin(extension.as_deref(), &registry)?;
+    let settings = Settings::load()?;
+    let device = parse_device(&settings.device(args.device.as_deref()))?;
+    let backend_plugin = find_backend_plugin(&settings.backend(args.backend.as_deref()), &device, &registry)?;
+
+    let output_path = match &args.output {
+        Some(path) => path.clone(),
+        None => default_output(&args.model, args.bits),
+    };
+    if output_path.extension().and_then(|e| e.to_str()) != Some("hdss") {
+        return Err(format!(
+            "Quantized models are written as snapshots; use a .hdss output (got: {})",
+            output_path.display()
+        )
+        .into());
+    }
+
+    let mut manager = match args.timeout {
+        Some(secs) => PluginManager::with_timeout(secs)?,
+        None => PluginManager::new()?,
+    };
+    manager.add_config_overrides(&args.plugin_config)?;
+    manager.allow_read(&args.model);
+    manager.allow_write(&output_path);
+
+    if !plugin_supports(&mut manager, &backend_plugin.name, methods::BACKEND_QUANTIZE)? {
+        return Err(format!(
+            "Backend '{}' does not support backend.quantize. Pick another with --backend.",
+            backend_plugin.name
+        )
+        .into());
+    }
+
+    let model_name = args
+        .model
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| args.model.display().to_string());
+    // The temp file keeps an imported ONNX model's snapshot alive until quantization completes
+    let (snapshot_path, _onnx_snapshot) = load_model_snapshot(&args.model, format_plugin, &model_name, &mut manager)?;
+    let (snapshot, _) = hdss::load_lazy(&snapshot_path)?;
+
+    // Calibration tensors are written once, and kept until the backend is done with them
+    let inline_limit = manager.get_plugin(&backend_plugin.name)?.inline_tensor_limit();
+    let mut calibration = Vec::with_capacity(args.calibration.len());
+    let mut temp_files: Vec<NamedTempFile> = Vec::new();
+    for sample in &args.calibration {
+        let inputs = parse_inputs(&split_sample(sample), &snapshot)?;
+        let (input_refs, files) = save_inputs(&inputs, inline_limit)?;
+        calibration.push(input_refs);
+        temp_files.extend(files);
+    }
+
+    let params = QuantizeParams {
+        snapshot_path: path_to_str(&snapshot_path)?.to_string(),
+        output_path: path_to_str(&output_path)?.to_string(),
+        device: device.clone(),
+        scheme: args.scheme.clone(),
+        bits: args.bits,
+        calibration,
+    };
+    params.validate().map_err(|e| e.to_string())?;
+
+    // Set up Ctrl+C handler for cancellation
+    let cancelled = Arc::new(AtomicBool::new(false));
+    if let Some(handle) = manager.get_cancellation_handle(&backend_plugin.name) {
+        let cancelled = Arc::clone(&cancelled);
+        if let Err(e) = ctrlc::set_handler(move || {
+            cancelled.store(true, Ordering::SeqCst);
+            eprintln!("\nCancelling...");
+            if let Err(cancel_err) = handle.cancel() {
+                eprintln!("Warning: Failed to send cancellation: {}", cancel_err);
+            }
+        }) {
+            output::warning(&format!(
+                "Failed to set Ctrl+C handler: {}. Cancellation may not work.",
+                e
+            ));
+        }
+    }
+
+    output::quantizing(&format!(
+        "{} ({}, {} bits, {} calibration samples)",
+        model_name,
+        args.scheme,
+        args.bits,
+        args.calibration.len()
+    ));
+    let start = Instant::now();
+    let result = manager.get_plugin(&backend_plugin.name)?.quantize(params);
+    if cancelled.load(Ordering::SeqCst) {
+        return Err(errors::classified(ErrorClass::Cancelled, "Operation cancelled by user"));
+    }
+    let result = result?;
+    output::finished(&format!(
+        "{} nodes quantized in {} -> {}",
+        result.quantized_nodes,
+        output::format_duration(start.elapsed().as_secs_f64()),
+        result.snapshot_path
+    ));
+
+    match args.format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&result)?),
+        _ => print_accuracy(&result),
+    }
+    Ok(())
+}
+
+/// Split one `--calibration` value into `name=path` inputs
+///
+/// Commas separate inputs, except within a CSV column pick (`name=table.csv#a,b`), whose
+/// columns carry no `=`.
+fn split_sample(sample: &str) -> Vec<String> {
+    let mut inputs: Vec<String> = Vec::new();
+    for part in sample.split(',') {
+        match inputs.last_mut() {
+            Some(last) if !part.contains('=') => {
+                last.push(',');
+                last.push_str(part);
+            },
+            _ => inputs.push(part.to_string()),
+        }
+    }
+    inputs
+}
+
+/// `<dir>/<stem>.q<bits>.hdss` next to the model
+fn default_output(model: &Path, bits: u8) -> PathBuf {
+    let stem = model
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "model".to_string());
+    model.with_file_name(format!("{}.q{}.hdss", stem, bits))
+}
+
+fn print_accuracy(result: &QuantizeResult) {
+    if result.accuracy.is_empty() {
+        output::info("no calibration samples, so accuracy was not measured");
+        return;
+    }
+    println!(
+        "{:<24} {:>14} {:>14} {:>10}",
+        "OUTPUT", "MAX ABS ERR", "MEAN ABS ERR", "SQNR"
+    );
+    for output in &result.accuracy {
+        let sqnr = output
+            .sqnr_db
+            .map_or_else(|| "exact".to_string(), |db| format!("{:.1} dB", db));
+        println!(
+            "{:<24} {:>14.6} {:>14.6} {:>10}",
+            output::sanitize_for_terminal(&output.name),
+            output.max_abs_error,
+            output.mean_abs_error,
+            sqnr
+        );
+    }
+}
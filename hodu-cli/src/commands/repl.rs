@@ -0,0 +1,565 @@
+//! Repl command - evaluate expressions on tensors and models interactively
+//!
+//! Each line is a statement of a small expression language (see [`parse`]): `name = expr` binds a
+//! value, a bare expression prints it, and lines starting with `:` are commands such as `:device`
+//! or `:vars`. Tensor functions and operators run on the host; a model loaded with `model(path)`
+//! runs on the current device, on the backend plugin picked as `hodu run` would, or on the
+//! reference interpreter when no backend is installed for cpu.
+//!
+//! Given a script, or statements on a non-terminal stdin, the command runs them in order and stops
+//! at the first error.
+
+mod functions;
+mod parse;
+mod value;
+
+use super::compare_backends::{parse_target, run_target, Target, INTERPRETER};
+use super::convert::load_tensor;
+use super::run::{find_backend_plugin, find_model_format_plugin, load_model_snapshot, parse_device};
+use crate::config::Settings;
+use crate::output::{self, colors};
+use crate::plugins::{load_registry, PluginManager, PluginRegistry};
+use crate::utils::core_dtype_to_plugin;
+use clap::Args;
+use functions::{CallArgs, FUNCTIONS};
+use hodu_core::format::hdss;
+use hodu_core::tensor::Tensor;
+use hodu_plugin::{Device, TensorData};
+use parse::{parse_statement, BinOp, Expr, Statement};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+use value::{Model, Value};
+
+#[derive(Args)]
+pub struct ReplArgs {
+    /// Run the statements in this file instead of reading them interactively
+    pub script: Option<PathBuf>,
+
+    /// Device models run on (cpu, metal, cuda::0)
+    #[arg(short, long)]
+    pub device: Option<String>,
+
+    /// Backend plugin models run on, or `interpreter` (default: picked by device)
+    #[arg(short, long)]
+    pub backend: Option<String>,
+
+    /// Timeout in seconds for plugin operations
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Plugin config override, can be repeated
+    #[arg(long = "plugin-config", value_name = "PLUGIN.KEY=VALUE")]
+    pub plugin_config: Vec<String>,
+}
+
+/// What to do after a line
+enum Flow {
+    Continue,
+    Quit,
+}
+
+/// Variables and the plugins models run on
+struct Session {
+    vars: BTreeMap<String, Value>,
+    device: Device,
+    /// Backend named with `--backend` or `:backend`; `None` picks one for the device
+    backend: Option<String>,
+    settings: Settings,
+    registry: PluginRegistry,
+    manager: PluginManager,
+}
+
+pub fn execute(args: ReplArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let settings = Settings::load()?;
+    let device = parse_device(&settings.device(args.device.as_deref()))?;
+    let registry = load_registry()?;
+    let mut manager = match args.timeout {
+        Some(secs) => PluginManager::with_timeout(secs)?,
+        None => PluginManager::new()?,
+    };
+    manager.add_config_overrides(&args.plugin_config)?;
+
+    let mut session = Session {
+        vars: BTreeMap::new(),
+        device,
+        backend: args.backend.clone(),
+        settings,
+        registry,
+        manager,
+    };
+    // An unknown --backend fails up front rather than on the first model run
+    session.target()?;
+
+    match &args.script {
+        Some(path) => {
+            let script =
+                std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            session.run_script(&script, &path.display().to_string())
+        },
+        None if std::io::stdin().is_terminal() => session.interactive(),
+        None => {
+            let mut script = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut script)?;
+            session.run_script(&script, "<stdin>")
+        },
+    }
+}
+
+impl Session {
+    /// Read and run lines from the terminal until `:quit` or end of input
+    fn interactive(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        println!(
+            "hodu repl ({}); :help lists commands and functions, :quit leaves",
+            self.device
+        );
+        let stdin = std::io::stdin();
+        let mut line = String::new();
+        loop {
+            if output::supports_color() {
+                print!("{}hodu[{}]>{} ", colors::BOLD, self.device, colors::RESET);
+            } else {
+                print!("hodu[{}]> ", self.device);
+            }
+            std::io::stdout().flush()?;
+            line.clear();
+            if stdin.lock().read_line(&mut line)? == 0 {
+                println!();
+                return Ok(());
+            }
+            match self.line(&line) {
+                Ok(Flow::Continue) => {},
+                Ok(Flow::Quit) => return Ok(()),
+                Err(e) => output::error(&e.to_string()),
+            }
+        }
+    }
+
+    /// Run every line of `script`, stopping at the first error
+    fn run_script(&mut self, script: &str, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        for (number, line) in script.lines().enumerate() {
+            match self.line(line) {
+                Ok(Flow::Continue) => {},
+                Ok(Flow::Quit) => break,
+                Err(e) => return Err(format!("{}:{}: {}", name, number + 1, e).into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn line(&mut self, line: &str) -> Result<Flow, Box<dyn std::error::Error>> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(Flow::Continue);
+        }
+        if let Some(command) = line.strip_prefix(':') {
+            return self.command(command);
+        }
+        match parse_statement(line)? {
+            Statement::Assign(name, expr) => {
+                if functions::lookup(&name).is_some() || name == "true" || name == "false" {
+                    return Err(format!("'{}' is a built-in name and cannot be assigned", name).into());
+                }
+                let value = self.eval(&expr)?;
+                if matches!(value, Value::None) {
+                    return Err(format!("The right-hand side of '{}' has no value", name).into());
+                }
+                self.vars.insert(name, value);
+            },
+            Statement::Expr(expr) => {
+                let value = self.eval(&expr)?;
+                if !matches!(value, Value::None) {
+                    println!("{}", value.render());
+                }
+            },
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Run a `:command`
+    fn command(&mut self, command: &str) -> Result<Flow, Box<dyn std::error::Error>> {
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let arg = words.next();
+        match name {
+            "q" | "quit" | "exit" => return Ok(Flow::Quit),
+            "h" | "help" => print_help(arg)?,
+            "vars" => {
+                if self.vars.is_empty() {
+                    println!("No variables.");
+                }
+                let width = self.vars.keys().map(String::len).max().unwrap_or(0);
+                for (name, value) in &self.vars {
+                    println!("{:<width$}  {}", name, value.summary(), width = width);
+                }
+            },
+            "del" => {
+                let names: Vec<&str> = std::iter::once(arg).flatten().chain(words).collect();
+                if names.is_empty() {
+                    return Err("Usage: :del NAME...".into());
+                }
+                for name in names {
+                    self.vars
+                        .remove(name)
+                        .ok_or_else(|| format!("Unknown variable '{}'", name))?;
+                }
+            },
+            "device" => {
+                if let Some(device) = arg {
+                    let previous = std::mem::replace(&mut self.device, parse_device(device)?);
+                    if let Err(e) = self.target() {
+                        self.device = previous;
+                        return Err(e);
+                    }
+                }
+                println!("{} ({})", self.device, self.target()?.label());
+            },
+            "backend" => {
+                if let Some(backend) = arg {
+                    let backend = (backend != "auto").then(|| backend.to_string());
+                    let previous = std::mem::replace(&mut self.backend, backend);
+                    if let Err(e) = self.target() {
+                        self.backend = previous;
+                        return Err(e);
+                    }
+                }
+                println!("{}", self.target()?.label());
+            },
+            "" => return Err("Expected a command after ':' (see :help)".into()),
+            other => return Err(format!("Unknown command ':{}' (see :help)", other).into()),
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Backend and device models run on
+    fn target(&self) -> Result<Target, Box<dyn std::error::Error>> {
+        let name = match self.backend.clone().or_else(|| self.settings.backend(None)) {
+            Some(name) => name,
+            None => match self.registry.find_backend_by_device(&self.device) {
+                None if self.device == "cpu" => INTERPRETER.to_string(),
+                _ => find_backend_plugin(&None, &self.device, &self.registry)?.name.clone(),
+            },
+        };
+        parse_target(&name, &self.device, &self.registry)
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Value, Box<dyn std::error::Error>> {
+        Ok(match expr {
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Str(s) => Value::Str(s.clone()),
+            Expr::Var(name) => match (self.vars.get(name), name.as_str()) {
+                (Some(value), _) => value.clone(),
+                (None, "true") => Value::Number(1.0),
+                (None, "false") => Value::Number(0.0),
+                (None, name) if functions::lookup(name).is_some() => {
+                    return Err(format!("'{}' is a function; call it as {}(...)", name, name).into())
+                },
+                (None, name) => return Err(format!("Unknown name '{}'", name).into()),
+            },
+            Expr::List(items) => Value::List(items.iter().map(|item| self.eval(item)).collect::<Result<_, _>>()?),
+            Expr::Neg(operand) => match self.eval(operand)? {
+                Value::Number(n) => Value::Number(-n),
+                Value::Tensor(tensor) => Value::Tensor(tensor.neg()?),
+                other => return Err(format!("Cannot negate a {}", other.kind()).into()),
+            },
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.eval(lhs)?, self.eval(rhs)?);
+                binary(*op, lhs, rhs)?
+            },
+            Expr::Attr(object, name) => functions::attribute(&self.eval(object)?, name)?,
+            Expr::Index(object, index) => {
+                let (object, index) = (self.eval(object)?, self.eval(index)?);
+                index_value(object, index)?
+            },
+            Expr::Call(callee, args) => {
+                // `value.name(args)` calls `name` with `value` first
+                let (name, first) = match callee.as_ref() {
+                    Expr::Var(name) if !self.vars.contains_key(name) => (name.as_str(), None),
+                    Expr::Attr(object, name) => (name.as_str(), Some(self.eval(object)?)),
+                    other => match self.eval(other)? {
+                        Value::Model(model) => {
+                            let args = self.eval_args(None, args)?;
+                            return self.run_model(&model, args);
+                        },
+                        other => return Err(format!("A {} cannot be called", other.kind()).into()),
+                    },
+                };
+                let args = self.eval_args(first, args)?;
+                match name {
+                    "load" => self.load_tensor(args)?,
+                    "model" => self.load_model(args)?,
+                    name => functions::call(name, args)?,
+                }
+            },
+        })
+    }
+
+    fn eval_args(&mut self, first: Option<Value>, args: &[parse::Arg]) -> Result<CallArgs, Box<dyn std::error::Error>> {
+        let mut call = CallArgs::default();
+        call.positional.extend(first);
+        for arg in args {
+            let value = self.eval(&arg.value)?;
+            match &arg.name {
+                Some(name) => call.named.push((name.clone(), value)),
+                None => call.positional.push(value),
+            }
+        }
+        Ok(call)
+    }
+
+    /// `load(path)`
+    fn load_tensor(&mut self, args: CallArgs) -> Result<Value, Box<dyn std::error::Error>> {
+        let path = PathBuf::from(functions::bind("load", args)?.string("path")?);
+        self.manager.allow_read(&path);
+        Ok(Value::Tensor(load_tensor(&path, &self.registry, &mut self.manager)?))
+    }
+
+    /// `model(path)`
+    fn load_model(&mut self, args: CallArgs) -> Result<Value, Box<dyn std::error::Error>> {
+        let path = PathBuf::from(functions::bind("model", args)?.string("path")?);
+        let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase());
+        let format_plugin = find_model_format_plugin(extension.as_deref(), &self.registry)?;
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        self.manager.allow_read(&path);
+        let (snapshot_path, imported) = load_model_snapshot(&path, format_plugin, &name, &mut self.manager)?;
+        let (snapshot, weights) = hdss::load_lazy(&snapshot_path)?;
+        Ok(Value::Model(Rc::new(Model {
+            name,
+            snapshot_path,
+            snapshot,
+            weights,
+            _imported: imported,
+        })))
+    }
+
+    /// Run `model` on the current target, with its inputs by position or by name
+    fn run_model(&mut self, model: &Model, args: CallArgs) -> Result<Value, Box<dyn std::error::Error>> {
+        let specs = &model.snapshot.inputs;
+        let input_names = || {
+            specs
+                .iter()
+                .map(|spec| spec.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if args.positional.len() > specs.len() {
+            return Err(format!(
+                "{} takes {} inputs ({}), got {}",
+                model.name,
+                specs.len(),
+                input_names(),
+                args.positional.len()
+            )
+            .into());
+        }
+        let mut values: HashMap<&str, Value> = specs
+            .iter()
+            .map(|spec| spec.name.as_str())
+            .zip(args.positional)
+            .collect();
+        for (name, value) in args.named {
+            let spec = specs
+                .iter()
+                .find(|spec| spec.name == name)
+                .ok_or_else(|| format!("{} has no input '{}' (inputs: {})", model.name, name, input_names()))?;
+            if values.insert(&spec.name, value).is_some() {
+                return Err(format!("Input '{}' given twice", name).into());
+            }
+        }
+
+        let mut inputs = HashMap::new();
+        for spec in specs {
+            let tensor = match values.remove(spec.name.as_str()) {
+                Some(Value::Tensor(tensor)) => tensor,
+                Some(other) => {
+                    return Err(format!("Input '{}' must be a tensor, got {}", spec.name, other.kind()).into())
+                },
+                None => {
+                    return Err(format!("Missing input '{}' ({}{:?})", spec.name, spec.dtype, spec.shape.dims()).into())
+                },
+            };
+            if tensor.shape().dims() != spec.shape.dims() || tensor.dtype() != spec.dtype {
+                return Err(format!(
+                    "Input '{}' must be {}{:?}, got {}{:?}",
+                    spec.name,
+                    spec.dtype,
+                    spec.shape.dims(),
+                    tensor.dtype(),
+                    tensor.shape().dims()
+                )
+                .into());
+            }
+            let data = TensorData::new(
+                tensor.to_bytes()?,
+                spec.shape.dims().to_vec(),
+                core_dtype_to_plugin(spec.dtype),
+            );
+            inputs.insert(spec.name.clone(), data);
+        }
+
+        let target = self.target()?;
+        output::running(&format!("{} ({})", model.name, target.label()));
+        let start = std::time::Instant::now();
+        let mut outputs = run_target(
+            &target,
+            &model.snapshot,
+            &model.snapshot_path,
+            model.weights.as_ref(),
+            &model.name,
+            &inputs,
+            &self.registry,
+            &mut self.manager,
+        )?;
+        output::finished(&format!(
+            "{} in {}",
+            target.label(),
+            output::format_duration(start.elapsed().as_secs_f64())
+        ));
+
+        let mut ordered: Vec<(String, Tensor)> = model
+            .snapshot
+            .targets
+            .iter()
+            .filter_map(|target| outputs.remove(&target.name).map(|tensor| (target.name.clone(), tensor)))
+            .collect();
+        Ok(match ordered.len() {
+            1 => Value::Tensor(ordered.remove(0).1),
+            _ => Value::Outputs(ordered),
+        })
+    }
+}
+
+/// `lhs op rhs`, elementwise with broadcasting except for `@`
+fn binary(op: BinOp, lhs: Value, rhs: Value) -> Result<Value, Box<dyn std::error::Error>> {
+    Ok(match (lhs, rhs) {
+        (Value::Number(a), Value::Number(b)) => Value::Number(match op {
+            BinOp::Add => a + b,
+            BinOp::Sub => a - b,
+            BinOp::Mul => a * b,
+            BinOp::Div => a / b,
+            BinOp::Rem => a % b,
+            BinOp::Pow => a.powf(b),
+            BinOp::MatMul => return Err("'@' needs two tensors".into()),
+        }),
+        (Value::Tensor(x), Value::Number(n)) => {
+            let n = n as f32;
+            Value::Tensor(match op {
+                BinOp::Add => x.add_scalar(n)?,
+                BinOp::Sub => x.sub_scalar(n)?,
+                BinOp::Mul => x.mul_scalar(n)?,
+                BinOp::Div => x.div_scalar(n)?,
+                BinOp::Rem => x.rem_scalar(n)?,
+                BinOp::Pow => x.pow_scalar(n)?,
+                BinOp::MatMul => return Err("'@' needs two tensors".into()),
+            })
+        },
+        (Value::Number(n), Value::Tensor(x)) => {
+            if op == BinOp::MatMul {
+                return Err("'@' needs two tensors".into());
+            }
+            binary(op, Value::Tensor(Tensor::full_like(&x, n as f32)?), Value::Tensor(x))?
+        },
+        (Value::Tensor(a), Value::Tensor(b)) => Value::Tensor(match op {
+            BinOp::Add => a.add(&b)?,
+            BinOp::Sub => a.sub(&b)?,
+            BinOp::Mul => a.mul(&b)?,
+            BinOp::Div => a.div(&b)?,
+            BinOp::Rem => a.rem(&b)?,
+            BinOp::Pow => a.pow(&b)?,
+            BinOp::MatMul => a.matmul(&b)?,
+        }),
+        (lhs, rhs) => {
+            return Err(format!(
+                "Cannot apply '{}' to a {} and a {}",
+                op.symbol(),
+                lhs.kind(),
+                rhs.kind()
+            )
+            .into())
+        },
+    })
+}
+
+/// `object[index]`: an item of a list or outputs, an output by name, or a slice of a tensor along
+/// its first dimension
+fn index_value(object: Value, index: Value) -> Result<Value, Box<dyn std::error::Error>> {
+    let position = |len: usize| -> Result<usize, Box<dyn std::error::Error>> {
+        let Value::Number(n) = index else {
+            return Err(format!("Index must be a number, got {}", index.kind()).into());
+        };
+        let i = if n < 0.0 { n + len as f64 } else { n };
+        if n.fract() != 0.0 || i < 0.0 || i >= len as f64 {
+            return Err(format!("Index {} is out of range for length {}", n, len).into());
+        }
+        Ok(i as usize)
+    };
+    Ok(match object {
+        Value::Outputs(outputs) => match &index {
+            Value::Str(name) => functions::attribute(&Value::Outputs(outputs), name)?,
+            _ => Value::Tensor(outputs[position(outputs.len())?].1.clone()),
+        },
+        Value::List(items) => items[position(items.len())?].clone(),
+        Value::Tensor(tensor) => {
+            let len = tensor
+                .shape()
+                .dims()
+                .first()
+                .copied()
+                .ok_or("Cannot index a scalar tensor")?;
+            let i = position(len)?;
+            let indices = Tensor::from_slice(vec![i as i32], [1])?;
+            Value::Tensor(tensor.index_select(0, &indices)?.squeeze(&[0])?)
+        },
+        other => return Err(format!("Cannot index a {}", other.kind()).into()),
+    })
+}
+
+/// Print the commands and functions, or the entry of one function
+fn print_help(name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(name) = name {
+        let (_, signature, summary) = functions::lookup(name).ok_or_else(|| format!("Unknown function '{}'", name))?;
+        println!("{}({})", name, signature);
+        println!("  {}", summary);
+        return Ok(());
+    }
+
+    let section = |title: &str| {
+        if output::supports_color() {
+            println!("{}{}{}", colors::BOLD, title, colors::RESET);
+        } else {
+            println!("{}", title);
+        }
+    };
+    section("Statements");
+    println!("  name = expr        bind a value");
+    println!("  expr               evaluate and print a value");
+    println!("  + - * / % ^        elementwise, broadcasting; @ is matmul; x.f(a) calls f(x, a)");
+    println!("  m(x) / m(name=x)   run a model loaded with model(path) on the current device");
+    println!();
+    section("Commands");
+    println!("  :device [DEVICE]   show or switch the device models run on");
+    println!("  :backend [NAME]    show or switch the backend (a plugin, interpreter or auto)");
+    println!("  :vars              list variables");
+    println!("  :del NAME...       remove variables");
+    println!("  :help [FUNCTION]   show this help, or one function's");
+    println!("  :quit              leave");
+    println!();
+    section("Functions");
+    let width = FUNCTIONS
+        .iter()
+        .map(|(name, signature, _)| name.len() + signature.len() + 2)
+        .max()
+        .unwrap_or(0);
+    for (name, signature, summary) in FUNCTIONS {
+        let call = match *name {
+            "unary" | "reduce" => format!("f({})", signature),
+            name => format!("{}({})", name, signature),
+        };
+        println!("  {:<width$}  {}", call, summary, width = width);
+    }
+    Ok(())
+}
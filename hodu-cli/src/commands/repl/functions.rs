@@ -0,0 +1,545 @@
+//! Built-in functions of the REPL
+//!
+//! Every function is listed in [`FUNCTIONS`] with its parameters, which arguments are bound to by
+//! position or by name. Tensor functions run on the host through hodu_core.
+
+use super::value::Value;
+use crate::commands::diff::{compare, format_error, Outcome, Tolerance};
+use crate::utils::plugin_dtype_to_core;
+use hodu_core::format::{hdt, json, npy};
+use hodu_core::tensor::Tensor;
+use hodu_core::types::{DType, Shape};
+use hodu_plugin::PluginDType;
+use std::path::Path;
+
+/// Elementwise functions taking one tensor
+const UNARY: &[&str] = &[
+    "abs", "ceil", "cos", "erf", "exp", "floor", "gelu", "ln", "neg", "recip", "relu", "round", "sigmoid", "sign",
+    "silu", "sin", "softplus", "sqrt", "square", "tanh",
+];
+
+/// Reductions over one dimension or all of them
+const REDUCTIONS: &[&str] = &["sum", "mean", "max", "min", "prod", "std", "var"];
+
+/// Name, parameters and summary of every function, for binding arguments and for `:help`
+///
+/// Parameters with a `=default` are optional. `load` and `model` are run by the session, which
+/// holds the plugins they may need.
+pub(super) const FUNCTIONS: &[(&str, &str, &str)] = &[
+    (
+        "load",
+        "path",
+        "Load a tensor file (.hdt, .json, .npy, .npz or a plugin format)",
+    ),
+    (
+        "model",
+        "path",
+        "Load a model (.hdss, .onnx or a plugin format); call it with its inputs to run it",
+    ),
+    ("save", "x, path", "Save a tensor as .hdt, .json or .npy"),
+    (
+        "tensor",
+        "values, dtype=f32",
+        "Tensor from a number or a nested list of numbers",
+    ),
+    ("zeros", "shape, dtype=f32", "Tensor of zeros"),
+    ("ones", "shape, dtype=f32", "Tensor of ones"),
+    ("full", "shape, value, dtype=f32", "Tensor filled with a value"),
+    ("arange", "start, end, step=1", "Values from start up to end (f32)"),
+    ("rand", "shape, low=0, high=1", "Uniformly distributed values (f32)"),
+    ("randn", "shape, mean=0, std=1", "Normally distributed values (f32)"),
+    ("eye", "n, dtype=f32", "Identity matrix"),
+    (
+        "unary",
+        "x",
+        "abs ceil cos erf exp floor gelu ln neg recip relu round sigmoid sign silu sin softplus sqrt square tanh",
+    ),
+    ("pow", "x, y", "x to the power y, elementwise (also x ^ y)"),
+    ("maximum", "x, y", "Elementwise maximum"),
+    ("minimum", "x, y", "Elementwise minimum"),
+    ("clamp", "x, min, max", "Clamp values to [min, max]"),
+    (
+        "reduce",
+        "x, dim=all, keepdim=0",
+        "sum mean max min prod std var, over one dimension or all of them",
+    ),
+    (
+        "argmax",
+        "x, dim=-1, keepdim=0",
+        "Index of the largest value along a dimension",
+    ),
+    (
+        "argmin",
+        "x, dim=-1, keepdim=0",
+        "Index of the smallest value along a dimension",
+    ),
+    ("softmax", "x, dim=-1", "Softmax along a dimension"),
+    ("matmul", "x, y", "Matrix product (also x @ y)"),
+    ("reshape", "x, shape", "Reshape, keeping the elements in order"),
+    ("transpose", "x, dim0=-2, dim1=-1", "Swap two dimensions"),
+    ("permute", "x, axes", "Reorder the dimensions"),
+    ("flatten", "x", "Flatten to one dimension"),
+    ("squeeze", "x, dim=all", "Remove a dimension of size 1, or all of them"),
+    ("unsqueeze", "x, dim", "Insert a dimension of size 1"),
+    (
+        "cat",
+        "tensors, dim=0",
+        "Concatenate a list of tensors along a dimension",
+    ),
+    (
+        "stack",
+        "tensors, dim=0",
+        "Stack a list of tensors along a new dimension",
+    ),
+    (
+        "cast",
+        "x, dtype",
+        "Convert to another dtype (f32, f16, bf16, i64, ...)",
+    ),
+    ("shape", "x", "Shape of a tensor (also x.shape)"),
+    ("dtype", "x", "Dtype of a tensor (also x.dtype)"),
+    ("numel", "x", "Number of elements of a tensor (also x.numel)"),
+    ("print", "x", "Print every value of a tensor"),
+    (
+        "diff",
+        "x, reference, rtol=1e-5, atol=1e-8",
+        "Compare a tensor with a reference like `hodu diff`",
+    ),
+];
+
+/// Arguments of a call, as written
+#[derive(Default)]
+pub(super) struct CallArgs {
+    pub positional: Vec<Value>,
+    pub named: Vec<(String, Value)>,
+}
+
+/// Arguments bound to the parameters of a function
+pub(super) struct Params {
+    function: String,
+    values: Vec<(&'static str, Option<Value>)>,
+}
+
+/// Entry of `name` in [`FUNCTIONS`]
+pub(super) fn lookup(name: &str) -> Option<&'static (&'static str, &'static str, &'static str)> {
+    let entry = if UNARY.contains(&name) {
+        "unary"
+    } else if REDUCTIONS.contains(&name) {
+        "reduce"
+    } else {
+        name
+    };
+    FUNCTIONS.iter().find(|(function, _, _)| *function == entry)
+}
+
+/// Bind `args` to the parameters of `name`
+pub(super) fn bind(name: &str, args: CallArgs) -> Result<Params, String> {
+    let (_, signature, _) = lookup(name).ok_or_else(|| format!("Unknown function '{}'", name))?;
+    let parameters: Vec<(&str, bool)> = signature
+        .split(',')
+        .map(|param| match param.split_once('=') {
+            Some((param, _)) => (param.trim(), false),
+            None => (param.trim(), true),
+        })
+        .collect();
+    if args.positional.len() > parameters.len() {
+        return Err(format!(
+            "{}({}) takes {} arguments, got {}",
+            name,
+            signature,
+            parameters.len(),
+            args.positional.len()
+        ));
+    }
+
+    let mut values: Vec<(&'static str, Option<Value>)> = parameters.iter().map(|(param, _)| (*param, None)).collect();
+    for (slot, value) in values.iter_mut().zip(args.positional) {
+        slot.1 = Some(value);
+    }
+    for (arg, value) in args.named {
+        let slot = values
+            .iter_mut()
+            .find(|(param, _)| *param == arg)
+            .ok_or_else(|| format!("{}({}) has no parameter '{}'", name, signature, arg))?;
+        if slot.1.is_some() {
+            return Err(format!("{}() got '{}' twice", name, arg));
+        }
+        slot.1 = Some(value);
+    }
+    for ((param, required), (_, value)) in parameters.iter().zip(&values) {
+        if *required && value.is_none() {
+            return Err(format!("{}({}) is missing '{}'", name, signature, param));
+        }
+    }
+    Ok(Params {
+        function: name.to_string(),
+        values,
+    })
+}
+
+impl Params {
+    fn get(&self, param: &str) -> Option<&Value> {
+        self.values
+            .iter()
+            .find(|(name, _)| *name == param)
+            .and_then(|(_, value)| value.as_ref())
+    }
+
+    fn invalid(&self, param: &str, expected: &str, value: &Value) -> String {
+        format!(
+            "{}(): '{}' must be {}, got {}",
+            self.function,
+            param,
+            expected,
+            value.kind()
+        )
+    }
+
+    pub fn tensor(&self, param: &str) -> Result<Tensor, String> {
+        match self.get(param) {
+            Some(Value::Tensor(tensor)) => Ok(tensor.clone()),
+            Some(value) => Err(self.invalid(param, "a tensor", value)),
+            None => Err(format!("{}(): '{}' is missing", self.function, param)),
+        }
+    }
+
+    pub fn string(&self, param: &str) -> Result<String, String> {
+        match self.get(param) {
+            Some(Value::Str(s)) => Ok(s.clone()),
+            Some(value) => Err(self.invalid(param, "a string", value)),
+            None => Err(format!("{}(): '{}' is missing", self.function, param)),
+        }
+    }
+
+    fn number(&self, param: &str) -> Result<Option<f64>, String> {
+        match self.get(param) {
+            Some(Value::Number(n)) => Ok(Some(*n)),
+            Some(value) => Err(self.invalid(param, "a number", value)),
+            None => Ok(None),
+        }
+    }
+
+    fn number_or(&self, param: &str, default: f64) -> Result<f64, String> {
+        Ok(self.number(param)?.unwrap_or(default))
+    }
+
+    fn int(&self, param: &str) -> Result<Option<i64>, String> {
+        match self.number(param)? {
+            Some(n) if n.fract() == 0.0 => Ok(Some(n as i64)),
+            Some(n) => Err(format!(
+                "{}(): '{}' must be an integer, got {}",
+                self.function, param, n
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn int_or(&self, param: &str, default: i64) -> Result<i64, String> {
+        Ok(self.int(param)?.unwrap_or(default))
+    }
+
+    fn flag(&self, param: &str) -> Result<bool, String> {
+        Ok(self.number(param)?.is_some_and(|n| n != 0.0))
+    }
+
+    fn ints(&self, param: &str) -> Result<Vec<i64>, String> {
+        let items = match self.get(param) {
+            Some(Value::List(items)) => items.as_slice(),
+            Some(number @ Value::Number(_)) => std::slice::from_ref(number),
+            Some(value) => return Err(self.invalid(param, "a list of integers", value)),
+            None => return Err(format!("{}(): '{}' is missing", self.function, param)),
+        };
+        items
+            .iter()
+            .map(|item| match item {
+                Value::Number(n) if n.fract() == 0.0 => Ok(*n as i64),
+                _ => Err(format!("{}(): '{}' must be a list of integers", self.function, param)),
+            })
+            .collect()
+    }
+
+    fn shape(&self, param: &str) -> Result<Shape, String> {
+        let dims = self.ints(param)?;
+        if dims.iter().any(|&dim| dim < 0) {
+            return Err(format!("{}(): '{}' has a negative dimension", self.function, param));
+        }
+        Ok(Shape::new(&dims.iter().map(|&dim| dim as usize).collect::<Vec<_>>()))
+    }
+
+    fn dtype(&self, param: &str) -> Result<Option<DType>, String> {
+        match self.get(param) {
+            Some(Value::Str(name)) => Ok(Some(parse_dtype(name)?)),
+            Some(value) => Err(self.invalid(param, "a dtype name such as \"f16\"", value)),
+            None => Ok(None),
+        }
+    }
+
+    fn tensors(&self, param: &str) -> Result<Vec<Tensor>, String> {
+        match self.get(param) {
+            Some(Value::List(items)) => items
+                .iter()
+                .map(|item| match item {
+                    Value::Tensor(tensor) => Ok(tensor.clone()),
+                    other => Err(self.invalid(param, "a list of tensors", other)),
+                })
+                .collect(),
+            Some(value) => Err(self.invalid(param, "a list of tensors", value)),
+            None => Err(format!("{}(): '{}' is missing", self.function, param)),
+        }
+    }
+}
+
+/// Parse a dtype name (f32, f16, bf16, i8, ...)
+pub(super) fn parse_dtype(name: &str) -> Result<DType, String> {
+    let dtype = name.parse::<PluginDType>().map_err(|e| e.to_string())?;
+    plugin_dtype_to_core(dtype).map_err(|e| e.to_string())
+}
+
+/// Call the function `name`, other than `load` and `model`
+pub(super) fn call(name: &str, args: CallArgs) -> Result<Value, Box<dyn std::error::Error>> {
+    let p = bind(name, args)?;
+    let with_dtype = |tensor: Tensor, p: &Params| -> Result<Value, Box<dyn std::error::Error>> {
+        Ok(Value::Tensor(match p.dtype("dtype")? {
+            Some(dtype) if dtype != tensor.dtype() => tensor.to_dtype(dtype)?,
+            _ => tensor,
+        }))
+    };
+
+    if UNARY.contains(&name) {
+        let x = p.tensor("x")?;
+        let y = match name {
+            "abs" => x.abs(),
+            "ceil" => x.ceil(),
+            "cos" => x.cos(),
+            "erf" => x.erf(),
+            "exp" => x.exp(),
+            "floor" => x.floor(),
+            "gelu" => x.gelu(),
+            "ln" => x.ln(),
+            "neg" => x.neg(),
+            "recip" => x.recip(),
+            "relu" => x.relu(),
+            "round" => x.round(),
+            "sigmoid" => x.sigmoid(),
+            "sign" => x.sign(),
+            "silu" => x.silu(),
+            "sin" => x.sin(),
+            "softplus" => x.softplus(),
+            "sqrt" => x.sqrt(),
+            "square" => x.square(),
+            _ => x.tanh(),
+        }?;
+        return Ok(Value::Tensor(y));
+    }
+    if REDUCTIONS.contains(&name) {
+        let x = p.tensor("x")?;
+        let keepdim = p.flag("keepdim")?;
+        let dims: Vec<i64> = match p.int("dim")? {
+            Some(dim) => vec![dim],
+            None => (0..x.shape().dims().len() as i64).collect(),
+        };
+        let y = match name {
+            "sum" => x.sum(&dims, keepdim),
+            "mean" => x.mean(&dims, keepdim),
+            "max" => x.max(&dims, keepdim),
+            "min" => x.min(&dims, keepdim),
+            "prod" => x.prod(&dims, keepdim),
+            "std" => x.std(&dims, keepdim),
+            _ => x.var(&dims, keepdim),
+        }?;
+        return Ok(Value::Tensor(y));
+    }
+
+    let value = match name {
+        "save" => {
+            let x = p.tensor("x")?;
+            let path = p.string("path")?;
+            save(&x, Path::new(&path))?;
+            Value::None
+        },
+        "tensor" => {
+            let (values, shape) = match p.get("values") {
+                Some(value) => flatten(value)?,
+                None => return Err("tensor(): 'values' is missing".into()),
+            };
+            let tensor = match shape.is_empty() {
+                true => Tensor::scalar(values[0] as f32)?,
+                false => Tensor::from_slice(values.iter().map(|&v| v as f32).collect::<Vec<_>>(), shape)?,
+            };
+            with_dtype(tensor, &p)?
+        },
+        "zeros" => with_dtype(Tensor::zeros(p.shape("shape")?, DType::F32)?, &p)?,
+        "ones" => with_dtype(Tensor::ones(p.shape("shape")?, DType::F32)?, &p)?,
+        "full" => with_dtype(Tensor::full(p.shape("shape")?, p.number_or("value", 0.0)? as f32)?, &p)?,
+        "arange" => Value::Tensor(Tensor::arange(
+            p.number_or("start", 0.0)? as f32,
+            p.number_or("end", 0.0)? as f32,
+            p.number_or("step", 1.0)? as f32,
+        )?),
+        "rand" => Value::Tensor(Tensor::rand_uniform(
+            p.shape("shape")?,
+            p.number_or("low", 0.0)? as f32,
+            p.number_or("high", 1.0)? as f32,
+        )?),
+        "randn" => Value::Tensor(Tensor::randn(
+            p.shape("shape")?,
+            p.number_or("mean", 0.0)? as f32,
+            p.number_or("std", 1.0)? as f32,
+        )?),
+        "eye" => {
+            let n = p.int_or("n", 0)?;
+            if n < 0 {
+                return Err("eye(): 'n' must not be negative".into());
+            }
+            Value::Tensor(Tensor::eye(n as usize, p.dtype("dtype")?.unwrap_or(DType::F32))?)
+        },
+        "pow" | "maximum" | "minimum" => {
+            let x = p.tensor("x")?;
+            let y = match (name, p.get("y")) {
+                ("pow", Some(Value::Number(n))) => x.pow_scalar(*n as f32)?,
+                ("maximum", Some(Value::Number(n))) => x.maximum_scalar(*n as f32)?,
+                ("minimum", Some(Value::Number(n))) => x.minimum_scalar(*n as f32)?,
+                ("pow", _) => x.pow(&p.tensor("y")?)?,
+                ("maximum", _) => x.maximum(&p.tensor("y")?)?,
+                _ => x.minimum(&p.tensor("y")?)?,
+            };
+            Value::Tensor(y)
+        },
+        "clamp" => Value::Tensor(p.tensor("x")?.clamp(
+            p.number_or("min", f64::MIN)? as f32,
+            p.number_or("max", f64::MAX)? as f32,
+        )?),
+        "argmax" => Value::Tensor(p.tensor("x")?.argmax(&[p.int_or("dim", -1)?], p.flag("keepdim")?)?),
+        "argmin" => Value::Tensor(p.tensor("x")?.argmin(&[p.int_or("dim", -1)?], p.flag("keepdim")?)?),
+        "softmax" => Value::Tensor(p.tensor("x")?.softmax(p.int_or("dim", -1)?)?),
+        "matmul" => Value::Tensor(p.tensor("x")?.matmul(&p.tensor("y")?)?),
+        "reshape" => Value::Tensor(p.tensor("x")?.reshape(p.shape("shape")?)?),
+        "transpose" => Value::Tensor(p.tensor("x")?.transpose(p.int_or("dim0", -2)?, p.int_or("dim1", -1)?)?),
+        "permute" => Value::Tensor(p.tensor("x")?.permute(&p.ints("axes")?)?),
+        "flatten" => Value::Tensor(p.tensor("x")?.flatten()?),
+        "squeeze" => {
+            let x = p.tensor("x")?;
+            let dims: Vec<i64> = match p.int("dim")? {
+                Some(dim) => vec![dim],
+                None => (0..x.shape().dims().len() as i64)
+                    .filter(|&dim| x.shape().dims()[dim as usize] == 1)
+                    .collect(),
+            };
+            Value::Tensor(x.squeeze(&dims)?)
+        },
+        "unsqueeze" => Value::Tensor(p.tensor("x")?.unsqueeze(p.int_or("dim", 0)?)?),
+        "cat" | "stack" => {
+            let tensors = p.tensors("tensors")?;
+            let refs: Vec<&Tensor> = tensors.iter().collect();
+            let dim = p.int_or("dim", 0)?;
+            Value::Tensor(match name {
+                "cat" => Tensor::cat(&refs, dim)?,
+                _ => Tensor::stack(&refs, dim)?,
+            })
+        },
+        "cast" => {
+            let dtype = p.dtype("dtype")?.ok_or("cast(): 'dtype' is missing")?;
+            Value::Tensor(p.tensor("x")?.to_dtype(dtype)?)
+        },
+        "shape" | "dtype" | "numel" => attribute(&Value::Tensor(p.tensor("x")?), name)?,
+        "print" => {
+            println!("{}", p.tensor("x")?);
+            Value::None
+        },
+        "diff" => {
+            let tolerance = Tolerance {
+                rtol: p.number_or("rtol", 1e-5)?,
+                atol: p.number_or("atol", 1e-8)?,
+            };
+            tolerance.validate()?;
+            match compare(&p.tensor("x")?, &p.tensor("reference")?, tolerance, 0)? {
+                Outcome::Compared(c) => println!(
+                    "{}: max abs {}, mean abs {}, max rel {}, {} of {} mismatch",
+                    if c.mismatches == 0 { "match" } else { "mismatch" },
+                    format_error(c.max_abs),
+                    format_error(c.mean_abs),
+                    format_error(c.max_rel),
+                    c.mismatches,
+                    c.elements()
+                ),
+                Outcome::Shape { a, b } => println!("shapes differ: {:?} vs {:?}", a, b),
+                Outcome::Missing { .. } => {},
+            }
+            Value::None
+        },
+        _ => return Err(format!("'{}' cannot be called here", name).into()),
+    };
+    Ok(value)
+}
+
+/// `value.name`: a tensor's `shape`, `dtype`, `ndim` or `numel`, or an output by name
+pub(super) fn attribute(value: &Value, name: &str) -> Result<Value, String> {
+    match (value, name) {
+        (Value::Tensor(tensor), "shape") => Ok(Value::List(
+            tensor
+                .shape()
+                .dims()
+                .iter()
+                .map(|&dim| Value::Number(dim as f64))
+                .collect(),
+        )),
+        (Value::Tensor(tensor), "dtype") => Ok(Value::Str(tensor.dtype().to_string())),
+        (Value::Tensor(tensor), "ndim") => Ok(Value::Number(tensor.shape().dims().len() as f64)),
+        (Value::Tensor(tensor), "numel") => Ok(Value::Number(tensor.shape().dims().iter().product::<usize>() as f64)),
+        (Value::Outputs(outputs), name) => outputs
+            .iter()
+            .find(|(output, _)| output == name)
+            .map(|(_, tensor)| Value::Tensor(tensor.clone()))
+            .ok_or_else(|| {
+                format!(
+                    "No output '{}' (outputs: {})",
+                    name,
+                    outputs
+                        .iter()
+                        .map(|(output, _)| output.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }),
+        (value, name) => Err(format!("A {} has no attribute '{}'", value.kind(), name)),
+    }
+}
+
+/// Values of a number or nested list of numbers, and its shape
+fn flatten(value: &Value) -> Result<(Vec<f64>, Vec<usize>), String> {
+    match value {
+        Value::Number(n) => Ok((vec![*n], Vec::new())),
+        Value::List(items) => {
+            let mut values = Vec::new();
+            let mut inner: Option<Vec<usize>> = None;
+            for item in items {
+                let (item_values, item_shape) = flatten(item)?;
+                if inner.as_ref().is_some_and(|shape| *shape != item_shape) {
+                    return Err("tensor(): the nested lists have different lengths".to_string());
+                }
+                inner = Some(item_shape);
+                values.extend(item_values);
+            }
+            let mut shape = vec![items.len()];
+            shape.extend(inner.unwrap_or_default());
+            Ok((values, shape))
+        },
+        other => Err(format!("tensor(): expected numbers, got {}", other.kind())),
+    }
+}
+
+/// Save a tensor in the format of the path's extension
+fn save(tensor: &Tensor, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "hdt" => hdt::save(tensor, path)?,
+        "json" => json::save(tensor, path)?,
+        "npy" => npy::save(tensor, path)?,
+        _ => return Err(format!("Cannot save .{} files (supported: .hdt, .json, .npy)", extension).into()),
+    }
+    Ok(())
+}
@@ -0,0 +1,348 @@
+//! Parser of the REPL's expression language
+//!
+//! ```text
+//! statement := NAME "=" expr | expr
+//! expr      := expr ("+" | "-") term | term
+//! term      := term ("*" | "/" | "%" | "@") unary | unary
+//! unary     := "-" unary | power
+//! power     := postfix ("^" unary)?
+//! postfix   := primary ("(" args ")" | "." NAME | "[" expr "]")*
+//! primary   := NUMBER | STRING | NAME | "[" (expr ("," expr)*)? "]" | "(" expr ")"
+//! args      := ((NAME "=")? expr ("," (NAME "=")? expr)*)?
+//! ```
+
+/// A parsed line
+#[derive(Debug)]
+pub(super) enum Statement {
+    /// `name = expr`
+    Assign(String, Expr),
+    Expr(Expr),
+}
+
+#[derive(Debug)]
+pub(super) enum Expr {
+    Number(f64),
+    Str(String),
+    Var(String),
+    /// `[a, b, ...]`
+    List(Vec<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    /// `callee(args)`; `value.name(args)` calls `name` with `value` first
+    Call(Box<Expr>, Vec<Arg>),
+    /// `value.name`
+    Attr(Box<Expr>, String),
+    /// `value[index]`
+    Index(Box<Expr>, Box<Expr>),
+}
+
+/// A call argument, named with `name=value`
+#[derive(Debug)]
+pub(super) struct Arg {
+    pub name: Option<String>,
+    pub value: Expr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    MatMul,
+}
+
+impl BinOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Rem => "%",
+            BinOp::Pow => "^",
+            BinOp::MatMul => "@",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Name(String),
+    Punct(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Str(s) => write!(f, "\"{}\"", s),
+            Token::Name(name) => write!(f, "{}", name),
+            Token::Punct(c) => write!(f, "'{}'", c),
+        }
+    }
+}
+
+/// Parse one line into a statement
+pub(super) fn parse_statement(line: &str) -> Result<Statement, String> {
+    let tokens = tokenize(line)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let statement = match (parser.tokens.first(), parser.tokens.get(1)) {
+        (Some((Token::Name(name), _)), Some((Token::Punct('='), _))) => {
+            let name = name.clone();
+            parser.pos = 2;
+            Statement::Assign(name, parser.expr()?)
+        },
+        _ => Statement::Expr(parser.expr()?),
+    };
+    match parser.peek() {
+        None => Ok(statement),
+        Some(token) => Err(format!("Unexpected {} at column {}", token, parser.column())),
+    }
+}
+
+/// Split a line into tokens, each with the column it starts at
+fn tokenize(line: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        let column = start + 1;
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '#' {
+            break;
+        } else if c.is_ascii_digit() || (c == '.' && line[start + 1..].starts_with(|d: char| d.is_ascii_digit())) {
+            let mut end = start;
+            while let Some(&(i, d)) = chars.peek() {
+                let exponent_sign = (d == '-' || d == '+') && matches!(line[..i].chars().last(), Some('e' | 'E'));
+                if d.is_ascii_alphanumeric() || d == '.' || exponent_sign {
+                    end = i + d.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let text = &line[start..end];
+            let number = text
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid number '{}' at column {}", text, column))?;
+            tokens.push((Token::Number(number), column));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, d)) = chars.peek() {
+                if d.is_alphanumeric() || d == '_' {
+                    end = i + d.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push((Token::Name(line[start..end].to_string()), column));
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => text.push('\n'),
+                        Some((_, 't')) => text.push('\t'),
+                        Some((_, escaped)) => text.push(escaped),
+                        None => return Err(format!("Unterminated string at column {}", column)),
+                    },
+                    Some((_, q)) if q == c => break,
+                    Some((_, other)) => text.push(other),
+                    None => return Err(format!("Unterminated string at column {}", column)),
+                }
+            }
+            tokens.push((Token::Str(text), column));
+        } else if "+-*/%@^()[],.=".contains(c) {
+            chars.next();
+            // `**` is accepted for `^`
+            if c == '*' && chars.peek().is_some_and(|&(_, d)| d == '*') {
+                chars.next();
+                tokens.push((Token::Punct('^'), column));
+            } else {
+                tokens.push((Token::Punct(c), column));
+            }
+        } else {
+            return Err(format!("Unexpected character '{}' at column {}", c, column));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    /// Column of the next token, or just past the end of the line
+    fn column(&self) -> usize {
+        match self.tokens.get(self.pos) {
+            Some((_, column)) => *column,
+            None => self.tokens.last().map_or(1, |(_, column)| column + 1),
+        }
+    }
+
+    fn eat(&mut self, punct: char) -> bool {
+        if self.peek() == Some(&Token::Punct(punct)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), String> {
+        if self.eat(punct) {
+            return Ok(());
+        }
+        Err(match self.peek() {
+            Some(token) => format!("Expected '{}' but found {} at column {}", punct, token, self.column()),
+            None => format!("Expected '{}' at the end of the line", punct),
+        })
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Punct('+')) => BinOp::Add,
+                Some(Token::Punct('-')) => BinOp::Sub,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Punct('*')) => BinOp::Mul,
+                Some(Token::Punct('/')) => BinOp::Div,
+                Some(Token::Punct('%')) => BinOp::Rem,
+                Some(Token::Punct('@')) => BinOp::MatMul,
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(match self.unary()? {
+                Expr::Number(n) => Expr::Number(-n),
+                operand => Expr::Neg(Box::new(operand)),
+            });
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.postfix()?;
+        if self.eat('^') {
+            // Right-associative, and binds tighter than a leading minus: -x^2 is -(x^2)
+            return Ok(Expr::Binary(BinOp::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat('(') {
+                expr = Expr::Call(Box::new(expr), self.args()?);
+            } else if self.eat('.') {
+                match self.tokens.get(self.pos) {
+                    Some((Token::Name(name), _)) => {
+                        expr = Expr::Attr(Box::new(expr), name.clone());
+                        self.pos += 1;
+                    },
+                    _ => return Err(format!("Expected a name after '.' at column {}", self.column())),
+                }
+            } else if self.eat('[') {
+                let index = self.expr()?;
+                self.expect(']')?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// Arguments up to the closing parenthesis
+    fn args(&mut self) -> Result<Vec<Arg>, String> {
+        let mut args = Vec::new();
+        if self.eat(')') {
+            return Ok(args);
+        }
+        loop {
+            let name = match (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+                (Some((Token::Name(name), _)), Some((Token::Punct('='), _))) => {
+                    let name = name.clone();
+                    self.pos += 2;
+                    Some(name)
+                },
+                _ => None,
+            };
+            if name.is_none() && args.iter().any(|arg: &Arg| arg.name.is_some()) {
+                return Err(format!(
+                    "Positional argument after a named one at column {}",
+                    self.column()
+                ));
+            }
+            args.push(Arg {
+                name,
+                value: self.expr()?,
+            });
+            if self.eat(')') {
+                return Ok(args);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let column = self.column();
+        let Some((token, _)) = self.tokens.get(self.pos).cloned() else {
+            return Err("Unexpected end of the line".to_string());
+        };
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Str(s) => Ok(Expr::Str(s)),
+            Token::Name(name) => Ok(Expr::Var(name)),
+            Token::Punct('(') => {
+                let expr = self.expr()?;
+                self.expect(')')?;
+                Ok(expr)
+            },
+            Token::Punct('[') => {
+                let mut items = Vec::new();
+                if !self.eat(']') {
+                    loop {
+                        items.push(self.expr()?);
+                        if self.eat(']') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                Ok(Expr::List(items))
+            },
+            token => Err(format!("Unexpected {} at column {}", token, column)),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Values of the REPL's expression language
+
+use crate::commands::inspect::{format_value, TensorStats};
+use hodu_core::format::hdss::ShardedWeights;
+use hodu_core::snapshot::Snapshot;
+use hodu_core::tensor::Tensor;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+use tempfile::NamedTempFile;
+
+/// Tensors of at most this many elements are printed in full
+const PRINT_ELEMENTS: usize = 16;
+
+#[derive(Clone)]
+pub(super) enum Value {
+    /// What statements like `save(...)` return; not printed
+    None,
+    Number(f64),
+    Str(String),
+    List(Vec<Value>),
+    Tensor(Tensor),
+    Model(Rc<Model>),
+    /// Outputs of a model with several targets, by name in target order
+    Outputs(Vec<(String, Tensor)>),
+}
+
+/// A loaded model, run by calling it with its inputs
+pub(super) struct Model {
+    pub name: String,
+    pub snapshot_path: PathBuf,
+    pub snapshot: Snapshot,
+    pub weights: Option<ShardedWeights>,
+    /// Keeps an imported ONNX model's snapshot alive while the model is bound
+    pub _imported: Option<NamedTempFile>,
+}
+
+impl Value {
+    /// Name of the value's type, for error messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::None => "nothing",
+            Value::Number(_) => "number",
+            Value::Str(_) => "string",
+            Value::List(_) => "list",
+            Value::Tensor(_) => "tensor",
+            Value::Model(_) => "model",
+            Value::Outputs(_) => "outputs",
+        }
+    }
+
+    /// One line describing the value, as `:vars` lists it
+    pub fn summary(&self) -> String {
+        match self {
+            Value::Tensor(tensor) => tensor_summary(tensor),
+            Value::Model(model) => format!(
+                "model {} ({} inputs, {} outputs)",
+                model.name,
+                model.snapshot.inputs.len(),
+                model.snapshot.targets.len()
+            ),
+            Value::Outputs(outputs) => format!(
+                "outputs {}",
+                outputs
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Value::List(items) => format!("list of {}", items.len()),
+            other => other.to_string(),
+        }
+    }
+
+    /// The value as the REPL echoes it
+    pub fn render(&self) -> String {
+        match self {
+            Value::Tensor(tensor) => {
+                let mut text = tensor_summary(tensor);
+                if tensor.shape().dims().iter().product::<usize>() <= PRINT_ELEMENTS {
+                    let _ = write!(text, "\n{}", tensor);
+                }
+                text
+            },
+            Value::Model(model) => {
+                let mut text = self.summary();
+                for input in &model.snapshot.inputs {
+                    let _ = write!(text, "\n  in  {} {}{:?}", input.name, input.dtype, input.shape.dims());
+                }
+                for target in &model.snapshot.targets {
+                    let _ = write!(text, "\n  out {}", target.name);
+                }
+                text
+            },
+            Value::Outputs(outputs) => outputs
+                .iter()
+                .map(|(name, tensor)| format!("{}: {}", name, tensor_summary(tensor)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::None => Ok(()),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{:?}", s),
+            Value::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            other => write!(f, "{}", other.summary()),
+        }
+    }
+}
+
+/// Dtype, shape and value statistics of a tensor
+pub(super) fn tensor_summary(tensor: &Tensor) -> String {
+    let mut text = format!("{}{:?}", tensor.dtype(), tensor.shape().dims());
+    match TensorStats::new(tensor) {
+        Ok(stats) => {
+            let _ = write!(
+                text,
+                "  min {}  max {}  mean {}",
+                format_value(stats.min),
+                format_value(stats.max),
+                format_value(stats.mean)
+            );
+            if stats.nan_count > 0 {
+                let _ = write!(text, "  nan {}", stats.nan_count);
+            }
+            if stats.inf_count > 0 {
+                let _ = write!(text, "  inf {}", stats.inf_count);
+            }
+        },
+        Err(e) => {
+            let _ = write!(text, "  (no statistics: {})", e);
+        },
+    }
+    text
+}
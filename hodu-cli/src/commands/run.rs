@@ -2,22 +2,36 @@
 //!
 //! This command uses JSON-RPC based plugins to load models and run inference.
 
+mod batch;
+mod profile;
+
+use crate::bundle::{self, UnpackedBundle};
+use crate::cache::{artifact_key, ArtifactCache, ArtifactSpec};
+use crate::config::Settings;
+use crate::errors::{self, ErrorClass};
 use crate::output;
-use crate::plugins::{backend_plugin_name, load_registry, PluginManager, PluginRegistry};
-use crate::tensor::{load_tensor_data, load_tensor_file, save_outputs, save_tensor_data};
-use crate::utils::{core_dtype_to_plugin, path_to_str, plugin_dtype_to_core};
+use crate::plugins::{
+    backend_plugin_name, load_registry, CancellationHandle, PluginClient, PluginEntry, PluginManager, PluginRegistry,
+};
+use crate::tensor::{load_tensor_data, load_tensor_file, save_outputs, save_tensor_data, split_column_selection};
+use crate::utils::{core_dtype_to_plugin, import_onnx, path_to_str, plugin_dtype_to_core};
+use crate::watch;
 use clap::Args;
-use fs2::FileExt;
-use hodu_core::snapshot::Snapshot;
+use hodu_core::error::{HoduError, HoduResult};
+use hodu_core::format::{hdss, hdss::ShardedWeights, hdt};
+use hodu_core::ops::CustomParams;
+use hodu_core::profiler;
+use hodu_core::snapshot::{Interpreter, Snapshot, SnapshotConstant, SnapshotNode, SnapshotTarget};
 use hodu_core::tensor::Tensor;
 use hodu_core::types::{Device as CoreDevice, Shape};
-use hodu_plugin::rpc::TensorInput;
-use hodu_plugin::{current_host_triple, Device, TensorData};
-use sha2::{Digest, Sha256};
+use hodu_plugin::rpc::{methods, PrecisionParams, StreamEvent, StreamParams, TensorInput, TensorOutput};
+use hodu_plugin::{current_host_triple, BuildTarget, Device, TensorData};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 use tempfile::NamedTempFile;
 
 /// Minimum timeout in seconds
@@ -25,14 +39,16 @@ const MIN_TIMEOUT_SECS: u64 = 1;
 /// Maximum timeout in seconds (1 hour)
 const MAX_TIMEOUT_SECS: u64 = 3600;
 /// Known device prefixes for validation
-const KNOWN_DEVICE_PREFIXES: &[&str] = &["cpu", "metal", "cuda", "rocm", "vulkan", "directml"];
+const KNOWN_DEVICE_PREFIXES: &[&str] = &["cpu", "metal", "cuda", "rocm", "vulkan", "directml", "webgpu"];
+/// Target name prefix for intermediates exposed to backend plugins for dumping
+const DUMP_TARGET_PREFIX: &str = "__dump_";
 
 #[derive(Args)]
 pub struct RunArgs {
     /// Model file (.onnx, .hdss, etc.)
     pub model: PathBuf,
 
-    /// Input tensor (name=path), can be repeated
+    /// Input tensor (name=path, or name=table.csv#col,col to pick columns), can be repeated
     #[arg(short, long = "input", value_name = "NAME=PATH")]
     pub input: Vec<String>,
 
@@ -40,11 +56,11 @@ pub struct RunArgs {
     #[arg(long = "inputs", value_name = "INPUTS", value_delimiter = ',')]
     pub inputs: Vec<String>,
 
-    /// Execution device (cpu, metal, cuda::0)
-    #[arg(short, long, default_value = "cpu")]
-    pub device: String,
+    /// Execution device (cpu, metal, cuda::0); default: `device` in ~/.hodu/config.toml, else cpu
+    #[arg(short, long)]
+    pub device: Option<String>,
 
-    /// Backend plugin to use (auto-select if not specified)
+    /// Backend plugin to use (default: `backend` in ~/.hodu/config.toml, else auto-select)
     #[arg(long)]
     pub backend: Option<String>,
 
@@ -52,12 +68,12 @@ pub struct RunArgs {
     #[arg(short, long, default_value = "pretty")]
     pub format: String,
 
-    /// Save outputs to directory
+    /// Save outputs to directory (or to a .hdta file with the hdta format)
     #[arg(long)]
     pub save: Option<PathBuf>,
 
-    /// Save format (hdt, json, or format plugin extension)
-    #[arg(long, default_value = "hdt")]
+    /// Save format (hdta, hdt, json, npy, or format plugin extension)
+    #[arg(long, default_value = "hdta")]
     pub save_format: String,
 
     /// Dry run (show what would be executed)
@@ -71,12 +87,88 @@ pub struct RunArgs {
     /// Timeout in seconds for plugin operations (default: 300)
     #[arg(long, value_name = "SECONDS")]
     pub timeout: Option<u64>,
+
+    /// Override a plugin config value from ~/.hodu/config.toml, can be repeated
+    #[arg(long = "plugin-config", value_name = "PLUGIN.KEY=VALUE")]
+    pub plugin_config: Vec<String>,
+
+    /// Write plugin logs and tracing events to a file as JSON lines
+    #[arg(long, value_name = "PATH")]
+    pub trace_file: Option<PathBuf>,
+
+    /// Dump intermediate results of matching nodes (indices, ranges like 3..8, or name globs like encoder.*)
+    #[arg(long, value_name = "PATTERN")]
+    pub dump_intermediates: Option<String>,
+
+    /// Directory for dumped intermediates
+    #[arg(long, value_name = "DIR", default_value = "intermediates")]
+    pub dump_dir: PathBuf,
+
+    /// Let f32 matmul and convolution run on TF32 tensor cores
+    #[arg(long)]
+    pub allow_tf32: bool,
+
+    /// Let f16/bf16 matmul and convolution accumulate in 16 bits instead of f32
+    #[arg(long)]
+    pub f16_accumulate: bool,
+
+    /// Profile every op on the reference interpreter and write a chrome://tracing JSON trace
+    #[arg(long, value_name = "PATH")]
+    pub profile: Option<PathBuf>,
+
+    /// Keep the model loaded and run it again for each line of inputs read from stdin
+    #[arg(long)]
+    pub keep_alive: bool,
+
+    /// Run again whenever the model, an input or a plugin it uses changes
+    #[arg(long, conflicts_with_all = ["keep_alive", "dry_run"])]
+    pub watch: bool,
+
+    /// Run every case of a directory: a subdirectory of `<input>.<ext>` files per case, or one file
+    /// per case for a model with a single input
+    #[arg(long, value_name = "DIR", requires = "output_dir", conflicts_with_all = BATCH_CONFLICTS)]
+    pub input_dir: Option<PathBuf>,
+
+    /// Run every case of a JSON lines manifest, one {"id": ..., "inputs": {name: path}} per line
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "output_dir",
+        conflicts_with = "input_dir",
+        conflicts_with_all = BATCH_CONFLICTS
+    )]
+    pub manifest: Option<PathBuf>,
+
+    /// Directory receiving the outputs of each case of a batch and a summary.json report
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Batch cases loaded and saved in parallel while the backend runs one at a time
+    #[arg(short = 'j', long, default_value_t = 4)]
+    pub jobs: usize,
 }
 
-pub fn execute(args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
+/// Options of a single run that don't apply to batches
+const BATCH_CONFLICTS: [&str; 5] = ["keep_alive", "watch", "save", "dump_intermediates", "profile"];
+
+pub fn execute(mut args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if output::is_json() {
+        args.format = "json".to_string();
+    }
+
     // Note: We don't check exists() here to avoid TOCTOU race conditions.
     // File operations will fail with descriptive errors if the file doesn't exist.
 
+    // Load plugin registry
+    let registry = load_registry()?;
+
+    // A bundle runs its snapshot, with its run options where none are given; it is unpacked until
+    // the command ends
+    let _bundle = match bundle::is_bundle(&args.model) {
+        true => Some(open_bundle(&mut args, &registry)?),
+        false => None,
+    };
+
     // Validate timeout range
     if let Some(timeout) = args.timeout {
         if !(MIN_TIMEOUT_SECS..=MAX_TIMEOUT_SECS).contains(&timeout) {
@@ -95,46 +187,37 @@ pub fn execute(args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
         .and_then(|e| e.to_str())
         .map(|e| e.to_lowercase());
 
-    // Load plugin registry
-    let registry = load_registry()?;
-
     // Check for model format plugin (for non-builtin formats)
-    let format_plugin = match extension.as_deref() {
-        Some("hdss") | Some("hdt") | Some("json") => {
-            // Builtin formats
-            None
-        },
-        Some(ext) => {
-            let plugin = registry.find_model_format_by_extension(ext);
-            if plugin.is_none() {
-                return Err(friendly_format_error(ext, &registry).into());
-            }
-            // Validate that the plugin has load_model capability
-            if let Some(p) = &plugin {
-                if !p.capabilities.load_model.unwrap_or(false) {
-                    return Err(format!(
-                        "Plugin '{}' doesn't support loading models (missing load_model capability)",
-                        p.name
-                    )
-                    .into());
-                }
-            }
-            plugin
-        },
-        None => {
-            return Err("Model file has no extension. Cannot determine format.".into());
-        },
-    };
+    let format_plugin = find_model_format_plugin(extension.as_deref(), &registry)?;
 
     // Parse device
-    let device = parse_device(&args.device)?;
+    let settings = Settings::load()?;
+    let device = parse_device(&settings.device(args.device.as_deref()))?;
 
     // Find backend plugin
-    let backend_plugin = find_backend_plugin(&args.backend, &device, &registry)?;
+    let backend_plugin = find_backend_plugin(&settings.backend(args.backend.as_deref()), &device, &registry)?;
 
     // Combine --input and --inputs arguments
     let all_inputs: Vec<String> = args.input.iter().chain(args.inputs.iter()).cloned().collect();
 
+    if args.dry_run && args.format == "json" {
+        let inputs: Vec<serde_json::Value> = all_inputs
+            .iter()
+            .filter_map(|input_arg| input_arg.split_once('='))
+            .map(|(name, spec)| {
+                let (path, _) = split_column_selection(spec);
+                serde_json::json!({ "name": name, "path": path })
+            })
+            .collect();
+        output::print_json(&serde_json::json!({
+            "model": args.model.display().to_string(),
+            "model_format": format_plugin.map(|p| p.name.as_str()).unwrap_or("builtin"),
+            "inputs": inputs,
+            "backend": backend_plugin.name,
+            "device": device.to_string(),
+        }))?;
+        return Ok(());
+    }
     if args.dry_run {
         println!(
             "Model format: {} ({})",
@@ -144,7 +227,8 @@ pub fn execute(args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
                 .unwrap_or_else(|| "builtin".to_string())
         );
         for input_arg in &all_inputs {
-            if let Some((name, path)) = input_arg.split_once('=') {
+            if let Some((name, spec)) = input_arg.split_once('=') {
+                let (path, _) = split_column_selection(spec);
                 let ext = std::path::Path::new(path)
                     .extension()
                     .and_then(|e| e.to_str())
@@ -166,6 +250,98 @@ pub fn execute(args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
         Some(secs) => PluginManager::with_timeout(secs)?,
         None => PluginManager::new()?,
     };
+    manager.add_config_overrides(&args.plugin_config)?;
+    manager.allow_read(&args.model);
+    if let Some(path) = &args.trace_file {
+        manager.set_trace_file(path)?;
+    }
+
+    // Between runs the main thread is blocked reading stdin or waiting for changes, so there is
+    // nothing to cancel and Ctrl+C ends the command
+    let interrupt = Interrupt::default();
+    interrupt.install(args.keep_alive || args.watch);
+
+    if args.input_dir.is_some() || args.manifest.is_some() || args.output_dir.is_some() {
+        return batch::run(
+            &args,
+            &registry,
+            format_plugin,
+            &device,
+            backend_plugin,
+            &all_inputs,
+            &mut manager,
+            &interrupt,
+        );
+    }
+
+    let mut session = None;
+    let mut stale_build = false;
+    if !args.watch {
+        return run_model(
+            &args,
+            &registry,
+            format_plugin,
+            &device,
+            backend_plugin,
+            &all_inputs,
+            &mut manager,
+            &interrupt,
+            &mut session,
+            &mut stale_build,
+        );
+    }
+
+    let mut files = vec![args.model.clone()];
+    for input_arg in &all_inputs {
+        if let Some((_, spec)) = input_arg.split_once('=') {
+            files.push(expand_path(split_column_selection(spec).0)?);
+        }
+    }
+    let plugins: Vec<String> = format_plugin
+        .map(|plugin| plugin.name.clone())
+        .into_iter()
+        .chain([backend_plugin.name.clone()])
+        .collect();
+    watch::watch(&mut manager, files, &plugins, |manager, restarted| {
+        if restarted.contains(&backend_plugin.name) {
+            // The session ended with the old process, and the model's build is stale
+            session = None;
+            stale_build = true;
+        }
+        run_model(
+            &args,
+            &registry,
+            format_plugin,
+            &device,
+            backend_plugin,
+            &all_inputs,
+            manager,
+            &interrupt,
+            &mut session,
+            &mut stale_build,
+        )
+    })
+}
+
+/// Load the model and its inputs and run it once, with `--keep-alive` once per line of inputs
+///
+/// With `--watch`, `session` keeps the model loaded on the backend between runs of the same
+/// compiled model, and `stale_build` is set after the backend restarts so the model's cached
+/// artifact is rebuilt instead of reused; it is cleared once that happens.
+#[allow(clippy::too_many_arguments)]
+fn run_model(
+    args: &RunArgs,
+    registry: &PluginRegistry,
+    format_plugin: Option<&PluginEntry>,
+    device: &Device,
+    backend_plugin: &PluginEntry,
+    all_inputs: &[String],
+    manager: &mut PluginManager,
+    interrupt: &Interrupt,
+    session: &mut Option<WarmSession>,
+    stale_build: &mut bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    interrupt.cancelled.store(false, Ordering::SeqCst);
 
     // Load model (using format plugin if needed)
     let model_name = args
@@ -173,61 +349,237 @@ pub fn execute(args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
         .file_name()
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_else(|| args.model.display().to_string());
-    let snapshot_path = if let Some(format_entry) = format_plugin {
-        // Use format plugin to convert to snapshot
-        output::loading(&model_name);
-        let client = manager.get_plugin(&format_entry.name)?;
-        let result = client.load_model(path_to_str(&args.model)?)?;
-        // Validate plugin-returned snapshot path
-        let snapshot_path = PathBuf::from(&result.snapshot_path);
-        if result.snapshot_path.is_empty() {
-            return Err("Plugin returned empty snapshot path".into());
+    // The temp file keeps an imported ONNX model's snapshot alive until the run completes
+    let (snapshot_path, _onnx_snapshot) = load_model_snapshot(&args.model, format_plugin, &model_name, manager)?;
+
+    // Load the snapshot; the weights of a sharded snapshot are read as they are needed
+    let (snapshot, weights) = hdss::load_lazy(&snapshot_path)?;
+
+    // Parse input tensors; with --keep-alive they may all come from stdin instead
+    let inputs = if args.keep_alive && all_inputs.is_empty() {
+        HashMap::new()
+    } else {
+        parse_inputs(all_inputs, &snapshot)?
+    };
+
+    // Resolve intermediates to dump up front so a pattern matching nothing fails before running
+    let dumps = match &args.dump_intermediates {
+        Some(pattern) => select_dumps(&snapshot, pattern, &args.dump_dir)?,
+        None => HashMap::new(),
+    };
+
+    // Custom ops are executed by the plugins declaring them instead of being compiled by the backend,
+    // so they run in-process; so does profiling when the backend cannot report per-op timings
+    let custom_ops = snapshot.custom_op_names();
+    let profile_on_backend = args.profile.is_some()
+        && custom_ops.is_empty()
+        && !args.keep_alive
+        && plugin_supports(manager, &backend_plugin.name, methods::BACKEND_PROFILE)?;
+    if !custom_ops.is_empty() || (args.profile.is_some() && !profile_on_backend) {
+        if args.keep_alive {
+            return Err("--keep-alive needs a backend plugin to hold the model, so it cannot be combined with custom ops or --profile".into());
         }
-        // Canonicalize to resolve any path traversal attempts
-        snapshot_path
-            .canonicalize()
-            .map_err(|e| format!("Invalid snapshot path from plugin '{}': {}", result.snapshot_path, e))?
+        if !custom_ops.is_empty() {
+            output::warning(&format!(
+                "Backend '{}' cannot compile custom ops ({}); running on the in-process interpreter instead",
+                backend_plugin.name,
+                custom_ops.join(", ")
+            ));
+        } else {
+            output::warning(&format!(
+                "Backend '{}' does not implement {}; profiling the in-process interpreter on {} instead, so timings don't reflect the backend's kernels",
+                backend_plugin.name,
+                methods::BACKEND_PROFILE,
+                device
+            ));
+        }
+        let mut details = vec![device.to_string()];
+        if !custom_ops.is_empty() {
+            details.push(format!("custom ops: {}", custom_ops.join(", ")));
+        }
+        if args.profile.is_some() {
+            details.push("profiling".to_string());
+        }
+        output::running(&format!("{} ({})", model_name, details.join(", ")));
+        let start = std::time::Instant::now();
+        let (outputs, recorded) = run_in_process(
+            &snapshot,
+            weights.as_ref(),
+            &inputs,
+            &dumps,
+            args.profile.is_some(),
+            device,
+            registry,
+            manager,
+        )?;
+        if !args.quiet {
+            let duration = start.elapsed().as_secs_f64();
+            output::finished(&format!("inference in {}", output::format_duration(duration)));
+        }
+        if let (Some(recorded), Some(path)) = (recorded, &args.profile) {
+            profile::report(&profile::from_core(&recorded), path, args.quiet)?;
+        }
+        report_dumps(&dumps, args);
+        return emit_outputs(&outputs, args);
+    }
+
+    // Backend plugins only return targets, so dumped intermediates are compiled in as extra targets
+    let dump_snapshot = if dumps.is_empty() {
+        None
     } else {
-        // Builtin format - model is already a snapshot
-        args.model.clone()
+        let mut extended = snapshot.clone();
+        if let Some(weights) = &weights {
+            weights.load_into(&mut extended)?;
+        }
+        let mut indices: Vec<usize> = dumps.keys().copied().collect();
+        indices.sort_unstable();
+        for index in indices {
+            extended.targets.push(SnapshotTarget {
+                name: format!("{}{}", DUMP_TARGET_PREFIX, index),
+                id: extended.nodes[index].output_id,
+            });
+        }
+        let temp_file = tempfile::Builder::new()
+            .prefix("hodu_dump_")
+            .suffix(".hdss")
+            .tempfile()
+            .map_err(|e| format!("Failed to create temp file for snapshot: {}", e))?;
+        extended.save(temp_file.path())?;
+        Some(temp_file)
+    };
+    let snapshot_path = match &dump_snapshot {
+        Some(temp_file) => temp_file.path().to_path_buf(),
+        None => snapshot_path,
     };
 
-    // Load the snapshot
-    let snapshot = Snapshot::load(&snapshot_path)?;
+    // Run inference using backend plugin
+    // First, spawn the backend plugin so Ctrl+C can cancel its requests
+    let supports_sessions = plugin_supports(manager, &backend_plugin.name, methods::BACKEND_LOAD_SESSION)?;
+    interrupt.set_handle(manager.get_cancellation_handle(&backend_plugin.name));
+    let cancelled = &interrupt.cancelled;
 
-    // Parse input tensors
-    let inputs = parse_inputs(&all_inputs, &snapshot)?;
+    let start = std::time::Instant::now();
+    let library_path = compile(
+        manager,
+        &backend_plugin.name,
+        &snapshot_path,
+        weights.as_ref().filter(|_| dump_snapshot.is_none()),
+        &model_name,
+        device,
+        *stale_build,
+    )?;
+    *stale_build = false;
+    let backend_client = manager.get_plugin(&backend_plugin.name)?;
 
-    // Save input tensors to temp files and create TensorInput refs
-    // Use tempfile crate for secure, atomic temp file creation
-    let mut input_refs = Vec::new();
-    let mut temp_files = Vec::new(); // Keep temp files alive until inference completes
-    for (name, tensor_data) in &inputs {
-        let temp_file = NamedTempFile::with_prefix(format!("hodu_input_{}_", name))
-            .map_err(|e| format!("Failed to create temp file for input '{}': {}", name, e))?;
-        let temp_path = temp_file.path().to_path_buf();
-        save_tensor_data(tensor_data, &temp_path)?;
-        input_refs.push(TensorInput {
-            name: name.clone(),
-            path: temp_path.to_string_lossy().to_string(),
-        });
-        temp_files.push(temp_file); // Keep file handle to prevent deletion
+    // Run with cached library
+    if !args.quiet {
+        backend_client.set_stream_handler(Box::new(stream_printer(&args.format)));
+    }
+    let library_path = path_to_str(&library_path)?;
+    let snapshot_path = path_to_str(&snapshot_path)?;
+    let precision = precision_params(args);
+    let library = Runner::Library {
+        library_path,
+        snapshot_path,
+        device,
+        precision,
+    };
+    let label = format!("{} ({})", model_name, device);
+    if args.keep_alive {
+        let runner = if supports_sessions {
+            output::loading(&label);
+            Runner::Session(backend_client.load_session(library_path, snapshot_path, device, precision)?)
+        } else {
+            output::warning(&format!(
+                "Backend '{}' does not support sessions; the model is reloaded on every run",
+                backend_plugin.name
+            ));
+            library
+        };
+        let result = run_keep_alive(
+            std::io::stdin().lock(),
+            backend_client,
+            &runner,
+            &label,
+            &snapshot,
+            &inputs,
+            &dumps,
+            args,
+            cancelled,
+        );
+        if let Runner::Session(session_id) = &runner {
+            if let Err(e) = backend_client.close_session(session_id) {
+                output::warning(&format!("Failed to close session: {}", e));
+            }
+        }
+        if cancelled.load(Ordering::SeqCst) {
+            return Err(errors::classified(ErrorClass::Cancelled, "Operation cancelled by user"));
+        }
+        return result;
     }
 
-    // Run inference using backend plugin
-    // First, spawn the backend plugin and get cancellation handle
-    let _ = manager.get_plugin(&backend_plugin.name)?; // Ensure plugin is running
-    let cancel_handle = manager.get_cancellation_handle(&backend_plugin.name);
-
-    // Set up Ctrl+C handler for cancellation
-    let cancelled = Arc::new(AtomicBool::new(false));
-    let cancelled_clone = Arc::clone(&cancelled);
-    if let Some(handle) = cancel_handle {
+    output::running(&label);
+    let (outputs, recorded) = if profile_on_backend {
+        let (input_refs, _temp_files) = save_inputs(&inputs, backend_client.inline_tensor_limit())?;
+        let mut recorded = backend_client.profile(library_path, snapshot_path, device, input_refs, precision)?;
+        let outputs = load_outputs(backend_client, std::mem::take(&mut recorded.outputs), &dumps)?;
+        (outputs, Some(recorded))
+    } else if args.watch && supports_sessions {
+        let runner = Runner::Session(warm_session(
+            backend_client,
+            session,
+            library_path,
+            snapshot_path,
+            device,
+            precision,
+        )?);
+        (run_inputs(backend_client, &runner, &inputs, &dumps)?, None)
+    } else {
+        (run_inputs(backend_client, &library, &inputs, &dumps)?, None)
+    };
+    let duration = start.elapsed().as_secs_f64();
+    if !args.quiet {
+        output::finished(&format!("inference in {}", output::format_duration(duration)));
+    }
+    if let (Some(recorded), Some(path)) = (recorded, &args.profile) {
+        profile::report(&recorded, path, args.quiet)?;
+    }
+
+    // Check if was cancelled
+    if cancelled.load(Ordering::SeqCst) {
+        return Err(errors::classified(ErrorClass::Cancelled, "Operation cancelled by user"));
+    }
+
+    report_dumps(&dumps, args);
+    emit_outputs(&outputs, args)
+}
+
+/// Ctrl+C handling shared by every run of the command, cancelling the backend's request
+#[derive(Default)]
+struct Interrupt {
+    cancelled: Arc<AtomicBool>,
+    /// Cancellation handle of the backend running the model, once it is started
+    handle: Arc<Mutex<Option<CancellationHandle>>>,
+}
+
+impl Interrupt {
+    /// Install the Ctrl+C handler; with `exit_when_idle`, Ctrl+C while the backend is idle ends
+    /// the command instead of cancelling
+    fn install(&self, exit_when_idle: bool) {
+        let cancelled = Arc::clone(&self.cancelled);
+        let handle = Arc::clone(&self.handle);
         if let Err(e) = ctrlc::set_handler(move || {
-            cancelled_clone.store(true, Ordering::SeqCst);
-            eprintln!("\nCancelling...");
-            if let Err(cancel_err) = handle.cancel() {
-                eprintln!("Warning: Failed to send cancellation: {}", cancel_err);
+            let handle = handle.lock().unwrap_or_else(PoisonError::into_inner).clone();
+            match handle {
+                Some(handle) if !exit_when_idle || handle.is_busy() => {
+                    cancelled.store(true, Ordering::SeqCst);
+                    eprintln!("\nCancelling...");
+                    if let Err(cancel_err) = handle.cancel() {
+                        eprintln!("Warning: Failed to send cancellation: {}", cancel_err);
+                    }
+                },
+                // Before the backend starts there is no request to cancel
+                _ => std::process::exit(130),
             }
         }) {
             output::warning(&format!(
@@ -237,38 +589,191 @@ pub fn execute(args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    // Compute cache key from snapshot content (with size limit check)
-    // Use a single open file handle to avoid TOCTOU race conditions
-    // Limit is configurable via HODU_MAX_SNAPSHOT_SIZE env var (in bytes)
-    const DEFAULT_MAX_SNAPSHOT_SIZE: u64 = 10 * 1024 * 1024 * 1024; // 10GB default
-    let max_snapshot_size = std::env::var("HODU_MAX_SNAPSHOT_SIZE")
-        .ok()
-        .and_then(|s| s.parse::<u64>().ok())
-        .unwrap_or(DEFAULT_MAX_SNAPSHOT_SIZE);
-    let snapshot_file = std::fs::File::open(&snapshot_path).map_err(|e| format!("Failed to open snapshot: {}", e))?;
-    let snapshot_size = snapshot_file
-        .metadata()
-        .map_err(|e| format!("Failed to read snapshot metadata: {}", e))?
-        .len();
-    if snapshot_size > max_snapshot_size {
-        return Err(format!(
-            "Snapshot file too large: {} bytes (max: {} bytes, set HODU_MAX_SNAPSHOT_SIZE to override)",
-            snapshot_size, max_snapshot_size
-        )
-        .into());
+    fn set_handle(&self, handle: Option<CancellationHandle>) {
+        *self.handle.lock().unwrap_or_else(PoisonError::into_inner) = handle;
+    }
+}
+
+/// A model kept loaded on the backend between `--watch` runs
+struct WarmSession {
+    library_path: String,
+    id: String,
+}
+
+/// The session of `library_path` kept from an earlier run, or a new one replacing the previous
+fn warm_session(
+    client: &mut PluginClient,
+    session: &mut Option<WarmSession>,
+    library_path: &str,
+    snapshot_path: &str,
+    device: &Device,
+    precision: Option<PrecisionParams>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    if let Some(warm) = session.as_ref().filter(|warm| warm.library_path == library_path) {
+        return Ok(warm.id.clone());
+    }
+    if let Some(stale) = session.take() {
+        if let Err(e) = client.close_session(&stale.id) {
+            output::warning(&format!("Failed to close session: {}", e));
+        }
+    }
+    let id = client.load_session(library_path, snapshot_path, device, precision)?;
+    *session = Some(WarmSession {
+        library_path: library_path.to_string(),
+        id: id.clone(),
+    });
+    Ok(id)
+}
+
+/// Whether the plugin lists `method` in its capabilities, starting it if needed
+pub(crate) fn plugin_supports(
+    manager: &mut PluginManager,
+    plugin_name: &str,
+    method: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let _ = manager.get_plugin(plugin_name)?; // Ensure plugin is running
+    Ok(manager
+        .get_info(plugin_name)
+        .is_some_and(|info| info.capabilities.iter().any(|capability| capability == method)))
+}
+
+/// Unpack the bundle `args.model`, point `args.model` at its snapshot and fill in its run options
+fn open_bundle(args: &mut RunArgs, registry: &PluginRegistry) -> Result<UnpackedBundle, Box<dyn std::error::Error>> {
+    if args.watch {
+        return Err(errors::classified(
+            ErrorClass::Usage,
+            "--watch cannot watch a bundle; run its model file instead",
+        ));
     }
-    // Read from the already-opened handle to avoid TOCTOU
-    use std::io::Read;
-    let mut snapshot_content = Vec::new();
-    std::io::BufReader::new(snapshot_file)
-        .read_to_end(&mut snapshot_content)
-        .map_err(|e| format!("Failed to read snapshot: {}", e))?;
-    let mut hasher = Sha256::new();
-    hasher.update(&snapshot_content);
-    hasher.update(current_host_triple().as_bytes());
-    let snapshot_hash = hex::encode(hasher.finalize());
-
-    // Determine library extension and cache path
+    output::unpacking(&args.model.display().to_string());
+    let bundle = UnpackedBundle::open(&args.model)?;
+    bundle.check_plugins(registry)?;
+
+    let defaults = &bundle.manifest().run;
+    args.device = args.device.take().or_else(|| defaults.device.clone());
+    args.backend = args.backend.take().or_else(|| defaults.backend.clone());
+    args.timeout = args.timeout.or(defaults.timeout);
+    args.allow_tf32 |= defaults.allow_tf32;
+    args.f16_accumulate |= defaults.f16_accumulate;
+    // Overrides given on the command line come last, so they win
+    args.plugin_config = defaults
+        .plugin_config
+        .iter()
+        .chain(&args.plugin_config)
+        .cloned()
+        .collect();
+    args.model = bundle.snapshot_path();
+    Ok(bundle)
+}
+
+/// Find the format plugin loading a model with `extension`, or `None` for builtin formats
+pub(crate) fn find_model_format_plugin<'a>(
+    extension: Option<&str>,
+    registry: &'a PluginRegistry,
+) -> Result<Option<&'a PluginEntry>, Box<dyn std::error::Error>> {
+    let plugin = match extension {
+        Some("hdss") | Some("hdt") | Some("json") => {
+            // Builtin formats
+            None
+        },
+        Some(ext) => {
+            let plugin = registry.find_model_format_by_extension(ext);
+            // Without a plugin, .onnx falls back to the builtin importer
+            if plugin.is_none() && ext != "onnx" {
+                return Err(errors::classified(
+                    ErrorClass::Plugin,
+                    friendly_format_error(ext, registry),
+                ));
+            }
+            // Validate that the plugin has load_model capability
+            if let Some(p) = &plugin {
+                if !p.capabilities.load_model.unwrap_or(false) {
+                    return Err(errors::classified(
+                        ErrorClass::Plugin,
+                        format!(
+                            "Plugin '{}' doesn't support loading models (missing load_model capability)",
+                            p.name
+                        ),
+                    ));
+                }
+            }
+            plugin
+        },
+        None => {
+            return Err("Model file has no extension. Cannot determine format.".into());
+        },
+    };
+    Ok(plugin)
+}
+
+/// Get a snapshot of `model`, converting it with its format plugin or the builtin ONNX importer
+///
+/// Returns the snapshot path, and the temp file holding an imported snapshot, which must be kept
+/// alive as long as the path is used.
+pub(crate) fn load_model_snapshot(
+    model: &Path,
+    format_plugin: Option<&PluginEntry>,
+    model_name: &str,
+    manager: &mut PluginManager,
+) -> Result<(PathBuf, Option<NamedTempFile>), Box<dyn std::error::Error>> {
+    let is_onnx = model
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("onnx"));
+    Ok(if let Some(format_entry) = format_plugin {
+        // Use format plugin to convert to snapshot
+        output::loading(model_name);
+        let client = manager.get_plugin(&format_entry.name)?;
+        let result = client.load_model(path_to_str(model)?)?;
+        // Validate plugin-returned snapshot path
+        let snapshot_path = PathBuf::from(&result.snapshot_path);
+        if result.snapshot_path.is_empty() {
+            return Err("Plugin returned empty snapshot path".into());
+        }
+        client.check_plugin_path(&result.snapshot_path)?;
+        // Canonicalize to resolve any path traversal attempts
+        let snapshot_path = snapshot_path
+            .canonicalize()
+            .map_err(|e| format!("Invalid snapshot path from plugin '{}': {}", result.snapshot_path, e))?;
+        (snapshot_path, None)
+    } else if is_onnx {
+        output::loading(model_name);
+        let temp_file = import_onnx(model)?;
+        (temp_file.path().to_path_buf(), Some(temp_file))
+    } else {
+        // Builtin format - model is already a snapshot
+        (model.to_path_buf(), None)
+    })
+}
+
+/// Compile the snapshot into a shared library for the host, or reuse the cached one
+///
+/// The artifact is looked up in the [`ArtifactCache`] by the snapshot, the weight shards in
+/// `weights`, the host target and the backend's version and config.
+pub(crate) fn compile_cached(
+    manager: &mut PluginManager,
+    backend_name: &str,
+    snapshot_path: &Path,
+    weights: Option<&ShardedWeights>,
+    model_name: &str,
+    device: &Device,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    compile(manager, backend_name, snapshot_path, weights, model_name, device, false)
+}
+
+/// [`compile_cached`], first removing the cached artifact if `rebuild` is set
+fn compile(
+    manager: &mut PluginManager,
+    backend_name: &str,
+    snapshot_path: &Path,
+    weights: Option<&ShardedWeights>,
+    model_name: &str,
+    device: &Device,
+    rebuild: bool,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let format = "sharedlib";
+    let spec = backend_artifact_spec(manager, backend_name, &BuildTarget::host(device.clone()), format)?;
+    let key = artifact_key(snapshot_path, weights, &spec)?;
     let lib_ext = if cfg!(target_os = "macos") {
         "dylib"
     } else if cfg!(target_os = "windows") {
@@ -277,76 +782,478 @@ pub fn execute(args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
         "so"
     };
 
-    let cache_dir = dirs::home_dir()
-        .ok_or("Could not determine home directory")?
-        .join(".hodu")
-        .join("cache")
-        .join(&backend_plugin.name);
-    std::fs::create_dir_all(&cache_dir)?;
-    let library_path = cache_dir.join(format!("{}.{}", snapshot_hash, lib_ext));
-
-    // Build if not cached (use file lock to prevent concurrent builds)
-    let backend_client = manager.get_plugin(&backend_plugin.name)?;
-    let start = std::time::Instant::now();
-    let lock_path = cache_dir.join(format!("{}.lock", snapshot_hash));
-    let lock_file = std::fs::File::create(&lock_path)?;
-    lock_file.lock_exclusive()?;
-    // Check inside lock to prevent TOCTOU race
-    if !library_path.exists() {
+    let cache = ArtifactCache::open()?;
+    if rebuild {
+        cache.remove(backend_name, &key, lib_ext)?;
+    }
+    let backend_client = manager.get_plugin(backend_name)?;
+    let (library_path, hit) = cache.get_or_build(&key, lib_ext, model_name, &spec, |path| {
         output::compiling(&format!("{} ({})", model_name, device));
         backend_client.build(
-            path_to_str(&snapshot_path)?,
+            path_to_str(snapshot_path)?,
             current_host_triple(),
-            &device,
-            "sharedlib",
-            path_to_str(&library_path)?,
+            device,
+            format,
+            path_to_str(path)?,
         )?;
-    } else {
-        output::cached(&model_name);
+        Ok(())
+    })?;
+    if hit {
+        output::cached(model_name);
     }
-    lock_file.unlock()?;
-    // Clean up lock file (best effort)
-    let _ = std::fs::remove_file(&lock_path);
+    Ok(library_path)
+}
 
-    // Run with cached library
-    output::running(&format!("{} ({})", model_name, device));
-    let result = backend_client.run(
-        path_to_str(&library_path)?,
-        path_to_str(&snapshot_path)?,
-        &device,
-        input_refs,
-    )?;
-    let duration = start.elapsed().as_secs_f64();
+/// What a backend's artifacts for `target` in `format` are built from, starting the backend to
+/// learn its version
+pub(crate) fn backend_artifact_spec(
+    manager: &mut PluginManager,
+    backend_name: &str,
+    target: &BuildTarget,
+    format: &str,
+) -> Result<ArtifactSpec, Box<dyn std::error::Error>> {
+    manager.get_plugin(backend_name)?;
+    let version = manager
+        .get_info(backend_name)
+        .map(|info| info.version.clone())
+        .unwrap_or_default();
+    let config = manager.plugin_config(backend_name).unwrap_or_default();
+    Ok(ArtifactSpec::new(backend_name, &version, target, format).with_options(serde_json::json!({ "config": config })))
+}
+
+/// How a run reaches the backend plugin
+pub(crate) enum Runner<'a> {
+    /// `backend.run`, which loads the compiled library on every call
+    Library {
+        library_path: &'a str,
+        snapshot_path: &'a str,
+        device: &'a str,
+        precision: Option<PrecisionParams>,
+    },
+    /// `backend.run_session` on a model the plugin keeps loaded
+    Session(String),
+}
+
+/// Run `inputs` on the backend and load its outputs, saving dumped intermediates as they arrive
+pub(crate) fn run_inputs(
+    client: &mut PluginClient,
+    runner: &Runner<'_>,
+    inputs: &HashMap<String, TensorData>,
+    dumps: &HashMap<usize, PathBuf>,
+) -> Result<HashMap<String, TensorData>, Box<dyn std::error::Error>> {
+    let (input_refs, _temp_files) = save_inputs(inputs, client.inline_tensor_limit())?;
+
+    let result = match runner {
+        Runner::Library {
+            library_path,
+            snapshot_path,
+            device,
+            precision,
+        } => client.run(library_path, snapshot_path, device, input_refs, *precision)?,
+        Runner::Session(session_id) => client.run_session(session_id, input_refs)?,
+    };
+
+    load_outputs(client, result.outputs, dumps)
+}
+
+/// Save input tensors to temp files for the backend to read, or inline those of at most
+/// `inline_limit` bytes
+///
+/// The temp files are deleted when dropped, so they must be kept until the backend is done.
+pub(crate) fn save_inputs(
+    inputs: &HashMap<String, TensorData>,
+    inline_limit: u64,
+) -> Result<(Vec<TensorInput>, Vec<NamedTempFile>), Box<dyn std::error::Error>> {
+    // Use tempfile crate for secure, atomic temp file creation
+    let mut input_refs = Vec::new();
+    let mut temp_files = Vec::new();
+    for (name, tensor_data) in inputs {
+        if inline_limit > 0 && tensor_data.data.len() as u64 <= inline_limit {
+            input_refs.push(TensorInput::inline(name.clone(), tensor_data));
+            continue;
+        }
+        let temp_file = NamedTempFile::with_prefix(format!("hodu_input_{}_", name))
+            .map_err(|e| format!("Failed to create temp file for input '{}': {}", name, e))?;
+        let temp_path = temp_file.path().to_path_buf();
+        save_tensor_data(tensor_data, &temp_path)?;
+        input_refs.push(TensorInput::new(name.clone(), temp_path.to_string_lossy()));
+        temp_files.push(temp_file); // Keep file handle to prevent deletion
+    }
+    Ok((input_refs, temp_files))
+}
+
+/// Load the output tensors the backend wrote or sent inline, saving dumped intermediates instead
+/// of returning them
+///
+/// Output files of a sandboxed plugin must lie inside its sandbox.
+fn load_outputs(
+    client: &PluginClient,
+    output_refs: Vec<TensorOutput>,
+    dumps: &HashMap<usize, PathBuf>,
+) -> Result<HashMap<String, TensorData>, Box<dyn std::error::Error>> {
+    let mut outputs: HashMap<String, TensorData> = HashMap::new();
+    for output_ref in output_refs {
+        let tensor_data = match &output_ref.data {
+            Some(inline) => inline
+                .decode()
+                .map_err(|e| format!("Invalid inline output '{}': {}", output_ref.name, e))?,
+            None => {
+                client.check_plugin_path(&output_ref.path)?;
+                load_tensor_data(&output_ref.path)?
+            },
+        };
+        let dump_path = output_ref
+            .name
+            .strip_prefix(DUMP_TARGET_PREFIX)
+            .and_then(|index| index.parse::<usize>().ok())
+            .and_then(|index| dumps.get(&index));
+        match dump_path {
+            Some(path) => save_tensor_data(&tensor_data, path)?,
+            None => {
+                outputs.insert(output_ref.name, tensor_data);
+            },
+        }
+    }
+    Ok(outputs)
+}
+
+/// Run the command-line inputs, if any, then one more run per line of inputs read from `lines`
+///
+/// Each line holds `name=path` specs separated by spaces or commas, like `--inputs`. A line that
+/// fails, or times out, is reported and the next one is read; end of input or Ctrl+C ends the
+/// loop, and so does the backend exiting.
+#[allow(clippy::too_many_arguments)]
+fn run_keep_alive(
+    lines: impl BufRead,
+    client: &mut PluginClient,
+    runner: &Runner<'_>,
+    label: &str,
+    snapshot: &Snapshot,
+    inputs: &HashMap<String, TensorData>,
+    dumps: &HashMap<usize, PathBuf>,
+    args: &RunArgs,
+    cancelled: &AtomicBool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let run = |client: &mut PluginClient, inputs: &HashMap<String, TensorData>| {
+        output::running(label);
+        let start = std::time::Instant::now();
+        let outputs = run_inputs(client, runner, inputs, dumps)?;
+        if !args.quiet {
+            let duration = start.elapsed().as_secs_f64();
+            output::finished(&format!("inference in {}", output::format_duration(duration)));
+        }
+        report_dumps(dumps, args);
+        emit_outputs(&outputs, args)
+    };
+
+    if !inputs.is_empty() {
+        run(client, inputs)?;
+    }
     if !args.quiet {
-        output::finished(&format!("inference in {}", output::format_duration(duration)));
+        output::info("reading inputs from stdin, one run per line (name=path ...)");
+    }
+    for line in lines.lines() {
+        let line = line.map_err(|e| format!("Failed to read inputs from stdin: {}", e))?;
+        let specs: Vec<String> = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|spec| !spec.is_empty())
+            .map(str::to_string)
+            .collect();
+        if specs.is_empty() {
+            continue;
+        }
+        let result = parse_inputs(&specs, snapshot).and_then(|inputs| run(client, &inputs));
+        if cancelled.load(Ordering::SeqCst) {
+            break;
+        }
+        if let Err(e) = result {
+            if client.is_closed() {
+                return Err(e);
+            }
+            output::error(&e.to_string());
+        }
     }
+    Ok(())
+}
 
-    // Check if was cancelled
-    if cancelled.load(Ordering::SeqCst) {
-        return Err("Operation cancelled by user".into());
+/// Precision overrides requested on the command line, if any
+fn precision_params(args: &RunArgs) -> Option<PrecisionParams> {
+    (args.allow_tf32 || args.f16_accumulate).then(|| PrecisionParams {
+        allow_tf32: args.allow_tf32.then_some(true),
+        f16_accumulate_f32: args.f16_accumulate.then_some(false),
+    })
+}
+
+/// Map the nodes selected by `pattern` to the .hdt files their results are dumped to
+fn select_dumps(
+    snapshot: &Snapshot,
+    pattern: &str,
+    dump_dir: &Path,
+) -> Result<HashMap<usize, PathBuf>, Box<dyn std::error::Error>> {
+    let selected = snapshot.select_nodes(pattern);
+    if selected.is_empty() {
+        return Err(format!("No nodes match --dump-intermediates pattern '{}'", pattern).into());
     }
+    std::fs::create_dir_all(dump_dir)
+        .map_err(|e| format!("Failed to create dump directory '{}': {}", dump_dir.display(), e))?;
+
+    Ok(selected
+        .into_iter()
+        .map(|index| {
+            let node = &snapshot.nodes[index];
+            let label = node.name.clone().unwrap_or_else(|| node.op.to_string());
+            let label: String = label
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            let label = label.trim_matches('_');
+            (index, dump_dir.join(format!("{:04}_{}.hdt", index, label)))
+        })
+        .collect())
+}
 
-    // Load output tensors from paths
-    let mut outputs: HashMap<String, TensorData> = HashMap::new();
-    for output_ref in result.outputs {
-        let tensor_data = load_tensor_data(&output_ref.path)?;
-        outputs.insert(output_ref.name, tensor_data);
+fn report_dumps(dumps: &HashMap<usize, PathBuf>, args: &RunArgs) {
+    if !dumps.is_empty() && !args.quiet {
+        output::info(&format!(
+            "dumped {} intermediate(s) to {}",
+            dumps.len(),
+            args.dump_dir.display()
+        ));
+    }
+}
+
+/// Print results the backend streams while it runs
+///
+/// In pretty format, text items such as generated tokens are written as they arrive and any other
+/// item is printed on its own line. In json format, every item is one JSON line.
+fn stream_printer(format: &str) -> impl FnMut(&StreamParams) + Send + 'static {
+    let json = format == "json";
+    // Whether streamed text left the cursor mid-line
+    let mut mid_line = false;
+    move |params| {
+        output::clear_progress();
+        let (index, data) = match &params.event {
+            StreamEvent::Chunk { index, data } => (*index, data),
+            StreamEvent::Close { .. } => {
+                if mid_line {
+                    println!();
+                    mid_line = false;
+                }
+                return;
+            },
+            StreamEvent::Open => return,
+        };
+
+        if json {
+            println!(
+                "{}",
+                serde_json::json!({ "stream": params.stream, "index": index, "data": data })
+            );
+            return;
+        }
+
+        match data {
+            serde_json::Value::String(text) => {
+                print!("{}", output::sanitize_for_terminal(text));
+                let _ = std::io::stdout().flush();
+                mid_line = !text.ends_with('\n');
+            },
+            data => {
+                if mid_line {
+                    println!();
+                    mid_line = false;
+                }
+                println!("{}: {}", output::sanitize_for_terminal(&params.stream), data);
+            },
+        }
     }
+}
 
-    // Save outputs if requested
+/// Save outputs if requested and print them unless quiet
+fn emit_outputs(outputs: &HashMap<String, TensorData>, args: &RunArgs) -> Result<(), Box<dyn std::error::Error>> {
     if let Some(save_dir) = &args.save {
-        save_outputs(&outputs, save_dir, &args.save_format)?;
+        save_outputs(outputs, save_dir, &args.save_format)?;
     }
 
-    // Output results
     if !args.quiet {
-        output_results(&outputs, &args)?;
+        output_results(outputs, args)?;
     }
 
     Ok(())
 }
 
-fn parse_inputs(
+/// Outputs of an in-process run, with its profile when one was recorded
+type InProcessRun = (HashMap<String, TensorData>, Option<profiler::Profile>);
+
+/// Device the in-process interpreter runs on, failing when this build has no kernels for it
+pub(crate) fn interpreter_device(device: &str) -> Result<CoreDevice, Box<dyn std::error::Error>> {
+    device.parse::<CoreDevice>().map_err(|_| {
+        format!(
+            "The in-process interpreter cannot run on {} in this build of hodu (rebuild with its device feature)",
+            device
+        )
+        .into()
+    })
+}
+
+/// Run a snapshot on the reference interpreter
+///
+/// Builtin nodes execute in-process on `device`, while every custom op node is forwarded to the
+/// plugin declaring its `op.<name>` capability, exchanging tensors through temporary HDT files.
+/// With `profile`, every node is timed and the recorded profile is returned with the outputs.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_in_process(
+    snapshot: &Snapshot,
+    weights: Option<&ShardedWeights>,
+    inputs: &HashMap<String, TensorData>,
+    dumps: &HashMap<usize, PathBuf>,
+    profile: bool,
+    device: &Device,
+    registry: &PluginRegistry,
+    manager: &mut PluginManager,
+) -> Result<InProcessRun, Box<dyn std::error::Error>> {
+    let core_device = interpreter_device(device)?;
+
+    // Resolve every provider up front so a missing plugin fails before any work is done
+    let mut providers = HashMap::new();
+    for name in snapshot.custom_op_names() {
+        let plugin = registry.find_custom_op(&name).ok_or_else(|| {
+            format!(
+                "No plugin provides custom op '{}' (expected a plugin with the 'op.{}' capability)",
+                name, name
+            )
+        })?;
+        providers.insert(name, plugin.name.clone());
+    }
+
+    let temp_dir = tempfile::tempdir().map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let manager = RefCell::new(manager);
+    let calls = Cell::new(0);
+    let handler = |params: &CustomParams, inputs: &[Tensor]| -> HoduResult<Vec<Tensor>> {
+        let call = calls.get();
+        calls.set(call + 1);
+        let plugin = &providers[&params.name];
+        forward_custom_op(
+            params,
+            inputs,
+            plugin,
+            device,
+            &temp_dir.path().join(call.to_string()),
+            &mut manager.borrow_mut(),
+        )
+        .map_err(|e| HoduError::BackendError(format!("custom op '{}' failed in '{}': {}", params.name, plugin, e)))
+    };
+
+    let tensors = inputs
+        .iter()
+        .map(|(name, data)| {
+            let tensor = Tensor::from_bytes(
+                &data.data,
+                Shape::new(&data.shape),
+                plugin_dtype_to_core(data.dtype)?,
+                CoreDevice::CPU,
+            )?;
+            Ok((name.as_str(), tensor))
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    let named: Vec<(&str, &Tensor)> = tensors.iter().map(|(name, tensor)| (*name, tensor)).collect();
+
+    let observer = |index: usize, _: &SnapshotNode, tensor: &Tensor| -> HoduResult<()> {
+        match dumps.get(&index) {
+            Some(path) => hdt::save(tensor, path),
+            None => Ok(()),
+        }
+    };
+
+    let loader = |constant: &SnapshotConstant| -> HoduResult<Tensor> {
+        weights
+            .ok_or_else(|| HoduError::InvalidArgument(format!("constant {} has no weights", constant.id.0)))?
+            .get(constant)
+    };
+
+    let interpreter = Interpreter::new(snapshot)
+        .device(core_device)
+        .custom_op_handler(&handler)
+        .node_observer(&observer)
+        .constant_loader(&loader);
+    let (outputs, recorded) = if profile {
+        profiler::start()?;
+        let outputs = interpreter.run(&named);
+        let recorded = profiler::stop()?;
+        (outputs?, Some(recorded))
+    } else {
+        (interpreter.run(&named)?, None)
+    };
+
+    let outputs = outputs
+        .into_iter()
+        .map(|(name, tensor)| {
+            let data = TensorData::new(
+                tensor.to_bytes()?,
+                tensor.shape().dims().to_vec(),
+                core_dtype_to_plugin(tensor.dtype()),
+            );
+            Ok((name, data))
+        })
+        .collect::<Result<_, Box<dyn std::error::Error>>>()?;
+    Ok((outputs, recorded))
+}
+
+/// Execute one custom op node through its plugin
+fn forward_custom_op(
+    params: &CustomParams,
+    inputs: &[Tensor],
+    plugin: &str,
+    device: &Device,
+    work_dir: &Path,
+    manager: &mut PluginManager,
+) -> Result<Vec<Tensor>, Box<dyn std::error::Error>> {
+    let attributes = if params.attributes.trim().is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_str(&params.attributes).map_err(|e| format!("invalid attributes: {}", e))?
+    };
+
+    std::fs::create_dir_all(work_dir)?;
+    let mut input_refs = Vec::with_capacity(inputs.len());
+    for (i, tensor) in inputs.iter().enumerate() {
+        let path = work_dir.join(format!("input{}.hdt", i));
+        hdt::save(tensor, &path)?;
+        input_refs.push(TensorInput::new(format!("input{}", i), path_to_str(&path)?));
+    }
+
+    let client = manager.get_plugin(plugin)?;
+    let result = client.custom_op(&params.name, attributes, device, input_refs)?;
+
+    result
+        .outputs
+        .iter()
+        .map(|output| match &output.data {
+            Some(inline) => {
+                let data = inline.decode()?;
+                let dtype = plugin_dtype_to_core(data.dtype)?;
+                Ok(Tensor::from_bytes(
+                    &data.data,
+                    Shape::new(&data.shape),
+                    dtype,
+                    CoreDevice::CPU,
+                )?)
+            },
+            None => {
+                client.check_plugin_path(&output.path)?;
+                Ok(hdt::load(&output.path)?)
+            },
+        })
+        .collect()
+}
+
+pub(crate) fn parse_inputs(
     input_args: &[String],
     snapshot: &Snapshot,
 ) -> Result<HashMap<String, TensorData>, Box<dyn std::error::Error>> {
@@ -368,10 +1275,14 @@ fn parse_inputs(
             return Err(format!("Input name '{}' contains invalid characters", name).into());
         }
 
-        let path = expand_path(parts[1])?;
+        let (path, columns) = split_column_selection(parts[1]);
+        let path = expand_path(path)?;
 
         if !path.exists() {
-            return Err(format!("Input file not found: {}", path.display()).into());
+            return Err(errors::classified(
+                ErrorClass::Io,
+                format!("Input file not found: {}", path.display()),
+            ));
         }
 
         let input_spec = snapshot.inputs.iter().find(|i| i.name == name).ok_or_else(|| {
@@ -382,7 +1293,12 @@ fn parse_inputs(
             )
         })?;
 
-        let tensor_data = load_tensor_file(&path, input_spec.shape.dims(), core_dtype_to_plugin(input_spec.dtype))?;
+        let tensor_data = load_tensor_file(
+            &path,
+            columns.as_deref(),
+            input_spec.shape.dims(),
+            core_dtype_to_plugin(input_spec.dtype),
+        )?;
         inputs.insert(name.to_string(), tensor_data);
     }
 
@@ -489,7 +1405,7 @@ fn expand_path(path: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
     Ok(expanded)
 }
 
-fn parse_device(device_str: &str) -> Result<Device, Box<dyn std::error::Error>> {
+pub(crate) fn parse_device(device_str: &str) -> Result<Device, Box<dyn std::error::Error>> {
     // Validate device string doesn't contain dangerous characters
     if device_str.is_empty() {
         return Err("Device string cannot be empty".into());
@@ -562,11 +1478,11 @@ fn parse_device(device_str: &str) -> Result<Device, Box<dyn std::error::Error>>
     }
 }
 
-fn find_backend_plugin<'a>(
+pub(crate) fn find_backend_plugin<'a>(
     backend_name: &Option<String>,
     device: &Device,
     registry: &'a PluginRegistry,
-) -> Result<&'a crate::plugins::PluginEntry, Box<dyn std::error::Error>> {
+) -> Result<&'a PluginEntry, Box<dyn std::error::Error>> {
     if let Some(name) = backend_name {
         if let Some(plugin) = registry.find(name) {
             return Ok(plugin);
@@ -575,14 +1491,20 @@ fn find_backend_plugin<'a>(
         if let Some(plugin) = registry.find(&prefixed) {
             return Ok(plugin);
         }
-        return Err(format!("Backend '{}' not found.", name).into());
+        return Err(errors::classified(
+            ErrorClass::Plugin,
+            format!("Backend '{}' not found.", name),
+        ));
     }
 
     if let Some(plugin) = registry.find_backend_by_device(device) {
         return Ok(plugin);
     }
 
-    Err(friendly_backend_error(device, registry).into())
+    Err(errors::classified(
+        ErrorClass::Plugin,
+        friendly_backend_error(device, registry),
+    ))
 }
 
 fn friendly_format_error(extension: &str, registry: &PluginRegistry) -> String {
@@ -598,7 +1520,7 @@ fn friendly_format_error(extension: &str, registry: &PluginRegistry) -> String {
             ));
         }
     }
-    msg.push_str("\nBuiltin formats: .hdss");
+    msg.push_str("\nBuiltin formats: .hdss, .onnx");
     msg
 }
 
@@ -619,3 +1541,106 @@ fn friendly_backend_error(device: &Device, registry: &PluginRegistry) -> String
 
     msg
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use hodu_core::snapshot::{SnapshotInput, SnapshotTensorId};
+    use hodu_core::types::DType;
+    use hodu_plugin::rpc::{Request, Response, RunResult};
+    use hodu_plugin::PluginDType;
+    use hodu_plugin_runtime::PluginEndpoint;
+    use std::io::BufReader;
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        run: RunArgs,
+    }
+
+    fn scalar(value: f32) -> TensorData {
+        TensorData::new(value.to_le_bytes().to_vec(), vec![1], PluginDType::F32)
+    }
+
+    /// A backend over TCP that answers its first run only after `delay`, and later ones at once
+    fn backend_slow_once(delay: Duration) -> PluginEndpoint {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = PluginEndpoint::Tcp(listener.local_addr().unwrap().to_string());
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut runs = 0;
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                let request: Request = serde_json::from_str(&line).unwrap();
+                if request.method != methods::BACKEND_RUN {
+                    continue;
+                }
+                runs += 1;
+                if runs == 1 {
+                    std::thread::sleep(delay);
+                }
+                let result = RunResult {
+                    outputs: vec![TensorOutput::inline("y", &scalar(runs as f32))],
+                };
+                let response = Response::success(request.id, serde_json::to_value(result).unwrap());
+                writeln!(writer, "{}", serde_json::to_string(&response).unwrap()).unwrap();
+            }
+        });
+        endpoint
+    }
+
+    #[test]
+    fn test_keep_alive_runs_next_line_after_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("x.hdt");
+        save_tensor_data(&scalar(1.0), &input).unwrap();
+        let mut snapshot = Snapshot::new();
+        snapshot.inputs.push(SnapshotInput {
+            name: "x".to_string(),
+            id: SnapshotTensorId(0),
+            shape: Shape::new(&[1]),
+            dtype: DType::F32,
+        });
+        let save_dir = dir.path().join("outputs");
+        let args = Cli::parse_from([
+            "hodu",
+            "model.hdss",
+            "--keep-alive",
+            "--quiet",
+            "--save-format",
+            "hdt",
+            "--save",
+            save_dir.to_str().unwrap(),
+        ])
+        .run;
+        let runner = Runner::Library {
+            library_path: "model.so",
+            snapshot_path: "model.hdss",
+            device: "cpu",
+            precision: None,
+        };
+
+        // The first line times out; the answer to it arrives while the second line waits
+        let mut client = PluginClient::connect(&backend_slow_once(Duration::from_millis(300))).unwrap();
+        client.set_timeout(Duration::from_millis(200));
+        let line = format!("x={}\n", input.display());
+        run_keep_alive(
+            line.repeat(2).as_bytes(),
+            &mut client,
+            &runner,
+            "model",
+            &snapshot,
+            &HashMap::new(),
+            &HashMap::new(),
+            &args,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        let saved = load_tensor_data(save_dir.join("y.hdt")).unwrap();
+        assert_eq!(saved.data, 2.0f32.to_le_bytes());
+    }
+}
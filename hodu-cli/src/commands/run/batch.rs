@@ -0,0 +1,554 @@
+//! Batch inference for `hodu run --input-dir` and `--manifest`
+//!
+//! The backend loads the model once, into a session if it supports sessions, and runs the cases
+//! one at a time. Around it, `--jobs` threads load the inputs of the next cases and save the
+//! outputs of finished ones, with bounded queues between the stages so memory stays bounded
+//! however many cases there are.
+//!
+//! Cases come from a directory, with one subdirectory of `<input>.<ext>` files per case or, for a
+//! model with a single input, one file per case. A manifest lists one case per line as
+//! `{"id": "case", "inputs": {"x": "path"}}`, with paths relative to the manifest. Inputs given
+//! with `--input` are shared by every case.
+//!
+//! Each case's outputs are saved in the output directory as `<id>.hdta`, or `<id>/<output>.<ext>`
+//! with another `--save-format`, next to a `summary.json` report.
+
+use super::{
+    compile_cached, expand_path, load_model_snapshot, parse_inputs, plugin_supports, precision_params, run_in_process,
+    run_inputs, Interrupt, RunArgs, Runner,
+};
+use crate::errors::{self, ErrorClass};
+use crate::output;
+use crate::plugins::{PluginEntry, PluginManager, PluginRegistry};
+use crate::tensor::save_outputs;
+use crate::utils::path_to_str;
+use hodu_core::format::hdss;
+use hodu_core::snapshot::Snapshot;
+use hodu_plugin::rpc::methods;
+use hodu_plugin::{Device, TensorData};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex, PoisonError};
+use std::time::Instant;
+
+/// File name of the report written to the output directory
+const SUMMARY_FILE: &str = "summary.json";
+
+/// Backend name reported for snapshots run on the reference interpreter
+const INTERPRETER: &str = "interpreter";
+
+/// One sample to run
+struct Case {
+    id: String,
+    /// Input specs (`name=path`), after the shared ones so they take precedence
+    inputs: Vec<String>,
+}
+
+/// How a case went
+enum Status {
+    Ok {
+        latency_ms: f64,
+        output: PathBuf,
+    },
+    Failed(String),
+    /// Not run, because the batch was cancelled
+    Skipped,
+}
+
+type Outputs = Result<HashMap<String, TensorData>, String>;
+
+/// Run every case of `--input-dir` or `--manifest` and write the summary report
+#[allow(clippy::too_many_arguments)]
+pub(super) fn run(
+    args: &RunArgs,
+    registry: &PluginRegistry,
+    format_plugin: Option<&PluginEntry>,
+    device: &Device,
+    backend_plugin: &PluginEntry,
+    shared_inputs: &[String],
+    manager: &mut PluginManager,
+    interrupt: &Interrupt,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_dir = args
+        .output_dir
+        .as_ref()
+        .ok_or("--input-dir and --manifest need --output-dir")?;
+    if args.jobs == 0 {
+        return Err("--jobs must be at least 1".into());
+    }
+
+    let model_name = args
+        .model
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| args.model.display().to_string());
+    // The temp file keeps an imported ONNX model's snapshot alive until every case has run
+    let (snapshot_path, _onnx_snapshot) = load_model_snapshot(&args.model, format_plugin, &model_name, manager)?;
+    let (snapshot, weights) = hdss::load_lazy(&snapshot_path)?;
+
+    let cases = match (&args.input_dir, &args.manifest) {
+        (Some(dir), _) => cases_from_dir(dir, &snapshot, shared_inputs)?,
+        (None, Some(manifest)) => cases_from_manifest(manifest)?,
+        (None, None) => return Err("--output-dir needs --input-dir or --manifest".into()),
+    };
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Failed to create output directory '{}': {}", output_dir.display(), e))?;
+
+    // Custom ops are executed by the plugins declaring them, so such snapshots run in-process
+    let in_process = !snapshot.custom_op_names().is_empty();
+    let backend_name = if in_process {
+        INTERPRETER
+    } else {
+        backend_plugin.name.as_str()
+    };
+    if in_process {
+        output::warning(&format!(
+            "Backend '{}' cannot compile custom ops ({}); running on the in-process interpreter instead",
+            backend_plugin.name,
+            snapshot.custom_op_names().join(", ")
+        ));
+    }
+    let mut library_path = None;
+    let mut supports_sessions = false;
+    if !in_process {
+        supports_sessions = plugin_supports(manager, &backend_plugin.name, methods::BACKEND_LOAD_SESSION)?;
+        interrupt.set_handle(manager.get_cancellation_handle(&backend_plugin.name));
+        library_path = Some(compile_cached(
+            manager,
+            &backend_plugin.name,
+            &snapshot_path,
+            weights.as_ref(),
+            &model_name,
+            device,
+        )?);
+    }
+    let library_path = library_path.as_deref().map(path_to_str).transpose()?;
+    let snapshot_path = path_to_str(&snapshot_path)?;
+    let precision = precision_params(args);
+    let runner = match library_path {
+        None => None,
+        Some(library_path) if supports_sessions => {
+            output::loading(&format!("{} ({})", model_name, device));
+            let client = manager.get_plugin(&backend_plugin.name)?;
+            Some(Runner::Session(client.load_session(
+                library_path,
+                snapshot_path,
+                device,
+                precision,
+            )?))
+        },
+        Some(library_path) => {
+            output::warning(&format!(
+                "Backend '{}' does not support sessions; the model is reloaded for every case",
+                backend_plugin.name
+            ));
+            Some(Runner::Library {
+                library_path,
+                snapshot_path,
+                device,
+                precision,
+            })
+        },
+    };
+
+    if !args.quiet {
+        output::running(&format!(
+            "{} ({} cases, {}, {} jobs)",
+            model_name,
+            cases.len(),
+            device,
+            args.jobs
+        ));
+    }
+    let start = Instant::now();
+    let dumps = HashMap::new();
+    let run_case = |inputs: &HashMap<String, TensorData>| match &runner {
+        Some(runner) => run_inputs(manager.get_plugin(&backend_plugin.name)?, runner, inputs, &dumps),
+        None => {
+            let (outputs, _) = run_in_process(
+                &snapshot,
+                weights.as_ref(),
+                inputs,
+                &dumps,
+                false,
+                device,
+                registry,
+                manager,
+            )?;
+            Ok(outputs)
+        },
+    };
+    let statuses = run_cases(&cases, &snapshot, output_dir, args, run_case, &interrupt.cancelled);
+    let elapsed = start.elapsed().as_secs_f64();
+    output::clear_progress();
+
+    if let Some(Runner::Session(session_id)) = &runner {
+        if let Ok(client) = manager.get_plugin(&backend_plugin.name) {
+            if let Err(e) = client.close_session(session_id) {
+                output::warning(&format!("Failed to close session: {}", e));
+            }
+        }
+    }
+
+    let summary = summary_json(&cases, &statuses, &model_name, backend_name, device, elapsed);
+    let summary_path = output_dir.join(SUMMARY_FILE);
+    std::fs::write(&summary_path, serde_json::to_string_pretty(&summary)?)
+        .map_err(|e| format!("Failed to write '{}': {}", summary_path.display(), e))?;
+    if args.format == "json" && !args.quiet {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    }
+
+    if interrupt.cancelled.load(Ordering::SeqCst) {
+        return Err(errors::classified(ErrorClass::Cancelled, "Operation cancelled by user"));
+    }
+    let failed = statuses
+        .iter()
+        .filter(|status| matches!(status, Status::Failed(_)))
+        .count();
+    if failed > 0 {
+        return Err(errors::classified(
+            ErrorClass::CheckFailed,
+            format!(
+                "{} of {} cases failed (see {})",
+                failed,
+                cases.len(),
+                summary_path.display()
+            ),
+        ));
+    }
+    if !args.quiet {
+        let latencies: Vec<f64> = statuses
+            .iter()
+            .filter_map(|status| match status {
+                Status::Ok { latency_ms, .. } => Some(*latency_ms),
+                _ => None,
+            })
+            .collect();
+        let mean = latencies.iter().sum::<f64>() / latencies.len().max(1) as f64;
+        output::finished(&format!(
+            "{} cases in {} ({:.2} ms mean latency), outputs in {}",
+            cases.len(),
+            output::format_duration(elapsed),
+            mean,
+            output_dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Run the cases on `run_case`, loading and saving them on `--jobs` threads
+///
+/// Stops starting new cases once `cancelled` is set; those are reported as skipped.
+fn run_cases(
+    cases: &[Case],
+    snapshot: &Snapshot,
+    output_dir: &Path,
+    args: &RunArgs,
+    mut run_case: impl FnMut(
+        &HashMap<String, TensorData>,
+    ) -> Result<HashMap<String, TensorData>, Box<dyn std::error::Error>>,
+    cancelled: &AtomicBool,
+) -> Vec<Status> {
+    let statuses: Mutex<Vec<Status>> = Mutex::new(cases.iter().map(|_| Status::Skipped).collect());
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    let (loaded_tx, loaded_rx) = mpsc::sync_channel::<(usize, Outputs)>(args.jobs);
+    let (ran_tx, ran_rx) = mpsc::sync_channel::<(usize, Outputs, f64)>(args.jobs);
+    let ran_rx = Mutex::new(ran_rx);
+
+    let record = |index: usize, status: Status| {
+        if let Status::Failed(e) = &status {
+            failed.fetch_add(1, Ordering::SeqCst);
+            if !args.quiet {
+                output::error(&format!("{}: {}", cases[index].id, e));
+            }
+        }
+        statuses.lock().unwrap_or_else(PoisonError::into_inner)[index] = status;
+        let finished = finished.fetch_add(1, Ordering::SeqCst) + 1;
+        if !args.quiet {
+            output::draw_progress(&[output::ProgressBar {
+                label: "Running",
+                percent: Some((finished * 100 / cases.len()) as u8),
+                detail: format!(
+                    "{}/{} cases, {} failed",
+                    finished,
+                    cases.len(),
+                    failed.load(Ordering::SeqCst)
+                ),
+            }]);
+        }
+    };
+
+    std::thread::scope(|scope| {
+        for _ in 0..args.jobs {
+            // Loader: reads the inputs of the next case not taken yet
+            let loaded_tx = loaded_tx.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                if index >= cases.len() || cancelled.load(Ordering::SeqCst) {
+                    break;
+                }
+                let inputs = parse_inputs(&cases[index].inputs, snapshot).map_err(|e| e.to_string());
+                if loaded_tx.send((index, inputs)).is_err() {
+                    break;
+                }
+            });
+
+            // Saver: writes the outputs of finished cases
+            let ran_rx = &ran_rx;
+            let record = &record;
+            scope.spawn(move || loop {
+                let job = ran_rx.lock().unwrap_or_else(PoisonError::into_inner).recv();
+                let Ok((index, outputs, latency_ms)) = job else {
+                    break;
+                };
+                let status = outputs
+                    .and_then(|outputs| save_case(&outputs, &cases[index].id, output_dir, &args.save_format))
+                    .map_or_else(Status::Failed, |output| Status::Ok { latency_ms, output });
+                record(index, status);
+            });
+        }
+        drop(loaded_tx);
+
+        // The backend runs one case at a time, in the order the loaders finish them
+        for (index, inputs) in loaded_rx.iter() {
+            let outputs = match inputs {
+                Ok(inputs) => {
+                    let start = Instant::now();
+                    let outputs = run_case(&inputs).map_err(|e| e.to_string());
+                    (outputs, start.elapsed().as_secs_f64() * 1000.0)
+                },
+                Err(e) => (Err(e), 0.0),
+            };
+            if cancelled.load(Ordering::SeqCst) {
+                break;
+            }
+            if ran_tx.send((index, outputs.0, outputs.1)).is_err() {
+                break;
+            }
+        }
+        // Loaders blocked on a full queue give up once the receiver is gone
+        drop(loaded_rx);
+        drop(ran_tx);
+    });
+
+    statuses.into_inner().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Save a case's outputs, returning where they went
+fn save_case(
+    outputs: &HashMap<String, TensorData>,
+    id: &str,
+    output_dir: &Path,
+    save_format: &str,
+) -> Result<PathBuf, String> {
+    let path = if save_format.eq_ignore_ascii_case("hdta") {
+        output_dir.join(format!("{}.hdta", id))
+    } else {
+        output_dir.join(id)
+    };
+    save_outputs(outputs, &path, save_format).map_err(|e| format!("Failed to save outputs: {}", e))?;
+    Ok(path)
+}
+
+/// Cases of an input directory: one per subdirectory, or one per file for a single free input
+fn cases_from_dir(
+    dir: &Path,
+    snapshot: &Snapshot,
+    shared_inputs: &[String],
+) -> Result<Vec<Case>, Box<dyn std::error::Error>> {
+    let entries = visible_entries(dir)?;
+    let case_dirs: Vec<&PathBuf> = entries.iter().filter(|path| path.is_dir()).collect();
+    let input_names: HashSet<&str> = snapshot.inputs.iter().map(|input| input.name.as_str()).collect();
+
+    let mut cases = Vec::new();
+    if !case_dirs.is_empty() {
+        for case_dir in case_dirs {
+            let mut inputs = shared_inputs.to_vec();
+            for file in visible_entries(case_dir)?.into_iter().filter(|path| path.is_file()) {
+                let stem = file.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+                if let Some(name) = stem.filter(|stem| input_names.contains(stem.as_str())) {
+                    inputs.push(format!("{}={}", name, file.display()));
+                }
+            }
+            cases.push(Case {
+                id: file_name(case_dir),
+                inputs,
+            });
+        }
+    } else {
+        let shared: HashSet<&str> = shared_inputs
+            .iter()
+            .filter_map(|spec| spec.split_once('=').map(|(name, _)| name))
+            .collect();
+        let free: Vec<&str> = snapshot
+            .inputs
+            .iter()
+            .map(|input| input.name.as_str())
+            .filter(|name| !shared.contains(name))
+            .collect();
+        let [name] = free.as_slice() else {
+            return Err(format!(
+                "{} holds files only, which needs a model with one input not given by --input (found: {:?}); put each case's inputs in its own subdirectory",
+                dir.display(),
+                free
+            )
+            .into());
+        };
+        for file in entries.iter().filter(|path| path.is_file()) {
+            let mut inputs = shared_inputs.to_vec();
+            inputs.push(format!("{}={}", name, file.display()));
+            let id = file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| file_name(file));
+            cases.push(Case { id, inputs });
+        }
+    }
+
+    if cases.is_empty() {
+        return Err(format!("No cases found in {}", dir.display()).into());
+    }
+    check_unique_ids(&cases)?;
+    Ok(cases)
+}
+
+/// Cases of a JSON lines manifest, skipping blank lines and `#` comments
+fn cases_from_manifest(manifest: &Path) -> Result<Vec<Case>, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(manifest)
+        .map_err(|e| format!("Failed to read manifest '{}': {}", manifest.display(), e))?;
+    let base = manifest.parent().unwrap_or(Path::new(""));
+
+    let mut cases = Vec::new();
+    for (number, line) in content.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |reason: &str| format!("{}:{}: {}", manifest.display(), number, reason);
+        let entry: serde_json::Value = serde_json::from_str(line).map_err(|e| invalid(&e.to_string()))?;
+        let id = match entry.get("id") {
+            Some(serde_json::Value::String(id)) => id.clone(),
+            Some(serde_json::Value::Number(id)) => id.to_string(),
+            Some(_) => return Err(invalid("\"id\" must be a string or a number").into()),
+            None => format!("{:04}", cases.len()),
+        };
+        if id.is_empty() || id.contains(['/', '\\', '\0']) || id == "." || id == ".." {
+            return Err(invalid(&format!("invalid case id '{}'", id)).into());
+        }
+        let inputs = entry
+            .get("inputs")
+            .and_then(|inputs| inputs.as_object())
+            .ok_or_else(|| invalid("expected {\"id\": ..., \"inputs\": {name: path}}"))?;
+        let mut specs = Vec::with_capacity(inputs.len());
+        for (name, path) in inputs {
+            let path = path
+                .as_str()
+                .ok_or_else(|| invalid(&format!("input '{}' must be a path", name)))?;
+            let resolved = if Path::new(path).is_absolute() || path.starts_with("~/") {
+                PathBuf::from(path)
+            } else {
+                base.join(path)
+            };
+            specs.push(format!("{}={}", name, resolved.display()));
+        }
+        cases.push(Case { id, inputs: specs });
+    }
+
+    if cases.is_empty() {
+        return Err(format!("No cases found in {}", manifest.display()).into());
+    }
+    check_unique_ids(&cases)?;
+    Ok(cases)
+}
+
+/// Entries of `dir` not starting with a dot, sorted by name
+fn visible_entries(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let dir = expand_path(&dir.to_string_lossy())?;
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(&dir).map_err(|e| format!("Failed to read '{}': {}", dir.display(), e))? {
+        let path = entry?.path();
+        if !file_name(&path).starts_with('.') {
+            entries.push(path);
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+/// Case ids name their output files, so they must be unique
+fn check_unique_ids(cases: &[Case]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut seen = HashSet::new();
+    for case in cases {
+        if !seen.insert(case.id.as_str()) {
+            return Err(format!("Duplicate case '{}'", case.id).into());
+        }
+    }
+    Ok(())
+}
+
+fn summary_json(
+    cases: &[Case],
+    statuses: &[Status],
+    model: &str,
+    backend: &str,
+    device: &Device,
+    elapsed: f64,
+) -> serde_json::Value {
+    let latencies: Vec<f64> = statuses
+        .iter()
+        .filter_map(|status| match status {
+            Status::Ok { latency_ms, .. } => Some(*latency_ms),
+            _ => None,
+        })
+        .collect();
+    let latency = (!latencies.is_empty()).then(|| {
+        serde_json::json!({
+            "mean": latencies.iter().sum::<f64>() / latencies.len() as f64,
+            "min": latencies.iter().copied().fold(f64::INFINITY, f64::min),
+            "max": latencies.iter().copied().fold(0.0, f64::max),
+        })
+    });
+    let count = |matches: fn(&Status) -> bool| statuses.iter().filter(|status| matches(status)).count();
+
+    let results: Vec<_> = cases
+        .iter()
+        .zip(statuses)
+        .map(|(case, status)| match status {
+            Status::Ok { latency_ms, output } => serde_json::json!({
+                "id": case.id,
+                "status": "ok",
+                "latency_ms": latency_ms,
+                "output": output.display().to_string(),
+            }),
+            Status::Failed(error) => serde_json::json!({
+                "id": case.id,
+                "status": "failed",
+                "error": error,
+            }),
+            Status::Skipped => serde_json::json!({
+                "id": case.id,
+                "status": "skipped",
+            }),
+        })
+        .collect();
+
+    serde_json::json!({
+        "model": model,
+        "backend": backend,
+        "device": device,
+        "cases": cases.len(),
+        "succeeded": count(|status| matches!(status, Status::Ok { .. })),
+        "failed": count(|status| matches!(status, Status::Failed(_))),
+        "skipped": count(|status| matches!(status, Status::Skipped)),
+        "elapsed_secs": elapsed,
+        "latency_ms": latency,
+        "results": results,
+    })
+}
@@ -0,0 +1,151 @@
+//! Rendering of `hodu run --profile` results
+//!
+//! Profiles from backend plugins (`backend.profile`) and from the in-process interpreter share the
+//! `ProfileResult` schema, so both are printed as the same per-op table and written as the same
+//! chrome://tracing JSON.
+
+use crate::output;
+use hodu_core::profiler::Profile;
+use hodu_plugin::rpc::{ProfileResult, ProfiledAlloc, ProfiledOp};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Rows of the per-op table; the rest are summed up in one line
+const MAX_ROWS: usize = 20;
+
+/// Convert a profile recorded by the interpreter to the `backend.profile` schema
+pub(super) fn from_core(profile: &Profile) -> ProfileResult {
+    let ops = profile
+        .ops
+        .iter()
+        .map(|op| {
+            let mut args: std::collections::BTreeMap<String, String> = op.args.iter().cloned().collect();
+            let node = args.remove("node").and_then(|node| node.parse().ok());
+            ProfiledOp {
+                name: op.name.clone(),
+                node,
+                kernel: None,
+                device: op.device.to_string(),
+                start_us: op.start_us,
+                duration_us: op.duration_us,
+                allocated_bytes: op.allocated_bytes as u64,
+                args,
+            }
+        })
+        .collect();
+    let allocs = profile
+        .allocs
+        .iter()
+        .map(|alloc| ProfiledAlloc {
+            device: alloc.device.to_string(),
+            time_us: alloc.time_us,
+            bytes: alloc.bytes as u64,
+            cached: alloc.cached,
+        })
+        .collect();
+    ProfileResult {
+        outputs: Vec::new(),
+        ops,
+        allocs,
+        peak_memory_bytes: None,
+    }
+}
+
+/// Write the profile as chrome://tracing JSON to `path`, and print its per-op table unless quiet
+pub(super) fn report(result: &ProfileResult, path: &Path, quiet: bool) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, result.to_chrome_trace())
+        .map_err(|e| format!("Failed to write profile to '{}': {}", path.display(), e))?;
+    if !quiet {
+        print_table(result);
+        output::info(&format!("wrote profile to {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Time and memory of all ops sharing a name (or kernel)
+struct OpSummary<'a> {
+    name: &'a str,
+    count: usize,
+    total_us: f64,
+    allocated_bytes: u64,
+}
+
+/// Print ops grouped by kernel (or op name when the backend reports no kernels), slowest first
+///
+/// The table goes to stderr like other status output, so stdout keeps only the outputs.
+fn print_table(result: &ProfileResult) {
+    let mut summaries: Vec<OpSummary<'_>> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for op in &result.ops {
+        let name = op.kernel.as_deref().unwrap_or(&op.name);
+        let i = *index.entry(name).or_insert_with(|| {
+            summaries.push(OpSummary {
+                name,
+                count: 0,
+                total_us: 0.0,
+                allocated_bytes: 0,
+            });
+            summaries.len() - 1
+        });
+        let summary = &mut summaries[i];
+        summary.count += 1;
+        summary.total_us += op.duration_us;
+        summary.allocated_bytes += op.allocated_bytes;
+    }
+    summaries.sort_by(|a, b| b.total_us.total_cmp(&a.total_us));
+    let total_us: f64 = summaries.iter().map(|summary| summary.total_us).sum();
+
+    eprintln!();
+    eprintln!(
+        "{:<28} {:>6} {:>12} {:>12} {:>7} {:>12}",
+        "OP", "CALLS", "TOTAL", "MEAN", "TIME%", "ALLOCATED"
+    );
+    for summary in summaries.iter().take(MAX_ROWS) {
+        eprintln!(
+            "{:<28} {:>6} {:>12} {:>12} {:>6.1}% {:>12}",
+            output::sanitize_for_terminal(summary.name),
+            summary.count,
+            format_us(summary.total_us),
+            format_us(summary.total_us / summary.count as f64),
+            percent(summary.total_us, total_us),
+            output::format_size(summary.allocated_bytes as usize)
+        );
+    }
+    if summaries.len() > MAX_ROWS {
+        let rest = &summaries[MAX_ROWS..];
+        let rest_us: f64 = rest.iter().map(|summary| summary.total_us).sum();
+        eprintln!(
+            "{:<28} {:>6} {:>12} {:>12} {:>6.1}%",
+            format!("({} more)", rest.len()),
+            rest.iter().map(|summary| summary.count).sum::<usize>(),
+            format_us(rest_us),
+            "",
+            percent(rest_us, total_us)
+        );
+    }
+    eprintln!();
+    eprintln!("{} ops, {} total", result.ops.len(), format_us(total_us));
+    if let Some(bytes) = result.peak_memory_bytes {
+        eprintln!("peak memory {}", output::format_size(bytes as usize));
+    }
+    eprintln!();
+}
+
+fn percent(part: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        part / total * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Format microseconds with a unit that keeps a few significant digits
+fn format_us(us: f64) -> String {
+    if us < 1000.0 {
+        format!("{:.1}us", us)
+    } else if us < 1_000_000.0 {
+        format!("{:.2}ms", us / 1000.0)
+    } else {
+        format!("{:.2}s", us / 1_000_000.0)
+    }
+}
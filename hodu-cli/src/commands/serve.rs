@@ -0,0 +1,540 @@
+//! Serve command - keep a model loaded and run it for HTTP requests
+//!
+//! The backend plugin loads the model once, into a session if it supports sessions, and a single
+//! worker runs requests on it in arrival order. Connections are handled by a pool of threads that
+//! hand runs to the worker through a bounded queue, so a burst of requests waits instead of
+//! loading the plugin more than once, and requests beyond the queue are turned away with 503.
+//! A backend that crashes fails the run it was on, and is started again for the next one.
+//!
+//! Endpoints:
+//! - `GET /health`: liveness and the number of queued runs
+//! - `GET /v1/model`: the model's inputs and outputs
+//! - `POST /v1/run`: run the model on `{"inputs": {name: {"shape", "dtype", "data"}}}`; with
+//!   `"stream": true` the reply is a server-sent event stream of the items the backend streams,
+//!   ending with an `outputs` or `error` event
+
+use super::run::{
+    compile_cached, find_backend_plugin, find_model_format_plugin, load_model_snapshot, parse_device, plugin_supports,
+    run_inputs, Runner,
+};
+use crate::config::Settings;
+use crate::output;
+use crate::plugins::{load_registry, PluginManager};
+use crate::utils::{core_dtype_to_plugin, path_to_str, plugin_dtype_to_core};
+use clap::Args;
+use hodu_core::format::{hdss, json};
+use hodu_core::snapshot::Snapshot;
+use hodu_core::tensor::Tensor;
+use hodu_core::types::{Device as CoreDevice, Shape};
+use hodu_plugin::rpc::{methods, PrecisionParams, StreamEvent};
+use hodu_plugin::TensorData;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Largest request body accepted (256MB)
+const MAX_BODY_BYTES: u64 = 256 * 1024 * 1024;
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Model file (.onnx, .hdss, etc.)
+    pub model: PathBuf,
+
+    /// Execution device (cpu, metal, cuda::0); default: `device` in ~/.hodu/config.toml, else cpu
+    #[arg(short, long)]
+    pub device: Option<String>,
+
+    /// Backend plugin to use (default: `backend` in ~/.hodu/config.toml, else auto-select)
+    #[arg(long)]
+    pub backend: Option<String>,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// Port to listen on
+    #[arg(short, long, default_value_t = 8080)]
+    pub port: u16,
+
+    /// Runs that may wait for the model before further requests are turned away with 503
+    #[arg(long, default_value_t = 64)]
+    pub queue_size: usize,
+
+    /// Threads handling connections
+    #[arg(long, default_value_t = 8)]
+    pub threads: usize,
+
+    /// Timeout in seconds for plugin operations (default: 300)
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Override a plugin config value from ~/.hodu/config.toml, can be repeated
+    #[arg(long = "plugin-config", value_name = "PLUGIN.KEY=VALUE")]
+    pub plugin_config: Vec<String>,
+
+    /// Let f32 matmul and convolution run on TF32 tensor cores
+    #[arg(long)]
+    pub allow_tf32: bool,
+
+    /// Let f16/bf16 matmul and convolution accumulate in 16 bits instead of f32
+    #[arg(long)]
+    pub f16_accumulate: bool,
+
+    /// Do not log requests
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+/// Work for the worker holding the model
+enum Job {
+    Run(RunJob),
+    /// Stop after Ctrl+C
+    Shutdown,
+}
+
+struct RunJob {
+    inputs: HashMap<String, TensorData>,
+    /// Receives the items the backend streams while the job runs
+    stream: Option<Sender<String>>,
+    reply: Sender<Result<serde_json::Value, String>>,
+}
+
+/// State shared by the connection threads
+struct Shared {
+    jobs: SyncSender<Job>,
+    /// Runs waiting in the queue or running
+    queued: AtomicUsize,
+    stopping: AtomicBool,
+    /// Response of `GET /v1/model`
+    model_info: serde_json::Value,
+    snapshot: Snapshot,
+    quiet: bool,
+}
+
+pub fn execute(args: ServeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.queue_size == 0 || args.threads == 0 {
+        return Err("--queue-size and --threads must be at least 1".into());
+    }
+
+    let extension = args
+        .model
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    let registry = load_registry()?;
+    let format_plugin = find_model_format_plugin(extension.as_deref(), &registry)?;
+    let settings = Settings::load()?;
+    let device = parse_device(&settings.device(args.device.as_deref()))?;
+    let backend_plugin = find_backend_plugin(&settings.backend(args.backend.as_deref()), &device, &registry)?;
+
+    let mut manager = match args.timeout {
+        Some(secs) => PluginManager::with_timeout(secs)?,
+        None => PluginManager::new()?,
+    };
+    manager.add_config_overrides(&args.plugin_config)?;
+    manager.allow_read(&args.model);
+
+    let model_name = args
+        .model
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| args.model.display().to_string());
+    // The temp file keeps an imported ONNX model's snapshot alive while serving
+    let (snapshot_path, _onnx_snapshot) = load_model_snapshot(&args.model, format_plugin, &model_name, &mut manager)?;
+    let (snapshot, weights) = hdss::load_lazy(&snapshot_path)?;
+    if !snapshot.custom_op_names().is_empty() {
+        return Err("Models with custom ops run in-process and cannot be served; use `hodu run`".into());
+    }
+
+    let supports_sessions = plugin_supports(&mut manager, &backend_plugin.name, methods::BACKEND_LOAD_SESSION)?;
+    let library_path = compile_cached(
+        &mut manager,
+        &backend_plugin.name,
+        &snapshot_path,
+        weights.as_ref(),
+        &model_name,
+        &device,
+    )?;
+    let library_path = path_to_str(&library_path)?;
+    let snapshot_path = path_to_str(&snapshot_path)?;
+    let precision = precision_params(&args);
+    if !supports_sessions {
+        output::warning(&format!(
+            "Backend '{}' does not support sessions; the model is reloaded on every run",
+            backend_plugin.name
+        ));
+    }
+
+    // Items the backend streams go to the request being run, if it asked for them
+    let stream_target: Arc<Mutex<Option<Sender<String>>>> = Arc::new(Mutex::new(None));
+    let backend = Backend {
+        name: &backend_plugin.name,
+        library_path,
+        snapshot_path,
+        device: &device,
+        precision,
+        supports_sessions,
+        stream_target: &stream_target,
+    };
+    if supports_sessions {
+        output::loading(&format!("{} ({})", model_name, device));
+    }
+    let mut runner = Some(backend.start(&mut manager)?);
+
+    let address = format!("{}:{}", args.host, args.port);
+    let server = Arc::new(Server::http(&address).map_err(|e| format!("Failed to listen on {}: {}", address, e))?);
+    let (jobs, job_queue) = mpsc::sync_channel(args.queue_size);
+    let shared = Arc::new(Shared {
+        jobs,
+        queued: AtomicUsize::new(0),
+        stopping: AtomicBool::new(false),
+        model_info: model_info(&snapshot, &model_name, &backend_plugin.name, &device),
+        snapshot,
+        quiet: args.quiet,
+    });
+
+    for _ in 0..args.threads {
+        let server = Arc::clone(&server);
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || {
+            while let Ok(request) = server.recv() {
+                handle_request(request, &shared);
+            }
+        });
+    }
+
+    // Queued runs are answered with an error, the running one finishes, then the session is closed
+    let stop = Arc::clone(&shared);
+    if let Err(e) = ctrlc::set_handler(move || {
+        if !stop.stopping.swap(true, Ordering::SeqCst) {
+            eprintln!("\nShutting down...");
+            let _ = stop.jobs.send(Job::Shutdown);
+        }
+    }) {
+        output::warning(&format!("Failed to set Ctrl+C handler: {}", e));
+    }
+
+    output::info(&format!(
+        "serving {} on http://{} ({}, {})",
+        model_name, address, backend_plugin.name, device
+    ));
+    for job in job_queue {
+        let job = match job {
+            Job::Run(job) => job,
+            Job::Shutdown => break,
+        };
+        shared.queued.fetch_sub(1, Ordering::SeqCst);
+        if shared.stopping.load(Ordering::SeqCst) {
+            let _ = job.reply.send(Err("Server is shutting down".to_string()));
+            continue;
+        }
+
+        if let Ok(mut target) = stream_target.lock() {
+            *target = job.stream;
+        }
+        let start = Instant::now();
+        let result = match &runner {
+            Some(runner) => Ok(runner),
+            None => backend.start(&mut manager).map(|started| &*runner.insert(started)),
+        }
+        .and_then(|runner| run_inputs(manager.get_plugin(backend.name)?, runner, &job.inputs, &HashMap::new()))
+        .and_then(|outputs| outputs_to_json(&outputs, start.elapsed().as_secs_f64()))
+        .map_err(|e| e.to_string());
+        // A backend that crashed is started again, and the model reloaded, for the next run
+        if result.is_err()
+            && manager
+                .get_plugin(backend.name)
+                .map_or(true, |client| client.is_closed())
+        {
+            output::warning(&format!("Backend '{}' exited; restarting it", backend.name));
+            let _ = manager.shutdown_plugin(backend.name);
+            runner = None;
+        }
+        // Dropping the stream sender ends the request's event stream before its reply is sent
+        if let Ok(mut target) = stream_target.lock() {
+            *target = None;
+        }
+        let _ = job.reply.send(result);
+    }
+
+    if let Some(Runner::Session(session_id)) = &runner {
+        if let Err(e) = manager.get_plugin(backend.name)?.close_session(session_id) {
+            output::warning(&format!("Failed to close session: {}", e));
+        }
+    }
+    Ok(())
+}
+
+/// The backend plugin a server runs its model on
+struct Backend<'a> {
+    name: &'a str,
+    library_path: &'a str,
+    snapshot_path: &'a str,
+    device: &'a str,
+    precision: Option<PrecisionParams>,
+    supports_sessions: bool,
+    stream_target: &'a Arc<Mutex<Option<Sender<String>>>>,
+}
+
+impl<'a> Backend<'a> {
+    /// Start the plugin, or reuse its running process, and load the model into a session if it can
+    fn start(&self, manager: &mut PluginManager) -> Result<Runner<'a>, Box<dyn std::error::Error>> {
+        let client = manager.get_plugin(self.name)?;
+        let target = Arc::clone(self.stream_target);
+        client.set_stream_handler(Box::new(move |params| {
+            let StreamEvent::Chunk { index, data } = &params.event else {
+                return;
+            };
+            if let Some(sender) = target.lock().ok().as_ref().and_then(|t| t.as_ref()) {
+                let item = serde_json::json!({ "stream": params.stream, "index": index, "data": data });
+                let _ = sender.send(item.to_string());
+            }
+        }));
+        if !self.supports_sessions {
+            return Ok(Runner::Library {
+                library_path: self.library_path,
+                snapshot_path: self.snapshot_path,
+                device: self.device,
+                precision: self.precision,
+            });
+        }
+        let session_id = client.load_session(self.library_path, self.snapshot_path, self.device, self.precision)?;
+        Ok(Runner::Session(session_id))
+    }
+}
+
+/// Precision overrides requested on the command line, if any
+fn precision_params(args: &ServeArgs) -> Option<PrecisionParams> {
+    (args.allow_tf32 || args.f16_accumulate).then(|| PrecisionParams {
+        allow_tf32: args.allow_tf32.then_some(true),
+        f16_accumulate_f32: args.f16_accumulate.then_some(false),
+    })
+}
+
+fn model_info(snapshot: &Snapshot, model_name: &str, backend: &str, device: &str) -> serde_json::Value {
+    let inputs: Vec<_> = snapshot
+        .inputs
+        .iter()
+        .map(|input| {
+            serde_json::json!({
+                "name": input.name,
+                "shape": input.shape.dims(),
+                "dtype": core_dtype_to_plugin(input.dtype).name(),
+            })
+        })
+        .collect();
+    let outputs: Vec<_> = snapshot.targets.iter().map(|target| &target.name).collect();
+    serde_json::json!({
+        "model": model_name,
+        "backend": backend,
+        "device": device,
+        "inputs": inputs,
+        "outputs": outputs,
+    })
+}
+
+fn handle_request(mut request: Request, shared: &Shared) {
+    let start = Instant::now();
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let path = url.split('?').next().unwrap_or_default();
+
+    let status = match (&method, path) {
+        (Method::Get, "/health") => {
+            let body = serde_json::json!({
+                "status": if shared.stopping.load(Ordering::SeqCst) { "stopping" } else { "ok" },
+                "queued": shared.queued.load(Ordering::SeqCst),
+            });
+            respond_json(request, 200, &body)
+        },
+        (Method::Get, "/v1/model") => respond_json(request, 200, &shared.model_info),
+        (Method::Post, "/v1/run") => match parse_run_request(&mut request, &shared.snapshot) {
+            Ok((inputs, stream)) => run(request, shared, inputs, stream),
+            Err(e) => respond_error(request, 400, &e),
+        },
+        (_, "/health" | "/v1/model" | "/v1/run") => respond_error(request, 405, "Method not allowed"),
+        _ => respond_error(request, 404, &format!("No endpoint at {}", path)),
+    };
+
+    if !shared.quiet {
+        output::info(&format!(
+            "{} {} {} in {}",
+            method,
+            url,
+            status,
+            output::format_duration(start.elapsed().as_secs_f64())
+        ));
+    }
+}
+
+/// Queue a run and send its outputs, or its stream of events
+fn run(request: Request, shared: &Shared, inputs: HashMap<String, TensorData>, stream: bool) -> u16 {
+    let (reply, reply_rx) = mpsc::channel();
+    let (stream_tx, stream_rx) = if stream {
+        let (tx, rx) = mpsc::channel();
+        (Some(tx), Some(rx))
+    } else {
+        (None, None)
+    };
+
+    shared.queued.fetch_add(1, Ordering::SeqCst);
+    let job = Job::Run(RunJob {
+        inputs,
+        stream: stream_tx,
+        reply,
+    });
+    if let Err(e) = shared.jobs.try_send(job) {
+        shared.queued.fetch_sub(1, Ordering::SeqCst);
+        let message = match e {
+            TrySendError::Full(_) => "Too many queued requests, try again later",
+            TrySendError::Disconnected(_) => "Server is shutting down",
+        };
+        return respond_error(request, 503, message);
+    }
+
+    match stream_rx {
+        Some(stream_rx) => stream_events(request, stream_rx, reply_rx),
+        None => match reply_rx.recv() {
+            Ok(Ok(body)) => respond_json(request, 200, &body),
+            Ok(Err(e)) => respond_error(request, 500, &e),
+            Err(_) => respond_error(request, 503, "Server is shutting down"),
+        },
+    }
+}
+
+/// Send a run as server-sent events: one `data` event per streamed item, then `outputs` or `error`
+///
+/// Each event is written to the connection as its own chunk as soon as it arrives.
+fn stream_events(
+    request: Request,
+    stream_rx: Receiver<String>,
+    reply_rx: Receiver<Result<serde_json::Value, String>>,
+) -> u16 {
+    let mut writer = request.into_writer();
+    let head =
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nTransfer-Encoding: chunked\r\n\r\n";
+    // A client that disconnects mid-stream only loses its own events
+    let _ = writer.write_all(head.as_bytes()).and_then(|_| {
+        // The worker drops the sender when the run ends
+        for item in stream_rx.iter() {
+            write_event(&mut writer, None, &item)?;
+        }
+        let (event, data) = match reply_rx.recv() {
+            Ok(Ok(body)) => ("outputs", body),
+            Ok(Err(e)) => ("error", serde_json::json!({ "error": e })),
+            Err(_) => ("error", serde_json::json!({ "error": "Server is shutting down" })),
+        };
+        write_event(&mut writer, Some(event), &data.to_string())?;
+        // The empty chunk ends the response
+        writer.write_all(b"0\r\n\r\n")?;
+        writer.flush()
+    });
+    200
+}
+
+/// Write one server-sent event as a chunk of a chunked response
+fn write_event(writer: &mut dyn Write, event: Option<&str>, data: &str) -> std::io::Result<()> {
+    let mut text = String::new();
+    if let Some(event) = event {
+        text.push_str(&format!("event: {}\n", event));
+    }
+    text.push_str(&format!("data: {}\n\n", data));
+    write!(writer, "{:x}\r\n{}\r\n", text.len(), text)?;
+    writer.flush()
+}
+
+/// Parse the body of `POST /v1/run` into input tensors checked against the model, and whether to stream
+fn parse_run_request(
+    request: &mut Request,
+    snapshot: &Snapshot,
+) -> Result<(HashMap<String, TensorData>, bool), String> {
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| format!("Failed to read request body: {}", e))?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err(format!("Request body exceeds {} bytes", MAX_BODY_BYTES));
+    }
+    let body: serde_json::Value = serde_json::from_slice(&body).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let stream = body.get("stream").and_then(|s| s.as_bool()).unwrap_or(false);
+    let tensors = body
+        .get("inputs")
+        .and_then(|i| i.as_object())
+        .ok_or("Expected {\"inputs\": {name: {\"shape\", \"dtype\", \"data\"}}}")?;
+
+    let mut inputs = HashMap::new();
+    for (name, value) in tensors {
+        let spec = snapshot.inputs.iter().find(|i| &i.name == name).ok_or_else(|| {
+            format!(
+                "Unknown input '{}'. Available: {:?}",
+                name,
+                snapshot.inputs.iter().map(|i| &i.name).collect::<Vec<_>>()
+            )
+        })?;
+        let tensor = json::json_to_tensor(value).map_err(|e| format!("Invalid input '{}': {}", name, e))?;
+        if tensor.shape().dims() != spec.shape.dims() {
+            return Err(format!(
+                "Shape mismatch for '{}': got {:?}, model expects {:?}",
+                name,
+                tensor.shape().dims(),
+                spec.shape.dims()
+            ));
+        }
+        if tensor.dtype() != spec.dtype {
+            return Err(format!(
+                "DType mismatch for '{}': got {}, model expects {}",
+                name,
+                core_dtype_to_plugin(tensor.dtype()).name(),
+                core_dtype_to_plugin(spec.dtype).name()
+            ));
+        }
+        let data = tensor
+            .to_bytes()
+            .map_err(|e| format!("Invalid input '{}': {}", name, e))?;
+        let shape = tensor.shape().dims().to_vec();
+        inputs.insert(
+            name.clone(),
+            TensorData::new(data, shape, core_dtype_to_plugin(tensor.dtype())),
+        );
+    }
+    Ok((inputs, stream))
+}
+
+fn outputs_to_json(
+    outputs: &HashMap<String, TensorData>,
+    duration_secs: f64,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let mut tensors = serde_json::Map::new();
+    for (name, data) in outputs {
+        let tensor = Tensor::from_bytes(
+            &data.data,
+            Shape::new(&data.shape),
+            plugin_dtype_to_core(data.dtype)?,
+            CoreDevice::CPU,
+        )?;
+        tensors.insert(name.clone(), json::tensor_to_json(&tensor)?);
+    }
+    Ok(serde_json::json!({ "outputs": tensors, "duration_ms": duration_secs * 1000.0 }))
+}
+
+fn respond_json(request: Request, status: u16, body: &serde_json::Value) -> u16 {
+    let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("valid header");
+    let response = Response::from_data(body.to_string())
+        .with_status_code(status)
+        .with_header(header);
+    // The client may have gone; there is no one left to tell
+    let _ = request.respond(response);
+    status
+}
+
+fn respond_error(request: Request, status: u16, message: &str) -> u16 {
+    respond_json(request, status, &serde_json::json!({ "error": message }))
+}
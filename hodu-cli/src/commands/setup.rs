@@ -1,6 +1,6 @@
 //! First-run setup command - install recommended plugins
 
-use crate::commands::plugin::{get_plugins_dir, install_from_registry};
+use crate::commands::plugin::{get_plugins_dir, install_from_index};
 use crate::output;
 use crate::plugins::is_registry_empty;
 use inquire::Select;
@@ -78,7 +78,7 @@ pub fn run_setup() -> Result<(), Box<dyn std::error::Error>> {
     println!();
 
     for plugin in plugins {
-        if let Err(e) = install_from_registry(plugin, None, false, false, false) {
+        if let Err(e) = install_from_index(plugin, None, false, false, false, false) {
             output::warning(&format!("Failed to install {}: {}", plugin, e));
         }
     }
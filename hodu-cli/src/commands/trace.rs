@@ -0,0 +1,143 @@
+//! Trace command - record JSON-RPC exchanges with plugins and replay them
+//!
+//! `record` runs another hodu command with [`RPC_TRACE_ENV`] set, so every message it exchanges
+//! with a plugin lands in the trace file. `replay` sends the recorded requests to the plugins
+//! installed now and reports where their responses differ from the recording.
+
+use crate::output;
+use crate::plugins::{backend_plugin_name, format_plugin_name, PluginManager};
+use clap::{Args, Subcommand};
+use hodu_plugin::trace::{Replay, Trace, RPC_TRACE_ENV};
+use hodu_plugin_runtime::ClientError;
+use std::fs::File;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[derive(Args)]
+pub struct TraceArgs {
+    #[command(subcommand)]
+    pub command: TraceCommands,
+}
+
+#[derive(Subcommand)]
+pub enum TraceCommands {
+    /// Run a hodu command, recording every message it exchanges with plugins
+    Record(RecordArgs),
+
+    /// Send recorded requests to the installed plugins and compare their responses
+    Replay(ReplayArgs),
+}
+
+#[derive(Args)]
+pub struct RecordArgs {
+    /// Trace file to write
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// The hodu command to run, e.g. `-- run model.onnx --device cpu`
+    #[arg(last = true, required = true)]
+    pub command: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Trace file written by `hodu trace record`
+    pub trace: PathBuf,
+
+    /// Only replay the exchanges with this plugin
+    #[arg(long)]
+    pub plugin: Option<String>,
+
+    /// JSON pointer into results to leave out of the comparison (`*` matches any key)
+    #[arg(long, value_name = "PATH")]
+    pub ignore: Vec<String>,
+}
+
+pub fn execute(args: TraceArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        TraceCommands::Record(args) => record(args),
+        TraceCommands::Replay(args) => replay(args),
+    }
+}
+
+fn record(args: RecordArgs) -> Result<(), Box<dyn std::error::Error>> {
+    // Start from an empty file: the recorded command appends to it
+    File::create(&args.output).map_err(|e| format!("Failed to create {}: {}", args.output.display(), e))?;
+
+    let status = Command::new(std::env::current_exe()?)
+        .args(&args.command)
+        .env(RPC_TRACE_ENV, &args.output)
+        .status()?;
+
+    let trace = Trace::load(&args.output)?;
+    output::finished(&format!(
+        "recorded {} messages over {} plugin connections to {}",
+        trace.entries.len(),
+        trace.connections().len(),
+        args.output.display()
+    ));
+
+    if !status.success() {
+        return Err(format!("Recorded command failed ({})", status).into());
+    }
+    Ok(())
+}
+
+fn replay(args: ReplayArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let trace = Trace::load(&args.trace)?;
+
+    let wanted = args
+        .plugin
+        .as_deref()
+        .map(|name| [name.to_string(), backend_plugin_name(name), format_plugin_name(name)]);
+
+    let mut manager = PluginManager::new()?;
+    let mut replayed = 0;
+    let mut differences = 0;
+
+    for connection in trace.connections() {
+        // Connections are labelled `<plugin>#<process>.<n>` by the recording manager
+        let plugin = connection.split('#').next().unwrap_or(connection);
+        if let Some(wanted) = &wanted {
+            if !wanted.iter().any(|name| name == plugin) {
+                continue;
+            }
+        }
+
+        let mut replay = Replay::new(trace.connection(connection));
+        for path in &args.ignore {
+            replay = replay.ignore(path.clone());
+        }
+
+        output::running(&format!("{} ({} requests)", connection, replay.remaining()));
+        let client = manager.get_plugin(plugin)?;
+        while let Some((method, params)) = replay.next_request() {
+            match client.request(&method, params) {
+                Ok(result) => replay.check(Ok(result)),
+                Err(ClientError::Rpc(e)) => replay.check(Err(e)),
+                Err(e) => return Err(format!("{} failed on {}: {}", plugin, method, e).into()),
+            }
+        }
+        manager.shutdown_plugin(plugin)?;
+
+        let report = replay.finish();
+        for difference in &report.differences {
+            output::error(&difference.to_string());
+        }
+        if report.skipped > 0 {
+            output::skipping(&format!("{} requests not replayable", report.skipped));
+        }
+        replayed += report.replayed;
+        differences += report.differences.len();
+    }
+
+    if replayed == 0 && differences == 0 {
+        output::warning("no requests to replay");
+        return Ok(());
+    }
+    if differences > 0 {
+        return Err(format!("{} of the replayed responses differ from the recording", differences).into());
+    }
+    output::finished(&format!("{} requests matched the recording", replayed));
+    Ok(())
+}
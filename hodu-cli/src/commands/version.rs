@@ -1,6 +1,11 @@
+use crate::output;
 use crate::plugins::load_registry;
 
 pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
+    if output::is_json() {
+        return print_json();
+    }
+
     println!("hodu {}", env!("CARGO_PKG_VERSION"));
     println!("hodu-plugin {}", hodu_plugin::PLUGIN_VERSION);
     println!("Platform: {}", hodu_plugin::current_host_triple());
@@ -34,3 +39,23 @@ pub fn execute() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+fn print_json() -> Result<(), Box<dyn std::error::Error>> {
+    let plugins: Vec<serde_json::Value> = match load_registry() {
+        Ok(registry) => registry
+            .backends()
+            .map(|plugin| (plugin, "backend"))
+            .chain(registry.model_formats().map(|plugin| (plugin, "model_format")))
+            .chain(registry.tensor_formats().map(|plugin| (plugin, "tensor_format")))
+            .map(|(plugin, kind)| serde_json::json!({ "name": plugin.name, "version": plugin.version, "type": kind }))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    output::print_json(&serde_json::json!({
+        "hodu": env!("CARGO_PKG_VERSION"),
+        "plugin_protocol": hodu_plugin::PLUGIN_VERSION,
+        "platform": hodu_plugin::current_host_triple(),
+        "plugins": plugins,
+    }))?;
+    Ok(())
+}
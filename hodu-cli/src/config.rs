@@ -0,0 +1,284 @@
+//! Settings in ~/.hodu/config.toml, so options repeated on every command can be set once
+//!
+//! ```toml
+//! backend = "hodu-backend-llvm"
+//! device = "cuda::0"
+//! cache_dir = "/data/hodu-cache"
+//! timeout = 600 # seconds
+//! ```
+//!
+//! Each setting resolves to the first of:
+//!
+//! 1. the command-line flag (`--backend`, `--device`, `--timeout`)
+//! 2. the environment variable (`HODU_BACKEND`, `HODU_DEVICE`, `HODU_CACHE_DIR`, `HODU_TIMEOUT`)
+//! 3. the config file
+//! 4. the built-in default
+//!
+//! The same file holds plugin settings (`[plugins.<name>]`) and the daemon pool (`[pool]`), read by
+//! [`PluginConfig`](crate::plugins::PluginConfig).
+
+use crate::plugins::{ConfigError, PluginConfig, DEFAULT_TIMEOUT};
+use std::path::{Path, PathBuf};
+
+/// Device used when neither a flag, the environment nor the config file names one
+pub const DEFAULT_DEVICE: &str = "cpu";
+
+/// Kind of value a setting holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Any non-empty string
+    Text,
+    /// A filesystem path; `~/` expands to the home directory
+    Path,
+    /// A positive number of seconds
+    Seconds,
+}
+
+/// A top-level setting of the config file
+#[derive(Debug)]
+pub struct Setting {
+    /// Key in the config file
+    pub key: &'static str,
+    /// Environment variable overriding the file
+    pub env: &'static str,
+    /// Kind of value
+    pub kind: Kind,
+    /// What the setting does, for `hodu config list`
+    pub description: &'static str,
+}
+
+/// Every top-level setting
+pub const SETTINGS: &[Setting] = &[
+    Setting {
+        key: "backend",
+        env: "HODU_BACKEND",
+        kind: Kind::Text,
+        description: "Backend plugin when --backend is not given (default: by device)",
+    },
+    Setting {
+        key: "device",
+        env: "HODU_DEVICE",
+        kind: Kind::Text,
+        description: "Device when --device is not given",
+    },
+    Setting {
+        key: "cache_dir",
+        env: "HODU_CACHE_DIR",
+        kind: Kind::Path,
+        description: "Where compiled models are cached",
+    },
+    Setting {
+        key: "timeout",
+        env: "HODU_TIMEOUT",
+        kind: Kind::Seconds,
+        description: "Seconds a plugin may take to answer when --timeout is not given",
+    },
+];
+
+/// Tables of the config file read elsewhere
+const TABLES: [&str; 2] = ["plugins", "pool"];
+
+/// Where a setting's value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Command-line flag
+    Flag,
+    /// Environment variable
+    Env(&'static str),
+    /// Config file
+    File,
+    /// Built-in default
+    Default,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Flag => write!(f, "flag"),
+            Source::Env(var) => write!(f, "{}", var),
+            Source::File => write!(f, "config file"),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// A resolved setting and where it came from
+#[derive(Debug, Clone)]
+pub struct Resolved<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Resolved<T> {
+    fn new(value: T, source: Source) -> Self {
+        Self { value, source }
+    }
+}
+
+/// Settings resolved from the environment, the config file and the defaults
+///
+/// Flags are applied by the commands, with [`Settings::backend`] and [`Settings::device`].
+#[derive(Debug, Clone)]
+pub struct Settings {
+    backend: Option<Resolved<String>>,
+    device: Resolved<String>,
+    cache_dir: Resolved<PathBuf>,
+    timeout: Resolved<u64>,
+}
+
+impl Settings {
+    /// Resolve the settings with the config file at the default path
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_from(&PluginConfig::default_path()?)
+    }
+
+    /// Resolve the settings with the config file at `path` (a missing file sets nothing)
+    pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+        let file = read_settings(path)?;
+        let lookup = |key: &str| -> Result<Option<Resolved<String>>, ConfigError> {
+            let setting = find_setting(key).expect("every looked up key is in SETTINGS");
+            if let Some(value) = std::env::var_os(setting.env) {
+                let value = value.to_string_lossy().into_owned();
+                check_value(setting, &value).map_err(|e| ConfigError::Env(format!("{}: {}", setting.env, e)))?;
+                return Ok(Some(Resolved::new(value, Source::Env(setting.env))));
+            }
+            Ok(file
+                .get(key)
+                .map(|value| Resolved::new(value_text(value), Source::File)))
+        };
+
+        let backend = lookup("backend")?;
+        let device = lookup("device")?.unwrap_or_else(|| Resolved::new(DEFAULT_DEVICE.to_string(), Source::Default));
+        let cache_dir = match lookup("cache_dir")? {
+            Some(resolved) => Resolved::new(expand_home(&resolved.value)?, resolved.source),
+            None => Resolved::new(default_cache_dir()?, Source::Default),
+        };
+        let timeout = match lookup("timeout")? {
+            Some(resolved) => Resolved::new(
+                resolved.value.parse().expect("checked to be a positive integer"),
+                resolved.source,
+            ),
+            None => Resolved::new(DEFAULT_TIMEOUT.as_secs(), Source::Default),
+        };
+
+        Ok(Self {
+            backend,
+            device,
+            cache_dir,
+            timeout,
+        })
+    }
+
+    /// Backend plugin name: the `--backend` flag, else the configured one, else `None` to pick by
+    /// device
+    pub fn backend(&self, flag: Option<&str>) -> Option<String> {
+        flag.map(str::to_string)
+            .or_else(|| self.backend.as_ref().map(|resolved| resolved.value.clone()))
+    }
+
+    /// Device: the `--device` flag, else the configured one, else [`DEFAULT_DEVICE`]
+    pub fn device(&self, flag: Option<&str>) -> String {
+        flag.map_or_else(|| self.device.value.clone(), str::to_string)
+    }
+
+    /// Directory of cached build artifacts
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir.value
+    }
+
+    /// Seconds a plugin may take to answer, when the command has no `--timeout`
+    pub fn timeout(&self) -> u64 {
+        self.timeout.value
+    }
+
+    /// Every top-level setting as (key, value, source), unset ones with `None`
+    pub fn resolved(&self) -> Vec<(&'static str, Option<String>, Source)> {
+        let text = |resolved: &Resolved<String>| (Some(resolved.value.clone()), resolved.source.clone());
+        SETTINGS
+            .iter()
+            .map(|setting| {
+                let (value, source) = match setting.key {
+                    "backend" => match &self.backend {
+                        Some(resolved) => text(resolved),
+                        None => (None, Source::Default),
+                    },
+                    "device" => text(&self.device),
+                    "cache_dir" => (
+                        Some(self.cache_dir.value.display().to_string()),
+                        self.cache_dir.source.clone(),
+                    ),
+                    _ => (Some(self.timeout.value.to_string()), self.timeout.source.clone()),
+                };
+                (setting.key, value, source)
+            })
+            .collect()
+    }
+}
+
+/// The top-level setting named `key`
+pub fn find_setting(key: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|setting| setting.key == key)
+}
+
+/// Check a value given for `setting` as text, from the environment or `hodu config set`
+pub fn check_value(setting: &Setting, value: &str) -> Result<(), String> {
+    match setting.kind {
+        Kind::Text | Kind::Path if value.trim().is_empty() => Err("must not be empty".to_string()),
+        Kind::Seconds if !value.trim().parse::<u64>().is_ok_and(|secs| secs > 0) => {
+            Err("must be a positive number of seconds".to_string())
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Top-level settings of the config file at `path`, checked against [`SETTINGS`]
+fn read_settings(path: &Path) -> Result<toml::Table, ConfigError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(toml::Table::new()),
+        Err(e) => return Err(ConfigError::Io(format!("{}: {}", path.display(), e))),
+    };
+    let mut file: toml::Table = content
+        .parse()
+        .map_err(|e| ConfigError::Parse(format!("{}: {}", path.display(), e)))?;
+    file.retain(|key, _| !TABLES.contains(&key));
+
+    for (key, value) in &file {
+        let invalid = |message: String| ConfigError::Parse(format!("{}: {}", path.display(), message));
+        let setting = find_setting(key).ok_or_else(|| {
+            let known: Vec<&str> = SETTINGS.iter().map(|setting| setting.key).chain(TABLES).collect();
+            invalid(format!("Unknown key `{}` (known keys: {})", key, known.join(", ")))
+        })?;
+        let checked = match (setting.kind, value) {
+            (Kind::Seconds, toml::Value::Integer(_)) | (Kind::Text | Kind::Path, toml::Value::String(_)) => {
+                check_value(setting, &value_text(value))
+            },
+            (Kind::Seconds, _) => Err("must be a positive number of seconds".to_string()),
+            _ => Err("must be a string".to_string()),
+        };
+        checked.map_err(|e| invalid(format!("`{}` {}", key, e)))?;
+    }
+    Ok(file)
+}
+
+/// A setting's value as text, without the quotes of a TOML string
+fn value_text(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn expand_home(path: &str) -> Result<PathBuf, ConfigError> {
+    match path.strip_prefix("~/") {
+        Some(rest) => Ok(dirs::home_dir().ok_or(ConfigError::NoHomeDir)?.join(rest)),
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+fn default_cache_dir() -> Result<PathBuf, ConfigError> {
+    Ok(dirs::home_dir()
+        .ok_or(ConfigError::NoHomeDir)?
+        .join(".hodu")
+        .join("cache"))
+}
@@ -0,0 +1,238 @@
+//! Reporting command errors, with remediation for errors plugins describe in structured data,
+//! and the exit code each kind of error ends the process with
+
+use crate::output;
+use crate::plugins::{ClientError, ProcessError};
+use hodu_plugin::rpc::{error_codes, ErrorData, ErrorKind, RpcError};
+
+/// Kind of failure that ended a command, which decides the process exit code
+///
+/// The codes are stable, so scripts can tell failures apart without parsing messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// Any error not covered below (exit code 1)
+    Failure,
+    /// Invalid arguments or options (exit code 2, the code clap exits with for parse errors)
+    Usage,
+    /// The command ran, but what it checks failed: tensors differ in `diff` or
+    /// `compare-backends`, or cases of a batch run failed (exit code 3)
+    CheckFailed,
+    /// A file is missing or can't be read or written (exit code 4)
+    Io,
+    /// A plugin is missing or disabled, failed to start, crashed or returned an error (exit code 5)
+    Plugin,
+    /// A plugin didn't answer in time (exit code 6)
+    Timeout,
+    /// Interrupted with Ctrl+C (exit code 130)
+    Cancelled,
+}
+
+impl ErrorClass {
+    /// Process exit code for this class
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorClass::Failure => 1,
+            ErrorClass::Usage => 2,
+            ErrorClass::CheckFailed => 3,
+            ErrorClass::Io => 4,
+            ErrorClass::Plugin => 5,
+            ErrorClass::Timeout => 6,
+            ErrorClass::Cancelled => 130,
+        }
+    }
+
+    /// Name of this class in JSON error documents
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorClass::Failure => "failure",
+            ErrorClass::Usage => "usage",
+            ErrorClass::CheckFailed => "check_failed",
+            ErrorClass::Io => "io",
+            ErrorClass::Plugin => "plugin",
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A command error that states its class, for failures whose type alone doesn't tell
+#[derive(Debug)]
+pub struct ClassifiedError {
+    class: ErrorClass,
+    message: String,
+}
+
+impl std::fmt::Display for ClassifiedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ClassifiedError {}
+
+/// A command error of `class`
+pub fn classified(class: ErrorClass, message: impl Into<String>) -> Box<dyn std::error::Error> {
+    Box::new(ClassifiedError {
+        class,
+        message: message.into(),
+    })
+}
+
+/// Class of a command error, from the first error in its chain that tells
+pub fn classify(error: &(dyn std::error::Error + 'static)) -> ErrorClass {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(classified) = error.downcast_ref::<ClassifiedError>() {
+            return classified.class;
+        }
+        if error.is::<std::io::Error>() {
+            return ErrorClass::Io;
+        }
+        if let Some(client) = error.downcast_ref::<ClientError>() {
+            return classify_client(client);
+        }
+        if let Some(process) = error.downcast_ref::<ProcessError>() {
+            return match process {
+                ProcessError::Client(client) => classify_client(client),
+                ProcessError::TraceFile(_) => ErrorClass::Io,
+                ProcessError::Registry(_) | ProcessError::Config(_) => ErrorClass::Failure,
+                _ => ErrorClass::Plugin,
+            };
+        }
+        current = error.source();
+    }
+    ErrorClass::Failure
+}
+
+fn classify_client(error: &ClientError) -> ErrorClass {
+    match error {
+        ClientError::Timeout(_) => ErrorClass::Timeout,
+        ClientError::Rpc(rpc) => match rpc.code {
+            error_codes::REQUEST_CANCELLED => ErrorClass::Cancelled,
+            error_codes::FILE_NOT_FOUND | error_codes::ACCESS_DENIED => ErrorClass::Io,
+            _ if rpc.error_data().kind == Some(ErrorKind::Timeout) => ErrorClass::Timeout,
+            _ => ErrorClass::Plugin,
+        },
+        _ => ErrorClass::Plugin,
+    }
+}
+
+/// Print a command's error as a JSON document on stdout, for `--output json`
+///
+/// `{"error": {"class", "exit_code", "message"}}`, with a `plugin` object holding the JSON-RPC
+/// `code` and the plugin's structured error data when a plugin reported the error.
+pub fn report_json(error: &(dyn std::error::Error + 'static)) {
+    let class = classify(error);
+    let mut body = serde_json::json!({
+        "class": class.name(),
+        "exit_code": class.exit_code(),
+        "message": error.to_string(),
+    });
+    if let Some(rpc) = find_rpc_error(error) {
+        let mut plugin = serde_json::to_value(rpc.error_data()).unwrap_or_else(|_| serde_json::json!({}));
+        plugin["code"] = rpc.code.into();
+        body["plugin"] = plugin;
+    }
+    // Nothing sensible to do if stdout itself fails
+    let _ = output::print_json(&serde_json::json!({ "error": body }));
+}
+
+/// Print a command's error, followed by what to do about it when a plugin said enough to tell
+pub fn report(error: &(dyn std::error::Error + 'static)) {
+    output::error(&error.to_string());
+    let Some(rpc) = find_rpc_error(error) else {
+        return;
+    };
+
+    let data = rpc.error_data();
+    let mut location = Vec::new();
+    if let Some(node) = &data.node {
+        location.push(format!("node '{}'", output::sanitize_for_terminal(node)));
+    }
+    if let Some(tensor) = &data.tensor {
+        location.push(format!("tensor '{}'", output::sanitize_for_terminal(tensor)));
+    }
+    if !location.is_empty() {
+        eprintln!("  at {}", location.join(", "));
+    }
+    if let Some(cause) = &data.cause {
+        eprintln!("  caused by: {}", output::sanitize_for_terminal(cause));
+    }
+
+    for hint in remediation(rpc, &data) {
+        eprintln!("  hint: {}", output::sanitize_for_terminal(&hint));
+    }
+    // The code's catalog entry covers retrying unless the plugin said more
+    match data.retry_after_ms {
+        Some(ms) => eprintln!("  retry in {}", output::format_duration(ms as f64 / 1000.0)),
+        None if data.retryable == Some(true) => eprintln!("  this error is temporary; retrying may succeed"),
+        None => {},
+    }
+}
+
+/// The plugin error behind a command error, if there is one
+fn find_rpc_error<'a>(error: &'a (dyn std::error::Error + 'static)) -> Option<&'a RpcError> {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(ClientError::Rpc(rpc)) = error.downcast_ref::<ClientError>() {
+            return Some(rpc);
+        }
+        if let Some(ProcessError::Client(ClientError::Rpc(rpc))) = error.downcast_ref::<ProcessError>() {
+            return Some(rpc);
+        }
+        current = error.source();
+    }
+    None
+}
+
+/// Hints the plugin sent, else advice for the kind of failure, else for the error code
+fn remediation(rpc: &RpcError, data: &ErrorData) -> Vec<String> {
+    if !data.hints.is_empty() {
+        return data.hints.clone();
+    }
+    if let Some(hint) = data.kind.and_then(|kind| kind_remediation(kind, data)) {
+        return vec![hint];
+    }
+    error_codes::lookup(rpc.code)
+        .filter(|info| info.code != error_codes::PLUGIN_ERROR)
+        .map(|info| vec![info.remediation.to_string()])
+        .unwrap_or_default()
+}
+
+fn kind_remediation(kind: ErrorKind, data: &ErrorData) -> Option<String> {
+    let tensor = data
+        .tensor
+        .as_ref()
+        .map_or_else(|| "the input".to_string(), |name| format!("'{}'", name));
+    let hint = match kind {
+        ErrorKind::ShapeMismatch => format!(
+            "Check the shape of {} against the model inputs listed by `hodu inspect <model>`",
+            tensor
+        ),
+        ErrorKind::DtypeMismatch => format!(
+            "Convert {} to the dtype the model expects (`hodu inspect <model>` lists them)",
+            tensor
+        ),
+        ErrorKind::MissingInput => match &data.tensor {
+            Some(name) => format!("Pass the input with -i {}=<path>", name),
+            None => "Pass every model input with -i name=<path>".to_string(),
+        },
+        ErrorKind::UnsupportedOp => match &data.node {
+            Some(node) => format!(
+                "Node '{}' uses an op this backend cannot run; pick another with --backend",
+                node
+            ),
+            None => "The model uses an op this backend cannot run; pick another with --backend".to_string(),
+        },
+        ErrorKind::UnsupportedDtype => {
+            "Pick another backend with --backend, or convert the model to a supported dtype".to_string()
+        },
+        ErrorKind::OutOfMemory => "Use smaller inputs, or a device with more memory (--device)".to_string(),
+        ErrorKind::DeviceLost => "Check the device with `hodu doctor`, then run again".to_string(),
+        ErrorKind::Timeout => "Allow the plugin more time with --timeout".to_string(),
+        ErrorKind::Io => "Check the paths exist and are readable and writable".to_string(),
+        ErrorKind::InvalidModel => "Check the model file with `hodu inspect`".to_string(),
+        ErrorKind::Internal | ErrorKind::Other => return None,
+    };
+    Some(hint)
+}
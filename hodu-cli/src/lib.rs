@@ -1,5 +1,11 @@
+pub mod bundle;
+pub mod cache;
 pub mod commands;
+pub mod config;
+pub mod errors;
+pub mod logging;
 pub mod output;
 pub mod plugins;
 pub mod tensor;
 pub mod utils;
+pub mod watch;
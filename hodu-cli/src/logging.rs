@@ -0,0 +1,250 @@
+//! Plugin logs: which `$/log` notifications are shown, how, and where
+//!
+//! `--log-level` takes a default level and per-plugin levels, as `warn,cpu=debug,onnx=off`.
+//! Plugins are named as elsewhere on the command line, so `cpu` stands for `hodu-backend-cpu`.
+//! Each log is written as one line with its time, level and plugin, or as a JSON object with
+//! `--log-format json`, to stderr or to the file given with `--log-file`.
+//!
+//! ```text
+//! 2026-03-01T09:12:44.318Z WARN  [hodu-backend-cpu] falling back to scalar kernels op=conv2d
+//! ```
+
+use crate::output::{self, colors};
+use crate::plugins::{backend_plugin_name, format_plugin_name};
+use hodu_plugin::rpc::LogParams;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+/// Level of the logs shown when `--log-level` names none
+pub const DEFAULT_LEVEL: LogLevel = LogLevel::Warn;
+
+/// Level plugins forward logs down to unless told otherwise
+pub const PLUGIN_DEFAULT_LEVEL: LogLevel = LogLevel::Info;
+
+/// Severity of a log, from the most severe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// Nothing is shown (only as a filter)
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Off => "off",
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+
+    /// Level of a plugin's log; levels plugins shouldn't send count as `info`
+    fn of_log(level: &str) -> Self {
+        match level.parse() {
+            Ok(LogLevel::Off) | Err(_) => LogLevel::Info,
+            Ok(level) => level,
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            LogLevel::Off | LogLevel::Error => colors::BOLD_RED,
+            LogLevel::Warn => colors::BOLD_YELLOW,
+            LogLevel::Info => colors::BOLD_GREEN,
+            LogLevel::Debug | LogLevel::Trace => colors::BOLD_CYAN,
+        }
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" => Ok(LogLevel::Off),
+            "error" => Ok(LogLevel::Error),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            other => Err(format!(
+                "Unknown log level '{}' (expected off, error, warn, info, debug or trace)",
+                other
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Levels of shown logs: a default and overrides by plugin
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogFilter {
+    default: LogLevel,
+    plugins: Vec<(String, LogLevel)>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            default: DEFAULT_LEVEL,
+            plugins: Vec::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    /// Most verbose level shown for `plugin`
+    pub fn level_for(&self, plugin: &str) -> LogLevel {
+        // The last directive naming the plugin wins, as when a flag is repeated
+        self.plugins
+            .iter()
+            .rev()
+            .find(|(name, _)| {
+                name == plugin || backend_plugin_name(name) == plugin || format_plugin_name(name) == plugin
+            })
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    pub fn enabled(&self, plugin: &str, level: LogLevel) -> bool {
+        level != LogLevel::Off && level <= self.level_for(plugin)
+    }
+}
+
+impl std::str::FromStr for LogFilter {
+    type Err = String;
+
+    /// Parse `LEVEL`, `PLUGIN=LEVEL` or several of them separated by commas
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = LogFilter::default();
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((plugin, level)) => {
+                    let plugin = plugin.trim();
+                    if plugin.is_empty() {
+                        return Err(format!("Missing plugin name in '{}'", directive));
+                    }
+                    filter.plugins.push((plugin.to_string(), level.parse()?));
+                },
+                None => filter.default = directive.parse()?,
+            }
+        }
+        Ok(filter)
+    }
+}
+
+/// How each log is written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// One line with the time, level, plugin, message and fields
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+struct Logger {
+    filter: LogFilter,
+    format: LogFormat,
+    /// The `--log-file`; logs go to stderr without one
+    file: Option<Mutex<File>>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+fn logger() -> &'static Logger {
+    LOGGER.get_or_init(|| Logger {
+        filter: LogFilter::default(),
+        format: LogFormat::default(),
+        file: None,
+    })
+}
+
+/// Set how plugin logs are handled for the rest of the process
+///
+/// Logs are appended to `file` when given, as several commands may share one log file. Only
+/// the first call has an effect.
+pub fn init(filter: LogFilter, format: LogFormat, file: Option<&Path>) -> std::io::Result<()> {
+    let file = match file {
+        Some(path) => Some(Mutex::new(OpenOptions::new().create(true).append(true).open(path)?)),
+        None => None,
+    };
+    let _ = LOGGER.set(Logger { filter, format, file });
+    Ok(())
+}
+
+/// Most verbose level shown for `plugin`, which it should be asked to forward logs down to
+pub fn level_for(plugin: &str) -> LogLevel {
+    logger().filter.level_for(plugin)
+}
+
+/// Show a log `plugin` sent, if the filter lets it through
+pub fn log(plugin: &str, params: &LogParams) {
+    let logger = logger();
+    let level = LogLevel::of_log(&params.level);
+    if !logger.filter.enabled(plugin, level) {
+        return;
+    }
+    let time = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+
+    let line = match logger.format {
+        LogFormat::Json => {
+            let mut record = serde_json::Map::new();
+            record.insert("time".to_string(), time.into());
+            record.insert("level".to_string(), level.as_str().into());
+            record.insert("plugin".to_string(), plugin.into());
+            if let Ok(serde_json::Value::Object(params)) = serde_json::to_value(params) {
+                record.extend(params.into_iter().filter(|(key, _)| key != "level"));
+            }
+            serde_json::Value::Object(record).to_string()
+        },
+        LogFormat::Text => {
+            // What the plugin sent is escaped, so it can't inject terminal escape sequences
+            let mut message = params.message.clone();
+            for (key, value) in &params.fields {
+                match value {
+                    serde_json::Value::String(text) => message.push_str(&format!(" {}={}", key, text)),
+                    value => message.push_str(&format!(" {}={}", key, value)),
+                }
+            }
+            let message = output::sanitize_for_terminal(&message);
+            let label = level.as_str().to_uppercase();
+            match logger.file.is_none() && output::supports_color() {
+                true => format!(
+                    "{} {}{:<5}{} [{}] {}",
+                    time,
+                    level.color(),
+                    label,
+                    colors::RESET,
+                    plugin,
+                    message
+                ),
+                false => format!("{} {:<5} [{}] {}", time, label, plugin, message),
+            }
+        },
+    };
+
+    match &logger.file {
+        Some(file) => {
+            let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+            let _ = writeln!(file, "{}", line);
+        },
+        None => {
+            output::clear_progress();
+            eprintln!("{}", line);
+        },
+    }
+}
@@ -1,11 +1,33 @@
 use clap::{Parser, Subcommand};
 use hodu_cli::commands;
-use hodu_cli::output;
+use hodu_cli::commands::plugin::PluginCommands;
+use hodu_cli::errors::{self, ErrorClass};
+use hodu_cli::logging::{self, LogFilter, LogFormat};
+use hodu_cli::output::{self, OutputMode};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "hodu")]
 #[command(author, version, about = "hodu", long_about = None)]
 pub struct Cli {
+    /// Print results on stdout as text or as JSON (exit codes: 1 failure, 2 usage, 3 check
+    /// failed, 4 file error, 5 plugin error, 6 timeout, 130 cancelled)
+    #[arg(long, value_enum, default_value = "pretty", value_name = "MODE")]
+    pub output: OutputMode,
+
+    /// Plugin logs to show: a level (off, error, warn, info, debug, trace), PLUGIN=LEVEL, or
+    /// several separated by commas, e.g. `warn,cpu=debug`
+    #[arg(long, default_value = "warn", value_name = "FILTER")]
+    pub log_level: LogFilter,
+
+    /// Append plugin logs to this file instead of printing them on stderr
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Write each plugin log as a line of text or as a JSON object
+    #[arg(long, value_enum, default_value = "text", value_name = "FORMAT")]
+    pub log_format: LogFormat,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -15,21 +37,54 @@ pub enum Commands {
     /// Run a model
     Run(commands::run::RunArgs),
 
+    /// Benchmark a model on a backend plugin
+    Bench(commands::bench::BenchArgs),
+
     /// Build a model to native artifact
     Build(commands::build::BuildArgs),
 
     /// Convert models and tensors between formats
     Convert(commands::convert::ConvertArgs),
 
+    /// Bundle a model, its weights, required plugins and run defaults into one .hpk file
+    Pack(commands::pack::PackArgs),
+
+    /// Quantize a model through a backend plugin
+    Quantize(commands::quantize::QuantizeArgs),
+
+    /// Serve a model over HTTP, keeping it loaded between requests
+    Serve(commands::serve::ServeArgs),
+
+    /// Run a model on several backends and compare their outputs
+    CompareBackends(commands::compare_backends::CompareBackendsArgs),
+
+    /// Compare tensors numerically
+    Diff(commands::diff::DiffArgs),
+
     /// Inspect a model file
     Inspect(commands::inspect::InspectArgs),
 
-    /// Diagnose available devices and buildable targets on this host
+    /// Check a model before running or building it
+    Model(commands::model::ModelArgs),
+
+    /// Evaluate tensor and model expressions interactively
+    Repl(commands::repl::ReplArgs),
+
+    /// Diagnose plugins, devices, toolchains and caches on this host
     Doctor,
 
+    /// Show or edit settings in ~/.hodu/config.toml
+    Config(commands::config::ConfigArgs),
+
     /// Manage plugins
     Plugin(commands::plugin::PluginArgs),
 
+    /// Record JSON-RPC exchanges with plugins and replay them
+    Trace(commands::trace::TraceArgs),
+
+    /// Inspect the build artifact cache
+    Cache(commands::cache::CacheArgs),
+
     /// Clean build cache
     Clean(commands::clean::CleanArgs),
 
@@ -41,9 +96,38 @@ pub enum Commands {
 }
 
 fn main() {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) if e.use_stderr() && json_requested() => {
+            let error = errors::classified(ErrorClass::Usage, e.kind().to_string());
+            errors::report_json(error.as_ref());
+            e.exit()
+        },
+        Err(e) => e.exit(),
+    };
+    output::set_mode(cli.output);
+
+    if let Err(e) = logging::init(cli.log_level.clone(), cli.log_format, cli.log_file.as_deref()) {
+        let path = cli.log_file.clone().unwrap_or_default();
+        let error = errors::classified(ErrorClass::Io, format!("{}: {}", path.display(), e));
+        errors::report(error.as_ref());
+        if output::is_json() {
+            errors::report_json(error.as_ref());
+        }
+        std::process::exit(ErrorClass::Io.exit_code());
+    }
+
+    if let Some(unsupported) = output::is_json().then(|| json_unsupported(&cli.command)).flatten() {
+        let error = errors::classified(ErrorClass::Usage, unsupported);
+        errors::report(error.as_ref());
+        errors::report_json(error.as_ref());
+        std::process::exit(ErrorClass::Usage.exit_code());
+    }
+
     // First-run setup: show plugin installation wizard if no plugins installed
-    // Runs before command execution but after parsing, so user's command is preserved
-    if commands::setup::is_first_run() && !commands::setup::was_setup_shown() {
+    // Runs before command execution but after parsing, so user's command is preserved. Scripts
+    // asking for JSON never get the interactive wizard.
+    if !output::is_json() && commands::setup::is_first_run() && !commands::setup::was_setup_shown() {
         if let Err(e) = commands::setup::run_setup() {
             output::warning(&format!("Setup skipped: {e}"));
         }
@@ -53,22 +137,74 @@ fn main() {
         // Continue to execute user's command after setup (don't return early)
     }
 
-    let cli = Cli::parse();
-
     let result = match cli.command {
         Commands::Run(args) => commands::run::execute(args),
+        Commands::Bench(args) => commands::bench::execute(args),
         Commands::Build(args) => commands::build::execute(args),
         Commands::Convert(args) => commands::convert::execute(args),
+        Commands::Pack(args) => commands::pack::execute(args),
+        Commands::Quantize(args) => commands::quantize::execute(args),
+        Commands::Serve(args) => commands::serve::execute(args),
+        Commands::CompareBackends(args) => commands::compare_backends::execute(args),
+        Commands::Diff(args) => commands::diff::execute(args),
         Commands::Inspect(args) => commands::inspect::execute(args),
+        Commands::Model(args) => commands::model::execute(args),
+        Commands::Repl(args) => commands::repl::execute(args),
         Commands::Doctor => commands::doctor::execute(),
+        Commands::Config(args) => commands::config::execute(args),
         Commands::Plugin(args) => commands::plugin::execute(args),
+        Commands::Trace(args) => commands::trace::execute(args),
+        Commands::Cache(args) => commands::cache::execute(args),
         Commands::Clean(args) => commands::clean::execute(args),
         Commands::Version => commands::version::execute(),
         Commands::Completions(args) => commands::completions::execute::<Cli>(args),
     };
 
     if let Err(e) = result {
-        output::error(&format!("{e}"));
-        std::process::exit(1);
+        errors::report(e.as_ref());
+        if output::is_json() {
+            errors::report_json(e.as_ref());
+        }
+        std::process::exit(errors::classify(e.as_ref()).exit_code());
+    }
+}
+
+/// Why the command can't run under `--output json`, if it can't
+///
+/// These commands print text or run interactively, so they refuse JSON mode rather than mixing
+/// text into what scripts parse.
+fn json_unsupported(command: &Commands) -> Option<String> {
+    let name = match command {
+        Commands::Plugin(args) => match args.command {
+            PluginCommands::List | PluginCommands::Status(_) => return None,
+            _ => {
+                return Some(
+                    "Of the plugin commands, only `hodu plugin list` and `hodu plugin status` have JSON output"
+                        .to_string(),
+                )
+            },
+        },
+        Commands::Serve(_) => "serve",
+        Commands::Repl(_) => "repl",
+        Commands::Doctor => "doctor",
+        Commands::Trace(_) => "trace",
+        Commands::Completions(_) => "completions",
+        _ => return None,
+    };
+    Some(format!("`hodu {}` has no JSON output", name))
+}
+
+/// Whether `--output json` precedes the subcommand, for reporting argument errors before the
+/// arguments parse
+fn json_requested() -> bool {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => return args.next().as_deref() == Some("json"),
+            "--output=json" => return true,
+            _ if arg.starts_with('-') => {},
+            _ => return false,
+        }
     }
+    false
 }
@@ -2,7 +2,9 @@
 //!
 //! Provides consistent, colorful terminal output similar to cargo.
 
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
 
 /// ANSI color codes
 pub mod colors {
@@ -18,6 +20,35 @@ pub mod colors {
     pub const BOLD_RED: &str = "\x1b[1;31m";
 }
 
+/// What commands print on stdout
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputMode {
+    /// Human-readable text
+    Pretty,
+    /// One JSON document per result, and a JSON error document on failure
+    Json,
+}
+
+static JSON_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Set the output mode for the rest of the process (`hodu --output`)
+pub fn set_mode(mode: OutputMode) {
+    JSON_MODE.store(mode == OutputMode::Json, Ordering::Relaxed);
+}
+
+/// Whether `--output json` was given
+///
+/// Commands print JSON on stdout instead of text; status messages still go to stderr.
+pub fn is_json() -> bool {
+    JSON_MODE.load(Ordering::Relaxed)
+}
+
+/// Print `value` as a JSON document on stdout
+pub fn print_json(value: &impl serde::Serialize) -> Result<(), serde_json::Error> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
 /// Check if terminal supports colors
 pub fn supports_color() -> bool {
     std::env::var("NO_COLOR").is_err() && std::env::var("TERM").map(|t| t != "dumb").unwrap_or(true)
@@ -26,6 +57,7 @@ pub fn supports_color() -> bool {
 /// Print a status message in cargo style
 /// Format: "   {status} {message}"
 fn print_status(status: &str, color: &str, message: &str) {
+    clear_progress();
     if supports_color() {
         eprintln!("{}{:>12}{} {}", color, status, colors::RESET, message);
     } else {
@@ -48,6 +80,11 @@ pub fn running(message: &str) {
     print_status("Running", colors::BOLD_GREEN, message);
 }
 
+/// Print "Benchmarking" status (green)
+pub fn benchmarking(message: &str) {
+    print_status("Benchmarking", colors::BOLD_GREEN, message);
+}
+
 /// Print "Finished" status (green)
 pub fn finished(message: &str) {
     print_status("Finished", colors::BOLD_GREEN, message);
@@ -63,6 +100,16 @@ pub fn installed(message: &str) {
     print_status("Installed", colors::BOLD_GREEN, message);
 }
 
+/// Print "Verified" status (green)
+pub fn verified(message: &str) {
+    print_status("Verified", colors::BOLD_GREEN, message);
+}
+
+/// Print "Signed" status (green)
+pub fn signed(message: &str) {
+    print_status("Signed", colors::BOLD_GREEN, message);
+}
+
 /// Print "Removing" status (green)
 pub fn removing(message: &str) {
     print_status("Removing", colors::BOLD_GREEN, message);
@@ -73,6 +120,11 @@ pub fn removed(message: &str) {
     print_status("Removed", colors::BOLD_GREEN, message);
 }
 
+/// Print "Stopped" status (green)
+pub fn stopped(message: &str) {
+    print_status("Stopped", colors::BOLD_GREEN, message);
+}
+
 /// Print "Updating" status (green)
 pub fn updating(message: &str) {
     print_status("Updating", colors::BOLD_GREEN, message);
@@ -98,11 +150,46 @@ pub fn converting(message: &str) {
     print_status("Converting", colors::BOLD_CYAN, message);
 }
 
+/// Print "Quantizing" status (cyan)
+pub fn quantizing(message: &str) {
+    print_status("Quantizing", colors::BOLD_CYAN, message);
+}
+
 /// Print "Inspecting" status (cyan)
 pub fn inspecting(message: &str) {
     print_status("Inspecting", colors::BOLD_CYAN, message);
 }
 
+/// Print "Validating" status (cyan)
+pub fn validating(message: &str) {
+    print_status("Validating", colors::BOLD_CYAN, message);
+}
+
+/// Print "Comparing" status (cyan)
+pub fn comparing(message: &str) {
+    print_status("Comparing", colors::BOLD_CYAN, message);
+}
+
+/// Print "Packing" status (cyan)
+pub fn packing(message: &str) {
+    print_status("Packing", colors::BOLD_CYAN, message);
+}
+
+/// Print "Unpacking" status (cyan)
+pub fn unpacking(message: &str) {
+    print_status("Unpacking", colors::BOLD_CYAN, message);
+}
+
+/// Print "Watching" status (cyan)
+pub fn watching(message: &str) {
+    print_status("Watching", colors::BOLD_CYAN, message);
+}
+
+/// Print "Changed" status (yellow)
+pub fn changed(message: &str) {
+    print_status("Changed", colors::BOLD_YELLOW, message);
+}
+
 /// Print "Downloading" status (cyan)
 pub fn downloading(message: &str) {
     print_status("Downloading", colors::BOLD_CYAN, message);
@@ -145,6 +232,7 @@ pub struct Progress {
 
 impl Progress {
     pub fn new(status: &str, message: &str) -> Self {
+        clear_progress();
         if supports_color() {
             eprint!("{}{:>12}{} {}...", colors::BOLD_CYAN, status, colors::RESET, message);
         } else {
@@ -171,6 +259,91 @@ impl Progress {
     }
 }
 
+/// Width of live progress bars in characters
+const PROGRESS_BAR_WIDTH: usize = 25;
+
+/// Longest detail text shown next to a live progress bar, so lines never wrap
+const MAX_PROGRESS_DETAIL_CHARS: usize = 60;
+
+/// Number of lines the live progress display currently occupies on stderr
+static PROGRESS_LINES: Mutex<usize> = Mutex::new(0);
+
+/// One bar of the live progress display
+pub struct ProgressBar<'a> {
+    /// Status-column label (e.g. "Progress" or a stage name)
+    pub label: &'a str,
+    /// Percent complete, or `None` to show only the detail
+    pub percent: Option<u8>,
+    /// Text after the bar
+    pub detail: String,
+}
+
+/// Redraw the live progress display in place with `bars`
+///
+/// The first bar is drawn like a status line and the rest are nested under it. Only shown when
+/// stderr is a color terminal; any status message clears it first.
+pub fn draw_progress(bars: &[ProgressBar]) {
+    if !supports_color() || !io::stderr().is_terminal() {
+        return;
+    }
+    let mut drawn = PROGRESS_LINES.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut stderr = io::stderr().lock();
+    erase_lines(&mut stderr, *drawn);
+    for (i, bar) in bars.iter().enumerate() {
+        let label: String = bar.label.chars().take(12).collect();
+        let detail: String = bar.detail.chars().take(MAX_PROGRESS_DETAIL_CHARS).collect();
+        let color = if i == 0 { colors::BOLD_CYAN } else { colors::CYAN };
+        let _ = match bar.percent {
+            Some(percent) => writeln!(
+                stderr,
+                "{}{:>12}{} {} {:>3}% {}",
+                color,
+                label,
+                colors::RESET,
+                progress_bar(percent),
+                percent.min(100),
+                detail
+            ),
+            None => writeln!(stderr, "{}{:>12}{} {}", color, label, colors::RESET, detail),
+        };
+    }
+    let _ = stderr.flush();
+    *drawn = bars.len();
+}
+
+/// Remove the live progress display, if one is shown
+pub fn clear_progress() {
+    let mut drawn = PROGRESS_LINES.lock().unwrap_or_else(PoisonError::into_inner);
+    if *drawn == 0 {
+        return;
+    }
+    let mut stderr = io::stderr().lock();
+    erase_lines(&mut stderr, *drawn);
+    let _ = stderr.flush();
+    *drawn = 0;
+}
+
+/// Move the cursor up `count` lines and clear everything below it
+fn erase_lines(out: &mut impl Write, count: usize) {
+    if count > 0 {
+        let _ = write!(out, "\x1b[{}F\x1b[J", count);
+    }
+}
+
+/// Render a bar such as `[=========>               ]`
+fn progress_bar(percent: u8) -> String {
+    let filled = PROGRESS_BAR_WIDTH * percent.min(100) as usize / 100;
+    let mut bar = String::with_capacity(PROGRESS_BAR_WIDTH + 2);
+    bar.push('[');
+    bar.push_str(&"=".repeat(filled));
+    if filled < PROGRESS_BAR_WIDTH {
+        bar.push('>');
+        bar.push_str(&" ".repeat(PROGRESS_BAR_WIDTH - filled - 1));
+    }
+    bar.push(']');
+    bar
+}
+
 /// Format duration in human readable form
 pub fn format_duration(secs: f64) -> String {
     if secs < 1.0 {
@@ -8,11 +8,18 @@ pub use hodu_plugin_runtime::backend;
 pub use hodu_plugin_runtime::format;
 pub use hodu_plugin_runtime::{
     detect_plugin_type, CancellationHandle, ClientError, DetectedPluginType, PluginCapabilities, PluginClient,
-    PluginDetectError, PluginEntry, PluginRegistry, PluginSource, PluginType, RegistryError, DEFAULT_TIMEOUT,
+    PluginDetectError, PluginEntry, PluginRegistry, PluginSource, PluginType, RegistryError, ResourceLimits,
+    DEFAULT_TIMEOUT,
 };
 
+mod config;
+#[cfg(unix)]
+mod pool;
 mod process;
 
+pub use config::*;
+#[cfg(unix)]
+pub use pool::*;
 pub use process::*;
 
 // Plugin name prefixes
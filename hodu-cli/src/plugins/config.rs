@@ -0,0 +1,382 @@
+//! Per-plugin configuration in ~/.hodu/config.toml
+//!
+//! Each plugin reads the table named after it under `plugins`:
+//!
+//! ```toml
+//! [plugins.hodu-backend-llvm]
+//! threads = 8
+//! ```
+//!
+//! `--plugin-config PLUGIN.KEY=VALUE` overrides a value for a single command. The merged table is
+//! sent to the plugin in `initialize`.
+//!
+//! The `pool` table configures the plugin daemon pool:
+//!
+//! ```toml
+//! [pool]
+//! enabled = true
+//! idle_timeout = 600 # seconds
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Default time a pooled plugin daemon stays alive without a connection (10 minutes)
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Plugin configuration from the config file, plus command-line overrides
+#[derive(Debug, Default)]
+pub struct PluginConfig {
+    /// `plugins` table of the config file
+    plugins: toml::Table,
+    /// Overrides from the command line, by plugin name
+    overrides: HashMap<String, toml::Table>,
+    /// `pool` table of the config file
+    pool: PoolConfig,
+}
+
+/// Settings of the plugin daemon pool
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    /// Keep local plugins running as daemons between commands (Unix only)
+    pub enabled: bool,
+    /// How long a daemon waits for the next command before exiting
+    pub idle_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+        }
+    }
+}
+
+impl PoolConfig {
+    fn from_table(table: &toml::Table) -> Result<Self, String> {
+        let mut pool = Self::default();
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("enabled", toml::Value::Boolean(enabled)) => pool.enabled = *enabled,
+                ("idle_timeout", toml::Value::Integer(secs)) if *secs > 0 => {
+                    pool.idle_timeout = Duration::from_secs(*secs as u64)
+                },
+                ("enabled", _) => return Err("`pool.enabled` must be a boolean".to_string()),
+                ("idle_timeout", _) => {
+                    return Err("`pool.idle_timeout` must be a positive number of seconds".to_string())
+                },
+                _ => return Err(format!("Unknown key `pool.{}`", key)),
+            }
+        }
+        Ok(pool)
+    }
+}
+
+impl PluginConfig {
+    /// Get default config path (~/.hodu/config.toml)
+    pub fn default_path() -> Result<PathBuf, ConfigError> {
+        let home = dirs::home_dir().ok_or(ConfigError::NoHomeDir)?;
+        Ok(home.join(".hodu").join("config.toml"))
+    }
+
+    /// Load the config file from the default path (a missing file is an empty config)
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load a config file (a missing file is an empty config)
+    pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(ConfigError::Io(format!("{}: {}", path.display(), e))),
+        };
+        let mut file: toml::Table = content
+            .parse()
+            .map_err(|e| ConfigError::Parse(format!("{}: {}", path.display(), e)))?;
+
+        let plugins = match file.remove("plugins") {
+            None => toml::Table::new(),
+            Some(toml::Value::Table(plugins)) => plugins,
+            Some(_) => {
+                return Err(ConfigError::Parse(format!(
+                    "{}: `plugins` must be a table",
+                    path.display()
+                )))
+            },
+        };
+        if let Some((name, _)) = plugins.iter().find(|(_, value)| !value.is_table()) {
+            return Err(ConfigError::Parse(format!(
+                "{}: `plugins.{}` must be a table",
+                path.display(),
+                name
+            )));
+        }
+
+        let pool = match file.remove("pool") {
+            None => PoolConfig::default(),
+            Some(toml::Value::Table(pool)) => {
+                PoolConfig::from_table(&pool).map_err(|e| ConfigError::Parse(format!("{}: {}", path.display(), e)))?
+            },
+            Some(_) => {
+                return Err(ConfigError::Parse(format!(
+                    "{}: `pool` must be a table",
+                    path.display()
+                )))
+            },
+        };
+
+        Ok(Self {
+            plugins,
+            overrides: HashMap::new(),
+            pool,
+        })
+    }
+
+    /// Settings of the plugin daemon pool
+    pub fn pool(&self) -> &PoolConfig {
+        &self.pool
+    }
+
+    /// Override one value for this command, given as `PLUGIN.KEY=VALUE`
+    ///
+    /// The value is read as a TOML value (`8`, `true`, `"text"`, `[1, 2]`), falling back to a
+    /// plain string. Dotted keys set values in nested tables.
+    pub fn add_override(&mut self, spec: &str) -> Result<(), ConfigError> {
+        let invalid = || ConfigError::InvalidOverride(spec.to_string());
+        let (path, raw) = spec.split_once('=').ok_or_else(invalid)?;
+        let (plugin, key) = path
+            .trim()
+            .split_once('.')
+            .filter(|(plugin, key)| !plugin.is_empty() && !key.is_empty())
+            .ok_or_else(invalid)?;
+
+        let table = self.overrides.entry(plugin.to_string()).or_default();
+        insert_value(table, key, parse_value(raw))
+    }
+
+    /// Config stored in the file for `plugin`
+    pub fn file_table(&self, plugin: &str) -> Option<&toml::Table> {
+        self.plugins.get(plugin).and_then(toml::Value::as_table)
+    }
+
+    /// Config to send to `plugin`, or `None` if nothing is configured for it
+    pub fn for_plugin(&self, plugin: &str) -> Option<serde_json::Value> {
+        let overrides = self.overrides.get(plugin);
+        let mut table = match (self.file_table(plugin), overrides) {
+            (None, None) => return None,
+            (file, _) => file.cloned().unwrap_or_default(),
+        };
+        if let Some(overrides) = overrides {
+            merge_tables(&mut table, overrides);
+        }
+        serde_json::to_value(table).ok()
+    }
+}
+
+/// Read a value the way `--plugin-config` and `hodu plugin config --set` do
+pub fn parse_value(raw: &str) -> toml::Value {
+    let raw = raw.trim();
+    if raw.contains('\n') {
+        return toml::Value::String(raw.to_string());
+    }
+    format!("value = {}", raw)
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Read a value for the config file the way [`parse_value`] does, keeping its formatting
+pub fn parse_edit_value(raw: &str) -> toml_edit::Value {
+    raw.trim()
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| toml_edit::Value::from(raw.trim()))
+}
+
+/// Set a dotted `key` in `table`, creating intermediate tables
+fn insert_value(table: &mut toml::Table, key: &str, value: toml::Value) -> Result<(), ConfigError> {
+    let mut parts = key.split('.').peekable();
+    let mut current = table;
+    while let Some(part) = parts.next() {
+        if part.is_empty() {
+            return Err(ConfigError::InvalidKey(key.to_string()));
+        }
+        if parts.peek().is_none() {
+            current.insert(part.to_string(), value);
+            return Ok(());
+        }
+        current = current
+            .entry(part.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| ConfigError::InvalidKey(key.to_string()))?;
+    }
+    Err(ConfigError::InvalidKey(key.to_string()))
+}
+
+/// Merge `overrides` into `table`, recursing into tables present in both
+fn merge_tables(table: &mut toml::Table, overrides: &toml::Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(nested)) => merge_tables(existing, nested),
+            _ => {
+                table.insert(key.clone(), value.clone());
+            },
+        }
+    }
+}
+
+/// Check a value for a dotted `key` against a plugin's config schema
+///
+/// Keys are checked against `properties` wherever the schema lists them, and values against the
+/// property's `type`. Parts of the schema that do neither accept anything.
+pub fn check_against_schema(schema: &serde_json::Value, key: &str, value: &toml::Value) -> Result<(), String> {
+    let mut current = schema;
+    for part in key.split('.') {
+        let Some(properties) = current.get("properties").and_then(|p| p.as_object()) else {
+            return Ok(());
+        };
+        current = properties.get(part).ok_or_else(|| {
+            let mut known: Vec<_> = properties.keys().map(String::as_str).collect();
+            known.sort_unstable();
+            format!("Unknown config key '{}' (known keys: {})", key, known.join(", "))
+        })?;
+    }
+
+    let Some(expected) = current.get("type").and_then(|t| t.as_str()) else {
+        return Ok(());
+    };
+    let matches = match expected {
+        "boolean" => value.is_bool(),
+        "integer" => value.is_integer(),
+        "number" => value.is_integer() || value.is_float(),
+        "string" => value.is_str(),
+        "array" => value.is_array(),
+        "object" => value.is_table(),
+        _ => true,
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(format!(
+            "Config key '{}' must be of type {}, got {}",
+            key,
+            expected,
+            value.type_str()
+        ))
+    }
+}
+
+/// Set a dotted `key` of `plugin` in the config file at `path`, keeping its comments and layout
+pub fn set_config_value(path: &Path, plugin: &str, key: &str, raw: &str) -> Result<(), ConfigError> {
+    set_value(path, &format!("plugins.{}.{}", plugin, key), parse_edit_value(raw))
+}
+
+/// Remove a dotted `key` of `plugin` from the config file at `path`
+///
+/// Returns whether the key was present.
+pub fn unset_config_value(path: &Path, plugin: &str, key: &str) -> Result<bool, ConfigError> {
+    unset_value(path, &format!("plugins.{}.{}", plugin, key))
+}
+
+/// Set a dotted `key` anywhere in the config file at `path`, keeping its comments and layout
+///
+/// Missing tables are created as `[a.b]` headers, without empty headers for their parents.
+pub fn set_value(path: &Path, key: &str, value: toml_edit::Value) -> Result<(), ConfigError> {
+    let mut doc = read_document(path)?;
+    let mut table = doc.as_table_mut();
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if part.is_empty() {
+            return Err(ConfigError::InvalidKey(key.to_string()));
+        }
+        if parts.peek().is_none() {
+            table.insert(part, toml_edit::Item::Value(value));
+            break;
+        }
+        table = table
+            .entry(part)
+            .or_insert_with(|| {
+                let mut nested = toml_edit::Table::new();
+                nested.set_implicit(true);
+                toml_edit::Item::Table(nested)
+            })
+            .as_table_mut()
+            .ok_or_else(|| ConfigError::InvalidKey(key.to_string()))?;
+    }
+
+    write_document(path, &doc)
+}
+
+/// Remove a dotted `key` from the config file at `path`
+///
+/// Returns whether the key was present.
+pub fn unset_value(path: &Path, key: &str) -> Result<bool, ConfigError> {
+    let mut doc = read_document(path)?;
+    let (parents, last) = match key.rsplit_once('.') {
+        Some((parents, last)) => (Some(parents), last),
+        None => (None, key),
+    };
+    let mut table: &mut dyn toml_edit::TableLike = doc.as_table_mut();
+    for part in parents.into_iter().flat_map(|parents| parents.split('.')) {
+        match table.get_mut(part).and_then(toml_edit::Item::as_table_like_mut) {
+            Some(nested) => table = nested,
+            None => return Ok(false),
+        }
+    }
+    if table.remove(last).is_none() {
+        return Ok(false);
+    }
+
+    write_document(path, &doc)?;
+    Ok(true)
+}
+
+fn read_document(path: &Path) -> Result<toml_edit::DocumentMut, ConfigError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(ConfigError::Io(format!("{}: {}", path.display(), e))),
+    };
+    content
+        .parse()
+        .map_err(|e| ConfigError::Parse(format!("{}: {}", path.display(), e)))
+}
+
+fn write_document(path: &Path, doc: &toml_edit::DocumentMut) -> Result<(), ConfigError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| ConfigError::Io(format!("{}: {}", parent.display(), e)))?;
+    }
+    std::fs::write(path, doc.to_string()).map_err(|e| ConfigError::Io(format!("{}: {}", path.display(), e)))
+}
+
+/// Plugin configuration errors
+#[derive(Debug)]
+pub enum ConfigError {
+    NoHomeDir,
+    Io(String),
+    Parse(String),
+    InvalidOverride(String),
+    InvalidKey(String),
+    Env(String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::NoHomeDir => write!(f, "Could not find home directory"),
+            ConfigError::Io(e) => write!(f, "IO error: {}", e),
+            ConfigError::Parse(e) => write!(f, "Invalid config file: {}", e),
+            ConfigError::InvalidOverride(spec) => {
+                write!(f, "Invalid plugin config '{}' (expected PLUGIN.KEY=VALUE)", spec)
+            },
+            ConfigError::InvalidKey(key) => write!(f, "Invalid config key: {}", key),
+            ConfigError::Env(e) => write!(f, "Invalid environment variable {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
@@ -0,0 +1,341 @@
+//! Pool of plugin daemons kept alive between commands
+//!
+//! With `pool.enabled` set in ~/.hodu/config.toml, local plugins are started as daemons listening
+//! on a Unix socket in ~/.hodu/pool, so later commands skip the plugin's startup. Each plugin gets
+//! up to [`POOL_SLOTS`] daemons, each serving one command at a time. Every slot has:
+//!
+//! - `<plugin>.<slot>.sock`: socket the daemon listens on
+//! - `<plugin>.<slot>.lock`: locked by the command using the daemon
+//! - `<plugin>.<slot>.json`: [`DaemonState`], listed by `hodu plugin ps`
+//! - `<plugin>.<slot>.log`: the daemon's stderr
+//!
+//! Daemons exit after the configured idle timeout, or when `hodu plugin stop` sends `$/exit`.
+
+use super::config::PoolConfig;
+use super::process::ProcessError;
+use crate::output;
+use fs2::FileExt;
+use hodu_plugin::rpc::{IDLE_TIMEOUT_ENV, LISTEN_ENV};
+use hodu_plugin_runtime::{PluginClient, PluginEndpoint, PluginEntry};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::UnixStream;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Daemons kept per plugin, which bounds how many commands share a plugin's daemons at once
+pub const POOL_SLOTS: usize = 4;
+
+/// Interval between attempts to connect to a starting daemon
+const CONNECT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Timeout for a daemon to answer `$/exit`
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the pool records about a running daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonState {
+    pub plugin: String,
+    pub slot: usize,
+    pub pid: u32,
+    pub version: String,
+    /// Installation timestamp of the binary, telling reinstalls of the same version apart
+    pub installed_at: String,
+    /// Start time (RFC 3339)
+    pub started_at: String,
+    pub idle_timeout_secs: u64,
+}
+
+/// A daemon found in the pool
+#[derive(Debug)]
+pub struct PooledDaemon {
+    pub state: DaemonState,
+    /// Whether a command is using the daemon
+    pub busy: bool,
+}
+
+/// Daemons affected by [`stop_daemons`]
+#[derive(Debug, Default)]
+pub struct StopReport {
+    pub stopped: Vec<DaemonState>,
+    /// Daemons left running because a command is using them
+    pub busy: Vec<DaemonState>,
+}
+
+/// A connection to a pooled daemon
+pub struct Checkout {
+    pub client: PluginClient,
+    pub lease: Lease,
+    /// Whether the daemon was started for this connection
+    pub started: bool,
+}
+
+/// Exclusive use of a slot, released when dropped
+pub struct Lease {
+    _lock: File,
+    slot: Slot,
+}
+
+impl Lease {
+    /// Forget a daemon that turned out to be unusable, and release its slot
+    pub fn discard(self) {
+        self.slot.clear();
+    }
+}
+
+/// Files of one slot
+struct Slot {
+    dir: PathBuf,
+    plugin: String,
+    index: usize,
+}
+
+impl Slot {
+    fn new(dir: &Path, plugin: &str, index: usize) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            plugin: plugin.to_string(),
+            index,
+        }
+    }
+
+    fn path(&self, ext: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}.{}", self.plugin, self.index, ext))
+    }
+
+    fn endpoint(&self) -> PluginEndpoint {
+        PluginEndpoint::Unix(self.path("sock"))
+    }
+
+    /// Lock the slot, or `None` if a command is using it
+    fn try_lock(&self) -> Result<Option<File>, ProcessError> {
+        let path = self.path("lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| pool_error(&path, e))?;
+        match file.try_lock_exclusive() {
+            Ok(()) => Ok(Some(file)),
+            Err(e) if e.kind() == fs2::lock_contended_error().kind() => Ok(None),
+            Err(e) => Err(pool_error(&path, e)),
+        }
+    }
+
+    fn read_state(&self) -> Option<DaemonState> {
+        let content = std::fs::read_to_string(self.path("json")).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn write_state(&self, state: &DaemonState) -> Result<(), ProcessError> {
+        let path = self.path("json");
+        let content = serde_json::to_string_pretty(state).map_err(|e| ProcessError::Pool(e.to_string()))?;
+        std::fs::write(&path, content).map_err(|e| pool_error(&path, e))
+    }
+
+    /// Whether a daemon accepts connections on the slot's socket
+    fn is_listening(&self) -> bool {
+        UnixStream::connect(self.path("sock")).is_ok()
+    }
+
+    /// Forget the slot's daemon
+    fn clear(&self) {
+        let _ = std::fs::remove_file(self.path("sock"));
+        let _ = std::fs::remove_file(self.path("json"));
+    }
+
+    /// Ask the slot's daemon to exit, then forget it
+    ///
+    /// Returns whether the daemon was running.
+    fn stop(&self) -> bool {
+        let stopped = PluginClient::connect(&self.endpoint())
+            .and_then(|mut client| {
+                client.set_timeout(STOP_TIMEOUT);
+                client.initialize()?;
+                client.exit()
+            })
+            .is_ok();
+        self.clear();
+        stopped
+    }
+}
+
+/// Get the pool directory (~/.hodu/pool), creating it readable only by the user
+pub fn pool_dir() -> Result<PathBuf, ProcessError> {
+    let home = dirs::home_dir().ok_or_else(|| ProcessError::Pool("Could not find home directory".to_string()))?;
+    let dir = home.join(".hodu").join("pool");
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| pool_error(&dir, e))?;
+    Ok(dir)
+}
+
+/// Connect to an idle daemon of `entry`, starting one in a free slot if none is running
+///
+/// Daemons of another version or installation of the plugin are stopped on the way. Returns
+/// `None` when every slot is in use, or when the plugin exits instead of listening (plugins built
+/// without daemon support); the caller then spawns the plugin itself.
+pub fn checkout_daemon(
+    entry: &PluginEntry,
+    binary: &Path,
+    config: &PoolConfig,
+    timeout: Duration,
+) -> Result<Option<Checkout>, ProcessError> {
+    let dir = pool_dir()?;
+    let mut free = None;
+    for index in 0..POOL_SLOTS {
+        let slot = Slot::new(&dir, &entry.name, index);
+        let Some(lock) = slot.try_lock()? else {
+            continue;
+        };
+        let lease = Lease { _lock: lock, slot };
+        match lease.slot.read_state() {
+            Some(state) if state.version == entry.version && state.installed_at == entry.installed_at => {
+                if let Ok(client) = PluginClient::connect(&lease.slot.endpoint()) {
+                    return Ok(Some(Checkout {
+                        client,
+                        lease,
+                        started: false,
+                    }));
+                }
+                lease.slot.clear();
+            },
+            Some(_) => {
+                lease.slot.stop();
+            },
+            None => lease.slot.clear(),
+        }
+        free.get_or_insert(lease);
+    }
+
+    match free {
+        Some(lease) => start_daemon(entry, binary, config, timeout, lease),
+        None => Ok(None),
+    }
+}
+
+/// Start a daemon of `entry` in the leased slot and connect to it
+fn start_daemon(
+    entry: &PluginEntry,
+    binary: &Path,
+    config: &PoolConfig,
+    timeout: Duration,
+    lease: Lease,
+) -> Result<Option<Checkout>, ProcessError> {
+    let slot = &lease.slot;
+    let log_path = slot.path("log");
+    let log = File::create(&log_path).map_err(|e| pool_error(&log_path, e))?;
+    let mut child = Command::new(binary)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(log)
+        .env(LISTEN_ENV, format!("unix://{}", slot.path("sock").display()))
+        .env(IDLE_TIMEOUT_ENV, config.idle_timeout.as_secs().to_string())
+        // Keep Ctrl+C in the terminal from reaching the daemon
+        .process_group(0)
+        .spawn()
+        .map_err(|e| ProcessError::Spawn(e.to_string()))?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Ok(client) = PluginClient::connect(&slot.endpoint()) {
+            slot.write_state(&DaemonState {
+                plugin: entry.name.clone(),
+                slot: slot.index,
+                pid: child.id(),
+                version: entry.version.clone(),
+                installed_at: entry.installed_at.clone(),
+                started_at: chrono::Utc::now().to_rfc3339(),
+                idle_timeout_secs: config.idle_timeout.as_secs(),
+            })?;
+            return Ok(Some(Checkout {
+                client,
+                lease,
+                started: true,
+            }));
+        }
+        if let Ok(Some(status)) = child.try_wait() {
+            output::warning(&format!(
+                "Plugin {} exited ({}) instead of running as a daemon, starting it without the pool (log: {})",
+                entry.name,
+                status,
+                log_path.display()
+            ));
+            return Ok(None);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(ProcessError::Spawn(format!(
+                "{} did not start listening within {:?} (log: {})",
+                entry.name,
+                timeout,
+                log_path.display()
+            )));
+        }
+        std::thread::sleep(CONNECT_POLL_INTERVAL);
+    }
+}
+
+/// List running daemons, forgetting ones that have exited
+pub fn list_daemons() -> Result<Vec<PooledDaemon>, ProcessError> {
+    let mut daemons = Vec::new();
+    for (slot, state) in read_states()? {
+        let busy = match slot.try_lock()? {
+            None => true,
+            Some(_lock) if slot.is_listening() => false,
+            Some(_lock) => {
+                slot.clear();
+                continue;
+            },
+        };
+        daemons.push(PooledDaemon { state, busy });
+    }
+    Ok(daemons)
+}
+
+/// Stop the idle daemons of plugins matching `filter`
+pub fn stop_daemons(filter: impl Fn(&str) -> bool) -> Result<StopReport, ProcessError> {
+    let mut report = StopReport::default();
+    for (slot, state) in read_states()? {
+        if !filter(&state.plugin) {
+            continue;
+        }
+        match slot.try_lock()? {
+            None => report.busy.push(state),
+            Some(_lock) => {
+                if slot.stop() {
+                    report.stopped.push(state);
+                }
+            },
+        }
+    }
+    Ok(report)
+}
+
+/// Read every slot's state, ordered by plugin and slot
+fn read_states() -> Result<Vec<(Slot, DaemonState)>, ProcessError> {
+    let dir = pool_dir()?;
+    let entries = std::fs::read_dir(&dir).map_err(|e| pool_error(&dir, e))?;
+    let mut states: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| {
+            let content = std::fs::read_to_string(entry.path()).ok()?;
+            let state: DaemonState = serde_json::from_str(&content).ok()?;
+            Some((Slot::new(&dir, &state.plugin, state.slot), state))
+        })
+        .collect();
+    states.sort_by(|(_, a), (_, b)| (&a.plugin, a.slot).cmp(&(&b.plugin, b.slot)));
+    Ok(states)
+}
+
+fn pool_error(path: &Path, e: std::io::Error) -> ProcessError {
+    ProcessError::Pool(format!("{}: {}", path.display(), e))
+}
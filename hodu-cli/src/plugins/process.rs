@@ -3,19 +3,35 @@
 //! This module provides a unified plugin manager for the CLI that handles
 //! both format and backend plugins with CLI-specific notification handling.
 
+use super::config::{ConfigError, PluginConfig};
+#[cfg(unix)]
+use super::pool::{checkout_daemon, stop_daemons, Lease};
+use super::{backend_plugin_name, format_plugin_name};
+use crate::config::Settings;
+use crate::logging::{self, LogLevel, PLUGIN_DEFAULT_LEVEL};
 use crate::output;
-use hodu_plugin::rpc::{methods, InitializeResult, LogParams};
+use hodu_plugin::rpc::{methods, InitializeResult, LogParams, ProgressParams, TRACE_LEVEL_ENV};
+use hodu_plugin::trace::RPC_TRACE_ENV;
+use hodu_plugin::SandboxPolicy;
 use hodu_plugin_runtime::{
-    CancellationHandle, ClientError, PluginClient, PluginEntry, PluginRegistry, RegistryError, DEFAULT_TIMEOUT,
+    tie_to_parent, CancellationHandle, ClientError, HostServices, PluginClient, PluginEntry, PluginRegistry,
+    PluginSource, RegistryError, RpcRecorder,
 };
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
 
 /// Maximum number of concurrent plugin processes
 const MAX_PLUGIN_PROCESSES: usize = 16;
 
+/// Shared memory directory plugins write outputs to, where the system has one
+const SHARED_MEMORY_DIR: &str = "/dev/shm";
+
 /// Timeout for plugin spawn and initialization (30 seconds)
 const PLUGIN_SPAWN_TIMEOUT: Duration = Duration::from_secs(30);
 
@@ -32,27 +48,74 @@ pub struct PluginManager {
     plugins_dir: PathBuf,
     /// Timeout for plugin operations
     timeout: Duration,
+    /// Directory of cached build artifacts, which sandboxed plugins may write to
+    cache_dir: PathBuf,
+    /// Answers requests plugins make back to the CLI, shared by all of them
+    host: Arc<Mutex<HostServices>>,
+    /// Configuration sent to each plugin in `initialize`
+    config: PluginConfig,
+    /// File receiving every plugin log as a JSON line
+    trace_file: Option<Arc<Mutex<File>>>,
+    /// File recording every JSON-RPC message exchanged with plugins, from `HODU_RPC_TRACE`
+    rpc_trace: Option<Arc<Mutex<File>>>,
+    /// Connections opened so far, numbering them in the RPC trace
+    connections: AtomicUsize,
+    /// Paths the command hands to plugins, which sandboxed plugins may use besides the defaults
+    sandbox_paths: SandboxPolicy,
 }
 
-/// A managed plugin process
+/// A managed plugin process, or a connection to a remote or pooled plugin daemon
 struct ManagedPlugin {
-    child: Child,
+    /// Shared with the client's exit check for plugins with resource limits
+    child: Option<Arc<Mutex<Child>>>,
     client: PluginClient,
     info: InitializeResult,
+    /// Slot of a pooled daemon, held while this manager uses the daemon
+    #[cfg(unix)]
+    lease: Option<Lease>,
 }
 
 impl PluginManager {
-    /// Create a new plugin manager
+    /// Create a new plugin manager, configuring plugins from ~/.hodu/config.toml
     pub fn new() -> Result<Self, ProcessError> {
+        Self::with_config(PluginConfig::load().map_err(ProcessError::Config)?)
+    }
+
+    /// Create a plugin manager that starts plugins without any configuration
+    pub fn without_config() -> Result<Self, ProcessError> {
+        Self::with_config(PluginConfig::default())
+    }
+
+    fn with_config(config: PluginConfig) -> Result<Self, ProcessError> {
+        let settings = Settings::load().map_err(ProcessError::Config)?;
         let registry_path = PluginRegistry::default_path().map_err(ProcessError::Registry)?;
         let registry = PluginRegistry::load(&registry_path).map_err(ProcessError::Registry)?;
         let plugins_dir = PluginRegistry::plugins_dir().map_err(ProcessError::Registry)?;
+        // Appended to, as `hodu trace record` may run commands that start several managers
+        let rpc_trace = match std::env::var_os(RPC_TRACE_ENV) {
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .map_err(|e| ProcessError::TraceFile(format!("{}: {}", Path::new(&path).display(), e)))?;
+                Some(Arc::new(Mutex::new(file)))
+            },
+            None => None,
+        };
 
         Ok(Self {
             processes: HashMap::new(),
             registry,
             plugins_dir,
-            timeout: DEFAULT_TIMEOUT,
+            timeout: Duration::from_secs(settings.timeout()),
+            cache_dir: settings.cache_dir().to_path_buf(),
+            host: Arc::new(Mutex::new(HostServices::new())),
+            config,
+            trace_file: None,
+            rpc_trace,
+            connections: AtomicUsize::new(0),
+            sandbox_paths: SandboxPolicy::new(),
         })
     }
 
@@ -68,6 +131,44 @@ impl PluginManager {
         self.timeout = Duration::from_secs(timeout_secs);
     }
 
+    /// Override plugin config values (`PLUGIN.KEY=VALUE`) for plugins started from now on
+    pub fn add_config_overrides(&mut self, specs: &[String]) -> Result<(), ProcessError> {
+        for spec in specs {
+            // Accept short plugin names, as elsewhere on the command line
+            let spec = match spec.split_once('.') {
+                Some((plugin, rest)) if self.registry.find(plugin).is_none() => {
+                    [backend_plugin_name(plugin), format_plugin_name(plugin)]
+                        .into_iter()
+                        .find(|name| self.registry.find(name).is_some())
+                        .map_or_else(|| spec.clone(), |name| format!("{}.{}", name, rest))
+                },
+                _ => spec.clone(),
+            };
+            self.config.add_override(&spec).map_err(ProcessError::Config)?;
+        }
+        Ok(())
+    }
+
+    /// Let sandboxed plugins started from now on read `path` (a file or a directory)
+    pub fn allow_read(&mut self, path: impl AsRef<Path>) {
+        self.sandbox_paths.allow_read(path);
+    }
+
+    /// Let sandboxed plugins started from now on read and write `path` (a file or a directory)
+    pub fn allow_write(&mut self, path: impl AsRef<Path>) {
+        self.sandbox_paths.allow_write(path);
+    }
+
+    /// Write the logs of plugins started from now on to `path`, one JSON object per line
+    ///
+    /// Plugins are asked to forward their tracing events down to `debug` level, unless
+    /// `HODU_PLUGIN_TRACE` already picks a level.
+    pub fn set_trace_file(&mut self, path: &Path) -> Result<(), ProcessError> {
+        let file = File::create(path).map_err(|e| ProcessError::TraceFile(format!("{}: {}", path.display(), e)))?;
+        self.trace_file = Some(Arc::new(Mutex::new(file)));
+        Ok(())
+    }
+
     /// Get or spawn a plugin by name
     pub fn get_plugin(&mut self, name: &str) -> Result<&mut PluginClient, ProcessError> {
         // Check if already running
@@ -130,30 +231,142 @@ impl PluginManager {
         self.get_plugin(&entry.name)
     }
 
-    /// Spawn a plugin process
+    /// Spawn a plugin process, or connect to it if it runs remotely or in the pool
     fn spawn_plugin(&self, entry: &PluginEntry) -> Result<ManagedPlugin, ProcessError> {
-        let binary_path = self.plugins_dir.join(&entry.name).join(&entry.binary);
+        let (child, mut client) = match &entry.source {
+            PluginSource::Remote { endpoint } => {
+                let client = PluginClient::connect_remote(endpoint).map_err(ProcessError::Client)?;
+                (None, client)
+            },
+            _ => {
+                let binary_path = self.plugins_dir.join(&entry.name).join(&entry.binary);
 
-        if !binary_path.exists() {
-            return Err(ProcessError::BinaryNotFound(binary_path.to_string_lossy().to_string()));
-        }
+                if !binary_path.exists() {
+                    return Err(ProcessError::BinaryNotFound(binary_path.to_string_lossy().to_string()));
+                }
+
+                // Pooled daemons were started with another environment, so tracing or a more verbose
+                // log level needs a fresh process. A CPU limit would count every command a daemon
+                // served, so limited plugins get one too
+                let log_level = match self.trace_file {
+                    Some(_) => logging::level_for(&entry.name).max(LogLevel::Debug),
+                    None => logging::level_for(&entry.name),
+                };
+                #[cfg(unix)]
+                if self.config.pool().enabled && log_level <= PLUGIN_DEFAULT_LEVEL && entry.limits.is_empty() {
+                    if let Some(managed) = self.checkout_pooled(entry, &binary_path)? {
+                        return Ok(managed);
+                    }
+                }
+
+                // Spawn process
+                let mut command = Command::new(&binary_path);
+                command
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::inherit());
+                if log_level > PLUGIN_DEFAULT_LEVEL && std::env::var_os(TRACE_LEVEL_ENV).is_none() {
+                    command.env(TRACE_LEVEL_ENV, log_level.as_str());
+                }
+                let limits = entry.limits;
+                limits.apply(&mut command);
+                let mut child = command.spawn().map_err(|e| ProcessError::Spawn(e.to_string()))?;
+                tie_to_parent(&child);
+                limits.bind(&child);
+
+                // Create client, which reports a plugin killed at its limits as RESOURCE_EXHAUSTED
+                let mut client = PluginClient::new(&mut child).map_err(ProcessError::Client)?;
+                let child = Arc::new(Mutex::new(child));
+                if !limits.is_empty() {
+                    let child = Arc::clone(&child);
+                    client.set_exit_check(Box::new(move || {
+                        limits.check_exit(&mut child.lock().unwrap_or_else(PoisonError::into_inner))
+                    }));
+                }
+                (Some(child), client)
+            },
+        };
 
-        // Spawn process
-        let mut child = Command::new(&binary_path)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::inherit())
-            .spawn()
-            .map_err(|e| ProcessError::Spawn(e.to_string()))?;
+        let info = self.initialize_client(entry, &mut client)?;
 
-        // Create client
-        let mut client = PluginClient::new(&mut child).map_err(ProcessError::Client)?;
+        Ok(ManagedPlugin {
+            child,
+            client,
+            info,
+            #[cfg(unix)]
+            lease: None,
+        })
+    }
 
+    /// Use a daemon from the pool, or `None` to spawn the plugin as a child instead
+    #[cfg(unix)]
+    fn checkout_pooled(&self, entry: &PluginEntry, binary_path: &Path) -> Result<Option<ManagedPlugin>, ProcessError> {
+        // A daemon reaching its idle timeout as it is handed out fails to initialize, so retry once
+        for _ in 0..2 {
+            let Some(mut checkout) = checkout_daemon(entry, binary_path, self.config.pool(), PLUGIN_SPAWN_TIMEOUT)?
+            else {
+                return Ok(None);
+            };
+            match self.initialize_client(entry, &mut checkout.client) {
+                Ok(info) => {
+                    return Ok(Some(ManagedPlugin {
+                        child: None,
+                        client: checkout.client,
+                        info,
+                        lease: Some(checkout.lease),
+                    }))
+                },
+                Err(e) if checkout.started => return Err(e),
+                Err(_) => checkout.lease.discard(),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Set up the CLI's handlers on a new client and initialize the plugin
+    fn initialize_client(
+        &self,
+        entry: &PluginEntry,
+        client: &mut PluginClient,
+    ) -> Result<InitializeResult, ProcessError> {
         // Set spawn timeout for initialization (shorter than operation timeout)
         client.set_timeout(PLUGIN_SPAWN_TIMEOUT);
 
-        // Set CLI-specific notification handler
-        client.set_notification_handler(Box::new(cli_notification_handler));
+        if let Some(file) = &self.rpc_trace {
+            let connection = self.connections.fetch_add(1, Ordering::Relaxed) + 1;
+            let label = format!("{}#{}.{}", entry.name, std::process::id(), connection);
+            client.set_recorder(RpcRecorder::new(Arc::clone(file), label));
+        }
+
+        if let Some(config) = self.config.for_plugin(&entry.name) {
+            client.set_config(config);
+        }
+        if !entry.trusted {
+            client.set_sandbox(self.sandbox_policy(entry));
+        }
+
+        // Set CLI-specific notification handler, recording logs when tracing to a file
+        let trace_file = self.trace_file.clone();
+        let plugin = entry.name.clone();
+        client.set_notification_handler(Box::new(move |method, params| {
+            if let (Some(file), Some(params)) = (&trace_file, params) {
+                if method == methods::NOTIFY_LOG {
+                    write_trace_record(file, &plugin, params);
+                }
+            }
+            cli_notification_handler(&plugin, method, params);
+        }));
+
+        // Let the plugin delegate loading files to other installed plugins, within its sandbox
+        let host = Arc::clone(&self.host);
+        let sandbox = client.sandbox().cloned();
+        client.set_request_handler(Box::new(move |method, params| {
+            let mut host = host.lock().unwrap_or_else(PoisonError::into_inner);
+            match &sandbox {
+                Some(sandbox) => host.handle_sandboxed(method, params, sandbox),
+                None => host.handle(method, params),
+            }
+        }));
 
         // Initialize with spawn timeout
         let info = client.initialize().map_err(ProcessError::Client)?;
@@ -161,18 +374,64 @@ impl PluginManager {
         // Set operation timeout for subsequent calls
         client.set_timeout(self.timeout);
 
-        Ok(ManagedPlugin { child, client, info })
+        Ok(info)
+    }
+
+    /// Directories an untrusted plugin is confined to
+    ///
+    /// It may read its own install directory, the working directory and the paths the command
+    /// allowed, and write the temp directory, shared memory, the build cache and the paths the
+    /// command allowed for writing.
+    fn sandbox_policy(&self, entry: &PluginEntry) -> SandboxPolicy {
+        let mut policy = self.sandbox_paths.clone();
+        policy.allow_read(self.plugins_dir.join(&entry.name));
+        if let Ok(cwd) = std::env::current_dir() {
+            policy.allow_read(cwd);
+        }
+        policy.allow_write(std::env::temp_dir());
+        let shared_memory = Path::new(SHARED_MEMORY_DIR);
+        if shared_memory.is_dir() {
+            policy.allow_write(shared_memory);
+        }
+        policy.allow_write(&self.cache_dir);
+        policy
     }
 
     /// Shutdown a specific plugin
+    ///
+    /// A pooled daemon only ends the connection, and its slot is released for the next command.
     pub fn shutdown_plugin(&mut self, name: &str) -> Result<(), ProcessError> {
         if let Some(mut managed) = self.processes.remove(name) {
             let _ = managed.client.shutdown();
-            let _ = managed.child.wait();
+            if let Some(child) = managed.child {
+                let _ = child.lock().unwrap_or_else(PoisonError::into_inner).wait();
+            }
+            // The daemon accepts the next connection once it is done with this one
+            #[cfg(unix)]
+            drop(managed.lease);
         }
         Ok(())
     }
 
+    /// Make the next use of a plugin start it anew, after its binary was replaced
+    ///
+    /// Ends the plugin's process, and its pooled daemons, which would keep running the old binary.
+    pub fn restart_plugin(&mut self, name: &str) -> Result<(), ProcessError> {
+        self.shutdown_plugin(name)?;
+        #[cfg(unix)]
+        stop_daemons(|plugin| plugin == name)?;
+        Ok(())
+    }
+
+    /// Binary of an installed plugin, `None` for plugins reached over a socket
+    pub fn plugin_binary_path(&self, name: &str) -> Option<PathBuf> {
+        let entry = self.registry.find(name)?;
+        match entry.source {
+            PluginSource::Remote { .. } => None,
+            _ => Some(self.plugins_dir.join(&entry.name).join(&entry.binary)),
+        }
+    }
+
     /// Shutdown all plugins
     pub fn shutdown_all(&mut self) {
         let names: Vec<String> = self.processes.keys().cloned().collect();
@@ -186,6 +445,11 @@ impl PluginManager {
         self.processes.get(name).map(|p| &p.info)
     }
 
+    /// Config sent to a plugin in `initialize`, with command-line overrides applied
+    pub fn plugin_config(&self, name: &str) -> Option<serde_json::Value> {
+        self.config.for_plugin(name)
+    }
+
     /// Get cancellation handle for a plugin (for Ctrl+C handling)
     pub fn get_cancellation_handle(&self, name: &str) -> Option<CancellationHandle> {
         self.processes.get(name).map(|p| p.client.cancellation_handle())
@@ -198,22 +462,20 @@ impl Drop for PluginManager {
     }
 }
 
-/// CLI-specific notification handler that uses Cargo-style output
-fn cli_notification_handler(method: &str, params: Option<&serde_json::Value>) {
+/// CLI-specific notification handler: Cargo-style progress, and logs through [`logging`]
+fn cli_notification_handler(plugin: &str, method: &str, params: Option<&serde_json::Value>) {
     match method {
         methods::NOTIFY_PROGRESS => {
-            // Progress notifications are silent - status is shown via output module
+            if let Some(params) = params {
+                if let Ok(p) = serde_json::from_value::<ProgressParams>(params.clone()) {
+                    show_progress(&p);
+                }
+            }
         },
         methods::NOTIFY_LOG => {
             if let Some(params) = params {
                 if let Ok(p) = serde_json::from_value::<LogParams>(params.clone()) {
-                    match p.level.to_lowercase().as_str() {
-                        "error" => output::error(&p.message),
-                        "warn" | "warning" => output::warning(&p.message),
-                        _ => {
-                            // Info and debug logs are silent in normal mode
-                        },
-                    }
+                    logging::log(plugin, &p);
                 }
             }
         },
@@ -223,6 +485,59 @@ fn cli_notification_handler(method: &str, params: Option<&serde_json::Value>) {
     }
 }
 
+/// Append one plugin log to the trace file, tagged with the plugin and the time it arrived
+fn write_trace_record(file: &Mutex<File>, plugin: &str, params: &serde_json::Value) {
+    let mut record = serde_json::Map::new();
+    record.insert("time".to_string(), chrono::Utc::now().to_rfc3339().into());
+    record.insert("plugin".to_string(), plugin.into());
+    if let Some(params) = params.as_object() {
+        record.extend(params.clone());
+    }
+    let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Err(e) = writeln!(file, "{}", serde_json::Value::Object(record)) {
+        output::warning(&format!("Failed to write trace file: {}", e));
+    }
+}
+
+/// Draw plugin progress as an overall bar, with a nested bar for the current stage if named
+fn show_progress(p: &ProgressParams) {
+    let one_line = |s: &str| output::sanitize_for_terminal(s).replace('\n', " ");
+
+    let mut counts = Vec::new();
+    if let (Some(done), Some(total)) = (p.done, p.total) {
+        counts.push(format!("{}/{}", done, total));
+    }
+    if let Some(eta) = p.eta_secs.filter(|eta| eta.is_finite() && *eta >= 0.0) {
+        counts.push(format!("ETA {}", output::format_duration(eta)));
+    }
+    let counts = counts.join(", ");
+
+    let mut overall = output::ProgressBar {
+        label: "Progress",
+        percent: p.percent,
+        detail: one_line(&p.message),
+    };
+    let stage = p.stage.as_deref().map(one_line);
+    let mut bars = Vec::with_capacity(2);
+    match &stage {
+        Some(stage) => {
+            bars.push(overall);
+            bars.push(output::ProgressBar {
+                label: stage,
+                percent: p.effective_stage_percent(),
+                detail: counts,
+            });
+        },
+        None => {
+            if !counts.is_empty() {
+                overall.detail = format!("{} ({})", overall.detail, counts);
+            }
+            bars.push(overall);
+        },
+    }
+    output::draw_progress(&bars);
+}
+
 /// Process management errors
 #[derive(Debug)]
 pub enum ProcessError {
@@ -234,7 +549,10 @@ pub enum ProcessError {
     BinaryNotFound(String),
     Spawn(String),
     Client(ClientError),
+    Config(ConfigError),
+    TraceFile(String),
     TooManyProcesses(usize),
+    Pool(String),
 }
 
 impl std::fmt::Display for ProcessError {
@@ -258,9 +576,12 @@ impl std::fmt::Display for ProcessError {
             },
             ProcessError::Spawn(e) => write!(f, "Failed to spawn plugin: {}", e),
             ProcessError::Client(e) => write!(f, "Client error: {}", e),
+            ProcessError::Config(e) => write!(f, "Config error: {}", e),
+            ProcessError::TraceFile(e) => write!(f, "Failed to open trace file: {}", e),
             ProcessError::TooManyProcesses(max) => {
                 write!(f, "Too many plugin processes (max: {})", max)
             },
+            ProcessError::Pool(e) => write!(f, "Plugin pool error: {}", e),
         }
     }
 }
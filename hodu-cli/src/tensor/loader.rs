@@ -1,15 +1,36 @@
 //! Tensor loading utilities
 
-use crate::utils::core_dtype_to_plugin;
-use hodu_core::format::hdt;
+use crate::utils::{core_dtype_to_plugin, plugin_dtype_to_core};
+use hodu_core::format::csv::{self, CsvOptions};
+use hodu_core::format::image::{self, Channels, ImageLayout, ImageOptions};
+use hodu_core::format::parquet::{self, ParquetOptions};
+use hodu_core::format::{hdt, npy, npz, wav};
+use hodu_core::tensor::Tensor;
 use hodu_plugin::{PluginDType, TensorData};
 use std::path::Path;
 
 /// Maximum file size for tensor loading (100 MB)
 const MAX_TENSOR_FILE_SIZE: u64 = 100 * 1024 * 1024;
 
+/// Extensions of tabular formats, whose columns can be selected with a `#col,col` suffix
+const TABLE_EXTENSIONS: &[&str] = &["csv", "tsv", "parquet"];
+
+/// Split a `path#col,col` input spec into the path and the selected columns
+///
+/// Only tabular files take a column selection; other paths are returned whole.
+pub fn split_column_selection(spec: &str) -> (&str, Option<Vec<String>>) {
+    if let Some((path, columns)) = spec.rsplit_once('#') {
+        let ext = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+        if TABLE_EXTENSIONS.contains(&ext) {
+            return (path, Some(columns.split(',').map(|c| c.trim().to_string()).collect()));
+        }
+    }
+    (spec, None)
+}
+
 pub fn load_tensor_file(
     path: &Path,
+    columns: Option<&[String]>,
     expected_shape: &[usize],
     expected_dtype: PluginDType,
 ) -> Result<TensorData, Box<dyn std::error::Error>> {
@@ -25,11 +46,25 @@ pub fn load_tensor_file(
     }
 
     let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if columns.is_some() && !TABLE_EXTENSIONS.contains(&ext) {
+        return Err(format!("Column selection only applies to tabular inputs, not .{}", ext).into());
+    }
 
     match ext {
         "hdt" => load_tensor_hdt(path, expected_shape, expected_dtype),
         "json" => load_tensor_json(path, expected_shape, expected_dtype),
-        _ => Err(format!("Unsupported tensor format: .{}\nSupported: .hdt, .json", ext).into()),
+        "npy" => load_tensor_npy(path, expected_shape, expected_dtype),
+        "npz" => load_tensor_npz(path, expected_shape, expected_dtype),
+        "csv" | "tsv" => load_tensor_csv(path, columns, expected_shape, expected_dtype),
+        "parquet" => load_tensor_parquet(path, columns, expected_shape, expected_dtype),
+        "png" | "jpg" | "jpeg" => load_tensor_image(path, expected_shape, expected_dtype),
+        "wav" => load_tensor_wav(path, expected_shape, expected_dtype),
+        _ => Err(format!(
+            "Unsupported tensor format: .{}\nSupported: .hdt, .json, .npy, .npz, .csv, .tsv, .parquet, .png, .jpg, \
+             .wav",
+            ext
+        )
+        .into()),
     }
 }
 
@@ -39,7 +74,147 @@ fn load_tensor_hdt(
     expected_dtype: PluginDType,
 ) -> Result<TensorData, Box<dyn std::error::Error>> {
     let tensor = hdt::load(path).map_err(|e| format!("Failed to load HDT file: {}", e))?;
+    tensor_to_data(&tensor, expected_shape, expected_dtype)
+}
+
+fn load_tensor_npy(
+    path: &Path,
+    expected_shape: &[usize],
+    expected_dtype: PluginDType,
+) -> Result<TensorData, Box<dyn std::error::Error>> {
+    let tensor = npy::load(path).map_err(|e| format!("Failed to load NPY file: {}", e))?;
+    tensor_to_data(&tensor, expected_shape, expected_dtype)
+}
+
+/// An input file holds one tensor, so the archive must contain exactly one array
+fn load_tensor_npz(
+    path: &Path,
+    expected_shape: &[usize],
+    expected_dtype: PluginDType,
+) -> Result<TensorData, Box<dyn std::error::Error>> {
+    let tensors = npz::load_many(path).map_err(|e| format!("Failed to load NPZ file: {}", e))?;
+    if tensors.len() != 1 {
+        let mut names: Vec<&String> = tensors.keys().collect();
+        names.sort();
+        return Err(format!(
+            "NPZ input must contain exactly one array, found {}: {:?}",
+            tensors.len(),
+            names
+        )
+        .into());
+    }
+    let tensor = tensors.into_values().next().ok_or("NPZ file is empty")?;
+    tensor_to_data(&tensor, expected_shape, expected_dtype)
+}
+
+/// Tables load as [rows, columns], converted to the dtype the model expects
+fn load_tensor_csv(
+    path: &Path,
+    columns: Option<&[String]>,
+    expected_shape: &[usize],
+    expected_dtype: PluginDType,
+) -> Result<TensorData, Box<dyn std::error::Error>> {
+    let mut options = CsvOptions::default().with_dtype(plugin_dtype_to_core(expected_dtype)?);
+    if path.extension().and_then(|e| e.to_str()) == Some("tsv") {
+        options = options.with_delimiter(b'\t');
+    }
+    options.columns = columns.map(<[String]>::to_vec);
+    let tensor = csv::load_with(path, &options).map_err(|e| format!("Failed to load CSV file: {}", e))?;
+    tensor_to_data(&tensor, expected_shape, expected_dtype)
+}
 
+fn load_tensor_parquet(
+    path: &Path,
+    columns: Option<&[String]>,
+    expected_shape: &[usize],
+    expected_dtype: PluginDType,
+) -> Result<TensorData, Box<dyn std::error::Error>> {
+    let mut options = ParquetOptions::default().with_dtype(plugin_dtype_to_core(expected_dtype)?);
+    options.columns = columns.map(<[String]>::to_vec);
+    let tensor = parquet::load_with(path, &options).map_err(|e| format!("Failed to load Parquet file: {}", e))?;
+    tensor_to_data(&tensor, expected_shape, expected_dtype)
+}
+
+/// Images are resized and laid out to fit the input: [H, W, C] or [C, H, W] with 1, 3 or 4
+/// channels, optionally behind a batch dimension of 1. Float inputs get pixels scaled to [0, 1].
+fn load_tensor_image(
+    path: &Path,
+    expected_shape: &[usize],
+    expected_dtype: PluginDType,
+) -> Result<TensorData, Box<dyn std::error::Error>> {
+    let dims = match expected_shape {
+        [1, rest @ ..] if rest.len() == 3 => rest,
+        dims => dims,
+    };
+    let channels = |c: usize| match c {
+        1 => Some(Channels::Gray),
+        3 => Some(Channels::Rgb),
+        4 => Some(Channels::Rgba),
+        _ => None,
+    };
+    let (layout, channels, height, width) = match *dims {
+        [h, w, c] if channels(c).is_some() => (ImageLayout::HWC, channels(c), h, w),
+        [c, h, w] => (ImageLayout::CHW, channels(c), h, w),
+        [h, w] => (ImageLayout::HWC, Some(Channels::Gray), h, w),
+        _ => (ImageLayout::HWC, None, 0, 0),
+    };
+    let channels = channels.ok_or_else(|| {
+        format!(
+            "Image inputs need a [H, W, C] or [C, H, W] shape with 1, 3 or 4 channels, model expects {:?}",
+            expected_shape
+        )
+    })?;
+
+    let options = ImageOptions::default()
+        .with_channels(channels)
+        .with_layout(layout)
+        .with_dtype(plugin_dtype_to_core(expected_dtype)?)
+        .with_resize(u32::try_from(width)?, u32::try_from(height)?);
+    let tensor = image::load_with(path, &options).map_err(|e| format!("Failed to load image: {}", e))?;
+    let tensor = tensor
+        .reshape(expected_shape)
+        .map_err(|e| format!("Failed to reshape image: {}", e))?;
+    tensor_to_data(&tensor, expected_shape, expected_dtype)
+}
+
+/// Audio loads as [channels, samples] f32; an input of the same size but another rank (e.g.
+/// [samples] or [1, samples] for mono) is filled by reshaping
+fn load_tensor_wav(
+    path: &Path,
+    expected_shape: &[usize],
+    expected_dtype: PluginDType,
+) -> Result<TensorData, Box<dyn std::error::Error>> {
+    let audio = wav::load(path).map_err(|e| format!("Failed to load WAV file: {}", e))?;
+    let dims = audio.samples.shape().dims().to_vec();
+    if dims.iter().product::<usize>() != expected_shape.iter().product::<usize>() {
+        return Err(format!(
+            "Shape mismatch: audio is {:?} ({} channels, {} Hz), model expects {:?}",
+            dims,
+            audio.channels(),
+            audio.sample_rate,
+            expected_shape
+        )
+        .into());
+    }
+
+    let dtype = plugin_dtype_to_core(expected_dtype)?;
+    let mut tensor = audio
+        .samples
+        .reshape(expected_shape)
+        .map_err(|e| format!("Failed to reshape audio: {}", e))?;
+    if dtype.is_float() && tensor.dtype() != dtype {
+        tensor = tensor
+            .to_dtype(dtype)
+            .map_err(|e| format!("Failed to convert audio: {}", e))?;
+    }
+    tensor_to_data(&tensor, expected_shape, expected_dtype)
+}
+
+fn tensor_to_data(
+    tensor: &Tensor,
+    expected_shape: &[usize],
+    expected_dtype: PluginDType,
+) -> Result<TensorData, Box<dyn std::error::Error>> {
     let shape: Vec<usize> = tensor.shape().dims().to_vec();
     let dtype: PluginDType = core_dtype_to_plugin(tensor.dtype());
 
@@ -2,9 +2,11 @@
 
 mod loader;
 mod saver;
+mod stream;
 
-pub use loader::{load_tensor_file, str_to_plugin_dtype};
+pub use loader::{load_tensor_file, split_column_selection, str_to_plugin_dtype};
 pub use saver::save_outputs;
+pub use stream::stream_tensor_to_hdt;
 
 use crate::utils::{core_dtype_to_plugin, plugin_dtype_to_core};
 use hodu_core::format::hdt;
@@ -1,7 +1,7 @@
 //! Tensor saving utilities
 
 use crate::utils::plugin_dtype_to_core;
-use hodu_core::format::{hdt, json};
+use hodu_core::format::{hdt, hdta, json, npy};
 use hodu_core::tensor::Tensor;
 use hodu_core::types::{Device as CoreDevice, Shape};
 use hodu_plugin::TensorData;
@@ -40,11 +40,26 @@ fn validate_output_name(name: &str) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn to_tensor(data: &TensorData) -> Result<Tensor, Box<dyn std::error::Error>> {
+    let dtype = plugin_dtype_to_core(data.dtype)?;
+    let shape = Shape::new(&data.shape);
+    Ok(Tensor::from_bytes(&data.data, shape, dtype, CoreDevice::CPU)
+        .map_err(|e| format!("Failed to create tensor: {}", e))?)
+}
+
+/// Save outputs as `<name>.<format>` files in `save_dir`
+///
+/// The `hdta` format writes a single archive instead: `save_dir` itself when it ends in `.hdta`,
+/// otherwise `outputs.hdta` inside it.
 pub fn save_outputs(
     outputs: &HashMap<String, TensorData>,
     save_dir: &Path,
     format: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    if format.eq_ignore_ascii_case("hdta") {
+        return save_archive(outputs, save_dir);
+    }
+
     std::fs::create_dir_all(save_dir)?;
 
     for (name, data) in outputs {
@@ -52,18 +67,37 @@ pub fn save_outputs(
         validate_output_name(name)?;
 
         let file_path = save_dir.join(format!("{}.{}", name, format.to_lowercase()));
-
-        let dtype = plugin_dtype_to_core(data.dtype)?;
-        let shape = Shape::new(&data.shape);
-        let tensor = Tensor::from_bytes(&data.data, shape, dtype, CoreDevice::CPU)
-            .map_err(|e| format!("Failed to create tensor: {}", e))?;
+        let tensor = to_tensor(data)?;
 
         match format.to_lowercase().as_str() {
             "hdt" => hdt::save(&tensor, &file_path)?,
             "json" => json::save(&tensor, &file_path)?,
+            "npy" => npy::save(&tensor, &file_path)?,
             _ => return Err(format!("Unsupported save format: {}", format).into()),
         }
     }
 
     Ok(())
 }
+
+fn save_archive(outputs: &HashMap<String, TensorData>, save_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let archive_path = if save_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("hdta"))
+    {
+        if let Some(parent) = save_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        save_path.to_path_buf()
+    } else {
+        std::fs::create_dir_all(save_path)?;
+        save_path.join("outputs.hdta")
+    };
+
+    let tensors = outputs
+        .iter()
+        .map(|(name, data)| Ok((name.clone(), to_tensor(data)?)))
+        .collect::<Result<HashMap<_, _>, Box<dyn std::error::Error>>>()?;
+    hdta::save(&tensors, &archive_path)?;
+    Ok(())
+}
@@ -0,0 +1,176 @@
+//! Assembling tensors streamed by `format.stream_load_tensor`
+
+use crate::plugins::PluginClient;
+use crate::utils::{path_to_str, plugin_dtype_to_core};
+use hodu_core::format::hdt;
+use hodu_core::types::Shape;
+use hodu_plugin::rpc::{StreamEvent, StreamParams, TensorChunk, TENSOR_STREAM};
+use hodu_plugin::SandboxPolicy;
+use std::fs::File;
+use std::io::{BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Load the tensor file `input` through a format plugin's `format.stream_load_tensor`, writing
+/// it to the .hdt file `output` as the chunks arrive
+///
+/// Only one chunk is held in memory at a time, so tensors larger than RAM can be imported.
+/// Returns the bytes of tensor data written.
+pub fn stream_tensor_to_hdt(
+    client: &mut PluginClient,
+    input: &Path,
+    output: &Path,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let assembler = Arc::new(Mutex::new(Assembler::new(
+        output.to_path_buf(),
+        client.sandbox().cloned(),
+    )));
+    let handler_assembler = Arc::clone(&assembler);
+    client.set_stream_handler(Box::new(move |params: &StreamParams| {
+        if params.stream != TENSOR_STREAM {
+            return;
+        }
+        if let StreamEvent::Chunk { data, .. } = &params.event {
+            if let Ok(mut assembler) = handler_assembler.lock() {
+                assembler.receive(data);
+            }
+        }
+    }));
+    let result = client.stream_load_tensor(path_to_str(input)?, None);
+    client.take_stream_handler();
+    let result = result?;
+
+    let mut assembler = assembler.lock().map_err(|_| "Tensor stream state was poisoned")?;
+    if let Some(error) = assembler.error.take() {
+        return Err(error.into());
+    }
+    let writer = assembler
+        .writer
+        .take()
+        .ok_or("Plugin finished without sending the tensor header")?;
+    if assembler.received != result.total_bytes {
+        return Err(format!(
+            "Received {} of the {} bytes of tensor data the plugin sent",
+            assembler.received, result.total_bytes
+        )
+        .into());
+    }
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to finish {}: {}", output.display(), e))?;
+    Ok(assembler.received)
+}
+
+/// Writes streamed chunks to an .hdt file, keeping the first error
+struct Assembler {
+    output: PathBuf,
+    /// Sandbox of the plugin, whose shared chunks must lie inside it
+    sandbox: Option<SandboxPolicy>,
+    writer: Option<hdt::Writer<BufWriter<File>>>,
+    received: u64,
+    error: Option<String>,
+}
+
+impl Assembler {
+    fn new(output: PathBuf, sandbox: Option<SandboxPolicy>) -> Self {
+        Self {
+            output,
+            sandbox,
+            writer: None,
+            received: 0,
+            error: None,
+        }
+    }
+
+    fn receive(&mut self, data: &serde_json::Value) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(e) = self.write(data) {
+            self.error = Some(e);
+        }
+    }
+
+    fn write(&mut self, data: &serde_json::Value) -> Result<(), String> {
+        let chunk: TensorChunk =
+            serde_json::from_value(data.clone()).map_err(|e| format!("Invalid tensor chunk: {}", e))?;
+        match chunk {
+            TensorChunk::Header { shape, dtype } => {
+                if self.writer.is_some() {
+                    return Err("Plugin sent a second tensor header".to_string());
+                }
+                let dtype = plugin_dtype_to_core(dtype).map_err(|e| e.to_string())?;
+                let writer = hdt::Writer::create(&self.output, Shape::new(&shape), dtype)
+                    .map_err(|e| format!("Failed to create {}: {}", self.output.display(), e))?;
+                self.writer = Some(writer);
+            },
+            TensorChunk::Data { offset, data } => {
+                self.check_offset(offset)?;
+                let bytes = hodu_plugin::base64::decode(&data).map_err(|e| e.to_string())?;
+                self.append(&bytes)?;
+            },
+            TensorChunk::Shared { offset, path, len } => {
+                self.check_offset(offset)?;
+                // Never read or remove a file outside the sandbox on the plugin's word
+                if let Some(sandbox) = &self.sandbox {
+                    sandbox.check_write(&path).map_err(|e| e.to_string())?;
+                }
+                let result = self.copy_shared(Path::new(&path), len);
+                // The file is ours to remove once read, even if reading it failed
+                let _ = std::fs::remove_file(&path);
+                result?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Chunks must continue exactly where the previous one ended
+    fn check_offset(&self, offset: u64) -> Result<(), String> {
+        if self.writer.is_none() {
+            return Err("Plugin sent tensor data before the header".to_string());
+        }
+        if offset != self.received {
+            return Err(format!(
+                "Tensor chunk at byte {} arrived out of order (expected byte {})",
+                offset, self.received
+            ));
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or("Plugin sent tensor data before the header")?;
+        writer
+            .write_chunk(bytes)
+            .map_err(|e| format!("Failed to write {}: {}", self.output.display(), e))?;
+        self.received += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Copy a shared chunk in pieces, so it needs no more memory than an inline one
+    fn copy_shared(&mut self, path: &Path, len: u64) -> Result<(), String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open shared chunk {}: {}", path.display(), e))?;
+        let mut file = file.take(len);
+        let mut buf = vec![0u8; hdt::DEFAULT_CHUNK_SIZE.min(len as usize)];
+        let mut copied = 0u64;
+        while copied < len {
+            let n = file
+                .read(&mut buf)
+                .map_err(|e| format!("Failed to read shared chunk {}: {}", path.display(), e))?;
+            if n == 0 {
+                return Err(format!(
+                    "Shared chunk {} holds {} of its {} bytes",
+                    path.display(),
+                    copied,
+                    len
+                ));
+            }
+            self.append(&buf[..n])?;
+            copied += n as u64;
+        }
+        Ok(())
+    }
+}
@@ -4,6 +4,21 @@ use hodu_core::types::DType;
 use hodu_plugin::PluginDType;
 use std::path::Path;
 
+/// Import an ONNX model with the builtin importer into a temporary snapshot file
+///
+/// Used when no model format plugin handles `.onnx`. The snapshot is deleted when the returned
+/// file is dropped.
+pub fn import_onnx(path: &Path) -> Result<tempfile::NamedTempFile, Box<dyn std::error::Error>> {
+    let snapshot = hodu_core::format::onnx::load(path)?;
+    let temp_file = tempfile::Builder::new()
+        .prefix("hodu_onnx_")
+        .suffix(".hdss")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temp file for snapshot: {}", e))?;
+    snapshot.save(temp_file.path())?;
+    Ok(temp_file)
+}
+
 /// Convert a path to a string, returning an error if the path is not valid UTF-8
 pub fn path_to_str(path: &Path) -> Result<&str, Box<dyn std::error::Error>> {
     path.to_str()
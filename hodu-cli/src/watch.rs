@@ -0,0 +1,128 @@
+//! File watching for `--watch`
+//!
+//! Files are polled for changes to their modification time or size. Polling works the same on
+//! every platform, and keeps working when a file is replaced rather than written in place, as
+//! editors and `cargo build` do.
+
+use crate::output;
+use crate::plugins::PluginManager;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How often the files are checked
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long the files must stay unchanged before a change is reported, so that a file written in
+/// several steps, or several files saved together, trigger a single rerun
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Modification time and size of a file, `None` while it doesn't exist
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Files watched for changes
+pub struct Watcher {
+    files: Vec<(PathBuf, Stamp)>,
+}
+
+impl Watcher {
+    /// Watch `paths`, taking their current state as unchanged
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut files: Vec<(PathBuf, Stamp)> = Vec::new();
+        for path in paths {
+            if !files.iter().any(|(watched, _)| *watched == path) {
+                let stamp = stamp(&path);
+                files.push((path, stamp));
+            }
+        }
+        Self { files }
+    }
+
+    /// Print the watched files, so the user knows what triggers a rerun
+    pub fn announce(&self) {
+        let names: Vec<String> = self.files.iter().map(|(path, _)| path.display().to_string()).collect();
+        output::watching(&format!("{} (Ctrl+C to stop)", names.join(", ")));
+    }
+
+    /// Block until files change and then stay unchanged for [`DEBOUNCE`], returning the changed
+    /// files
+    pub fn wait(&mut self) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = Vec::new();
+        let mut settled_at = None;
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let mut any = false;
+            for (path, last) in &mut self.files {
+                let current = stamp(path);
+                if current != *last {
+                    *last = current;
+                    any = true;
+                    if !changed.contains(path) {
+                        changed.push(path.clone());
+                    }
+                }
+            }
+            if any {
+                settled_at = Some(std::time::Instant::now() + DEBOUNCE);
+            }
+            if settled_at.is_some_and(|at| std::time::Instant::now() >= at) {
+                return changed;
+            }
+        }
+    }
+}
+
+/// Print which files changed before a rerun
+fn report_changes(changed: &[PathBuf]) {
+    let names: Vec<String> = changed
+        .iter()
+        .map(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string())
+        })
+        .collect();
+    output::changed(&names.join(", "));
+}
+
+/// Run `iteration`, then run it again after every change to `files` or to the binaries of
+/// `plugins`, until the process is interrupted
+///
+/// Errors of an iteration are printed and the next change reruns it. Plugins stay running
+/// between iterations, except those whose binary changed: they are restarted and passed to the
+/// next iteration.
+pub fn watch(
+    manager: &mut PluginManager,
+    files: impl IntoIterator<Item = PathBuf>,
+    plugins: &[String],
+    mut iteration: impl FnMut(&mut PluginManager, &[String]) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let binaries: Vec<(PathBuf, &String)> = plugins
+        .iter()
+        .filter_map(|plugin| Some((manager.plugin_binary_path(plugin)?, plugin)))
+        .collect();
+    let mut watcher = Watcher::new(files.into_iter().chain(binaries.iter().map(|(path, _)| path.clone())));
+
+    if let Err(e) = iteration(manager, &[]) {
+        output::error(&e.to_string());
+    }
+    watcher.announce();
+    loop {
+        let changed = watcher.wait();
+        report_changes(&changed);
+        let mut restarted = Vec::new();
+        for (binary, plugin) in &binaries {
+            if changed.contains(binary) {
+                manager.restart_plugin(plugin)?;
+                restarted.push(plugin.to_string());
+            }
+        }
+        if let Err(e) = iteration(manager, &restarted) {
+            output::error(&e.to_string());
+        }
+    }
+}
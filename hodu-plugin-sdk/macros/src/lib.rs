@@ -6,9 +6,20 @@ use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::{parse_macro_input, Data, DeriveInput, Fields};
 
-/// Derive macro for creating plugin method handlers with less boilerplate
+/// Derive macro for plugin method handlers and groups of them
 ///
-/// This macro generates the necessary wrapper code for async handlers.
+/// On a struct, names the method its `handle` fn serves: `#[method(name = "...")]` or
+/// `#[method("...")]`, defaulting to the struct name in snake_case. Add `validate` to answer
+/// params failing `ValidateParams` with `INVALID_PARAMS` before `handle` runs. Generates a
+/// `METHOD_NAME` constant and a `PluginMethod` impl whose `register_all` registers `handle`.
+///
+/// On an enum whose variants each wrap a handler (or another group), generates a
+/// `PluginMethod` impl with every handler's name in `METHOD_NAMES` and a `register_all`
+/// registering them all, served with `PluginServer::methods`.
+///
+/// Compile errors catch drift between declared and served methods: a handler without `handle`,
+/// a `backend.*` or `format.*` name that is not a protocol method, and a group serving a method
+/// twice.
 ///
 /// # Example
 ///
@@ -16,7 +27,7 @@ use syn::{parse_macro_input, Data, DeriveInput, Fields};
 /// use hodu_plugin_sdk::PluginMethod;
 ///
 /// #[derive(PluginMethod)]
-/// #[method(name = "backend.run")]
+/// #[method(name = "backend.run", validate)]
 /// struct RunHandler;
 ///
 /// impl RunHandler {
@@ -24,25 +35,119 @@ use syn::{parse_macro_input, Data, DeriveInput, Fields};
 ///         // implementation
 ///     }
 /// }
+///
+/// #[derive(PluginMethod)]
+/// enum Methods {
+///     Run(RunHandler),
+///     Build(BuildHandler),
+/// }
+///
+/// PluginServer::new("my-backend", "0.1.0").methods::<Methods>()
 /// ```
 #[proc_macro_derive(PluginMethod, attributes(method))]
 pub fn derive_plugin_method(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    let result = if !input.generics.params.is_empty() {
+        Err(syn::Error::new_spanned(
+            &input.generics,
+            "#[derive(PluginMethod)] does not support generic types",
+        ))
+    } else {
+        match &input.data {
+            Data::Enum(data) => derive_method_group(&input, data),
+            _ => derive_method_handler(&input),
+        }
+    };
+    match result {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
 
+fn derive_method_handler(input: &DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
     let name = &input.ident;
+    let options = extract_method_options(input)?;
+    let method_name = options.name;
 
-    match extract_method_name(&input) {
-        Ok(method_name) => {
-            let expanded = quote! {
-                impl #name {
-                    /// Get the method name for this handler
-                    pub const METHOD_NAME: &'static str = #method_name;
-                }
-            };
-            TokenStream::from(expanded)
-        },
-        Err(err) => err.to_compile_error().into(),
+    let handler = if options.validate {
+        quote! {
+            |ctx, params| {
+                let call = hodu_plugin_sdk::check_params(&params).map(|()| #name::handle(ctx, params));
+                async move { call?.await }
+            }
+        }
+    } else {
+        quote! { #name::handle }
+    };
+    let unknown = format!(
+        "`{}` is not a protocol method; `backend.*` and `format.*` handlers must serve one of `rpc::methods`",
+        method_name
+    );
+
+    Ok(quote! {
+        impl #name {
+            /// Get the method name for this handler
+            pub const METHOD_NAME: &'static str = #method_name;
+        }
+
+        impl hodu_plugin_sdk::PluginMethod for #name {
+            const METHOD_NAMES: &'static [&'static str] = &[#method_name];
+
+            fn register_all<M: hodu_plugin_sdk::MethodRegistry>(registry: M) -> M {
+                <M as hodu_plugin_sdk::MethodRegistry>::method(registry, #method_name, #handler)
+            }
+        }
+
+        const _: () = ::std::assert!(hodu_plugin_sdk::is_known_method(#method_name), #unknown);
+    })
+}
+
+fn derive_method_group(input: &DeriveInput, data: &syn::DataEnum) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &input.ident;
+    if let Some(attr) = input.attrs.iter().find(|attr| attr.path().is_ident("method")) {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "#[method(...)] goes on the handler structs, not on an enum grouping them",
+        ));
     }
+    if data.variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            input,
+            "a method group needs at least one handler",
+        ));
+    }
+
+    let mut handlers = Vec::new();
+    for variant in &data.variants {
+        match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => handlers.push(&fields.unnamed[0].ty),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "each variant wraps the handler it serves, e.g. `Run(RunHandler)`",
+                ))
+            },
+        }
+    }
+    let duplicate = format!("`{}` serves a method more than once", name);
+
+    Ok(quote! {
+        impl hodu_plugin_sdk::PluginMethod for #name {
+            const METHOD_NAMES: &'static [&'static str] = &hodu_plugin_sdk::concat_method_names::<
+                { 0 #(+ <#handlers as hodu_plugin_sdk::PluginMethod>::METHOD_NAMES.len())* },
+            >(&[#(<#handlers as hodu_plugin_sdk::PluginMethod>::METHOD_NAMES),*]);
+
+            fn register_all<M: hodu_plugin_sdk::MethodRegistry>(registry: M) -> M {
+                #(let registry = <#handlers as hodu_plugin_sdk::PluginMethod>::register_all(registry);)*
+                registry
+            }
+        }
+
+        const _: () = ::std::assert!(
+            hodu_plugin_sdk::distinct_method_names(<#name as hodu_plugin_sdk::PluginMethod>::METHOD_NAMES),
+            #duplicate
+        );
+    })
 }
 
 /// Reserved method name prefixes that plugins cannot use
@@ -68,58 +173,73 @@ fn validate_method_name(name: &str) -> Option<&'static str> {
     None
 }
 
-fn extract_method_name(input: &DeriveInput) -> Result<String, syn::Error> {
+/// Options of a handler's `#[method(...)]` attribute
+struct MethodOptions {
+    name: String,
+    validate: bool,
+}
+
+fn extract_method_options(input: &DeriveInput) -> Result<MethodOptions, syn::Error> {
+    let mut options = MethodOptions {
+        name: String::new(),
+        validate: false,
+    };
+    let mut name = None;
     for attr in &input.attrs {
-        if attr.path().is_ident("method") {
-            // Parse #[method(name = "...")] using proper syn parsing
-            if let Ok(meta) = attr.meta.require_list() {
-                // Try to parse as name = value pair
-                if let Ok(nv) = syn::parse2::<syn::MetaNameValue>(meta.tokens.clone()) {
-                    if nv.path.is_ident("name") {
-                        if let syn::Expr::Lit(syn::ExprLit {
-                            lit: syn::Lit::Str(lit_str),
-                            ..
-                        }) = &nv.value
-                        {
-                            let name = lit_str.value();
-                            if let Some(err) = validate_method_name(&name) {
-                                return Err(syn::Error::new_spanned(lit_str, err));
-                            }
-                            return Ok(name);
-                        }
-                        return Err(syn::Error::new_spanned(
-                            &nv.value,
-                            "expected string literal for method name",
-                        ));
-                    }
-                    return Err(syn::Error::new_spanned(
-                        &nv.path,
-                        "expected `name = \"...\"` in #[method(...)]",
-                    ));
-                }
-                // Also try parsing as just a string literal: #[method("name")]
-                if let Ok(lit_str) = syn::parse2::<syn::LitStr>(meta.tokens.clone()) {
-                    let name = lit_str.value();
-                    if let Some(err) = validate_method_name(&name) {
-                        return Err(syn::Error::new_spanned(&lit_str, err));
-                    }
-                    return Ok(name);
-                }
-                // Attribute present but couldn't parse
-                return Err(syn::Error::new_spanned(
-                    attr,
-                    "invalid #[method(...)] attribute, expected #[method(name = \"...\")] or #[method(\"...\")]",
-                ));
-            }
+        if !attr.path().is_ident("method") {
+            continue;
+        }
+        if attr.meta.require_list().is_err() {
             // #[method] without arguments - error
             return Err(syn::Error::new_spanned(
                 attr,
                 "#[method] attribute requires a method name: #[method(name = \"...\")] or #[method(\"...\")]",
             ));
         }
+        attr.parse_args_with(|input: syn::parse::ParseStream| {
+            // The name may come first as a bare string literal: #[method("name")]
+            if input.peek(syn::LitStr) {
+                name = Some(input.parse::<syn::LitStr>()?);
+                if !input.is_empty() {
+                    input.parse::<syn::Token![,]>()?;
+                }
+            }
+            while !input.is_empty() {
+                let key: syn::Ident = input.parse()?;
+                if key == "name" {
+                    input.parse::<syn::Token![=]>()?;
+                    name = Some(
+                        input
+                            .parse()
+                            .map_err(|e| syn::Error::new(e.span(), "expected string literal for method name"))?,
+                    );
+                } else if key == "validate" {
+                    options.validate = true;
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "invalid #[method(...)] option, expected `name = \"...\"` or `validate`",
+                    ));
+                }
+                if !input.is_empty() {
+                    input.parse::<syn::Token![,]>()?;
+                }
+            }
+            Ok(())
+        })?;
     }
-    // No #[method] attribute: default to snake_case conversion
-    Ok(to_snake_case(&input.ident.to_string()))
+    options.name = match name {
+        Some(lit_str) => {
+            let name = lit_str.value();
+            if let Some(err) = validate_method_name(&name) {
+                return Err(syn::Error::new_spanned(lit_str, err));
+            }
+            name
+        },
+        // No name given: default to snake_case conversion
+        None => to_snake_case(&input.ident.to_string()),
+    };
+    Ok(options)
 }
 
 fn to_snake_case(s: &str) -> String {
@@ -195,6 +315,147 @@ pub fn plugin_handler(attr: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// Attribute macro for an impl of `BackendPlugin` or `FormatPlugin`
+///
+/// Registers every method the impl defines under its protocol name (`run` as `backend.run`,
+/// `load_model` as `format.load_model`), and generates a `main` serving `Default::default()`.
+///
+/// Options:
+/// - `name = "..."`: plugin name reported in `initialize` (default: the package name)
+/// - `version = "..."`: plugin version (default: the package version)
+/// - `main = false`: skip `main`, e.g. to serve one type as both a backend and a format with
+///   `PluginServer::new(..).backend(..).format(..)`
+///
+/// # Example
+///
+/// ```ignore
+/// use hodu_plugin_sdk::{hodu_plugin, BackendPlugin};
+///
+/// #[derive(Default)]
+/// struct MyBackend;
+///
+/// #[hodu_plugin]
+/// impl BackendPlugin for MyBackend {
+///     const DEVICES: &'static [&'static str] = &["cpu"];
+///
+///     async fn run(&self, ctx: Context, params: RunParams) -> Result<RunResult, RpcError> {
+///         // implementation
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn hodu_plugin(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut options = PluginOptions::default();
+    let parser = syn::meta::parser(|meta| options.parse(meta));
+    parse_macro_input!(attr with parser);
+    let input = parse_macro_input!(item as syn::ItemImpl);
+    match hodu_plugin_impl(&options, &input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct PluginOptions {
+    name: Option<syn::LitStr>,
+    version: Option<syn::LitStr>,
+    no_main: bool,
+}
+
+impl PluginOptions {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("version") {
+            self.version = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("main") {
+            let main: syn::LitBool = meta.value()?.parse()?;
+            self.no_main = !main.value;
+        } else {
+            return Err(meta.error("expected `name`, `version` or `main`"));
+        }
+        Ok(())
+    }
+}
+
+fn hodu_plugin_impl(options: &PluginOptions, input: &syn::ItemImpl) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let Some((_, trait_path, _)) = &input.trait_ else {
+        return Err(syn::Error::new_spanned(
+            input.self_ty.as_ref(),
+            "#[hodu_plugin] goes on an `impl BackendPlugin for ...` or `impl FormatPlugin for ...`",
+        ));
+    };
+    let trait_name = trait_path.segments.last().map(|segment| segment.ident.to_string());
+    let (prefix, register_trait, serve_method) = match trait_name.as_deref() {
+        Some("BackendPlugin") => ("BACKEND", quote!(RegisterBackend), quote!(backend)),
+        Some("FormatPlugin") => ("FORMAT", quote!(RegisterFormat), quote!(format)),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                trait_path,
+                "#[hodu_plugin] supports impls of `BackendPlugin` and `FormatPlugin`",
+            ))
+        },
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "#[hodu_plugin] does not support generic plugin types",
+        ));
+    }
+    let self_ty = &input.self_ty;
+
+    // Every fn but `configure` is a protocol method, named after its constant in `rpc::methods`
+    let registrations = input.items.iter().filter_map(|item| match item {
+        syn::ImplItem::Fn(method) if method.sig.ident != "configure" => {
+            let ident = &method.sig.ident;
+            let constant = format_ident!("{}_{}", prefix, ident.to_string().to_uppercase());
+            Some(quote! {
+                let registry = {
+                    let plugin = ::std::sync::Arc::clone(&plugin);
+                    registry.method(hodu_plugin_sdk::rpc::methods::#constant, move |ctx, params| {
+                        let plugin = ::std::sync::Arc::clone(&plugin);
+                        async move { <#self_ty as #trait_path>::#ident(&plugin, ctx, params).await }
+                    })
+                };
+            })
+        },
+        _ => None,
+    });
+
+    let main = if options.no_main {
+        quote!()
+    } else {
+        let name = match &options.name {
+            Some(name) => quote!(#name),
+            None => quote!(env!("CARGO_PKG_NAME")),
+        };
+        let version = match &options.version {
+            Some(version) => quote!(#version),
+            None => quote!(env!("CARGO_PKG_VERSION")),
+        };
+        quote! {
+            fn main() -> ::std::result::Result<(), ::std::boxed::Box<dyn ::std::error::Error>> {
+                hodu_plugin_sdk::server::PluginServer::new(#name, #version)
+                    .#serve_method(<#self_ty as ::std::default::Default>::default())
+                    .run_blocking()
+            }
+        }
+    };
+
+    Ok(quote! {
+        #input
+
+        impl hodu_plugin_sdk::#register_trait for #self_ty {
+            fn register<M: hodu_plugin_sdk::MethodRegistry>(plugin: ::std::sync::Arc<Self>, registry: M) -> M {
+                #(#registrations)*
+                registry
+            }
+        }
+
+        #main
+    })
+}
+
 /// Macro for defining params structs with automatic serde derives
 ///
 /// # Example
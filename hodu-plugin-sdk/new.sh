@@ -42,15 +42,19 @@ printf "  Select ${DIM}[1-3]${RESET}: "
 read TYPE_NUM
 
 case "$TYPE_NUM" in
-    1) TYPE="backend" ;;
-    2) TYPE="model_format" ;;
-    3) TYPE="tensor_format" ;;
+    1) TYPE="backend"; DESCRIPTION="Backend plugin for Hodu" ;;
+    2) TYPE="model_format"; DESCRIPTION="Model format plugin for Hodu" ;;
+    3) TYPE="tensor_format"; DESCRIPTION="Tensor format plugin for Hodu" ;;
     *)
         echo "${RED}✗${RESET} Invalid selection"
         exit 1
         ;;
 esac
 
+LICENSE="MIT"
+# File extension of a format plugin, from its name (hodu-format-npy -> npy)
+EXTENSION=$(echo "${NAME##*-}" | tr -cd '[:alnum:]')
+
 echo ""
 echo "${DIM}Creating $TYPE plugin...${RESET}"
 
@@ -59,14 +63,20 @@ mkdir -p "$NAME/src"
 # Download and process templates
 curl -fsSL "$TEMPLATE_URL/Cargo.toml.template" | \
     sed "s/{{NAME}}/$NAME/g" | \
-    sed "s/{{SDK_VERSION}}/$SDK_VERSION/g" > "$NAME/Cargo.toml"
+    sed "s/{{SDK_VERSION}}/$SDK_VERSION/g" | \
+    sed "s/{{DESCRIPTION}}/$DESCRIPTION/g" | \
+    sed "s/{{LICENSE}}/$LICENSE/g" > "$NAME/Cargo.toml"
 
 curl -fsSL "$TEMPLATE_URL/manifest.$TYPE.json" | \
     sed "s/{{NAME}}/$NAME/g" | \
-    sed "s/{{PLUGIN_VERSION}}/$PLUGIN_VERSION/g" > "$NAME/manifest.json"
+    sed "s/{{PLUGIN_VERSION}}/$PLUGIN_VERSION/g" | \
+    sed "s/{{DESCRIPTION}}/$DESCRIPTION/g" | \
+    sed "s/{{LICENSE}}/$LICENSE/g" | \
+    sed "s/{{EXTENSION}}/$EXTENSION/g" > "$NAME/manifest.json"
 
 curl -fsSL "$TEMPLATE_URL/src/main.$TYPE.rs" | \
-    sed "s/{{NAME}}/$NAME/g" > "$NAME/src/main.rs"
+    sed "s/{{NAME}}/$NAME/g" | \
+    sed "s/{{EXTENSION}}/$EXTENSION/g" > "$NAME/src/main.rs"
 
 echo ""
 echo "${GREEN}✓${RESET} Created ${CYAN}$NAME${RESET}/"
@@ -1,7 +1,14 @@
 //! Compiled artifact types for AOT compilation output
+//!
+//! Artifacts can be signed with an ed25519 [`SigningKey`], so whoever loads them can check they
+//! come from a trusted build; see [`CompiledArtifact::sign`].
 
 use crate::PluginDType;
 
+pub use hodu_plugin::signing::{
+    signature_path, verify_file, DetachedSignature, PublicKey, SignatureError, SigningKey, TrustedKeys,
+};
+
 /// Compiled artifact produced by a backend's build function
 #[derive(Debug, Clone)]
 pub struct CompiledArtifact {
@@ -80,6 +87,26 @@ impl CompiledArtifact {
         self
     }
 
+    /// Sign the artifact's data
+    ///
+    /// Save the signature next to the written artifact with
+    /// `signature.save(signature_path(&output))`, or sign the written file with
+    /// [`SigningKey::sign_file`].
+    pub fn sign(&self, key: &SigningKey) -> DetachedSignature {
+        key.sign(&self.data)
+    }
+
+    /// Check that `signature` was made over the artifact's data by one of the `trusted` keys
+    ///
+    /// Returns the name the signer's key is trusted under.
+    pub fn verify<'t>(
+        &self,
+        signature: &DetachedSignature,
+        trusted: &'t TrustedKeys,
+    ) -> Result<&'t str, SignatureError> {
+        signature.verify(&self.data, trusted)
+    }
+
     /// Get the raw data
     pub fn data(&self) -> &[u8] {
         &self.data
@@ -3,9 +3,11 @@
 //! Types for backend plugins that execute models on various devices.
 //! Common types (Device, BuildTarget, current_host_triple) are re-exported from hodu_plugin at crate root.
 
-use hodu_plugin::current_host_triple;
+use hodu_core::types::{get_precision, Precision};
+pub use hodu_plugin::is_tool_available;
+use hodu_plugin::manifest::{manifest_schema, validate_manifest, ManifestError};
+use hodu_plugin::{current_host_triple, rpc::RunParams};
 use serde::{Deserialize, Serialize};
-use std::process::Command;
 
 // ============================================================================
 // Build Target Capability (plugin SDK specific)
@@ -85,40 +87,6 @@ impl BuildCapability {
 // Host / Tool Detection Utilities
 // ============================================================================
 
-/// Check if a tool is available on the system
-///
-/// Tool names are validated to prevent command injection:
-/// - Must not be empty
-/// - Must not contain path separators (`/`, `\`)
-/// - Must not contain shell metacharacters
-/// - Must not contain null bytes or control characters
-///
-/// Returns `false` for invalid tool names without executing anything.
-pub fn is_tool_available(tool: &str) -> bool {
-    // Validate tool name to prevent command injection
-    if tool.is_empty() {
-        return false;
-    }
-    // Reject path separators (prevents executing arbitrary paths)
-    if tool.contains('/') || tool.contains('\\') {
-        return false;
-    }
-    // Reject shell metacharacters and control characters
-    const FORBIDDEN_CHARS: &[char] = &[
-        '\0', '\n', '\r', '\t', ' ', '&', '|', ';', '$', '`', '(', ')', '{', '}', '[', ']', '<', '>', '\'', '"', '!',
-        '*', '?', '#', '~', '^',
-    ];
-    if tool.chars().any(|c| c.is_control() || FORBIDDEN_CHARS.contains(&c)) {
-        return false;
-    }
-
-    Command::new(tool)
-        .arg("--version")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
-}
-
 /// Check if host matches a pattern (supports glob * wildcards)
 ///
 /// Supports wildcards at any position:
@@ -214,10 +182,39 @@ impl PluginManifest {
     }
 
     /// Load manifest from a specific path
+    ///
+    /// The manifest is checked with [`validate`](Self::validate); the error lists every problem
+    /// found, one `path:line:column: message` per line.
     pub fn load_from(path: &std::path::Path) -> Result<Self, String> {
         let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read manifest: {}", e))?;
 
-        serde_json::from_str(&content).map_err(|e| format!("Failed to parse manifest: {}", e))
+        Self::validate(&content).map_err(|errors| {
+            let errors: Vec<String> = errors.iter().map(|e| format!("{}:{}", path.display(), e)).collect();
+            format!("Invalid manifest:\n{}", errors.join("\n"))
+        })
+    }
+
+    /// JSON Schema describing manifest.json
+    pub fn to_json_schema() -> serde_json::Value {
+        manifest_schema()
+    }
+
+    /// Parse a manifest, checking it against [`to_json_schema`](Self::to_json_schema)
+    ///
+    /// Returns every problem found, each with the line and column it is on.
+    pub fn validate(source: &str) -> Result<Self, Vec<ManifestError>> {
+        let errors = validate_manifest(source);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        serde_json::from_str(source).map_err(|e| {
+            vec![ManifestError {
+                line: e.line(),
+                column: e.column(),
+                path: String::new(),
+                message: e.to_string(),
+            }]
+        })
     }
 
     /// Check if a target triple is supported and can be built
@@ -334,3 +331,24 @@ pub fn check_build_capability(target: &SupportedTarget) -> BuildCapability {
 
     BuildCapability::available(available)
 }
+
+// ============================================================================
+// Run Settings
+// ============================================================================
+
+/// Precision policy for a run: the plugin's current policy with the request's overrides
+///
+/// Execute the model inside `run_precision(&params).scope(|| ...)` so GPU matmul and
+/// convolution kernels honor it.
+pub fn run_precision(params: &RunParams) -> Precision {
+    let mut precision = get_precision();
+    if let Some(overrides) = &params.precision {
+        if let Some(allow_tf32) = overrides.allow_tf32 {
+            precision.allow_tf32 = allow_tf32;
+        }
+        if let Some(f16_accumulate_f32) = overrides.f16_accumulate_f32 {
+            precision.f16_accumulate_f32 = f16_accumulate_f32;
+        }
+    }
+    precision
+}
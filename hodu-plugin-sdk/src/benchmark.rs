@@ -0,0 +1,109 @@
+//! Timing loop for `backend.benchmark`
+//!
+//! [`benchmark`] runs the warmup and timed iterations of a [`BenchmarkParams`] around a closure
+//! running the model once, reporting progress and honoring cancellation, and fills in a
+//! [`BenchmarkResult`]. Backends only have to load the model and inputs once and run them:
+//!
+//! ```ignore
+//! async fn handle_benchmark(ctx: Context, params: BenchmarkParams) -> Result<BenchmarkResult, RpcError> {
+//!     let model = Arc::new(LoadedModel::open(&params.library_path, &params.device)?);
+//!     let inputs = Arc::new(load_inputs(&params.inputs)?);
+//!     let mut result = hodu_plugin_sdk::benchmark(&ctx, &params, || {
+//!         let (model, inputs) = (model.clone(), inputs.clone());
+//!         async move { model.run(&inputs).map(drop) }
+//!     })
+//!     .await?;
+//!     // Accelerators report their own device memory instead of the process's
+//!     result.peak_memory_bytes = model.peak_device_memory();
+//!     Ok(result)
+//! }
+//! ```
+
+use crate::rpc::{BenchmarkParams, BenchmarkResult, ProgressParams, RpcError};
+use crate::Context;
+use hodu_plugin::memory::{peak_memory, reset_peak_memory};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Minimum interval between progress notifications, so fast models do not flood the CLI
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Run `params.warmup` untimed and `params.iterations` timed iterations of `run_once`
+///
+/// Progress is reported per stage (`warmup`, then `measure`) with the iterations done. The peak
+/// memory is the process's peak resident memory during the timed iterations, where the OS can
+/// tell (Linux); backends running on a device should replace it with the device's peak.
+///
+/// # Errors
+/// Returns the first error of `run_once`, or a cancelled error once the request is cancelled.
+pub async fn benchmark<F, Fut>(
+    ctx: &Context,
+    params: &BenchmarkParams,
+    mut run_once: F,
+) -> Result<BenchmarkResult, RpcError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), RpcError>>,
+{
+    let total = u64::from(params.warmup) + u64::from(params.iterations);
+    let mut progress = Progress::new(ctx, total);
+
+    for i in 0..params.warmup {
+        if ctx.is_cancelled() {
+            return Err(RpcError::cancelled());
+        }
+        run_once().await?;
+        progress.report("warmup", u64::from(i) + 1);
+    }
+
+    reset_peak_memory();
+    let mut latencies_ms = Vec::with_capacity(params.iterations as usize);
+    let start = Instant::now();
+    for i in 0..params.iterations {
+        if ctx.is_cancelled() {
+            return Err(RpcError::cancelled());
+        }
+        let iteration = Instant::now();
+        run_once().await?;
+        latencies_ms.push(iteration.elapsed().as_secs_f64() * 1000.0);
+        progress.report("measure", u64::from(params.warmup) + u64::from(i) + 1);
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    Ok(BenchmarkResult {
+        throughput: if elapsed > 0.0 {
+            latencies_ms.len() as f64 / elapsed
+        } else {
+            0.0
+        },
+        latencies_ms,
+        peak_memory_bytes: peak_memory(),
+    })
+}
+
+/// Throttled progress notifications over all iterations
+struct Progress<'a> {
+    ctx: &'a Context,
+    total: u64,
+    last: Option<Instant>,
+}
+
+impl<'a> Progress<'a> {
+    fn new(ctx: &'a Context, total: u64) -> Self {
+        Self { ctx, total, last: None }
+    }
+
+    fn report(&mut self, stage: &str, done: u64) {
+        let due = self.last.is_none_or(|last| last.elapsed() >= PROGRESS_INTERVAL);
+        if !due && done < self.total {
+            return;
+        }
+        self.last = Some(Instant::now());
+        let percent = (done * 100 / self.total.max(1)) as u8;
+        self.ctx.progress_with(
+            ProgressParams::new(Some(percent), format!("Iteration {}/{}", done, self.total))
+                .with_stage(stage, None)
+                .with_items(done, self.total),
+        );
+    }
+}
@@ -2,14 +2,25 @@
 //!
 //! Provides cancellation support, request metadata, and shared state access.
 
-use crate::rpc::RequestId;
+use crate::metrics::{self, Counter};
+use crate::rpc::{
+    features, CancelReason, Notification, ProgressParams, RequestId, RpcError, StreamLoadTensorParams, TensorOutput,
+};
+use crate::server::{self, ResultStream};
+use crate::tensor_io;
+use crate::tensor_stream::TensorStreamWriter;
+use crate::{PluginDType, TensorData};
+use serde::{de::DeserializeOwned, Serialize};
 use std::any::Any;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
 use tokio_util::sync::CancellationToken;
 
 /// Context passed to async handlers
 ///
 /// Contains cancellation token, request metadata, and optional shared state.
+/// Sub-tasks get their own tokens with [`child_token`](Context::child_token), and blocking
+/// sections can be interrupted with [`on_cancel`](Context::on_cancel).
 ///
 /// # Example
 ///
@@ -37,16 +48,30 @@ use tokio_util::sync::CancellationToken;
 pub struct Context {
     request_id: RequestId,
     cancellation_token: CancellationToken,
+    cancel_state: Arc<CancelState>,
     state: Option<Arc<dyn Any + Send + Sync>>,
+    config: Option<Arc<dyn Any + Send + Sync>>,
+    features: Arc<[String]>,
+    inline_tensor_limit: u64,
+    /// Where progress and log notifications go instead of the CLI, for handlers under test
+    captured: Option<CapturedNotifications>,
 }
 
+/// Notifications captured from a [`Context`] instead of being sent
+pub(crate) type CapturedNotifications = Arc<Mutex<Vec<Notification>>>;
+
 impl Context {
     /// Create a new context
     pub(crate) fn new(request_id: RequestId) -> Self {
         Self {
             request_id,
             cancellation_token: CancellationToken::new(),
+            cancel_state: Arc::default(),
             state: None,
+            config: None,
+            features: Arc::from([]),
+            inline_tensor_limit: 0,
+            captured: None,
         }
     }
 
@@ -55,10 +80,44 @@ impl Context {
         Self {
             request_id,
             cancellation_token: CancellationToken::new(),
+            cancel_state: Arc::default(),
             state: Some(state),
+            config: None,
+            features: Arc::from([]),
+            inline_tensor_limit: 0,
+            captured: None,
+        }
+    }
+
+    /// Replace the shared state
+    pub(crate) fn with_state(mut self, state: Arc<dyn Any + Send + Sync>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Capture progress and log notifications in `captured` instead of sending them
+    pub(crate) fn with_captured_notifications(mut self, captured: CapturedNotifications) -> Self {
+        self.captured = Some(captured);
+        self
+    }
+
+    /// Send a progress or log notification, or capture it for a test
+    fn notify(&self, notification: Notification) -> Result<(), std::io::Error> {
+        match &self.captured {
+            Some(captured) => {
+                captured.lock().unwrap_or_else(|e| e.into_inner()).push(notification);
+                Ok(())
+            },
+            None => server::send_notification(&notification),
         }
     }
 
+    /// Attach the plugin configuration received in `initialize`
+    pub(crate) fn with_config(mut self, config: Option<Arc<dyn Any + Send + Sync>>) -> Self {
+        self.config = config;
+        self
+    }
+
     /// Get the shared state
     ///
     /// Returns a **cloned `Arc`** to the shared state, not a reference. This means
@@ -109,6 +168,103 @@ impl Context {
         })
     }
 
+    /// Attach the protocol features negotiated in `initialize`
+    pub(crate) fn with_features(mut self, features: Arc<[String]>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Check whether the CLI negotiated a protocol feature
+    ///
+    /// Older CLIs negotiate nothing, so check before relying on a newer feature and fall back
+    /// when it is missing.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// if ctx.has_feature(features::STREAMING) {
+    ///     let mut tokens = ctx.stream::<String>("tokens")?;
+    ///     // ...
+    /// }
+    /// ```
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    /// Get the protocol features negotiated with the CLI
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    /// Attach the inline tensor limit negotiated in `initialize`
+    pub(crate) fn with_inline_tensor_limit(mut self, limit: u64) -> Self {
+        self.inline_tensor_limit = limit;
+        self
+    }
+
+    /// Get the largest tensor in bytes that may be sent inline (0 if the CLI does not accept any)
+    pub fn inline_tensor_limit(&self) -> u64 {
+        self.inline_tensor_limit
+    }
+
+    /// Hand an output tensor to the CLI the cheapest way available
+    ///
+    /// Tensors within [`inline_tensor_limit`](Self::inline_tensor_limit) are sent inline. Larger
+    /// ones are written to an .hdt file in shared memory (/dev/shm) where the system has it, or
+    /// in the temp directory otherwise. See [`write_output`](crate::write_output).
+    pub fn output(&self, name: &str, tensor: &TensorData) -> Result<TensorOutput, RpcError> {
+        tensor_io::write_output(name, tensor, self.inline_tensor_limit)
+    }
+
+    /// Get the custom counter `name`, exported by `$/metrics` as `hodu_plugin_<name>`
+    ///
+    /// Counters are shared across requests, so repeated calls with the same name return the same
+    /// counter. Characters not allowed in Prometheus metric names are replaced with `_`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// ctx.metric("tokens_generated_total").add(tokens.len() as u64);
+    /// ```
+    pub fn metric(&self, name: &str) -> Counter {
+        metrics::registry().counter(name)
+    }
+
+    /// Get the plugin configuration
+    ///
+    /// Returns the config registered with `PluginServer::config::<C>()`, deserialized from what
+    /// the CLI sent in `initialize`. Returns `None` if no config type was registered or if the
+    /// type doesn't match.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// #[derive(Default, Serialize, Deserialize)]
+    /// struct MyConfig {
+    ///     threads: usize,
+    /// }
+    ///
+    /// async fn handler(ctx: Context, params: Params) -> Result<Response, RpcError> {
+    ///     let threads = ctx.config::<MyConfig>().map_or(1, |c| c.threads);
+    ///     // ...
+    /// }
+    /// ```
+    pub fn config<C: Send + Sync + 'static>(&self) -> Option<Arc<C>> {
+        let config = self.config.as_ref()?;
+        match config.clone().downcast::<C>() {
+            Ok(config) => Some(config),
+            Err(_) => {
+                // Log in all builds - this indicates a programming error
+                eprintln!(
+                    "Warning: Config downcast failed for type '{}' (request_id: {:?})",
+                    std::any::type_name::<C>(),
+                    self.request_id
+                );
+                None
+            },
+        }
+    }
+
     /// Get the request ID
     pub fn request_id(&self) -> &RequestId {
         &self.request_id
@@ -142,20 +298,206 @@ impl Context {
         self.cancellation_token.cancelled().await
     }
 
+    /// Why the request was cancelled, once it has been
+    ///
+    /// Lets a handler tell a user abort from a timeout or a shutdown, e.g. to keep a resumable
+    /// checkpoint only when the plugin is shutting down.
+    pub fn cancel_reason(&self) -> Option<CancelReason> {
+        self.cancel_state.reason.get().copied()
+    }
+
+    /// Create a token for a sub-task that is cancelled along with the request
+    ///
+    /// Cancelling the child token stops only the sub-task, not the request, so one token per
+    /// spawned task lets a handler give up on part of its work:
+    ///
+    /// ```ignore
+    /// let token = ctx.child_token();
+    /// let prefetch = tokio::spawn(async move {
+    ///     tokio::select! {
+    ///         weights = load_weights(path) => Some(weights),
+    ///         _ = token.cancelled() => None,
+    ///     }
+    /// });
+    /// ```
+    pub fn child_token(&self) -> CancellationToken {
+        self.cancellation_token.child_token()
+    }
+
+    /// Run `callback` once when the request is cancelled, until the returned guard is dropped
+    ///
+    /// For blocking sections that never reach an `.await` or check [`is_cancelled`](Self::is_cancelled),
+    /// such as a call into a native library with its own abort flag. The callback runs on the
+    /// thread that cancels the request (on `$/cancel`, a timeout, or shutdown), so it should only
+    /// signal the blocked work, not wait for it. If the request is already cancelled it runs at once.
+    ///
+    /// ```ignore
+    /// let abort = Arc::new(AtomicBool::new(false));
+    /// let _guard = ctx.on_cancel({
+    ///     let abort = abort.clone();
+    ///     move || abort.store(true, Ordering::Relaxed)
+    /// });
+    /// engine.execute(&inputs, &abort)?;
+    /// ```
+    pub fn on_cancel<F>(&self, callback: F) -> CancelGuard
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let mut registry = self.cancel_state.lock();
+        // Checked under the lock, so the callback runs here or in the cancelling thread's drain
+        if self.cancellation_token.is_cancelled() {
+            drop(registry);
+            callback();
+            return CancelGuard { registration: None };
+        }
+        let id = registry.next_id();
+        registry.callbacks.insert(id, Box::new(callback));
+        CancelGuard {
+            registration: Some((Arc::clone(&self.cancel_state), id)),
+        }
+    }
+
+    /// Run `cleanup` with the reason if the request ends cancelled, after the handler has stopped
+    ///
+    /// For removing partially written artifacts: unlike [`on_cancel`](Self::on_cancel) callbacks,
+    /// cleanups run only once the handler task has finished or been aborted, so they never race
+    /// its writes, and the cancellation error is sent after they are done. They run newest first
+    /// when the request fails after being cancelled, and are discarded when it succeeds.
+    ///
+    /// Register one per checkpoint and [`disarm`](CleanupGuard::disarm) it once the artifact is
+    /// complete. Dropping the guard keeps the cleanup registered, as an aborted handler drops it too.
+    ///
+    /// ```ignore
+    /// let partial = params.output_path.with_extension("partial");
+    /// let cleanup = ctx.cleanup_on_cancel({
+    ///     let partial = partial.clone();
+    ///     move |_reason| {
+    ///         let _ = std::fs::remove_file(&partial);
+    ///     }
+    /// });
+    /// write_artifact(&partial).await?;
+    /// std::fs::rename(&partial, &params.output_path)?;
+    /// cleanup.disarm();
+    /// ```
+    pub fn cleanup_on_cancel<F>(&self, cleanup: F) -> CleanupGuard
+    where
+        F: FnOnce(CancelReason) + Send + 'static,
+    {
+        let mut registry = self.cancel_state.lock();
+        let id = registry.next_id();
+        registry.cleanups.insert(id, Box::new(cleanup));
+        CleanupGuard {
+            state: Arc::clone(&self.cancel_state),
+            id,
+        }
+    }
+
+    /// Send a request to the CLI and wait for its result
+    ///
+    /// The CLI answers `host.*` methods, such as `host.load_tensor` to load a file through
+    /// whichever installed format plugin handles its extension. Other requests to and from the
+    /// CLI keep flowing while the call is pending.
+    ///
+    /// Fails with `NotSupported` unless the CLI negotiated [`features::HOST_CALLS`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let loaded: LoadTensorResult = ctx
+    ///     .call(methods::HOST_LOAD_TENSOR, LoadTensorParams { path: input.path.clone() })
+    ///     .await?;
+    /// ```
+    pub async fn call<P: Serialize, R: DeserializeOwned>(&self, method: &str, params: P) -> Result<R, RpcError> {
+        if !self.has_feature(features::HOST_CALLS) {
+            return Err(RpcError::not_supported(format!(
+                "{} (the CLI does not accept requests from plugins)",
+                method
+            )));
+        }
+        let params = serde_json::to_value(params).map_err(|e| RpcError::invalid_params(e.to_string()))?;
+        let result = crate::server::call_host(method, Some(params)).await?;
+        serde_json::from_value(result)
+            .map_err(|e| RpcError::internal_error(format!("Invalid result for {}: {}", method, e)))
+    }
+
+    /// Open a typed stream of results for this request
+    ///
+    /// Items sent on the stream reach the CLI as they are produced, before the handler returns.
+    /// A handler can open several streams with different names.
+    /// CLIs that did not negotiate [`features::STREAMING`] drop the items, so check
+    /// [`has_feature`](Self::has_feature) first when the results must arrive some other way.
+    /// With [`features::STREAM_CREDITS`] negotiated, [`ResultStream::write`] waits for the CLI
+    /// to keep up.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut tokens = ctx.stream::<String>("tokens")?;
+    /// tokens.write(&token).await?;
+    /// tokens.close()?;
+    /// ```
+    pub fn stream<T: Serialize>(&self, name: &str) -> Result<ResultStream<T>, RpcError> {
+        let flow = self
+            .has_feature(features::STREAM_CREDITS)
+            .then(|| self.cancellation_token.clone());
+        ResultStream::open(self.request_id.clone(), name, flow)
+    }
+
+    /// Open the `tensor` stream answering a `format.stream_load_tensor` request
+    ///
+    /// Sends the tensor's shape and dtype; the returned writer sends its data.
+    pub fn stream_tensor(
+        &self,
+        params: &StreamLoadTensorParams,
+        shape: Vec<usize>,
+        dtype: PluginDType,
+    ) -> Result<TensorStreamWriter, RpcError> {
+        TensorStreamWriter::open(self, params, shape, dtype)
+    }
+
     /// Send a progress notification
     ///
     /// # Arguments
     /// * `percent` - Progress percentage (0-100), None for indeterminate. Values > 100 are clamped to 100.
     /// * `message` - Progress message
     pub fn progress(&self, percent: Option<u8>, message: &str) {
-        crate::notify_progress(percent, message);
+        if let Err(e) = self.try_progress(percent, message) {
+            eprintln!("Warning: Failed to send progress notification: {}", e);
+        }
     }
 
     /// Send a progress notification with error handling
     ///
     /// Returns an error if the notification fails to send.
     pub fn try_progress(&self, percent: Option<u8>, message: &str) -> Result<(), std::io::Error> {
-        crate::try_notify_progress(percent, message)
+        self.notify(server::progress_notification(percent, message))
+    }
+
+    /// Send a progress notification for one stage of a multi-stage operation
+    ///
+    /// The CLI shows the overall percent and the stage's own progress as nested bars.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// ctx.progress_with(
+    ///     ProgressParams::new(Some(40), "Compiling kernels")
+    ///         .with_stage("compile", None)
+    ///         .with_items(done, total)
+    ///         .with_eta(remaining),
+    /// );
+    /// ```
+    pub fn progress_with(&self, params: ProgressParams) {
+        if let Err(e) = self.try_progress_with(params) {
+            eprintln!("Warning: Failed to send progress notification: {}", e);
+        }
+    }
+
+    /// Send a staged progress notification with error handling
+    ///
+    /// Returns an error if the notification fails to send.
+    pub fn try_progress_with(&self, params: ProgressParams) -> Result<(), std::io::Error> {
+        self.notify(server::progress_with_notification(params))
     }
 
     /// Send a log message
@@ -164,14 +506,16 @@ impl Context {
     /// * `level` - Log level: "error", "warn", "info", "debug", "trace". Invalid levels default to "info".
     /// * `message` - Log message
     pub fn log(&self, level: &str, message: &str) {
-        crate::notify_log(level, message);
+        if let Err(e) = self.try_log(level, message) {
+            eprintln!("Warning: Failed to send log notification: {}", e);
+        }
     }
 
     /// Send a log message with error handling
     ///
     /// Returns an error if the notification fails to send.
     pub fn try_log(&self, level: &str, message: &str) -> Result<(), std::io::Error> {
-        crate::try_notify_log(level, message)
+        self.notify(server::log_notification(level, message))
     }
 
     /// Log an info message
@@ -195,20 +539,113 @@ impl Context {
     }
 }
 
+/// Why and how a request is cancelled, shared by its contexts and cancellation handles
+#[derive(Default)]
+struct CancelState {
+    reason: OnceLock<CancelReason>,
+    registry: Mutex<CallbackRegistry>,
+}
+
+#[derive(Default)]
+struct CallbackRegistry {
+    next_id: u64,
+    callbacks: HashMap<u64, Box<dyn FnOnce() + Send>>,
+    cleanups: HashMap<u64, Box<dyn FnOnce(CancelReason) + Send>>,
+}
+
+impl CallbackRegistry {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+impl CancelState {
+    fn lock(&self) -> std::sync::MutexGuard<'_, CallbackRegistry> {
+        self.registry.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run and forget every [`Context::on_cancel`] callback
+    fn run_callbacks(&self) {
+        let callbacks: Vec<_> = self.lock().callbacks.drain().map(|(_, callback)| callback).collect();
+        for callback in callbacks {
+            callback();
+        }
+    }
+
+    /// Run and forget every [`Context::cleanup_on_cancel`] cleanup, newest first
+    fn run_cleanups(&self) {
+        let mut cleanups: Vec<_> = self.lock().cleanups.drain().collect();
+        cleanups.sort_by_key(|(id, _)| std::cmp::Reverse(*id));
+        let reason = self.reason.get().copied().unwrap_or(CancelReason::Other);
+        for (_, cleanup) in cleanups {
+            cleanup(reason);
+        }
+    }
+}
+
+/// Keeps a [`Context::on_cancel`] callback registered; dropping it unregisters the callback
+#[must_use = "the callback is unregistered when the guard is dropped"]
+pub struct CancelGuard {
+    registration: Option<(Arc<CancelState>, u64)>,
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        if let Some((state, id)) = self.registration.take() {
+            state.lock().callbacks.remove(&id);
+        }
+    }
+}
+
+/// A cleanup registered with [`Context::cleanup_on_cancel`]
+pub struct CleanupGuard {
+    state: Arc<CancelState>,
+    id: u64,
+}
+
+impl CleanupGuard {
+    /// Unregister the cleanup, once what it would clean up is complete
+    pub fn disarm(self) {
+        self.state.lock().cleanups.remove(&self.id);
+    }
+}
+
 /// Handle for cancelling a request from outside
 #[derive(Clone)]
 pub(crate) struct CancellationHandle {
     token: CancellationToken,
+    state: Arc<CancelState>,
 }
 
 impl CancellationHandle {
     pub fn new(ctx: &Context) -> Self {
         Self {
             token: ctx.cancellation_token.clone(),
+            state: Arc::clone(&ctx.cancel_state),
         }
     }
 
-    pub fn cancel(&self) {
+    /// Cancel the request's token and its child tokens, and run its cancel callbacks
+    ///
+    /// The first reason given sticks if the request is cancelled more than once.
+    pub fn cancel(&self, reason: CancelReason) {
+        let _ = self.state.reason.set(reason);
         self.token.cancel();
+        self.state.run_callbacks();
+    }
+
+    /// Whether cleanups are waiting for the request to end cancelled
+    pub fn has_cleanups(&self) -> bool {
+        !self.state.lock().cleanups.is_empty()
+    }
+
+    /// Run the cleanups of a request that ended cancelled
+    pub fn run_cleanups(&self) {
+        self.state.run_cleanups();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
     }
 }
@@ -0,0 +1,122 @@
+//! File formats, with the path-taking functions checked against the plugin's sandbox
+//!
+//! Everything else is re-exported from hodu_core unchanged.
+
+/// Hodu tensor (.hdt) files
+pub mod hdt {
+    pub use hodu_core::format::hdt::*;
+
+    use crate::sandbox::{check_read, check_write};
+    use crate::Tensor;
+    use hodu_core::error::HoduResult;
+    use hodu_core::format::{hdt, Compression};
+    use hodu_core::types::BlockFormat;
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    /// Load a single tensor from .hdt file
+    pub fn load(path: impl AsRef<Path>) -> HoduResult<Tensor> {
+        check_read(&path)?;
+        hdt::load(path)
+    }
+
+    /// Save a single tensor to .hdt file
+    pub fn save(tensor: &Tensor, path: impl AsRef<Path>) -> HoduResult<()> {
+        check_write(&path)?;
+        hdt::save(tensor, path)
+    }
+
+    /// Save a single tensor to .hdt file with the given payload compression
+    pub fn save_with(tensor: &Tensor, path: impl AsRef<Path>, compression: Compression) -> HoduResult<()> {
+        check_write(&path)?;
+        hdt::save_with(tensor, path, compression)
+    }
+
+    /// Load multiple named tensors from .hdt file
+    pub fn load_many(path: impl AsRef<Path>) -> HoduResult<HashMap<String, Tensor>> {
+        check_read(&path)?;
+        hdt::load_many(path)
+    }
+
+    /// Save multiple named tensors to .hdt file
+    pub fn save_many(tensors: &HashMap<String, Tensor>, path: impl AsRef<Path>) -> HoduResult<()> {
+        check_write(&path)?;
+        hdt::save_many(tensors, path)
+    }
+
+    /// Save multiple named tensors to .hdt file with the given payload compression
+    pub fn save_many_with(
+        tensors: &HashMap<String, Tensor>,
+        path: impl AsRef<Path>,
+        compression: Compression,
+    ) -> HoduResult<()> {
+        check_write(&path)?;
+        hdt::save_many_with(tensors, path, compression)
+    }
+
+    /// Load a block-quantized tensor from .hdt file
+    pub fn load_packed(path: impl AsRef<Path>) -> HoduResult<(Tensor, BlockFormat)> {
+        check_read(&path)?;
+        hdt::load_packed(path)
+    }
+
+    /// Save a block-quantized tensor to .hdt file
+    pub fn save_packed(tensor: &Tensor, format: BlockFormat, path: impl AsRef<Path>) -> HoduResult<()> {
+        check_write(&path)?;
+        hdt::save_packed(tensor, format, path)
+    }
+
+    /// Save a block-quantized tensor to .hdt file with the given payload compression
+    pub fn save_packed_with(
+        tensor: &Tensor,
+        format: BlockFormat,
+        path: impl AsRef<Path>,
+        compression: Compression,
+    ) -> HoduResult<()> {
+        check_write(&path)?;
+        hdt::save_packed_with(tensor, format, path, compression)
+    }
+}
+
+/// Hodu snapshot (.hdss) files
+pub mod hdss {
+    pub use hodu_core::format::hdss::*;
+
+    use crate::sandbox::{check_read, check_write};
+    use crate::Snapshot;
+    use hodu_core::error::HoduResult;
+    use hodu_core::format::{hdss, Compression};
+    use std::path::Path;
+
+    /// Load a snapshot from .hdss file
+    pub fn load(path: impl AsRef<Path>) -> HoduResult<Snapshot> {
+        check_read(&path)?;
+        hdss::load(path)
+    }
+
+    /// Load a snapshot without reading the data of sharded constants
+    ///
+    /// Weight shards are read from next to the snapshot, so the sandbox allows them too.
+    pub fn load_lazy(path: impl AsRef<Path>) -> HoduResult<(Snapshot, Option<ShardedWeights>)> {
+        check_read(&path)?;
+        hdss::load_lazy(path)
+    }
+
+    /// Save a snapshot to .hdss file
+    pub fn save(snapshot: &Snapshot, path: impl AsRef<Path>) -> HoduResult<()> {
+        check_write(&path)?;
+        hdss::save(snapshot, path)
+    }
+
+    /// Save a snapshot to .hdss file with the given payload compression
+    pub fn save_with(snapshot: &Snapshot, path: impl AsRef<Path>, compression: Compression) -> HoduResult<()> {
+        check_write(&path)?;
+        hdss::save_with(snapshot, path, compression)
+    }
+
+    /// Save a snapshot with its constants split across weight shards next to it
+    pub fn save_sharded(snapshot: &Snapshot, path: impl AsRef<Path>, max_shard_size: usize) -> HoduResult<()> {
+        check_write(&path)?;
+        hdss::save_sharded(snapshot, path, max_shard_size)
+    }
+}
@@ -31,6 +31,9 @@
 //! }
 //! ```
 //!
+//! Plugins can also implement [`BackendPlugin`] or [`FormatPlugin`] and annotate the impl with
+//! [`#[hodu_plugin]`](hodu_plugin), which registers its methods and generates `main`.
+//!
 //! ## Architecture
 //!
 //! Plugins are standalone executables that communicate with the CLI via JSON-RPC 2.0 over stdio:
@@ -46,21 +49,43 @@
 //! - `format.save_model` - Save model to file
 //! - `format.load_tensor` - Load tensor from file
 //! - `format.save_tensor` - Save tensor to file
+//! - `format.stream_load_tensor` - Load a tensor too large to convert in one piece, in chunks
 //! - `backend.run` - Execute model inference
 //! - `backend.build` - AOT compile model
+//! - `backend.load_session` / `backend.run_session` / `backend.close_session` - Keep a model
+//!   loaded across runs
+//! - `backend.benchmark` - Time repeated runs of a compiled model
+//! - `backend.profile` - Run once, recording per-op timings and memory
+//! - `backend.quantize` - Write a quantized copy of a snapshot
+
+// Lets the `hodu_plugin_sdk::` paths the macros generate resolve inside this crate too
+extern crate self as hodu_plugin_sdk;
 
 mod artifact;
 mod backend;
+mod benchmark;
 mod context;
+mod format;
+mod method;
+mod metrics;
+mod plugin;
+mod profile;
+mod quantize;
+pub mod sandbox;
 pub mod server;
+mod session;
+mod status;
 mod tensor;
+mod tensor_io;
+mod tensor_stream;
 pub mod testing;
+mod trace;
 
 // Re-export rpc module from hodu_plugin
 pub use hodu_plugin::rpc;
 
 // Re-export Context for async handlers
-pub use context::Context;
+pub use context::{CancelGuard, Context};
 
 // Re-export from hodu_plugin (common types shared with hodu-cli)
 pub use hodu_plugin::{BuildTarget, Device, PluginDType, PluginError, PluginResult, TensorData, PLUGIN_VERSION};
@@ -73,14 +98,36 @@ pub use tensor::{core_dtype_to_plugin, plugin_dtype_to_core, SdkDType, TensorDat
 
 // Re-export notification helpers for convenience
 pub use server::{
-    log_debug, log_error, log_info, log_warn, notify_log, notify_progress, try_notify_log, try_notify_progress,
+    log_debug, log_error, log_info, log_warn, notify_log, notify_log_with, notify_progress, notify_progress_with,
+    try_notify_log, try_notify_log_with, try_notify_progress, try_notify_progress_with,
 };
 
 // Re-export streaming support
-pub use server::StreamWriter;
+pub use server::{ResultStream, StreamWriter};
+pub use tensor_io::{load_input, write_output};
+pub use tensor_stream::TensorStreamWriter;
 
 // Re-export middleware/hook types
-pub use server::{PreRequestAction, RequestInfo, ResponseInfo};
+pub use server::{MethodCall, Next, PreRequestAction, RequestInfo, ResponseInfo};
+
+// Re-export metrics support
+pub use metrics::Counter;
+
+// Re-export benchmark support
+pub use benchmark::benchmark;
+
+// Re-export profiling support
+pub use profile::{OpRecord, ProfileBuilder};
+
+// Re-export quantization support
+pub use quantize::{load_calibration, AccuracyReport};
+
+// Re-export session support
+pub use session::SessionStore;
+
+// Re-export tracing support, and the tracing crate for its macros
+pub use trace::{init_tracing, LogForwarder};
+pub use tracing;
 
 // Re-export debug options
 pub use server::DebugOptions;
@@ -88,22 +135,38 @@ pub use server::DebugOptions;
 // Plugin SDK specific types (for plugin development only)
 pub use artifact::*;
 pub use backend::{
-    check_build_capability, host_matches_pattern, is_tool_available, BuildCapability, PluginManifest, SupportedTarget,
+    check_build_capability, host_matches_pattern, is_tool_available, run_precision, BuildCapability, PluginManifest,
+    SupportedTarget,
 };
+pub use hodu_plugin::manifest::ManifestError;
 
 // Re-export from hodu_core for plugin development
 pub use hodu_core::{
-    format::{hdss, hdt, json},
+    format::json,
     op_params::{self, OpParams},
     ops,
     scalar::Scalar,
     snapshot::{self, Snapshot, SnapshotNode},
     tensor::Tensor,
-    types::{DType, Device as CoreDevice, Layout, Shape},
+    types::{DType, Device as CoreDevice, Layout, Precision, Shape},
 };
 
+// File formats whose path-taking functions respect the sandbox
+pub use format::{hdss, hdt};
+pub use hodu_plugin::SandboxPolicy;
+
+// Re-export plugin traits, implemented with #[hodu_plugin]
+pub use plugin::{BackendPlugin, FormatPlugin, MethodRegistry};
+#[doc(hidden)]
+pub use plugin::{RegisterBackend, RegisterFormat};
+
+// Re-export handler traits, implemented with #[derive(PluginMethod)]
+#[doc(hidden)]
+pub use method::{check_params, concat_method_names, distinct_method_names, is_known_method};
+pub use method::{PluginMethod, ValidateParams};
+
 // Re-export procedural macros
-pub use hodu_plugin_sdk_macros::{define_params, define_result, plugin_handler, PluginMethod};
+pub use hodu_plugin_sdk_macros::{define_params, define_result, hodu_plugin, plugin_handler, PluginMethod};
 
 // Re-export tokio::main for plugin entry points
 pub use tokio::main;
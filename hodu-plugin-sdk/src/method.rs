@@ -0,0 +1,202 @@
+//! Handlers declared with `#[derive(PluginMethod)]`
+//!
+//! A handler struct names its method and defines `handle`; an enum groups handlers into the
+//! method set a plugin serves, registered in one call:
+//!
+//! ```ignore
+//! use hodu_plugin_sdk::rpc::{LoadModelParams, LoadModelResult, RpcError};
+//! use hodu_plugin_sdk::{Context, PluginMethod};
+//!
+//! #[derive(PluginMethod)]
+//! #[method(name = "format.load_model", validate)]
+//! struct LoadModel;
+//!
+//! impl LoadModel {
+//!     async fn handle(ctx: Context, params: LoadModelParams) -> Result<LoadModelResult, RpcError> {
+//!         // ...
+//!     }
+//! }
+//!
+//! #[derive(PluginMethod)]
+//! enum Methods {
+//!     LoadModel(LoadModel),
+//!     SaveModel(SaveModel),
+//! }
+//!
+//! PluginServer::new("my-format", "0.1.0").methods::<Methods>()
+//! ```
+//!
+//! Drift between what a plugin declares and what it serves fails to compile: a handler without a
+//! `handle` fn, a `backend.*` or `format.*` name that is not a protocol method, and an enum
+//! serving a method twice are all errors.
+
+use crate::plugin::MethodRegistry;
+use crate::rpc::{
+    methods, BenchmarkParams, BuildParams, CloseSessionParams, CustomOpParams, LoadModelParams, LoadSessionParams,
+    LoadTensorParams, QuantizeParams, RpcError, RunParams, RunSessionParams, SaveModelParams, SaveTensorParams,
+    StreamLoadTensorParams, ValidationError,
+};
+use crate::server::PluginServer;
+use crate::testing::TestHarness;
+
+/// A handler, or group of handlers, derived with `#[derive(PluginMethod)]`
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a plugin method",
+    label = "add `#[derive(PluginMethod)]` to `{Self}`"
+)]
+pub trait PluginMethod {
+    /// Methods registered by [`register_all`](Self::register_all), in order
+    const METHOD_NAMES: &'static [&'static str];
+
+    /// Register every method's handler with `registry`
+    fn register_all<M: MethodRegistry>(registry: M) -> M;
+}
+
+/// Params checked before a `#[method(validate)]` handler runs
+///
+/// Implemented for the protocol's params types; implement it for a custom method's params to
+/// validate those too.
+#[diagnostic::on_unimplemented(message = "`{Self}` has no `ValidateParams` impl, needed by `#[method(validate)]`")]
+pub trait ValidateParams {
+    /// Check the params, describing the first problem found
+    fn validate_params(&self) -> Result<(), ValidationError>;
+}
+
+macro_rules! validate_params {
+    ($($params:ty),* $(,)?) => {
+        $(
+            impl ValidateParams for $params {
+                fn validate_params(&self) -> Result<(), ValidationError> {
+                    self.validate()
+                }
+            }
+        )*
+    };
+}
+
+validate_params!(
+    LoadModelParams,
+    SaveModelParams,
+    LoadTensorParams,
+    StreamLoadTensorParams,
+    SaveTensorParams,
+    RunParams,
+    LoadSessionParams,
+    RunSessionParams,
+    CloseSessionParams,
+    BenchmarkParams,
+    CustomOpParams,
+    QuantizeParams,
+    BuildParams,
+);
+
+/// Answer invalid params with `INVALID_PARAMS`, before a `#[method(validate)]` handler runs
+#[doc(hidden)]
+pub fn check_params<P: ValidateParams>(params: &P) -> Result<(), RpcError> {
+    params
+        .validate_params()
+        .map_err(|e| RpcError::invalid_params(e.to_string()))
+}
+
+/// Every `backend.*` and `format.*` method of the protocol
+const PROTOCOL_METHODS: &[&str] = &[
+    methods::FORMAT_LOAD_MODEL,
+    methods::FORMAT_SAVE_MODEL,
+    methods::FORMAT_LOAD_TENSOR,
+    methods::FORMAT_SAVE_TENSOR,
+    methods::FORMAT_STREAM_LOAD_TENSOR,
+    methods::BACKEND_RUN,
+    methods::BACKEND_BUILD,
+    methods::BACKEND_SUPPORTED_DEVICES,
+    methods::BACKEND_SUPPORTED_TARGETS,
+    methods::BACKEND_LOAD_SESSION,
+    methods::BACKEND_RUN_SESSION,
+    methods::BACKEND_CLOSE_SESSION,
+    methods::BACKEND_BENCHMARK,
+    methods::BACKEND_PROFILE,
+    methods::BACKEND_QUANTIZE,
+];
+
+/// Whether `name` is a protocol method, or outside the `backend.*` and `format.*` namespaces
+#[doc(hidden)]
+pub const fn is_known_method(name: &str) -> bool {
+    if !starts_with(name, "backend.") && !starts_with(name, "format.") {
+        return true;
+    }
+    let mut i = 0;
+    while i < PROTOCOL_METHODS.len() {
+        if str_eq(name, PROTOCOL_METHODS[i]) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether no name appears twice in `names`
+#[doc(hidden)]
+pub const fn distinct_method_names(names: &[&str]) -> bool {
+    let mut i = 0;
+    while i < names.len() {
+        let mut j = i + 1;
+        while j < names.len() {
+            if str_eq(names[i], names[j]) {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// `groups` joined into one array of `N` names, for an enum's [`PluginMethod::METHOD_NAMES`]
+#[doc(hidden)]
+pub const fn concat_method_names<const N: usize>(groups: &[&[&'static str]]) -> [&'static str; N] {
+    let mut names = [""; N];
+    let mut n = 0;
+    let mut i = 0;
+    while i < groups.len() {
+        let mut j = 0;
+        while j < groups[i].len() {
+            names[n] = groups[i][j];
+            n += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    names
+}
+
+const fn starts_with(s: &str, prefix: &str) -> bool {
+    let (s, prefix) = (s.as_bytes(), prefix.as_bytes());
+    if s.len() < prefix.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < prefix.len() {
+        if s[i] != prefix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && starts_with(a, b)
+}
+
+impl PluginServer {
+    /// Serve every method of `M`, a `#[derive(PluginMethod)]` handler or group of handlers
+    pub fn methods<M: PluginMethod>(self) -> Self {
+        M::register_all(self)
+    }
+}
+
+impl TestHarness {
+    /// Register every method of `M`, a `#[derive(PluginMethod)]` handler or group of handlers
+    pub fn methods<M: PluginMethod>(self) -> Self {
+        M::register_all(self)
+    }
+}
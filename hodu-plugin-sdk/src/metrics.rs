@@ -0,0 +1,262 @@
+//! Request metrics in Prometheus text format
+//!
+//! The server records a count, a latency histogram and error counts for every method call, plus
+//! the number of requests in flight. Handlers add their own counters with `ctx.metric()`. The
+//! registry is process-wide and is read through the `$/metrics` method or the HTTP exporter
+//! started by [`PluginServer::metrics_http`](crate::server::PluginServer::metrics_http).
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::time::Duration;
+
+/// Prefix of every exported metric name
+const PREFIX: &str = "hodu_plugin_";
+
+/// Upper bounds of the latency histogram buckets, in seconds
+///
+/// Inference calls can take far longer than typical RPCs, so the buckets reach a minute.
+const LATENCY_BUCKETS: [f64; 13] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// Method label for calls to methods without a handler, so unknown names cannot grow the registry
+pub(crate) const UNKNOWN_METHOD: &str = "unknown";
+
+static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::default);
+
+/// Process-wide metrics registry
+#[derive(Default)]
+pub(crate) struct Registry {
+    methods: Mutex<BTreeMap<String, MethodStats>>,
+    counters: Mutex<BTreeMap<String, Counter>>,
+    active: AtomicI64,
+}
+
+/// Statistics for one method
+#[derive(Default)]
+struct MethodStats {
+    requests: u64,
+    /// Errors by error code
+    errors: BTreeMap<i32, u64>,
+    /// Calls per latency bucket (not cumulative); the last slot is `+Inf`
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    seconds: f64,
+}
+
+/// Get the process-wide registry
+pub(crate) fn registry() -> &'static Registry {
+    &REGISTRY
+}
+
+impl Registry {
+    /// Count a request as in flight until the returned guard is dropped
+    pub(crate) fn start_request(&'static self) -> ActiveRequest {
+        self.active.fetch_add(1, Ordering::Relaxed);
+        ActiveRequest { registry: self }
+    }
+
+    /// Record a finished call, with the error code if it failed
+    pub(crate) fn record(&self, method: &str, duration: Duration, error_code: Option<i32>) {
+        let mut methods = self.methods.lock().unwrap_or_else(PoisonError::into_inner);
+        let stats = methods.entry(method.to_string()).or_default();
+        let seconds = duration.as_secs_f64();
+        stats.requests += 1;
+        stats.seconds += seconds;
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        stats.buckets[bucket] += 1;
+        if let Some(code) = error_code {
+            *stats.errors.entry(code).or_default() += 1;
+        }
+    }
+
+    /// Get the custom counter `name`, creating it on first use
+    pub(crate) fn counter(&self, name: &str) -> Counter {
+        let name = sanitize_name(name);
+        self.counters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(name)
+            .or_default()
+            .clone()
+    }
+
+    /// Render every metric in the Prometheus text exposition format
+    pub(crate) fn render(&self) -> String {
+        let mut out = String::new();
+        let methods = self.methods.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let _ = writeln!(out, "# HELP {}requests_total Method calls handled.", PREFIX);
+        let _ = writeln!(out, "# TYPE {}requests_total counter", PREFIX);
+        for (method, stats) in methods.iter() {
+            let _ = writeln!(
+                out,
+                "{}requests_total{{method=\"{}\"}} {}",
+                PREFIX,
+                escape_label(method),
+                stats.requests
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP {}request_errors_total Method calls that returned an error.",
+            PREFIX
+        );
+        let _ = writeln!(out, "# TYPE {}request_errors_total counter", PREFIX);
+        for (method, stats) in methods.iter() {
+            for (code, count) in &stats.errors {
+                let _ = writeln!(
+                    out,
+                    "{}request_errors_total{{method=\"{}\",code=\"{}\"}} {}",
+                    PREFIX,
+                    escape_label(method),
+                    code,
+                    count
+                );
+            }
+        }
+
+        let _ = writeln!(out, "# HELP {}request_duration_seconds Method call latency.", PREFIX);
+        let _ = writeln!(out, "# TYPE {}request_duration_seconds histogram", PREFIX);
+        for (method, stats) in methods.iter() {
+            let method = escape_label(method);
+            let mut cumulative = 0;
+            for (i, count) in stats.buckets.iter().enumerate() {
+                cumulative += count;
+                let le = LATENCY_BUCKETS.get(i).map_or("+Inf".to_string(), f64::to_string);
+                let _ = writeln!(
+                    out,
+                    "{}request_duration_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                    PREFIX, method, le, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "{}request_duration_seconds_sum{{method=\"{}\"}} {}",
+                PREFIX, method, stats.seconds
+            );
+            let _ = writeln!(
+                out,
+                "{}request_duration_seconds_count{{method=\"{}\"}} {}",
+                PREFIX, method, stats.requests
+            );
+        }
+        drop(methods);
+
+        let _ = writeln!(out, "# HELP {}active_requests Method calls in flight.", PREFIX);
+        let _ = writeln!(out, "# TYPE {}active_requests gauge", PREFIX);
+        let _ = writeln!(out, "{}active_requests {}", PREFIX, self.active.load(Ordering::Relaxed));
+
+        let counters = self.counters.lock().unwrap_or_else(PoisonError::into_inner);
+        for (name, counter) in counters.iter() {
+            let _ = writeln!(out, "# TYPE {}{} counter", PREFIX, name);
+            let _ = writeln!(out, "{}{} {}", PREFIX, name, counter.get());
+        }
+
+        out
+    }
+}
+
+/// Keeps a request counted as in flight
+pub(crate) struct ActiveRequest {
+    registry: &'static Registry,
+}
+
+impl Drop for ActiveRequest {
+    fn drop(&mut self) {
+        self.registry.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A custom counter, exported as `hodu_plugin_<name>`
+///
+/// Obtained with `ctx.metric(name)`. Clones share the same value.
+///
+/// # Example
+///
+/// ```ignore
+/// ctx.metric("tokens_generated_total").add(tokens.len() as u64);
+/// ```
+#[derive(Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    /// Add one
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    /// Add `n`
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Current value
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Replace characters Prometheus does not allow in metric names with `_`
+fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "unnamed".to_string()
+    } else {
+        name
+    }
+}
+
+/// Escape a label value for the text format
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Bind `addrs` and serve `GET /metrics` from a background thread
+///
+/// The exporter speaks just enough HTTP/1.1 for Prometheus scrapes: each connection gets one
+/// response and is closed.
+pub(crate) fn start_http_exporter(addrs: &[SocketAddr]) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addrs)?;
+    log::info!("Serving metrics on http://{}/metrics", listener.local_addr()?);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+            let mut request_line = String::new();
+            if BufReader::new(&stream).read_line(&mut request_line).is_err() {
+                continue;
+            }
+            let mut parts = request_line.split_whitespace();
+            let response = match (parts.next(), parts.next()) {
+                (Some("GET"), Some("/metrics")) => {
+                    let body = registry().render();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                },
+                _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,282 @@
+//! Plugins defined as trait impls
+//!
+//! Implement [`BackendPlugin`] or [`FormatPlugin`] and annotate the impl with
+//! [`#[hodu_plugin]`](crate::hodu_plugin). Every method the impl defines is registered under its
+//! protocol name, so the capabilities the plugin reports follow from the code, and a `main` that
+//! serves `Default::default()` is generated:
+//!
+//! ```ignore
+//! use hodu_plugin_sdk::rpc::{RunParams, RunResult, RpcError};
+//! use hodu_plugin_sdk::{hodu_plugin, BackendPlugin, Context};
+//!
+//! #[derive(Default)]
+//! struct MyBackend;
+//!
+//! #[hodu_plugin]
+//! impl BackendPlugin for MyBackend {
+//!     const DEVICES: &'static [&'static str] = &["cpu"];
+//!
+//!     async fn run(&self, ctx: Context, params: RunParams) -> Result<RunResult, RpcError> {
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! Methods left out keep their default, which answers `NOT_SUPPORTED`, and are not registered.
+
+use crate::rpc::{
+    BenchmarkParams, BenchmarkResult, BuildParams, CloseSessionParams, LoadModelParams, LoadModelResult,
+    LoadSessionParams, LoadSessionResult, LoadTensorParams, LoadTensorResult, ProfileParams, ProfileResult,
+    QuantizeParams, QuantizeResult, RpcError, RunParams, RunResult, RunSessionParams, SaveModelParams,
+    SaveTensorParams, StreamLoadTensorParams, StreamLoadTensorResult,
+};
+use crate::server::PluginServer;
+use crate::testing::TestHarness;
+use crate::Context;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+
+/// A backend plugin: `backend.*` methods as trait methods
+///
+/// Annotate the impl with [`#[hodu_plugin]`](crate::hodu_plugin) to register the methods it
+/// defines.
+pub trait BackendPlugin: Send + Sync + 'static {
+    /// Devices the backend runs on, e.g. `&["cpu"]`
+    const DEVICES: &'static [&'static str] = &[];
+
+    /// Adjust the server before the methods are registered (metadata, config, limits)
+    fn configure(server: PluginServer) -> PluginServer {
+        server
+    }
+
+    /// `backend.run`: run inference
+    fn run(&self, ctx: Context, params: RunParams) -> impl Future<Output = Result<RunResult, RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("backend.run")
+    }
+
+    /// `backend.build`: compile a model ahead of time
+    fn build(&self, ctx: Context, params: BuildParams) -> impl Future<Output = Result<(), RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("backend.build")
+    }
+
+    /// `backend.load_session`: load a model to serve many runs
+    fn load_session(
+        &self,
+        ctx: Context,
+        params: LoadSessionParams,
+    ) -> impl Future<Output = Result<LoadSessionResult, RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("backend.load_session")
+    }
+
+    /// `backend.run_session`: run inference on a loaded session
+    fn run_session(
+        &self,
+        ctx: Context,
+        params: RunSessionParams,
+    ) -> impl Future<Output = Result<RunResult, RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("backend.run_session")
+    }
+
+    /// `backend.close_session`: release a session
+    fn close_session(
+        &self,
+        ctx: Context,
+        params: CloseSessionParams,
+    ) -> impl Future<Output = Result<(), RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("backend.close_session")
+    }
+
+    /// `backend.benchmark`: time repeated runs
+    fn benchmark(
+        &self,
+        ctx: Context,
+        params: BenchmarkParams,
+    ) -> impl Future<Output = Result<BenchmarkResult, RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("backend.benchmark")
+    }
+
+    /// `backend.profile`: run once, recording per-op timings and memory
+    fn profile(
+        &self,
+        ctx: Context,
+        params: ProfileParams,
+    ) -> impl Future<Output = Result<ProfileResult, RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("backend.profile")
+    }
+
+    /// `backend.quantize`: write a quantized copy of a snapshot
+    fn quantize(
+        &self,
+        ctx: Context,
+        params: QuantizeParams,
+    ) -> impl Future<Output = Result<QuantizeResult, RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("backend.quantize")
+    }
+}
+
+/// A model or tensor format plugin: `format.*` methods as trait methods
+///
+/// Annotate the impl with [`#[hodu_plugin]`](crate::hodu_plugin) to register the methods it
+/// defines.
+pub trait FormatPlugin: Send + Sync + 'static {
+    /// Model file extensions the plugin loads or saves, e.g. `&["onnx"]`
+    const MODEL_EXTENSIONS: &'static [&'static str] = &[];
+    /// Tensor file extensions the plugin loads or saves, e.g. `&["npy"]`
+    const TENSOR_EXTENSIONS: &'static [&'static str] = &[];
+
+    /// Adjust the server before the methods are registered (metadata, config, limits)
+    fn configure(server: PluginServer) -> PluginServer {
+        server
+    }
+
+    /// `format.load_model`: convert a model file to a snapshot
+    fn load_model(
+        &self,
+        ctx: Context,
+        params: LoadModelParams,
+    ) -> impl Future<Output = Result<LoadModelResult, RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("format.load_model")
+    }
+
+    /// `format.save_model`: write a snapshot in the plugin's format
+    fn save_model(&self, ctx: Context, params: SaveModelParams) -> impl Future<Output = Result<(), RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("format.save_model")
+    }
+
+    /// `format.load_tensor`: convert a tensor file to .hdt
+    fn load_tensor(
+        &self,
+        ctx: Context,
+        params: LoadTensorParams,
+    ) -> impl Future<Output = Result<LoadTensorResult, RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("format.load_tensor")
+    }
+
+    /// `format.save_tensor`: write an .hdt tensor in the plugin's format
+    fn save_tensor(&self, ctx: Context, params: SaveTensorParams) -> impl Future<Output = Result<(), RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("format.save_tensor")
+    }
+
+    /// `format.stream_load_tensor`: load a tensor file, streaming its data in chunks
+    fn stream_load_tensor(
+        &self,
+        ctx: Context,
+        params: StreamLoadTensorParams,
+    ) -> impl Future<Output = Result<StreamLoadTensorResult, RpcError>> + Send {
+        let _ = (ctx, params);
+        not_supported("format.stream_load_tensor")
+    }
+}
+
+fn not_supported<R: Send>(method: &str) -> impl Future<Output = Result<R, RpcError>> + Send {
+    std::future::ready(Err(RpcError::not_supported(method)))
+}
+
+/// Something method handlers are registered with: [`PluginServer`] or [`TestHarness`]
+pub trait MethodRegistry: Sized {
+    /// Register `handler` for `name`
+    fn method<F, Fut, P, R>(self, name: &str, handler: F) -> Self
+    where
+        F: Fn(Context, P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, RpcError>> + Send + 'static,
+        P: DeserializeOwned + Send + 'static,
+        R: Serialize + 'static;
+}
+
+impl MethodRegistry for PluginServer {
+    fn method<F, Fut, P, R>(self, name: &str, handler: F) -> Self
+    where
+        F: Fn(Context, P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, RpcError>> + Send + 'static,
+        P: DeserializeOwned + Send + 'static,
+        R: Serialize + 'static,
+    {
+        PluginServer::method(self, name, handler)
+    }
+}
+
+impl MethodRegistry for TestHarness {
+    fn method<F, Fut, P, R>(self, name: &str, handler: F) -> Self
+    where
+        F: Fn(Context, P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, RpcError>> + Send + 'static,
+        P: DeserializeOwned + Send + 'static,
+        R: Serialize + 'static,
+    {
+        self.handler(name, handler)
+    }
+}
+
+/// Registers the methods a `#[hodu_plugin]` impl of [`BackendPlugin`] defines
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(message = "annotate the `impl BackendPlugin for {Self}` with `#[hodu_plugin]`")]
+pub trait RegisterBackend: BackendPlugin {
+    fn register<M: MethodRegistry>(plugin: Arc<Self>, registry: M) -> M;
+}
+
+/// Registers the methods a `#[hodu_plugin]` impl of [`FormatPlugin`] defines
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(message = "annotate the `impl FormatPlugin for {Self}` with `#[hodu_plugin]`")]
+pub trait RegisterFormat: FormatPlugin {
+    fn register<M: MethodRegistry>(plugin: Arc<Self>, registry: M) -> M;
+}
+
+impl PluginServer {
+    /// Serve the methods `plugin`'s `#[hodu_plugin]` impl of [`BackendPlugin`] defines
+    pub fn backend<P: RegisterBackend>(self, plugin: P) -> Self {
+        let mut server = P::configure(self);
+        if !P::DEVICES.is_empty() {
+            server = server.devices(P::DEVICES.to_vec());
+        }
+        P::register(Arc::new(plugin), server)
+    }
+
+    /// Serve the methods `plugin`'s `#[hodu_plugin]` impl of [`FormatPlugin`] defines
+    pub fn format<P: RegisterFormat>(self, plugin: P) -> Self {
+        let mut server = P::configure(self);
+        if !P::MODEL_EXTENSIONS.is_empty() {
+            server = server.model_extensions(P::MODEL_EXTENSIONS.to_vec());
+        }
+        if !P::TENSOR_EXTENSIONS.is_empty() {
+            server = server.tensor_extensions(P::TENSOR_EXTENSIONS.to_vec());
+        }
+        P::register(Arc::new(plugin), server)
+    }
+
+    /// Run the server on a Tokio runtime of its own, for a `main` that is not async
+    ///
+    /// # Errors
+    /// Returns error if the runtime cannot be started, or as [`run`](Self::run) does.
+    pub fn run_blocking(self) -> Result<(), Box<dyn std::error::Error>> {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?
+            .block_on(self.run())
+    }
+}
+
+impl TestHarness {
+    /// Register the methods `plugin`'s `#[hodu_plugin]` impl of [`BackendPlugin`] defines
+    pub fn backend<P: RegisterBackend>(self, plugin: P) -> Self {
+        P::register(Arc::new(plugin), self)
+    }
+
+    /// Register the methods `plugin`'s `#[hodu_plugin]` impl of [`FormatPlugin`] defines
+    pub fn format<P: RegisterFormat>(self, plugin: P) -> Self {
+        P::register(Arc::new(plugin), self)
+    }
+}
@@ -0,0 +1,182 @@
+//! Builder for `backend.profile` results
+//!
+//! [`ProfileBuilder`] records ops and allocations against one clock and produces the
+//! [`ProfileResult`] the CLI renders as a table and a chrome://tracing file. Backends timing ops
+//! themselves wrap each op in [`time_op`](ProfileBuilder::time_op), or record durations taken
+//! from device timers with [`record_op`](ProfileBuilder::record_op):
+//!
+//! ```ignore
+//! async fn handle_profile(ctx: Context, params: ProfileParams) -> Result<ProfileResult, RpcError> {
+//!     let model = LoadedModel::open(&params.library_path, &params.device)?;
+//!     let mut profile = ProfileBuilder::new();
+//!     for (index, kernel) in model.kernels().enumerate() {
+//!         profile
+//!             .time_op(kernel.op_name(), &params.device, || kernel.launch_and_wait())
+//!             .node(index)
+//!             .kernel(kernel.name())
+//!             .into_value()?;
+//!     }
+//!     Ok(profile.peak_memory(model.peak_device_memory()).finish(model.save_outputs()?))
+//! }
+//! ```
+//!
+//! Backends running snapshots on the hodu interpreter get the same schema from
+//! [`extend_from_core`](ProfileBuilder::extend_from_core).
+
+use crate::rpc::{ProfileResult, ProfiledAlloc, ProfiledOp, TensorOutput};
+use hodu_core::profiler::Profile;
+use std::time::{Duration, Instant};
+
+/// Collects the ops and allocations of one profiled run
+pub struct ProfileBuilder {
+    origin: Instant,
+    ops: Vec<ProfiledOp>,
+    allocs: Vec<ProfiledAlloc>,
+    peak_memory_bytes: Option<u64>,
+}
+
+impl Default for ProfileBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProfileBuilder {
+    /// Start a profile; op and allocation times are measured from now
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            ops: Vec::new(),
+            allocs: Vec::new(),
+            peak_memory_bytes: None,
+        }
+    }
+
+    /// Run `f` and record it as op `name` on `device`
+    ///
+    /// Host time only covers the device's work if `f` waits for it to finish.
+    pub fn time_op<T>(&mut self, name: impl Into<String>, device: &str, f: impl FnOnce() -> T) -> OpRecord<'_, T> {
+        let start = Instant::now();
+        let value = f();
+        let duration = start.elapsed();
+        let op = self.record_op(name, device, start, duration);
+        OpRecord { op, value }
+    }
+
+    /// Record op `name` that ran on `device` for `duration` from `start`
+    ///
+    /// Returns the op to attach a node, kernel, allocated bytes or metadata to.
+    pub fn record_op(
+        &mut self,
+        name: impl Into<String>,
+        device: &str,
+        start: Instant,
+        duration: Duration,
+    ) -> &mut ProfiledOp {
+        self.ops.push(ProfiledOp {
+            name: name.into(),
+            device: device.to_string(),
+            start_us: self.elapsed_us(start),
+            duration_us: duration.as_secs_f64() * 1e6,
+            ..ProfiledOp::default()
+        });
+        self.ops.last_mut().expect("op was just pushed")
+    }
+
+    /// Record a buffer of `bytes` allocated on `device` now
+    pub fn record_alloc(&mut self, device: &str, bytes: u64, cached: bool) -> &mut Self {
+        let time_us = self.elapsed_us(Instant::now());
+        self.allocs.push(ProfiledAlloc {
+            device: device.to_string(),
+            time_us,
+            bytes,
+            cached,
+        });
+        self
+    }
+
+    /// Set the peak memory of the run, e.g. the device allocator's high-water mark
+    pub fn peak_memory(&mut self, bytes: Option<u64>) -> &mut Self {
+        self.peak_memory_bytes = bytes;
+        self
+    }
+
+    /// Add the ops and allocations of a profile recorded by `hodu_core::profiler`
+    ///
+    /// The node index the interpreter records in the op's `node` argument becomes
+    /// [`ProfiledOp::node`]. Times keep the core profile's own origin.
+    pub fn extend_from_core(&mut self, profile: &Profile) -> &mut Self {
+        for op in &profile.ops {
+            let mut args: std::collections::BTreeMap<String, String> = op.args.iter().cloned().collect();
+            let node = args.remove("node").and_then(|node| node.parse().ok());
+            self.ops.push(ProfiledOp {
+                name: op.name.clone(),
+                node,
+                kernel: None,
+                device: op.device.to_string(),
+                start_us: op.start_us,
+                duration_us: op.duration_us,
+                allocated_bytes: op.allocated_bytes as u64,
+                args,
+            });
+        }
+        self.allocs.extend(profile.allocs.iter().map(|alloc| ProfiledAlloc {
+            device: alloc.device.to_string(),
+            time_us: alloc.time_us,
+            bytes: alloc.bytes as u64,
+            cached: alloc.cached,
+        }));
+        self
+    }
+
+    /// Finish the profile with the run's outputs
+    pub fn finish(&mut self, outputs: Vec<TensorOutput>) -> ProfileResult {
+        ProfileResult {
+            outputs,
+            ops: std::mem::take(&mut self.ops),
+            allocs: std::mem::take(&mut self.allocs),
+            peak_memory_bytes: self.peak_memory_bytes,
+        }
+    }
+
+    fn elapsed_us(&self, at: Instant) -> f64 {
+        at.saturating_duration_since(self.origin).as_secs_f64() * 1e6
+    }
+}
+
+/// An op recorded by [`ProfileBuilder::time_op`], with the value its closure returned
+pub struct OpRecord<'a, T> {
+    op: &'a mut ProfiledOp,
+    value: T,
+}
+
+impl<T> OpRecord<'_, T> {
+    /// Set the snapshot node the op executes
+    pub fn node(self, node: usize) -> Self {
+        self.op.node = Some(node);
+        self
+    }
+
+    /// Set the kernel that ran
+    pub fn kernel(self, kernel: impl Into<String>) -> Self {
+        self.op.kernel = Some(kernel.into());
+        self
+    }
+
+    /// Set the bytes allocated while the op ran
+    pub fn allocated_bytes(self, bytes: u64) -> Self {
+        self.op.allocated_bytes = bytes;
+        self
+    }
+
+    /// Add op metadata such as a shape or dtype
+    pub fn arg(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.op.args.insert(key.into(), value.into());
+        self
+    }
+
+    /// Value returned by the timed closure
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
@@ -0,0 +1,153 @@
+//! Helpers for `backend.quantize`
+//!
+//! [`load_calibration`] reads the calibration samples of a [`QuantizeParams`], and
+//! [`AccuracyReport`] compares the float and quantized outputs on them to fill in
+//! [`QuantizeResult::accuracy`](crate::rpc::QuantizeResult::accuracy):
+//!
+//! ```ignore
+//! async fn handle_quantize(ctx: Context, params: QuantizeParams) -> Result<QuantizeResult, RpcError> {
+//!     let model = FloatModel::open(&params.snapshot_path, &params.device)?;
+//!     let samples = hodu_plugin_sdk::load_calibration(&params)?;
+//!     let quantized = model.quantize(&params.scheme, params.bits, &samples)?;
+//!     quantized.save(&params.output_path)?;
+//!
+//!     let mut report = AccuracyReport::new();
+//!     for sample in &samples {
+//!         for ((name, reference), (_, output)) in model.run(sample)?.iter().zip(&quantized.run(sample)?) {
+//!             report.compare(name, reference, output)?;
+//!         }
+//!     }
+//!     Ok(QuantizeResult {
+//!         snapshot_path: params.output_path,
+//!         quantized_nodes: quantized.quantized_nodes(),
+//!         accuracy: report.finish(),
+//!     })
+//! }
+//! ```
+
+use crate::rpc::{OutputAccuracy, QuantizeParams, RpcError};
+use crate::tensor_io::load_input_tensor;
+use hodu_core::{tensor::Tensor, types::DType};
+
+/// Load every calibration sample of `params` as `(input name, tensor)` pairs, in request order
+///
+/// # Errors
+/// Returns a tensor error naming the first input that fails to load.
+pub fn load_calibration(params: &QuantizeParams) -> Result<Vec<Vec<(String, Tensor)>>, RpcError> {
+    params
+        .calibration
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            sample
+                .iter()
+                .map(|input| {
+                    let tensor = load_input_tensor(input).map_err(|e| {
+                        RpcError::tensor_error(format!(
+                            "Failed to load calibration sample {} input '{}': {}",
+                            i, input.name, e
+                        ))
+                    })?;
+                    Ok((input.name.clone(), tensor))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Accumulates the error of quantized outputs against float outputs over calibration samples
+#[derive(Default)]
+pub struct AccuracyReport {
+    outputs: Vec<OutputError>,
+}
+
+/// Running sums for one output
+struct OutputError {
+    name: String,
+    count: usize,
+    max_abs: f64,
+    sum_abs: f64,
+    signal: f64,
+    noise: f64,
+}
+
+impl AccuracyReport {
+    /// Start an empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare one sample's float output `reference` with the quantized output of the same name
+    ///
+    /// # Errors
+    /// Returns a tensor error if either tensor cannot be read as f32, or their sizes differ.
+    pub fn compare(&mut self, name: &str, reference: &Tensor, quantized: &Tensor) -> Result<(), RpcError> {
+        let reference = to_f32(name, reference)?;
+        let quantized = to_f32(name, quantized)?;
+        self.compare_values(name, &reference, &quantized)
+    }
+
+    /// Compare one sample's float output values with the quantized ones
+    ///
+    /// # Errors
+    /// Returns a tensor error if the two have different lengths.
+    pub fn compare_values(&mut self, name: &str, reference: &[f32], quantized: &[f32]) -> Result<(), RpcError> {
+        if reference.len() != quantized.len() {
+            return Err(RpcError::tensor_error(format!(
+                "Output '{}' has {} elements, but its quantized output has {}",
+                name,
+                reference.len(),
+                quantized.len()
+            )));
+        }
+        let index = match self.outputs.iter().position(|output| output.name == name) {
+            Some(index) => index,
+            None => {
+                self.outputs.push(OutputError {
+                    name: name.to_string(),
+                    count: 0,
+                    max_abs: 0.0,
+                    sum_abs: 0.0,
+                    signal: 0.0,
+                    noise: 0.0,
+                });
+                self.outputs.len() - 1
+            },
+        };
+        let output = &mut self.outputs[index];
+        for (&r, &q) in reference.iter().zip(quantized) {
+            let (r, q) = (f64::from(r), f64::from(q));
+            let error = (r - q).abs();
+            output.max_abs = output.max_abs.max(error);
+            output.sum_abs += error;
+            output.signal += r * r;
+            output.noise += error * error;
+        }
+        output.count += reference.len();
+        Ok(())
+    }
+
+    /// The accuracy of every compared output, in the order first compared
+    pub fn finish(self) -> Vec<OutputAccuracy> {
+        self.outputs
+            .into_iter()
+            .map(|output| OutputAccuracy {
+                name: output.name,
+                max_abs_error: output.max_abs,
+                mean_abs_error: if output.count > 0 {
+                    output.sum_abs / output.count as f64
+                } else {
+                    0.0
+                },
+                sqnr_db: (output.noise > 0.0).then(|| 10.0 * (output.signal / output.noise).log10()),
+            })
+            .collect()
+    }
+}
+
+fn to_f32(name: &str, tensor: &Tensor) -> Result<Vec<f32>, RpcError> {
+    tensor
+        .to_dtype(DType::F32)
+        .and_then(|tensor| tensor.to_flatten_vec::<f32>())
+        .map_err(|e| RpcError::tensor_error(format!("Failed to read output '{}' as f32: {}", name, e)))
+}
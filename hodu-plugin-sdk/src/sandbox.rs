@@ -0,0 +1,46 @@
+//! The filesystem sandbox the CLI confines untrusted plugins to
+//!
+//! The CLI sends a [`SandboxPolicy`] in `initialize` for plugins the user has not trusted. Once
+//! it has, the SDK's file helpers ([`TensorDataExt::load`](crate::TensorDataExt::load) and
+//! [`save`](crate::TensorDataExt::save), [`hdt`](crate::hdt), [`hdss`](crate::hdss),
+//! [`load_input`](crate::load_input) and [`Context::output`](crate::Context::output)) refuse paths
+//! outside its roots. Plugins doing their own file I/O should check paths the same way:
+//!
+//! ```ignore
+//! sandbox::check_write(&params.output_path).map_err(|e| RpcError::access_denied(e.to_string()))?;
+//! std::fs::write(&params.output_path, &artifact)?;
+//! ```
+
+use crate::SandboxPolicy;
+use std::io;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Policy from the last `initialize`; `None` when the plugin is trusted
+static POLICY: RwLock<Option<SandboxPolicy>> = RwLock::new(None);
+
+/// The sandbox the plugin runs in, or `None` if the CLI trusts it with any file
+pub fn policy() -> Option<SandboxPolicy> {
+    POLICY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Fail with `PermissionDenied` if the sandbox does not allow reading `path`
+pub fn check_read(path: impl AsRef<Path>) -> io::Result<()> {
+    match &*POLICY.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(policy) => policy.check_read(path),
+        None => Ok(()),
+    }
+}
+
+/// Fail with `PermissionDenied` if the sandbox does not allow writing `path`
+pub fn check_write(path: impl AsRef<Path>) -> io::Result<()> {
+    match &*POLICY.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(policy) => policy.check_write(path),
+        None => Ok(()),
+    }
+}
+
+/// Apply the policy the CLI sent in `initialize`
+pub(crate) fn set_policy(policy: Option<SandboxPolicy>) {
+    *POLICY.write().unwrap_or_else(|e| e.into_inner()) = policy;
+}
@@ -1,6 +1,8 @@
-//! Plugin server framework for JSON-RPC communication over stdio
+//! Plugin server framework for JSON-RPC communication over stdio or sockets
 //!
 //! This module provides the runtime for plugins to handle JSON-RPC requests.
+//! [`PluginServer::run`] serves the CLI that spawned the plugin over stdio, while
+//! [`PluginServer::listen_tcp`] and [`PluginServer::listen_unix`] run it as a long-lived daemon.
 //!
 //! # Example
 //!
@@ -28,29 +30,53 @@
 //! ```
 
 use crate::context::{CancellationHandle, Context};
+use crate::metrics;
 use crate::rpc::{
-    error_codes, methods, CancelParams, InitializeParams, InitializeResult, Notification, PluginMetadataRpc, Request,
-    RequestId, Response, RpcError, PROTOCOL_VERSION,
+    error_codes, features, methods, negotiate_features, negotiate_inline_tensor_limit, CancelParams, CancelReason,
+    CustomOpParams, InitializeParams, InitializeResult, LogParams, MetricsResult, Notification, PluginMetadataRpc,
+    ProgressParams, Request, RequestId, Response, RpcError, RunResult, StreamCreditParams, StreamEvent,
+    DEFAULT_INLINE_TENSOR_LIMIT, IDLE_TIMEOUT_ENV, INITIAL_STREAM_CREDITS, LISTEN_ENV, MAX_INLINE_TENSOR_LIMIT,
+    PROTOCOL_VERSION,
 };
+use crate::status::StatusSource;
 use crate::PLUGIN_VERSION;
+use hodu_plugin::framing::{read_message, Frame, Framing};
 use serde::{de::DeserializeOwned, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 use std::io::{BufRead, BufReader, Write};
 use std::pin::Pin;
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 /// Maximum allowed request size (1MB)
 const MAX_REQUEST_SIZE: usize = 1024 * 1024;
 
+/// How often a stdio plugin checks whether the CLI that spawned it is still alive
+const PARENT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Messages the reader task may read ahead of the server loop
+const INCOMING_QUEUE_SIZE: usize = 64;
+
 /// Maximum allowed batch request count (prevents DoS)
 const MAX_BATCH_SIZE: usize = 100;
 
 /// Default request timeout (5 minutes)
 const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
 
+/// Default time in-flight requests get to finish after `shutdown`
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default time the shutdown callback gets to finish before the server exits anyway
+const DEFAULT_SHUTDOWN_CALLBACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time a daemon's client gets to complete `initialize` before it is disconnected
+const DEFAULT_INITIALIZE_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Maximum StreamWriter chunk size (10MB)
 ///
 /// This limit prevents memory exhaustion from single large chunk writes.
@@ -92,7 +118,7 @@ fn truncate_utf8(s: &str, max_bytes: usize) -> &str {
 }
 
 /// Reserved method name prefixes that plugins cannot register
-const RESERVED_PREFIXES: &[&str] = &["$/", "rpc.", "system."];
+const RESERVED_PREFIXES: &[&str] = &["$/", "rpc.", "system.", "host."];
 
 // ============================================================================
 // Param Deserialization Helper
@@ -142,13 +168,174 @@ pub struct PluginMetadata {
 // Shutdown Handler
 // ============================================================================
 
-/// Type for shutdown cleanup callback
-type ShutdownCallback = Box<dyn FnOnce() + Send + 'static>;
+/// State registered with `with_state`, type-erased
+type SharedState = Arc<dyn std::any::Any + Send + Sync>;
+
+/// Type for shutdown cleanup callback, given the shared state if any
+type ShutdownCallback =
+    Box<dyn FnOnce(Option<SharedState>) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + 'static>;
+
+// ============================================================================
+// Plugin Configuration
+// ============================================================================
+
+/// Turns the config sent in `initialize` into the registered config type
+type ConfigParser =
+    Box<dyn Fn(Option<serde_json::Value>) -> Result<Arc<dyn std::any::Any + Send + Sync>, String> + Send + Sync>;
+
+/// Describe a config type by the shape of its default value
+///
+/// Produces a JSON Schema with one property per field, typed after the default and carrying it
+/// as `default`. Fields whose default is `null` (e.g. `Option` fields) accept any value.
+fn schema_from_default(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::{json, Value};
+    match value {
+        Value::Object(fields) => {
+            let properties: serde_json::Map<String, Value> = fields
+                .iter()
+                .map(|(name, field)| {
+                    let mut schema = schema_from_default(field);
+                    if !field.is_object() {
+                        if let Some(schema) = schema.as_object_mut() {
+                            schema.insert("default".to_string(), field.clone());
+                        }
+                    }
+                    (name.clone(), schema)
+                })
+                .collect();
+            json!({ "type": "object", "properties": properties })
+        },
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(n) if n.is_f64() => json!({ "type": "number" }),
+        Value::Number(_) => json!({ "type": "integer" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(_) => json!({ "type": "array" }),
+        Value::Null => json!({}),
+    }
+}
 
 // ============================================================================
 // Middleware/Hooks
 // ============================================================================
 
+/// A method call passing through the middleware chain
+///
+/// Layers may rewrite `params` before passing the call on; the handler parses whatever arrives.
+pub struct MethodCall {
+    method: String,
+    /// The raw params (if any)
+    pub params: Option<serde_json::Value>,
+    ctx: Context,
+}
+
+impl MethodCall {
+    /// The method being called
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The request ID
+    pub fn id(&self) -> &RequestId {
+        self.ctx.request_id()
+    }
+
+    /// The context the handler will receive
+    pub fn context(&self) -> &Context {
+        &self.ctx
+    }
+}
+
+/// The rest of the middleware chain, ending in the method's handler
+pub struct Next {
+    layers: Arc<Vec<Layer>>,
+    index: usize,
+    endpoint: Endpoint,
+}
+
+impl Next {
+    /// Pass the call to the next layer, or to the handler after the last one
+    pub async fn run(self, call: MethodCall) -> Result<serde_json::Value, RpcError> {
+        match self.layers.get(self.index).cloned() {
+            Some(layer) => {
+                layer(
+                    call,
+                    Next {
+                        index: self.index + 1,
+                        ..self
+                    },
+                )
+                .await
+            },
+            None => self.endpoint.run(call).await,
+        }
+    }
+}
+
+/// End of the middleware chain: runs the handler on its own task
+struct Endpoint {
+    /// Handler for the method, or the error to answer with instead
+    handler: Result<HandlerFn, RpcError>,
+    timeout: Option<Duration>,
+    limits: Option<Arc<MethodLimits>>,
+}
+
+impl Endpoint {
+    async fn run(self, call: MethodCall) -> Result<serde_json::Value, RpcError> {
+        let handler = self.handler?;
+        let MethodCall { method, params, ctx } = call;
+        // Held until the handler finishes, freeing the concurrency slot
+        let _permit = match &self.limits {
+            Some(limits) => limits.admit(&method)?,
+            None => None,
+        };
+        let deadline = async {
+            match self.timeout {
+                Some(timeout_duration) => tokio::time::sleep(timeout_duration).await,
+                None => std::future::pending().await,
+            }
+        };
+        let cancel_handle = CancellationHandle::new(&ctx);
+        let cancelled = ctx.cancellation_token().clone();
+
+        // Execute handler on its own task so a timeout or `$/cancel` can abort it
+        // instead of leaving it running unobserved; it stays inside the request's span
+        let mut task = tokio::spawn(handler(ctx, params).in_current_span());
+        let result = tokio::select! {
+            joined = &mut task => match joined {
+                Ok(result) => result,
+                Err(e) if e.is_panic() => {
+                    Err(RpcError::internal_error(format!("Handler for '{}' panicked", method)))
+                },
+                Err(_) => Err(RpcError::cancelled()),
+            },
+            () = deadline => {
+                cancel_handle.cancel(CancelReason::Timeout);
+                task.abort();
+                Err(RpcError::new(
+                    error_codes::REQUEST_CANCELLED,
+                    format!("Request timed out after {:?}", self.timeout.unwrap_or_default()),
+                ))
+            },
+            () = cancelled.cancelled() => {
+                task.abort();
+                Err(RpcError::cancelled())
+            },
+        };
+
+        // Cleanups wait for the aborted handler to stop, so they never race its writes
+        if result.is_err() && cancelled.is_cancelled() && cancel_handle.has_cleanups() {
+            if !task.is_finished() {
+                let _ = task.await;
+            }
+            cancel_handle.run_cleanups();
+        }
+        result
+    }
+}
+
+/// Type-erased middleware layer
+type Layer = Arc<dyn Fn(MethodCall, Next) -> HandlerFuture + Send + Sync>;
+
 /// Information about an incoming request (for hooks)
 #[derive(Clone)]
 pub struct RequestInfo {
@@ -183,11 +370,76 @@ pub enum PreRequestAction {
     Reject(RpcError),
 }
 
-/// Type for pre-request hook
-type PreRequestHook = Box<dyn Fn(&RequestInfo) -> PreRequestAction + Send + Sync>;
+// ============================================================================
+// Rate and Concurrency Limits
+// ============================================================================
 
-/// Type for post-request hook
-type PostRequestHook = Box<dyn Fn(&ResponseInfo) + Send + Sync>;
+/// Limits for one method, checked before its handler runs
+#[derive(Default)]
+struct MethodLimits {
+    rate: Option<std::sync::Mutex<TokenBucket>>,
+    concurrency: Option<(usize, Arc<tokio::sync::Semaphore>)>,
+}
+
+impl MethodLimits {
+    /// Admit one call, or answer `BUSY`
+    ///
+    /// The returned permit holds a concurrency slot until it is dropped.
+    fn admit(&self, method: &str) -> Result<Option<tokio::sync::OwnedSemaphorePermit>, RpcError> {
+        if let Some(bucket) = &self.rate {
+            let mut bucket = bucket.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Err(retry_after) = bucket.take() {
+                return Err(RpcError::busy(
+                    format!(
+                        "Rate limit exceeded for '{}' ({} requests/s)",
+                        method, bucket.per_second
+                    ),
+                    Some(retry_after),
+                ));
+            }
+        }
+        match &self.concurrency {
+            Some((max, semaphore)) => semaphore.clone().try_acquire_owned().map(Some).map_err(|_| {
+                RpcError::busy(
+                    format!("Too many concurrent '{}' requests (max: {})", method, max),
+                    None,
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Token bucket allowing `per_second` calls on average, in bursts of up to one second's worth
+struct TokenBucket {
+    per_second: f64,
+    tokens: f64,
+    refilled_at: std::time::Instant,
+}
+
+impl TokenBucket {
+    fn new(per_second: f64) -> Self {
+        Self {
+            per_second,
+            tokens: per_second.max(1.0),
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Take a token, or return how long until one is available
+    fn take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.per_second.max(1.0));
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.per_second))
+        }
+    }
+}
 
 // ============================================================================
 // Debug/Profiling Options
@@ -224,20 +476,465 @@ impl DebugOptions {
     }
 }
 
+// ============================================================================
+// Input
+// ============================================================================
+
+/// Messages read by the reader task, in arrival order
+type Incoming = mpsc::Receiver<std::io::Result<Frame>>;
+
+/// How the server winds down, shared between the server loop and the reader loop
+#[derive(Clone)]
+struct ShutdownState {
+    /// Set once `shutdown` arrives; requests that have not started are refused from then on
+    draining: Arc<AtomicBool>,
+    /// How long in-flight requests get to finish before they are cancelled
+    timeout: Duration,
+    /// How long the shutdown callback gets to finish
+    callback_timeout: Duration,
+    /// Cancelled when the CLI that spawned the plugin exits
+    orphaned: CancellationToken,
+}
+
+impl ShutdownState {
+    fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Relaxed)
+    }
+}
+
+/// Read stdin on a dedicated thread so `$/cancel` arrives while handlers run
+///
+/// A plain thread rather than a runtime task, so a read blocked on stdin does not keep the
+/// runtime from shutting down once [`PluginServer::run`] returns.
+fn spawn_stdin_reader() -> Incoming {
+    let (tx, rx) = mpsc::channel(INCOMING_QUEUE_SIZE);
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(std::io::stdin().lock());
+        while let Some(frame) = read_message(&mut reader, MAX_REQUEST_SIZE).transpose() {
+            let failed = frame.is_err();
+            if tx.blocking_send(frame).is_err() || failed {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Read a socket connection on a blocking task feeding the same kind of channel as stdin
+fn spawn_blocking_reader<R: BufRead + Send + 'static>(mut reader: R) -> Incoming {
+    let (tx, rx) = mpsc::channel(INCOMING_QUEUE_SIZE);
+    tokio::task::spawn_blocking(move || {
+        while let Some(frame) = read_message(&mut reader, MAX_REQUEST_SIZE).transpose() {
+            let failed = frame.is_err();
+            if tx.blocking_send(frame).is_err() || failed {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Inode of the socket file at `path`, identifying the socket a daemon bound
+#[cfg(unix)]
+fn socket_inode(path: &std::path::Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|metadata| metadata.ino())
+}
+
+/// Resolve once the process that spawned this plugin has exited
+///
+/// An orphaned Unix process is reparented to init or a subreaper, so a changed parent PID means
+/// the CLI is gone. Never resolves elsewhere; on Windows the CLI places plugins in a job object
+/// that is killed along with it.
+async fn parent_exited() {
+    #[cfg(unix)]
+    {
+        let parent = std::os::unix::process::parent_id();
+        loop {
+            tokio::time::sleep(PARENT_POLL_INTERVAL).await;
+            if std::os::unix::process::parent_id() != parent {
+                return;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    std::future::pending::<()>().await
+}
+
+/// A request being handled, for `$/cancel` and `$/status`
+pub(crate) struct ActiveRequest {
+    pub handle: CancellationHandle,
+    pub method: String,
+    pub started: Instant,
+}
+
+/// Requests being handled, by ID
+pub(crate) type ActiveRequests = Mutex<HashMap<RequestId, ActiveRequest>>;
+
+/// Cancel the active request named by `$/cancel` params
+async fn cancel_request(active_requests: &ActiveRequests, params: Option<serde_json::Value>) {
+    let Some(params) = try_deserialize_params::<CancelParams>(params) else {
+        return;
+    };
+
+    let reason = params.reason.unwrap_or(CancelReason::UserAbort);
+    let active = active_requests.lock().await;
+    if let Some(request) = active.get(&params.id) {
+        request.handle.cancel(reason);
+        log_debug(&format!("Request {:?} cancelled ({:?})", params.id, reason));
+    }
+}
+
+/// Drive a request answered on the server loop, or a batch, to completion while reading ahead
+///
+/// `$/cancel` and `$/stream_credit` are applied and `$/status` answered as soon as they arrive,
+/// and all other messages
+/// wait in `pending` until
+/// the request is answered. `shutdown` starts draining: running requests get
+/// `shutdown.timeout` to finish before they are cancelled. The end of input or the CLI exiting
+/// cancels it at once, as nobody is left to read the result, unless it is already draining.
+async fn while_reading<T>(
+    request: impl Future<Output = T>,
+    incoming: &mut Incoming,
+    pending: &mut VecDeque<std::io::Result<Frame>>,
+    active_requests: &ActiveRequests,
+    shutdown: &ShutdownState,
+    status: &StatusSource,
+) -> T {
+    tokio::pin!(request);
+    let mut open = true;
+    let mut drain_deadline = None;
+    loop {
+        tokio::select! {
+            output = &mut request => return output,
+            () = tokio::time::sleep_until(drain_deadline.unwrap_or_else(tokio::time::Instant::now)),
+                if drain_deadline.is_some() =>
+            {
+                log::warn!("Shutdown timeout reached, cancelling in-flight requests");
+                cancel_all(active_requests, CancelReason::Shutdown).await;
+                drain_deadline = None;
+            },
+            () = shutdown.orphaned.cancelled(), if open => {
+                fail_host_calls();
+                cancel_all(active_requests, CancelReason::Disconnected).await;
+                open = false;
+            },
+            frame = incoming.recv(), if open => {
+                let Some(frame) = frame else {
+                    // Nobody is left to read the result, though the CLI closes its end right
+                    // after `shutdown` and then waits for the drain
+                    fail_host_calls();
+                    if !shutdown.is_draining() {
+                        cancel_all(active_requests, CancelReason::Disconnected).await;
+                    }
+                    open = false;
+                    continue;
+                };
+                if let Ok(Frame::Message(message)) = &frame {
+                    // The running handler may be waiting on this
+                    if deliver_host_response(message) {
+                        continue;
+                    }
+                    if let Ok(notification) = serde_json::from_str::<Request>(message) {
+                        match notification.method.as_str() {
+                            methods::CANCEL => {
+                                cancel_request(active_requests, notification.params).await;
+                                continue;
+                            },
+                            methods::STREAM_CREDIT => {
+                                grant_stream_credit(notification.params);
+                                continue;
+                            },
+                            methods::STATUS => {
+                                let result = status.report(active_requests).await;
+                                let response = Response::success(notification.id, serde_json::json!(result));
+                                let sent = serde_json::to_string(&response)
+                                    .map_err(std::io::Error::from)
+                                    .and_then(|json| write_output(&json));
+                                if let Err(e) = sent {
+                                    log::warn!("Failed to answer $/status: {}", e);
+                                }
+                                continue;
+                            },
+                            methods::SHUTDOWN if !shutdown.is_draining() => {
+                                shutdown.draining.store(true, Ordering::Relaxed);
+                                drain_deadline = Some(tokio::time::Instant::now() + shutdown.timeout);
+                            },
+                            _ => {},
+                        }
+                    }
+                }
+                pending.push_back(frame);
+            },
+        }
+    }
+}
+
+/// The response to a request, logged as the debug options ask
+fn respond(
+    id: RequestId,
+    method: &str,
+    start_time: Instant,
+    result: Result<serde_json::Value, RpcError>,
+    debug_options: &DebugOptions,
+) -> Response {
+    // Debug: log profiling info
+    if debug_options.log_profiling {
+        let status = if result.is_ok() { "OK" } else { "ERR" };
+        eprintln!(
+            "[PROFILE] {} {:?} - {} ({:?})",
+            method,
+            id,
+            status,
+            start_time.elapsed()
+        );
+    }
+
+    let response = match result {
+        Ok(value) => Response::success(id, value),
+        Err(error) => Response::error(id, error),
+    };
+
+    // Debug: log response
+    if debug_options.log_responses {
+        if let Ok(json) = serde_json::to_string(&response) {
+            eprintln!("[DEBUG] ← {}", json);
+        }
+    }
+    response
+}
+
+/// Write a response, or an error in its place if it is too large to send
+fn send_response(response: &Response) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string(response)?;
+    if json.len() > MAX_RESPONSE_SIZE {
+        eprintln!(
+            "Warning: Response size {} bytes exceeds limit {} bytes, sending error",
+            json.len(),
+            MAX_RESPONSE_SIZE
+        );
+        // Error response is guaranteed small (< 1KB) - no size check needed
+        let error_resp = Response::error(
+            response.id.clone(),
+            RpcError::new(error_codes::INTERNAL_ERROR, "Response too large"),
+        );
+        write_output(&serde_json::to_string(&error_resp)?)?;
+    } else {
+        write_output(&json)?;
+    }
+    Ok(())
+}
+
+/// Cancel every active request
+async fn cancel_all(active_requests: &ActiveRequests, reason: CancelReason) {
+    for request in active_requests.lock().await.values() {
+        request.handle.cancel(reason);
+    }
+}
+
+// ============================================================================
+// Host calls (plugin -> CLI requests)
+// ============================================================================
+
+/// Id of the next request sent to the CLI
+static NEXT_HOST_CALL_ID: AtomicI64 = AtomicI64::new(1);
+
+/// Requests sent to the CLI that are waiting for a response
+static HOST_CALLS: LazyLock<std::sync::Mutex<HashMap<RequestId, oneshot::Sender<Response>>>> =
+    LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// Forgets a host call once its caller stops waiting, even if the handler is aborted
+struct HostCallGuard(RequestId);
+
+impl Drop for HostCallGuard {
+    fn drop(&mut self) {
+        HOST_CALLS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.0);
+    }
+}
+
+/// Send a request to the CLI and wait for its response
+///
+/// The request shares the connection with everything else; its response is picked out of the
+/// input by [`deliver_host_response`].
+pub(crate) async fn call_host(method: &str, params: Option<serde_json::Value>) -> Result<serde_json::Value, RpcError> {
+    let id = RequestId::Number(NEXT_HOST_CALL_ID.fetch_add(1, Ordering::Relaxed));
+    let (tx, rx) = oneshot::channel();
+    HOST_CALLS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(id.clone(), tx);
+    let _guard = HostCallGuard(id.clone());
+
+    let request = Request::new(method, params, id);
+    let json = serde_json::to_string(&request).map_err(|e| RpcError::internal_error(e.to_string()))?;
+    write_output(&json).map_err(|e| RpcError::internal_error(format!("Failed to send request to CLI: {}", e)))?;
+
+    let response = rx
+        .await
+        .map_err(|_| RpcError::internal_error("Connection to the CLI closed"))?;
+    match response.error {
+        Some(error) => Err(error),
+        None => Ok(response.result.unwrap_or(serde_json::Value::Null)),
+    }
+}
+
+/// Hand a response from the CLI to the host call waiting for it
+///
+/// Returns `false` if `message` is not a response, so it should be handled as a request.
+fn deliver_host_response(message: &str) -> bool {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(message) else {
+        return false;
+    };
+    if value.get("method").is_some() || !(value.get("result").is_some() || value.get("error").is_some()) {
+        return false;
+    }
+    match serde_json::from_value::<Response>(value) {
+        Ok(response) => {
+            let waiting = HOST_CALLS
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&response.id);
+            match waiting {
+                Some(tx) => {
+                    let _ = tx.send(response);
+                },
+                None => log::debug!("Dropping response to unknown host call {:?}", response.id),
+            }
+        },
+        Err(e) => log::warn!("Invalid response from CLI: {}", e),
+    }
+    true
+}
+
+/// Fail every pending host call once the CLI can no longer answer
+fn fail_host_calls() {
+    HOST_CALLS.lock().unwrap_or_else(PoisonError::into_inner).clear();
+}
+
+// ============================================================================
+// Stream credits (flow control for `$/stream`)
+// ============================================================================
+
+/// Chunks a stream may still send, and a signal for when the CLI grants more
+#[derive(Default)]
+struct Credits {
+    available: std::sync::Mutex<u64>,
+    granted: tokio::sync::Notify,
+}
+
+impl Credits {
+    /// Take a credit if one is available
+    fn try_take(&self) -> bool {
+        let mut available = self.available.lock().unwrap_or_else(PoisonError::into_inner);
+        if *available == 0 {
+            return false;
+        }
+        *available -= 1;
+        true
+    }
+
+    /// Take a credit, waiting for the CLI to grant one if none is available
+    async fn take(&self, cancelled: &CancellationToken) -> Result<(), RpcError> {
+        loop {
+            if self.try_take() {
+                return Ok(());
+            }
+            // A grant between the check and here leaves a permit, so it is not missed
+            tokio::select! {
+                () = self.granted.notified() => {},
+                () = cancelled.cancelled() => return Err(RpcError::cancelled()),
+            }
+        }
+    }
+}
+
+/// Credits of the open streams under flow control, by request and stream name
+type StreamCredits = HashMap<(RequestId, String), Arc<Credits>>;
+
+static STREAM_CREDITS: LazyLock<std::sync::Mutex<StreamCredits>> =
+    LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// Add the credits granted by `$/stream_credit` params to their stream
+fn grant_stream_credit(params: Option<serde_json::Value>) {
+    let Some(params) = try_deserialize_params::<StreamCreditParams>(params) else {
+        return;
+    };
+    let credits = STREAM_CREDITS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&(params.request_id, params.stream))
+        .cloned();
+    // Credits for a stream closed since are stale
+    if let Some(credits) = credits {
+        let mut available = credits.available.lock().unwrap_or_else(PoisonError::into_inner);
+        *available = available.saturating_add(params.credits);
+        credits.granted.notify_one();
+    }
+}
+
+/// Flow control of one stream: its credits, and the request token that ends waiting for them
+struct StreamFlow {
+    credits: Arc<Credits>,
+    cancelled: CancellationToken,
+}
+
+// ============================================================================
+// Output framing
+// ============================================================================
+
+/// Where responses and notifications go
+struct Output {
+    /// Framing switched to once `initialize` is answered
+    framing: Framing,
+    /// Socket of the connection being served, or `None` for stdout
+    sink: Option<Box<dyn Write + Send>>,
+}
+
+/// Output of the connection being served, shared with the notification helpers
+static OUTPUT: std::sync::Mutex<Output> = std::sync::Mutex::new(Output {
+    framing: Framing::Line,
+    sink: None,
+});
+
+/// Write one message to the current connection in its negotiated framing and flush it
+fn write_output(json: &str) -> Result<(), std::io::Error> {
+    let mut output = OUTPUT.lock().unwrap_or_else(PoisonError::into_inner);
+    let framing = output.framing;
+    match output.sink.as_mut() {
+        Some(sink) => {
+            framing.write_message(sink, json)?;
+            sink.flush()
+        },
+        None => {
+            // Use single stdout lock for both write and flush
+            let stdout = std::io::stdout();
+            let mut handle = stdout.lock();
+            framing.write_message(&mut handle, json)?;
+            handle.flush()
+        },
+    }
+}
+
+/// Point output at a new connection (`None` for stdout), starting over in line framing
+fn set_output(sink: Option<Box<dyn Write + Send>>) {
+    *OUTPUT.lock().unwrap_or_else(PoisonError::into_inner) = Output {
+        framing: Framing::Line,
+        sink,
+    };
+}
+
 // ============================================================================
 // Notification helpers (can be called from handlers)
 // ============================================================================
 
 /// Internal helper to send a notification to stdout
-fn send_notification(notification: &Notification) -> Result<(), std::io::Error> {
-    use std::io::Write;
+pub(crate) fn send_notification(notification: &Notification) -> Result<(), std::io::Error> {
     let json =
         serde_json::to_string(notification).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-    // Use single stdout lock for both write and flush
-    let stdout = std::io::stdout();
-    let mut handle = stdout.lock();
-    writeln!(handle, "{}", json)?;
-    handle.flush()
+    write_output(&json)
 }
 
 /// Send a progress notification to the CLI (fire-and-forget)
@@ -259,12 +956,42 @@ pub fn notify_progress(percent: Option<u8>, message: &str) {
 ///
 /// Messages exceeding 64KB will be truncated to prevent memory issues.
 pub fn try_notify_progress(percent: Option<u8>, message: &str) -> Result<(), std::io::Error> {
+    send_notification(&progress_notification(percent, message))
+}
+
+/// The `$/progress` notification [`try_notify_progress`] sends
+pub(crate) fn progress_notification(percent: Option<u8>, message: &str) -> Notification {
     // Clamp percent to 0-100
     let percent = percent.map(|p| p.min(100));
     // Truncate message if too long (UTF-8 safe)
     let message = truncate_utf8(message, MAX_NOTIFICATION_MESSAGE_LEN);
-    let notification = Notification::progress(percent, message);
-    send_notification(&notification)
+    Notification::progress(percent, message)
+}
+
+/// Send a staged progress notification to the CLI (fire-and-forget)
+///
+/// Like [`notify_progress`], with the stage name, per-stage percent, item counts and ETA of
+/// `params`, which the CLI shows as a second progress bar.
+pub fn notify_progress_with(params: ProgressParams) {
+    if let Err(e) = try_notify_progress_with(params) {
+        eprintln!("Warning: Failed to send progress notification: {}", e);
+    }
+}
+
+/// Send a staged progress notification to the CLI with error handling
+///
+/// Messages and stage names exceeding 64KB will be truncated.
+pub fn try_notify_progress_with(params: ProgressParams) -> Result<(), std::io::Error> {
+    send_notification(&progress_with_notification(params))
+}
+
+/// The `$/progress` notification [`try_notify_progress_with`] sends
+pub(crate) fn progress_with_notification(mut params: ProgressParams) -> Notification {
+    params.message = truncate_utf8(&params.message, MAX_NOTIFICATION_MESSAGE_LEN).to_string();
+    if let Some(stage) = &mut params.stage {
+        *stage = truncate_utf8(stage, MAX_NOTIFICATION_MESSAGE_LEN).to_string();
+    }
+    Notification::progress_with(params)
 }
 
 /// Valid log levels
@@ -289,6 +1016,11 @@ pub fn notify_log(level: &str, message: &str) {
 ///
 /// Messages exceeding 64KB will be truncated to prevent memory issues.
 pub fn try_notify_log(level: &str, message: &str) -> Result<(), std::io::Error> {
+    send_notification(&log_notification(level, message))
+}
+
+/// The `$/log` notification [`try_notify_log`] sends
+pub(crate) fn log_notification(level: &str, message: &str) -> Notification {
     // Validate log level, default to "info" if invalid
     let level = if VALID_LOG_LEVELS.contains(&level) {
         level
@@ -297,8 +1029,25 @@ pub fn try_notify_log(level: &str, message: &str) -> Result<(), std::io::Error>
     };
     // Truncate message if too long (UTF-8 safe)
     let message = truncate_utf8(message, MAX_NOTIFICATION_MESSAGE_LEN);
-    let notification = Notification::log(level, message);
-    send_notification(&notification)
+    Notification::log(level, message)
+}
+
+/// Send a log notification with a target, spans and structured fields (fire-and-forget)
+pub fn notify_log_with(params: LogParams) {
+    if let Err(e) = try_notify_log_with(params) {
+        eprintln!("Warning: Failed to send log notification: {}", e);
+    }
+}
+
+/// Send a log notification with a target, spans and structured fields, with error handling
+///
+/// Invalid levels default to "info", and messages exceeding 64KB will be truncated.
+pub fn try_notify_log_with(mut params: LogParams) -> Result<(), std::io::Error> {
+    if !params.is_valid_level() {
+        params.level = "info".to_string();
+    }
+    params.message = truncate_utf8(&params.message, MAX_NOTIFICATION_MESSAGE_LEN).to_string();
+    send_notification(&Notification::log_with(params))
 }
 
 /// Convenience functions for different log levels
@@ -332,6 +1081,10 @@ pub fn log_debug(message: &str) {
 /// If you need to send more data, consider using multiple streams or
 /// compressing the data before sending.
 ///
+/// To stream results of the current request, prefer [`Context::stream`], which ties each
+/// chunk to the request ID and is shown by the CLI as it arrives. A `StreamWriter` has no flow
+/// control: it never waits for the CLI to catch up.
+///
 /// # Example
 ///
 /// ```ignore
@@ -374,8 +1127,6 @@ impl StreamWriter {
         data: &[u8],
         metadata: Option<serde_json::Value>,
     ) -> Result<(), std::io::Error> {
-        use std::io::Write;
-
         // Check chunk size limit
         if data.len() > MAX_STREAM_CHUNK_SIZE {
             return Err(std::io::Error::new(
@@ -396,7 +1147,7 @@ impl StreamWriter {
             )));
         }
 
-        let encoded = base64_encode(data);
+        let encoded = hodu_plugin::base64::encode(data);
         let mut params = serde_json::json!({
             "index": self.chunk_index,
             "data": encoded,
@@ -417,11 +1168,7 @@ impl StreamWriter {
             )
         })?;
 
-        // Use single stdout lock for both write and flush
-        let stdout = std::io::stdout();
-        let mut handle = stdout.lock();
-        writeln!(handle, "{}", json)?;
-        handle.flush()?;
+        write_output(&json)?;
 
         self.chunk_index += 1;
         Ok(())
@@ -429,8 +1176,6 @@ impl StreamWriter {
 
     /// Write a JSON chunk (for structured data streaming)
     pub fn write_json(&mut self, value: &serde_json::Value) -> Result<(), std::io::Error> {
-        use std::io::Write;
-
         let params = serde_json::json!({
             "index": self.chunk_index,
             "json": value,
@@ -444,11 +1189,7 @@ impl StreamWriter {
             )
         })?;
 
-        // Use single stdout lock for both write and flush
-        let stdout = std::io::stdout();
-        let mut handle = stdout.lock();
-        writeln!(handle, "{}", json)?;
-        handle.flush()?;
+        write_output(&json)?;
 
         self.chunk_index += 1;
         Ok(())
@@ -456,8 +1197,6 @@ impl StreamWriter {
 
     /// Signal that streaming is complete
     pub fn finish(&self) -> Result<(), std::io::Error> {
-        use std::io::Write;
-
         let params = serde_json::json!({
             "finished": true,
             "total_chunks": self.chunk_index,
@@ -471,11 +1210,7 @@ impl StreamWriter {
             )
         })?;
 
-        // Use single stdout lock for both write and flush
-        let stdout = std::io::stdout();
-        let mut handle = stdout.lock();
-        writeln!(handle, "{}", json)?;
-        handle.flush()?;
+        write_output(&json)?;
 
         Ok(())
     }
@@ -486,56 +1221,190 @@ impl StreamWriter {
     }
 }
 
-/// Simple base64 encoding for chunk data
-fn base64_encode(data: &[u8]) -> String {
-    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-
-    // Use saturating arithmetic to prevent overflow on 32-bit systems with huge data
-    let capacity = data.len().saturating_add(2) / 3 * 4;
-    let mut result = Vec::with_capacity(capacity);
+/// A typed stream of results for the request being handled
+///
+/// Created with [`Context::stream`]. Each item is sent to the CLI as a `$/stream` notification
+/// carrying the request ID, so results such as generated tokens or per-batch outputs reach the
+/// user before the handler returns. The stream is opened on creation and closed by
+/// [`close`](Self::close) or on drop.
+///
+/// Items must serialize to at most 10MB of JSON each. Unlike [`StreamWriter`] there is no
+/// limit on the number of items.
+///
+/// # Flow control
+///
+/// With [`features::STREAM_CREDITS`] negotiated, the CLI grants credits for the items it has
+/// consumed and [`write`](Self::write) waits for one before sending, so a handler producing
+/// items faster than the CLI reads them is held back instead of filling the CLI's memory.
+/// [`send`](Self::send) never waits; use it where items are few or the handler cannot await.
+///
+/// # Example
+///
+/// ```ignore
+/// async fn handle_run(ctx: Context, params: RunParams) -> Result<RunResult, RpcError> {
+///     let mut tokens = ctx.stream::<String>("tokens")?;
+///     for token in generate(&params) {
+///         tokens.write(&token).await?;
+///     }
+///     tokens.close()?;
+///     Ok(result)
+/// }
+/// ```
+pub struct ResultStream<T> {
+    request_id: RequestId,
+    name: String,
+    next_index: u64,
+    closed: bool,
+    flow: Option<StreamFlow>,
+    _item: std::marker::PhantomData<fn(&T)>,
+}
 
-    for chunk in data.chunks(3) {
-        let b0 = chunk[0] as usize;
-        let b1 = chunk.get(1).copied().unwrap_or(0) as usize;
-        let b2 = chunk.get(2).copied().unwrap_or(0) as usize;
+impl<T: Serialize> ResultStream<T> {
+    /// Open a stream for `request_id`, under flow control until `cancelled` if it is given
+    pub(crate) fn open(
+        request_id: RequestId,
+        name: impl Into<String>,
+        cancelled: Option<CancellationToken>,
+    ) -> Result<Self, RpcError> {
+        let name = name.into();
+        let flow = cancelled.map(|cancelled| {
+            let credits = Arc::new(Credits {
+                available: std::sync::Mutex::new(INITIAL_STREAM_CREDITS),
+                granted: tokio::sync::Notify::new(),
+            });
+            STREAM_CREDITS
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert((request_id.clone(), name.clone()), credits.clone());
+            StreamFlow { credits, cancelled }
+        });
+        let stream = Self {
+            request_id,
+            name,
+            next_index: 0,
+            closed: false,
+            flow,
+            _item: std::marker::PhantomData,
+        };
+        stream.emit(StreamEvent::Open)?;
+        Ok(stream)
+    }
 
-        result.push(ALPHABET[b0 >> 2]);
-        result.push(ALPHABET[((b0 & 0x03) << 4) | (b1 >> 4)]);
+    /// Send one item, waiting for the CLI to grant a credit if the stream is out of them
+    ///
+    /// # Errors
+    /// Returns a cancelled error if the request is cancelled while waiting.
+    pub async fn write(&mut self, item: &T) -> Result<(), RpcError> {
+        let data = self.encode(item)?;
+        if let Some(flow) = &self.flow {
+            flow.credits.take(&flow.cancelled).await?;
+        }
+        self.emit_chunk(data)
+    }
 
-        if chunk.len() > 1 {
-            result.push(ALPHABET[((b1 & 0x0f) << 2) | (b2 >> 6)]);
-        } else {
-            result.push(b'=');
+    /// Send one item without waiting for credit
+    pub fn send(&mut self, item: &T) -> Result<(), RpcError> {
+        let data = self.encode(item)?;
+        if let Some(flow) = &self.flow {
+            // Still counted, so credits granted later are not spent twice
+            flow.credits.try_take();
         }
+        self.emit_chunk(data)
+    }
 
-        if chunk.len() > 2 {
-            result.push(ALPHABET[b2 & 0x3f]);
-        } else {
-            result.push(b'=');
+    /// Close the stream, telling the CLI how many items were sent
+    pub fn close(mut self) -> Result<(), RpcError> {
+        self.closed = true;
+        self.emit(StreamEvent::Close {
+            total_chunks: self.next_index,
+        })
+    }
+
+    /// Get the stream name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the number of items sent so far
+    pub fn items_sent(&self) -> u64 {
+        self.next_index
+    }
+
+    /// Whether the stream waits for the CLI to grant credits
+    pub fn is_flow_controlled(&self) -> bool {
+        self.flow.is_some()
+    }
+
+    fn encode(&self, item: &T) -> Result<serde_json::Value, RpcError> {
+        let data = serde_json::to_value(item)
+            .map_err(|e| RpcError::internal_error(format!("Failed to serialize {} item: {}", self.name, e)))?;
+        let size = serde_json::to_string(&data).map(|json| json.len()).unwrap_or(0);
+        if size > MAX_STREAM_CHUNK_SIZE {
+            return Err(RpcError::invalid_params(format!(
+                "Stream item size {} exceeds maximum {} bytes",
+                size, MAX_STREAM_CHUNK_SIZE
+            )));
         }
+        Ok(data)
+    }
+
+    fn emit_chunk(&mut self, data: serde_json::Value) -> Result<(), RpcError> {
+        self.emit(StreamEvent::Chunk {
+            index: self.next_index,
+            data,
+        })?;
+        self.next_index += 1;
+        Ok(())
     }
 
-    // SAFETY: base64 encoding only produces ASCII characters, so this is always valid UTF-8
-    String::from_utf8(result).expect("base64 encoding produces only ASCII characters")
+    fn emit(&self, event: StreamEvent) -> Result<(), RpcError> {
+        let notification = Notification::stream(self.request_id.clone(), self.name.clone(), event);
+        send_notification(&notification)
+            .map_err(|e| RpcError::internal_error(format!("Failed to send {} stream: {}", self.name, e)))
+    }
+}
+
+impl<T> Drop for ResultStream<T> {
+    fn drop(&mut self) {
+        if self.flow.is_some() {
+            STREAM_CREDITS
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(&(self.request_id.clone(), self.name.clone()));
+        }
+        if self.closed {
+            return;
+        }
+        let event = StreamEvent::Close {
+            total_chunks: self.next_index,
+        };
+        let notification = Notification::stream(self.request_id.clone(), self.name.clone(), event);
+        // Best effort: the connection may already be gone
+        let _ = send_notification(&notification);
+    }
 }
 
 // ============================================================================
 // Handler Types
 // ============================================================================
 
+/// Future returned by type-erased handlers and layers
+type HandlerFuture = Pin<Box<dyn Future<Output = Result<serde_json::Value, RpcError>> + Send>>;
+
+/// How a request is answered
+enum Handling {
+    /// Answered on the server loop without a handler, or not at all for a notification
+    Done(Option<Response>),
+    /// Answered by a handler call, which runs alongside the requests read after it
+    Call(Pin<Box<dyn Future<Output = Response> + Send>>),
+}
+
 /// Type-erased async handler function
-type BoxedHandlerFn = Box<
-    dyn Fn(
-            Context,
-            Option<serde_json::Value>,
-        ) -> Pin<Box<dyn Future<Output = Result<serde_json::Value, RpcError>> + Send>>
-        + Send
-        + Sync,
->;
+type HandlerFn = Arc<dyn Fn(Context, Option<serde_json::Value>) -> HandlerFuture + Send + Sync>;
 
 /// Handler with optional timeout
 struct Handler {
-    func: BoxedHandlerFn,
+    func: HandlerFn,
     timeout: Option<Duration>,
 }
 
@@ -545,7 +1414,7 @@ struct Handler {
 /// ensuring cleanup even if the handler panics.
 struct ActiveRequestGuard {
     id: RequestId,
-    active_requests: Arc<Mutex<HashMap<RequestId, CancellationHandle>>>,
+    active_requests: Arc<ActiveRequests>,
     stale_ids: Arc<std::sync::Mutex<Vec<RequestId>>>,
 }
 
@@ -589,10 +1458,17 @@ impl Drop for ActiveRequestGuard {
 ///
 /// # Timeout Behavior
 ///
-/// By default, all handlers have a 5-minute timeout. When a handler exceeds its timeout:
+/// Requests start as they are read and each handler runs as its own task, so a slow request
+/// does not hold up the ones after it; `shutdown` waits for those still running. By default, all handlers have a 5-minute timeout.
+/// When a handler exceeds its timeout or its request is cancelled with `$/cancel`:
 /// 1. The handler's cancellation token is triggered
-/// 2. The request returns a `REQUEST_CANCELLED` error with a timeout message
-/// 3. The handler may continue running until it checks `ctx.is_cancelled()`
+/// 2. The handler task is aborted at its next `.await`
+/// 3. The request returns a `REQUEST_CANCELLED` error (with a timeout message on timeout)
+///
+/// Abort cannot interrupt blocking code, so long CPU-bound loops should still check
+/// `ctx.is_cancelled()` or move the work to `tokio::task::spawn_blocking`, and blocking calls
+/// with their own abort mechanism can hook it up with `ctx.on_cancel()`. Tasks a handler spawns
+/// are not aborted with it; give each a `ctx.child_token()` to stop them.
 ///
 /// ## Timeout Configuration
 ///
@@ -642,27 +1518,61 @@ pub struct PluginServer {
     handlers: HashMap<String, Handler>,
     initialized: bool,
     /// Active requests that can be cancelled
-    active_requests: Arc<Mutex<HashMap<RequestId, CancellationHandle>>>,
+    active_requests: Arc<ActiveRequests>,
+    /// Handler calls of the current connection that have not been answered yet
+    in_flight: tokio::task::JoinSet<()>,
     /// Stale request IDs that failed to cleanup (for deferred cleanup)
     stale_request_ids: Arc<std::sync::Mutex<Vec<RequestId>>>,
     /// Plugin metadata
     metadata: PluginMetadata,
     /// Shutdown cleanup callback
     shutdown_callback: Option<ShutdownCallback>,
+    /// Drain state and timeout for shutting down
+    shutdown: ShutdownState,
     /// Shared state across handlers
-    state: Option<Arc<dyn std::any::Any + Send + Sync>>,
+    state: Option<SharedState>,
+    /// Parser for the registered config type
+    config_parser: Option<ConfigParser>,
+    /// Schema reported to the CLI in `initialize`
+    config_schema: Option<serde_json::Value>,
+    /// Config received in `initialize`, shared with handlers
+    config: Option<Arc<dyn std::any::Any + Send + Sync>>,
+    /// Protocol features offered in `initialize`
+    features: Vec<String>,
+    /// Features agreed on with the CLI, shared with handlers
+    negotiated_features: Arc<[String]>,
+    /// Largest tensor in bytes offered to accept inline
+    inline_tensor_limit: u64,
+    /// Largest tensor in bytes either side sends inline, agreed on with the CLI
+    negotiated_inline_tensor_limit: u64,
     /// Default timeout for handlers (None = no timeout)
     default_timeout: Option<Duration>,
-    /// Pre-request hook
-    pre_request_hook: Option<PreRequestHook>,
-    /// Post-request hook
-    post_request_hook: Option<PostRequestHook>,
+    /// Middleware layers, outermost first
+    layers: Arc<Vec<Layer>>,
+    /// Rate and concurrency limits by method
+    limits: HashMap<String, Arc<MethodLimits>>,
+    /// Address of the HTTP metrics exporter, if enabled
+    metrics_addr: Option<Vec<std::net::SocketAddr>>,
     /// Debug/profiling options
     debug_options: DebugOptions,
     /// Build-time validation errors (reported on run())
     build_errors: Vec<String>,
     /// Flag to indicate graceful shutdown was requested
     shutdown_requested: bool,
+    /// Framing chosen during `initialize`, applied once its response is written
+    negotiated_framing: Option<Framing>,
+    /// Token clients must present in `initialize`
+    auth_token: Option<String>,
+    /// Serving socket connections, where `shutdown` only ends the connection
+    listening: bool,
+    /// How long a daemon waits for a connection before exiting (None = forever)
+    idle_timeout: Option<Duration>,
+    /// How long a daemon's client gets to complete `initialize`
+    initialize_timeout: Duration,
+    /// A client sent `$/exit`, so a daemon stops once the connection ends
+    exit_requested: bool,
+    /// When the server was created, for `$/status`
+    started: Instant,
 }
 
 impl PluginServer {
@@ -678,16 +1588,43 @@ impl PluginServer {
             handlers: HashMap::new(),
             initialized: false,
             active_requests: Arc::new(Mutex::new(HashMap::new())),
+            in_flight: tokio::task::JoinSet::new(),
             stale_request_ids: Arc::new(std::sync::Mutex::new(Vec::new())),
             metadata: PluginMetadata::default(),
             shutdown_callback: None,
+            shutdown: ShutdownState {
+                draining: Arc::new(AtomicBool::new(false)),
+                timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+                callback_timeout: DEFAULT_SHUTDOWN_CALLBACK_TIMEOUT,
+                orphaned: CancellationToken::new(),
+            },
             state: None,
+            config_parser: None,
+            config_schema: None,
+            config: None,
+            features: vec![
+                features::STREAMING.to_string(),
+                features::STREAM_CREDITS.to_string(),
+                features::HOST_CALLS.to_string(),
+                features::INLINE_TENSORS.to_string(),
+            ],
+            negotiated_features: Arc::from([]),
+            inline_tensor_limit: DEFAULT_INLINE_TENSOR_LIMIT,
+            negotiated_inline_tensor_limit: 0,
             default_timeout: Some(DEFAULT_REQUEST_TIMEOUT),
-            pre_request_hook: None,
-            post_request_hook: None,
+            layers: Arc::new(Vec::new()),
+            limits: HashMap::new(),
+            metrics_addr: None,
             debug_options: DebugOptions::default(),
             build_errors: Vec::new(),
             shutdown_requested: false,
+            negotiated_framing: None,
+            auth_token: None,
+            listening: false,
+            idle_timeout: None,
+            initialize_timeout: DEFAULT_INITIALIZE_TIMEOUT,
+            exit_requested: false,
+            started: Instant::now(),
         }
     }
 
@@ -742,6 +1679,24 @@ impl PluginServer {
         self
     }
 
+    /// Require clients to present `token` in `initialize`
+    ///
+    /// Clients with a missing or wrong token get an `UNAUTHORIZED` error and are disconnected.
+    /// Meant for [`listen_tcp`](Self::listen_tcp), where anyone who can reach the port can connect.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// PluginServer::new("my-plugin", "1.0.0")
+    ///     .auth_token(std::env::var("HODU_PLUGIN_TOKEN")?)
+    ///     .listen_tcp("0.0.0.0:7878")
+    ///     .await
+    /// ```
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
     /// Set shared state that will be available to all handlers
     ///
     /// The state is wrapped in an `Arc` and can be accessed via `ctx.state::<T>()` in handlers.
@@ -782,6 +1737,79 @@ impl PluginServer {
         self
     }
 
+    /// Accept a typed configuration from the CLI
+    ///
+    /// The CLI sends the plugin's table from `~/.hodu/config.toml`, merged with
+    /// `--plugin-config` overrides, in `initialize`. It is deserialized into `C`, starting from
+    /// `C::default()` when nothing was sent, and handlers read it with `ctx.config::<C>()`.
+    /// A config that fails to deserialize fails `initialize`.
+    ///
+    /// A JSON Schema inferred from `C::default()` is reported to the CLI, which `hodu plugin
+    /// config` uses to list and check settings. Use [`config_schema`](Self::config_schema) to
+    /// report a hand-written schema instead.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// #[derive(Default, Serialize, Deserialize)]
+    /// #[serde(default)]
+    /// struct MyConfig {
+    ///     threads: usize,
+    ///     fast_math: bool,
+    /// }
+    ///
+    /// PluginServer::new("my-backend", "1.0.0")
+    ///     .config::<MyConfig>()
+    ///     .method("backend.run", handle_run)
+    ///     .run()
+    ///     .await
+    /// ```
+    pub fn config<C>(mut self) -> Self
+    where
+        C: DeserializeOwned + Serialize + Default + Send + Sync + 'static,
+    {
+        match serde_json::to_value(C::default()) {
+            Ok(defaults) => self.config_schema = Some(schema_from_default(&defaults)),
+            Err(e) => self
+                .build_errors
+                .push(format!("Failed to serialize default config: {}", e)),
+        }
+        self.config_parser = Some(Box::new(|config| {
+            let config = match config {
+                Some(serde_json::Value::Null) | None => C::default(),
+                Some(config) => serde_json::from_value::<C>(config).map_err(|e| e.to_string())?,
+            };
+            Ok(Arc::new(config))
+        }));
+        self
+    }
+
+    /// Offer an additional protocol feature in `initialize`
+    ///
+    /// The SDK already offers the features it implements, such as [`features::STREAMING`] and
+    /// [`features::HOST_CALLS`]. Use this for features the plugin implements itself, and check
+    /// with `ctx.has_feature()` whether the CLI agreed before using one.
+    pub fn feature(mut self, feature: impl Into<String>) -> Self {
+        self.features.push(feature.into());
+        self
+    }
+
+    /// Set the largest tensor in bytes accepted inline in requests (default 64KB)
+    ///
+    /// The CLI states its own limit, and the smaller of the two applies to both directions.
+    /// Values above [`MAX_INLINE_TENSOR_LIMIT`](crate::rpc::MAX_INLINE_TENSOR_LIMIT) are capped
+    /// to it, and 0 sends every tensor through a file.
+    pub fn inline_tensor_limit(mut self, bytes: u64) -> Self {
+        self.inline_tensor_limit = bytes;
+        self
+    }
+
+    /// Report a hand-written JSON Schema for the config instead of the inferred one
+    pub fn config_schema(mut self, schema: serde_json::Value) -> Self {
+        self.config_schema = Some(schema);
+        self
+    }
+
     /// Set plugin description
     pub fn description(mut self, desc: impl Into<String>) -> Self {
         self.metadata.description = Some(desc.into());
@@ -846,7 +1874,10 @@ impl PluginServer {
 
     /// Set shutdown cleanup callback
     ///
-    /// The callback is called before the server exits (on `$/shutdown` request).
+    /// The callback is called before the server exits: on a `shutdown` request once in-flight
+    /// requests have drained, or when [`run`](Self::run) finds stdin closed or the CLI that
+    /// spawned it gone. It runs on a blocking thread, and the server exits without waiting for
+    /// it once [`shutdown_callback_timeout`](Self::shutdown_callback_timeout) expires.
     ///
     /// # Example
     ///
@@ -863,24 +1894,234 @@ impl PluginServer {
     where
         F: FnOnce() + Send + 'static,
     {
-        self.shutdown_callback = Some(Box::new(callback));
+        self.shutdown_callback = Some(Box::new(move |_| {
+            Box::pin(async move {
+                if tokio::task::spawn_blocking(callback).await.is_err() {
+                    log::error!("Shutdown callback panicked");
+                }
+            })
+        }));
         self
     }
 
-    /// Set pre-request hook (called before each handler)
+    /// Set an async shutdown cleanup callback
     ///
-    /// Can be used for logging, authentication, rate limiting, etc.
-    /// Return `PreRequestAction::Reject(error)` to skip the handler.
+    /// Like [`on_shutdown`](Self::on_shutdown), for cleanup that awaits, such as flushing a
+    /// writer or closing a connection pool. The server exits once the returned future completes,
+    /// or when [`shutdown_callback_timeout`](Self::shutdown_callback_timeout) expires.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// PluginServer::new("my-plugin", "1.0.0")
+    ///     .on_shutdown_async(|| async move {
+    ///         cache.flush().await;
+    ///     })
+    /// ```
+    pub fn on_shutdown_async<F, Fut>(mut self, callback: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.shutdown_callback = Some(Box::new(move |_| Box::pin(callback())));
+        self
+    }
+
+    /// Set an async shutdown cleanup callback receiving the state set with
+    /// [`with_state`](Self::with_state)
+    ///
+    /// For state that holds resources needing an orderly teardown, such as GPU contexts or
+    /// network connections. The callback is skipped, with a warning, when no state of type `S`
+    /// was set. Otherwise it runs like [`on_shutdown_async`](Self::on_shutdown_async).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// PluginServer::new("my-backend", "1.0.0")
+    ///     .with_state(Devices::open()?)
+    ///     .on_shutdown_with_state(|devices: Arc<Devices>| async move {
+    ///         devices.synchronize().await;
+    ///         devices.release();
+    ///     })
+    /// ```
+    pub fn on_shutdown_with_state<S, F, Fut>(mut self, callback: F) -> Self
+    where
+        S: Send + Sync + 'static,
+        F: FnOnce(Arc<S>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.shutdown_callback = Some(Box::new(move |state| {
+            match state.and_then(|state| state.downcast::<S>().ok()) {
+                Some(state) => Box::pin(callback(state)),
+                None => {
+                    log::warn!(
+                        "Skipping shutdown callback: no state of type '{}'",
+                        std::any::type_name::<S>()
+                    );
+                    Box::pin(async {})
+                },
+            }
+        }));
+        self
+    }
+
+    /// Set how long the shutdown callback gets to finish
+    ///
+    /// The server exits when the timeout expires, even if the callback is still running.
+    /// Default is 30 seconds.
+    pub fn shutdown_callback_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown.callback_timeout = timeout;
+        self
+    }
+
+    /// Exit a daemon after `timeout` without a connection
+    ///
+    /// Applies to [`listen_tcp`](Self::listen_tcp) and [`listen_unix`](Self::listen_unix). The
+    /// shutdown callback runs before the daemon exits. `HODU_PLUGIN_IDLE_TIMEOUT` overrides it
+    /// for daemons the CLI starts through [`run`](Self::run).
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Disconnect a daemon's client that hasn't completed `initialize` within `timeout`
+    ///
+    /// A daemon serves one connection at a time, so a client that connects and sends nothing
+    /// would otherwise keep every other client, and the
+    /// [`idle_timeout`](Self::idle_timeout), waiting. With an
+    /// [`auth_token`](Self::auth_token), this bounds how long an unauthenticated client holds the
+    /// daemon. Default is 5 seconds.
+    pub fn initialize_timeout(mut self, timeout: Duration) -> Self {
+        self.initialize_timeout = timeout;
+        self
+    }
+
+    /// Set how long in-flight requests get to finish after `shutdown`
+    ///
+    /// Once `shutdown` arrives, requests that have not started are answered with a
+    /// `SHUTTING_DOWN` error, and the running request is cancelled if it is still going when the
+    /// timeout expires. Default is 10 seconds.
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown.timeout = timeout;
+        self
+    }
+
+    /// Add a middleware layer around every method handler
+    ///
+    /// Layers run in the order they are added, each wrapping the ones added after it. A layer
+    /// receives the call and the rest of the chain: it can inspect or rewrite `call.params`, pass
+    /// the call on with `next.run(call).await` and inspect or rewrite the result, or answer
+    /// without calling `next` at all to short-circuit.
+    ///
+    /// Layers run for every parsed request except internal methods (`$/cancel`, `$/ping`),
+    /// `initialize` and `shutdown`. They also see requests for unknown methods, which reach the
+    /// end of the chain as `method_not_found`. Timeouts and `$/cancel` abort only the handler, so
+    /// outer layers see those errors as results.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// PluginServer::new("my-plugin", "1.0.0")
+    ///     .layer(|call, next| async move {
+    ///         let method = call.method().to_string();
+    ///         let result = next.run(call).await;
+    ///         eprintln!("{} -> {}", method, if result.is_ok() { "ok" } else { "error" });
+    ///         result
+    ///     })
+    ///     .layer(|call, next| async move {
+    ///         if call.method().starts_with("admin.") {
+    ///             return Err(RpcError::invalid_request("admin methods are disabled"));
+    ///         }
+    ///         next.run(call).await
+    ///     })
+    ///     .run()
+    ///     .await
+    /// ```
+    pub fn layer<F, Fut>(mut self, layer: F) -> Self
+    where
+        F: Fn(MethodCall, Next) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<serde_json::Value, RpcError>> + Send + 'static,
+    {
+        Arc::make_mut(&mut self.layers).push(Arc::new(move |call, next| Box::pin(layer(call, next))));
+        self
+    }
+
+    /// Serve request metrics over HTTP at `http://<addr>/metrics`
+    ///
+    /// The exporter starts with [`run`](Self::run) or the `listen_*` methods and serves the same
+    /// Prometheus text as the `$/metrics` method, so a scraper can watch a daemon without
+    /// speaking JSON-RPC.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// PluginServer::new("my-plugin", "1.0.0")
+    ///     .metrics_http("127.0.0.1:9464")
+    ///     .listen_tcp("127.0.0.1:7000")
+    ///     .await
+    /// ```
+    pub fn metrics_http(mut self, addr: impl std::net::ToSocketAddrs) -> Self {
+        match addr.to_socket_addrs() {
+            Ok(addrs) => self.metrics_addr = Some(addrs.collect()),
+            Err(e) => self.build_errors.push(format!("Invalid metrics address: {}", e)),
+        }
+        self
+    }
+
+    /// Limit a method to `per_second` requests per second on average
+    ///
+    /// Bursts of up to one second's worth of requests are allowed. Requests over the limit are
+    /// answered with a `BUSY` error whose `data.retry_after_ms` says when to retry, without
+    /// running the handler. The limit spans connections, so it also holds for a daemon serving
+    /// many short-lived CLI sessions.
     ///
-    /// # Hook Coverage
+    /// # Example
+    ///
+    /// ```ignore
+    /// PluginServer::new("my-plugin", "1.0.0")
+    ///     .method("backend.run", handle_run)
+    ///     .rate_limit("backend.run", 5.0)
+    /// ```
+    pub fn rate_limit(mut self, method: &str, per_second: f64) -> Self {
+        if !(per_second.is_finite() && per_second > 0.0) {
+            self.build_errors.push(format!(
+                "Rate limit for '{}' must be a positive number, got {}",
+                method, per_second
+            ));
+            return self;
+        }
+        self.method_limits(method).rate = Some(std::sync::Mutex::new(TokenBucket::new(per_second)));
+        self
+    }
+
+    /// Allow at most `max` requests for a method to run at once
     ///
-    /// This hook is called for all successfully parsed requests, **except**:
-    /// - Internal methods (`$/cancel`, `$/ping`)
-    /// - `initialize` and `shutdown` methods
-    /// - Requests that fail validation before parsing (e.g., too large, invalid JSON)
+    /// Requests beyond the cap are answered with a `BUSY` error without running the handler,
+    /// which keeps a GPU-backed handler from being queued past what the device can hold. The
+    /// requests of a connection run alongside each other, while a daemon serves its connections
+    /// one after another, so the cap counts the running requests of the current client.
+    pub fn max_concurrency(mut self, method: &str, max: usize) -> Self {
+        if max == 0 {
+            self.build_errors
+                .push(format!("Concurrency limit for '{}' must be at least 1", method));
+            return self;
+        }
+        self.method_limits(method).concurrency = Some((max, Arc::new(tokio::sync::Semaphore::new(max))));
+        self
+    }
+
+    /// Limits for `method`, created on first use while the server is being built
+    fn method_limits(&mut self, method: &str) -> &mut MethodLimits {
+        let limits = self.limits.entry(method.to_string()).or_default();
+        Arc::get_mut(limits).expect("limits are only shared once the server runs")
+    }
+
+    /// Add a layer that runs a synchronous hook before each handler
     ///
-    /// For pre-parsing validation errors, the server returns an error response
-    /// directly without calling this hook, since no method information is available.
+    /// Can be used for logging, authentication, rate limiting, etc.
+    /// Return `PreRequestAction::Reject(error)` to skip the handler. The hook is a shorthand for
+    /// [`layer`](Self::layer) and takes its place in the chain like any other layer, so calling
+    /// this again adds a second hook after the first instead of replacing it.
     ///
     /// # Example
     ///
@@ -893,17 +2134,31 @@ impl PluginServer {
     ///     .run()
     ///     .await
     /// ```
-    pub fn on_request<F>(mut self, hook: F) -> Self
+    pub fn on_request<F>(self, hook: F) -> Self
     where
         F: Fn(&RequestInfo) -> PreRequestAction + Send + Sync + 'static,
     {
-        self.pre_request_hook = Some(Box::new(hook));
-        self
+        self.layer(move |call, next| {
+            let action = hook(&RequestInfo {
+                method: call.method.clone(),
+                id: call.id().clone(),
+                params: call.params.clone(),
+            });
+            async move {
+                match action {
+                    PreRequestAction::Continue => next.run(call).await,
+                    PreRequestAction::Reject(error) => Err(error),
+                }
+            }
+        })
     }
 
-    /// Set post-request hook (called after each handler)
+    /// Add a layer that runs a synchronous hook after each handler
     ///
-    /// Can be used for logging, metrics, profiling, etc.
+    /// Can be used for logging, metrics, profiling, etc. The hook sees the result of everything
+    /// added after it, so add it first to also see requests rejected by later layers. Like
+    /// [`on_request`](Self::on_request), calling this again adds another hook rather than
+    /// replacing the previous one.
     ///
     /// # Example
     ///
@@ -916,12 +2171,28 @@ impl PluginServer {
     ///     .run()
     ///     .await
     /// ```
-    pub fn on_response<F>(mut self, hook: F) -> Self
+    pub fn on_response<F>(self, hook: F) -> Self
     where
         F: Fn(&ResponseInfo) + Send + Sync + 'static,
     {
-        self.post_request_hook = Some(Box::new(hook));
-        self
+        let hook = Arc::new(hook);
+        self.layer(move |call, next| {
+            let hook = hook.clone();
+            async move {
+                let start_time = Instant::now();
+                let method = call.method.clone();
+                let id = call.id().clone();
+                let result = next.run(call).await;
+                hook(&ResponseInfo {
+                    method,
+                    id,
+                    success: result.is_ok(),
+                    error_code: result.as_ref().err().map(|e| e.code),
+                    duration: start_time.elapsed(),
+                });
+                result
+            }
+        })
     }
 
     /// Set supported file extensions for model format plugins
@@ -966,7 +2237,7 @@ impl PluginServer {
         R: Serialize + 'static,
     {
         let handler = Arc::new(handler);
-        let boxed: BoxedHandlerFn = Box::new(move |ctx, params| {
+        let boxed: HandlerFn = Arc::new(move |ctx, params| {
             let handler = handler.clone();
             Box::pin(async move {
                 let params: P = deserialize_params(params)?;
@@ -977,6 +2248,28 @@ impl PluginServer {
         self.register_handler(name, boxed, None)
     }
 
+    /// Register a handler for the custom op `name`
+    ///
+    /// The handler is exposed as the `op.<name>` method, so the op is advertised as a capability
+    /// and snapshots containing it are forwarded to this plugin.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// async fn fused_gelu(ctx: Context, params: CustomOpParams) -> Result<RunResult, RpcError> {
+    ///     // ...
+    /// }
+    ///
+    /// server.custom_op("acme.fused_gelu", fused_gelu)
+    /// ```
+    pub fn custom_op<F, Fut>(self, name: &str, handler: F) -> Self
+    where
+        F: Fn(Context, CustomOpParams) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<RunResult, RpcError>> + Send + 'static,
+    {
+        self.method(&format!("{}{}", methods::CUSTOM_OP_PREFIX, name), handler)
+    }
+
     /// Register an async method handler with custom timeout
     ///
     /// Overrides the default timeout for this specific method.
@@ -996,7 +2289,7 @@ impl PluginServer {
         R: Serialize + 'static,
     {
         let handler = Arc::new(handler);
-        let boxed: BoxedHandlerFn = Box::new(move |ctx, params| {
+        let boxed: HandlerFn = Arc::new(move |ctx, params| {
             let handler = handler.clone();
             Box::pin(async move {
                 let params: P = deserialize_params(params)?;
@@ -1015,7 +2308,7 @@ impl PluginServer {
         R: Serialize + 'static,
     {
         let handler = Arc::new(handler);
-        let boxed: BoxedHandlerFn = Box::new(move |ctx, _params| {
+        let boxed: HandlerFn = Arc::new(move |ctx, _params| {
             let handler = handler.clone();
             Box::pin(async move {
                 let result = handler(ctx).await?;
@@ -1032,7 +2325,7 @@ impl PluginServer {
     /// - Empty names
     /// - Names containing control characters
     /// - Names using reserved prefixes (`$/`, `rpc.`, `system.`)
-    fn register_handler(mut self, name: &str, func: BoxedHandlerFn, timeout: Option<Duration>) -> Self {
+    fn register_handler(mut self, name: &str, func: HandlerFn, timeout: Option<Duration>) -> Self {
         // Validate handler name - collect errors for reporting at run()
         if name.is_empty() {
             self.build_errors.push("Handler name cannot be empty".to_string());
@@ -1054,8 +2347,8 @@ impl PluginServer {
             eprintln!("Warning: Handler '{}' is being overwritten", name);
         }
 
-        // Auto-register capability for format/backend methods
-        if (name.starts_with("format.") || name.starts_with("backend."))
+        // Auto-register capability for format/backend/custom op methods
+        if (name.starts_with("format.") || name.starts_with("backend.") || name.starts_with(methods::CUSTOM_OP_PREFIX))
             && !self.capabilities.contains(&name.to_string())
         {
             self.capabilities.push(name.to_string());
@@ -1068,48 +2361,301 @@ impl PluginServer {
     ///
     /// Starts the JSON-RPC server loop, reading from stdin and writing to stdout.
     /// Supports cancellation via `$/cancel` requests and batch requests per JSON-RPC 2.0 spec.
+    /// Stdin is read on its own thread, so `$/cancel` and `shutdown` take effect while a handler
+    /// is still running.
+    ///
+    /// On `shutdown` the server stops taking new requests, gives the running one up to the
+    /// [`shutdown_timeout`](Self::shutdown_timeout) to finish, awaits the shutdown callback and
+    /// then returns, so the plugin's destructors run as `main` unwinds. The server also shuts down,
+    /// calling the shutdown callback, when stdin closes or (on Unix) the CLI process exits without
+    /// sending `shutdown`, so crashed CLIs do not leave plugins behind.
     ///
     /// # Errors
     /// Returns error if there were validation errors during server construction
     /// (e.g., invalid handler names).
     pub async fn run(mut self) -> Result<(), Box<dyn std::error::Error>> {
-        // Check for build-time validation errors
-        if !self.build_errors.is_empty() {
-            let errors = self.build_errors.join("; ");
+        // The CLI starts pooled plugins as daemons
+        if let Ok(endpoint) = std::env::var(LISTEN_ENV) {
+            if let Some(secs) = std::env::var(IDLE_TIMEOUT_ENV).ok().and_then(|secs| secs.parse().ok()) {
+                self.idle_timeout = Some(Duration::from_secs(secs));
+            }
+            if let Some(addr) = endpoint.strip_prefix("tcp://") {
+                return self.listen_tcp(addr).await;
+            }
+            #[cfg(unix)]
+            if let Some(path) = endpoint.strip_prefix("unix://") {
+                return self.listen_unix(path).await;
+            }
+            return Err(format!("Unsupported {} endpoint: {}", LISTEN_ENV, endpoint).into());
+        }
+
+        self.check_build_errors()?;
+        self.start_metrics_exporter()?;
+
+        let orphaned = self.shutdown.orphaned.clone();
+        let watcher = tokio::spawn(async move {
+            parent_exited().await;
+            orphaned.cancel();
+        });
+        let result = self.serve(spawn_stdin_reader()).await;
+        watcher.abort();
+
+        let orphaned = self.shutdown.orphaned.is_cancelled();
+        if orphaned {
+            log::warn!("Parent process exited, shutting down");
+        } else if !self.shutdown_requested {
+            log::info!("Input closed, shutting down");
+        }
+        // Already run if the CLI asked for the shutdown
+        self.run_shutdown_callback().await;
+        if orphaned {
+            // Output to the CLI may have failed once it was gone
+            return Ok(());
+        }
+        result
+    }
+
+    /// Run the server as a daemon accepting CLI connections on a TCP address
+    ///
+    /// Connections are served one at a time, each with its own `initialize`, which must complete
+    /// within the [`initialize_timeout`](Self::initialize_timeout). A `shutdown` request ends the
+    /// connection but not the daemon. The daemon stops, calling the shutdown
+    /// callback, after a connection that sent `$/exit` or once the
+    /// [`idle_timeout`](Self::idle_timeout) passes without a connection.
+    /// Combine with [`auth_token`](Self::auth_token) when the port is reachable by others.
+    ///
+    /// # Errors
+    /// Returns error on configuration errors or if the address cannot be bound.
+    pub async fn listen_tcp(mut self, addr: impl std::net::ToSocketAddrs) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_build_errors()?;
+        self.start_metrics_exporter()?;
+
+        let listener = std::net::TcpListener::bind(addr)?;
+        log::info!("Listening on tcp://{}", listener.local_addr()?);
+        listener.set_nonblocking(true)?;
+        let listener = tokio::net::TcpListener::from_std(listener)?;
+        self.listening = true;
+        while let Some(accepted) = self.accept(listener.accept()).await {
+            let stream = match accepted.and_then(|(stream, _)| stream.into_std()) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::warn!("Failed to accept connection: {}", e);
+                    continue;
+                },
+            };
+            // Connections are read on a blocking task
+            stream.set_nonblocking(false)?;
+            let _ = stream.set_nodelay(true);
+            let writer = stream.try_clone()?;
+            let reader = stream.try_clone()?;
+            self.serve_connection(BufReader::new(reader), Box::new(writer)).await;
+            // Unblock the reader task if the client kept its end open
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        self.stop_daemon().await;
+        Ok(())
+    }
+
+    /// Run the server as a daemon accepting CLI connections on a Unix domain socket
+    ///
+    /// Behaves like [`listen_tcp`](Self::listen_tcp). The socket file must not exist yet, and is
+    /// removed when the daemon stops.
+    ///
+    /// # Errors
+    /// Returns error on configuration errors or if the socket cannot be bound.
+    #[cfg(unix)]
+    pub async fn listen_unix(mut self, path: impl AsRef<std::path::Path>) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_build_errors()?;
+        self.start_metrics_exporter()?;
+
+        let path = path.as_ref();
+        let listener = std::os::unix::net::UnixListener::bind(path)?;
+        let socket_inode = socket_inode(path);
+        log::info!("Listening on unix://{}", path.display());
+        listener.set_nonblocking(true)?;
+        let listener = tokio::net::UnixListener::from_std(listener)?;
+        self.listening = true;
+        while let Some(accepted) = self.accept(listener.accept()).await {
+            let stream = match accepted.and_then(|(stream, _)| stream.into_std()) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::warn!("Failed to accept connection: {}", e);
+                    continue;
+                },
+            };
+            stream.set_nonblocking(false)?;
+            let writer = stream.try_clone()?;
+            let reader = stream.try_clone()?;
+            self.serve_connection(BufReader::new(reader), Box::new(writer)).await;
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        // A successor may already have replaced the socket after `$/exit`
+        if socket_inode.is_some() && socket_inode == self::socket_inode(path) {
+            let _ = std::fs::remove_file(path);
+        }
+        self.stop_daemon().await;
+        Ok(())
+    }
+
+    /// Wait for the next connection, or `None` once the daemon should stop
+    async fn accept<T>(&self, accept: impl Future<Output = std::io::Result<T>>) -> Option<std::io::Result<T>> {
+        if self.exit_requested {
+            log::info!("Exit requested, stopping");
+            return None;
+        }
+        match self.idle_timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, accept).await {
+                Ok(accepted) => Some(accepted),
+                Err(_) => {
+                    log::info!("No connection for {:?}, stopping", timeout);
+                    None
+                },
+            },
+            None => Some(accept.await),
+        }
+    }
+
+    /// Run the shutdown callback as a daemon stops
+    async fn stop_daemon(&mut self) {
+        self.run_shutdown_callback().await;
+    }
+
+    /// Run the shutdown callback, if not run yet, giving up once its timeout expires
+    async fn run_shutdown_callback(&mut self) {
+        let Some(callback) = self.shutdown_callback.take() else {
+            return;
+        };
+        let timeout = self.shutdown.callback_timeout;
+        if tokio::time::timeout(timeout, callback(self.state.clone()))
+            .await
+            .is_err()
+        {
+            log::warn!("Shutdown callback did not finish within {:?}, exiting anyway", timeout);
+        }
+    }
+
+    /// Report validation errors collected while the server was built
+    pub(crate) fn check_build_errors(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut errors = self.build_errors.clone();
+        let mut unknown: Vec<_> = self
+            .limits
+            .keys()
+            .filter(|method| !self.handlers.contains_key(*method))
+            .collect();
+        unknown.sort_unstable();
+        errors.extend(
+            unknown
+                .into_iter()
+                .map(|method| format!("Limits set for unregistered method '{}'", method)),
+        );
+        if !errors.is_empty() {
+            let errors = errors.join("; ");
             return Err(format!("Plugin server configuration errors: {}", errors).into());
         }
+        Ok(())
+    }
 
-        let stdin = std::io::stdin();
-        let mut stdout = std::io::stdout();
-        let reader = BufReader::new(stdin.lock());
+    /// Start the HTTP metrics exporter, if one was configured
+    fn start_metrics_exporter(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(addrs) = &self.metrics_addr {
+            metrics::start_http_exporter(addrs).map_err(|e| format!("Failed to start metrics exporter: {}", e))?;
+        }
+        Ok(())
+    }
 
-        for line in reader.lines() {
-            let line = line?;
-            if line.is_empty() {
-                continue;
-            }
+    /// Serve one socket connection as a fresh session, then detach output from it
+    pub(crate) async fn serve_connection<R: BufRead + Send + 'static>(
+        &mut self,
+        reader: R,
+        writer: Box<dyn Write + Send>,
+    ) {
+        self.initialized = false;
+        self.shutdown_requested = false;
+        self.shutdown.draining.store(false, Ordering::Relaxed);
+        self.negotiated_framing = None;
+        self.negotiated_features = Arc::from([]);
+        self.negotiated_inline_tensor_limit = 0;
+        set_output(Some(writer));
+        if let Err(e) = self.serve(spawn_blocking_reader(reader)).await {
+            log::warn!("Connection closed with error: {}", e);
+        }
+        set_output(None);
+    }
 
-            // Check request size limit
-            if line.len() > MAX_REQUEST_SIZE {
-                let resp = Response::error(
-                    RequestId::Null,
-                    RpcError::invalid_request(format!(
-                        "Request too large: {} bytes (max: {} bytes)",
-                        line.len(),
-                        MAX_REQUEST_SIZE
-                    )),
-                );
-                let json = serde_json::to_string(&resp)?;
-                writeln!(stdout, "{}", json)?;
-                stdout.flush()?;
+    /// Read and answer requests until end of input or `shutdown`
+    async fn serve(&mut self, mut incoming: Incoming) -> Result<(), Box<dyn std::error::Error>> {
+        // Messages that arrived while an earlier request was being handled
+        let mut pending = VecDeque::new();
+        let active_requests = self.active_requests.clone();
+        let shutdown = self.shutdown.clone();
+        let mut status = self.status_source();
+        // Only daemons give up on clients that don't initialize; the CLI owns a stdio plugin
+        let initialize_deadline = tokio::time::Instant::now() + self.initialize_timeout;
+        loop {
+            let frame = match pending.pop_front() {
+                Some(frame) => frame,
+                // Messages read ahead of `shutdown` have been answered; nothing more is read
+                None if self.shutdown_requested => break,
+                None => tokio::select! {
+                    frame = incoming.recv() => match frame {
+                        Some(frame) => frame,
+                        None => break,
+                    },
+                    () = shutdown.orphaned.cancelled() => break,
+                    () = tokio::time::sleep_until(initialize_deadline), if self.listening && !self.initialized => {
+                        log::warn!("Client did not initialize within {:?}, disconnecting", self.initialize_timeout);
+                        break;
+                    },
+                },
+            };
+            let message = match frame? {
+                Frame::Message(message) => message,
+                // Check request size limit
+                Frame::TooLarge(len) => {
+                    let resp = Response::error(
+                        RequestId::Null,
+                        RpcError::invalid_request(format!(
+                            "Request too large: {} bytes (max: {} bytes)",
+                            len, MAX_REQUEST_SIZE
+                        )),
+                    );
+                    write_output(&serde_json::to_string(&resp)?)?;
+                    continue;
+                },
+                Frame::Invalid(reason) => {
+                    let resp = Response::error(RequestId::Null, RpcError::parse_error(reason));
+                    write_output(&serde_json::to_string(&resp)?)?;
+                    continue;
+                },
+            };
+
+            // A late answer to a host call whose handler already finished
+            if deliver_host_response(&message) {
                 continue;
             }
+            status.features = self.negotiated_features.clone();
 
             // Check if batch request (starts with '[')
-            let trimmed = line.trim_start();
+            let trimmed = message.trim_start();
             if trimmed.starts_with('[') {
                 // Batch request
-                let responses = self.handle_batch(&line).await;
+                let batch = while_reading(
+                    self.handle_batch(&message),
+                    &mut incoming,
+                    &mut pending,
+                    &active_requests,
+                    &shutdown,
+                    &status,
+                )
+                .await;
+                let responses = match batch {
+                    Ok(responses) => responses,
+                    // A batch rejected as a whole is answered with a single response, not an array
+                    Err(error_resp) => {
+                        write_output(&serde_json::to_string(&error_resp)?)?;
+                        Vec::new()
+                    },
+                };
                 if !responses.is_empty() {
                     let json = serde_json::to_string(&responses)?;
                     if json.len() > MAX_RESPONSE_SIZE {
@@ -1123,74 +2669,81 @@ impl PluginServer {
                             RequestId::Null,
                             RpcError::new(error_codes::INTERNAL_ERROR, "Response too large"),
                         );
-                        let error_json = serde_json::to_string(&[error_resp])?;
-                        writeln!(stdout, "{}", error_json)?;
+                        write_output(&serde_json::to_string(&[error_resp])?)?;
                     } else {
-                        writeln!(stdout, "{}", json)?;
+                        write_output(&json)?;
                     }
-                    stdout.flush()?;
                 }
             } else {
                 // Single request
-                let response = self.handle_message(&line).await;
-                if let Some(resp) = response {
-                    let json = serde_json::to_string(&resp)?;
-                    if json.len() > MAX_RESPONSE_SIZE {
-                        eprintln!(
-                            "Warning: Response size {} bytes exceeds limit {} bytes, sending error",
-                            json.len(),
-                            MAX_RESPONSE_SIZE
-                        );
-                        // Error response is guaranteed small (< 1KB) - no size check needed
-                        let error_resp = Response::error(
-                            resp.id,
-                            RpcError::new(error_codes::INTERNAL_ERROR, "Response too large"),
-                        );
-                        let error_json = serde_json::to_string(&error_resp)?;
-                        writeln!(stdout, "{}", error_json)?;
-                    } else {
-                        writeln!(stdout, "{}", json)?;
-                    }
-                    stdout.flush()?;
+                let response = while_reading(
+                    self.handle_message(&message),
+                    &mut incoming,
+                    &mut pending,
+                    &active_requests,
+                    &shutdown,
+                    &status,
+                )
+                .await;
+                match response {
+                    Handling::Done(Some(response)) => send_response(&response)?,
+                    Handling::Done(None) => {},
+                    // Read on while the handler runs, so the next request can start before it ends
+                    Handling::Call(call) => {
+                        self.in_flight.spawn(async move {
+                            if let Err(e) = send_response(&call.await) {
+                                log::warn!("Failed to send response: {}", e);
+                            }
+                        });
+                    },
                 }
             }
 
-            // Check for graceful shutdown after processing the request
-            if self.shutdown_requested {
-                break;
+            // The initialize response went out in the old framing; everything after uses the new one
+            if let Some(framing) = self.negotiated_framing.take() {
+                OUTPUT.lock().unwrap_or_else(PoisonError::into_inner).framing = framing;
             }
         }
 
+        // Nobody is left to read the results of calls still running, unless they are draining
+        fail_host_calls();
+        if !shutdown.is_draining() {
+            cancel_all(&active_requests, CancelReason::Disconnected).await;
+        }
+        while self.in_flight.join_next().await.is_some() {}
         Ok(())
     }
 
     /// Handle a batch of JSON-RPC requests
-    async fn handle_batch(&mut self, line: &str) -> Vec<Response> {
+    ///
+    /// Returns the error response for a batch that is rejected as a whole: unparseable, empty or
+    /// too large.
+    async fn handle_batch(&mut self, line: &str) -> Result<Vec<Response>, Response> {
         // Parse as array of requests
         let requests: Vec<serde_json::Value> = match serde_json::from_str(line) {
             Ok(reqs) => reqs,
             Err(e) => {
-                return vec![Response::error(RequestId::Null, RpcError::parse_error(e.to_string()))];
+                return Err(Response::error(RequestId::Null, RpcError::parse_error(e.to_string())));
             },
         };
 
         if requests.is_empty() {
-            return vec![Response::error(
+            return Err(Response::error(
                 RequestId::Null,
                 RpcError::invalid_request("Empty batch"),
-            )];
+            ));
         }
 
         // Check batch size limit to prevent DoS
         if requests.len() > MAX_BATCH_SIZE {
-            return vec![Response::error(
+            return Err(Response::error(
                 RequestId::Null,
                 RpcError::invalid_request(format!(
                     "Batch too large: {} requests (max: {})",
                     requests.len(),
                     MAX_BATCH_SIZE
                 )),
-            )];
+            ));
         }
 
         // Check for duplicate request IDs (warn but continue)
@@ -1204,18 +2757,28 @@ impl PluginServer {
             }
         }
 
-        let mut responses = Vec::new();
+        // Calls start as they are read and run alongside each other; responses keep batch order
+        let mut handled = Vec::new();
         for req_value in requests {
             // Convert Value directly to Request (avoids double-parsing)
-            if let Some(resp) = self.handle_request_value(req_value).await {
-                responses.push(resp);
-            }
+            handled.push(match self.handle_request_value(req_value).await {
+                Handling::Done(response) => Ok(response),
+                Handling::Call(call) => Err(tokio::spawn(call)),
+            });
         }
-        responses
+        let mut responses = Vec::new();
+        for handling in handled {
+            let response = match handling {
+                Ok(response) => response,
+                Err(call) => call.await.ok(),
+            };
+            responses.extend(response);
+        }
+        Ok(responses)
     }
 
     /// Handle a pre-parsed JSON value (used by batch handler to avoid double-parsing)
-    async fn handle_request_value(&mut self, value: serde_json::Value) -> Option<Response> {
+    async fn handle_request_value(&mut self, value: serde_json::Value) -> Handling {
         // Debug: log raw request
         if self.debug_options.log_requests {
             eprintln!("[DEBUG] Request: {}", value);
@@ -1225,14 +2788,17 @@ impl PluginServer {
         let request: Request = match serde_json::from_value(value) {
             Ok(req) => req,
             Err(e) => {
-                return Some(Response::error(RequestId::Null, RpcError::parse_error(e.to_string())));
+                return Handling::Done(Some(Response::error(
+                    RequestId::Null,
+                    RpcError::parse_error(e.to_string()),
+                )));
             },
         };
 
         self.handle_request(request).await
     }
 
-    async fn handle_message(&mut self, line: &str) -> Option<Response> {
+    async fn handle_message(&mut self, line: &str) -> Handling {
         // Debug: log raw request
         if self.debug_options.log_requests {
             eprintln!("[DEBUG] Request: {}", line);
@@ -1242,7 +2808,10 @@ impl PluginServer {
         let request: Request = match serde_json::from_str(line) {
             Ok(req) => req,
             Err(e) => {
-                return Some(Response::error(RequestId::Null, RpcError::parse_error(e.to_string())));
+                return Handling::Done(Some(Response::error(
+                    RequestId::Null,
+                    RpcError::parse_error(e.to_string()),
+                )));
             },
         };
 
@@ -1250,166 +2819,172 @@ impl PluginServer {
     }
 
     /// Core request handling logic (used by both handle_message and handle_request_value)
-    async fn handle_request(&mut self, request: Request) -> Option<Response> {
+    ///
+    /// Methods of the protocol itself are answered right away; every other method is returned as
+    /// a call for the caller to run, so it does not hold up the requests behind it.
+    async fn handle_request(&mut self, request: Request) -> Handling {
         let Request { id, method, params, .. } = request;
-        // Wrap id in Arc to reduce cloning cost (especially for String IDs)
-        let id = Arc::new(id);
-        let start_time = std::time::Instant::now();
+        let start_time = Instant::now();
 
         // Debug: log parsed request info
         if self.debug_options.log_requests {
             eprintln!("[DEBUG] → {} (id: {:?})", method, id);
         }
 
-        // Check if hooks should be called (skip for internal methods)
-        let call_hooks = !method.starts_with("$/") && method != methods::INITIALIZE && method != methods::SHUTDOWN;
-
-        // Call pre-request hook
-        if call_hooks {
-            if let Some(ref hook) = self.pre_request_hook {
-                let request_info = RequestInfo {
-                    method: method.clone(),
-                    id: (*id).clone(),
-                    params: params.clone(),
-                };
-                if let PreRequestAction::Reject(error) = hook(&request_info) {
-                    // Call post-request hook on rejection
-                    if let Some(ref post_hook) = self.post_request_hook {
-                        post_hook(&ResponseInfo {
-                            method,
-                            id: (*id).clone(),
-                            success: false,
-                            error_code: Some(error.code),
-                            duration: start_time.elapsed(),
-                        });
-                    }
-                    return Some(Response::error(Arc::unwrap_or_clone(id), error));
-                }
-            }
-        }
-
         // Handle based on method
         let result = match method.as_str() {
             methods::INITIALIZE => self.handle_initialize(params),
             methods::SHUTDOWN => {
-                // Call cleanup callback if set
-                if let Some(callback) = self.shutdown_callback.take() {
-                    callback();
+                self.shutdown.draining.store(true, Ordering::Relaxed);
+                self.drain_in_flight().await;
+                // Call cleanup callback if set (daemons outlive each connection's shutdown)
+                if !self.listening {
+                    self.run_shutdown_callback().await;
                 }
                 // Signal graceful shutdown (run loop will exit after sending response)
                 self.shutdown_requested = true;
                 Ok(serde_json::json!(null))
             },
+            methods::EXIT if !self.initialized => {
+                Err(RpcError::new(error_codes::INVALID_REQUEST, "Server not initialized"))
+            },
+            methods::EXIT => {
+                // Ends the connection like `shutdown`; a daemon then stops listening
+                self.shutdown.draining.store(true, Ordering::Relaxed);
+                self.drain_in_flight().await;
+                self.exit_requested = true;
+                self.shutdown_requested = true;
+                Ok(serde_json::json!(null))
+            },
             methods::CANCEL => {
                 self.handle_cancel(params).await;
-                return None; // Cancel is a notification, no response
+                return Handling::Done(None); // Cancel is a notification, no response
+            },
+            methods::STREAM_CREDIT => {
+                grant_stream_credit(params);
+                return Handling::Done(None);
             },
             "$/ping" => {
                 // Health check endpoint
                 Ok(serde_json::json!({ "status": "ok" }))
             },
-            _ => {
-                if !self.initialized {
-                    Err(RpcError::new(error_codes::INVALID_REQUEST, "Server not initialized"))
-                } else if let Some(handler) = self.handlers.get(&method) {
-                    // Cache request ID clone (needed for context, active_requests, and guard)
-                    // We need 3 owned copies: context stores it, HashMap uses it as key, guard stores it
-                    let request_id = (*id).clone();
-
-                    // Create context with cancellation token and shared state
-                    let ctx = match &self.state {
-                        Some(state) => Context::with_state_dyn(request_id.clone(), state.clone()),
-                        None => Context::new(request_id.clone()),
-                    };
-                    let cancel_handle = CancellationHandle::new(&ctx);
-
-                    // Register active request with RAII guard for cleanup
-                    // Note: active_requests Arc clone is cheap (ref count increment)
-                    self.active_requests
-                        .lock()
-                        .await
-                        .insert(request_id.clone(), cancel_handle.clone());
-                    let _guard = ActiveRequestGuard {
-                        id: request_id,
-                        active_requests: self.active_requests.clone(),
-                        stale_ids: self.stale_request_ids.clone(),
-                    };
-
-                    // Determine effective timeout (handler-specific overrides default)
-                    let effective_timeout = handler.timeout.or(self.default_timeout);
-
-                    // Execute handler with optional timeout
-                    // The _guard ensures cleanup even if handler panics
-                    match effective_timeout {
-                        Some(timeout_duration) => {
-                            match tokio::time::timeout(timeout_duration, (handler.func)(ctx, params)).await {
-                                Ok(result) => result,
-                                Err(_elapsed) => {
-                                    cancel_handle.cancel();
-                                    Err(RpcError::new(
-                                        error_codes::REQUEST_CANCELLED,
-                                        format!("Request timed out after {:?}", timeout_duration),
-                                    ))
-                                },
-                            }
-                        },
-                        None => (handler.func)(ctx, params).await,
-                    }
-                    // _guard dropped here, cleaning up active_requests
-                } else {
-                    Err(RpcError::method_not_found(&method))
-                }
-            },
+            methods::METRICS => Ok(serde_json::json!(MetricsResult {
+                text: metrics::registry().render(),
+            })),
+            methods::STATUS => Ok(serde_json::json!(
+                self.status_source().report(&self.active_requests).await
+            )),
+            _ if self.shutdown.is_draining() => Err(RpcError::shutting_down()),
+            _ => return Handling::Call(Box::pin(self.call(id, method, params, start_time).await)),
         };
+        Handling::Done(Some(respond(id, &method, start_time, result, &self.debug_options)))
+    }
 
-        let duration = start_time.elapsed();
-
-        // Debug: log profiling info
-        if self.debug_options.log_profiling {
-            let status = if result.is_ok() { "OK" } else { "ERR" };
-            eprintln!("[PROFILE] {} {:?} - {} ({:?})", method, id, status, duration);
+    /// Start a call of a registered method, answered once its handler finishes
+    async fn call(
+        &self,
+        id: RequestId,
+        method: String,
+        params: Option<serde_json::Value>,
+        start_time: Instant,
+    ) -> impl Future<Output = Response> + Send + 'static {
+        // Create context with cancellation token and shared state
+        let ctx = match &self.state {
+            Some(state) => Context::with_state_dyn(id.clone(), state.clone()),
+            None => Context::new(id.clone()),
         }
+        .with_config(self.config.clone())
+        .with_features(self.negotiated_features.clone())
+        .with_inline_tensor_limit(self.negotiated_inline_tensor_limit);
+
+        // Register active request with RAII guard for cleanup, so `$/cancel` reaches
+        // layers as well as the handler, even if it arrives before the call starts
+        self.active_requests.lock().await.insert(
+            id.clone(),
+            ActiveRequest {
+                handle: CancellationHandle::new(&ctx),
+                method: method.clone(),
+                started: start_time,
+            },
+        );
+        let guard = ActiveRequestGuard {
+            id: id.clone(),
+            active_requests: self.active_requests.clone(),
+            stale_ids: self.stale_request_ids.clone(),
+        };
 
-        // Call post-request hook
-        if call_hooks {
-            if let Some(ref hook) = self.post_request_hook {
-                hook(&ResponseInfo {
-                    method,
-                    id: (*id).clone(),
-                    success: result.is_ok(),
-                    error_code: result.as_ref().err().map(|e| e.code),
-                    duration,
-                });
-            }
-        }
+        // Determine the handler and its effective timeout (handler-specific overrides default)
+        let handler = self.handlers.get(&method);
+        let endpoint = Endpoint {
+            handler: match handler {
+                _ if !self.initialized => Err(RpcError::new(error_codes::INVALID_REQUEST, "Server not initialized")),
+                Some(handler) => Ok(handler.func.clone()),
+                None => Err(RpcError::method_not_found(&method)),
+            },
+            timeout: handler.and_then(|h| h.timeout).or(self.default_timeout),
+            limits: self.limits.get(&method).cloned(),
+        };
 
-        // Unwrap Arc for final response (no more clones needed)
-        let id = Arc::unwrap_or_clone(id);
-        let response = match result {
-            Ok(value) => Response::success(id, value),
-            Err(error) => Response::error(id, error),
+        let next = Next {
+            layers: self.layers.clone(),
+            index: 0,
+            endpoint,
         };
+        let metrics_label = match handler {
+            Some(_) => method.clone(),
+            None => metrics::UNKNOWN_METHOD.to_string(),
+        };
+        let debug_options = self.debug_options.clone();
+        async move {
+            let _active = metrics::registry().start_request();
+            let span = tracing::info_span!("request", method = %method, id = %id);
+            let result = next
+                .run(MethodCall {
+                    method: method.clone(),
+                    params,
+                    ctx,
+                })
+                .instrument(span)
+                .await;
+            metrics::registry().record(
+                &metrics_label,
+                start_time.elapsed(),
+                result.as_ref().err().map(|e| e.code),
+            );
+            // Cleans up active_requests before the response goes out
+            drop(guard);
+            respond(id, &method, start_time, result, &debug_options)
+        }
+    }
 
-        // Debug: log response
-        if self.debug_options.log_responses {
-            if let Ok(json) = serde_json::to_string(&response) {
-                eprintln!("[DEBUG] ← {}", json);
-            }
+    /// Wait for the calls in flight, cancelling those still running after the shutdown timeout
+    async fn drain_in_flight(&mut self) {
+        let drained = tokio::time::timeout(self.shutdown.timeout, async {
+            while self.in_flight.join_next().await.is_some() {}
+        })
+        .await;
+        if drained.is_err() {
+            log::warn!("Shutdown timeout reached, cancelling in-flight requests");
+            cancel_all(&self.active_requests, CancelReason::Shutdown).await;
+            while self.in_flight.join_next().await.is_some() {}
         }
+    }
 
-        Some(response)
+    /// What `$/status` reports about this server
+    fn status_source(&self) -> StatusSource {
+        let mut handlers: Vec<String> = self.handlers.keys().cloned().collect();
+        handlers.sort();
+        StatusSource {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            started: self.started,
+            handlers: handlers.into(),
+            features: self.negotiated_features.clone(),
+        }
     }
 
     async fn handle_cancel(&self, params: Option<serde_json::Value>) {
-        let Some(params) = try_deserialize_params::<CancelParams>(params) else {
-            return;
-        };
-
-        let active = self.active_requests.lock().await;
-        if let Some(handle) = active.get(&params.id) {
-            handle.cancel();
-            log_debug(&format!("Request {:?} cancelled", params.id));
-        }
+        cancel_request(&self.active_requests, params).await;
     }
 
     fn handle_initialize(&mut self, params: Option<serde_json::Value>) -> Result<serde_json::Value, RpcError> {
@@ -1417,10 +2992,40 @@ impl PluginServer {
             return Err(RpcError::new(error_codes::INVALID_REQUEST, "Already initialized"));
         }
 
-        let _params: InitializeParams = deserialize_params(params)?;
+        let params: InitializeParams = deserialize_params(params)?;
+
+        if let Some(expected) = &self.auth_token {
+            let presented = params.auth_token.as_deref().unwrap_or_default();
+            if !constant_time_eq(presented.as_bytes(), expected.as_bytes()) {
+                // Drop the connection once the error is sent
+                self.shutdown_requested = true;
+                return Err(RpcError::unauthorized());
+            }
+        }
+
+        if let Some(parse) = &self.config_parser {
+            let config = parse(params.config)
+                .map_err(|e| RpcError::invalid_params(format!("Invalid config for '{}': {}", self.name, e)))?;
+            self.config = Some(config);
+        }
 
         self.initialized = true;
 
+        let features = negotiate_features(&self.features, &params.features);
+        self.negotiated_features = features.clone().into();
+        self.negotiated_inline_tensor_limit = negotiate_inline_tensor_limit(
+            features.iter().any(|f| f == features::INLINE_TENSORS),
+            Some(self.inline_tensor_limit),
+            params.inline_tensor_limit,
+        );
+
+        // The SDK's file helpers keep to the sandbox from here on
+        crate::sandbox::set_policy(params.sandbox);
+
+        // Take the CLI's preferred framing; every framing is supported
+        let framing = params.framings.first().copied().unwrap_or_default();
+        self.negotiated_framing = Some(framing);
+
         // Convert local metadata to RPC metadata
         let metadata = if self.metadata.description.is_some()
             || self.metadata.author.is_some()
@@ -1453,6 +3058,10 @@ impl PluginServer {
             tensor_extensions: self.tensor_extensions.clone(),
             devices: self.devices.clone(),
             metadata,
+            framing,
+            config_schema: self.config_schema.clone(),
+            features,
+            inline_tensor_limit: Some(self.inline_tensor_limit.min(MAX_INLINE_TENSOR_LIMIT)),
         };
 
         // Validate result limits before sending
@@ -1466,3 +3075,8 @@ impl PluginServer {
         serde_json::to_value(result).map_err(|e| RpcError::internal_error(e.to_string()))
     }
 }
+
+/// Compare secrets without leaking the length of the matching prefix through timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
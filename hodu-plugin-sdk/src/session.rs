@@ -0,0 +1,131 @@
+//! Keyed store for loaded model sessions
+//!
+//! Backends implementing `backend.load_session`, `backend.run_session` and
+//! `backend.close_session` keep whatever they loaded (a `dlopen`ed library, device buffers holding
+//! the weights) in a [`SessionStore`], and look it up by the session ID the CLI sends back.
+//!
+//! ```ignore
+//! struct Backend {
+//!     sessions: SessionStore<LoadedModel>,
+//! }
+//!
+//! async fn load_session(ctx: Context, params: LoadSessionParams) -> Result<LoadSessionResult, RpcError> {
+//!     let backend = ctx.state::<Backend>().ok_or_else(|| RpcError::internal_error("missing state"))?;
+//!     let model = LoadedModel::open(&params.library_path, &params.device)?;
+//!     let session_id = backend.sessions.insert(model)?;
+//!     Ok(LoadSessionResult { session_id })
+//! }
+//!
+//! async fn run_session(ctx: Context, params: RunSessionParams) -> Result<RunResult, RpcError> {
+//!     let backend = ctx.state::<Backend>().ok_or_else(|| RpcError::internal_error("missing state"))?;
+//!     let model = backend.sessions.get(&params.session_id)?;
+//!     model.run(&params.inputs)
+//! }
+//! ```
+
+use crate::rpc::RpcError;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Sessions by ID, shared by every handler holding a clone
+///
+/// Sessions are handed out as `Arc`s, so a session closed while one of its runs is in progress
+/// is dropped once that run finishes.
+pub struct SessionStore<T> {
+    sessions: Arc<Mutex<HashMap<String, Arc<T>>>>,
+    next_id: Arc<AtomicU64>,
+    max_sessions: Option<usize>,
+}
+
+impl<T> Clone for SessionStore<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sessions: self.sessions.clone(),
+            next_id: self.next_id.clone(),
+            max_sessions: self.max_sessions,
+        }
+    }
+}
+
+impl<T> Default for SessionStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SessionStore<T> {
+    /// Create an empty store without a session limit
+    pub fn new() -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            max_sessions: None,
+        }
+    }
+
+    /// Refuse to open more than `max` sessions at once
+    ///
+    /// Each session usually pins a model's weights in memory, so backends serving several CLIs
+    /// should set a limit.
+    pub fn with_limit(mut self, max: usize) -> Self {
+        self.max_sessions = Some(max);
+        self
+    }
+
+    /// Store a session and return its new ID
+    ///
+    /// # Errors
+    /// Returns a `BUSY` error if the store is at its limit.
+    pub fn insert(&self, session: T) -> Result<String, RpcError> {
+        let mut sessions = self.lock();
+        if let Some(max) = self.max_sessions {
+            if sessions.len() >= max {
+                return Err(RpcError::busy(format!("Too many open sessions (max: {})", max), None));
+            }
+        }
+        let session_id = format!("session-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        sessions.insert(session_id.clone(), Arc::new(session));
+        Ok(session_id)
+    }
+
+    /// Get the session with ID `session_id`
+    ///
+    /// # Errors
+    /// Returns a `SESSION_NOT_FOUND` error if no open session has that ID.
+    pub fn get(&self, session_id: &str) -> Result<Arc<T>, RpcError> {
+        self.lock()
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| RpcError::session_not_found(session_id))
+    }
+
+    /// Remove the session with ID `session_id` and return it
+    ///
+    /// # Errors
+    /// Returns a `SESSION_NOT_FOUND` error if no open session has that ID.
+    pub fn remove(&self, session_id: &str) -> Result<Arc<T>, RpcError> {
+        self.lock()
+            .remove(session_id)
+            .ok_or_else(|| RpcError::session_not_found(session_id))
+    }
+
+    /// Number of open sessions
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no session is open
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Remove every session, e.g. from a shutdown callback
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<T>>> {
+        self.sessions.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,47 @@
+//! `$/status`: what a running plugin is doing
+
+use crate::rpc::{ActiveRequestStatus, StatusResult};
+use crate::server::ActiveRequests;
+use hodu_plugin::memory::{peak_memory, resident_memory};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Everything `$/status` reports besides the live request table
+#[derive(Clone)]
+pub(crate) struct StatusSource {
+    pub name: String,
+    pub version: String,
+    pub started: Instant,
+    pub handlers: Arc<[String]>,
+    pub features: Arc<[String]>,
+}
+
+impl StatusSource {
+    /// Snapshot the plugin's status
+    pub async fn report(&self, active_requests: &ActiveRequests) -> StatusResult {
+        let mut active: Vec<ActiveRequestStatus> = active_requests
+            .lock()
+            .await
+            .iter()
+            .map(|(id, request)| ActiveRequestStatus {
+                id: id.clone(),
+                method: request.method.clone(),
+                elapsed_ms: request.started.elapsed().as_millis() as u64,
+                cancelled: request.handle.is_cancelled(),
+            })
+            .collect();
+        active.sort_by_key(|request| std::cmp::Reverse(request.elapsed_ms));
+
+        StatusResult {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            pid: std::process::id(),
+            uptime_ms: self.started.elapsed().as_millis() as u64,
+            active_requests: active,
+            features: self.features.to_vec(),
+            handlers: self.handlers.to_vec(),
+            resident_memory_bytes: resident_memory(),
+            peak_memory_bytes: peak_memory(),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Reading tensor inputs and writing tensor outputs, inline or through files
+//!
+//! Once `inline_tensors` is negotiated, small tensors travel inside messages instead of through
+//! .hdt files. [`load_input`] reads an input either way, and [`Context::output`](crate::Context::output)
+//! picks how to send an output:
+//!
+//! ```ignore
+//! async fn handle_run(ctx: Context, params: RunParams) -> Result<RunResult, RpcError> {
+//!     let inputs = params.inputs.iter().map(load_input).collect::<Result<Vec<_>, _>>()?;
+//!     let outputs = model.run(&inputs)?;
+//!     Ok(RunResult {
+//!         outputs: outputs
+//!             .iter()
+//!             .map(|(name, tensor)| ctx.output(name, tensor))
+//!             .collect::<Result<_, _>>()?,
+//!     })
+//! }
+//! ```
+
+use crate::rpc::{RpcError, TensorInput, TensorOutput};
+use crate::{hdt, sandbox, CoreDevice, Shape, Tensor, TensorData, TensorDataExt};
+use std::path::PathBuf;
+
+/// Shared memory directory preferred for output files, where the system has one
+const SHARED_MEMORY_DIR: &str = "/dev/shm";
+
+/// Load an input tensor, whether it was sent inline or as a file
+///
+/// # Errors
+/// Returns an access denied error if the file is outside the sandbox, or a tensor error naming
+/// the input if it cannot be decoded or loaded.
+pub fn load_input(input: &TensorInput) -> Result<TensorData, RpcError> {
+    match &input.data {
+        Some(inline) => inline
+            .decode()
+            .map_err(|e| RpcError::tensor_error(format!("Invalid inline input '{}': {}", input.name, e))),
+        None => {
+            sandbox::check_read(&input.path).map_err(|e| RpcError::access_denied(e.to_string()))?;
+            TensorData::load(&input.path)
+                .map_err(|e| RpcError::tensor_error(format!("Failed to load input '{}': {}", input.name, e)))
+        },
+    }
+}
+
+/// Load an input as a hodu_core tensor on the CPU, whether it was sent inline or as a file
+pub(crate) fn load_input_tensor(input: &TensorInput) -> Result<Tensor, String> {
+    match &input.data {
+        Some(inline) => {
+            let data = inline.decode().map_err(|e| e.to_string())?;
+            let dtype = data.core_dtype().map_err(|e| e.to_string())?;
+            Tensor::from_bytes(&data.data, Shape::new(&data.shape), dtype, CoreDevice::CPU).map_err(|e| e.to_string())
+        },
+        None => hdt::load(&input.path).map_err(|e| e.to_string()),
+    }
+}
+
+/// Describe an output tensor for the CLI, inline if its data is at most `inline_limit` bytes
+///
+/// Larger tensors are written to `hodu_output_<pid>_<name>.hdt` in /dev/shm, or in the temp
+/// directory where there is no /dev/shm. The name is fixed per output, so each run overwrites
+/// the previous run's file instead of accumulating them.
+///
+/// # Errors
+/// Returns an invalid params error for an invalid output name, an access denied error if the
+/// output directory is outside the sandbox, or a tensor error if the file cannot be written.
+pub fn write_output(name: &str, tensor: &TensorData, inline_limit: u64) -> Result<TensorOutput, RpcError> {
+    if !TensorOutput::new(name, "").is_valid_name() {
+        return Err(RpcError::invalid_params(format!(
+            "Invalid output tensor name '{}'",
+            name
+        )));
+    }
+    if inline_limit > 0 && tensor.data.len() as u64 <= inline_limit {
+        return Ok(TensorOutput::inline(name, tensor));
+    }
+
+    let path = output_dir().join(format!("hodu_output_{}_{}.hdt", std::process::id(), name));
+    sandbox::check_write(&path).map_err(|e| RpcError::access_denied(e.to_string()))?;
+    tensor
+        .save(&path)
+        .map_err(|e| RpcError::tensor_error(format!("Failed to write output '{}': {}", name, e)))?;
+    Ok(TensorOutput::new(name, path.to_string_lossy()))
+}
+
+fn output_dir() -> PathBuf {
+    let shared = PathBuf::from(SHARED_MEMORY_DIR);
+    if shared.is_dir() {
+        shared
+    } else {
+        std::env::temp_dir()
+    }
+}
@@ -0,0 +1,151 @@
+//! Sending tensors for `format.stream_load_tensor`
+//!
+//! [`TensorStreamWriter`] sends a tensor's header and then its data on the request's `tensor`
+//! stream, so the CLI writes the tensor to disk as it arrives and neither side holds all of it:
+//!
+//! ```ignore
+//! async fn handle_stream_load_tensor(
+//!     ctx: Context,
+//!     params: StreamLoadTensorParams,
+//! ) -> Result<StreamLoadTensorResult, RpcError> {
+//!     let mut file = NpyReader::open(&params.path)?;
+//!     let mut writer = ctx.stream_tensor(&params, file.shape().to_vec(), file.dtype())?;
+//!     let mut buf = vec![0u8; writer.chunk_size()];
+//!     while writer.bytes_remaining() > 0 {
+//!         let n = file.read(&mut buf)?;
+//!         writer.write(&buf[..n]).await?;
+//!     }
+//!     writer.finish()
+//! }
+//! ```
+
+use crate::rpc::{
+    features, RpcError, StreamLoadTensorParams, StreamLoadTensorResult, TensorChunk, MAX_TENSOR_CHUNK_SIZE,
+    TENSOR_STREAM,
+};
+use crate::server::ResultStream;
+use crate::{sandbox, Context, PluginDType};
+use std::path::Path;
+
+/// Writes one tensor to the CLI as a stream of chunks
+///
+/// Created with [`Context::stream_tensor`], which sends the header. Data must be written in
+/// order, and all `numel * dtype size` bytes of it before [`finish`](Self::finish). Writes wait
+/// for the CLI to consume earlier chunks when it negotiated flow control, so a tensor read faster
+/// than the CLI writes it to disk is not buffered in the CLI.
+pub struct TensorStreamWriter {
+    stream: ResultStream<TensorChunk>,
+    chunk_size: usize,
+    written: u64,
+    total: u64,
+}
+
+impl TensorStreamWriter {
+    /// Open the `tensor` stream of the request and send the header
+    pub(crate) fn open(
+        ctx: &Context,
+        params: &StreamLoadTensorParams,
+        shape: Vec<usize>,
+        dtype: PluginDType,
+    ) -> Result<Self, RpcError> {
+        let total = shape
+            .iter()
+            .try_fold(dtype.size_in_bytes() as u64, |acc, &dim| acc.checked_mul(dim as u64))
+            .ok_or_else(|| RpcError::tensor_error(format!("Tensor shape {:?} is too large", shape)))?;
+        let chunk_size = params
+            .chunk_size
+            .unwrap_or(MAX_TENSOR_CHUNK_SIZE)
+            .clamp(1, MAX_TENSOR_CHUNK_SIZE) as usize;
+
+        // Without streaming the CLI would drop every chunk
+        if !ctx.has_feature(features::STREAMING) {
+            return Err(RpcError::not_supported(
+                "format.stream_load_tensor without the streaming feature",
+            ));
+        }
+        let mut stream = ctx.stream(TENSOR_STREAM)?;
+        stream.send(&TensorChunk::Header { shape, dtype })?;
+        Ok(Self {
+            stream,
+            chunk_size,
+            written: 0,
+            total,
+        })
+    }
+
+    /// Bytes of data per inline chunk the CLI asked for
+    ///
+    /// Reading the source in buffers of this size sends each buffer as one chunk.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Bytes of tensor data still to be written
+    pub fn bytes_remaining(&self) -> u64 {
+        self.total - self.written
+    }
+
+    /// Send the next bytes of raw little-endian tensor data inline, in chunks of at most
+    /// [`chunk_size`](Self::chunk_size)
+    pub async fn write(&mut self, data: &[u8]) -> Result<(), RpcError> {
+        self.check_len(data.len() as u64)?;
+        for chunk in data.chunks(self.chunk_size) {
+            self.stream.write(&TensorChunk::data(self.written, chunk)).await?;
+            self.written += chunk.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Send the next `len` bytes of tensor data as a file the CLI reads them from
+    ///
+    /// For large chunks this skips base64 and the JSON transport: write the data to the start of
+    /// a file on fast storage (e.g. /dev/shm) and pass its path. The CLI deletes the file once it
+    /// has read it.
+    pub async fn write_shared(&mut self, path: impl AsRef<Path>, len: u64) -> Result<(), RpcError> {
+        self.check_len(len)?;
+        let path = path.as_ref();
+        // The CLI refuses chunks a sandboxed plugin could not have written
+        sandbox::check_write(path).map_err(|e| RpcError::access_denied(e.to_string()))?;
+        let path = path
+            .to_str()
+            .ok_or_else(|| RpcError::invalid_params(format!("Shared chunk path is not UTF-8: {}", path.display())))?;
+        self.stream
+            .write(&TensorChunk::Shared {
+                offset: self.written,
+                path: path.to_string(),
+                len,
+            })
+            .await?;
+        self.written += len;
+        Ok(())
+    }
+
+    /// Close the stream once all data is written, returning the response for the request
+    ///
+    /// # Errors
+    /// Returns a tensor error if data is missing.
+    pub fn finish(self) -> Result<StreamLoadTensorResult, RpcError> {
+        if self.written != self.total {
+            return Err(RpcError::tensor_error(format!(
+                "Streamed {} of {} bytes of tensor data",
+                self.written, self.total
+            )));
+        }
+        self.stream.close()?;
+        Ok(StreamLoadTensorResult {
+            total_bytes: self.written,
+        })
+    }
+
+    fn check_len(&self, len: u64) -> Result<(), RpcError> {
+        if len > self.bytes_remaining() {
+            return Err(RpcError::tensor_error(format!(
+                "Writing {} bytes would exceed the tensor's {} bytes ({} remaining)",
+                len,
+                self.total,
+                self.bytes_remaining()
+            )));
+        }
+        Ok(())
+    }
+}
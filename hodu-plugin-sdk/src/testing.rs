@@ -1,6 +1,13 @@
 //! Testing utilities for plugin development
 //!
-//! This module provides tools for testing plugins without running a full server.
+//! This module provides tools for testing plugins without running a full server:
+//! [`MockClient`] and [`TestHarness`] call handlers directly, [`MockContext`] is a context for
+//! calling them yourself, and [`TestClient`] drives a whole [`PluginServer`] in-process, through
+//! `initialize` and the JSON-RPC loop the CLI talks to. [`SnapshotBuilder`] fabricates small models
+//! for them to load or run, and [`conformance`] runs a backend through a shared suite of
+//! golden-file cases. [`TestClient::replay`] checks a plugin against an exchange recorded with
+//! `hodu trace record`, and [`fuzz`] hardens handlers and message parsing against adversarial
+//! input.
 //!
 //! # Example
 //!
@@ -19,13 +26,26 @@
 //! }
 //! ```
 
-use crate::context::Context;
-use crate::rpc::{Request, RequestId, RpcError};
+pub mod conformance;
+pub mod fuzz;
+mod snapshot_builder;
+
+pub use hodu_plugin::trace::{Difference, Replay, ReplayReport, Trace};
+pub use snapshot_builder::SnapshotBuilder;
+
+use crate::context::{CancellationHandle, CapturedNotifications, Context};
+use crate::rpc::{
+    features, methods, CancelParams, CancelReason, InitializeParams, InitializeResult, LogParams, Notification,
+    ProgressParams, Request, RequestId, Response, RpcError, StreamCreditParams, PROTOCOL_VERSION,
+};
+use crate::server::PluginServer;
 use serde::{de::DeserializeOwned, Serialize};
 use std::collections::HashMap;
 use std::future::Future;
+use std::io::{BufReader, Read, Write};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{mpsc, Arc, PoisonError};
 
 // ============================================================================
 // Mock Client
@@ -75,18 +95,23 @@ pub struct MockClient {
 /// assert!(matches!(result, Err(e) if e.code == error_codes::REQUEST_CANCELLED));
 /// ```
 pub struct MockCancellationHandle {
-    token: tokio_util::sync::CancellationToken,
+    handle: CancellationHandle,
 }
 
 impl MockCancellationHandle {
-    /// Cancel the associated request
+    /// Cancel the associated request, as `$/cancel` from a user abort would
     pub fn cancel(&self) {
-        self.token.cancel();
+        self.handle.cancel(CancelReason::UserAbort);
+    }
+
+    /// Cancel the associated request for the given reason, e.g. to test timeout handling
+    pub fn cancel_with(&self, reason: CancelReason) {
+        self.handle.cancel(reason);
     }
 
     /// Check if cancellation was requested
     pub fn is_cancelled(&self) -> bool {
-        self.token.is_cancelled()
+        self.handle.is_cancelled()
     }
 }
 
@@ -118,7 +143,8 @@ impl MockClient {
     /// Call a handler with cancellation support
     ///
     /// Returns a cancellation handle and a future. Use the handle to cancel the
-    /// request, which will set `ctx.is_cancelled()` to true in the handler.
+    /// request, which will set `ctx.is_cancelled()` to true in the handler. Cleanups registered
+    /// with `ctx.cleanup_on_cancel()` run if the handler then returns an error.
     ///
     /// # Example
     ///
@@ -149,9 +175,19 @@ impl MockClient {
         Fut: Future<Output = Result<R, RpcError>>,
     {
         let ctx = Context::new(self.next_id());
-        let token = ctx.cancellation_token().clone();
-        let handle = MockCancellationHandle { token };
+        let cancel_handle = CancellationHandle::new(&ctx);
+        let handle = MockCancellationHandle {
+            handle: cancel_handle.clone(),
+        };
         let future = handler(ctx, params);
+        // Run cleanups as the server would when the request ends cancelled
+        let future = async move {
+            let result = future.await;
+            if result.is_err() && cancel_handle.is_cancelled() {
+                cancel_handle.run_cleanups();
+            }
+            result
+        };
         (handle, future)
     }
 
@@ -190,6 +226,137 @@ impl Default for MockClient {
     }
 }
 
+// ============================================================================
+// Mock Context
+// ============================================================================
+
+/// A [`Context`] for calling handler functions directly in unit tests
+///
+/// Cancel it when the test decides, give it the shared state and configuration the handler
+/// expects, and read back the progress and log notifications the handler sent, which are
+/// captured here instead of written to stdout.
+///
+/// # Example
+///
+/// ```ignore
+/// use hodu_plugin_sdk::testing::MockContext;
+///
+/// #[tokio::test]
+/// async fn test_handler() {
+///     let mock = MockContext::new().with_state(MyState::default());
+///     let result = handle_run(mock.context(), params).await;
+///
+///     assert!(result.is_ok());
+///     assert_eq!(mock.progress().last().unwrap().percent, Some(100));
+///     assert!(mock.logs().iter().any(|log| log.message.contains("compiled")));
+/// }
+/// ```
+pub struct MockContext {
+    ctx: Context,
+    handle: CancellationHandle,
+    notifications: CapturedNotifications,
+}
+
+impl MockContext {
+    /// Create a context for request ID 1, not cancelled, with no state or configuration
+    pub fn new() -> Self {
+        let notifications = CapturedNotifications::default();
+        let ctx = Context::new(RequestId::Number(1)).with_captured_notifications(notifications.clone());
+        Self {
+            handle: CancellationHandle::new(&ctx),
+            ctx,
+            notifications,
+        }
+    }
+
+    /// Give the context shared state, as [`PluginServer::with_state`] does
+    pub fn with_state<S: Send + Sync + 'static>(mut self, state: S) -> Self {
+        self.ctx = self.ctx.with_state(Arc::new(state));
+        self
+    }
+
+    /// Give the context the plugin configuration, as received in `initialize`
+    pub fn with_config<C: Send + Sync + 'static>(mut self, config: C) -> Self {
+        self.ctx = self.ctx.with_config(Some(Arc::new(config)));
+        self
+    }
+
+    /// The context to pass to the handler
+    ///
+    /// Every call returns a clone sharing cancellation and captured notifications, so it can be
+    /// called again after the handler took one.
+    pub fn context(&self) -> Context {
+        self.ctx.clone()
+    }
+
+    /// The shared state given with [`with_state`](Self::with_state), to inspect after the handler ran
+    pub fn state<S: Send + Sync + 'static>(&self) -> Option<Arc<S>> {
+        self.ctx.state()
+    }
+
+    /// Cancel the request, as `$/cancel` from a user abort would
+    pub fn cancel(&self) {
+        self.handle.cancel(CancelReason::UserAbort);
+    }
+
+    /// Cancel the request for the given reason, e.g. to test timeout handling
+    pub fn cancel_with(&self, reason: CancelReason) {
+        self.handle.cancel(reason);
+    }
+
+    /// Check if cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.handle.is_cancelled()
+    }
+
+    /// Run the handler's `cleanup_on_cancel` cleanups, as the server does when a cancelled
+    /// request ends with an error
+    pub fn run_cleanups(&self) {
+        self.handle.run_cleanups();
+    }
+
+    /// Progress and log notifications the handler sent, oldest first
+    pub fn notifications(&self) -> Vec<Notification> {
+        self.notifications
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Forget the notifications captured so far
+    pub fn clear_notifications(&self) {
+        self.notifications
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// `$/progress` notifications the handler sent
+    pub fn progress(&self) -> Vec<ProgressParams> {
+        notification_params(&self.notifications(), methods::NOTIFY_PROGRESS)
+    }
+
+    /// `$/log` notifications the handler sent
+    pub fn logs(&self) -> Vec<LogParams> {
+        notification_params(&self.notifications(), methods::NOTIFY_LOG)
+    }
+}
+
+impl Default for MockContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Params of the notifications of `method` in `notifications`
+fn notification_params<T: DeserializeOwned>(notifications: &[Notification], method: &str) -> Vec<T> {
+    notifications
+        .iter()
+        .filter(|n| n.method == method)
+        .filter_map(|n| serde_json::from_value(n.params.clone()?).ok())
+        .collect()
+}
+
 // ============================================================================
 // Test Harness
 // ============================================================================
@@ -363,6 +530,499 @@ impl Default for TestHarness {
     }
 }
 
+// ============================================================================
+// In-Process Client
+// ============================================================================
+
+/// Serializes [`TestClient`]s, since plugin output and the sandbox policy are process-wide
+static EXCLUSIVE: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// A client driving a [`PluginServer`] in-process, as the CLI would over stdio
+///
+/// The server runs on a task of its own, connected to the client by in-memory pipes, and
+/// `initialize` is sent on [`start`](Self::start). Requests go through the same parsing,
+/// dispatch, cancellation and notification paths as in a real plugin, so handlers are tested
+/// the way the CLI calls them. Notifications the plugin sends are recorded for the assertion
+/// helpers.
+///
+/// Plugin output is process-wide, so clients in concurrently running tests take turns: a second
+/// `start` waits until the first client is dropped.
+///
+/// # Example
+///
+/// ```ignore
+/// use hodu_plugin_sdk::testing::TestClient;
+///
+/// #[tokio::test]
+/// async fn test_run() {
+///     let server = PluginServer::new("my-backend", "0.1.0").method("backend.run", handle_run);
+///     let client = TestClient::start(server).await.unwrap();
+///
+///     let result = client.call::<RunParams, RunResult>("backend.run", params).await.unwrap();
+///     client.assert_progress(100);
+///
+///     // Cancel a request once it reports progress
+///     let call = client.send("backend.run", params);
+///     client.wait_for_notification("$/progress").await;
+///     client.cancel(&call);
+///     assert_error_code(&call.result::<RunResult>().await, error_codes::REQUEST_CANCELLED);
+/// }
+/// ```
+pub struct TestClient {
+    /// Messages to the server, one line each
+    input: mpsc::Sender<Vec<u8>>,
+    inbox: Arc<Inbox>,
+    request_counter: AtomicI64,
+    info: InitializeResult,
+}
+
+/// Messages from the server, filled in as it writes them
+#[derive(Default)]
+struct Inbox {
+    state: std::sync::Mutex<InboxState>,
+    changed: tokio::sync::Notify,
+}
+
+#[derive(Default)]
+struct InboxState {
+    responses: HashMap<RequestId, Response>,
+    notifications: Vec<Notification>,
+    /// Notifications before this index were returned by, or skipped for, `wait_for_notification`
+    waited: usize,
+    /// Set once the server task has ended
+    closed: bool,
+}
+
+impl Inbox {
+    fn update(&self, f: impl FnOnce(&mut InboxState)) {
+        f(&mut self.state.lock().unwrap_or_else(PoisonError::into_inner));
+        self.changed.notify_waiters();
+    }
+
+    /// Wait until `f` finds what it looks for, or `None` once the server has ended
+    async fn wait_for<T>(&self, mut f: impl FnMut(&mut InboxState) -> Option<T>) -> Option<T> {
+        loop {
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+            {
+                let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+                if let Some(found) = f(&mut state) {
+                    return Some(found);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            changed.await;
+        }
+    }
+
+    async fn response(&self, id: &RequestId) -> Result<serde_json::Value, RpcError> {
+        let response = self
+            .wait_for(|state| state.responses.remove(id))
+            .await
+            .ok_or_else(|| RpcError::internal_error("Plugin server stopped before answering"))?;
+        match response.error {
+            Some(error) => Err(error),
+            None => Ok(response.result.unwrap_or(serde_json::Value::Null)),
+        }
+    }
+}
+
+/// Marks the inbox closed when the server task ends, even by panicking
+struct CloseOnDrop(Arc<Inbox>);
+
+impl Drop for CloseOnDrop {
+    fn drop(&mut self) {
+        self.0.update(|state| state.closed = true);
+    }
+}
+
+/// Server output, parsed into the inbox line by line
+struct OutputPipe {
+    inbox: Arc<Inbox>,
+    buf: Vec<u8>,
+}
+
+impl OutputPipe {
+    fn receive(&self, message: serde_json::Value) {
+        if message.get("method").is_some() {
+            let notification: Notification = serde_json::from_value(message)
+                .unwrap_or_else(|e| panic!("Plugin wrote an invalid notification: {}", e));
+            self.inbox.update(|state| state.notifications.push(notification));
+        } else {
+            let response: Response =
+                serde_json::from_value(message).unwrap_or_else(|e| panic!("Plugin wrote an invalid response: {}", e));
+            self.inbox.update(|state| {
+                state.responses.insert(response.id.clone(), response);
+            });
+        }
+    }
+}
+
+impl Write for OutputPipe {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        while let Some(end) = self.buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=end).collect();
+            let message: serde_json::Value = match serde_json::from_slice(&line) {
+                Ok(message) => message,
+                Err(e) => panic!("Plugin wrote invalid JSON: {} ({})", String::from_utf8_lossy(&line), e),
+            };
+            // Batches are answered with an array of responses
+            let messages = match message {
+                serde_json::Value::Array(messages) => messages,
+                message => vec![message],
+            };
+            for message in messages {
+                self.receive(message);
+            }
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Server input, read from the client's messages until the client is dropped
+struct InputPipe {
+    messages: mpsc::Receiver<Vec<u8>>,
+    current: std::io::Cursor<Vec<u8>>,
+}
+
+impl Read for InputPipe {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let read = self.current.read(buf)?;
+            if read > 0 || buf.is_empty() {
+                return Ok(read);
+            }
+            match self.messages.recv() {
+                Ok(message) => self.current = std::io::Cursor::new(message),
+                // The client is gone: end of input
+                Err(_) => return Ok(0),
+            }
+        }
+    }
+}
+
+/// Send one message to the server
+fn write_message(input: &mpsc::Sender<Vec<u8>>, message: &impl Serialize) {
+    let mut line = serde_json::to_vec(message).expect("JSON-RPC messages serialize");
+    line.push(b'\n');
+    // A stopped server is reported when the response is awaited
+    let _ = input.send(line);
+}
+
+/// A request sent with [`TestClient::send`] whose response has not been read yet
+pub struct PendingCall {
+    id: RequestId,
+    inbox: Arc<Inbox>,
+}
+
+impl PendingCall {
+    /// ID of the request, as the handler's [`Context`] sees it
+    pub fn id(&self) -> &RequestId {
+        &self.id
+    }
+
+    /// Wait for the response
+    pub async fn result<R: DeserializeOwned>(self) -> Result<R, RpcError> {
+        let result = self.inbox.response(&self.id).await?;
+        serde_json::from_value(result).map_err(|e| RpcError::internal_error(e.to_string()))
+    }
+}
+
+impl TestClient {
+    /// Start `server` in-process and initialize it as the CLI would
+    ///
+    /// The client offers the `streaming` feature and no sandbox, like the CLI for a trusted
+    /// plugin; use [`start_with`](Self::start_with) to initialize with other params.
+    ///
+    /// # Errors
+    /// Returns error if the server was misconfigured or `initialize` failed.
+    pub async fn start(server: PluginServer) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::start_with(server, Self::initialize_params()).await
+    }
+
+    /// Start `server` in-process and initialize it with `params`
+    ///
+    /// # Errors
+    /// Returns error if the server was misconfigured or `initialize` failed.
+    pub async fn start_with(
+        mut server: PluginServer,
+        params: InitializeParams,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        server.check_build_errors()?;
+        let exclusive = EXCLUSIVE.lock().await;
+
+        let (input, messages) = mpsc::channel();
+        let inbox = Arc::new(Inbox::default());
+        let reader = BufReader::new(InputPipe {
+            messages,
+            current: std::io::Cursor::new(Vec::new()),
+        });
+        let writer = OutputPipe {
+            inbox: inbox.clone(),
+            buf: Vec::new(),
+        };
+        let closer = CloseOnDrop(inbox.clone());
+        // A thread and runtime of its own, as the plugin would have in its process. It keeps the
+        // next client waiting until the server has let go of the process-wide output.
+        let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+        std::thread::spawn(move || {
+            let _exclusive = exclusive;
+            let _closer = closer;
+            runtime.block_on(server.serve_connection(reader, Box::new(writer)));
+        });
+
+        // Sent ahead of the requests the client numbers from 1
+        let initialize = PendingCall {
+            id: RequestId::Number(0),
+            inbox: inbox.clone(),
+        };
+        let params = serde_json::to_value(params)?;
+        write_message(
+            &input,
+            &Request::new(methods::INITIALIZE, Some(params), initialize.id.clone()),
+        );
+        let info = initialize
+            .result()
+            .await
+            .map_err(|e| format!("Plugin failed to initialize: {}", e.message))?;
+
+        Ok(Self {
+            input,
+            inbox,
+            request_counter: AtomicI64::new(1),
+            info,
+        })
+    }
+
+    /// The `initialize` params [`start`](Self::start) sends
+    pub fn initialize_params() -> InitializeParams {
+        InitializeParams {
+            plugin_version: crate::PLUGIN_VERSION.to_string(),
+            protocol_version: PROTOCOL_VERSION.to_string(),
+            framings: Vec::new(),
+            auth_token: None,
+            config: None,
+            features: vec![features::STREAMING.to_string()],
+            inline_tensor_limit: None,
+            sandbox: None,
+        }
+    }
+
+    /// What the plugin answered to `initialize`
+    pub fn info(&self) -> &InitializeResult {
+        &self.info
+    }
+
+    fn next_id(&self) -> RequestId {
+        RequestId::Number(self.request_counter.fetch_add(1, Ordering::SeqCst))
+    }
+
+    /// Call `method` with typed params and result, e.g. `call::<RunParams, RunResult>("backend.run", params)`
+    pub async fn call<P, R>(&self, method: &str, params: P) -> Result<R, RpcError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        self.send(method, params).result().await
+    }
+
+    /// Call `method` with JSON params
+    pub async fn call_json(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+        self.send(method, params).result().await
+    }
+
+    /// Send the requests of a recorded exchange and compare the responses with the recording
+    ///
+    /// Record the exchange with `hodu trace record`, then check a new build of the plugin
+    /// against it. To initialize as the recording did, start the client with
+    /// [`Replay::initialize_params`]:
+    ///
+    /// ```ignore
+    /// let trace = Trace::load("tests/fixtures/run.trace")?;
+    /// let replay = Replay::new(trace.plugin("my-backend").unwrap());
+    /// let params = replay.initialize_params().cloned().unwrap_or_else(TestClient::initialize_params);
+    /// let client = TestClient::start_with(server, params).await?;
+    /// let report = client.replay(replay).await;
+    /// assert!(report.is_match(), "{:?}", report.differences);
+    /// ```
+    pub async fn replay(&self, mut replay: Replay) -> ReplayReport {
+        while let Some((method, params)) = replay.next_request() {
+            let result = self.call_json(&method, params.unwrap_or(serde_json::Value::Null)).await;
+            replay.check(result);
+        }
+        replay.finish()
+    }
+
+    /// Send a request without waiting for its response, e.g. to cancel it while it runs
+    ///
+    /// # Panics
+    /// Panics if `params` cannot be serialized.
+    pub fn send<P: Serialize>(&self, method: &str, params: P) -> PendingCall {
+        let params = serde_json::to_value(params).expect("params serialize to JSON");
+        let id = self.next_id();
+        write_message(&self.input, &Request::new(method, Some(params), id.clone()));
+        PendingCall {
+            id,
+            inbox: self.inbox.clone(),
+        }
+    }
+
+    /// Write `frame` to the server as is, e.g. a malformed message
+    ///
+    /// Frames are read a line at a time, so end `frame` with a newline.
+    pub fn send_raw(&self, frame: &[u8]) {
+        let _ = self.input.send(frame.to_vec());
+    }
+
+    /// Whether the server task has ended
+    fn stopped(&self) -> bool {
+        self.inbox.state.lock().unwrap_or_else(PoisonError::into_inner).closed
+    }
+
+    /// Cancel a pending request, as `$/cancel` from a user abort would
+    pub fn cancel(&self, call: &PendingCall) {
+        self.cancel_with(call, CancelReason::UserAbort);
+    }
+
+    /// Cancel a pending request for the given reason, e.g. to test timeout handling
+    pub fn cancel_with(&self, call: &PendingCall, reason: CancelReason) {
+        let params = CancelParams {
+            id: call.id.clone(),
+            reason: Some(reason),
+        };
+        let params = serde_json::to_value(params).expect("cancel params serialize to JSON");
+        // Sent as a request with an ID of its own, as the CLI does; it is not answered
+        write_message(
+            &self.input,
+            &Request::new(methods::CANCEL, Some(params), self.next_id()),
+        );
+    }
+
+    /// Let the `stream` of a pending request send `credits` more chunks, as the CLI does as it
+    /// consumes them
+    ///
+    /// Only streams of a client started with [`features::STREAM_CREDITS`] wait for credit.
+    pub fn grant_stream_credit(&self, call: &PendingCall, stream: &str, credits: u64) {
+        let params = StreamCreditParams {
+            request_id: call.id.clone(),
+            stream: stream.to_string(),
+            credits,
+        };
+        let params = serde_json::to_value(params).expect("stream credit params serialize to JSON");
+        // Sent as a request with an ID of its own, as the CLI does; it is not answered
+        write_message(
+            &self.input,
+            &Request::new(methods::STREAM_CREDIT, Some(params), self.next_id()),
+        );
+    }
+
+    /// Wait for a notification of `method`, e.g. `$/progress`, after those returned before
+    ///
+    /// Notifications received before the last one returned, or before
+    /// [`clear_notifications`](Self::clear_notifications), are not waited for again; clear them
+    /// before sending a request to wait for what it sends.
+    ///
+    /// # Panics
+    /// Panics if the server stops first.
+    pub async fn wait_for_notification(&self, method: &str) -> Notification {
+        let waited = self
+            .inbox
+            .wait_for(|state| {
+                let offset = state.notifications[state.waited..]
+                    .iter()
+                    .position(|n| n.method == method)?;
+                state.waited += offset + 1;
+                Some(state.notifications[state.waited - 1].clone())
+            })
+            .await;
+        waited.unwrap_or_else(|| panic!("Plugin server stopped before sending {}", method))
+    }
+
+    /// Notifications the plugin sent so far, oldest first
+    pub fn notifications(&self) -> Vec<Notification> {
+        self.inbox
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .notifications
+            .clone()
+    }
+
+    /// Forget the notifications received so far
+    pub fn clear_notifications(&self) {
+        self.inbox.update(|state| {
+            state.notifications.clear();
+            state.waited = 0;
+        });
+    }
+
+    /// `$/progress` notifications received so far
+    pub fn progress(&self) -> Vec<ProgressParams> {
+        notification_params(&self.notifications(), methods::NOTIFY_PROGRESS)
+    }
+
+    /// `$/log` notifications received so far
+    pub fn logs(&self) -> Vec<LogParams> {
+        notification_params(&self.notifications(), methods::NOTIFY_LOG)
+    }
+
+    /// Assert that a notification of `method` was received
+    pub fn assert_notified(&self, method: &str) {
+        let notifications = self.notifications();
+        assert!(
+            notifications.iter().any(|n| n.method == method),
+            "Expected a {} notification, got: {:?}",
+            method,
+            notifications.iter().map(|n| &n.method).collect::<Vec<_>>()
+        );
+    }
+
+    /// Assert that progress reached at least `percent`
+    pub fn assert_progress(&self, percent: u8) {
+        let progress = self.progress();
+        assert!(
+            progress.iter().any(|p| p.percent.is_some_and(|p| p >= percent)),
+            "Expected progress to reach {}%, got: {:?}",
+            percent,
+            progress.iter().map(|p| p.percent).collect::<Vec<_>>()
+        );
+    }
+
+    /// Assert that a `$/log` message with `level` containing `message_contains` was received
+    pub fn assert_logged(&self, level: &str, message_contains: &str) {
+        let logs = self.logs();
+        assert!(
+            logs.iter()
+                .any(|log| log.level == level && log.message.contains(message_contains)),
+            "Expected log with level '{}' containing '{}' not found. Logs: {:?}",
+            level,
+            message_contains,
+            logs.iter().map(|log| (&log.level, &log.message)).collect::<Vec<_>>()
+        );
+    }
+
+    /// Send `shutdown` and wait for the server to stop, running its shutdown callback
+    ///
+    /// # Errors
+    /// Returns the error `shutdown` was answered with.
+    pub async fn shutdown(self) -> Result<(), RpcError> {
+        let result = self.call_json(methods::SHUTDOWN, serde_json::Value::Null).await;
+        let Self { input, inbox, .. } = self;
+        // The server stops reading after `shutdown`; closing the input ends its reader
+        drop(input);
+        inbox.wait_for(|_| None::<()>).await;
+        result.map(|_| ())
+    }
+}
+
 // ============================================================================
 // Assertion Helpers
 // ============================================================================
@@ -399,6 +1059,8 @@ pub fn assert_error_contains(result: &Result<impl std::fmt::Debug, RpcError>, su
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rpc::INITIAL_STREAM_CREDITS;
+    use std::sync::atomic::{AtomicBool, Ordering};
 
     async fn echo_handler(_ctx: Context, params: String) -> Result<String, RpcError> {
         Ok(format!("Echo: {}", params))
@@ -411,6 +1073,75 @@ mod tests {
         assert_eq!(result.unwrap(), "Echo: hello");
     }
 
+    #[tokio::test]
+    async fn test_cancellation_hierarchy() {
+        let client = MockClient::new();
+        let (handle, future) = client.call_handler_cancellable(
+            |ctx: Context, _: ()| async move {
+                let sub_task = ctx.child_token();
+                let fired = Arc::new(AtomicBool::new(false));
+                let _guard = ctx.on_cancel({
+                    let fired = fired.clone();
+                    move || fired.store(true, Ordering::SeqCst)
+                });
+                let dropped = ctx.on_cancel(|| panic!("unregistered callback ran"));
+                drop(dropped);
+
+                // A cancelled child leaves the request running
+                let early = ctx.child_token();
+                early.cancel();
+                assert!(!ctx.is_cancelled());
+
+                ctx.cancelled().await;
+                assert!(sub_task.is_cancelled());
+                assert!(fired.load(Ordering::SeqCst));
+                Err::<(), _>(RpcError::cancelled())
+            },
+            (),
+        );
+        let (result, ()) = tokio::join!(future, async move {
+            tokio::task::yield_now().await;
+            handle.cancel();
+        });
+        assert_error_code(&result, crate::rpc::error_codes::REQUEST_CANCELLED);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_reason_and_cleanup() {
+        let cleaned = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let client = MockClient::new();
+        let (handle, future) = client.call_handler_cancellable(
+            {
+                let cleaned = cleaned.clone();
+                move |ctx: Context, _: ()| async move {
+                    for checkpoint in ["first", "second", "committed"] {
+                        let cleaned = cleaned.clone();
+                        let guard = ctx.cleanup_on_cancel(move |reason| {
+                            cleaned.lock().unwrap().push((checkpoint, reason));
+                        });
+                        if checkpoint == "committed" {
+                            guard.disarm();
+                        }
+                    }
+                    ctx.cancelled().await;
+                    assert_eq!(ctx.cancel_reason(), Some(CancelReason::Timeout));
+                    Err::<(), _>(RpcError::cancelled())
+                }
+            },
+            (),
+        );
+        handle.cancel_with(CancelReason::Timeout);
+        // A later cancellation does not replace the first reason
+        handle.cancel();
+        assert!(future.await.is_err());
+
+        // Newest first, and only those still armed
+        assert_eq!(
+            *cleaned.lock().unwrap(),
+            vec![("second", CancelReason::Timeout), ("first", CancelReason::Timeout)]
+        );
+    }
+
     #[tokio::test]
     async fn test_harness() {
         let harness = TestHarness::new().handler("test.echo", echo_handler);
@@ -419,6 +1150,654 @@ mod tests {
         assert_eq!(result, "Echo: world");
     }
 
+    #[derive(Default)]
+    struct EchoFormat;
+
+    #[crate::hodu_plugin(main = false)]
+    impl crate::FormatPlugin for EchoFormat {
+        const MODEL_EXTENSIONS: &'static [&'static str] = &["echo"];
+
+        async fn load_model(
+            &self,
+            _ctx: Context,
+            params: crate::rpc::LoadModelParams,
+        ) -> Result<crate::rpc::LoadModelResult, RpcError> {
+            Ok(crate::rpc::LoadModelResult {
+                snapshot_path: params.path,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trait_plugin() {
+        let harness = TestHarness::new().format(EchoFormat);
+        let params = serde_json::json!({ "path": "model.echo" });
+        let result = harness.call_json("format.load_model", params).await.unwrap();
+        assert_eq!(result["snapshot_path"], "model.echo");
+
+        // Methods the impl leaves out are not registered, so they are not capabilities either
+        let params = serde_json::json!({ "snapshot_path": "a.hdss", "output_path": "b.echo" });
+        let result = harness.call_json("format.save_model", params).await;
+        assert_error_code(&result, crate::rpc::error_codes::METHOD_NOT_FOUND);
+    }
+
+    #[derive(crate::PluginMethod)]
+    #[method(name = "format.load_model", validate)]
+    struct LoadModelHandler;
+
+    impl LoadModelHandler {
+        async fn handle(
+            _ctx: Context,
+            params: crate::rpc::LoadModelParams,
+        ) -> Result<crate::rpc::LoadModelResult, RpcError> {
+            Ok(crate::rpc::LoadModelResult {
+                snapshot_path: params.path,
+            })
+        }
+    }
+
+    #[derive(crate::PluginMethod)]
+    #[method("custom.echo")]
+    struct EchoHandler;
+
+    impl EchoHandler {
+        async fn handle(_ctx: Context, params: String) -> Result<String, RpcError> {
+            Ok(params)
+        }
+    }
+
+    #[derive(crate::PluginMethod)]
+    #[allow(dead_code)]
+    enum Methods {
+        LoadModel(LoadModelHandler),
+        Echo(EchoHandler),
+    }
+
+    #[tokio::test]
+    async fn test_derived_methods() {
+        use crate::PluginMethod;
+        assert_eq!(LoadModelHandler::METHOD_NAME, "format.load_model");
+        assert_eq!(Methods::METHOD_NAMES, &["format.load_model", "custom.echo"]);
+
+        let harness = TestHarness::new().methods::<Methods>();
+        let result: String = harness.call("custom.echo", "hi".to_string()).await.unwrap();
+        assert_eq!(result, "hi");
+        let params = serde_json::json!({ "path": "model.echo" });
+        let result = harness.call_json("format.load_model", params).await.unwrap();
+        assert_eq!(result["snapshot_path"], "model.echo");
+
+        // `validate` answers params failing validation before the handler runs
+        let params = serde_json::json!({ "path": "" });
+        let result = harness.call_json("format.load_model", params).await;
+        assert_error_code(&result, crate::rpc::error_codes::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_client_in_process() {
+        let server = PluginServer::new("test-plugin", "0.1.0")
+            .method("custom.echo", |ctx: Context, params: String| async move {
+                ctx.progress(Some(100), "echoed");
+                ctx.log("info", &format!("echoing {}", params));
+                Ok::<_, RpcError>(params)
+            })
+            .method("custom.wait", |ctx: Context, _: String| async move {
+                ctx.progress(Some(0), "waiting");
+                ctx.cancelled().await;
+                Err::<(), _>(RpcError::cancelled())
+            });
+        let client = TestClient::start(server).await.unwrap();
+        assert_eq!(client.info().name, "test-plugin");
+
+        let echoed = client
+            .call::<String, String>("custom.echo", "hi".to_string())
+            .await
+            .unwrap();
+        assert_eq!(echoed, "hi");
+        client.assert_progress(100);
+        client.assert_logged("info", "echoing hi");
+
+        // Cancel a request once it is running
+        client.clear_notifications();
+        let call = client.send("custom.wait", "forever");
+        client.wait_for_notification(methods::NOTIFY_PROGRESS).await;
+        client.cancel(&call);
+        assert_error_code(&call.result::<()>().await, crate::rpc::error_codes::REQUEST_CANCELLED);
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_hooks_stack_as_layers() {
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let push = |order: &Arc<std::sync::Mutex<Vec<String>>>, step: &str| {
+            order.lock().unwrap().push(step.to_string());
+        };
+        let (o1, o2, o3, o4, o5) = (
+            order.clone(),
+            order.clone(),
+            order.clone(),
+            order.clone(),
+            order.clone(),
+        );
+        let server = PluginServer::new("test-plugin", "0.1.0")
+            .on_request(move |_| {
+                push(&o1, "outer request");
+                crate::PreRequestAction::Continue
+            })
+            .layer(move |call, next| {
+                let order = o2.clone();
+                async move {
+                    push(&order, "layer before");
+                    let result = next.run(call).await;
+                    push(&order, "layer after");
+                    result
+                }
+            })
+            .on_response(move |resp| push(&o3, &format!("response ok={}", resp.success)))
+            .on_request(move |req| {
+                if req.params == Some(serde_json::json!("blocked")) {
+                    return crate::PreRequestAction::Reject(RpcError::invalid_request("blocked"));
+                }
+                push(&o4, "inner request");
+                crate::PreRequestAction::Continue
+            })
+            .method("custom.echo", move |_: Context, params: String| {
+                push(&o5, "handler");
+                async move { Ok::<_, RpcError>(params) }
+            });
+        let client = TestClient::start(server).await.unwrap();
+
+        // A second hook of the same kind is added inside the first instead of replacing it
+        let echoed = client.call::<_, String>("custom.echo", "hi").await.unwrap();
+        assert_eq!(echoed, "hi");
+        assert_eq!(
+            std::mem::take(&mut *order.lock().unwrap()),
+            [
+                "outer request",
+                "layer before",
+                "inner request",
+                "handler",
+                "response ok=true",
+                "layer after"
+            ]
+        );
+
+        // A rejecting hook skips the handler, and the layers outside it see the error
+        let result = client.call::<_, String>("custom.echo", "blocked").await;
+        assert_error_code(&result, crate::rpc::error_codes::INVALID_REQUEST);
+        assert_eq!(
+            std::mem::take(&mut *order.lock().unwrap()),
+            ["outer request", "layer before", "response ok=false", "layer after"]
+        );
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_answers_busy() {
+        let server = PluginServer::new("test-plugin", "0.1.0")
+            .method("custom.echo", |_: Context, params: String| async move {
+                Ok::<_, RpcError>(params)
+            })
+            .rate_limit("custom.echo", 2.0);
+        let client = TestClient::start(server).await.unwrap();
+
+        // A burst of one second's worth goes through, the next call is told when to retry
+        for _ in 0..2 {
+            client.call::<_, String>("custom.echo", "hi").await.unwrap();
+        }
+        let error = client.call::<_, String>("custom.echo", "hi").await.unwrap_err();
+        assert_eq!(error.code, crate::rpc::error_codes::BUSY);
+        let retry_after_ms = error.data.as_ref().and_then(|data| data["retry_after_ms"].as_u64());
+        assert!(retry_after_ms.is_some_and(|ms| ms > 0 && ms <= 500), "{:?}", error.data);
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrency_answers_busy() {
+        let server = PluginServer::new("test-plugin", "0.1.0")
+            .method("custom.wait", |ctx: Context, _: String| async move {
+                ctx.progress(Some(0), "waiting");
+                ctx.cancelled().await;
+                Err::<(), _>(RpcError::cancelled())
+            })
+            .max_concurrency("custom.wait", 2);
+        let client = TestClient::start(server).await.unwrap();
+
+        // Requests run alongside each other, up to the limit
+        let mut running = Vec::new();
+        for _ in 0..2 {
+            client.clear_notifications();
+            running.push(client.send("custom.wait", "forever"));
+            client.wait_for_notification(methods::NOTIFY_PROGRESS).await;
+        }
+        let error = client.call::<_, ()>("custom.wait", "forever").await.unwrap_err();
+        assert_eq!(error.code, crate::rpc::error_codes::BUSY);
+
+        // A finished call frees its slot
+        client.cancel(&running[0]);
+        assert_error_code(
+            &running.remove(0).result::<()>().await,
+            crate::rpc::error_codes::REQUEST_CANCELLED,
+        );
+        client.clear_notifications();
+        let call = client.send("custom.wait", "forever");
+        client.wait_for_notification(methods::NOTIFY_PROGRESS).await;
+        for call in [call, running.remove(0)] {
+            client.cancel(&call);
+            assert_error_code(&call.result::<()>().await, crate::rpc::error_codes::REQUEST_CANCELLED);
+        }
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stream_credits() {
+        let server =
+            PluginServer::new("test-plugin", "0.1.0").method("custom.count", |ctx: Context, n: u64| async move {
+                let mut numbers = ctx.stream::<u64>("numbers")?;
+                for i in 0..n {
+                    numbers.write(&i).await?;
+                }
+                numbers.close()?;
+                Ok::<_, RpcError>(n)
+            });
+        let mut params = TestClient::initialize_params();
+        params.features.push(features::STREAM_CREDITS.to_string());
+        let client = TestClient::start_with(server, params).await.unwrap();
+        let chunks = |client: &TestClient| {
+            client
+                .notifications()
+                .iter()
+                .filter(|n| n.method == methods::NOTIFY_STREAM)
+                .filter(|n| n.params.as_ref().is_some_and(|p| p["event"] == "chunk"))
+                .count() as u64
+        };
+
+        // The stream stops once it has used its initial credit
+        let call = client.send("custom.count", 20);
+        for _ in 0..=INITIAL_STREAM_CREDITS {
+            client.wait_for_notification(methods::NOTIFY_STREAM).await;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(chunks(&client), INITIAL_STREAM_CREDITS);
+
+        client.grant_stream_credit(&call, "numbers", 4);
+        assert_eq!(call.result::<u64>().await.unwrap(), 20);
+        assert_eq!(chunks(&client), 20);
+
+        // A stream waiting for credit is cancelled with its request
+        let call = client.send("custom.count", 20);
+        for _ in 0..=INITIAL_STREAM_CREDITS {
+            client.wait_for_notification(methods::NOTIFY_STREAM).await;
+        }
+        client.cancel(&call);
+        assert_error_code(&call.result::<u64>().await, crate::rpc::error_codes::REQUEST_CANCELLED);
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_with_state() {
+        struct Connections {
+            closed: Arc<AtomicBool>,
+        }
+
+        let closed = Arc::new(AtomicBool::new(false));
+        let server = PluginServer::new("test-plugin", "0.1.0")
+            .with_state(Connections {
+                closed: Arc::clone(&closed),
+            })
+            .on_shutdown_with_state(|connections: Arc<Connections>| async move {
+                tokio::task::yield_now().await;
+                connections.closed.store(true, Ordering::SeqCst);
+            });
+        let client = TestClient::start(server).await.unwrap();
+        client.shutdown().await.unwrap();
+        assert!(closed.load(Ordering::SeqCst));
+
+        // A callback that hangs does not keep the server from exiting
+        let server = PluginServer::new("test-plugin", "0.1.0")
+            .shutdown_callback_timeout(std::time::Duration::from_millis(10))
+            .on_shutdown_async(std::future::pending);
+        let client = TestClient::start(server).await.unwrap();
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_client_replay() {
+        let recording = [
+            r#"{"connection":"test-plugin#1.1","direction":"to_plugin","elapsed_ms":0,"message":{"jsonrpc":"2.0","method":"initialize","params":{"plugin_version":"0.1.0","protocol_version":"1.0.0"},"id":1}}"#,
+            r#"{"connection":"test-plugin#1.1","direction":"from_plugin","elapsed_ms":1,"message":{"jsonrpc":"2.0","result":{"name":"test-plugin"},"id":1}}"#,
+            r#"{"connection":"test-plugin#1.1","direction":"to_plugin","elapsed_ms":2,"message":{"jsonrpc":"2.0","method":"custom.double","params":2,"id":2}}"#,
+            r#"{"connection":"test-plugin#1.1","direction":"from_plugin","elapsed_ms":3,"message":{"jsonrpc":"2.0","result":4,"id":2}}"#,
+            r#"{"connection":"test-plugin#1.1","direction":"to_plugin","elapsed_ms":4,"message":{"jsonrpc":"2.0","method":"custom.double","params":3,"id":3}}"#,
+            r#"{"connection":"test-plugin#1.1","direction":"from_plugin","elapsed_ms":5,"message":{"jsonrpc":"2.0","result":6,"id":3}}"#,
+        ]
+        .join("\n");
+        let trace = Trace::read(recording.as_bytes()).unwrap();
+        let replay = Replay::new(trace.plugin("test-plugin").unwrap());
+        assert_eq!(replay.remaining(), 2);
+        let params = replay.initialize_params().cloned().unwrap();
+
+        // A build that got 3 * 2 wrong
+        let server = PluginServer::new("test-plugin", "0.1.0")
+            .method("custom.double", |_: Context, n: i64| async move {
+                Ok::<_, RpcError>(if n == 3 { 7 } else { n * 2 })
+            });
+        let client = TestClient::start_with(server, params).await.unwrap();
+        let report = client.replay(replay).await;
+        assert_eq!(report.replayed, 2);
+        assert_eq!(report.differences.len(), 1);
+        assert_eq!(report.differences[0].actual, serde_json::json!(7));
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fuzzing() {
+        use fuzz::Fuzzer;
+
+        let mut fuzzer = Fuzzer::new(1).max_numel(64);
+        for _ in 0..64 {
+            assert!(fuzzer.tensor_data().is_valid());
+            let tensor = fuzzer.tensor().unwrap();
+            assert_eq!(
+                tensor.to_bytes().unwrap().len(),
+                tensor.shape().size() * tensor.dtype().size_in_bytes()
+            );
+            assert!(fuzzer.malformed_frame().ends_with(b"\n"));
+        }
+
+        #[derive(Serialize, serde::Deserialize)]
+        struct Params {
+            path: String,
+            count: u32,
+        }
+        let valid = || Params {
+            path: "model.onnx".to_string(),
+            count: 2,
+        };
+        let report = fuzz::fuzz_handler(
+            |_: Context, params: Params| async move {
+                if params.path.is_empty() {
+                    return Err(RpcError::invalid_params("empty path"));
+                }
+                Ok::<_, RpcError>(params.count)
+            },
+            valid(),
+        )
+        .await;
+        report.assert_passed();
+        assert!(report.accepted > 0 && report.rejected > 0);
+
+        // A handler indexing on an unchecked count
+        let report =
+            Fuzzer::new(2)
+                .iterations(128)
+                .fuzz_handler(
+                    |_: Context, params: Params| async move {
+                        Ok::<_, RpcError>(params.path.as_bytes()[params.count as usize])
+                    },
+                    valid(),
+                )
+                .await;
+        assert!(!report.passed());
+        assert!(report.failures[0].message.starts_with("panicked"));
+
+        let server = PluginServer::new("test-plugin", "0.1.0")
+            .method("custom.echo", |_: Context, params: String| async move {
+                Ok::<_, RpcError>(params)
+            });
+        let client = TestClient::start(server).await.unwrap();
+        Fuzzer::new(3).iterations(64).fuzz_frames(&client).await.assert_passed();
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_context() {
+        struct Counter(std::sync::atomic::AtomicU32);
+
+        async fn count(ctx: Context, steps: u32) -> Result<u32, RpcError> {
+            let counter = ctx.state::<Counter>().unwrap();
+            for step in 1..=steps {
+                if ctx.is_cancelled() {
+                    ctx.log_warn("stopped early");
+                    return Err(RpcError::cancelled());
+                }
+                counter.0.fetch_add(1, Ordering::SeqCst);
+                ctx.progress(Some((step * 100 / steps) as u8), "counting");
+            }
+            Ok(counter.0.load(Ordering::SeqCst))
+        }
+
+        let mock = MockContext::new().with_state(Counter(std::sync::atomic::AtomicU32::new(0)));
+        assert_eq!(count(mock.context(), 4).await.unwrap(), 4);
+        let percents: Vec<_> = mock.progress().iter().map(|p| p.percent).collect();
+        assert_eq!(percents, [Some(25), Some(50), Some(75), Some(100)]);
+        assert!(mock.logs().is_empty());
+
+        mock.clear_notifications();
+        mock.cancel();
+        assert_error_code(
+            &count(mock.context(), 4).await,
+            crate::rpc::error_codes::REQUEST_CANCELLED,
+        );
+        assert_eq!(mock.state::<Counter>().unwrap().0.load(Ordering::SeqCst), 4);
+        assert_eq!(mock.logs()[0].message, "stopped early");
+        assert_eq!(mock.notifications().len(), 1);
+    }
+
+    /// A backend interpreting the snapshot, its outputs shifted by `skew`
+    fn interpreter_backend(skew: f32) -> PluginServer {
+        use crate::rpc::{BuildParams, RunParams, RunResult};
+        use crate::{hdss, load_input, Tensor, TensorData, TensorDataExt};
+
+        PluginServer::new("interpreter", "0.1.0")
+            .devices(vec!["cpu"])
+            .method("backend.build", |_ctx: Context, params: BuildParams| async move {
+                std::fs::copy(&params.snapshot_path, &params.output_path)
+                    .map_err(|e| RpcError::internal_error(e.to_string()))?;
+                Ok::<_, RpcError>(serde_json::json!({}))
+            })
+            .method("backend.run", move |ctx: Context, params: RunParams| async move {
+                let fail = |e: hodu_core::error::HoduError| RpcError::internal_error(e.to_string());
+                let snapshot = hdss::load(&params.library_path).map_err(fail)?;
+                let mut inputs = Vec::new();
+                for input in &params.inputs {
+                    let data = load_input(input)?;
+                    let dtype = data.core_dtype().map_err(|e| RpcError::tensor_error(e.to_string()))?;
+                    let tensor = Tensor::from_bytes(&data.data, data.shape.clone(), dtype, crate::CoreDevice::CPU)
+                        .map_err(fail)?;
+                    inputs.push((input.name.clone(), tensor));
+                }
+                let refs: Vec<_> = inputs.iter().map(|(name, tensor)| (name.as_str(), tensor)).collect();
+                let outputs = crate::snapshot::Interpreter::new(&snapshot).run(&refs).map_err(fail)?;
+                let mut result = RunResult { outputs: Vec::new() };
+                for (name, tensor) in outputs {
+                    let tensor = tensor.add_scalar(skew).map_err(fail)?;
+                    let data = TensorData::from_core_dtype(
+                        tensor.to_bytes().map_err(fail)?,
+                        tensor.shape().to_vec(),
+                        tensor.dtype(),
+                    );
+                    result.outputs.push(ctx.output(&name, &data)?);
+                }
+                Ok::<_, RpcError>(result)
+            })
+    }
+
+    #[tokio::test]
+    async fn test_conformance_suite() {
+        use crate::Tensor;
+        use conformance::{allclose, ConformanceSuite, Mismatch, Tolerance};
+
+        let expected = Tensor::from_slice(vec![1.0f32, 100.0, 0.0], [3]).unwrap();
+        let near = Tensor::from_slice(vec![1.0f32 + f32::EPSILON, 100.005, 0.0], [3]).unwrap();
+        assert!(allclose(&near, &expected, Tolerance::default()).is_ok());
+        assert!(allclose(&near, &expected, Tolerance::EXACT.ulps(1)).is_err());
+        assert!(allclose(&near, &expected, Tolerance::EXACT.rel(1e-4).ulps(1)).is_ok());
+        assert!(matches!(
+            allclose(&near, &expected, Tolerance::EXACT),
+            Err(Mismatch::Values {
+                index: 0,
+                count: 2,
+                total: 3,
+                ..
+            })
+        ));
+        let reshaped = expected.reshape([1, 3]).unwrap();
+        assert!(matches!(
+            allclose(&reshaped, &expected, Tolerance::default()),
+            Err(Mismatch::Shape { .. })
+        ));
+
+        // Golden files round-trip
+        let dir = std::env::temp_dir().join(format!("hodu_conformance_test_{}", std::process::id()));
+        let suite = ConformanceSuite::new();
+        suite.write_fixtures(&dir).unwrap();
+        let golden = ConformanceSuite::load(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(golden.cases().len(), suite.cases().len());
+
+        let client = TestClient::start(interpreter_backend(0.0)).await.unwrap();
+        golden.run(&client).await.assert_passed();
+        client.shutdown().await.unwrap();
+
+        let client = TestClient::start(interpreter_backend(1e-2)).await.unwrap();
+        let report = suite.run(&client).await;
+        assert!(!report.passed());
+        assert_eq!(report.failures().count(), suite.cases().len());
+        let looser = suite.tolerance(Tolerance::EXACT.abs(2e-2)).run(&client).await;
+        looser.assert_passed();
+        client.shutdown().await.unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_builder() {
+        use crate::snapshot::Interpreter;
+        use crate::{DType, Tensor};
+
+        let bias = Tensor::from_slice(vec![0.5f32, -1.0, 2.0], [3]).unwrap();
+        let mut builder = SnapshotBuilder::new("linear");
+        let x = builder.input("x", [2, 4], DType::F32);
+        let w = builder.input("w", [4, 3], DType::F32);
+        let b = builder.constant("b", &bias).unwrap();
+        let y = builder.matmul(x, w).unwrap();
+        let y = builder.add(y, b).unwrap();
+        let y = builder.relu(y).unwrap();
+        let total = builder.sum(y, &[1], true).unwrap();
+        let flat = builder.reshape(total, [2]).unwrap();
+        builder.output("y", y).output("total", flat);
+        assert_eq!(builder.shape(total).dims(), [2, 1]);
+        assert!(builder.matmul(x, x).is_err());
+        assert!(builder.add(x, w).is_err());
+        let snapshot = builder.build();
+
+        // Deterministic, and runnable like a captured snapshot
+        let x = Tensor::from_slice(vec![1.0f32, -2.0, 3.0, 0.5, -1.0, 4.0, 2.0, -3.0], [2, 4]).unwrap();
+        let w = Tensor::from_slice((0..12).map(|i| i as f32 / 6.0 - 1.0).collect::<Vec<_>>(), [4, 3]).unwrap();
+        let outputs = Interpreter::new(&snapshot).run(&[("x", &x), ("w", &w)]).unwrap();
+        let expected = x.matmul(&w).unwrap().add(&bias).unwrap().relu().unwrap();
+        assert_eq!(outputs[0].0, "y");
+        assert_eq!(
+            outputs[0].1.to_flatten_vec::<f32>().unwrap(),
+            expected.to_flatten_vec::<f32>().unwrap()
+        );
+        assert_eq!(
+            outputs[1].1.to_flatten_vec::<f32>().unwrap(),
+            expected.sum(&[1], false).unwrap().to_flatten_vec::<f32>().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_rejected_as_a_whole() {
+        #[derive(Clone, Default)]
+        struct Output(Arc<std::sync::Mutex<Vec<u8>>>);
+
+        impl Write for Output {
+            fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(data);
+                Ok(data.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let _exclusive = EXCLUSIVE.lock().await;
+        let output = Output::default();
+        let mut server = PluginServer::new("test-plugin", "0.1.0");
+        let input = std::io::Cursor::new(b"[]\n[{\"jsonrpc\": \"2.0\",\n".to_vec());
+        server.serve_connection(input, Box::new(output.clone())).await;
+
+        // One error object each, as JSON-RPC 2.0 requires, not an array holding it
+        let written = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let codes: Vec<i32> = written
+            .lines()
+            .map(|line| serde_json::from_str::<Response>(line).unwrap().error.unwrap().code)
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                crate::rpc::error_codes::INVALID_REQUEST,
+                crate::rpc::error_codes::PARSE_ERROR
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_daemon_drops_client_that_does_not_initialize() {
+        use std::io::BufRead;
+        use std::os::unix::net::UnixStream;
+        use std::time::Duration;
+
+        // The daemon takes over the process-wide output, as a client would
+        let _exclusive = EXCLUSIVE.lock().await;
+        let path = std::env::temp_dir().join(format!("hodu-sdk-silent-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let server = PluginServer::new("daemon", "0.1.0")
+            .initialize_timeout(Duration::from_millis(200))
+            .idle_timeout(Duration::from_secs(1));
+        let daemon = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap();
+                runtime.block_on(server.listen_unix(path)).unwrap();
+            })
+        };
+        while !path.exists() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        // Connects first and never sends anything, not even a token
+        let mut silent = UnixStream::connect(&path).unwrap();
+        silent.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut client = UnixStream::connect(&path).unwrap();
+        client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let params = serde_json::to_value(TestClient::initialize_params()).unwrap();
+        let mut line =
+            serde_json::to_vec(&Request::new(methods::INITIALIZE, Some(params), RequestId::Number(0))).unwrap();
+        line.push(b'\n');
+        client.write_all(&line).unwrap();
+
+        let mut response = String::new();
+        BufReader::new(&client).read_line(&mut response).unwrap();
+        let response: Response = serde_json::from_str(&response).unwrap();
+        assert!(response.error.is_none(), "initialize failed: {:?}", response.error);
+        // The silent client was disconnected to get there
+        assert_eq!(silent.read(&mut [0u8; 1]).unwrap(), 0);
+
+        drop(client);
+        daemon.join().unwrap();
+        assert!(!path.exists());
+    }
+
     #[tokio::test]
     async fn test_harness_method_not_found() {
         let harness = TestHarness::new();
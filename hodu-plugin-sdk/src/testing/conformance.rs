@@ -0,0 +1,683 @@
+//! Golden-file conformance suite for backend plugins
+//!
+//! Every backend should compute the same outputs for the same snapshot. The suite ships a set of
+//! prebuilt snapshots covering the common op families, with inputs and expected outputs computed
+//! by the reference interpreter, and runs each through a plugin the way the CLI does:
+//! `backend.build`, then `backend.run`, then a tolerance-aware comparison of every output.
+//!
+//! ```ignore
+//! use hodu_plugin_sdk::testing::conformance::ConformanceSuite;
+//! use hodu_plugin_sdk::testing::TestClient;
+//!
+//! #[tokio::test]
+//! async fn conformance() {
+//!     let client = TestClient::start(my_backend_server()).await.unwrap();
+//!     ConformanceSuite::new().device("cpu").run(&client).await.assert_passed();
+//! }
+//! ```
+//!
+//! The fixtures can also be written out with [`ConformanceSuite::write_fixtures`] and checked in,
+//! so CI compares against golden files instead of regenerating them:
+//!
+//! ```text
+//! <dir>/<case>/model.hdss
+//! <dir>/<case>/inputs/<name>.hdt
+//! <dir>/<case>/expected/<name>.hdt
+//! ```
+
+use super::TestClient;
+use crate::rpc::{methods, BuildParams, RunParams, RunResult, TensorInput, TensorOutput};
+use crate::{current_host_triple, CoreDevice, DType, Shape, Snapshot, Tensor, TensorDataExt};
+use hodu_core::error::{HoduError, HoduResult};
+use hodu_core::format::{hdss, hdt};
+use hodu_core::snapshot::{CaptureBoard, Interpreter};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// ============================================================================
+// Tolerance
+// ============================================================================
+
+/// How far an output element may be from its expected value
+///
+/// An element matches if it is within `abs + rel * |expected|` of the expected value, or within
+/// `ulps` units in the last place of it in the tensor's dtype. NaN matches NaN and infinities
+/// must match exactly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Absolute difference allowed
+    pub abs: f64,
+    /// Difference allowed relative to the expected value
+    pub rel: f64,
+    /// Units in the last place allowed
+    pub ulps: u64,
+}
+
+impl Tolerance {
+    /// No difference allowed
+    pub const EXACT: Self = Self {
+        abs: 0.0,
+        rel: 0.0,
+        ulps: 0,
+    };
+
+    /// The tolerance the suite uses for outputs of `dtype`, unless told otherwise
+    ///
+    /// Loose enough for a backend that reorders reductions or fuses multiply-adds; integer and
+    /// bool outputs must match exactly.
+    pub fn for_dtype(dtype: DType) -> Self {
+        let (abs, rel, ulps) = match dtype {
+            DType::F8E4M3 => (1e-1, 1.25e-1, 2),
+            DType::F8E5M2 => (1e-1, 2.5e-1, 2),
+            DType::BF16 => (1e-2, 1.6e-2, 2),
+            DType::F16 => (1e-3, 2e-3, 4),
+            DType::F32 => (1e-5, 1e-4, 8),
+            DType::F64 => (1e-12, 1e-10, 8),
+            _ => return Self::EXACT,
+        };
+        Self { abs, rel, ulps }
+    }
+
+    /// With `abs` as the absolute difference allowed
+    pub fn abs(mut self, abs: f64) -> Self {
+        self.abs = abs;
+        self
+    }
+
+    /// With `rel` as the difference allowed relative to the expected value
+    pub fn rel(mut self, rel: f64) -> Self {
+        self.rel = rel;
+        self
+    }
+
+    /// With `ulps` as the units in the last place allowed
+    pub fn ulps(mut self, ulps: u64) -> Self {
+        self.ulps = ulps;
+        self
+    }
+
+    fn accepts(&self, actual: f64, expected: f64, dtype: DType) -> bool {
+        if actual.is_nan() || expected.is_nan() {
+            return actual.is_nan() && expected.is_nan();
+        }
+        if actual.is_infinite() || expected.is_infinite() {
+            return actual == expected;
+        }
+        let diff = (actual - expected).abs();
+        diff <= self.abs + self.rel * expected.abs() || diff <= self.ulps as f64 * ulp(expected, dtype)
+    }
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self::for_dtype(DType::F32)
+    }
+}
+
+/// The spacing of `dtype` values around `x`
+fn ulp(x: f64, dtype: DType) -> f64 {
+    // Mantissa bits and smallest normal exponent
+    let (mantissa, min_exponent) = match dtype {
+        DType::F8E4M3 => (3, -6),
+        DType::F8E5M2 => (2, -14),
+        DType::BF16 => (7, -126),
+        DType::F16 => (10, -14),
+        DType::F32 => (23, -126),
+        DType::F64 => (52, -1022),
+        _ => return 1.0,
+    };
+    let exponent = if x == 0.0 {
+        min_exponent
+    } else {
+        (x.abs().log2().floor() as i32).max(min_exponent)
+    };
+    2f64.powi(exponent - mantissa)
+}
+
+// ============================================================================
+// Comparison
+// ============================================================================
+
+/// Why an output did not match its expected value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mismatch {
+    /// The output has the wrong shape
+    Shape { expected: Vec<usize>, actual: Vec<usize> },
+    /// The output has the wrong dtype
+    DType { expected: DType, actual: DType },
+    /// Elements differ by more than the tolerance allows
+    Values {
+        /// Flat index of the first differing element
+        index: usize,
+        expected: f64,
+        actual: f64,
+        /// How many elements differ
+        count: usize,
+        /// Elements compared
+        total: usize,
+    },
+    /// The output could not be read
+    Unreadable(String),
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shape { expected, actual } => write!(f, "expected shape {:?}, got {:?}", expected, actual),
+            Self::DType { expected, actual } => write!(f, "expected dtype {}, got {}", expected, actual),
+            Self::Values {
+                index,
+                expected,
+                actual,
+                count,
+                total,
+            } => write!(
+                f,
+                "{} of {} elements differ, first at index {}: expected {}, got {}",
+                count, total, index, expected, actual
+            ),
+            Self::Unreadable(message) => write!(f, "unreadable output: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// Check that `actual` matches `expected` in shape, dtype and every element, within `tolerance`
+///
+/// # Errors
+/// Returns the first way the tensors differ; for elements, the first differing element and how
+/// many differ in all.
+pub fn allclose(actual: &Tensor, expected: &Tensor, tolerance: Tolerance) -> Result<(), Mismatch> {
+    if actual.shape() != expected.shape() {
+        return Err(Mismatch::Shape {
+            expected: expected.shape().to_vec(),
+            actual: actual.shape().to_vec(),
+        });
+    }
+    let dtype = expected.dtype();
+    if actual.dtype() != dtype {
+        return Err(Mismatch::DType {
+            expected: dtype,
+            actual: actual.dtype(),
+        });
+    }
+
+    let values = |tensor: &Tensor| {
+        tensor
+            .to_dtype(DType::F64)
+            .and_then(|t| t.to_flatten_vec::<f64>())
+            .map_err(|e| Mismatch::Unreadable(e.to_string()))
+    };
+    let (actual, expected) = (values(actual)?, values(expected)?);
+
+    let mut first = None;
+    let mut count = 0;
+    for (index, (&a, &e)) in actual.iter().zip(&expected).enumerate() {
+        if !tolerance.accepts(a, e, dtype) {
+            first.get_or_insert((index, e, a));
+            count += 1;
+        }
+    }
+    match first {
+        None => Ok(()),
+        Some((index, expected_value, actual_value)) => Err(Mismatch::Values {
+            index,
+            expected: expected_value,
+            actual: actual_value,
+            count,
+            total: expected.len(),
+        }),
+    }
+}
+
+/// Assert that `actual` matches `expected` within `tolerance`
+///
+/// # Panics
+/// Panics describing the mismatch if they differ.
+pub fn assert_allclose(actual: &Tensor, expected: &Tensor, tolerance: Tolerance) {
+    if let Err(mismatch) = allclose(actual, expected, tolerance) {
+        panic!("Tensors differ: {}", mismatch);
+    }
+}
+
+// ============================================================================
+// Cases
+// ============================================================================
+
+/// A snapshot with inputs and the outputs a conforming backend produces from them
+#[derive(Debug, Clone)]
+pub struct ConformanceCase {
+    /// Case name, also its fixture directory name
+    pub name: String,
+    /// The model to build and run
+    pub snapshot: Snapshot,
+    /// Inputs, by snapshot input name
+    pub inputs: Vec<(String, Tensor)>,
+    /// Expected outputs, by snapshot target name
+    pub expected: Vec<(String, Tensor)>,
+    /// Tolerance for this case's outputs, instead of the suite's
+    pub tolerance: Option<Tolerance>,
+}
+
+impl ConformanceCase {
+    /// A case whose expected outputs are computed from `inputs` by the reference interpreter
+    ///
+    /// # Errors
+    /// Returns error if the interpreter cannot run the snapshot on the inputs.
+    pub fn new(name: impl Into<String>, snapshot: Snapshot, inputs: Vec<(String, Tensor)>) -> HoduResult<Self> {
+        let refs: Vec<_> = inputs.iter().map(|(name, tensor)| (name.as_str(), tensor)).collect();
+        let expected = Interpreter::new(&snapshot).run(&refs)?;
+        Ok(Self {
+            name: name.into(),
+            snapshot,
+            inputs,
+            expected,
+            tolerance: None,
+        })
+    }
+
+    /// With `tolerance` for this case's outputs
+    pub fn tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Write the case's fixtures to `dir`
+    ///
+    /// # Errors
+    /// Returns error if a file cannot be written.
+    pub fn write(&self, dir: impl AsRef<Path>) -> HoduResult<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir.join("inputs"))?;
+        std::fs::create_dir_all(dir.join("expected"))?;
+        hdss::save(&self.snapshot, dir.join("model.hdss"))?;
+        for (name, tensor) in &self.inputs {
+            hdt::save(tensor, input_path(dir, name))?;
+        }
+        for (name, tensor) in &self.expected {
+            hdt::save(tensor, dir.join("expected").join(format!("{}.hdt", name)))?;
+        }
+        Ok(())
+    }
+
+    /// Load a case from fixtures written by [`write`](Self::write), named after `dir`
+    ///
+    /// # Errors
+    /// Returns error if the snapshot, or a file for one of its inputs or targets, is missing or
+    /// cannot be read.
+    pub fn load(dir: impl AsRef<Path>) -> HoduResult<Self> {
+        let dir = dir.as_ref();
+        let name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let snapshot = hdss::load(dir.join("model.hdss"))?;
+        let inputs = snapshot
+            .inputs
+            .iter()
+            .map(|input| Ok((input.name.clone(), hdt::load(input_path(dir, &input.name))?)))
+            .collect::<HoduResult<_>>()?;
+        let expected = snapshot
+            .targets
+            .iter()
+            .map(|target| {
+                let path = dir.join("expected").join(format!("{}.hdt", target.name));
+                Ok((target.name.clone(), hdt::load(path)?))
+            })
+            .collect::<HoduResult<_>>()?;
+        Ok(Self {
+            name,
+            snapshot,
+            inputs,
+            expected,
+            tolerance: None,
+        })
+    }
+}
+
+fn input_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join("inputs").join(format!("{}.hdt", name))
+}
+
+/// The prebuilt cases, covering elementwise, broadcasting, matmul, reduction and normalization
+///
+/// Inputs are deterministic, so fixtures written on any machine are the same.
+pub fn builtin_cases() -> HoduResult<Vec<ConformanceCase>> {
+    let f32 = DType::F32;
+    Ok(vec![
+        case("add", &[("x", &[2, 3]), ("y", &[2, 3])], f32, |t| {
+            Ok(vec![("out", t[0].add(&t[1])?)])
+        })?,
+        case("broadcast_mul", &[("x", &[4, 3]), ("y", &[3])], f32, |t| {
+            Ok(vec![("out", t[0].mul(&t[1])?)])
+        })?,
+        case("unary", &[("x", &[16])], f32, |t| {
+            Ok(vec![
+                ("relu", t[0].relu()?),
+                ("exp", t[0].exp()?),
+                ("tanh", t[0].tanh()?),
+            ])
+        })?,
+        case("matmul", &[("a", &[4, 8]), ("b", &[8, 5])], f32, |t| {
+            Ok(vec![("out", t[0].matmul(&t[1])?)])
+        })?,
+        case("reduce", &[("x", &[3, 7])], f32, |t| {
+            Ok(vec![
+                ("sum", t[0].sum(&[1], false)?),
+                ("mean", t[0].mean(&[0], true)?),
+                ("max", t[0].max(&[1], false)?),
+            ])
+        })?,
+        case("softmax", &[("x", &[2, 10])], f32, |t| {
+            Ok(vec![("out", t[0].softmax(1)?)])
+        })?,
+        case(
+            "mlp",
+            &[("x", &[2, 6]), ("w1", &[6, 8]), ("b1", &[8]), ("w2", &[8, 3])],
+            f32,
+            |t| {
+                let hidden = t[0].matmul(&t[1])?.add(&t[2])?.relu()?;
+                Ok(vec![("out", hidden.matmul(&t[3])?.softmax(1)?)])
+            },
+        )?,
+    ])
+}
+
+/// Capture `build` over inputs of the given names and shapes into a case
+fn case(
+    name: &str,
+    inputs: &[(&str, &[usize])],
+    dtype: DType,
+    build: impl FnOnce(&[Tensor]) -> HoduResult<Vec<(&'static str, Tensor)>>,
+) -> HoduResult<ConformanceCase> {
+    let board = CaptureBoard::with_name(name);
+    board.open();
+    let placeholders = inputs
+        .iter()
+        .map(|(input, shape)| Tensor::input(input, *shape, dtype))
+        .collect::<HoduResult<Vec<_>>>();
+    let targets = placeholders.and_then(|placeholders| build(&placeholders));
+    board.close();
+    for (target, tensor) in targets? {
+        board.with_target(target, tensor);
+    }
+    let snapshot = board.capture();
+
+    let values = inputs
+        .iter()
+        .enumerate()
+        .map(|(i, (input, shape))| Ok((input.to_string(), fixture_tensor(shape, i)?.to_dtype(dtype)?)))
+        .collect::<HoduResult<_>>()?;
+    ConformanceCase::new(name, snapshot, values)
+}
+
+/// Values spread over [-2, 2], different for each `seed`
+fn fixture_tensor(shape: &[usize], seed: usize) -> HoduResult<Tensor> {
+    let size = shape.iter().product::<usize>();
+    let values: Vec<f32> = (0..size)
+        .map(|i| {
+            let step = (i * 37 + seed * 101 + 13) % 257;
+            step as f32 / 64.0 - 2.0
+        })
+        .collect();
+    Tensor::from_slice(values, shape)
+}
+
+// ============================================================================
+// Suite
+// ============================================================================
+
+/// Conformance cases, run against a backend plugin through a [`TestClient`]
+///
+/// Each case is built for the host with `backend.build` and run with `backend.run`, its inputs
+/// passed as .hdt files, and every output compared with [`allclose`].
+pub struct ConformanceSuite {
+    cases: Vec<ConformanceCase>,
+    device: String,
+    tolerance: Option<Tolerance>,
+    work_dir: Option<PathBuf>,
+}
+
+impl ConformanceSuite {
+    /// The [`builtin_cases`], run on the CPU
+    ///
+    /// # Panics
+    /// Panics if the reference interpreter cannot run a builtin case, which is a bug in hodu.
+    pub fn new() -> Self {
+        let cases = builtin_cases().unwrap_or_else(|e| panic!("Failed to build conformance cases: {}", e));
+        Self::with_cases(cases)
+    }
+
+    /// A suite of `cases`, run on the CPU
+    pub fn with_cases(cases: Vec<ConformanceCase>) -> Self {
+        Self {
+            cases,
+            device: "cpu".to_string(),
+            tolerance: None,
+            work_dir: None,
+        }
+    }
+
+    /// A suite of the golden fixtures in `dir`, one case per subdirectory, in name order
+    ///
+    /// # Errors
+    /// Returns error if `dir` or a case in it cannot be read.
+    pub fn load(dir: impl AsRef<Path>) -> HoduResult<Self> {
+        let mut dirs = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        dirs.retain(|path| path.is_dir());
+        dirs.sort();
+        let cases = dirs.iter().map(ConformanceCase::load).collect::<HoduResult<_>>()?;
+        Ok(Self::with_cases(cases))
+    }
+
+    /// With `case` run after the others
+    pub fn case(mut self, case: ConformanceCase) -> Self {
+        self.cases.push(case);
+        self
+    }
+
+    /// Run on `device` instead of the CPU
+    pub fn device(mut self, device: impl Into<String>) -> Self {
+        self.device = device.into();
+        self
+    }
+
+    /// Compare every output with `tolerance` instead of [`Tolerance::for_dtype`]
+    ///
+    /// A case's own tolerance still takes precedence.
+    pub fn tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Write fixtures and build artifacts under `dir` instead of a fresh temp directory
+    ///
+    /// Useful when the plugin's sandbox only allows certain paths.
+    pub fn work_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.work_dir = Some(dir.into());
+        self
+    }
+
+    /// The suite's cases
+    pub fn cases(&self) -> &[ConformanceCase] {
+        &self.cases
+    }
+
+    /// Write every case's fixtures to a subdirectory of `dir`, for checking in as golden files
+    ///
+    /// # Errors
+    /// Returns error if a file cannot be written.
+    pub fn write_fixtures(&self, dir: impl AsRef<Path>) -> HoduResult<()> {
+        let dir = dir.as_ref();
+        self.cases.iter().try_for_each(|case| case.write(dir.join(&case.name)))
+    }
+
+    /// Build and run every case with the plugin behind `client`, comparing its outputs
+    ///
+    /// A work directory the suite created is removed afterwards.
+    pub async fn run(&self, client: &TestClient) -> ConformanceReport {
+        static RUNS: AtomicU64 = AtomicU64::new(0);
+        let (work_dir, temporary) = match &self.work_dir {
+            Some(dir) => (dir.clone(), false),
+            None => {
+                let run = RUNS.fetch_add(1, Ordering::Relaxed);
+                let dir = std::env::temp_dir().join(format!("hodu_conformance_{}_{}", std::process::id(), run));
+                (dir, true)
+            },
+        };
+
+        let mut results = Vec::with_capacity(self.cases.len());
+        for case in &self.cases {
+            let outcome = self.run_case(client, case, &work_dir.join(&case.name)).await;
+            results.push(CaseResult {
+                name: case.name.clone(),
+                outcome,
+            });
+        }
+        if temporary {
+            let _ = std::fs::remove_dir_all(&work_dir);
+        }
+        ConformanceReport { results }
+    }
+
+    async fn run_case(&self, client: &TestClient, case: &ConformanceCase, dir: &Path) -> Result<(), String> {
+        case.write(dir)
+            .map_err(|e| format!("Failed to write fixtures: {}", e))?;
+        let snapshot_path = path_string(&dir.join("model.hdss"));
+        let library_path = path_string(&dir.join(format!("model.{}", std::env::consts::DLL_EXTENSION)));
+
+        client
+            .call::<_, serde_json::Value>(
+                methods::BACKEND_BUILD,
+                BuildParams {
+                    snapshot_path: snapshot_path.clone(),
+                    target: current_host_triple().to_string(),
+                    device: self.device.clone(),
+                    format: "sharedlib".to_string(),
+                    output_path: library_path.clone(),
+                },
+            )
+            .await
+            .map_err(|e| format!("backend.build failed: {}", e.message))?;
+
+        let inputs = case
+            .inputs
+            .iter()
+            .map(|(name, _)| TensorInput::new(name, path_string(&input_path(dir, name))))
+            .collect();
+        let result = client
+            .call::<_, RunResult>(
+                methods::BACKEND_RUN,
+                RunParams {
+                    library_path,
+                    snapshot_path,
+                    device: self.device.clone(),
+                    inputs,
+                    precision: None,
+                },
+            )
+            .await
+            .map_err(|e| format!("backend.run failed: {}", e.message))?;
+
+        let mut failures = Vec::new();
+        for (name, expected) in &case.expected {
+            let Some(output) = result.outputs.iter().find(|output| &output.name == name) else {
+                failures.push(format!("output '{}' missing", name));
+                continue;
+            };
+            let tolerance = case
+                .tolerance
+                .or(self.tolerance)
+                .unwrap_or_else(|| Tolerance::for_dtype(expected.dtype()));
+            let compared = read_output(output).and_then(|actual| allclose(&actual, expected, tolerance));
+            if let Err(mismatch) = compared {
+                failures.push(format!("output '{}': {}", name, mismatch));
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures.join("; "))
+        }
+    }
+}
+
+impl Default for ConformanceSuite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Read an output tensor, whether it was sent inline or as a file
+fn read_output(output: &TensorOutput) -> Result<Tensor, Mismatch> {
+    let tensor = match &output.data {
+        Some(inline) => inline
+            .decode()
+            .map_err(|e| HoduError::InternalError(e.to_string()))
+            .and_then(|data| {
+                let dtype = data.core_dtype().map_err(|e| HoduError::InternalError(e.to_string()))?;
+                Tensor::from_bytes(&data.data, Shape::new(&data.shape), dtype, CoreDevice::CPU)
+            }),
+        None => hdt::load(&output.path),
+    };
+    tensor.map_err(|e| Mismatch::Unreadable(e.to_string()))
+}
+
+// ============================================================================
+// Report
+// ============================================================================
+
+/// How one case went
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    /// Case name
+    pub name: String,
+    /// `Err` describing every way the case failed
+    pub outcome: Result<(), String>,
+}
+
+/// How every case of a [`ConformanceSuite`] run went
+#[derive(Debug, Clone)]
+pub struct ConformanceReport {
+    /// Results, in case order
+    pub results: Vec<CaseResult>,
+}
+
+impl ConformanceReport {
+    /// Whether every case passed
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|result| result.outcome.is_ok())
+    }
+
+    /// The cases that failed
+    pub fn failures(&self) -> impl Iterator<Item = &CaseResult> {
+        self.results.iter().filter(|result| result.outcome.is_err())
+    }
+
+    /// Assert that every case passed
+    ///
+    /// # Panics
+    /// Panics listing every failed case and why.
+    pub fn assert_passed(&self) {
+        let failures: Vec<_> = self
+            .failures()
+            .map(|result| format!("  {}: {}", result.name, result.outcome.as_ref().unwrap_err()))
+            .collect();
+        if !failures.is_empty() {
+            panic!(
+                "{} of {} conformance cases failed:\n{}",
+                failures.len(),
+                self.results.len(),
+                failures.join("\n")
+            );
+        }
+    }
+}
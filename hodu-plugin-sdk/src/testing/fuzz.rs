@@ -0,0 +1,609 @@
+//! Property-based fuzzing for plugin handlers and message parsing
+//!
+//! A [`Fuzzer`] generates inputs a plugin should survive: arbitrary but valid [`TensorData`]
+//! and strided [`Tensor`] views, malformed JSON-RPC frames, and adversarial variations of a
+//! handler's params. [`fuzz_handler`] feeds a handler hundreds of such params and reports every
+//! panic or hang; [`Fuzzer::fuzz_frames`] does the same for a whole server's message loop:
+//!
+//! ```ignore
+//! use hodu_plugin_sdk::testing::fuzz::{fuzz_handler, Fuzzer};
+//!
+//! #[tokio::test]
+//! async fn survives_adversarial_params() {
+//!     let valid = LoadModelParams { path: "model.onnx".into(), ..Default::default() };
+//!     fuzz_handler(load_model, valid).await.assert_passed();
+//!
+//!     let client = TestClient::start(server()).await.unwrap();
+//!     Fuzzer::new(7).fuzz_frames(&client).await.assert_passed();
+//! }
+//! ```
+//!
+//! Runs are seeded, so a failure reproduces with the seed printed in the report.
+
+use super::TestClient;
+use crate::context::Context;
+use crate::rpc::{error_codes, methods, RequestId, RpcError};
+use crate::{plugin_dtype_to_core, CoreDevice, PluginDType, Tensor, TensorData};
+use hodu_core::error::HoduResult;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
+use std::time::Duration;
+
+/// Seed [`fuzz_handler`] runs with
+pub const DEFAULT_SEED: u64 = 0x686f_6475;
+
+/// Inputs each fuzzing run tries, unless set with [`Fuzzer::iterations`]
+pub const DEFAULT_ITERATIONS: usize = 256;
+
+/// Every dtype a plugin may be handed
+const ALL_DTYPES: &[PluginDType] = &[
+    PluginDType::BOOL,
+    PluginDType::F8E4M3,
+    PluginDType::F8E5M2,
+    PluginDType::BF16,
+    PluginDType::F16,
+    PluginDType::F32,
+    PluginDType::F64,
+    PluginDType::U8,
+    PluginDType::U16,
+    PluginDType::U32,
+    PluginDType::U64,
+    PluginDType::I8,
+    PluginDType::I16,
+    PluginDType::I32,
+    PluginDType::I64,
+];
+
+/// Strings parsers tend to mishandle
+const ADVERSARIAL_STRINGS: &[&str] = &[
+    "",
+    " ",
+    "../../../../etc/passwd",
+    "/dev/zero",
+    "C:\\Windows\\System32",
+    "model\0.onnx",
+    "\u{202e}xnno.ledom",
+    "🦀🦀🦀",
+    "%s%n%x",
+    "NaN",
+    "-1",
+    "\n\r\t",
+];
+
+/// Numbers at the edges of what params hold
+const ADVERSARIAL_NUMBERS: &[f64] = &[
+    0.0,
+    -1.0,
+    -0.0,
+    0.5,
+    255.0,
+    65536.0,
+    2147483648.0,
+    4294967296.0,
+    9.007_199_254_740_993e15,
+    1e300,
+    -1e300,
+];
+
+/// A seeded generator of inputs a plugin should handle without panicking or hanging
+pub struct Fuzzer {
+    seed: u64,
+    rng: SmallRng,
+    iterations: usize,
+    max_rank: usize,
+    max_numel: usize,
+    dtypes: Vec<PluginDType>,
+    timeout: Duration,
+}
+
+impl Fuzzer {
+    /// A fuzzer generating the same inputs for the same `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+            iterations: DEFAULT_ITERATIONS,
+            max_rank: 4,
+            max_numel: 4096,
+            dtypes: ALL_DTYPES.to_vec(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Inputs each fuzzing run tries
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Highest rank of generated tensors
+    pub fn max_rank(mut self, max_rank: usize) -> Self {
+        self.max_rank = max_rank;
+        self
+    }
+
+    /// Most elements in a generated tensor
+    pub fn max_numel(mut self, max_numel: usize) -> Self {
+        self.max_numel = max_numel.max(1);
+        self
+    }
+
+    /// Dtypes of generated tensors, all of them by default
+    ///
+    /// # Panics
+    /// Panics if `dtypes` is empty.
+    pub fn dtypes(mut self, dtypes: &[PluginDType]) -> Self {
+        assert!(!dtypes.is_empty(), "fuzzing needs at least one dtype");
+        self.dtypes = dtypes.to_vec();
+        self
+    }
+
+    /// How long one input may take before the run counts as hung
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Seed the fuzzer was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // ========================================================================
+    // Tensors
+    // ========================================================================
+
+    /// A random shape of at most `max_rank` dims and `max_numel` elements, without zero dims
+    pub fn shape(&mut self) -> Vec<usize> {
+        let rank = self.rng.random_range(0..=self.max_rank);
+        let mut shape = Vec::with_capacity(rank);
+        let mut numel = 1;
+        for _ in 0..rank {
+            // Mostly small dims, with an occasional long one
+            let dim = if self.rng.random_bool(0.1) {
+                self.rng.random_range(1..=self.max_numel)
+            } else {
+                self.rng.random_range(1..=8)
+            };
+            let dim = dim.min(self.max_numel / numel).max(1);
+            numel *= dim;
+            shape.push(dim);
+        }
+        shape
+    }
+
+    /// One of the configured dtypes
+    pub fn dtype(&mut self) -> PluginDType {
+        self.dtypes[self.rng.random_range(0..self.dtypes.len())]
+    }
+
+    /// Valid tensor data of a random shape and dtype
+    ///
+    /// Float values are mostly small and finite, sprinkled with NaN, infinities, negative zero
+    /// and extremes; integers take any bit pattern and bools are 0 or 1.
+    pub fn tensor_data(&mut self) -> TensorData {
+        let shape = self.shape();
+        let dtype = self.dtype();
+        let numel = shape.iter().product();
+        TensorData::new(self.values(numel, dtype), shape, dtype)
+    }
+
+    /// A tensor of a random shape and dtype viewing its storage through a strided layout
+    ///
+    /// The view is sliced with a step, transposed or broadcast, so kernels reading it see
+    /// non-contiguous strides, zero strides and offsets.
+    pub fn tensor(&mut self) -> HoduResult<Tensor> {
+        let data = self.tensor_data();
+        let dtype = plugin_dtype_to_core(data.dtype).expect("every dtype exists in hodu_core");
+        let mut tensor = Tensor::from_bytes(&data.data, data.shape.clone(), dtype, CoreDevice::CPU)?;
+        let rank = data.shape.len();
+        if rank == 0 {
+            return Ok(tensor);
+        }
+
+        // Slicing makes a contiguous tensor strided, so it goes before the other views
+        let dim = self.rng.random_range(0..rank);
+        if data.shape[dim] > 1 && self.rng.random_bool(0.5) {
+            let start = self.rng.random_range(0..data.shape[dim] as i32 - 1);
+            let step = self.rng.random_range(1..=3);
+            tensor = tensor.slice(dim as u32, start, None, step)?;
+        }
+        if rank > 1 && self.rng.random_bool(0.5) {
+            let mut axes: Vec<u32> = (0..rank as u32).collect();
+            for i in (1..rank).rev() {
+                axes.swap(i, self.rng.random_range(0..=i));
+            }
+            tensor = tensor.permute(&axes)?;
+        }
+        if self.rng.random_bool(0.25) {
+            let mut shape = tensor.shape().dims().to_vec();
+            shape.insert(0, self.rng.random_range(2..=3));
+            tensor = tensor.broadcast(shape)?;
+        }
+        Ok(tensor)
+    }
+
+    fn values(&mut self, numel: usize, dtype: PluginDType) -> Vec<u8> {
+        match dtype {
+            PluginDType::BOOL => (0..numel).map(|_| self.rng.random_bool(0.5) as u8).collect(),
+            PluginDType::F32 => (0..numel).flat_map(|_| (self.float() as f32).to_le_bytes()).collect(),
+            PluginDType::F64 => (0..numel).flat_map(|_| self.float().to_le_bytes()).collect(),
+            dtype if dtype.is_float() => {
+                // Narrow floats round from f32, which saturates the extremes as a cast would
+                let values: Vec<f32> = (0..numel).map(|_| self.float() as f32).collect();
+                let dtype = plugin_dtype_to_core(dtype).expect("float dtypes exist in hodu_core");
+                Tensor::from_slice(values, vec![numel])
+                    .and_then(|t| t.to_dtype(dtype))
+                    .and_then(|t| t.to_bytes())
+                    .expect("f32 values cast to every float dtype")
+            },
+            dtype => {
+                let mut bytes = vec![0u8; numel * dtype.size_in_bytes()];
+                self.rng.fill(&mut bytes[..]);
+                bytes
+            },
+        }
+    }
+
+    fn float(&mut self) -> f64 {
+        if self.rng.random_bool(0.05) {
+            const SPECIAL: &[f64] = &[
+                f64::NAN,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                -0.0,
+                f64::MIN_POSITIVE,
+                65504.0,
+                -1e30,
+            ];
+            SPECIAL[self.rng.random_range(0..SPECIAL.len())]
+        } else {
+            self.rng.random_range(-4.0..4.0)
+        }
+    }
+
+    // ========================================================================
+    // Messages
+    // ========================================================================
+
+    /// A malformed JSON-RPC message on a line of its own
+    ///
+    /// Every frame is a blank line or starts with `{` or `[`, so it stays a line-delimited
+    /// message: a line announcing a Content-Length body would make the server wait for the body.
+    /// Frames never carry a valid `shutdown` or `exit`, and the IDs they carry are strings the
+    /// [`TestClient`] never uses, so the exchange can continue after each one.
+    pub fn malformed_frame(&mut self) -> Vec<u8> {
+        let id = json!(format!("fuzz-{}", self.rng.random::<u32>()));
+        let method = self.method();
+        let mut frame = match self.rng.random_range(0..14) {
+            0 => Vec::new(),
+            1 => b"{".to_vec(),
+            2 => {
+                let text = json!({"jsonrpc": "2.0", "method": method, "params": {}, "id": id}).to_string();
+                let cut = self.rng.random_range(1..text.len());
+                text.as_bytes()[..cut].to_vec()
+            },
+            3 => vec![b'{', 0xff, 0xfe, 0xc3, 0x28, b'}'],
+            4 => json!({"jsonrpc": "1.0", "method": method, "id": id})
+                .to_string()
+                .into_bytes(),
+            5 => json!({"jsonrpc": "2.0", "id": id}).to_string().into_bytes(),
+            6 => json!({"jsonrpc": "2.0", "method": 42, "id": id})
+                .to_string()
+                .into_bytes(),
+            7 => json!({"jsonrpc": "2.0", "method": method, "id": {"nested": [id]}})
+                .to_string()
+                .into_bytes(),
+            8 => json!({"jsonrpc": "2.0", "method": method, "params": self.junk(), "id": id})
+                .to_string()
+                .into_bytes(),
+            9 => json!([1, "two", null]).to_string().into_bytes(),
+            10 => {
+                let depth = self.rng.random_range(64..4096);
+                let mut text = "[".repeat(depth);
+                text.push_str(&"]".repeat(depth));
+                text.into_bytes()
+            },
+            11 => format!(r#"{{"jsonrpc": "2.0", "method": "{}", "id": {}}} trailing"#, method, id).into_bytes(),
+            12 => json!({"jsonrpc": "2.0", "method": methods::CANCEL, "params": {"id": self.junk()}})
+                .to_string()
+                .into_bytes(),
+            _ => {
+                let mut bytes = vec![0u8; self.rng.random_range(1..256)];
+                self.rng.fill(&mut bytes[..]);
+                bytes.retain(|&b| b != b'\n');
+                bytes.insert(0, b'{');
+                bytes
+            },
+        };
+        frame.push(b'\n');
+        frame
+    }
+
+    /// A method to address malformed frames to: one the protocol defines, or an unknown one
+    fn method(&mut self) -> &'static str {
+        const TARGETS: &[&str] = &[
+            methods::FORMAT_LOAD_MODEL,
+            methods::FORMAT_LOAD_TENSOR,
+            methods::BACKEND_RUN,
+            methods::BACKEND_BUILD,
+            methods::BACKEND_LOAD_SESSION,
+            methods::BACKEND_RUN_SESSION,
+            "fuzz.unknown",
+            "",
+        ];
+        TARGETS[self.rng.random_range(0..TARGETS.len())]
+    }
+
+    /// A JSON value of a random type
+    fn junk(&mut self) -> Value {
+        match self.rng.random_range(0..7) {
+            0 => Value::Null,
+            1 => json!(self.rng.random_bool(0.5)),
+            2 => json!(ADVERSARIAL_NUMBERS[self.rng.random_range(0..ADVERSARIAL_NUMBERS.len())]),
+            3 => json!(self.rng.random::<i64>()),
+            4 => json!(self.string()),
+            5 => json!([self.string(), -1, null]),
+            _ => json!({"": null, "__proto__": {}, "x": self.string()}),
+        }
+    }
+
+    fn string(&mut self) -> String {
+        if self.rng.random_bool(0.1) {
+            "A".repeat(self.rng.random_range(1024..65536))
+        } else {
+            ADVERSARIAL_STRINGS[self.rng.random_range(0..ADVERSARIAL_STRINGS.len())].to_string()
+        }
+    }
+
+    /// `valid` params with one to three fields removed, retyped or pushed to an extreme
+    pub fn adversarial_params(&mut self, valid: &Value) -> Value {
+        let mut params = valid.clone();
+        for _ in 0..self.rng.random_range(1..=3) {
+            self.mutate(&mut params);
+        }
+        params
+    }
+
+    /// Mutate one value somewhere within `value`
+    fn mutate(&mut self, value: &mut Value) {
+        // Descend into a random field or element most of the time
+        let descend = self.rng.random_bool(0.75);
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                let key = map
+                    .keys()
+                    .nth(self.rng.random_range(0..map.len()))
+                    .cloned()
+                    .unwrap_or_default();
+                if descend {
+                    return self.mutate(map.get_mut(&key).expect("key was just picked"));
+                }
+                match self.rng.random_range(0..3) {
+                    0 => {
+                        map.remove(&key);
+                    },
+                    1 => {
+                        map.insert(format!("{}_unexpected", key), self.junk());
+                    },
+                    _ => *value = self.junk(),
+                }
+            },
+            Value::Array(items) if !items.is_empty() => {
+                let index = self.rng.random_range(0..items.len());
+                if descend {
+                    return self.mutate(&mut items[index]);
+                }
+                match self.rng.random_range(0..3) {
+                    0 => items.clear(),
+                    1 => {
+                        let item = items[index].clone();
+                        let copies = self.rng.random_range(2..2048);
+                        items.extend(std::iter::repeat_n(item, copies));
+                    },
+                    _ => items[index] = self.junk(),
+                }
+            },
+            Value::String(_) if self.rng.random_bool(0.7) => *value = json!(self.string()),
+            Value::Number(_) if self.rng.random_bool(0.7) => {
+                *value = json!(ADVERSARIAL_NUMBERS[self.rng.random_range(0..ADVERSARIAL_NUMBERS.len())])
+            },
+            _ => *value = self.junk(),
+        }
+    }
+
+    // ========================================================================
+    // Runs
+    // ========================================================================
+
+    /// Call `handler` with adversarial variations of `valid`, reporting panics and hangs
+    ///
+    /// Params that no longer deserialize count as rejected, as the server would answer them with
+    /// `INVALID_PARAMS` before the handler runs. Errors the handler returns are fine.
+    ///
+    /// # Panics
+    /// Panics if `valid` cannot be serialized.
+    pub async fn fuzz_handler<F, Fut, P, R>(&mut self, handler: F, valid: P) -> FuzzReport
+    where
+        F: Fn(Context, P) -> Fut,
+        Fut: Future<Output = Result<R, RpcError>>,
+        P: Serialize + DeserializeOwned,
+        R: Serialize,
+    {
+        let valid = serde_json::to_value(valid).expect("params serialize to JSON");
+        let mut report = FuzzReport::new(self.seed);
+        for i in 0..self.iterations {
+            // The unchanged params first, so a handler failing on valid input stands out
+            let params = if i == 0 {
+                valid.clone()
+            } else {
+                self.adversarial_params(&valid)
+            };
+            report.runs += 1;
+            let typed: P = match serde_json::from_value(params.clone()) {
+                Ok(typed) => typed,
+                Err(_) => {
+                    report.rejected += 1;
+                    continue;
+                },
+            };
+
+            let ctx = Context::new(RequestId::Number(i as i64 + 1));
+            let call = CatchUnwind(Box::pin(handler(ctx, typed)));
+            match tokio::time::timeout(self.timeout, call).await {
+                Err(_) => report.fail(params.to_string(), format!("no answer within {:?}", self.timeout)),
+                Ok(Err(panic)) => report.fail(params.to_string(), format!("panicked: {}", panic)),
+                Ok(Ok(Ok(result))) => match serde_json::to_value(result) {
+                    Ok(_) => report.accepted += 1,
+                    Err(e) => report.fail(params.to_string(), format!("result does not serialize: {}", e)),
+                },
+                Ok(Ok(Err(error))) => *report.errors.entry(error.code).or_default() += 1,
+            }
+        }
+        report
+    }
+
+    /// Send malformed frames to the server behind `client`, checking it answers after each one
+    ///
+    /// A server survives a frame when it still answers a request sent after it. Frames that
+    /// stop the server, or leave it unresponsive, are reported.
+    pub async fn fuzz_frames(&mut self, client: &TestClient) -> FuzzReport {
+        let mut report = FuzzReport::new(self.seed);
+        for _ in 0..self.iterations {
+            if client.stopped() {
+                break;
+            }
+            let frame = self.malformed_frame();
+            report.runs += 1;
+            client.send_raw(&frame);
+
+            let probe = client.call_json("fuzz.probe", Value::Null);
+            let input = String::from_utf8_lossy(&frame).trim_end().chars().take(256).collect();
+            match tokio::time::timeout(self.timeout, probe).await {
+                Err(_) => report.fail(input, format!("no answer within {:?}", self.timeout)),
+                Ok(Err(e)) if e.code == error_codes::METHOD_NOT_FOUND => report.rejected += 1,
+                Ok(Err(e)) => report.fail(input, format!("server stopped answering: {}", e.message)),
+                Ok(Ok(_)) => report.accepted += 1,
+            }
+        }
+        report
+    }
+}
+
+/// Fuzz `handler` with [`DEFAULT_ITERATIONS`] adversarial variations of `valid` params
+///
+/// Shorthand for [`Fuzzer::fuzz_handler`] with [`DEFAULT_SEED`].
+pub async fn fuzz_handler<F, Fut, P, R>(handler: F, valid: P) -> FuzzReport
+where
+    F: Fn(Context, P) -> Fut,
+    Fut: Future<Output = Result<R, RpcError>>,
+    P: Serialize + DeserializeOwned,
+    R: Serialize,
+{
+    Fuzzer::new(DEFAULT_SEED).fuzz_handler(handler, valid).await
+}
+
+/// A future that resolves to the panic message if polling it panics
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let inner = self.0.as_mut();
+        match panic::catch_unwind(AssertUnwindSafe(|| inner.poll(cx))) {
+            Ok(poll) => poll.map(Ok),
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "non-string panic payload".to_string());
+                Poll::Ready(Err(message))
+            },
+        }
+    }
+}
+
+/// An input that made the plugin panic or hang
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzFailure {
+    /// The params or frame, as sent
+    pub input: String,
+    /// What went wrong
+    pub message: String,
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Long inputs are mostly repetition
+        let input: String = self.input.chars().take(512).collect();
+        write!(f, "{} for input {}", self.message, input)
+    }
+}
+
+/// How a fuzzing run went
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzReport {
+    /// Seed of the run, to reproduce it with [`Fuzzer::new`]
+    pub seed: u64,
+    /// Inputs tried
+    pub runs: usize,
+    /// Inputs answered with a result
+    pub accepted: usize,
+    /// Inputs rejected before a handler ran
+    pub rejected: usize,
+    /// Errors handlers returned, counted by code
+    pub errors: BTreeMap<i32, usize>,
+    /// Inputs that made the plugin panic or hang
+    pub failures: Vec<FuzzFailure>,
+}
+
+impl FuzzReport {
+    fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            runs: 0,
+            accepted: 0,
+            rejected: 0,
+            errors: BTreeMap::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    fn fail(&mut self, input: String, message: String) {
+        self.failures.push(FuzzFailure { input, message });
+    }
+
+    /// Whether every input was answered without a panic or hang
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Panic listing the failures, if any
+    ///
+    /// # Panics
+    /// Panics if an input made the plugin panic or hang.
+    pub fn assert_passed(&self) {
+        if self.passed() {
+            return;
+        }
+        let failures: Vec<String> = self.failures.iter().map(|f| format!("  {}", f)).collect();
+        panic!(
+            "{} of {} fuzzed inputs failed (seed {}):\n{}",
+            self.failures.len(),
+            self.runs,
+            self.seed,
+            failures.join("\n")
+        );
+    }
+}
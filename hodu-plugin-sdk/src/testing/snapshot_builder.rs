@@ -0,0 +1,428 @@
+//! Snapshots built node by node, without capture mode
+
+use crate::op_params::{BinaryParams, DotParams, MatmulParams, OpParams, ReduceParams, UnaryParams};
+use crate::ops::{BinaryOp, MatrixOp, Op, ReduceOp, ShapeOp, UnaryOp};
+use crate::snapshot::{SnapshotConstant, SnapshotInput, SnapshotTarget, SnapshotTensorId};
+use crate::{DType, Layout, Scalar, Shape, Snapshot, SnapshotNode, Tensor};
+use hodu_core::error::{HoduError, HoduResult};
+use std::collections::BTreeMap;
+
+/// Builds a [`Snapshot`] from inputs, constants and ops, without running capture mode
+///
+/// Every value is a [`SnapshotTensorId`], numbered in the order it was added, so the same calls
+/// always build the same snapshot. Output shapes are inferred the way capture would record them,
+/// including broadcasting the operands of binary ops, which makes the result runnable by the
+/// reference interpreter as well as by a backend under test.
+///
+/// # Example
+///
+/// ```ignore
+/// use hodu_plugin_sdk::testing::SnapshotBuilder;
+/// use hodu_plugin_sdk::DType;
+///
+/// let mut builder = SnapshotBuilder::new("linear");
+/// let x = builder.input("x", [2, 4], DType::F32);
+/// let w = builder.input("w", [4, 3], DType::F32);
+/// let b = builder.constant("b", &Tensor::zeros([3], DType::F32)?)?;
+/// let y = builder.matmul(x, w)?;
+/// let y = builder.add(y, b)?;
+/// let y = builder.relu(y)?;
+/// builder.output("y", y);
+/// let snapshot = builder.build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SnapshotBuilder {
+    snapshot: Snapshot,
+    /// Layout and dtype of every value, by id
+    values: Vec<(Layout, DType)>,
+}
+
+impl SnapshotBuilder {
+    /// An empty snapshot named `name`
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            snapshot: Snapshot::with_name(name),
+            values: Vec::new(),
+        }
+    }
+
+    /// Add a model input
+    pub fn input(&mut self, name: impl Into<String>, shape: impl Into<Shape>, dtype: DType) -> SnapshotTensorId {
+        let shape = shape.into();
+        let id = self.value(Layout::from_shape(&shape), dtype);
+        self.snapshot.inputs.push(SnapshotInput {
+            name: name.into(),
+            id,
+            shape,
+            dtype,
+        });
+        id
+    }
+
+    /// Add a constant holding `tensor`'s data
+    ///
+    /// # Errors
+    /// Returns error if the tensor's data cannot be read.
+    pub fn constant(&mut self, name: impl Into<String>, tensor: &Tensor) -> HoduResult<SnapshotTensorId> {
+        let shape = tensor.shape();
+        let dtype = tensor.dtype();
+        let data = tensor.to_bytes()?;
+        let id = self.value(Layout::from_shape(&shape), dtype);
+        self.snapshot.constants.push(SnapshotConstant {
+            id,
+            name: Some(name.into()),
+            shape,
+            dtype,
+            data,
+        });
+        Ok(id)
+    }
+
+    /// Mark `value` as a model output
+    pub fn output(&mut self, name: impl Into<String>, value: SnapshotTensorId) -> &mut Self {
+        self.snapshot.targets.push(SnapshotTarget {
+            name: name.into(),
+            id: value,
+        });
+        self
+    }
+
+    /// Set model-level metadata
+    pub fn metadata(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.snapshot.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Shape of `value`
+    ///
+    /// # Panics
+    /// Panics if `value` was not added by this builder.
+    pub fn shape(&self, value: SnapshotTensorId) -> &Shape {
+        self.values[value.0].0.shape()
+    }
+
+    /// Dtype of `value`
+    ///
+    /// # Panics
+    /// Panics if `value` was not added by this builder.
+    pub fn dtype(&self, value: SnapshotTensorId) -> DType {
+        self.values[value.0].1
+    }
+
+    /// The snapshot built so far
+    pub fn build(self) -> Snapshot {
+        self.snapshot
+    }
+
+    /// Apply `op` to `lhs` and `rhs`, broadcasting them to a common shape first
+    ///
+    /// # Errors
+    /// Returns error if the operands have different dtypes or shapes that do not broadcast.
+    pub fn binary(
+        &mut self,
+        op: BinaryOp,
+        lhs: SnapshotTensorId,
+        rhs: SnapshotTensorId,
+    ) -> HoduResult<SnapshotTensorId> {
+        let (lhs_layout, dtype) = self.layout_of(lhs)?;
+        let (rhs_layout, rhs_dtype) = self.layout_of(rhs)?;
+        if dtype != rhs_dtype {
+            return Err(HoduError::DTypeConflictInOp {
+                left: dtype,
+                right: rhs_dtype,
+                op: Op::Binary(op),
+            });
+        }
+        let shape = Shape::broadcast_shape(lhs_layout.shape(), rhs_layout.shape()).ok_or_else(|| {
+            HoduError::InvalidArgument(format!(
+                "cannot broadcast {:?} and {:?} for {}",
+                lhs_layout.shape(),
+                rhs_layout.shape(),
+                op
+            ))
+        })?;
+        let lhs = self.broadcast(lhs, &shape)?;
+        let rhs = self.broadcast(rhs, &shape)?;
+        let input_layouts = vec![self.values[lhs.0].0.clone(), self.values[rhs.0].0.clone()];
+        Ok(self.push(
+            Op::Binary(op),
+            Some(OpParams::Binary(BinaryParams)),
+            vec![lhs, rhs],
+            input_layouts,
+            &shape,
+            dtype,
+        ))
+    }
+
+    /// `lhs + rhs`, broadcasting
+    pub fn add(&mut self, lhs: SnapshotTensorId, rhs: SnapshotTensorId) -> HoduResult<SnapshotTensorId> {
+        self.binary(BinaryOp::Add, lhs, rhs)
+    }
+
+    /// `lhs - rhs`, broadcasting
+    pub fn sub(&mut self, lhs: SnapshotTensorId, rhs: SnapshotTensorId) -> HoduResult<SnapshotTensorId> {
+        self.binary(BinaryOp::Sub, lhs, rhs)
+    }
+
+    /// `lhs * rhs`, broadcasting
+    pub fn mul(&mut self, lhs: SnapshotTensorId, rhs: SnapshotTensorId) -> HoduResult<SnapshotTensorId> {
+        self.binary(BinaryOp::Mul, lhs, rhs)
+    }
+
+    /// `lhs / rhs`, broadcasting
+    pub fn div(&mut self, lhs: SnapshotTensorId, rhs: SnapshotTensorId) -> HoduResult<SnapshotTensorId> {
+        self.binary(BinaryOp::Div, lhs, rhs)
+    }
+
+    /// Apply `op` elementwise
+    ///
+    /// # Errors
+    /// Returns error if `value` was not added by this builder.
+    pub fn unary(&mut self, op: UnaryOp, value: SnapshotTensorId) -> HoduResult<SnapshotTensorId> {
+        let (layout, dtype) = self.layout_of(value)?;
+        let shape = layout.shape().clone();
+        Ok(self.push(
+            Op::Unary(op),
+            Some(OpParams::Unary(UnaryParams)),
+            vec![value],
+            vec![layout],
+            &shape,
+            dtype,
+        ))
+    }
+
+    /// `max(value, 0)`
+    pub fn relu(&mut self, value: SnapshotTensorId) -> HoduResult<SnapshotTensorId> {
+        self.unary(UnaryOp::Relu, value)
+    }
+
+    /// `e^value`
+    pub fn exp(&mut self, value: SnapshotTensorId) -> HoduResult<SnapshotTensorId> {
+        self.unary(UnaryOp::Exp, value)
+    }
+
+    /// `tanh(value)`
+    pub fn tanh(&mut self, value: SnapshotTensorId) -> HoduResult<SnapshotTensorId> {
+        self.unary(UnaryOp::Tanh, value)
+    }
+
+    /// `-value`
+    pub fn neg(&mut self, value: SnapshotTensorId) -> HoduResult<SnapshotTensorId> {
+        self.unary(UnaryOp::Neg, value)
+    }
+
+    /// Matrix product over the last two dimensions, broadcasting any leading batch dimensions
+    ///
+    /// # Errors
+    /// Returns error if either operand has fewer than two dimensions, the inner dimensions
+    /// differ, or the batch dimensions do not broadcast.
+    pub fn matmul(&mut self, lhs: SnapshotTensorId, rhs: SnapshotTensorId) -> HoduResult<SnapshotTensorId> {
+        let (lhs_layout, dtype) = self.layout_of(lhs)?;
+        let (rhs_layout, _) = self.layout_of(rhs)?;
+        let (l, r) = (lhs_layout.shape().dims(), rhs_layout.shape().dims());
+        if l.len() < 2 || r.len() < 2 || l[l.len() - 1] != r[r.len() - 2] {
+            return Err(HoduError::InvalidArgument(format!(
+                "cannot multiply matrices of shapes {:?} and {:?}",
+                lhs_layout.shape(),
+                rhs_layout.shape()
+            )));
+        }
+        let (op, params) = if l.len() == 2 && r.len() == 2 {
+            (MatrixOp::Dot, OpParams::Dot(DotParams))
+        } else {
+            (MatrixOp::Matmul, OpParams::Matmul(MatmulParams))
+        };
+        let batch = Shape::broadcast_shape(&Shape::new(&l[..l.len() - 2]), &Shape::new(&r[..r.len() - 2])).ok_or_else(
+            || {
+                HoduError::InvalidArgument(format!(
+                    "cannot broadcast the batch dimensions of {:?} and {:?}",
+                    lhs_layout.shape(),
+                    rhs_layout.shape()
+                ))
+            },
+        )?;
+        let mut dims = batch.dims().to_vec();
+        dims.extend([l[l.len() - 2], r[r.len() - 1]]);
+        Ok(self.push(
+            Op::Matrix(op),
+            Some(params),
+            vec![lhs, rhs],
+            vec![lhs_layout, rhs_layout],
+            &Shape::new(&dims),
+            dtype,
+        ))
+    }
+
+    /// Reduce `value` with `op` over `dims`, keeping them as size 1 if `keep_dim`
+    ///
+    /// The output has `value`'s dtype, so index-producing reductions such as `ArgMax` need
+    /// [`node`](Self::node) instead.
+    ///
+    /// # Errors
+    /// Returns error if a dimension is out of range.
+    pub fn reduce(
+        &mut self,
+        op: ReduceOp,
+        value: SnapshotTensorId,
+        dims: &[usize],
+        keep_dim: bool,
+    ) -> HoduResult<SnapshotTensorId> {
+        let (layout, dtype) = self.layout_of(value)?;
+        let ndim = layout.ndim();
+        if let Some(&dim) = dims.iter().find(|&&dim| dim >= ndim) {
+            return Err(HoduError::InvalidAxis { axis: dim as i32, ndim });
+        }
+        let out_dims: Vec<usize> = layout
+            .shape()
+            .dims()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &size)| match (dims.contains(&i), keep_dim) {
+                (false, _) => Some(size),
+                (true, true) => Some(1),
+                (true, false) => None,
+            })
+            .collect();
+        let params = ReduceParams {
+            dims: dims.iter().map(|&dim| Scalar::from(dim as u32)).collect(),
+            keep_dim,
+        };
+        Ok(self.push(
+            Op::Reduce(op),
+            Some(OpParams::Reduce(params)),
+            vec![value],
+            vec![layout],
+            &Shape::new(&out_dims),
+            dtype,
+        ))
+    }
+
+    /// Sum over `dims`
+    pub fn sum(&mut self, value: SnapshotTensorId, dims: &[usize], keep_dim: bool) -> HoduResult<SnapshotTensorId> {
+        self.reduce(ReduceOp::Sum, value, dims, keep_dim)
+    }
+
+    /// Mean over `dims`
+    pub fn mean(&mut self, value: SnapshotTensorId, dims: &[usize], keep_dim: bool) -> HoduResult<SnapshotTensorId> {
+        self.reduce(ReduceOp::Mean, value, dims, keep_dim)
+    }
+
+    /// Maximum over `dims`
+    pub fn max(&mut self, value: SnapshotTensorId, dims: &[usize], keep_dim: bool) -> HoduResult<SnapshotTensorId> {
+        self.reduce(ReduceOp::Max, value, dims, keep_dim)
+    }
+
+    /// View `value` with `shape`, which must have as many elements
+    ///
+    /// # Errors
+    /// Returns error if the element counts differ or `value` is a broadcast view.
+    pub fn reshape(&mut self, value: SnapshotTensorId, shape: impl Into<Shape>) -> HoduResult<SnapshotTensorId> {
+        let (layout, dtype) = self.layout_of(value)?;
+        let output_layout = layout.reshape(&shape.into())?;
+        let id = self.value(output_layout.clone(), dtype);
+        self.snapshot.nodes.push(node(
+            Op::Shape(ShapeOp::Reshape),
+            None,
+            vec![value],
+            vec![layout],
+            id,
+            output_layout,
+            dtype,
+        ));
+        Ok(id)
+    }
+
+    /// Add a node for any op, with an output of `shape` and `dtype`
+    ///
+    /// Nothing is checked: `params`, `shape` and `dtype` must be what capture would record for
+    /// `op` on `inputs`, or the snapshot will not run.
+    ///
+    /// # Errors
+    /// Returns error if an input was not added by this builder.
+    pub fn node(
+        &mut self,
+        op: Op,
+        params: Option<OpParams>,
+        inputs: &[SnapshotTensorId],
+        shape: impl Into<Shape>,
+        dtype: DType,
+    ) -> HoduResult<SnapshotTensorId> {
+        let input_layouts = inputs
+            .iter()
+            .map(|&input| self.layout_of(input).map(|(layout, _)| layout))
+            .collect::<HoduResult<_>>()?;
+        Ok(self.push(op, params, inputs.to_vec(), input_layouts, &shape.into(), dtype))
+    }
+
+    fn value(&mut self, layout: Layout, dtype: DType) -> SnapshotTensorId {
+        self.values.push((layout, dtype));
+        SnapshotTensorId(self.values.len() - 1)
+    }
+
+    fn layout_of(&self, value: SnapshotTensorId) -> HoduResult<(Layout, DType)> {
+        self.values
+            .get(value.0)
+            .cloned()
+            .ok_or_else(|| HoduError::InvalidArgument(format!("tensor {} is not part of this snapshot", value.0)))
+    }
+
+    /// `value` broadcast to `shape`, or `value` itself if it already has that shape
+    fn broadcast(&mut self, value: SnapshotTensorId, shape: &Shape) -> HoduResult<SnapshotTensorId> {
+        let (layout, dtype) = self.layout_of(value)?;
+        if layout.shape() == shape {
+            return Ok(value);
+        }
+        let output_layout = layout.broadcast_to(shape)?;
+        let id = self.value(output_layout.clone(), dtype);
+        self.snapshot.nodes.push(node(
+            Op::Shape(ShapeOp::Broadcast),
+            None,
+            vec![value],
+            vec![layout],
+            id,
+            output_layout,
+            dtype,
+        ));
+        Ok(id)
+    }
+
+    fn push(
+        &mut self,
+        op: Op,
+        params: Option<OpParams>,
+        input_ids: Vec<SnapshotTensorId>,
+        input_layouts: Vec<Layout>,
+        shape: &Shape,
+        dtype: DType,
+    ) -> SnapshotTensorId {
+        let output_layout = Layout::from_shape(shape);
+        let id = self.value(output_layout.clone(), dtype);
+        self.snapshot
+            .nodes
+            .push(node(op, params, input_ids, input_layouts, id, output_layout, dtype));
+        id
+    }
+}
+
+fn node(
+    op: Op,
+    params: Option<OpParams>,
+    input_ids: Vec<SnapshotTensorId>,
+    input_layouts: Vec<Layout>,
+    output_id: SnapshotTensorId,
+    output_layout: Layout,
+    output_dtype: DType,
+) -> SnapshotNode {
+    SnapshotNode {
+        op,
+        params,
+        input_ids,
+        output_id,
+        input_layouts,
+        output_layout,
+        output_dtype,
+        symbolic_output_layout: None,
+        name: None,
+        metadata: BTreeMap::new(),
+    }
+}
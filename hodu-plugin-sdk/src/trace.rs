@@ -0,0 +1,169 @@
+//! Forward `tracing` events to the CLI
+//!
+//! [`LogForwarder`] is a `tracing-subscriber` layer that sends every event as a `$/log`
+//! notification carrying its target, the names of its spans and the fields of both. Closing a
+//! span sends one more notification with the span's fields and its duration in `elapsed_ms`, so
+//! the server's `request` spans and hodu_core's `snapshot` spans time themselves.
+//!
+//! ```ignore
+//! hodu_plugin_sdk::init_tracing();
+//!
+//! tracing::info!(layers = 32, "model loaded");
+//! ```
+
+use crate::rpc::{LogParams, TRACE_LEVEL_ENV};
+use crate::server::notify_log_with;
+use serde_json::{Map, Value};
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Install a global subscriber that forwards `tracing` events to the CLI
+///
+/// Events down to the level in `HODU_PLUGIN_TRACE` are forwarded, or down to `info` when it is
+/// unset or invalid. The CLI raises the level when it records plugin logs to a file. Does
+/// nothing if a global subscriber is already installed.
+pub fn init_tracing() {
+    let level = std::env::var(TRACE_LEVEL_ENV)
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(LevelFilter::INFO);
+    let subscriber = tracing_subscriber::registry().with(LogForwarder::new(level));
+    let _ = tracing::subscriber::set_global_default(subscriber);
+}
+
+/// Layer that sends `tracing` events to the CLI as `$/log` notifications
+///
+/// Use [`init_tracing`] unless the plugin composes its own subscriber.
+pub struct LogForwarder {
+    max_level: LevelFilter,
+}
+
+/// Fields and start time of an open span, kept in its extensions
+struct SpanData {
+    fields: Map<String, Value>,
+    opened_at: Instant,
+}
+
+impl LogForwarder {
+    /// Forward events at `max_level` or more severe
+    pub fn new(max_level: LevelFilter) -> Self {
+        Self { max_level }
+    }
+}
+
+impl<S> Layer<S> for LogForwarder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: LayerContext<'_, S>) -> bool {
+        self.max_level >= *metadata.level()
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut fields = Map::new();
+        attrs.record(&mut JsonVisitor(&mut fields));
+        span.extensions_mut().insert(SpanData {
+            fields,
+            opened_at: Instant::now(),
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+        let mut extensions = span.extensions_mut();
+        if let Some(data) = extensions.get_mut::<SpanData>() {
+            values.record(&mut JsonVisitor(&mut data.fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let mut fields = Map::new();
+        let mut spans = Vec::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                spans.push(span.name().to_string());
+                if let Some(data) = span.extensions().get::<SpanData>() {
+                    fields.extend(data.fields.clone());
+                }
+            }
+        }
+        // Event fields win over span fields of the same name
+        event.record(&mut JsonVisitor(&mut fields));
+        let message = match fields.remove("message") {
+            Some(Value::String(message)) => message,
+            Some(other) => other.to_string(),
+            None => event.metadata().name().to_string(),
+        };
+
+        let metadata = event.metadata();
+        let mut params = LogParams::new(level_name(metadata.level()), message)
+            .with_target(metadata.target())
+            .with_spans(spans);
+        params.fields = fields;
+        notify_log_with(params);
+    }
+
+    fn on_close(&self, id: Id, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(&id) else { return };
+        let Some(data) = span.extensions_mut().remove::<SpanData>() else {
+            return;
+        };
+        let spans = span.scope().from_root().map(|s| s.name().to_string()).collect();
+        let elapsed_ms = data.opened_at.elapsed().as_secs_f64() * 1000.0;
+
+        let metadata = span.metadata();
+        let mut params = LogParams::new(level_name(metadata.level()), format!("{} finished", span.name()))
+            .with_target(metadata.target())
+            .with_spans(spans);
+        params.fields = data.fields;
+        params.fields.insert("elapsed_ms".to_string(), Value::from(elapsed_ms));
+        notify_log_with(params);
+    }
+}
+
+fn level_name(level: &Level) -> &'static str {
+    match *level {
+        Level::ERROR => "error",
+        Level::WARN => "warn",
+        Level::INFO => "info",
+        Level::DEBUG => "debug",
+        Level::TRACE => "trace",
+    }
+}
+
+/// Records `tracing` fields as JSON values
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), Value::from(format!("{:?}", value)));
+    }
+}
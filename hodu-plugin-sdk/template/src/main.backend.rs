@@ -4,7 +4,7 @@ use hodu_plugin_sdk::{
     hdss,
     rpc::{RpcError, RunParams, RunResult},
     server::PluginServer,
-    Context, TensorData,
+    Context, TensorData, TensorDataExt,
 };
 use std::collections::HashMap;
 
@@ -21,7 +21,9 @@ async fn main() {
 }
 
 async fn handle_run(_ctx: Context, params: RunParams) -> Result<RunResult, RpcError> {
-    let snapshot = hdss::load(&params.snapshot_path)
+    // Sharded snapshots keep constant data in weight shards; `weights.get(constant)` reads one
+    // shard on demand, while `hdss::load` would read them all up front
+    let (snapshot, _weights) = hdss::load_lazy(&params.snapshot_path)
         .map_err(|e| RpcError::internal_error(format!("Failed to load snapshot: {}", e)))?;
 
     let mut inputs: HashMap<String, TensorData> = HashMap::new();
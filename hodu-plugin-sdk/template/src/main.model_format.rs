@@ -10,7 +10,7 @@ use std::path::Path;
 #[hodu_plugin_sdk::main]
 async fn main() {
     let server = PluginServer::new("{{NAME}}", env!("CARGO_PKG_VERSION"))
-        .model_extensions(vec!["ext"]) // TODO: Change to your format extension
+        .model_extensions(vec!["{{EXTENSION}}"])
         .method("format.load_model", handle_load_model);
 
     if let Err(e) = server.run().await {
@@ -3,14 +3,14 @@
 use hodu_plugin_sdk::{
     rpc::{LoadTensorParams, LoadTensorResult, RpcError},
     server::PluginServer,
-    Context, TensorData,
+    Context,
 };
 use std::path::Path;
 
 #[hodu_plugin_sdk::main]
 async fn main() {
     let server = PluginServer::new("{{NAME}}", env!("CARGO_PKG_VERSION"))
-        .tensor_extensions(vec!["ext"]) // TODO: Change to your format extension
+        .tensor_extensions(vec!["{{EXTENSION}}"])
         .method("format.load_tensor", handle_load_tensor);
 
     if let Err(e) = server.run().await {
@@ -0,0 +1,189 @@
+//! Python buffer protocol (PEP 3118) support
+//!
+//! NumPy arrays, memoryviews and any other buffer exporter convert to tensors by copying their
+//! elements in row-major order. Tensors export a read-only row-major copy of their data, so
+//! `numpy.asarray(tensor)` works without NumPy being a dependency of this crate.
+
+use crate::error::IntoPyResult;
+use hodu_core::tensor::Tensor;
+use hodu_core::types::{DType, Device};
+use pyo3::exceptions::PyBufferError;
+use pyo3::ffi;
+use pyo3::prelude::*;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+
+/// What an exported view points into, kept alive until the consumer releases the view
+struct Export {
+    bytes: Vec<u8>,
+    shape: Vec<ffi::Py_ssize_t>,
+    strides: Vec<ffi::Py_ssize_t>,
+    format: CString,
+}
+
+/// A view obtained from `PyObject_GetBuffer`, released on drop
+struct View(ffi::Py_buffer);
+
+impl Drop for View {
+    fn drop(&mut self) {
+        // SAFETY: the view was filled by a successful PyObject_GetBuffer and is released once
+        unsafe { ffi::PyBuffer_Release(&mut self.0) }
+    }
+}
+
+/// Copy an object exporting the buffer protocol into a tensor on `device`
+pub(crate) fn tensor_from_buffer(obj: &Bound<'_, PyAny>, device: Device) -> PyResult<Tensor> {
+    let py = obj.py();
+    let mut view = std::mem::MaybeUninit::<ffi::Py_buffer>::uninit();
+    // SAFETY: `obj` is a live object and `view` is only read after the call succeeds
+    if unsafe { ffi::PyObject_GetBuffer(obj.as_ptr(), view.as_mut_ptr(), ffi::PyBUF_RECORDS_RO) } == -1 {
+        return Err(PyErr::fetch(py));
+    }
+    // SAFETY: PyObject_GetBuffer succeeded and filled the view
+    let view = View(unsafe { view.assume_init() });
+    let raw = &view.0;
+
+    let format = if raw.format.is_null() {
+        "B"
+    } else {
+        // SAFETY: exporters set `format` to a NUL-terminated string when it is requested
+        unsafe { CStr::from_ptr(raw.format) }.to_str().unwrap_or_default()
+    };
+    let dtype = dtype_of(format, raw.itemsize as usize)
+        .ok_or_else(|| PyBufferError::new_err(format!("unsupported buffer format '{}'", format)))?;
+    let shape: Vec<usize> = if raw.ndim == 0 {
+        Vec::new()
+    } else {
+        // SAFETY: PyBUF_RECORDS_RO requests the shape, which has `ndim` entries
+        unsafe { std::slice::from_raw_parts(raw.shape, raw.ndim as usize) }
+            .iter()
+            .map(|&dim| dim as usize)
+            .collect()
+    };
+
+    let mut bytes = vec![0u8; raw.len as usize];
+    // SAFETY: `bytes` holds `len` bytes, the size of the view's data
+    let copied = unsafe { ffi::PyBuffer_ToContiguous(bytes.as_mut_ptr() as *mut c_void, raw, raw.len, b'C' as c_char) };
+    if copied == -1 {
+        return Err(PyErr::fetch(py));
+    }
+    Tensor::from_bytes(&bytes, shape, dtype, device).into_py_result()
+}
+
+/// Fill a buffer view with a row-major copy of `tensor` for `__getbuffer__`
+///
+/// # Safety
+///
+/// `view` must be the view passed to `__getbuffer__`; it must be released with [`release_view`].
+pub(crate) unsafe fn fill_view(
+    view: *mut ffi::Py_buffer,
+    flags: c_int,
+    tensor: &Tensor,
+    owner: Bound<'_, PyAny>,
+) -> PyResult<()> {
+    if view.is_null() {
+        return Err(PyBufferError::new_err("View is null"));
+    }
+    if (flags & ffi::PyBUF_WRITABLE) == ffi::PyBUF_WRITABLE {
+        return Err(PyBufferError::new_err("Tensor buffers are read-only"));
+    }
+
+    let dtype = tensor.dtype();
+    let format = format_of(dtype).ok_or_else(|| {
+        PyBufferError::new_err(format!(
+            "{} tensors have no buffer format; convert them to f32 first",
+            dtype
+        ))
+    })?;
+    let dims = tensor.shape().dims().to_vec();
+    let mut strides = vec![dtype.size_in_bytes() as ffi::Py_ssize_t; dims.len()];
+    for axis in (0..dims.len().saturating_sub(1)).rev() {
+        strides[axis] = strides[axis + 1] * dims[axis + 1] as ffi::Py_ssize_t;
+    }
+    let mut export = Box::new(Export {
+        bytes: tensor.to_bytes().into_py_result()?,
+        shape: dims.iter().map(|&dim| dim as ffi::Py_ssize_t).collect(),
+        strides,
+        format: CString::new(format).unwrap_or_default(),
+    });
+
+    (*view).obj = owner.into_ptr();
+    (*view).buf = export.bytes.as_mut_ptr() as *mut c_void;
+    (*view).len = export.bytes.len() as ffi::Py_ssize_t;
+    (*view).readonly = 1;
+    (*view).itemsize = dtype.size_in_bytes() as ffi::Py_ssize_t;
+    (*view).format = if (flags & ffi::PyBUF_FORMAT) == ffi::PyBUF_FORMAT {
+        export.format.as_ptr() as *mut c_char
+    } else {
+        std::ptr::null_mut()
+    };
+    (*view).ndim = export.shape.len() as c_int;
+    (*view).shape = if (flags & ffi::PyBUF_ND) == ffi::PyBUF_ND {
+        export.shape.as_mut_ptr()
+    } else {
+        std::ptr::null_mut()
+    };
+    (*view).strides = if (flags & ffi::PyBUF_STRIDES) == ffi::PyBUF_STRIDES {
+        export.strides.as_mut_ptr()
+    } else {
+        std::ptr::null_mut()
+    };
+    (*view).suboffsets = std::ptr::null_mut();
+    (*view).internal = Box::into_raw(export) as *mut c_void;
+    Ok(())
+}
+
+/// Free what [`fill_view`] allocated, for `__releasebuffer__`
+///
+/// # Safety
+///
+/// `view` must have been filled by [`fill_view`] and not released before.
+pub(crate) unsafe fn release_view(view: *mut ffi::Py_buffer) {
+    drop(Box::from_raw((*view).internal as *mut Export));
+}
+
+fn format_of(dtype: DType) -> Option<&'static str> {
+    Some(match dtype {
+        DType::BOOL => "?",
+        DType::F16 => "e",
+        DType::F32 => "f",
+        DType::F64 => "d",
+        DType::U8 => "B",
+        DType::U16 => "H",
+        DType::U32 => "I",
+        DType::U64 => "Q",
+        DType::I8 => "b",
+        DType::I16 => "h",
+        DType::I32 => "i",
+        DType::I64 => "q",
+        _ => return None,
+    })
+}
+
+/// The dtype of a struct-module format code, sized by the exporter's itemsize
+fn dtype_of(format: &str, itemsize: usize) -> Option<DType> {
+    // Native and little-endian byte orders; big-endian data would need swapping
+    let code = format.strip_prefix(['@', '=', '<']).unwrap_or(format);
+    let signed = |size| match size {
+        1 => Some(DType::I8),
+        2 => Some(DType::I16),
+        4 => Some(DType::I32),
+        8 => Some(DType::I64),
+        _ => None,
+    };
+    let unsigned = |size| match size {
+        1 => Some(DType::U8),
+        2 => Some(DType::U16),
+        4 => Some(DType::U32),
+        8 => Some(DType::U64),
+        _ => None,
+    };
+    match (code, itemsize) {
+        ("?", 1) => Some(DType::BOOL),
+        ("e", 2) => Some(DType::F16),
+        ("f", 4) => Some(DType::F32),
+        ("d", 8) => Some(DType::F64),
+        ("b" | "h" | "i" | "l" | "q" | "n", size) => signed(size),
+        ("B" | "H" | "I" | "L" | "Q" | "N", size) => unsigned(size),
+        _ => None,
+    }
+}
@@ -0,0 +1,16 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::PyResult;
+
+create_exception!(hodu, HoduError, PyException, "Error raised by hodu operations.");
+
+/// Raise errors from the Rust crates as `hodu.HoduError`
+pub(crate) trait IntoPyResult<T> {
+    fn into_py_result(self) -> PyResult<T>;
+}
+
+impl<T, E: std::fmt::Display> IntoPyResult<T> for Result<T, E> {
+    fn into_py_result(self) -> PyResult<T> {
+        self.map_err(|e| HoduError::new_err(e.to_string()))
+    }
+}
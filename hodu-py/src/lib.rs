@@ -0,0 +1,56 @@
+//! Python bindings for Hodu
+//!
+//! Built into the `hodu` Python module with maturin (see `pyproject.toml`). The module exposes
+//! tensors with NumPy interop through the buffer protocol, snapshot capture and interpretation,
+//! hdt/hdss IO, and model execution through installed plugins.
+
+mod buffer;
+mod error;
+mod runtime;
+mod snapshot;
+mod tensor;
+
+use hodu_core::format::hdt;
+use hodu_core::tensor::set_runtime_device;
+use hodu_core::types::Device;
+use pyo3::prelude::*;
+use std::path::PathBuf;
+
+use error::IntoPyResult;
+use tensor::PyTensor;
+
+/// Load a tensor from an .hdt file
+#[pyfunction]
+fn load(path: PathBuf) -> PyResult<PyTensor> {
+    hdt::load(path).into_py_result().map(PyTensor)
+}
+
+/// Save a tensor to an .hdt file
+#[pyfunction]
+fn save(tensor: &PyTensor, path: PathBuf) -> PyResult<()> {
+    hdt::save(&tensor.0, path).into_py_result()
+}
+
+/// Set the device new tensors are created on, e.g. "cpu" or "cuda::0"
+#[pyfunction]
+fn set_device(device: &str) -> PyResult<()> {
+    set_runtime_device(device.parse::<Device>().into_py_result()?);
+    Ok(())
+}
+
+#[pymodule(name = "hodu")]
+fn hodu_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("HoduError", m.py().get_type::<error::HoduError>())?;
+
+    m.add_class::<PyTensor>()?;
+    m.add_class::<snapshot::PySnapshot>()?;
+    m.add_class::<snapshot::PyCaptureBoard>()?;
+    m.add_class::<runtime::PyRuntime>()?;
+    m.add_class::<runtime::PyModel>()?;
+
+    m.add_function(wrap_pyfunction!(load, m)?)?;
+    m.add_function(wrap_pyfunction!(save, m)?)?;
+    m.add_function(wrap_pyfunction!(set_device, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use crate::error::IntoPyResult;
+use crate::tensor::PyTensor;
+use hodu_core::tensor::Tensor;
+use hodu_plugin_runtime::{Model, Runtime};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Loads models and runs them through installed plugins
+///
+/// Models in formats other than .hdss need a format plugin, and running needs a backend plugin
+/// for the device (see `hodu plugin install`).
+#[pyclass(name = "Runtime", module = "hodu", unsendable)]
+pub(crate) struct PyRuntime(Runtime);
+
+#[pymethods]
+impl PyRuntime {
+    /// Create a runtime, optionally with a plugin timeout in seconds
+    #[new]
+    #[pyo3(signature = (timeout = None))]
+    fn new(timeout: Option<u64>) -> PyResult<Self> {
+        match timeout {
+            Some(timeout) => Runtime::with_timeout(timeout),
+            None => Runtime::new(),
+        }
+        .into_py_result()
+        .map(Self)
+    }
+
+    fn load(&mut self, path: PathBuf) -> PyResult<PyModel> {
+        self.0.load(path).into_py_result().map(PyModel)
+    }
+
+    /// Run `model` with named inputs, returning its outputs by name
+    ///
+    /// `backend` names a backend plugin; when empty one is picked for `device`.
+    #[pyo3(signature = (model, inputs, device = "cpu", backend = ""))]
+    fn run(
+        &mut self,
+        model: &PyModel,
+        inputs: HashMap<String, PyTensor>,
+        device: &str,
+        backend: &str,
+    ) -> PyResult<HashMap<String, PyTensor>> {
+        let inputs: Vec<(&str, &Tensor)> = inputs.iter().map(|(name, tensor)| (name.as_str(), &tensor.0)).collect();
+        let outputs = self.0.run(&model.0, &inputs, device, backend).into_py_result()?;
+        Ok(outputs
+            .into_iter()
+            .map(|(name, tensor)| (name, PyTensor(tensor)))
+            .collect())
+    }
+}
+
+/// A model loaded by a [`Runtime`]
+#[pyclass(name = "Model", module = "hodu", frozen)]
+pub(crate) struct PyModel(Model);
+
+#[pymethods]
+impl PyModel {
+    /// The .hdss snapshot the model runs from
+    #[getter]
+    fn snapshot_path(&self) -> PathBuf {
+        self.0.snapshot_path().to_path_buf()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Model({})", self.0.snapshot_path().display())
+    }
+}
@@ -0,0 +1,159 @@
+use crate::error::{HoduError, IntoPyResult};
+use crate::tensor::{parse_device, PyTensor};
+use hodu_core::snapshot::{CaptureBoard, Interpreter, Snapshot};
+use hodu_core::tensor::Tensor;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A captured computation graph (.hdss)
+#[pyclass(name = "Snapshot", module = "hodu", frozen)]
+pub(crate) struct PySnapshot(pub(crate) Snapshot);
+
+#[pymethods]
+impl PySnapshot {
+    #[staticmethod]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Snapshot::load(path).into_py_result().map(Self)
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        Snapshot::from_bytes(data).into_py_result().map(Self)
+    }
+
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        self.0.save(path).into_py_result()
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new(py, &self.0.to_bytes().into_py_result()?))
+    }
+
+    #[getter]
+    fn name(&self) -> Option<String> {
+        self.0.name.clone()
+    }
+
+    /// Input names, in declaration order
+    #[getter]
+    fn inputs(&self) -> Vec<String> {
+        self.0.inputs.iter().map(|input| input.name.clone()).collect()
+    }
+
+    /// Target names, in declaration order
+    #[getter]
+    fn targets(&self) -> Vec<String> {
+        self.0.targets.iter().map(|target| target.name.clone()).collect()
+    }
+
+    /// Interpret the snapshot in-process, returning its targets by name
+    #[pyo3(signature = (inputs, device = "cpu"))]
+    fn run(&self, inputs: HashMap<String, PyTensor>, device: &str) -> PyResult<HashMap<String, PyTensor>> {
+        let inputs: Vec<(&str, &Tensor)> = inputs.iter().map(|(name, tensor)| (name.as_str(), &tensor.0)).collect();
+        let outputs = Interpreter::new(&self.0)
+            .device(parse_device(device)?)
+            .run(&inputs)
+            .into_py_result()?;
+        Ok(outputs
+            .into_iter()
+            .map(|(name, tensor)| (name, PyTensor(tensor)))
+            .collect())
+    }
+
+    fn __repr__(&self) -> String {
+        let name = match &self.0.name {
+            Some(name) => format!("{:?}", name),
+            None => "None".to_string(),
+        };
+        format!(
+            "Snapshot(name={}, inputs={:?}, targets={:?}, nodes={})",
+            name,
+            self.inputs(),
+            self.targets(),
+            self.0.nodes.len()
+        )
+    }
+}
+
+/// Records the ops run while it is open into a [`Snapshot`]
+///
+/// ```python
+/// board = hodu.CaptureBoard("model")
+/// with board:
+///     x = hodu.Tensor.input("x", [1, 4])
+///     y = (x * 2).relu()
+/// board.target("y", y)
+/// snapshot = board.capture()
+/// ```
+#[pyclass(name = "CaptureBoard", module = "hodu", frozen)]
+pub(crate) struct PyCaptureBoard(Mutex<Option<CaptureBoard>>);
+
+impl PyCaptureBoard {
+    fn with_board<R>(&self, f: impl FnOnce(&CaptureBoard) -> R) -> PyResult<R> {
+        match self.0.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            Some(board) => Ok(f(board)),
+            None => Err(HoduError::new_err("capture board was already captured")),
+        }
+    }
+}
+
+#[pymethods]
+impl PyCaptureBoard {
+    #[new]
+    #[pyo3(signature = (name = None))]
+    fn new(name: Option<String>) -> Self {
+        let board = match name {
+            Some(name) => CaptureBoard::with_name(name),
+            None => CaptureBoard::new(),
+        };
+        Self(Mutex::new(Some(board)))
+    }
+
+    fn open(&self) -> PyResult<()> {
+        self.with_board(CaptureBoard::open)
+    }
+
+    fn close(&self) -> PyResult<()> {
+        self.with_board(CaptureBoard::close)
+    }
+
+    /// Mark `tensor` as a graph output named `name`
+    fn target(&self, name: String, tensor: &PyTensor) -> PyResult<()> {
+        self.with_board(|board| {
+            board.with_target(name, tensor.0.clone());
+        })
+    }
+
+    /// Attach a model-level metadata entry to the snapshot
+    fn metadata(&self, key: String, value: String) -> PyResult<()> {
+        self.with_board(|board| {
+            board.with_metadata(key, value);
+        })
+    }
+
+    /// Build the snapshot; the board cannot be used afterwards
+    fn capture(&self) -> PyResult<PySnapshot> {
+        let board = self.0.lock().unwrap_or_else(|e| e.into_inner()).take();
+        board
+            .map(|board| PySnapshot(board.capture()))
+            .ok_or_else(|| HoduError::new_err("capture board was already captured"))
+    }
+
+    fn __enter__(slf: Bound<'_, Self>) -> PyResult<Bound<'_, Self>> {
+        slf.get().open()?;
+        Ok(slf)
+    }
+
+    fn __exit__(
+        &self,
+        _exc_type: Option<Bound<'_, PyAny>>,
+        _exc_value: Option<Bound<'_, PyAny>>,
+        _traceback: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        self.close()?;
+        Ok(false)
+    }
+}
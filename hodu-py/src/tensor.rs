@@ -0,0 +1,477 @@
+use crate::buffer;
+use crate::error::IntoPyResult;
+use hodu_core::error::HoduResult;
+use hodu_core::scalar::Scalar;
+use hodu_core::tensor::{get_runtime_device, Tensor};
+use hodu_core::types::{DType, Device};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyFloat, PyInt, PyList, PySequence, PyTuple};
+use std::ffi::c_int;
+
+/// A hodu tensor
+///
+/// Created from nested lists, numbers, NumPy arrays or any other object exporting the buffer
+/// protocol. `numpy.asarray(tensor)` and `tensor.numpy()` convert back.
+#[pyclass(name = "Tensor", module = "hodu", frozen)]
+#[derive(Clone)]
+pub(crate) struct PyTensor(pub(crate) Tensor);
+
+/// The right-hand side of an arithmetic operator
+#[derive(FromPyObject)]
+enum Operand<'py> {
+    Tensor(PyRef<'py, PyTensor>),
+    Scalar(f64),
+}
+
+type BinaryOp = fn(&Tensor, &Tensor) -> HoduResult<Tensor>;
+type ScalarOp = fn(&Tensor, Scalar) -> HoduResult<Tensor>;
+
+impl PyTensor {
+    fn binary(&self, rhs: Operand<'_>, op: BinaryOp, scalar_op: ScalarOp) -> PyResult<Self> {
+        match rhs {
+            Operand::Tensor(rhs) => op(&self.0, &rhs.0),
+            Operand::Scalar(value) => scalar_op(&self.0, self.scalar(value)),
+        }
+        .into_py_result()
+        .map(Self)
+    }
+
+    fn scalar(&self, value: f64) -> Scalar {
+        Scalar::from(value).to_dtype(self.0.dtype())
+    }
+
+    fn reduce(
+        &self,
+        dims: Option<Vec<i32>>,
+        keepdim: bool,
+        op: fn(&Tensor, &[i32], bool) -> HoduResult<Tensor>,
+    ) -> PyResult<Self> {
+        let dims = dims.unwrap_or_else(|| (0..self.0.ndim() as i32).collect());
+        op(&self.0, &dims, keepdim).into_py_result().map(Self)
+    }
+}
+
+#[pymethods]
+impl PyTensor {
+    #[new]
+    #[pyo3(signature = (data, dtype = None, device = None))]
+    fn new(data: &Bound<'_, PyAny>, dtype: Option<&str>, device: Option<&str>) -> PyResult<Self> {
+        let device = device.map(parse_device).transpose()?;
+        let tensor = if let Ok(tensor) = data.cast::<PyTensor>() {
+            tensor.get().0.clone()
+        } else if is_nested(data) {
+            from_nested(data)?
+        } else {
+            buffer::tensor_from_buffer(data, device.unwrap_or_else(get_runtime_device))?
+        };
+
+        let tensor = match dtype.map(parse_dtype).transpose()? {
+            Some(dtype) if dtype != tensor.dtype() => tensor.to_dtype(dtype).into_py_result()?,
+            _ => tensor,
+        };
+        match device {
+            Some(device) if device != tensor.device() => tensor.to_device(device).into_py_result().map(Self),
+            _ => Ok(Self(tensor)),
+        }
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (shape, dtype = "f32"))]
+    fn zeros(shape: Vec<usize>, dtype: &str) -> PyResult<Self> {
+        Tensor::zeros(shape, parse_dtype(dtype)?).into_py_result().map(Self)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (shape, dtype = "f32"))]
+    fn ones(shape: Vec<usize>, dtype: &str) -> PyResult<Self> {
+        Tensor::ones(shape, parse_dtype(dtype)?).into_py_result().map(Self)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (shape, value, dtype = "f32"))]
+    fn full(shape: Vec<usize>, value: f64, dtype: &str) -> PyResult<Self> {
+        let value = Scalar::from(value).to_dtype(parse_dtype(dtype)?);
+        Tensor::full(shape, value).into_py_result().map(Self)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (start, end, step = 1.0, dtype = "f32"))]
+    fn arange(start: f64, end: f64, step: f64, dtype: &str) -> PyResult<Self> {
+        let dtype = parse_dtype(dtype)?;
+        let [start, end, step] = [start, end, step].map(|value| Scalar::from(value).to_dtype(dtype));
+        Tensor::arange(start, end, step).into_py_result().map(Self)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (shape, mean = 0.0, std = 1.0, dtype = "f32"))]
+    fn randn(shape: Vec<usize>, mean: f64, std: f64, dtype: &str) -> PyResult<Self> {
+        let dtype = parse_dtype(dtype)?;
+        let [mean, std] = [mean, std].map(|value| Scalar::from(value).to_dtype(dtype));
+        Tensor::randn(shape, mean, std).into_py_result().map(Self)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (shape, low = 0.0, high = 1.0, dtype = "f32"))]
+    fn rand(shape: Vec<usize>, low: f64, high: f64, dtype: &str) -> PyResult<Self> {
+        let dtype = parse_dtype(dtype)?;
+        let [low, high] = [low, high].map(|value| Scalar::from(value).to_dtype(dtype));
+        Tensor::rand_uniform(shape, low, high).into_py_result().map(Self)
+    }
+
+    /// A named graph input, for use inside a `CaptureBoard`
+    #[staticmethod]
+    #[pyo3(signature = (name, shape, dtype = "f32"))]
+    fn input(name: &str, shape: Vec<usize>, dtype: &str) -> PyResult<Self> {
+        Tensor::input(name, shape, parse_dtype(dtype)?)
+            .into_py_result()
+            .map(Self)
+    }
+
+    #[getter]
+    fn shape<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTuple>> {
+        PyTuple::new(py, self.0.shape().dims())
+    }
+
+    #[getter]
+    fn dtype(&self) -> String {
+        self.0.dtype().to_string()
+    }
+
+    #[getter]
+    fn device(&self) -> String {
+        self.0.device().to_string()
+    }
+
+    #[getter]
+    fn ndim(&self) -> usize {
+        self.0.ndim()
+    }
+
+    #[getter]
+    fn requires_grad(&self) -> bool {
+        self.0.is_requires_grad()
+    }
+
+    /// Convert to a NumPy array (requires NumPy)
+    fn numpy<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+        slf.py().import("numpy")?.call_method1("asarray", (slf,))
+    }
+
+    /// The value of a single-element tensor as a Python number
+    fn item<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        if self.0.shape().size() != 1 {
+            return Err(PyValueError::new_err(format!(
+                "item() needs a single-element tensor, got shape {:?}",
+                self.0.shape().dims()
+            )));
+        }
+        let dtype = self.0.dtype();
+        let value = if dtype.is_bool() {
+            self.0.to_flatten_vec::<bool>().into_py_result()?[0]
+                .into_pyobject(py)?
+                .to_owned()
+                .into_any()
+        } else if dtype.is_float() {
+            let values = self.0.to_dtype(DType::F64).and_then(|t| t.to_flatten_vec::<f64>());
+            values.into_py_result()?[0].into_pyobject(py)?.into_any()
+        } else {
+            let values = self.0.to_dtype(DType::I64).and_then(|t| t.to_flatten_vec::<i64>());
+            values.into_py_result()?[0].into_pyobject(py)?.into_any()
+        };
+        Ok(value)
+    }
+
+    fn to(&self, dtype: &str) -> PyResult<Self> {
+        self.0.to_dtype(parse_dtype(dtype)?).into_py_result().map(Self)
+    }
+
+    fn to_device(&self, device: &str) -> PyResult<Self> {
+        self.0.to_device(parse_device(device)?).into_py_result().map(Self)
+    }
+
+    fn contiguous(&self) -> PyResult<Self> {
+        self.0.contiguous().into_py_result().map(Self)
+    }
+
+    fn reshape(&self, shape: Vec<usize>) -> PyResult<Self> {
+        self.0.reshape(shape).into_py_result().map(Self)
+    }
+
+    fn transpose(&self, dim0: i32, dim1: i32) -> PyResult<Self> {
+        self.0.transpose(dim0, dim1).into_py_result().map(Self)
+    }
+
+    fn permute(&self, dims: Vec<i32>) -> PyResult<Self> {
+        self.0.permute(&dims).into_py_result().map(Self)
+    }
+
+    fn unsqueeze(&self, dim: i32) -> PyResult<Self> {
+        self.0.unsqueeze(dim).into_py_result().map(Self)
+    }
+
+    fn matmul(&self, other: &Self) -> PyResult<Self> {
+        self.0.matmul(&other.0).into_py_result().map(Self)
+    }
+
+    #[pyo3(signature = (dims = None, keepdim = false))]
+    fn sum(&self, dims: Option<Vec<i32>>, keepdim: bool) -> PyResult<Self> {
+        self.reduce(dims, keepdim, Tensor::sum)
+    }
+
+    #[pyo3(signature = (dims = None, keepdim = false))]
+    fn mean(&self, dims: Option<Vec<i32>>, keepdim: bool) -> PyResult<Self> {
+        self.reduce(dims, keepdim, Tensor::mean)
+    }
+
+    #[pyo3(signature = (dims = None, keepdim = false))]
+    fn max(&self, dims: Option<Vec<i32>>, keepdim: bool) -> PyResult<Self> {
+        self.reduce(dims, keepdim, Tensor::max)
+    }
+
+    #[pyo3(signature = (dims = None, keepdim = false))]
+    fn min(&self, dims: Option<Vec<i32>>, keepdim: bool) -> PyResult<Self> {
+        self.reduce(dims, keepdim, Tensor::min)
+    }
+
+    #[pyo3(signature = (dim, keepdim = false))]
+    fn argmax(&self, dim: i32, keepdim: bool) -> PyResult<Self> {
+        self.0.argmax(&[dim], keepdim).into_py_result().map(Self)
+    }
+
+    fn softmax(&self, dim: i32) -> PyResult<Self> {
+        self.0.softmax(dim).into_py_result().map(Self)
+    }
+
+    fn relu(&self) -> PyResult<Self> {
+        self.0.relu().into_py_result().map(Self)
+    }
+
+    fn gelu(&self) -> PyResult<Self> {
+        self.0.gelu().into_py_result().map(Self)
+    }
+
+    fn sigmoid(&self) -> PyResult<Self> {
+        self.0.sigmoid().into_py_result().map(Self)
+    }
+
+    fn tanh(&self) -> PyResult<Self> {
+        self.0.tanh().into_py_result().map(Self)
+    }
+
+    fn exp(&self) -> PyResult<Self> {
+        self.0.exp().into_py_result().map(Self)
+    }
+
+    fn log(&self) -> PyResult<Self> {
+        self.0.ln().into_py_result().map(Self)
+    }
+
+    fn sqrt(&self) -> PyResult<Self> {
+        self.0.sqrt().into_py_result().map(Self)
+    }
+
+    fn abs(&self) -> PyResult<Self> {
+        self.0.abs().into_py_result().map(Self)
+    }
+
+    fn __add__(&self, rhs: Operand<'_>) -> PyResult<Self> {
+        self.binary(rhs, Tensor::add, Tensor::add_scalar::<Scalar>)
+    }
+
+    fn __radd__(&self, lhs: f64) -> PyResult<Self> {
+        self.0.add_scalar(self.scalar(lhs)).into_py_result().map(Self)
+    }
+
+    fn __sub__(&self, rhs: Operand<'_>) -> PyResult<Self> {
+        self.binary(rhs, Tensor::sub, Tensor::sub_scalar::<Scalar>)
+    }
+
+    fn __rsub__(&self, lhs: f64) -> PyResult<Self> {
+        let negated = self.0.neg().into_py_result()?;
+        negated.add_scalar(self.scalar(lhs)).into_py_result().map(Self)
+    }
+
+    fn __mul__(&self, rhs: Operand<'_>) -> PyResult<Self> {
+        self.binary(rhs, Tensor::mul, Tensor::mul_scalar::<Scalar>)
+    }
+
+    fn __rmul__(&self, lhs: f64) -> PyResult<Self> {
+        self.0.mul_scalar(self.scalar(lhs)).into_py_result().map(Self)
+    }
+
+    fn __truediv__(&self, rhs: Operand<'_>) -> PyResult<Self> {
+        self.binary(rhs, Tensor::div, Tensor::div_scalar::<Scalar>)
+    }
+
+    fn __rtruediv__(&self, lhs: f64) -> PyResult<Self> {
+        let lhs = Tensor::full_like(&self.0, self.scalar(lhs)).into_py_result()?;
+        lhs.div(&self.0).into_py_result().map(Self)
+    }
+
+    fn __pow__(&self, rhs: Operand<'_>, _modulo: Option<Py<PyAny>>) -> PyResult<Self> {
+        self.binary(rhs, Tensor::pow, Tensor::pow_scalar::<Scalar>)
+    }
+
+    fn __matmul__(&self, rhs: &Self) -> PyResult<Self> {
+        self.matmul(rhs)
+    }
+
+    fn __neg__(&self) -> PyResult<Self> {
+        self.0.neg().into_py_result().map(Self)
+    }
+
+    fn __len__(&self) -> PyResult<usize> {
+        self.0
+            .shape()
+            .dims()
+            .first()
+            .copied()
+            .ok_or_else(|| PyTypeError::new_err("len() of a 0-d tensor"))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{}", self.0)
+    }
+
+    unsafe fn __getbuffer__(slf: Bound<'_, Self>, view: *mut ffi::Py_buffer, flags: c_int) -> PyResult<()> {
+        let tensor = slf.get().0.clone();
+        buffer::fill_view(view, flags, &tensor, slf.into_any())
+    }
+
+    unsafe fn __releasebuffer__(&self, view: *mut ffi::Py_buffer) {
+        buffer::release_view(view)
+    }
+}
+
+/// Parse a dtype name such as "f32", "float32" or "int64"
+pub(crate) fn parse_dtype(name: &str) -> PyResult<DType> {
+    let short = name
+        .trim()
+        .to_ascii_lowercase()
+        .replace("bfloat", "bf")
+        .replace("float", "f")
+        .replace("uint", "u")
+        .replace("int", "i");
+    [
+        DType::BOOL,
+        DType::F8E4M3,
+        DType::F8E5M2,
+        DType::BF16,
+        DType::F16,
+        DType::F32,
+        DType::F64,
+        DType::U8,
+        DType::U16,
+        DType::U32,
+        DType::U64,
+        DType::I8,
+        DType::I16,
+        DType::I32,
+        DType::I64,
+    ]
+    .into_iter()
+    .find(|dtype| dtype.to_string() == short)
+    .ok_or_else(|| PyValueError::new_err(format!("unknown dtype '{}'", name)))
+}
+
+pub(crate) fn parse_device(name: &str) -> PyResult<Device> {
+    name.parse::<Device>().into_py_result()
+}
+
+fn is_nested(data: &Bound<'_, PyAny>) -> bool {
+    data.is_instance_of::<PyList>()
+        || data.is_instance_of::<PyTuple>()
+        || data.is_instance_of::<PyInt>()
+        || data.is_instance_of::<PyFloat>()
+}
+
+enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+}
+
+/// Build a tensor from a number or nested lists of numbers
+///
+/// Like tables loaded from CSV, booleans become bool, integers that fit in i32 become i32
+/// (otherwise i64), and anything with a float becomes f32.
+fn from_nested(data: &Bound<'_, PyAny>) -> PyResult<Tensor> {
+    // The shape follows the first element at each level; every other element must match it
+    let mut shape = Vec::new();
+    let mut first = data.clone();
+    while first.is_instance_of::<PyList>() || first.is_instance_of::<PyTuple>() {
+        let sequence = first.cast::<PySequence>()?;
+        shape.push(sequence.len()?);
+        if shape.last() == Some(&0) {
+            break;
+        }
+        first = sequence.get_item(0)?;
+    }
+    let mut values = Vec::with_capacity(shape.iter().product());
+    collect(data, &shape, &mut values)?;
+
+    let tensor = if values.iter().any(|value| matches!(value, Value::Float(_))) || values.is_empty() {
+        let values: Vec<f32> = values
+            .iter()
+            .map(|value| match *value {
+                Value::Bool(v) => v as u8 as f32,
+                Value::Int(v) => v as f32,
+                Value::Float(v) => v as f32,
+            })
+            .collect();
+        Tensor::from_slice(values, shape)
+    } else if values.iter().all(|value| matches!(value, Value::Bool(_))) {
+        let values: Vec<bool> = values.iter().map(|value| matches!(value, Value::Bool(true))).collect();
+        Tensor::from_slice(values, shape)
+    } else {
+        let values: Vec<i64> = values
+            .iter()
+            .map(|value| match *value {
+                Value::Bool(v) => v as i64,
+                Value::Int(v) => v,
+                Value::Float(v) => v as i64,
+            })
+            .collect();
+        if values.iter().all(|&v| i32::try_from(v).is_ok()) {
+            Tensor::from_slice(values.into_iter().map(|v| v as i32).collect::<Vec<_>>(), shape)
+        } else {
+            Tensor::from_slice(values, shape)
+        }
+    };
+    tensor.into_py_result()
+}
+
+fn collect(data: &Bound<'_, PyAny>, shape: &[usize], values: &mut Vec<Value>) -> PyResult<()> {
+    let Some((&len, rest)) = shape.split_first() else {
+        let value = if data.is_instance_of::<PyBool>() {
+            Value::Bool(data.extract()?)
+        } else if data.is_instance_of::<PyInt>() {
+            Value::Int(data.extract()?)
+        } else if data.is_instance_of::<PyFloat>() {
+            Value::Float(data.extract()?)
+        } else {
+            return Err(PyTypeError::new_err(format!(
+                "tensor data must be numbers, got {}",
+                data.get_type().name()?
+            )));
+        };
+        values.push(value);
+        return Ok(());
+    };
+
+    let is_sequence = data.is_instance_of::<PyList>() || data.is_instance_of::<PyTuple>();
+    let sequence = data.cast::<PySequence>().ok().filter(|_| is_sequence);
+    match sequence {
+        Some(sequence) if sequence.len()? == len => {
+            for item in sequence.try_iter()? {
+                collect(&item?, rest, values)?;
+            }
+            Ok(())
+        },
+        _ => Err(PyValueError::new_err(
+            "tensor data is ragged: nested lists must have equal lengths",
+        )),
+    }
+}
@@ -18,10 +18,12 @@ result_c="skipped"
 result_cuda="skipped"
 result_metal="skipped"
 result_cargo="skipped"
+result_wasm="skipped"
 details_c=""
 details_cuda=""
 details_metal=""
 details_cargo=""
+details_wasm=""
 
 # Parse command line arguments
 ADDITIONAL_FEATURES=()
@@ -330,6 +332,26 @@ fi
 
 echo ""
 
+# Check hodu_core builds for wasm without its std-only pieces
+echo -e "${BRIGHT_BLUE}▶${NC} ${BOLD}[WASM] Checking hodu_core without std...${NC}"
+if ! rustup target list --installed 2>/dev/null | grep -q "^wasm32-unknown-unknown$"; then
+    echo -e "${DIM}  Skipping WASM check (wasm32-unknown-unknown target not installed)${NC}\n"
+    result_wasm="skipped"
+    details_wasm="target not installed"
+else
+    echo -ne "  ${CYAN}→${NC} ${DIM}wasm32-unknown-unknown, no default features${NC} ... "
+    if cargo check -p hodu_core --no-default-features --target wasm32-unknown-unknown &> /dev/null; then
+        echo -e "${BRIGHT_GREEN}✓${NC}"
+        result_wasm="passed"
+        details_wasm="wasm32-unknown-unknown"
+    else
+        echo -e "${BRIGHT_RED}✗${NC}"
+        result_wasm="failed"
+        details_wasm="wasm32-unknown-unknown failed"
+    fi
+    echo ""
+fi
+
 # ============================================================================
 # Summary
 # ============================================================================
@@ -396,6 +418,20 @@ case "$result_cargo" in
         ;;
 esac
 
+# WASM
+case "$result_wasm" in
+    "passed")
+        echo -e "  ${BRIGHT_GREEN}✓${NC} ${BOLD}WASM${NC}: ${DIM}${details_wasm}${NC}"
+        ;;
+    "failed")
+        echo -e "  ${BRIGHT_RED}✗${NC} ${BOLD}WASM${NC}: ${details_wasm}"
+        has_failures=true
+        ;;
+    "skipped")
+        echo -e "  ${BRIGHT_YELLOW}○${NC} ${BOLD}WASM${NC}: ${DIM}${details_wasm}${NC}"
+        ;;
+esac
+
 echo ""
 
 if [ "$has_failures" = true ]; then